@@ -5,10 +5,18 @@ use std::cmp::Ordering;
 /// Shared module for detecting Claude Code binary installations
 /// Supports NVM installations, aliased paths, version-based selection, and bundled sidecars
 /// Cross-platform support for Windows and macOS
-use std::collections::HashSet;
+use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::OsString;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::process::Command;
-use tauri::Manager;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::commands::app_settings::{self, read_setting, remove_setting, write_setting, CLAUDE_BINARY_PATH};
+use crate::commands::storage::open_agent_db;
 
 /// 运行时环境信息（替换单纯的 #[cfg] 检测，支持容器/WSL/架构）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -714,18 +722,176 @@ fn select_best_with_priority(
     installations.into_iter().map(|p| p.installation).next()
 }
 
+/// 检测结果缓存键：PATH（及覆盖用环境变量）或 binaries.json 变化时自动失效
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BinaryCacheKey {
+    env_hash: u64,
+    config_mtime: Option<SystemTime>,
+}
+
+impl BinaryCacheKey {
+    fn current(env_var: &str, config_key: &str) -> Self {
+        let config_mtime = get_home_dir().ok().and_then(|home| {
+            std::fs::metadata(PathBuf::from(home).join(".claude").join("binaries.json"))
+                .and_then(|m| m.modified())
+                .ok()
+        });
+        Self::new(
+            std::env::var_os("PATH"),
+            std::env::var_os(env_var),
+            config_key,
+            config_mtime,
+        )
+    }
+
+    fn new(
+        path: Option<OsString>,
+        env_override: Option<OsString>,
+        config_key: &str,
+        config_mtime: Option<SystemTime>,
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        env_override.hash(&mut hasher);
+        config_key.hash(&mut hasher);
+
+        Self {
+            env_hash: hasher.finish(),
+            config_mtime,
+        }
+    }
+}
+
+type CachedDetection = (BinaryCacheKey, RuntimeEnvironment, Option<ClaudeInstallation>);
+
+/// tool -> 最近一次检测结果
+static BINARY_CACHE: Lazy<Mutex<HashMap<String, CachedDetection>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 清除指定工具的检测缓存（设置/清除自定义路径、安装或升级后调用）
+///
+/// 同时清空 `--version` 输出缓存（按路径缓存，无法按工具区分）。
+pub fn invalidate_binary_cache(tool: &str) {
+    if BINARY_CACHE.lock().unwrap().remove(tool).is_some() {
+        debug!("Invalidated binary detection cache for {}", tool);
+    }
+    VERSION_CACHE.lock().unwrap().clear();
+}
+
+/// 一次 `--version` 调用的输出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl VersionOutput {
+    pub fn from_output(output: &std::process::Output) -> Self {
+        Self {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
+    }
+}
+
+/// 二进制路径 -> (修改时间, 最近一次 `--version` 输出)
+static VERSION_CACHE: Lazy<Mutex<HashMap<String, (SystemTime, VersionOutput)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 二进制文件的修改时间；裸命令名先在 PATH 中解析，符号链接取目标文件
+fn binary_mtime(path: &str) -> Option<SystemTime> {
+    let resolved = if std::path::Path::new(path).components().count() <= 1 {
+        which::which(path).ok()?
+    } else {
+        PathBuf::from(path)
+    };
+    std::fs::metadata(resolved).and_then(|m| m.modified()).ok()
+}
+
+/// 读取缓存的 `--version` 输出，二进制被替换（修改时间变化）后失效
+pub fn cached_version_output(path: &str) -> Option<VersionOutput> {
+    let mtime = binary_mtime(path)?;
+    match VERSION_CACHE.lock().unwrap().get(path) {
+        Some((cached_mtime, output)) if *cached_mtime == mtime => Some(output.clone()),
+        _ => None,
+    }
+}
+
+/// 记录 `--version` 输出；只缓存成功的调用，无法获取修改时间时不缓存
+pub fn store_version_output(path: &str, output: &VersionOutput) {
+    if !output.success {
+        return;
+    }
+    if let Some(mtime) = binary_mtime(path) {
+        VERSION_CACHE
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), (mtime, output.clone()));
+    }
+}
+
+/// 执行 `<path> --version`，结果按 (路径, 修改时间) 缓存，供状态轮询复用
+pub fn probe_version_cached(path: &str) -> std::io::Result<VersionOutput> {
+    if let Some(output) = cached_version_output(path) {
+        debug!("Using cached --version output for {}", path);
+        return Ok(output);
+    }
+
+    let mut cmd = Command::new(path);
+    cmd.arg("--version");
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let output = VersionOutput::from_output(&cmd.output()?);
+    store_version_output(path, &output);
+    Ok(output)
+}
+
+/// 读取缓存的检测结果：键不一致或缓存的二进制已不存在时失效
+fn cached_detection(
+    tool: &str,
+    key: &BinaryCacheKey,
+) -> Option<(RuntimeEnvironment, Option<ClaudeInstallation>)> {
+    let cache = BINARY_CACHE.lock().unwrap();
+    let (cached_key, env, best) = cache.get(tool)?;
+    let still_exists = best
+        .as_ref()
+        .map_or(true, |inst| std::path::Path::new(&inst.path).exists());
+    (cached_key == key && still_exists).then(|| (env.clone(), best.clone()))
+}
+
 /// 通用检测入口，可供 Codex/其他二进制共享
+///
+/// 结果按 (tool, PATH 哈希, binaries.json 修改时间) 缓存，避免频繁的状态轮询
+/// 反复启动 `--version` 子进程。
 pub fn detect_binary_for_tool(
     tool: &str,
     env_var: &str,
     config_key: &str,
 ) -> (RuntimeEnvironment, Option<ClaudeInstallation>) {
+    let key = BinaryCacheKey::current(env_var, config_key);
+    if let Some(cached) = cached_detection(tool, &key) {
+        debug!("Using cached binary detection for {}", tool);
+        return cached;
+    }
+
     let runtime_env = detect_runtime_environment();
     let user_cfg = load_binary_search_config();
     let user_section = pick_section(&user_cfg, config_key);
 
     let prioritized = collect_runtime_candidates(tool, env_var, &runtime_env, user_section);
     let best = select_best_with_priority(prioritized);
+
+    BINARY_CACHE
+        .lock()
+        .unwrap()
+        .insert(tool.to_string(), (key, runtime_env.clone(), best.clone()));
     (runtime_env, best)
 }
 
@@ -825,73 +991,46 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
     info!("Platform: Windows");
 
     // First check if we have a stored path in the database
-    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
-        let db_path = app_data_dir.join("agents.db");
-        if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                // Check if app version has changed - if so, clear CLI path cache
-                let stored_version: Option<String> = conn
-                    .query_row(
-                        "SELECT value FROM app_settings WHERE key = 'app_version'",
-                        [],
-                        |row| row.get::<_, String>(0),
-                    )
-                    .ok();
-
-                let version_changed = stored_version.as_deref() != Some(APP_VERSION);
-                if version_changed {
-                    info!(
-                        "App version changed from {:?} to {}, clearing CLI path cache",
-                        stored_version, APP_VERSION
-                    );
-                    // Clear cached CLI paths on version upgrade
-                    let _ = conn.execute(
-                        "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                        [],
-                    );
-                    // Update stored version
-                    let _ = conn.execute(
-                        "INSERT OR REPLACE INTO app_settings (key, value) VALUES ('app_version', ?1)",
-                        rusqlite::params![APP_VERSION],
-                    );
-                }
+    if let Ok(conn) = open_agent_db() {
+        // Check if app version has changed - if so, clear CLI path cache
+        let stored_version: Option<String> = read_setting(&conn, app_settings::APP_VERSION).ok().flatten();
+
+        let version_changed = stored_version.as_deref() != Some(APP_VERSION);
+        if version_changed {
+            info!(
+                "App version changed from {:?} to {}, clearing CLI path cache",
+                stored_version, APP_VERSION
+            );
+            // Clear cached CLI paths on version upgrade
+            let _ = remove_setting(&conn, CLAUDE_BINARY_PATH);
+            // Update stored version
+            let _ = write_setting(&conn, app_settings::APP_VERSION, APP_VERSION);
+        }
 
-                // Only use cached path if version hasn't changed
-                if !version_changed {
-                    if let Ok(stored_path) = conn.query_row(
-                        "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
-                        [],
-                        |row| row.get::<_, String>(0),
-                    ) {
-                        info!("Found cached claude path in database: {}", stored_path);
-
-                        // Verify the stored path still exists and is accessible
-                        let path_buf = PathBuf::from(&stored_path);
-                        if path_buf.exists() && path_buf.is_file() {
-                            // Test if the binary is actually executable
-                            if test_claude_binary(&stored_path) {
-                                info!("✅ Using cached Claude CLI path: {}", stored_path);
-                                return Ok(stored_path);
-                            } else {
-                                warn!(
-                                    "❌ Cached claude path exists but is not executable: {}",
-                                    stored_path
-                                );
-                                // Remove invalid cached path
-                                let _ = conn.execute(
-                                    "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                                    [],
-                                );
-                            }
-                        } else {
-                            warn!("❌ Cached claude path no longer exists: {}", stored_path);
-                            // Remove invalid cached path
-                            let _ = conn.execute(
-                                "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                                [],
-                            );
-                        }
+        // Only use cached path if version hasn't changed
+        if !version_changed {
+            if let Ok(Some(stored_path)) = read_setting::<String>(&conn, CLAUDE_BINARY_PATH) {
+                info!("Found cached claude path in database: {}", stored_path);
+
+                // Verify the stored path still exists and is accessible
+                let path_buf = PathBuf::from(&stored_path);
+                if path_buf.exists() && path_buf.is_file() {
+                    // Test if the binary is actually executable
+                    if test_claude_binary(&stored_path) {
+                        info!("✅ Using cached Claude CLI path: {}", stored_path);
+                        return Ok(stored_path);
+                    } else {
+                        warn!(
+                            "❌ Cached claude path exists but is not executable: {}",
+                            stored_path
+                        );
+                        // Remove invalid cached path
+                        let _ = remove_setting(&conn, CLAUDE_BINARY_PATH);
                     }
+                } else {
+                    warn!("❌ Cached claude path no longer exists: {}", stored_path);
+                    // Remove invalid cached path
+                    let _ = remove_setting(&conn, CLAUDE_BINARY_PATH);
                 }
             }
         }
@@ -953,63 +1092,22 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
 
 /// Store Claude CLI path in database for future use
 fn store_claude_path(app_handle: &tauri::AppHandle, path: &str) -> Result<(), String> {
-    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
-        if let Err(e) = std::fs::create_dir_all(&app_data_dir) {
-            return Err(format!("Failed to create app data directory: {}", e));
-        }
-
-        let db_path = app_data_dir.join("agents.db");
-        match rusqlite::Connection::open(&db_path) {
-            Ok(conn) => {
-                // Create table if it doesn't exist
-                if let Err(e) = conn.execute(
-                    "CREATE TABLE IF NOT EXISTS app_settings (
-                        key TEXT PRIMARY KEY,
-                        value TEXT NOT NULL
-                    )",
-                    [],
-                ) {
-                    return Err(format!("Failed to create settings table: {}", e));
-                }
-
-                // Store the path
-                if let Err(e) = conn.execute(
-                    "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-                    rusqlite::params!["claude_binary_path", path],
-                ) {
-                    return Err(format!("Failed to store claude path: {}", e));
-                }
-
-                info!("Stored claude path in database: {}", path);
-                Ok(())
-            }
-            Err(e) => Err(format!("Failed to open database: {}", e)),
-        }
-    } else {
-        Err("Failed to get app data directory".to_string())
-    }
+    let conn = open_agent_db()?;
+    write_setting(&conn, CLAUDE_BINARY_PATH, path)?;
+    app_settings::notify_setting_changed(app_handle, CLAUDE_BINARY_PATH, Some(path.into()));
+    info!("Stored claude path in database: {}", path);
+    Ok(())
 }
 
 /// Test if a Claude binary is actually functional (cross-platform)
 fn test_claude_binary(path: &str) -> bool {
     debug!("Testing Claude binary at: {}", path);
 
-    // Test with a simple --version command
-    let mut cmd = Command::new(path);
-    cmd.arg("--version");
-
-    // Add CREATE_NO_WINDOW flag on Windows to prevent terminal window popup
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-    }
-
-    match cmd.output() {
+    // Test with a simple --version command (cached by path + mtime)
+    match probe_version_cached(path) {
         Ok(output) => {
-            let success = output.status.success();
-            debug!("Claude binary test result: success={}", success);
-            success
+            debug!("Claude binary test result: success={}", output.success);
+            output.success
         }
         Err(e) => {
             debug!("Failed to test Claude binary: {}", e);
@@ -1735,17 +1833,8 @@ fn find_macos_installations() -> Vec<ClaudeInstallation> {
 
 /// 通用的版本获取（用于 Claude/Codex 等 CLI）
 fn get_binary_version_generic(path: &str) -> Option<String> {
-    let mut cmd = Command::new(path);
-    cmd.arg("--version");
-
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000);
-    }
-
-    match cmd.output() {
-        Ok(output) if output.status.success() => extract_version_from_output(&output.stdout),
+    match probe_version_cached(path) {
+        Ok(output) if output.success => extract_version_from_output(output.stdout.as_bytes()),
         _ => None,
     }
 }
@@ -2116,3 +2205,111 @@ pub fn create_command_with_env(program: &str) -> Command {
 
     cmd
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// `invalidate_binary_cache` clears the shared version cache, keep tests calling it apart
+    static INVALIDATION_LOCK: Mutex<()> = Mutex::new(());
+
+    fn output(stdout: &str, success: bool) -> VersionOutput {
+        VersionOutput { success, stdout: stdout.to_string(), stderr: String::new() }
+    }
+
+    #[test]
+    fn version_cache_invalidates_on_mtime_change_and_explicit_clear() {
+        let _guard = INVALIDATION_LOCK.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("codex");
+        std::fs::write(&binary, "v1").unwrap();
+        let path = binary.to_string_lossy().to_string();
+
+        store_version_output(&path, &output("codex 1.0.0", true));
+        assert_eq!(cached_version_output(&path), Some(output("codex 1.0.0", true)));
+
+        // 升级替换二进制后修改时间变化，缓存失效
+        let file = std::fs::File::options().write(true).open(&binary).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        assert_eq!(cached_version_output(&path), None);
+
+        store_version_output(&path, &output("codex 1.1.0", true));
+        assert_eq!(cached_version_output(&path), Some(output("codex 1.1.0", true)));
+        invalidate_binary_cache("codex");
+        assert_eq!(cached_version_output(&path), None);
+    }
+
+    #[test]
+    fn version_cache_skips_failed_and_missing_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("gemini");
+        std::fs::write(&binary, "").unwrap();
+        let path = binary.to_string_lossy().to_string();
+
+        store_version_output(&path, &output("", false));
+        assert_eq!(cached_version_output(&path), None);
+
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        store_version_output(&missing, &output("1.0.0", true));
+        assert_eq!(cached_version_output(&missing), None);
+    }
+
+    #[test]
+    fn detection_cache_key_tracks_path_override_and_config_mtime() {
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let key = |path: &str, custom: Option<&str>, mtime: Option<SystemTime>| {
+            BinaryCacheKey::new(Some(path.into()), custom.map(OsString::from), "codex", mtime)
+        };
+
+        let base = key("/usr/bin", None, Some(mtime));
+        assert_eq!(base, key("/usr/bin", None, Some(mtime)));
+        assert_ne!(base, key("/usr/bin:/opt/bin", None, Some(mtime)));
+        assert_ne!(base, key("/usr/bin", Some("/opt/codex"), Some(mtime)));
+        assert_ne!(base, key("/usr/bin", None, Some(mtime + Duration::from_secs(1))));
+        assert_ne!(base, key("/usr/bin", None, None));
+        assert_ne!(base, BinaryCacheKey::new(Some("/usr/bin".into()), None, "claude", Some(mtime)));
+    }
+
+    #[test]
+    fn detection_cache_hits_until_key_binary_or_invalidation_changes() {
+        let _guard = INVALIDATION_LOCK.lock().unwrap();
+        let tool = "detection-cache-test";
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join(tool);
+        std::fs::write(&binary, "").unwrap();
+
+        let env = RuntimeEnvironment {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            is_wsl: false,
+            is_container: false,
+            distro: None,
+        };
+        let installation = ClaudeInstallation {
+            path: binary.to_string_lossy().to_string(),
+            version: Some("1.0.0".to_string()),
+            source: "system".to_string(),
+            installation_type: InstallationType::System,
+        };
+        let key = BinaryCacheKey::new(Some("/usr/bin".into()), None, tool, None);
+        BINARY_CACHE
+            .lock()
+            .unwrap()
+            .insert(tool.to_string(), (key.clone(), env, Some(installation)));
+
+        let (_, best) = cached_detection(tool, &key).unwrap();
+        assert_eq!(best.unwrap().version.as_deref(), Some("1.0.0"));
+        let other_path = BinaryCacheKey::new(Some("/opt/bin".into()), None, tool, None);
+        assert!(cached_detection(tool, &other_path).is_none());
+
+        // 缓存的二进制被删除后重新检测
+        std::fs::remove_file(&binary).unwrap();
+        assert!(cached_detection(tool, &key).is_none());
+
+        std::fs::write(&binary, "").unwrap();
+        assert!(cached_detection(tool, &key).is_some());
+        invalidate_binary_cache(tool);
+        assert!(cached_detection(tool, &key).is_none());
+    }
+}
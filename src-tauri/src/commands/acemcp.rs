@@ -21,6 +21,10 @@ use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 use log::{debug, error, info, warn};
 use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
 
 // Windows: 导入 CommandExt trait 以使用 creation_flags
 #[cfg(target_os = "windows")]
@@ -288,6 +292,8 @@ fn generate_smart_query(
 struct AcemcpClient {
     child: tokio::process::Child,
     request_id: u64,
+    /// 工具调用追踪所属的会话
+    session_id: Option<String>,
 }
 
 impl AcemcpClient {
@@ -403,7 +409,8 @@ impl AcemcpClient {
 
         Ok(Self {
             child,
-            request_id: 0
+            request_id: 0,
+            session_id: None,
         })
     }
 
@@ -508,15 +515,12 @@ impl AcemcpClient {
     async fn search_context(&mut self, project_path: &str, query: &str) -> Result<String> {
         info!("Calling search_context: project={}, query={}", project_path, query);
 
-        let params = json!({
-            "name": "search_context",
-            "arguments": {
-                "project_root_path": project_path.replace('\\', "/"),
-                "query": query
-            }
+        let arguments = json!({
+            "project_root_path": project_path.replace('\\', "/"),
+            "query": query
         });
 
-        let result = self.send_request("tools/call", Some(params)).await?;
+        let result = self.call_tool("search_context", arguments, None).await.0?;
 
         // 解析结果
         if let Some(content) = result.get("content").and_then(|c| c.as_array()) {
@@ -530,6 +534,38 @@ impl AcemcpClient {
         Err(anyhow::anyhow!("Invalid search_context response format"))
     }
 
+    /// 调用 MCP 工具，并把参数、结果和耗时写入追踪表
+    ///
+    /// 返回调用结果和追踪记录 ID（记录失败时为 None，不影响调用本身）。
+    async fn call_tool(&mut self, name: &str, arguments: Value, replay_of: Option<i64>) -> (Result<Value>, Option<i64>) {
+        let params = json!({ "name": name, "arguments": arguments });
+        let started = std::time::Instant::now();
+        let result = self.send_request("tools/call", Some(params)).await;
+
+        let trace = NewToolTrace {
+            session_id: self.session_id.clone(),
+            tool_name: name.to_string(),
+            params: arguments,
+            result: result.as_ref().ok().map(|value| value.to_string()),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            latency_ms: started.elapsed().as_millis() as u64,
+            replay_of,
+        };
+        let trace_id = match tokio::task::spawn_blocking(move || insert_tool_trace(&*open_trace_db()?, &trace)).await {
+            Ok(Ok(id)) => Some(id),
+            Ok(Err(e)) => {
+                warn!("Failed to record acemcp tool trace: {}", e);
+                None
+            }
+            Err(e) => {
+                warn!("Failed to record acemcp tool trace: {}", e);
+                None
+            }
+        };
+
+        (result, trace_id)
+    }
+
     /// 多轮搜索：使用不同的查询策略获取更全面的上下文
     async fn multi_round_search(
         &mut self,
@@ -1047,6 +1083,8 @@ pub async fn enhance_prompt_with_context(
         }
     };
 
+    client.session_id = session_id.clone();
+
     // 初始化 MCP 会话
     if let Err(e) = client.initialize().await {
         error!("Failed to initialize MCP session: {}", e);
@@ -1586,3 +1624,227 @@ pub async fn get_extracted_sidecar_path() -> Result<Option<String>, String> {
         Ok(None)
     }
 }
+
+// ============================================================================
+// 工具调用追踪
+// ============================================================================
+
+/// 单条结果保存的最大字节数（search_context 的结果可能很大）
+const MAX_TRACE_RESULT_BYTES: usize = 64 * 1024;
+
+/// 最多保留的追踪记录数
+const MAX_TRACE_ROWS: i64 = 5000;
+
+/// 查询默认返回条数
+const DEFAULT_TRACE_LIMIT: u32 = 200;
+
+/// 一次 acemcp 工具调用记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcemcpToolTrace {
+    pub id: i64,
+    pub session_id: Option<String>,
+    pub tool_name: String,
+    pub params: Value,
+    /// 原始结果 JSON（超长时截断）
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+    pub created_at: String,
+    /// 重放时指向原始记录
+    pub replay_of: Option<i64>,
+}
+
+/// 待写入的追踪记录
+struct NewToolTrace {
+    session_id: Option<String>,
+    tool_name: String,
+    params: Value,
+    result: Option<String>,
+    error: Option<String>,
+    latency_ms: u64,
+    replay_of: Option<i64>,
+}
+
+/// 创建工具调用追踪表（幂等）
+fn ensure_trace_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS acemcp_tool_traces (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT,
+            tool_name TEXT NOT NULL,
+            params TEXT NOT NULL,
+            result TEXT,
+            error TEXT,
+            latency_ms INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            replay_of INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_acemcp_tool_traces_session
+            ON acemcp_tool_traces(session_id, id);",
+    )
+    .map_err(|e| format!("创建 acemcp 追踪表失败: {}", e))
+}
+
+fn open_trace_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_trace_schema(&conn)?;
+    Ok(conn)
+}
+
+fn insert_tool_trace(conn: &Connection, trace: &NewToolTrace) -> Result<i64, String> {
+    let result = trace.result.as_deref().map(|text| {
+        if text.len() > MAX_TRACE_RESULT_BYTES {
+            format!("{}…[truncated {} bytes]", truncate_utf8_safe(text, MAX_TRACE_RESULT_BYTES), text.len())
+        } else {
+            text.to_string()
+        }
+    });
+
+    conn.execute(
+        "INSERT INTO acemcp_tool_traces
+            (session_id, tool_name, params, result, error, latency_ms, created_at, replay_of)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            trace.session_id,
+            trace.tool_name,
+            trace.params.to_string(),
+            result,
+            trace.error,
+            trace.latency_ms as i64,
+            chrono::Utc::now().to_rfc3339(),
+            trace.replay_of,
+        ],
+    )
+    .map_err(|e| format!("写入 acemcp 追踪记录失败: {}", e))?;
+    let id = conn.last_insert_rowid();
+
+    conn.execute(
+        "DELETE FROM acemcp_tool_traces WHERE id <= ?1",
+        params![id - MAX_TRACE_ROWS],
+    )
+    .map_err(|e| format!("清理 acemcp 追踪记录失败: {}", e))?;
+    Ok(id)
+}
+
+const TRACE_COLUMNS: &str =
+    "id, session_id, tool_name, params, result, error, latency_ms, created_at, replay_of";
+
+fn row_to_trace(row: &rusqlite::Row) -> rusqlite::Result<AcemcpToolTrace> {
+    let params: String = row.get(3)?;
+    Ok(AcemcpToolTrace {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        tool_name: row.get(2)?,
+        params: serde_json::from_str(&params).unwrap_or(Value::String(params)),
+        result: row.get(4)?,
+        error: row.get(5)?,
+        latency_ms: row.get::<_, i64>(6)? as u64,
+        created_at: row.get(7)?,
+        replay_of: row.get(8)?,
+    })
+}
+
+/// 某个会话的追踪记录（最新的在前）
+fn query_session_traces(conn: &Connection, session_id: &str, limit: u32) -> Result<Vec<AcemcpToolTrace>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM acemcp_tool_traces WHERE session_id = ?1 ORDER BY id DESC LIMIT ?2",
+            TRACE_COLUMNS
+        ))
+        .map_err(|e| format!("查询 acemcp 追踪记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![session_id, limit], row_to_trace)
+        .map_err(|e| format!("查询 acemcp 追踪记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取 acemcp 追踪记录失败: {}", e));
+    rows
+}
+
+fn get_tool_trace(conn: &Connection, trace_id: i64) -> Result<AcemcpToolTrace, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM acemcp_tool_traces WHERE id = ?1", TRACE_COLUMNS),
+        params![trace_id],
+        row_to_trace,
+    )
+    .optional()
+    .map_err(|e| format!("查询 acemcp 追踪记录失败: {}", e))?
+    .ok_or_else(|| format!("追踪记录不存在: {}", trace_id))
+}
+
+/// 查询会话中的 acemcp 工具调用记录（最新的在前）
+#[tauri::command]
+pub async fn acemcp_list_tool_traces(
+    session_id: String,
+    limit: Option<u32>,
+) -> Result<Vec<AcemcpToolTrace>, String> {
+    let limit = limit.unwrap_or(DEFAULT_TRACE_LIMIT);
+    tokio::task::spawn_blocking(move || query_session_traces(&*open_trace_db()?, &session_id, limit))
+        .await
+        .map_err(|e| format!("查询 acemcp 追踪记录失败: {}", e))?
+}
+
+/// 用相同参数重新执行一次记录过的工具调用，返回新的追踪记录
+///
+/// 用于排查结果不稳定的 MCP 服务器：新记录的 `replay_of` 指向原始记录，便于对比结果和耗时。
+#[tauri::command]
+pub async fn acemcp_replay_tool_trace(app: AppHandle, trace_id: i64) -> Result<AcemcpToolTrace, String> {
+    let original = tokio::task::spawn_blocking(move || get_tool_trace(&*open_trace_db()?, trace_id))
+        .await
+        .map_err(|e| format!("查询 acemcp 追踪记录失败: {}", e))??;
+    info!("Replaying acemcp tool call #{} ({})", original.id, original.tool_name);
+
+    let mut client = AcemcpClient::start(&app)
+        .await
+        .map_err(|e| format!("Failed to start acemcp: {}", e))?;
+    client.session_id = original.session_id.clone();
+    if let Err(e) = client.initialize().await {
+        let _ = client.shutdown().await;
+        return Err(format!("Failed to initialize MCP: {}", e));
+    }
+
+    let (_, replay_id) = client
+        .call_tool(&original.tool_name, original.params.clone(), Some(original.id))
+        .await;
+    let _ = client.shutdown().await;
+
+    let replay_id = replay_id.ok_or_else(|| "重放已执行，但写入追踪记录失败".to_string())?;
+    tokio::task::spawn_blocking(move || get_tool_trace(&*open_trace_db()?, replay_id))
+        .await
+        .map_err(|e| format!("查询 acemcp 追踪记录失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_and_queries_tool_traces() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_trace_schema(&conn).unwrap();
+
+        let trace = NewToolTrace {
+            session_id: Some("s1".to_string()),
+            tool_name: "search_context".to_string(),
+            params: json!({ "query": "auth" }),
+            result: Some("x".repeat(MAX_TRACE_RESULT_BYTES + 10)),
+            error: None,
+            latency_ms: 42,
+            replay_of: None,
+        };
+        let first = insert_tool_trace(&conn, &trace).unwrap();
+        let replay = insert_tool_trace(
+            &conn,
+            &NewToolTrace { result: None, error: Some("timeout".to_string()), replay_of: Some(first), ..trace },
+        )
+        .unwrap();
+
+        let traces = query_session_traces(&conn, "s1", 10).unwrap();
+        assert_eq!(traces.iter().map(|t| t.id).collect::<Vec<_>>(), vec![replay, first]);
+        assert_eq!(traces[0].replay_of, Some(first));
+        assert_eq!(traces[1].params["query"], "auth");
+        assert!(traces[1].result.as_deref().unwrap().ends_with("[truncated 65546 bytes]"));
+        assert!(query_session_traces(&conn, "s2", 10).unwrap().is_empty());
+        assert!(get_tool_trace(&conn, 999).is_err());
+    }
+}
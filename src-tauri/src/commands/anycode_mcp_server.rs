@@ -0,0 +1,437 @@
+//! 内置 MCP 服务器
+//!
+//! 把 Any Code 自身的数据（项目与会话、提示词历史、文件变更记录及 diff）以 MCP 工具的形式
+//! 暴露给外部 CLI。服务器监听 `127.0.0.1`，使用 Streamable HTTP 传输（JSON 响应模式），
+//! 每次启动生成新的 Bearer token，例如：
+//!
+//! ```text
+//! claude mcp add --transport http anycode http://127.0.0.1:<port>/mcp \
+//!     --header "Authorization: Bearer <token>"
+//! ```
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use super::mcp::MCP_PROTOCOL_VERSION;
+
+/// 请求体上限
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// 单次读取的超时时间，避免空闲连接一直占用任务
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// accept 失败后的等待时间（如文件描述符耗尽），避免空转
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(200);
+
+/// 正在运行的服务器信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnycodeMcpServerInfo {
+    pub port: u16,
+    pub url: String,
+    pub token: String,
+    /// 可直接写入 mcpServers 的配置
+    pub config: Value,
+}
+
+struct RunningServer {
+    info: AnycodeMcpServerInfo,
+    task: JoinHandle<()>,
+}
+
+static SERVER: Lazy<Mutex<Option<RunningServer>>> = Lazy::new(|| Mutex::new(None));
+
+// ============================================================================
+// 工具
+// ============================================================================
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "list_projects",
+            "description": "List projects known to Any Code",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "list_sessions",
+            "description": "List Claude sessions of a project (id from list_projects)",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "project_id": { "type": "string" } },
+                "required": ["project_id"]
+            }
+        },
+        {
+            "name": "search_prompt_history",
+            "description": "Search prompts previously sent from Any Code, most recent first",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "engine": { "type": "string", "enum": ["claude", "codex", "gemini"] },
+                    "project_path": { "type": "string" },
+                    "limit": { "type": "integer", "minimum": 1 }
+                }
+            }
+        },
+        {
+            "name": "list_change_records",
+            "description": "List file changes recorded for a session",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "session_id": { "type": "string" } },
+                "required": ["session_id"]
+            }
+        },
+        {
+            "name": "get_file_change_diff",
+            "description": "Get the unified diff of one recorded file change",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": { "type": "string" },
+                    "change_id": { "type": "string" }
+                },
+                "required": ["session_id", "change_id"]
+            }
+        }
+    ])
+}
+
+fn to_value<T: Serialize, E: Into<String>>(result: Result<T, E>) -> Result<Value, String> {
+    result
+        .map_err(Into::into)
+        .and_then(|v| serde_json::to_value(v).map_err(|e| e.to_string()))
+}
+
+async fn call_tool(name: &str, args: &Value) -> Result<Value, String> {
+    let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let required = |key: &str| str_arg(key).ok_or_else(|| format!("缺少参数: {}", key));
+
+    match name {
+        "list_projects" => to_value(super::claude::list_projects().await),
+        "list_sessions" => to_value(super::claude::get_project_sessions(required("project_id")?, None).await),
+        "search_prompt_history" => to_value(
+            super::prompt_history::search_prompt_history(
+                str_arg("query"),
+                str_arg("engine"),
+                str_arg("project_path"),
+                None,
+                None,
+                args.get("limit").and_then(|v| v.as_u64()).map(|v| v as u32),
+            )
+            .await,
+        ),
+        "list_change_records" => {
+            to_value(super::codex::change_tracker::codex_list_file_changes(required("session_id")?).await)
+        }
+        "get_file_change_diff" => {
+            let change = super::codex::change_tracker::codex_get_change_detail(
+                required("session_id")?,
+                required("change_id")?,
+            )
+            .await?;
+            Ok(json!({
+                "file_path": change.file_path,
+                "unified_diff": change.unified_diff.unwrap_or_default(),
+            }))
+        }
+        _ => Err(format!("未知工具: {}", name)),
+    }
+}
+
+// ============================================================================
+// JSON-RPC
+// ============================================================================
+
+fn jsonrpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// 处理一条 JSON-RPC 消息；通知（无 id）返回 None
+async fn handle_message(message: Value) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": params
+                .get("protocolVersion")
+                .and_then(|v| v.as_str())
+                .unwrap_or(MCP_PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "anycode", "version": env!("CARGO_PKG_VERSION") }
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tool_definitions() }),
+        "tools/call" => {
+            let name = params.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+            let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+            // 工具错误按 MCP 约定放在结果里（isError），而不是协议错误
+            match call_tool(name, &args).await {
+                Ok(value) => json!({
+                    "content": [{
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&value).unwrap_or_default()
+                    }]
+                }),
+                Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+            }
+        }
+        _ => return Some(jsonrpc_error(id, -32601, &format!("Method not found: {}", method))),
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+/// 处理请求体：单条消息或批量数组；全部是通知时返回 None
+async fn handle_body(body: &[u8]) -> Option<Value> {
+    let message: Value = match serde_json::from_slice(body) {
+        Ok(message) => message,
+        Err(e) => return Some(jsonrpc_error(Value::Null, -32700, &format!("Parse error: {}", e))),
+    };
+    match message {
+        Value::Array(batch) => {
+            let mut responses = Vec::new();
+            for message in batch {
+                if let Some(response) = handle_message(message).await {
+                    responses.push(response);
+                }
+            }
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        message => handle_message(message).await,
+    }
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+pub(crate) struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+}
+
+/// 解析请求行和头部（header 名转为小写）
+pub(crate) fn parse_request_head(head: &str) -> Option<HttpRequest> {
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+    Some(HttpRequest { method, path, headers })
+}
+
+pub(crate) async fn write_response(stream: &mut TcpStream, status: &str, body: Option<&Value>) -> std::io::Result<()> {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// 比较 token，耗时与内容无关
+fn token_matches(provided: &str, expected: &str) -> bool {
+    let (a, b) = (provided.as_bytes(), expected.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 带超时的读取，超时视为连接错误
+async fn read_chunk(stream: &mut TcpStream, chunk: &mut [u8]) -> std::io::Result<usize> {
+    tokio::time::timeout(READ_TIMEOUT, stream.read(chunk))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "read timed out"))?
+}
+
+async fn handle_connection(mut stream: TcpStream, token: String) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > 64 * 1024 {
+            return write_response(&mut stream, "431 Request Header Fields Too Large", None).await;
+        }
+        let n = read_chunk(&mut stream, &mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let Some(request) = parse_request_head(&String::from_utf8_lossy(&buffer[..head_end])) else {
+        return write_response(&mut stream, "400 Bad Request", None).await;
+    };
+    if request.path.split('?').next() != Some("/mcp") {
+        return write_response(&mut stream, "404 Not Found", None).await;
+    }
+    let authorized = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|provided| token_matches(provided, &token));
+    if !authorized {
+        return write_response(&mut stream, "401 Unauthorized", None).await;
+    }
+    if request.method != "POST" {
+        // 不提供服务端推送流
+        return write_response(&mut stream, "405 Method Not Allowed", None).await;
+    }
+
+    let content_length: usize = request
+        .headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return write_response(&mut stream, "413 Payload Too Large", None).await;
+    }
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = read_chunk(&mut stream, &mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    match handle_body(&body).await {
+        Some(response) => write_response(&mut stream, "200 OK", Some(&response)).await,
+        None => write_response(&mut stream, "202 Accepted", None).await,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 启动内置 MCP 服务器（已在运行时返回当前信息）；`port` 为空时由系统分配
+#[tauri::command]
+pub async fn start_anycode_mcp_server(port: Option<u16>) -> Result<AnycodeMcpServerInfo, String> {
+    if let Some(running) = SERVER.lock().unwrap().as_ref() {
+        return Ok(running.info.clone());
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", port.unwrap_or(0)))
+        .await
+        .map_err(|e| format!("绑定端口失败: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let url = format!("http://127.0.0.1:{}/mcp", port);
+
+    let server_token = token.clone();
+    let task = tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("[AnycodeMcp] Accept failed: {}", e);
+                    tokio::time::sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            };
+            let token = server_token.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, token).await {
+                    log::debug!("[AnycodeMcp] Connection error: {}", e);
+                }
+            });
+        }
+    });
+
+    let info = AnycodeMcpServerInfo {
+        port,
+        config: json!({
+            "type": "http",
+            "url": url,
+            "headers": { "Authorization": format!("Bearer {}", token) }
+        }),
+        url,
+        token,
+    };
+    log::info!("[AnycodeMcp] Listening on {}", info.url);
+
+    let mut server = SERVER.lock().unwrap();
+    if let Some(running) = server.as_ref() {
+        // 并发启动时保留先完成的那个
+        task.abort();
+        return Ok(running.info.clone());
+    }
+    *server = Some(RunningServer { info: info.clone(), task });
+    Ok(info)
+}
+
+/// 停止内置 MCP 服务器
+#[tauri::command]
+pub async fn stop_anycode_mcp_server() -> Result<(), String> {
+    if let Some(running) = SERVER.lock().unwrap().take() {
+        running.task.abort();
+        log::info!("[AnycodeMcp] Stopped server on port {}", running.info.port);
+    }
+    Ok(())
+}
+
+/// 获取内置 MCP 服务器状态（未运行时为 None）
+#[tauri::command]
+pub async fn get_anycode_mcp_server_status() -> Result<Option<AnycodeMcpServerInfo>, String> {
+    Ok(SERVER.lock().unwrap().as_ref().map(|running| running.info.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn handles_handshake_and_rejects_unknown_methods() {
+        let init = handle_body(br#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(init["result"]["serverInfo"]["name"], "anycode");
+
+        assert!(handle_body(br#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).await.is_none());
+
+        let batch = handle_body(br#"[{"jsonrpc":"2.0","id":2,"method":"tools/list"},{"jsonrpc":"2.0","id":3,"method":"nope"}]"#)
+            .await
+            .unwrap();
+        assert_eq!(batch[0]["result"]["tools"].as_array().unwrap().len(), 5);
+        assert_eq!(batch[1]["error"]["code"], -32601);
+
+        let call = handle_body(br#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"list_sessions","arguments":{}}}"#)
+            .await
+            .unwrap();
+        assert_eq!(call["result"]["isError"], true);
+
+        assert_eq!(handle_body(b"{").await.unwrap()["error"]["code"], -32700);
+
+        let request = parse_request_head("POST /mcp HTTP/1.1\r\nContent-Length: 12\r\nAuthorization: Bearer t").unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/mcp"));
+        assert_eq!(request.headers["content-length"], "12");
+        assert_eq!(request.headers["authorization"], "Bearer t");
+    }
+
+    #[test]
+    fn token_comparison() {
+        assert!(token_matches("abc123", "abc123"));
+        assert!(!token_matches("abc124", "abc123"));
+        assert!(!token_matches("abc12", "abc123"));
+        assert!(!token_matches("", "abc123"));
+    }
+}
@@ -0,0 +1,247 @@
+//! 通用键值设置（agents.db 的 app_settings 表）
+//!
+//! 所有 app_settings 的读写都经过这里：值以 JSON 文本保存，旧版本直接写入的纯文本值
+//! （如 CLI 路径、app_version）读取时按字符串处理。
+//!
+//! 前端通过 `settings_get` / `settings_set` / `settings_delete` 访问；窗口调用 `settings_watch`
+//! 订阅若干键后，这些键被修改（包括后端内部修改）时会收到 `settings-changed` 事件。
+
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Window};
+
+use super::storage::with_agent_db;
+
+/// 设置变更事件名
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// 常用键
+pub const CLAUDE_BINARY_PATH: &str = "claude_binary_path";
+pub const CODEX_BINARY_PATH: &str = "codex_binary_path";
+pub const APP_VERSION: &str = "app_version";
+
+/// 窗口 label → 订阅的键
+static WATCHERS: Lazy<Mutex<HashMap<String, HashSet<String>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 设置变更事件（value 为 null 表示已删除）
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChange {
+    pub key: String,
+    pub value: Option<Value>,
+}
+
+fn validate_key(key: &str) -> Result<(), String> {
+    let valid = !key.is_empty()
+        && key.len() <= 128
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("无效的设置键: {}", key))
+    }
+}
+
+/// 解析保存的文本；不是合法 JSON 的旧值按字符串处理
+fn decode(raw: String) -> Value {
+    serde_json::from_str(&raw).unwrap_or(Value::String(raw))
+}
+
+fn read_value(conn: &Connection, key: &str) -> Result<Option<Value>, String> {
+    conn.query_row("SELECT value FROM app_settings WHERE key = ?1", params![key], |row| {
+        row.get::<_, String>(0)
+    })
+    .optional()
+    .map(|raw| raw.map(decode))
+    .map_err(|e| format!("读取设置 {} 失败: {}", key, e))
+}
+
+/// 读取设置并转换为 `T`
+pub fn read_setting<T: DeserializeOwned>(conn: &Connection, key: &str) -> Result<Option<T>, String> {
+    read_value(conn, key)?
+        .map(|value| serde_json::from_value(value).map_err(|e| format!("设置 {} 的类型不匹配: {}", key, e)))
+        .transpose()
+}
+
+pub fn write_setting<T: Serialize + ?Sized>(conn: &Connection, key: &str, value: &T) -> Result<(), String> {
+    let text = serde_json::to_string(value).map_err(|e| format!("序列化设置 {} 失败: {}", key, e))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![key, text],
+    )
+    .map_err(|e| format!("保存设置 {} 失败: {}", key, e))?;
+    Ok(())
+}
+
+/// 删除设置，返回是否存在
+pub fn remove_setting(conn: &Connection, key: &str) -> Result<bool, String> {
+    conn.execute("DELETE FROM app_settings WHERE key = ?1", params![key])
+        .map(|count| count > 0)
+        .map_err(|e| format!("删除设置 {} 失败: {}", key, e))
+}
+
+fn watchers_for(key: &str) -> Vec<String> {
+    WATCHERS
+        .lock()
+        .map(|watchers| {
+            watchers
+                .iter()
+                .filter(|(_, keys)| keys.contains(key))
+                .map(|(label, _)| label.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 通知订阅了该键的窗口
+pub fn notify_setting_changed(app: &AppHandle, key: &str, value: Option<Value>) {
+    let change = SettingChange {
+        key: key.to_string(),
+        value,
+    };
+    for label in watchers_for(key) {
+        let _ = app.emit_to(label.as_str(), SETTINGS_CHANGED_EVENT, &change);
+    }
+}
+
+/// 异步读取设置（在阻塞线程池中访问数据库）
+pub async fn get_setting<T: DeserializeOwned + Send + 'static>(key: &str) -> Result<Option<T>, String> {
+    let key = key.to_string();
+    with_agent_db(move |conn| read_setting(conn, &key)).await
+}
+
+/// 异步保存设置并发送变更事件
+pub async fn set_setting<T: Serialize + ?Sized>(app: &AppHandle, key: &str, value: &T) -> Result<(), String> {
+    let value = serde_json::to_value(value).map_err(|e| format!("序列化设置 {} 失败: {}", key, e))?;
+    let (db_key, db_value) = (key.to_string(), value.clone());
+    with_agent_db(move |conn| write_setting(conn, &db_key, &db_value)).await?;
+    notify_setting_changed(app, key, Some(value));
+    Ok(())
+}
+
+/// 异步删除设置并发送变更事件
+pub async fn delete_setting(app: &AppHandle, key: &str) -> Result<bool, String> {
+    let db_key = key.to_string();
+    let removed = with_agent_db(move |conn| remove_setting(conn, &db_key)).await?;
+    if removed {
+        notify_setting_changed(app, key, None);
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn settings_get(key: String) -> Result<Option<Value>, String> {
+    validate_key(&key)?;
+    get_setting(&key).await
+}
+
+#[tauri::command]
+pub async fn settings_set(app: AppHandle, key: String, value: Value) -> Result<(), String> {
+    validate_key(&key)?;
+    set_setting(&app, &key, &value).await
+}
+
+#[tauri::command]
+pub async fn settings_delete(app: AppHandle, key: String) -> Result<bool, String> {
+    validate_key(&key)?;
+    delete_setting(&app, &key).await
+}
+
+/// 订阅设置变更，返回这些键的当前值（未设置的键不在结果中）
+#[tauri::command]
+pub async fn settings_watch(window: Window, keys: Vec<String>) -> Result<HashMap<String, Value>, String> {
+    for key in &keys {
+        validate_key(key)?;
+    }
+    WATCHERS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .entry(window.label().to_string())
+        .or_default()
+        .extend(keys.iter().cloned());
+
+    with_agent_db(move |conn| {
+        let mut values = HashMap::new();
+        for key in keys {
+            if let Some(value) = read_value(conn, &key)? {
+                values.insert(key, value);
+            }
+        }
+        Ok(values)
+    })
+    .await
+}
+
+/// 取消订阅（不传 keys 时取消该窗口的全部订阅）
+#[tauri::command]
+pub async fn settings_unwatch(window: Window, keys: Option<Vec<String>>) -> Result<(), String> {
+    let mut watchers = WATCHERS.lock().map_err(|e| e.to_string())?;
+    let label = window.label();
+    match keys {
+        Some(keys) => {
+            if let Some(watched) = watchers.get_mut(label) {
+                for key in &keys {
+                    watched.remove(key);
+                }
+                if watched.is_empty() {
+                    watchers.remove(label);
+                }
+            }
+        }
+        None => {
+            watchers.remove(label);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stores_json_values_and_reads_legacy_text() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        super::super::db_migrations::run_migrations(&mut conn).unwrap();
+
+        write_setting(&conn, "ui.sidebar", &json!({ "width": 280, "collapsed": false })).unwrap();
+        assert_eq!(read_value(&conn, "ui.sidebar").unwrap(), Some(json!({ "width": 280, "collapsed": false })));
+        write_setting(&conn, CODEX_BINARY_PATH, "/usr/local/bin/codex").unwrap();
+        assert_eq!(
+            read_setting::<String>(&conn, CODEX_BINARY_PATH).unwrap().as_deref(),
+            Some("/usr/local/bin/codex")
+        );
+        assert!(read_setting::<u32>(&conn, CODEX_BINARY_PATH).is_err());
+
+        // 旧版本直接写入的纯文本
+        conn.execute(
+            "INSERT INTO app_settings (key, value) VALUES (?1, 'C:\\Tools\\claude.exe')",
+            params![CLAUDE_BINARY_PATH],
+        )
+        .unwrap();
+        assert_eq!(
+            read_setting::<String>(&conn, CLAUDE_BINARY_PATH).unwrap().as_deref(),
+            Some("C:\\Tools\\claude.exe")
+        );
+
+        assert!(remove_setting(&conn, CLAUDE_BINARY_PATH).unwrap());
+        assert!(!remove_setting(&conn, CLAUDE_BINARY_PATH).unwrap());
+        assert_eq!(read_setting::<String>(&conn, CLAUDE_BINARY_PATH).unwrap(), None);
+
+        assert!(validate_key("editor.font-size").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("a'; DROP TABLE x").is_err());
+
+        WATCHERS
+            .lock()
+            .unwrap()
+            .insert("main".to_string(), HashSet::from(["ui.sidebar".to_string()]));
+        assert_eq!(watchers_for("ui.sidebar"), vec!["main".to_string()]);
+        assert!(watchers_for("other").is_empty());
+    }
+}
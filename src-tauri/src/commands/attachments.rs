@@ -0,0 +1,374 @@
+//! 提示词附件
+//!
+//! `stage_attachment` 校验文件（类型、大小）后复制到 `~/.anycode/attachments/<session_id>/`，
+//! 前端把返回的 `StagedAttachment` 列表随执行命令传入，再由各引擎转换为 CLI 的附件语法：
+//! - Claude / Gemini：在提示词末尾追加 `@<path>` 引用（Gemini 额外把附件目录加入
+//!   `--include-directories`，否则工作区外的文件无法读取）
+//! - Codex：新会话的图片通过 `--image` 传入，其余文件以路径列表写进提示词
+//!
+//! 会话开始后附件以 (engine, session_id) 记录在 agents.db 的 `session_attachments` 表中，
+//! 会话列表通过 `SessionMetadata::attachments` 带出。
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::storage::open_agent_db;
+use super::data_root::anycode_dir;
+
+/// 图片 / PDF 的大小上限
+const MAX_BINARY_BYTES: u64 = 20 * 1024 * 1024;
+
+/// 文本文件的大小上限
+const MAX_TEXT_BYTES: u64 = 2 * 1024 * 1024;
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+/// 附件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    Image,
+    Pdf,
+    Text,
+}
+
+/// 已暂存的附件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedAttachment {
+    pub id: String,
+    pub file_name: String,
+    /// 暂存副本的路径（传给 CLI 的路径）
+    pub path: String,
+    /// 用户选择的原始路径
+    pub original_path: String,
+    pub kind: AttachmentKind,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+fn get_attachments_dir() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("attachments"))
+}
+
+/// 按扩展名和内容判断附件类型，不支持的文件返回错误
+fn detect_kind(path: &Path) -> Result<AttachmentKind, String> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Ok(AttachmentKind::Image);
+    }
+    if extension == "pdf" {
+        return Ok(AttachmentKind::Pdf);
+    }
+
+    // 其余文件只接受文本：检查开头 8KB 是否为 UTF-8 且不含 NUL
+    let mut head = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(8192).read_to_end(&mut head))
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    let valid_prefix = match std::str::from_utf8(&head) {
+        Ok(_) => true,
+        // 截断在多字节字符中间
+        Err(e) => e.error_len().is_none(),
+    };
+    if !valid_prefix || head.contains(&0) {
+        return Err(format!("不支持的附件类型: {}", path.display()));
+    }
+    Ok(AttachmentKind::Text)
+}
+
+fn max_size(kind: AttachmentKind) -> u64 {
+    match kind {
+        AttachmentKind::Text => MAX_TEXT_BYTES,
+        AttachmentKind::Image | AttachmentKind::Pdf => MAX_BINARY_BYTES,
+    }
+}
+
+/// 只保留适合作为文件名的字符（`@` 引用中的空格等会被 CLI 截断）
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    match sanitized.trim_matches('.') {
+        "" => "attachment".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// 校验并复制文件到 `staging_dir`
+fn stage_file(staging_dir: &Path, source: &Path) -> Result<StagedAttachment, String> {
+    let metadata = fs::metadata(source).map_err(|e| format!("无法读取附件 {}: {}", source.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("附件不是文件: {}", source.display()));
+    }
+    let kind = detect_kind(source)?;
+    if metadata.len() > max_size(kind) {
+        return Err(format!(
+            "附件过大: {} ({} KB，上限 {} KB)",
+            source.display(),
+            metadata.len() / 1024,
+            max_size(kind) / 1024
+        ));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let file_name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+    fs::create_dir_all(staging_dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+    let target = staging_dir.join(format!("{}_{}", &id[..8], sanitize_file_name(&file_name)));
+    fs::copy(source, &target).map_err(|e| format!("复制附件失败: {}", e))?;
+
+    Ok(StagedAttachment {
+        id,
+        file_name,
+        path: target.to_string_lossy().to_string(),
+        original_path: source.to_string_lossy().to_string(),
+        kind,
+        size_bytes: metadata.len(),
+        created_at: Utc::now().to_rfc3339(),
+    })
+}
+
+/// 过滤掉暂存文件已不存在的附件
+fn existing(attachments: &[StagedAttachment]) -> impl Iterator<Item = &StagedAttachment> {
+    attachments.iter().filter(|attachment| {
+        let exists = Path::new(&attachment.path).is_file();
+        if !exists {
+            log::warn!("[Attachments] Staged file missing, skipped: {}", attachment.path);
+        }
+        exists
+    })
+}
+
+/// 以 `@<path>` 引用附件（Claude / Gemini）
+pub fn mention_attachments(prompt: &str, attachments: &[StagedAttachment]) -> String {
+    let mentions: Vec<String> = existing(attachments).map(|a| format!("@{}", a.path)).collect();
+    if mentions.is_empty() {
+        return prompt.to_string();
+    }
+    format!("{}\n\n{}", prompt, mentions.join("\n"))
+}
+
+/// Codex 的附件参数，返回 (提示词, 额外参数)
+///
+/// `image_flags` 为 false（例如 `codex exec resume` 不接受新选项）时图片也写进提示词。
+pub fn codex_attachments(
+    prompt: &str,
+    attachments: &[StagedAttachment],
+    image_flags: bool,
+) -> (String, Vec<String>) {
+    let mut args = Vec::new();
+    let mut listed = Vec::new();
+    for attachment in existing(attachments) {
+        if image_flags && attachment.kind == AttachmentKind::Image {
+            args.push("--image".to_string());
+            args.push(attachment.path.clone());
+        } else {
+            listed.push(format!("- {}", attachment.path));
+        }
+    }
+    let prompt = if listed.is_empty() {
+        prompt.to_string()
+    } else {
+        format!("{}\n\nAttached files:\n{}", prompt, listed.join("\n"))
+    };
+    (prompt, args)
+}
+
+/// 附件所在的暂存目录（去重，供 Gemini `--include-directories` 使用）
+pub fn attachment_dirs(attachments: &[StagedAttachment]) -> Vec<String> {
+    let mut dirs: Vec<String> = Vec::new();
+    for attachment in attachments {
+        if let Some(parent) = Path::new(&attachment.path).parent() {
+            let dir = parent.to_string_lossy().to_string();
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+/// 创建会话附件表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_attachments (
+            engine TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            attachment_id TEXT NOT NULL,
+            attachment TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (engine, session_id, attachment_id)
+        );",
+    )
+    .map_err(|e| format!("创建会话附件表失败: {}", e))
+}
+
+fn save_attachments(
+    conn: &Connection,
+    engine: &str,
+    session_id: &str,
+    attachments: &[StagedAttachment],
+) -> Result<(), String> {
+    for attachment in attachments {
+        let json = serde_json::to_string(attachment).map_err(|e| format!("序列化附件失败: {}", e))?;
+        // 超时重试会再次记录同一批附件
+        conn.execute(
+            "INSERT OR IGNORE INTO session_attachments (engine, session_id, attachment_id, attachment, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![engine, session_id, attachment.id, json, attachment.created_at],
+        )
+        .map_err(|e| format!("保存会话附件失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 读取某个引擎的全部会话附件（按暂存时间排序）
+pub fn load_engine_attachments(
+    conn: &Connection,
+    engine: &str,
+) -> Result<HashMap<String, Vec<StagedAttachment>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT session_id, attachment FROM session_attachments WHERE engine = ?1 ORDER BY created_at")
+        .map_err(|e| format!("查询会话附件失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![engine], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("查询会话附件失败: {}", e))?
+        .filter_map(|row| row.ok());
+
+    let mut attachments: HashMap<String, Vec<StagedAttachment>> = HashMap::new();
+    for (session_id, json) in rows {
+        if let Ok(attachment) = serde_json::from_str(&json) {
+            attachments.entry(session_id).or_default().push(attachment);
+        }
+    }
+    Ok(attachments)
+}
+
+/// 会话开始后记录本次使用的附件；失败只记录日志，不影响执行
+pub fn record_attachments(engine: &str, session_id: &str, attachments: &[StagedAttachment]) {
+    if attachments.is_empty() || session_id.is_empty() {
+        return;
+    }
+    let (engine, session_id, attachments) = (engine.to_string(), session_id.to_string(), attachments.to_vec());
+    tokio::task::spawn_blocking(move || {
+        let result = open_agent_db().and_then(|conn| {
+            ensure_schema(&conn)?;
+            save_attachments(&conn, &engine, &session_id, &attachments)
+        });
+        match result {
+            Ok(()) => log::info!(
+                "[Attachments] Recorded {} attachment(s) for {} session {}",
+                attachments.len(),
+                engine,
+                session_id
+            ),
+            Err(e) => log::warn!("[Attachments] Failed to record attachments for {}: {}", session_id, e),
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 暂存附件（校验类型与大小后复制到附件目录）
+///
+/// `session_id` 可以是尚未开始的会话的临时 ID，只用于划分暂存目录。
+#[tauri::command]
+pub async fn stage_attachment(session_id: String, path: String) -> Result<StagedAttachment, String> {
+    let session_dir = sanitize_file_name(session_id.trim());
+    let staging_dir = get_attachments_dir()?.join(session_dir);
+    let attachment = tokio::task::spawn_blocking(move || stage_file(&staging_dir, Path::new(&path)))
+        .await
+        .map_err(|e| format!("暂存附件失败: {}", e))??;
+    log::info!(
+        "[Attachments] Staged {} ({:?}, {} bytes) for {}",
+        attachment.file_name,
+        attachment.kind,
+        attachment.size_bytes,
+        session_id
+    );
+    Ok(attachment)
+}
+
+/// 删除暂存目录中的文件；两侧都规范化后比较，`..` 和符号链接无法逃出暂存目录
+fn remove_staged_file(root: &Path, path: &Path) -> Result<(), String> {
+    let not_staged = || format!("不是暂存的附件: {}", path.display());
+    let root = fs::canonicalize(root).map_err(|_| not_staged())?;
+    let path = fs::canonicalize(path).map_err(|_| not_staged())?;
+    if !path.starts_with(&root) || !path.is_file() {
+        return Err(not_staged());
+    }
+    fs::remove_file(&path).map_err(|e| format!("删除附件失败: {}", e))
+}
+
+/// 删除尚未使用的暂存附件
+#[tauri::command]
+pub async fn remove_staged_attachment(path: String) -> Result<(), String> {
+    let root = get_attachments_dir()?;
+    tokio::task::spawn_blocking(move || remove_staged_file(&root, Path::new(&path)))
+        .await
+        .map_err(|e| format!("删除附件失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_and_translates_attachments() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("my notes.md");
+        fs::write(&source, "# notes").unwrap();
+        let image = dir.path().join("shot.PNG");
+        fs::write(&image, [0x89, b'P', b'N', b'G']).unwrap();
+        let binary = dir.path().join("blob.bin");
+        fs::write(&binary, [0u8, 1, 2]).unwrap();
+
+        let staging = dir.path().join("staged");
+        let text = stage_file(&staging, &source).unwrap();
+        assert_eq!(text.kind, AttachmentKind::Text);
+        assert!(text.path.ends_with("_my_notes.md"));
+        let image = stage_file(&staging, &image).unwrap();
+        assert_eq!(image.kind, AttachmentKind::Image);
+        assert!(stage_file(&staging, &binary).unwrap_err().contains("不支持"));
+
+        let attachments = vec![text.clone(), image.clone()];
+        assert_eq!(
+            mention_attachments("look", &attachments),
+            format!("look\n\n@{}\n@{}", text.path, image.path)
+        );
+
+        let (prompt, args) = codex_attachments("look", &attachments, true);
+        assert_eq!(prompt, format!("look\n\nAttached files:\n- {}", text.path));
+        assert_eq!(args, vec!["--image".to_string(), image.path.clone()]);
+        let (prompt, args) = codex_attachments("look", &attachments, false);
+        assert!(prompt.ends_with(&format!("- {}", image.path)) && args.is_empty());
+
+        assert_eq!(attachment_dirs(&attachments), vec![staging.to_string_lossy().to_string()]);
+
+        // 只能删除暂存目录中的文件
+        let escaped = staging.join("..").join("my notes.md");
+        assert!(remove_staged_file(&staging, &escaped).unwrap_err().contains("不是暂存的附件"));
+        assert!(source.exists());
+        assert!(remove_staged_file(&staging, &staging).is_err());
+
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        save_attachments(&conn, "claude", "s1", &attachments).unwrap();
+        save_attachments(&conn, "claude", "s1", &attachments).unwrap();
+        assert_eq!(load_engine_attachments(&conn, "claude").unwrap()["s1"].len(), 2);
+    }
+}
@@ -0,0 +1,278 @@
+//! 自动修复循环
+//!
+//! 执行选项中带上 `autoFix` 后，引擎执行成功结束时在项目目录中运行验证命令；验证失败则把失败输出
+//! 作为跟进提示词续接同一会话重新执行，直到验证通过或用完迭代次数。整个循环由后端编排
+//! （复用各引擎的重试路径），每个阶段发送 `auto-fix:{session_id}` / `auto-fix` 事件，
+//! 循环期间可通过 `cancel_auto_fix` 取消。
+//!
+//! 未指定验证命令时使用项目设置中的后处理命令（`postProcessors`）。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio::task::AbortHandle;
+
+use super::notifications;
+use super::post_processors::{
+    follow_up_prompt, lock_project, run_processor, PostProcessor, ProcessorKind, ProcessorResult, ProcessorStatus,
+};
+use super::project_settings::load_project_settings;
+
+/// 未指定时最多发送的修复提示词数
+const DEFAULT_MAX_ITERATIONS: u32 = 3;
+
+/// 执行选项中的自动修复设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoFixOptions {
+    /// 验证命令，留空时使用项目的后处理命令
+    #[serde(default)]
+    pub verify_command: Option<String>,
+    /// 最多发送的修复提示词数，留空使用 3
+    #[serde(default)]
+    pub max_iterations: Option<u32>,
+    /// 验证命令的超时时间（秒），留空使用 300 秒
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl AutoFixOptions {
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations.unwrap_or(DEFAULT_MAX_ITERATIONS)
+    }
+
+    /// 本次验证要执行的命令
+    fn verify_processors(&self, project_path: &str) -> Vec<PostProcessor> {
+        match self.verify_command.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            Some(command) => vec![PostProcessor {
+                name: "verify".to_string(),
+                kind: ProcessorKind::Test,
+                command: command.to_string(),
+                timeout_secs: self.timeout_secs,
+            }],
+            None => load_project_settings(project_path)
+                .post_processors
+                .map(|settings| settings.processors)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoFixPhase {
+    /// 正在执行验证命令
+    Verifying,
+    /// 验证通过，循环结束
+    Passed,
+    /// 验证失败，已发送修复提示词
+    Fixing,
+    /// 验证失败且迭代次数已用完
+    Exhausted,
+    /// 用户取消
+    Cancelled,
+}
+
+/// `auto-fix` 事件内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoFixEvent {
+    pub engine: String,
+    pub session_id: String,
+    pub phase: AutoFixPhase,
+    /// 已发送的修复提示词数（验证首次执行时为 0）
+    pub iteration: u32,
+    pub max_iterations: u32,
+    /// 本次验证的结果（`verifying` / `cancelled` 时为空）
+    pub results: Vec<ProcessorResult>,
+}
+
+struct LoopState {
+    engine: String,
+    /// 正在执行的验证任务
+    task: Option<AbortHandle>,
+    cancelled: bool,
+}
+
+/// 进行中的自动修复循环（引擎会话 ID -> 状态）
+static LOOPS: Lazy<Mutex<HashMap<String, LoopState>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn emit_event(app: &AppHandle, event: &AutoFixEvent) {
+    let _ = app.emit(&format!("auto-fix:{}", event.session_id), event);
+    let _ = app.emit("auto-fix", event);
+}
+
+/// 引擎执行成功结束后调用：有验证命令时在后台执行验证，失败时通过 `resubmit` 发送修复提示词
+///
+/// `iteration` 为本次执行之前已发送的修复提示词数。返回是否已接管本次执行的结束通知
+/// （循环结束时统一通知）；未开启自动修复、没有验证命令或循环已取消时返回 false。
+pub fn schedule_auto_fix<F, Fut>(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    project_path: &str,
+    options: &AutoFixOptions,
+    iteration: u32,
+    resubmit: F,
+) -> bool
+where
+    F: FnOnce(String) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    if session_id.is_empty() {
+        return false;
+    }
+    let processors = options.verify_processors(project_path);
+    if processors.is_empty() {
+        log::warn!("[AutoFix] No verify command for {} session {}, skipping", engine, session_id);
+        return false;
+    }
+
+    let mut loops = LOOPS.lock().unwrap();
+    if iteration == 0 {
+        loops.remove(session_id);
+    } else if loops.get(session_id).is_some_and(|state| state.cancelled) {
+        loops.remove(session_id);
+        return false;
+    }
+
+    let max_iterations = options.max_iterations();
+    let app = app.clone();
+    let engine_name = engine.to_string();
+    let engine = engine.to_string();
+    let key = session_id.to_string();
+    let project_path = project_path.to_string();
+    let mut event = AutoFixEvent {
+        engine: engine.clone(),
+        session_id: key.clone(),
+        phase: AutoFixPhase::Verifying,
+        iteration,
+        max_iterations,
+        results: Vec::new(),
+    };
+    emit_event(&app, &event);
+
+    let task = tokio::spawn(async move {
+        log::info!(
+            "[AutoFix] Verifying {} session {} ({}/{})",
+            engine,
+            key,
+            iteration,
+            max_iterations
+        );
+        let mut results = Vec::with_capacity(processors.len());
+        let guard = lock_project(&project_path).await;
+        for processor in &processors {
+            results.push(run_processor(&project_path, processor).await);
+        }
+        drop(guard);
+        let prompt = follow_up_prompt(&results);
+        let passed = results.iter().all(|r| r.status == ProcessorStatus::Passed);
+        event.results = results;
+
+        let cancelled = {
+            let mut loops = LOOPS.lock().unwrap();
+            let cancelled = loops.get(&key).is_some_and(|state| state.cancelled);
+            if cancelled || passed || iteration >= max_iterations {
+                loops.remove(&key);
+            } else if let Some(state) = loops.get_mut(&key) {
+                state.task = None;
+            }
+            cancelled
+        };
+        if cancelled {
+            return;
+        }
+
+        match prompt.filter(|_| iteration < max_iterations) {
+            Some(prompt) => {
+                event.phase = AutoFixPhase::Fixing;
+                event.iteration = iteration + 1;
+                emit_event(&app, &event);
+                log::info!("[AutoFix] Sending fix prompt {}/{} to {} session {}", iteration + 1, max_iterations, engine, key);
+                if let Err(e) = resubmit(prompt).await {
+                    log::error!("[AutoFix] Failed to resubmit {} session {}: {}", engine, key, e);
+                    LOOPS.lock().unwrap().remove(&key);
+                    notifications::notify_run_finished(&app, &engine, &key, false);
+                }
+            }
+            None => {
+                event.phase = if passed { AutoFixPhase::Passed } else { AutoFixPhase::Exhausted };
+                emit_event(&app, &event);
+                log::info!("[AutoFix] {} session {} finished: {:?}", engine, key, event.phase);
+                notifications::notify_run_finished(&app, &engine, &key, passed);
+            }
+        }
+    });
+    loops.insert(
+        session_id.to_string(),
+        LoopState { engine: engine_name, task: Some(task.abort_handle()), cancelled: false },
+    );
+    true
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 取消会话的自动修复循环，返回是否存在进行中的循环
+///
+/// 正在验证时立即终止，并代替被接管的执行发送结束通知（按失败处理）；修复提示词正在执行时，
+/// 该次执行结束后不再验证，由引擎照常发送结束通知。
+#[tauri::command]
+pub async fn cancel_auto_fix(app: AppHandle, session_id: String) -> Result<bool, String> {
+    let state = {
+        let mut loops = LOOPS.lock().unwrap();
+        match loops.get_mut(&session_id) {
+            Some(state) if !state.cancelled => {
+                state.cancelled = true;
+                Some((state.engine.clone(), state.task.take()))
+            }
+            _ => None,
+        }
+    };
+    let Some((engine, task)) = state else {
+        return Ok(false);
+    };
+    if let Some(task) = task {
+        // 验证命令设置了 kill_on_drop，终止任务即结束命令
+        task.abort();
+        LOOPS.lock().unwrap().remove(&session_id);
+        notifications::notify_run_finished(&app, &engine, &session_id, false);
+    }
+    log::info!("[AutoFix] Cancelled auto-fix loop for {} session {}", engine, session_id);
+    emit_event(
+        &app,
+        &AutoFixEvent {
+            engine,
+            session_id,
+            phase: AutoFixPhase::Cancelled,
+            iteration: 0,
+            max_iterations: 0,
+            results: Vec::new(),
+        },
+    );
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_default_and_verify_command() {
+        let options: AutoFixOptions = serde_json::from_str(r#"{"verifyCommand":"  cargo test  "}"#).unwrap();
+        assert_eq!(options.max_iterations(), DEFAULT_MAX_ITERATIONS);
+        let processors = options.verify_processors("/nonexistent");
+        assert_eq!(processors.len(), 1);
+        assert_eq!(processors[0].command, "cargo test");
+        assert_eq!(processors[0].kind, ProcessorKind::Test);
+
+        let options: AutoFixOptions = serde_json::from_str(r#"{"verifyCommand":" ","maxIterations":5}"#).unwrap();
+        assert_eq!(options.max_iterations(), 5);
+        assert!(options.verify_processors("/nonexistent").is_empty());
+    }
+}
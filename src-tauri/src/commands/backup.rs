@@ -0,0 +1,788 @@
+//! 数据备份与恢复
+//!
+//! `create_backup` 把 agents.db、AnyCode 数据目录中的设置与预设、Codex / Gemini 提示词模板
+//! 打包成带时间戳的 zip（默认保存在 ~/.anycode/backups）。
+//!
+//! 代理商配置（providers.json、auth.json 等含 API Key 的文件）只有在显式选择并设置密码时才会备份，
+//! 以 PBKDF2-HMAC-SHA256 派生密钥、AES-256-GCM 加密后存为 `secrets.enc`；此时 agents.db 快照
+//! 也一起放进 `secrets.enc`，不以明文写入 zip（清单中标记为 `encrypted`）。
+//!
+//! 打包、加解密（PBKDF2）和解压都在 `spawn_blocking` 中执行，数据库快照通过连接池的阻塞线程生成。
+//!
+//! 恢复前可用 `preview_restore` 查看每个文件将被新建还是覆盖，`restore_backup` 执行写回。
+//! agents.db 在运行中无法替换，恢复时先写成 agents.db.restore，下次启动时由 `init_database` 换入。
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::num::NonZeroU32;
+use std::path::{Component, Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::data_root::{anycode_dir, app_data_dir};
+use super::storage::with_agent_db;
+
+const BACKUP_FORMAT_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const SECRETS_FILE: &str = "secrets.enc";
+const DATABASE_ENTRY: &str = "database/agents.db";
+const AGENT_DB_FILE: &str = "agents.db";
+/// 待换入的数据库（恢复备份后由下次启动处理）
+const PENDING_DB_FILE: &str = "agents.db.restore";
+
+/// 数据目录中不备份的条目：日志、缓存、运行时状态和备份本身
+const EXCLUDED_ENTRIES: &[&str] = &[
+    "backups",
+    "logs",
+    "trash",
+    "diagnostics",
+    "hook_events",
+    "file_batches",
+    "attachments",
+    "remote",
+    "semantic_index.json",
+    "hook_bridge.json",
+    "instance.json",
+    "data_root.json",
+];
+
+/// 含密钥的文件：(zip 内目录, 文件名)，目录通过 `BackupRoots` 映射到本地路径
+const SECRET_FILES: &[(&str, &str)] = &[
+    ("secrets/claude", "providers.json"),
+    ("secrets/codex", "providers.json"),
+    ("secrets/codex", "auth.json"),
+    ("secrets/anycode", "gemini_providers.json"),
+    ("secrets/anycode", "claude_settings_providers.json"),
+    ("secrets/anycode", "codex_config_providers.json"),
+];
+
+const SECRETS_MAGIC: &[u8] = b"ACBK1";
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 200_000;
+
+/// 备份清单（manifest.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub version: u32,
+    pub created_at: String,
+    pub app_version: String,
+    pub includes_secrets: bool,
+    pub entries: Vec<BackupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupEntry {
+    pub path: String,
+    /// database / settings / prompts / providers
+    pub category: String,
+    pub size: u64,
+    /// 内容保存在加密的 `secrets.enc` 中
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+impl BackupEntry {
+    /// 旧版本的清单没有 `encrypted` 字段，代理商文件总是加密的
+    fn is_encrypted(&self) -> bool {
+        self.encrypted || category_of(&self.path) == "providers"
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResult {
+    pub path: String,
+    pub created_at: String,
+    pub file_count: usize,
+    pub size: u64,
+    pub includes_secrets: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreAction {
+    Create,
+    Overwrite,
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePreviewEntry {
+    pub path: String,
+    pub category: String,
+    pub target: Option<String>,
+    pub action: RestoreAction,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePreview {
+    pub manifest: BackupManifest,
+    pub entries: Vec<RestorePreviewEntry>,
+    pub overwrite_count: usize,
+    /// 备份包含密钥但未提供密码
+    pub requires_passphrase: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub restored: usize,
+    pub skipped: Vec<String>,
+    pub restart_required: bool,
+}
+
+/// zip 内目录前缀与本地目录的对应关系
+struct BackupRoots(Vec<(&'static str, PathBuf)>);
+
+impl BackupRoots {
+    fn detect(app: &AppHandle) -> Result<Self, String> {
+        let anycode = anycode_dir()?;
+        let mut roots = vec![
+            ("database", app_data_dir(app)?),
+            ("anycode", anycode.clone()),
+            ("secrets/anycode", anycode),
+        ];
+        if let Ok(codex_dir) = super::codex::config::get_codex_config_dir() {
+            roots.push(("prompts/codex", codex_dir.join("prompts")));
+            roots.push(("secrets/codex", codex_dir));
+        }
+        if let Ok(gemini_dir) = super::gemini::config::get_gemini_dir() {
+            roots.push(("prompts/gemini", gemini_dir.join("prompts")));
+        }
+        if let Ok(claude_dir) = super::claude::get_claude_dir() {
+            roots.push(("secrets/claude", claude_dir));
+        }
+        Ok(BackupRoots(roots))
+    }
+
+    fn get(&self, prefix: &str) -> Option<&Path> {
+        self.0.iter().find(|(p, _)| *p == prefix).map(|(_, path)| path.as_path())
+    }
+
+    /// zip 内路径对应的本地文件；拒绝 `..`、绝对路径等越界路径
+    fn target_for(&self, archive_path: &str) -> Option<PathBuf> {
+        let (prefix, rest) = self
+            .0
+            .iter()
+            .filter_map(|(prefix, _)| {
+                archive_path
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_prefix('/'))
+                    .map(|rest| (*prefix, rest))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())?;
+        let relative = Path::new(rest);
+        if rest.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return None;
+        }
+        Some(self.get(prefix)?.join(relative))
+    }
+}
+
+fn category_of(archive_path: &str) -> &'static str {
+    match archive_path.split('/').next().unwrap_or_default() {
+        "database" => "database",
+        "prompts" => "prompts",
+        "secrets" => "providers",
+        _ => "settings",
+    }
+}
+
+fn zip_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 收集需要备份的普通文件：(zip 内路径, 本地路径)
+fn collect_files(roots: &BackupRoots) -> Vec<(String, PathBuf)> {
+    let anycode_secrets: Vec<&str> = SECRET_FILES
+        .iter()
+        .filter(|(prefix, _)| *prefix == "secrets/anycode")
+        .map(|(_, name)| *name)
+        .collect();
+
+    let mut files = Vec::new();
+    for prefix in ["anycode", "prompts/codex", "prompts/gemini"] {
+        let Some(root) = roots.get(prefix).filter(|root| root.is_dir()) else {
+            continue;
+        };
+        let walker = walkdir::WalkDir::new(root).follow_links(false).into_iter().filter_entry(|entry| {
+            entry.depth() != 1
+                || prefix != "anycode"
+                || !entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| EXCLUDED_ENTRIES.contains(&name) || anycode_secrets.contains(&name))
+        });
+        for entry in walker.flatten().filter(|e| e.file_type().is_file()) {
+            if let Ok(relative) = entry.path().strip_prefix(root) {
+                files.push((format!("{}/{}", prefix, zip_path(relative)), entry.path().to_path_buf()));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// 读取含密钥的文件：zip 内路径 → 文件内容
+fn collect_secrets(roots: &BackupRoots) -> BTreeMap<String, Vec<u8>> {
+    SECRET_FILES
+        .iter()
+        .filter_map(|(prefix, name)| {
+            let content = fs::read(roots.get(prefix)?.join(name)).ok()?;
+            Some((format!("{}/{}", prefix, name), content))
+        })
+        .collect()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey, String> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    UnboundKey::new(&AES_256_GCM, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "初始化加密密钥失败".to_string())
+}
+
+/// 加密：magic | salt | nonce | 密文+tag
+fn encrypt_secrets(plain: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt).map_err(|_| "生成随机数失败".to_string())?;
+    rng.fill(&mut nonce).map_err(|_| "生成随机数失败".to_string())?;
+
+    let mut data = plain.to_vec();
+    derive_key(passphrase, &salt)?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(SECRETS_MAGIC), &mut data)
+        .map_err(|_| "加密密钥文件失败".to_string())?;
+
+    let mut out = Vec::with_capacity(SECRETS_MAGIC.len() + SALT_LEN + NONCE_LEN + data.len());
+    out.extend_from_slice(SECRETS_MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&data);
+    Ok(out)
+}
+
+fn decrypt_secrets(blob: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let header = SECRETS_MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if blob.len() < header || !blob.starts_with(SECRETS_MAGIC) {
+        return Err("无效的密钥数据: 格式不支持".to_string());
+    }
+    let salt = &blob[SECRETS_MAGIC.len()..SECRETS_MAGIC.len() + SALT_LEN];
+    let nonce = Nonce::try_assume_unique_for_key(&blob[SECRETS_MAGIC.len() + SALT_LEN..header])
+        .map_err(|_| "无效的密钥数据".to_string())?;
+
+    let mut data = blob[header..].to_vec();
+    let plain = derive_key(passphrase, salt)?
+        .open_in_place(nonce, Aad::from(SECRETS_MAGIC), &mut data)
+        .map_err(|_| "备份密码错误或密钥数据已损坏".to_string())?;
+    Ok(plain.to_vec())
+}
+
+fn encode_secrets(secrets: &BTreeMap<String, Vec<u8>>, passphrase: &str) -> Result<Vec<u8>, String> {
+    let encoded: BTreeMap<&String, String> = secrets.iter().map(|(path, content)| (path, BASE64.encode(content))).collect();
+    let plain = serde_json::to_vec(&encoded).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+    encrypt_secrets(&plain, passphrase)
+}
+
+fn decode_secrets(blob: &[u8], passphrase: &str) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let plain = decrypt_secrets(blob, passphrase)?;
+    let encoded: BTreeMap<String, String> =
+        serde_json::from_slice(&plain).map_err(|e| format!("解析密钥数据失败: {}", e))?;
+    encoded
+        .into_iter()
+        .map(|(path, content)| {
+            BASE64
+                .decode(content)
+                .map(|bytes| (path, bytes))
+                .map_err(|e| format!("解析密钥数据失败: {}", e))
+        })
+        .collect()
+}
+
+/// 写出备份 zip，返回清单
+fn write_archive(
+    output: &Path,
+    files: &[(String, PathBuf)],
+    secrets: Option<Vec<u8>>,
+    secret_entries: Vec<BackupEntry>,
+    created_at: &str,
+) -> Result<BackupManifest, String> {
+    let file = File::create(output).map_err(|e| format!("创建备份文件失败 {}: {}", output.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+
+    let mut entries = Vec::new();
+    for (name, path) in files {
+        // 备份过程中被删除的文件（如临时会话）直接跳过
+        let Ok(mut source) = File::open(path) else {
+            continue;
+        };
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("写入 zip 失败: {}", e))?;
+        let size = std::io::copy(&mut source, &mut zip).map_err(|e| format!("写入 {} 失败: {}", name, e))?;
+        entries.push(BackupEntry {
+            path: name.clone(),
+            category: category_of(name).to_string(),
+            size,
+            encrypted: false,
+        });
+    }
+
+    let includes_secrets = secrets.is_some();
+    if let Some(blob) = secrets {
+        zip.start_file(SECRETS_FILE, options)
+            .map_err(|e| format!("写入 zip 失败: {}", e))?;
+        zip.write_all(&blob).map_err(|e| format!("写入 {} 失败: {}", SECRETS_FILE, e))?;
+        entries.extend(secret_entries);
+    }
+
+    let manifest = BackupManifest {
+        version: BACKUP_FORMAT_VERSION,
+        created_at: created_at.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        includes_secrets,
+        entries,
+    };
+    zip.start_file(MANIFEST_FILE, options)
+        .map_err(|e| format!("写入 zip 失败: {}", e))?;
+    let content = serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.write_all(&content).map_err(|e| format!("写入 {} 失败: {}", MANIFEST_FILE, e))?;
+    zip.finish().map_err(|e| format!("完成 zip 失败: {}", e))?;
+    Ok(manifest)
+}
+
+/// 待恢复文件的内容来源
+enum Payload {
+    Archive(String),
+    Secret(Vec<u8>),
+}
+
+struct PlannedEntry {
+    preview: RestorePreviewEntry,
+    target: Option<PathBuf>,
+    payload: Payload,
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<File>, String> {
+    let file = File::open(path).map_err(|e| format!("打开备份文件失败 {}: {}", path.display(), e))?;
+    ZipArchive::new(file).map_err(|e| format!("无效的备份文件: {}", e))
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive.by_name(name).map_err(|e| format!("读取备份中的 {} 失败: {}", name, e))?;
+    let mut content = Vec::new();
+    entry
+        .read_to_end(&mut content)
+        .map_err(|e| format!("读取备份中的 {} 失败: {}", name, e))?;
+    Ok(content)
+}
+
+/// 解析备份并确定每个文件的恢复目标
+fn plan_restore(
+    archive: &mut ZipArchive<File>,
+    roots: &BackupRoots,
+    passphrase: Option<&str>,
+) -> Result<(BackupManifest, Vec<PlannedEntry>, bool), String> {
+    let manifest: BackupManifest = serde_json::from_slice(&read_entry(archive, MANIFEST_FILE)?)
+        .map_err(|e| format!("解析备份清单失败: {}", e))?;
+    if manifest.version > BACKUP_FORMAT_VERSION {
+        return Err(format!("不支持的备份版本: {}（请升级 AnyCode）", manifest.version));
+    }
+
+    let mut sources: Vec<(String, Payload)> = Vec::new();
+    for entry in manifest.entries.iter().filter(|e| !e.is_encrypted()) {
+        sources.push((entry.path.clone(), Payload::Archive(entry.path.clone())));
+    }
+
+    let mut requires_passphrase = false;
+    let mut locked_secrets = Vec::new();
+    if manifest.includes_secrets {
+        match passphrase.filter(|p| !p.is_empty()) {
+            Some(passphrase) => {
+                let secrets = decode_secrets(&read_entry(archive, SECRETS_FILE)?, passphrase)?;
+                sources.extend(secrets.into_iter().map(|(path, content)| (path, Payload::Secret(content))));
+            }
+            None => {
+                requires_passphrase = true;
+                locked_secrets.extend(
+                    manifest
+                        .entries
+                        .iter()
+                        .filter(|e| e.is_encrypted())
+                        .map(|e| e.path.clone()),
+                );
+            }
+        }
+    }
+
+    let mut planned = Vec::new();
+    for (path, payload) in sources {
+        let target = roots.target_for(&path);
+        let (action, reason) = match &target {
+            Some(target) if target.exists() => (RestoreAction::Overwrite, None),
+            Some(_) => (RestoreAction::Create, None),
+            None => (RestoreAction::Skip, Some("无法确定恢复位置".to_string())),
+        };
+        planned.push(PlannedEntry {
+            preview: RestorePreviewEntry {
+                category: category_of(&path).to_string(),
+                target: target.as_ref().map(|t| t.to_string_lossy().to_string()),
+                path,
+                action,
+                reason,
+            },
+            target,
+            payload,
+        });
+    }
+    for path in locked_secrets {
+        planned.push(PlannedEntry {
+            preview: RestorePreviewEntry {
+                category: category_of(&path).to_string(),
+                target: roots.target_for(&path).map(|t| t.to_string_lossy().to_string()),
+                path: path.clone(),
+                action: RestoreAction::Skip,
+                reason: Some("需要备份密码".to_string()),
+            },
+            target: None,
+            payload: Payload::Archive(path),
+        });
+    }
+    Ok((manifest, planned, requires_passphrase))
+}
+
+fn build_preview(manifest: BackupManifest, planned: &[PlannedEntry], requires_passphrase: bool) -> RestorePreview {
+    let entries: Vec<RestorePreviewEntry> = planned.iter().map(|p| p.preview.clone()).collect();
+    RestorePreview {
+        overwrite_count: entries.iter().filter(|e| e.action == RestoreAction::Overwrite).count(),
+        manifest,
+        entries,
+        requires_passphrase,
+    }
+}
+
+/// 写回文件；数据库写到待换入文件
+fn apply_restore(archive: &mut ZipArchive<File>, planned: Vec<PlannedEntry>) -> Result<RestoreReport, String> {
+    let mut report = RestoreReport {
+        restored: 0,
+        skipped: Vec::new(),
+        restart_required: false,
+    };
+    for entry in planned {
+        let Some(mut target) = entry.target.filter(|_| entry.preview.action != RestoreAction::Skip) else {
+            report.skipped.push(entry.preview.path);
+            continue;
+        };
+        if entry.preview.path == DATABASE_ENTRY {
+            target.set_file_name(PENDING_DB_FILE);
+            report.restart_required = true;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败 {}: {}", parent.display(), e))?;
+        }
+        let mut output = File::create(&target).map_err(|e| format!("写入文件失败 {}: {}", target.display(), e))?;
+        match entry.payload {
+            Payload::Archive(name) => {
+                let mut source = archive
+                    .by_name(&name)
+                    .map_err(|e| format!("读取备份中的 {} 失败: {}", name, e))?;
+                std::io::copy(&mut source, &mut output)
+                    .map_err(|e| format!("写入文件失败 {}: {}", target.display(), e))?;
+            }
+            Payload::Secret(content) => {
+                output
+                    .write_all(&content)
+                    .map_err(|e| format!("写入文件失败 {}: {}", target.display(), e))?;
+            }
+        }
+        report.restored += 1;
+    }
+    Ok(report)
+}
+
+/// 启动时换入恢复的数据库（在打开 agents.db 之前调用）
+pub fn apply_pending_database(app_dir: &Path) {
+    let pending = app_dir.join(PENDING_DB_FILE);
+    if !pending.exists() {
+        return;
+    }
+    let db_path = app_dir.join(AGENT_DB_FILE);
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(app_dir.join(format!("{}{}", AGENT_DB_FILE, suffix)));
+    }
+    match fs::rename(&pending, &db_path) {
+        Ok(()) => log::info!("[Backup] Restored agents.db from backup"),
+        Err(e) => log::error!("[Backup] Failed to apply restored agents.db: {}", e),
+    }
+}
+
+/// 打包备份；提供密码时密钥文件和数据库快照加密存入 `secrets.enc`
+fn build_backup(
+    output: &Path,
+    roots: &BackupRoots,
+    database: &Path,
+    passphrase: Option<&str>,
+    created_at: &str,
+) -> Result<BackupManifest, String> {
+    let mut files = collect_files(roots);
+    let (secrets, secret_entries) = match passphrase {
+        Some(passphrase) => {
+            let mut secrets = collect_secrets(roots);
+            let snapshot = fs::read(database).map_err(|e| format!("读取数据库快照失败: {}", e))?;
+            secrets.insert(DATABASE_ENTRY.to_string(), snapshot);
+            let entries = secrets
+                .iter()
+                .map(|(path, content)| BackupEntry {
+                    path: path.clone(),
+                    category: category_of(path).to_string(),
+                    size: content.len() as u64,
+                    encrypted: true,
+                })
+                .collect();
+            (Some(encode_secrets(&secrets, passphrase)?), entries)
+        }
+        None => {
+            files.insert(0, (DATABASE_ENTRY.to_string(), database.to_path_buf()));
+            (None, Vec::new())
+        }
+    };
+    write_archive(output, &files, secrets, secret_entries, created_at)
+}
+
+/// 创建备份（include_secrets 为 true 时必须提供 passphrase）
+#[tauri::command]
+pub async fn create_backup(
+    app: AppHandle,
+    include_secrets: bool,
+    passphrase: Option<String>,
+    output_path: Option<String>,
+) -> Result<BackupResult, String> {
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    if include_secrets && passphrase.is_none() {
+        return Err("备份代理商密钥时必须设置密码".to_string());
+    }
+
+    let now = chrono::Local::now();
+    let output = match output_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => anycode_dir()?
+            .join("backups")
+            .join(format!("anycode-backup-{}.zip", now.format("%Y%m%d-%H%M%S"))),
+    };
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败 {}: {}", parent.display(), e))?;
+    }
+
+    let roots = BackupRoots::detect(&app)?;
+
+    // agents.db 正在使用，用 VACUUM INTO 生成一致的快照
+    let snapshot = std::env::temp_dir().join(format!("anycode-backup-{}.db", uuid::Uuid::new_v4()));
+    let snapshot_path = snapshot.to_string_lossy().to_string();
+    with_agent_db(move |conn| {
+        conn.execute("VACUUM INTO ?1", [snapshot_path])
+            .map_err(|e| format!("导出数据库失败: {}", e))
+    })
+    .await?;
+
+    let passphrase = passphrase.filter(|_| include_secrets);
+    let created_at = now.to_rfc3339();
+    let archive = output.clone();
+    let manifest = tokio::task::spawn_blocking(move || {
+        let result = build_backup(&archive, &roots, &snapshot, passphrase.as_deref(), &created_at);
+        let _ = fs::remove_file(&snapshot);
+        result
+    })
+    .await
+    .map_err(|e| format!("创建备份失败: {}", e))??;
+
+    let size = fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+    log::info!(
+        "[Backup] Created {} ({} files, {} bytes, secrets: {})",
+        output.display(),
+        manifest.entries.len(),
+        size,
+        manifest.includes_secrets
+    );
+    Ok(BackupResult {
+        path: output.to_string_lossy().to_string(),
+        created_at: manifest.created_at,
+        file_count: manifest.entries.len(),
+        size,
+        includes_secrets: manifest.includes_secrets,
+    })
+}
+
+/// 预览恢复：列出每个文件的目标位置以及是否会覆盖现有文件
+#[tauri::command]
+pub async fn preview_restore(
+    app: AppHandle,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<RestorePreview, String> {
+    let roots = BackupRoots::detect(&app)?;
+    tokio::task::spawn_blocking(move || {
+        let mut archive = open_archive(Path::new(&path))?;
+        let (manifest, planned, requires_passphrase) = plan_restore(&mut archive, &roots, passphrase.as_deref())?;
+        Ok(build_preview(manifest, &planned, requires_passphrase))
+    })
+    .await
+    .map_err(|e| format!("读取备份失败: {}", e))?
+}
+
+/// 从备份恢复；数据库在重启后生效
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<RestoreReport, String> {
+    let roots = BackupRoots::detect(&app)?;
+    let archive_path = path.clone();
+    let report = tokio::task::spawn_blocking(move || {
+        let mut archive = open_archive(Path::new(&archive_path))?;
+        let (_, planned, _) = plan_restore(&mut archive, &roots, passphrase.as_deref())?;
+        apply_restore(&mut archive, planned)
+    })
+    .await
+    .map_err(|e| format!("恢复备份失败: {}", e))??;
+    log::info!(
+        "[Backup] Restored {} files from {} ({} skipped)",
+        report.restored,
+        path,
+        report.skipped.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_archives_and_encrypted_secrets() {
+        let source = tempfile::tempdir().unwrap();
+        let restore = tempfile::tempdir().unwrap();
+        let roots_in = |base: &Path| {
+            BackupRoots(vec![
+                ("database", base.join("app")),
+                ("anycode", base.join("anycode")),
+                ("secrets/anycode", base.join("anycode")),
+                ("prompts/codex", base.join("codex/prompts")),
+                ("secrets/codex", base.join("codex")),
+            ])
+        };
+        let roots = roots_in(source.path());
+        let write = |path: PathBuf, content: &str| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(source.path().join("app/agents.db"), "db");
+        write(source.path().join("anycode/hotkeys.json"), "{}");
+        write(source.path().join("anycode/logs/run.log"), "log");
+        write(source.path().join("anycode/gemini_providers.json"), "gemini-key");
+        write(source.path().join("codex/prompts/review.md"), "review");
+        write(source.path().join("codex/auth.json"), "codex-key");
+
+        let mut files = collect_files(&roots);
+        assert_eq!(
+            files.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+            ["anycode/hotkeys.json", "prompts/codex/review.md"]
+        );
+        files.insert(0, (DATABASE_ENTRY.to_string(), source.path().join("app/agents.db")));
+        let secrets = collect_secrets(&roots);
+        assert_eq!(secrets.len(), 2);
+
+        let archive_path = source.path().join("backup.zip");
+        let manifest =
+            write_archive(&archive_path, &files, Some(encode_secrets(&secrets, "pw").unwrap()), Vec::new(), "now")
+                .unwrap();
+        assert!(manifest.includes_secrets);
+        assert!(decode_secrets(&encrypt_secrets(b"x", "pw").unwrap(), "wrong").is_err());
+
+        // 目标目录已有 hotkeys.json → 覆盖；没有密码时密钥文件不恢复
+        let target_roots = roots_in(restore.path());
+        write(restore.path().join("anycode/hotkeys.json"), "old");
+        let mut archive = open_archive(&archive_path).unwrap();
+        let (manifest, planned, _) = plan_restore(&mut archive, &target_roots, None).unwrap();
+        let preview = build_preview(manifest, &planned, false);
+        assert_eq!(preview.overwrite_count, 1);
+        assert_eq!(preview.entries.len(), 3);
+
+        let (_, planned, requires_passphrase) = plan_restore(&mut archive, &target_roots, Some("pw")).unwrap();
+        assert!(!requires_passphrase);
+        let report = apply_restore(&mut archive, planned).unwrap();
+        assert_eq!(report.restored, 5);
+        assert!(report.restart_required);
+        assert_eq!(fs::read_to_string(restore.path().join("anycode/hotkeys.json")).unwrap(), "{}");
+        assert_eq!(fs::read_to_string(restore.path().join("codex/auth.json")).unwrap(), "codex-key");
+        assert_eq!(fs::read_to_string(restore.path().join("app").join(PENDING_DB_FILE)).unwrap(), "db");
+
+        apply_pending_database(&restore.path().join("app"));
+        assert!(restore.path().join("app/agents.db").exists());
+        assert!(target_roots.target_for("anycode/../escape.json").is_none());
+        assert!(plan_restore(&mut archive, &target_roots, Some("wrong")).is_err());
+    }
+
+    #[test]
+    fn encrypted_backups_keep_the_database_out_of_the_plain_archive() {
+        let source = tempfile::tempdir().unwrap();
+        let restore = tempfile::tempdir().unwrap();
+        let roots_in = |base: &Path| {
+            BackupRoots(vec![
+                ("database", base.join("app")),
+                ("anycode", base.join("anycode")),
+                ("secrets/anycode", base.join("anycode")),
+            ])
+        };
+        fs::create_dir_all(source.path().join("anycode")).unwrap();
+        fs::write(source.path().join("anycode/hotkeys.json"), "{}").unwrap();
+        fs::write(source.path().join("anycode/gemini_providers.json"), "gemini-key").unwrap();
+        let snapshot = source.path().join("snapshot.db");
+        fs::write(&snapshot, "db").unwrap();
+
+        let archive_path = source.path().join("backup.zip");
+        let manifest =
+            build_backup(&archive_path, &roots_in(source.path()), &snapshot, Some("pw"), "now").unwrap();
+        let database = manifest.entries.iter().find(|e| e.path == DATABASE_ENTRY).unwrap();
+        assert!(database.encrypted);
+
+        let mut archive = open_archive(&archive_path).unwrap();
+        assert!(archive.by_name(DATABASE_ENTRY).is_err());
+        let target_roots = roots_in(restore.path());
+        let (_, planned, requires_passphrase) = plan_restore(&mut archive, &target_roots, None).unwrap();
+        assert!(requires_passphrase);
+        let database = planned.iter().find(|p| p.preview.path == DATABASE_ENTRY).unwrap();
+        assert_eq!(database.preview.action, RestoreAction::Skip);
+
+        let (_, planned, _) = plan_restore(&mut archive, &target_roots, Some("pw")).unwrap();
+        let report = apply_restore(&mut archive, planned).unwrap();
+        assert_eq!(report.restored, 3);
+        assert!(report.restart_required);
+        assert_eq!(fs::read_to_string(restore.path().join("app").join(PENDING_DB_FILE)).unwrap(), "db");
+    }
+}
@@ -0,0 +1,320 @@
+//! 会话变更与其他分支的对比
+//!
+//! 根据会话的提示词 Git 记录确定会话起点和改动过的文件，把这些文件的当前内容（含未提交的修改）
+//! 与目标分支（如 `main`）对比，生成 diff，并逐个文件以两者的 merge-base 为基准做三方合并，
+//! 标出合并时会冲突的文件。用于创建 PR 之前确认会话的改动能否干净地合入目标分支。
+//!
+//! 只读操作：工作区快照写入临时索引，不改动暂存区和工作区。
+
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+use super::claude::encode_project_path;
+use super::codex::git_ops::load_codex_git_records;
+use super::gemini::git_ops::load_gemini_git_records;
+use super::prompt_tracker;
+use super::simple_git::{self, run_git, GitError};
+
+/// 文件合入目标分支时的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStatus {
+    /// 与目标分支的内容相同
+    Identical,
+    /// 可以自动合并
+    Clean,
+    /// 两边都修改过且无法自动合并（包括修改/删除冲突）
+    Conflict,
+}
+
+/// 会话改动过的一个文件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchCompareFile {
+    /// 项目相对路径
+    pub path: String,
+    pub status: MergeStatus,
+    /// 目标分支自 merge-base 以来也修改过该文件
+    pub changed_on_branch: bool,
+    /// 相对目标分支新增 / 删除的行数（二进制文件为 0）
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+/// `compare_session_with_branch` 的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionBranchComparison {
+    pub session_id: String,
+    pub branch: String,
+    /// 会话第一条提示词之前的提交
+    pub session_base: String,
+    /// 目标分支与 HEAD 的 merge-base
+    pub merge_base: String,
+    /// 会话净改动过的文件，冲突的文件在前
+    pub files: Vec<BranchCompareFile>,
+    pub conflicts: usize,
+    /// 目标分支 -> 会话当前内容的 unified diff（仅限 `files`）
+    pub diff: String,
+}
+
+/// 会话的起点与各提示词改动过的文件
+#[derive(Debug, Clone, Default)]
+struct SessionSpan {
+    base: String,
+    files: BTreeSet<String>,
+}
+
+/// 按提示词顺序排列的 (执行前, 执行后) 提交或树
+fn session_records(project_path: &str, engine: &str, session_id: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    let records: Vec<(String, Option<String>)> = match engine {
+        "claude" => {
+            let records = prompt_tracker::load_git_records(session_id, &encode_project_path(project_path))
+                .map_err(|e| format!("读取会话 Git 记录失败: {}", e))?;
+            let mut records: Vec<_> = records.into_iter().collect();
+            records.sort_by_key(|(index, _)| *index);
+            records.into_iter().map(|(_, r)| (r.commit_before, r.commit_after)).collect()
+        }
+        "codex" => {
+            let mut records = load_codex_git_records(session_id)?.records;
+            records.sort_by_key(|r| r.prompt_index);
+            records
+                .into_iter()
+                // 工作区树不含提示词之前就未提交的文件，比提交边界更准确
+                .map(|r| match (r.tree_before, r.tree_after) {
+                    (Some(before), Some(after)) => (before, Some(after)),
+                    _ => (r.commit_before, r.commit_after),
+                })
+                .collect()
+        }
+        "gemini" => {
+            let mut records = load_gemini_git_records(session_id)?.records;
+            records.sort_by_key(|r| r.prompt_index);
+            records.into_iter().map(|r| (r.commit_before, r.commit_after)).collect()
+        }
+        other => return Err(format!("不支持的引擎: {}", other)),
+    };
+    Ok(records.into_iter().filter(|(before, _)| !before.is_empty()).collect())
+}
+
+/// 根据提示词记录计算会话起点和改动过的文件；`worktree` 为当前工作区的树
+///
+/// 最后一条提示词还没有执行后的记录（仍在执行或未自动提交）时，以当前工作区作为终点。
+fn session_span(project_path: &str, records: &[(String, Option<String>)], worktree: &str) -> Result<SessionSpan, String> {
+    let Some((base, _)) = records.first() else {
+        return Err("会话没有 Git 记录，无法确定会话的改动".to_string());
+    };
+    let mut span = SessionSpan { base: base.clone(), files: BTreeSet::new() };
+    for (i, (before, after)) in records.iter().enumerate() {
+        let after = match after {
+            Some(after) => after.as_str(),
+            None if i + 1 == records.len() => worktree,
+            None => continue,
+        };
+        // 回滚后记录中的提交可能已不存在
+        match simple_git::git_changed_paths(project_path, before, after) {
+            Ok(paths) => span.files.extend(paths),
+            Err(e) => log::warn!("[BranchCompare] Skipping prompt #{} ({}..{}): {}", i, before, after, e),
+        }
+    }
+    Ok(span)
+}
+
+/// 文件在某个提交或树中的内容，不存在时返回 None
+fn file_at(project_path: &str, rev: &str, path: &str) -> Result<Option<String>, String> {
+    match run_git(project_path, &["show", &format!("{}:./{}", rev, path)]) {
+        Ok(content) => Ok(Some(content)),
+        Err(GitError::CommandFailed { .. }) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// 以 `base` 为基准，把会话内容 `ours` 与目标分支内容 `theirs` 做三方合并
+fn merge_status(base: Option<&str>, ours: Option<&str>, theirs: Option<&str>) -> Result<MergeStatus, String> {
+    if ours == theirs {
+        return Ok(MergeStatus::Identical);
+    }
+    if theirs == base || ours == base {
+        return Ok(MergeStatus::Clean);
+    }
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) if !ours.contains('\0') && !theirs.contains('\0') => {
+            let merged = simple_git::git_merge_file(ours, base.unwrap_or_default(), theirs)?;
+            Ok(if merged.is_some() { MergeStatus::Clean } else { MergeStatus::Conflict })
+        }
+        // 修改/删除，或两边都修改过的二进制文件
+        _ => Ok(MergeStatus::Conflict),
+    }
+}
+
+/// 解析 `git diff --numstat` 的输出：路径 -> (新增, 删除)
+fn parse_numstat(output: &str) -> HashMap<String, (usize, usize)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let added = parts.next()?.parse().unwrap_or(0);
+            let removed = parts.next()?.parse().unwrap_or(0);
+            Some((parts.next()?.to_string(), (added, removed)))
+        })
+        .collect()
+}
+
+/// 把 `span` 中的文件在工作区树 `worktree` 中的内容与 `branch` 对比
+fn compare_span(
+    project_path: &str,
+    session_id: &str,
+    span: &SessionSpan,
+    branch: &str,
+    worktree: &str,
+) -> Result<SessionBranchComparison, String> {
+    run_git(project_path, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", branch)])
+        .map_err(|_| GitError::BranchNotFound(branch.to_string()))?;
+    let merge_base = run_git(project_path, &["merge-base", branch, "HEAD"])
+        .map_err(|e| format!("无法确定 {} 与当前分支的共同祖先: {}", branch, e))?
+        .trim()
+        .to_string();
+
+    // 会话中改过又改回去的文件不算净改动
+    let net: BTreeSet<String> = simple_git::git_changed_paths(project_path, &span.base, worktree)?
+        .into_iter()
+        .collect();
+    let paths: Vec<&String> = span.files.iter().filter(|path| net.contains(*path)).collect();
+
+    let mut files = Vec::with_capacity(paths.len());
+    let mut diff = String::new();
+    if !paths.is_empty() {
+        let mut args = vec!["diff", "--no-renames", "--relative", branch, worktree, "--"];
+        args.extend(paths.iter().map(|p| p.as_str()));
+        diff = run_git(project_path, &args)?;
+        args.insert(1, "--numstat");
+        let stats = parse_numstat(&run_git(project_path, &args)?);
+
+        for path in paths {
+            let base = file_at(project_path, &merge_base, path)?;
+            let ours = file_at(project_path, worktree, path)?;
+            let theirs = file_at(project_path, branch, path)?;
+            let (lines_added, lines_removed) = stats.get(path).copied().unwrap_or_default();
+            files.push(BranchCompareFile {
+                path: path.clone(),
+                status: merge_status(base.as_deref(), ours.as_deref(), theirs.as_deref())?,
+                changed_on_branch: theirs != base,
+                lines_added,
+                lines_removed,
+            });
+        }
+    }
+    files.sort_by_key(|f| f.status != MergeStatus::Conflict);
+    let conflicts = files.iter().filter(|f| f.status == MergeStatus::Conflict).count();
+
+    Ok(SessionBranchComparison {
+        session_id: session_id.to_string(),
+        branch: branch.to_string(),
+        session_base: span.base.clone(),
+        merge_base,
+        files,
+        conflicts,
+        diff,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 对比会话的净改动与目标分支，标出合并时会冲突的文件
+#[tauri::command]
+pub async fn compare_session_with_branch(
+    project_path: String,
+    engine: String,
+    session_id: String,
+    branch: String,
+) -> Result<SessionBranchComparison, String> {
+    tokio::task::spawn_blocking(move || {
+        if !simple_git::is_git_repo(&project_path) {
+            return Err(format!("项目不是 Git 仓库: {}", project_path));
+        }
+        let records = session_records(&project_path, &engine, &session_id)?;
+        let worktree = simple_git::git_worktree_tree(&project_path)?;
+        let span = session_span(&project_path, &records, &worktree)?;
+        log::info!(
+            "[BranchCompare] Comparing {} session {} ({} files) with {}",
+            engine,
+            session_id,
+            span.files.len(),
+            branch
+        );
+        compare_span(&project_path, &session_id, &span, &branch, &worktree)
+    })
+    .await
+    .map_err(|e| format!("对比会话与分支失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        run_git(dir, args).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn finds_conflicts_against_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let write = |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
+        git(dir.path(), &["init", "-q", "-b", "main"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        git(dir.path(), &["config", "user.email", "t@t"]);
+        write("shared.txt", "a\nb\nc\nd\ne\nf\n");
+        write("conflict.txt", "one\n");
+        write("untouched.txt", "x\n");
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-q", "-m", "init"]);
+
+        // main 上的并行修改
+        git(dir.path(), &["checkout", "-q", "-b", "feature"]);
+        git(dir.path(), &["checkout", "-q", "main"]);
+        write("shared.txt", "a\nb\nc\nd\ne\nF\n");
+        write("conflict.txt", "main\n");
+        write("untouched.txt", "y\n");
+        git(dir.path(), &["commit", "-q", "-am", "main work"]);
+        git(dir.path(), &["checkout", "-q", "feature"]);
+
+        // 会话：第一条提示词已提交，第二条仍未提交
+        let start = git(dir.path(), &["rev-parse", "HEAD"]);
+        write("shared.txt", "A\nb\nc\nd\ne\nf\n");
+        write("conflict.txt", "session\n");
+        git(dir.path(), &["commit", "-q", "-am", "prompt 1"]);
+        let after = git(dir.path(), &["rev-parse", "HEAD"]);
+        write("new.txt", "new\n");
+        write("reverted.txt", "tmp\n");
+        std::fs::remove_file(dir.path().join("reverted.txt")).unwrap();
+
+        let worktree = simple_git::git_worktree_tree(path).unwrap();
+        let records = vec![(start.clone(), Some(after.clone())), (after, None)];
+        let span = session_span(path, &records, &worktree).unwrap();
+        assert_eq!(span.base, start);
+
+        let result = compare_span(path, "s", &span, "main", &worktree).unwrap();
+        let files: Vec<_> = result.files.iter().map(|f| (f.path.as_str(), f.status, f.changed_on_branch)).collect();
+        assert_eq!(
+            files,
+            vec![
+                ("conflict.txt", MergeStatus::Conflict, true),
+                ("new.txt", MergeStatus::Clean, false),
+                ("shared.txt", MergeStatus::Clean, true),
+            ]
+        );
+        assert_eq!(result.conflicts, 1);
+        assert_eq!((result.files[1].lines_added, result.files[1].lines_removed), (1, 0));
+        assert!(result.diff.contains("+session") && !result.diff.contains("untouched"));
+        // 未改动暂存区
+        assert!(git(dir.path(), &["diff", "--cached", "--name-only"]).is_empty());
+
+        assert!(compare_span(path, "s", &span, "nope", &worktree).is_err());
+        assert_eq!(merge_status(Some("a"), None, Some("b")).unwrap(), MergeStatus::Conflict);
+        assert_eq!(merge_status(None, Some("x"), Some("x")).unwrap(), MergeStatus::Identical);
+    }
+}
@@ -0,0 +1,325 @@
+//! 引擎能力矩阵
+//!
+//! 前端需要在一个地方知道每个引擎 / 代理商 / 模型支持什么：图片输入、推理强度档位、
+//! 扩展思考、最大上下文和工具调用。静态表提供已知模型的能力，各引擎的探测在其上补充：
+//! Codex 使用 selector 模块的模型与推理模式，Gemini 使用模型列表，三个引擎都会把当前
+//! 代理商配置的自定义模型加入矩阵（能力按模型名推断）。
+//!
+//! 结果在内存中缓存 10 分钟；切换代理商、刷新 Codex 能力时会清除缓存。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::codex::selector::{get_codex_capabilities_internal, get_supported_reasoning_modes_for_model};
+use super::preflight::context_window;
+
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CapabilitySource {
+    /// 内置静态表
+    Static,
+    /// 引擎探测结果
+    Probe,
+    /// 当前代理商配置的模型（能力为推断值）
+    Provider,
+}
+
+/// 单个模型的能力
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCapabilities {
+    pub id: String,
+    pub label: String,
+    /// 支持图片输入
+    pub vision: bool,
+    pub tool_use: bool,
+    /// 可选的推理强度（空表示不可调）
+    pub reasoning_efforts: Vec<String>,
+    /// 支持扩展思考预算（Claude maxThinkingTokens）
+    pub extended_thinking: bool,
+    pub max_context: usize,
+    pub is_default: bool,
+    pub source: CapabilitySource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineCapabilities {
+    pub engine: String,
+    /// 当前代理商的接口地址（官方默认配置时为空）
+    pub provider: Option<String>,
+    pub default_model: String,
+    pub models: Vec<ModelCapabilities>,
+    pub cli_version: Option<String>,
+    /// 探测失败的原因（此时只使用静态表）
+    pub probe_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityMatrix {
+    pub engines: Vec<EngineCapabilities>,
+    pub generated_at: String,
+}
+
+static CACHE: Lazy<Mutex<Option<(Instant, CapabilityMatrix)>>> = Lazy::new(|| Mutex::new(None));
+
+struct StaticModel {
+    engine: &'static str,
+    id: &'static str,
+    label: &'static str,
+    is_default: bool,
+}
+
+/// 已知模型（推理强度、上下文等按模型名推断，与 selector / preflight 保持一致）
+const STATIC_MODELS: &[StaticModel] = &[
+    StaticModel { engine: "claude", id: "sonnet", label: "Claude 4.5 Sonnet", is_default: true },
+    StaticModel { engine: "claude", id: "sonnet1m", label: "Claude 4.5 Sonnet 1M", is_default: false },
+    StaticModel { engine: "claude", id: "opus", label: "Claude 4.5 Opus", is_default: false },
+    StaticModel { engine: "claude", id: "haiku", label: "Claude 4.5 Haiku", is_default: false },
+    StaticModel { engine: "codex", id: "gpt-5.2-codex", label: "GPT-5.2-Codex", is_default: true },
+    StaticModel { engine: "codex", id: "gpt-5.1-codex-max", label: "GPT-5.1-Codex-Max", is_default: false },
+    StaticModel { engine: "codex", id: "gpt-5.1-codex-mini", label: "GPT-5.1-Codex-Mini", is_default: false },
+    StaticModel { engine: "codex", id: "gpt-5.2", label: "GPT-5.2", is_default: false },
+    StaticModel { engine: "gemini", id: "gemini-3-pro-preview", label: "Gemini 3 Pro (Preview)", is_default: true },
+    StaticModel { engine: "gemini", id: "gemini-2.5-pro", label: "Gemini 2.5 Pro", is_default: false },
+    StaticModel { engine: "gemini", id: "gemini-2.5-flash", label: "Gemini 2.5 Flash", is_default: false },
+];
+
+fn is_claude_family(model: &str) -> bool {
+    ["claude", "sonnet", "opus", "haiku"].iter().any(|name| model.contains(name))
+}
+
+/// 按模型名推断能力（用于静态表和代理商的自定义模型）
+fn infer_model(engine: &str, id: &str, label: &str, source: CapabilitySource) -> ModelCapabilities {
+    let model = id.to_lowercase();
+    let is_o_series = model.len() > 1 && model.starts_with('o') && model[1..].starts_with(|c: char| c.is_ascii_digit());
+    let (vision, reasoning_efforts, extended_thinking) = match engine {
+        "codex" => {
+            let known = model.starts_with("gpt-") || is_o_series || model.contains("codex");
+            let legacy = model.starts_with("gpt-3.5") || model == "gpt-4";
+            let efforts = if known && !legacy { get_supported_reasoning_modes_for_model(&model) } else { Vec::new() };
+            (known && !legacy, efforts, false)
+        }
+        "gemini" => (model.starts_with("gemini"), Vec::new(), false),
+        _ => (is_claude_family(&model), Vec::new(), is_claude_family(&model)),
+    };
+    ModelCapabilities {
+        id: id.to_string(),
+        label: label.to_string(),
+        vision,
+        tool_use: true,
+        reasoning_efforts,
+        extended_thinking,
+        max_context: context_window(engine, id),
+        is_default: false,
+        source,
+    }
+}
+
+fn static_models(engine: &str) -> Vec<ModelCapabilities> {
+    STATIC_MODELS
+        .iter()
+        .filter(|m| m.engine == engine)
+        .map(|m| ModelCapabilities {
+            is_default: m.is_default,
+            ..infer_model(engine, m.id, m.label, CapabilitySource::Static)
+        })
+        .collect()
+}
+
+/// 探测结果覆盖静态表中的同名模型，未知模型追加到末尾
+fn merge_model(models: &mut Vec<ModelCapabilities>, probed: ModelCapabilities) {
+    match models.iter_mut().find(|m| m.id == probed.id) {
+        Some(existing) => {
+            existing.label = probed.label;
+            existing.reasoning_efforts = probed.reasoning_efforts;
+            existing.max_context = probed.max_context;
+            existing.source = probed.source;
+        }
+        None => models.push(probed),
+    }
+}
+
+/// 把当前代理商配置的模型加入矩阵并设为默认模型
+fn apply_provider_model(capabilities: &mut EngineCapabilities, model: Option<String>) {
+    let Some(model) = model.map(|m| m.trim().to_string()).filter(|m| !m.is_empty()) else {
+        return;
+    };
+    if !capabilities.models.iter().any(|m| m.id == model) {
+        let engine = capabilities.engine.clone();
+        capabilities.models.push(infer_model(&engine, &model, &model, CapabilitySource::Provider));
+    }
+    for m in &mut capabilities.models {
+        m.is_default = m.id == model;
+    }
+    capabilities.default_model = model;
+}
+
+/// 接口地址的主机名
+fn provider_host(base_url: Option<String>) -> Option<String> {
+    let url = base_url?;
+    let rest = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+    let host = rest.split(['/', '?']).next().unwrap_or_default();
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+fn new_engine(engine: &str) -> EngineCapabilities {
+    let models = static_models(engine);
+    let default_model = models.iter().find(|m| m.is_default).map(|m| m.id.clone()).unwrap_or_default();
+    EngineCapabilities {
+        engine: engine.to_string(),
+        provider: None,
+        default_model,
+        models,
+        cli_version: None,
+        probe_error: None,
+    }
+}
+
+fn probe_claude() -> EngineCapabilities {
+    let mut capabilities = new_engine("claude");
+    match super::provider::get_current_provider_config() {
+        Ok(config) => {
+            capabilities.provider = provider_host(config.anthropic_base_url);
+            apply_provider_model(&mut capabilities, config.anthropic_model);
+        }
+        Err(e) => capabilities.probe_error = Some(e),
+    }
+    capabilities
+}
+
+async fn probe_codex() -> EngineCapabilities {
+    let mut capabilities = new_engine("codex");
+    match get_codex_capabilities_internal().await {
+        Ok(probed) => {
+            for model in probed.models.iter().filter(|m| m.is_available) {
+                merge_model(
+                    &mut capabilities.models,
+                    ModelCapabilities {
+                        reasoning_efforts: model.supported_reasoning_modes.clone(),
+                        ..infer_model("codex", &model.value, &model.label, CapabilitySource::Probe)
+                    },
+                );
+            }
+            capabilities.cli_version = probed.codex_version;
+            apply_provider_model(&mut capabilities, Some(probed.defaults.model));
+        }
+        Err(e) => capabilities.probe_error = Some(e),
+    }
+    if let Ok(config) = super::codex::config::get_current_codex_config().await {
+        capabilities.provider = provider_host(config.base_url);
+        apply_provider_model(&mut capabilities, config.model);
+    }
+    capabilities
+}
+
+async fn probe_gemini() -> EngineCapabilities {
+    let mut capabilities = new_engine("gemini");
+    match super::gemini::get_gemini_models().await {
+        Ok(models) => {
+            for model in models {
+                merge_model(
+                    &mut capabilities.models,
+                    ModelCapabilities {
+                        max_context: model.context_window as usize,
+                        ..infer_model("gemini", &model.id, &model.name, CapabilitySource::Probe)
+                    },
+                );
+            }
+        }
+        Err(e) => capabilities.probe_error = Some(e.to_string()),
+    }
+    if let Ok(config) = super::gemini::get_current_gemini_provider_config().await {
+        capabilities.provider = provider_host(config.base_url);
+        apply_provider_model(&mut capabilities, config.model);
+    }
+    capabilities
+}
+
+/// 清除能力缓存（代理商或模型配置变化后调用）
+pub fn invalidate_capability_cache() {
+    if let Ok(mut cache) = CACHE.lock() {
+        *cache = None;
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 获取各引擎的模型能力矩阵（`refresh` 为 true 时忽略缓存重新探测）
+#[tauri::command]
+pub async fn get_capability_matrix(refresh: Option<bool>) -> Result<CapabilityMatrix, String> {
+    if !refresh.unwrap_or(false) {
+        let cache = CACHE.lock().map_err(|e| e.to_string())?;
+        if let Some((at, matrix)) = cache.as_ref() {
+            if at.elapsed() < CACHE_TTL {
+                return Ok(matrix.clone());
+            }
+        }
+    }
+
+    let (codex, gemini) = tokio::join!(probe_codex(), probe_gemini());
+    let matrix = CapabilityMatrix {
+        engines: vec![probe_claude(), codex, gemini],
+        generated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    log::info!(
+        "[Capabilities] Probed {} models across {} engines",
+        matrix.engines.iter().map(|e| e.models.len()).sum::<usize>(),
+        matrix.engines.len()
+    );
+    *CACHE.lock().map_err(|e| e.to_string())? = Some((Instant::now(), matrix.clone()));
+    Ok(matrix)
+}
+
+/// 清除能力矩阵缓存
+#[tauri::command]
+pub async fn invalidate_capability_matrix() -> Result<(), String> {
+    invalidate_capability_cache();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_and_merges_model_capabilities() {
+        let claude = static_models("claude");
+        let sonnet1m = claude.iter().find(|m| m.id == "sonnet1m").unwrap();
+        assert_eq!(sonnet1m.max_context, 1_000_000);
+        assert!(sonnet1m.vision && sonnet1m.extended_thinking && sonnet1m.reasoning_efforts.is_empty());
+
+        let mini = infer_model("codex", "gpt-5.1-codex-mini", "mini", CapabilitySource::Static);
+        assert_eq!(mini.reasoning_efforts, vec!["low", "medium"]);
+        assert!(infer_model("codex", "o3", "o3", CapabilitySource::Static).vision);
+        let custom = infer_model("claude", "deepseek-chat", "deepseek-chat", CapabilitySource::Provider);
+        assert!(!custom.vision && !custom.extended_thinking && custom.tool_use);
+
+        let mut gemini = new_engine("gemini");
+        assert_eq!(gemini.default_model, "gemini-3-pro-preview");
+        merge_model(
+            &mut gemini.models,
+            ModelCapabilities {
+                max_context: 2_000_000,
+                ..infer_model("gemini", "gemini-2.5-pro", "Gemini 2.5 Pro", CapabilitySource::Probe)
+            },
+        );
+        let pro = gemini.models.iter().find(|m| m.id == "gemini-2.5-pro").unwrap();
+        assert_eq!((pro.max_context, pro.source), (2_000_000, CapabilitySource::Probe));
+
+        apply_provider_model(&mut gemini, Some("my-gemini".to_string()));
+        assert_eq!(gemini.default_model, "my-gemini");
+        assert_eq!(gemini.models.iter().filter(|m| m.is_default).count(), 1);
+        assert_eq!(gemini.models.last().unwrap().source, CapabilitySource::Provider);
+
+        assert_eq!(provider_host(Some("https://api.example.com/v1".into())), Some("api.example.com".into()));
+        assert_eq!(provider_host(None), None);
+    }
+}
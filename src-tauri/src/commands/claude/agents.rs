@@ -0,0 +1,363 @@
+//! Claude 子代理管理
+//!
+//! 子代理是 `~/.claude/agents/<name>.md`（用户级）或 `<project>/.claude/agents/<name>.md`（项目级）中
+//! 带 YAML frontmatter 的 Markdown 文件：
+//!
+//! ```markdown
+//! ---
+//! name: code-reviewer
+//! description: 审查刚修改的代码，关注正确性与可维护性
+//! tools: Read, Grep, Glob
+//! model: sonnet
+//! ---
+//! 你是一名资深代码审查者……
+//! ```
+//!
+//! 与 Codex 提示词模板类似，用户级子代理可以按项目启用（复制到项目的 `.claude/agents/`）。
+
+use crate::error::AnyCodeError;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::paths::get_claude_dir;
+use crate::commands::prompt_library::split_frontmatter;
+
+static AGENT_NAME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9][a-z0-9-]*$").unwrap());
+/// 内置工具名（如 `Read`、`Bash`）或 MCP 工具（`mcp__server__tool`）
+static TOOL_NAME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Z][A-Za-z]*|mcp__[A-Za-z0-9_-]+(__[A-Za-z0-9_-]+)?)$").unwrap());
+const MODEL_ALIASES: &[&str] = &["sonnet", "opus", "haiku", "inherit"];
+
+#[derive(Debug, Default, Deserialize)]
+struct AgentFrontmatter {
+    name: Option<String>,
+    description: Option<String>,
+    /// 逗号分隔的字符串或列表；省略表示继承主会话的全部工具
+    tools: Option<serde_yaml::Value>,
+    model: Option<String>,
+    color: Option<String>,
+}
+
+/// 子代理定义
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeAgent {
+    pub name: String,
+    pub description: String,
+    /// None 表示继承全部工具
+    pub tools: Option<Vec<String>>,
+    pub model: Option<String>,
+    pub color: Option<String>,
+    /// 正文（子代理的系统提示词）
+    pub prompt: String,
+    /// 文件原始内容
+    pub content: String,
+    pub path: String,
+    /// "user" | "project"
+    pub scope: String,
+    /// 用户级子代理是否已在当前项目启用
+    pub active_in_project: bool,
+    /// frontmatter 校验错误（非空时 Claude Code 可能无法加载）
+    pub errors: Vec<String>,
+}
+
+/// frontmatter 校验结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentValidation {
+    pub valid: bool,
+    pub name: Option<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+struct ParsedAgent {
+    meta: AgentFrontmatter,
+    tools: Option<Vec<String>>,
+    prompt: String,
+    validation: AgentValidation,
+}
+
+fn parse_tools(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::String(s) => s.split(',').map(|t| t.trim().to_string()).collect(),
+        serde_yaml::Value::Sequence(items) => items
+            .iter()
+            .map(|item| item.as_str().unwrap_or_default().trim().to_string())
+            .collect(),
+        _ => vec![String::new()],
+    }
+}
+
+/// 解析并校验子代理文件
+fn parse_agent(content: &str) -> ParsedAgent {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    let (frontmatter, body) = split_frontmatter(content);
+    let meta = match frontmatter {
+        Some(yaml) => serde_yaml::from_str::<AgentFrontmatter>(yaml).unwrap_or_else(|e| {
+            errors.push(format!("frontmatter 解析失败: {}", e));
+            AgentFrontmatter::default()
+        }),
+        None => {
+            errors.push("缺少 YAML frontmatter（以 --- 开头和结尾）".to_string());
+            AgentFrontmatter::default()
+        }
+    };
+
+    match meta.name.as_deref().map(str::trim) {
+        None | Some("") => errors.push("缺少 name 字段".to_string()),
+        Some(name) if !AGENT_NAME_RE.is_match(name) => {
+            errors.push(format!("name 只能包含小写字母、数字和连字符: {}", name))
+        }
+        _ => {}
+    }
+    if meta.description.as_deref().is_none_or(|d| d.trim().is_empty()) {
+        errors.push("缺少 description 字段（Claude 据此决定何时调用该子代理）".to_string());
+    }
+
+    let tools = meta.tools.as_ref().map(parse_tools);
+    for tool in tools.iter().flatten() {
+        if tool.is_empty() {
+            errors.push("tools 中有空的工具名".to_string());
+        } else if !TOOL_NAME_RE.is_match(tool) {
+            errors.push(format!("无效的工具名: {}", tool));
+        }
+    }
+
+    if let Some(model) = meta.model.as_deref().map(str::trim) {
+        if !MODEL_ALIASES.contains(&model) && !model.starts_with("claude-") {
+            errors.push(format!("model 应为 {} 或完整的模型 ID: {}", MODEL_ALIASES.join(" / "), model));
+        }
+    }
+
+    let prompt = body.trim().to_string();
+    if prompt.is_empty() {
+        warnings.push("正文为空，子代理将没有系统提示词".to_string());
+    }
+
+    ParsedAgent {
+        validation: AgentValidation {
+            valid: errors.is_empty(),
+            name: meta.name.as_deref().map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+            errors,
+            warnings,
+        },
+        tools,
+        prompt,
+        meta,
+    }
+}
+
+fn user_agents_dir() -> Result<PathBuf, String> {
+    Ok(get_claude_dir().map_err(|e| e.to_string())?.join("agents"))
+}
+
+fn project_agents_dir(project_path: &str) -> Result<PathBuf, String> {
+    if project_path.trim().is_empty() {
+        return Err("项目路径不能为空".to_string());
+    }
+    let project_dir = Path::new(project_path);
+    if !project_dir.is_dir() {
+        return Err(format!("项目路径不存在或不是目录: {}", project_path));
+    }
+    Ok(project_dir.join(".claude").join("agents"))
+}
+
+fn agents_dir(project_path: Option<&str>) -> Result<PathBuf, String> {
+    match project_path {
+        Some(project_path) => project_agents_dir(project_path),
+        None => user_agents_dir(),
+    }
+}
+
+fn validate_agent_file_name(name: &str) -> Result<(), String> {
+    if AGENT_NAME_RE.is_match(name) {
+        Ok(())
+    } else {
+        Err(format!("无效的子代理名称: {}", name))
+    }
+}
+
+fn load_agent(path: &Path, scope: &str) -> Option<ClaudeAgent> {
+    let content = fs::read_to_string(path).ok()?;
+    let file_name = path.file_stem()?.to_str()?.to_string();
+    let parsed = parse_agent(&content);
+    let mut errors = parsed.validation.errors;
+    if parsed.validation.name.as_deref().is_some_and(|name| name != file_name) {
+        errors.push(format!("name 与文件名不一致: {}", file_name));
+    }
+    Some(ClaudeAgent {
+        name: parsed.validation.name.unwrap_or(file_name),
+        description: parsed.meta.description.unwrap_or_default().trim().to_string(),
+        tools: parsed.tools,
+        model: parsed.meta.model,
+        color: parsed.meta.color,
+        prompt: parsed.prompt,
+        content,
+        path: path.to_string_lossy().to_string(),
+        scope: scope.to_string(),
+        active_in_project: false,
+        errors,
+    })
+}
+
+fn scan_agents(dir: &Path, scope: &str) -> Vec<ClaudeAgent> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut agents: Vec<ClaudeAgent> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().and_then(|s| s.to_str()) == Some("md"))
+        .filter_map(|p| load_agent(&p, scope))
+        .collect();
+    agents.sort_by(|a, b| a.name.cmp(&b.name));
+    agents
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出用户级子代理（标记是否已在项目中启用），以及项目级子代理
+#[tauri::command]
+pub async fn list_claude_agents(project_path: Option<String>) -> Result<Vec<ClaudeAgent>, AnyCodeError> {
+    let mut agents = scan_agents(&user_agents_dir().map_err(AnyCodeError::Io)?, "user");
+    if let Some(project_path) = project_path.as_deref() {
+        let project_agents = scan_agents(&project_agents_dir(project_path).map_err(AnyCodeError::Io)?, "project");
+        for agent in agents.iter_mut() {
+            agent.active_in_project = project_agents.iter().any(|p| p.name == agent.name);
+        }
+        agents.extend(project_agents);
+    }
+    Ok(agents)
+}
+
+/// 校验子代理文件内容（编辑时实时提示）
+#[tauri::command]
+pub async fn validate_claude_agent(content: String) -> Result<AgentValidation, AnyCodeError> {
+    Ok(parse_agent(&content).validation)
+}
+
+/// 新建或更新子代理，文件名取自 frontmatter 的 name
+///
+/// `project_path` 为空时保存为用户级子代理；`previous_name` 不同于新名称时视为重命名。
+#[tauri::command]
+pub async fn save_claude_agent(
+    content: String,
+    project_path: Option<String>,
+    previous_name: Option<String>,
+) -> Result<ClaudeAgent, AnyCodeError> {
+    let parsed = parse_agent(&content);
+    if !parsed.validation.valid {
+        return Err(AnyCodeError::Validation(format!("子代理定义无效: {}", parsed.validation.errors.join("; "))));
+    }
+    let name = parsed.validation.name.unwrap_or_default();
+
+    let dir = agents_dir(project_path.as_deref()).map_err(AnyCodeError::Io)?;
+    fs::create_dir_all(&dir).map_err(|e| AnyCodeError::Io(format!("创建子代理目录失败: {}", e)))?;
+    let path = dir.join(format!("{}.md", name));
+    if path.exists() && previous_name.as_deref() != Some(name.as_str()) {
+        return Err(AnyCodeError::Validation(format!("子代理已存在: {}", name)));
+    }
+
+    fs::write(&path, &content).map_err(|e| AnyCodeError::Io(format!("保存子代理失败: {}", e)))?;
+    if let Some(previous) = previous_name.filter(|previous| *previous != name) {
+        validate_agent_file_name(&previous).map_err(AnyCodeError::Validation)?;
+        let old_path = dir.join(format!("{}.md", previous));
+        if old_path.exists() {
+            fs::remove_file(&old_path).map_err(|e| AnyCodeError::Io(format!("删除旧子代理文件失败: {}", e)))?;
+        }
+    }
+
+    log::info!("[ClaudeAgents] Saved agent '{}' to {:?}", name, path);
+    let scope = if project_path.is_some() { "project" } else { "user" };
+    load_agent(&path, scope).ok_or_else(|| AnyCodeError::NotFound(format!("读取子代理失败: {}", name)))
+}
+
+/// 删除子代理
+#[tauri::command]
+pub async fn delete_claude_agent(name: String, project_path: Option<String>) -> Result<String, AnyCodeError> {
+    validate_agent_file_name(&name).map_err(AnyCodeError::Validation)?;
+    let path = agents_dir(project_path.as_deref()).map_err(AnyCodeError::Io)?.join(format!("{}.md", name));
+    if !path.exists() {
+        return Err(AnyCodeError::NotFound(format!("子代理不存在: {}", name)));
+    }
+    fs::remove_file(&path).map_err(|e| AnyCodeError::Io(format!("删除子代理失败: {}", e)))?;
+    log::info!("[ClaudeAgents] Deleted agent '{}' ({:?})", name, path);
+    Ok(format!("子代理 '{}' 已删除", name))
+}
+
+/// 在项目中启用用户级子代理（复制到项目的 .claude/agents/）
+///
+/// 项目中已有内容不同的同名子代理时，需传入 `overwrite` 才会覆盖。
+#[tauri::command]
+pub async fn activate_claude_agent_to_project(
+    name: String,
+    project_path: String,
+    overwrite: Option<bool>,
+) -> Result<String, AnyCodeError> {
+    validate_agent_file_name(&name).map_err(AnyCodeError::Validation)?;
+    let source = user_agents_dir().map_err(AnyCodeError::Io)?.join(format!("{}.md", name));
+    let content = fs::read_to_string(&source).map_err(|_| AnyCodeError::NotFound(format!("子代理不存在: {}", name)))?;
+
+    let dir = project_agents_dir(&project_path).map_err(AnyCodeError::Io)?;
+    let target = dir.join(format!("{}.md", name));
+    if let Ok(existing) = fs::read_to_string(&target) {
+        if existing == content {
+            return Ok(format!("子代理 '{}' 已在项目中启用", name));
+        }
+        if !overwrite.unwrap_or(false) {
+            return Err(AnyCodeError::Validation(format!("项目中已存在内容不同的同名子代理: {}", name)));
+        }
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| AnyCodeError::Io(format!("创建子代理目录失败: {}", e)))?;
+    fs::write(&target, content).map_err(|e| AnyCodeError::Io(format!("写入项目子代理失败: {}", e)))?;
+    log::info!("[ClaudeAgents] Activated agent '{}' in project: {}", name, project_path);
+    Ok(format!("子代理 '{}' 已在项目中启用", name))
+}
+
+/// 在项目中停用子代理（删除项目中的副本）
+#[tauri::command]
+pub async fn deactivate_claude_agent_from_project(name: String, project_path: String) -> Result<String, AnyCodeError> {
+    validate_agent_file_name(&name).map_err(AnyCodeError::Validation)?;
+    let target = project_agents_dir(&project_path).map_err(AnyCodeError::Io)?.join(format!("{}.md", name));
+    if !target.exists() {
+        return Err(AnyCodeError::NotFound(format!("子代理未在项目中启用: {}", name)));
+    }
+    fs::remove_file(&target).map_err(|e| AnyCodeError::Io(format!("删除项目子代理失败: {}", e)))?;
+    log::info!("[ClaudeAgents] Deactivated agent '{}' in project: {}", name, project_path);
+    Ok(format!("子代理 '{}' 已在项目中停用", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_agent_frontmatter() {
+        let parsed = parse_agent(
+            "---\nname: code-reviewer\ndescription: Reviews code\ntools: Read, Grep, mcp__github__get_pr\nmodel: sonnet\n---\nYou review code.\n",
+        );
+        assert!(parsed.validation.valid, "{:?}", parsed.validation.errors);
+        assert_eq!(parsed.validation.name.as_deref(), Some("code-reviewer"));
+        assert_eq!(parsed.tools.unwrap(), vec!["Read", "Grep", "mcp__github__get_pr"]);
+        assert_eq!(parsed.prompt, "You review code.");
+
+        let listed = parse_agent("---\nname: a\ndescription: d\ntools: [Read, Bash]\n---\nx");
+        assert_eq!(listed.tools.unwrap(), vec!["Read", "Bash"]);
+
+        let invalid = parse_agent("---\nname: Code Reviewer\ntools: Read,,rm -rf\nmodel: gpt-5\n---\n");
+        assert!(!invalid.validation.valid);
+        assert_eq!(invalid.validation.errors.len(), 5);
+        assert_eq!(invalid.validation.warnings.len(), 1);
+
+        assert!(!parse_agent("no frontmatter").validation.valid);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::AnyCodeError;
 use std::fs;
 use std::process::Stdio;
 use std::sync::Arc;
@@ -7,7 +8,28 @@ use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
 use crate::commands::permission_config::{
-    ClaudePermissionConfig, ClaudeExecutionConfig, build_execution_args,
+    apply_permission_profile, build_execution_args, ClaudeExecutionConfig, ClaudePermissionConfig,
+};
+use crate::commands::attachments::{self, StagedAttachment};
+use crate::commands::auto_fix::{self, AutoFixOptions};
+use crate::commands::docker_backend;
+use crate::commands::model_routing;
+use crate::commands::project_defaults;
+use crate::commands::prompt_history;
+use crate::commands::project_memory;
+use crate::commands::prompt_library::{apply_prompt_template, PromptTemplateRef};
+use crate::commands::provider::provider_settings_override;
+use crate::commands::provider_metrics::{ErrorCategory, StreamMetrics};
+use crate::commands::notifications;
+use crate::commands::rate_limit::{self, RateLimitWatcher};
+use crate::commands::command_audit::CommandAuditor;
+use crate::commands::prompt_translation::{self, ResponseTranslator, TranslationRecord};
+use crate::commands::output_batcher::OutputBatcher;
+use crate::commands::session_log::SessionLogWriter;
+use crate::commands::ssh_remote;
+use crate::commands::tool_approval;
+use crate::process::{
+    kill_process_tree_verified_async, ExecutionTimeoutOptions, ExecutionWatchdog, ProcessKillReport, TimeoutKind,
 };
 
 use super::paths::{encode_project_path, get_claude_dir};
@@ -255,6 +277,49 @@ fn create_windows_command(
     Ok(cmd)
 }
 
+/// Which Claude CLI entry point a run uses
+#[derive(Debug, Clone)]
+enum ClaudeRunKind {
+    /// New conversation
+    Execute,
+    /// `-c`: continue the most recent conversation in the project
+    Continue,
+    /// `--resume <session_id>`
+    Resume(String),
+}
+
+/// A single Claude run request, kept so a timed-out run can be retried
+#[derive(Debug, Clone)]
+struct ClaudeRun {
+    kind: ClaudeRunKind,
+    project_path: String,
+    prompt: String,
+    model: String,
+    plan_mode: bool,
+    max_thinking_tokens: Option<u32>,
+    /// Permission profile ID (overrides the global permission config)
+    permission_profile: Option<String>,
+    /// Passed via `--append-system-prompt` (project system prompt template and memory notes)
+    append_system_prompt: Option<String>,
+    /// Provider preset ID (`--settings` plus child env for secrets, settings.json is left untouched)
+    provider: Option<String>,
+    timeout: ExecutionTimeoutOptions,
+    /// 0-based attempt number (incremented on timeout retry)
+    attempt: u32,
+    /// Times this prompt was resubmitted after a rate limit
+    rate_limit_requeues: u32,
+    /// The frontend can answer tool approval requests (false for headless runs)
+    interactive: bool,
+    /// Attachments already referenced in `prompt`, recorded once the session ID is known
+    attachments: Vec<StagedAttachment>,
+    /// Original prompt when `prompt` was translated before sending (see `prompt_translation`)
+    translation: Option<TranslationRecord>,
+    /// Verify after completion and send fix prompts on failure (auto-fix loop)
+    auto_fix: Option<AutoFixOptions>,
+    /// Fix prompts already sent by the auto-fix loop
+    auto_fix_iteration: u32,
+}
+
 /// Execute Claude Code session with project context resume and streaming output
 /// Always tries to resume project context first for better continuity
 /// Enhanced for Windows with better error handling
@@ -263,53 +328,58 @@ pub async fn execute_claude_code(
     app: AppHandle,
     project_path: String,
     prompt: String,
-    model: String,
+    model: Option<String>,
     plan_mode: Option<bool>,
     max_thinking_tokens: Option<u32>,
-) -> Result<(), String> {
+    timeout: Option<ExecutionTimeoutOptions>,
+    permission_profile: Option<String>,
+    prompt_template: Option<PromptTemplateRef>,
+    include_memory: Option<bool>,
+    attachments: Option<Vec<StagedAttachment>>,
+    auto_fix: Option<AutoFixOptions>,
+) -> Result<(), AnyCodeError> {
     let plan_mode = plan_mode.unwrap_or(false);
+    prompt_history::record_prompt("claude", &project_path, &prompt).await;
+    let (prompt, translation) = prompt_translation::prepare_prompt("claude", prompt).await;
+    let prompt = apply_prompt_template("claude", prompt, prompt_template.as_ref()).await.map_err(AnyCodeError::Config)?;
+    let attachments = attachments.unwrap_or_default();
+    let prompt = attachments::mention_attachments(&prompt, &attachments);
     log::info!(
         "Starting Claude Code session with project context resume in: {} with model: {}, plan_mode: {}",
         project_path,
-        model,
+        model.as_deref().unwrap_or("(project default)"),
         plan_mode
     );
 
-    let claude_path = crate::claude_binary::find_claude_binary(&app)?;
-    
-    // 获取当前执行配置
-    let mut execution_config = get_claude_execution_config(app.clone()).await
-        .unwrap_or_else(|e| {
-            log::warn!("Failed to load execution config, using default: {}", e);
-            ClaudeExecutionConfig::default()
-        });
-    
-    // 设置 maxThinkingTokens（如果提供）
-    if let Some(tokens) = max_thinking_tokens {
-        execution_config.max_thinking_tokens = Some(tokens);
-        log::info!("Setting maxThinkingTokens to {}", tokens);
-    }
-
-    // 如果启用 Plan Mode，使用 Claude CLI 原生的 plan 权限模式
-    if plan_mode {
-        execution_config.permissions = ClaudePermissionConfig::plan_mode();
-    }
-
-    log::info!("Using execution config: permissions_mode={:?}, dangerous_skip={}, plan_mode={}, max_thinking_tokens={:?}",
-        execution_config.permissions.permission_mode,
-        execution_config.permissions.enable_dangerous_skip,
-        plan_mode,
-        execution_config.max_thinking_tokens
-    );
-
-    // 使用新的参数构建函数（先映射模型名称）
-    // 🔥 修复：prompt 不再通过命令行参数传递，改为 stdin 管道传递
-    let mapped_model = map_model_to_claude_alias(&model);
-    let args = build_execution_args(&execution_config, &mapped_model, &project_path);
-
-    // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
-    spawn_claude_process(app, cmd, prompt, model, project_path).await
+    start_claude_run(
+        app,
+        with_project_defaults(
+            ClaudeRun {
+                kind: ClaudeRunKind::Execute,
+                project_path,
+                prompt,
+                model: model.unwrap_or_default(),
+                plan_mode,
+                max_thinking_tokens,
+                permission_profile,
+                append_system_prompt: None,
+                provider: None,
+                timeout: timeout.unwrap_or_default(),
+                attempt: 0,
+                rate_limit_requeues: 0,
+                interactive: true,
+                attachments,
+                translation,
+                auto_fix,
+                auto_fix_iteration: 0,
+            },
+            include_memory,
+            false,
+        )
+        .await.map_err(AnyCodeError::Config)?,
+    )
+    .await
+    .map_err(AnyCodeError::Engine)
 }
 
 /// Continue an existing Claude Code conversation with streaming output
@@ -319,56 +389,58 @@ pub async fn continue_claude_code(
     app: AppHandle,
     project_path: String,
     prompt: String,
-    model: String,
+    model: Option<String>,
     plan_mode: Option<bool>,
     max_thinking_tokens: Option<u32>,
-) -> Result<(), String> {
+    timeout: Option<ExecutionTimeoutOptions>,
+    permission_profile: Option<String>,
+    prompt_template: Option<PromptTemplateRef>,
+    include_memory: Option<bool>,
+    attachments: Option<Vec<StagedAttachment>>,
+    auto_fix: Option<AutoFixOptions>,
+) -> Result<(), AnyCodeError> {
     let plan_mode = plan_mode.unwrap_or(false);
+    prompt_history::record_prompt("claude", &project_path, &prompt).await;
+    let (prompt, translation) = prompt_translation::prepare_prompt("claude", prompt).await;
+    let prompt = apply_prompt_template("claude", prompt, prompt_template.as_ref()).await.map_err(AnyCodeError::Config)?;
+    let attachments = attachments.unwrap_or_default();
+    let prompt = attachments::mention_attachments(&prompt, &attachments);
     log::info!(
         "Continuing Claude Code conversation in: {} with model: {}, plan_mode: {}",
         project_path,
-        model,
+        model.as_deref().unwrap_or("(project default)"),
         plan_mode
     );
 
-    let claude_path = crate::claude_binary::find_claude_binary(&app)?;
-    
-    // 获取当前执行配置
-    let mut execution_config = get_claude_execution_config(app.clone()).await
-        .unwrap_or_else(|e| {
-            log::warn!("Failed to load execution config, using default: {}", e);
-            ClaudeExecutionConfig::default()
-        });
-
-    // 设置 maxThinkingTokens（如果提供）
-    if let Some(tokens) = max_thinking_tokens {
-        execution_config.max_thinking_tokens = Some(tokens);
-        log::info!("Setting maxThinkingTokens to {}", tokens);
-    }
-
-    // 如果启用 Plan Mode，使用 Claude CLI 原生的 plan 权限模式
-    if plan_mode {
-        execution_config.permissions = ClaudePermissionConfig::plan_mode();
-    }
-
-    log::info!("Continuing with execution config: permissions_mode={:?}, dangerous_skip={}, plan_mode={}, max_thinking_tokens={:?}",
-        execution_config.permissions.permission_mode,
-        execution_config.permissions.enable_dangerous_skip,
-        plan_mode,
-        execution_config.max_thinking_tokens
-    );
-
-    // 使用新的参数构建函数，添加 -c 标志用于继续对话（先映射模型名称）
-    // 🔥 修复：prompt 不再通过命令行参数传递，改为 stdin 管道传递
-    let mapped_model = map_model_to_claude_alias(&model);
-    let mut args = build_execution_args(&execution_config, &mapped_model, &project_path);
-
-    // 在开头插入 -c 标志
-    args.insert(0, "-c".to_string());
-
-    // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
-    spawn_claude_process(app, cmd, prompt, model, project_path).await
+    start_claude_run(
+        app,
+        with_project_defaults(
+            ClaudeRun {
+                kind: ClaudeRunKind::Continue,
+                project_path,
+                prompt,
+                model: model.unwrap_or_default(),
+                plan_mode,
+                max_thinking_tokens,
+                permission_profile,
+                append_system_prompt: None,
+                provider: None,
+                timeout: timeout.unwrap_or_default(),
+                attempt: 0,
+                rate_limit_requeues: 0,
+                interactive: true,
+                attachments,
+                translation,
+                auto_fix,
+                auto_fix_iteration: 0,
+            },
+            include_memory,
+            false,
+        )
+        .await.map_err(AnyCodeError::Config)?,
+    )
+    .await
+    .map_err(AnyCodeError::Engine)
 }
 
 /// Resume an existing Claude Code session by ID with streaming output
@@ -379,16 +451,27 @@ pub async fn resume_claude_code(
     project_path: String,
     session_id: String,
     prompt: String,
-    model: String,
+    model: Option<String>,
     plan_mode: Option<bool>,
     max_thinking_tokens: Option<u32>,
-) -> Result<(), String> {
+    timeout: Option<ExecutionTimeoutOptions>,
+    permission_profile: Option<String>,
+    prompt_template: Option<PromptTemplateRef>,
+    include_memory: Option<bool>,
+    attachments: Option<Vec<StagedAttachment>>,
+    auto_fix: Option<AutoFixOptions>,
+) -> Result<(), AnyCodeError> {
     let plan_mode = plan_mode.unwrap_or(false);
+    prompt_history::record_prompt("claude", &project_path, &prompt).await;
+    let (prompt, translation) = prompt_translation::prepare_prompt("claude", prompt).await;
+    let prompt = apply_prompt_template("claude", prompt, prompt_template.as_ref()).await.map_err(AnyCodeError::Config)?;
+    let attachments = attachments.unwrap_or_default();
+    let prompt = attachments::mention_attachments(&prompt, &attachments);
     log::info!(
         "Resuming Claude Code session: {} in: {} with model: {}, plan_mode: {}",
         session_id,
         project_path,
-        model,
+        model.as_deref().unwrap_or("(project default)"),
         plan_mode
     );
     
@@ -401,8 +484,153 @@ pub async fn resume_claude_code(
     log::info!("Expected session file directory: {}", session_dir);
     log::info!("Session ID to resume: {}", session_id);
 
-    let claude_path = crate::claude_binary::find_claude_binary(&app)?;
-    
+    start_claude_run(
+        app,
+        with_project_defaults(
+            ClaudeRun {
+                kind: ClaudeRunKind::Resume(session_id),
+                project_path,
+                prompt,
+                model: model.unwrap_or_default(),
+                plan_mode,
+                max_thinking_tokens,
+                permission_profile,
+                append_system_prompt: None,
+                provider: None,
+                timeout: timeout.unwrap_or_default(),
+                attempt: 0,
+                rate_limit_requeues: 0,
+                interactive: true,
+                attachments,
+                translation,
+                auto_fix,
+                auto_fix_iteration: 0,
+            },
+            include_memory,
+            false,
+        )
+        .await.map_err(AnyCodeError::Config)?,
+    )
+    .await
+    .map_err(AnyCodeError::Engine)
+}
+
+/// Fills values not given explicitly from the project's Claude defaults
+///
+/// Internal helper runs (summaries, commit messages) get neither the system prompt template nor memory notes.
+async fn with_project_defaults(
+    mut run: ClaudeRun,
+    include_memory: Option<bool>,
+    internal: bool,
+) -> Result<ClaudeRun, String> {
+    let defaults = project_defaults::resolve_engine_defaults(&run.project_path, "claude");
+    let model = apply_engine_defaults(&mut run, &defaults);
+    run.model = model_routing::resolve_model("claude", model, &run.prompt, run.provider.as_deref())
+        .await
+        .unwrap_or_else(|| "sonnet".to_string());
+    if internal {
+        return Ok(run);
+    }
+    if let Some(template_id) = defaults.system_prompt_template.as_deref() {
+        run.append_system_prompt = Some(project_defaults::load_system_prompt_template(template_id).await?);
+    }
+    if project_memory::should_include(include_memory, defaults.include_memory) {
+        if let Some(memory) = project_memory::memory_for_prompt(&run.project_path, &run.prompt).await {
+            run.append_system_prompt = Some(match run.append_system_prompt.take() {
+                Some(template) => format!("{}\n\n{}", template, memory),
+                None => memory,
+            });
+        }
+    }
+    // Selected project files only go into a new session; continued sessions already have them
+    if matches!(run.kind, ClaudeRunKind::Execute) {
+        if let Some(context) = project_defaults::context_block(&run.project_path, &run.prompt, &defaults).await {
+            run.append_system_prompt = Some(match run.append_system_prompt.take() {
+                Some(existing) => format!("{}\n\n{}", existing, context),
+                None => context,
+            });
+        }
+    }
+    Ok(run)
+}
+
+/// Applies the project's Claude defaults to a run and returns the model to route
+///
+/// An empty model means the caller (e.g. the GUI without an explicit pick) left it to the project.
+fn apply_engine_defaults(run: &mut ClaudeRun, defaults: &project_defaults::EngineDefaults) -> Option<String> {
+    run.permission_profile = project_defaults::or_default(run.permission_profile.take(), &defaults.permission_profile);
+    run.provider = defaults.provider.clone();
+    project_defaults::or_default(Some(std::mem::take(&mut run.model)), &defaults.model)
+}
+
+/// Re-runs a timed-out Claude execution (boxed to break the async recursion)
+fn retry_claude_run(
+    app: AppHandle,
+    run: ClaudeRun,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send>> {
+    Box::pin(async move { start_claude_run(app, run).await })
+}
+
+/// Build the CLI command for a run and spawn it
+async fn start_claude_run(app: AppHandle, run: ClaudeRun) -> Result<(), String> {
+    let (cmd, approvals) = build_claude_command(&app, &run).await?;
+
+    match spawn_claude_process(app.clone(), cmd, run.clone(), approvals).await {
+        Ok(_) => Ok(()),
+        Err(resume_error) if matches!(run.kind, ClaudeRunKind::Resume(_)) => {
+            // Try to spawn the process - if resume fails, fall back to continue mode
+            log::warn!("Resume failed: {}, trying continue mode as fallback", resume_error);
+            retry_claude_run(app, ClaudeRun { kind: ClaudeRunKind::Continue, ..run }).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Builds a one-shot Claude command for backend orchestration (e.g. pipelines)
+///
+/// Resolves project defaults like `execute_claude_code`; the caller spawns the
+/// command, writes the prompt to stdin and consumes the stream-json output itself.
+/// `internal` marks backend helper runs (see `HeadlessRequest::internal`).
+pub async fn build_headless_claude_command(
+    app: &AppHandle,
+    project_path: String,
+    prompt: String,
+    model: Option<String>,
+    permission_profile: Option<String>,
+    internal: bool,
+) -> Result<(Command, String), String> {
+    let run = with_project_defaults(
+        ClaudeRun {
+            kind: ClaudeRunKind::Execute,
+            project_path,
+            prompt,
+            model: model.unwrap_or_default(),
+            plan_mode: false,
+            max_thinking_tokens: None,
+            permission_profile,
+            append_system_prompt: None,
+            provider: None,
+            timeout: ExecutionTimeoutOptions::default(),
+            attempt: 0,
+            rate_limit_requeues: 0,
+            interactive: false,
+            attachments: Vec::new(),
+            translation: None,
+            auto_fix: None,
+            auto_fix_iteration: 0,
+        },
+        None,
+        internal,
+    )
+    .await?;
+    let (cmd, _) = build_claude_command(app, &run).await?;
+    Ok((cmd, run.prompt))
+}
+
+/// Build the CLI command for a run
+///
+/// Also returns whether tool approvals are mediated through stdin (see `tool_approval`).
+async fn build_claude_command(app: &AppHandle, run: &ClaudeRun) -> Result<(Command, bool), String> {
     // 获取当前执行配置
     let mut execution_config = get_claude_execution_config(app.clone()).await
         .unwrap_or_else(|e| {
@@ -411,46 +639,99 @@ pub async fn resume_claude_code(
         });
 
     // 设置 maxThinkingTokens（如果提供）
-    if let Some(tokens) = max_thinking_tokens {
+    if let Some(tokens) = run.max_thinking_tokens {
         execution_config.max_thinking_tokens = Some(tokens);
         log::info!("Setting maxThinkingTokens to {}", tokens);
     }
 
     // 如果启用 Plan Mode，使用 Claude CLI 原生的 plan 权限模式
-    if plan_mode {
+    if run.plan_mode {
         execution_config.permissions = ClaudePermissionConfig::plan_mode();
     }
 
-    log::info!("Resuming with execution config: permissions_mode={:?}, dangerous_skip={}, plan_mode={}, max_thinking_tokens={:?}",
+    log::info!("Using execution config ({:?}): permissions_mode={:?}, dangerous_skip={}, plan_mode={}, max_thinking_tokens={:?}",
+        run.kind,
         execution_config.permissions.permission_mode,
         execution_config.permissions.enable_dangerous_skip,
-        plan_mode,
+        run.plan_mode,
         execution_config.max_thinking_tokens
     );
 
-    // 使用新的参数构建函数，添加 --resume 和 session_id（先映射模型名称）
+    // 使用新的参数构建函数（先映射模型名称）
     // 🔥 修复：prompt 不再通过命令行参数传递，改为 stdin 管道传递
-    let mapped_model = map_model_to_claude_alias(&model);
-    let mut args = build_execution_args(&execution_config, &mapped_model, &project_path);
+    let mapped_model = map_model_to_claude_alias(&run.model);
+    // Plan Mode 优先于权限档案
+    let permission_args = match run.permission_profile.as_deref() {
+        Some(profile_id) if !run.plan_mode => Some(apply_permission_profile("claude", profile_id)?),
+        _ => None,
+    };
+    let mut args = build_execution_args(&execution_config, &mapped_model, &run.project_path, permission_args);
+
+    match &run.kind {
+        ClaudeRunKind::Execute => {}
+        ClaudeRunKind::Continue => {
+            // 在开头插入 -c 标志
+            args.insert(0, "-c".to_string());
+        }
+        ClaudeRunKind::Resume(session_id) => {
+            // 为resume模式重新组织参数：--resume session_id 应该在最前面
+            args.insert(0, "--resume".to_string());
+            args.insert(1, session_id.clone());
+            log::info!("Resume command: claude {}", args.join(" "));
+        }
+    }
 
-    // 为resume模式重新组织参数：--resume session_id 应该在最前面
-    args.insert(0, "--resume".to_string());
-    args.insert(1, session_id.clone());
+    let provider_override = run.provider.as_deref().map(provider_settings_override).transpose()?;
+    if let Some(provider) = &provider_override {
+        args.push("--settings".to_string());
+        args.push(provider.settings.to_string());
+    }
+    if let Some(system_prompt) = &run.append_system_prompt {
+        args.push("--append-system-prompt".to_string());
+        args.push(system_prompt.clone());
+    }
 
-    log::info!("Resume command: claude {}", args.join(" "));
+    // 交互式审批：权限请求通过 stdin 控制协议交给前端（跳过权限检查时无意义）
+    let approvals = run.interactive
+        && execution_config.interactive_approvals
+        && !args.iter().any(|arg| arg == "--dangerously-skip-permissions");
+    if approvals {
+        args.extend(
+            ["--input-format", "stream-json", "--permission-prompt-tool", "stdio"]
+                .iter()
+                .map(|arg| arg.to_string()),
+        );
+    }
 
-    // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
-    
-    // Try to spawn the process - if it fails, fall back to continue mode
-    match spawn_claude_process(app.clone(), cmd, prompt.clone(), model.clone(), project_path.clone()).await {
-        Ok(_) => Ok(()),
-        Err(resume_error) => {
-            log::warn!("Resume failed: {}, trying continue mode as fallback", resume_error);
-            // Fallback to continue mode
-            continue_claude_code(app, project_path, prompt, model, Some(plan_mode), max_thinking_tokens).await
+    // 远程 SSH 模式：在远程主机上执行（模型已通过 --model 传递，本地环境变量不会转发）
+    // 项目启用容器时仍在本机 Docker 中执行
+    let remote = ssh_remote::active_claude_remote()
+        .filter(|_| !docker_backend::container_enabled(&run.project_path));
+    if let Some(remote) = remote {
+        let remote_dir = ssh_remote::remote_project_dir(&run.project_path);
+        log::info!("[Claude Remote] Running on {} (cd {})", remote.destination(), remote_dir);
+        if provider_override.as_ref().is_some_and(|p| !p.secret_env.is_empty()) {
+            log::warn!("[Claude Remote] Provider credentials are not forwarded over SSH; the remote login is used");
         }
+        let mut cmd = ssh_remote::build_ssh_claude_command_async(&remote, &args, Some(&remote_dir));
+        cmd.stdin(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        platform::apply_no_window_async(&mut cmd);
+        #[cfg(unix)]
+        cmd.process_group(0);
+        return Ok((cmd, approvals));
     }
+
+    // Create command
+    let claude_path = crate::claude_binary::find_claude_binary(app)?;
+    let engine_args = args.clone();
+    let mut cmd = create_system_command(&claude_path, args, &run.project_path, Some(&mapped_model), run.max_thinking_tokens)?;
+    if let Some(provider) = provider_override {
+        cmd.envs(provider.secret_env);
+    }
+    // 项目设置启用容器时，在 Docker 中执行（使用 .cmd 解析之前的参数）
+    Ok((docker_backend::wrap_command_for_project(&run.project_path, "claude", &engine_args, cmd)?, approvals))
 }
 
 /// Cancel the currently running Claude Code execution
@@ -458,7 +739,7 @@ pub async fn resume_claude_code(
 pub async fn cancel_claude_execution(
     app: AppHandle,
     session_id: Option<String>,
-) -> Result<(), String> {
+) -> Result<Vec<ProcessKillReport>, AnyCodeError> {
     log::info!(
         "Cancelling Claude Code execution for session: {:?}",
         session_id
@@ -466,6 +747,7 @@ pub async fn cancel_claude_execution(
 
     let mut killed = false;
     let mut attempted_methods = Vec::new();
+    let mut reports = Vec::new();
 
     // Method 1: Try to find and kill via ProcessRegistry using session ID
     if let Some(sid) = &session_id {
@@ -508,6 +790,15 @@ pub async fn cancel_claude_execution(
             let pid = child.id();
             log::info!("Attempting to kill Claude process via ClaudeProcessState with PID: {:?}", pid);
 
+            // Kill the whole tree first (MCP servers etc.) and verify nothing survived
+            if let Some(pid) = pid {
+                let report = kill_process_tree_verified_async(pid).await;
+                if !report.fully_terminated() {
+                    log::error!("Claude process {} left {} surviving process(es)", pid, report.survivors.len());
+                }
+                reports.push(report);
+            }
+
             // Kill the process
             match child.kill().await {
                 Ok(_) => {
@@ -560,15 +851,15 @@ pub async fn cancel_claude_execution(
         log::warn!("Claude process cancellation attempted but process may have already exited. Attempted methods: {:?}", attempted_methods);
     }
     
-    Ok(())
+    Ok(reports)
 }
 
 /// Get all running Claude sessions
 #[tauri::command]
 pub async fn list_running_claude_sessions(
     registry: tauri::State<'_, crate::process::ProcessRegistryState>,
-) -> Result<Vec<crate::process::ProcessInfo>, String> {
-    registry.0.get_running_claude_sessions()
+) -> Result<Vec<crate::process::ProcessInfo>, AnyCodeError> {
+    registry.0.get_running_claude_sessions().map_err(AnyCodeError::Internal)
 }
 
 /// Get live output from a Claude session
@@ -576,10 +867,10 @@ pub async fn list_running_claude_sessions(
 pub async fn get_claude_session_output(
     registry: tauri::State<'_, crate::process::ProcessRegistryState>,
     session_id: String,
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     // Find the process by session ID
-    if let Some(process_info) = registry.0.get_claude_session_by_id(&session_id)? {
-        registry.0.get_live_output(process_info.run_id)
+    if let Some(process_info) = registry.0.get_claude_session_by_id(&session_id).map_err(AnyCodeError::Internal)? {
+        registry.0.get_live_output(process_info.run_id).map_err(AnyCodeError::Internal)
     } else {
         Ok(String::new())
     }
@@ -588,18 +879,53 @@ pub async fn get_claude_session_output(
 /// Helper function to spawn Claude process and handle streaming
 /// 🔥 修复：prompt 现在通过 stdin 管道传递，而非命令行参数
 /// 这样可以避免操作系统命令行长度限制（Windows ~8KB, Linux/macOS ~128KB-2MB）
-async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String, model: String, project_path: String) -> Result<(), String> {
+///
+/// With `approvals` the prompt is sent as a stream-json user message and stdin stays
+/// open for approval responses until the run produces its result.
+async fn spawn_claude_process(app: AppHandle, mut cmd: Command, run: ClaudeRun, approvals: bool) -> Result<(), String> {
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Mutex;
 
+    let prompt = run.prompt.clone();
+    let model = run.model.clone();
+    let project_path = run.project_path.clone();
+    let watchdog = Arc::new(ExecutionWatchdog::new(run.timeout.clone()));
+
     // Spawn the process
     let mut child = cmd
         .spawn()
         .map_err(|e| format!("Failed to spawn Claude: {}", e))?;
 
+    // 交互式审批：stdin 保持打开，由通道写入（先是 prompt，之后是审批回复）
+    let mut approval_stdin: Option<tokio::sync::mpsc::UnboundedSender<String>> = None;
+    if approvals {
+        let mut stdin = child.stdin.take().ok_or("Failed to get stdin")?;
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let user_message = serde_json::json!({
+            "type": "user",
+            "message": { "role": "user", "content": prompt },
+        });
+        let _ = tx.send(user_message.to_string());
+        tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                if let Err(e) = stdin.write_all(format!("{}\n", line).as_bytes()).await {
+                    log::error!("Failed to write to Claude stdin: {}", e);
+                    return;
+                }
+                let _ = stdin.flush().await;
+            }
+            // 所有发送端释放（运行结束），关闭 stdin 让 CLI 退出
+            let _ = stdin.shutdown().await;
+        });
+        approval_stdin = Some(tx);
+    }
+
     // 🔥 修复：通过 stdin 管道传递 prompt，避免命令行长度限制
     // 这是解决长文本发送失败问题的关键修改
-    if let Some(mut stdin) = child.stdin.take() {
+    if approvals {
+        log::info!("Sent prompt as stream-json message ({} bytes), approvals enabled", prompt.len());
+    } else if let Some(mut stdin) = child.stdin.take() {
         // 克隆 prompt 以便在 async 块中使用（避免生命周期问题）
         let prompt_for_stdin = prompt.clone();
         let prompt_len = prompt_for_stdin.len();
@@ -632,6 +958,12 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
         pid
     );
 
+    // Persist PID so a crashed app can detect the orphan on next launch
+    if pid != 0 {
+        crate::process::orphans::track_process(pid, "claude", None, &run.project_path);
+        docker_backend::track_container_run(pid, &cmd);
+    }
+
     // Create readers first (before moving child)
     let stdout_reader = BufReader::new(stdout);
     let stderr_reader = BufReader::new(stderr);
@@ -664,9 +996,78 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
     let project_path_clone = project_path.clone();
     let prompt_clone = prompt.clone();
     let model_clone = model.clone();
+    // Tee raw output to ~/.anycode/logs/claude/<session_id>.log
+    // (new sessions only learn their ID from the init message)
+    let session_log = match &run.kind {
+        ClaudeRunKind::Resume(session_id) => SessionLogWriter::open("claude", session_id),
+        _ => SessionLogWriter::open_pending("claude", pid),
+    };
+    let session_log_stdout = session_log.clone();
+    let session_log_stderr = session_log;
+    let command_auditor = CommandAuditor::new(
+        "claude",
+        match &run.kind {
+            ClaudeRunKind::Resume(session_id) => session_id,
+            _ => "",
+        },
+        &run.project_path,
+    );
+    let write_guard = crate::commands::guardrails::WriteGuard::new(
+        &app,
+        "claude",
+        match &run.kind {
+            ClaudeRunKind::Resume(session_id) => session_id,
+            _ => "",
+        },
+        &run.project_path,
+    );
+    let run_attachments = run.attachments.clone();
+    let response_translator = ResponseTranslator::new(
+        &app,
+        "claude",
+        match &run.kind {
+            ClaudeRunKind::Resume(session_id) => session_id,
+            _ => "",
+        },
+        // Timeout retries and rate-limit requeues resend the same prompt; its translation is only recorded once
+        run.translation.clone().filter(|_| run.attempt == 0 && run.rate_limit_requeues == 0),
+    )
+    .await;
+    let stream_metrics = StreamMetrics::new(
+        "claude",
+        match &run.kind {
+            ClaudeRunKind::Resume(session_id) => session_id,
+            _ => "",
+        },
+        run.provider.as_deref(),
+    )
+    .await;
+    let stream_metrics_stdout = stream_metrics.clone();
+    let stream_metrics_stderr = stream_metrics.clone();
+    let rate_limit_watcher = RateLimitWatcher::new(
+        &app,
+        "claude",
+        match &run.kind {
+            ClaudeRunKind::Resume(session_id) => session_id,
+            _ => "",
+        },
+    );
+    let rate_limit_stdout = rate_limit_watcher.clone();
+    let rate_limit_stderr = rate_limit_watcher.clone();
+    let watchdog_stdout = watchdog.clone();
+    let output_batcher = OutputBatcher::new(&app, "claude-output").await;
     let stdout_task = tokio::spawn(async move {
         let mut lines = stdout_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
+            watchdog_stdout.touch();
+            if let Some(log) = &session_log_stdout {
+                log.write_line("stdout", &line);
+            }
+            command_auditor.observe(&line);
+            write_guard.observe(&line);
+            response_translator.observe(&line);
+            stream_metrics_stdout.observe(&line);
+            rate_limit_stdout.observe(&line);
             log::debug!("Claude stdout: {}", line);
             
             // Parse the line to check for init message with session ID
@@ -677,6 +1078,13 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                         if session_id_guard.is_none() {
                             *session_id_guard = Some(claude_session_id.to_string());
                             log::info!("Extracted Claude session ID: {}", claude_session_id);
+                            if let Some(log) = &session_log_stdout {
+                                log.assign_session(claude_session_id);
+                            }
+                            if let Some(stdin) = &approval_stdin {
+                                tool_approval::register_session(claude_session_id, "claude", stdin.clone());
+                            }
+                            attachments::record_attachments("claude", claude_session_id, &run_attachments);
 
                             // Register with auto-compact manager
                             if auto_compact_available {
@@ -729,6 +1137,25 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                     }
                 }
 
+                if let Some(reason) = notifications::claude_limit_reason(&msg) {
+                    let session_id = session_id_holder_clone.lock().unwrap().clone().unwrap_or_default();
+                    notifications::notify_budget_reached(&app_handle, "claude", &session_id, reason);
+                }
+
+                if approvals {
+                    let session_id = session_id_holder_clone.lock().unwrap().clone();
+                    if let Some(session_id) = session_id {
+                        if tool_approval::handle_claude_control_message(&app_handle, &session_id, &msg) {
+                            continue;
+                        }
+                        // 本轮结束：释放 stdin 通道，CLI 读到 EOF 后退出
+                        if msg["type"] == "result" {
+                            approval_stdin = None;
+                            tool_approval::close_session(&app_handle, &session_id);
+                        }
+                    }
+                }
+
                 // Check for usage information and update context tracking
                 if let Some(usage) = msg.get("usage") {
                     if let (Some(input_tokens), Some(output_tokens)) =
@@ -781,21 +1208,27 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                 let _ = registry_clone.append_live_output(run_id, &line);
             }
             
-            // Emit the line to the frontend with session isolation if we have session ID
-            if let Some(ref session_id) = *session_id_holder_clone.lock().unwrap() {
-                let _ = app_handle.emit(&format!("claude-output:{}", session_id), &line);
-            }
-            // Also emit to the generic event for backward compatibility and early messages
-            let _ = app_handle.emit("claude-output", &line);
+            // Emit the line to the frontend (batched) with session isolation if we have session ID;
+            // the generic event is always emitted for backward compatibility and early messages
+            let session_id = session_id_holder_clone.lock().unwrap().clone();
+            output_batcher.push(session_id.as_deref(), line);
         }
+        output_batcher.flush();
     });
 
     let app_handle_stderr = app.clone();
     let session_id_holder_clone2 = session_id_holder.clone();
+    let watchdog_stderr = watchdog.clone();
     let stderr_task = tokio::spawn(async move {
         let mut lines = stderr_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
+            watchdog_stderr.touch();
+            if let Some(log) = &session_log_stderr {
+                log.write_line("stderr", &line);
+            }
             log::error!("Claude stderr: {}", line);
+            stream_metrics_stderr.observe_stderr(&line);
+            rate_limit_stderr.observe_stderr(&line);
             // Emit error lines to the frontend with session isolation if we have session ID
             if let Some(ref session_id) = *session_id_holder_clone2.lock().unwrap() {
                 let _ = app_handle_stderr.emit(&format!("claude-error:{}", session_id), &line);
@@ -805,6 +1238,29 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
         }
     });
 
+    // Watchdog: kill the process tree on idle / max-duration timeout.
+    // Killing closes stdout/stderr, so the wait task below finishes normally and handles retry.
+    let finished = Arc::new(AtomicBool::new(false));
+    let timed_out: Arc<Mutex<Option<TimeoutKind>>> = Arc::new(Mutex::new(None));
+    if run.timeout.is_enabled() {
+        let watchdog_monitor = watchdog.clone();
+        let finished_monitor = finished.clone();
+        let timed_out_monitor = timed_out.clone();
+        tokio::spawn(async move {
+            while !finished_monitor.load(Ordering::Relaxed) {
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                if let Some(kind) = watchdog_monitor.check() {
+                    log::warn!("Claude process {} timed out ({:?}), killing process tree", pid, kind);
+                    *timed_out_monitor.lock().unwrap() = Some(kind);
+                    if let Err(e) = platform::kill_process_tree(pid) {
+                        log::warn!("Failed to kill Claude process tree {}: {}", pid, e);
+                    }
+                    break;
+                }
+            }
+        });
+    }
+
     // Wait for the process to complete
     let app_handle_wait = app.clone();
     let claude_state_wait = claude_state.current_process.clone();
@@ -817,10 +1273,35 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
 
         // Get the child from the state to wait on it
         let mut current_process = claude_state_wait.lock().await;
+        let mut exited_with_error = false;
+        // None when the process was killed by a signal (cancelled or timed out)
+        let mut run_succeeded = None;
         if let Some(mut child) = current_process.take() {
             match child.wait().await {
                 Ok(status) => {
                     log::info!("Claude process exited with status: {}", status);
+                    // 远程模式：完成事件前把远程会话文件同步到本地，历史记录才能读到本次执行
+                    let remote = ssh_remote::active_claude_remote()
+                        .filter(|_| !docker_backend::container_enabled(&run.project_path));
+                    if let Some(remote) = remote {
+                        let project_path = run.project_path.clone();
+                        let synced = tokio::task::spawn_blocking(move || {
+                            ssh_remote::sync_remote_claude_project(&remote, &project_path)
+                        })
+                        .await;
+                        if let Ok(Err(e)) = synced {
+                            log::warn!("[Claude Remote] Failed to sync sessions after run: {}", e);
+                        }
+                    }
+                    exited_with_error = status.code().is_some_and(|code| code != 0);
+                    run_succeeded = status.code().map(|_| status.success());
+                    // Killed by a signal outside the watchdog means the user cancelled,
+                    // which says nothing about the provider
+                    if timed_out.lock().unwrap().is_some() {
+                        stream_metrics.finish(Some(ErrorCategory::Timeout));
+                    } else if status.code().is_some() {
+                        stream_metrics.finish((!status.success()).then_some(ErrorCategory::Other));
+                    }
                     // Add a small delay to ensure all messages are processed
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
@@ -842,6 +1323,7 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                 }
                 Err(e) => {
                     log::error!("Failed to wait for Claude process: {}", e);
+                    run_succeeded = Some(false);
                     // Add a small delay to ensure all messages are processed
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
@@ -867,11 +1349,146 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
         if let Some(run_id) = *run_id_holder_clone2.lock().unwrap() {
             let _ = registry_clone2.unregister_process(run_id);
         }
+        let finished_session = session_id_holder_clone3.lock().unwrap().clone();
+        if let Some(session_id) = finished_session {
+            tool_approval::close_session(&app_handle_wait, &session_id);
+        }
+        crate::process::orphans::untrack_process(pid);
+        docker_backend::release_container_run(pid);
 
         // Clear the process from state
         *current_process = None;
+        drop(current_process);
+        finished.store(true, Ordering::Relaxed);
+
+        // Explain the timeout and optionally retry the same prompt
+        let timed_out_kind = *timed_out.lock().unwrap();
+        let completed_session = session_id_holder_clone3.lock().unwrap().clone().unwrap_or_default();
+        let mut retrying = false;
+        if let Some(kind) = timed_out_kind {
+            let will_retry = watchdog.should_retry(run.attempt);
+            retrying = will_retry;
+            let payload = watchdog.timeout_payload(kind, &completed_session, run.attempt, will_retry);
+            if !completed_session.is_empty() {
+                let _ = app_handle_wait.emit(&format!("claude-timeout:{}", completed_session), &payload);
+            }
+            let _ = app_handle_wait.emit("claude-timeout", &payload);
+
+            if will_retry {
+                let next = ClaudeRun { attempt: run.attempt + 1, ..run };
+                log::info!("Retrying Claude execution (attempt {}/{})", next.attempt, next.timeout.max_retries);
+                if let Err(e) = retry_claude_run(app_handle_wait.clone(), next).await {
+                    log::error!("Claude retry failed to start: {}", e);
+                }
+            }
+        } else if exited_with_error {
+            // Resubmit the prompt after the backoff when the run failed on a rate limit
+            if let Some(signal) = rate_limit_watcher.signal() {
+                let requeues = run.rate_limit_requeues;
+                // The failed run already created the session: resume it instead of starting another one
+                let next = if completed_session.is_empty() {
+                    ClaudeRun { rate_limit_requeues: requeues + 1, ..run }
+                } else {
+                    ClaudeRun {
+                        kind: ClaudeRunKind::Resume(completed_session.clone()),
+                        rate_limit_requeues: requeues + 1,
+                        attachments: Vec::new(),
+                        ..run
+                    }
+                };
+                retrying = rate_limit::schedule_requeue(
+                    &app_handle_wait,
+                    "claude",
+                    &completed_session,
+                    &rate_limit_watcher.channel_id(),
+                    &signal,
+                    requeues,
+                    retry_claude_run(app_handle_wait.clone(), next),
+                );
+            }
+        } else if run_succeeded == Some(true) {
+            // Auto-fix loop: verify, then resume the session with the failure output
+            if let Some(options) = run.auto_fix.clone() {
+                let project_path = run.project_path.clone();
+                let app = app_handle_wait.clone();
+                let target = completed_session.clone();
+                retrying = auto_fix::schedule_auto_fix(
+                    &app_handle_wait,
+                    "claude",
+                    &completed_session,
+                    &project_path,
+                    &options,
+                    run.auto_fix_iteration,
+                    move |prompt| {
+                        let next = ClaudeRun {
+                            kind: ClaudeRunKind::Resume(target),
+                            prompt,
+                            attempt: 0,
+                            rate_limit_requeues: 0,
+                            attachments: Vec::new(),
+                            translation: None,
+                            auto_fix_iteration: run.auto_fix_iteration + 1,
+                            ..run
+                        };
+                        retry_claude_run(app, next)
+                    },
+                );
+            }
+        }
+
+        // Cancelled runs and runs that are about to be retried are not notified
+        let outcome = if timed_out_kind.is_some() { Some(false) } else { run_succeeded };
+        if let (Some(success), false) = (outcome, retrying) {
+            notifications::notify_run_finished(&app_handle_wait, "claude", &completed_session, success);
+        }
     });
 
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gui_run(model: Option<String>) -> ClaudeRun {
+        ClaudeRun {
+            kind: ClaudeRunKind::Execute,
+            project_path: "/tmp/project".to_string(),
+            prompt: "fix the build".to_string(),
+            model: model.unwrap_or_default(),
+            plan_mode: false,
+            max_thinking_tokens: None,
+            permission_profile: None,
+            append_system_prompt: None,
+            provider: None,
+            timeout: ExecutionTimeoutOptions::default(),
+            attempt: 0,
+            rate_limit_requeues: 0,
+            interactive: true,
+            attachments: Vec::new(),
+            translation: None,
+            auto_fix: None,
+            auto_fix_iteration: 0,
+        }
+    }
+
+    #[test]
+    fn gui_runs_without_explicit_model_use_project_default() {
+        let defaults = project_defaults::EngineDefaults {
+            model: Some("opus".to_string()),
+            permission_profile: Some("read-only".to_string()),
+            ..Default::default()
+        };
+
+        let mut run = gui_run(None);
+        assert_eq!(apply_engine_defaults(&mut run, &defaults).as_deref(), Some("opus"));
+        assert_eq!(run.permission_profile.as_deref(), Some("read-only"));
+
+        let mut run = gui_run(Some("sonnet".to_string()));
+        assert_eq!(apply_engine_defaults(&mut run, &defaults).as_deref(), Some("sonnet"));
+
+        let mut run = gui_run(None);
+        assert_eq!(apply_engine_defaults(&mut run, &project_defaults::EngineDefaults::default()), None);
+    }
+}
@@ -1,13 +1,14 @@
+use crate::tr;
+use crate::error::AnyCodeError;
 use std::fs;
 use std::path::PathBuf;
 use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tauri_plugin_shell::ShellExt;
 use regex::Regex;
 use dirs;
-use rusqlite;
 
 
 
@@ -18,12 +19,14 @@ use crate::commands::permission_config::{
     DEVELOPMENT_TOOLS, SAFE_TOOLS, ALL_TOOLS
 };
 use super::{ClaudeMdFile, ClaudeSettings, ClaudeVersionStatus};
+use crate::commands::data_root::anycode_dir;
+use crate::commands::app_settings::{delete_setting, get_setting, set_setting, CLAUDE_BINARY_PATH};
 
 #[tauri::command]
-pub async fn get_claude_settings() -> Result<ClaudeSettings, String> {
+pub async fn get_claude_settings() -> Result<ClaudeSettings, AnyCodeError> {
     log::info!("Reading Claude settings");
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| AnyCodeError::Io(e.to_string()))?;
     let settings_path = claude_dir.join("settings.json");
 
     if !settings_path.exists() {
@@ -34,24 +37,24 @@ pub async fn get_claude_settings() -> Result<ClaudeSettings, String> {
     }
 
     let content = fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read settings file: {}", e)))?;
 
     let data: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings JSON: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse settings JSON: {}", e)))?;
 
     Ok(ClaudeSettings { data })
 }
 
 /// Opens a new Claude Code session by executing the claude command
 #[tauri::command]
-pub async fn open_new_session(app: AppHandle, path: Option<String>) -> Result<String, String> {
+pub async fn open_new_session(app: AppHandle, path: Option<String>) -> Result<String, AnyCodeError> {
     log::info!("Opening new Claude Code session at path: {:?}", path);
 
     #[cfg(not(debug_assertions))]
-    let _claude_path = crate::claude_binary::find_claude_binary(&app)?;
+    let _claude_path = crate::claude_binary::find_claude_binary(&app).map_err(AnyCodeError::Engine)?;
 
     #[cfg(debug_assertions)]
-    let claude_path = crate::claude_binary::find_claude_binary(&app)?;
+    let claude_path = crate::claude_binary::find_claude_binary(&app).map_err(AnyCodeError::Engine)?;
 
     // In production, we can't use std::process::Command directly
     // The user should launch Claude Code through other means or use the execute_claude_code command
@@ -85,7 +88,7 @@ pub async fn open_new_session(app: AppHandle, path: Option<String>) -> Result<St
             }
             Err(e) => {
                 log::error!("Failed to launch Claude Code: {}", e);
-                Err(format!("Failed to launch Claude Code: {}", e))
+                Err(AnyCodeError::Engine(format!("Failed to launch Claude Code: {}", e)))
             }
         }
     }
@@ -93,10 +96,10 @@ pub async fn open_new_session(app: AppHandle, path: Option<String>) -> Result<St
 
 /// Reads the CLAUDE.md system prompt file
 #[tauri::command]
-pub async fn get_system_prompt() -> Result<String, String> {
+pub async fn get_system_prompt() -> Result<String, AnyCodeError> {
     log::info!("Reading CLAUDE.md system prompt");
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| AnyCodeError::Io(e.to_string()))?;
     let claude_md_path = claude_dir.join("CLAUDE.md");
 
     if !claude_md_path.exists() {
@@ -104,12 +107,12 @@ pub async fn get_system_prompt() -> Result<String, String> {
         return Ok(String::new());
     }
 
-    fs::read_to_string(&claude_md_path).map_err(|e| format!("Failed to read CLAUDE.md: {}", e))
+    fs::read_to_string(&claude_md_path).map_err(|e| AnyCodeError::Io(format!("Failed to read CLAUDE.md: {}", e)))
 }
 
 /// Checks if Claude Code is installed and gets its version
 #[tauri::command]
-pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus, String> {
+pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus, AnyCodeError> {
     log::info!("Checking Claude Code version");
 
     let claude_path = match crate::claude_binary::find_claude_binary(&app) {
@@ -211,22 +214,13 @@ pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus,
     use log::debug;
     debug!("Claude path: {}", claude_path);
 
-    // For system installations, try to check version
-    let mut cmd = std::process::Command::new(&claude_path);
-    cmd.arg("--version");
-    
-    // On Windows, ensure the command runs without creating a console window
-    #[cfg(target_os = "windows")]
-    {
-        platform::apply_no_window(&mut cmd);
-    }
-    
-    let output = cmd.output();
+    // For system installations, try to check version (cached by binary path + mtime)
+    let output = crate::claude_binary::probe_version_cached(&claude_path);
 
     match output {
         Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            let stdout = output.stdout;
+            let stderr = output.stderr;
             
             // Use regex to directly extract version pattern (e.g., "1.0.41")
             let version_regex = Regex::new(r"(\d+\.\d+\.\d+(?:-[a-zA-Z0-9.-]+)?(?:\+[a-zA-Z0-9.-]+)?)").ok();
@@ -249,7 +243,7 @@ pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus,
             let is_valid = stdout.contains("(Claude Code)") || stdout.contains("Claude Code");
 
             Ok(ClaudeVersionStatus {
-                is_installed: is_valid && output.status.success(),
+                is_installed: is_valid && output.success,
                 version,
                 output: full_output.trim().to_string(),
             })
@@ -267,26 +261,26 @@ pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus,
 
 /// Saves the CLAUDE.md system prompt file
 #[tauri::command]
-pub async fn save_system_prompt(content: String) -> Result<String, String> {
+pub async fn save_system_prompt(content: String) -> Result<String, AnyCodeError> {
     log::info!("Saving CLAUDE.md system prompt");
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| AnyCodeError::Io(e.to_string()))?;
     let claude_md_path = claude_dir.join("CLAUDE.md");
 
-    fs::write(&claude_md_path, content).map_err(|e| format!("Failed to write CLAUDE.md: {}", e))?;
+    fs::write(&claude_md_path, content).map_err(|e| AnyCodeError::Io(format!("Failed to write CLAUDE.md: {}", e)))?;
 
     Ok("System prompt saved successfully".to_string())
 }
 
 /// Saves the Claude settings file
 #[tauri::command]
-pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String, String> {
+pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String, AnyCodeError> {
     log::info!("Saving Claude settings - received data: {}", settings.to_string());
 
     let claude_dir = get_claude_dir().map_err(|e| {
         let error_msg = format!("Failed to get claude dir: {}", e);
         log::error!("{}", error_msg);
-        error_msg
+        AnyCodeError::Io(error_msg)
     })?;
     log::info!("Claude directory: {:?}", claude_dir);
 
@@ -328,7 +322,7 @@ pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String,
         .map_err(|e| {
             let error_msg = format!("Failed to serialize settings: {}", e);
             log::error!("{}", error_msg);
-            error_msg
+            AnyCodeError::Internal(error_msg)
         })?;
 
     log::info!("Serialized JSON length: {} characters", json_string.len());
@@ -337,7 +331,7 @@ pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String,
         .map_err(|e| {
             let error_msg = format!("Failed to write settings file: {}", e);
             log::error!("{}", error_msg);
-            error_msg
+            AnyCodeError::Io(error_msg)
         })?;
 
     log::info!("Settings saved successfully to: {:?}", settings_path);
@@ -346,18 +340,18 @@ pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String,
 
 /// Updates the thinking mode in settings.json by modifying the MAX_THINKING_TOKENS env variable
 #[tauri::command]
-pub async fn update_thinking_mode(enabled: bool, tokens: Option<u32>) -> Result<String, String> {
+pub async fn update_thinking_mode(enabled: bool, tokens: Option<u32>) -> Result<String, AnyCodeError> {
     log::info!("Updating thinking mode: enabled={}, tokens={:?}", enabled, tokens);
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| AnyCodeError::Io(e.to_string()))?;
     let settings_path = claude_dir.join("settings.json");
 
     // Read existing settings
     let mut settings = if settings_path.exists() {
         let content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read settings: {}", e)))?;
         serde_json::from_str::<serde_json::Value>(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?
+            .map_err(|e| AnyCodeError::Config(format!("Failed to parse settings: {}", e)))?
     } else {
         serde_json::json!({})
     };
@@ -373,7 +367,7 @@ pub async fn update_thinking_mode(enabled: bool, tokens: Option<u32>) -> Result<
     }
 
     let env_obj = settings_obj.get_mut("env").unwrap().as_object_mut()
-        .ok_or("env is not an object")?;
+        .ok_or_else(|| AnyCodeError::Config("env is not an object".to_string()))?;
 
     // Update MAX_THINKING_TOKENS
     if enabled {
@@ -394,10 +388,10 @@ pub async fn update_thinking_mode(enabled: bool, tokens: Option<u32>) -> Result<
 
     // Write back to file
     let json_string = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize settings: {}", e)))?;
 
     fs::write(&settings_path, &json_string)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write settings: {}", e)))?;
 
     log::info!("Thinking mode updated successfully");
     Ok(format!("Thinking mode {} successfully", if enabled { "enabled" } else { "disabled" }))
@@ -405,16 +399,16 @@ pub async fn update_thinking_mode(enabled: bool, tokens: Option<u32>) -> Result<
 
 /// Recursively finds all CLAUDE.md files in a project directory
 #[tauri::command]
-pub async fn find_claude_md_files(project_path: String) -> Result<Vec<ClaudeMdFile>, String> {
+pub async fn find_claude_md_files(project_path: String) -> Result<Vec<ClaudeMdFile>, AnyCodeError> {
     log::info!("Finding CLAUDE.md files in project: {}", project_path);
 
     let path = PathBuf::from(&project_path);
     if !path.exists() {
-        return Err(format!("Project path does not exist: {}", project_path));
+        return Err(AnyCodeError::NotFound(format!("Project path does not exist: {}", project_path)));
     }
 
     let mut claude_files = Vec::new();
-    find_claude_md_recursive(&path, &path, &mut claude_files)?;
+    find_claude_md_recursive(&path, &path, &mut claude_files).map_err(AnyCodeError::Io)?;
 
     // Sort by relative path
     claude_files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
@@ -491,20 +485,20 @@ fn find_claude_md_recursive(
 
 /// Reads a specific CLAUDE.md file by its absolute path
 #[tauri::command]
-pub async fn read_claude_md_file(file_path: String) -> Result<String, String> {
+pub async fn read_claude_md_file(file_path: String) -> Result<String, AnyCodeError> {
     log::info!("Reading CLAUDE.md file: {}", file_path);
 
     let path = PathBuf::from(&file_path);
     if !path.exists() {
-        return Err(format!("File does not exist: {}", file_path));
+        return Err(AnyCodeError::NotFound(format!("File does not exist: {}", file_path)));
     }
 
-    fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))
+    fs::read_to_string(&path).map_err(|e| AnyCodeError::Io(format!("Failed to read file: {}", e)))
 }
 
 /// Saves a specific CLAUDE.md file by its absolute path
 #[tauri::command]
-pub async fn save_claude_md_file(file_path: String, content: String) -> Result<String, String> {
+pub async fn save_claude_md_file(file_path: String, content: String) -> Result<String, AnyCodeError> {
     log::info!("Saving CLAUDE.md file: {}", file_path);
 
     let path = PathBuf::from(&file_path);
@@ -512,31 +506,31 @@ pub async fn save_claude_md_file(file_path: String, content: String) -> Result<S
     // Ensure the parent directory exists
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create parent directory: {}", e)))?;
     }
 
-    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))?;
+    fs::write(&path, content).map_err(|e| AnyCodeError::Io(format!("Failed to write file: {}", e)))?;
 
     Ok("File saved successfully".to_string())
 }
 #[tauri::command]
-pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Result<(), String> {
+pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Result<(), AnyCodeError> {
     log::info!("Setting custom Claude CLI path: {}", custom_path);
 
-    let expanded_path = expand_user_path(&custom_path)?;
+    let expanded_path = expand_user_path(&custom_path).map_err(AnyCodeError::Validation)?;
 
     // Validate the path exists and is executable
     if !expanded_path.exists() {
-        return Err("File does not exist".to_string());
+        return Err(AnyCodeError::NotFound("File does not exist".to_string()));
     }
 
     if !expanded_path.is_file() {
-        return Err("Path is not a file".to_string());
+        return Err(AnyCodeError::Io("Path is not a file".to_string()));
     }
 
     let path_str = expanded_path
         .to_str()
-        .ok_or_else(|| "Invalid path encoding".to_string())?
+        .ok_or_else(|| AnyCodeError::Validation("Invalid path encoding".to_string()))?
         .to_string();
 
     // Test if it's actually Claude CLI by running --version
@@ -551,47 +545,19 @@ pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Resu
     match cmd.output() {
         Ok(output) => {
             if !output.status.success() {
-                return Err("File is not a valid Claude CLI executable".to_string());
+                return Err(AnyCodeError::Engine("File is not a valid Claude CLI executable".to_string()));
             }
         }
         Err(e) => {
-            return Err(format!("Failed to test Claude CLI: {}", e));
+            return Err(AnyCodeError::Engine(format!("Failed to test Claude CLI: {}", e)));
         }
     }
 
     // Store the custom path in database
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        if let Err(e) = std::fs::create_dir_all(&app_data_dir) {
-            return Err(format!("Failed to create app data directory: {}", e));
-        }
-
-        let db_path = app_data_dir.join("agents.db");
-        match rusqlite::Connection::open(&db_path) {
-            Ok(conn) => {
-                if let Err(e) = conn.execute(
-                    "CREATE TABLE IF NOT EXISTS app_settings (
-                        key TEXT PRIMARY KEY,
-                        value TEXT NOT NULL
-                    )",
-                    [],
-                ) {
-                    return Err(format!("Failed to create settings table: {}", e));
-                }
-
-                if let Err(e) = conn.execute(
-                    "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-                    rusqlite::params!["claude_binary_path", path_str],
-                ) {
-                    return Err(format!("Failed to store custom Claude path: {}", e));
-                }
-
-                log::info!("Successfully stored custom Claude CLI path: {}", path_str);
-            }
-            Err(e) => return Err(format!("Failed to open database: {}", e)),
-        }
-    } else {
-        return Err("Failed to get app data directory".to_string());
-    }
+    set_setting(&app, CLAUDE_BINARY_PATH, &path_str)
+        .await
+        .map_err(AnyCodeError::Database)?;
+    log::info!("Successfully stored custom Claude CLI path: {}", path_str);
 
     // 记录到 binaries.json 供跨平台检测复用
     if let Err(e) = update_binary_override("claude", &path_str) {
@@ -603,24 +569,13 @@ pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Resu
 
 /// Get current Claude CLI path (custom or auto-detected)
 #[tauri::command]
-pub async fn get_claude_path(app: AppHandle) -> Result<String, String> {
+pub async fn get_claude_path(app: AppHandle) -> Result<String, AnyCodeError> {
     log::info!("Getting current Claude CLI path");
     
     // Try to get from database first
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let db_path = app_data_dir.join("agents.db");
-        if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                if let Ok(stored_path) = conn.query_row(
-                    "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
-                    [],
-                    |row| row.get::<_, String>(0),
-                ) {
-                    log::info!("Found stored Claude path: {}", stored_path);
-                    return Ok(stored_path);
-                }
-            }
-        }
+    if let Ok(Some(stored_path)) = get_setting::<String>(CLAUDE_BINARY_PATH).await {
+        log::info!("Found stored Claude path: {}", stored_path);
+        return Ok(stored_path);
     }
     
     // Fall back to auto-detection
@@ -629,38 +584,26 @@ pub async fn get_claude_path(app: AppHandle) -> Result<String, String> {
             log::info!("Auto-detected Claude path: {}", path);
             Ok(path)
         }
-        Err(e) => Err(e),
+        Err(e) => Err(AnyCodeError::Engine(e)),
     }
 }
 
 /// Clear custom Claude CLI path and revert to auto-detection
 #[tauri::command]
-pub async fn clear_custom_claude_path(app: AppHandle) -> Result<(), String> {
+pub async fn clear_custom_claude_path(app: AppHandle) -> Result<(), AnyCodeError> {
     log::info!("Clearing custom Claude CLI path");
-    
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let db_path = app_data_dir.join("agents.db");
-        if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                if let Err(e) = conn.execute(
-                    "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                    [],
-                ) {
-                    return Err(format!("Failed to clear custom Claude path: {}", e));
-                }
-            }
-        }
 
-        // 清理 binaries.json 覆盖记录（忽略错误）
-        if let Err(e) = clear_binary_override("claude") {
-            log::warn!("Failed to clear binaries.json override: {}", e);
-        }
+    delete_setting(&app, CLAUDE_BINARY_PATH)
+        .await
+        .map_err(AnyCodeError::Database)?;
 
-        log::info!("Successfully cleared custom Claude CLI path");
-        return Ok(());
+    // 清理 binaries.json 覆盖记录（忽略错误）
+    if let Err(e) = clear_binary_override("claude") {
+        log::warn!("Failed to clear binaries.json override: {}", e);
     }
 
-    Err("Failed to get app data directory".to_string())
+    log::info!("Successfully cleared custom Claude CLI path");
+    Ok(())
 }
 
 fn expand_user_path(input: &str) -> Result<PathBuf, String> {
@@ -727,6 +670,7 @@ fn update_binary_override(tool: &str, override_path: &str) -> Result<(), String>
         .map_err(|e| format!("Failed to serialize binaries.json: {}", e))?;
     std::fs::write(&config_path, serialized)
         .map_err(|e| format!("Failed to write binaries.json: {}", e))?;
+    crate::claude_binary::invalidate_binary_cache(tool);
 
     Ok(())
 }
@@ -755,14 +699,15 @@ fn clear_binary_override(tool: &str) -> Result<(), String> {
         .map_err(|e| format!("Failed to serialize binaries.json: {}", e))?;
     std::fs::write(&config_path, serialized)
         .map_err(|e| format!("Failed to write binaries.json: {}", e))?;
+    crate::claude_binary::invalidate_binary_cache(tool);
 
     Ok(())
 }
 /// 获取当前Claude执行配置
 #[tauri::command]
-pub async fn get_claude_execution_config(_app: AppHandle) -> Result<ClaudeExecutionConfig, String> {
+pub async fn get_claude_execution_config(_app: AppHandle) -> Result<ClaudeExecutionConfig, AnyCodeError> {
     let claude_dir = get_claude_dir()
-        .map_err(|e| format!("Failed to get Claude directory: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to get Claude directory: {}", e)))?;
     let config_file = claude_dir.join("execution_config.json");
     
     if config_file.exists() {
@@ -795,16 +740,16 @@ pub async fn get_claude_execution_config(_app: AppHandle) -> Result<ClaudeExecut
 pub async fn update_claude_execution_config(
     _app: AppHandle,
     config: ClaudeExecutionConfig,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     let claude_dir = get_claude_dir()
-        .map_err(|e| format!("Failed to get Claude directory: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to get Claude directory: {}", e)))?;
     let config_file = claude_dir.join("execution_config.json");
     
     let json_string = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize config: {}", e)))?;
         
     fs::write(&config_file, json_string)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write config file: {}", e)))?;
         
     log::info!("Updated Claude execution config");
     Ok(())
@@ -812,14 +757,14 @@ pub async fn update_claude_execution_config(
 
 /// 重置Claude执行配置为默认值
 #[tauri::command]
-pub async fn reset_claude_execution_config(app: AppHandle) -> Result<(), String> {
+pub async fn reset_claude_execution_config(app: AppHandle) -> Result<(), AnyCodeError> {
     let config = ClaudeExecutionConfig::default();
     update_claude_execution_config(app, config).await
 }
 
 /// 获取当前权限配置
 #[tauri::command]
-pub async fn get_claude_permission_config(app: AppHandle) -> Result<ClaudePermissionConfig, String> {
+pub async fn get_claude_permission_config(app: AppHandle) -> Result<ClaudePermissionConfig, AnyCodeError> {
     let execution_config = get_claude_execution_config(app).await?;
     Ok(execution_config.permissions)
 }
@@ -829,7 +774,7 @@ pub async fn get_claude_permission_config(app: AppHandle) -> Result<ClaudePermis
 pub async fn update_claude_permission_config(
     app: AppHandle,
     permission_config: ClaudePermissionConfig,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     let mut execution_config = get_claude_execution_config(app.clone()).await?;
     execution_config.permissions = permission_config;
     update_claude_execution_config(app, execution_config).await
@@ -837,7 +782,7 @@ pub async fn update_claude_permission_config(
 
 /// 获取预设权限配置选项
 #[tauri::command]
-pub async fn get_permission_presets() -> Result<serde_json::Value, String> {
+pub async fn get_permission_presets() -> Result<serde_json::Value, AnyCodeError> {
     let presets = serde_json::json!({
         "development": {
             "name": "开发模式",
@@ -866,7 +811,7 @@ pub async fn get_permission_presets() -> Result<serde_json::Value, String> {
 
 /// 获取可用工具列表
 #[tauri::command]
-pub async fn get_available_tools() -> Result<serde_json::Value, String> {
+pub async fn get_available_tools() -> Result<serde_json::Value, AnyCodeError> {
     let tools = serde_json::json!({
         "development_tools": DEVELOPMENT_TOOLS,
         "safe_tools": SAFE_TOOLS,
@@ -880,7 +825,7 @@ pub async fn get_available_tools() -> Result<serde_json::Value, String> {
 #[tauri::command]
 pub async fn validate_permission_config(
     config: ClaudePermissionConfig,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AnyCodeError> {
     let mut validation_result = serde_json::json!({
         "valid": true,
         "warnings": [],
@@ -948,12 +893,12 @@ fn get_effective_codex_dir() -> Result<(std::path::PathBuf, bool), String> {
 /// Reads the AGENTS.md system prompt file from Codex directory
 /// Automatically selects Windows native or WSL path based on current Codex mode
 #[tauri::command]
-pub async fn get_codex_system_prompt() -> Result<String, String> {
+pub async fn get_codex_system_prompt() -> Result<String, AnyCodeError> {
     log::info!("Reading AGENTS.md system prompt from Codex directory");
 
     let (codex_dir, is_wsl) = get_effective_codex_dir().map_err(|e| {
         log::error!("Failed to get Codex directory: {}", e);
-        e
+        AnyCodeError::Io(e)
     })?;
     
     log::info!("Using Codex directory: {:?} (WSL mode: {})", codex_dir, is_wsl);
@@ -968,18 +913,18 @@ pub async fn get_codex_system_prompt() -> Result<String, String> {
     fs::read_to_string(&agents_md_path).map_err(|e| {
         log::error!("Failed to read AGENTS.md: {}", e);
         format!("读取 AGENTS.md 失败: {}", e)
-    })
+    }).map_err(AnyCodeError::Io)
 }
 
 /// Saves the AGENTS.md system prompt file to Codex directory
 /// Automatically selects Windows native or WSL path based on current Codex mode
 #[tauri::command]
-pub async fn save_codex_system_prompt(content: String) -> Result<String, String> {
+pub async fn save_codex_system_prompt(content: String) -> Result<String, AnyCodeError> {
     log::info!("Saving AGENTS.md system prompt to Codex directory");
 
     let (codex_dir, is_wsl) = get_effective_codex_dir().map_err(|e| {
         log::error!("Failed to get Codex directory: {}", e);
-        e
+        AnyCodeError::Io(e)
     })?;
     
     log::info!("Using Codex directory: {:?} (WSL mode: {})", codex_dir, is_wsl);
@@ -988,7 +933,7 @@ pub async fn save_codex_system_prompt(content: String) -> Result<String, String>
 
     fs::write(&agents_md_path, content).map_err(|e| {
         log::error!("Failed to write AGENTS.md: {}", e);
-        format!("保存 AGENTS.md 失败: {}", e)
+        AnyCodeError::Io(format!("保存 AGENTS.md 失败: {}", e))
     })?;
 
     log::info!("Successfully saved AGENTS.md to {:?}", agents_md_path);
@@ -1081,11 +1026,11 @@ fn save_prompts_config(config: &CodexPromptsConfig) -> Result<(), String> {
 
 /// Lists all Codex prompt templates
 #[tauri::command]
-pub async fn list_codex_prompts() -> Result<Vec<CodexPromptTemplate>, String> {
+pub async fn list_codex_prompts() -> Result<Vec<CodexPromptTemplate>, AnyCodeError> {
     log::info!("Listing Codex prompt templates");
     
-    let (prompts_dir, _) = get_codex_prompts_dir()?;
-    let config = load_prompts_config()?;
+    let (prompts_dir, _) = get_codex_prompts_dir().map_err(AnyCodeError::Io)?;
+    let config = load_prompts_config().map_err(AnyCodeError::Config)?;
     
     let mut templates = Vec::new();
     
@@ -1138,36 +1083,36 @@ pub async fn list_codex_prompts() -> Result<Vec<CodexPromptTemplate>, String> {
 
 /// Gets a specific Codex prompt template content
 #[tauri::command]
-pub async fn get_codex_prompt(id: String) -> Result<String, String> {
+pub async fn get_codex_prompt(id: String) -> Result<String, AnyCodeError> {
     log::info!("Getting Codex prompt template: {}", id);
     
-    let (prompts_dir, _) = get_codex_prompts_dir()?;
+    let (prompts_dir, _) = get_codex_prompts_dir().map_err(AnyCodeError::Io)?;
     let prompt_path = prompts_dir.join(format!("{}.md", id));
     
     if !prompt_path.exists() {
-        return Err(format!("提示词模板不存在: {}", id));
+        return Err(AnyCodeError::NotFound(tr!("prompt_template.not_found", id = id)));
     }
     
     fs::read_to_string(&prompt_path).map_err(|e| {
         format!("读取提示词模板失败: {}", e)
-    })
+    }).map_err(AnyCodeError::Io)
 }
 
 /// Creates or updates a Codex prompt template
 #[tauri::command]
-pub async fn save_codex_prompt(id: String, content: String) -> Result<String, String> {
+pub async fn save_codex_prompt(id: String, content: String) -> Result<String, AnyCodeError> {
     log::info!("Saving Codex prompt template: {}", id);
     
     // Validate ID (only alphanumeric, dash, underscore)
     if !id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
-        return Err("提示词ID只能包含字母、数字、横线和下划线".to_string());
+        return Err(AnyCodeError::Validation("提示词ID只能包含字母、数字、横线和下划线".to_string()));
     }
     
-    let (prompts_dir, _) = get_codex_prompts_dir()?;
+    let (prompts_dir, _) = get_codex_prompts_dir().map_err(AnyCodeError::Io)?;
     let prompt_path = prompts_dir.join(format!("{}.md", id));
     
     fs::write(&prompt_path, content).map_err(|e| {
-        format!("保存提示词模板失败: {}", e)
+        AnyCodeError::Io(format!("保存提示词模板失败: {}", e))
     })?;
     
     log::info!("Successfully saved Codex prompt template: {}", id);
@@ -1176,14 +1121,14 @@ pub async fn save_codex_prompt(id: String, content: String) -> Result<String, St
 
 /// Renames a Codex prompt template (changes the template ID / filename)
 #[tauri::command]
-pub async fn rename_codex_prompt(old_id: String, new_id: String) -> Result<String, String> {
+pub async fn rename_codex_prompt(old_id: String, new_id: String) -> Result<String, AnyCodeError> {
     let old_id = old_id.trim().to_string();
     let new_id = new_id.trim().to_string();
 
     log::info!("Renaming Codex prompt template: {} -> {}", old_id, new_id);
 
     if old_id.is_empty() || new_id.is_empty() {
-        return Err("提示词名称不能为空".to_string());
+        return Err(AnyCodeError::Validation("提示词名称不能为空".to_string()));
     }
 
     if old_id == new_id {
@@ -1192,13 +1137,13 @@ pub async fn rename_codex_prompt(old_id: String, new_id: String) -> Result<Strin
 
     // Validate new ID (only alphanumeric, dash, underscore)
     if !new_id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
-        return Err("提示词名称只能包含字母、数字、横线和下划线".to_string());
+        return Err(AnyCodeError::Validation("提示词名称只能包含字母、数字、横线和下划线".to_string()));
     }
 
-    let (prompts_dir, _) = get_codex_prompts_dir()?;
+    let (prompts_dir, _) = get_codex_prompts_dir().map_err(AnyCodeError::Io)?;
     let old_path = prompts_dir.join(format!("{}.md", old_id));
     if !old_path.exists() {
-        return Err(format!("提示词模板不存在: {}", old_id));
+        return Err(AnyCodeError::NotFound(tr!("prompt_template.not_found", id = old_id)));
     }
 
     let new_path = prompts_dir.join(format!("{}.md", new_id));
@@ -1212,7 +1157,7 @@ pub async fn rename_codex_prompt(old_id: String, new_id: String) -> Result<Strin
             _ => false,
         };
         if !(case_only && same_file) {
-            return Err(format!("目标名称已存在: {}", new_id));
+            return Err(AnyCodeError::Validation(format!("目标名称已存在: {}", new_id)));
         }
     }
 
@@ -1227,22 +1172,22 @@ pub async fn rename_codex_prompt(old_id: String, new_id: String) -> Result<Strin
         }
 
         fs::rename(&old_path, &temp_path).map_err(|e| {
-            format!("重命名提示词模板失败: {}", e)
+            AnyCodeError::Io(format!("重命名提示词模板失败: {}", e))
         })?;
         fs::rename(&temp_path, &new_path).map_err(|e| {
-            format!("重命名提示词模板失败: {}", e)
+            AnyCodeError::Io(format!("重命名提示词模板失败: {}", e))
         })?;
     } else {
         fs::rename(&old_path, &new_path).map_err(|e| {
-            format!("重命名提示词模板失败: {}", e)
+            AnyCodeError::Io(format!("重命名提示词模板失败: {}", e))
         })?;
     }
 
     // Update config if needed
-    let mut config = load_prompts_config()?;
+    let mut config = load_prompts_config().map_err(AnyCodeError::Config)?;
     if config.active_prompt_id.as_deref() == Some(&old_id) {
         config.active_prompt_id = Some(new_id.clone());
-        save_prompts_config(&config)?;
+        save_prompts_config(&config).map_err(AnyCodeError::Io)?;
     }
 
     log::info!("Successfully renamed Codex prompt template: {} -> {}", old_id, new_id);
@@ -1251,34 +1196,34 @@ pub async fn rename_codex_prompt(old_id: String, new_id: String) -> Result<Strin
 
 /// Deletes a Codex prompt template
 #[tauri::command]
-pub async fn delete_codex_prompt(id: String) -> Result<String, String> {
+pub async fn delete_codex_prompt(id: String) -> Result<String, AnyCodeError> {
     log::info!("Deleting Codex prompt template: {}", id);
     
-    let (prompts_dir, _) = get_codex_prompts_dir()?;
+    let (prompts_dir, _) = get_codex_prompts_dir().map_err(AnyCodeError::Io)?;
     let prompt_path = prompts_dir.join(format!("{}.md", id));
     
     if !prompt_path.exists() {
-        return Err(format!("提示词模板不存在: {}", id));
+        return Err(AnyCodeError::NotFound(tr!("prompt_template.not_found", id = id)));
     }
     
     // If this is the active prompt, deactivate it first
-    let mut config = load_prompts_config()?;
+    let mut config = load_prompts_config().map_err(AnyCodeError::Config)?;
     if config.active_prompt_id.as_deref() == Some(&id) {
         config.active_prompt_id = None;
-        save_prompts_config(&config)?;
+        save_prompts_config(&config).map_err(AnyCodeError::Io)?;
         
         // Also clear the AGENTS.md file
-        let (codex_dir, _) = get_effective_codex_dir()?;
+        let (codex_dir, _) = get_effective_codex_dir().map_err(AnyCodeError::Io)?;
         let agents_md_path = codex_dir.join("AGENTS.md");
         if agents_md_path.exists() {
             fs::write(&agents_md_path, "").map_err(|e| {
-                format!("清空 AGENTS.md 失败: {}", e)
+                AnyCodeError::Io(format!("清空 AGENTS.md 失败: {}", e))
             })?;
         }
     }
     
     fs::remove_file(&prompt_path).map_err(|e| {
-        format!("删除提示词模板失败: {}", e)
+        AnyCodeError::Io(format!("删除提示词模板失败: {}", e))
     })?;
     
     log::info!("Successfully deleted Codex prompt template: {}", id);
@@ -1287,33 +1232,33 @@ pub async fn delete_codex_prompt(id: String) -> Result<String, String> {
 
 /// Activates a Codex prompt template (copies it to AGENTS.md)
 #[tauri::command]
-pub async fn activate_codex_prompt(id: String) -> Result<String, String> {
+pub async fn activate_codex_prompt(id: String) -> Result<String, AnyCodeError> {
     log::info!("Activating Codex prompt template: {}", id);
     
-    let (prompts_dir, _) = get_codex_prompts_dir()?;
+    let (prompts_dir, _) = get_codex_prompts_dir().map_err(AnyCodeError::Io)?;
     let prompt_path = prompts_dir.join(format!("{}.md", id));
     
     if !prompt_path.exists() {
-        return Err(format!("提示词模板不存在: {}", id));
+        return Err(AnyCodeError::NotFound(tr!("prompt_template.not_found", id = id)));
     }
     
     // Read the template content
     let content = fs::read_to_string(&prompt_path).map_err(|e| {
-        format!("读取提示词模板失败: {}", e)
+        AnyCodeError::Io(format!("读取提示词模板失败: {}", e))
     })?;
     
     // Write to AGENTS.md
-    let (codex_dir, _) = get_effective_codex_dir()?;
+    let (codex_dir, _) = get_effective_codex_dir().map_err(AnyCodeError::Io)?;
     let agents_md_path = codex_dir.join("AGENTS.md");
     
     fs::write(&agents_md_path, &content).map_err(|e| {
-        format!("写入 AGENTS.md 失败: {}", e)
+        AnyCodeError::Io(format!("写入 AGENTS.md 失败: {}", e))
     })?;
     
     // Update config
-    let mut config = load_prompts_config()?;
+    let mut config = load_prompts_config().map_err(AnyCodeError::Config)?;
     config.active_prompt_id = Some(id.clone());
-    save_prompts_config(&config)?;
+    save_prompts_config(&config).map_err(AnyCodeError::Io)?;
     
     log::info!("Successfully activated Codex prompt template: {}", id);
     Ok(format!("提示词模板 '{}' 已激活", id))
@@ -1321,23 +1266,23 @@ pub async fn activate_codex_prompt(id: String) -> Result<String, String> {
 
 /// Deactivates the current Codex prompt (clears AGENTS.md)
 #[tauri::command]
-pub async fn deactivate_codex_prompt() -> Result<String, String> {
+pub async fn deactivate_codex_prompt() -> Result<String, AnyCodeError> {
     log::info!("Deactivating current Codex prompt");
     
     // Clear AGENTS.md
-    let (codex_dir, _) = get_effective_codex_dir()?;
+    let (codex_dir, _) = get_effective_codex_dir().map_err(AnyCodeError::Io)?;
     let agents_md_path = codex_dir.join("AGENTS.md");
     
     if agents_md_path.exists() {
         fs::write(&agents_md_path, "").map_err(|e| {
-            format!("清空 AGENTS.md 失败: {}", e)
+            AnyCodeError::Io(format!("清空 AGENTS.md 失败: {}", e))
         })?;
     }
     
     // Update config
-    let mut config = load_prompts_config()?;
+    let mut config = load_prompts_config().map_err(AnyCodeError::Config)?;
     config.active_prompt_id = None;
-    save_prompts_config(&config)?;
+    save_prompts_config(&config).map_err(AnyCodeError::Io)?;
     
     log::info!("Successfully deactivated Codex prompt");
     Ok("已停用当前提示词".to_string())
@@ -1345,8 +1290,8 @@ pub async fn deactivate_codex_prompt() -> Result<String, String> {
 
 /// Gets the currently active prompt ID
 #[tauri::command]
-pub async fn get_active_codex_prompt_id() -> Result<Option<String>, String> {
-    let config = load_prompts_config()?;
+pub async fn get_active_codex_prompt_id() -> Result<Option<String>, AnyCodeError> {
+    let config = load_prompts_config().map_err(AnyCodeError::Config)?;
     Ok(config.active_prompt_id)
 }
 
@@ -1355,11 +1300,11 @@ pub async fn get_active_codex_prompt_id() -> Result<Option<String>, String> {
 // Project-Level AGENTS.md Management
 // ============================================================================
 
-/// Status of AGENTS.md file in a project directory
+/// Status of a project-level prompt file (AGENTS.md / GEMINI.md) in a project directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentsMdStatus {
-    /// Whether AGENTS.md exists in the project directory
+    /// Whether the prompt file exists in the project directory
     pub exists: bool,
     /// Whether a backup file exists
     pub has_backup: bool,
@@ -1381,27 +1326,27 @@ pub struct ActivationResult {
 
 /// Check if AGENTS.md exists in the project directory
 #[tauri::command]
-pub async fn check_project_agents_md(project_path: String) -> Result<AgentsMdStatus, String> {
+pub async fn check_project_agents_md(project_path: String) -> Result<AgentsMdStatus, AnyCodeError> {
     log::info!("Checking AGENTS.md status in project: {}", project_path);
     
     // Validate project path
     if project_path.trim().is_empty() {
-        return Err("项目路径不能为空".to_string());
+        return Err(AnyCodeError::Validation("项目路径不能为空".to_string()));
     }
     
     let project_dir = std::path::PathBuf::from(&project_path);
     if !project_dir.exists() {
-        return Err(format!("项目路径不存在: {}", project_path));
+        return Err(AnyCodeError::NotFound(tr!("project.path_not_found", path = project_path)));
     }
     if !project_dir.is_dir() {
-        return Err(format!("项目路径不是目录: {}", project_path));
+        return Err(AnyCodeError::Io(format!("项目路径不是目录: {}", project_path)));
     }
     
     let agents_md_path = project_dir.join("AGENTS.md");
     let backup_path = project_dir.join("AGENTS.md.backup");
     
     let exists = agents_md_path.exists();
-    let has_backup = backup_path.exists() || has_timestamped_backup(&project_dir);
+    let has_backup = backup_path.exists() || has_timestamped_backup(&project_dir, "AGENTS.md");
     
     let content_preview = if exists {
         match fs::read_to_string(&agents_md_path) {
@@ -1428,12 +1373,13 @@ pub async fn check_project_agents_md(project_path: String) -> Result<AgentsMdSta
     })
 }
 
-/// Check if any timestamped backup file exists
-fn has_timestamped_backup(project_dir: &std::path::Path) -> bool {
+/// Check if any timestamped backup of `file_name` exists
+pub(crate) fn has_timestamped_backup(project_dir: &std::path::Path, file_name: &str) -> bool {
+    let prefix = format!("{}.backup.", file_name);
     if let Ok(entries) = fs::read_dir(project_dir) {
         for entry in entries.flatten() {
             if let Some(name) = entry.file_name().to_str() {
-                if name.starts_with("AGENTS.md.backup.") {
+                if name.starts_with(&prefix) {
                     return true;
                 }
             }
@@ -1443,19 +1389,19 @@ fn has_timestamped_backup(project_dir: &std::path::Path) -> bool {
 }
 
 
-/// Generate a unique backup filename
+/// Generate a unique backup filename for `file_name` (e.g. "AGENTS.md")
 /// Returns "AGENTS.md.backup" if it doesn't exist, otherwise "AGENTS.md.backup.{timestamp}"
-fn generate_backup_filename(project_dir: &std::path::Path) -> String {
-    let default_backup = "AGENTS.md.backup";
-    let default_path = project_dir.join(default_backup);
+pub(crate) fn generate_backup_filename(project_dir: &std::path::Path, file_name: &str) -> String {
+    let default_backup = format!("{}.backup", file_name);
+    let default_path = project_dir.join(&default_backup);
     
     if !default_path.exists() {
-        return default_backup.to_string();
+        return default_backup;
     }
     
     // Generate timestamped filename
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    format!("AGENTS.md.backup.{}", timestamp)
+    format!("{}.backup.{}", file_name, timestamp)
 }
 
 /// Activate a Codex prompt template to a project directory
@@ -1464,33 +1410,33 @@ pub async fn activate_codex_prompt_to_project(
     id: String,
     project_path: String,
     backup_existing: bool,
-) -> Result<ActivationResult, String> {
+) -> Result<ActivationResult, AnyCodeError> {
     log::info!("Activating Codex prompt '{}' to project: {}", id, project_path);
     
     // Validate project path
     if project_path.trim().is_empty() {
-        return Err("项目路径不能为空".to_string());
+        return Err(AnyCodeError::Validation("项目路径不能为空".to_string()));
     }
     
     let project_dir = std::path::PathBuf::from(&project_path);
     if !project_dir.exists() {
-        return Err(format!("项目路径不存在: {}", project_path));
+        return Err(AnyCodeError::NotFound(tr!("project.path_not_found", path = project_path)));
     }
     if !project_dir.is_dir() {
-        return Err(format!("项目路径不是目录: {}", project_path));
+        return Err(AnyCodeError::Io(format!("项目路径不是目录: {}", project_path)));
     }
     
     // Get the prompt template
-    let (prompts_dir, _) = get_codex_prompts_dir()?;
+    let (prompts_dir, _) = get_codex_prompts_dir().map_err(AnyCodeError::Io)?;
     let prompt_path = prompts_dir.join(format!("{}.md", id));
     
     if !prompt_path.exists() {
-        return Err(format!("提示词模板不存在: {}", id));
+        return Err(AnyCodeError::NotFound(tr!("prompt_template.not_found", id = id)));
     }
     
     // Read the template content
     let content = fs::read_to_string(&prompt_path).map_err(|e| {
-        format!("读取提示词模板失败: {}", e)
+        AnyCodeError::Io(format!("读取提示词模板失败: {}", e))
     })?;
     
     let agents_md_path = project_dir.join("AGENTS.md");
@@ -1498,11 +1444,11 @@ pub async fn activate_codex_prompt_to_project(
     
     // Backup existing file if requested and exists
     if backup_existing && agents_md_path.exists() {
-        let backup_filename = generate_backup_filename(&project_dir);
+        let backup_filename = generate_backup_filename(&project_dir, "AGENTS.md");
         let backup_path = project_dir.join(&backup_filename);
         
         fs::copy(&agents_md_path, &backup_path).map_err(|e| {
-            format!("备份文件失败: {}", e)
+            AnyCodeError::Io(format!("备份文件失败: {}", e))
         })?;
         
         backup_path_result = Some(backup_path.to_string_lossy().to_string());
@@ -1511,7 +1457,7 @@ pub async fn activate_codex_prompt_to_project(
     
     // Write the new content
     fs::write(&agents_md_path, &content).map_err(|e| {
-        format!("写入 AGENTS.md 失败: {}", e)
+        AnyCodeError::Io(format!("写入 AGENTS.md 失败: {}", e))
     })?;
     
     let message = if let Some(ref backup) = backup_path_result {
@@ -1530,9 +1476,10 @@ pub async fn activate_codex_prompt_to_project(
 }
 
 
-/// Find the most recent backup file in the project directory
-fn find_latest_backup(project_dir: &std::path::Path) -> Option<std::path::PathBuf> {
-    let default_backup = project_dir.join("AGENTS.md.backup");
+/// Find the most recent backup of `file_name` in the project directory
+pub(crate) fn find_latest_backup(project_dir: &std::path::Path, file_name: &str) -> Option<std::path::PathBuf> {
+    let default_backup = project_dir.join(format!("{}.backup", file_name));
+    let prefix = format!("{}.backup.", file_name);
     
     // First check for the default backup file
     if default_backup.exists() {
@@ -1545,7 +1492,7 @@ fn find_latest_backup(project_dir: &std::path::Path) -> Option<std::path::PathBu
     if let Ok(entries) = fs::read_dir(project_dir) {
         for entry in entries.flatten() {
             if let Some(name) = entry.file_name().to_str() {
-                if name.starts_with("AGENTS.md.backup.") {
+                if name.starts_with(&prefix) {
                     if let Ok(metadata) = entry.metadata() {
                         if let Ok(modified) = metadata.modified() {
                             match &latest_backup {
@@ -1572,33 +1519,33 @@ fn find_latest_backup(project_dir: &std::path::Path) -> Option<std::path::PathBu
 pub async fn deactivate_codex_prompt_from_project(
     project_path: String,
     restore_backup: bool,
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     log::info!("Deactivating Codex prompt from project: {}, restore_backup: {}", project_path, restore_backup);
     
     // Validate project path
     if project_path.trim().is_empty() {
-        return Err("项目路径不能为空".to_string());
+        return Err(AnyCodeError::Validation("项目路径不能为空".to_string()));
     }
     
     let project_dir = std::path::PathBuf::from(&project_path);
     if !project_dir.exists() {
-        return Err(format!("项目路径不存在: {}", project_path));
+        return Err(AnyCodeError::NotFound(tr!("project.path_not_found", path = project_path)));
     }
     if !project_dir.is_dir() {
-        return Err(format!("项目路径不是目录: {}", project_path));
+        return Err(AnyCodeError::Io(format!("项目路径不是目录: {}", project_path)));
     }
     
     let agents_md_path = project_dir.join("AGENTS.md");
     
     if restore_backup {
         // Find and restore the backup
-        if let Some(backup_path) = find_latest_backup(&project_dir) {
+        if let Some(backup_path) = find_latest_backup(&project_dir, "AGENTS.md") {
             let backup_content = fs::read_to_string(&backup_path).map_err(|e| {
-                format!("读取备份文件失败: {}", e)
+                AnyCodeError::Io(format!("读取备份文件失败: {}", e))
             })?;
             
             fs::write(&agents_md_path, &backup_content).map_err(|e| {
-                format!("恢复备份失败: {}", e)
+                AnyCodeError::Io(format!("恢复备份失败: {}", e))
             })?;
             
             // Optionally remove the backup file after restoration
@@ -1607,13 +1554,13 @@ pub async fn deactivate_codex_prompt_from_project(
             log::info!("Restored backup from: {:?}", backup_path);
             return Ok("已恢复备份文件".to_string());
         } else {
-            return Err("未找到备份文件".to_string());
+            return Err(AnyCodeError::NotFound("未找到备份文件".to_string()));
         }
     } else {
         // Clear the AGENTS.md file
         if agents_md_path.exists() {
             fs::write(&agents_md_path, "").map_err(|e| {
-                format!("清空 AGENTS.md 失败: {}", e)
+                AnyCodeError::Io(format!("清空 AGENTS.md 失败: {}", e))
             })?;
         }
         
@@ -1622,6 +1569,228 @@ pub async fn deactivate_codex_prompt_from_project(
     }
 }
 
+// ============================================================================
+// CLAUDE.md Template Library
+// ============================================================================
+
+/// CLAUDE.md template metadata (templates live in ~/.anycode/claude_md_templates)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeMdTemplate {
+    /// Unique identifier (filename without extension)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// First markdown heading of the template
+    pub description: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Gets the CLAUDE.md templates directory, creating it if needed
+fn get_claude_md_templates_dir() -> Result<PathBuf, String> {
+    let dir = get_anycode_dir()?.join("claude_md_templates");
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("无法创建模板目录: {}", e))?;
+    }
+    Ok(dir)
+}
+
+fn get_claude_md_template_path(id: &str) -> Result<PathBuf, String> {
+    // Templates added by hand may use other characters; only path components are rejected
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("无效的 CLAUDE.md 模板名称: {}", id));
+    }
+    let path = get_claude_md_templates_dir()?.join(format!("{}.md", id));
+    if !path.exists() {
+        return Err(format!("CLAUDE.md 模板不存在: {}", id));
+    }
+    Ok(path)
+}
+
+/// Validates a project path and returns it as a directory
+fn get_valid_project_dir(project_path: &str) -> Result<PathBuf, String> {
+    if project_path.trim().is_empty() {
+        return Err("项目路径不能为空".to_string());
+    }
+    let project_dir = PathBuf::from(project_path);
+    if !project_dir.exists() {
+        return Err(tr!("project.path_not_found", path = project_path));
+    }
+    if !project_dir.is_dir() {
+        return Err(format!("项目路径不是目录: {}", project_path));
+    }
+    Ok(project_dir)
+}
+
+/// Lists all CLAUDE.md templates, most recently updated first
+#[tauri::command]
+pub async fn list_claude_md_templates() -> Result<Vec<ClaudeMdTemplate>, AnyCodeError> {
+    let templates = list_claude_md_templates_in(&get_claude_md_templates_dir().map_err(AnyCodeError::Io)?);
+    log::info!("Found {} CLAUDE.md templates", templates.len());
+    Ok(templates)
+}
+
+fn list_claude_md_templates_in(templates_dir: &std::path::Path) -> Vec<ClaudeMdTemplate> {
+    let to_secs = |time: std::io::Result<SystemTime>| {
+        time.ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    };
+
+    let mut templates = Vec::new();
+    if let Ok(entries) = fs::read_dir(templates_dir) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("md") {
+                continue;
+            }
+            let (Some(stem), Ok(metadata)) = (path.file_stem().and_then(|s| s.to_str()), fs::metadata(&path)) else {
+                continue;
+            };
+            let description = fs::read_to_string(&path).ok().and_then(|content| {
+                content
+                    .lines()
+                    .next()
+                    .filter(|line| line.starts_with("# ") || line.starts_with("## "))
+                    .map(|line| line.trim_start_matches('#').trim().to_string())
+            });
+            templates.push(ClaudeMdTemplate {
+                id: stem.to_string(),
+                name: stem.to_string(),
+                description,
+                created_at: to_secs(metadata.created()),
+                updated_at: to_secs(metadata.modified()),
+            });
+        }
+    }
+    templates.sort_by_key(|t| std::cmp::Reverse(t.updated_at));
+    templates
+}
+
+/// Gets a CLAUDE.md template content
+#[tauri::command]
+pub async fn get_claude_md_template(id: String) -> Result<String, AnyCodeError> {
+    let path = get_claude_md_template_path(&id).map_err(AnyCodeError::Io)?;
+    fs::read_to_string(&path).map_err(|e| AnyCodeError::Io(format!("读取 CLAUDE.md 模板失败: {}", e)))
+}
+
+/// Creates or updates a CLAUDE.md template
+#[tauri::command]
+pub async fn save_claude_md_template(id: String, content: String) -> Result<String, AnyCodeError> {
+    let id = id.trim().to_string();
+    if id.is_empty() || !id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return Err(AnyCodeError::Validation("模板名称只能包含字母、数字、横线和下划线".to_string()));
+    }
+
+    let path = get_claude_md_templates_dir().map_err(AnyCodeError::Io)?.join(format!("{}.md", id));
+    fs::write(&path, content).map_err(|e| AnyCodeError::Io(format!("保存 CLAUDE.md 模板失败: {}", e)))?;
+
+    log::info!("Saved CLAUDE.md template: {}", id);
+    Ok(format!("CLAUDE.md 模板 '{}' 保存成功", id))
+}
+
+/// Deletes a CLAUDE.md template (projects it was activated to are not touched)
+#[tauri::command]
+pub async fn delete_claude_md_template(id: String) -> Result<String, AnyCodeError> {
+    let path = get_claude_md_template_path(&id).map_err(AnyCodeError::Io)?;
+    fs::remove_file(&path).map_err(|e| AnyCodeError::Io(format!("删除 CLAUDE.md 模板失败: {}", e)))?;
+
+    log::info!("Deleted CLAUDE.md template: {}", id);
+    Ok(format!("CLAUDE.md 模板 '{}' 删除成功", id))
+}
+
+/// Check if CLAUDE.md exists in the project root
+#[tauri::command]
+pub async fn check_project_claude_md(project_path: String) -> Result<AgentsMdStatus, AnyCodeError> {
+    let project_dir = get_valid_project_dir(&project_path).map_err(AnyCodeError::Io)?;
+    let claude_md_path = project_dir.join("CLAUDE.md");
+
+    let exists = claude_md_path.exists();
+    let has_backup =
+        project_dir.join("CLAUDE.md.backup").exists() || has_timestamped_backup(&project_dir, "CLAUDE.md");
+    let content_preview = if exists {
+        fs::read_to_string(&claude_md_path).ok().map(|content| {
+            let preview: String = content.chars().take(200).collect();
+            if content.len() > 200 {
+                format!("{}...", preview)
+            } else {
+                preview
+            }
+        })
+    } else {
+        None
+    };
+
+    Ok(AgentsMdStatus { exists, has_backup, content_preview })
+}
+
+/// Activate a CLAUDE.md template to a project (writes <project>/CLAUDE.md)
+#[tauri::command]
+pub async fn activate_claude_md_template_to_project(
+    id: String,
+    project_path: String,
+    backup_existing: bool,
+) -> Result<ActivationResult, AnyCodeError> {
+    log::info!("Activating CLAUDE.md template '{}' to project: {}", id, project_path);
+
+    let project_dir = get_valid_project_dir(&project_path).map_err(AnyCodeError::Io)?;
+    let template_path = get_claude_md_template_path(&id).map_err(AnyCodeError::Io)?;
+    let content = fs::read_to_string(&template_path).map_err(|e| AnyCodeError::Io(format!("读取 CLAUDE.md 模板失败: {}", e)))?;
+    let backup_path_result = write_project_claude_md(&project_dir, &content, backup_existing)?;
+
+    let message = match &backup_path_result {
+        Some(backup) => format!("模板已激活到项目，原文件已备份到: {}", backup),
+        None => "模板已激活到项目".to_string(),
+    };
+    Ok(ActivationResult { success: true, message, backup_path: backup_path_result })
+}
+
+/// Writes `<project>/CLAUDE.md`, optionally backing up the existing file; returns the backup path
+fn write_project_claude_md(
+    project_dir: &std::path::Path,
+    content: &str,
+    backup_existing: bool,
+) -> Result<Option<String>, AnyCodeError> {
+    let claude_md_path = project_dir.join("CLAUDE.md");
+    let mut backup_path_result: Option<String> = None;
+    if backup_existing && claude_md_path.exists() {
+        let backup_path = project_dir.join(generate_backup_filename(project_dir, "CLAUDE.md"));
+        fs::copy(&claude_md_path, &backup_path).map_err(|e| AnyCodeError::Io(format!("备份文件失败: {}", e)))?;
+        log::info!("Created backup at: {:?}", backup_path);
+        backup_path_result = Some(backup_path.to_string_lossy().to_string());
+    }
+
+    fs::write(&claude_md_path, content).map_err(|e| AnyCodeError::Io(format!("写入 CLAUDE.md 失败: {}", e)))?;
+    Ok(backup_path_result)
+}
+
+/// Deactivate the CLAUDE.md template from a project (restore the latest backup or clear CLAUDE.md)
+#[tauri::command]
+pub async fn deactivate_claude_md_template_from_project(
+    project_path: String,
+    restore_backup: bool,
+) -> Result<String, AnyCodeError> {
+    log::info!("Deactivating CLAUDE.md template from project: {}, restore_backup: {}", project_path, restore_backup);
+
+    let project_dir = get_valid_project_dir(&project_path).map_err(AnyCodeError::Io)?;
+    let claude_md_path = project_dir.join("CLAUDE.md");
+
+    if restore_backup {
+        let backup_path = find_latest_backup(&project_dir, "CLAUDE.md").ok_or_else(|| AnyCodeError::NotFound("未找到备份文件".to_string()))?;
+        let backup_content = fs::read_to_string(&backup_path).map_err(|e| AnyCodeError::Io(format!("读取备份文件失败: {}", e)))?;
+        fs::write(&claude_md_path, &backup_content).map_err(|e| AnyCodeError::Io(format!("恢复备份失败: {}", e)))?;
+        let _ = fs::remove_file(&backup_path);
+        log::info!("Restored backup from: {:?}", backup_path);
+        return Ok("已恢复备份文件".to_string());
+    }
+
+    if claude_md_path.exists() {
+        fs::write(&claude_md_path, "").map_err(|e| AnyCodeError::Io(format!("清空 CLAUDE.md 失败: {}", e)))?;
+    }
+    Ok("已清空 CLAUDE.md".to_string())
+}
+
 // ============================================================================
 // settings.json File Switching (AnyCode)
 // ============================================================================
@@ -1640,8 +1809,7 @@ pub struct ClaudeSettingsFileProvider {
 }
 
 fn get_anycode_dir() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
-    let dir = home.join(".anycode");
+    let dir = anycode_dir()?;
     if !dir.exists() {
         fs::create_dir_all(&dir).map_err(|e| format!("Failed to create .anycode directory: {}", e))?;
     }
@@ -1654,8 +1822,8 @@ fn get_claude_settings_file_providers_path() -> Result<PathBuf, String> {
 
 /// Read raw ~/.claude/settings.json (creates a minimal default if missing)
 #[tauri::command]
-pub async fn read_claude_settings_json_text() -> Result<String, String> {
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+pub async fn read_claude_settings_json_text() -> Result<String, AnyCodeError> {
+    let claude_dir = get_claude_dir().map_err(|e| AnyCodeError::Io(e.to_string()))?;
     let settings_path = claude_dir.join("settings.json");
 
     if !settings_path.exists() {
@@ -1663,28 +1831,28 @@ pub async fn read_claude_settings_json_text() -> Result<String, String> {
     }
 
     fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings.json: {}", e))
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read settings.json: {}", e)))
 }
 
 /// Write ~/.claude/settings.json
 /// This replaces the file content. The content must be a valid JSON object.
 #[tauri::command]
-pub async fn write_claude_settings_json_text(content: String) -> Result<String, String> {
+pub async fn write_claude_settings_json_text(content: String) -> Result<String, AnyCodeError> {
     let value: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Invalid JSON: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Invalid JSON: {}", e)))?;
     if !value.is_object() {
-        return Err("settings.json 必须是 JSON 对象".to_string());
+        return Err(AnyCodeError::Validation("settings.json 必须是 JSON 对象".to_string()));
     }
 
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| AnyCodeError::Io(e.to_string()))?;
     let settings_path = claude_dir.join("settings.json");
 
     let pretty = serde_json::to_string_pretty(&value)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize settings: {}", e)))?;
     fs::write(&settings_path, pretty)
-        .map_err(|e| format!("Failed to write settings.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write settings.json: {}", e)))?;
 
-    Ok(format!("✅ 已写入 {}", settings_path.display()))
+    Ok(tr!("config.written", path = settings_path.display()))
 }
 
 fn get_claude_json_path() -> Result<PathBuf, String> {
@@ -1694,91 +1862,91 @@ fn get_claude_json_path() -> Result<PathBuf, String> {
 
 /// Read raw ~/.claude.json (creates a minimal default if missing)
 #[tauri::command]
-pub async fn read_claude_json_text() -> Result<String, String> {
-    let claude_json_path = get_claude_json_path()?;
+pub async fn read_claude_json_text() -> Result<String, AnyCodeError> {
+    let claude_json_path = get_claude_json_path().map_err(AnyCodeError::Io)?;
 
     if !claude_json_path.exists() {
         return Ok("{\n  \"mcpServers\": {}\n}\n".to_string());
     }
 
     fs::read_to_string(&claude_json_path)
-        .map_err(|e| format!("Failed to read .claude.json: {}", e))
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read .claude.json: {}", e)))
 }
 
 /// Write ~/.claude.json
 /// This replaces the file content. The content must be a valid JSON object.
 #[tauri::command]
-pub async fn write_claude_json_text(content: String) -> Result<String, String> {
+pub async fn write_claude_json_text(content: String) -> Result<String, AnyCodeError> {
     let trimmed = content.trim();
     let json_str = if trimmed.is_empty() { "{}" } else { trimmed };
 
     let value: serde_json::Value = serde_json::from_str(json_str)
-        .map_err(|e| format!("Invalid JSON: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Invalid JSON: {}", e)))?;
     if !value.is_object() {
-        return Err(".claude.json 必须是 JSON 对象".to_string());
+        return Err(AnyCodeError::Validation(".claude.json 必须是 JSON 对象".to_string()));
     }
 
-    let claude_json_path = get_claude_json_path()?;
+    let claude_json_path = get_claude_json_path().map_err(AnyCodeError::Io)?;
     let pretty = serde_json::to_string_pretty(&value)
-        .map_err(|e| format!("Failed to serialize .claude.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize .claude.json: {}", e)))?;
 
     fs::write(&claude_json_path, pretty)
-        .map_err(|e| format!("Failed to write .claude.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write .claude.json: {}", e)))?;
 
-    Ok(format!("✅ 已写入 {}", claude_json_path.display()))
+    Ok(tr!("config.written", path = claude_json_path.display()))
 }
 
 /// Write both ~/.claude/settings.json and ~/.claude.json
 /// This validates both files before writing to reduce partial updates.
 #[tauri::command]
-pub async fn write_claude_config_files(settings_json: String, claude_json: String) -> Result<String, String> {
+pub async fn write_claude_config_files(settings_json: String, claude_json: String) -> Result<String, AnyCodeError> {
     // Validate settings.json (accept empty as {})
     let settings_trimmed = settings_json.trim();
     let settings_str = if settings_trimmed.is_empty() { "{}" } else { settings_trimmed };
     let settings_value: serde_json::Value = serde_json::from_str(settings_str)
-        .map_err(|e| format!("Invalid JSON (settings.json): {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Invalid JSON (settings.json): {}", e)))?;
     if !settings_value.is_object() {
-        return Err("settings.json 必须是 JSON 对象".to_string());
+        return Err(AnyCodeError::Validation("settings.json 必须是 JSON 对象".to_string()));
     }
 
     // Validate .claude.json (accept empty as {})
     let claude_trimmed = claude_json.trim();
     let claude_str = if claude_trimmed.is_empty() { "{}" } else { claude_trimmed };
     let claude_value: serde_json::Value = serde_json::from_str(claude_str)
-        .map_err(|e| format!("Invalid JSON (.claude.json): {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Invalid JSON (.claude.json): {}", e)))?;
     if !claude_value.is_object() {
-        return Err(".claude.json 必须是 JSON 对象".to_string());
+        return Err(AnyCodeError::Validation(".claude.json 必须是 JSON 对象".to_string()));
     }
 
     // Ensure ~/.claude exists and write settings.json
-    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| AnyCodeError::Io(e.to_string()))?;
     let settings_path = claude_dir.join("settings.json");
     let settings_pretty = serde_json::to_string_pretty(&settings_value)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize settings: {}", e)))?;
     fs::write(&settings_path, settings_pretty)
-        .map_err(|e| format!("Failed to write settings.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write settings.json: {}", e)))?;
 
     // Write ~/.claude.json
-    let claude_json_path = get_claude_json_path()?;
+    let claude_json_path = get_claude_json_path().map_err(AnyCodeError::Io)?;
     let claude_pretty = serde_json::to_string_pretty(&claude_value)
-        .map_err(|e| format!("Failed to serialize .claude.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize .claude.json: {}", e)))?;
     fs::write(&claude_json_path, claude_pretty)
-        .map_err(|e| format!("Failed to write .claude.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write .claude.json: {}", e)))?;
 
-    Ok(format!("✅ 已写入 {} 和 {}", settings_path.display(), claude_json_path.display()))
+    Ok(tr!("config.written_both", first = settings_path.display(), second = claude_json_path.display()))
 }
 
 /// Get Claude settings.json presets (AnyCode-managed)
 #[tauri::command]
-pub async fn get_claude_settings_file_providers() -> Result<Vec<ClaudeSettingsFileProvider>, String> {
-    let providers_path = get_claude_settings_file_providers_path()?;
+pub async fn get_claude_settings_file_providers() -> Result<Vec<ClaudeSettingsFileProvider>, AnyCodeError> {
+    let providers_path = get_claude_settings_file_providers_path().map_err(AnyCodeError::Io)?;
     if !providers_path.exists() {
         return Ok(vec![]);
     }
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let providers: Vec<ClaudeSettingsFileProvider> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
     Ok(providers)
 }
 
@@ -1786,34 +1954,34 @@ pub async fn get_claude_settings_file_providers() -> Result<Vec<ClaudeSettingsFi
 #[tauri::command]
 pub async fn add_claude_settings_file_provider(
     config: ClaudeSettingsFileProvider,
-) -> Result<String, String> {
-    let providers_path = get_claude_settings_file_providers_path()?;
+) -> Result<String, AnyCodeError> {
+    let providers_path = get_claude_settings_file_providers_path().map_err(AnyCodeError::Io)?;
 
     if let Some(parent) = providers_path.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
+                .map_err(|e| AnyCodeError::Io(format!("Failed to create directory: {}", e)))?;
         }
     }
 
     let mut providers: Vec<ClaudeSettingsFileProvider> = if providers_path.exists() {
         let content = fs::read_to_string(&providers_path)
-            .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
         serde_json::from_str(&content).unwrap_or_default()
     } else {
         vec![]
     };
 
     if providers.iter().any(|p| p.id == config.id) {
-        return Err(format!("Provider with ID '{}' already exists", config.id));
+        return Err(AnyCodeError::Provider(format!("Provider with ID '{}' already exists", config.id)));
     }
 
     providers.push(config.clone());
 
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     Ok(format!("Successfully added Claude settings preset: {}", config.name))
 }
@@ -1822,52 +1990,106 @@ pub async fn add_claude_settings_file_provider(
 #[tauri::command]
 pub async fn update_claude_settings_file_provider(
     config: ClaudeSettingsFileProvider,
-) -> Result<String, String> {
-    let providers_path = get_claude_settings_file_providers_path()?;
+) -> Result<String, AnyCodeError> {
+    let providers_path = get_claude_settings_file_providers_path().map_err(AnyCodeError::Io)?;
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", config.id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = config.id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<ClaudeSettingsFileProvider> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     let index = providers.iter().position(|p| p.id == config.id)
-        .ok_or_else(|| format!("Provider with ID '{}' not found", config.id))?;
+        .ok_or_else(|| AnyCodeError::Provider(tr!("provider.not_found", id = config.id)))?;
     providers[index] = config.clone();
 
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     Ok(format!("Successfully updated Claude settings preset: {}", config.name))
 }
 
 /// Delete a Claude settings.json preset (AnyCode-managed)
 #[tauri::command]
-pub async fn delete_claude_settings_file_provider(id: String) -> Result<String, String> {
-    let providers_path = get_claude_settings_file_providers_path()?;
+pub async fn delete_claude_settings_file_provider(id: String) -> Result<String, AnyCodeError> {
+    let providers_path = get_claude_settings_file_providers_path().map_err(AnyCodeError::Io)?;
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<ClaudeSettingsFileProvider> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     let initial_len = providers.len();
     providers.retain(|p| p.id != id);
     if providers.len() == initial_len {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     Ok("Successfully deleted Claude settings preset".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claude_md_template_path_rejects_path_components() {
+        for id in ["", "..", "../settings", "a/b", "a\\b", "/etc/passwd"] {
+            let err = get_claude_md_template_path(id).unwrap_err();
+            assert!(err.contains("无效的 CLAUDE.md 模板名称"), "{}: {}", id, err);
+        }
+    }
+
+    #[test]
+    fn lists_claude_md_templates_newest_first_with_heading_descriptions() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("rust.md"), "# Rust project\n\nUse cargo.").unwrap();
+        fs::write(dir.path().join("plain.md"), "no heading").unwrap();
+        fs::write(dir.path().join("notes.txt"), "# ignored").unwrap();
+        let older = fs::File::options().write(true).open(dir.path().join("plain.md")).unwrap();
+        older.set_modified(SystemTime::now() - std::time::Duration::from_secs(3600)).unwrap();
+
+        let templates = list_claude_md_templates_in(dir.path());
+        let ids: Vec<&str> = templates.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["rust", "plain"]);
+        assert_eq!(templates[0].description.as_deref(), Some("Rust project"));
+        assert_eq!(templates[1].description, None);
+    }
+
+    #[tokio::test]
+    async fn claude_md_activation_backs_up_and_deactivation_restores() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_path = dir.path().to_string_lossy().to_string();
+        let claude_md = dir.path().join("CLAUDE.md");
+        fs::write(&claude_md, "original").unwrap();
+
+        let backup = write_project_claude_md(dir.path(), "# Template", true).unwrap().unwrap();
+        assert!(backup.ends_with("CLAUDE.md.backup"));
+        assert_eq!(fs::read_to_string(&claude_md).unwrap(), "# Template");
+        let status = check_project_claude_md(project_path.clone()).await.unwrap();
+        assert!(status.exists && status.has_backup);
+        assert_eq!(status.content_preview.as_deref(), Some("# Template"));
+
+        deactivate_claude_md_template_from_project(project_path.clone(), true).await.unwrap();
+        assert_eq!(fs::read_to_string(&claude_md).unwrap(), "original");
+        assert!(!check_project_claude_md(project_path.clone()).await.unwrap().has_backup);
+        assert!(deactivate_claude_md_template_from_project(project_path.clone(), true).await.is_err());
+
+        // Without a backup, deactivation clears the file
+        assert_eq!(write_project_claude_md(dir.path(), "# Template", false).unwrap(), None);
+        deactivate_claude_md_template_from_project(project_path, false).await.unwrap();
+        assert_eq!(fs::read_to_string(&claude_md).unwrap(), "");
+    }
+}
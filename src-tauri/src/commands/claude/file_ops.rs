@@ -2,6 +2,7 @@
 //!
 //! Provides utilities for browsing and searching files/directories.
 
+use crate::error::AnyCodeError;
 use std::fs;
 use std::path::PathBuf;
 
@@ -19,13 +20,13 @@ use super::models::FileEntry;
 /// * `Ok(Vec<FileEntry>)` - List of directory entries
 /// * `Err(String)` - Error description if the operation fails
 #[tauri::command]
-pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileEntry>, String> {
+pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileEntry>, AnyCodeError> {
     log::info!("Listing directory contents: '{}'", directory_path);
 
     // Check if path is empty
     if directory_path.trim().is_empty() {
         log::error!("Directory path is empty or whitespace");
-        return Err("Directory path cannot be empty".to_string());
+        return Err(AnyCodeError::Validation("Directory path cannot be empty".to_string()));
     }
 
     let path = PathBuf::from(&directory_path);
@@ -33,25 +34,25 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
 
     if !path.exists() {
         log::error!("Path does not exist: {:?}", path);
-        return Err(format!("Path does not exist: {}", directory_path));
+        return Err(AnyCodeError::NotFound(format!("Path does not exist: {}", directory_path)));
     }
 
     if !path.is_dir() {
         log::error!("Path is not a directory: {:?}", path);
-        return Err(format!("Path is not a directory: {}", directory_path));
+        return Err(AnyCodeError::Io(format!("Path is not a directory: {}", directory_path)));
     }
 
     let mut entries = Vec::new();
 
     let dir_entries =
-        fs::read_dir(&path).map_err(|e| format!("Failed to read directory: {}", e))?;
+        fs::read_dir(&path).map_err(|e| AnyCodeError::Io(format!("Failed to read directory: {}", e)))?;
 
     for entry in dir_entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let entry = entry.map_err(|e| AnyCodeError::Io(format!("Failed to read entry: {}", e)))?;
         let entry_path = entry.path();
         let metadata = entry
             .metadata()
-            .map_err(|e| format!("Failed to read metadata: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read metadata: {}", e)))?;
 
         // Skip hidden files/directories unless they are .claude directories
         if let Some(name) = entry_path.file_name().and_then(|n| n.to_str()) {
@@ -107,13 +108,13 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
 /// * `Ok(Vec<FileEntry>)` - List of matching entries
 /// * `Err(String)` - Error description if the operation fails
 #[tauri::command]
-pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEntry>, String> {
+pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEntry>, AnyCodeError> {
     log::info!("Searching files in '{}' for: '{}'", base_path, query);
 
     // Check if path is empty
     if base_path.trim().is_empty() {
         log::error!("Base path is empty or whitespace");
-        return Err("Base path cannot be empty".to_string());
+        return Err(AnyCodeError::Validation("Base path cannot be empty".to_string()));
     }
 
     // Check if query is empty
@@ -127,13 +128,13 @@ pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEn
 
     if !path.exists() {
         log::error!("Base path does not exist: {:?}", path);
-        return Err(format!("Path does not exist: {}", base_path));
+        return Err(AnyCodeError::NotFound(format!("Path does not exist: {}", base_path)));
     }
 
     let query_lower = query.to_lowercase();
     let mut results = Vec::new();
 
-    search_files_recursive(&path, &path, &query_lower, &mut results, 0)?;
+    search_files_recursive(&path, &path, &query_lower, &mut results, 0).map_err(AnyCodeError::Io)?;
 
     // Sort by relevance: exact matches first, then by name
     results.sort_by(|a, b| {
@@ -1,31 +1,347 @@
+use crate::error::AnyCodeError;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-
-
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
 
 use super::paths::get_claude_dir;
 use super::platform;
 
-#[tauri::command]
-pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Result<serde_json::Value, String> {
-    log::info!("Getting hooks config for scope: {}, project: {:?}", scope, project_path);
+/// Claude Code 支持的 hook 事件
+const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+/// 使用 matcher 的事件（其余事件的 matcher 会被 Claude Code 忽略）
+const MATCHER_EVENTS: &[&str] = &["PreToolUse", "PostToolUse", "PreCompact", "SessionStart"];
+
+const HOOK_SCOPES: &[&str] = &["user", "project", "local"];
+
+/// Claude Code 的默认 hook 超时（秒）
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+
+/// 测试输出的最大保留长度
+const MAX_TEST_OUTPUT_BYTES: usize = 64 * 1024;
 
-    let settings_path = match scope.as_str() {
+/// settings.json 中的一条 hook 命令（展开后的扁平视图）
+///
+/// `id` 形如 `{scope}:{event}:{group}:{index}`，在配置未被外部修改前保持稳定。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeHookEntry {
+    pub id: String,
+    pub scope: String,
+    pub event: String,
+    pub matcher: Option<String>,
+    #[serde(rename = "type")]
+    pub hook_type: String,
+    pub command: String,
+    pub timeout: Option<u64>,
+    pub settings_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HooksSchemaValidation {
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// `test_hook` 的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookTestResult {
+    pub hook_id: String,
+    pub event: String,
+    pub command: String,
+    /// 通过 stdin 传给 hook 的事件 JSON
+    pub payload: serde_json::Value,
+    /// 被信号终止或超时时为 None
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// stdout 为 JSON 时的解析结果（hook 的结构化输出）
+    pub json_output: Option<serde_json::Value>,
+    /// 退出码 2：Claude Code 会阻止本次操作并把 stderr 反馈给 Claude
+    pub blocking: bool,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+}
+
+/// 解析某个作用域的 settings 文件路径；`create_dir` 为 true 时创建项目 .claude 目录
+fn hooks_settings_path(scope: &str, project_path: Option<String>, create_dir: bool) -> Result<PathBuf, String> {
+    let file_name = match scope {
         "user" => {
-            get_claude_dir()
+            return Ok(get_claude_dir()
                 .map_err(|e| e.to_string())?
-                .join("settings.json")
-        },
-        "project" => {
-            let path = project_path.ok_or("Project path required for project scope")?;
-            PathBuf::from(path).join(".claude").join("settings.json")
-        },
-        "local" => {
-            let path = project_path.ok_or("Project path required for local scope")?;
-            PathBuf::from(path).join(".claude").join("settings.local.json")
-        },
-        _ => return Err("Invalid scope".to_string())
+                .join("settings.json"))
+        }
+        "project" => "settings.json",
+        "local" => "settings.local.json",
+        _ => return Err("Invalid scope".to_string()),
+    };
+    let path = project_path.ok_or_else(|| format!("Project path required for {} scope", scope))?;
+    let claude_dir = PathBuf::from(path).join(".claude");
+    if create_dir {
+        fs::create_dir_all(&claude_dir)
+            .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
+    }
+    Ok(claude_dir.join(file_name))
+}
+
+fn read_settings(path: &PathBuf) -> Result<serde_json::Value, String> {
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))
+}
+
+fn write_hooks(path: &PathBuf, hooks: serde_json::Value) -> Result<(), String> {
+    let mut settings = read_settings(path)?;
+    if hooks.as_object().is_some_and(|map| map.is_empty()) {
+        if let Some(map) = settings.as_object_mut() {
+            map.remove("hooks");
+        }
+    } else {
+        settings["hooks"] = hooks;
+    }
+    let json_string = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json_string).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// 按 Claude Code 的 hooks 格式校验配置：
+/// `{ Event: [{ matcher?, hooks: [{ type: "command", command, timeout? }] }] }`
+pub fn validate_hooks_value(hooks: &serde_json::Value) -> HooksSchemaValidation {
+    let mut result = HooksSchemaValidation::default();
+    let Some(events) = hooks.as_object() else {
+        result.errors.push("hooks 必须是以事件名为键的对象".to_string());
+        return result;
+    };
+
+    for (event, groups) in events {
+        if !HOOK_EVENTS.contains(&event.as_str()) {
+            result.errors.push(format!("未知的 hook 事件: {}", event));
+            continue;
+        }
+        let Some(groups) = groups.as_array() else {
+            result.errors.push(format!("{}: 必须是数组", event));
+            continue;
+        };
+        for (g, group) in groups.iter().enumerate() {
+            let at = format!("{}[{}]", event, g);
+            let Some(group) = group.as_object() else {
+                result.errors.push(format!("{}: 必须是对象", at));
+                continue;
+            };
+            match group.get("matcher") {
+                None | Some(serde_json::Value::Null) => {}
+                Some(serde_json::Value::String(matcher)) => {
+                    if !MATCHER_EVENTS.contains(&event.as_str()) && !matcher.is_empty() {
+                        result.warnings.push(format!("{}: {} 事件会忽略 matcher", at, event));
+                    } else if !matcher.is_empty() && matcher != "*" {
+                        if let Err(e) = regex::Regex::new(matcher) {
+                            result.errors.push(format!("{}: matcher 不是合法的正则表达式: {}", at, e));
+                        }
+                    }
+                }
+                Some(_) => result.errors.push(format!("{}.matcher: 必须是字符串", at)),
+            }
+            let Some(commands) = group.get("hooks").and_then(|h| h.as_array()) else {
+                result.errors.push(format!("{}.hooks: 缺少命令数组", at));
+                continue;
+            };
+            if commands.is_empty() {
+                result.warnings.push(format!("{}.hooks: 没有任何命令", at));
+            }
+            for (i, hook) in commands.iter().enumerate() {
+                let at = format!("{}.hooks[{}]", at, i);
+                match hook.get("type").and_then(|t| t.as_str()) {
+                    Some("command") => match hook.get("command").and_then(|c| c.as_str()) {
+                        Some(command) if !command.trim().is_empty() => {}
+                        _ => result.errors.push(format!("{}.command: 不能为空", at)),
+                    },
+                    Some("prompt") => {
+                        if hook.get("prompt").and_then(|p| p.as_str()).is_none_or(|p| p.trim().is_empty()) {
+                            result.errors.push(format!("{}.prompt: 不能为空", at));
+                        }
+                    }
+                    Some(other) => result.errors.push(format!("{}.type: 不支持的类型 {}", at, other)),
+                    None => result.errors.push(format!("{}.type: 缺少类型（应为 \"command\"）", at)),
+                }
+                match hook.get("timeout") {
+                    None | Some(serde_json::Value::Null) => {}
+                    Some(t) if t.as_u64().is_some_and(|t| t > 0) => {}
+                    Some(_) => result.errors.push(format!("{}.timeout: 必须是正整数（秒）", at)),
+                }
+            }
+        }
+    }
+
+    result.valid = result.errors.is_empty();
+    result
+}
+
+fn ensure_valid_hooks(hooks: &serde_json::Value) -> Result<(), String> {
+    let validation = validate_hooks_value(hooks);
+    if validation.valid {
+        Ok(())
+    } else {
+        Err(format!("Hooks 配置无效: {}", validation.errors.join("; ")))
+    }
+}
+
+/// 把某个作用域的 hooks 配置展开为扁平列表
+fn flatten_hooks(scope: &str, settings_path: &str, hooks: &serde_json::Value) -> Vec<ClaudeHookEntry> {
+    let mut entries = Vec::new();
+    let Some(events) = hooks.as_object() else {
+        return entries;
+    };
+    for (event, groups) in events {
+        for (g, group) in groups.as_array().into_iter().flatten().enumerate() {
+            let matcher = group.get("matcher").and_then(|m| m.as_str()).map(str::to_string);
+            let commands = group.get("hooks").and_then(|h| h.as_array());
+            for (i, hook) in commands.into_iter().flatten().enumerate() {
+                let hook_type = hook.get("type").and_then(|t| t.as_str()).unwrap_or("command");
+                let command = if hook_type == "prompt" {
+                    hook.get("prompt")
+                } else {
+                    hook.get("command")
+                };
+                entries.push(ClaudeHookEntry {
+                    id: format!("{}:{}:{}:{}", scope, event, g, i),
+                    scope: scope.to_string(),
+                    event: event.clone(),
+                    matcher: matcher.clone(),
+                    hook_type: hook_type.to_string(),
+                    command: command.and_then(|c| c.as_str()).unwrap_or_default().to_string(),
+                    timeout: hook.get("timeout").and_then(|t| t.as_u64()),
+                    settings_path: settings_path.to_string(),
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// 解析 hook ID，返回 (scope, event, group, index)
+fn parse_hook_id(hook_id: &str) -> Result<(String, String, usize, usize), String> {
+    let parts: Vec<&str> = hook_id.split(':').collect();
+    let invalid = || format!("无效的 hook ID: {}", hook_id);
+    let [scope, event, group, index] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    if !HOOK_SCOPES.contains(scope) {
+        return Err(invalid());
+    }
+    Ok((
+        scope.to_string(),
+        event.to_string(),
+        group.parse().map_err(|_| invalid())?,
+        index.parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// 从 hooks 配置中移除一条命令，返回被移除的命令；组或事件变空时一并删除
+fn remove_hook_at(
+    hooks: &mut serde_json::Value,
+    event: &str,
+    group: usize,
+    index: usize,
+) -> Option<serde_json::Value> {
+    let events = hooks.as_object_mut()?;
+    let groups = events.get_mut(event)?.as_array_mut()?;
+    let commands = groups.get_mut(group)?.get_mut("hooks")?.as_array_mut()?;
+    if index >= commands.len() {
+        return None;
+    }
+    let removed = commands.remove(index);
+    if commands.is_empty() {
+        groups.remove(group);
+    }
+    if groups.is_empty() {
+        events.remove(event);
+    }
+    Some(removed)
+}
+
+/// 为测试构造与 Claude Code 一致的事件 payload，`overrides` 中的字段覆盖默认值
+fn sample_hook_payload(
+    entry: &ClaudeHookEntry,
+    project_path: Option<&str>,
+    overrides: Option<serde_json::Value>,
+) -> serde_json::Value {
+    let cwd = project_path
+        .map(str::to_string)
+        .or_else(|| dirs::home_dir().map(|h| h.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    // matcher 为单个工具名时用它作为示例工具
+    let tool_name = entry
+        .matcher
+        .as_deref()
+        .filter(|m| !m.is_empty() && m.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or("Bash");
+
+    let mut payload = serde_json::json!({
+        "session_id": "anycode-hook-test",
+        "transcript_path": "",
+        "cwd": cwd,
+        "hook_event_name": entry.event,
+    });
+    let extra = match entry.event.as_str() {
+        "PreToolUse" => serde_json::json!({
+            "tool_name": tool_name,
+            "tool_input": { "command": "echo hello" },
+        }),
+        "PostToolUse" => serde_json::json!({
+            "tool_name": tool_name,
+            "tool_input": { "command": "echo hello" },
+            "tool_response": { "stdout": "hello", "stderr": "", "interrupted": false },
+        }),
+        "Notification" => serde_json::json!({ "message": "Claude needs your permission to use Bash" }),
+        "UserPromptSubmit" => serde_json::json!({ "prompt": "Hello from AnyCode hook test" }),
+        "Stop" | "SubagentStop" => serde_json::json!({ "stop_hook_active": false }),
+        "PreCompact" => serde_json::json!({ "trigger": "manual", "custom_instructions": "" }),
+        "SessionStart" => serde_json::json!({ "source": "startup" }),
+        "SessionEnd" => serde_json::json!({ "reason": "other" }),
+        _ => serde_json::json!({}),
     };
+    for source in [Some(extra), overrides].into_iter().flatten() {
+        if let (Some(target), serde_json::Value::Object(fields)) = (payload.as_object_mut(), source) {
+            target.extend(fields);
+        }
+    }
+    payload
+}
+
+fn truncate_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    if text.len() <= MAX_TEST_OUTPUT_BYTES {
+        return text.to_string();
+    }
+    let mut end = MAX_TEST_OUTPUT_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n... (truncated)", &text[..end])
+}
+
+#[tauri::command]
+pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Result<serde_json::Value, AnyCodeError> {
+    log::info!("Getting hooks config for scope: {}, project: {:?}", scope, project_path);
+
+    let settings_path = hooks_settings_path(&scope, project_path, false).map_err(AnyCodeError::Io)?;
 
     log::info!("Settings file path: {:?}", settings_path);
 
@@ -35,12 +351,12 @@ pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Re
     }
 
     let content = fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read settings: {}", e)))?;
 
     log::debug!("Settings file content length: {} bytes", content.len());
 
     let settings: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse settings: {}", e)))?;
 
     let hooks = settings.get("hooks").cloned().unwrap_or(serde_json::json!({}));
     log::info!("Returning hooks config: {}", serde_json::to_string_pretty(&hooks).unwrap_or_default());
@@ -54,38 +370,18 @@ pub async fn update_hooks_config(
     scope: String, 
     hooks: serde_json::Value,
     project_path: Option<String>
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     log::info!("Updating hooks config for scope: {}, project: {:?}", scope, project_path);
 
-    let settings_path = match scope.as_str() {
-        "user" => {
-            get_claude_dir()
-                .map_err(|e| e.to_string())?
-                .join("settings.json")
-        },
-        "project" => {
-            let path = project_path.ok_or("Project path required for project scope")?;
-            let claude_dir = PathBuf::from(path).join(".claude");
-            fs::create_dir_all(&claude_dir)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-            claude_dir.join("settings.json")
-        },
-        "local" => {
-            let path = project_path.ok_or("Project path required for local scope")?;
-            let claude_dir = PathBuf::from(path).join(".claude");
-            fs::create_dir_all(&claude_dir)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-            claude_dir.join("settings.local.json")
-        },
-        _ => return Err("Invalid scope".to_string())
-    };
+    ensure_valid_hooks(&hooks).map_err(AnyCodeError::Validation)?;
+    let settings_path = hooks_settings_path(&scope, project_path, true).map_err(AnyCodeError::Io)?;
 
     // Read existing settings or create new
     let mut settings = if settings_path.exists() {
         let content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read settings: {}", e)))?;
         serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?
+            .map_err(|e| AnyCodeError::Config(format!("Failed to parse settings: {}", e)))?
     } else {
         serde_json::json!({})
     };
@@ -95,17 +391,17 @@ pub async fn update_hooks_config(
 
     // Write back with pretty formatting
     let json_string = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize settings: {}", e)))?;
     
     fs::write(&settings_path, json_string)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write settings: {}", e)))?;
 
     Ok("Hooks configuration updated successfully".to_string())
 }
 
 /// Validates a hook command by dry-running it
 #[tauri::command]
-pub async fn validate_hook_command(command: String) -> Result<serde_json::Value, String> {
+pub async fn validate_hook_command(command: String) -> Result<serde_json::Value, AnyCodeError> {
     log::info!("Validating hook command syntax");
 
     // Validate syntax without executing
@@ -132,6 +428,271 @@ pub async fn validate_hook_command(command: String) -> Result<serde_json::Value,
                 }))
             }
         }
-        Err(e) => Err(format!("Failed to validate command: {}", e))
+        Err(e) => Err(AnyCodeError::Internal(format!("Failed to validate command: {}", e)))
+    }
+}
+
+/// Lists every configured hook across user / project / local settings
+#[tauri::command]
+pub async fn list_claude_hooks(project_path: Option<String>) -> Result<Vec<ClaudeHookEntry>, AnyCodeError> {
+    let mut entries = Vec::new();
+    for scope in HOOK_SCOPES {
+        if *scope != "user" && project_path.is_none() {
+            continue;
+        }
+        let path = hooks_settings_path(scope, project_path.clone(), false).map_err(AnyCodeError::Io)?;
+        let settings = match read_settings(&path) {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::warn!("[Hooks] Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if let Some(hooks) = settings.get("hooks") {
+            entries.extend(flatten_hooks(scope, &path.to_string_lossy(), hooks));
+        }
+    }
+    Ok(entries)
+}
+
+/// Validates a hooks configuration against the Claude Code hooks schema
+#[tauri::command]
+pub async fn validate_hooks_config(hooks: serde_json::Value) -> Result<HooksSchemaValidation, AnyCodeError> {
+    Ok(validate_hooks_value(&hooks))
+}
+
+/// Adds a hook command, or edits the one identified by `hook_id`
+///
+/// 编辑时若事件和 matcher 未变则原位替换，否则移动到新的事件/matcher 分组下。
+#[tauri::command]
+pub async fn save_claude_hook(
+    scope: String,
+    project_path: Option<String>,
+    hook_id: Option<String>,
+    event: String,
+    matcher: Option<String>,
+    command: String,
+    timeout: Option<u64>,
+) -> Result<ClaudeHookEntry, AnyCodeError> {
+    let settings_path = hooks_settings_path(&scope, project_path, true).map_err(AnyCodeError::Io)?;
+    let settings = read_settings(&settings_path).map_err(AnyCodeError::Config)?;
+    let mut hooks = settings.get("hooks").cloned().unwrap_or_else(|| serde_json::json!({}));
+    if !hooks.is_object() {
+        return Err(AnyCodeError::Config("settings 中的 hooks 不是对象".to_string()));
+    }
+
+    let matcher = matcher.filter(|m| !m.is_empty());
+    let mut hook = serde_json::json!({ "type": "command", "command": command });
+    if let Some(timeout) = timeout {
+        hook["timeout"] = serde_json::json!(timeout);
+    }
+
+    let mut position = None;
+    if let Some(hook_id) = &hook_id {
+        let (id_scope, old_event, group, index) = parse_hook_id(hook_id).map_err(AnyCodeError::Validation)?;
+        if id_scope != scope {
+            return Err(AnyCodeError::Validation(format!("Hook {} 不属于 {} 作用域", hook_id, scope)));
+        }
+        let old_group = hooks[&old_event][group].clone();
+        let old_matcher = old_group.get("matcher").and_then(|m| m.as_str()).filter(|m| !m.is_empty());
+        if old_event == event && old_matcher == matcher.as_deref() {
+            let slot = old_group["hooks"]
+                .get(index)
+                .ok_or_else(|| AnyCodeError::NotFound(format!("Hook 不存在: {}", hook_id)))?;
+            // 保留未在表单中编辑的字段
+            if let (Some(merged), Some(existing)) = (hook.as_object_mut(), slot.as_object()) {
+                for (key, value) in existing {
+                    if key != "timeout" {
+                        merged.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                }
+            }
+            hooks[&event][group]["hooks"][index] = hook.clone();
+            position = Some((group, index));
+        } else {
+            remove_hook_at(&mut hooks, &old_event, group, index)
+                .ok_or_else(|| AnyCodeError::NotFound(format!("Hook 不存在: {}", hook_id)))?;
+        }
+    }
+
+    let (group, index) = match position {
+        Some(position) => position,
+        None => {
+            let groups = hooks
+                .as_object_mut()
+                .map(|events| events.entry(event.clone()).or_insert_with(|| serde_json::json!([])))
+                .and_then(|groups| groups.as_array_mut())
+                .ok_or_else(|| AnyCodeError::Config(format!("hooks.{} 不是数组", event)))?;
+            let existing = groups.iter().position(|g| {
+                g.get("matcher").and_then(|m| m.as_str()).filter(|m| !m.is_empty()) == matcher.as_deref()
+            });
+            let group = match existing {
+                Some(group) => group,
+                None => {
+                    let mut new_group = serde_json::json!({ "hooks": [] });
+                    if let Some(matcher) = &matcher {
+                        new_group["matcher"] = serde_json::json!(matcher);
+                    }
+                    groups.push(new_group);
+                    groups.len() - 1
+                }
+            };
+            let commands = groups[group]["hooks"]
+                .as_array_mut()
+                .ok_or_else(|| AnyCodeError::Config(format!("hooks.{}[{}].hooks 不是数组", event, group)))?;
+            commands.push(hook);
+            (group, commands.len() - 1)
+        }
+    };
+
+    ensure_valid_hooks(&hooks).map_err(AnyCodeError::Validation)?;
+    write_hooks(&settings_path, hooks.clone()).map_err(AnyCodeError::Io)?;
+    log::info!("[Hooks] Saved {} hook in {:?}", event, settings_path);
+
+    let id = format!("{}:{}:{}:{}", scope, event, group, index);
+    flatten_hooks(&scope, &settings_path.to_string_lossy(), &hooks)
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| AnyCodeError::NotFound(format!("Hook 保存后未找到: {}", id)))
+}
+
+/// Removes the hook identified by `hook_id`
+#[tauri::command]
+pub async fn remove_claude_hook(hook_id: String, project_path: Option<String>) -> Result<String, AnyCodeError> {
+    let (scope, event, group, index) = parse_hook_id(&hook_id).map_err(AnyCodeError::Validation)?;
+    let settings_path = hooks_settings_path(&scope, project_path, false).map_err(AnyCodeError::Io)?;
+    let settings = read_settings(&settings_path).map_err(AnyCodeError::Config)?;
+    let mut hooks = settings.get("hooks").cloned().unwrap_or_else(|| serde_json::json!({}));
+    remove_hook_at(&mut hooks, &event, group, index).ok_or_else(|| AnyCodeError::NotFound(format!("Hook 不存在: {}", hook_id)))?;
+    write_hooks(&settings_path, hooks).map_err(AnyCodeError::Io)?;
+
+    log::info!("[Hooks] Removed {} from {:?}", hook_id, settings_path);
+    Ok(format!("Hook {} removed", hook_id))
+}
+
+/// Runs a configured hook once with a synthetic event payload on stdin
+///
+/// `sample_event` 中的字段会覆盖默认构造的 payload（如 `tool_name`、`tool_input`）。
+#[tauri::command]
+pub async fn test_hook(
+    hook_id: String,
+    sample_event: Option<serde_json::Value>,
+    project_path: Option<String>,
+) -> Result<HookTestResult, AnyCodeError> {
+    let (scope, _, _, _) = parse_hook_id(&hook_id).map_err(AnyCodeError::Validation)?;
+    let settings_path = hooks_settings_path(&scope, project_path.clone(), false).map_err(AnyCodeError::Io)?;
+    let settings = read_settings(&settings_path).map_err(AnyCodeError::Config)?;
+    let hooks = settings.get("hooks").cloned().unwrap_or_else(|| serde_json::json!({}));
+    let entry = flatten_hooks(&scope, &settings_path.to_string_lossy(), &hooks)
+        .into_iter()
+        .find(|entry| entry.id == hook_id)
+        .ok_or_else(|| AnyCodeError::NotFound(format!("Hook 不存在: {}", hook_id)))?;
+    if entry.hook_type != "command" {
+        return Err(AnyCodeError::Validation(format!("只能测试 command 类型的 hook（当前为 {}）", entry.hook_type)));
+    }
+
+    let payload = sample_hook_payload(&entry, project_path.as_deref(), sample_event);
+    let input = serde_json::to_vec(&payload).map_err(|e| AnyCodeError::Internal(format!("Failed to serialize payload: {}", e)))?;
+
+    let mut cmd = tokio::process::Command::new("bash");
+    cmd.arg("-c")
+        .arg(&entry.command)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(project) = &project_path {
+        cmd.current_dir(project).env("CLAUDE_PROJECT_DIR", project);
+    }
+    platform::apply_no_window_async(&mut cmd);
+
+    log::info!("[Hooks] Testing {} ({})", hook_id, entry.command);
+    let started = Instant::now();
+    let mut child = cmd.spawn().map_err(|e| AnyCodeError::Io(format!("Failed to spawn hook process: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // hook 可能不读取 stdin，写入失败不影响测试
+        let _ = stdin.write_all(&input).await;
+    }
+
+    let timeout = Duration::from_secs(entry.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+    let (output, timed_out) = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => (Some(output.map_err(|e| AnyCodeError::Io(format!("Hook execution failed: {}", e)))?), false),
+        Err(_) => (None, true),
+    };
+
+    let exit_code = output.as_ref().and_then(|o| o.status.code());
+    let stdout = output.as_ref().map(|o| truncate_output(&o.stdout)).unwrap_or_default();
+    let stderr = match &output {
+        Some(o) => truncate_output(&o.stderr),
+        None => format!("Hook 执行超时（{} 秒）", timeout.as_secs()),
+    };
+    let json_output = serde_json::from_str::<serde_json::Value>(stdout.trim())
+        .ok()
+        .filter(|v| v.is_object());
+
+    Ok(HookTestResult {
+        hook_id,
+        event: entry.event,
+        command: entry.command,
+        payload,
+        exit_code,
+        stdout,
+        stderr,
+        json_output,
+        blocking: exit_code == Some(2),
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_and_edits_hooks_config() {
+        let mut hooks = serde_json::json!({
+            "PreToolUse": [
+                { "matcher": "Edit|Write", "hooks": [
+                    { "type": "command", "command": "npm run lint", "timeout": 30 },
+                    { "type": "command", "command": "echo done" }
+                ] }
+            ],
+            "Stop": [{ "hooks": [{ "type": "command", "command": "notify-send done" }] }]
+        });
+        assert!(validate_hooks_value(&hooks).valid);
+
+        let entries = flatten_hooks("user", "settings.json", &hooks);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].id, "user:PreToolUse:0:1");
+        assert_eq!(entries[0].matcher.as_deref(), Some("Edit|Write"));
+        assert_eq!(entries[0].timeout, Some(30));
+        assert_eq!(parse_hook_id("user:PreToolUse:0:1").unwrap().3, 1);
+        assert!(parse_hook_id("global:Stop:0:0").is_err());
+
+        let invalid = serde_json::json!({
+            "PreToolUse": [{ "matcher": "(", "hooks": [{ "type": "command", "command": "" }] }],
+            "Notification": [{ "matcher": "x", "hooks": [{ "type": "command", "command": "a", "timeout": 0 }] }],
+            "OnSave": []
+        });
+        let validation = validate_hooks_value(&invalid);
+        assert!(!validation.valid);
+        assert_eq!(validation.errors.len(), 4);
+        assert_eq!(validation.warnings.len(), 1);
+
+        // 移除组内最后一条命令时删除空组和空事件
+        assert!(remove_hook_at(&mut hooks, "Stop", 0, 0).is_some());
+        assert!(hooks.get("Stop").is_none());
+        assert!(remove_hook_at(&mut hooks, "PreToolUse", 0, 5).is_none());
+
+        let payload = sample_hook_payload(
+            &entries[0],
+            Some("/tmp/project"),
+            Some(serde_json::json!({ "tool_name": "Write" })),
+        );
+        assert_eq!(payload["hook_event_name"], "PreToolUse");
+        assert_eq!(payload["cwd"], "/tmp/project");
+        assert_eq!(payload["tool_name"], "Write");
+        assert!(payload.get("tool_input").is_some());
     }
 }
@@ -1,3 +1,4 @@
+mod agents;
 mod cli_runner;
 mod config;
 mod hooks;
@@ -11,8 +12,11 @@ mod file_ops;
 pub use models::*;
 pub use paths::*;
 // Export platform utilities for process window hiding
-pub use platform::apply_no_window_async;
+pub use platform::{apply_no_window, apply_no_window_async};
+// Export process tree termination for other engines (Codex / Gemini timeouts)
+pub use platform::kill_process_tree;
 pub use self::cli_runner::{
+    build_headless_claude_command,
     cancel_claude_execution,
     continue_claude_code,
     execute_claude_code,
@@ -61,6 +65,14 @@ pub use self::config::{
     deactivate_codex_prompt_from_project,
     AgentsMdStatus,
     ActivationResult,
+    // CLAUDE.md template library
+    list_claude_md_templates,
+    get_claude_md_template,
+    save_claude_md_template,
+    delete_claude_md_template,
+    check_project_claude_md,
+    activate_claude_md_template_to_project,
+    deactivate_claude_md_template_from_project,
     // settings.json file switching (AnyCode)
     read_claude_settings_json_text,
     write_claude_settings_json_text,
@@ -72,13 +84,30 @@ pub use self::config::{
     update_claude_settings_file_provider,
     delete_claude_settings_file_provider,
 };
+// Backup helpers for project-level prompt files (shared with Gemini)
+pub(crate) use self::config::{find_latest_backup, generate_backup_filename, has_timestamped_backup};
+pub use self::agents::{
+    activate_claude_agent_to_project,
+    deactivate_claude_agent_from_project,
+    delete_claude_agent,
+    list_claude_agents,
+    save_claude_agent,
+    validate_claude_agent,
+};
 pub use self::hooks::{
     get_hooks_config,
+    list_claude_hooks,
+    remove_claude_hook,
+    save_claude_hook,
+    test_hook,
     update_hooks_config,
     validate_hook_command,
+    validate_hooks_config,
 };
+use crate::error::AnyCodeError;
 use self::project_store::ProjectStore;
 use tauri::async_runtime;
+use crate::commands::session_metadata::{attach_session_metadata, SessionFilter};
 pub use file_ops::{list_directory_contents, search_files};
 // Agent functionality removed
 
@@ -86,24 +115,31 @@ pub use file_ops::{list_directory_contents, search_files};
 
 
 #[tauri::command]
-pub async fn list_projects() -> Result<Vec<Project>, String> {
+pub async fn list_projects() -> Result<Vec<Project>, AnyCodeError> {
     async_runtime::spawn_blocking(|| {
         let store = ProjectStore::new()?;
         store.list_projects()
     })
     .await
-    .map_err(|e| format!("Failed to load projects: {}", e))?
+    .map_err(|e| AnyCodeError::Internal(format!("Failed to load projects: {}", e)))?
+    .map_err(AnyCodeError::Io)
 }
 
 /// Gets sessions for a specific project
+/// `filter` selects by tag / pinned / archived state (archived sessions are hidden by default)
 #[tauri::command]
-pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, String> {
+pub async fn get_project_sessions(
+    project_id: String,
+    filter: Option<SessionFilter>,
+) -> Result<Vec<Session>, AnyCodeError> {
     async_runtime::spawn_blocking(move || {
-        let store = ProjectStore::new()?;
-        store.get_project_sessions(&project_id)
+        let store = ProjectStore::new().map_err(AnyCodeError::Io)?;
+        let mut sessions = store.get_project_sessions(&project_id).map_err(AnyCodeError::Io)?;
+        attach_session_metadata("claude", &mut sessions, filter.as_ref());
+        Ok(sessions)
     })
     .await
-    .map_err(|e| format!("Failed to load sessions: {}", e))?
+    .map_err(|e| AnyCodeError::Internal(format!("Failed to load sessions: {}", e)))?
 }
 
 /// Deletes a session and all its associated data
@@ -111,9 +147,9 @@ pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, St
 pub async fn delete_session(
     session_id: String,
     project_id: String,
-) -> Result<String, String> {
-    let store = ProjectStore::new()?;
-    let session_deleted = store.delete_session(&project_id, &session_id)?;
+) -> Result<String, AnyCodeError> {
+    let store = ProjectStore::new().map_err(AnyCodeError::Io)?;
+    let session_deleted = store.delete_session(&project_id, &session_id).map_err(AnyCodeError::Io)?;
 
     if session_deleted {
         Ok(format!("Successfully deleted session: {}", session_id))
@@ -130,17 +166,22 @@ pub async fn delete_session(
 pub async fn delete_sessions_batch(
     session_ids: Vec<String>,
     project_id: String,
-) -> Result<String, String> {
-    let store = ProjectStore::new()?;
+) -> Result<String, AnyCodeError> {
+    let store = ProjectStore::new().map_err(AnyCodeError::Io)?;
     let outcome = store.delete_sessions_batch(&project_id, &session_ids);
 
     if outcome.failed_count > 0 {
-        Err(format!(
+        Err(AnyCodeError::Io(format!(
             "Batch delete completed with errors: {} deleted, {} failed. Errors: {}",
             outcome.deleted_count,
             outcome.failed_count,
             outcome.errors.join("; ")
         ))
+        .with_details(serde_json::json!({
+            "deletedCount": outcome.deleted_count,
+            "failedCount": outcome.failed_count,
+            "errors": outcome.errors,
+        })))
     } else {
         Ok(format!(
             "Successfully deleted {} sessions",
@@ -151,9 +192,9 @@ pub async fn delete_sessions_batch(
 
 /// Removes a project from the project list (without deleting files)
 #[tauri::command]
-pub async fn delete_project(project_id: String) -> Result<String, String> {
-    let store = ProjectStore::new()?;
-    let newly_hidden = store.hide_project(&project_id)?;
+pub async fn delete_project(project_id: String) -> Result<String, AnyCodeError> {
+    let store = ProjectStore::new().map_err(AnyCodeError::Io)?;
+    let newly_hidden = store.hide_project(&project_id).map_err(AnyCodeError::Io)?;
 
     let result_msg = if newly_hidden {
         format!(
@@ -173,9 +214,9 @@ pub async fn delete_project(project_id: String) -> Result<String, String> {
 
 /// Restores a project to the project list
 #[tauri::command]
-pub async fn restore_project(project_id: String) -> Result<String, String> {
-    let store = ProjectStore::new()?;
-    store.restore_project(&project_id)?;
+pub async fn restore_project(project_id: String) -> Result<String, AnyCodeError> {
+    let store = ProjectStore::new().map_err(AnyCodeError::Io)?;
+    store.restore_project(&project_id).map_err(AnyCodeError::Io)?;
 
     let result_msg = format!("Project '{}' has been restored to the list", project_id);
     log::info!("{}", result_msg);
@@ -184,9 +225,9 @@ pub async fn restore_project(project_id: String) -> Result<String, String> {
 
 /// Permanently delete a project from the file system with intelligent directory detection
 #[tauri::command]
-pub async fn delete_project_permanently(project_id: String) -> Result<String, String> {
-    let store = ProjectStore::new()?;
-    let actual_project_id = store.delete_project_permanently(&project_id)?;
+pub async fn delete_project_permanently(project_id: String) -> Result<String, AnyCodeError> {
+    let store = ProjectStore::new().map_err(AnyCodeError::Io)?;
+    let actual_project_id = store.delete_project_permanently(&project_id).map_err(AnyCodeError::Io)?;
 
     let result_msg = if actual_project_id != project_id {
         format!(
@@ -203,9 +244,9 @@ pub async fn delete_project_permanently(project_id: String) -> Result<String, St
 
 /// Lists all hidden projects with intelligent directory existence check
 #[tauri::command]
-pub async fn list_hidden_projects() -> Result<Vec<String>, String> {
-    let store = ProjectStore::new()?;
-    store.list_hidden_projects()
+pub async fn list_hidden_projects() -> Result<Vec<String>, AnyCodeError> {
+    let store = ProjectStore::new().map_err(AnyCodeError::Io)?;
+    store.list_hidden_projects().map_err(AnyCodeError::Io)
 }
 
 /// Reads the Claude settings file
@@ -216,6 +257,6 @@ pub async fn list_hidden_projects() -> Result<Vec<String>, String> {
 pub async fn load_session_history(
     session_id: String,
     project_id: String,
-) -> Result<Vec<serde_json::Value>, String> {
-    session_history::load_session_history(&session_id, &project_id)
+) -> Result<Vec<serde_json::Value>, AnyCodeError> {
+    session_history::load_session_history(&session_id, &project_id).map_err(AnyCodeError::Io)
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::commands::session_metadata::SessionMetadata;
+
 /// Represents a project in the ~/.claude/projects directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -35,6 +37,9 @@ pub struct Session {
     pub last_message_timestamp: Option<String>,
     /// The model used in this session (if available)
     pub model: Option<String>,
+    /// Tags / pinned / archived state managed by AnyCode
+    #[serde(default)]
+    pub metadata: SessionMetadata,
 }
 
 /// Represents a message entry in the JSONL file
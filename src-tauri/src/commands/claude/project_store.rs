@@ -7,6 +7,7 @@ use std::time::SystemTime;
 use serde_json::Value;
 
 use super::models::{Project, Session};
+use crate::commands::session_metadata::SessionMetadata;
 use super::paths::{decode_project_path, get_claude_dir, normalize_path_for_comparison};
 use super::session_history::extract_session_metadata;
 
@@ -228,6 +229,7 @@ impl ProjectStore {
                         message_timestamp,
                         last_message_timestamp,
                         model,
+                        metadata: SessionMetadata::default(),
                     });
                 }
             }
@@ -1,16 +1,81 @@
 use base64::{engine::general_purpose, Engine};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use tauri::{command, AppHandle};
 
 // ⚡ 新增：文本剪贴板支持
 use arboard::Clipboard;
 
+/// 临时图片保留时长（超过后在下次保存时清理）
+const IMAGE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SavedImageResult {
     pub success: bool,
     pub file_path: Option<String>,
     pub error: Option<String>,
+    /// 图片尺寸（无法识别格式时为空）
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+}
+
+/// 剪贴板图片临时目录
+fn clipboard_images_dir() -> Result<PathBuf, String> {
+    // 获取用户临时目录，确保使用完整路径
+    let temp_dir = std::env::var("TEMP")
+        .or_else(|_| std::env::var("TMP"))
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| std::env::temp_dir());
+
+    // 规范化路径，确保获得完整的长文件名路径
+    let temp_dir = temp_dir.canonicalize().unwrap_or(temp_dir);
+
+    let images_dir = temp_dir.join("claude_workbench_clipboard_images");
+
+    // 创建目录
+    fs::create_dir_all(&images_dir)
+        .map_err(|e| format!("Failed to create images directory: {}", e))?;
+    Ok(images_dir)
+}
+
+/// 生成唯一文件名
+fn new_image_path(images_dir: &Path, extension: &str) -> PathBuf {
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f");
+    images_dir.join(format!("clipboard_image_{}.{}", timestamp, extension))
+}
+
+/// 返回清洁的Windows文件路径，移除UNC前缀 \\?\
+fn clean_path(path: &Path) -> String {
+    let path_str = path.to_string_lossy().to_string();
+    match path_str.strip_prefix("\\\\?\\") {
+        Some(stripped) => stripped.to_string(),
+        None => path_str,
+    }
+}
+
+/// 删除目录中修改时间早于 `max_age` 的图片，返回删除数量
+fn remove_old_images(images_dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(images_dir) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("clipboard_image_"))
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > max_age)
+        })
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
 }
 
 /// 保存Base64图片数据到临时文件
@@ -66,28 +131,14 @@ pub async fn save_clipboard_image(
 
     println!("Decoded image data size: {} bytes", image_data.len());
 
-    // 获取用户临时目录，确保使用完整路径
-    let temp_dir = std::env::var("TEMP")
-        .or_else(|_| std::env::var("TMP"))
-        .map(std::path::PathBuf::from)
-        .unwrap_or_else(|_| std::env::temp_dir());
-
-    // 规范化路径，确保获得完整的长文件名路径
-    let temp_dir = temp_dir.canonicalize().unwrap_or(temp_dir);
-
-    let images_dir = temp_dir.join("claude_workbench_clipboard_images");
-
-    // 创建目录
-    fs::create_dir_all(&images_dir)
-        .map_err(|e| format!("Failed to create images directory: {}", e))?;
-
-    // 生成唯一文件名
-    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f");
-    let filename = format!("clipboard_image_{}.{}", timestamp, extension);
-    let file_path = images_dir.join(&filename);
+    let images_dir = clipboard_images_dir()?;
+    remove_old_images(&images_dir, IMAGE_MAX_AGE);
+    let file_path = new_image_path(&images_dir, extension);
 
     println!("Saving image to: {}", file_path.display());
 
+    let dimensions = image::load_from_memory(&image_data).ok().map(|img| (img.width(), img.height()));
+
     // 保存文件
     fs::write(&file_path, image_data).map_err(|e| format!("Failed to write image file: {}", e))?;
 
@@ -97,6 +148,8 @@ pub async fn save_clipboard_image(
             success: false,
             file_path: None,
             error: Some("File was not saved successfully".to_string()),
+            width: None,
+            height: None,
         });
     }
 
@@ -104,13 +157,7 @@ pub async fn save_clipboard_image(
 
     println!("Image saved successfully! File size: {} bytes", file_size);
 
-    // 返回清洁的Windows文件路径，移除UNC前缀
-    let mut path_str = file_path.to_string_lossy().to_string();
-
-    // 移除Windows长路径前缀 \\?\
-    if path_str.starts_with("\\\\?\\") {
-        path_str = path_str[4..].to_string();
-    }
+    let path_str = clean_path(&file_path);
 
     println!("Final cleaned path: {}", path_str);
 
@@ -118,9 +165,63 @@ pub async fn save_clipboard_image(
         success: true,
         file_path: Some(path_str),
         error: None,
+        width: dimensions.map(|(w, _)| w),
+        height: dimensions.map(|(_, h)| h),
     })
 }
 
+/// 把系统剪贴板中的图片保存为 PNG 临时文件
+///
+/// 剪贴板中没有图片时返回 `success: false`，供附加截图到提示词使用。
+#[command]
+pub async fn save_system_clipboard_image() -> Result<SavedImageResult, String> {
+    let mut clipboard =
+        Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    let image = match clipboard.get_image() {
+        Ok(image) => image,
+        Err(e) => {
+            return Ok(SavedImageResult {
+                success: false,
+                file_path: None,
+                error: Some(format!("No image in clipboard: {}", e)),
+                width: None,
+                height: None,
+            })
+        }
+    };
+
+    let (width, height) = (image.width as u32, image.height as u32);
+    let buffer = image::RgbaImage::from_raw(width, height, image.bytes.into_owned())
+        .ok_or_else(|| "Invalid clipboard image data".to_string())?;
+
+    let images_dir = clipboard_images_dir()?;
+    remove_old_images(&images_dir, IMAGE_MAX_AGE);
+    let file_path = new_image_path(&images_dir, "png");
+    buffer
+        .save_with_format(&file_path, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to write image file: {}", e))?;
+
+    log::info!("Saved clipboard image ({}x{}) to {}", width, height, file_path.display());
+    Ok(SavedImageResult {
+        success: true,
+        file_path: Some(clean_path(&file_path)),
+        error: None,
+        width: Some(width),
+        height: Some(height),
+    })
+}
+
+/// 清理过期的剪贴板临时图片，返回删除数量
+///
+/// `max_age_hours` 为空时使用默认的 24 小时。
+#[command]
+pub async fn cleanup_clipboard_images(max_age_hours: Option<u64>) -> Result<usize, String> {
+    let max_age = max_age_hours.map_or(IMAGE_MAX_AGE, |hours| Duration::from_secs(hours * 60 * 60));
+    let removed = remove_old_images(&clipboard_images_dir()?, max_age);
+    log::info!("Removed {} old clipboard images", removed);
+    Ok(removed)
+}
+
 /// 写入文本到剪贴板
 #[command]
 pub async fn write_to_clipboard(text: String) -> Result<(), String> {
@@ -150,3 +251,25 @@ pub async fn read_from_clipboard() -> Result<String, String> {
 
     Ok(text)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_only_old_clipboard_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("clipboard_image_old.png");
+        let other = dir.path().join("notes.txt");
+        fs::write(&old, b"x").unwrap();
+        fs::write(&other, b"x").unwrap();
+
+        assert_eq!(remove_old_images(dir.path(), Duration::from_secs(3600)), 0);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(remove_old_images(dir.path(), Duration::from_millis(1)), 1);
+        assert!(!old.exists() && other.exists());
+
+        assert_eq!(clean_path(Path::new(r"\\?\C:\tmp\a.png")), r"C:\tmp\a.png");
+        assert!(new_image_path(dir.path(), "png").to_string_lossy().ends_with(".png"));
+    }
+}
@@ -0,0 +1,334 @@
+//! 命令副作用检测的大仓库性能模式
+//!
+//! `detect_changes_after_command` 默认在命令前后各执行一次完整的 `git status -uall`，
+//! 在十万级文件的仓库（尤其是带 LFS 的仓库）上会阻塞很久。性能模式下：
+//! - 使用 `git status --porcelain=v2 --untracked-files=normal`（未跟踪目录只报告目录本身）
+//! - 从命令参数中提取路径作为 pathspec，只检查命令可能修改的位置
+//! - 可选启用 git 内置 fsmonitor（`core.fsmonitor=true`）
+//! - 超出时间预算时终止 git，返回「变更未知」而不是阻塞输出流
+//!
+//! 配置保存在 app_settings 的 `codex_change_detection` 键中；`auto` 模式下按 Git 索引的
+//! 条目数判断是否为大仓库。
+
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use super::super::app_settings::read_setting;
+use super::super::storage::open_agent_db;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+pub const CHANGE_DETECTION_KEY: &str = "codex_change_detection";
+
+/// 最多使用的 pathspec 数量，更多时不限制路径
+const MAX_PATHSPECS: usize = 32;
+/// 只会修改参数（含重定向目标）中路径的命令；其他命令（构建、脚本、cd 等）无法从参数判断影响范围
+const SCOPED_PROGRAMS: [&str; 18] = [
+    "sed", "rm", "mv", "cp", "touch", "mkdir", "rmdir", "tee", "echo", "printf", "cat", "chmod", "ln",
+    "truncate", "head", "tail", "ls", "wc",
+];
+const SHELLS: [&str; 6] = ["sh", "bash", "zsh", "pwsh", "powershell", "cmd"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PerformanceMode {
+    /// 索引条目数超过阈值时启用
+    #[default]
+    Auto,
+    Always,
+    Off,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChangeDetectionConfig {
+    pub performance_mode: PerformanceMode,
+    /// `auto` 模式下视为大仓库的索引条目数
+    pub large_repo_threshold: u32,
+    /// 性能模式下启用 git 内置 fsmonitor
+    pub use_fsmonitor: bool,
+    /// 性能模式下单次 git status 的时间预算（毫秒）
+    pub time_budget_ms: u64,
+}
+
+impl Default for ChangeDetectionConfig {
+    fn default() -> Self {
+        Self {
+            performance_mode: PerformanceMode::Auto,
+            large_repo_threshold: 50_000,
+            use_fsmonitor: false,
+            time_budget_ms: 2_000,
+        }
+    }
+}
+
+impl ChangeDetectionConfig {
+    pub fn performance_enabled(&self, index_entries: Option<u32>) -> bool {
+        match self.performance_mode {
+            PerformanceMode::Always => true,
+            PerformanceMode::Off => false,
+            PerformanceMode::Auto => index_entries.is_some_and(|n| n >= self.large_repo_threshold),
+        }
+    }
+}
+
+/// 性能模式下 git status 的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusOutcome {
+    /// 有变更的路径（项目相对路径；未跟踪目录以 '/' 结尾）
+    Known(Vec<String>),
+    /// 无法在时间预算内得到结果，附带原因
+    Unknown(String),
+}
+
+pub fn load_config() -> ChangeDetectionConfig {
+    let config = open_agent_db().and_then(|conn| read_setting(&conn, CHANGE_DETECTION_KEY));
+    match config {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("[ChangeDetection] Failed to load config: {}", e);
+            ChangeDetectionConfig::default()
+        }
+    }
+}
+
+/// Git 索引中的条目数（只读取索引文件头）
+///
+/// 索引位置由 `git rev-parse --git-path index` 给出，worktree、子模块和 `GIT_INDEX_FILE` 都能正确定位。
+pub fn index_entry_count(project_path: &str) -> Option<u32> {
+    let mut cmd = Command::new("git");
+    cmd.args(["rev-parse", "--git-path", "index"]);
+    cmd.current_dir(project_path);
+    cmd.stdin(Stdio::null()).stderr(Stdio::null());
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd.output().ok().filter(|o| o.status.success())?;
+    let index_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if index_path.is_empty() {
+        return None;
+    }
+    // 相对路径相对于项目目录
+    let mut header = [0u8; 12];
+    let mut file = std::fs::File::open(Path::new(project_path).join(index_path)).ok()?;
+    file.read_exact(&mut header).ok()?;
+    if &header[..4] != b"DIRC" {
+        return None;
+    }
+    Some(u32::from_be_bytes([header[8], header[9], header[10], header[11]]))
+}
+
+/// 把命令拆成若干简单命令（按 `&&`、`||`、`;`、`|` 分隔），`bash -lc '...'` 会展开内部脚本
+fn simple_commands(command: &str) -> Option<Vec<Vec<String>>> {
+    let normalized = command.replace("&&", " ; ").replace("||", " ; ").replace('|', " ; ");
+    let tokens = shell_words::split(&normalized).ok()?;
+    let mut commands = Vec::new();
+    for part in tokens.split(|t| t == ";").filter(|p| !p.is_empty()) {
+        let program = part[0].rsplit(['/', '\\']).next().unwrap_or_default().trim_end_matches(".exe");
+        let script = part.iter().position(|t| (t.starts_with('-') && t.ends_with('c')) || t == "/C");
+        match script {
+            Some(i) if SHELLS.contains(&program) && i + 1 < part.len() => {
+                commands.extend(simple_commands(&part[i + 1..].join(" "))?);
+            }
+            _ => commands.push(part.to_vec()),
+        }
+    }
+    Some(commands)
+}
+
+/// 简单命令中可能是路径的参数；sed 的脚本（第一个位置参数或 `-e`/`-f` 的值）不是路径
+fn path_args(args: &[String]) -> Vec<&String> {
+    if args[0] != "sed" {
+        return args[1..].iter().collect();
+    }
+    let has_script_option = args[1..]
+        .iter()
+        .any(|a| a == "-e" || a == "-f" || a.starts_with("--expression") || a.starts_with("--file"));
+    let mut paths = Vec::new();
+    let mut skip_next = false;
+    let mut script_seen = has_script_option;
+    for arg in &args[1..] {
+        if skip_next {
+            skip_next = false;
+        } else if arg == "-e" || arg == "-f" || arg == "--expression" || arg == "--file" {
+            skip_next = true;
+        } else if arg.starts_with('-') {
+            continue;
+        } else if !script_seen {
+            script_seen = true;
+        } else {
+            paths.push(arg);
+        }
+    }
+    paths
+}
+
+/// 从命令参数推导 pathspec
+///
+/// 不检查路径是否存在：命令前后推导出的 pathspec 必须一致，不存在的路径（包括 sed 脚本等
+/// 非路径参数）不会匹配任何文件。返回空列表表示无法确定范围（命令不在白名单中、没有路径
+/// 参数等），此时检查整个仓库。
+pub fn command_pathspecs(project_path: &str, command: &str) -> Vec<String> {
+    let Some(commands) = simple_commands(command) else {
+        return Vec::new();
+    };
+    let root = Path::new(project_path);
+    let mut pathspecs: Vec<String> = Vec::new();
+    for args in &commands {
+        if !SCOPED_PROGRAMS.contains(&args[0].as_str()) {
+            return Vec::new();
+        }
+        for arg in path_args(args) {
+            let arg = arg.trim_start_matches(['>', '<']).trim_start_matches("1>").trim_start_matches("2>");
+            if arg.is_empty() || arg.starts_with(['-', ':']) || arg.contains(['*', '?', '[', '$', '`']) {
+                continue;
+            }
+            let candidate = Path::new(arg);
+            if candidate.is_absolute() || arg.split(['/', '\\']).any(|c| c == "..") {
+                // 项目外或向上引用的路径无法用 pathspec 限定
+                if !candidate.starts_with(root) {
+                    continue;
+                }
+            }
+            let relative = candidate.strip_prefix(root).unwrap_or(candidate);
+            let pathspec = relative.to_string_lossy().replace('\\', "/");
+            let pathspec = pathspec.trim_start_matches("./").trim_end_matches('/');
+            let pathspec = if pathspec.is_empty() { "." } else { pathspec };
+            if !pathspecs.iter().any(|p| p == pathspec) {
+                pathspecs.push(pathspec.to_string());
+            }
+        }
+    }
+    // 没有路径参数、路径太多或包含项目根目录时直接检查整个仓库
+    if pathspecs.is_empty() || pathspecs.len() > MAX_PATHSPECS || pathspecs.iter().any(|p| p == ".") {
+        return Vec::new();
+    }
+    pathspecs
+}
+
+/// 解析 `git status --porcelain=v2 -z` 的输出
+pub fn parse_porcelain_v2(output: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        let path = match record.as_bytes()[0] {
+            b'1' => record.splitn(9, ' ').nth(8),
+            b'2' => {
+                let path = record.splitn(10, ' ').nth(9);
+                // 重命名记录后紧跟原路径
+                if let Some(original) = records.next() {
+                    paths.push(original.to_string());
+                }
+                path
+            }
+            b'u' => record.splitn(11, ' ').nth(10),
+            b'?' => record.get(2..),
+            _ => None,
+        };
+        if let Some(path) = path {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+/// 在时间预算内执行 `git status --porcelain=v2`
+pub fn git_status_within_budget(
+    project_path: &str,
+    pathspecs: &[String],
+    use_fsmonitor: bool,
+    budget: Duration,
+) -> Result<StatusOutcome, String> {
+    let mut cmd = Command::new("git");
+    if use_fsmonitor {
+        cmd.args(["-c", "core.fsmonitor=true", "-c", "core.untrackedCache=true"]);
+    }
+    cmd.args(["status", "--porcelain=v2", "--untracked-files=normal", "--no-renames", "-z", "--"]);
+    cmd.args(pathspecs);
+    cmd.current_dir(project_path);
+    cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let started = Instant::now();
+    let mut child = cmd.spawn().map_err(|e| format!("执行 git status 失败: {}", e))?;
+    // 在线程中读取 stdout 和 stderr，避免输出填满管道后 git 阻塞
+    let mut stdout = child.stdout.take().ok_or("无法读取 git status 输出")?;
+    let reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+    let mut stderr = child.stderr.take().ok_or("无法读取 git status 输出")?;
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf);
+        buf
+    });
+
+    let status = loop {
+        match child.try_wait().map_err(|e| format!("等待 git status 失败: {}", e))? {
+            Some(status) => break status,
+            None if started.elapsed() >= budget => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(StatusOutcome::Unknown(format!(
+                    "git status 超过 {} ms 未完成",
+                    budget.as_millis()
+                )));
+            }
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    };
+
+    let output = reader
+        .join()
+        .map_err(|_| "读取 git status 输出失败".to_string())?
+        .map_err(|e| format!("读取 git status 输出失败: {}", e))?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if !status.success() {
+        return Err(format!("git status 失败: {}", stderr.trim()));
+    }
+    log::debug!(
+        "[ChangeDetection] git status ({} pathspecs) took {} ms",
+        pathspecs.len(),
+        started.elapsed().as_millis()
+    );
+    Ok(StatusOutcome::Known(parse_porcelain_v2(&String::from_utf8_lossy(&output))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_pathspecs_and_parses_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        let specs = |command: &str| command_pathspecs(root, command);
+        // sed 的脚本不是路径
+        assert_eq!(specs("sed -i 's/a/b/' src/lib.rs"), vec!["src/lib.rs"]);
+        assert_eq!(specs("sed -i.bak -e 's/a/b/' -e 's/c/d/' a.rs b.rs"), vec!["a.rs", "b.rs"]);
+        assert_eq!(specs("bash -lc 'echo > src/new.rs && cat ./src/lib.rs'"), vec!["src/new.rs", "src/lib.rs"]);
+        assert_eq!(specs(&format!("rm -r {}/src/", root)), vec!["src"]);
+        // 无法确定范围时检查整个仓库
+        assert!(specs("cargo fmt").is_empty());
+        assert!(specs("cd src && touch x.rs").is_empty());
+        assert!(specs("rm src/*.rs").is_empty());
+        assert!(specs("cp -r src .").is_empty());
+
+        let output = "1 .M N... 100644 100644 100644 abc abc src/a b.rs\0\
+                      2 R. N... 100644 100644 100644 abc abc R100 new.rs\0old.rs\0\
+                      ? notes/\0";
+        assert_eq!(parse_porcelain_v2(output), vec!["src/a b.rs", "old.rs", "new.rs", "notes/"]);
+
+        let config = ChangeDetectionConfig::default();
+        assert!(!config.performance_enabled(Some(10)) && config.performance_enabled(Some(60_000)));
+        assert!(!config.performance_enabled(None));
+    }
+}
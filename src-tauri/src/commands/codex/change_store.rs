@@ -0,0 +1,265 @@
+//! Codex 变更记录持久化（SQLite）
+//!
+//! 变更记录以「每个变更一行」的形式存储在 agents.db 中，写入在事务内完成，
+//! 避免多个会话同时写整份 JSON 文件时互相覆盖。
+//! 旧版 `~/.codex/change-records/<session_id>.json` 在首次读取时自动导入，
+//! 同时保留 JSON 导出作为兼容路径。
+
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+
+use super::change_tracker::{CodexChangeRecords, CodexFileChange};
+use crate::commands::db_pool::PooledConnection;
+use crate::commands::storage::open_agent_db;
+
+/// 创建变更记录相关表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS codex_change_sessions (
+            session_id TEXT PRIMARY KEY,
+            project_path TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS codex_change_records (
+            session_id TEXT NOT NULL,
+            change_id TEXT NOT NULL,
+            seq INTEGER NOT NULL,
+            prompt_index INTEGER NOT NULL,
+            file_path TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            PRIMARY KEY (session_id, change_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_codex_change_records_session
+            ON codex_change_records(session_id, seq);",
+    )
+    .map_err(|e| format!("创建变更记录表失败: {}", e))
+}
+
+/// 打开 agents.db 并确保表结构存在
+pub fn open_change_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+/// 按变更 ID 查找所属会话
+pub fn find_change_session(conn: &Connection, change_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT session_id FROM codex_change_records WHERE change_id = ?1 LIMIT 1",
+        params![change_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("查询变更记录失败: {}", e))
+}
+
+/// 读取会话的全部变更记录（按写入顺序）
+pub fn load_session(conn: &Connection, session_id: &str) -> Result<Option<CodexChangeRecords>, String> {
+    let header = conn
+        .query_row(
+            "SELECT project_path, created_at, updated_at FROM codex_change_sessions WHERE session_id = ?1",
+            params![session_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+        )
+        .optional()
+        .map_err(|e| format!("查询变更会话失败: {}", e))?;
+
+    let Some((project_path, created_at, updated_at)) = header else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT payload FROM codex_change_records WHERE session_id = ?1 ORDER BY seq ASC")
+        .map_err(|e| format!("查询变更记录失败: {}", e))?;
+    let payloads = stmt
+        .query_map(params![session_id], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("查询变更记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取变更记录失败: {}", e))?;
+
+    let mut changes = Vec::with_capacity(payloads.len());
+    for payload in payloads {
+        match serde_json::from_str::<CodexFileChange>(&payload) {
+            Ok(change) => changes.push(change),
+            Err(e) => log::warn!("[ChangeStore] Skipping malformed change row ({}): {}", session_id, e),
+        }
+    }
+
+    Ok(Some(CodexChangeRecords {
+        session_id: session_id.to_string(),
+        project_path,
+        created_at,
+        updated_at,
+        changes,
+    }))
+}
+
+fn upsert_session_header(conn: &Connection, records: &CodexChangeRecords) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO codex_change_sessions (session_id, project_path, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(session_id) DO UPDATE SET
+            project_path = excluded.project_path,
+            updated_at = excluded.updated_at",
+        params![
+            records.session_id,
+            records.project_path,
+            records.created_at,
+            records.updated_at
+        ],
+    )
+}
+
+fn upsert_change_row(conn: &Connection, seq: usize, change: &CodexFileChange) -> Result<(), String> {
+    let payload = serde_json::to_string(change).map_err(|e| format!("序列化失败: {}", e))?;
+    conn.execute(
+        "INSERT INTO codex_change_records
+            (session_id, change_id, seq, prompt_index, file_path, timestamp, payload)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(session_id, change_id) DO UPDATE SET
+            seq = excluded.seq,
+            prompt_index = excluded.prompt_index,
+            file_path = excluded.file_path,
+            timestamp = excluded.timestamp,
+            payload = excluded.payload",
+        params![
+            change.session_id,
+            change.id,
+            seq as i64,
+            change.prompt_index,
+            change.file_path,
+            change.timestamp,
+            payload
+        ],
+    )
+    .map_err(|e| format!("写入变更记录失败: {}", e))?;
+    Ok(())
+}
+
+/// 写入（或更新）单条变更，只触碰该变更所在的行
+pub fn upsert_change(conn: &mut Connection, records: &CodexChangeRecords, change_id: &str) -> Result<(), String> {
+    let (seq, change) = records
+        .changes
+        .iter()
+        .enumerate()
+        .find(|(_, c)| c.id == change_id)
+        .ok_or_else(|| format!("变更 {} 未找到", change_id))?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    upsert_session_header(&tx, records).map_err(|e| format!("写入变更会话失败: {}", e))?;
+    upsert_change_row(&tx, seq, change)?;
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+}
+
+/// 用内存中的记录整体替换会话（用于截断、升级、修复）
+pub fn replace_session(conn: &mut Connection, records: &CodexChangeRecords) -> Result<(), String> {
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    upsert_session_header(&tx, records).map_err(|e| format!("写入变更会话失败: {}", e))?;
+    tx.execute(
+        "DELETE FROM codex_change_records WHERE session_id = ?1",
+        params![records.session_id],
+    )
+    .map_err(|e| format!("清理旧变更记录失败: {}", e))?;
+    for (seq, change) in records.changes.iter().enumerate() {
+        upsert_change_row(&tx, seq, change)?;
+    }
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+}
+
+/// 删除会话的全部变更记录
+pub fn delete_session(conn: &mut Connection, session_id: &str) -> Result<(), String> {
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    tx.execute("DELETE FROM codex_change_records WHERE session_id = ?1", params![session_id])
+        .map_err(|e| format!("删除变更记录失败: {}", e))?;
+    tx.execute("DELETE FROM codex_change_sessions WHERE session_id = ?1", params![session_id])
+        .map_err(|e| format!("删除变更会话失败: {}", e))?;
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::codex::change_tracker::{ChangeSource, ChangeType};
+
+    fn change(session_id: &str, id: &str, prompt_index: i32) -> CodexFileChange {
+        CodexFileChange {
+            id: id.to_string(),
+            session_id: session_id.to_string(),
+            prompt_index,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            file_path: "src/main.rs".to_string(),
+            change_type: ChangeType::Update,
+            source: ChangeSource::Tool,
+            old_content: Some("a".to_string()),
+            new_content: Some("b".to_string()),
+            unified_diff: None,
+            lines_added: Some(1),
+            lines_removed: Some(1),
+            tool_name: None,
+            tool_call_id: None,
+            command: None,
+        }
+    }
+
+    fn records(session_id: &str, changes: Vec<CodexFileChange>) -> CodexChangeRecords {
+        CodexChangeRecords {
+            session_id: session_id.to_string(),
+            project_path: "/tmp/project".to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            changes,
+        }
+    }
+
+    #[test]
+    fn sessions_on_same_project_do_not_clobber_each_other() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let a = records("a", vec![change("a", "change_a_0", 0)]);
+        let b = records("b", vec![change("b", "change_b_0", 0), change("b", "change_b_1", 1)]);
+        upsert_change(&mut conn, &a, "change_a_0").unwrap();
+        replace_session(&mut conn, &b).unwrap();
+
+        assert_eq!(load_session(&conn, "a").unwrap().unwrap().changes.len(), 1);
+        assert_eq!(load_session(&conn, "b").unwrap().unwrap().changes.len(), 2);
+    }
+
+    #[test]
+    fn finds_the_session_that_owns_a_change() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+
+        replace_session(&mut conn, &records("a", vec![change("a", "change_a_0", 0)])).unwrap();
+        replace_session(&mut conn, &records("b", vec![change("b", "change_b_0", 0)])).unwrap();
+
+        assert_eq!(find_change_session(&conn, "change_b_0").unwrap().as_deref(), Some("b"));
+        assert_eq!(find_change_session(&conn, "change_a_0").unwrap().as_deref(), Some("a"));
+        assert_eq!(find_change_session(&conn, "missing").unwrap(), None);
+    }
+
+    #[test]
+    fn replace_session_drops_truncated_rows_and_keeps_order() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let mut r = records("s", vec![change("s", "c0", 0), change("s", "c1", 1), change("s", "c2", 2)]);
+        replace_session(&mut conn, &r).unwrap();
+        r.changes.retain(|c| c.prompt_index <= 1);
+        replace_session(&mut conn, &r).unwrap();
+
+        let loaded = load_session(&conn, "s").unwrap().unwrap();
+        let ids: Vec<_> = loaded.changes.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["c0", "c1"]);
+
+        delete_session(&mut conn, "s").unwrap();
+        assert!(load_session(&conn, "s").unwrap().is_none());
+    }
+}
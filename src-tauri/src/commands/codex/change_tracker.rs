@@ -4,21 +4,27 @@
 //! - 自动记录文件创建、修改、删除
 //! - 通过 git status 检测命令执行的副作用
 //! - 导出为 patch 文件（可在 IDEA 中打开）
-//! - 持久化存储到 JSON 文件
+//! - 持久化存储到 SQLite（agents.db），JSON 仅作为导入/导出兼容格式
 
+use crate::error::AnyCodeError;
 use chrono::Utc;
 use log;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use once_cell::sync::Lazy;
 use tauri::{AppHandle, Emitter};
 
+use super::change_detection::{self, StatusOutcome};
+use super::change_store;
 use super::git_ops::load_codex_git_records;
+use super::super::simple_git;
+use super::super::trash;
 use super::super::wsl_utils;
 
 #[cfg(target_os = "windows")]
@@ -104,12 +110,35 @@ pub struct CodexChangeRecords {
     pub changes: Vec<CodexFileChange>,
 }
 
+/// patch 导出格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchFormat {
+    /// 按记录顺序拼接每个变更的 diff
+    #[default]
+    Raw,
+    /// 每个文件合并为一个 git 格式 diff（带 new/deleted/rename 标记），IDEA 可直接应用
+    Idea,
+}
+
 /// 内存中的变更追踪器（按会话 ID 索引）
 static CHANGE_TRACKERS: Lazy<Mutex<HashMap<String, CodexChangeRecords>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// 文件快照缓存（用于命令执行前后对比）
-static FILE_SNAPSHOTS: Lazy<Mutex<HashMap<String, HashMap<String, String>>>> =
+/// 性能模式下展开单个未跟踪目录时最多读取的文件数
+const MAX_UNTRACKED_DIR_FILES: usize = 200;
+
+/// 命令执行前的文件快照，超过这个时间仍未检测的（命令被取消等）在下次保存快照时丢弃
+const COMMAND_SNAPSHOT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// 单条命令执行前的文件内容（相对路径 → 内容）
+struct CommandSnapshot {
+    files: HashMap<String, String>,
+    taken_at: Instant,
+}
+
+/// 文件快照缓存（用于命令执行前后对比），按 (会话 ID, 命令 call_id) 区分同一会话中并发的命令
+static FILE_SNAPSHOTS: Lazy<Mutex<HashMap<(String, String), CommandSnapshot>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// 获取变更记录存储目录
@@ -124,31 +153,78 @@ fn get_change_records_dir() -> Result<PathBuf, String> {
     Ok(dir)
 }
 
-/// 获取会话变更记录文件路径
+/// 获取会话变更记录文件路径（旧版 JSON 存储 / 导出默认路径）
 fn get_change_records_path(session_id: &str) -> Result<PathBuf, String> {
     let dir = get_change_records_dir()?;
     Ok(dir.join(format!("{}.json", session_id)))
 }
 
+/// 从数据库加载会话记录；数据库中没有时尝试导入旧版 JSON 文件
+fn load_persisted_records(session_id: &str) -> Result<Option<CodexChangeRecords>, String> {
+    let mut conn = change_store::open_change_db()?;
+    if let Some(records) = change_store::load_session(&conn, session_id)? {
+        return Ok(Some(records));
+    }
+
+    let legacy_path = get_change_records_path(session_id)?;
+    if !legacy_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&legacy_path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let records: CodexChangeRecords =
+        serde_json::from_str(&content).map_err(|e| format!("解析 JSON 失败: {}", e))?;
+    change_store::replace_session(&mut conn, &records)?;
+
+    log::info!(
+        "[ChangeTracker] Imported legacy JSON change records for session {} ({} changes)",
+        session_id,
+        records.changes.len()
+    );
+    Ok(Some(records))
+}
+
+/// 整体写回会话记录（截断 / 升级 / 修复后使用）
+fn persist_records(records: &CodexChangeRecords) -> Result<(), String> {
+    let mut conn = change_store::open_change_db()?;
+    change_store::replace_session(&mut conn, records)
+}
+
+/// 加载并升级会话记录，升级后的结果会写回数据库
+fn load_and_upgrade_records(session_id: &str, context: &str) -> Result<Option<CodexChangeRecords>, String> {
+    let Some(mut records) = load_persisted_records(session_id)? else {
+        return Ok(None);
+    };
+
+    // Upgrade legacy records (normalize paths / merge duplicates / backfill diff context)
+    if upgrade_change_records(session_id, &mut records) {
+        if let Err(e) = persist_records(&records) {
+            log::warn!(
+                "[ChangeTracker] Failed to persist upgraded records on {} ({}): {}",
+                context,
+                session_id,
+                e
+            );
+        }
+    }
+
+    Ok(Some(records))
+}
+
 /// Truncate change records after a specific prompt index (inclusive).
 ///
 /// This is important when the session is truncated (rewind/revert conversation),
 /// otherwise stale change entries for prompts that no longer exist will remain
 /// and break "files changed" + history ordering.
 pub fn truncate_change_records_after_prompt(session_id: &str, prompt_index: i32) -> Result<usize, String> {
-    let path = get_change_records_path(session_id)?;
-
-    // Load records from memory first, then file.
+    // Load records from memory first, then database.
     let mut records: Option<CodexChangeRecords> = {
         let trackers = CHANGE_TRACKERS.lock().unwrap();
         trackers.get(session_id).cloned()
     };
 
-    if records.is_none() && path.exists() {
-        let content = fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))?;
-        let parsed: CodexChangeRecords =
-            serde_json::from_str(&content).map_err(|e| format!("解析 JSON 失败: {}", e))?;
-        records = Some(parsed);
+    if records.is_none() {
+        records = load_persisted_records(session_id)?;
     }
 
     let Some(mut records) = records else {
@@ -165,9 +241,8 @@ pub fn truncate_change_records_after_prompt(session_id: &str, prompt_index: i32)
 
     records.updated_at = Utc::now().to_rfc3339();
 
-    // Persist to disk (even if the tracker wasn't initialized in-memory).
-    let content = serde_json::to_string_pretty(&records).map_err(|e| format!("序列化失败: {}", e))?;
-    fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    // Persist (even if the tracker wasn't initialized in-memory).
+    persist_records(&records)?;
 
     // Update in-memory cache.
     let mut trackers = CHANGE_TRACKERS.lock().unwrap();
@@ -333,12 +408,10 @@ fn read_text_best_effort(path: &Path) -> Option<String> {
     fs::read_to_string(path).ok()
 }
 
-fn normalize_file_path_for_record(project_path: &str, file_path: &str) -> String {
-    // Ensure project root uses the same "host" path style as resolve_full_path().
-    // This avoids cases where project_path is a WSL path but full_path is a Windows path,
-    // which would make relative path calculation fail and create duplicate entries.
+/// Project root in the same "host" path style as resolve_full_path().
+fn resolve_project_root(project_path: &str) -> String {
     #[cfg(target_os = "windows")]
-    let project_root = {
+    {
         let p = normalize_project_path_for_windows(project_path);
         if p.starts_with('/') {
             resolve_wsl_path_to_unc(&p)
@@ -347,9 +420,18 @@ fn normalize_file_path_for_record(project_path: &str, file_path: &str) -> String
         } else {
             p
         }
-    };
+    }
     #[cfg(not(target_os = "windows"))]
-    let project_root = project_path.to_string();
+    {
+        project_path.to_string()
+    }
+}
+
+fn normalize_file_path_for_record(project_path: &str, file_path: &str) -> String {
+    // Ensure project root uses the same "host" path style as resolve_full_path().
+    // This avoids cases where project_path is a WSL path but full_path is a Windows path,
+    // which would make relative path calculation fail and create duplicate entries.
+    let project_root = resolve_project_root(project_path);
 
     let full = resolve_full_path(project_path, file_path);
     let full_str = full.to_string_lossy();
@@ -427,32 +509,15 @@ fn get_commit_after_for_prompt(session_id: &str, prompt_index: i32) -> Option<St
 pub fn init_change_tracker(session_id: &str, project_path: &str) {
     let mut trackers = CHANGE_TRACKERS.lock().unwrap();
 
-    // 尝试从文件加载已有记录
-    if let Ok(path) = get_change_records_path(session_id) {
-        if path.exists() {
-            if let Ok(content) = fs::read_to_string(&path) {
-                if let Ok(mut records) = serde_json::from_str::<CodexChangeRecords>(&content) {
-                    // Upgrade legacy records (normalize paths / merge duplicates / backfill diff context)
-                    let upgraded = upgrade_change_records(session_id, &mut records);
-
-                    if upgraded {
-                        if let Ok(pretty) = serde_json::to_string_pretty(&records) {
-                            if let Err(e) = fs::write(&path, pretty) {
-                                log::warn!(
-                                    "[ChangeTracker] Failed to persist upgraded records on init ({}): {}",
-                                    session_id,
-                                    e
-                                );
-                            }
-                        }
-                    }
-
-                    log::info!("[ChangeTracker] 加载已有记录: {} 条变更", records.changes.len());
-                    trackers.insert(session_id.to_string(), records);
-                    return;
-                }
-            }
+    // 尝试从数据库加载已有记录（数据库是唯一可信来源，其他会话的写入也会被读到）
+    match load_and_upgrade_records(session_id, "init") {
+        Ok(Some(records)) => {
+            log::info!("[ChangeTracker] 加载已有记录: {} 条变更", records.changes.len());
+            trackers.insert(session_id.to_string(), records);
+            return;
         }
+        Ok(None) => {}
+        Err(e) => log::warn!("[ChangeTracker] Failed to load change records ({}): {}", session_id, e),
     }
 
     // 创建新记录
@@ -725,7 +790,7 @@ pub fn record_file_change(
 
         // Persist
         drop(trackers);
-        save_change_records(session_id)?;
+        save_change(session_id, &existing_id)?;
 
         log::info!("[ChangeTracker] 合并文件变更: {} ({})", file_path, existing_id);
         return Ok(existing_id);
@@ -800,9 +865,9 @@ pub fn record_file_change(
     records.changes.push(change);
     records.updated_at = now;
 
-    // 持久化到文件
+    // 持久化到数据库
     drop(trackers);
-    save_change_records(session_id)?;
+    save_change(session_id, &id)?;
 
     log::info!("[ChangeTracker] 记录文件变更: {} ({})", file_path, id);
     Ok(id)
@@ -1084,21 +1149,18 @@ fn repair_tool_fragment_changes(records: &mut CodexChangeRecords) -> bool {
     mutated
 }
 
-/// 保存变更记录到文件
-fn save_change_records(session_id: &str) -> Result<(), String> {
+/// 保存单条变更到数据库（只写该变更所在的行）
+fn save_change(session_id: &str, change_id: &str) -> Result<(), String> {
     let trackers = CHANGE_TRACKERS.lock().unwrap();
 
     let records = trackers
         .get(session_id)
         .ok_or_else(|| format!("会话 {} 未初始化", session_id))?;
 
-    let path = get_change_records_path(session_id)?;
-    let content = serde_json::to_string_pretty(records)
-        .map_err(|e| format!("序列化失败: {}", e))?;
+    let mut conn = change_store::open_change_db()?;
+    change_store::upsert_change(&mut conn, records, change_id)?;
 
-    fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
-
-    log::debug!("[ChangeTracker] 保存变更记录到: {:?}", path);
+    log::debug!("[ChangeTracker] 保存变更记录: {} ({})", change_id, session_id);
     Ok(())
 }
 
@@ -1113,46 +1175,174 @@ pub fn get_file_content_before(project_path: &str, file_path: &str) -> Option<St
     fs::read_to_string(&full_path).ok()
 }
 
-/// 在命令执行前保存文件快照（用于检测副作用）
-pub fn snapshot_files_before_command(session_id: &str, project_path: &str) -> Result<(), String> {
-    let changed_files = get_git_changed_files(project_path)?;
-    let mut snapshots = FILE_SNAPSHOTS.lock().unwrap();
+/// 命令执行后的变更检测结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandChangeDetection {
+    pub change_ids: Vec<String>,
+    /// 变更未知的原因（性能模式下超出时间预算），此时没有记录任何变更
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_reason: Option<String>,
+}
 
-    let session_snapshots = snapshots.entry(session_id.to_string()).or_insert_with(HashMap::new);
-    session_snapshots.clear();
+/// 命令可能影响的变更文件；大仓库使用性能模式（见 change_detection 模块）
+fn changed_files_for_command(project_path: &str, command: &str) -> Result<StatusOutcome, String> {
+    let config = change_detection::load_config();
+    if !config.performance_enabled(change_detection::index_entry_count(project_path)) {
+        return get_git_changed_files(project_path).map(StatusOutcome::Known);
+    }
 
+    let pathspecs = change_detection::command_pathspecs(project_path, command);
+    let budget = std::time::Duration::from_millis(config.time_budget_ms);
+    let outcome = change_detection::git_status_within_budget(project_path, &pathspecs, config.use_fsmonitor, budget)?;
+    let StatusOutcome::Known(paths) = outcome else {
+        return Ok(outcome);
+    };
+    // --untracked-files=normal 只报告未跟踪目录本身，展开其中的文件
+    let mut files = Vec::new();
+    for path in paths {
+        match path.strip_suffix('/') {
+            Some(dir) => files.extend(
+                walkdir::WalkDir::new(Path::new(project_path).join(dir))
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .take(MAX_UNTRACKED_DIR_FILES)
+                    .filter_map(|e| {
+                        e.path()
+                            .strip_prefix(project_path)
+                            .ok()
+                            .map(|p| p.to_string_lossy().replace('\\', "/"))
+                    }),
+            ),
+            None => files.push(path),
+        }
+    }
+    Ok(StatusOutcome::Known(files))
+}
+
+/// 在命令执行前保存文件快照（用于检测副作用）
+pub fn snapshot_files_before_command(
+    session_id: &str,
+    call_id: &str,
+    project_path: &str,
+    command: &str,
+) -> Result<(), String> {
+    let key = (session_id.to_string(), call_id.to_string());
+    let changed_files = match changed_files_for_command(project_path, command)? {
+        StatusOutcome::Known(files) => files,
+        StatusOutcome::Unknown(reason) => {
+            // 没有快照时命令后的检测也无法区分命令前已有的修改
+            log::warn!("[ChangeTracker] Skipping pre-command snapshot: {}", reason);
+            FILE_SNAPSHOTS.lock().unwrap().remove(&key);
+            return Ok(());
+        }
+    };
+
+    let mut files = HashMap::new();
     for file in &changed_files {
         let full_path = Path::new(project_path).join(file);
         if full_path.exists() {
             if let Ok(content) = fs::read_to_string(&full_path) {
-                session_snapshots.insert(file.clone(), content);
+                files.insert(file.clone(), content);
             }
         }
     }
 
-    // 同时记录所有现有文件的列表（用于检测新建文件）
-    // 这里简化处理，只记录 git tracked 的文件
-
-    log::debug!("[ChangeTracker] 保存文件快照: {} 个文件", session_snapshots.len());
+    log::debug!("[ChangeTracker] 保存文件快照: {} 个文件", files.len());
+    let mut snapshots = FILE_SNAPSHOTS.lock().unwrap();
+    snapshots.retain(|_, s| s.taken_at.elapsed() < COMMAND_SNAPSHOT_TTL);
+    snapshots.insert(key, CommandSnapshot { files, taken_at: Instant::now() });
     Ok(())
 }
 
+/// 在 Codex 输出流中捕获命令开始事件，在转发给前端之前保存命令执行前的文件快照
+///
+/// 前端收到开始事件再请求快照时命令可能已经改了文件；在读取输出的任务中同步保存快照，
+/// 之后的事件（包括命令完成事件）都在快照保存后才会转发。
+pub struct CommandSnapshotter {
+    project_path: String,
+    /// 执行时已知的会话 ID，收到 `thread.started` 后替换为 Codex 的 thread_id（变更记录使用的 ID）
+    session_id: Mutex<String>,
+}
+
+impl CommandSnapshotter {
+    pub fn new(session_id: &str, project_path: &str) -> Self {
+        Self {
+            project_path: project_path.to_string(),
+            session_id: Mutex::new(session_id.to_string()),
+        }
+    }
+
+    /// 处理一行原始输出；快照失败只记录日志
+    pub async fn observe(&self, line: &str) {
+        if !line.contains("\"thread.started\"") && !line.contains("\"item.started\"") {
+            return;
+        }
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+            return;
+        };
+        if let Some(session_id) = crate::commands::command_audit::extract_session_id("codex", &event) {
+            *self.session_id.lock().unwrap() = session_id;
+            return;
+        }
+        let item = &event["item"];
+        if event["type"] != "item.started" || item["type"] != "command_execution" {
+            return;
+        }
+        let (Some(call_id), Some(command)) = (item["id"].as_str(), item["command"].as_str()) else {
+            return;
+        };
+
+        let session_id = self.session_id.lock().unwrap().clone();
+        let (call_id, command, project_path) = (call_id.to_string(), command.to_string(), self.project_path.clone());
+        let result = tokio::task::spawn_blocking(move || {
+            snapshot_files_before_command(&session_id, &call_id, &project_path, &command)
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::warn!("[ChangeTracker] Failed to snapshot before command: {}", e),
+            Err(e) => log::warn!("[ChangeTracker] Failed to snapshot before command: {}", e),
+        }
+    }
+}
+
 /// 在命令执行后检测文件变更
 pub fn detect_changes_after_command(
     session_id: &str,
+    call_id: &str,
     project_path: &str,
     prompt_index: i32,
     command: &str,
-) -> Result<Vec<String>, String> {
-    let changed_files = get_git_changed_files(project_path)?;
-    let snapshots = FILE_SNAPSHOTS.lock().unwrap();
-    let session_snapshots = snapshots.get(session_id);
+) -> Result<CommandChangeDetection, String> {
+    // 每条命令的快照只用一次
+    let snapshot = FILE_SNAPSHOTS
+        .lock()
+        .unwrap()
+        .remove(&(session_id.to_string(), call_id.to_string()));
+    let changed_files = match changed_files_for_command(project_path, command)? {
+        StatusOutcome::Known(files) => files,
+        StatusOutcome::Unknown(reason) => {
+            log::warn!("[ChangeTracker] Changes after command unknown: {}", reason);
+            return Ok(CommandChangeDetection {
+                change_ids: Vec::new(),
+                unknown_reason: Some(reason),
+            });
+        }
+    };
+    let Some(snapshot) = snapshot else {
+        return Ok(CommandChangeDetection {
+            change_ids: Vec::new(),
+            unknown_reason: Some("命令执行前没有文件快照".to_string()),
+        });
+    };
 
     let mut change_ids = Vec::new();
 
     for file in &changed_files {
         let full_path = Path::new(project_path).join(file);
-        let mut old_content = session_snapshots.and_then(|s| s.get(file).cloned());
+        let mut old_content = snapshot.files.get(file).cloned();
         let new_content = if full_path.exists() {
             fs::read_to_string(&full_path).ok()
         } else {
@@ -1209,7 +1399,10 @@ pub fn detect_changes_after_command(
     }
 
     log::info!("[ChangeTracker] 命令执行后检测到 {} 个文件变更", change_ids.len());
-    Ok(change_ids)
+    Ok(CommandChangeDetection {
+        change_ids,
+        unknown_reason: None,
+    })
 }
 
 /// 通过 git status 获取变更文件列表
@@ -1246,7 +1439,7 @@ fn get_git_changed_files(project_path: &str) -> Result<Vec<String>, String> {
 }
 
 /// 生成 unified diff 格式
-fn generate_unified_diff(file_path: &str, old_content: &str, new_content: &str) -> String {
+pub(crate) fn generate_unified_diff(file_path: &str, old_content: &str, new_content: &str) -> String {
     if let Some(diff) = generate_unified_diff_via_git(file_path, old_content, new_content) {
         return diff;
     }
@@ -1421,7 +1614,7 @@ fn generate_unified_diff_naive(file_path: &str, old_content: &str, new_content:
 }
 
 /// 生成创建文件的 diff
-fn generate_create_diff(file_path: &str, content: &str) -> String {
+pub(crate) fn generate_create_diff(file_path: &str, content: &str) -> String {
     use std::fmt::Write;
 
     let mut diff = String::new();
@@ -1523,6 +1716,126 @@ pub fn export_session_as_patch(session_id: &str) -> Result<String, String> {
     Ok(patch)
 }
 
+/// 读取会话的全部变更（优先内存，其次数据库）
+fn load_session_changes(session_id: &str) -> Result<Vec<CodexFileChange>, String> {
+    {
+        let trackers = CHANGE_TRACKERS.lock().unwrap();
+        if let Some(records) = trackers.get(session_id) {
+            return Ok(records.changes.clone());
+        }
+    }
+    load_and_upgrade_records(session_id, "export_patch")?
+        .map(|records| records.changes)
+        .ok_or_else(|| format!("会话 {} 未找到", session_id))
+}
+
+/// 生成 IDEA 可应用的 patch
+///
+/// 先把每个文件的全部记录合并为净变更（最早的旧内容 -> 最新的新内容），再配对删除与
+/// 内容相同的新建输出为重命名（与两者的先后顺序无关），最后按文件首次出现的顺序输出。
+pub(crate) fn build_idea_patch(changes: &[CodexFileChange]) -> String {
+    // 路径 -> (修改前, 修改后)，None 表示文件不存在
+    let mut order: Vec<String> = Vec::new();
+    let mut files: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
+    for change in changes {
+        let path = change.file_path.replace('\\', "/");
+        let path = path.trim_start_matches("./").to_string();
+        let new = match change.change_type {
+            ChangeType::Delete => None,
+            _ => Some(change.new_content.clone().unwrap_or_default()),
+        };
+        match files.get_mut(&path) {
+            Some(entry) => entry.1 = new,
+            None => {
+                let old = match change.change_type {
+                    ChangeType::Create => None,
+                    _ => Some(change.old_content.clone().unwrap_or_default()),
+                };
+                order.push(path.clone());
+                files.insert(path, (old, new));
+            }
+        }
+    }
+
+    // 删除的路径 -> 重命名后的路径
+    let mut renames: HashMap<&str, &str> = HashMap::new();
+    let mut rename_targets: HashSet<&str> = HashSet::new();
+    for path in &order {
+        let (Some(old), None) = &files[path] else {
+            continue;
+        };
+        if old.is_empty() {
+            continue;
+        }
+        let target = order.iter().find(|other| {
+            !rename_targets.contains(other.as_str())
+                && matches!(&files[*other], (None, Some(content)) if content == old)
+        });
+        if let Some(target) = target {
+            renames.insert(path, target);
+            rename_targets.insert(target);
+        }
+    }
+
+    let mut patch = String::new();
+    for path in &order {
+        if rename_targets.contains(path.as_str()) {
+            continue;
+        }
+        if let Some(to) = renames.get(path.as_str()) {
+            patch.push_str(&format!(
+                "diff --git a/{} b/{}\nsimilarity index 100%\nrename from {}\nrename to {}\n",
+                path, to, path, to
+            ));
+            continue;
+        }
+        let (old, new) = &files[path];
+        match (old, new) {
+            (None, None) => {}
+            (Some(old), Some(new)) if old == new => {}
+            (Some(old), Some(new)) => {
+                patch.push_str(&format!("diff --git a/{} b/{}\n", path, path));
+                patch.push_str(&format!("--- a/{}\n+++ b/{}\n", path, path));
+                patch.push_str(&diff_hunks(path, old, new));
+            }
+            (None, Some(new)) => {
+                patch.push_str(&format!("diff --git a/{} b/{}\nnew file mode 100644\n", path, path));
+                if !new.is_empty() {
+                    patch.push_str(&format!("--- /dev/null\n+++ b/{}\n", path));
+                    patch.push_str(&diff_hunks(path, "", new));
+                }
+            }
+            (Some(old), None) => {
+                patch.push_str(&format!("diff --git a/{} b/{}\ndeleted file mode 100644\n", path, path));
+                if !old.is_empty() {
+                    patch.push_str(&format!("--- a/{}\n+++ /dev/null\n", path));
+                    patch.push_str(&diff_hunks(path, old, ""));
+                }
+            }
+        }
+    }
+    patch
+}
+
+/// 只保留 diff 中的 hunk 部分（从第一个 `@@` 开始）
+fn diff_hunks(file_path: &str, old_content: &str, new_content: &str) -> String {
+    let diff = generate_unified_diff(file_path, old_content, new_content);
+    let mut hunks = String::new();
+    for line in diff.lines().skip_while(|line| !line.starts_with("@@")) {
+        hunks.push_str(line);
+        hunks.push('\n');
+    }
+    hunks
+}
+
+/// 按指定格式导出整个会话的变更
+pub fn export_session_patch(session_id: &str, format: PatchFormat) -> Result<String, String> {
+    match format {
+        PatchFormat::Raw => export_session_as_patch(session_id),
+        PatchFormat::Idea => Ok(build_idea_patch(&load_session_changes(session_id)?)),
+    }
+}
+
 /// 导出单个变更为 patch 文件
 pub fn export_single_change_as_patch(session_id: &str, change_id: &str) -> Result<String, String> {
     let trackers = CHANGE_TRACKERS.lock().unwrap();
@@ -1543,6 +1856,166 @@ pub fn export_single_change_as_patch(session_id: &str, change_id: &str) -> Resul
         .ok_or_else(|| "该变更没有 diff 内容".to_string())
 }
 
+// ===== 部分回滚（保留手动修改）=====
+
+/// 某个提示词中 Codex 修改过的文件（项目相对路径）
+pub fn prompt_changed_files(session_id: &str, prompt_index: i32) -> Vec<String> {
+    let changes = match load_session_changes(session_id) {
+        Ok(changes) => changes,
+        Err(_) => return Vec::new(),
+    };
+    let mut files: Vec<String> = changes
+        .into_iter()
+        .filter(|c| c.prompt_index == prompt_index)
+        .map(|c| normalize_separators_to_slash(&c.file_path).trim_start_matches("./").to_string())
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// 撤销单条变更的结果
+#[derive(Debug, PartialEq)]
+enum UndoStep {
+    Write(String),
+    Remove,
+    /// 文件已经是变更前的状态
+    Unchanged,
+    /// 变更后文件又被修改，且无法自动合并
+    Conflict,
+    /// 记录中没有完整的变更前内容
+    MissingSnapshot,
+}
+
+/// 根据文件当前内容计算撤销 `change` 后的内容
+///
+/// 文件在变更后又被手动修改时，用 `git merge-file` 把 "new -> old" 应用到当前内容上，
+/// 这样只撤销 Codex 的修改而保留手动修改。
+fn undo_change(change: &CodexFileChange, current: Option<&str>) -> Result<UndoStep, String> {
+    let old = change.old_content.as_deref();
+    let new = change.new_content.as_deref();
+    let step = match change.change_type {
+        ChangeType::Create => match (current, new) {
+            (None, _) => UndoStep::Unchanged,
+            (Some(current), Some(new)) if current == new => UndoStep::Remove,
+            (Some(_), Some(_)) => UndoStep::Conflict,
+            (Some(_), None) => UndoStep::MissingSnapshot,
+        },
+        ChangeType::Delete => match (current, old) {
+            (_, None) => UndoStep::MissingSnapshot,
+            (None, Some(old)) => UndoStep::Write(old.to_string()),
+            (Some(current), Some(old)) if current == old => UndoStep::Unchanged,
+            (Some(_), Some(_)) => UndoStep::Conflict,
+        },
+        ChangeType::Update => {
+            let (Some(old), Some(new)) = (old, new) else {
+                return Ok(UndoStep::MissingSnapshot);
+            };
+            // 旧版记录中的 old_content 可能只是补丁片段，写回会丢失内容
+            if looks_like_fragment_text(old, new) {
+                return Ok(UndoStep::MissingSnapshot);
+            }
+            match current {
+                None => UndoStep::Conflict,
+                Some(current) if current == new => UndoStep::Write(old.to_string()),
+                Some(current) if current == old => UndoStep::Unchanged,
+                Some(current) => match simple_git::git_merge_file(current, new, old).map_err(|e| e.to_string())? {
+                    Some(merged) => UndoStep::Write(merged),
+                    None => UndoStep::Conflict,
+                },
+            }
+        }
+    };
+    Ok(step)
+}
+
+/// 部分回滚结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRevertReport {
+    /// 已恢复的文件
+    pub reverted: Vec<String>,
+    /// 缺少变更前内容、未处理的文件
+    pub skipped: Vec<String>,
+}
+
+/// 只撤销 Codex 在 `from_prompt_index` 及之后提示词中的文件修改，保留期间的手动修改
+///
+/// 先在内存中按记录倒序逐条撤销，任何文件无法自动合并时直接返回错误，不写入任何文件。
+/// 需要删除的文件（Codex 新建的文件）移入回收站。
+pub fn revert_agent_changes(
+    session_id: &str,
+    project_path: &str,
+    from_prompt_index: i32,
+) -> Result<AgentRevertReport, String> {
+    let mut changes: Vec<CodexFileChange> = load_session_changes(session_id)?
+        .into_iter()
+        .filter(|c| c.prompt_index >= from_prompt_index)
+        .collect();
+    changes.sort_by(|a, b| a.prompt_index.cmp(&b.prompt_index).then_with(|| a.timestamp.cmp(&b.timestamp)));
+
+    // 文件路径 -> (完整路径, 磁盘上的内容, 撤销后的内容)
+    let mut order: Vec<String> = Vec::new();
+    let mut files: HashMap<String, (PathBuf, Option<String>, Option<String>)> = HashMap::new();
+    let mut conflicts: Vec<String> = Vec::new();
+    let mut report = AgentRevertReport::default();
+    for change in changes.iter().rev() {
+        let file = files.entry(change.file_path.clone()).or_insert_with(|| {
+            order.push(change.file_path.clone());
+            let full_path = resolve_full_path(project_path, &change.file_path);
+            let content = read_text_best_effort(&full_path);
+            (full_path, content.clone(), content)
+        });
+        match undo_change(change, file.2.as_deref())? {
+            UndoStep::Write(content) => file.2 = Some(content),
+            UndoStep::Remove => file.2 = None,
+            UndoStep::Unchanged => {}
+            UndoStep::Conflict => conflicts.push(change.file_path.clone()),
+            UndoStep::MissingSnapshot => report.skipped.push(change.file_path.clone()),
+        }
+    }
+
+    if !conflicts.is_empty() {
+        conflicts.sort();
+        conflicts.dedup();
+        return Err(format!(
+            "以下文件在 Codex 修改后又被手动修改，无法自动撤销：{}",
+            conflicts.join(", ")
+        ));
+    }
+
+    for path in order {
+        let (full_path, on_disk, reverted) = &files[&path];
+        if on_disk == reverted {
+            continue;
+        }
+        match reverted {
+            Some(content) => {
+                if let Some(parent) = full_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+                }
+                fs::write(full_path, content).map_err(|e| format!("写入文件 {} 失败: {}", path, e))?;
+            }
+            None => {
+                trash::trash_project_file(project_path, full_path, Some("revert"))
+                    .map_err(|e| format!("删除文件 {} 失败: {}", path, e))?;
+            }
+        }
+        report.reverted.push(path);
+    }
+    report.skipped.sort();
+    report.skipped.dedup();
+
+    log::info!(
+        "[ChangeTracker] Reverted agent changes for session {} from prompt #{} ({} files, {} skipped)",
+        session_id,
+        from_prompt_index,
+        report.reverted.len(),
+        report.skipped.len()
+    );
+    Ok(report)
+}
+
 // ===== Tauri 命令 =====
 
 /// 记录文件变更
@@ -1561,7 +2034,7 @@ pub async fn codex_record_file_change(
     tool_call_id: Option<String>,
     diff_hint: Option<String>,
     app_handle: AppHandle,
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     // Keep for future UI display (avoid unused warnings)
     let _ = prompt_text;
 
@@ -1573,14 +2046,14 @@ pub async fn codex_record_file_change(
         "create" => ChangeType::Create,
         "update" => ChangeType::Update,
         "delete" => ChangeType::Delete,
-        _ => return Err(format!("未知的变更类型: {}", change_type)),
+        _ => return Err(AnyCodeError::Validation(format!("未知的变更类型: {}", change_type))),
     };
 
     // 解析来源
     let source_enum = match source.as_str() {
         "tool" => ChangeSource::Tool,
         "command" => ChangeSource::Command,
-        _ => return Err(format!("未知的变更来源: {}", source)),
+        _ => return Err(AnyCodeError::Validation(format!("未知的变更来源: {}", source))),
     };
 
     // 记录变更
@@ -1602,7 +2075,19 @@ pub async fn codex_record_file_change(
         tool_call_id,
         diff_hint,
         None, // command
-    )?;
+    ).map_err(AnyCodeError::Io)?;
+
+    // 文件系统护栏：写到项目外或禁止路径时终止会话/提示用户
+    let full_path = resolve_full_path(&project_path, &file_path);
+    crate::commands::guardrails::enforce_file_change(
+        &app_handle,
+        "codex",
+        &session_id,
+        &project_path,
+        &resolve_project_root(&project_path),
+        &full_path.to_string_lossy(),
+    )
+    .await;
 
     // Notify frontend for real-time refresh
     let session_id_for_evt = session_id.clone();
@@ -1633,12 +2118,52 @@ pub async fn codex_record_file_change(
         );
     }
 
+    crate::commands::test_watch::notify_changes(&app_handle, &session_id, &project_path, prompt_index);
+
     Ok(change_id)
 }
 
+/// 命令执行后检测并记录命令产生的文件变更
+///
+/// 命令执行前的快照由 `CommandSnapshotter` 在输出流中保存；`call_id` 为 Codex 的命令 item ID。
+/// 大仓库超出时间预算或没有快照时返回 `unknownReason`，不记录任何变更。
+#[tauri::command]
+pub async fn codex_detect_command_changes(
+    session_id: String,
+    call_id: String,
+    project_path: String,
+    prompt_index: i32,
+    command: String,
+    app_handle: AppHandle,
+) -> Result<CommandChangeDetection, AnyCodeError> {
+    init_change_tracker(&session_id, &project_path);
+
+    let (id, path) = (session_id.clone(), project_path.clone());
+    let detection = tokio::task::spawn_blocking(move || {
+        detect_changes_after_command(&id, &call_id, &path, prompt_index, &command)
+    })
+    .await
+    .map_err(|e| AnyCodeError::Internal(format!("检测命令变更失败: {}", e)))?
+    .map_err(AnyCodeError::Io)?;
+
+    if !detection.change_ids.is_empty() {
+        let evt_payload = serde_json::json!({
+            "session_id": session_id,
+            "change_ids": detection.change_ids,
+            "prompt_index": prompt_index,
+            "source": "command",
+        });
+        if let Err(e) = app_handle.emit(&format!("codex-change-recorded:{}", session_id), &evt_payload) {
+            log::warn!("[ChangeTracker] Failed to emit codex-change-recorded for command: {}", e);
+        }
+        crate::commands::test_watch::notify_changes(&app_handle, &session_id, &project_path, prompt_index);
+    }
+    Ok(detection)
+}
+
 /// 获取会话的所有文件变更
 #[tauri::command]
-pub async fn codex_list_file_changes(session_id: String) -> Result<Vec<CodexFileChange>, String> {
+pub async fn codex_list_file_changes(session_id: String) -> Result<Vec<CodexFileChange>, AnyCodeError> {
     fn to_summary(change: &CodexFileChange) -> CodexFileChange {
         // Keep list payload small (history panel loads fast). Detail API fetches full content on demand.
         let mut c = change.clone();
@@ -1657,28 +2182,8 @@ pub async fn codex_list_file_changes(session_id: String) -> Result<Vec<CodexFile
 
     drop(trackers);
 
-    // 尝试从文件加载
-    let path = get_change_records_path(&session_id)?;
-    if path.exists() {
-        let content = fs::read_to_string(&path)
-            .map_err(|e| format!("读取文件失败: {}", e))?;
-        let mut records: CodexChangeRecords = serde_json::from_str(&content)
-            .map_err(|e| format!("解析 JSON 失败: {}", e))?;
-
-        // Upgrade legacy records (normalize paths / merge duplicates / backfill diff context)
-        let upgraded = upgrade_change_records(&session_id, &mut records);
-        if upgraded {
-            if let Ok(pretty) = serde_json::to_string_pretty(&records) {
-                if let Err(e) = fs::write(&path, pretty) {
-                    log::warn!(
-                        "[ChangeTracker] Failed to persist upgraded records on list ({}): {}",
-                        session_id,
-                        e
-                    );
-                }
-            }
-        }
-
+    // 尝试从数据库加载
+    if let Some(records) = load_and_upgrade_records(&session_id, "list").map_err(AnyCodeError::Io)? {
         let summaries: Vec<CodexFileChange> = records.changes.iter().map(to_summary).collect();
 
         // 缓存到内存（保存完整记录，详情页可直接使用）
@@ -1696,7 +2201,7 @@ pub async fn codex_list_file_changes(session_id: String) -> Result<Vec<CodexFile
 pub async fn codex_get_change_detail(
     session_id: String,
     change_id: String,
-) -> Result<CodexFileChange, String> {
+) -> Result<CodexFileChange, AnyCodeError> {
     // Prefer in-memory full records if available.
     {
         let trackers = CHANGE_TRACKERS.lock().unwrap();
@@ -1707,27 +2212,8 @@ pub async fn codex_get_change_detail(
         }
     }
 
-    // Fallback to file (full payload).
-    let path = get_change_records_path(&session_id)?;
-    if path.exists() {
-        let content = fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))?;
-        let mut records: CodexChangeRecords =
-            serde_json::from_str(&content).map_err(|e| format!("解析 JSON 失败: {}", e))?;
-
-        // Upgrade legacy records (normalize paths / merge duplicates / backfill diff context)
-        let upgraded = upgrade_change_records(&session_id, &mut records);
-        if upgraded {
-            if let Ok(pretty) = serde_json::to_string_pretty(&records) {
-                if let Err(e) = fs::write(&path, pretty) {
-                    log::warn!(
-                        "[ChangeTracker] Failed to persist upgraded records on get_detail ({}): {}",
-                        session_id,
-                        e
-                    );
-                }
-            }
-        }
-
+    // Fallback to database (full payload).
+    if let Some(records) = load_and_upgrade_records(&session_id, "get_detail").map_err(AnyCodeError::Io)? {
         if let Some(found) = records.changes.iter().find(|c| c.id == change_id) {
             let out = found.clone();
             // Cache full records so subsequent detail/list reads are consistent.
@@ -1737,18 +2223,21 @@ pub async fn codex_get_change_detail(
         }
     }
 
-    Err(format!("变更 {} 未找到", change_id))
+    Err(AnyCodeError::NotFound(format!("变更 {} 未找到", change_id)))
 }
 
 /// 导出整个会话的变更为 patch 文件
+///
+/// `format` 为 `idea` 时导出 IDEA 可直接应用的格式，默认按记录顺序拼接。
 #[tauri::command]
 pub async fn codex_export_patch(
     session_id: String,
     output_path: String,
-) -> Result<String, String> {
-    let patch = export_session_as_patch(&session_id)?;
+    format: Option<PatchFormat>,
+) -> Result<String, AnyCodeError> {
+    let patch = export_session_patch(&session_id, format.unwrap_or_default()).map_err(AnyCodeError::Io)?;
 
-    fs::write(&output_path, &patch).map_err(|e| format!("写入文件失败: {}", e))?;
+    fs::write(&output_path, &patch).map_err(|e| AnyCodeError::Io(format!("写入文件失败: {}", e)))?;
 
     log::info!("[ChangeTracker] 导出 patch 到: {}", output_path);
     Ok(output_path)
@@ -1760,10 +2249,10 @@ pub async fn codex_export_single_change(
     session_id: String,
     change_id: String,
     output_path: String,
-) -> Result<String, String> {
-    let patch = export_single_change_as_patch(&session_id, &change_id)?;
+) -> Result<String, AnyCodeError> {
+    let patch = export_single_change_as_patch(&session_id, &change_id).map_err(AnyCodeError::Io)?;
 
-    fs::write(&output_path, &patch).map_err(|e| format!("写入文件失败: {}", e))?;
+    fs::write(&output_path, &patch).map_err(|e| AnyCodeError::Io(format!("写入文件失败: {}", e)))?;
 
     log::info!("[ChangeTracker] 导出单个变更 patch 到: {}", output_path);
     Ok(output_path)
@@ -1771,15 +2260,21 @@ pub async fn codex_export_single_change(
 
 /// 清理会话的变更记录
 #[tauri::command]
-pub async fn codex_clear_change_records(session_id: String) -> Result<(), String> {
+pub async fn codex_clear_change_records(session_id: String) -> Result<(), AnyCodeError> {
     // 从内存移除
-    let mut trackers = CHANGE_TRACKERS.lock().unwrap();
-    trackers.remove(&session_id);
+    CHANGE_TRACKERS.lock().unwrap().remove(&session_id);
 
-    // 删除文件
-    let path = get_change_records_path(&session_id)?;
+    // 删除数据库记录
+    let id = session_id.clone();
+    tokio::task::spawn_blocking(move || change_store::delete_session(&mut *change_store::open_change_db()?, &id))
+        .await
+        .map_err(|e| AnyCodeError::Internal(format!("删除变更记录失败: {}", e)))?
+        .map_err(AnyCodeError::Database)?;
+
+    // 删除旧版 JSON 文件（如有）
+    let path = get_change_records_path(&session_id).map_err(AnyCodeError::Io)?;
     if path.exists() {
-        fs::remove_file(&path).map_err(|e| format!("删除文件失败: {}", e))?;
+        fs::remove_file(&path).map_err(|e| AnyCodeError::Io(format!("删除文件失败: {}", e)))?;
     }
 
     log::info!("[ChangeTracker] 清理会话变更记录: {}", session_id);
@@ -1792,30 +2287,24 @@ pub async fn codex_clear_change_records(session_id: String) -> Result<(), String
 /// - App 热更新/版本升级后，历史记录仍是旧格式或统计不一致
 /// - 记录在内存中已加载，但升级逻辑只在从文件读取时触发
 #[tauri::command]
-pub async fn codex_repair_change_records(session_id: String) -> Result<bool, String> {
-    let path = get_change_records_path(&session_id)?;
-
-    // Load from memory first, then fall back to disk.
+pub async fn codex_repair_change_records(session_id: String) -> Result<bool, AnyCodeError> {
+    // Load from memory first, then fall back to the database.
     let mut records: Option<CodexChangeRecords> = {
         let trackers = CHANGE_TRACKERS.lock().unwrap();
         trackers.get(&session_id).cloned()
     };
 
-    if records.is_none() && path.exists() {
-        let content = fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))?;
-        let parsed: CodexChangeRecords =
-            serde_json::from_str(&content).map_err(|e| format!("解析 JSON 失败: {}", e))?;
-        records = Some(parsed);
+    if records.is_none() {
+        records = load_persisted_records(&session_id).map_err(AnyCodeError::Io)?;
     }
 
     let Some(mut records) = records else {
-        return Err(format!("会话 {} 未找到", session_id));
+        return Err(AnyCodeError::NotFound(format!("会话 {} 未找到", session_id)));
     };
 
     let upgraded = upgrade_change_records(&session_id, &mut records);
     if upgraded {
-        let content = serde_json::to_string_pretty(&records).map_err(|e| format!("序列化失败: {}", e))?;
-        fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))?;
+        persist_records(&records).map_err(AnyCodeError::Io)?;
 
         // Update in-memory cache so list/detail reflect the repaired content immediately.
         let mut trackers = CHANGE_TRACKERS.lock().unwrap();
@@ -1826,3 +2315,101 @@ pub async fn codex_repair_change_records(session_id: String) -> Result<bool, Str
 
     Ok(upgraded)
 }
+
+/// 导出会话变更记录为 JSON（兼容旧版 `~/.codex/change-records/<session_id>.json` 格式）
+///
+/// 未指定 `output_path` 时写入旧版默认路径，供依赖 JSON 文件的外部工具读取。
+#[tauri::command]
+pub async fn codex_export_change_records_json(
+    session_id: String,
+    output_path: Option<String>,
+) -> Result<String, AnyCodeError> {
+    let records = load_persisted_records(&session_id).map_err(AnyCodeError::Io)?
+        .ok_or_else(|| AnyCodeError::NotFound(format!("会话 {} 未找到", session_id)))?;
+
+    let path = match output_path {
+        Some(p) => PathBuf::from(p),
+        None => get_change_records_path(&session_id).map_err(AnyCodeError::Io)?,
+    };
+    let content = serde_json::to_string_pretty(&records).map_err(|e| AnyCodeError::Internal(format!("序列化失败: {}", e)))?;
+    fs::write(&path, content).map_err(|e| AnyCodeError::Io(format!("写入文件失败: {}", e)))?;
+
+    log::info!("[ChangeTracker] 导出变更记录 JSON 到: {:?}", path);
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(file_path: &str, change_type: ChangeType, old: Option<&str>, new: Option<&str>) -> CodexFileChange {
+        CodexFileChange {
+            id: file_path.to_string(),
+            session_id: "s".to_string(),
+            prompt_index: 0,
+            timestamp: String::new(),
+            file_path: file_path.to_string(),
+            change_type,
+            source: ChangeSource::Tool,
+            old_content: old.map(str::to_string),
+            new_content: new.map(str::to_string),
+            unified_diff: None,
+            lines_added: None,
+            lines_removed: None,
+            tool_name: None,
+            tool_call_id: None,
+            command: None,
+        }
+    }
+
+    #[test]
+    fn builds_idea_patch() {
+        let patch = build_idea_patch(&[
+            change("src\\lib.rs", ChangeType::Update, Some("a\nb\n"), Some("a\nc\n")),
+            change("src/lib.rs", ChangeType::Update, Some("a\nc\n"), Some("a\nd\n")),
+            change("new.txt", ChangeType::Create, None, Some("hi\n")),
+            change("old.rs", ChangeType::Delete, Some("fn x() {}\n"), None),
+            change("moved.rs", ChangeType::Create, None, Some("fn x() {}\n")),
+            change("tmp.txt", ChangeType::Create, None, Some("x\n")),
+            change("tmp.txt", ChangeType::Delete, Some("x\n"), None),
+        ]);
+
+        // 同一文件的两次修改合并为一个 diff
+        assert_eq!(patch.matches("diff --git a/src/lib.rs b/src/lib.rs").count(), 1);
+        assert!(patch.contains("-b\n+d\n") && !patch.contains("+c"));
+        assert!(patch.contains("diff --git a/new.txt b/new.txt\nnew file mode 100644\n--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+hi\n"));
+        assert!(patch.contains("rename from old.rs\nrename to moved.rs\n"));
+        assert!(!patch.contains("deleted file mode") && !patch.contains("b/moved.rs\nnew file"));
+        assert!(!patch.contains("tmp.txt"));
+
+        // 先新建再删除（例如复制后删除）同样输出为一次重命名
+        let patch = build_idea_patch(&[
+            change("moved.rs", ChangeType::Create, None, Some("fn x() {}\n")),
+            change("old.rs", ChangeType::Delete, Some("fn x() {}\n"), None),
+        ]);
+        assert_eq!(patch, "diff --git a/old.rs b/moved.rs\nsimilarity index 100%\nrename from old.rs\nrename to moved.rs\n");
+    }
+
+    #[test]
+    fn undoes_agent_changes_around_manual_edits() {
+        let update = change("a.txt", ChangeType::Update, Some("1\n2\n3\n4\n5\n"), Some("1\nagent\n3\n4\n5\n"));
+        let undo = |c: &CodexFileChange, current: Option<&str>| undo_change(c, current).unwrap();
+
+        assert_eq!(undo(&update, Some("1\nagent\n3\n4\n5\n")), UndoStep::Write("1\n2\n3\n4\n5\n".into()));
+        assert_eq!(undo(&update, Some("1\n2\n3\n4\n5\n")), UndoStep::Unchanged);
+        // 手动修改了其他行：只撤销 Codex 的修改
+        assert_eq!(undo(&update, Some("1\nagent\n3\n4\nmanual\n")), UndoStep::Write("1\n2\n3\n4\nmanual\n".into()));
+        assert_eq!(undo(&update, Some("1\nmanual\n3\n4\n5\n")), UndoStep::Conflict);
+        assert_eq!(undo(&update, None), UndoStep::Conflict);
+
+        let create = change("new.txt", ChangeType::Create, None, Some("hi\n"));
+        assert_eq!(undo(&create, Some("hi\n")), UndoStep::Remove);
+        assert_eq!(undo(&create, Some("hi\nmanual\n")), UndoStep::Conflict);
+        assert_eq!(undo(&create, None), UndoStep::Unchanged);
+
+        let delete = change("old.txt", ChangeType::Delete, Some("x\n"), None);
+        assert_eq!(undo(&delete, None), UndoStep::Write("x\n".into()));
+        assert_eq!(undo(&delete, Some("y\n")), UndoStep::Conflict);
+        assert_eq!(undo(&change("b.txt", ChangeType::Update, None, Some("x")), Some("x")), UndoStep::MissingSnapshot);
+    }
+}
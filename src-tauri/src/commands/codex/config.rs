@@ -8,19 +8,28 @@
  * - Provider management (presets, switching, CRUD)
  */
 
+use crate::tr;
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::fs;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use dirs;
-use rusqlite;
 
 // Import platform-specific utilities for window hiding
 use crate::commands::claude::apply_no_window_async;
+use crate::commands::provider_metrics;
 use crate::claude_binary::detect_binary_for_tool;
 // Import WSL utilities
 use super::super::wsl_utils;
+use super::super::ssh_remote::{self, RemoteConfig};
+use crate::commands::data_root::anycode_dir;
+use crate::commands::app_settings::{delete_setting, get_setting, set_setting, CODEX_BINARY_PATH};
 
 // ============================================================================
 // Type Definitions
@@ -50,6 +59,8 @@ pub struct CodexModeInfo {
     pub wsl_available: bool,
     /// List of available WSL distros
     pub available_distros: Vec<String>,
+    /// Remote SSH host (if configured)
+    pub remote: Option<RemoteConfig>,
 }
 
 /// Codex provider configuration
@@ -146,6 +157,7 @@ pub fn update_binary_override(tool: &str, override_path: &str) -> Result<(), Str
         .map_err(|e| format!("Failed to serialize binaries.json: {}", e))?;
     std::fs::write(&config_path, serialized)
         .map_err(|e| format!("Failed to write binaries.json: {}", e))?;
+    crate::claude_binary::invalidate_binary_cache(tool);
 
     Ok(())
 }
@@ -174,6 +186,7 @@ pub fn clear_binary_override(tool: &str) -> Result<(), String> {
         .map_err(|e| format!("Failed to serialize binaries.json: {}", e))?;
     std::fs::write(&config_path, serialized)
         .map_err(|e| format!("Failed to write binaries.json: {}", e))?;
+    crate::claude_binary::invalidate_binary_cache(tool);
     Ok(())
 }
 
@@ -201,6 +214,12 @@ pub fn get_binary_override(tool: &str) -> Option<String> {
 /// On Windows with WSL mode enabled, returns the WSL UNC path
 pub fn get_codex_sessions_dir() -> Result<PathBuf, String> {
     log::debug!("[get_codex_sessions_dir] Getting Codex sessions directory");
+
+    // Remote SSH mode: local sftp mirror of the remote sessions directory
+    // (refreshed in the background, this is called from async commands)
+    if let Some(remote) = wsl_utils::get_codex_config().active_remote() {
+        return ssh_remote::sync_remote_sessions_in_background(remote);
+    }
     
     // Check for WSL mode on Windows
     #[cfg(target_os = "windows")]
@@ -228,15 +247,76 @@ pub fn get_codex_sessions_dir() -> Result<PathBuf, String> {
     Ok(sessions_dir)
 }
 
+/// Get the Codex sessions directory for a project
+/// Projects pinned to a WSL distro in their project settings use that distro's directory
+pub fn get_codex_sessions_dir_for_project(project_path: &str) -> Result<PathBuf, String> {
+    if wsl_utils::get_codex_config().active_remote().is_some() {
+        return get_codex_sessions_dir();
+    }
+
+    let wsl_config = wsl_utils::get_wsl_config_for_project(project_path);
+    if wsl_config.enabled {
+        if let Some(codex_dir) = wsl_config.codex_dir_unc {
+            let sessions_dir = codex_dir.join("sessions");
+            log::info!("[get_codex_sessions_dir] Using project WSL sessions directory: {:?}", sessions_dir);
+            return Ok(sessions_dir);
+        }
+    }
+    get_codex_sessions_dir()
+}
+
+/// Get every Codex sessions directory: the global one plus those of per-project WSL distros
+pub fn get_all_codex_sessions_dirs() -> Result<Vec<PathBuf>, String> {
+    let mut dirs = vec![get_codex_sessions_dir()?];
+    if wsl_utils::get_codex_config().active_remote().is_some() {
+        return Ok(dirs);
+    }
+    for wsl_config in wsl_utils::get_project_wsl_configs() {
+        if let Some(codex_dir) = wsl_config.codex_dir_unc {
+            let sessions_dir = codex_dir.join("sessions");
+            if !dirs.contains(&sessions_dir) {
+                dirs.push(sessions_dir);
+            }
+        }
+    }
+    Ok(dirs)
+}
+
 // ============================================================================
 // Availability Check
 // ============================================================================
 
 /// Checks if Codex is available and properly configured
 #[tauri::command]
-pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
+pub async fn check_codex_availability() -> Result<CodexAvailability, AnyCodeError> {
     log::info!("[Codex] Checking availability...");
 
+    // 0) Remote SSH mode
+    if let Some(remote) = wsl_utils::get_codex_config().active_remote().cloned() {
+        let destination = remote.destination();
+        let result = tokio::task::spawn_blocking(move || ssh_remote::check_remote_codex(&remote))
+            .await
+            .map_err(|e| AnyCodeError::Internal(format!("Failed to check remote Codex: {}", e)))?;
+        return Ok(match result {
+            Ok(version) => {
+                log::info!("[Codex] Available on remote {} - version: {}", destination, version);
+                CodexAvailability {
+                    available: true,
+                    version: Some(format!("Remote ({}): {}", destination, version)),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                log::warn!("[Codex] Remote check failed for {}: {}", destination, e);
+                CodexAvailability {
+                    available: false,
+                    version: None,
+                    error: Some(e),
+                }
+            }
+        });
+    }
+
     // 1) Windows: Check WSL mode first
     #[cfg(target_os = "windows")]
     {
@@ -266,14 +346,25 @@ pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
     // 2) Runtime detection (env vars / PATH / registry / common dirs / user config)
     let (_env_info, detected) = detect_binary_for_tool("codex", "CODEX_PATH", "codex");
     if let Some(inst) = detected {
-        let mut cmd = Command::new(&inst.path);
-        cmd.arg("--version");
-        apply_no_window_async(&mut cmd);
+        // `--version` 输出按 (路径, 修改时间) 缓存，状态轮询不再每次启动子进程
+        let output = match crate::claude_binary::cached_version_output(&inst.path) {
+            Some(output) => Ok(output),
+            None => {
+                let mut cmd = Command::new(&inst.path);
+                cmd.arg("--version");
+                apply_no_window_async(&mut cmd);
+                cmd.output().await.map(|output| {
+                    let output = crate::claude_binary::VersionOutput::from_output(&output);
+                    crate::claude_binary::store_version_output(&inst.path, &output);
+                    output
+                })
+            }
+        };
 
-        match cmd.output().await {
+        match output {
             Ok(output) => {
-                let stdout_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                let stderr_str = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                let stdout_str = output.stdout.trim().to_string();
+                let stderr_str = output.stderr.trim().to_string();
                 let version = if !stdout_str.is_empty() {
                     stdout_str.clone()
                 } else if !stderr_str.is_empty() {
@@ -282,7 +373,7 @@ pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
                     inst.version.clone().unwrap_or_else(|| "Unknown version".to_string())
                 };
 
-                if output.status.success() {
+                if output.success {
                     log::info!(
                         "[Codex] Available - path: {}, source: {}, version: {}",
                         inst.path,
@@ -296,9 +387,8 @@ pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
                     });
                 } else {
                     log::warn!(
-                        "[Codex] Version probe failed for {} (status {:?}), stderr: {}",
+                        "[Codex] Version probe failed for {}, stderr: {}",
                         inst.path,
-                        output.status.code(),
                         stderr_str
                     );
                 }
@@ -313,50 +403,161 @@ pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
         }
     }
 
-    // 3) Fallback: use legacy candidate list
-    let codex_commands = get_codex_command_candidates();
-    for cmd_path in codex_commands {
-        log::info!("[Codex] Fallback trying: {}", cmd_path);
+    // 3) Fallback: probe legacy candidate list concurrently
+    if let Some((cmd_path, version)) = probe_codex_candidates(get_codex_command_candidates()).await {
+        log::info!(
+            "[Codex] Available via fallback - path: {}, version: {}",
+            cmd_path,
+            version
+        );
+        return Ok(CodexAvailability {
+            available: true,
+            version: Some(version),
+            error: None,
+        });
+    }
 
-        let mut cmd = Command::new(&cmd_path);
-        cmd.arg("--version");
-        apply_no_window_async(&mut cmd);
+    // 4) Complete failure
+    log::error!("[Codex] Codex CLI not found via runtime detection or fallback list");
+    Ok(CodexAvailability {
+        available: false,
+        version: None,
+        error: Some("Codex CLI not found. Please set CODEX_PATH or install codex CLI".to_string()),
+    })
+}
 
-        match cmd.output().await {
-            Ok(output) => {
-                let stdout_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                let stderr_str = String::from_utf8_lossy(&output.stderr).trim().to_string();
+// ============================================================================
+// Candidate Probing
+// ============================================================================
 
-                if output.status.success() {
-                    let version = if !stdout_str.is_empty() {
-                        stdout_str
-                    } else if !stderr_str.is_empty() {
-                        stderr_str
-                    } else {
-                        "Unknown version".to_string()
-                    };
+/// Maximum number of `--version` probes running at the same time
+const MAX_CONCURRENT_PROBES: usize = 4;
+/// Timeout of a single `--version` probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Timeout of the whole fallback probing phase
+const PROBE_OVERALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Filter candidates down to those that exist, deduplicated in priority order
+///
+/// Bare command names (e.g. `codex`) are resolved through PATH via `resolve`,
+/// so they dedupe against the absolute paths that point to the same binary.
+fn existing_unique_candidates<F>(candidates: Vec<String>, resolve: F) -> Vec<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut seen = std::collections::HashSet::new();
+    candidates
+        .into_iter()
+        .filter_map(|candidate| resolve(&candidate))
+        .filter(|path| seen.insert(path.clone()))
+        .collect()
+}
 
-                    log::info!("[Codex] Available via fallback - version: {}", version);
-                    return Ok(CodexAvailability {
-                        available: true,
-                        version: Some(version),
-                        error: None,
-                    });
+fn resolve_candidate(candidate: &str) -> Option<String> {
+    let path = std::path::Path::new(candidate);
+    if path.components().count() <= 1 {
+        return which::which(candidate)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+    }
+    path.is_file().then(|| candidate.to_string())
+}
+
+/// Run `<candidate> --version`, returning the version string on success
+async fn probe_codex_version(cmd_path: &str) -> Option<String> {
+    let mut cmd = Command::new(cmd_path);
+    cmd.arg("--version");
+    cmd.kill_on_drop(true);
+    apply_no_window_async(&mut cmd);
+
+    let output = match tokio::time::timeout(PROBE_TIMEOUT, cmd.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            log::warn!("[Codex] Fallback command '{}' failed: {}", cmd_path, e);
+            return None;
+        }
+        Err(_) => {
+            log::warn!("[Codex] Fallback command '{}' timed out", cmd_path);
+            return None;
+        }
+    };
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr_str = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Some(if !stdout_str.is_empty() {
+        stdout_str
+    } else if !stderr_str.is_empty() {
+        stderr_str
+    } else {
+        "Unknown version".to_string()
+    })
+}
+
+/// Probe candidates concurrently and return the highest-priority working one
+///
+/// Non-existent paths are skipped before spawning anything; at most
+/// `MAX_CONCURRENT_PROBES` probes run at once, and probing stops after
+/// `PROBE_OVERALL_TIMEOUT` with whatever results are in by then.
+async fn probe_codex_candidates(candidates: Vec<String>) -> Option<(String, String)> {
+    let total = candidates.len();
+    let candidates = tokio::task::spawn_blocking(move || {
+        existing_unique_candidates(candidates, resolve_candidate)
+    })
+    .await
+    .ok()?;
+    log::info!(
+        "[Codex] Probing {} existing candidate(s) out of {}",
+        candidates.len(),
+        total
+    );
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_PROBES));
+    let mut join_set = JoinSet::new();
+    for (index, cmd_path) in candidates.iter().cloned().enumerate() {
+        let semaphore = semaphore.clone();
+        join_set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (index, probe_codex_version(&cmd_path).await)
+        });
+    }
+
+    // Keep the lowest-index success; stop early once no better candidate can finish
+    let mut best: Option<(usize, String)> = None;
+    let mut finished = vec![false; candidates.len()];
+    let collect = async {
+        while let Some(result) = join_set.join_next().await {
+            let Ok((index, version)) = result else {
+                continue;
+            };
+            finished[index] = true;
+            if let Some(version) = version {
+                if best.as_ref().map_or(true, |(b, _)| index < *b) {
+                    best = Some((index, version));
                 }
             }
-            Err(e) => {
-                log::warn!("[Codex] Fallback command '{}' failed: {}", cmd_path, e);
+            if let Some((b, _)) = &best {
+                if finished[..*b].iter().all(|f| *f) {
+                    break;
+                }
             }
         }
+    };
+    if tokio::time::timeout(PROBE_OVERALL_TIMEOUT, collect).await.is_err() {
+        log::warn!(
+            "[Codex] Candidate probing timed out after {:?}",
+            PROBE_OVERALL_TIMEOUT
+        );
     }
+    join_set.abort_all();
 
-    // 4) Complete failure
-    log::error!("[Codex] Codex CLI not found via runtime detection or fallback list");
-    Ok(CodexAvailability {
-        available: false,
-        version: None,
-        error: Some("Codex CLI not found. Please set CODEX_PATH or install codex CLI".to_string()),
-    })
+    best.map(|(index, version)| (candidates[index].clone(), version))
 }
 
 // ============================================================================
@@ -365,20 +566,20 @@ pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
 
 /// Set custom Codex CLI path, supports ~ expansion and relative paths
 #[tauri::command]
-pub async fn set_custom_codex_path(app: AppHandle, custom_path: String) -> Result<(), String> {
+pub async fn set_custom_codex_path(app: AppHandle, custom_path: String) -> Result<(), AnyCodeError> {
     log::info!("[Codex] Setting custom path: {}", custom_path);
 
-    let expanded_path = expand_user_path(&custom_path)?;
+    let expanded_path = expand_user_path(&custom_path).map_err(AnyCodeError::Validation)?;
     if !expanded_path.exists() {
-        return Err("File does not exist".to_string());
+        return Err(AnyCodeError::NotFound("File does not exist".to_string()));
     }
     if !expanded_path.is_file() {
-        return Err("Path is not a file".to_string());
+        return Err(AnyCodeError::Io("Path is not a file".to_string()));
     }
 
     let path_str = expanded_path
         .to_str()
-        .ok_or_else(|| "Invalid path encoding".to_string())?
+        .ok_or_else(|| AnyCodeError::Validation("Invalid path encoding".to_string()))?
         .to_string();
 
     let mut cmd = Command::new(&path_str);
@@ -388,10 +589,10 @@ pub async fn set_custom_codex_path(app: AppHandle, custom_path: String) -> Resul
     match cmd.output().await {
         Ok(output) => {
             if !output.status.success() {
-                return Err("File is not a valid Codex CLI executable".to_string());
+                return Err(AnyCodeError::Engine("File is not a valid Codex CLI executable".to_string()));
             }
         }
-        Err(e) => return Err(format!("Failed to test Codex CLI: {}", e)),
+        Err(e) => return Err(AnyCodeError::Engine(format!("Failed to test Codex CLI: {}", e))),
     }
 
     // Write to binaries.json for unified detection
@@ -400,51 +601,20 @@ pub async fn set_custom_codex_path(app: AppHandle, custom_path: String) -> Resul
     }
 
     // Also store in app_settings for compatibility
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let db_path = app_data_dir.join("agents.db");
-        if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-            let _ = conn.execute(
-                "CREATE TABLE IF NOT EXISTS app_settings (
-                    key TEXT PRIMARY KEY,
-                    value TEXT NOT NULL
-                )",
-                [],
-            );
-            let _ = conn.execute(
-                "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-                rusqlite::params!["codex_binary_path", path_str],
-            );
-        }
+    if let Err(e) = set_setting(&app, CODEX_BINARY_PATH, &path_str).await {
+        log::warn!("[Codex] Failed to store custom path in app_settings: {}", e);
     }
 
     Ok(())
 }
 
-fn read_custom_codex_path_from_db(app: &AppHandle) -> Option<String> {
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let db_path = app_data_dir.join("agents.db");
-        if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                if let Ok(val) = conn.query_row(
-                    "SELECT value FROM app_settings WHERE key = 'codex_binary_path'",
-                    [],
-                    |row| row.get::<_, String>(0),
-                ) {
-                    return Some(val);
-                }
-            }
-        }
-    }
-    None
-}
-
 /// Get current Codex path (custom first, then runtime detection)
 #[tauri::command]
-pub async fn get_codex_path(app: AppHandle) -> Result<String, String> {
+pub async fn get_codex_path() -> Result<String, AnyCodeError> {
     if let Some(override_path) = get_binary_override("codex") {
         return Ok(override_path);
     }
-    if let Some(db_path) = read_custom_codex_path_from_db(&app) {
+    if let Some(db_path) = get_setting::<String>(CODEX_BINARY_PATH).await.ok().flatten() {
         return Ok(db_path);
     }
 
@@ -453,23 +623,13 @@ pub async fn get_codex_path(app: AppHandle) -> Result<String, String> {
         return Ok(inst.path);
     }
 
-    Err("Codex CLI not found. Please set CODEX_PATH or install codex CLI".to_string())
+    Err(AnyCodeError::Engine("Codex CLI not found. Please set CODEX_PATH or install codex CLI".to_string()))
 }
 
 /// Clear custom Codex path, restore auto detection
 #[tauri::command]
-pub async fn clear_custom_codex_path(app: AppHandle) -> Result<(), String> {
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        let db_path = app_data_dir.join("agents.db");
-        if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                let _ = conn.execute(
-                    "DELETE FROM app_settings WHERE key = 'codex_binary_path'",
-                    [],
-                );
-            }
-        }
-    }
+pub async fn clear_custom_codex_path(app: AppHandle) -> Result<(), AnyCodeError> {
+    let _ = delete_setting(&app, CODEX_BINARY_PATH).await;
 
     if let Err(e) = clear_binary_override("codex") {
         log::warn!("[Codex] Failed to clear binaries.json override: {}", e);
@@ -761,7 +921,7 @@ pub fn get_codex_command_candidates() -> Vec<String> {
 
 /// Get Codex mode configuration
 #[tauri::command]
-pub async fn get_codex_mode_config() -> Result<CodexModeInfo, String> {
+pub async fn get_codex_mode_config() -> Result<CodexModeInfo, AnyCodeError> {
     log::info!("[Codex] Getting mode configuration...");
 
     let config = wsl_utils::get_codex_config();
@@ -783,9 +943,16 @@ pub async fn get_codex_mode_config() -> Result<CodexModeInfo, String> {
         wsl_utils::CodexMode::Auto => "auto",
         wsl_utils::CodexMode::Native => "native",
         wsl_utils::CodexMode::Wsl => "wsl",
+        wsl_utils::CodexMode::Remote => "remote",
     };
 
-    let actual_mode = if wsl_config.enabled { "wsl" } else { "native" };
+    let actual_mode = if config.active_remote().is_some() {
+        "remote"
+    } else if wsl_config.enabled {
+        "wsl"
+    } else {
+        "native"
+    };
 
     Ok(CodexModeInfo {
         mode: mode_str.to_string(),
@@ -794,6 +961,7 @@ pub async fn get_codex_mode_config() -> Result<CodexModeInfo, String> {
         native_available,
         wsl_available,
         available_distros,
+        remote: config.remote.clone(),
     })
 }
 
@@ -802,22 +970,39 @@ pub async fn get_codex_mode_config() -> Result<CodexModeInfo, String> {
 pub async fn set_codex_mode_config(
     mode: String,
     wsl_distro: Option<String>,
-) -> Result<String, String> {
-    log::info!("[Codex] Setting mode configuration: mode={}, wsl_distro={:?}", mode, wsl_distro);
+    remote: Option<RemoteConfig>,
+) -> Result<String, AnyCodeError> {
+    log::info!(
+        "[Codex] Setting mode configuration: mode={}, wsl_distro={:?}, remote={:?}",
+        mode,
+        wsl_distro,
+        remote.as_ref().map(|r| r.destination())
+    );
 
     let codex_mode = match mode.to_lowercase().as_str() {
         "auto" => wsl_utils::CodexMode::Auto,
         "native" => wsl_utils::CodexMode::Native,
         "wsl" => wsl_utils::CodexMode::Wsl,
-        _ => return Err(format!("Invalid mode: {}. Use 'auto', 'native', or 'wsl'", mode)),
+        "remote" => wsl_utils::CodexMode::Remote,
+        _ => return Err(AnyCodeError::Validation(format!("Invalid mode: {}. Use 'auto', 'native', 'wsl' or 'remote'", mode))),
     };
 
+    if codex_mode == wsl_utils::CodexMode::Remote
+        && remote.as_ref().map_or(true, |r| r.host.trim().is_empty())
+    {
+        return Err(AnyCodeError::Validation("Remote mode requires a host".to_string()));
+    }
+
+    // Keep the saved remote host when switching to another mode
+    let remote = remote.or_else(|| wsl_utils::get_codex_config().remote.clone());
+
     let config = wsl_utils::CodexConfig {
         mode: codex_mode,
         wsl_distro,
+        remote,
     };
 
-    wsl_utils::save_codex_config(&config)?;
+    wsl_utils::save_codex_config(&config).map_err(AnyCodeError::Io)?;
 
     Ok("Configuration saved. Would you like to restart the app for changes to take effect?".to_string())
 }
@@ -827,7 +1012,7 @@ pub async fn set_codex_mode_config(
 // ============================================================================
 
 /// Get Codex config directory path (supports WSL mode on Windows)
-fn get_codex_config_dir() -> Result<PathBuf, String> {
+pub(crate) fn get_codex_config_dir() -> Result<PathBuf, String> {
     // Check for WSL mode on Windows
     #[cfg(target_os = "windows")]
     {
@@ -846,6 +1031,28 @@ fn get_codex_config_dir() -> Result<PathBuf, String> {
     Ok(home_dir.join(".codex"))
 }
 
+/// Get the Codex config directory for a project
+/// Projects pinned to a WSL distro in their project settings use that distro's ~/.codex
+pub(crate) fn get_codex_config_dir_for_project(project_path: &str) -> Result<PathBuf, String> {
+    let wsl_config = wsl_utils::get_wsl_config_for_project(project_path);
+    if wsl_config.enabled {
+        if let Some(codex_dir) = wsl_config.codex_dir_unc {
+            return Ok(codex_dir);
+        }
+    }
+    get_codex_config_dir()
+}
+
+/// ~/.codex directories of WSL distros pinned by project settings (excluding the global one)
+pub(crate) fn get_project_codex_config_dirs() -> Vec<PathBuf> {
+    let global = get_codex_config_dir().ok();
+    wsl_utils::get_project_wsl_configs()
+        .into_iter()
+        .filter_map(|config| config.codex_dir_unc)
+        .filter(|dir| Some(dir) != global.as_ref())
+        .collect()
+}
+
 /// Get Codex auth.json path
 fn get_codex_auth_path() -> Result<PathBuf, String> {
     Ok(get_codex_config_dir()?.join("auth.json"))
@@ -861,6 +1068,11 @@ fn get_codex_providers_path() -> Result<PathBuf, String> {
     Ok(get_codex_config_dir()?.join("providers.json"))
 }
 
+/// Get path to the provider fallback chain (failover.json)
+pub(super) fn get_codex_failover_path() -> Result<PathBuf, String> {
+    Ok(get_codex_config_dir()?.join("failover.json"))
+}
+
 /// Get backup path for config.toml (before switching providers)
 fn get_config_backup_path() -> Result<PathBuf, String> {
     Ok(get_codex_config_dir()?.join("config.toml.bak"))
@@ -914,11 +1126,64 @@ fn extract_model_from_config(config: &str) -> Option<String> {
 // Provider Management Commands
 // ============================================================================
 
-/// Get Codex provider presets (custom user-defined presets)
-#[tauri::command]
-pub async fn get_codex_provider_presets() -> Result<Vec<CodexProviderConfig>, String> {
-    log::info!("[Codex Provider] Getting provider presets");
+/// Find a Codex provider preset by ID
+pub fn load_codex_provider(id: &str) -> Result<CodexProviderConfig, String> {
+    load_codex_provider_presets()?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("未找到ID为 '{}' 的 Codex 代理商配置", id))
+}
+
+/// Flatten a provider's config.toml into `-c key=value` overrides
+/// so a single execution can use it without rewriting ~/.codex/config.toml
+fn flatten_toml_overrides(prefix: &str, value: &toml::Value, out: &mut Vec<String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, child) in table {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_toml_overrides(&path, child, out);
+            }
+        }
+        other => {
+            out.push("-c".to_string());
+            out.push(format!("{}={}", prefix, other));
+        }
+    }
+}
 
+/// `-c` overrides and API key for running with a provider preset
+pub fn codex_provider_overrides(
+    provider: &CodexProviderConfig,
+) -> Result<(Vec<String>, Option<String>), String> {
+    let value: toml::Value = provider
+        .config
+        .parse()
+        .map_err(|e| format!("Failed to parse provider config.toml: {}", e))?;
+    let mut args = Vec::new();
+    flatten_toml_overrides("", &value, &mut args);
+    Ok((args, extract_api_key_from_auth(&provider.auth)))
+}
+
+/// ID of the preset whose base_url matches ~/.codex/config.toml
+/// (falls back to the configured base_url itself, None when using official OpenAI)
+pub fn active_codex_provider_id() -> Option<String> {
+    let config = fs::read_to_string(get_codex_config_path().ok()?).ok()?;
+    let base_url = extract_base_url_from_config(&config)?;
+    let preset = load_codex_provider_presets()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|p| {
+            extract_base_url_from_config(&p.config).as_deref().map(|u| u.trim_end_matches('/'))
+                == Some(base_url.trim_end_matches('/'))
+        });
+    Some(preset.map(|p| p.id).unwrap_or(base_url))
+}
+
+fn load_codex_provider_presets() -> Result<Vec<CodexProviderConfig>, String> {
     let providers_path = get_codex_providers_path()?;
 
     if !providers_path.exists() {
@@ -928,26 +1193,42 @@ pub async fn get_codex_provider_presets() -> Result<Vec<CodexProviderConfig>, St
     let content = fs::read_to_string(&providers_path)
         .map_err(|e| format!("Failed to read providers.json: {}", e))?;
 
-    let providers: Vec<CodexProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse providers.json: {}", e))
+}
 
-    Ok(providers)
+/// Replace all Codex provider presets at once (atomic write, used by bundle import)
+pub(crate) fn save_codex_provider_presets(providers: &[CodexProviderConfig]) -> Result<(), String> {
+    let providers_path = get_codex_providers_path()?;
+    if let Some(parent) = providers_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(providers)
+        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+    crate::commands::file_operations::write_atomic(&providers_path, content)
+        .map_err(|e| format!("Failed to write providers.json: {}", e))
+}
+
+/// Get Codex provider presets (custom user-defined presets)
+#[tauri::command]
+pub async fn get_codex_provider_presets() -> Result<Vec<CodexProviderConfig>, AnyCodeError> {
+    log::info!("[Codex Provider] Getting provider presets");
+    load_codex_provider_presets().map_err(AnyCodeError::Config)
 }
 
 /// Get current Codex configuration
 #[tauri::command]
-pub async fn get_current_codex_config() -> Result<CurrentCodexConfig, String> {
+pub async fn get_current_codex_config() -> Result<CurrentCodexConfig, AnyCodeError> {
     log::info!("[Codex Provider] Getting current config");
 
-    let auth_path = get_codex_auth_path()?;
-    let config_path = get_codex_config_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
 
     // Read auth.json
     let auth: serde_json::Value = if auth_path.exists() {
         let content = fs::read_to_string(&auth_path)
-            .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read auth.json: {}", e)))?;
         serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse auth.json: {}", e))?
+            .map_err(|e| AnyCodeError::Config(format!("Failed to parse auth.json: {}", e)))?
     } else {
         serde_json::json!({})
     };
@@ -955,7 +1236,7 @@ pub async fn get_current_codex_config() -> Result<CurrentCodexConfig, String> {
     // Read config.toml
     let config: String = if config_path.exists() {
         fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config.toml: {}", e))?
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read config.toml: {}", e)))?
     } else {
         String::new()
     };
@@ -977,23 +1258,24 @@ pub async fn get_current_codex_config() -> Result<CurrentCodexConfig, String> {
 /// Switch to a Codex provider configuration
 /// Preserves user's custom settings and OAuth tokens
 #[tauri::command]
-pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String, String> {
+pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Switching to provider: {}", config.name);
+    crate::commands::capabilities::invalidate_capability_cache();
 
-    let config_dir = get_codex_config_dir()?;
-    let auth_path = get_codex_auth_path()?;
-    let config_path = get_codex_config_path()?;
+    let config_dir = get_codex_config_dir().map_err(AnyCodeError::Io)?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
 
     // Ensure config directory exists
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create .codex directory: {}", e)))?;
     }
 
     // Validate new TOML if not empty
     let new_config_table: Option<toml::Table> = if !config.config.trim().is_empty() {
         Some(toml::from_str(&config.config)
-            .map_err(|e| format!("Invalid TOML configuration: {}", e))?)
+            .map_err(|e| AnyCodeError::Config(format!("Invalid TOML configuration: {}", e)))?)
     } else {
         None
     };
@@ -1004,12 +1286,12 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
 
     let final_auth = if auth_path.exists() {
         let existing_content = fs::read_to_string(&auth_path)
-            .map_err(|e| format!("Failed to read existing auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read existing auth.json: {}", e)))?;
 
         if let Ok(mut existing_auth) = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&existing_content) {
             // Merge new auth into existing - new values take precedence
             if let serde_json::Value::Object(new_auth_map) = serde_json::to_value(&config.auth)
-                .map_err(|e| format!("Failed to convert auth: {}", e))?
+                .map_err(|e| AnyCodeError::Internal(format!("Failed to convert auth: {}", e)))?
             {
                 // Check if new auth has any API key set (non-empty value)
                 let new_auth_has_api_key = api_key_fields.iter().any(|key| {
@@ -1038,28 +1320,28 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
         } else {
             // Existing auth is invalid, use new auth directly
             serde_json::to_value(&config.auth)
-                .map_err(|e| format!("Failed to convert auth: {}", e))?
+                .map_err(|e| AnyCodeError::Internal(format!("Failed to convert auth: {}", e)))?
         }
     } else {
         // No existing auth, use new auth directly
         serde_json::to_value(&config.auth)
-            .map_err(|e| format!("Failed to convert auth: {}", e))?
+            .map_err(|e| AnyCodeError::Internal(format!("Failed to convert auth: {}", e)))?
     };
 
     // Write merged auth.json
     let auth_content = serde_json::to_string_pretty(&final_auth)
-        .map_err(|e| format!("Failed to serialize auth: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize auth: {}", e)))?;
     fs::write(&auth_path, auth_content)
-        .map_err(|e| format!("Failed to write auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write auth.json: {}", e)))?;
 
     // Merge config.toml - preserve user's custom settings using string-level operations
     // to keep comments, formatting, and other user customizations
     let final_config = if config_path.exists() {
         // IMPORTANT: Backup FIRST before any processing
-        backup_config_toml()?;
+        backup_config_toml().map_err(AnyCodeError::Io)?;
         
         let existing_content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read existing config.toml: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read existing config.toml: {}", e)))?;
         
         log::info!("[Codex Provider] Original config.toml content:\n{}", existing_content);
 
@@ -1197,7 +1479,7 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
 
     // Write merged config.toml (backup already done above)
     fs::write(&config_path, &final_config)
-        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write config.toml: {}", e)))?;
 
     log::info!("[Codex Provider] Successfully switched to: {}", config.name);
     Ok(format!("Successfully switched to Codex provider: {}", config.name))
@@ -1205,23 +1487,23 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
 
 /// Add a new Codex provider configuration
 #[tauri::command]
-pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<String, String> {
+pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Adding provider: {}", config.name);
 
-    let providers_path = get_codex_providers_path()?;
+    let providers_path = get_codex_providers_path().map_err(AnyCodeError::Io)?;
 
     // Ensure parent directory exists
     if let Some(parent) = providers_path.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
+                .map_err(|e| AnyCodeError::Io(format!("Failed to create directory: {}", e)))?;
         }
     }
 
     // Load existing providers
     let mut providers: Vec<CodexProviderConfig> = if providers_path.exists() {
         let content = fs::read_to_string(&providers_path)
-            .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
         serde_json::from_str(&content).unwrap_or_default()
     } else {
         vec![]
@@ -1229,16 +1511,16 @@ pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<St
 
     // Check for duplicate ID
     if providers.iter().any(|p| p.id == config.id) {
-        return Err(format!("Provider with ID '{}' already exists", config.id));
+        return Err(AnyCodeError::Provider(format!("Provider with ID '{}' already exists", config.id)));
     }
 
     providers.push(config.clone());
 
     // Save providers
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     log::info!("[Codex Provider] Successfully added provider: {}", config.name);
     Ok(format!("Successfully added Codex provider: {}", config.name))
@@ -1246,31 +1528,31 @@ pub async fn add_codex_provider_config(config: CodexProviderConfig) -> Result<St
 
 /// Update an existing Codex provider configuration
 #[tauri::command]
-pub async fn update_codex_provider_config(config: CodexProviderConfig) -> Result<String, String> {
+pub async fn update_codex_provider_config(config: CodexProviderConfig) -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Updating provider: {}", config.name);
 
-    let providers_path = get_codex_providers_path()?;
+    let providers_path = get_codex_providers_path().map_err(AnyCodeError::Io)?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", config.id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = config.id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<CodexProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     // Find and update the provider
     let index = providers.iter().position(|p| p.id == config.id)
-        .ok_or_else(|| format!("Provider with ID '{}' not found", config.id))?;
+        .ok_or_else(|| AnyCodeError::Provider(tr!("provider.not_found", id = config.id)))?;
 
     providers[index] = config.clone();
 
     // Save providers
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     log::info!("[Codex Provider] Successfully updated provider: {}", config.name);
     Ok(format!("Successfully updated Codex provider: {}", config.name))
@@ -1278,33 +1560,33 @@ pub async fn update_codex_provider_config(config: CodexProviderConfig) -> Result
 
 /// Delete a Codex provider configuration
 #[tauri::command]
-pub async fn delete_codex_provider_config(id: String) -> Result<String, String> {
+pub async fn delete_codex_provider_config(id: String) -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Deleting provider: {}", id);
 
-    let providers_path = get_codex_providers_path()?;
+    let providers_path = get_codex_providers_path().map_err(AnyCodeError::Io)?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<CodexProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     // Find and remove the provider
     let initial_len = providers.len();
     providers.retain(|p| p.id != id);
 
     if providers.len() == initial_len {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     // Save providers
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     log::info!("[Codex Provider] Successfully deleted provider: {}", id);
     Ok(format!("Successfully deleted Codex provider: {}", id))
@@ -1312,22 +1594,22 @@ pub async fn delete_codex_provider_config(id: String) -> Result<String, String>
 
 /// Clear Codex provider configuration (reset to official)
 #[tauri::command]
-pub async fn clear_codex_provider_config() -> Result<String, String> {
+pub async fn clear_codex_provider_config() -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Clearing config");
 
-    let auth_path = get_codex_auth_path()?;
-    let config_path = get_codex_config_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
 
     // Remove auth.json if exists
     if auth_path.exists() {
         fs::remove_file(&auth_path)
-            .map_err(|e| format!("Failed to remove auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to remove auth.json: {}", e)))?;
     }
 
     // Remove config.toml if exists
     if config_path.exists() {
         fs::remove_file(&config_path)
-            .map_err(|e| format!("Failed to remove config.toml: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to remove config.toml: {}", e)))?;
     }
 
     log::info!("[Codex Provider] Successfully cleared config");
@@ -1336,14 +1618,20 @@ pub async fn clear_codex_provider_config() -> Result<String, String> {
 
 /// Test Codex provider connection
 #[tauri::command]
-pub async fn test_codex_provider_connection(base_url: String, api_key: Option<String>) -> Result<String, String> {
+pub async fn test_codex_provider_connection(
+    base_url: String,
+    api_key: Option<String>,
+    provider_id: Option<String>,
+) -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Testing connection to: {}", base_url);
+    let metrics_id = provider_id.unwrap_or_else(|| base_url.clone());
+    let started = std::time::Instant::now();
 
     // Simple connectivity test - just try to reach the endpoint
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        .map_err(|e| AnyCodeError::Network(format!("Failed to create HTTP client: {}", e)))?;
 
     let test_url = format!("{}/models", base_url.trim_end_matches('/'));
 
@@ -1356,6 +1644,7 @@ pub async fn test_codex_provider_connection(base_url: String, api_key: Option<St
     match request.send().await {
         Ok(response) => {
             let status = response.status();
+            provider_metrics::record_connection_test("codex", &metrics_id, started, provider_metrics::classify_status(status.as_u16()));
             if status.is_success() || status.as_u16() == 401 {
                 // 401 means the endpoint exists but auth is required
                 Ok(format!("Connection test successful: endpoint is reachable (status: {})", status))
@@ -1364,7 +1653,8 @@ pub async fn test_codex_provider_connection(base_url: String, api_key: Option<St
             }
         }
         Err(e) => {
-            Err(format!("Connection test failed: {}", e))
+            provider_metrics::record_connection_test("codex", &metrics_id, started, Some(provider_metrics::classify_request_error(&e)));
+            Err(AnyCodeError::Network(format!("Connection test failed: {}", e)))
         }
     }
 }
@@ -1413,7 +1703,7 @@ fn has_official_oauth_tokens(auth: &serde_json::Value) -> bool {
 }
 
 /// Mask API key for display
-fn mask_api_key(key: &str) -> String {
+pub(crate) fn mask_api_key(key: &str) -> String {
     if key.len() <= 10 {
         return "*".repeat(key.len());
     }
@@ -1424,18 +1714,18 @@ fn mask_api_key(key: &str) -> String {
 
 /// Get current provider mode status
 #[tauri::command]
-pub async fn get_codex_provider_mode() -> Result<CodexProviderMode, String> {
+pub async fn get_codex_provider_mode() -> Result<CodexProviderMode, AnyCodeError> {
     log::info!("[Codex Provider] Getting provider mode status");
 
-    let auth_path = get_codex_auth_path()?;
-    let config_path = get_codex_config_path()?;
-    let third_party_backup_path = get_third_party_auth_backup_path()?;
-    let official_backup_path = get_official_auth_backup_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
+    let third_party_backup_path = get_third_party_auth_backup_path().map_err(AnyCodeError::Io)?;
+    let official_backup_path = get_official_auth_backup_path().map_err(AnyCodeError::Io)?;
 
     // Read current auth.json
     let auth: serde_json::Value = if auth_path.exists() {
         let content = fs::read_to_string(&auth_path)
-            .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read auth.json: {}", e)))?;
         serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
     } else {
         serde_json::json!({})
@@ -1444,7 +1734,7 @@ pub async fn get_codex_provider_mode() -> Result<CodexProviderMode, String> {
     // Read current config.toml
     let config: String = if config_path.exists() {
         fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config.toml: {}", e))?
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read config.toml: {}", e)))?
     } else {
         String::new()
     };
@@ -1488,18 +1778,18 @@ fn extract_model_provider_from_config(config: &str) -> Option<String> {
 
 /// Backup current auth.json for third-party mode
 #[tauri::command]
-pub async fn backup_third_party_auth() -> Result<String, String> {
+pub async fn backup_third_party_auth() -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Backing up third-party auth");
 
-    let auth_path = get_codex_auth_path()?;
-    let backup_path = get_third_party_auth_backup_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let backup_path = get_third_party_auth_backup_path().map_err(AnyCodeError::Io)?;
 
     if !auth_path.exists() {
-        return Err("No auth.json found to backup".to_string());
+        return Err(AnyCodeError::NotFound("No auth.json found to backup".to_string()));
     }
 
     fs::copy(&auth_path, &backup_path)
-        .map_err(|e| format!("Failed to backup auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to backup auth.json: {}", e)))?;
 
     log::info!("[Codex Provider] Third-party auth backed up to {:?}", backup_path);
     Ok("Third-party auth.json backed up successfully".to_string())
@@ -1507,28 +1797,28 @@ pub async fn backup_third_party_auth() -> Result<String, String> {
 
 /// Backup current auth.json for official mode (OAuth tokens)
 #[tauri::command]
-pub async fn backup_official_auth() -> Result<String, String> {
+pub async fn backup_official_auth() -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Backing up official auth");
 
-    let auth_path = get_codex_auth_path()?;
-    let backup_path = get_official_auth_backup_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let backup_path = get_official_auth_backup_path().map_err(AnyCodeError::Io)?;
 
     if !auth_path.exists() {
-        return Err("No auth.json found to backup".to_string());
+        return Err(AnyCodeError::NotFound("No auth.json found to backup".to_string()));
     }
 
     // Check if current auth has OAuth tokens
     let content = fs::read_to_string(&auth_path)
-        .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read auth.json: {}", e)))?;
     let auth: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse auth.json: {}", e)))?;
 
     if !has_official_oauth_tokens(&auth) {
-        return Err("Current auth.json does not contain official OAuth tokens".to_string());
+        return Err(AnyCodeError::Validation("Current auth.json does not contain official OAuth tokens".to_string()));
     }
 
     fs::copy(&auth_path, &backup_path)
-        .map_err(|e| format!("Failed to backup auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to backup auth.json: {}", e)))?;
 
     log::info!("[Codex Provider] Official auth backed up to {:?}", backup_path);
     Ok("Official auth.json backed up successfully".to_string())
@@ -1536,18 +1826,18 @@ pub async fn backup_official_auth() -> Result<String, String> {
 
 /// Restore third-party auth.json from backup
 #[tauri::command]
-pub async fn restore_third_party_auth() -> Result<String, String> {
+pub async fn restore_third_party_auth() -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Restoring third-party auth");
 
-    let auth_path = get_codex_auth_path()?;
-    let backup_path = get_third_party_auth_backup_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let backup_path = get_third_party_auth_backup_path().map_err(AnyCodeError::Io)?;
 
     if !backup_path.exists() {
-        return Err("No third-party auth backup found".to_string());
+        return Err(AnyCodeError::NotFound("No third-party auth backup found".to_string()));
     }
 
     fs::copy(&backup_path, &auth_path)
-        .map_err(|e| format!("Failed to restore auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to restore auth.json: {}", e)))?;
 
     log::info!("[Codex Provider] Third-party auth restored from {:?}", backup_path);
     Ok("Third-party auth.json restored successfully".to_string())
@@ -1555,18 +1845,18 @@ pub async fn restore_third_party_auth() -> Result<String, String> {
 
 /// Restore official auth.json from backup
 #[tauri::command]
-pub async fn restore_official_auth() -> Result<String, String> {
+pub async fn restore_official_auth() -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Restoring official auth");
 
-    let auth_path = get_codex_auth_path()?;
-    let backup_path = get_official_auth_backup_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let backup_path = get_official_auth_backup_path().map_err(AnyCodeError::Io)?;
 
     if !backup_path.exists() {
-        return Err("No official auth backup found".to_string());
+        return Err(AnyCodeError::NotFound("No official auth backup found".to_string()));
     }
 
     fs::copy(&backup_path, &auth_path)
-        .map_err(|e| format!("Failed to restore auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to restore auth.json: {}", e)))?;
 
     log::info!("[Codex Provider] Official auth restored from {:?}", backup_path);
     Ok("Official auth.json restored successfully".to_string())
@@ -1578,29 +1868,29 @@ pub async fn restore_official_auth() -> Result<String, String> {
 /// 2. Restore official auth.json if backup exists, or clear auth.json
 /// 3. Comment out third-party config in config.toml
 #[tauri::command]
-pub async fn switch_to_official_mode() -> Result<String, String> {
+pub async fn switch_to_official_mode() -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Switching to official mode");
 
-    let auth_path = get_codex_auth_path()?;
-    let config_path = get_codex_config_path()?;
-    let config_dir = get_codex_config_dir()?;
-    let third_party_backup_path = get_third_party_auth_backup_path()?;
-    let official_backup_path = get_official_auth_backup_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
+    let config_dir = get_codex_config_dir().map_err(AnyCodeError::Io)?;
+    let third_party_backup_path = get_third_party_auth_backup_path().map_err(AnyCodeError::Io)?;
+    let official_backup_path = get_official_auth_backup_path().map_err(AnyCodeError::Io)?;
 
     // Ensure config directory exists
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create .codex directory: {}", e)))?;
     }
 
     // Step 1: Backup current auth if it has API key (third-party)
     if auth_path.exists() {
         let content = fs::read_to_string(&auth_path)
-            .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read auth.json: {}", e)))?;
         if let Ok(auth) = serde_json::from_str::<serde_json::Value>(&content) {
             if extract_api_key_from_auth(&auth).is_some() && !has_official_oauth_tokens(&auth) {
                 fs::copy(&auth_path, &third_party_backup_path)
-                    .map_err(|e| format!("Failed to backup third-party auth: {}", e))?;
+                    .map_err(|e| AnyCodeError::Io(format!("Failed to backup third-party auth: {}", e)))?;
                 log::info!("[Codex Provider] Third-party auth backed up");
             }
         }
@@ -1609,7 +1899,7 @@ pub async fn switch_to_official_mode() -> Result<String, String> {
     // Step 2: Restore official auth if backup exists, otherwise clear auth
     if official_backup_path.exists() {
         fs::copy(&official_backup_path, &auth_path)
-            .map_err(|e| format!("Failed to restore official auth: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to restore official auth: {}", e)))?;
         log::info!("[Codex Provider] Official auth restored from backup");
     } else {
         // Create empty auth for official login
@@ -1617,23 +1907,23 @@ pub async fn switch_to_official_mode() -> Result<String, String> {
             "OPENAI_API_KEY": null
         });
         let content = serde_json::to_string_pretty(&empty_auth)
-            .map_err(|e| format!("Failed to serialize auth: {}", e))?;
+            .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize auth: {}", e)))?;
         fs::write(&auth_path, content)
-            .map_err(|e| format!("Failed to write auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to write auth.json: {}", e)))?;
         log::info!("[Codex Provider] Auth cleared for official login");
     }
 
     // Step 3: Backup and comment out third-party config in config.toml
     if config_path.exists() {
         // Backup before modifying
-        backup_config_toml()?;
+        backup_config_toml().map_err(AnyCodeError::Io)?;
 
         let config_content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config.toml: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read config.toml: {}", e)))?;
         
         let commented_config = comment_third_party_config(&config_content);
         fs::write(&config_path, &commented_config)
-            .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to write config.toml: {}", e)))?;
         log::info!("[Codex Provider] Third-party config commented out");
     }
 
@@ -1743,29 +2033,29 @@ pub async fn switch_to_third_party_mode(
     model_provider: Option<String>,
     model: Option<String>,
     model_reasoning_effort: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Switching to third-party mode");
 
-    let auth_path = get_codex_auth_path()?;
-    let config_path = get_codex_config_path()?;
-    let config_dir = get_codex_config_dir()?;
-    let third_party_backup_path = get_third_party_auth_backup_path()?;
-    let official_backup_path = get_official_auth_backup_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
+    let config_dir = get_codex_config_dir().map_err(AnyCodeError::Io)?;
+    let third_party_backup_path = get_third_party_auth_backup_path().map_err(AnyCodeError::Io)?;
+    let official_backup_path = get_official_auth_backup_path().map_err(AnyCodeError::Io)?;
 
     // Ensure config directory exists
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create .codex directory: {}", e)))?;
     }
 
     // Step 1: Backup current auth if it has OAuth tokens (official)
     if auth_path.exists() {
         let content = fs::read_to_string(&auth_path)
-            .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read auth.json: {}", e)))?;
         if let Ok(auth) = serde_json::from_str::<serde_json::Value>(&content) {
             if has_official_oauth_tokens(&auth) {
                 fs::copy(&auth_path, &official_backup_path)
-                    .map_err(|e| format!("Failed to backup official auth: {}", e))?;
+                    .map_err(|e| AnyCodeError::Io(format!("Failed to backup official auth: {}", e)))?;
                 log::info!("[Codex Provider] Official auth backed up");
             }
         }
@@ -1778,23 +2068,23 @@ pub async fn switch_to_third_party_mode(
             "OPENAI_API_KEY": key
         });
         let content = serde_json::to_string_pretty(&auth)
-            .map_err(|e| format!("Failed to serialize auth: {}", e))?;
+            .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize auth: {}", e)))?;
         fs::write(&auth_path, content)
-            .map_err(|e| format!("Failed to write auth.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to write auth.json: {}", e)))?;
         log::info!("[Codex Provider] Third-party auth set with new API key");
     } else if third_party_backup_path.exists() {
         // Restore from backup
         fs::copy(&third_party_backup_path, &auth_path)
-            .map_err(|e| format!("Failed to restore third-party auth: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to restore third-party auth: {}", e)))?;
         log::info!("[Codex Provider] Third-party auth restored from backup");
     } else {
-        return Err("No API key provided and no third-party backup found".to_string());
+        return Err(AnyCodeError::Provider("No API key provided and no third-party backup found".to_string()));
     }
 
     // Step 3: Update config.toml
     let mut config_content = if config_path.exists() {
         fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config.toml: {}", e))?
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read config.toml: {}", e)))?
     } else {
         String::new()
     };
@@ -1803,7 +2093,7 @@ pub async fn switch_to_third_party_mode(
     config_content = uncomment_third_party_config(&config_content);
 
     // Backup before modifying
-    backup_config_toml()?;
+    backup_config_toml().map_err(AnyCodeError::Io)?;
 
     // Update or add config values
     if let Some(provider) = model_provider {
@@ -1817,7 +2107,7 @@ pub async fn switch_to_third_party_mode(
     }
 
     fs::write(&config_path, &config_content)
-        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write config.toml: {}", e)))?;
 
     Ok("Switched to third-party mode successfully".to_string())
 }
@@ -1839,93 +2129,44 @@ fn update_or_add_toml_value(config: &str, key: &str, value: &str) -> String {
 
 /// Open terminal for Codex authentication
 #[tauri::command]
-pub async fn open_codex_auth_terminal() -> Result<String, String> {
+pub async fn open_codex_auth_terminal() -> Result<String, AnyCodeError> {
     log::info!("[Codex Provider] Opening terminal for Codex auth");
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::process::Command as StdCommand;
-        
-        // Try to open PowerShell with wsl codex auth login command (codex is installed in WSL)
-        let result = StdCommand::new("cmd")
-            .args(["/c", "start", "powershell", "-NoExit", "-Command", "wsl codex auth login"])
-            .spawn();
-
-        match result {
-            Ok(_) => {
-                log::info!("[Codex Provider] PowerShell terminal opened for auth via WSL");
-                Ok("Terminal opened. Please complete the authentication in the new window.".to_string())
-            }
-            Err(e) => {
-                log::error!("[Codex Provider] Failed to open terminal: {}", e);
-                Err(format!("Failed to open terminal: {}. Please run 'wsl codex auth login' manually.", e))
-            }
-        }
-    }
+    // Codex installed in WSL has to be logged in from inside WSL
+    let wsl_config = wsl_utils::get_wsl_config();
+    let command = match (wsl_config.enabled, &wsl_config.distro) {
+        (true, Some(distro)) => format!("wsl -d {} codex auth login", distro),
+        (true, None) => "wsl codex auth login".to_string(),
+        _ => "codex auth login".to_string(),
+    };
 
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command as StdCommand;
-        
-        // Open Terminal.app with codex auth login command
-        let script = r#"tell application "Terminal"
-            activate
-            do script "codex auth login"
-        end tell"#;
-        
-        let result = StdCommand::new("osascript")
-            .args(["-e", script])
-            .spawn();
-
-        match result {
-            Ok(_) => {
-                log::info!("[Codex Provider] Terminal opened for auth");
-                Ok("Terminal opened. Please complete the authentication in the new window.".to_string())
-            }
-            Err(e) => {
-                log::error!("[Codex Provider] Failed to open terminal: {}", e);
-                Err(format!("Failed to open terminal: {}. Please run 'codex auth login' manually.", e))
-            }
+    match super::super::terminal::launch_terminal(&command, None) {
+        Ok(terminal) => {
+            log::info!("[Codex Provider] {} opened for auth", terminal);
+            Ok("Terminal opened. Please complete the authentication in the new window.".to_string())
         }
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command as StdCommand;
-        
-        // Try common terminal emulators
-        let terminals = [
-            ("gnome-terminal", vec!["--", "bash", "-c", "codex auth login; exec bash"]),
-            ("konsole", vec!["-e", "bash", "-c", "codex auth login; exec bash"]),
-            ("xterm", vec!["-e", "bash", "-c", "codex auth login; exec bash"]),
-        ];
-
-        for (terminal, args) in terminals {
-            if let Ok(_) = StdCommand::new(terminal).args(&args).spawn() {
-                log::info!("[Codex Provider] {} terminal opened for auth", terminal);
-                return Ok("Terminal opened. Please complete the authentication in the new window.".to_string());
-            }
+        Err(e) => {
+            log::error!("[Codex Provider] Failed to open terminal: {}", e);
+            Err(AnyCodeError::Engine(format!("Failed to open terminal: {}. Please run '{}' manually.", e, command)))
         }
-
-        Err("Failed to open terminal. Please run 'codex auth login' manually.".to_string())
     }
 }
 
 /// Check if Codex authentication is valid
 #[tauri::command]
-pub async fn check_codex_auth_status() -> Result<bool, String> {
+pub async fn check_codex_auth_status() -> Result<bool, AnyCodeError> {
     log::info!("[Codex Provider] Checking auth status");
 
-    let auth_path = get_codex_auth_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
     
     if !auth_path.exists() {
         return Ok(false);
     }
 
     let content = fs::read_to_string(&auth_path)
-        .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read auth.json: {}", e)))?;
     let auth: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse auth.json: {}", e)))?;
 
     // Check for valid OAuth tokens or API key
     let has_tokens = has_official_oauth_tokens(&auth);
@@ -1952,8 +2193,7 @@ pub struct CodexConfigFileProvider {
 }
 
 fn get_anycode_dir() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
-    let dir = home.join(".anycode");
+    let dir = anycode_dir()?;
     if !dir.exists() {
         fs::create_dir_all(&dir).map_err(|e| format!("Failed to create .anycode directory: {}", e))?;
     }
@@ -1966,25 +2206,25 @@ fn get_codex_config_file_providers_path() -> Result<PathBuf, String> {
 
 /// Read current ~/.codex/config.toml (or WSL path on Windows when enabled)
 #[tauri::command]
-pub async fn read_codex_config_toml() -> Result<String, String> {
-    let config_path = get_codex_config_path()?;
+pub async fn read_codex_config_toml() -> Result<String, AnyCodeError> {
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
     if !config_path.exists() {
         return Ok(String::new());
     }
     fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config.toml: {}", e))
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read config.toml: {}", e)))
 }
 
 /// Read current ~/.codex/auth.json (or WSL path on Windows when enabled)
 #[tauri::command]
-pub async fn read_codex_auth_json_text() -> Result<String, String> {
-    let auth_path = get_codex_auth_path()?;
+pub async fn read_codex_auth_json_text() -> Result<String, AnyCodeError> {
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
     if !auth_path.exists() {
         return Ok("{\n}\n".to_string());
     }
 
     let content = fs::read_to_string(&auth_path)
-        .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read auth.json: {}", e)))?;
 
     // Normalize formatting if it's valid JSON; otherwise return raw content for user to fix.
     match serde_json::from_str::<serde_json::Value>(&content) {
@@ -1996,116 +2236,116 @@ pub async fn read_codex_auth_json_text() -> Result<String, String> {
 /// Write ~/.codex/config.toml (or WSL path on Windows when enabled)
 /// This replaces the file content. If the file exists, a .bak backup is created first.
 #[tauri::command]
-pub async fn write_codex_config_toml(content: String) -> Result<String, String> {
+pub async fn write_codex_config_toml(content: String) -> Result<String, AnyCodeError> {
     // Validate TOML when not empty
     if !content.trim().is_empty() {
         let _table: toml::Table = toml::from_str(&content)
-            .map_err(|e| format!("Invalid TOML configuration: {}", e))?;
+            .map_err(|e| AnyCodeError::Config(format!("Invalid TOML configuration: {}", e)))?;
     }
 
-    let config_dir = get_codex_config_dir()?;
+    let config_dir = get_codex_config_dir().map_err(AnyCodeError::Io)?;
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create .codex directory: {}", e)))?;
     }
 
     // Backup existing file (if any)
-    let config_path = get_codex_config_path()?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
     if config_path.exists() {
-        backup_config_toml()?;
+        backup_config_toml().map_err(AnyCodeError::Io)?;
     }
 
     fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write config.toml: {}", e)))?;
 
-    Ok(format!("✅ 已写入 {}", config_path.display()))
+    Ok(tr!("config.written", path = config_path.display()))
 }
 
 /// Write ~/.codex/auth.json (or WSL path on Windows when enabled)
 /// This replaces the file content. The content must be a valid JSON object.
 #[tauri::command]
-pub async fn write_codex_auth_json_text(content: String) -> Result<String, String> {
+pub async fn write_codex_auth_json_text(content: String) -> Result<String, AnyCodeError> {
     let trimmed = content.trim();
     let json_str = if trimmed.is_empty() { "{}" } else { trimmed };
 
     let value: serde_json::Value = serde_json::from_str(json_str)
-        .map_err(|e| format!("Invalid JSON: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Invalid JSON: {}", e)))?;
     if !value.is_object() {
-        return Err("auth.json 必须是 JSON 对象".to_string());
+        return Err(AnyCodeError::Validation("auth.json 必须是 JSON 对象".to_string()));
     }
 
-    let config_dir = get_codex_config_dir()?;
+    let config_dir = get_codex_config_dir().map_err(AnyCodeError::Io)?;
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create .codex directory: {}", e)))?;
     }
 
-    let auth_path = get_codex_auth_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
     let pretty = serde_json::to_string_pretty(&value)
-        .map_err(|e| format!("Failed to serialize auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize auth.json: {}", e)))?;
 
     fs::write(&auth_path, pretty)
-        .map_err(|e| format!("Failed to write auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write auth.json: {}", e)))?;
 
-    Ok(format!("✅ 已写入 {}", auth_path.display()))
+    Ok(tr!("config.written", path = auth_path.display()))
 }
 
 /// Write both ~/.codex/config.toml and ~/.codex/auth.json (WSL-aware on Windows)
 /// This validates both files before writing to reduce partial updates.
 #[tauri::command]
-pub async fn write_codex_config_files(config_toml: String, auth_json: String) -> Result<String, String> {
+pub async fn write_codex_config_files(config_toml: String, auth_json: String) -> Result<String, AnyCodeError> {
     // Validate TOML when not empty
     if !config_toml.trim().is_empty() {
         let _table: toml::Table = toml::from_str(&config_toml)
-            .map_err(|e| format!("Invalid TOML configuration: {}", e))?;
+            .map_err(|e| AnyCodeError::Config(format!("Invalid TOML configuration: {}", e)))?;
     }
 
     // Validate auth.json (accept empty as {})
     let auth_trimmed = auth_json.trim();
     let auth_str = if auth_trimmed.is_empty() { "{}" } else { auth_trimmed };
     let auth_value: serde_json::Value = serde_json::from_str(auth_str)
-        .map_err(|e| format!("Invalid JSON (auth.json): {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Invalid JSON (auth.json): {}", e)))?;
     if !auth_value.is_object() {
-        return Err("auth.json 必须是 JSON 对象".to_string());
+        return Err(AnyCodeError::Validation("auth.json 必须是 JSON 对象".to_string()));
     }
 
-    let config_dir = get_codex_config_dir()?;
+    let config_dir = get_codex_config_dir().map_err(AnyCodeError::Io)?;
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create .codex directory: {}", e)))?;
     }
 
     // Backup existing config.toml (if any)
-    let config_path = get_codex_config_path()?;
+    let config_path = get_codex_config_path().map_err(AnyCodeError::Io)?;
     if config_path.exists() {
-        backup_config_toml()?;
+        backup_config_toml().map_err(AnyCodeError::Io)?;
     }
 
     // Write config.toml (keep user formatting)
     fs::write(&config_path, config_toml)
-        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write config.toml: {}", e)))?;
 
     // Write auth.json (pretty JSON)
-    let auth_path = get_codex_auth_path()?;
+    let auth_path = get_codex_auth_path().map_err(AnyCodeError::Io)?;
     let auth_pretty = serde_json::to_string_pretty(&auth_value)
-        .map_err(|e| format!("Failed to serialize auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize auth.json: {}", e)))?;
     fs::write(&auth_path, auth_pretty)
-        .map_err(|e| format!("Failed to write auth.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write auth.json: {}", e)))?;
 
-    Ok(format!("✅ 已写入 {} 和 {}", config_path.display(), auth_path.display()))
+    Ok(tr!("config.written_both", first = config_path.display(), second = auth_path.display()))
 }
 
 /// Get Codex config.toml presets (AnyCode-managed)
 #[tauri::command]
-pub async fn get_codex_config_file_providers() -> Result<Vec<CodexConfigFileProvider>, String> {
-    let providers_path = get_codex_config_file_providers_path()?;
+pub async fn get_codex_config_file_providers() -> Result<Vec<CodexConfigFileProvider>, AnyCodeError> {
+    let providers_path = get_codex_config_file_providers_path().map_err(AnyCodeError::Io)?;
     if !providers_path.exists() {
         return Ok(vec![]);
     }
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let providers: Vec<CodexConfigFileProvider> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
     Ok(providers)
 }
 
@@ -2113,34 +2353,34 @@ pub async fn get_codex_config_file_providers() -> Result<Vec<CodexConfigFileProv
 #[tauri::command]
 pub async fn add_codex_config_file_provider(
     config: CodexConfigFileProvider,
-) -> Result<String, String> {
-    let providers_path = get_codex_config_file_providers_path()?;
+) -> Result<String, AnyCodeError> {
+    let providers_path = get_codex_config_file_providers_path().map_err(AnyCodeError::Io)?;
 
     if let Some(parent) = providers_path.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
+                .map_err(|e| AnyCodeError::Io(format!("Failed to create directory: {}", e)))?;
         }
     }
 
     let mut providers: Vec<CodexConfigFileProvider> = if providers_path.exists() {
         let content = fs::read_to_string(&providers_path)
-            .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
         serde_json::from_str(&content).unwrap_or_default()
     } else {
         vec![]
     };
 
     if providers.iter().any(|p| p.id == config.id) {
-        return Err(format!("Provider with ID '{}' already exists", config.id));
+        return Err(AnyCodeError::Provider(format!("Provider with ID '{}' already exists", config.id)));
     }
 
     providers.push(config.clone());
 
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     Ok(format!("Successfully added Codex config preset: {}", config.name))
 }
@@ -2149,52 +2389,99 @@ pub async fn add_codex_config_file_provider(
 #[tauri::command]
 pub async fn update_codex_config_file_provider(
     config: CodexConfigFileProvider,
-) -> Result<String, String> {
-    let providers_path = get_codex_config_file_providers_path()?;
+) -> Result<String, AnyCodeError> {
+    let providers_path = get_codex_config_file_providers_path().map_err(AnyCodeError::Io)?;
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", config.id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = config.id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<CodexConfigFileProvider> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     let index = providers.iter().position(|p| p.id == config.id)
-        .ok_or_else(|| format!("Provider with ID '{}' not found", config.id))?;
+        .ok_or_else(|| AnyCodeError::Provider(tr!("provider.not_found", id = config.id)))?;
     providers[index] = config.clone();
 
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     Ok(format!("Successfully updated Codex config preset: {}", config.name))
 }
 
 /// Delete a Codex config.toml preset (AnyCode-managed)
 #[tauri::command]
-pub async fn delete_codex_config_file_provider(id: String) -> Result<String, String> {
-    let providers_path = get_codex_config_file_providers_path()?;
+pub async fn delete_codex_config_file_provider(id: String) -> Result<String, AnyCodeError> {
+    let providers_path = get_codex_config_file_providers_path().map_err(AnyCodeError::Io)?;
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<CodexConfigFileProvider> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     let initial_len = providers.len();
     providers.retain(|p| p.id != id);
     if providers.len() == initial_len {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     Ok("Successfully deleted Codex config preset".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn existing_unique_candidates_filters_and_dedupes_in_order() {
+        let candidates = vec![
+            "codex".to_string(),
+            "/missing/codex".to_string(),
+            "/usr/local/bin/codex".to_string(),
+            "/opt/codex".to_string(),
+        ];
+        let resolved = existing_unique_candidates(candidates, |c| match c {
+            "codex" | "/usr/local/bin/codex" => Some("/usr/local/bin/codex".to_string()),
+            "/opt/codex" => Some(c.to_string()),
+            _ => None,
+        });
+        assert_eq!(resolved, vec!["/usr/local/bin/codex", "/opt/codex"]);
+    }
+
+    #[test]
+    fn provider_config_flattens_to_dotted_overrides() {
+        let value: toml::Value = r#"
+model_provider = "relay"
+
+[model_providers.relay]
+base_url = "https://relay.example.com/v1"
+requires_openai_auth = true
+"#
+        .parse()
+        .unwrap();
+        let mut args = Vec::new();
+        flatten_toml_overrides("", &value, &mut args);
+        assert_eq!(
+            args,
+            vec![
+                "-c",
+                "model_provider=\"relay\"",
+                "-c",
+                "model_providers.relay.base_url=\"https://relay.example.com/v1\"",
+                "-c",
+                "model_providers.relay.requires_openai_auth=true",
+            ]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Codex 代理商自动故障转移
+//!
+//! 把若干代理商预设标记为有序的备用链。一次 Codex 执行在开始输出之前因鉴权失败、
+//! 连接失败或 429 退出（或这些错误持续超过阈值仍未开始输出）时，切换到链中的下一个
+//! 代理商并重试，同时发送 `codex-failover:{session_id}` / `codex-failover` 事件。
+//!
+//! - 未指定代理商的执行：通过 `switch_codex_provider` 改写 ~/.codex/config.toml，
+//!   后续执行也会使用新的代理商
+//! - 指定了代理商预设的执行：只替换本次执行的 `-c` 覆盖，不改动 config.toml
+//!
+//! 多个会话同时失败时，后发现的会话若看到 config.toml 已被切走，直接沿用新的代理商，
+//! 不会再次切换。
+
+use crate::error::AnyCodeError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Emitter};
+
+use super::config::{
+    active_codex_provider_id, codex_provider_overrides, get_codex_failover_path, load_codex_provider,
+    switch_codex_provider,
+};
+use super::session::CodexExecutionOptions;
+use crate::commands::provider_metrics::ErrorCategory;
+
+/// 备用链配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodexFailoverConfig {
+    pub enabled: bool,
+    /// 按优先级排列的代理商预设 ID
+    pub chain: Vec<String>,
+    /// 出现可转移的错误后，超过该秒数仍未开始输出即放弃当前代理商
+    pub start_timeout_secs: u64,
+}
+
+impl Default for CodexFailoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            chain: Vec::new(),
+            start_timeout_secs: 20,
+        }
+    }
+}
+
+impl CodexFailoverConfig {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.chain.is_empty()
+    }
+}
+
+/// 读取备用链配置（不存在或损坏时返回默认值，即关闭）
+pub fn load_failover_config() -> CodexFailoverConfig {
+    let Ok(path) = get_codex_failover_path() else {
+        return CodexFailoverConfig::default();
+    };
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 值得换代理商重试的失败：鉴权、连接、限流
+pub fn is_failover_error(category: ErrorCategory) -> bool {
+    matches!(
+        category,
+        ErrorCategory::Auth | ErrorCategory::Connection | ErrorCategory::RateLimit
+    )
+}
+
+/// 链中位于 `failed` 之后、且本次执行尚未尝试过的下一个代理商（循环查找）
+fn next_provider(chain: &[String], failed: &str, tried: &[String]) -> Option<String> {
+    let start = chain.iter().position(|id| id == failed).map_or(0, |i| i + 1);
+    (0..chain.len())
+        .map(|offset| &chain[(start + offset) % chain.len()])
+        .find(|id| id.as_str() != failed && !tried.contains(id))
+        .cloned()
+}
+
+/// 从 `failed` 切换到下一个代理商，返回新的代理商 ID；链已用尽或切换失败时返回 None
+///
+/// `tried` 记录本次执行已失败过的代理商，避免在链中来回切换。
+pub async fn fail_over(
+    app: &AppHandle,
+    session_id: &str,
+    options: &mut CodexExecutionOptions,
+    tried: &mut Vec<String>,
+    failed: &str,
+    reason: ErrorCategory,
+) -> Option<String> {
+    let config = load_failover_config();
+    tried.push(failed.to_string());
+
+    let explicit = options.provider.is_some();
+    // 其他会话可能已经完成切换，沿用即可
+    let already_switched = if explicit {
+        None
+    } else {
+        active_codex_provider_id().filter(|active| active != failed && !tried.contains(active))
+    };
+
+    let config_rewritten = !explicit && already_switched.is_none();
+    let target = match already_switched {
+        Some(active) => active,
+        None => {
+            let Some(next) = next_provider(&config.chain, failed, tried) else {
+                log::warn!("[Codex Failover] No provider left in the chain after {}", failed);
+                return None;
+            };
+            let preset = match load_codex_provider(&next) {
+                Ok(preset) => preset,
+                Err(e) => {
+                    log::warn!("[Codex Failover] {}", e);
+                    return None;
+                }
+            };
+            if explicit {
+                options.provider = Some(next.clone());
+            } else if let Err(e) = switch_codex_provider(preset).await {
+                log::warn!("[Codex Failover] Failed to switch config.toml to {}: {}", next, e);
+                return None;
+            }
+            next
+        }
+    };
+
+    // 旧代理商的 API key 不能带到新代理商
+    options.api_key = load_codex_provider(&target)
+        .ok()
+        .and_then(|preset| codex_provider_overrides(&preset).ok())
+        .and_then(|(_, key)| key);
+
+    log::info!(
+        "[Codex Failover] {} failed before streaming ({}), switching to {}",
+        failed,
+        reason.as_str(),
+        target
+    );
+    let payload = serde_json::json!({
+        "session_id": session_id,
+        "from_provider": failed,
+        "to_provider": target,
+        "reason": reason,
+        "attempt": tried.len(),
+        "config_rewritten": config_rewritten,
+    });
+    if let Err(e) = app.emit(&format!("codex-failover:{}", session_id), &payload) {
+        log::error!("Failed to emit codex-failover (session-specific): {}", e);
+    }
+    if let Err(e) = app.emit("codex-failover", &payload) {
+        log::error!("Failed to emit codex-failover (global): {}", e);
+    }
+    Some(target)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the Codex provider fallback chain
+#[tauri::command]
+pub async fn get_codex_failover_config() -> Result<CodexFailoverConfig, AnyCodeError> {
+    Ok(load_failover_config())
+}
+
+/// Save the Codex provider fallback chain
+#[tauri::command]
+pub async fn set_codex_failover_config(config: CodexFailoverConfig) -> Result<(), AnyCodeError> {
+    for (i, id) in config.chain.iter().enumerate() {
+        if config.chain[..i].contains(id) {
+            return Err(AnyCodeError::Provider(format!("备用链中重复的代理商: {}", id)));
+        }
+        load_codex_provider(id).map_err(AnyCodeError::Config)?;
+    }
+
+    let path = get_codex_failover_path().map_err(AnyCodeError::Io)?;
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize failover config: {}", e)))?;
+    fs::write(&path, content).map_err(|e| AnyCodeError::Io(format!("Failed to write failover.json: {}", e)))?;
+    log::info!("[Codex Failover] Saved chain: {:?} (enabled: {})", config.chain, config.enabled);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_next_untried_provider_in_chain() {
+        let chain: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+
+        assert_eq!(next_provider(&chain, "a", &["a".to_string()]), Some("b".to_string()));
+        // 从链中位置之后开始，循环回到开头
+        assert_eq!(next_provider(&chain, "c", &["c".to_string()]), Some("a".to_string()));
+        // 不在链中的代理商（如官方）从链首开始
+        assert_eq!(next_provider(&chain, "official", &["official".to_string()]), Some("a".to_string()));
+        let tried: Vec<String> = ["b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(next_provider(&chain, "b", &tried), Some("a".to_string()));
+        let all: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(next_provider(&chain, "a", &all), None);
+
+        assert!(is_failover_error(ErrorCategory::RateLimit));
+        assert!(!is_failover_error(ErrorCategory::Server));
+        let config: CodexFailoverConfig = serde_json::from_str(r#"{"enabled":true,"chain":["a"]}"#).unwrap();
+        assert!(config.is_active());
+        assert_eq!(config.start_timeout_secs, 20);
+    }
+}
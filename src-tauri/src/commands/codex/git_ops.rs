@@ -8,19 +8,21 @@
  * - Session truncation and revert operations
  */
 
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::fs;
 use chrono::Utc;
 
 // Import simple_git for rewind operations
 use super::super::simple_git;
 // Import rewind helpers/types shared with Claude
-use super::super::prompt_tracker::{RewindMode, RewindCapabilities, PromptRecord as ClaudePromptRecord, load_execution_config};
+use super::super::prompt_tracker::{RewindMode, RewindCapabilities, RewindStrategy, PromptRecord as ClaudePromptRecord, load_execution_config};
 // Import WSL utilities
 use super::super::wsl_utils;
 // Import session helpers
-use super::session::find_session_file;
+use super::session::find_session_file_in_all_dirs;
 
 // Align Codex prompt record type with Claude prompt tracker representation
 pub type PromptRecord = ClaudePromptRecord;
@@ -48,6 +50,38 @@ pub struct CodexPromptGitRecord {
     pub commit_before: String,
     pub commit_after: Option<String>,
     pub timestamp: String,
+    /// Working tree hash (including uncommitted files) when the prompt was sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree_before: Option<String>,
+    /// Working tree hash when the prompt completed, before the auto-commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree_after: Option<String>,
+    /// The prompt's changes are mixed with manual edits, so commit boundaries
+    /// don't reflect what Codex changed
+    #[serde(default)]
+    pub mixed: bool,
+    /// Files changed outside of Codex (uncommitted before the prompt or edited during it)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_files: Vec<String>,
+    /// File snapshot taken before the prompt, used instead of commits when the project isn't a git repo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<String>,
+}
+
+/// Files changed by someone other than Codex around a prompt
+///
+/// `dirty_before`: uncommitted changes when the prompt was sent (they end up in the auto-commit).
+/// `changed`: files that changed while the prompt ran; those not recorded by the change tracker
+/// were edited manually.
+fn detect_external_changes(dirty_before: &[String], changed: &[String], agent_files: &[String]) -> Vec<String> {
+    let mut external: Vec<String> = dirty_before
+        .iter()
+        .chain(changed.iter().filter(|path| !agent_files.contains(path)))
+        .cloned()
+        .collect();
+    external.sort();
+    external.dedup();
+    external
 }
 
 /// Collection of Git records for a Codex session
@@ -79,45 +113,16 @@ pub fn get_codex_git_records_dir() -> Result<PathBuf, String> {
     Ok(records_dir)
 }
 
-/// Get the Codex sessions directory
-/// Get the Codex sessions directory
-/// On Windows with WSL mode enabled, returns the WSL UNC path
-pub fn get_codex_sessions_dir() -> Result<PathBuf, String> {
-    log::debug!("[get_codex_sessions_dir] Getting Codex sessions directory");
-    
-    // Check for WSL mode on Windows
-    #[cfg(target_os = "windows")]
-    {
-        let wsl_config = wsl_utils::get_wsl_config();
-        log::debug!("[get_codex_sessions_dir] WSL config: enabled={}", wsl_config.enabled);
-        
-        if wsl_config.enabled {
-            if let Some(sessions_dir) = wsl_utils::get_wsl_codex_sessions_dir() {
-                log::info!("[get_codex_sessions_dir] Using WSL sessions directory: {:?}", sessions_dir);
-                log::debug!("[get_codex_sessions_dir] Directory exists: {}", sessions_dir.exists());
-                return Ok(sessions_dir);
-            }
-        }
-    }
-
-    // Native mode: use local home directory
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Failed to get home directory".to_string())?;
-
-    let sessions_dir = home_dir.join(".codex").join("sessions");
-    log::info!("[get_codex_sessions_dir] Using native sessions directory: {:?}", sessions_dir);
-    log::debug!("[get_codex_sessions_dir] Directory exists: {}", sessions_dir.exists());
-    
-    Ok(sessions_dir)
-}
-
 // ============================================================================
 // Git Records CRUD Operations
 // ============================================================================
 
 /// Load Git records for a Codex session
 pub fn load_codex_git_records(session_id: &str) -> Result<CodexGitRecords, String> {
-    let records_dir = get_codex_git_records_dir()?;
+    load_codex_git_records_in(&get_codex_git_records_dir()?, session_id)
+}
+
+fn load_codex_git_records_in(records_dir: &Path, session_id: &str) -> Result<CodexGitRecords, String> {
     let records_file = records_dir.join(format!("{}.json", session_id));
 
     if !records_file.exists() {
@@ -137,7 +142,10 @@ pub fn load_codex_git_records(session_id: &str) -> Result<CodexGitRecords, Strin
 
 /// Save Git records for a Codex session
 pub fn save_codex_git_records(session_id: &str, records: &CodexGitRecords) -> Result<(), String> {
-    let records_dir = get_codex_git_records_dir()?;
+    save_codex_git_records_in(&get_codex_git_records_dir()?, session_id, records)
+}
+
+fn save_codex_git_records_in(records_dir: &Path, session_id: &str, records: &CodexGitRecords) -> Result<(), String> {
     let records_file = records_dir.join(format!("{}.json", session_id));
 
     let content = serde_json::to_string_pretty(records)
@@ -163,6 +171,30 @@ pub fn truncate_codex_git_records(session_id: &str, prompt_index: usize) -> Resu
     Ok(())
 }
 
+/// Snapshot IDs still referenced by git records, skipping the given sessions
+pub fn referenced_snapshot_ids(excluded_sessions: &HashSet<String>) -> Result<HashSet<String>, String> {
+    let records_dir = get_codex_git_records_dir()?;
+    let entries = fs::read_dir(&records_dir)
+        .map_err(|e| format!("Failed to read git records directory: {}", e))?;
+
+    let mut ids = HashSet::new();
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read git records: {}", e))?;
+        // An unreadable record file would orphan its snapshots, so refuse to guess
+        let records: CodexGitRecords = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse git records {}: {}", path.display(), e))?;
+        if excluded_sessions.contains(&records.session_id) {
+            continue;
+        }
+        ids.extend(records.records.into_iter().filter_map(|r| r.snapshot_id));
+    }
+    Ok(ids)
+}
+
 // ============================================================================
 // Prompt Extraction
 // ============================================================================
@@ -171,19 +203,13 @@ pub fn truncate_codex_git_records(session_id: &str, prompt_index: usize) -> Resu
 /// Extract all user prompts from a Codex session JSONL
 /// This mirrors Claude prompt extraction so indices stay consistent
 pub fn extract_codex_prompts(session_id: &str) -> Result<Vec<PromptRecord>, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    
     log::info!(
         "[extract_codex_prompts] Extracting prompts for session: {}",
         session_id
     );
-    log::info!(
-        "[extract_codex_prompts] Sessions directory: {:?}",
-        sessions_dir
-    );
 
-    // 使用改进后的 find_session_file,它现在返回 Result
-    let session_file = find_session_file(&sessions_dir, session_id)?;
+    // 在所有会话目录中查找（含项目级 WSL 发行版）
+    let session_file = find_session_file_in_all_dirs(session_id)?;
 
     log::info!("[extract_codex_prompts] Reading session file: {:?}", session_file);
 
@@ -285,8 +311,8 @@ pub fn extract_codex_prompts(session_id: &str) -> Result<Vec<PromptRecord>, Stri
 
 /// Get prompt list for Codex sessions (for revert picker)
 #[tauri::command]
-pub async fn get_codex_prompt_list(session_id: String) -> Result<Vec<PromptRecord>, String> {
-    extract_codex_prompts(&session_id)
+pub async fn get_codex_prompt_list(session_id: String) -> Result<Vec<PromptRecord>, AnyCodeError> {
+    extract_codex_prompts(&session_id).map_err(AnyCodeError::Io)
 }
 
 // ============================================================================
@@ -298,7 +324,7 @@ pub async fn get_codex_prompt_list(session_id: String) -> Result<Vec<PromptRecor
 pub async fn check_codex_rewind_capabilities(
     session_id: String,
     prompt_index: usize,
-) -> Result<RewindCapabilities, String> {
+) -> Result<RewindCapabilities, AnyCodeError> {
     log::info!(
         "[Codex Rewind] Checking capabilities for session {} prompt #{}",
         session_id,
@@ -307,14 +333,14 @@ pub async fn check_codex_rewind_capabilities(
 
     // Respect global execution config for git operations
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
     let git_operations_disabled = execution_config.disable_rewind_git_operations;
 
     // Extract prompts to validate index and source
-    let prompts = extract_codex_prompts(&session_id)?;
+    let prompts = extract_codex_prompts(&session_id).map_err(AnyCodeError::Io)?;
     let prompt = prompts
         .get(prompt_index)
-        .ok_or_else(|| format!("Prompt #{} not found", prompt_index))?;
+        .ok_or_else(|| AnyCodeError::NotFound(format!("Prompt #{} not found", prompt_index)))?;
 
     if git_operations_disabled {
         return Ok(RewindCapabilities {
@@ -323,28 +349,47 @@ pub async fn check_codex_rewind_capabilities(
             both: false,
             warning: Some("Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string()),
             source: prompt.source.clone(),
+            mixed: false,
+            strategy: None,
         });
     }
 
     // Look up git record for this prompt index
-    let git_records = load_codex_git_records(&session_id)?;
+    let git_records = load_codex_git_records(&session_id).map_err(AnyCodeError::Io)?;
     let git_record = git_records
         .records
         .iter()
         .find(|r| r.prompt_index == prompt_index);
 
     if let Some(record) = git_record {
-        let has_valid_commit = !record.commit_before.is_empty();
+        let strategy = if !record.commit_before.is_empty() {
+            Some(RewindStrategy::Git)
+        } else if record.snapshot_id.is_some() {
+            Some(RewindStrategy::Snapshot)
+        } else {
+            None
+        };
+        let mixed_records: Vec<&CodexPromptGitRecord> = git_records
+            .records
+            .iter()
+            .filter(|r| r.prompt_index >= prompt_index && r.mixed)
+            .collect();
+        let mixed = strategy == Some(RewindStrategy::Git) && !mixed_records.is_empty();
+        let warning = if strategy.is_none() {
+            Some("此提示词没有关联的 Git 记录或文件快照，只能删除对话历史。".to_string())
+        } else if mixed {
+            Some(mixed_warning(&mixed_records))
+        } else {
+            None
+        };
         Ok(RewindCapabilities {
             conversation: true,
-            code: has_valid_commit,
-            both: has_valid_commit,
-            warning: if has_valid_commit {
-                None
-            } else {
-                Some("此提示词没有关联的 Git 记录，只能删除对话历史。".to_string())
-            },
+            code: strategy.is_some(),
+            both: strategy.is_some(),
+            warning,
             source: "project".to_string(),
+            mixed,
+            strategy,
         })
     } else {
         Ok(RewindCapabilities {
@@ -355,10 +400,39 @@ pub async fn check_codex_rewind_capabilities(
                 "此提示词没有关联的 Git 记录（可能来自 CLI），只能删除对话历史。".to_string(),
             ),
             source: prompt.source.clone(),
+            mixed: false,
+            strategy: None,
         })
     }
 }
 
+/// Warning for prompts whose commits also contain manual edits
+fn mixed_warning(records: &[&CodexPromptGitRecord]) -> String {
+    let prompts: Vec<String> = records.iter().map(|r| format!("#{}", r.prompt_index + 1)).collect();
+    let mut files: Vec<&str> = records
+        .iter()
+        .flat_map(|r| r.external_files.iter().map(String::as_str))
+        .collect();
+    files.sort_unstable();
+    files.dedup();
+
+    let mut warning = format!(
+        "提示词 {} 执行期间检测到手动修改，按提交回滚会同时丢弃这些修改。",
+        prompts.join("、")
+    );
+    if !files.is_empty() {
+        let shown: Vec<&str> = files.iter().take(5).copied().collect();
+        let more = if files.len() > shown.len() {
+            format!(" 等 {} 个文件", files.len())
+        } else {
+            String::new()
+        };
+        warning.push_str(&format!("涉及文件：{}{}。", shown.join(", "), more));
+    }
+    warning.push_str("可以选择只撤销 Codex 的修改（基于变更记录，保留手动修改）。");
+    warning
+}
+
 // ============================================================================
 // Session Truncation
 // ============================================================================
@@ -366,8 +440,7 @@ pub async fn check_codex_rewind_capabilities(
 /// Get prompt text from Codex session file
 #[allow(dead_code)]
 pub fn get_codex_prompt_text(session_id: &str, prompt_index: usize) -> Result<String, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    let session_file = find_session_file(&sessions_dir, session_id)?;
+    let session_file = find_session_file_in_all_dirs(session_id)?;
 
     use std::io::{BufRead, BufReader};
     let file = fs::File::open(&session_file)
@@ -413,10 +486,19 @@ pub fn get_codex_prompt_text(session_id: &str, prompt_index: usize) -> Result<St
 
 /// Truncate Codex session file to before a specific prompt
 pub fn truncate_codex_session_to_prompt(session_id: &str, prompt_index: usize) -> Result<(), String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    let session_file = find_session_file(&sessions_dir, session_id)?;
+    let session_file = find_session_file_in_all_dirs(session_id)?;
+    truncate_session_file(&session_file, prompt_index)?;
 
-    let content = fs::read_to_string(&session_file)
+    // 远程模式：截断的是本地镜像，需写回远程主机
+    if let Some(remote) = wsl_utils::get_codex_config().active_remote() {
+        super::super::ssh_remote::upload_session_file(remote, &session_file)?;
+    }
+
+    Ok(())
+}
+
+fn truncate_session_file(session_file: &Path, prompt_index: usize) -> Result<(), String> {
+    let content = fs::read_to_string(session_file)
         .map_err(|e| format!("Failed to read session file: {}", e))?;
 
     let lines: Vec<&str> = content.lines().collect();
@@ -485,7 +567,7 @@ pub fn truncate_codex_session_to_prompt(session_id: &str, prompt_index: usize) -
         truncated_lines.join("\n") + "\n"
     };
 
-    fs::write(&session_file, new_content)
+    fs::write(session_file, new_content)
         .map_err(|e| format!("Failed to write truncated session: {}", e))?;
 
     log::info!("[Codex Rewind] Truncated session: kept {} lines, deleted {} lines",
@@ -494,6 +576,84 @@ pub fn truncate_codex_session_to_prompt(session_id: &str, prompt_index: usize) -
     Ok(())
 }
 
+/// Fork a Codex session: copy its history before `prompt_index` into a new session file
+/// Git records and recorded file changes up to the fork point are copied as well.
+/// Returns the new session ID.
+pub fn fork_codex_session(session_id: &str, prompt_index: usize) -> Result<String, String> {
+    let session_file = find_session_file_in_all_dirs(session_id)?;
+    let mut conn = super::change_store::open_change_db()?;
+    let (new_session_id, new_file) =
+        fork_session_file(&session_file, &get_codex_git_records_dir()?, &mut conn, session_id, prompt_index)?;
+
+    // 远程模式：分叉出的是本地镜像，需写回远程主机
+    if let Some(remote) = wsl_utils::get_codex_config().active_remote() {
+        super::super::ssh_remote::upload_session_file(remote, &new_file)?;
+    }
+
+    log::info!("[Codex Fork] Forked session {} at prompt #{} -> {}", session_id, prompt_index, new_session_id);
+    Ok(new_session_id)
+}
+
+/// Copies the session file, git records and file changes; returns the new session ID and file
+fn fork_session_file(
+    session_file: &Path,
+    records_dir: &Path,
+    conn: &mut rusqlite::Connection,
+    session_id: &str,
+    prompt_index: usize,
+) -> Result<(String, PathBuf), String> {
+    let content = fs::read_to_string(session_file)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let new_session_id = uuid::Uuid::new_v4().to_string();
+    let new_content: String = content
+        .lines()
+        .map(|line| match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(mut event) if event["type"].as_str() == Some("session_meta") => {
+                event["payload"]["id"] = serde_json::Value::String(new_session_id.clone());
+                event.to_string()
+            }
+            _ => line.to_string(),
+        })
+        .map(|line| line + "\n")
+        .collect();
+
+    // Session files are looked up by the ID in their name (rollout-<timestamp>-<id>.jsonl)
+    let file_name = session_file
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| n.contains(session_id))
+        .map(|n| n.replace(session_id, &new_session_id))
+        .unwrap_or_else(|| {
+            format!("rollout-{}-{}.jsonl", Utc::now().format("%Y-%m-%dT%H-%M-%S"), new_session_id)
+        });
+    let new_file = session_file.with_file_name(file_name);
+    fs::write(&new_file, new_content)
+        .map_err(|e| format!("Failed to write forked session: {}", e))?;
+    if let Err(e) = truncate_session_file(&new_file, prompt_index) {
+        let _ = fs::remove_file(&new_file);
+        return Err(e);
+    }
+
+    let mut git_records = load_codex_git_records_in(records_dir, session_id)?;
+    if !git_records.records.is_empty() {
+        git_records.session_id = new_session_id.clone();
+        git_records.records.retain(|r| r.prompt_index <= prompt_index);
+        save_codex_git_records_in(records_dir, &new_session_id, &git_records)?;
+    }
+
+    if let Some(mut changes) = super::change_store::load_session(conn, session_id)? {
+        changes.session_id = new_session_id.clone();
+        changes.changes.retain(|c| (c.prompt_index as usize) < prompt_index);
+        for change in &mut changes.changes {
+            change.session_id = new_session_id.clone();
+        }
+        super::change_store::replace_session(conn, &changes)?;
+    }
+
+    Ok((new_session_id, new_file))
+}
+
 // ============================================================================
 // Prompt Recording (for rewind tracking)
 // ============================================================================
@@ -504,7 +664,14 @@ pub async fn record_codex_prompt_sent(
     session_id: String,
     project_path: String,
     _prompt_text: String,
-) -> Result<usize, String> {
+) -> Result<usize, AnyCodeError> {
+    // Snapshotting the working tree hashes every file, keep it off the async runtime
+    tokio::task::spawn_blocking(move || record_prompt_sent(session_id, project_path))
+        .await
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to record prompt: {}", e)))?
+}
+
+fn record_prompt_sent(session_id: String, project_path: String) -> Result<usize, AnyCodeError> {
     log::info!("[Codex Record] Recording prompt sent for session: {}", session_id);
 
     // Derive prompt_index from the current session JSONL prompt count, so indices stay aligned with
@@ -550,7 +717,7 @@ pub async fn record_codex_prompt_sent(
 
     // Check if Git operations are disabled in config
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
 
     if execution_config.disable_rewind_git_operations {
         log::info!("[Codex Record] Git operations disabled, skipping git record");
@@ -565,7 +732,7 @@ pub async fn record_codex_prompt_sent(
         }
 
         // Fallback to legacy behavior when session file is unavailable.
-        let git_records = load_codex_git_records(&session_id)?;
+        let git_records = load_codex_git_records(&session_id).map_err(AnyCodeError::Io)?;
         let prompt_index = git_records.records.len();
         log::info!(
             "[Codex Record] Returning prompt index #{} (no git record, legacy fallback)",
@@ -574,16 +741,35 @@ pub async fn record_codex_prompt_sent(
         return Ok(prompt_index);
     }
 
+    // Projects without git are rewound from file snapshots instead of initializing a repository
+    if !simple_git::is_git_repo(&project_path_for_git) {
+        return record_codex_prompt_snapshot(
+            &session_id,
+            &project_path,
+            &project_path_for_git,
+            prompt_index_from_session,
+        );
+    }
+
     // Ensure Git repository is initialized
     simple_git::ensure_git_repo(&project_path_for_git)
-        .map_err(|e| format!("Failed to ensure Git repo: {}", e))?;
+        .map_err(|e| AnyCodeError::Git(format!("Failed to ensure Git repo: {}", e)))?;
 
     // Get current commit (state before prompt execution)
     let commit_before = simple_git::git_current_commit(&project_path_for_git)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+        .map_err(|e| AnyCodeError::Git(format!("Failed to get current commit: {}", e)))?;
+
+    // Working tree snapshot, used to tell manual edits apart from Codex's changes on completion
+    let tree_before = match simple_git::git_worktree_tree(&project_path_for_git) {
+        Ok(tree) => Some(tree),
+        Err(e) => {
+            log::warn!("[Codex Record] Failed to snapshot working tree: {}", e);
+            None
+        }
+    };
 
     // Load existing records
-    let mut git_records = load_codex_git_records(&session_id)?;
+    let mut git_records = load_codex_git_records(&session_id).map_err(AnyCodeError::Io)?;
 
     // Update project path if needed
     if git_records.project_path.is_empty() {
@@ -599,6 +785,11 @@ pub async fn record_codex_prompt_sent(
         commit_before: commit_before.clone(),
         commit_after: None,
         timestamp: Utc::now().to_rfc3339(),
+        tree_before,
+        tree_after: None,
+        mixed: false,
+        external_files: Vec::new(),
+        snapshot_id: None,
     };
 
     // Avoid duplicates if the command is triggered twice for the same prompt index.
@@ -607,13 +798,11 @@ pub async fn record_codex_prompt_sent(
         .iter_mut()
         .find(|r| r.prompt_index == prompt_index)
     {
-        existing.commit_before = record.commit_before;
-        existing.commit_after = None;
-        existing.timestamp = record.timestamp;
+        *existing = record;
     } else {
         git_records.records.push(record);
     }
-    save_codex_git_records(&session_id, &git_records)?;
+    save_codex_git_records(&session_id, &git_records).map_err(AnyCodeError::Io)?;
 
     log::info!("[Codex Record] Recorded prompt #{} with commit_before: {}",
         prompt_index, &commit_before[..8.min(commit_before.len())]);
@@ -621,13 +810,102 @@ pub async fn record_codex_prompt_sent(
     Ok(prompt_index)
 }
 
+/// Record a prompt in a project without git: snapshot the working tree before execution
+fn record_codex_prompt_snapshot(
+    session_id: &str,
+    project_path: &str,
+    project_path_for_snapshot: &str,
+    prompt_index_from_session: Option<usize>,
+) -> Result<usize, AnyCodeError> {
+    let mut git_records = load_codex_git_records(session_id).map_err(AnyCodeError::Io)?;
+    if git_records.project_path.is_empty() {
+        git_records.project_path = project_path.to_string();
+    }
+    let prompt_index = prompt_index_from_session.unwrap_or(git_records.records.len());
+
+    // Reuse hashes of unchanged files from the latest earlier snapshot
+    let previous = git_records
+        .records
+        .iter()
+        .filter(|r| r.prompt_index < prompt_index)
+        .max_by_key(|r| r.prompt_index)
+        .and_then(|r| r.snapshot_id.clone());
+    let snapshot_id = match super::snapshot_store::create_snapshot(project_path_for_snapshot, previous.as_deref()) {
+        Ok(id) => Some(id),
+        Err(e) => {
+            log::warn!("[Codex Record] Failed to snapshot project: {}", e);
+            None
+        }
+    };
+
+    let record = CodexPromptGitRecord {
+        prompt_index,
+        commit_before: String::new(),
+        commit_after: None,
+        timestamp: Utc::now().to_rfc3339(),
+        tree_before: None,
+        tree_after: None,
+        mixed: false,
+        external_files: Vec::new(),
+        snapshot_id,
+    };
+    git_records.records.retain(|r| r.prompt_index != prompt_index);
+    git_records.records.push(record);
+    save_codex_git_records(session_id, &git_records).map_err(AnyCodeError::Io)?;
+
+    log::info!("[Codex Record] Recorded prompt #{} with a file snapshot (no git repository)", prompt_index);
+    Ok(prompt_index)
+}
+
+/// Fill `tree_after` and flag the prompt as mixed when manual edits are interleaved with Codex's changes
+fn detect_mixed_prompt(record: &mut CodexPromptGitRecord, session_id: &str, project_path: &str) {
+    let tree_after = match simple_git::git_worktree_tree(project_path) {
+        Ok(tree) => tree,
+        Err(e) => {
+            log::warn!("[Codex Record] Failed to snapshot working tree: {}", e);
+            return;
+        }
+    };
+    record.tree_after = Some(tree_after.clone());
+    let Some(tree_before) = record.tree_before.clone() else {
+        return;
+    };
+
+    // Commits made while the prompt ran (e.g. by the user) also break the commit boundaries
+    let head_moved = simple_git::git_current_commit(project_path)
+        .map(|head| head != record.commit_before)
+        .unwrap_or(false);
+    let dirty_before = simple_git::git_changed_paths(project_path, &record.commit_before, &tree_before);
+    let changed = simple_git::git_changed_paths(project_path, &tree_before, &tree_after);
+    let (dirty_before, changed) = match (dirty_before, changed) {
+        (Ok(dirty_before), Ok(changed)) => (dirty_before, changed),
+        (Err(e), _) | (_, Err(e)) => {
+            log::warn!("[Codex Record] Failed to diff working tree snapshots: {}", e);
+            return;
+        }
+    };
+    let agent_files = super::change_tracker::prompt_changed_files(session_id, record.prompt_index as i32);
+
+    record.external_files = detect_external_changes(&dirty_before, &changed, &agent_files);
+    record.mixed = head_moved || !record.external_files.is_empty();
+    if record.mixed {
+        log::info!(
+            "[Codex Record] Prompt #{} is mixed with manual changes (HEAD moved: {}, files: {:?})",
+            record.prompt_index,
+            head_moved,
+            record.external_files
+        );
+    }
+}
+
 /// Record a Codex prompt completion (called after AI response)
 #[tauri::command]
 pub async fn record_codex_prompt_completed(
+    app_handle: tauri::AppHandle,
     session_id: String,
     project_path: String,
     prompt_index: usize,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     log::info!("[Codex Record] Recording prompt #{} completed for session: {}",
         prompt_index, session_id);
 
@@ -653,15 +931,37 @@ pub async fn record_codex_prompt_completed(
 
     // Check if Git operations are disabled in config
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
 
     if execution_config.disable_rewind_git_operations {
         log::info!("[Codex Record] Git operations disabled, skipping git commit and record update");
         return Ok(());
     }
 
+    // Compare working tree snapshots before the auto-commit folds everything into one commit
+    let (sid, git_path) = (session_id.clone(), project_path_for_git.clone());
+    let uses_snapshot = tokio::task::spawn_blocking(move || -> Result<bool, AnyCodeError> {
+        let mut git_records = load_codex_git_records(&sid).map_err(AnyCodeError::Io)?;
+        if let Some(record) = git_records.records.iter_mut().find(|r| r.prompt_index == prompt_index) {
+            if record.snapshot_id.is_some() {
+                return Ok(true);
+            }
+            detect_mixed_prompt(record, &sid, &git_path);
+            save_codex_git_records(&sid, &git_records).map_err(AnyCodeError::Io)?;
+        }
+        Ok(false)
+    })
+    .await
+    .map_err(|e| AnyCodeError::Internal(format!("Failed to snapshot working tree: {}", e)))??;
+    if uses_snapshot {
+        log::debug!("[Codex Record] Prompt #{} uses a file snapshot, nothing to commit", prompt_index);
+        return Ok(());
+    }
+
     // Auto-commit any changes made by AI
-    let commit_message = format!("[Codex] After prompt #{}", prompt_index);
+    let commit_message =
+        super::super::commit_message::auto_commit_message(&app_handle, &project_path_for_git, "codex", prompt_index)
+            .await;
     match simple_git::git_commit_changes(&project_path_for_git, &commit_message) {
         Ok(true) => {
             log::info!("[Codex Record] Auto-committed changes after prompt #{}", prompt_index);
@@ -677,14 +977,14 @@ pub async fn record_codex_prompt_completed(
 
     // Get current commit (state after AI completion)
     let commit_after = simple_git::git_current_commit(&project_path_for_git)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+        .map_err(|e| AnyCodeError::Git(format!("Failed to get current commit: {}", e)))?;
 
     // Update the record
-    let mut git_records = load_codex_git_records(&session_id)?;
+    let mut git_records = load_codex_git_records(&session_id).map_err(AnyCodeError::Io)?;
 
     if let Some(record) = git_records.records.iter_mut().find(|r| r.prompt_index == prompt_index) {
         record.commit_after = Some(commit_after.clone());
-        save_codex_git_records(&session_id, &git_records)?;
+        save_codex_git_records(&session_id, &git_records).map_err(AnyCodeError::Io)?;
 
         log::info!("[Codex Record] Updated prompt #{} with commit_after: {}",
             prompt_index, &commit_after[..8.min(commit_after.len())]);
@@ -699,6 +999,29 @@ pub async fn record_codex_prompt_completed(
 // Revert Operations
 // ============================================================================
 
+/// Undo only the file changes Codex made from `prompt_index` onwards (based on change_tracker
+/// records), keeping manual edits. Used for prompts marked as mixed.
+fn revert_codex_changes_only(session_id: &str, project_path: &str, prompt_index: usize) -> Result<(), String> {
+    let report = super::change_tracker::revert_agent_changes(session_id, project_path, prompt_index as i32)?;
+    if !report.skipped.is_empty() {
+        log::warn!(
+            "[Codex Rewind] Skipped files without a full snapshot: {:?}",
+            report.skipped
+        );
+    }
+    log::info!("[Codex Rewind] Partially reverted {} files to prompt #{}", report.reverted.len(), prompt_index);
+    Ok(())
+}
+
+/// Restore a file snapshot; like creating one, this walks the whole tree, so keep it off the async runtime
+async fn restore_snapshot_blocking(snapshot_id: String) -> Result<(), AnyCodeError> {
+    tokio::task::spawn_blocking(move || super::snapshot_store::restore_snapshot(&snapshot_id))
+        .await
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to restore snapshot: {}", e)))?
+        .map(|_| ())
+        .map_err(AnyCodeError::Io)
+}
+
 /// Revert Codex session to a specific prompt
 #[tauri::command]
 pub async fn revert_codex_to_prompt(
@@ -706,13 +1029,15 @@ pub async fn revert_codex_to_prompt(
     project_path: String,
     prompt_index: usize,
     mode: RewindMode,
-) -> Result<String, String> {
-    log::info!("[Codex Rewind] Reverting session {} to prompt #{} with mode: {:?}",
-        session_id, prompt_index, mode);
+    partial: Option<bool>,
+) -> Result<String, AnyCodeError> {
+    let partial = partial.unwrap_or(false);
+    log::info!("[Codex Rewind] Reverting session {} to prompt #{} with mode: {:?} (partial: {})",
+        session_id, prompt_index, mode, partial);
 
     // Load execution config to check if Git operations are disabled
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
 
     let git_operations_disabled = execution_config.disable_rewind_git_operations;
 
@@ -722,23 +1047,23 @@ pub async fn revert_codex_to_prompt(
 
     // Extract prompts to validate index and retrieve text
     log::info!("[Codex Rewind] Extracting prompts from session...");
-    let prompts = extract_codex_prompts(&session_id)?;
+    let prompts = extract_codex_prompts(&session_id).map_err(AnyCodeError::Io)?;
     log::info!("[Codex Rewind] Found {} prompts in session", prompts.len());
 
     if prompts.is_empty() {
-        return Err(format!(
+        return Err(AnyCodeError::NotFound(format!(
             "No valid prompts found in session {}. The session may be empty or corrupted.",
             session_id
-        ));
+        )));
     }
 
     if prompt_index >= prompts.len() {
-        return Err(format!(
+        return Err(AnyCodeError::Validation(format!(
             "Prompt index {} out of range. Session only has {} prompts (indices 0-{}).",
             prompt_index,
             prompts.len(),
             prompts.len() - 1
-        ));
+        )));
     }
 
     let prompt = &prompts[prompt_index];
@@ -746,22 +1071,22 @@ pub async fn revert_codex_to_prompt(
         prompt.text.chars().take(50).collect::<String>());
 
     // Load Git records
-    let git_records = load_codex_git_records(&session_id)?;
+    let git_records = load_codex_git_records(&session_id).map_err(AnyCodeError::Io)?;
     let git_record = git_records.records.iter().find(|r| r.prompt_index == prompt_index);
 
     // Validate mode compatibility
     match mode {
         RewindMode::CodeOnly | RewindMode::Both => {
             if git_operations_disabled {
-                return Err(
-                    "无法回滚代码：Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".into()
-                );
+                return Err(AnyCodeError::Config(
+                    "无法回滚代码：Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string(),
+                ));
             }
-            if git_record.is_none() {
-                return Err(format!(
-                    "无法回滚代码：提示词 #{} 没有关联的 Git 记录",
+            if git_record.is_none_or(|r| r.commit_before.is_empty() && r.snapshot_id.is_none()) {
+                return Err(AnyCodeError::Git(format!(
+                    "无法回滚代码：提示词 #{} 没有关联的 Git 记录或文件快照",
                     prompt_index
-                ));
+                )));
             }
         }
         RewindMode::ConversationOnly => {}
@@ -773,11 +1098,11 @@ pub async fn revert_codex_to_prompt(
             log::info!("[Codex Rewind] Reverting conversation only");
 
             // Truncate session messages
-            truncate_codex_session_to_prompt(&session_id, prompt_index)?;
+            truncate_codex_session_to_prompt(&session_id, prompt_index).map_err(AnyCodeError::Io)?;
 
             // Truncate git records
             if !git_operations_disabled {
-                truncate_codex_git_records(&session_id, prompt_index)?;
+                truncate_codex_git_records(&session_id, prompt_index).map_err(AnyCodeError::Io)?;
             }
 
             // Truncate change records so history/files-changed don't include stale prompts.
@@ -798,16 +1123,22 @@ pub async fn revert_codex_to_prompt(
         RewindMode::CodeOnly => {
             log::info!("[Codex Rewind] Reverting code only");
 
-            let record = git_record.unwrap();
+            if partial {
+                revert_codex_changes_only(&session_id, &project_path, prompt_index).map_err(AnyCodeError::Io)?;
+            } else if let Some(snapshot_id) = git_record.and_then(|r| r.snapshot_id.as_deref()) {
+                restore_snapshot_blocking(snapshot_id.to_string()).await?;
+            } else {
+                let record = git_record.unwrap();
 
-            // Stash uncommitted changes
-            simple_git::git_stash_save(&project_path,
-                &format!("Auto-stash before Codex code revert to prompt #{}", prompt_index))
-                .map_err(|e| format!("Failed to stash changes: {}", e))?;
+                // Stash uncommitted changes
+                simple_git::git_stash_save(&project_path,
+                    &format!("Auto-stash before Codex code revert to prompt #{}", prompt_index))
+                    .map_err(|e| AnyCodeError::Git(format!("Failed to stash changes: {}", e)))?;
 
-            // Reset to commit before this prompt
-            simple_git::git_reset_hard(&project_path, &record.commit_before)
-                .map_err(|e| format!("Failed to reset code: {}", e))?;
+                // Reset to commit before this prompt
+                simple_git::git_reset_hard(&project_path, &record.commit_before)
+                    .map_err(|e| AnyCodeError::Git(format!("Failed to reset code: {}", e)))?;
+            }
 
             log::info!("[Codex Rewind] Successfully reverted code to prompt #{}", prompt_index);
         }
@@ -815,23 +1146,29 @@ pub async fn revert_codex_to_prompt(
         RewindMode::Both => {
             log::info!("[Codex Rewind] Reverting both conversation and code");
 
-            let record = git_record.unwrap();
+            if partial {
+                revert_codex_changes_only(&session_id, &project_path, prompt_index).map_err(AnyCodeError::Io)?;
+            } else if let Some(snapshot_id) = git_record.and_then(|r| r.snapshot_id.as_deref()) {
+                restore_snapshot_blocking(snapshot_id.to_string()).await?;
+            } else {
+                let record = git_record.unwrap();
 
-            // Stash uncommitted changes
-            simple_git::git_stash_save(&project_path,
-                &format!("Auto-stash before Codex full revert to prompt #{}", prompt_index))
-                .map_err(|e| format!("Failed to stash changes: {}", e))?;
+                // Stash uncommitted changes
+                simple_git::git_stash_save(&project_path,
+                    &format!("Auto-stash before Codex full revert to prompt #{}", prompt_index))
+                    .map_err(|e| AnyCodeError::Git(format!("Failed to stash changes: {}", e)))?;
 
-            // Reset code
-            simple_git::git_reset_hard(&project_path, &record.commit_before)
-                .map_err(|e| format!("Failed to reset code: {}", e))?;
+                // Reset code
+                simple_git::git_reset_hard(&project_path, &record.commit_before)
+                    .map_err(|e| AnyCodeError::Git(format!("Failed to reset code: {}", e)))?;
+            }
 
             // Truncate session
-            truncate_codex_session_to_prompt(&session_id, prompt_index)?;
+            truncate_codex_session_to_prompt(&session_id, prompt_index).map_err(AnyCodeError::Io)?;
 
             // Truncate git records
             if !git_operations_disabled {
-                truncate_codex_git_records(&session_id, prompt_index)?;
+                truncate_codex_git_records(&session_id, prompt_index).map_err(AnyCodeError::Io)?;
             }
 
             // Truncate change records so history/files-changed don't include stale prompts.
@@ -853,3 +1190,137 @@ pub async fn revert_codex_to_prompt(
     // Return the prompt text for restoring to input
     Ok(prompt.text.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::codex::change_tracker::{
+        ChangeSource, ChangeType, CodexChangeRecords, CodexFileChange,
+    };
+
+    #[test]
+    fn detects_external_changes() {
+        let paths = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let agent = paths(&["src/lib.rs"]);
+
+        assert!(detect_external_changes(&[], &paths(&["src/lib.rs"]), &agent).is_empty());
+        assert_eq!(
+            detect_external_changes(&paths(&["notes.md"]), &paths(&["src/lib.rs", "src/main.rs"]), &agent),
+            paths(&["notes.md", "src/main.rs"])
+        );
+        assert_eq!(
+            detect_external_changes(&paths(&["src/lib.rs"]), &paths(&["src/lib.rs"]), &agent),
+            paths(&["src/lib.rs"])
+        );
+    }
+
+    fn message(role: &str, text: &str) -> String {
+        let content_type = if role == "user" { "input_text" } else { "output_text" };
+        serde_json::json!({
+            "type": "response_item",
+            "payload": {"type": "message", "role": role, "content": [{"type": content_type, "text": text}]}
+        })
+        .to_string()
+    }
+
+    fn git_record(prompt_index: usize) -> CodexPromptGitRecord {
+        CodexPromptGitRecord {
+            prompt_index,
+            commit_before: format!("commit{}", prompt_index),
+            commit_after: None,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            tree_before: None,
+            tree_after: None,
+            mixed: false,
+            external_files: Vec::new(),
+            snapshot_id: None,
+        }
+    }
+
+    fn change(session_id: &str, prompt_index: i32) -> CodexFileChange {
+        CodexFileChange {
+            id: format!("change_{}", prompt_index),
+            session_id: session_id.to_string(),
+            prompt_index,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            file_path: "src/main.rs".to_string(),
+            change_type: ChangeType::Update,
+            source: ChangeSource::Tool,
+            old_content: Some("a".to_string()),
+            new_content: Some("b".to_string()),
+            unified_diff: None,
+            lines_added: Some(1),
+            lines_removed: Some(1),
+            tool_name: None,
+            tool_call_id: None,
+            command: None,
+        }
+    }
+
+    #[test]
+    fn fork_keeps_history_records_and_changes_up_to_the_fork_point() {
+        let dir = tempfile::tempdir().unwrap();
+        let records_dir = dir.path().join("git-records");
+        fs::create_dir_all(&records_dir).unwrap();
+        let session_id = "0a1b2c3d-source";
+        let session_file = dir.path().join(format!("rollout-2026-01-01T10-00-00-{}.jsonl", session_id));
+
+        let meta = serde_json::json!({"type": "session_meta", "payload": {"id": session_id}}).to_string();
+        let source: String = [
+            meta,
+            message("user", "first"),
+            message("assistant", "one"),
+            message("user", "second"),
+            message("assistant", "two"),
+            message("user", "third"),
+        ]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect();
+        fs::write(&session_file, &source).unwrap();
+        let records = CodexGitRecords {
+            session_id: session_id.to_string(),
+            project_path: "/home/dev/app".to_string(),
+            records: (0..3).map(git_record).collect(),
+        };
+        save_codex_git_records_in(&records_dir, session_id, &records).unwrap();
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        super::super::change_store::ensure_schema(&conn).unwrap();
+        let changes = CodexChangeRecords {
+            session_id: session_id.to_string(),
+            project_path: "/home/dev/app".to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            changes: (0..3).map(|i| change(session_id, i)).collect(),
+        };
+        super::super::change_store::replace_session(&mut conn, &changes).unwrap();
+
+        let (forked, forked_file) = fork_session_file(&session_file, &records_dir, &mut conn, session_id, 1).unwrap();
+
+        // 分叉点的提示词本身不在新会话中
+        assert!(forked_file.to_string_lossy().ends_with(&format!("{}.jsonl", forked)));
+        let events: Vec<serde_json::Value> = fs::read_to_string(&forked_file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["payload"]["id"], forked.as_str());
+        assert_eq!(events[1]["payload"]["content"][0]["text"], "first");
+
+        let forked_records = load_codex_git_records_in(&records_dir, &forked).unwrap();
+        assert_eq!(forked_records.session_id, forked);
+        let indices: Vec<usize> = forked_records.records.iter().map(|r| r.prompt_index).collect();
+        assert_eq!(indices, vec![0, 1]);
+
+        let forked_changes = super::super::change_store::load_session(&conn, &forked).unwrap().unwrap();
+        assert_eq!(forked_changes.changes.len(), 1);
+        assert_eq!(forked_changes.changes[0].session_id, forked);
+
+        // 原会话不受影响
+        assert_eq!(fs::read_to_string(&session_file).unwrap(), source);
+        assert_eq!(load_codex_git_records_in(&records_dir, session_id).unwrap().records.len(), 3);
+        let source_changes = super::super::change_store::load_session(&conn, session_id).unwrap().unwrap();
+        assert_eq!(source_changes.changes.len(), 3);
+    }
+}
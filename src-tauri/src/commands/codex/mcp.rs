@@ -4,13 +4,16 @@
 //! Supports the [mcp_servers.xxx] format used by Codex CLI.
 //! Supports both Windows native and WSL modes.
 
+use crate::error::AnyCodeError;
 use anyhow::{Context, Result};
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::commands::file_operations::write_atomic;
 
+use super::super::mcp::MCPServerExtras;
 #[cfg(target_os = "windows")]
 use super::super::wsl_utils::{get_wsl_codex_dir, get_wsl_config};
 
@@ -41,6 +44,9 @@ pub struct CodexMCPServer {
     /// Whether the server is disabled
     #[serde(default)]
     pub disabled: bool,
+    /// cwd / http_headers / enabled_tools / disabled_tools
+    #[serde(flatten, default)]
+    pub extras: MCPServerExtras,
 }
 
 /// Raw TOML structure for a single MCP server
@@ -58,6 +64,13 @@ struct RawMCPServerConfig {
     tool_timeout_sec: Option<u64>,
     #[serde(default)]
     disabled: bool,
+    cwd: Option<String>,
+    #[serde(default)]
+    http_headers: HashMap<String, String>,
+    #[serde(default)]
+    enabled_tools: Vec<String>,
+    #[serde(default)]
+    disabled_tools: Vec<String>,
 }
 
 /// Raw TOML structure for the mcp_servers section
@@ -102,6 +115,28 @@ pub fn get_codex_config_path() -> Result<PathBuf> {
     Ok(get_codex_config_dir()?.join("config.toml"))
 }
 
+/// Applies the same edit to the global config.toml and to those of WSL distros pinned
+/// by project settings, so MCP changes reach projects running in another distro
+///
+/// The global config's result is returned; failures in other distros are only logged.
+fn for_each_codex_config<F>(apply: F) -> Result<()>
+where
+    F: Fn(&Path) -> Result<()>,
+{
+    let primary = get_codex_config_path()?;
+    let result = apply(&primary);
+    for dir in super::config::get_project_codex_config_dirs() {
+        let path = dir.join("config.toml");
+        if path == primary {
+            continue;
+        }
+        if let Err(e) = apply(&path) {
+            warn!("[Codex MCP] Failed to update {:?}: {}", path, e);
+        }
+    }
+    result
+}
+
 /// Checks if currently using WSL mode
 #[cfg(target_os = "windows")]
 pub fn is_wsl_mode() -> bool {
@@ -157,6 +192,13 @@ pub fn parse_codex_mcp_from_string(content: &str) -> Result<Vec<CodexMCPServer>>
             startup_timeout_sec: raw_config.startup_timeout_sec,
             tool_timeout_sec: raw_config.tool_timeout_sec,
             disabled: raw_config.disabled,
+            extras: MCPServerExtras {
+                cwd: raw_config.cwd,
+                headers: raw_config.http_headers,
+                trust: None,
+                include_tools: raw_config.enabled_tools,
+                exclude_tools: raw_config.disabled_tools,
+            },
         };
         
         servers.push(server);
@@ -166,6 +208,28 @@ pub fn parse_codex_mcp_from_string(content: &str) -> Result<Vec<CodexMCPServer>>
     Ok(servers)
 }
 
+/// Writes the Codex-supported extended fields into a server table; empty values remove the key
+fn apply_extras_to_table(table: &mut toml::Table, extras: &MCPServerExtras) {
+    let strings = |values: &[String]| toml::Value::Array(values.iter().map(|s| toml::Value::String(s.clone())).collect());
+    let mut set = |key: &str, value: Option<toml::Value>| match value {
+        Some(value) => {
+            table.insert(key.to_string(), value);
+        }
+        None => {
+            table.remove(key);
+        }
+    };
+    set("cwd", extras.cwd.clone().map(toml::Value::String));
+    set(
+        "http_headers",
+        (!extras.headers.is_empty()).then(|| {
+            toml::Value::Table(extras.headers.iter().map(|(k, v)| (k.clone(), toml::Value::String(v.clone()))).collect())
+        }),
+    );
+    set("enabled_tools", (!extras.include_tools.is_empty()).then(|| strings(&extras.include_tools)));
+    set("disabled_tools", (!extras.exclude_tools.is_empty()).then(|| strings(&extras.exclude_tools)));
+}
+
 /// Converts CodexMCPServer to the unified MCPServer format used by the frontend
 pub fn to_unified_mcp_server(server: &CodexMCPServer) -> super::super::mcp::MCPServer {
     super::super::mcp::MCPServer {
@@ -177,23 +241,21 @@ pub fn to_unified_mcp_server(server: &CodexMCPServer) -> super::super::mcp::MCPS
         url: server.url.clone(),
         scope: "user".to_string(), // Codex config is always user-level
         is_active: !server.disabled,
-        status: super::super::mcp::ServerStatus {
-            running: false,
-            error: None,
-            last_checked: None,
-        },
+        status: super::super::mcp::ServerStatus::default(),
     }
 }
 
 /// Sets the enabled/disabled status for a Codex MCP server
 pub fn set_codex_mcp_enabled(server_name: &str, enabled: bool) -> Result<()> {
-    let config_path = get_codex_config_path()?;
-    
+    for_each_codex_config(|config_path| set_codex_mcp_enabled_in(config_path, server_name, enabled))
+}
+
+fn set_codex_mcp_enabled_in(config_path: &Path, server_name: &str, enabled: bool) -> Result<()> {
     if !config_path.exists() {
         return Err(anyhow::anyhow!("Codex config file not found"));
     }
     
-    let content = fs::read_to_string(&config_path)
+    let content = fs::read_to_string(config_path)
         .context("Failed to read Codex config file")?;
     
     // Parse as generic TOML to preserve other settings
@@ -215,7 +277,7 @@ pub fn set_codex_mcp_enabled(server_name: &str, enabled: bool) -> Result<()> {
                     // Write back to file
                     let new_content = toml::to_string_pretty(&config)
                         .context("Failed to serialize Codex config")?;
-                    fs::write(&config_path, new_content)
+                    write_atomic(config_path, new_content)
                         .context("Failed to write Codex config file")?;
                     
                     info!("[Codex MCP] Set server '{}' enabled={}", server_name, enabled);
@@ -230,18 +292,21 @@ pub fn set_codex_mcp_enabled(server_name: &str, enabled: bool) -> Result<()> {
 
 /// Adds a new MCP server to Codex config
 pub fn add_codex_mcp_server(server: &CodexMCPServer) -> Result<()> {
-    let config_path = get_codex_config_path()?;
-    let config_dir = get_codex_config_dir()?;
+    for_each_codex_config(|config_path| add_codex_mcp_server_in(config_path, server))
+}
+
+fn add_codex_mcp_server_in(config_path: &Path, server: &CodexMCPServer) -> Result<()> {
+    let config_dir = config_path.parent().context("Invalid Codex config path")?;
     
     // Ensure config directory exists
     if !config_dir.exists() {
-        fs::create_dir_all(&config_dir)
+        fs::create_dir_all(config_dir)
             .context("Failed to create Codex config directory")?;
     }
     
     // Read existing config or create new
     let mut config: toml::Table = if config_path.exists() {
-        let content = fs::read_to_string(&config_path)
+        let content = fs::read_to_string(config_path)
             .context("Failed to read Codex config file")?;
         toml::from_str(&content).unwrap_or_default()
     } else {
@@ -305,13 +370,15 @@ pub fn add_codex_mcp_server(server: &CodexMCPServer) -> Result<()> {
         server_table.insert("disabled".to_string(), toml::Value::Boolean(true));
     }
     
+    apply_extras_to_table(&mut server_table, &server.extras);
+    
     // Add server to mcp_servers
     mcp_servers.insert(server.name.clone(), toml::Value::Table(server_table));
     
     // Write back to file
     let new_content = toml::to_string_pretty(&config)
         .context("Failed to serialize Codex config")?;
-    fs::write(&config_path, new_content)
+    write_atomic(config_path, new_content)
         .context("Failed to write Codex config file")?;
     
     info!("[Codex MCP] Added server '{}'", server.name);
@@ -320,13 +387,15 @@ pub fn add_codex_mcp_server(server: &CodexMCPServer) -> Result<()> {
 
 /// Removes an MCP server from Codex config
 pub fn remove_codex_mcp_server(server_name: &str) -> Result<()> {
-    let config_path = get_codex_config_path()?;
-    
+    for_each_codex_config(|config_path| remove_codex_mcp_server_in(config_path, server_name))
+}
+
+fn remove_codex_mcp_server_in(config_path: &Path, server_name: &str) -> Result<()> {
     if !config_path.exists() {
         return Err(anyhow::anyhow!("Codex config file not found"));
     }
     
-    let content = fs::read_to_string(&config_path)
+    let content = fs::read_to_string(config_path)
         .context("Failed to read Codex config file")?;
     
     let mut config: toml::Table = toml::from_str(&content)
@@ -339,7 +408,7 @@ pub fn remove_codex_mcp_server(server_name: &str) -> Result<()> {
                 // Write back to file
                 let new_content = toml::to_string_pretty(&config)
                     .context("Failed to serialize Codex config")?;
-                fs::write(&config_path, new_content)
+                write_atomic(config_path, new_content)
                     .context("Failed to write Codex config file")?;
                 
                 info!("[Codex MCP] Removed server '{}'", server_name);
@@ -357,26 +426,26 @@ pub fn remove_codex_mcp_server(server_name: &str) -> Result<()> {
 
 /// Lists all MCP servers from Codex config
 #[tauri::command]
-pub async fn codex_mcp_list() -> Result<Vec<CodexMCPServer>, String> {
-    parse_codex_mcp_config().map_err(|e| e.to_string())
+pub async fn codex_mcp_list() -> Result<Vec<CodexMCPServer>, AnyCodeError> {
+    parse_codex_mcp_config().map_err(|e| AnyCodeError::Config(e.to_string()))
 }
 
 /// Sets enabled/disabled status for a Codex MCP server
 #[tauri::command]
-pub async fn codex_mcp_set_enabled(server_name: String, enabled: bool) -> Result<(), String> {
-    set_codex_mcp_enabled(&server_name, enabled).map_err(|e| e.to_string())
+pub async fn codex_mcp_set_enabled(server_name: String, enabled: bool) -> Result<(), AnyCodeError> {
+    set_codex_mcp_enabled(&server_name, enabled).map_err(|e| AnyCodeError::Config(e.to_string()))
 }
 
 /// Adds a new MCP server to Codex config
 #[tauri::command]
-pub async fn codex_mcp_add(server: CodexMCPServer) -> Result<(), String> {
-    add_codex_mcp_server(&server).map_err(|e| e.to_string())
+pub async fn codex_mcp_add(server: CodexMCPServer) -> Result<(), AnyCodeError> {
+    add_codex_mcp_server(&server).map_err(|e| AnyCodeError::Config(e.to_string()))
 }
 
 /// Removes an MCP server from Codex config
 #[tauri::command]
-pub async fn codex_mcp_remove(server_name: String) -> Result<(), String> {
-    remove_codex_mcp_server(&server_name).map_err(|e| e.to_string())
+pub async fn codex_mcp_remove(server_name: String) -> Result<(), AnyCodeError> {
+    remove_codex_mcp_server(&server_name).map_err(|e| AnyCodeError::Config(e.to_string()))
 }
 
 // ============================================================================
@@ -438,7 +507,7 @@ fn save_codex_mcp_projects_config(config: &CodexMCPProjectsConfig) -> Result<()>
     
     let content = serde_json::to_string_pretty(config)
         .context("Failed to serialize Codex MCP projects config")?;
-    fs::write(&config_path, content)
+    write_atomic(&config_path, content)
         .context("Failed to write Codex MCP projects config")?;
     
     Ok(())
@@ -500,7 +569,7 @@ pub fn get_codex_project_list(server_name: &str) -> Vec<serde_json::Value> {
 
 /// Tauri command: Gets project list for Codex MCP server
 #[tauri::command]
-pub async fn codex_mcp_get_project_list(server_name: String) -> Result<Vec<serde_json::Value>, String> {
+pub async fn codex_mcp_get_project_list(server_name: String) -> Result<Vec<serde_json::Value>, AnyCodeError> {
     Ok(get_codex_project_list(&server_name))
 }
 
@@ -510,20 +579,20 @@ pub async fn codex_mcp_set_enabled_for_project(
     server_name: String,
     project_path: String,
     enabled: bool,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     set_codex_mcp_enabled_for_project(&server_name, &project_path, enabled)
-        .map_err(|e| e.to_string())
+        .map_err(|e| AnyCodeError::Config(e.to_string()))
 }
 
 /// Adds a project to the Codex MCP projects config (for tracking)
 #[tauri::command]
-pub async fn codex_mcp_add_project(project_path: String) -> Result<(), String> {
+pub async fn codex_mcp_add_project(project_path: String) -> Result<(), AnyCodeError> {
     let mut config = load_codex_mcp_projects_config();
     
     // Add project if not exists
     if !config.projects.contains_key(&project_path) {
         config.projects.insert(project_path.clone(), CodexProjectMCPConfig::default());
-        save_codex_mcp_projects_config(&config).map_err(|e| e.to_string())?;
+        save_codex_mcp_projects_config(&config).map_err(|e| AnyCodeError::Config(e.to_string()))?;
         info!("[Codex MCP] Added project '{}' to tracking", project_path);
     }
     
@@ -607,10 +676,35 @@ disabled = true
         assert_eq!(servers.len(), 1);
         assert!(servers[0].disabled);
     }
+    
+    #[test]
+    fn test_extended_fields_round_trip() {
+        let toml_content = r#"
+[mcp_servers.docs]
+url = "https://example.com/mcp"
+cwd = "/srv"
+enabled_tools = ["search"]
+http_headers = { Authorization = "Bearer t" }
+"#;
+        
+        let servers = parse_codex_mcp_from_string(toml_content).unwrap();
+        let extras = &servers[0].extras;
+        assert_eq!(extras.cwd.as_deref(), Some("/srv"));
+        assert_eq!(extras.include_tools, vec!["search".to_string()]);
+        assert_eq!(extras.headers.get("Authorization").map(String::as_str), Some("Bearer t"));
+        
+        let mut table = toml::Table::new();
+        apply_extras_to_table(&mut table, extras);
+        assert_eq!(table["enabled_tools"].as_array().map(|a| a.len()), Some(1));
+        assert!(!table.contains_key("disabled_tools"));
+    }
 }
 
 
 /// Updates an existing MCP server in Codex config
+///
+/// Timeouts are only written when given; `extras` replaces the extended fields when given.
+#[allow(clippy::too_many_arguments)]
 pub fn update_codex_mcp_server(
     server_name: &str,
     command: Option<String>,
@@ -618,14 +712,44 @@ pub fn update_codex_mcp_server(
     env: std::collections::HashMap<String, String>,
     url: Option<String>,
     enabled: bool,
+    startup_timeout_sec: Option<u64>,
+    tool_timeout_sec: Option<u64>,
+    extras: Option<&MCPServerExtras>,
+) -> Result<()> {
+    for_each_codex_config(|config_path| {
+        update_codex_mcp_server_in(
+            config_path,
+            server_name,
+            command.clone(),
+            args.clone(),
+            env.clone(),
+            url.clone(),
+            enabled,
+            startup_timeout_sec,
+            tool_timeout_sec,
+            extras,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_codex_mcp_server_in(
+    config_path: &Path,
+    server_name: &str,
+    command: Option<String>,
+    args: Vec<String>,
+    env: std::collections::HashMap<String, String>,
+    url: Option<String>,
+    enabled: bool,
+    startup_timeout_sec: Option<u64>,
+    tool_timeout_sec: Option<u64>,
+    extras: Option<&MCPServerExtras>,
 ) -> Result<()> {
-    let config_path = get_codex_config_path()?;
-    
     if !config_path.exists() {
         return Err(anyhow::anyhow!("Codex config file not found"));
     }
     
-    let content = fs::read_to_string(&config_path)
+    let content = fs::read_to_string(config_path)
         .context("Failed to read Codex config file")?;
     
     let mut config: toml::Table = toml::from_str(&content)
@@ -669,6 +793,16 @@ pub fn update_codex_mcp_server(
         server_table.insert("url".to_string(), toml::Value::String(u));
     }
     
+    if let Some(timeout) = startup_timeout_sec {
+        server_table.insert("startup_timeout_sec".to_string(), toml::Value::Integer(timeout as i64));
+    }
+    if let Some(timeout) = tool_timeout_sec {
+        server_table.insert("tool_timeout_sec".to_string(), toml::Value::Integer(timeout as i64));
+    }
+    if let Some(extras) = extras {
+        apply_extras_to_table(server_table, extras);
+    }
+    
     // Update disabled status
     if enabled {
         server_table.remove("disabled");
@@ -679,7 +813,7 @@ pub fn update_codex_mcp_server(
     // Write back to file
     let new_content = toml::to_string_pretty(&config)
         .context("Failed to serialize Codex config")?;
-    fs::write(&config_path, new_content)
+    write_atomic(config_path, new_content)
         .context("Failed to write Codex config file")?;
     
     info!("[Codex MCP] Updated server '{}'", server_name);
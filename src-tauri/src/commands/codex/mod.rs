@@ -8,16 +8,26 @@
  * - session.rs: Session lifecycle management (execute, resume, cancel, list, delete)
  * - git_ops.rs: Git operations for rewind functionality (records, truncate, revert)
  * - config.rs: Configuration management (availability, paths, mode, providers)
+ * - failover.rs: Provider fallback chain and automatic failover
+ * - reasoning_stats.rs: Reasoning effort outcome statistics and suggestions
  * - change_tracker.rs: Code change tracking and diff export
+ * - change_detection.rs: Large-repo performance mode for command side-effect detection
+ * - change_store.rs: SQLite persistence for change records
+ * - snapshot_store.rs: Content-addressed file snapshots for rewind in non-git projects
  */
 
+pub mod change_detection;  // 大仓库命令副作用检测（性能模式）
+pub mod change_store;  // 变更记录 SQLite 存储
 pub mod change_tracker;  // 代码变更追踪模块
 pub mod config;
+pub mod failover;  // 代理商备用链与自动故障转移
 pub mod git_ops;
 pub mod mcp;  // MCP configuration parser for Codex TOML format
+pub mod reasoning_stats;  // 推理强度与任务结果统计、推理模式推荐
 pub mod selector;  // Model and reasoning mode selector
 pub mod session;
 pub mod session_converter;
+pub mod snapshot_store;  // 非 Git 项目的文件快照（提示词级代码回滚）
 
 // ============================================================================
 // Re-export Types (allow unused for API compatibility)
@@ -196,6 +206,8 @@ pub use change_tracker::{
     codex_export_single_change,
     codex_clear_change_records,
     codex_repair_change_records,
+    codex_export_change_records_json,
+    codex_detect_command_changes,
     // Types
     CodexFileChange,
     ChangeType,
@@ -221,6 +233,7 @@ pub use config::{
 
 #[allow(unused_imports)]
 pub use session::{
+    build_headless_codex_command,
     find_session_file,
     parse_codex_session_file,
 };
@@ -233,4 +246,5 @@ pub use git_ops::{
     truncate_codex_git_records,
     extract_codex_prompts,
     truncate_codex_session_to_prompt,
+    fork_codex_session,
 };
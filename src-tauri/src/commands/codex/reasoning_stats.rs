@@ -0,0 +1,441 @@
+//! 推理强度与任务结果统计
+//!
+//! 每次 Codex 执行结束时记录实际使用的推理模式、模型、提示词长度、耗时和结果
+//! （完成 / 出错 / 超时 / 取消），写入 agents.db 的 `codex_reasoning_runs` 表。
+//! `suggest_reasoning_mode` 按项目和提示词长度分档汇总各推理模式的完成率与耗时，
+//! 推荐完成率最高的模式（完成率接近时选耗时更短的），供选择器界面提示。
+//!
+//! 代理商故障（故障转移、限流）与推理强度无关，不记录。样本不足时按提示词长度给出经验推荐。
+
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::selector::{get_codex_selection_config, get_supported_reasoning_modes_for_model, normalize_reasoning_mode};
+use crate::commands::provider_metrics::percentile;
+use crate::commands::storage::open_agent_db;
+use crate::commands::tokenizer::count_text;
+
+/// 每个模式至少需要的样本数
+const MIN_RUNS_PER_MODE: usize = 3;
+/// 完成率相差在该范围内时视为相当，选耗时更短的模式
+const SUCCESS_RATE_TOLERANCE: f64 = 0.1;
+/// 参与推荐的样本时间范围
+const LOOKBACK_DAYS: i64 = 90;
+/// 样本保留天数
+const RETENTION_DAYS: i64 = 180;
+
+/// 一次执行的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Completed,
+    Failed,
+    TimedOut,
+    Cancelled,
+}
+
+impl RunOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RunOutcome::Completed => "completed",
+            RunOutcome::Failed => "failed",
+            RunOutcome::TimedOut => "timed_out",
+            RunOutcome::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "completed" => RunOutcome::Completed,
+            "timed_out" => RunOutcome::TimedOut,
+            "cancelled" => RunOutcome::Cancelled,
+            _ => RunOutcome::Failed,
+        }
+    }
+}
+
+/// 提示词长度分档（按 token 数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptSize {
+    Short,
+    Medium,
+    Long,
+}
+
+impl PromptSize {
+    pub fn from_tokens(tokens: usize) -> Self {
+        match tokens {
+            0..200 => PromptSize::Short,
+            200..1500 => PromptSize::Medium,
+            _ => PromptSize::Long,
+        }
+    }
+
+    fn token_range(&self) -> (i64, i64) {
+        match self {
+            PromptSize::Short => (0, 200),
+            PromptSize::Medium => (200, 1500),
+            PromptSize::Long => (1500, i64::MAX),
+        }
+    }
+
+    /// 没有历史数据时的经验推荐
+    fn heuristic_mode(&self) -> &'static str {
+        match self {
+            PromptSize::Short => "low",
+            PromptSize::Medium => "medium",
+            PromptSize::Long => "high",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReasoningRun {
+    pub project_path: String,
+    pub model: String,
+    pub reasoning_mode: String,
+    pub prompt_tokens: usize,
+    pub duration_ms: u64,
+    pub outcome: RunOutcome,
+}
+
+/// 某个推理模式的汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReasoningModeStats {
+    pub mode: String,
+    pub runs: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+    pub cancelled: usize,
+    pub success_rate: f64,
+    /// 完成的任务的耗时中位数
+    pub median_duration_ms: Option<u64>,
+}
+
+/// 推荐依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionBasis {
+    /// 本项目中长度相近的任务
+    Project,
+    /// 所有项目中长度相近的任务
+    Global,
+    /// 样本不足，按提示词长度
+    Heuristic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReasoningSuggestion {
+    pub mode: String,
+    pub basis: SuggestionBasis,
+    pub prompt_tokens: usize,
+    pub prompt_size: PromptSize,
+    pub reason: String,
+    /// 参与比较的各模式统计（按完成率从高到低）
+    pub stats: Vec<ReasoningModeStats>,
+}
+
+fn insert_run(conn: &Connection, run: &ReasoningRun, recorded_at: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO codex_reasoning_runs
+            (project_path, model, reasoning_mode, prompt_tokens, duration_ms, outcome, recorded_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            run.project_path,
+            run.model,
+            run.reasoning_mode,
+            run.prompt_tokens as i64,
+            run.duration_ms as i64,
+            run.outcome.as_str(),
+            recorded_at,
+        ],
+    )
+    .map_err(|e| format!("写入推理统计失败: {}", e))?;
+    let cutoff = (Utc::now() - Duration::days(RETENTION_DAYS)).to_rfc3339();
+    conn.execute("DELETE FROM codex_reasoning_runs WHERE recorded_at < ?1", params![cutoff])
+        .map_err(|e| format!("清理推理统计失败: {}", e))?;
+    Ok(())
+}
+
+/// 读取长度分档内的样本（`project_path` 为空时读取所有项目）
+fn load_runs(
+    conn: &Connection,
+    project_path: Option<&str>,
+    size: PromptSize,
+    since: &str,
+) -> Result<Vec<ReasoningRun>, String> {
+    let (min_tokens, max_tokens) = size.token_range();
+    let mut stmt = conn
+        .prepare(
+            "SELECT project_path, model, reasoning_mode, prompt_tokens, duration_ms, outcome
+             FROM codex_reasoning_runs
+             WHERE (?1 IS NULL OR project_path = ?1) AND prompt_tokens >= ?2 AND prompt_tokens < ?3
+               AND recorded_at >= ?4
+             ORDER BY id",
+        )
+        .map_err(|e| format!("查询推理统计失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![project_path, min_tokens, max_tokens, since], |row| {
+            Ok(ReasoningRun {
+                project_path: row.get(0)?,
+                model: row.get(1)?,
+                reasoning_mode: row.get(2)?,
+                prompt_tokens: row.get::<_, i64>(3)? as usize,
+                duration_ms: row.get::<_, i64>(4)? as u64,
+                outcome: RunOutcome::parse(&row.get::<_, String>(5)?),
+            })
+        })
+        .map_err(|e| format!("查询推理统计失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取推理统计失败: {}", e))
+}
+
+/// 记录一次执行；未显式指定模型或推理模式时按 Codex 当前配置补全
+pub async fn record_run(
+    project_path: &str,
+    model: Option<&str>,
+    reasoning_mode: Option<&str>,
+    prompt_tokens: usize,
+    duration_ms: u64,
+    outcome: RunOutcome,
+) {
+    let selection = match (model, reasoning_mode) {
+        (Some(_), Some(_)) => None,
+        _ => get_codex_selection_config().await.ok().flatten(),
+    };
+    let run = ReasoningRun {
+        project_path: project_path.to_string(),
+        model: model
+            .map(str::to_string)
+            .or_else(|| selection.as_ref().map(|s| s.model.clone()))
+            .unwrap_or_default(),
+        reasoning_mode: reasoning_mode
+            .map(normalize_reasoning_mode)
+            .or_else(|| selection.as_ref().map(|s| s.reasoning_mode.clone()))
+            .unwrap_or_else(|| "medium".to_string()),
+        prompt_tokens,
+        duration_ms,
+        outcome,
+    };
+    let recorded_at = Utc::now().to_rfc3339();
+    let result = tokio::task::spawn_blocking(move || {
+        let conn = open_agent_db()?;
+        insert_run(&conn, &run, &recorded_at)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+    if let Err(e) = result {
+        log::warn!("[ReasoningStats] Failed to record run: {}", e);
+    }
+}
+
+/// 按推理模式汇总（按完成率从高到低）
+fn summarize(runs: &[ReasoningRun]) -> Vec<ReasoningModeStats> {
+    let mut by_mode: BTreeMap<&str, Vec<&ReasoningRun>> = BTreeMap::new();
+    for run in runs {
+        by_mode.entry(run.reasoning_mode.as_str()).or_default().push(run);
+    }
+    let mut stats: Vec<ReasoningModeStats> = by_mode
+        .into_iter()
+        .map(|(mode, runs)| {
+            let count = |outcome| runs.iter().filter(|r| r.outcome == outcome).count();
+            let completed = count(RunOutcome::Completed);
+            let mut durations: Vec<u64> = runs
+                .iter()
+                .filter(|r| r.outcome == RunOutcome::Completed)
+                .map(|r| r.duration_ms)
+                .collect();
+            durations.sort_unstable();
+            ReasoningModeStats {
+                mode: mode.to_string(),
+                runs: runs.len(),
+                completed,
+                failed: count(RunOutcome::Failed),
+                timed_out: count(RunOutcome::TimedOut),
+                cancelled: count(RunOutcome::Cancelled),
+                success_rate: completed as f64 / runs.len() as f64,
+                median_duration_ms: percentile(&durations, 0.5),
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| b.success_rate.total_cmp(&a.success_rate).then(b.runs.cmp(&a.runs)));
+    stats
+}
+
+/// 在样本充足的模式中选完成率最高的；完成率接近时选耗时更短的
+fn pick_mode<'a>(stats: &'a [ReasoningModeStats], supported: &[String]) -> Option<&'a ReasoningModeStats> {
+    let candidates: Vec<&ReasoningModeStats> = stats
+        .iter()
+        .filter(|s| s.runs >= MIN_RUNS_PER_MODE && s.completed > 0 && supported.contains(&s.mode))
+        .collect();
+    let best_rate = candidates.iter().map(|s| s.success_rate).fold(f64::NAN, f64::max);
+    candidates
+        .into_iter()
+        .filter(|s| s.success_rate >= best_rate - SUCCESS_RATE_TOLERANCE)
+        .min_by_key(|s| s.median_duration_ms.unwrap_or(u64::MAX))
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 60 {
+        format!("{} 分 {} 秒", secs / 60, secs % 60)
+    } else {
+        format!("{} 秒", secs)
+    }
+}
+
+fn suggest(
+    project_runs: &[ReasoningRun],
+    global_runs: &[ReasoningRun],
+    supported: &[String],
+    prompt_tokens: usize,
+) -> ReasoningSuggestion {
+    let prompt_size = PromptSize::from_tokens(prompt_tokens);
+    for (basis, runs) in [(SuggestionBasis::Project, project_runs), (SuggestionBasis::Global, global_runs)] {
+        let stats = summarize(runs);
+        if let Some(best) = pick_mode(&stats, supported) {
+            let scope = if basis == SuggestionBasis::Project { "本项目" } else { "所有项目" };
+            let reason = format!(
+                "{}中长度相近的 {} 次任务里，{} 的完成率为 {:.0}%（{} 次）{}",
+                scope,
+                runs.len(),
+                best.mode,
+                best.success_rate * 100.0,
+                best.runs,
+                best.median_duration_ms
+                    .map(|ms| format!("，中位耗时 {}", format_duration(ms)))
+                    .unwrap_or_default()
+            );
+            return ReasoningSuggestion {
+                mode: best.mode.clone(),
+                basis,
+                prompt_tokens,
+                prompt_size,
+                reason,
+                stats,
+            };
+        }
+    }
+
+    // 经验推荐需要落在模型支持的模式内（如 mini 模型不支持 high）
+    let preferred = prompt_size.heuristic_mode();
+    let mode = if supported.iter().any(|m| m == preferred) {
+        preferred.to_string()
+    } else {
+        supported.last().cloned().unwrap_or_else(|| preferred.to_string())
+    };
+    ReasoningSuggestion {
+        mode,
+        basis: SuggestionBasis::Heuristic,
+        prompt_tokens,
+        prompt_size,
+        reason: format!("历史样本不足（每个模式至少需要 {} 次），按提示词长度推荐", MIN_RUNS_PER_MODE),
+        stats: summarize(project_runs),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 根据历史执行结果推荐推理模式
+#[tauri::command]
+pub async fn suggest_reasoning_mode(
+    project_path: String,
+    prompt: String,
+    model: Option<String>,
+) -> Result<ReasoningSuggestion, String> {
+    let model = match model {
+        Some(model) => model,
+        None => get_codex_selection_config()
+            .await
+            .ok()
+            .flatten()
+            .map(|s| s.model)
+            .unwrap_or_default(),
+    };
+    tokio::task::spawn_blocking(move || {
+        let prompt_tokens = count_text(&model, &prompt);
+        let size = PromptSize::from_tokens(prompt_tokens);
+        let since = (Utc::now() - Duration::days(LOOKBACK_DAYS)).to_rfc3339();
+        let conn = open_agent_db()?;
+        let project_runs = load_runs(&conn, Some(&project_path), size, &since)?;
+        let global_runs = load_runs(&conn, None, size, &since)?;
+        Ok(suggest(
+            &project_runs,
+            &global_runs,
+            &get_supported_reasoning_modes_for_model(&model),
+            prompt_tokens,
+        ))
+    })
+    .await
+    .map_err(|e| format!("推荐推理模式失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(mode: &str, outcomes: &[(RunOutcome, u64)]) -> Vec<ReasoningRun> {
+        outcomes
+            .iter()
+            .map(|&(outcome, duration_ms)| ReasoningRun {
+                project_path: "/p".into(),
+                model: "gpt-5.2-codex".into(),
+                reasoning_mode: mode.into(),
+                prompt_tokens: 50,
+                duration_ms,
+                outcome,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn suggests_mode_from_success_rate_and_duration() {
+        use RunOutcome::*;
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::commands::db_migrations::run_migrations(&mut conn).unwrap();
+        let mut all = runs("low", &[(Completed, 20_000), (Failed, 5_000), (Cancelled, 9_000), (Completed, 30_000)]);
+        all.extend(runs("medium", &[(Completed, 60_000), (Completed, 70_000), (Completed, 80_000), (TimedOut, 1)]));
+        all.extend(runs("high", &[(Completed, 200_000), (Completed, 210_000), (Completed, 220_000), (Completed, 1)]));
+        for run in &all {
+            insert_run(&conn, run, &Utc::now().to_rfc3339()).unwrap();
+        }
+        let since = (Utc::now() - Duration::days(1)).to_rfc3339();
+        let loaded = load_runs(&conn, Some("/p"), PromptSize::Short, &since).unwrap();
+        assert_eq!(loaded, all);
+        assert!(load_runs(&conn, Some("/other"), PromptSize::Short, &since).unwrap().is_empty());
+        assert!(load_runs(&conn, None, PromptSize::Long, &since).unwrap().is_empty());
+
+        let stats = summarize(&loaded);
+        assert_eq!(stats.iter().map(|s| s.mode.as_str()).collect::<Vec<_>>(), ["high", "medium", "low"]);
+        assert_eq!((stats[2].completed, stats[2].failed, stats[2].cancelled), (2, 1, 1));
+        assert_eq!(stats[1].median_duration_ms, Some(70_000));
+
+        // high 全部完成，medium 75% 超出容差，选 high
+        let supported = get_supported_reasoning_modes_for_model("gpt-5.2-codex");
+        let suggestion = suggest(&loaded, &[], &supported, 50);
+        assert_eq!((suggestion.mode.as_str(), suggestion.basis), ("high", SuggestionBasis::Project));
+
+        // 完成率相当时选耗时更短的
+        let mut close = loaded.clone();
+        close.extend(runs("medium", &[(Completed, 60_000)]));
+        close.retain(|r| !(r.reasoning_mode == "high" && r.duration_ms == 1));
+        close.extend(runs("high", &[(TimedOut, 1)]));
+        assert_eq!(suggest(&close, &[], &supported, 50).mode, "medium");
+
+        // mini 模型不支持 high；没有样本时按长度推荐，并落在支持的模式内
+        let mini = get_supported_reasoning_modes_for_model("gpt-5.1-codex-mini");
+        assert_eq!(suggest(&loaded, &loaded, &mini, 50).mode, "medium");
+        let fallback = suggest(&[], &[], &mini, 5000);
+        assert_eq!((fallback.mode.as_str(), fallback.basis), ("medium", SuggestionBasis::Heuristic));
+        assert_eq!(fallback.prompt_size, PromptSize::Long);
+    }
+}
@@ -4,6 +4,8 @@
  * 处理 Codex 模型和推理模式的选择、配置管理和能力检测
  */
 
+use crate::tr;
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
 
 use tokio::process::Command;
@@ -188,7 +190,7 @@ fn get_builtin_reasoning_modes() -> Vec<ReasoningModeOption> {
 /// 模型支持情况（基于 Codex CLI 实际显示）：
 /// - 大多数模型支持全部 4 种推理模式（low/medium/high/xhigh）
 /// - mini 系列轻量模型仅支持 low/medium
-fn get_supported_reasoning_modes_for_model(model_id: &str) -> Vec<String> {
+pub fn get_supported_reasoning_modes_for_model(model_id: &str) -> Vec<String> {
     // mini 系列轻量模型仅支持 low/medium
     if model_id.contains("mini") {
         return vec![
@@ -270,7 +272,7 @@ fn get_builtin_capabilities() -> CodexCapabilities {
 /// 获取配置目录路径
 fn get_config_dir() -> Result<PathBuf, String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "无法获取用户主目录".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     Ok(home_dir.join(".kiro"))
 }
@@ -309,7 +311,7 @@ fn load_config_from_file() -> Result<Option<CodexSelectionConfig>, String> {
 /// 获取 Windows 本地 Codex config.toml 路径
 fn get_native_codex_config_toml_path() -> Result<PathBuf, String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "无法获取用户主目录".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     Ok(home_dir.join(".codex").join("config.toml"))
 }
 
@@ -637,7 +639,7 @@ async fn parse_codex_reasoning_modes(output: &str) -> Result<Vec<ReasoningModeOp
 
 /// 标准化推理模式值
 /// 支持多种格式：extra-high, xhigh, extra_high 等都映射到 xhigh
-fn normalize_reasoning_mode(mode: &str) -> String {
+pub(crate) fn normalize_reasoning_mode(mode: &str) -> String {
     match mode.to_lowercase().as_str() {
         "extra-high" | "extra_high" | "extrahigh" => "xhigh".to_string(),
         other => other.to_string(),
@@ -683,7 +685,7 @@ fn read_config_from_codex_toml() -> Result<Option<CodexSelectionConfig>, String>
 /// 获取 Codex 选择配置
 /// 优先从 Codex config.toml 读取，确保与 CLI 配置同步
 #[tauri::command]
-pub async fn get_codex_selection_config() -> Result<Option<CodexSelectionConfig>, String> {
+pub async fn get_codex_selection_config() -> Result<Option<CodexSelectionConfig>, AnyCodeError> {
     log::info!("[Codex Selector] 获取选择配置");
     
     // 优先从 Codex config.toml 读取当前配置
@@ -717,16 +719,17 @@ pub async fn get_codex_selection_config() -> Result<Option<CodexSelectionConfig>
 
 /// 保存 Codex 选择配置
 #[tauri::command]
-pub async fn save_codex_selection_config(config: CodexSelectionConfig) -> Result<(), String> {
+pub async fn save_codex_selection_config(config: CodexSelectionConfig) -> Result<(), AnyCodeError> {
     log::info!("[Codex Selector] 保存选择配置: {:?}", config);
     
-    save_config_to_file(&config)?;
+    save_config_to_file(&config).map_err(AnyCodeError::Io)?;
+    crate::commands::capabilities::invalidate_capability_cache();
     Ok(())
 }
 
 /// 获取默认 Codex 选择配置
 #[tauri::command]
-pub async fn get_default_codex_selection_config() -> Result<CodexSelectionConfig, String> {
+pub async fn get_default_codex_selection_config() -> Result<CodexSelectionConfig, AnyCodeError> {
     log::info!("[Codex Selector] 获取默认选择配置");
     
     let config = CodexSelectionConfig {
@@ -740,7 +743,7 @@ pub async fn get_default_codex_selection_config() -> Result<CodexSelectionConfig
 
 /// 获取可用的推理模式
 #[tauri::command]
-pub async fn get_available_reasoning_modes() -> Result<Vec<ReasoningModeOption>, String> {
+pub async fn get_available_reasoning_modes() -> Result<Vec<ReasoningModeOption>, AnyCodeError> {
     log::info!("[Codex Selector] 获取可用推理模式");
     
     // 尝试从 Codex CLI 获取
@@ -768,7 +771,7 @@ pub async fn get_available_reasoning_modes() -> Result<Vec<ReasoningModeOption>,
 
 /// 获取可用的 Codex 模型
 #[tauri::command]
-pub async fn get_available_codex_models() -> Result<Vec<CodexModelOption>, String> {
+pub async fn get_available_codex_models() -> Result<Vec<CodexModelOption>, AnyCodeError> {
     log::info!("[Codex Selector] 获取可用模型");
     
     // 尝试从 Codex CLI 获取
@@ -796,17 +799,18 @@ pub async fn get_available_codex_models() -> Result<Vec<CodexModelOption>, Strin
 
 /// 刷新 Codex 能力（实时获取，不使用缓存）
 #[tauri::command]
-pub async fn refresh_codex_capabilities() -> Result<CodexCapabilities, String> {
+pub async fn refresh_codex_capabilities() -> Result<CodexCapabilities, AnyCodeError> {
     log::info!("[Codex Selector] 刷新 Codex 能力（实时获取）");
     
     // 直接获取最新能力，不使用缓存
-    get_codex_capabilities_internal().await
+    get_codex_capabilities_internal().await.map_err(AnyCodeError::Engine)
 }
 
 /// 强制刷新 Codex 能力（与 refresh_codex_capabilities 相同，保持 API 兼容）
 #[tauri::command]
-pub async fn force_refresh_codex_capabilities() -> Result<CodexCapabilities, String> {
+pub async fn force_refresh_codex_capabilities() -> Result<CodexCapabilities, AnyCodeError> {
     log::info!("[Codex Selector] 强制刷新 Codex 能力");
+    crate::commands::capabilities::invalidate_capability_cache();
     
     // 删除现有缓存（如果存在）
     if let Ok(cache_path) = get_capabilities_cache_path() {
@@ -819,11 +823,11 @@ pub async fn force_refresh_codex_capabilities() -> Result<CodexCapabilities, Str
         }
     }
     
-    get_codex_capabilities_internal().await
+    get_codex_capabilities_internal().await.map_err(AnyCodeError::Engine)
 }
 
 /// 内部获取能力函数（实时获取，不使用缓存）
-async fn get_codex_capabilities_internal() -> Result<CodexCapabilities, String> {
+pub async fn get_codex_capabilities_internal() -> Result<CodexCapabilities, String> {
     // 直接使用内置模型定义（因为 Codex CLI 不提供 --list-models 命令）
     let reasoning_modes = get_builtin_reasoning_modes();
     let models = get_builtin_models();
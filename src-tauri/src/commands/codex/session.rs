@@ -7,22 +7,48 @@
  * - Session deletion
  */
 
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::time::Instant;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
 // Import platform-specific utilities for window hiding
-use crate::commands::claude::apply_no_window_async;
+use crate::commands::claude::{apply_no_window_async, kill_process_tree};
+use crate::process::{kill_process_tree_verified_async, ExecutionTimeoutOptions, ExecutionWatchdog, ProcessKillReport};
 use crate::claude_binary::detect_binary_for_tool;
 // Import WSL utilities for Windows + WSL Codex support
 use super::super::wsl_utils;
+use super::super::attachments::{self, StagedAttachment};
+use super::super::auto_fix::{self, AutoFixOptions};
+use super::super::ssh_remote::{self, RemoteConfig};
+use super::super::docker_backend;
+use super::super::model_routing;
+use super::super::permission_config::apply_permission_profile;
+use super::super::project_defaults;
+use super::super::project_memory;
+use super::super::prompt_history;
+use super::super::prompt_library::{apply_prompt_template, PromptTemplateRef};
+use super::super::prompt_translation::{self, ResponseTranslator, TranslationRecord};
+use super::super::provider_metrics::{ErrorCategory, StreamMetrics};
+use super::super::notifications;
+use super::super::output_batcher::OutputBatcher;
+use super::super::rate_limit::{self, RateLimitWatcher};
+use super::failover;
+use super::reasoning_stats::{self, RunOutcome};
+use super::super::session_metadata::{attach_session_metadata, SessionFilter, SessionMetadata};
+use super::super::tokenizer::count_text;
 // Import config module for sessions directory
-use super::config::get_codex_sessions_dir;
+use super::config::{
+    active_codex_provider_id, codex_provider_overrides, get_all_codex_sessions_dirs,
+    get_codex_config_dir, get_codex_config_dir_for_project, get_codex_sessions_dir_for_project,
+    load_codex_provider,
+};
 
 // ============================================================================
 // Type Definitions
@@ -66,6 +92,39 @@ pub struct CodexExecutionOptions {
     /// Reasoning mode to use (e.g., "medium", "high")
     pub reasoning_mode: Option<String>,
 
+    /// Permission profile ID (overrides `mode` when set)
+    #[serde(default)]
+    pub permission_profile: Option<String>,
+
+    /// Provider preset ID (applied via `-c` overrides for this execution only)
+    #[serde(default)]
+    pub provider: Option<String>,
+
+    /// Prompt library template rendered into the prompt before execution
+    #[serde(default)]
+    pub prompt_template: Option<PromptTemplateRef>,
+
+    /// Prepend relevant project memory notes to a new session (falls back to project defaults)
+    #[serde(default)]
+    pub include_memory: Option<bool>,
+
+    /// Staged attachments (images via `--image`, other files listed in the prompt)
+    #[serde(default)]
+    pub attachments: Vec<StagedAttachment>,
+
+    /// Original prompt when `prompt` was translated before sending (set by the backend)
+    #[serde(skip)]
+    pub prompt_translation: Option<TranslationRecord>,
+
+    /// Token count of the user's prompt before memory / system instructions are added (set by the backend)
+    #[serde(skip)]
+    pub prompt_tokens: usize,
+
+    /// Backend helper run (summaries, commit messages): no prompt history, translation,
+    /// memory or system prompt (set by the backend)
+    #[serde(skip)]
+    pub internal: bool,
+
     /// Enable JSON output mode
     #[serde(default = "default_json_mode")]
     pub json: bool,
@@ -89,6 +148,44 @@ pub struct CodexExecutionOptions {
     /// Resume last session
     #[serde(default)]
     pub resume_last: bool,
+
+    /// Verify after completion and send fix prompts on failure (auto-fix loop)
+    #[serde(default)]
+    pub auto_fix: Option<AutoFixOptions>,
+
+    /// Idle / max-duration timeout and retry policy
+    #[serde(default, flatten)]
+    pub timeout: ExecutionTimeoutOptions,
+}
+
+impl CodexExecutionOptions {
+    /// Options for a new session with everything else left to project defaults
+    pub fn new(project_path: String, prompt: String) -> Self {
+        Self {
+            project_path,
+            prompt,
+            mode: CodexExecutionMode::default(),
+            model: None,
+            reasoning_mode: None,
+            permission_profile: None,
+            provider: None,
+            prompt_template: None,
+            include_memory: None,
+            attachments: Vec::new(),
+            prompt_translation: None,
+            prompt_tokens: 0,
+            internal: false,
+            json: default_json_mode(),
+            output_schema: None,
+            output_file: None,
+            skip_git_repo_check: false,
+            api_key: None,
+            session_id: None,
+            resume_last: false,
+            auto_fix: None,
+            timeout: ExecutionTimeoutOptions::default(),
+        }
+    }
 }
 
 fn default_json_mode() -> bool {
@@ -145,6 +242,27 @@ pub struct CodexSession {
 
     /// Last message timestamp (ISO string)
     pub last_message_timestamp: Option<String>,
+
+    /// Tags / pinned / archived state managed by AnyCode
+    #[serde(default)]
+    pub metadata: SessionMetadata,
+}
+
+/// A single Codex run request, kept so a timed-out run can be retried
+#[derive(Debug, Clone)]
+struct CodexRun {
+    options: CodexExecutionOptions,
+    is_resume: bool,
+    /// Session ID (or "--last") passed to `codex exec resume`
+    resume_target: Option<String>,
+    /// 0-based attempt number (incremented on timeout retry)
+    attempt: u32,
+    /// Providers that already failed this request (automatic failover)
+    tried_providers: Vec<String>,
+    /// Times this request was resubmitted after a rate limit
+    requeues: u32,
+    /// Fix prompts already sent by the auto-fix loop
+    auto_fix_iteration: u32,
 }
 
 /// Global state to track Codex processes
@@ -166,19 +284,39 @@ impl Default for CodexProcessState {
 // Core Execution Methods
 // ============================================================================
 
+/// Run failures inside a WSL distro are reported as `wsl` errors so the UI can point at the distro setup
+fn run_error(project_path: &str) -> impl Fn(String) -> AnyCodeError {
+    let via_wsl = cfg!(target_os = "windows")
+        && !docker_backend::container_enabled(project_path)
+        && wsl_utils::get_wsl_config_for_project(project_path).enabled;
+    move |e| if via_wsl { AnyCodeError::Wsl(e) } else { AnyCodeError::Engine(e) }
+}
+
 /// Executes a Codex task in non-interactive mode with streaming output
 #[tauri::command]
 pub async fn execute_codex(
     options: CodexExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     log::info!("execute_codex called with options: {:?}", options);
-
-    // Build codex exec command
-    let (cmd, prompt) = build_codex_command(&options, false, None)?;
+    let options = resolve_execution_options(options, false).await.map_err(AnyCodeError::Config)?;
+    let run_error = run_error(&options.project_path);
 
     // Execute and stream output
-    execute_codex_process(cmd, prompt, options.project_path.clone(), app_handle).await
+    execute_codex_process(
+        CodexRun {
+            options,
+            is_resume: false,
+            resume_target: None,
+            attempt: 0,
+            tried_providers: Vec::new(),
+            requeues: 0,
+            auto_fix_iteration: 0,
+        },
+        app_handle,
+    )
+    .await
+    .map_err(run_error)
 }
 
 /// Resumes a previous Codex session
@@ -187,14 +325,26 @@ pub async fn resume_codex(
     session_id: String,
     options: CodexExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     log::info!("resume_codex called for session: {}", session_id);
-
-    // Build codex exec resume command (session_id added inside build function)
-    let (cmd, prompt) = build_codex_command(&options, true, Some(&session_id))?;
-
-    // Execute and stream output
-    execute_codex_process(cmd, prompt, options.project_path.clone(), app_handle).await
+    let options = resolve_execution_options(options, true).await.map_err(AnyCodeError::Config)?;
+    let run_error = run_error(&options.project_path);
+
+    // Execute and stream output (session_id added inside build function)
+    execute_codex_process(
+        CodexRun {
+            options,
+            is_resume: true,
+            resume_target: Some(session_id),
+            attempt: 0,
+            tried_providers: Vec::new(),
+            requeues: 0,
+            auto_fix_iteration: 0,
+        },
+        app_handle,
+    )
+    .await
+    .map_err(run_error)
 }
 
 /// Resumes the last Codex session
@@ -202,47 +352,75 @@ pub async fn resume_codex(
 pub async fn resume_last_codex(
     options: CodexExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     log::info!("resume_last_codex called");
-
-    // Build codex exec resume --last command
-    let (cmd, prompt) = build_codex_command(&options, true, Some("--last"))?;
-
-    // Execute and stream output
-    execute_codex_process(cmd, prompt, options.project_path.clone(), app_handle).await
+    let options = resolve_execution_options(options, true).await.map_err(AnyCodeError::Config)?;
+    let run_error = run_error(&options.project_path);
+
+    // Execute and stream output (codex exec resume --last)
+    execute_codex_process(
+        CodexRun {
+            options,
+            is_resume: true,
+            resume_target: Some("--last".to_string()),
+            attempt: 0,
+            tried_providers: Vec::new(),
+            requeues: 0,
+            auto_fix_iteration: 0,
+        },
+        app_handle,
+    )
+    .await
+    .map_err(run_error)
 }
 
 /// Cancels a running Codex execution
+///
+/// Kills the whole process tree (including MCP server children), verifies that
+/// no descendant survived and returns a report per cancelled process.
 #[tauri::command]
 pub async fn cancel_codex(
     session_id: Option<String>,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<Vec<ProcessKillReport>, AnyCodeError> {
     log::info!("cancel_codex called for session: {:?}", session_id);
 
     let state: tauri::State<'_, CodexProcessState> = app_handle.state();
-    let mut processes = state.processes.lock().await;
-
-    if let Some(sid) = session_id {
-        // Cancel specific session
-        if let Some(mut child) = processes.remove(&sid) {
-            child.kill().await.map_err(|e| format!("Failed to kill process: {}", e))?;
-            log::info!("Killed Codex process for session: {}", sid);
+    let targets: Vec<(String, Child)> = {
+        let mut processes = state.processes.lock().await;
+        if let Some(sid) = session_id {
+            // Cancel specific session
+            match processes.remove(&sid) {
+                Some(child) => vec![(sid, child)],
+                None => {
+                    log::warn!("No running process found for session: {}", sid);
+                    Vec::new()
+                }
+            }
         } else {
-            log::warn!("No running process found for session: {}", sid);
+            // Cancel all processes
+            processes.drain().collect()
         }
-    } else {
-        // Cancel all processes
-        for (sid, mut child) in processes.drain() {
-            if let Err(e) = child.kill().await {
-                log::error!("Failed to kill process for session {}: {}", sid, e);
-            } else {
-                log::info!("Killed Codex process for session: {}", sid);
+    };
+
+    let mut reports = Vec::new();
+    for (sid, mut child) in targets {
+        if let Some(pid) = child.id() {
+            let report = kill_process_tree_verified_async(pid).await;
+            if !report.fully_terminated() {
+                log::error!("Codex session {} left {} surviving process(es)", sid, report.survivors.len());
             }
+            reports.push(report);
+        }
+
+        // Reap the direct child (also covers a missing PID)
+        if let Err(e) = child.kill().await {
+            log::debug!("Codex child for session {} already gone: {}", sid, e);
         }
+        log::info!("Killed Codex process for session: {}", sid);
     }
 
-    Ok(())
+    Ok(reports)
 }
 
 // ============================================================================
@@ -252,25 +430,33 @@ pub async fn cancel_codex(
 /// Lists all Codex sessions by reading ~/.codex/sessions directory
 /// On Windows with WSL mode, reads from WSL filesystem via UNC path
 /// Optimized: Uses walkdir for efficient directory traversal
+/// `filter` selects by tag / pinned / archived state (archived sessions are hidden by default)
 #[tauri::command]
-pub async fn list_codex_sessions() -> Result<Vec<CodexSession>, String> {
-    tauri::async_runtime::spawn_blocking(|| {
+pub async fn list_codex_sessions(filter: Option<SessionFilter>) -> Result<Vec<CodexSession>, AnyCodeError> {
+    tauri::async_runtime::spawn_blocking(move || {
         log::info!("list_codex_sessions called");
 
-        // Use unified sessions directory function (supports WSL)
-        let sessions_dir = get_codex_sessions_dir()?;
-        log::info!("Looking for Codex sessions in: {:?}", sessions_dir);
-
-        if !sessions_dir.exists() {
-            log::warn!("Codex sessions directory does not exist: {:?}", sessions_dir);
-            return Ok(Vec::new());
-        }
+        // Use unified sessions directories (supports WSL and per-project distros)
+        let sessions_dirs: Vec<_> = get_all_codex_sessions_dirs().map_err(AnyCodeError::Io)?
+            .into_iter()
+            .filter(|dir| {
+                let exists = dir.exists();
+                if !exists {
+                    log::warn!("Codex sessions directory does not exist: {:?}", dir);
+                }
+                exists
+            })
+            .collect();
+        log::info!("Looking for Codex sessions in: {:?}", sessions_dirs);
 
         // Use walkdir for efficient recursive directory traversal
-        let mut sessions: Vec<CodexSession> = walkdir::WalkDir::new(&sessions_dir)
-            .min_depth(4) // Skip year/month/day directories, go directly to files
-            .max_depth(4) // Don't go deeper than needed
-            .into_iter()
+        let mut sessions: Vec<CodexSession> = sessions_dirs
+            .iter()
+            .flat_map(|sessions_dir| {
+                walkdir::WalkDir::new(sessions_dir)
+                    .min_depth(4) // Skip year/month/day directories, go directly to files
+                    .max_depth(4) // Don't go deeper than needed
+            })
             .filter_map(|e| e.ok())
             .filter(|e| {
                 e.path().extension().and_then(|s| s.to_str()) == Some("jsonl")
@@ -292,23 +478,28 @@ pub async fn list_codex_sessions() -> Result<Vec<CodexSession>, String> {
 
         // Sort by creation time (newest first)
         sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        attach_session_metadata("codex", &mut sessions, filter.as_ref());
 
         log::info!("Found {} Codex sessions", sessions.len());
         Ok(sessions)
     })
     .await
-    .map_err(|e| format!("Failed to list Codex sessions: {}", e))?
+    .map_err(|e| AnyCodeError::Internal(format!("Failed to list Codex sessions: {}", e)))?
 }
 
 /// Lists Codex sessions filtered by project path
 /// Optimized: Only parses session files that match the target project path
 /// This avoids loading all sessions when only one project's sessions are needed
+/// `filter` selects by tag / pinned / archived state (archived sessions are hidden by default)
 #[tauri::command]
-pub async fn list_codex_sessions_for_project(project_path: String) -> Result<Vec<CodexSession>, String> {
+pub async fn list_codex_sessions_for_project(
+    project_path: String,
+    filter: Option<SessionFilter>,
+) -> Result<Vec<CodexSession>, AnyCodeError> {
     tauri::async_runtime::spawn_blocking(move || {
         log::info!("list_codex_sessions_for_project called for: {}", project_path);
 
-        let sessions_dir = get_codex_sessions_dir()?;
+        let sessions_dir = get_codex_sessions_dir_for_project(&project_path).map_err(AnyCodeError::Io)?;
         
         if !sessions_dir.exists() {
             return Ok(Vec::new());
@@ -341,25 +532,29 @@ pub async fn list_codex_sessions_for_project(project_path: String) -> Result<Vec
             .collect();
 
         sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        attach_session_metadata("codex", &mut sessions, filter.as_ref());
         log::info!("Found {} Codex sessions for project {}", sessions.len(), project_path);
         Ok(sessions)
     })
     .await
-    .map_err(|e| format!("Failed to list Codex sessions: {}", e))?
+    .map_err(|e| AnyCodeError::Internal(format!("Failed to list Codex sessions: {}", e)))?
 }
 
 /// Lists all Codex projects by grouping sessions by project path
 /// Returns a list of projects with session counts and last activity timestamps
 #[tauri::command]
-pub async fn list_codex_projects() -> Result<Vec<CodexProject>, String> {
+pub async fn list_codex_projects() -> Result<Vec<CodexProject>, AnyCodeError> {
     tauri::async_runtime::spawn_blocking(|| {
         log::info!("list_codex_projects called");
 
-        let sessions_dir = get_codex_sessions_dir()?;
-        log::info!("Looking for Codex projects in: {:?}", sessions_dir);
+        let sessions_dirs: Vec<_> = get_all_codex_sessions_dirs().map_err(AnyCodeError::Io)?
+            .into_iter()
+            .filter(|dir| dir.exists())
+            .collect();
+        log::info!("Looking for Codex projects in: {:?}", sessions_dirs);
 
-        if !sessions_dir.exists() {
-            log::warn!("Codex sessions directory does not exist: {:?}", sessions_dir);
+        if sessions_dirs.is_empty() {
+            log::warn!("No Codex sessions directory exists");
             return Ok(Vec::new());
         }
 
@@ -380,10 +575,9 @@ pub async fn list_codex_projects() -> Result<Vec<CodexProject>, String> {
                 || norm.contains("/tmp/")
         };
 
-        for entry in walkdir::WalkDir::new(&sessions_dir)
-            .min_depth(4)
-            .max_depth(4)
-            .into_iter()
+        for entry in sessions_dirs
+            .iter()
+            .flat_map(|sessions_dir| walkdir::WalkDir::new(sessions_dir).min_depth(4).max_depth(4))
             .filter_map(|e| e.ok())
             .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
         {
@@ -424,7 +618,7 @@ pub async fn list_codex_projects() -> Result<Vec<CodexProject>, String> {
         Ok(projects)
     })
     .await
-    .map_err(|e| format!("Failed to list Codex projects: {}", e))?
+    .map_err(|e| AnyCodeError::Internal(format!("Failed to list Codex projects: {}", e)))?
 }
 
 /// Quick extraction of project info from session file (reads only first few lines)
@@ -630,6 +824,7 @@ pub fn parse_codex_session_file(path: &std::path::Path) -> Option<CodexSession>
         first_message,
         last_assistant_message,
         last_message_timestamp: final_timestamp,
+        metadata: SessionMetadata::default(),
     })
 }
 
@@ -722,19 +917,16 @@ fn extract_last_timestamp_from_tail(path: &std::path::Path) -> Option<String> {
 /// Loads Codex session history from JSONL file
 /// On Windows with WSL mode, reads from WSL filesystem via UNC path
 #[tauri::command]
-pub async fn load_codex_session_history(session_id: String) -> Result<Vec<serde_json::Value>, String> {
+pub async fn load_codex_session_history(session_id: String) -> Result<Vec<serde_json::Value>, AnyCodeError> {
     log::info!("load_codex_session_history called for: {}", session_id);
 
-    // Use unified sessions directory function (supports WSL)
-    let sessions_dir = get_codex_sessions_dir()?;
-
-    // Search for file containing this session_id
-    let session_file = find_session_file(&sessions_dir, &session_id)?;
+    // Search all sessions directories (supports WSL and per-project distros)
+    let session_file = find_session_file_in_all_dirs(&session_id).map_err(AnyCodeError::Io)?;
 
     // Read and parse JSONL file
     use std::io::{BufRead, BufReader};
     let file = std::fs::File::open(&session_file)
-        .map_err(|e| format!("Failed to open session file: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to open session file: {}", e)))?;
 
     let reader = BufReader::new(file);
     let mut events = Vec::new();
@@ -863,21 +1055,30 @@ pub fn find_session_file(
     Err(err)
 }
 
+/// Finds the JSONL file for a session in any known sessions directory
+pub fn find_session_file_in_all_dirs(session_id: &str) -> Result<std::path::PathBuf, String> {
+    let mut last_err = None;
+    for sessions_dir in get_all_codex_sessions_dirs()? {
+        match find_session_file(&sessions_dir, session_id) {
+            Ok(path) => return Ok(path),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| format!("Session file not found for ID: {}", session_id)))
+}
+
 /// Deletes a Codex session
 /// On Windows with WSL mode, deletes from WSL filesystem via UNC path
 #[tauri::command]
-pub async fn delete_codex_session(session_id: String) -> Result<String, String> {
+pub async fn delete_codex_session(session_id: String) -> Result<String, AnyCodeError> {
     log::info!("delete_codex_session called for: {}", session_id);
 
-    // Use unified sessions directory function (supports WSL)
-    let sessions_dir = get_codex_sessions_dir()?;
-
-    // Find the session file
-    let session_file = find_session_file(&sessions_dir, &session_id)?;
+    // Find the session file (supports WSL and per-project distros)
+    let session_file = find_session_file_in_all_dirs(&session_id).map_err(AnyCodeError::Io)?;
 
     // Delete the file
     std::fs::remove_file(&session_file)
-        .map_err(|e| format!("Failed to delete session file: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to delete session file: {}", e)))?;
 
     log::info!("Successfully deleted Codex session file: {:?}", session_file);
     Ok(format!("Session {} deleted", session_id))
@@ -887,6 +1088,131 @@ pub async fn delete_codex_session(session_id: String) -> Result<String, String>
 // Helper Functions
 // ============================================================================
 
+/// Renders the prompt template and fills options not given explicitly
+/// from the project's Codex defaults
+///
+/// Runs once per request (not per timeout retry) so templates are only
+/// applied once, and the system prompt only to the first prompt of a new session.
+async fn resolve_execution_options(
+    mut options: CodexExecutionOptions,
+    is_resume: bool,
+) -> Result<CodexExecutionOptions, String> {
+    if !options.internal {
+        prompt_history::record_prompt("codex", &options.project_path, &options.prompt).await;
+        let (prompt, translation) = prompt_translation::prepare_prompt("codex", options.prompt).await;
+        options.prompt = prompt;
+        options.prompt_translation = translation;
+    }
+    if let Some(template) = options.prompt_template.take() {
+        options.prompt = apply_prompt_template("codex", options.prompt, Some(&template)).await?;
+    }
+
+    let defaults = project_defaults::resolve_engine_defaults(&options.project_path, "codex");
+    options.model = project_defaults::or_default(options.model, &defaults.model);
+    options.reasoning_mode = project_defaults::or_default(options.reasoning_mode, &defaults.reasoning_effort);
+    options.permission_profile =
+        project_defaults::or_default(options.permission_profile, &defaults.permission_profile);
+    options.provider = project_defaults::or_default(options.provider, &defaults.provider);
+    if options.provider.is_none() {
+        options.provider = pinned_distro_provider(&options.project_path);
+    }
+    options.model =
+        model_routing::resolve_model("codex", options.model, &options.prompt, options.provider.as_deref()).await;
+    options.prompt_tokens = count_text(options.model.as_deref().unwrap_or_default(), &options.prompt);
+    if options.api_key.is_none() {
+        if let Some(provider_id) = options.provider.as_deref() {
+            options.api_key = codex_provider_overrides(&load_codex_provider(provider_id)?)?.1;
+        }
+    }
+
+    if !is_resume && !options.internal {
+        // Notes are matched against the user's prompt, before any system instructions are added
+        let memory = if project_memory::should_include(options.include_memory, defaults.include_memory) {
+            project_memory::memory_for_prompt(&options.project_path, &options.prompt).await
+        } else {
+            None
+        };
+        let context = project_defaults::context_block(&options.project_path, &options.prompt, &defaults).await;
+        if let Some(template_id) = defaults.system_prompt_template.as_deref() {
+            let template = project_defaults::load_system_prompt_template(template_id).await?;
+            options.prompt = project_defaults::prepend_system_prompt(&template, &options.prompt);
+        }
+        if let Some(context) = context {
+            options.prompt = format!("{}\n\n{}", context, options.prompt);
+        }
+        if let Some(memory) = memory {
+            options.prompt = format!("{}\n\n{}", memory, options.prompt);
+        }
+    }
+    Ok(options)
+}
+
+/// Provider for a project pinned to a WSL distro other than the global one
+///
+/// Provider switches only rewrite the global ~/.codex/config.toml, so the
+/// active preset is passed explicitly to reach the distro's Codex.
+fn pinned_distro_provider(project_path: &str) -> Option<String> {
+    let project_dir = get_codex_config_dir_for_project(project_path).ok()?;
+    if Some(&project_dir) == get_codex_config_dir().ok().as_ref() {
+        return None;
+    }
+    let provider_id = active_codex_provider_id()?;
+    load_codex_provider(&provider_id).ok().map(|_| provider_id)
+}
+
+/// Builds a one-shot Codex command for backend orchestration (e.g. pipelines)
+///
+/// Resolves options like `execute_codex`; the caller spawns the command,
+/// writes the returned prompt to stdin and consumes the JSONL output itself.
+/// Internal helper runs may execute outside a git repository (e.g. a temp dir).
+pub async fn build_headless_codex_command(
+    project_path: String,
+    prompt: String,
+    model: Option<String>,
+    permission_profile: Option<String>,
+    internal: bool,
+) -> Result<(Command, Option<String>), String> {
+    let options = CodexExecutionOptions {
+        model,
+        permission_profile,
+        internal,
+        skip_git_repo_check: internal,
+        ..CodexExecutionOptions::new(project_path, prompt)
+    };
+    let options = resolve_execution_options(options, false).await?;
+    build_codex_command(&options, false, None)
+}
+
+/// `-c` config overrides for a new session (reasoning effort, provider preset)
+fn codex_config_overrides(options: &CodexExecutionOptions) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    if let Some(effort) = options.reasoning_mode.as_deref() {
+        args.push("-c".to_string());
+        args.push(format!("model_reasoning_effort=\"{}\"", effort));
+    }
+    if let Some(provider_id) = options.provider.as_deref() {
+        let (provider_args, _) = codex_provider_overrides(&load_codex_provider(provider_id)?)?;
+        args.extend(provider_args);
+    }
+    Ok(args)
+}
+
+/// Sandbox / approval flags for a new session
+/// A permission profile takes precedence over the legacy execution mode
+fn codex_permission_args(options: &CodexExecutionOptions) -> Result<Vec<String>, String> {
+    if let Some(profile_id) = options.permission_profile.as_deref() {
+        return apply_permission_profile("codex", profile_id);
+    }
+    Ok(match options.mode {
+        CodexExecutionMode::FullAuto => vec!["--full-auto".to_string()],
+        CodexExecutionMode::DangerFullAccess => {
+            vec!["--sandbox".to_string(), "danger-full-access".to_string()]
+        }
+        // Read-only is default
+        CodexExecutionMode::ReadOnly => vec![],
+    })
+}
+
 /// Builds a Codex command with the given options
 /// Returns (Command, Option<String>) where the String is the prompt to be passed via stdin
 /// Supports both native execution and WSL mode on Windows
@@ -895,10 +1221,20 @@ fn build_codex_command(
     is_resume: bool,
     session_id: Option<&str>,
 ) -> Result<(Command, Option<String>), String> {
+    // A project container runs the Linux CLI through the host's docker,
+    // bypassing the remote and WSL launch modes
+    let in_container = docker_backend::container_enabled(&options.project_path);
+
+    // Remote SSH mode takes precedence over local execution
+    if let Some(remote) = wsl_utils::get_codex_config().active_remote().filter(|_| !in_container) {
+        log::info!("[Codex] Using remote mode (host: {})", remote.destination());
+        return build_remote_codex_command(options, is_resume, session_id, remote);
+    }
+
     // Check if we should use WSL mode on Windows
     #[cfg(target_os = "windows")]
-    {
-        let wsl_config = wsl_utils::get_wsl_config();
+    if !in_container {
+        let wsl_config = wsl_utils::get_wsl_config_for_project(&options.project_path);
         if wsl_config.enabled {
             log::info!("[Codex] Using WSL mode (distro: {:?})", wsl_config.distro);
             return build_wsl_codex_command(options, is_resume, session_id, &wsl_config);
@@ -920,6 +1256,9 @@ fn build_codex_command(
         "codex".to_string()
     };
 
+    // Images can only be added as flags to a new session
+    let (prompt, image_args) = attachments::codex_attachments(&options.prompt, &options.attachments, !is_resume);
+
     let mut cmd = Command::new(&codex_cmd);
     cmd.arg("exec");
 
@@ -953,18 +1292,8 @@ fn build_codex_command(
         // For new sessions: add other options
         // (--json already added above)
 
-        match options.mode {
-            CodexExecutionMode::FullAuto => {
-                cmd.arg("--full-auto");
-            }
-            CodexExecutionMode::DangerFullAccess => {
-                cmd.arg("--sandbox");
-                cmd.arg("danger-full-access");
-            }
-            CodexExecutionMode::ReadOnly => {
-                // Read-only is default
-            }
-        }
+        cmd.args(codex_permission_args(options)?);
+        cmd.args(codex_config_overrides(options)?);
 
         if let Some(ref model) = options.model {
             cmd.arg("--model");
@@ -981,6 +1310,7 @@ fn build_codex_command(
             cmd.arg(file);
         }
 
+        cmd.args(image_args);
     }
 
     // Set working directory
@@ -1002,12 +1332,20 @@ fn build_codex_command(
 
     let prompt_for_stdin = if is_resume {
         // For resume mode, prompt is still needed but passed via stdin
-        Some(options.prompt.clone())
+        Some(prompt)
     } else {
         // For new sessions, pass prompt via stdin
-        Some(options.prompt.clone())
+        Some(prompt)
     };
 
+    // Run inside the project's container when enabled in project settings
+    let engine_args: Vec<String> = cmd
+        .as_std()
+        .get_args()
+        .map(|a| a.to_string_lossy().to_string())
+        .collect();
+    let cmd = docker_backend::wrap_command_for_project(&options.project_path, "codex", &engine_args, cmd)?;
+
     Ok((cmd, prompt_for_stdin))
 }
 
@@ -1020,6 +1358,14 @@ fn build_wsl_codex_command(
     session_id: Option<&str>,
     wsl_config: &wsl_utils::WslConfig,
 ) -> Result<(Command, Option<String>), String> {
+    // Attachments are staged on the Windows side, reference them through /mnt
+    let wsl_attachments: Vec<StagedAttachment> = options
+        .attachments
+        .iter()
+        .map(|a| StagedAttachment { path: wsl_utils::windows_to_wsl_path(&a.path), ..a.clone() })
+        .collect();
+    let (prompt, image_args) = attachments::codex_attachments(&options.prompt, &wsl_attachments, !is_resume);
+
     // Build arguments for codex command
     let mut args: Vec<String> = vec!["exec".to_string()];
 
@@ -1039,16 +1385,8 @@ fn build_wsl_codex_command(
             args.push(sid.to_string());
         }
     } else {
-        match options.mode {
-            CodexExecutionMode::FullAuto => {
-                args.push("--full-auto".to_string());
-            }
-            CodexExecutionMode::DangerFullAccess => {
-                args.push("--sandbox".to_string());
-                args.push("danger-full-access".to_string());
-            }
-            CodexExecutionMode::ReadOnly => {}
-        }
+        args.extend(codex_permission_args(options)?);
+        args.extend(codex_config_overrides(options)?);
 
         if let Some(ref model) = options.model {
             args.push("--model".to_string());
@@ -1066,6 +1404,7 @@ fn build_wsl_codex_command(
             args.push(wsl_utils::windows_to_wsl_path(file));
         }
 
+        args.extend(image_args);
     }
 
     // Add stdin indicator
@@ -1093,16 +1432,108 @@ fn build_wsl_codex_command(
         args
     );
 
+    Ok((cmd, Some(prompt)))
+}
+
+/// Builds a Codex command that runs on a remote host over SSH
+/// The prompt is passed via stdin and output is streamed back through the SSH connection
+fn build_remote_codex_command(
+    options: &CodexExecutionOptions,
+    is_resume: bool,
+    session_id: Option<&str>,
+    remote: &RemoteConfig,
+) -> Result<(Command, Option<String>), String> {
+    let mut args: Vec<String> = vec!["exec".to_string()];
+
+    // --json / --skip-git-repo-check must come before 'resume'
+    if options.json {
+        args.push("--json".to_string());
+    }
+    if options.skip_git_repo_check {
+        args.push("--skip-git-repo-check".to_string());
+    }
+
+    if is_resume {
+        args.push("resume".to_string());
+        if let Some(sid) = session_id {
+            args.push(sid.to_string());
+        }
+    } else {
+        args.extend(codex_permission_args(options)?);
+        args.extend(codex_config_overrides(options)?);
+
+        if let Some(ref model) = options.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+
+        if let Some(ref schema) = options.output_schema {
+            args.push("--output-schema".to_string());
+            args.push(schema.clone());
+        }
+
+        if let Some(ref file) = options.output_file {
+            args.push("-o".to_string());
+            args.push(file.clone());
+        }
+    }
+
+    // Add stdin indicator
+    args.push("-".to_string());
+
+    // The project may live under a different path on the remote host
+    let remote_dir = ssh_remote::remote_project_dir(&options.project_path);
+
+    let cmd = ssh_remote::build_ssh_codex_command_async(remote, &args, Some(&remote_dir));
+
+    // ssh does not forward local environment variables, so the API key must already
+    // be configured on the remote host
+    if options.api_key.is_some() {
+        log::warn!("[Codex Remote] API key is not forwarded over SSH, configure it on {}", remote.destination());
+    }
+    // Staged attachments only exist locally
+    if !options.attachments.is_empty() {
+        log::warn!(
+            "[Codex Remote] {} attachment(s) are not uploaded to {}, skipped",
+            options.attachments.len(),
+            remote.destination()
+        );
+    }
+
+    log::info!(
+        "[Codex Remote] Command built: ssh {} (cd {}) codex {:?}",
+        remote.destination(),
+        remote_dir,
+        args
+    );
+
     Ok((cmd, Some(options.prompt.clone())))
 }
 
+/// Re-runs a timed-out Codex execution (boxed to break the async recursion)
+fn retry_codex_run(
+    run: CodexRun,
+    app_handle: AppHandle,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send>> {
+    Box::pin(async move { execute_codex_process(run, app_handle).await })
+}
+
 /// Executes a Codex process and streams output to frontend
 async fn execute_codex_process(
-    mut cmd: Command,
-    prompt: Option<String>,
-    project_path: String,
+    run: CodexRun,
     app_handle: AppHandle,
 ) -> Result<(), String> {
+    // Build codex exec command
+    let (mut cmd, prompt) =
+        build_codex_command(&run.options, run.is_resume, run.resume_target.as_deref())?;
+    let project_path = run.options.project_path.clone();
+    let watchdog = Arc::new(ExecutionWatchdog::new(run.options.timeout.clone()));
+    // Failover rewrites the local config, which remote runs don't read
+    let failover_config = failover::load_failover_config();
+    let failover_timeout = (failover_config.is_active()
+        && wsl_utils::get_codex_config().active_remote().is_none())
+    .then_some(failover_config.start_timeout_secs);
+
     // Setup stdio
     cmd.stdin(Stdio::piped());   // Enable stdin to pass prompt
     cmd.stdout(Stdio::piped());
@@ -1116,6 +1547,7 @@ async fn execute_codex_process(
     let mut child = cmd
         .spawn()
         .map_err(|e| format!("Failed to spawn codex: {}", e))?;
+    let started = Instant::now();
 
     // FIX: Write prompt to stdin if provided
     // This avoids command line length limits and special character issues
@@ -1152,6 +1584,13 @@ async fn execute_codex_process(
     super::change_tracker::init_change_tracker(&session_id, &project_path);
     log::info!("[ChangeTracker] Initialized for session: {}", session_id);
 
+    // Persist PID so a crashed app can detect the orphan on next launch
+    let tracked_pid = child.id();
+    if let Some(pid) = tracked_pid {
+        crate::process::orphans::track_process(pid, "codex", Some(&session_id), &project_path);
+        docker_backend::track_container_run(pid, &cmd);
+    }
+
     // Store process in state
     let state: tauri::State<'_, CodexProcessState> = app_handle.state();
     {
@@ -1163,7 +1602,6 @@ async fn execute_codex_process(
     }
 
     // Clone handles for async tasks
-    let app_handle_stdout = app_handle.clone();
     let app_handle_stderr = app_handle.clone();
     let app_handle_complete = app_handle.clone();
     let session_id_stdout = session_id.clone();  // Clone for stdout task
@@ -1174,39 +1612,76 @@ async fn execute_codex_process(
     // This event is sent on the global channel, frontend will use this to switch to session-specific listeners
     let init_payload = serde_json::json!({
         "type": "session_init",
-        "session_id": session_id
+        "session_id": session_id,
+        "attempt": run.attempt
     });
     if let Err(e) = app_handle.emit("codex-session-init", init_payload) {
         log::error!("Failed to emit codex-session-init: {}", e);
     }
     log::info!("Codex session initialized with ID: {}", session_id);
+    attachments::record_attachments("codex", &session_id, &run.options.attachments);
+
+    // Tee raw output to ~/.anycode/logs/codex/<session_id>.log
+    let session_log = crate::commands::session_log::SessionLogWriter::open("codex", &session_id);
+    let session_log_stdout = session_log.clone();
+    let session_log_stderr = session_log;
+    let command_auditor = crate::commands::command_audit::CommandAuditor::new("codex", &session_id, &project_path);
+    let command_snapshotter = super::change_tracker::CommandSnapshotter::new(&session_id, &project_path);
+    // Timeout retries, failovers and rate-limit requeues resend the same prompt; its translation is only recorded once
+    let prompt_record = run
+        .options
+        .prompt_translation
+        .clone()
+        .filter(|_| run.attempt == 0 && run.tried_providers.is_empty() && run.requeues == 0);
+    let response_translator = ResponseTranslator::new(&app_handle, "codex", &session_id, prompt_record).await;
+    let stream_metrics = StreamMetrics::new("codex", &session_id, run.options.provider.as_deref()).await;
+    let stream_metrics_stdout = stream_metrics.clone();
+    let stream_metrics_stderr = stream_metrics.clone();
+    let rate_limit_watcher = RateLimitWatcher::new(&app_handle, "codex", &session_id);
+    let rate_limit_stdout = rate_limit_watcher.clone();
+    let rate_limit_stderr = rate_limit_watcher.clone();
 
     // Spawn task to read stdout (JSONL events)
     // FIX: Emit to both session-specific and global channels for proper multi-tab isolation
+    let watchdog_stdout = watchdog.clone();
+    let output_batcher = OutputBatcher::new(&app_handle, "codex-output").await;
+    let output_batcher_complete = output_batcher.clone();
     tokio::spawn(async move {
         let mut reader = BufReader::new(stdout).lines();
         while let Ok(Some(line)) = reader.next_line().await {
+            watchdog_stdout.touch();
+            if let Some(log) = &session_log_stdout {
+                log.write_line("stdout", &line);
+            }
+            command_auditor.observe(&line);
+            // Snapshot files a command may touch before its start event reaches the frontend
+            command_snapshotter.observe(&line).await;
+            response_translator.observe(&line);
+            stream_metrics_stdout.observe(&line);
+            rate_limit_stdout.observe(&line);
             if !line.trim().is_empty() {
                 log::debug!("Codex output: {}", line);
-                // Emit to session-specific channel first (for multi-tab isolation)
-                if let Err(e) = app_handle_stdout.emit(&format!("codex-output:{}", session_id_stdout), &line) {
-                    log::error!("Failed to emit codex-output (session-specific): {}", e);
-                }
-                // Also emit to global channel for backward compatibility
-                if let Err(e) = app_handle_stdout.emit("codex-output", &line) {
-                    log::error!("Failed to emit codex-output (global): {}", e);
-                }
+                // Session-specific channel (for multi-tab isolation) plus the global channel for backward compatibility
+                output_batcher.push(Some(&session_id_stdout), line);
             }
         }
+        output_batcher.flush();
     });
 
     // Spawn task to read stderr (log errors, suppress debug output)
+    let watchdog_stderr = watchdog.clone();
     tokio::spawn(async move {
         let mut reader = BufReader::new(stderr).lines();
         while let Ok(Some(line)) = reader.next_line().await {
+            watchdog_stderr.touch();
+            if let Some(log) = &session_log_stderr {
+                log.write_line("stderr", &line);
+            }
             // Log error messages for debugging
             if !line.trim().is_empty() {
                 log::warn!("Codex stderr: {}", line);
+                stream_metrics_stderr.observe_stderr(&line);
+                rate_limit_stderr.observe_stderr(&line);
 
                 // Emit stderr lines so frontend can surface failures (e.g., git/trust checks)
                 if let Err(e) = app_handle_stderr.emit(
@@ -1228,10 +1703,13 @@ async fn execute_codex_process(
     // FIX: Use polling with try_wait() instead of removing process before wait()
     // This ensures the process stays in the HashMap while running, allowing cancel_codex to find and kill it
     tokio::spawn(async move {
+        let mut run = run;
         let state: tauri::State<'_, CodexProcessState> = app_handle_complete.state();
 
         // Poll for process completion without removing it from the HashMap
         // This allows cancel_codex to find and kill the process at any time
+        let mut timed_out = None;
+        let mut failover_reason = None;
         let exit_status: Option<std::process::ExitStatus> = loop {
             let mut processes = state.processes.lock().await;
 
@@ -1243,6 +1721,41 @@ async fn execute_codex_process(
                         break Some(status);
                     }
                     Ok(None) => {
+                        // Kill the whole process tree if the watchdog fired
+                        if let Some(kind) = watchdog.check() {
+                            log::warn!("Codex session {} timed out ({:?}), killing process tree", session_id_complete, kind);
+                            if let Some(pid) = child.id() {
+                                if let Err(e) = kill_process_tree(pid) {
+                                    log::warn!("Failed to kill Codex process tree {}: {}", pid, e);
+                                }
+                            }
+                            let _ = child.start_kill();
+                            processes.remove(&session_id_complete);
+                            timed_out = Some(kind);
+                            break None;
+                        }
+
+                        // The provider keeps failing before any output (Codex retries internally), give up on it
+                        if let Some(category) = failover_timeout
+                            .and_then(|secs| stream_metrics.stalled_error(secs))
+                            .filter(|c| failover::is_failover_error(*c))
+                        {
+                            log::warn!(
+                                "Codex session {} still failing ({}) before streaming, killing process tree for failover",
+                                session_id_complete,
+                                category.as_str()
+                            );
+                            if let Some(pid) = child.id() {
+                                if let Err(e) = kill_process_tree(pid) {
+                                    log::warn!("Failed to kill Codex process tree {}: {}", pid, e);
+                                }
+                            }
+                            let _ = child.start_kill();
+                            processes.remove(&session_id_complete);
+                            failover_reason = Some(category);
+                            break None;
+                        }
+
                         // Process still running, release lock and wait before polling again
                         drop(processes);
                         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
@@ -1260,19 +1773,175 @@ async fn execute_codex_process(
             }
         };
 
+        if let Some(pid) = tracked_pid {
+            crate::process::orphans::untrack_process(pid);
+            docker_backend::release_container_run(pid);
+        }
+
+        // Refresh the local mirror so history/rewind see the new session events
+        if let Some(remote) = wsl_utils::get_codex_config().active_remote().cloned() {
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = ssh_remote::sync_remote_sessions(&remote, true) {
+                    log::warn!("[Codex Remote] Failed to sync sessions after run: {}", e);
+                }
+            });
+        }
+
         if let Some(status) = exit_status {
             log::info!("Codex process exited with status: {}", status);
         }
 
+        // Exiting with an auth / connection / rate-limit error before any output also fails over
+        if failover_reason.is_none() && failover_timeout.is_some() && exit_status.is_some_and(|s| !s.success()) {
+            failover_reason = stream_metrics.startup_error().filter(|c| failover::is_failover_error(*c));
+        }
+
+        // Cancelled runs say nothing about the provider and are not recorded
+        if let Some(category) = failover_reason {
+            stream_metrics.finish(Some(category));
+        } else if timed_out.is_some() {
+            stream_metrics.finish(Some(ErrorCategory::Timeout));
+        } else if let Some(status) = exit_status {
+            stream_metrics.finish((!status.success()).then_some(ErrorCategory::Other));
+        }
+
+        // Reasoning effort statistics skip provider failures (failover, rate limits)
+        let reasoning_outcome = match (failover_reason, timed_out, exit_status) {
+            (Some(_), _, _) => None,
+            (None, Some(_), _) => Some(RunOutcome::TimedOut),
+            (None, None, Some(status)) if status.success() => Some(RunOutcome::Completed),
+            (None, None, Some(_)) => rate_limit_watcher.signal().is_none().then_some(RunOutcome::Failed),
+            (None, None, None) => Some(RunOutcome::Cancelled),
+        };
+        if let Some(outcome) = reasoning_outcome {
+            let options = run.options.clone();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            tokio::spawn(async move {
+                reasoning_stats::record_run(
+                    &options.project_path,
+                    options.model.as_deref(),
+                    options.reasoning_mode.as_deref(),
+                    options.prompt_tokens,
+                    duration_ms,
+                    outcome,
+                )
+                .await;
+            });
+        }
+
+        // Explain timeouts before the completion event so the UI can show why the run stopped
+        let will_retry = timed_out.is_some() && watchdog.should_retry(run.attempt);
+        if let Some(kind) = timed_out {
+            let payload = watchdog.timeout_payload(kind, &session_id_complete, run.attempt, will_retry);
+            if let Err(e) = app_handle_complete.emit(&format!("codex-timeout:{}", session_id_complete), &payload) {
+                log::error!("Failed to emit codex-timeout (session-specific): {}", e);
+            }
+            if let Err(e) = app_handle_complete.emit("codex-timeout", &payload) {
+                log::error!("Failed to emit codex-timeout (global): {}", e);
+            }
+        }
+
+        // Switch providers before the completion event so the UI can explain the retry
+        let failed_over = match failover_reason {
+            Some(reason) => failover::fail_over(
+                &app_handle_complete,
+                &session_id_complete,
+                &mut run.options,
+                &mut run.tried_providers,
+                stream_metrics.provider_id(),
+                reason,
+            )
+            .await
+            .is_some(),
+            None => false,
+        };
+
         // Emit completion event
         // FIX: Emit to both session-specific and global channels for proper multi-tab isolation
-        if let Err(e) = app_handle_complete.emit(&format!("codex-complete:{}", session_id_complete), true) {
+        // Buffered output goes out first so the completion event is always last
+        output_batcher_complete.flush();
+        let success = timed_out.is_none() && failover_reason.is_none();
+        if let Err(e) = app_handle_complete.emit(&format!("codex-complete:{}", session_id_complete), success) {
             log::error!("Failed to emit codex-complete (session-specific): {}", e);
         }
         // Also emit to global channel for backward compatibility
-        if let Err(e) = app_handle_complete.emit("codex-complete", true) {
+        if let Err(e) = app_handle_complete.emit("codex-complete", success) {
             log::error!("Failed to emit codex-complete (global): {}", e);
         }
+
+        // Cancelled runs (no exit status) and runs that are about to be retried are not notified
+        let outcome = if timed_out.is_some() || failover_reason.is_some() {
+            Some(false)
+        } else {
+            exit_status.map(|status| status.success())
+        };
+        let mut retrying = failed_over || will_retry;
+
+        if failed_over {
+            log::info!("Retrying Codex execution after failover ({} provider(s) tried)", run.tried_providers.len());
+            if let Err(e) = retry_codex_run(run, app_handle_complete.clone()).await {
+                log::error!("Codex failover retry failed to start: {}", e);
+            }
+        } else if will_retry {
+            let mut next = run;
+            next.attempt += 1;
+            log::info!("Retrying Codex execution (attempt {}/{})", next.attempt, next.options.timeout.max_retries);
+            if let Err(e) = retry_codex_run(next, app_handle_complete.clone()).await {
+                log::error!("Codex retry failed to start: {}", e);
+            }
+        } else if exit_status.is_some_and(|s| !s.success()) || failover_reason.is_some() {
+            // Rate limited with no provider left to fail over to: resubmit after the backoff
+            if let Some(signal) = rate_limit_watcher.signal() {
+                let requeues = run.requeues;
+                let mut next = run;
+                next.requeues += 1;
+                let app = app_handle_complete.clone();
+                retrying = rate_limit::schedule_requeue(
+                    &app_handle_complete,
+                    "codex",
+                    &rate_limit_watcher.session_id(),
+                    &rate_limit_watcher.channel_id(),
+                    &signal,
+                    requeues,
+                    retry_codex_run(next, app),
+                );
+            }
+        } else if outcome == Some(true) {
+            // Auto-fix loop: verify, then resume the thread with the failure output
+            let thread_id = rate_limit_watcher.session_id();
+            if let Some(options) = run.options.auto_fix.clone().filter(|_| thread_id != session_id_complete) {
+                let project_path = run.options.project_path.clone();
+                let iteration = run.auto_fix_iteration;
+                let app = app_handle_complete.clone();
+                let target = thread_id.clone();
+                retrying = auto_fix::schedule_auto_fix(
+                    &app_handle_complete,
+                    "codex",
+                    &thread_id,
+                    &project_path,
+                    &options,
+                    iteration,
+                    move |prompt| {
+                        let mut next = run;
+                        next.options.prompt_tokens =
+                            count_text(next.options.model.as_deref().unwrap_or_default(), &prompt);
+                        next.options.prompt = prompt;
+                        next.options.attachments.clear();
+                        next.options.prompt_translation = None;
+                        next.is_resume = true;
+                        next.resume_target = Some(target);
+                        next.attempt = 0;
+                        next.requeues = 0;
+                        next.auto_fix_iteration += 1;
+                        retry_codex_run(next, app)
+                    },
+                );
+            }
+        }
+
+        if let (Some(success), false) = (outcome, retrying) {
+            notifications::notify_run_finished(&app_handle_complete, "codex", &session_id_complete, success);
+        }
     });
 
     Ok(())
@@ -14,6 +14,7 @@
  * - 仅支持已完成的 Session 转换
  */
 
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -688,7 +689,8 @@ impl ClaudeToCodexConverter {
 
     /// 写入 Codex session 文件
     fn write_codex_session(&self, events: &[CodexEvent]) -> Result<String, String> {
-        let sessions_dir = super::config::get_codex_sessions_dir()
+        // 项目固定了 WSL 发行版时写入该发行版的会话目录，Codex 才能在该项目中恢复
+        let sessions_dir = super::config::get_codex_sessions_dir_for_project(&self.project_path)
             .map_err(|e| format!("Failed to get Codex sessions directory: {}", e))?;
 
         // 创建日期目录结构 YYYY/MM/DD
@@ -901,11 +903,8 @@ impl CodexToClaudeConverter {
 
     /// 读取 Codex session 文件
     fn read_codex_session(&self) -> Result<Vec<CodexEvent>, String> {
-        let sessions_dir = super::config::get_codex_sessions_dir()
-            .map_err(|e| format!("Failed to get Codex sessions directory: {}", e))?;
-
-        // 使用 codex/session.rs 中的 find_session_file 函数
-        let session_path = super::session::find_session_file(&sessions_dir, &self.source_session_id)?;
+        // 在所有会话目录中查找（含项目级 WSL 发行版）
+        let session_path = super::session::find_session_file_in_all_dirs(&self.source_session_id)?;
 
         let file = std::fs::File::open(&session_path)
             .map_err(|e| format!("Failed to open session file: {}", e))?;
@@ -1188,10 +1187,8 @@ impl CodexToClaudeConverter {
 /// 根据文件存在性判断 session 的源引擎类型
 fn detect_session_engine(session_id: &str, project_id: &str) -> Result<String, String> {
     // 1. 检查是否为 Codex session（查找 sessions 目录）
-    if let Ok(sessions_dir) = super::config::get_codex_sessions_dir() {
-        if super::session::find_session_file(&sessions_dir, session_id).is_ok() {
-            return Ok("codex".to_string());
-        }
+    if super::session::find_session_file_in_all_dirs(session_id).is_ok() {
+        return Ok("codex".to_string());
     }
 
     // 2. 检查是否为 Claude session（查找 projects 目录）
@@ -1215,7 +1212,7 @@ pub async fn convert_session(
     target_engine: String,
     project_id: String,
     project_path: String,
-) -> Result<ConversionResult, String> {
+) -> Result<ConversionResult, AnyCodeError> {
     log::info!(
         "Converting session {} to engine: {}, project_id: {}, project_path: {}",
         session_id,
@@ -1225,25 +1222,25 @@ pub async fn convert_session(
     );
 
     // 根据文件存在性检测源引擎
-    let source_engine = detect_session_engine(&session_id, &project_id)?;
+    let source_engine = detect_session_engine(&session_id, &project_id).map_err(AnyCodeError::NotFound)?;
 
     if source_engine == target_engine {
-        return Err(format!(
+        return Err(AnyCodeError::Io(format!(
             "Session {} is already a {} session",
             session_id, target_engine
-        ));
+        )));
     }
 
     match target_engine.as_str() {
         "codex" => {
             let converter = ClaudeToCodexConverter::new(session_id, project_id, project_path);
-            converter.convert()
+            converter.convert().map_err(AnyCodeError::Io)
         }
         "claude" => {
             let converter = CodexToClaudeConverter::new(session_id, project_id, project_path);
-            converter.convert()
+            converter.convert().map_err(AnyCodeError::Io)
         }
-        _ => Err(format!("Unknown target engine: {}", target_engine)),
+        _ => Err(AnyCodeError::Validation(format!("Unknown target engine: {}", target_engine))),
     }
 }
 
@@ -1253,7 +1250,7 @@ pub async fn convert_claude_to_codex(
     session_id: String,
     project_id: String,
     project_path: String,
-) -> Result<ConversionResult, String> {
+) -> Result<ConversionResult, AnyCodeError> {
     convert_session(session_id, "codex".to_string(), project_id, project_path).await
 }
 
@@ -1263,6 +1260,6 @@ pub async fn convert_codex_to_claude(
     session_id: String,
     project_id: String,
     project_path: String,
-) -> Result<ConversionResult, String> {
+) -> Result<ConversionResult, AnyCodeError> {
     convert_session(session_id, "claude".to_string(), project_id, project_path).await
 }
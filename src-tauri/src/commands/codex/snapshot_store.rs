@@ -0,0 +1,403 @@
+//! 非 Git 项目的文件快照（用于提示词级别的代码回滚）
+//!
+//! 项目不是 Git 仓库时，每次发送提示词前为工作区生成一份快照清单（路径 → 内容哈希），
+//! 文件内容按 SHA256 存入 `~/.anycode/snapshots/objects`，相同内容只存一份。
+//! 生成清单时复用上一份清单中大小和修改时间都未变的文件，只读取发生变化的文件。
+//!
+//! 回滚时把工作区恢复为清单中的状态：内容不同的文件先把当前版本移入回收站再写回，
+//! 清单中没有的文件移入回收站，回滚前的内容都可以从回收站找回。
+//! 超过大小上限的文件记录在 `skipped` 中，回滚时不会改动。
+//!
+//! 清单由 Codex 的 Git 记录（`snapshot_id`）引用，不再被引用的清单和内容对象由保留策略清理（见 `prune_snapshots`）。
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::super::data_root::anycode_dir;
+use super::super::trash;
+
+/// 单个文件的快照上限，更大的文件不参与快照和回滚
+const MAX_SNAPSHOT_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// 单个项目最多快照的文件数，超过时放弃快照（此时无法回滚代码）
+const MAX_SNAPSHOT_FILES: usize = 20_000;
+const SKIPPED_DIRS: [&str; 7] = ["node_modules", "target", "dist", "build", "vendor", "__pycache__", "venv"];
+/// 清理时跳过最近写入的清单和对象，避免删掉正在创建、尚未写入 Git 记录的快照
+const PRUNE_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 快照中的文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFile {
+    /// 内容的 SHA256
+    pub hash: String,
+    pub size: u64,
+    pub modified_ms: u128,
+}
+
+/// 工作区快照清单
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotManifest {
+    pub project_path: String,
+    pub created_at: String,
+    /// 项目相对路径（'/' 分隔）→ 文件
+    pub files: BTreeMap<String, SnapshotFile>,
+    /// 超过大小上限、未保存内容的文件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+}
+
+/// 快照恢复结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRestoreReport {
+    pub restored: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// 快照清理结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotPruneReport {
+    pub manifests: usize,
+    pub objects: usize,
+    pub freed_bytes: u64,
+}
+
+fn store_root() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("snapshots"))
+}
+
+fn object_path(root: &Path, hash: &str) -> PathBuf {
+    root.join("objects").join(&hash[..2]).join(&hash[2..])
+}
+
+fn manifest_path(root: &Path, id: &str) -> PathBuf {
+    root.join("manifests").join(format!("{}.json", id))
+}
+
+fn modified_ms(metadata: &fs::Metadata) -> u128 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+/// 项目中参与快照的文件：(相对路径, 完整路径, 元数据)
+fn scan_project(project_root: &Path) -> Result<Vec<(String, PathBuf, fs::Metadata)>, String> {
+    let mut files = Vec::new();
+    let walker = walkdir::WalkDir::new(project_root).into_iter().filter_entry(|e| {
+        let name = e.file_name().to_string_lossy();
+        e.depth() == 0 || !(e.file_type().is_dir() && (name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref())))
+    });
+    for entry in walker.filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let Ok(relative) = entry.path().strip_prefix(project_root) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if files.len() >= MAX_SNAPSHOT_FILES {
+            return Err(format!("项目文件超过 {} 个，无法创建快照", MAX_SNAPSHOT_FILES));
+        }
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        files.push((relative, entry.path().to_path_buf(), metadata));
+    }
+    Ok(files)
+}
+
+/// 写入内容对象（已存在时跳过），返回哈希
+fn store_object(root: &Path, content: &[u8]) -> Result<String, String> {
+    let hash = format!("{:x}", Sha256::digest(content));
+    let path = object_path(root, &hash);
+    if !path.exists() {
+        let parent = path.parent().ok_or("快照路径无效")?;
+        fs::create_dir_all(parent).map_err(|e| format!("创建快照目录失败: {}", e))?;
+        let temp = parent.join(format!("{}.{}.tmp", &hash[2..], uuid::Uuid::new_v4()));
+        fs::write(&temp, content).map_err(|e| format!("写入快照失败: {}", e))?;
+        fs::rename(&temp, &path).map_err(|e| format!("写入快照失败: {}", e))?;
+    }
+    Ok(hash)
+}
+
+fn create_manifest_in(
+    root: &Path,
+    project_root: &Path,
+    previous: Option<&SnapshotManifest>,
+) -> Result<SnapshotManifest, String> {
+    let mut manifest = SnapshotManifest {
+        project_path: project_root.to_string_lossy().to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+    let mut stored = 0;
+    for (relative, path, metadata) in scan_project(project_root)? {
+        let size = metadata.len();
+        if size > MAX_SNAPSHOT_FILE_BYTES {
+            manifest.skipped.push(relative);
+            continue;
+        }
+        let modified_ms = modified_ms(&metadata);
+        let unchanged = previous
+            .and_then(|p| p.files.get(&relative))
+            .filter(|f| f.size == size && f.modified_ms == modified_ms && object_path(root, &f.hash).exists());
+        let file = match unchanged {
+            Some(file) => file.clone(),
+            None => {
+                let Ok(content) = fs::read(&path) else {
+                    continue;
+                };
+                stored += 1;
+                SnapshotFile { hash: store_object(root, &content)?, size, modified_ms }
+            }
+        };
+        manifest.files.insert(relative, file);
+    }
+    log::info!(
+        "[Codex Snapshot] Snapshot of {} files ({} read, {} skipped) for {}",
+        manifest.files.len(),
+        stored,
+        manifest.skipped.len(),
+        manifest.project_path
+    );
+    Ok(manifest)
+}
+
+fn save_manifest_in(root: &Path, manifest: &SnapshotManifest) -> Result<String, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let path = manifest_path(root, &id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建快照目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string(manifest).map_err(|e| format!("序列化快照清单失败: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("写入快照清单失败: {}", e))?;
+    Ok(id)
+}
+
+fn load_manifest_in(root: &Path, id: &str) -> Result<SnapshotManifest, String> {
+    let content = fs::read_to_string(manifest_path(root, id)).map_err(|e| format!("快照 {} 不存在: {}", id, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析快照清单失败: {}", e))
+}
+
+fn restore_manifest_in(
+    root: &Path,
+    trash_root: &Path,
+    project_root: &Path,
+    manifest: &SnapshotManifest,
+) -> Result<SnapshotRestoreReport, String> {
+    // 先确认所有内容对象都在，避免恢复到一半
+    if let Some((path, _)) = manifest.files.iter().find(|(_, f)| !object_path(root, &f.hash).exists()) {
+        return Err(format!("快照内容缺失: {}", path));
+    }
+
+    let mut report = SnapshotRestoreReport::default();
+    let mut current: BTreeMap<String, (PathBuf, fs::Metadata)> = scan_project(project_root)?
+        .into_iter()
+        .map(|(relative, path, metadata)| (relative, (path, metadata)))
+        .collect();
+
+    for (relative, file) in &manifest.files {
+        if let Some((path, metadata)) = current.remove(relative) {
+            if metadata.len() == file.size && modified_ms(&metadata) == file.modified_ms {
+                continue;
+            }
+            let hash = fs::read(&path).map(|c| format!("{:x}", Sha256::digest(&c))).ok();
+            if hash.as_deref() == Some(file.hash.as_str()) {
+                continue;
+            }
+        }
+        let content = fs::read(object_path(root, &file.hash)).map_err(|e| format!("读取快照内容失败: {}", e))?;
+        let target = project_root.join(relative);
+        if target.exists() {
+            // 被覆盖的当前版本进入回收站
+            trash::trash_path_in(trash_root, project_root, &target, Some("snapshot"))
+                .map_err(|e| format!("备份文件 {} 失败: {}", relative, e))?;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        fs::write(&target, content).map_err(|e| format!("写入文件 {} 失败: {}", relative, e))?;
+        report.restored.push(relative.clone());
+    }
+
+    // 快照之后新建的文件
+    for (relative, (path, metadata)) in current {
+        if manifest.skipped.contains(&relative) || metadata.len() > MAX_SNAPSHOT_FILE_BYTES {
+            continue;
+        }
+        trash::trash_path_in(trash_root, project_root, &path, Some("snapshot"))
+            .map_err(|e| format!("删除文件 {} 失败: {}", relative, e))?;
+        report.removed.push(relative);
+    }
+    Ok(report)
+}
+
+fn is_stale(metadata: &fs::Metadata, before: SystemTime) -> bool {
+    metadata.modified().is_ok_and(|t| t < before)
+}
+
+/// 删除不在 `referenced` 中且早于 `before` 的清单，再删除不被剩余清单引用、且早于 `before` 的内容对象
+fn prune_in(root: &Path, referenced: &HashSet<String>, before: SystemTime, dry_run: bool) -> SnapshotPruneReport {
+    let mut report = SnapshotPruneReport::default();
+    let mut kept_hashes = HashSet::new();
+    let manifests = fs::read_dir(root.join("manifests")).into_iter().flatten().filter_map(|e| e.ok());
+    for entry in manifests {
+        let path = entry.path();
+        let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if referenced.contains(&id) || !is_stale(&metadata, before) {
+            match load_manifest_in(root, &id) {
+                Ok(manifest) => kept_hashes.extend(manifest.files.into_values().map(|f| f.hash)),
+                // 无法解析的清单保留，它引用的对象也无从判断，放弃清理对象
+                Err(_) if referenced.contains(&id) => return report,
+                Err(_) => {}
+            }
+            continue;
+        }
+        if dry_run || fs::remove_file(&path).is_ok() {
+            report.manifests += 1;
+            report.freed_bytes += metadata.len();
+        }
+    }
+
+    let objects = walkdir::WalkDir::new(root.join("objects")).min_depth(2).max_depth(2);
+    for entry in objects.into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let (Some(dir), Some(name)) = (
+            entry.path().parent().and_then(|p| p.file_name()),
+            entry.path().file_name(),
+        ) else {
+            continue;
+        };
+        let hash = format!("{}{}", dir.to_string_lossy(), name.to_string_lossy());
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if kept_hashes.contains(&hash) || !is_stale(&metadata, before) {
+            continue;
+        }
+        if dry_run || fs::remove_file(entry.path()).is_ok() {
+            report.objects += 1;
+            report.freed_bytes += metadata.len();
+        }
+    }
+    report
+}
+
+/// 清理不再被 Git 记录引用的快照（`referenced` 为仍在使用的快照 ID），`dry_run` 时只统计
+pub fn prune_snapshots(referenced: &HashSet<String>, dry_run: bool) -> Result<SnapshotPruneReport, String> {
+    let root = store_root()?;
+    let report = prune_in(&root, referenced, SystemTime::now() - PRUNE_GRACE, dry_run);
+    if !dry_run && report.manifests + report.objects > 0 {
+        log::info!(
+            "[Codex Snapshot] Pruned {} manifests and {} objects ({} bytes)",
+            report.manifests,
+            report.objects,
+            report.freed_bytes
+        );
+    }
+    Ok(report)
+}
+
+/// 为项目创建快照，返回快照 ID；`previous` 为同一会话上一次的快照，用于跳过未变化的文件
+pub fn create_snapshot(project_path: &str, previous: Option<&str>) -> Result<String, String> {
+    let root = store_root()?;
+    let previous = previous.and_then(|id| load_manifest_in(&root, id).ok());
+    let manifest = create_manifest_in(&root, Path::new(project_path), previous.as_ref())?;
+    save_manifest_in(&root, &manifest)
+}
+
+/// 把项目恢复为快照中的状态（项目路径取自快照清单）
+pub fn restore_snapshot(snapshot_id: &str) -> Result<SnapshotRestoreReport, String> {
+    let root = store_root()?;
+    let manifest = load_manifest_in(&root, snapshot_id)?;
+    // 回收站按规范化的项目路径归组
+    let project_root = fs::canonicalize(&manifest.project_path)
+        .map_err(|e| format!("项目目录无效 {}: {}", manifest.project_path, e))?;
+    let report = restore_manifest_in(&root, &trash::get_trash_root()?, &project_root, &manifest)?;
+    log::info!(
+        "[Codex Snapshot] Restored snapshot {} ({} restored, {} removed)",
+        snapshot_id,
+        report.restored.len(),
+        report.removed.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_and_restores_project() {
+        let store = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let (root, dir) = (store.path(), project.path());
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("node_modules/x")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join("README.md"), "hi\n").unwrap();
+        fs::write(dir.join("node_modules/x/index.js"), "ignored").unwrap();
+
+        let first = create_manifest_in(root, dir, None).unwrap();
+        assert_eq!(first.files.keys().collect::<Vec<_>>(), vec!["README.md", "src/lib.rs"]);
+        let id = save_manifest_in(root, &first).unwrap();
+        let loaded = load_manifest_in(root, &id).unwrap();
+        // 未变化的文件复用上一份清单
+        assert_eq!(create_manifest_in(root, dir, Some(&loaded)).unwrap().files, first.files);
+
+        fs::write(dir.join("src/lib.rs"), "fn b() {}\n").unwrap();
+        fs::remove_file(dir.join("README.md")).unwrap();
+        fs::write(dir.join("src/new.rs"), "new\n").unwrap();
+        let trash_root = store.path().join("trash");
+        let report = restore_manifest_in(root, &trash_root, dir, &loaded).unwrap();
+        assert_eq!(report.restored, vec!["README.md", "src/lib.rs"]);
+        assert_eq!(report.removed, vec!["src/new.rs"]);
+        // 删除的文件和被覆盖的 src/lib.rs 都进入回收站
+        assert!(!dir.join("src/new.rs").exists());
+        let trashed = walkdir::WalkDir::new(&trash_root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.file_name() == "data")
+            .map(|e| fs::read_to_string(e.path()).unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(trashed, HashSet::from(["new\n".to_string(), "fn b() {}\n".to_string()]));
+        assert_eq!(fs::read_to_string(dir.join("src/lib.rs")).unwrap(), "fn a() {}\n");
+        assert!(dir.join("node_modules/x/index.js").exists());
+        assert_eq!(restore_manifest_in(root, &trash_root, dir, &loaded).unwrap(), SnapshotRestoreReport::default());
+    }
+
+    #[test]
+    fn prunes_unreferenced_snapshots() {
+        let store = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let (root, dir) = (store.path(), project.path());
+        fs::write(dir.join("a.txt"), "shared\n").unwrap();
+        let kept = save_manifest_in(root, &create_manifest_in(root, dir, None).unwrap()).unwrap();
+        fs::write(dir.join("b.txt"), "only in old\n").unwrap();
+        let old = save_manifest_in(root, &create_manifest_in(root, dir, None).unwrap()).unwrap();
+        let referenced = HashSet::from([kept.clone()]);
+
+        // 宽限期内的快照不清理
+        let before = SystemTime::now() - PRUNE_GRACE;
+        assert_eq!(prune_in(root, &referenced, before, false), SnapshotPruneReport::default());
+
+        let later = SystemTime::now() + Duration::from_secs(60);
+        let preview = prune_in(root, &referenced, later, true);
+        assert_eq!((preview.manifests, preview.objects), (1, 1));
+        assert!(manifest_path(root, &old).exists());
+        assert_eq!(prune_in(root, &referenced, later, false), preview);
+        assert!(!manifest_path(root, &old).exists());
+        let kept = load_manifest_in(root, &kept).unwrap();
+        assert!(kept.files.values().all(|f| object_path(root, &f.hash).exists()));
+    }
+}
@@ -0,0 +1,453 @@
+//! 命令审计日志
+//!
+//! 从各引擎的流式输出中提取 Agent 执行的 shell 命令，写入 agents.db 的
+//! `command_audit` 表（时间、会话、工作目录、退出状态），便于在授予宽权限后做安全审查。
+//!
+//! - Codex: `item.started` / `item.completed`（`command_execution`）
+//! - Claude: `Bash` 工具的 `tool_use` / `tool_result`
+//! - Gemini: `run_shell_command` 工具的 `tool_use` / `tool_result`
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+
+/// 搜索默认返回条数
+const DEFAULT_SEARCH_LIMIT: u32 = 200;
+
+/// 一条执行过的命令
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutedCommand {
+    pub id: i64,
+    pub engine: String,
+    pub session_id: String,
+    pub call_id: String,
+    pub command: String,
+    pub cwd: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub exit_code: Option<i32>,
+    /// "running" | "success" | "error"
+    pub status: String,
+}
+
+/// 从单条流事件中提取出的命令事件
+#[derive(Debug, Clone, PartialEq)]
+enum CommandEvent {
+    Started {
+        call_id: String,
+        command: String,
+        cwd: Option<String>,
+    },
+    Finished {
+        call_id: String,
+        /// Codex 的 item.completed 会再次携带命令
+        command: Option<String>,
+        exit_code: Option<i32>,
+        success: bool,
+    },
+}
+
+/// 创建命令审计表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS command_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            engine TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            call_id TEXT NOT NULL,
+            command TEXT NOT NULL,
+            cwd TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            finished_at TEXT,
+            exit_code INTEGER,
+            status TEXT NOT NULL DEFAULT 'running',
+            UNIQUE (engine, session_id, call_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_command_audit_session
+            ON command_audit(session_id, started_at);",
+    )
+    .map_err(|e| format!("创建命令审计表失败: {}", e))
+}
+
+fn open_audit_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+// ============================================================================
+// 流事件解析
+// ============================================================================
+
+static CLAUDE_EXIT_CODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^Exit code (-?\d+)").unwrap());
+
+/// 事件中携带的真实 CLI 会话 ID
+pub(crate) fn extract_session_id(engine: &str, event: &Value) -> Option<String> {
+    let id = match engine {
+        "codex" if event["type"] == "thread.started" => event["thread_id"].as_str(),
+        "claude" if event["type"] == "system" && event["subtype"] == "init" => event["session_id"].as_str(),
+        "gemini" if event["type"] == "init" => event["session_id"].as_str(),
+        _ => None,
+    };
+    id.filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// Claude tool_result 的文本内容（字符串或文本块数组）
+fn claude_result_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn extract_command_events(engine: &str, event: &Value) -> Vec<CommandEvent> {
+    match engine {
+        "codex" => {
+            let item = &event["item"];
+            if item["type"] != "command_execution" {
+                return Vec::new();
+            }
+            let call_id = item["id"].as_str().unwrap_or_default().to_string();
+            let command = item["command"].as_str().unwrap_or_default().to_string();
+            match event["type"].as_str() {
+                Some("item.started") => vec![CommandEvent::Started { call_id, command, cwd: None }],
+                Some("item.completed") => {
+                    let exit_code = item["exit_code"].as_i64().map(|c| c as i32);
+                    vec![CommandEvent::Finished {
+                        call_id,
+                        command: Some(command),
+                        exit_code,
+                        success: item["status"] != "failed" && exit_code.unwrap_or(0) == 0,
+                    }]
+                }
+                _ => Vec::new(),
+            }
+        }
+        "claude" => {
+            let Some(blocks) = event["message"]["content"].as_array() else {
+                return Vec::new();
+            };
+            blocks
+                .iter()
+                .filter_map(|block| match block["type"].as_str() {
+                    Some("tool_use") if block["name"] == "Bash" => Some(CommandEvent::Started {
+                        call_id: block["id"].as_str().unwrap_or_default().to_string(),
+                        command: block["input"]["command"].as_str().unwrap_or_default().to_string(),
+                        cwd: None,
+                    }),
+                    Some("tool_result") => {
+                        let is_error = block["is_error"].as_bool().unwrap_or(false);
+                        let exit_code = CLAUDE_EXIT_CODE
+                            .captures(&claude_result_text(&block["content"]))
+                            .and_then(|c| c[1].parse().ok())
+                            .or(if is_error { None } else { Some(0) });
+                        Some(CommandEvent::Finished {
+                            call_id: block["tool_use_id"].as_str().unwrap_or_default().to_string(),
+                            command: None,
+                            exit_code,
+                            success: !is_error,
+                        })
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+        "gemini" => match event["type"].as_str() {
+            Some("tool_use") if event["tool_name"] == "run_shell_command" => {
+                let params = &event["parameters"];
+                vec![CommandEvent::Started {
+                    call_id: event["tool_id"].as_str().unwrap_or_default().to_string(),
+                    command: params["command"].as_str().unwrap_or_default().to_string(),
+                    cwd: params["directory"].as_str().map(str::to_string),
+                }]
+            }
+            Some("tool_result") => vec![CommandEvent::Finished {
+                call_id: event["tool_id"].as_str().unwrap_or_default().to_string(),
+                command: None,
+                exit_code: None,
+                success: event["status"] == "success",
+            }],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+// ============================================================================
+// 写入
+// ============================================================================
+
+fn insert_started(
+    conn: &Connection,
+    engine: &str,
+    session_id: &str,
+    call_id: &str,
+    command: &str,
+    cwd: &str,
+) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT OR IGNORE INTO command_audit (engine, session_id, call_id, command, cwd, started_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![engine, session_id, call_id, command, cwd, Utc::now().to_rfc3339()],
+    )
+}
+
+fn mark_finished(
+    conn: &Connection,
+    engine: &str,
+    session_id: &str,
+    call_id: &str,
+    exit_code: Option<i32>,
+    success: bool,
+) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE command_audit SET finished_at = ?4, exit_code = ?5, status = ?6
+         WHERE engine = ?1 AND session_id = ?2 AND call_id = ?3",
+        params![
+            engine,
+            session_id,
+            call_id,
+            Utc::now().to_rfc3339(),
+            exit_code,
+            if success { "success" } else { "error" }
+        ],
+    )
+}
+
+struct AuditorState {
+    session_id: String,
+    /// 已记录开始、尚未结束的调用（过滤非 shell 工具的 tool_result）
+    pending: HashSet<String>,
+}
+
+/// 单个进程的命令审计器（在 stdout 读取任务中逐行调用）
+pub struct CommandAuditor {
+    engine: String,
+    cwd: String,
+    state: Mutex<AuditorState>,
+}
+
+impl CommandAuditor {
+    /// `session_id` 为执行时已知的 ID，收到 CLI 的 init 事件后替换为真实会话 ID
+    pub fn new(engine: &str, session_id: &str, cwd: &str) -> Arc<Self> {
+        Arc::new(Self {
+            engine: engine.to_string(),
+            cwd: cwd.to_string(),
+            state: Mutex::new(AuditorState {
+                session_id: session_id.to_string(),
+                pending: HashSet::new(),
+            }),
+        })
+    }
+
+    /// 处理一行原始流输出；审计失败只记录日志，不影响执行
+    pub fn observe(&self, line: &str) {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(session_id) = extract_session_id(&self.engine, &event) {
+            state.session_id = session_id;
+            return;
+        }
+
+        let events = extract_command_events(&self.engine, &event);
+        if events.is_empty() {
+            return;
+        }
+        if let Err(e) = self.apply(&mut state, events) {
+            log::warn!("[CommandAudit] Failed to record {} command: {}", self.engine, e);
+        }
+    }
+
+    fn apply(&self, state: &mut AuditorState, events: Vec<CommandEvent>) -> Result<(), String> {
+        let conn = open_audit_db()?;
+        for event in events {
+            match event {
+                CommandEvent::Started { call_id, command, cwd } => {
+                    insert_started(
+                        &conn,
+                        &self.engine,
+                        &state.session_id,
+                        &call_id,
+                        &command,
+                        cwd.as_deref().unwrap_or(&self.cwd),
+                    )
+                    .map_err(|e| e.to_string())?;
+                    state.pending.insert(call_id);
+                }
+                CommandEvent::Finished { call_id, command, exit_code, success } => {
+                    if !state.pending.remove(&call_id) {
+                        // Codex 的快速命令可能只有 item.completed
+                        let Some(command) = command else { continue };
+                        insert_started(&conn, &self.engine, &state.session_id, &call_id, &command, &self.cwd)
+                            .map_err(|e| e.to_string())?;
+                    }
+                    mark_finished(&conn, &self.engine, &state.session_id, &call_id, exit_code, success)
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// 查询
+// ============================================================================
+
+const SELECT_COLUMNS: &str =
+    "id, engine, session_id, call_id, command, cwd, started_at, finished_at, exit_code, status";
+
+fn row_to_command(row: &rusqlite::Row) -> rusqlite::Result<ExecutedCommand> {
+    Ok(ExecutedCommand {
+        id: row.get(0)?,
+        engine: row.get(1)?,
+        session_id: row.get(2)?,
+        call_id: row.get(3)?,
+        command: row.get(4)?,
+        cwd: row.get(5)?,
+        started_at: row.get(6)?,
+        finished_at: row.get(7)?,
+        exit_code: row.get(8)?,
+        status: row.get(9)?,
+    })
+}
+
+fn query_session(conn: &Connection, session_id: &str) -> Result<Vec<ExecutedCommand>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM command_audit WHERE session_id = ?1 ORDER BY started_at ASC, id ASC",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| format!("查询命令记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![session_id], row_to_command)
+        .map_err(|e| format!("查询命令记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取命令记录失败: {}", e));
+    rows
+}
+
+fn query_pattern(conn: &Connection, pattern: &str, limit: u32) -> Result<Vec<ExecutedCommand>, String> {
+    let escaped = pattern.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM command_audit WHERE command LIKE ?1 ESCAPE '\\'
+             ORDER BY started_at DESC, id DESC LIMIT ?2",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| format!("搜索命令记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![format!("%{}%", escaped), limit], row_to_command)
+        .map_err(|e| format!("搜索命令记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取命令记录失败: {}", e));
+    rows
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出会话中执行过的命令（按时间顺序）
+#[tauri::command]
+pub async fn list_executed_commands(session_id: String) -> Result<Vec<ExecutedCommand>, String> {
+    tokio::task::spawn_blocking(move || query_session(&*open_audit_db()?, &session_id))
+        .await
+        .map_err(|e| format!("查询命令记录失败: {}", e))?
+}
+
+/// 按子串搜索所有会话中执行过的命令（最新的在前）
+#[tauri::command]
+pub async fn search_commands(
+    pattern: String,
+    limit: Option<u32>,
+) -> Result<Vec<ExecutedCommand>, String> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    tokio::task::spawn_blocking(move || query_pattern(&*open_audit_db()?, &pattern, limit))
+        .await
+        .map_err(|e| format!("搜索命令记录失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn extracts_shell_commands_from_each_engine() {
+        let codex = json!({
+            "type": "item.completed",
+            "item": {"id": "item_1", "type": "command_execution", "command": "bash -lc ls",
+                     "exit_code": 2, "status": "failed"}
+        });
+        assert_eq!(
+            extract_command_events("codex", &codex),
+            vec![CommandEvent::Finished {
+                call_id: "item_1".into(),
+                command: Some("bash -lc ls".into()),
+                exit_code: Some(2),
+                success: false,
+            }]
+        );
+
+        let claude = json!({"type": "user", "message": {"content": [
+            {"type": "tool_result", "tool_use_id": "toolu_1", "is_error": true,
+             "content": "Exit code 127\nnpm: not found"}
+        ]}});
+        assert_eq!(
+            extract_command_events("claude", &claude),
+            vec![CommandEvent::Finished {
+                call_id: "toolu_1".into(),
+                command: None,
+                exit_code: Some(127),
+                success: false,
+            }]
+        );
+
+        let gemini = json!({"type": "tool_use", "tool_name": "run_shell_command", "tool_id": "t1",
+                            "parameters": {"command": "cargo test", "directory": "crates/core"}});
+        assert_eq!(
+            extract_command_events("gemini", &gemini),
+            vec![CommandEvent::Started {
+                call_id: "t1".into(),
+                command: "cargo test".into(),
+                cwd: Some("crates/core".into()),
+            }]
+        );
+
+        let read_tool = json!({"type": "tool_use", "tool_name": "read_file", "tool_id": "t2"});
+        assert!(extract_command_events("gemini", &read_tool).is_empty());
+    }
+
+    #[test]
+    fn search_treats_pattern_literally() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        insert_started(&conn, "codex", "s1", "c1", "rm -rf build_dir", "/p").unwrap();
+        insert_started(&conn, "codex", "s1", "c2", "rm -rf buildXdir", "/p").unwrap();
+        mark_finished(&conn, "codex", "s1", "c1", Some(0), true).unwrap();
+
+        let hits = query_pattern(&conn, "build_dir", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].exit_code, Some(0));
+        assert_eq!(hits[0].status, "success");
+        assert_eq!(query_session(&conn, "s1").unwrap().len(), 2);
+    }
+}
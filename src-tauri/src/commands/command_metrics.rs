@@ -0,0 +1,357 @@
+//! 命令调用耗时统计（可选，仅本地）
+//!
+//! 记录每次 Tauri 命令调用的名称、耗时与结果（成功 / AnyCodeError 错误码），
+//! 供内置性能面板查看哪些命令慢、哪些命令经常失败。默认关闭，数据只写入本地的
+//! 内存环形缓冲区和 agents.db 的 `command_metrics` 表，不会发送到任何网络地址。
+//!
+//! Rust 侧无法拦截异步命令的完成时机（InvokeResolver 不对外开放），因此计时由前端
+//! 统一的 `invoke` 包装完成，按批调用 `record_command_metrics` 上报；耗时为前端视角的往返时间。
+//!
+//! 设置保存在 ~/.anycode/command_metrics.json。
+
+use chrono::{Duration, Utc};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::provider_metrics::{average, percentile, period_start};
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+use super::data_root::anycode_dir;
+
+/// 内存中保留的最近调用数
+const RING_CAPACITY: usize = 500;
+
+/// 数据库中样本的保留天数
+const RETENTION_DAYS: i64 = 14;
+
+/// 最近调用（面板实时列表），关闭统计时清空
+static RECENT: Lazy<Mutex<VecDeque<CommandSample>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// 统计设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommandMetricsSettings {
+    pub enabled: bool,
+}
+
+/// 一次命令调用
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandSample {
+    pub command: String,
+    pub duration_ms: u64,
+    pub success: bool,
+    /// 失败时的错误码（非结构化错误为 None）
+    #[serde(default)]
+    pub error_code: Option<String>,
+    /// 由后端在接收时填写
+    #[serde(default)]
+    pub recorded_at: String,
+}
+
+/// 单个命令的汇总
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMetrics {
+    pub command: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub error_rate: f64,
+    pub avg_ms: Option<f64>,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+    /// 失败次数按错误码统计（非结构化错误记为 "unknown"）
+    pub errors_by_code: BTreeMap<String, u64>,
+    pub last_called_at: Option<String>,
+}
+
+/// 性能面板数据
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMetricsReport {
+    pub enabled: bool,
+    pub period: String,
+    /// 按总耗时从高到低排序
+    pub commands: Vec<CommandMetrics>,
+    /// 最近的调用，最新的在前
+    pub recent: Vec<CommandSample>,
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("command_metrics.json"))
+}
+
+pub fn load_command_metrics_settings() -> CommandMetricsSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 创建命令统计表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS command_metrics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            command TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            success INTEGER NOT NULL,
+            error_code TEXT,
+            recorded_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_command_metrics_recorded
+            ON command_metrics(recorded_at);",
+    )
+    .map_err(|e| format!("创建命令统计表失败: {}", e))
+}
+
+fn open_metrics_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+/// 批量写入样本，并清理超过保留期的旧数据
+fn insert_samples(conn: &mut Connection, samples: &[CommandSample]) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("写入命令统计失败: {}", e))?;
+    for sample in samples {
+        tx.execute(
+            "INSERT INTO command_metrics (command, duration_ms, success, error_code, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                sample.command,
+                sample.duration_ms as i64,
+                sample.success,
+                sample.error_code,
+                sample.recorded_at,
+            ],
+        )
+        .map_err(|e| format!("写入命令统计失败: {}", e))?;
+    }
+    let cutoff = (Utc::now() - Duration::days(RETENTION_DAYS)).to_rfc3339();
+    tx.execute("DELETE FROM command_metrics WHERE recorded_at < ?1", params![cutoff])
+        .map_err(|e| format!("清理命令统计失败: {}", e))?;
+    tx.commit().map_err(|e| format!("写入命令统计失败: {}", e))
+}
+
+fn load_samples(conn: &Connection, since: Option<&str>) -> Result<Vec<CommandSample>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT command, duration_ms, success, error_code, recorded_at
+             FROM command_metrics
+             WHERE ?1 IS NULL OR recorded_at >= ?1
+             ORDER BY recorded_at",
+        )
+        .map_err(|e| format!("查询命令统计失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![since], |row| {
+            Ok(CommandSample {
+                command: row.get(0)?,
+                duration_ms: row.get::<_, i64>(1)? as u64,
+                success: row.get(2)?,
+                error_code: row.get(3)?,
+                recorded_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("查询命令统计失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取命令统计失败: {}", e))
+}
+
+/// 按命令汇总样本（输入按时间升序）
+fn summarize(samples: &[CommandSample]) -> Vec<CommandMetrics> {
+    let mut by_command: BTreeMap<&str, Vec<&CommandSample>> = BTreeMap::new();
+    for sample in samples {
+        by_command.entry(sample.command.as_str()).or_default().push(sample);
+    }
+
+    let mut metrics: Vec<(u64, CommandMetrics)> = by_command
+        .into_iter()
+        .map(|(command, samples)| {
+            let mut durations: Vec<u64> = samples.iter().map(|s| s.duration_ms).collect();
+            durations.sort_unstable();
+
+            let mut errors_by_code = BTreeMap::new();
+            for sample in samples.iter().filter(|s| !s.success) {
+                let code = sample.error_code.clone().unwrap_or_else(|| "unknown".to_string());
+                *errors_by_code.entry(code).or_insert(0) += 1;
+            }
+            let failures: u64 = errors_by_code.values().sum();
+
+            let total: u64 = durations.iter().sum();
+            let summary = CommandMetrics {
+                command: command.to_string(),
+                calls: samples.len() as u64,
+                failures,
+                error_rate: failures as f64 / samples.len() as f64,
+                avg_ms: average(durations.iter().copied()),
+                p50_ms: percentile(&durations, 0.5),
+                p95_ms: percentile(&durations, 0.95),
+                max_ms: durations.last().copied(),
+                errors_by_code,
+                last_called_at: samples.last().map(|s| s.recorded_at.clone()),
+            };
+            (total, summary)
+        })
+        .collect();
+
+    metrics.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.command.cmp(&b.1.command)));
+    metrics.into_iter().map(|(_, summary)| summary).collect()
+}
+
+fn push_recent(samples: &[CommandSample]) {
+    let mut recent = RECENT.lock().unwrap();
+    for sample in samples {
+        if recent.len() == RING_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(sample.clone());
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 前端批量上报命令调用；统计关闭时直接丢弃
+#[tauri::command]
+pub async fn record_command_metrics(samples: Vec<CommandSample>) -> Result<(), String> {
+    if samples.is_empty() || !load_command_metrics_settings().enabled {
+        return Ok(());
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let samples: Vec<CommandSample> = samples
+        .into_iter()
+        .map(|mut sample| {
+            if sample.recorded_at.is_empty() {
+                sample.recorded_at = now.clone();
+            }
+            sample
+        })
+        .collect();
+    push_recent(&samples);
+
+    tokio::task::spawn_blocking(move || {
+        let mut conn = open_metrics_db()?;
+        insert_samples(&mut conn, &samples)
+    })
+    .await
+    .map_err(|e| format!("写入命令统计失败: {}", e))?
+}
+
+/// 性能面板：各命令的调用次数、耗时分位数与错误码分布（period: "1h" / "24h" / "7d" / "all"，默认 "24h"）
+#[tauri::command]
+pub async fn get_command_metrics(period: Option<String>) -> Result<CommandMetricsReport, String> {
+    let period = period.unwrap_or_else(|| "24h".to_string());
+    let enabled = load_command_metrics_settings().enabled;
+    let recent: Vec<CommandSample> = RECENT.lock().unwrap().iter().rev().cloned().collect();
+
+    tokio::task::spawn_blocking(move || {
+        let since = period_start(&period)?;
+        let conn = open_metrics_db()?;
+        let samples = load_samples(&conn, since.as_deref())?;
+        Ok(CommandMetricsReport {
+            enabled,
+            commands: summarize(&samples),
+            period,
+            recent,
+        })
+    })
+    .await
+    .map_err(|e| format!("读取命令统计失败: {}", e))?
+}
+
+#[tauri::command]
+pub async fn get_command_metrics_settings() -> Result<CommandMetricsSettings, String> {
+    Ok(load_command_metrics_settings())
+}
+
+#[tauri::command]
+pub async fn set_command_metrics_settings(settings: CommandMetricsSettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize command metrics settings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write command_metrics.json: {}", e))?;
+
+    if !settings.enabled {
+        RECENT.lock().unwrap().clear();
+    }
+    log::info!("[CommandMetrics] Saved settings (enabled: {})", settings.enabled);
+    Ok(())
+}
+
+/// 清空内存和数据库中的全部统计
+#[tauri::command]
+pub async fn clear_command_metrics() -> Result<(), String> {
+    RECENT.lock().unwrap().clear();
+    tokio::task::spawn_blocking(|| {
+        let conn = open_metrics_db()?;
+        conn.execute("DELETE FROM command_metrics", [])
+            .map_err(|e| format!("清空命令统计失败: {}", e))?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("清空命令统计失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(command: &str, duration_ms: u64, error_code: Option<&str>, recorded_at: String) -> CommandSample {
+        CommandSample {
+            command: command.to_string(),
+            duration_ms,
+            success: error_code.is_none(),
+            error_code: error_code.map(str::to_string),
+            recorded_at,
+        }
+    }
+
+    #[test]
+    fn stores_prunes_and_summarizes_samples() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let now = Utc::now().to_rfc3339();
+        let stale = (Utc::now() - Duration::days(RETENTION_DAYS + 1)).to_rfc3339();
+        insert_samples(
+            &mut conn,
+            &[
+                sample("list_projects", 40, None, stale),
+                sample("list_projects", 20, None, now.clone()),
+                sample("execute_codex", 900, Some("engine"), now.clone()),
+                sample("execute_codex", 100, None, now.clone()),
+                sample("list_projects", 30, Some("io"), now.clone()),
+            ],
+        )
+        .unwrap();
+
+        // 超过保留期的样本在写入时被清理
+        let samples = load_samples(&conn, None).unwrap();
+        assert_eq!(samples.len(), 4);
+
+        let metrics = summarize(&samples);
+        assert_eq!(metrics[0].command, "execute_codex");
+        assert_eq!((metrics[0].calls, metrics[0].failures), (2, 1));
+        assert_eq!(metrics[0].max_ms, Some(900));
+        assert_eq!(metrics[0].errors_by_code.get("engine"), Some(&1));
+
+        assert_eq!(metrics[1].command, "list_projects");
+        assert_eq!(metrics[1].avg_ms, Some(25.0));
+        assert_eq!(metrics[1].error_rate, 0.5);
+        assert_eq!(metrics[1].last_called_at.as_deref(), Some(now.as_str()));
+    }
+}
@@ -0,0 +1,264 @@
+//! 提交信息生成
+//!
+//! 收集当前改动（暂存区或整个工作区）交给引擎生成 Conventional Commits 格式的提交信息。
+//! 引擎和提示词模板按项目配置（项目设置中的 `commitMessage`），模板中的 `{{stat}}` /
+//! `{{diff}}` 会被替换为改动统计和 diff。
+//!
+//! 开启 `autoCommit` 后，每个提示词完成时的自动提交也使用生成的信息，并附带
+//! `Any-Code-Prompt: <engine> #<index>` trailer，供 Git 统计识别 AI 提交。
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use super::headless::{run_headless, HeadlessRequest};
+use super::project_settings::load_project_settings;
+use super::simple_git::run_git;
+
+/// 交给引擎的 diff 最大长度（字符），超出时截断
+const MAX_DIFF_CHARS: usize = 40_000;
+
+/// 自动提交中标记提示词的 trailer
+pub const PROMPT_TRAILER: &str = "Any-Code-Prompt";
+
+const CONVENTIONAL_TYPES: [&str; 11] = [
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+const DEFAULT_TEMPLATE: &str = "Write a git commit message for the changes below.\n\
+Follow the Conventional Commits format: `<type>(<optional scope>): <subject>`, \
+where type is one of feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert.\n\
+Keep the subject under 72 characters, in the imperative mood, without a trailing period. \
+If the change needs explanation, add a blank line and a short body wrapped at 72 characters.\n\
+Reply with the commit message only, no code fences or commentary. Do not use any tools.\n\n\
+<stat>\n{{stat}}\n</stat>\n\n<diff>\n{{diff}}\n</diff>";
+
+/// 项目的提交信息设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitMessageSettings {
+    /// 生成用的引擎（留空使用 claude）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    /// 提示词模板（留空使用内置模板）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// 提示词完成后的自动提交也使用生成的信息
+    #[serde(default)]
+    pub auto_commit: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedCommitMessage {
+    /// 完整提交信息
+    pub message: String,
+    pub subject: String,
+    pub body: Option<String>,
+    pub engine: String,
+}
+
+/// 收集改动：返回 (统计, diff)；没有改动时返回 None
+fn collect_changes(project_path: &str, staged_only: bool) -> Result<Option<(String, String)>, String> {
+    let has_head = run_git(project_path, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok();
+    let base: &[&str] = match (staged_only, has_head) {
+        (true, _) => &["--cached"],
+        (false, true) => &["HEAD"],
+        // 还没有提交：与空树比较暂存区，未跟踪文件在下面列出
+        (false, false) => &["--cached"],
+    };
+
+    let diff_args = |extra: &[&str]| {
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+        args.extend_from_slice(base);
+        args.extend_from_slice(extra);
+        args.into_iter().map(str::to_string).collect::<Vec<_>>()
+    };
+    let run = |args: Vec<String>| run_git(project_path, &args.iter().map(String::as_str).collect::<Vec<_>>());
+
+    let mut stat = run(diff_args(&["--stat"]))?;
+    let mut diff = run(diff_args(&[]))?;
+    if !staged_only {
+        let untracked = run_git(project_path, &["ls-files", "--others", "--exclude-standard"])?;
+        for file in untracked.lines().filter(|l| !l.is_empty()) {
+            stat.push_str(&format!(" {} (new file)\n", file));
+            let content = std::fs::read_to_string(std::path::Path::new(project_path).join(file)).unwrap_or_default();
+            diff.push_str(&format!("\n--- /dev/null\n+++ b/{}\n", file));
+            for line in content.lines() {
+                diff.push('+');
+                diff.push_str(line);
+                diff.push('\n');
+            }
+        }
+    }
+
+    if stat.trim().is_empty() {
+        return Ok(None);
+    }
+    if diff.chars().count() > MAX_DIFF_CHARS {
+        diff = diff.chars().take(MAX_DIFF_CHARS).collect::<String>() + "\n... (diff truncated)";
+    }
+    Ok(Some((stat, diff)))
+}
+
+/// 整理引擎回复：去掉代码块和多余空行，类型不符合规范时补上 `chore: `
+fn normalize_reply(reply: &str) -> Option<GeneratedCommitMessage> {
+    let text = reply.trim();
+    let text = text
+        .strip_prefix("```")
+        .map(|rest| rest.split_once('\n').map_or("", |(_, body)| body))
+        .map(|body| body.trim_end().strip_suffix("```").unwrap_or(body))
+        .unwrap_or(text)
+        .trim();
+
+    let mut lines = text.lines();
+    let subject = lines.next()?.trim().trim_end_matches('.').to_string();
+    if subject.is_empty() {
+        return None;
+    }
+    let kind = subject
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('!');
+    let subject = if subject.contains(':') && CONVENTIONAL_TYPES.contains(&kind) {
+        subject
+    } else {
+        format!("chore: {}", subject)
+    };
+
+    let body = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+    let body = (!body.is_empty()).then_some(body);
+    let message = match &body {
+        Some(body) => format!("{}\n\n{}", subject, body),
+        None => subject.clone(),
+    };
+    Some(GeneratedCommitMessage {
+        message,
+        subject,
+        body,
+        engine: String::new(),
+    })
+}
+
+async fn generate(
+    app_handle: &AppHandle,
+    project_path: &str,
+    staged_only: bool,
+    engine: Option<String>,
+) -> Result<GeneratedCommitMessage, String> {
+    let settings = load_project_settings(project_path).commit_message.unwrap_or_default();
+    let engine = engine
+        .or(settings.engine)
+        .filter(|e| !e.is_empty())
+        .unwrap_or_else(|| "claude".to_string());
+
+    let (stat, diff) = collect_changes(project_path, staged_only)?
+        .ok_or_else(|| if staged_only { "暂存区没有改动" } else { "工作区没有改动" }.to_string())?;
+    let template = settings.template.filter(|t| !t.trim().is_empty());
+    let prompt = template
+        .as_deref()
+        .unwrap_or(DEFAULT_TEMPLATE)
+        .replace("{{stat}}", stat.trim_end())
+        .replace("{{diff}}", diff.trim_end());
+
+    // 与会话总结相同：在临时目录中只读执行，不在项目会话列表中留下记录
+    let work_dir = std::env::temp_dir().join("anycode-commit-messages");
+    std::fs::create_dir_all(&work_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let request = HeadlessRequest {
+        engine: engine.clone(),
+        project_path: work_dir.to_string_lossy().to_string(),
+        prompt,
+        model: None,
+        permission_profile: Some("read-only".to_string()),
+        internal: true,
+    };
+    let output = run_headless(app_handle, &request, &Mutex::new(None)).await?;
+    let reply = output.text.ok_or_else(|| format!("{} 未返回内容", engine))?;
+
+    let mut generated = normalize_reply(&reply).ok_or_else(|| format!("{} 返回的提交信息为空", engine))?;
+    generated.engine = engine;
+    Ok(generated)
+}
+
+/// 提示词完成后自动提交使用的信息
+///
+/// 未开启 `autoCommit`、没有改动或生成失败时返回原有的 `[Engine] After prompt #N`。
+pub async fn auto_commit_message(
+    app_handle: &AppHandle,
+    project_path: &str,
+    engine: &str,
+    prompt_index: usize,
+) -> String {
+    let label = match engine {
+        "codex" => "Codex",
+        "gemini" => "Gemini",
+        _ => "Claude Code",
+    };
+    let fallback = format!("[{}] After prompt #{}", label, prompt_index);
+
+    let enabled = load_project_settings(project_path)
+        .commit_message
+        .is_some_and(|s| s.auto_commit);
+    if !enabled {
+        return fallback;
+    }
+    match generate(app_handle, project_path, false, None).await {
+        Ok(generated) => format!("{}\n\n{}: {} #{}", generated.message, PROMPT_TRAILER, engine, prompt_index),
+        Err(e) => {
+            log::warn!("[CommitMessage] Falling back to default auto-commit message: {}", e);
+            fallback
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 为当前改动生成提交信息
+///
+/// `staged_only` 为 true 时只看暂存区，否则包含所有未提交改动（含未跟踪文件）；
+/// `engine` 为空时使用项目设置中的引擎。
+#[tauri::command]
+pub async fn generate_commit_message(
+    app_handle: AppHandle,
+    project_path: String,
+    staged_only: bool,
+    engine: Option<String>,
+) -> Result<GeneratedCommitMessage, String> {
+    let generated = generate(&app_handle, &project_path, staged_only, engine).await?;
+    log::info!("[CommitMessage] Generated with {}: {}", generated.engine, generated.subject);
+    Ok(generated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_replies_and_collects_changes() {
+        let fenced = normalize_reply("```text\nfeat(git): add hunk staging.\n\nLets users keep parts of a change.\n```").unwrap();
+        assert_eq!(fenced.subject, "feat(git): add hunk staging");
+        assert_eq!(fenced.body.as_deref(), Some("Lets users keep parts of a change."));
+        assert_eq!(fenced.message, "feat(git): add hunk staging\n\nLets users keep parts of a change.");
+        assert_eq!(normalize_reply("fix!: drop legacy API").unwrap().message, "fix!: drop legacy API");
+        assert_eq!(normalize_reply("Update readme").unwrap().subject, "chore: Update readme");
+        assert!(normalize_reply("  \n").is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        run_git(path, &["init", "-q"]).unwrap();
+        assert!(collect_changes(path, false).unwrap().is_none());
+        std::fs::write(dir.path().join("a.txt"), "hello\n").unwrap();
+        let (stat, diff) = collect_changes(path, false).unwrap().unwrap();
+        assert!(stat.contains("a.txt (new file)") && diff.contains("+hello"));
+        assert!(collect_changes(path, true).unwrap().is_none());
+        run_git(path, &["add", "a.txt"]).unwrap();
+        let (_, staged) = collect_changes(path, true).unwrap().unwrap();
+        assert!(staged.contains("+hello"));
+    }
+}
@@ -0,0 +1,325 @@
+//! 多引擎对比模式
+//!
+//! 用同一提示词、同一项目状态，在隔离的 git worktree 中并行运行多个引擎，
+//! 返回结构化的对比结果：各自产生的 diff、token 用量和耗时，便于评估
+//! 哪个 CLI 更适合某类任务。
+//!
+//! - 基准状态为当前 HEAD 加上已跟踪文件的未提交改动（`git stash create`），
+//!   未跟踪的文件不会带入 worktree
+//! - worktree 位于系统临时目录，默认在对比结束后删除；
+//!   保留时可稍后用 `cleanup_compare_worktrees` 清理
+//! - 未显式指定的模型和权限档案取源项目的默认执行选项（worktree 中没有项目设置）
+
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::AppHandle;
+
+use super::headless::{run_headless, HeadlessRequest, TokenUsage};
+use super::project_defaults;
+use super::simple_git::run_git;
+
+/// 未指定且项目没有默认值时使用的权限档案（引擎需要能写入 worktree 才会产生 diff）
+const DEFAULT_COMPARE_PROFILE: &str = "auto-edit";
+
+/// 参与对比的引擎
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareEngine {
+    pub engine: String,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// 单个文件的改动行数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiffStat {
+    pub path: String,
+    /// 二进制文件为 None
+    pub insertions: Option<u32>,
+    pub deletions: Option<u32>,
+}
+
+/// 单个引擎的对比结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineComparison {
+    pub engine: String,
+    pub model: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    /// 引擎的最终回复
+    pub output: Option<String>,
+    /// 相对基准状态的完整 diff
+    pub diff: String,
+    pub files: Vec<FileDiffStat>,
+    pub insertions: u32,
+    pub deletions: u32,
+    pub usage: TokenUsage,
+    pub duration_ms: u64,
+    /// 保留 worktree 时的路径
+    pub worktree_path: Option<String>,
+}
+
+/// 对比结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareResult {
+    pub compare_id: String,
+    pub base_commit: String,
+    pub prompt: String,
+    pub results: Vec<EngineComparison>,
+}
+
+/// 基准提交：含未提交改动时使用 `git stash create` 生成的临时提交（不改动工作区）
+fn resolve_base_commit(project: &Path) -> Result<String, String> {
+    let stash = run_git(project, &["stash", "create", "anycode compare base"])?;
+    let stash = stash.trim();
+    if !stash.is_empty() {
+        return Ok(stash.to_string());
+    }
+    Ok(run_git(project, &["rev-parse", "HEAD"])?.trim().to_string())
+}
+
+fn compare_root(compare_id: &str) -> PathBuf {
+    std::env::temp_dir().join("anycode-compare").join(compare_id)
+}
+
+/// 解析 `git diff --numstat` 输出
+fn parse_numstat(output: &str) -> Vec<FileDiffStat> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let insertions = parts.next()?;
+            let deletions = parts.next()?;
+            let path = parts.next()?;
+            Some(FileDiffStat {
+                path: path.to_string(),
+                insertions: insertions.parse().ok(),
+                deletions: deletions.parse().ok(),
+            })
+        })
+        .collect()
+}
+
+/// worktree 相对基准的 diff（包含新建文件）
+fn collect_diff(worktree: &Path) -> Result<(String, Vec<FileDiffStat>), String> {
+    run_git(worktree, &["add", "-A"])?;
+    let diff = run_git(worktree, &["diff", "--cached", "--no-color"])?;
+    let files = parse_numstat(&run_git(worktree, &["diff", "--cached", "--numstat"])?);
+    Ok((diff, files))
+}
+
+fn remove_worktree(project: &Path, worktree: &Path) {
+    let path = worktree.to_string_lossy().to_string();
+    if let Err(e) = run_git(project, &["worktree", "remove", "--force", &path]) {
+        log::warn!("[Compare] Failed to remove worktree {}: {}", path, e);
+    }
+}
+
+/// 从基准提交为每个引擎创建 worktree，任一失败时清理已创建的
+fn create_worktrees(project: &Path, root: &Path, engines: &[String], base_commit: &str) -> Result<Vec<PathBuf>, String> {
+    let mut worktrees = Vec::new();
+    for (index, engine) in engines.iter().enumerate() {
+        let worktree = root.join(format!("{}-{}", index, engine));
+        let path = worktree.to_string_lossy().to_string();
+        if let Err(e) = run_git(project, &["worktree", "add", "--detach", &path, base_commit]) {
+            worktrees.iter().for_each(|w: &PathBuf| remove_worktree(project, w));
+            return Err(format!("创建 worktree 失败: {}", e));
+        }
+        worktrees.push(worktree);
+    }
+    Ok(worktrees)
+}
+
+fn remove_compare_root(project: &Path, root: &Path, worktrees: &[PathBuf]) {
+    for worktree in worktrees {
+        remove_worktree(project, worktree);
+    }
+    let _ = std::fs::remove_dir_all(root);
+}
+
+// ============================================================================
+// 执行
+// ============================================================================
+
+async fn run_in_worktree(
+    app_handle: AppHandle,
+    spec: CompareEngine,
+    worktree: PathBuf,
+    prompt: String,
+    permission_profile: Option<String>,
+    project_path: String,
+) -> EngineComparison {
+    // worktree 中没有项目设置，默认值从源项目解析
+    let defaults = project_defaults::resolve_engine_defaults(&project_path, &spec.engine);
+    let spec = CompareEngine {
+        model: project_defaults::or_default(spec.model, &defaults.model),
+        ..spec
+    };
+    let permission_profile = project_defaults::or_default(permission_profile, &defaults.permission_profile)
+        .unwrap_or_else(|| DEFAULT_COMPARE_PROFILE.to_string());
+    let request = HeadlessRequest {
+        engine: spec.engine.clone(),
+        project_path: worktree.to_string_lossy().to_string(),
+        prompt,
+        model: spec.model.clone(),
+        permission_profile: Some(permission_profile),
+        internal: false,
+    };
+    let pid_slot = Mutex::new(None);
+    let started = Instant::now();
+    let result = run_headless(&app_handle, &request, &pid_slot).await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let (output, usage, mut error) = match result {
+        Ok(output) => (output.text, output.usage, None),
+        Err(e) => (None, TokenUsage::default(), Some(e)),
+    };
+    let (diff, files) = match tokio::task::spawn_blocking({
+        let worktree = worktree.clone();
+        move || collect_diff(&worktree)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r)
+    {
+        Ok(diff) => diff,
+        Err(e) => {
+            error.get_or_insert(format!("收集 diff 失败: {}", e));
+            (String::new(), Vec::new())
+        }
+    };
+
+    log::info!(
+        "[Compare] {} finished in {} ms, {} files changed{}",
+        spec.engine,
+        duration_ms,
+        files.len(),
+        error.as_deref().map(|e| format!(" (error: {})", e)).unwrap_or_default()
+    );
+
+    EngineComparison {
+        engine: spec.engine,
+        model: spec.model,
+        success: error.is_none(),
+        error,
+        output,
+        diff,
+        insertions: files.iter().filter_map(|f| f.insertions).sum(),
+        deletions: files.iter().filter_map(|f| f.deletions).sum(),
+        files,
+        usage,
+        duration_ms,
+        worktree_path: Some(worktree.to_string_lossy().to_string()),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 在隔离的 worktree 中并行运行多个引擎并比较结果
+///
+/// # Arguments
+/// * `engines` - 参与对比的引擎（至少两个）
+/// * `permission_profile` - 所有引擎使用的权限档案，默认取项目默认值，再退回 `auto-edit`
+/// * `keep_worktrees` - 保留 worktree 以便查看或合并结果
+#[tauri::command]
+pub async fn compare_engines(
+    project_path: String,
+    prompt: String,
+    engines: Vec<CompareEngine>,
+    permission_profile: Option<String>,
+    keep_worktrees: Option<bool>,
+    app_handle: AppHandle,
+) -> Result<CompareResult, String> {
+    if engines.len() < 2 {
+        return Err("对比模式至少需要两个引擎".to_string());
+    }
+    if let Some(spec) = engines.iter().find(|s| !["claude", "codex", "gemini"].contains(&s.engine.as_str())) {
+        return Err(tr!("engine.unsupported", engine = spec.engine));
+    }
+
+    let project = PathBuf::from(&project_path);
+    let compare_id = uuid::Uuid::new_v4().to_string();
+    let root = compare_root(&compare_id);
+    let engine_names: Vec<String> = engines.iter().map(|s| s.engine.clone()).collect();
+    let (base_commit, worktrees) = tokio::task::spawn_blocking({
+        let project = project.clone();
+        let root = root.clone();
+        move || -> Result<(String, Vec<PathBuf>), String> {
+            let base_commit = resolve_base_commit(&project)?;
+            let worktrees = create_worktrees(&project, &root, &engine_names, &base_commit)?;
+            Ok((base_commit, worktrees))
+        }
+    })
+    .await
+    .map_err(|e| format!("创建 worktree 失败: {}", e))??;
+    log::info!("[Compare] {} comparing {} engines at {} in {:?}", compare_id, engines.len(), base_commit, root);
+
+    let runs = engines.into_iter().zip(worktrees.iter().cloned()).map(|(spec, worktree)| {
+        run_in_worktree(
+            app_handle.clone(),
+            spec,
+            worktree,
+            prompt.clone(),
+            permission_profile.clone(),
+            project_path.clone(),
+        )
+    });
+    let mut results = futures::future::join_all(runs).await;
+
+    if !keep_worktrees.unwrap_or(false) {
+        tokio::task::spawn_blocking(move || remove_compare_root(&project, &root, &worktrees))
+            .await
+            .map_err(|e| format!("清理 worktree 失败: {}", e))?;
+        results.iter_mut().for_each(|r| r.worktree_path = None);
+    }
+
+    Ok(CompareResult { compare_id, base_commit, prompt, results })
+}
+
+/// 删除保留下来的对比 worktree
+#[tauri::command]
+pub async fn cleanup_compare_worktrees(project_path: String, compare_id: String) -> Result<(), String> {
+    if uuid::Uuid::parse_str(&compare_id).is_err() {
+        return Err(format!("无效的对比 ID: {}", compare_id));
+    }
+    let root = compare_root(&compare_id);
+    tokio::task::spawn_blocking(move || {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            return Err(format!("对比 worktree 不存在: {}", compare_id));
+        };
+        let project = PathBuf::from(&project_path);
+        for entry in entries.flatten() {
+            remove_worktree(&project, &entry.path());
+        }
+        let _ = run_git(&project, &["worktree", "prune"]);
+        std::fs::remove_dir_all(&root).map_err(|e| format!("删除对比目录失败: {}", e))
+    })
+    .await
+    .map_err(|e| format!("删除对比目录失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_numstat_including_binary_files() {
+        let stats = parse_numstat("12\t3\tsrc/lib.rs\n-\t-\tassets/logo.png\n0\t7\tdocs/a b.md\n");
+        assert_eq!(
+            stats,
+            vec![
+                FileDiffStat { path: "src/lib.rs".into(), insertions: Some(12), deletions: Some(3) },
+                FileDiffStat { path: "assets/logo.png".into(), insertions: None, deletions: None },
+                FileDiffStat { path: "docs/a b.md".into(), insertions: Some(0), deletions: Some(7) },
+            ]
+        );
+    }
+}
@@ -0,0 +1,827 @@
+//! 团队共享配置同步
+//!
+//! 从用户指定的 git 仓库（只读，浅克隆到 `~/.anycode/shared_config/repo`）拉取团队共享的配置，
+//! 与本地配置合并：
+//!
+//! ```text
+//! providers.json            # export_provider_presets 导出的代理商预设包
+//! prompts/*.md              # Codex 提示词模板（~/.codex/prompts）
+//! prompts/claude/*.md       # Claude 自定义命令（~/.claude/commands）
+//! prompts/gemini/*.md       # Gemini 提示词模板（~/.gemini/prompts）
+//! mcp_registry.json         # MCP 服务器清单（与 MCP 市场清单格式相同）
+//! permission_profiles.json  # 权限档案数组
+//! ```
+//!
+//! 同步过的配置记录在 `~/.anycode/shared_config/state.json` 中（来源标记），并保存同步时的内容哈希：
+//! - 本地没有的配置直接添加
+//! - 来自共享仓库且本地未改动的配置随仓库更新或删除
+//! - 本地改动过、或与本地同名配置内容不同时保留本地版本，记为冲突
+//!
+//! 代理商预设按去掉密钥后的内容比较，本地填写的密钥在更新时保留。
+//!
+//! 代理商预设（含会被执行的 `apiKeyHelper`）和权限档案不会自动应用：新增或更新时列入 `pending`，
+//! 用户逐项审阅后把批准的项（按内容哈希）传给下一次 `sync_shared_config` 才会写入。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::claude::{delete_codex_prompt, get_claude_dir, get_codex_prompt, list_codex_prompts, save_codex_prompt};
+use super::codex::config::{
+    add_codex_provider_config, delete_codex_provider_config, get_codex_provider_presets, update_codex_provider_config,
+    CodexProviderConfig,
+};
+use super::gemini::config::{delete_gemini_prompt, get_gemini_prompt, list_gemini_prompts, save_gemini_prompt};
+use super::gemini::provider::{
+    add_gemini_provider_config, delete_gemini_provider_config, get_gemini_provider_presets,
+    update_gemini_provider_config, GeminiProviderConfig,
+};
+use super::mcp_registry::{load_registry, McpRegistry, RegistryServer};
+use super::permission_config::{
+    delete_permission_profile, list_all_permission_profiles, save_permission_profile, PermissionProfile,
+};
+use super::provider::{
+    add_provider_config, delete_provider_config, get_provider_presets, update_provider_config, ProviderConfig,
+};
+use super::provider_bundle::{
+    redact_claude, redact_codex, redact_gemini, restore_claude, restore_codex, restore_gemini, ProviderPresetBundle,
+};
+use super::simple_git::run_git;
+use super::data_root::anycode_dir;
+
+/// 共享仓库设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SharedConfigSettings {
+    /// 为空表示未启用
+    pub repo_url: String,
+    /// 为空时使用远端默认分支
+    pub branch: Option<String>,
+}
+
+/// 共享配置的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SharedKind {
+    ClaudeProvider,
+    CodexProvider,
+    GeminiProvider,
+    /// Codex 提示词模板
+    PromptTemplate,
+    ClaudePromptTemplate,
+    GeminiPromptTemplate,
+    McpServer,
+    PermissionProfile,
+}
+
+const KINDS: [SharedKind; 8] = [
+    SharedKind::ClaudeProvider,
+    SharedKind::CodexProvider,
+    SharedKind::GeminiProvider,
+    SharedKind::PromptTemplate,
+    SharedKind::ClaudePromptTemplate,
+    SharedKind::GeminiPromptTemplate,
+    SharedKind::McpServer,
+    SharedKind::PermissionProfile,
+];
+
+/// 来自共享仓库的一项配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedItem {
+    pub kind: SharedKind,
+    pub id: String,
+}
+
+/// 用户批准应用的一项共享配置；按内容哈希批准，共享版本再变化时需要重新批准
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedApproval {
+    pub kind: SharedKind,
+    pub id: String,
+    pub hash: String,
+}
+
+/// 等待用户批准的共享配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingSharedItem {
+    #[serde(flatten)]
+    pub approval: SharedApproval,
+    /// false 表示会替换本地已有的同名配置
+    pub is_new: bool,
+    /// 共享版本的内容（代理商已去掉密钥），供用户审阅
+    pub content: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub kind: SharedKind,
+    pub id: String,
+    pub reason: String,
+}
+
+/// 一次同步的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedConfigSyncReport {
+    pub commit: String,
+    pub synced_at: String,
+    pub added: Vec<SharedItem>,
+    pub updated: Vec<SharedItem>,
+    pub removed: Vec<SharedItem>,
+    pub unchanged: usize,
+    pub conflicts: Vec<SyncConflict>,
+    /// 需要用户批准后才会应用的代理商预设和权限档案
+    pub pending: Vec<PendingSharedItem>,
+}
+
+/// 当前同步状态（`items` 即来源为共享仓库的配置）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedConfigStatus {
+    pub settings: SharedConfigSettings,
+    pub commit: Option<String>,
+    pub synced_at: Option<String>,
+    pub items: Vec<SharedItem>,
+    pub conflicts: Vec<SyncConflict>,
+    pub pending: Vec<PendingSharedItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrackedItem {
+    kind: SharedKind,
+    id: String,
+    /// 同步时的内容哈希，用于判断本地是否改动过
+    hash: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SyncState {
+    commit: Option<String>,
+    synced_at: Option<String>,
+    items: Vec<TrackedItem>,
+    conflicts: Vec<SyncConflict>,
+    #[serde(default)]
+    pending: Vec<PendingSharedItem>,
+}
+
+// ============================================================================
+// 路径与持久化
+// ============================================================================
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("shared_config.json"))
+}
+
+fn get_sync_dir() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("shared_config"))
+}
+
+fn get_state_path() -> Result<PathBuf, String> {
+    Ok(get_sync_dir()?.join("state.json"))
+}
+
+/// 已同步的 MCP 服务器（合并进 MCP 市场清单）
+fn get_mcp_store_path() -> Result<PathBuf, String> {
+    Ok(get_sync_dir()?.join("mcp_servers.json"))
+}
+
+fn read_json<T: serde::de::DeserializeOwned + Default>(path: &Path) -> T {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(value).map_err(|e| format!("序列化失败: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("写入 {:?} 失败: {}", path, e))
+}
+
+pub fn load_shared_config_settings() -> SharedConfigSettings {
+    get_settings_path().map(|path| read_json(&path)).unwrap_or_default()
+}
+
+fn load_state() -> SyncState {
+    get_state_path().map(|path| read_json(&path)).unwrap_or_default()
+}
+
+/// 从共享仓库同步的 MCP 服务器
+pub fn shared_mcp_servers() -> Vec<RegistryServer> {
+    get_mcp_store_path().map(|path| read_json(&path)).unwrap_or_default()
+}
+
+fn save_shared_mcp_servers(servers: &[RegistryServer]) -> Result<(), String> {
+    write_json(&get_mcp_store_path()?, &servers)
+}
+
+/// Claude 的提示词模板即 `~/.claude/commands/*.md` 自定义斜杠命令
+fn get_claude_commands_dir() -> Result<PathBuf, String> {
+    Ok(get_claude_dir().map_err(|e| e.to_string())?.join("commands"))
+}
+
+fn get_claude_command_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("无效的提示词ID: {}", id));
+    }
+    Ok(get_claude_commands_dir()?.join(format!("{}.md", id)))
+}
+
+/// 目录下的 `*.md` 模板（文件名 -> 内容）
+fn read_md_templates(dir: &Path) -> BTreeMap<String, Value> {
+    let mut items = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return items;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("md") {
+            continue;
+        }
+        if let (Some(id), Ok(content)) = (path.file_stem().and_then(|s| s.to_str()), fs::read_to_string(&path)) {
+            items.insert(id.to_string(), Value::String(content));
+        }
+    }
+    items
+}
+
+// ============================================================================
+// 拉取仓库
+// ============================================================================
+
+/// 浅克隆或更新共享仓库，返回当前提交
+fn pull_repo(settings: &SharedConfigSettings, dir: &Path) -> Result<String, String> {
+    let branch = settings.branch.as_deref().map(str::trim).filter(|b| !b.is_empty());
+    let origin = run_git(dir, &["remote", "get-url", "origin"]).ok();
+
+    if origin.as_deref().map(str::trim) == Some(settings.repo_url.as_str()) {
+        run_git(dir, &["fetch", "--depth", "1", "origin", branch.unwrap_or("HEAD")])?;
+        run_git(dir, &["reset", "--hard", "FETCH_HEAD"])?;
+    } else {
+        // 首次同步或仓库地址变更：重新克隆
+        if dir.exists() {
+            fs::remove_dir_all(dir).map_err(|e| format!("清理共享仓库目录失败: {}", e))?;
+        }
+        let parent = dir.parent().ok_or("无效的共享仓库目录")?;
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        let target = dir.to_string_lossy().to_string();
+        let mut args = vec!["clone", "--depth", "1"];
+        if let Some(branch) = branch {
+            args.extend(["--branch", branch]);
+        }
+        // `--`：以 `-` 开头的地址不会被当成 git 选项
+        args.extend(["--", settings.repo_url.as_str(), target.as_str()]);
+        run_git(parent, &args)?;
+    }
+
+    Ok(run_git(dir, &["rev-parse", "HEAD"])?.trim().to_string())
+}
+
+// ============================================================================
+// 各类配置的读写
+// ============================================================================
+
+fn to_value<T: Serialize>(item: &T) -> Result<Value, String> {
+    serde_json::to_value(item).map_err(|e| format!("序列化失败: {}", e))
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, String> {
+    serde_json::from_value(value.clone()).map_err(|e| format!("解析共享配置失败: {}", e))
+}
+
+/// 本地存在、共享版本没有的密钥字段（更新时保留）
+fn local_only_secrets(local: Vec<String>, shared: Vec<String>) -> Vec<String> {
+    local.into_iter().filter(|f| !shared.contains(f)).collect()
+}
+
+impl SharedKind {
+    /// 代理商预设会设置请求地址和 `apiKeyHelper` 命令，权限档案会放宽工具权限，都需要用户逐项批准
+    fn requires_approval(self) -> bool {
+        matches!(
+            self,
+            SharedKind::ClaudeProvider
+                | SharedKind::CodexProvider
+                | SharedKind::GeminiProvider
+                | SharedKind::PermissionProfile
+        )
+    }
+
+    /// 共享仓库中的配置（ID -> 内容）
+    fn load_shared(self, repo: &Path) -> Result<BTreeMap<String, Value>, String> {
+        let mut items = BTreeMap::new();
+        match self {
+            SharedKind::ClaudeProvider | SharedKind::CodexProvider | SharedKind::GeminiProvider => {
+                let path = repo.join("providers.json");
+                if !path.exists() {
+                    return Ok(items);
+                }
+                let content = fs::read_to_string(&path).map_err(|e| format!("读取 providers.json 失败: {}", e))?;
+                let bundle: ProviderPresetBundle =
+                    serde_json::from_str(&content).map_err(|e| format!("解析 providers.json 失败: {}", e))?;
+                match self {
+                    SharedKind::ClaudeProvider => {
+                        for p in &bundle.claude {
+                            items.insert(p.id.clone(), to_value(p)?);
+                        }
+                    }
+                    SharedKind::CodexProvider => {
+                        for p in &bundle.codex {
+                            items.insert(p.id.clone(), to_value(p)?);
+                        }
+                    }
+                    _ => {
+                        for p in &bundle.gemini {
+                            items.insert(p.id.clone(), to_value(p)?);
+                        }
+                    }
+                }
+            }
+            SharedKind::PromptTemplate => return Ok(read_md_templates(&repo.join("prompts"))),
+            SharedKind::ClaudePromptTemplate => return Ok(read_md_templates(&repo.join("prompts").join("claude"))),
+            SharedKind::GeminiPromptTemplate => return Ok(read_md_templates(&repo.join("prompts").join("gemini"))),
+            SharedKind::McpServer => {
+                let path = repo.join("mcp_registry.json");
+                if !path.exists() {
+                    return Ok(items);
+                }
+                let content = fs::read_to_string(&path).map_err(|e| format!("读取 mcp_registry.json 失败: {}", e))?;
+                let registry: McpRegistry =
+                    serde_json::from_str(&content).map_err(|e| format!("解析 mcp_registry.json 失败: {}", e))?;
+                for server in &registry.servers {
+                    items.insert(server.name.clone(), to_value(server)?);
+                }
+            }
+            SharedKind::PermissionProfile => {
+                let path = repo.join("permission_profiles.json");
+                if !path.exists() {
+                    return Ok(items);
+                }
+                let content =
+                    fs::read_to_string(&path).map_err(|e| format!("读取 permission_profiles.json 失败: {}", e))?;
+                let profiles: Vec<PermissionProfile> = serde_json::from_str(&content)
+                    .map_err(|e| format!("解析 permission_profiles.json 失败: {}", e))?;
+                for profile in profiles {
+                    items.insert(profile.id.clone(), to_value(&PermissionProfile { builtin: false, ..profile })?);
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// 本地配置（ID -> 内容）
+    async fn load_local(self) -> Result<HashMap<String, Value>, String> {
+        let mut items = HashMap::new();
+        match self {
+            SharedKind::ClaudeProvider => {
+                for p in get_provider_presets()? {
+                    items.insert(p.id.clone(), to_value(&p)?);
+                }
+            }
+            SharedKind::CodexProvider => {
+                for p in get_codex_provider_presets().await? {
+                    items.insert(p.id.clone(), to_value(&p)?);
+                }
+            }
+            SharedKind::GeminiProvider => {
+                for p in get_gemini_provider_presets().await? {
+                    items.insert(p.id.clone(), to_value(&p)?);
+                }
+            }
+            SharedKind::PromptTemplate => {
+                for template in list_codex_prompts().await? {
+                    let content = get_codex_prompt(template.id.clone()).await?;
+                    items.insert(template.id, Value::String(content));
+                }
+            }
+            SharedKind::ClaudePromptTemplate => {
+                items.extend(read_md_templates(&get_claude_commands_dir()?));
+            }
+            SharedKind::GeminiPromptTemplate => {
+                for template in list_gemini_prompts().await? {
+                    let content = get_gemini_prompt(template.id.clone()).await?;
+                    items.insert(template.id, Value::String(content));
+                }
+            }
+            SharedKind::McpServer => {
+                for server in load_registry().servers {
+                    items.insert(server.name.clone(), to_value(&server)?);
+                }
+            }
+            SharedKind::PermissionProfile => {
+                for profile in list_all_permission_profiles() {
+                    items.insert(profile.id.clone(), to_value(&profile)?);
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// 用于比较的内容：代理商去掉密钥，权限档案去掉内置标记
+    fn normalize(self, value: &Value) -> Result<Value, String> {
+        match self {
+            SharedKind::ClaudeProvider => {
+                let mut p: ProviderConfig = from_value(value)?;
+                redact_claude(&mut p);
+                to_value(&p)
+            }
+            SharedKind::CodexProvider => {
+                let mut p: CodexProviderConfig = from_value(value)?;
+                redact_codex(&mut p);
+                to_value(&p)
+            }
+            SharedKind::GeminiProvider => {
+                let mut p: GeminiProviderConfig = from_value(value)?;
+                redact_gemini(&mut p);
+                to_value(&p)
+            }
+            SharedKind::PermissionProfile => {
+                let mut value = value.clone();
+                value["builtin"] = Value::Bool(false);
+                Ok(value)
+            }
+            SharedKind::PromptTemplate
+            | SharedKind::ClaudePromptTemplate
+            | SharedKind::GeminiPromptTemplate
+            | SharedKind::McpServer => Ok(value.clone()),
+        }
+    }
+
+    fn hash(self, value: &Value) -> Result<String, String> {
+        let normalized = self.normalize(value)?;
+        Ok(format!("{:x}", Sha256::digest(normalized.to_string().as_bytes())))
+    }
+
+    /// 写入共享版本；`local` 为被替换的本地版本
+    async fn write(self, id: &str, shared: &Value, local: Option<&Value>) -> Result<(), String> {
+        match self {
+            SharedKind::ClaudeProvider => {
+                let mut p: ProviderConfig = from_value(shared)?;
+                match local {
+                    Some(local) => {
+                        let local: ProviderConfig = from_value(local)?;
+                        let fields = local_only_secrets(redact_claude(&mut local.clone()), redact_claude(&mut p.clone()));
+                        restore_claude(&mut p, &local, &fields);
+                        update_provider_config(p)?;
+                    }
+                    None => {
+                        add_provider_config(p)?;
+                    }
+                }
+            }
+            SharedKind::CodexProvider => {
+                let mut p: CodexProviderConfig = from_value(shared)?;
+                match local {
+                    Some(local) => {
+                        let local: CodexProviderConfig = from_value(local)?;
+                        let fields = local_only_secrets(redact_codex(&mut local.clone()), redact_codex(&mut p.clone()));
+                        restore_codex(&mut p, &local, &fields);
+                        update_codex_provider_config(p).await?;
+                    }
+                    None => {
+                        add_codex_provider_config(p).await?;
+                    }
+                }
+            }
+            SharedKind::GeminiProvider => {
+                let mut p: GeminiProviderConfig = from_value(shared)?;
+                match local {
+                    Some(local) => {
+                        let local: GeminiProviderConfig = from_value(local)?;
+                        let fields =
+                            local_only_secrets(redact_gemini(&mut local.clone()), redact_gemini(&mut p.clone()));
+                        restore_gemini(&mut p, &local, &fields);
+                        update_gemini_provider_config(p).await?;
+                    }
+                    None => {
+                        add_gemini_provider_config(p).await?;
+                    }
+                }
+            }
+            SharedKind::PromptTemplate => {
+                let content = shared.as_str().ok_or("提示词模板内容无效")?;
+                save_codex_prompt(id.to_string(), content.to_string()).await?;
+            }
+            SharedKind::ClaudePromptTemplate => {
+                let content = shared.as_str().ok_or("提示词模板内容无效")?;
+                let path = get_claude_command_path(id)?;
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+                }
+                fs::write(&path, content).map_err(|e| format!("写入 {:?} 失败: {}", path, e))?;
+            }
+            SharedKind::GeminiPromptTemplate => {
+                let content = shared.as_str().ok_or("提示词模板内容无效")?;
+                save_gemini_prompt(id.to_string(), content.to_string()).await?;
+            }
+            SharedKind::McpServer => {
+                let server: RegistryServer = from_value(shared)?;
+                let mut servers = shared_mcp_servers();
+                servers.retain(|s| s.name != server.name);
+                servers.push(server);
+                save_shared_mcp_servers(&servers)?;
+            }
+            SharedKind::PermissionProfile => {
+                save_permission_profile(from_value(shared)?).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove(self, id: &str) -> Result<(), String> {
+        match self {
+            SharedKind::ClaudeProvider => delete_provider_config(id.to_string()).map(|_| ()),
+            SharedKind::CodexProvider => delete_codex_provider_config(id.to_string()).await.map(|_| ()).map_err(String::from),
+            SharedKind::GeminiProvider => delete_gemini_provider_config(id.to_string()).await.map(|_| ()).map_err(String::from),
+            SharedKind::PromptTemplate => delete_codex_prompt(id.to_string()).await.map(|_| ()).map_err(String::from),
+            SharedKind::ClaudePromptTemplate => {
+                let path = get_claude_command_path(id)?;
+                fs::remove_file(&path).map_err(|e| format!("删除 {:?} 失败: {}", path, e))
+            }
+            SharedKind::GeminiPromptTemplate => delete_gemini_prompt(id.to_string()).await.map(|_| ()).map_err(String::from),
+            SharedKind::McpServer => {
+                let mut servers = shared_mcp_servers();
+                servers.retain(|s| s.name != id);
+                save_shared_mcp_servers(&servers)
+            }
+            SharedKind::PermissionProfile => delete_permission_profile(id.to_string()).await,
+        }
+    }
+}
+
+// ============================================================================
+// 合并
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum SyncAction {
+    Add,
+    Update,
+    /// 内容一致（本地同名配置与共享版本相同时开始跟踪）
+    Unchanged,
+    Remove,
+    /// 保留本地版本
+    Conflict(String),
+    /// 共享仓库已删除、本地改动过：保留本地版本并不再跟踪
+    Detach,
+}
+
+/// 根据共享版本、本地版本和上次同步时的哈希决定每项配置的处理方式
+fn plan_sync(
+    shared: &BTreeMap<String, String>,
+    local: &HashMap<String, String>,
+    tracked: &HashMap<String, String>,
+) -> Vec<(String, SyncAction)> {
+    let mut plan = Vec::new();
+    for (id, shared_hash) in shared {
+        let action = match (local.get(id), tracked.get(id)) {
+            (None, _) => SyncAction::Add,
+            (Some(local_hash), _) if local_hash == shared_hash => SyncAction::Unchanged,
+            (Some(local_hash), Some(synced_hash)) if local_hash == synced_hash => SyncAction::Update,
+            (Some(_), Some(_)) => SyncAction::Conflict("本地修改过共享配置，已保留本地版本".to_string()),
+            (Some(_), None) => SyncAction::Conflict("与本地同名配置内容不同，已保留本地版本".to_string()),
+        };
+        plan.push((id.clone(), action));
+    }
+
+    let mut removed: Vec<&String> = tracked.keys().filter(|id| !shared.contains_key(*id)).collect();
+    removed.sort();
+    for id in removed {
+        match local.get(id) {
+            Some(local_hash) if local_hash == &tracked[id] => plan.push((id.clone(), SyncAction::Remove)),
+            Some(_) => plan.push((id.clone(), SyncAction::Detach)),
+            None => {}
+        }
+    }
+    plan
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_shared_config_settings() -> Result<SharedConfigSettings, String> {
+    Ok(load_shared_config_settings())
+}
+
+#[tauri::command]
+pub async fn set_shared_config_settings(settings: SharedConfigSettings) -> Result<(), String> {
+    let settings = SharedConfigSettings {
+        repo_url: settings.repo_url.trim().to_string(),
+        ..settings
+    };
+    write_json(&get_settings_path()?, &settings)
+}
+
+/// 同步状态：来源为共享仓库的配置和上次同步的冲突
+#[tauri::command]
+pub async fn get_shared_config_status() -> Result<SharedConfigStatus, String> {
+    let state = load_state();
+    Ok(SharedConfigStatus {
+        settings: load_shared_config_settings(),
+        commit: state.commit,
+        synced_at: state.synced_at,
+        items: state
+            .items
+            .into_iter()
+            .map(|item| SharedItem { kind: item.kind, id: item.id })
+            .collect(),
+        conflicts: state.conflicts,
+        pending: state.pending,
+    })
+}
+
+/// 拉取共享仓库并与本地配置合并
+///
+/// `approved` 为用户批准应用的代理商预设和权限档案（取自上次结果的 `pending`），
+/// 其余需要批准的新增或更新继续留在 `pending` 中，不会写入本地。
+#[tauri::command]
+pub async fn sync_shared_config(approved: Option<Vec<SharedApproval>>) -> Result<SharedConfigSyncReport, String> {
+    let approved = approved.unwrap_or_default();
+    let settings = load_shared_config_settings();
+    if settings.repo_url.is_empty() {
+        return Err("未配置共享配置仓库".to_string());
+    }
+
+    let repo_dir = get_sync_dir()?.join("repo");
+    let commit = {
+        let settings = settings.clone();
+        let repo_dir = repo_dir.clone();
+        tokio::task::spawn_blocking(move || pull_repo(&settings, &repo_dir))
+            .await
+            .map_err(|e| format!("拉取共享仓库失败: {}", e))??
+    };
+    log::info!("[ConfigSync] Pulled {} at {}", settings.repo_url, commit);
+
+    let previous = load_state();
+    let mut report = SharedConfigSyncReport {
+        commit: commit.clone(),
+        synced_at: chrono::Utc::now().to_rfc3339(),
+        added: Vec::new(),
+        updated: Vec::new(),
+        removed: Vec::new(),
+        unchanged: 0,
+        conflicts: Vec::new(),
+        pending: Vec::new(),
+    };
+    let mut tracked_items = Vec::new();
+
+    for kind in KINDS {
+        let shared = kind.load_shared(&repo_dir)?;
+        let local = kind.load_local().await?;
+        let shared_hashes = shared
+            .iter()
+            .map(|(id, value)| Ok((id.clone(), kind.hash(value)?)))
+            .collect::<Result<BTreeMap<_, _>, String>>()?;
+        let local_hashes = local
+            .iter()
+            .filter_map(|(id, value)| kind.hash(value).ok().map(|hash| (id.clone(), hash)))
+            .collect::<HashMap<_, _>>();
+        let tracked: HashMap<String, String> = previous
+            .items
+            .iter()
+            .filter(|item| item.kind == kind)
+            .map(|item| (item.id.clone(), item.hash.clone()))
+            .collect();
+
+        for (id, action) in plan_sync(&shared_hashes, &local_hashes, &tracked) {
+            let item = SharedItem { kind, id: id.clone() };
+            let track = |hash: &str| TrackedItem { kind, id: id.clone(), hash: hash.to_string() };
+            let conflict = |reason: String| SyncConflict { kind, id: id.clone(), reason };
+            match action {
+                SyncAction::Add | SyncAction::Update
+                    if kind.requires_approval()
+                        && !approved.iter().any(|a| a.kind == kind && a.id == id && a.hash == shared_hashes[&id]) =>
+                {
+                    // 等待批准；更新时继续跟踪上次同步的版本
+                    if let Some(hash) = tracked.get(&id) {
+                        tracked_items.push(track(hash));
+                    }
+                    report.pending.push(PendingSharedItem {
+                        approval: SharedApproval { kind, id: id.clone(), hash: shared_hashes[&id].clone() },
+                        is_new: action == SyncAction::Add,
+                        content: kind.normalize(&shared[&id])?,
+                    });
+                }
+                SyncAction::Add | SyncAction::Update => {
+                    let result = kind.write(&id, &shared[&id], local.get(&id)).await;
+                    match result {
+                        Ok(()) => {
+                            tracked_items.push(track(&shared_hashes[&id]));
+                            if action == SyncAction::Add {
+                                report.added.push(item);
+                            } else {
+                                report.updated.push(item);
+                            }
+                        }
+                        Err(e) => {
+                            if let Some(hash) = tracked.get(&id) {
+                                tracked_items.push(track(hash));
+                            }
+                            report.conflicts.push(conflict(e));
+                        }
+                    }
+                }
+                SyncAction::Unchanged => {
+                    tracked_items.push(track(&shared_hashes[&id]));
+                    report.unchanged += 1;
+                }
+                SyncAction::Remove => match kind.remove(&id).await {
+                    Ok(()) => report.removed.push(item),
+                    Err(e) => report.conflicts.push(conflict(e)),
+                },
+                SyncAction::Conflict(reason) => {
+                    // 继续跟踪，本地改回共享版本后恢复同步
+                    if let Some(hash) = tracked.get(&id) {
+                        tracked_items.push(track(hash));
+                    }
+                    report.conflicts.push(conflict(reason));
+                }
+                SyncAction::Detach => {
+                    report
+                        .conflicts
+                        .push(conflict("共享仓库已删除该配置，本地修改过，已保留为本地配置".to_string()));
+                }
+            }
+        }
+    }
+
+    let state = SyncState {
+        commit: Some(commit),
+        synced_at: Some(report.synced_at.clone()),
+        items: tracked_items,
+        conflicts: report.conflicts.clone(),
+        pending: report.pending.clone(),
+    };
+    write_json(&get_state_path()?, &state)?;
+    log::info!(
+        "[ConfigSync] Synced: {} added, {} updated, {} removed, {} unchanged, {} conflicts, {} awaiting approval",
+        report.added.len(),
+        report.updated.len(),
+        report.removed.len(),
+        report.unchanged,
+        report.conflicts.len(),
+        report.pending.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map<'a>(pairs: &[(&'a str, &'a str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn plans_merge_with_local_changes() {
+        let shared: BTreeMap<String, String> = map(&[("new", "n1"), ("same", "s1"), ("upstream", "u2"), ("edited", "e2"), ("clash", "c1")])
+            .into_iter()
+            .collect();
+        let local = map(&[("same", "s1"), ("upstream", "u1"), ("edited", "e-local"), ("clash", "c-local"), ("gone", "g1"), ("gone-edited", "x")]);
+        let tracked = map(&[("same", "s1"), ("upstream", "u1"), ("edited", "e1"), ("gone", "g1"), ("gone-edited", "g1"), ("deleted-locally", "d1")]);
+
+        let plan: HashMap<String, SyncAction> = plan_sync(&shared, &local, &tracked).into_iter().collect();
+        assert_eq!(plan["new"], SyncAction::Add);
+        assert_eq!(plan["same"], SyncAction::Unchanged);
+        assert_eq!(plan["upstream"], SyncAction::Update);
+        assert!(matches!(plan["edited"], SyncAction::Conflict(_)));
+        assert!(matches!(plan["clash"], SyncAction::Conflict(_)));
+        assert_eq!(plan["gone"], SyncAction::Remove);
+        assert_eq!(plan["gone-edited"], SyncAction::Detach);
+        assert!(!plan.contains_key("deleted-locally"));
+
+        // 本地同名配置与共享版本一致时直接纳入同步
+        let adopt = plan_sync(&map(&[("a", "h")]).into_iter().collect(), &map(&[("a", "h")]), &HashMap::new());
+        assert_eq!(adopt, vec![("a".to_string(), SyncAction::Unchanged)]);
+    }
+
+    #[test]
+    fn reads_prompt_templates_per_engine() {
+        let repo = tempfile::tempdir().unwrap();
+        let prompts = repo.path().join("prompts");
+        fs::create_dir_all(prompts.join("claude")).unwrap();
+        fs::create_dir_all(prompts.join("gemini.md")).unwrap();
+        fs::write(prompts.join("review.md"), "codex").unwrap();
+        fs::write(prompts.join("claude").join("review.md"), "claude").unwrap();
+
+        let codex = SharedKind::PromptTemplate.load_shared(repo.path()).unwrap();
+        assert_eq!(codex.keys().collect::<Vec<_>>(), vec!["review"]);
+        assert_eq!(codex["review"], "codex");
+        let claude = SharedKind::ClaudePromptTemplate.load_shared(repo.path()).unwrap();
+        assert_eq!(claude["review"], "claude");
+        assert!(SharedKind::GeminiPromptTemplate.load_shared(repo.path()).unwrap().is_empty());
+
+        assert!(SharedKind::ClaudeProvider.requires_approval());
+        assert!(SharedKind::PermissionProfile.requires_approval());
+        assert!(!SharedKind::ClaudePromptTemplate.requires_approval());
+    }
+}
@@ -3,7 +3,8 @@
 /// These commands integrate the AutoCompactManager with the frontend,
 /// providing comprehensive context window management capabilities.
 use crate::commands::context_manager::{
-    AutoCompactConfig, AutoCompactManager, AutoCompactState, SessionContext,
+    build_context_bundle, AutoCompactConfig, AutoCompactManager, AutoCompactState, ContextBundle,
+    SessionContext,
 };
 use log::{error, info};
 use tauri::{command, AppHandle, Manager, State};
@@ -191,6 +192,25 @@ pub async fn get_auto_compact_status(
     })
 }
 
+/// Select and truncate project files into a token budget for `prompt`
+///
+/// Files are ranked by relevance to the prompt, recency and git churn. When the
+/// semantic index is enabled its search results also count as relevance. The
+/// returned bundle's `rendered` text can be prepended to the prompt before
+/// execution. `candidates` restricts the selection to the given relative paths.
+#[command]
+pub async fn build_context(
+    project_path: String,
+    prompt: String,
+    budget: usize,
+    candidates: Option<Vec<String>>,
+) -> Result<ContextBundle, String> {
+    let semantic = crate::commands::semantic_index::file_relevance(&project_path, &prompt).await;
+    tokio::task::spawn_blocking(move || build_context_bundle(&project_path, &prompt, budget, candidates, semantic))
+        .await
+        .map_err(|e| format!("Failed to build context: {}", e))?
+}
+
 /// Auto-compact status information for the UI
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct AutoCompactStatus {
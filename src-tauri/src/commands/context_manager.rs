@@ -1,6 +1,7 @@
 use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 /// Auto-compact context management system for Claude Code SDK integration
 ///
 /// This module provides intelligent context window management with automatic compaction
@@ -9,6 +10,8 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 use tokio::time::sleep;
 
+use super::simple_git::run_git;
+
 /// Configuration for auto-compact behavior
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoCompactConfig {
@@ -467,3 +470,370 @@ impl AutoCompactManager {
 /// State wrapper for AutoCompactManager
 #[derive(Clone)]
 pub struct AutoCompactState(pub Arc<AutoCompactManager>);
+
+// ============================================================================
+// Token-aware context selection
+// ============================================================================
+
+/// Files larger than this are never read into the context
+const MAX_CONTEXT_FILE_BYTES: u64 = 512 * 1024;
+
+/// Upper bound on candidate files considered when none are given explicitly
+const MAX_CONTEXT_CANDIDATES: usize = 5000;
+
+/// Recently modified files moved to the front of the candidate list
+const MAX_RECENT_CANDIDATES: usize = 200;
+
+/// A file is only truncated into the remaining budget if at least this many tokens are left
+const MIN_TRUNCATED_TOKENS: usize = 200;
+
+/// Git history window used for churn scoring
+const CHURN_WINDOW: &str = "90.days";
+
+/// Weights of the ranking signals (sum to 1.0)
+const RELEVANCE_WEIGHT: f64 = 0.6;
+const RECENCY_WEIGHT: f64 = 0.2;
+const CHURN_WEIGHT: f64 = 0.2;
+
+/// Token count with the default vocabulary, shared by all engines
+pub fn estimate_tokens(text: &str) -> usize {
+    super::tokenizer::count_text("", text)
+}
+
+/// A file selected into the context bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextFile {
+    /// Path relative to the project root
+    pub path: String,
+    pub content: String,
+    pub tokens: usize,
+    /// Combined ranking score (0.0-1.0)
+    pub score: f64,
+    /// Content was cut to fit the remaining budget
+    pub truncated: bool,
+}
+
+/// Result of `build_context`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextBundle {
+    pub files: Vec<ContextFile>,
+    pub total_tokens: usize,
+    pub budget: usize,
+    /// Relevant files left out because the budget ran out
+    pub omitted: Vec<String>,
+    /// Files rendered as a single block ready to be prepended to a prompt
+    pub rendered: String,
+}
+
+/// A candidate file with its ranking inputs
+#[derive(Debug, Clone)]
+struct ContextCandidate {
+    path: String,
+    content: String,
+    /// Seconds since last modification
+    age_secs: u64,
+    /// Commits touching the file within `CHURN_WINDOW`
+    churn: u32,
+}
+
+fn git_output(project_path: &Path, args: &[&str]) -> Option<String> {
+    run_git(project_path, args).ok()
+}
+
+/// Tracked and untracked-but-not-ignored files; falls back to a directory walk outside git
+pub fn list_candidate_paths(project_path: &Path) -> Vec<String> {
+    if let Some(output) = git_output(
+        project_path,
+        &["ls-files", "--cached", "--others", "--exclude-standard"],
+    ) {
+        return output
+            .lines()
+            .filter(|l| !l.is_empty())
+            .take(MAX_CONTEXT_CANDIDATES)
+            .map(|l| l.to_string())
+            .collect();
+    }
+
+    const SKIPPED_DIRS: [&str; 4] = ["node_modules", "target", "dist", "build"];
+    walkdir::WalkDir::new(project_path)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0 || !(name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            e.path()
+                .strip_prefix(project_path)
+                .ok()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+        })
+        .take(MAX_CONTEXT_CANDIDATES)
+        .collect()
+}
+
+/// Moves recently modified files to the front so they are kept when the
+/// candidate list is capped on large repos
+fn with_recent_first(project_path: &str, paths: Vec<String>) -> Vec<String> {
+    let recent = super::recent_files::recently_modified_files(project_path, None, Some(MAX_RECENT_CANDIDATES))
+        .unwrap_or_default();
+    let mut ordered: Vec<String> = recent.into_iter().map(|f| f.path).collect();
+    let seen: std::collections::HashSet<String> = ordered.iter().cloned().collect();
+    ordered.extend(paths.into_iter().filter(|p| !seen.contains(p)));
+    ordered
+}
+
+/// Commit counts per file within the churn window
+pub fn git_churn(project_path: &Path) -> HashMap<String, u32> {
+    let since = format!("--since={}", CHURN_WINDOW);
+    let mut churn = HashMap::new();
+    if let Some(output) = git_output(project_path, &["log", &since, "--name-only", "--format="]) {
+        for path in output.lines().filter(|l| !l.is_empty()) {
+            *churn.entry(path.to_string()).or_insert(0) += 1;
+        }
+    }
+    churn
+}
+
+/// Reads a candidate, skipping large, binary and non-UTF-8 files
+fn load_candidate(project_path: &Path, path: &str, churn: &HashMap<String, u32>) -> Option<ContextCandidate> {
+    let full_path = project_path.join(path);
+    let metadata = std::fs::metadata(&full_path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_CONTEXT_FILE_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(&full_path).ok()?;
+    if bytes.iter().take(8192).any(|b| *b == 0) {
+        return None;
+    }
+    let content = String::from_utf8(bytes).ok()?;
+    let age_secs = metadata
+        .modified()
+        .ok()
+        .and_then(|m| SystemTime::now().duration_since(m).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(u64::MAX);
+    Some(ContextCandidate {
+        path: path.to_string(),
+        content,
+        age_secs,
+        churn: churn.get(path).copied().unwrap_or(0),
+    })
+}
+
+/// Scores candidates by prompt relevance, recency and git churn; highest first.
+/// Files with no relevance to the prompt are dropped. `semantic` holds per-file
+/// similarity from the semantic index, used when higher than keyword relevance.
+fn rank_candidates(
+    candidates: Vec<ContextCandidate>,
+    prompt: &str,
+    semantic: Option<&HashMap<String, f64>>,
+) -> Vec<(ContextCandidate, f64)> {
+    let prompt_lower = prompt.to_lowercase();
+    let prompt_words = super::project_memory::keywords(prompt);
+    let max_churn = candidates.iter().map(|c| c.churn).max().unwrap_or(0).max(1) as f64;
+
+    let mut ranked: Vec<(ContextCandidate, f64)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            // A path mentioned verbatim in the prompt is always the most relevant
+            let mentioned = prompt_lower.contains(&candidate.path.to_lowercase());
+            let relevance = if mentioned {
+                1.0
+            } else if prompt_words.is_empty() {
+                0.0
+            } else {
+                let path_words = super::project_memory::keywords(&candidate.path);
+                let content_words = super::project_memory::keywords(&candidate.content);
+                let in_path = prompt_words.intersection(&path_words).count() as f64;
+                let in_content = prompt_words.intersection(&content_words).count() as f64;
+                ((2.0 * in_path + in_content) / (2.0 * prompt_words.len() as f64)).min(1.0)
+            };
+            let similarity = semantic.and_then(|s| s.get(&candidate.path)).copied().unwrap_or(0.0);
+            let relevance = relevance.max(similarity);
+            if relevance <= 0.0 {
+                return None;
+            }
+            let age_days = candidate.age_secs as f64 / 86_400.0;
+            let recency = 1.0 / (1.0 + age_days / 7.0);
+            let churn = candidate.churn as f64 / max_churn;
+            let score = RELEVANCE_WEIGHT * relevance + RECENCY_WEIGHT * recency + CHURN_WEIGHT * churn;
+            Some((candidate, score))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.path.cmp(&b.0.path)));
+    ranked
+}
+
+/// Keeps whole lines from the start of `content` within `max_tokens`;
+/// returns the kept text and its token count (summed per line)
+fn truncate_to_tokens(content: &str, max_tokens: usize) -> (String, usize) {
+    let mut kept = String::new();
+    let mut tokens = 0;
+    for line in content.split_inclusive('\n') {
+        let cost = estimate_tokens(line);
+        if tokens + cost > max_tokens {
+            break;
+        }
+        tokens += cost;
+        kept.push_str(line);
+    }
+    (kept, tokens)
+}
+
+fn render_context(files: &[ContextFile]) -> String {
+    files
+        .iter()
+        .map(|file| {
+            let marker = if file.truncated { " truncated=\"true\"" } else { "" };
+            format!("<file path=\"{}\"{}>\n{}\n</file>", file.path, marker, file.content.trim_end())
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Greedily packs ranked files into the budget, truncating the first file that does not fit
+fn pack_context(ranked: Vec<(ContextCandidate, f64)>, budget: usize) -> ContextBundle {
+    let mut files = Vec::new();
+    let mut omitted = Vec::new();
+    let mut remaining = budget;
+
+    for (candidate, score) in ranked {
+        let tokens = estimate_tokens(&candidate.content);
+        if tokens <= remaining {
+            remaining -= tokens;
+            files.push(ContextFile { path: candidate.path, content: candidate.content, tokens, score, truncated: false });
+        } else if remaining >= MIN_TRUNCATED_TOKENS {
+            let (content, tokens) = truncate_to_tokens(&candidate.content, remaining);
+            remaining -= tokens;
+            files.push(ContextFile { path: candidate.path, content, tokens, score, truncated: true });
+        } else {
+            omitted.push(candidate.path);
+        }
+    }
+
+    let rendered = render_context(&files);
+    ContextBundle { total_tokens: budget - remaining, budget, files, omitted, rendered }
+}
+
+/// Candidate paths must stay inside the project root
+fn validate_candidate(path: &str) -> Result<(), String> {
+    let relative = Path::new(path);
+    let escapes = relative.is_absolute()
+        || path.starts_with('/')
+        || path.starts_with('\\')
+        || relative
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir));
+    if path.is_empty() || escapes {
+        return Err(format!("Context candidate must be a path inside the project: {}", path));
+    }
+    Ok(())
+}
+
+/// Builds a context bundle for `prompt` within `budget` tokens.
+///
+/// `candidates` are paths relative to the project root (absolute paths and `..`
+/// are rejected); when omitted, all files not ignored by git are considered. `semantic` is optional per-file similarity
+/// from the semantic index. Blocking: run on a blocking thread.
+pub fn build_context_bundle(
+    project_path: &str,
+    prompt: &str,
+    budget: usize,
+    candidates: Option<Vec<String>>,
+    semantic: Option<HashMap<String, f64>>,
+) -> Result<ContextBundle, String> {
+    let root = Path::new(project_path);
+    if !root.is_dir() {
+        return Err(format!("Project path does not exist: {}", project_path));
+    }
+    if let Some(candidates) = &candidates {
+        candidates.iter().try_for_each(|path| validate_candidate(path))?;
+    }
+    let paths = candidates.unwrap_or_else(|| with_recent_first(project_path, list_candidate_paths(root)));
+    let churn = git_churn(root);
+    let loaded: Vec<ContextCandidate> = paths
+        .iter()
+        .filter_map(|path| load_candidate(root, path, &churn))
+        .collect();
+    let considered = loaded.len();
+
+    let bundle = pack_context(rank_candidates(loaded, prompt, semantic.as_ref()), budget);
+    info!(
+        "Built context for {}: {} of {} files, {}/{} tokens",
+        project_path,
+        bundle.files.len(),
+        considered,
+        bundle.total_tokens,
+        budget
+    );
+    Ok(bundle)
+}
+
+/// Context block for a new session's prompt (project default `contextBudget`);
+/// None when nothing relevant fits or selection fails
+pub async fn context_for_prompt(project_path: &str, prompt: &str, budget: usize) -> Option<String> {
+    let semantic = super::semantic_index::file_relevance(project_path, prompt).await;
+    let (path, text) = (project_path.to_string(), prompt.to_string());
+    let bundle = tokio::task::spawn_blocking(move || build_context_bundle(&path, &text, budget, None, semantic))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+    match bundle {
+        Ok(bundle) if !bundle.files.is_empty() => {
+            Some(format!("<project-context>\n{}\n</project-context>", bundle.rendered))
+        }
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("Failed to build context for {}: {}", project_path, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(path: &str, content: &str, age_days: u64, churn: u32) -> ContextCandidate {
+        ContextCandidate { path: path.into(), content: content.into(), age_secs: age_days * 86_400, churn }
+    }
+
+    #[test]
+    fn ranks_by_relevance_and_fits_budget() {
+        let long_body = "fn cache_lookup() {}\n".repeat(200);
+        let candidates = vec![
+            candidate("src/cache.rs", &long_body, 30, 0),
+            candidate("src/loader.rs", "// loads files through the cache\nfn load() {}\n", 0, 5),
+            candidate("README.md", "Project overview\n", 0, 9),
+            candidate("src/main.rs", "fn main() {}\n", 1, 2),
+            candidate("docs/cache.md", "cache notes\n", 365, 0),
+        ];
+        let ranked = rank_candidates(candidates, "Add expiry to the cache used by src/main.rs", None);
+        let order: Vec<&str> = ranked.iter().map(|(c, _)| c.path.as_str()).collect();
+        // README.md shares no keywords with the prompt and is dropped
+        assert_eq!(order, vec!["src/main.rs", "src/loader.rs", "src/cache.rs", "docs/cache.md"]);
+
+        let bundle = pack_context(ranked, 600);
+        assert_eq!(bundle.files.len(), 3);
+        assert!(!bundle.files[1].truncated);
+        assert!(bundle.files[2].truncated);
+        assert!(bundle.total_tokens <= 600);
+        assert_eq!(bundle.omitted, vec!["docs/cache.md"]);
+        assert!(bundle.rendered.contains("<file path=\"src/cache.rs\" truncated=\"true\">"));
+    }
+
+    #[test]
+    fn rejects_candidates_outside_the_project() {
+        assert!(validate_candidate("src/main.rs").is_ok());
+        assert!(validate_candidate("./docs/a b.md").is_ok());
+        assert!(validate_candidate("../secrets.txt").is_err());
+        assert!(validate_candidate("src/../../etc/passwd").is_err());
+        assert!(validate_candidate("/etc/passwd").is_err());
+        assert!(validate_candidate("").is_err());
+        assert!(build_context_bundle(".", "x", 100, Some(vec!["../x".into()]), None).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! 自定义命令
+//!
+//! 用户在 `~/.anycode/commands/<name>.md` 中定义斜杠命令（如 `/review`、`/changelog`），
+//! frontmatter 指定引擎和模型，正文是提示词模板：
+//!
+//! ```markdown
+//! ---
+//! description: 审查当前改动
+//! engine: codex
+//! model: gpt-5-codex
+//! ---
+//! 审查工作区中未提交的改动，重点关注：{{args}}
+//! ```
+//!
+//! `run_custom_command` 展开模板后在对应引擎上启动新会话。`{{args}}` 为命令参数；
+//! 正文中没有 `{{args}}` 时参数追加在提示词之后。
+
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use super::prompt_library::{
+    execute_in_new_session, extract_variables, render_template, split_frontmatter,
+};
+use super::data_root::anycode_dir;
+
+/// 命令参数变量
+const ARGS_VARIABLE: &str = "args";
+
+const SUPPORTED_ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 命令 frontmatter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CommandMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    engine: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+}
+
+/// 自定义命令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomCommand {
+    /// 命令名（不含 `/`，即文件名）
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 执行引擎，默认 claude
+    #[serde(default = "default_engine")]
+    pub engine: String,
+    /// 模型，为空时使用项目默认值
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 提示词模板正文
+    pub prompt: String,
+}
+
+fn default_engine() -> String {
+    "claude".to_string()
+}
+
+fn get_commands_dir() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("commands"))
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return Err("命令名只能包含字母、数字、横线和下划线".to_string());
+    }
+    Ok(())
+}
+
+fn parse_command(name: &str, content: &str) -> Result<CustomCommand, String> {
+    let (frontmatter, body) = split_frontmatter(content);
+    let meta: CommandMeta = match frontmatter {
+        Some(yaml) => serde_yaml::from_str(yaml)
+            .map_err(|e| format!("命令 '{}' 的 frontmatter 无效: {}", name, e))?,
+        None => CommandMeta::default(),
+    };
+    Ok(CustomCommand {
+        name: name.to_string(),
+        description: meta.description,
+        engine: meta.engine.unwrap_or_else(default_engine),
+        model: meta.model.filter(|m| !m.is_empty()),
+        prompt: body.trim().to_string(),
+    })
+}
+
+fn serialize_command(command: &CustomCommand) -> Result<String, String> {
+    let meta = CommandMeta {
+        description: command.description.clone().filter(|d| !d.is_empty()),
+        engine: Some(command.engine.clone()),
+        model: command.model.clone().filter(|m| !m.is_empty()),
+    };
+    let yaml = serde_yaml::to_string(&meta).map_err(|e| format!("序列化命令失败: {}", e))?;
+    Ok(format!("---\n{}---\n{}\n", yaml, command.prompt.trim()))
+}
+
+fn load_command(name: &str) -> Result<CustomCommand, String> {
+    validate_name(name)?;
+    let path = get_commands_dir()?.join(format!("{}.md", name));
+    if !path.exists() {
+        return Err(format!("自定义命令不存在: /{}", name));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取自定义命令失败: {}", e))?;
+    parse_command(name, &content)
+}
+
+/// 展开命令模板
+fn expand_command(command: &CustomCommand, args: &str) -> Result<String, String> {
+    let mut variables = HashMap::new();
+    variables.insert(ARGS_VARIABLE.to_string(), args.to_string());
+    let rendered = render_template(&command.prompt, &variables)?;
+
+    let uses_args = extract_variables(&command.prompt).iter().any(|v| v == ARGS_VARIABLE);
+    if uses_args || args.trim().is_empty() {
+        Ok(rendered)
+    } else {
+        Ok(format!("{}\n\n{}", rendered, args.trim()))
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出所有自定义命令（按名称排序）
+#[tauri::command]
+pub async fn list_custom_commands() -> Result<Vec<CustomCommand>, String> {
+    let dir = get_commands_dir()?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut commands = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("md") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_command(name, &content))
+        {
+            Ok(command) => commands.push(command),
+            Err(e) => log::warn!("[CustomCommands] Skipping {:?}: {}", path, e),
+        }
+    }
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(commands)
+}
+
+/// 新建或覆盖自定义命令
+#[tauri::command]
+pub async fn save_custom_command(command: CustomCommand) -> Result<(), String> {
+    validate_name(&command.name)?;
+    if !SUPPORTED_ENGINES.contains(&command.engine.as_str()) {
+        return Err(tr!("engine.unsupported", engine = command.engine));
+    }
+    if command.prompt.trim().is_empty() {
+        return Err("命令提示词不能为空".to_string());
+    }
+
+    let dir = get_commands_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建命令目录失败: {}", e))?;
+    let path = dir.join(format!("{}.md", command.name));
+    fs::write(&path, serialize_command(&command)?).map_err(|e| format!("保存自定义命令失败: {}", e))?;
+    log::info!("[CustomCommands] Saved /{} ({})", command.name, command.engine);
+    Ok(())
+}
+
+/// 删除自定义命令
+#[tauri::command]
+pub async fn delete_custom_command(name: String) -> Result<(), String> {
+    validate_name(&name)?;
+    let path = get_commands_dir()?.join(format!("{}.md", name));
+    if !path.exists() {
+        return Err(format!("自定义命令不存在: /{}", name));
+    }
+    fs::remove_file(&path).map_err(|e| format!("删除自定义命令失败: {}", e))
+}
+
+/// 展开命令并在对应引擎上启动新会话
+#[tauri::command]
+pub async fn run_custom_command(
+    name: String,
+    project_path: String,
+    args: Option<String>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let name = name.trim_start_matches('/');
+    let command = load_command(name)?;
+    let prompt = expand_command(&command, args.as_deref().unwrap_or_default())?;
+    log::info!("[CustomCommands] Running /{} with {} in {}", name, command.engine, project_path);
+
+    execute_in_new_session(app_handle, &command.engine, project_path, prompt, command.model).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_round_trips_and_expands_args() {
+        let command = CustomCommand {
+            name: "review".into(),
+            description: Some("Review changes".into()),
+            engine: "codex".into(),
+            model: None,
+            prompt: "Review the diff, focusing on {{args}}.".into(),
+        };
+        let content = serialize_command(&command).unwrap();
+        assert_eq!(parse_command("review", &content).unwrap(), command);
+        assert_eq!(
+            expand_command(&command, "error handling").unwrap(),
+            "Review the diff, focusing on error handling."
+        );
+
+        let changelog = parse_command("changelog", "Write a changelog entry.").unwrap();
+        assert_eq!(changelog.engine, "claude");
+        assert_eq!(
+            expand_command(&changelog, "since v1.2").unwrap(),
+            "Write a changelog entry.\n\nsince v1.2"
+        );
+    }
+}
@@ -0,0 +1,587 @@
+//! AnyCode 数据目录（默认 ~/.anycode）与便携模式
+//!
+//! 数据目录按以下顺序解析：
+//! 1. 环境变量 `ANYCODE_DATA_DIR`
+//! 2. 便携模式：可执行文件旁存在 `portable` 标记文件时，使用同目录下的 `data/`
+//! 3. `~/.anycode/data_root.json` 中记录的自定义目录
+//! 4. 默认的 `~/.anycode`
+//!
+//! 自定义 / 便携模式下 agents.db 等应用数据也放在数据目录中；默认模式仍使用系统的应用数据目录，
+//! 与旧版本保持一致。~/.claude、~/.codex、~/.gemini 归各 CLI 所有，不随数据目录迁移。
+//!
+//! `migrate_data_root` 只切换指针 / 便携标记并在默认目录写入暂存标记（`data_root_pending.json`）；
+//! 下次启动时在打开 agents.db 之前由 `apply_pending_migration` 把现有数据复制到新目录、改写其中
+//! （以及 Claude settings.json 中 hook 桥接）对旧路径的引用，然后删除旧数据。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+
+use super::file_operations::write_atomic;
+
+const ENV_DATA_DIR: &str = "ANYCODE_DATA_DIR";
+const POINTER_FILE: &str = "data_root.json";
+const PENDING_FILE: &str = "data_root_pending.json";
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DIR: &str = "data";
+const AGENT_DB_FILE: &str = "agents.db";
+const DB_FILES: &[&str] = &["agents.db", "agents.db-wal", "agents.db-shm"];
+
+/// 应用数据目录中除 agents.db 外需要随迁移移动的文件
+const APP_DATA_FILES: &[&str] = &["engine_config_profiles.json"];
+
+/// 改写路径引用时检查的文本文件类型
+const TEXT_EXTENSIONS: &[&str] = &["json", "jsonl", "sh", "toml", "md", "txt"];
+
+/// 数据目录模式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataRootMode {
+    #[default]
+    Default,
+    Custom,
+    Portable,
+    /// 由 ANYCODE_DATA_DIR 指定（不能在应用内迁移）
+    Env,
+}
+
+/// ~/.anycode/data_root.json
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DataRootPointer {
+    path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataRootInfo {
+    pub mode: DataRootMode,
+    pub path: String,
+    /// agents.db 等应用数据所在目录
+    pub app_data_path: Option<String>,
+    pub default_path: Option<String>,
+    /// 便携模式将使用的目录（无法确定可执行文件位置时为 None）
+    pub portable_path: Option<String>,
+    /// 等待下次启动执行的迁移
+    pub pending_migration: Option<PendingMigration>,
+    /// 本次启动时执行的迁移
+    pub last_migration: Option<DataRootMigrationReport>,
+}
+
+/// ~/.anycode/data_root_pending.json：等待下次启动执行的迁移
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingMigration {
+    pub mode: DataRootMode,
+    pub from: String,
+    pub to: String,
+    /// 迁移前后 agents.db 等应用数据所在目录
+    pub from_app_data: String,
+    pub to_app_data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataRootMigrationReport {
+    pub mode: DataRootMode,
+    pub from: String,
+    pub to: String,
+    pub copied_files: usize,
+    /// 改写了旧路径引用的文件数
+    pub updated_references: usize,
+    /// 未能清理的旧文件等非致命问题
+    pub warnings: Vec<String>,
+}
+
+static CURRENT: Lazy<RwLock<Option<(DataRootMode, PathBuf)>>> = Lazy::new(|| RwLock::new(resolve()));
+static LAST_MIGRATION: RwLock<Option<DataRootMigrationReport>> = RwLock::new(None);
+
+fn default_root() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".anycode"))
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+fn portable_root() -> Option<PathBuf> {
+    exe_dir().map(|dir| dir.join(PORTABLE_DIR))
+}
+
+fn read_pending(default_root: &Path) -> Option<PendingMigration> {
+    let content = fs::read_to_string(default_root.join(PENDING_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn read_pointer(default_root: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(default_root.join(POINTER_FILE)).ok()?;
+    let pointer: DataRootPointer = serde_json::from_str(&content).ok()?;
+    pointer.path.filter(|p| !p.trim().is_empty()).map(PathBuf::from)
+}
+
+/// 按优先级选出数据目录（便于测试，不读取环境）
+fn pick_root(
+    env_dir: Option<PathBuf>,
+    portable: Option<PathBuf>,
+    pointer: Option<PathBuf>,
+    default: Option<PathBuf>,
+) -> Option<(DataRootMode, PathBuf)> {
+    env_dir
+        .map(|p| (DataRootMode::Env, p))
+        .or_else(|| portable.map(|p| (DataRootMode::Portable, p)))
+        .or_else(|| pointer.map(|p| (DataRootMode::Custom, p)))
+        .or_else(|| default.map(|p| (DataRootMode::Default, p)))
+}
+
+fn resolve() -> Option<(DataRootMode, PathBuf)> {
+    let env_dir = std::env::var_os(ENV_DATA_DIR)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from);
+    let portable = exe_dir()
+        .filter(|dir| dir.join(PORTABLE_MARKER).exists())
+        .map(|dir| dir.join(PORTABLE_DIR));
+    let default = default_root();
+    let pointer = default.as_deref().and_then(read_pointer);
+    let resolved = pick_root(env_dir, portable, pointer, default);
+    if let Some((mode, path)) = &resolved {
+        log::info!("[DataRoot] Using {:?} data root: {}", mode, path.display());
+    }
+    resolved
+}
+
+fn current() -> Result<(DataRootMode, PathBuf), String> {
+    CURRENT
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "Failed to get home directory".to_string())
+}
+
+/// AnyCode 数据目录（替代硬编码的 ~/.anycode）
+pub fn anycode_dir() -> Result<PathBuf, String> {
+    current().map(|(_, path)| path)
+}
+
+/// agents.db 等应用数据目录：默认模式为系统应用数据目录，其余模式为数据目录本身
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match current()? {
+        (DataRootMode::Default, _) => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e)),
+        (_, path) => Ok(path),
+    }
+}
+
+/// 递归复制目录，返回复制的文件数；`skip` 为根目录下跳过的条目名
+///
+/// 目标中已存在的文件保留不覆盖（启动后新写入的日志等，或上次中断前已复制的文件）。
+fn copy_tree(src: &Path, dst: &Path, skip: &[&str]) -> Result<usize, String> {
+    fs::create_dir_all(dst).map_err(|e| format!("创建目录失败 {}: {}", dst.display(), e))?;
+    let mut copied = 0;
+    let entries = fs::read_dir(src).map_err(|e| format!("读取目录失败 {}: {}", src.display(), e))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if skip.iter().any(|s| name == *s) {
+            continue;
+        }
+        let from = entry.path();
+        let to = dst.join(&name);
+        if from.is_dir() {
+            copied += copy_tree(&from, &to, &[])?;
+        } else if !to.exists() {
+            fs::copy(&from, &to).map_err(|e| format!("复制文件失败 {}: {}", from.display(), e))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// 把文本中的旧目录替换为新目录（同时处理正斜杠形式，hook 脚本路径使用正斜杠）
+fn replace_path_refs(text: &str, from: &Path, to: &Path) -> Option<String> {
+    let from_native = from.to_string_lossy().to_string();
+    let to_native = to.to_string_lossy().to_string();
+    let from_slash = from_native.replace('\\', "/");
+    let to_slash = to_native.replace('\\', "/");
+
+    let mut result = text.replace(&from_native, &to_native);
+    if from_slash != from_native {
+        result = result.replace(&from_slash, &to_slash);
+    }
+    // JSON 中的 Windows 路径是转义过的反斜杠
+    let from_escaped = from_native.replace('\\', "\\\\");
+    if from_escaped != from_native {
+        result = result.replace(&from_escaped, &to_native.replace('\\', "\\\\"));
+    }
+    (result != text).then_some(result)
+}
+
+fn rewrite_file(path: &Path, from: &Path, to: &Path) -> bool {
+    let Ok(content) = fs::read_to_string(path) else {
+        return false;
+    };
+    match replace_path_refs(&content, from, to) {
+        Some(updated) => fs::write(path, updated).is_ok(),
+        None => false,
+    }
+}
+
+/// 改写目录下文本文件中对旧目录的引用，返回改写的文件数
+fn rewrite_references(dir: &Path, from: &Path, to: &Path) -> usize {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| TEXT_EXTENSIONS.contains(&ext))
+        })
+        .filter(|e| rewrite_file(e.path(), from, to))
+        .count()
+}
+
+/// 删除旧数据（保留 `keep` 中的条目），失败项作为警告返回
+fn remove_old_data(dir: &Path, keep: &[&str]) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut warnings = Vec::new();
+    for entry in entries.flatten() {
+        if keep.iter().any(|k| entry.file_name() == *k) {
+            continue;
+        }
+        let path = entry.path();
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        if let Err(e) = result {
+            warnings.push(format!("未能删除旧数据 {}: {}", path.display(), e));
+        }
+    }
+    warnings
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).map(|mut entries| entries.next().is_none()).unwrap_or(false)
+}
+
+/// 写入下次启动使用的数据目录（指针文件 / 便携标记）
+fn persist_mode(mode: DataRootMode, target: &Path, default: &Path) -> Result<(), String> {
+    let pointer_path = default.join(POINTER_FILE);
+    let marker = exe_dir().map(|dir| dir.join(PORTABLE_MARKER));
+
+    if mode == DataRootMode::Portable {
+        let marker = marker.ok_or("无法确定可执行文件所在目录")?;
+        fs::write(&marker, "").map_err(|e| format!("创建便携模式标记失败: {}", e))?;
+    } else if let Some(marker) = marker.filter(|m| m.exists()) {
+        fs::remove_file(&marker).map_err(|e| format!("删除便携模式标记失败: {}", e))?;
+    }
+
+    if mode == DataRootMode::Custom {
+        fs::create_dir_all(default).map_err(|e| format!("Failed to create {}: {}", default.display(), e))?;
+        let pointer = DataRootPointer {
+            path: Some(target.to_string_lossy().to_string()),
+        };
+        let content = serde_json::to_string_pretty(&pointer)
+            .map_err(|e| format!("Failed to serialize data root: {}", e))?;
+        fs::write(&pointer_path, content).map_err(|e| format!("Failed to write {}: {}", POINTER_FILE, e))?;
+    } else if pointer_path.exists() {
+        fs::remove_file(&pointer_path).map_err(|e| format!("Failed to remove {}: {}", POINTER_FILE, e))?;
+    }
+    Ok(())
+}
+
+/// 复制数据并切换到新目录（启动时调用，agents.db 尚未打开）
+fn apply_migration(pending: &PendingMigration, default: &Path) -> Result<DataRootMigrationReport, String> {
+    let from = PathBuf::from(&pending.from);
+    let to = PathBuf::from(&pending.to);
+    let from_app_data = PathBuf::from(&pending.from_app_data);
+    let to_app_data = PathBuf::from(&pending.to_app_data);
+
+    // 应用数据单独处理；默认目录中的指针文件 / 暂存标记不随迁移复制
+    let mut skip: Vec<&str> = vec![POINTER_FILE, PENDING_FILE];
+    if from_app_data == from {
+        skip.extend(DB_FILES);
+        skip.extend(APP_DATA_FILES);
+    }
+    let copied_files = if from.exists() { copy_tree(&from, &to, &skip)? } else { 0 };
+
+    let mut warnings = Vec::new();
+    if to_app_data != from_app_data {
+        let source_db = from_app_data.join(AGENT_DB_FILE);
+        fs::create_dir_all(&to_app_data).map_err(|e| format!("创建目录失败 {}: {}", to_app_data.display(), e))?;
+        if source_db.exists() {
+            // VACUUM INTO 生成包含 WAL 内容的一致副本；目标是上次中断留下的副本时先删除
+            let target_db = to_app_data.join(AGENT_DB_FILE);
+            for name in DB_FILES {
+                let _ = fs::remove_file(to_app_data.join(name));
+            }
+            let conn = rusqlite::Connection::open(&source_db)
+                .map_err(|e| format!("打开数据库失败 {}: {}", source_db.display(), e))?;
+            conn.execute("VACUUM INTO ?1", [target_db.to_string_lossy().to_string()])
+                .map_err(|e| format!("复制数据库失败: {}", e))?;
+            drop(conn);
+        }
+        for name in APP_DATA_FILES {
+            let source = from_app_data.join(name);
+            if source.exists() {
+                fs::copy(&source, to_app_data.join(name))
+                    .map_err(|e| format!("复制文件失败 {}: {}", source.display(), e))?;
+            }
+        }
+        // 旧数据库此时没有任何连接，可以安全删除
+        for name in DB_FILES.iter().chain(APP_DATA_FILES) {
+            let path = from_app_data.join(name);
+            if let Err(e) = fs::remove_file(&path) {
+                if path.exists() {
+                    warnings.push(format!("未能删除旧数据 {}: {}", path.display(), e));
+                }
+            }
+        }
+    }
+
+    let mut updated_references = rewrite_references(&to, &from, &to);
+    if let Ok(claude_dir) = super::claude::get_claude_dir() {
+        if rewrite_file(&claude_dir.join("settings.json"), &from, &to) {
+            updated_references += 1;
+        }
+    }
+
+    let keep: &[&str] = if from == default { &[POINTER_FILE, PENDING_FILE] } else { &[] };
+    warnings.extend(remove_old_data(&from, keep));
+    if from != default && warnings.is_empty() {
+        let _ = fs::remove_dir(&from);
+    }
+
+    Ok(DataRootMigrationReport {
+        mode: pending.mode,
+        from: pending.from.clone(),
+        to: pending.to.clone(),
+        copied_files,
+        updated_references,
+        warnings,
+    })
+}
+
+/// 启动时执行暂存的迁移（在打开 agents.db 之前调用，此时数据库还没有连接）
+///
+/// 只由 GUI 实例调用：headless 实例可能与仍在使用旧目录的 GUI 实例同时运行。
+pub fn apply_pending_migration() {
+    let Some(default) = default_root() else {
+        return;
+    };
+    let Some(pending) = read_pending(&default) else {
+        return;
+    };
+    let to = PathBuf::from(&pending.to);
+    let active = CURRENT.read().unwrap().clone();
+    if active.as_ref().map(|(_, path)| path) != Some(&to) {
+        log::warn!(
+            "[DataRoot] Pending migration to {} does not match the active data root {:?}, skipping",
+            to.display(),
+            active.map(|(_, path)| path)
+        );
+        return;
+    }
+
+    match apply_migration(&pending, &default) {
+        Ok(report) => {
+            log::info!(
+                "[DataRoot] Migrated {} files from {} to {}, updated {} references",
+                report.copied_files,
+                report.from,
+                report.to,
+                report.updated_references
+            );
+            for warning in &report.warnings {
+                log::warn!("[DataRoot] {}", warning);
+            }
+            let _ = fs::remove_file(default.join(PENDING_FILE));
+            *LAST_MIGRATION.write().unwrap() = Some(report);
+        }
+        // 保留暂存记录，下次启动重试（已复制的文件不会被覆盖）
+        Err(e) => log::error!("[DataRoot] Failed to migrate data root to {}: {}", to.display(), e),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_data_root_info(app: AppHandle) -> Result<DataRootInfo, String> {
+    let (mode, path) = current()?;
+    Ok(DataRootInfo {
+        mode,
+        path: path.to_string_lossy().to_string(),
+        app_data_path: app_data_dir(&app).ok().map(|p| p.to_string_lossy().to_string()),
+        default_path: default_root().map(|p| p.to_string_lossy().to_string()),
+        portable_path: portable_root().map(|p| p.to_string_lossy().to_string()),
+        pending_migration: default_root().as_deref().and_then(read_pending),
+        last_migration: LAST_MIGRATION.read().unwrap().clone(),
+    })
+}
+
+/// 暂存到新目录的迁移（mode: "default" / "custom" / "portable"，custom 需提供 path）
+///
+/// 这里只记录新目录并写入暂存标记，数据在下次启动、打开数据库之前复制；
+/// 迁移目标选回当前目录时取消暂存的迁移。
+#[tauri::command]
+pub async fn migrate_data_root(
+    app: AppHandle,
+    mode: DataRootMode,
+    path: Option<String>,
+) -> Result<Option<PendingMigration>, String> {
+    let (current_mode, from) = current()?;
+    if current_mode == DataRootMode::Env {
+        return Err(format!("数据目录由环境变量 {} 指定，无法在应用内迁移", ENV_DATA_DIR));
+    }
+    let default = default_root().ok_or("Failed to get home directory")?;
+    let to = match mode {
+        DataRootMode::Default => default.clone(),
+        DataRootMode::Portable => portable_root().ok_or("无法确定可执行文件所在目录")?,
+        DataRootMode::Custom => {
+            let path = PathBuf::from(path.filter(|p| !p.trim().is_empty()).ok_or("自定义数据目录不能为空")?);
+            if !path.is_absolute() {
+                return Err(format!("数据目录必须是绝对路径: {}", path.display()));
+            }
+            path
+        }
+        DataRootMode::Env => return Err(format!("无效的迁移目标: 请通过环境变量 {} 设置", ENV_DATA_DIR)),
+    };
+
+    if to == from {
+        if read_pending(&default).is_none() {
+            return Err(format!("已在使用该数据目录: {}", to.display()));
+        }
+        persist_mode(current_mode, &from, &default)?;
+        fs::remove_file(default.join(PENDING_FILE))
+            .map_err(|e| format!("Failed to remove {}: {}", PENDING_FILE, e))?;
+        log::info!("[DataRoot] Cancelled pending data root migration");
+        return Ok(None);
+    }
+    if to.starts_with(&from) || from.starts_with(&to) {
+        return Err(format!("新旧数据目录不能互相包含: {} / {}", from.display(), to.display()));
+    }
+    // 迁回默认目录时，默认目录中只剩指针文件 / 暂存标记
+    let reusable = to.exists() && (is_empty_dir(&to) || (to == default && read_pointer(&default).is_some()));
+    if to.exists() && !reusable {
+        return Err(format!("目标目录不为空: {}", to.display()));
+    }
+
+    let from_app_data = app_data_dir(&app)?;
+    let to_app_data = if mode == DataRootMode::Default {
+        app.path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?
+    } else {
+        to.clone()
+    };
+    let pending = PendingMigration {
+        mode,
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+        from_app_data: from_app_data.to_string_lossy().to_string(),
+        to_app_data: to_app_data.to_string_lossy().to_string(),
+    };
+
+    // 先写暂存标记再切换指针：中途失败时下次启动仍使用旧目录
+    fs::create_dir_all(&default).map_err(|e| format!("Failed to create {}: {}", default.display(), e))?;
+    let content = serde_json::to_string_pretty(&pending)
+        .map_err(|e| format!("Failed to serialize pending migration: {}", e))?;
+    write_atomic(&default.join(PENDING_FILE), content)
+        .map_err(|e| format!("Failed to write {}: {}", PENDING_FILE, e))?;
+    persist_mode(mode, &to, &default)?;
+
+    log::info!(
+        "[DataRoot] Scheduled data migration from {} to {} on next start",
+        from.display(),
+        to.display()
+    );
+    Ok(Some(pending))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_root_by_priority_and_migrates_files() {
+        let p = |s: &str| Some(PathBuf::from(s));
+        assert_eq!(
+            pick_root(None, p("/app/data"), p("/custom"), p("/home/u/.anycode")),
+            Some((DataRootMode::Portable, PathBuf::from("/app/data")))
+        );
+        assert_eq!(
+            pick_root(p("/env"), p("/app/data"), None, p("/home/u/.anycode")).map(|r| r.0),
+            Some(DataRootMode::Env)
+        );
+        assert_eq!(pick_root(None, None, p("/custom"), None).map(|r| r.0), Some(DataRootMode::Custom));
+        assert_eq!(pick_root(None, None, None, None), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("old");
+        let to = dir.path().join("new");
+        fs::create_dir_all(from.join("hooks")).unwrap();
+        fs::write(from.join(POINTER_FILE), "{}").unwrap();
+        fs::write(
+            from.join("hooks").join("bridge.sh"),
+            format!("ENDPOINT_FILE=\"{}/hook_bridge.json\"", from.to_string_lossy().replace('\\', "/")),
+        )
+        .unwrap();
+        fs::write(from.join("tray.json"), "{\"closeToTray\":true}").unwrap();
+
+        assert_eq!(copy_tree(&from, &to, &[POINTER_FILE]).unwrap(), 2);
+        assert!(!to.join(POINTER_FILE).exists());
+        assert_eq!(rewrite_references(&to, &from, &to), 1);
+        let script = fs::read_to_string(to.join("hooks").join("bridge.sh")).unwrap();
+        assert!(script.contains(&to.to_string_lossy().replace('\\', "/")));
+
+        assert!(remove_old_data(&from, &[POINTER_FILE]).is_empty());
+        assert!(from.join(POINTER_FILE).exists());
+        assert!(!from.join("hooks").exists());
+    }
+
+    #[test]
+    fn applies_staged_migration_with_database_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let default = dir.path().join("default");
+        let from = dir.path().join("old");
+        let to = dir.path().join("new");
+        fs::create_dir_all(&default).unwrap();
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(to.join("logs")).unwrap();
+        fs::write(from.join("tray.json"), "{}").unwrap();
+        fs::create_dir_all(from.join("logs")).unwrap();
+        fs::write(from.join("logs").join("app.log"), "old").unwrap();
+        // 启动后已写入新目录的文件不被覆盖
+        fs::write(to.join("logs").join("app.log"), "new").unwrap();
+        let conn = rusqlite::Connection::open(from.join(AGENT_DB_FILE)).unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('kept');").unwrap();
+        drop(conn);
+
+        let pending = PendingMigration {
+            mode: DataRootMode::Custom,
+            from: from.to_string_lossy().to_string(),
+            to: to.to_string_lossy().to_string(),
+            from_app_data: from.to_string_lossy().to_string(),
+            to_app_data: to.to_string_lossy().to_string(),
+        };
+        let report = apply_migration(&pending, &default).unwrap();
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        assert_eq!(report.copied_files, 1);
+        assert_eq!(fs::read_to_string(to.join("logs").join("app.log")).unwrap(), "new");
+        assert!(to.join("tray.json").exists());
+        let conn = rusqlite::Connection::open(to.join(AGENT_DB_FILE)).unwrap();
+        let value: String = conn.query_row("SELECT v FROM t", [], |row| row.get(0)).unwrap();
+        assert_eq!(value, "kept");
+        assert!(!from.exists());
+    }
+}
@@ -0,0 +1,284 @@
+//! agents.db 版本化迁移
+//!
+//! 表结构在 `MIGRATIONS` 中按版本号登记，启动时由 `init_database` 统一执行，
+//! 已执行的版本记录在 `schema_migrations` 表中；使用这些表的模块只读写数据。
+//!
+//! 引入迁移之前就存在的模块（prompt_history、session_metadata、command_audit 等）仍在各自的
+//! `ensure_schema` 中以 `CREATE TABLE IF NOT EXISTS` 建表，尚未迁入。新增表或列一律追加一条迁移
+//! （版本号递增，不修改已发布的迁移），不要再新增 `ensure_schema`。迁移需要可重复执行：
+//! 老版本的数据库可能已经由旧代码建好了部分表和列。
+
+use rusqlite::{params, Connection, Result as SqliteResult};
+
+/// 一条迁移
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub up: fn(&Connection) -> SqliteResult<()>,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "usage_entries table and indexes",
+        up: create_usage_entries,
+    },
+    Migration {
+        version: 2,
+        description: "app_settings key/value table",
+        up: create_app_settings,
+    },
+    Migration {
+        version: 3,
+        description: "usage_entries engine/request_id/source columns for native usage import",
+        up: add_usage_import_columns,
+    },
+    Migration {
+        version: 4,
+        description: "notification_channels table",
+        up: create_notification_channels,
+    },
+    Migration {
+        version: 5,
+        description: "session_compactions table",
+        up: create_session_compactions,
+    },
+    Migration {
+        version: 6,
+        description: "codex_reasoning_runs table",
+        up: create_codex_reasoning_runs,
+    },
+    Migration {
+        version: 7,
+        description: "verification_records table for post-processor runs",
+        up: create_verification_records,
+    },
+    Migration {
+        version: 8,
+        description: "test_watch_runs table",
+        up: create_test_watch_runs,
+    },
+];
+
+fn create_usage_entries(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS usage_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            model TEXT NOT NULL,
+            input_tokens INTEGER DEFAULT 0,
+            output_tokens INTEGER DEFAULT 0,
+            cache_creation_tokens INTEGER DEFAULT 0,
+            cache_read_tokens INTEGER DEFAULT 0,
+            total_tokens INTEGER DEFAULT 0,
+            cost REAL DEFAULT 0.0,
+            project_path TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        -- 会话查询（最常用的查询模式）
+        CREATE INDEX IF NOT EXISTS idx_usage_session_id ON usage_entries(session_id);
+        -- 时间范围查询（按时间排序和过滤）
+        CREATE INDEX IF NOT EXISTS idx_usage_timestamp ON usage_entries(timestamp DESC);
+        -- 项目统计
+        CREATE INDEX IF NOT EXISTS idx_usage_project_path ON usage_entries(project_path);
+        -- 模型 + 时间组合查询
+        CREATE INDEX IF NOT EXISTS idx_usage_model_timestamp ON usage_entries(model, timestamp DESC);
+        -- 项目 + 会话组合查询
+        CREATE INDEX IF NOT EXISTS idx_usage_project_session ON usage_entries(project_path, session_id);
+        -- 成本排序
+        CREATE INDEX IF NOT EXISTS idx_usage_cost ON usage_entries(cost DESC)",
+    )
+}
+
+fn create_app_settings(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+    )
+}
+
+fn add_usage_import_columns(conn: &Connection) -> SqliteResult<()> {
+    add_column_if_missing(conn, "usage_entries", "engine", "TEXT NOT NULL DEFAULT 'claude'")?;
+    add_column_if_missing(conn, "usage_entries", "request_id", "TEXT")?;
+    add_column_if_missing(conn, "usage_entries", "source", "TEXT NOT NULL DEFAULT 'anycode'")?;
+    conn.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_usage_engine_request
+         ON usage_entries(engine, request_id) WHERE request_id IS NOT NULL",
+    )
+}
+
+fn create_notification_channels(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS notification_channels (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            channel_type TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            events TEXT NOT NULL,
+            url TEXT,
+            sound TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+    )
+}
+
+fn create_session_compactions(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_compactions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            engine TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            compaction TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_compactions_session ON session_compactions (engine, session_id)",
+    )
+}
+
+fn create_codex_reasoning_runs(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS codex_reasoning_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT NOT NULL,
+            model TEXT NOT NULL,
+            reasoning_mode TEXT NOT NULL,
+            prompt_tokens INTEGER NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            outcome TEXT NOT NULL,
+            recorded_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_codex_reasoning_runs_project
+            ON codex_reasoning_runs(project_path, recorded_at)",
+    )
+}
+
+fn create_verification_records(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS verification_records (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            engine TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            prompt_index INTEGER NOT NULL,
+            passed INTEGER NOT NULL,
+            results TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_verification_records_session
+            ON verification_records(session_id, prompt_index)",
+    )
+}
+
+fn create_test_watch_runs(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS test_watch_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            prompt_index INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            exit_code INTEGER,
+            duration_ms INTEGER NOT NULL,
+            passed_count INTEGER,
+            failed_count INTEGER,
+            summary TEXT NOT NULL,
+            output TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_test_watch_runs_session
+            ON test_watch_runs(session_id, id)",
+    )
+}
+
+/// 列不存在时才添加（SQLite 没有 `ADD COLUMN IF NOT EXISTS`）
+pub fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> SqliteResult<()> {
+    let exists = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<SqliteResult<Vec<_>>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+/// 当前数据库已执行到的版本
+pub fn current_version(conn: &Connection) -> SqliteResult<i64> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )?;
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", [], |row| row.get(0))
+}
+
+/// 执行所有未执行的迁移，返回本次执行的数量；每条迁移在独立事务中执行
+pub fn run_migrations(conn: &mut Connection) -> SqliteResult<usize> {
+    let current = current_version(conn)?;
+    let latest = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
+    if current > latest {
+        log::warn!(
+            "[Migrations] agents.db schema version {} is newer than this build ({}), skipping",
+            current,
+            latest
+        );
+        return Ok(0);
+    }
+
+    let mut applied = 0;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, description) VALUES (?1, ?2)",
+            params![migration.version, migration.description],
+        )?;
+        tx.commit()?;
+        log::info!("[Migrations] Applied v{}: {}", migration.version, migration.description);
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_pending_migrations_once_and_tolerates_legacy_schema() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+
+        // 旧代码已经建好 usage_entries，并补过 engine 列
+        let mut conn = Connection::open_in_memory().unwrap();
+        create_usage_entries(&conn).unwrap();
+        conn.execute_batch("ALTER TABLE usage_entries ADD COLUMN engine TEXT NOT NULL DEFAULT 'claude'")
+            .unwrap();
+
+        assert_eq!(run_migrations(&mut conn).unwrap(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), MIGRATIONS.last().unwrap().version);
+        assert_eq!(run_migrations(&mut conn).unwrap(), 0);
+
+        conn.execute(
+            "INSERT INTO usage_entries (session_id, timestamp, model, request_id) VALUES ('s', 't', 'm', 'r1')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO app_settings (key, value) VALUES ('k', 'v')", []).unwrap();
+        let source: String = conn
+            .query_row("SELECT source FROM usage_entries WHERE request_id = 'r1'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(source, "anycode");
+
+        // 数据库版本比当前程序新时不做任何改动
+        conn.execute("INSERT INTO schema_migrations (version, description) VALUES (999, 'future')", [])
+            .unwrap();
+        assert_eq!(run_migrations(&mut conn).unwrap(), 0);
+    }
+}
@@ -0,0 +1,148 @@
+//! agents.db 连接池
+//!
+//! 流式输出期间会有大量命令同时读写 agents.db（用量记录、会话元数据、变更追踪等）。
+//! 以前每次调用都重新打开连接并在异步运行时上同步执行查询，这里改为复用连接：
+//! `get()` 取出空闲连接（没有则新建），归还时最多保留 `MAX_IDLE` 个。
+//!
+//! 异步命令通过 `run()`（或 `storage::with_agent_db`）在阻塞线程池中执行查询，避免阻塞 tokio 工作线程。
+
+use rusqlite::Connection;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 归还后保留的空闲连接数
+const MAX_IDLE: usize = 8;
+/// 多个连接并发写入（WAL）时等待而不是立即失败
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct DbPool {
+    path: PathBuf,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl DbPool {
+    pub fn new(path: PathBuf) -> Arc<Self> {
+        Arc::new(DbPool {
+            path,
+            idle: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// 新建连接并设置连接级参数（journal_mode=WAL 由 init_database 写入数据库文件）
+    pub fn connect(&self) -> rusqlite::Result<Connection> {
+        let conn = Connection::open(&self.path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.pragma_update(None, "cache_size", 10000)?;  // 10MB 缓存
+        conn.pragma_update(None, "temp_store", "MEMORY")?;
+        conn.pragma_update(None, "mmap_size", 30000000000i64)?;  // 30GB memory-mapped I/O
+        Ok(conn)
+    }
+
+    /// 取出一个连接，离开作用域时自动归还
+    pub fn get(self: &Arc<Self>) -> Result<PooledConnection, String> {
+        let idle = self.idle.lock().map_err(|e| e.to_string())?.pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => self.connect().map_err(|e| format!("打开数据库失败: {}", e))?,
+        };
+        Ok(PooledConnection {
+            conn: Some(conn),
+            pool: Arc::clone(self),
+        })
+    }
+
+    /// 把已有连接放入池中（init_database 执行完迁移的那个连接）
+    pub fn put(&self, conn: Connection) {
+        if let Ok(mut idle) = self.idle.lock() {
+            if idle.len() < MAX_IDLE {
+                idle.push(conn);
+            }
+        }
+    }
+
+    /// 丢弃所有空闲连接（重置数据库后使用）
+    pub fn clear(&self) {
+        if let Ok(mut idle) = self.idle.lock() {
+            idle.clear();
+        }
+    }
+
+    /// 在阻塞线程池中取连接执行 `f`
+    pub async fn run<T, F>(self: &Arc<Self>, f: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, String> + Send + 'static,
+    {
+        let pool = Arc::clone(self);
+        tokio::task::spawn_blocking(move || f(&mut *pool.get()?))
+            .await
+            .map_err(|e| format!("数据库任务执行失败: {}", e))?
+    }
+}
+
+/// 池中取出的连接
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<DbPool>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection already returned")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection already returned")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            // 未提交的事务说明调用方中途出错，这样的连接不再复用
+            if conn.is_autocommit() {
+                self.pool.put(conn);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reuses_returned_connections() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = DbPool::new(dir.path().join("agents.db"));
+
+        {
+            let conn = pool.get().unwrap();
+            conn.execute_batch("CREATE TABLE t (v INTEGER); INSERT INTO t VALUES (1);").unwrap();
+            conn.execute_batch("BEGIN").unwrap();
+        }
+        // 事务未结束的连接被丢弃
+        assert_eq!(pool.idle.lock().unwrap().len(), 0);
+
+        let held: Vec<_> = (0..MAX_IDLE + 2).map(|_| pool.get().unwrap()).collect();
+        drop(held);
+        assert_eq!(pool.idle.lock().unwrap().len(), MAX_IDLE);
+
+        let count = pool
+            .run(|conn| {
+                conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get::<_, i64>(0))
+                    .map_err(|e| e.to_string())
+            })
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(pool.idle.lock().unwrap().len(), MAX_IDLE);
+    }
+}
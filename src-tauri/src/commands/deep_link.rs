@@ -0,0 +1,299 @@
+//! 单实例与深度链接（`anycode://`）
+//!
+//! 再次启动 AnyCode（终端别名、文件管理器、浏览器中点击 `anycode://` 链接）时，
+//! single-instance 插件把新进程的启动参数转交给已运行的实例，由已运行的实例处理：
+//!
+//! - `anycode://open?project=/path` 打开（或聚焦）该项目的窗口
+//! - `anycode://open?project=/path&prompt=...&engine=codex` 在该项目中新建会话执行提示词
+//! - `anycode /path/to/project` 等同于打开项目
+//!
+//! macOS 的链接由系统事件送达（deep-link 插件）。来自链接的提示词默认需要在界面中
+//! 确认后才执行（`deep-link-pending` 事件），可在 ~/.anycode/deep_link.json 中开启自动执行。
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use super::data_root::anycode_dir;
+
+pub const URL_SCHEME: &str = "anycode";
+
+/// 深度链接 / 命令行请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLinkRequest {
+    pub project_path: Option<String>,
+    pub prompt: Option<String>,
+    pub engine: Option<String>,
+    pub model: Option<String>,
+}
+
+/// 深度链接设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeepLinkSettings {
+    /// 链接中的提示词直接执行，不在界面中确认
+    pub auto_run_prompts: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeepLinkEvent {
+    request: DeepLinkRequest,
+    error: Option<String>,
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("deep_link.json"))
+}
+
+pub fn load_deep_link_settings() -> DeepLinkSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+fn decode_component(value: &str) -> String {
+    let value = value.replace('+', " ");
+    urlencoding::decode(&value)
+        .map(|v| v.into_owned())
+        .unwrap_or(value)
+}
+
+/// 解析 `anycode://open?project=...&prompt=...&engine=...&model=...`
+pub fn parse_deep_link(url: &str) -> Result<DeepLinkRequest, String> {
+    let rest = url
+        .strip_prefix(&format!("{}://", URL_SCHEME))
+        .ok_or_else(|| format!("不是 {}:// 链接: {}", URL_SCHEME, url))?;
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let action = action.trim_matches('/');
+    if action != "open" {
+        return Err(format!("不支持的链接操作: {}", action));
+    }
+
+    let mut request = DeepLinkRequest::default();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = Some(decode_component(value)).filter(|v| !v.trim().is_empty());
+        match key {
+            "project" | "project_path" => request.project_path = value,
+            "prompt" => request.prompt = value,
+            "engine" => request.engine = value,
+            "model" => request.model = value,
+            other => log::debug!("[DeepLink] Ignoring unknown parameter: {}", other),
+        }
+    }
+    Ok(request)
+}
+
+/// 从启动参数中提取请求：`anycode://` 链接，或一个项目目录
+pub fn request_from_args(args: &[String], cwd: Option<&str>) -> Option<Result<DeepLinkRequest, String>> {
+    let args = args.iter().skip(1);
+    let mut project = None;
+    for arg in args {
+        if arg.starts_with(&format!("{}://", URL_SCHEME)) {
+            return Some(parse_deep_link(arg));
+        }
+        if !arg.starts_with('-') && project.is_none() {
+            let path = match cwd {
+                Some(cwd) if Path::new(arg).is_relative() => Path::new(cwd).join(arg),
+                _ => PathBuf::from(arg),
+            };
+            if path.is_dir() {
+                project = Some(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    project.map(|project_path| {
+        Ok(DeepLinkRequest {
+            project_path: Some(project_path),
+            ..Default::default()
+        })
+    })
+}
+
+// ============================================================================
+// Routing
+// ============================================================================
+
+/// 处理启动参数（首次启动或 single-instance 转交）
+pub fn handle_launch_args(app: &AppHandle, args: Vec<String>, cwd: Option<String>) {
+    match request_from_args(&args, cwd.as_deref()) {
+        Some(Ok(request)) => handle_request(app, request),
+        Some(Err(e)) => {
+            log::warn!("[DeepLink] {}", e);
+            emit_result(app, "deep-link-received", DeepLinkRequest::default(), Some(e));
+        }
+        None => {}
+    }
+}
+
+/// 处理 macOS 通过系统事件送达的链接（deep-link 插件的 `on_open_url`）
+#[cfg(target_os = "macos")]
+pub fn handle_urls(app: &AppHandle, urls: Vec<String>) {
+    for url in urls.into_iter().filter(|u| u.starts_with(&format!("{}://", URL_SCHEME))) {
+        match parse_deep_link(&url) {
+            Ok(request) => handle_request(app, request),
+            Err(e) => log::warn!("[DeepLink] {}", e),
+        }
+    }
+}
+
+fn emit_result(app: &AppHandle, event: &str, request: DeepLinkRequest, error: Option<String>) {
+    let _ = app.emit(event, DeepLinkEvent { request, error });
+}
+
+fn handle_request(app: &AppHandle, request: DeepLinkRequest) {
+    if request.prompt.is_some() && !load_deep_link_settings().auto_run_prompts {
+        // 链接可能来自浏览器，执行前交给界面确认
+        log::info!("[DeepLink] Prompt request waiting for confirmation");
+        super::tray::show_main_window(app);
+        emit_result(app, "deep-link-pending", request, None);
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = route_request(&app, request.clone()).await;
+        if let Err(e) = &result {
+            log::warn!("[DeepLink] Failed to handle request: {}", e);
+        }
+        emit_result(&app, "deep-link-received", request, result.err());
+    });
+}
+
+async fn route_request(app: &AppHandle, request: DeepLinkRequest) -> Result<(), String> {
+    let project_path = match request.project_path {
+        Some(path) => {
+            if !Path::new(&path).is_dir() {
+                return Err(format!("项目目录不存在: {}", path));
+            }
+            path
+        }
+        None if request.prompt.is_some() => return Err("执行提示词需要指定项目 (project=...)".to_string()),
+        None => {
+            super::tray::show_main_window(app);
+            return Ok(());
+        }
+    };
+
+    match request.prompt {
+        Some(prompt) => {
+            let engine = request.engine.unwrap_or_else(|| "claude".to_string());
+            log::info!("[DeepLink] Executing prompt with {} in {}", engine, project_path);
+            super::tray::show_main_window(app);
+            super::prompt_library::execute_in_new_session(app.clone(), &engine, project_path, prompt, request.model).await
+        }
+        None => super::window::open_project_window(app.clone(), project_path, request.engine)
+            .await
+            .map(|_| ()),
+    }
+}
+
+// ============================================================================
+// URL scheme registration
+// ============================================================================
+
+/// 确认 `anycode://` 指向当前可执行文件，未注册时才注册（只在启动时检查一次）。
+///
+/// 安装包（NSIS / deb / rpm / dmg）安装时已按 tauri.conf.json 中的 deep-link 配置注册，
+/// 这里只补上未经安装的运行方式（Windows 便携版、AppImage）；开发构建不注册，
+/// 避免把链接指向临时的调试二进制。
+pub fn register_url_scheme(app: &AppHandle) {
+    if cfg!(debug_assertions) {
+        return;
+    }
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    {
+        use tauri_plugin_deep_link::DeepLinkExt;
+
+        // Linux 上系统包通过自带的 .desktop 文件注册，只有 AppImage 需要自行注册
+        if cfg!(target_os = "linux") && std::env::var_os("APPIMAGE").is_none() {
+            return;
+        }
+        let deep_link = app.deep_link();
+        match deep_link.is_registered(URL_SCHEME) {
+            Ok(true) => {}
+            Ok(false) => match deep_link.register(URL_SCHEME) {
+                Ok(()) => log::info!("[DeepLink] Registered {}:// for this executable", URL_SCHEME),
+                Err(e) => log::warn!("[DeepLink] Failed to register {}://: {}", URL_SCHEME, e),
+            },
+            Err(e) => log::warn!("[DeepLink] Failed to query {}:// registration: {}", URL_SCHEME, e),
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let _ = app;
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_deep_link_settings() -> Result<DeepLinkSettings, String> {
+    Ok(load_deep_link_settings())
+}
+
+#[tauri::command]
+pub async fn set_deep_link_settings(settings: DeepLinkSettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize deep link settings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write deep_link.json: {}", e))?;
+    log::info!("[DeepLink] Saved settings (auto run prompts: {})", settings.auto_run_prompts);
+    Ok(())
+}
+
+/// 用户在界面中确认后执行 `deep-link-pending` 中的请求
+#[tauri::command]
+pub async fn run_deep_link_request(app: AppHandle, request: DeepLinkRequest) -> Result<(), String> {
+    route_request(&app, request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_open_links_with_encoded_parameters() {
+        let request =
+            parse_deep_link("anycode://open?project=%2Ftmp%2Fmy%20app&prompt=fix+the+tests%21&engine=codex&x=1")
+                .unwrap();
+        assert_eq!(
+            request,
+            DeepLinkRequest {
+                project_path: Some("/tmp/my app".into()),
+                prompt: Some("fix the tests!".into()),
+                engine: Some("codex".into()),
+                model: None,
+            }
+        );
+        assert_eq!(parse_deep_link("anycode://open/").unwrap(), DeepLinkRequest::default());
+        assert!(parse_deep_link("anycode://delete?project=/").is_err());
+        assert!(parse_deep_link("https://example.com").is_err());
+    }
+
+    #[test]
+    fn launch_args_accept_links_and_project_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().parent().unwrap().to_string_lossy().to_string();
+        let name = dir.path().file_name().unwrap().to_string_lossy().to_string();
+
+        let args = vec!["anycode".to_string(), "--flag".to_string(), name];
+        let request = request_from_args(&args, Some(&cwd)).unwrap().unwrap();
+        assert_eq!(request.project_path.as_deref(), Some(dir.path().to_string_lossy().as_ref()));
+
+        let args = vec!["anycode".to_string(), "anycode://open?prompt=hi".to_string()];
+        assert_eq!(request_from_args(&args, None).unwrap().unwrap().prompt.as_deref(), Some("hi"));
+
+        assert!(request_from_args(&["anycode".to_string()], None).is_none());
+    }
+}
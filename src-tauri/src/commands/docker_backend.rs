@@ -0,0 +1,436 @@
+//! Docker 执行后端
+//!
+//! 项目设置中启用容器后，Claude / Codex / Gemini CLI 不再直接在主机上运行，
+//! 而是通过 `docker run --rm -i` 在一次性容器中执行：只有项目目录（及额外配置的卷）
+//! 被挂载进容器，其余主机文件系统对 agent 不可见。
+//!
+//! 容器的 stdin/stdout/stderr 直接接到 docker 客户端进程上，因此现有的输出解析、
+//! 事件推送和日志落盘无需改动。所有容器都带 `anycode.managed=true` 标签，
+//! 便于列出和清理。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+use super::claude::apply_no_window_async;
+use super::project_settings::load_project_settings;
+
+/// 容器内默认工作目录（项目挂载点）
+const DEFAULT_WORKDIR: &str = "/workspace";
+/// AnyCode 管理的容器标签
+const MANAGED_LABEL: &str = "anycode.managed=true";
+
+/// 只对主机有意义的环境变量，不转发进容器
+const HOST_ONLY_ENV: &[&str] = &[
+    "PATH", "HOME", "USER", "SHELL", "NODE_PATH", "NVM_DIR", "NVM_BIN",
+    "HOMEBREW_PREFIX", "HOMEBREW_CELLAR", "USERPROFILE", "USERNAME",
+    "COMPUTERNAME", "APPDATA", "LOCALAPPDATA", "TEMP", "TMP",
+];
+
+/// 额外挂载的卷
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeMount {
+    pub host_path: String,
+    pub container_path: String,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// 项目的容器执行配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerConfig {
+    /// 是否在容器中执行引擎
+    #[serde(default)]
+    pub enabled: bool,
+    /// 镜像（需预装对应的 CLI）
+    pub image: String,
+    /// 项目在容器内的挂载路径（默认 `/workspace`）
+    #[serde(default)]
+    pub workdir: Option<String>,
+    /// 额外挂载的卷
+    #[serde(default)]
+    pub mounts: Vec<VolumeMount>,
+    /// 额外的环境变量
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// 网络（如 `none` 可完全断网）
+    #[serde(default)]
+    pub network: Option<String>,
+}
+
+impl ContainerConfig {
+    fn workdir(&self) -> &str {
+        self.workdir
+            .as_deref()
+            .filter(|w| !w.is_empty())
+            .unwrap_or(DEFAULT_WORKDIR)
+    }
+}
+
+/// 运行中的容器：docker 客户端 PID -> 容器名
+static RUNNING_CONTAINERS: Lazy<Mutex<HashMap<u32, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 主机上的项目路径参数映射为容器内路径
+fn map_arg(arg: &str, project_path: &str, workdir: &str) -> String {
+    match arg.strip_prefix(project_path) {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{}", workdir, rest.replace('\\', "/"))
+        }
+        _ => arg.to_string(),
+    }
+}
+
+/// 构建 `docker run` 参数
+fn build_run_args(
+    config: &ContainerConfig,
+    container_name: &str,
+    project_path: &str,
+    engine: &str,
+    engine_args: &[String],
+    forwarded_env: &[String],
+) -> Vec<String> {
+    let workdir = config.workdir();
+    let mut args: Vec<String> = vec![
+        "run".into(),
+        "--rm".into(),
+        "-i".into(),
+        "--name".into(),
+        container_name.into(),
+        "--label".into(),
+        MANAGED_LABEL.into(),
+        "--label".into(),
+        format!("anycode.engine={}", engine),
+        "--label".into(),
+        format!("anycode.project={}", project_path),
+        "-v".into(),
+        format!("{}:{}", project_path, workdir),
+    ];
+
+    for mount in &config.mounts {
+        args.push("-v".into());
+        args.push(if mount.read_only {
+            format!("{}:{}:ro", mount.host_path, mount.container_path)
+        } else {
+            format!("{}:{}", mount.host_path, mount.container_path)
+        });
+    }
+
+    args.push("-w".into());
+    args.push(workdir.into());
+
+    if let Some(network) = config.network.as_deref().filter(|n| !n.is_empty()) {
+        args.push("--network".into());
+        args.push(network.into());
+    }
+
+    // 只传变量名，值从 docker 客户端进程的环境中读取，避免出现在命令行里
+    let mut env_keys: Vec<&String> = forwarded_env.iter().chain(config.env.keys()).collect();
+    env_keys.sort();
+    env_keys.dedup();
+    for key in env_keys {
+        args.push("-e".into());
+        args.push(key.clone());
+    }
+
+    args.push(config.image.clone());
+    args.push(engine.into());
+    args.extend(engine_args.iter().map(|a| map_arg(a, project_path, workdir)));
+    args
+}
+
+/// 项目是否启用了容器执行（启用时引擎不再经过 WSL 等主机上的启动方式改写）
+pub fn container_enabled(project_path: &str) -> bool {
+    load_project_settings(project_path)
+        .container
+        .is_some_and(|c| c.enabled)
+}
+
+/// 项目启用了容器执行时，把引擎命令改写为 `docker run` 命令
+///
+/// `engine_args` 是 CLI 自身的参数，须在主机启动方式改写（Windows 上 `.cmd` 解析为
+/// `node <script>`、WSL 包装等）之前取得；`cmd` 只提供显式设置的环境变量。
+/// 未启用时原样返回 `cmd`。
+pub fn wrap_command_for_project(
+    project_path: &str,
+    engine: &str,
+    engine_args: &[String],
+    cmd: Command,
+) -> Result<Command, String> {
+    let Some(config) = load_project_settings(project_path).container.filter(|c| c.enabled) else {
+        return Ok(cmd);
+    };
+    if config.image.trim().is_empty() {
+        return Err("容器执行已启用，但未配置镜像".to_string());
+    }
+
+    let original = cmd.as_std();
+    let envs: Vec<(String, String)> = original
+        .get_envs()
+        .filter_map(|(k, v)| Some((k.to_string_lossy().to_string(), v?.to_string_lossy().to_string())))
+        .collect();
+    let forwarded_env: Vec<String> = envs
+        .iter()
+        .map(|(k, _)| k.clone())
+        .filter(|k| !HOST_ONLY_ENV.contains(&k.as_str()))
+        .collect();
+
+    let container_name = format!("anycode-{}-{}", engine, &uuid::Uuid::new_v4().simple().to_string()[..12]);
+    let run_args = build_run_args(&config, &container_name, project_path, engine, engine_args, &forwarded_env);
+
+    let mut docker = Command::new("docker");
+    docker.args(&run_args);
+    docker.envs(envs);
+    docker.envs(&config.env);
+    docker.current_dir(project_path);
+    docker.stdin(Stdio::piped());
+    docker.stdout(Stdio::piped());
+    docker.stderr(Stdio::piped());
+    apply_no_window_async(&mut docker);
+
+    log::info!(
+        "[Docker] Running {} in container {} (image: {}, project: {})",
+        engine,
+        container_name,
+        config.image,
+        project_path
+    );
+    Ok(docker)
+}
+
+/// 记录 docker 客户端进程对应的容器（进程启动后调用）
+pub fn track_container_run(pid: u32, cmd: &Command) {
+    if cmd.as_std().get_program() != "docker" {
+        return;
+    }
+    let args: Vec<String> = cmd
+        .as_std()
+        .get_args()
+        .map(|a| a.to_string_lossy().to_string())
+        .collect();
+    if let Some(name) = args.windows(2).find(|w| w[0] == "--name").map(|w| w[1].clone()) {
+        RUNNING_CONTAINERS.lock().unwrap().insert(pid, name);
+    }
+}
+
+/// 运行结束（正常退出、取消或超时）后确保容器被移除
+///
+/// 取消时 docker 客户端会把 SIGTERM 转发给容器，但被强制杀死时容器可能残留。
+pub fn release_container_run(pid: u32) {
+    let Some(name) = RUNNING_CONTAINERS.lock().unwrap().remove(&pid) else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = remove_container(&name).await {
+            log::debug!("[Docker] Container {} already gone: {}", name, e);
+        }
+    });
+}
+
+async fn run_docker(args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("docker");
+    cmd.args(args);
+    apply_no_window_async(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 docker: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+async fn remove_container(name: &str) -> Result<(), String> {
+    run_docker(&["rm", "-f", name]).await.map(|_| ())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Docker 可用性
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DockerStatus {
+    pub available: bool,
+    pub server_version: Option<String>,
+    pub error: Option<String>,
+}
+
+/// AnyCode 创建的容器
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedContainer {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub state: String,
+    pub status: String,
+    pub engine: Option<String>,
+    pub project_path: Option<String>,
+}
+
+/// 检查 Docker 是否可用
+#[tauri::command]
+pub async fn get_docker_status() -> Result<DockerStatus, String> {
+    Ok(match run_docker(&["version", "--format", "{{.Server.Version}}"]).await {
+        Ok(version) => DockerStatus {
+            available: true,
+            server_version: Some(version),
+            error: None,
+        },
+        Err(e) => DockerStatus {
+            available: false,
+            server_version: None,
+            error: Some(e),
+        },
+    })
+}
+
+/// 列出 AnyCode 创建的容器
+#[tauri::command]
+pub async fn list_managed_containers() -> Result<Vec<ManagedContainer>, String> {
+    let filter = format!("label={}", MANAGED_LABEL);
+    let output = run_docker(&["ps", "-a", "--filter", &filter, "--format", "{{json .}}"]).await?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .map(|v| {
+            let field = |key: &str| v[key].as_str().unwrap_or_default().to_string();
+            // Labels 格式: "k1=v1,k2=v2"
+            let labels: HashMap<String, String> = field("Labels")
+                .split(',')
+                .filter_map(|kv| kv.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            ManagedContainer {
+                id: field("ID"),
+                name: field("Names"),
+                image: field("Image"),
+                state: field("State"),
+                status: field("Status"),
+                engine: labels.get("anycode.engine").cloned(),
+                project_path: labels.get("anycode.project").cloned(),
+            }
+        })
+        .collect())
+}
+
+/// 停止并移除容器
+#[tauri::command]
+pub async fn remove_managed_container(name: String) -> Result<(), String> {
+    log::info!("[Docker] Removing container {}", name);
+    remove_container(&name).await
+}
+
+/// 移除所有未在运行中的 AnyCode 容器（或全部，`include_running = true`）
+#[tauri::command]
+pub async fn cleanup_managed_containers(include_running: Option<bool>) -> Result<usize, String> {
+    let include_running = include_running.unwrap_or(false);
+    let active: Vec<String> = RUNNING_CONTAINERS.lock().unwrap().values().cloned().collect();
+
+    let mut removed = 0;
+    for container in list_managed_containers().await? {
+        if !include_running && (container.state == "running" || active.contains(&container.name)) {
+            continue;
+        }
+        match remove_container(&container.name).await {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("[Docker] Failed to remove {}: {}", container.name, e),
+        }
+    }
+    Ok(removed)
+}
+
+/// 拉取镜像，进度通过 `docker-pull-output` 事件推送，结束时发送 `docker-pull-complete`
+#[tauri::command]
+pub async fn pull_docker_image(app: AppHandle, image: String) -> Result<(), String> {
+    log::info!("[Docker] Pulling image {}", image);
+    let mut cmd = Command::new("docker");
+    cmd.args(["pull", &image]);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    apply_no_window_async(&mut cmd);
+
+    let mut child = cmd.spawn().map_err(|e| format!("无法执行 docker: {}", e))?;
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+
+    let mut stdout_lines = BufReader::new(stdout).lines();
+    let mut stderr_lines = BufReader::new(stderr).lines();
+    let mut stderr_text = String::new();
+    let (mut stdout_done, mut stderr_done) = (false, false);
+    while !(stdout_done && stderr_done) {
+        tokio::select! {
+            line = stdout_lines.next_line(), if !stdout_done => match line {
+                Ok(Some(line)) => {
+                    let _ = app.emit("docker-pull-output", serde_json::json!({ "image": image, "line": line }));
+                }
+                _ => stdout_done = true,
+            },
+            line = stderr_lines.next_line(), if !stderr_done => match line {
+                Ok(Some(line)) => {
+                    stderr_text.push_str(&line);
+                    stderr_text.push('\n');
+                }
+                _ => stderr_done = true,
+            },
+        }
+    }
+
+    let status = child.wait().await.map_err(|e| format!("等待 docker 失败: {}", e))?;
+    let error = (!status.success()).then(|| stderr_text.trim().to_string());
+    let _ = app.emit(
+        "docker-pull-complete",
+        serde_json::json!({ "image": image, "success": status.success(), "error": error }),
+    );
+
+    match error {
+        None => Ok(()),
+        Some(e) => Err(format!("拉取镜像失败: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_args_mount_project_and_map_paths() {
+        let config = ContainerConfig {
+            enabled: true,
+            image: "node:22".to_string(),
+            mounts: vec![VolumeMount {
+                host_path: "/home/dev/.codex".to_string(),
+                container_path: "/root/.codex".to_string(),
+                read_only: true,
+            }],
+            network: Some("none".to_string()),
+            ..Default::default()
+        };
+        let args = build_run_args(
+            &config,
+            "anycode-codex-1",
+            "/home/dev/app",
+            "codex",
+            &["exec".to_string(), "-C".to_string(), "/home/dev/app/src".to_string(), "/home/dev/app2".to_string()],
+            &["OPENAI_API_KEY".to_string()],
+        );
+        let joined = args.join(" ");
+
+        assert!(joined.contains("-v /home/dev/app:/workspace"));
+        assert!(joined.contains("-v /home/dev/.codex:/root/.codex:ro"));
+        assert!(joined.contains("--network none"));
+        assert!(joined.contains("-e OPENAI_API_KEY"));
+        assert!(joined.ends_with("node:22 codex exec -C /workspace/src /home/dev/app2"));
+    }
+}
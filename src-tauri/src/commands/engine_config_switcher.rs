@@ -1,3 +1,4 @@
+use crate::tr;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -60,7 +61,7 @@ fn expand_user_path(input: &str) -> Result<PathBuf, String> {
 }
 
 fn default_engine_config_file(engine: &EngineConfigType) -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?;
+    let home = dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     Ok(match engine {
         EngineConfigType::Codex => home.join(".codex").join("config.toml"),
         EngineConfigType::Claude => home.join(".claude").join("settings.json"),
@@ -113,9 +114,7 @@ fn validate_engine_config_content(engine: &EngineConfigType, content: &str) -> R
 }
 
 fn get_profiles_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let dir = app
-        .path()
-        .app_data_dir()
+    let dir = super::data_root::app_data_dir(app)
         .map_err(|e| format!("Failed to get app data dir: {}", e))?;
     fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
     Ok(dir.join("engine_config_profiles.json"))
@@ -5,9 +5,14 @@
  * 支持 Native 和 WSL 环境检测
  */
 
+use crate::tr;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
-use rusqlite::Connection;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::app_settings::{delete_setting, CLAUDE_BINARY_PATH};
 
 // 导入各引擎的检查函数
 use crate::commands::claude::check_claude_version;
@@ -45,6 +50,14 @@ pub struct UnifiedEngineStatus {
     
     /// 最后检查时间戳
     pub last_checked: Option<i64>,
+    
+    /// 最新版本（来自最近一次更新检查）
+    #[serde(default)]
+    pub latest_version: Option<String>,
+    
+    /// 是否有可用更新（None 表示尚未检查）
+    #[serde(default)]
+    pub update_available: Option<bool>,
 }
 
 /// 引擎检测结果
@@ -105,30 +118,41 @@ pub async fn check_engine_status(
 ) -> Result<UnifiedEngineStatus, String> {
     log::info!("[EngineStatus] Checking status for engine: {}", engine);
     
-    // 清除 Claude 二进制路径缓存，强制重新检测
-    if engine.to_lowercase() == "claude" {
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
-            let db_path = app_data_dir.join("agents.db");
-            if db_path.exists() {
-                if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                    let _ = conn.execute(
-                        "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                        [],
-                    );
-                    log::info!("[EngineStatus] Cleared Claude binary path cache");
-                }
-            }
-        }
-    }
-    
+    // 检测结果与 `--version` 输出均有缓存（二进制被替换时自动失效），
+    // 需要强制重新检测时先调用 `invalidate_engine_cache`
     let now = chrono::Utc::now().timestamp();
     
-    match engine.to_lowercase().as_str() {
+    let mut status = match engine.to_lowercase().as_str() {
         "claude" => check_claude_status(app, now).await,
         "codex" => check_codex_status(now).await,
         "gemini" => check_gemini_status(now).await,
-        _ => Err(format!("Unknown engine: {}", engine))
+        _ => Err(tr!("engine.unsupported", engine = engine))
+    }?;
+    
+    // 附带最近一次更新检查的结果，UI 可直接显示 "有可用更新"
+    apply_cached_update(&mut status);
+    Ok(status)
+}
+
+/// 清除引擎的检测缓存（二进制路径、`--version` 输出、Claude 存储的路径），
+/// 下次状态检查时重新检测；`engine` 为空时清除全部引擎
+#[tauri::command]
+pub async fn invalidate_engine_cache(app: AppHandle, engine: Option<String>) -> Result<(), String> {
+    let engines = match engine.map(|e| e.to_lowercase()) {
+        Some(engine) => vec![engine],
+        None => vec!["claude".to_string(), "codex".to_string(), "gemini".to_string()],
+    };
+    for engine in &engines {
+        crate::claude_binary::invalidate_binary_cache(engine);
+        UPDATE_CACHE.lock().unwrap().remove(engine);
+        if engine == "claude" {
+            if let Ok(true) = delete_setting(&app, CLAUDE_BINARY_PATH).await {
+                log::info!("[EngineStatus] Cleared stored Claude binary path");
+            }
+        }
     }
+    log::info!("[EngineStatus] Invalidated detection cache for {:?}", engines);
+    Ok(())
 }
 
 /// 更新指定引擎
@@ -150,7 +174,7 @@ pub async fn update_engine(
         "claude" => update_claude(&environment, wsl_distro.as_deref()).await,
         "codex" => update_codex(&environment, wsl_distro.as_deref()).await,
         "gemini" => update_gemini(&environment, wsl_distro.as_deref()).await,
-        _ => return Err(format!("Unknown engine: {}", engine))
+        _ => return Err(tr!("engine.unsupported", engine = engine))
     };
     
     // 更新后重新检查版本
@@ -191,17 +215,8 @@ pub async fn check_engine_update(
     
     // 清除 Claude 二进制路径缓存，强制重新检测
     if engine.to_lowercase() == "claude" {
-        if let Ok(app_data_dir) = app.path().app_data_dir() {
-            let db_path = app_data_dir.join("agents.db");
-            if db_path.exists() {
-                if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                    let _ = conn.execute(
-                        "DELETE FROM app_settings WHERE key = 'claude_binary_path'",
-                        [],
-                    );
-                    log::info!("[EngineStatus] Cleared Claude binary path cache");
-                }
-            }
+        if let Ok(true) = delete_setting(&app, CLAUDE_BINARY_PATH).await {
+            log::info!("[EngineStatus] Cleared Claude binary path cache");
         }
     }
     
@@ -209,41 +224,107 @@ pub async fn check_engine_update(
     let current_status = check_engine_status(app, engine.clone()).await?;
     let current_version = current_status.version.clone();
     
-    // 查询最新版本
-    let latest_version_result = match engine.to_lowercase().as_str() {
-        "claude" => check_latest_version_npm("@anthropic-ai/claude-code", &environment, wsl_distro.as_deref()).await,
-        "codex" => check_latest_version_npm("@openai/codex", &environment, wsl_distro.as_deref()).await,
-        "gemini" => check_latest_version_pip("google-generativeai", &environment, wsl_distro.as_deref()).await,
-        _ => return Err(format!("Unknown engine: {}", engine))
-    };
+    Ok(check_update_for(&engine, current_version, &environment, wsl_distro.as_deref()).await)
+}
+
+/// 并发检查所有已安装引擎的更新
+#[tauri::command]
+pub async fn check_all_engine_updates(app: AppHandle) -> Result<HashMap<String, CheckUpdateResult>, String> {
+    log::info!("[EngineStatus] Checking updates for all engines...");
+    let now = chrono::Utc::now().timestamp();
     
-    match latest_version_result {
-        Ok(latest_version) => {
-            let update_available = if let Some(ref current) = current_version {
-                // 清理版本号，只保留数字和点
-                let clean_current = extract_version_number(current);
-                let clean_latest = extract_version_number(&latest_version);
-                clean_current != clean_latest
-            } else {
-                false
-            };
-            
-            Ok(CheckUpdateResult {
-                current_version,
-                latest_version: Some(latest_version),
-                update_available,
-                error: None,
-            })
-        }
-        Err(e) => {
-            Ok(CheckUpdateResult {
-                current_version,
-                latest_version: None,
-                update_available: false,
-                error: Some(e),
-            })
+    let (claude, codex, gemini) = tokio::join!(
+        check_claude_status(app, now),
+        check_codex_status(now),
+        check_gemini_status(now),
+    );
+    
+    let mut checks = Vec::new();
+    for status in [claude, codex, gemini].into_iter().flatten() {
+        if !status.is_installed {
+            continue;
         }
+        checks.push(async move {
+            let result = check_update_for(
+                &status.engine,
+                status.version.clone(),
+                &status.environment,
+                status.wsl_distro.as_deref(),
+            )
+            .await;
+            (status.engine, result)
+        });
     }
+    
+    Ok(futures::future::join_all(checks).await.into_iter().collect())
+}
+
+/// 升级引擎并实时推送输出
+///
+/// 输出通过 `engine-upgrade-output` 事件推送（`{ engine, stream, line }`），
+/// 结束时发送 `engine-upgrade-complete`（`EngineUpdateResult`）
+#[tauri::command]
+pub async fn upgrade_engine(
+    app: AppHandle,
+    engine: String,
+    environment: String,
+    wsl_distro: Option<String>
+) -> Result<EngineUpdateResult, String> {
+    let engine = engine.to_lowercase();
+    let command = update_command(&engine, &environment, wsl_distro.as_deref())?;
+    log::info!("[EngineStatus] Upgrading {} with streamed output: {}", engine, command);
+    
+    let old_version = check_engine_status(app.clone(), engine.clone()).await?.version;
+    
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", &command]);
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", &command]);
+        cmd
+    };
+    cmd.stdout(std::process::Stdio::piped());
+    cmd.stderr(std::process::Stdio::piped());
+    crate::commands::claude::apply_no_window_async(&mut cmd);
+    
+    let mut child = cmd.spawn().map_err(|e| format!("执行命令失败: {}", e))?;
+    let stdout = child.stdout.take().ok_or("无法获取 stdout")?;
+    let stderr = child.stderr.take().ok_or("无法获取 stderr")?;
+    
+    let (stdout_text, stderr_text, status) = tokio::join!(
+        stream_upgrade_output(&app, &engine, stdout, "stdout"),
+        stream_upgrade_output(&app, &engine, stderr, "stderr"),
+        child.wait(),
+    );
+    
+    // 升级后清除缓存的更新结果并重新检查版本
+    UPDATE_CACHE.lock().unwrap().remove(&engine);
+    crate::claude_binary::invalidate_binary_cache(&engine);
+    let new_version = check_engine_status(app.clone(), engine.clone()).await?.version;
+    
+    let success = status.as_ref().map(|s| s.success()).unwrap_or(false);
+    let result = EngineUpdateResult {
+        success,
+        old_version,
+        new_version,
+        output: format!("{}{}", stdout_text, stderr_text),
+        error: if success {
+            None
+        } else {
+            Some(match status {
+                Ok(status) => format!("更新失败 ({}): {}", status, stderr_text.trim()),
+                Err(e) => format!("等待进程失败: {}", e),
+            })
+        },
+    };
+    
+    let _ = app.emit(
+        "engine-upgrade-complete",
+        serde_json::json!({ "engine": engine, "result": &result }),
+    );
+    Ok(result)
 }
 
 // ============================================================================
@@ -282,6 +363,8 @@ async fn check_claude_status(app: AppHandle, timestamp: i64) -> Result<UnifiedEn
                     None
                 },
                 last_checked: Some(timestamp),
+                latest_version: None,
+                update_available: None,
             })
         }
         Err(e) => {
@@ -293,8 +376,10 @@ async fn check_claude_status(app: AppHandle, timestamp: i64) -> Result<UnifiedEn
                 environment: "native".to_string(),
                 wsl_distro: None,
                 path: None,
-                error: Some(e),
+                error: Some(e.to_string()),
                 last_checked: Some(timestamp),
+                latest_version: None,
+                update_available: None,
             })
         }
     }
@@ -335,6 +420,8 @@ async fn check_codex_status(timestamp: i64) -> Result<UnifiedEngineStatus, Strin
                 path: None, // TODO: 从 codex_status 中提取路径
                 error: codex_status.error,
                 last_checked: Some(timestamp),
+                latest_version: None,
+                update_available: None,
             })
         }
         Err(e) => {
@@ -346,8 +433,10 @@ async fn check_codex_status(timestamp: i64) -> Result<UnifiedEngineStatus, Strin
                 environment: "native".to_string(),
                 wsl_distro: None,
                 path: None,
-                error: Some(e),
+                error: Some(e.to_string()),
                 last_checked: Some(timestamp),
+                latest_version: None,
+                update_available: None,
             })
         }
     }
@@ -386,6 +475,8 @@ async fn check_gemini_status(timestamp: i64) -> Result<UnifiedEngineStatus, Stri
                 path: gemini_status.path,
                 error: gemini_status.error,
                 last_checked: Some(timestamp),
+                latest_version: None,
+                update_available: None,
             })
         }
         Err(e) => {
@@ -397,8 +488,10 @@ async fn check_gemini_status(timestamp: i64) -> Result<UnifiedEngineStatus, Stri
                 environment: "native".to_string(),
                 wsl_distro: None,
                 path: None,
-                error: Some(e),
+                error: Some(e.to_string()),
                 last_checked: Some(timestamp),
+                latest_version: None,
+                update_available: None,
             })
         }
     }
@@ -408,55 +501,171 @@ async fn check_gemini_status(timestamp: i64) -> Result<UnifiedEngineStatus, Stri
 // 辅助函数
 // ============================================================================
 
-/// 更新 Claude
-async fn update_claude(environment: &str, wsl_distro: Option<&str>) -> Result<String, String> {
-    log::info!("[EngineStatus] Updating Claude in {} environment", environment);
+/// 逐行推送升级输出，返回完整文本
+async fn stream_upgrade_output<R>(app: &AppHandle, engine: &str, reader: R, stream: &str) -> String
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::{AsyncBufReadExt, BufReader};
     
-    let command = if environment == "wsl" {
+    let mut collected = String::new();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let _ = app.emit(
+            "engine-upgrade-output",
+            serde_json::json!({ "engine": engine, "stream": stream, "line": line }),
+        );
+        collected.push_str(&line);
+        collected.push('\n');
+    }
+    collected
+}
+
+/// 各引擎对应的 npm 包
+fn engine_npm_package(engine: &str) -> Option<&'static str> {
+    match engine {
+        "claude" => Some("@anthropic-ai/claude-code"),
+        "codex" => Some("@openai/codex"),
+        "gemini" => Some("@google/gemini-cli"),
+        _ => None,
+    }
+}
+
+/// 构建升级命令（`npm install -g <package>@latest`，WSL 环境通过 `wsl` 转发）
+fn update_command(engine: &str, environment: &str, wsl_distro: Option<&str>) -> Result<String, String> {
+    let package = engine_npm_package(engine).ok_or_else(|| tr!("engine.unsupported", engine = engine))?;
+    let install = format!("npm install -g {}@latest", package);
+    
+    Ok(if environment == "wsl" {
         if let Some(distro) = wsl_distro {
-            format!("wsl -d {} npm install -g @anthropic-ai/claude-code", distro)
+            format!("wsl -d {} {}", distro, install)
         } else {
-            "wsl npm install -g @anthropic-ai/claude-code".to_string()
+            format!("wsl {}", install)
         }
     } else {
-        "npm install -g @anthropic-ai/claude-code".to_string()
-    };
-    
-    execute_update_command(&command).await
+        install
+    })
+}
+
+/// 更新 Claude
+async fn update_claude(environment: &str, wsl_distro: Option<&str>) -> Result<String, String> {
+    log::info!("[EngineStatus] Updating Claude in {} environment", environment);
+    execute_update_command(&update_command("claude", environment, wsl_distro)?).await
 }
 
 /// 更新 Codex
 async fn update_codex(environment: &str, wsl_distro: Option<&str>) -> Result<String, String> {
     log::info!("[EngineStatus] Updating Codex in {} environment", environment);
-    
-    let command = if environment == "wsl" {
-        if let Some(distro) = wsl_distro {
-            format!("wsl -d {} npm install -g @openai/codex", distro)
-        } else {
-            "wsl npm install -g @openai/codex".to_string()
-        }
-    } else {
-        "npm install -g @openai/codex".to_string()
-    };
-    
-    execute_update_command(&command).await
+    execute_update_command(&update_command("codex", environment, wsl_distro)?).await
 }
 
 /// 更新 Gemini
 async fn update_gemini(environment: &str, wsl_distro: Option<&str>) -> Result<String, String> {
     log::info!("[EngineStatus] Updating Gemini in {} environment", environment);
+    execute_update_command(&update_command("gemini", environment, wsl_distro)?).await
+}
+
+/// 最近一次更新检查结果（engine -> result），供 check_engine_status 附带
+static UPDATE_CACHE: Lazy<std::sync::Mutex<HashMap<String, CheckUpdateResult>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+fn apply_cached_update(status: &mut UnifiedEngineStatus) {
+    if let Some(cached) = UPDATE_CACHE.lock().unwrap().get(&status.engine) {
+        // 版本变化（如外部升级）后缓存失效
+        if cached.current_version == status.version {
+            status.latest_version = cached.latest_version.clone();
+            status.update_available = Some(cached.update_available);
+        }
+    }
+}
+
+/// 查询最新版本并与当前版本比较，结果写入缓存
+async fn check_update_for(
+    engine: &str,
+    current_version: Option<String>,
+    environment: &str,
+    wsl_distro: Option<&str>,
+) -> CheckUpdateResult {
+    let Some(package) = engine_npm_package(engine) else {
+        return CheckUpdateResult {
+            current_version,
+            latest_version: None,
+            update_available: false,
+            error: Some(tr!("engine.unsupported", engine = engine)),
+        };
+    };
     
-    let command = if environment == "wsl" {
-        if let Some(distro) = wsl_distro {
-            format!("wsl -d {} pip install --upgrade google-generativeai", distro)
-        } else {
-            "wsl pip install --upgrade google-generativeai".to_string()
+    // 优先直接查询 npm registry（无需本地 npm），失败时回退到 `npm view`
+    let latest = match fetch_latest_npm_version(package).await {
+        Ok(version) => Ok(version),
+        Err(e) => {
+            log::warn!("[EngineStatus] npm registry lookup failed for {}: {}, falling back to npm view", package, e);
+            check_latest_version_npm(package, environment, wsl_distro).await
         }
-    } else {
-        "pip install --upgrade google-generativeai".to_string()
     };
     
-    execute_update_command(&command).await
+    let result = match latest {
+        Ok(latest_version) => CheckUpdateResult {
+            update_available: current_version
+                .as_deref()
+                .map(|current| is_newer_version(current, &latest_version))
+                .unwrap_or(false),
+            current_version,
+            latest_version: Some(latest_version),
+            error: None,
+        },
+        Err(e) => CheckUpdateResult {
+            current_version,
+            latest_version: None,
+            update_available: false,
+            error: Some(e),
+        },
+    };
+    
+    if result.error.is_none() {
+        UPDATE_CACHE.lock().unwrap().insert(engine.to_string(), result.clone());
+    }
+    result
+}
+
+/// 从 npm registry 获取包的最新版本
+async fn fetch_latest_npm_version(package: &str) -> Result<String, String> {
+    let url = format!("https://registry.npmjs.org/{}/latest", package);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    
+    let resp = client.get(&url).send().await.map_err(|e| format!("请求 npm registry 失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("npm registry 返回 {}", resp.status()));
+    }
+    
+    let body: serde_json::Value = resp.json().await.map_err(|e| format!("解析 npm registry 响应失败: {}", e))?;
+    body.get("version")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "npm registry 响应中没有 version 字段".to_string())
+}
+
+/// `latest` 是否比 `current` 新（按数字段逐段比较）
+fn is_newer_version(current: &str, latest: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        extract_version_number(v)
+            .split('.')
+            .map(|part| part.parse::<u64>().unwrap_or(0))
+            .collect()
+    };
+    let (current, latest) = (parse(current), parse(latest));
+    
+    for i in 0..current.len().max(latest.len()) {
+        let c = current.get(i).copied().unwrap_or(0);
+        let l = latest.get(i).copied().unwrap_or(0);
+        if l != c {
+            return l > c;
+        }
+    }
+    false
 }
 
 /// 执行更新命令
@@ -555,66 +764,6 @@ async fn check_latest_version_npm(package: &str, environment: &str, wsl_distro:
     }
 }
 
-/// 检查 pip 包的最新版本
-async fn check_latest_version_pip(package: &str, environment: &str, wsl_distro: Option<&str>) -> Result<String, String> {
-    use std::process::Command;
-    
-    let command = if environment == "wsl" {
-        if let Some(distro) = wsl_distro {
-            format!("wsl -d {} pip index versions {}", distro, package)
-        } else {
-            format!("wsl pip index versions {}", package)
-        }
-    } else {
-        format!("pip index versions {}", package)
-    };
-    
-    log::info!("[EngineStatus] Checking latest version: {}", command);
-    
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(&["/C", &command])
-            .output()
-    } else {
-        Command::new("sh")
-            .args(&["-c", &command])
-            .output()
-    };
-    
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                // 从输出中提取版本号 (格式: "Available versions: 1.0.0, 0.9.0, ...")
-                // 过滤掉 "Active code page" 等无关信息
-                for line in stdout.lines() {
-                    if line.contains("Active code page") {
-                        continue;
-                    }
-                    if line.contains("Available versions:") {
-                        if let Some(versions) = line.split(':').nth(1) {
-                            if let Some(latest) = versions.trim().split(',').next() {
-                                let version = latest.trim().to_string();
-                                log::info!("[EngineStatus] Latest version: {}", version);
-                                return Ok(version);
-                            }
-                        }
-                    }
-                }
-                Err("无法解析版本信息".to_string())
-            } else {
-                let error = String::from_utf8_lossy(&output.stderr).to_string();
-                log::error!("[EngineStatus] Failed to check version: {}", error);
-                Err(format!("查询版本失败: {}", error))
-            }
-        }
-        Err(e) => {
-            log::error!("[EngineStatus] Failed to execute command: {}", e);
-            Err(format!("执行命令失败: {}", e))
-        }
-    }
-}
-
 /// 从版本字符串中提取纯数字版本号
 /// 例如: "2.0.75 (Claude Code)" -> "2.0.75"
 ///       "WSL: 0.72.0" -> "0.72.0"
@@ -677,6 +826,458 @@ fn extract_wsl_distro(text: &str) -> Option<String> {
     None
 }
 
+// ============================================================================
+// 健康检查（引擎状态面板）
+// ============================================================================
+
+/// 健康检查缓存有效期
+const HEALTH_CACHE_TTL: Duration = Duration::from_secs(60);
+/// 服务商连通性探测超时
+const PROVIDER_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 认证状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineAuthStatus {
+    /// 是否已认证（None 表示无法判断，如 macOS 钥匙串）
+    pub authenticated: Option<bool>,
+
+    /// 认证方式 ("oauth" | "api_key" | "vertex_ai" ...)
+    pub method: Option<String>,
+
+    /// 说明信息
+    pub detail: Option<String>,
+}
+
+/// 服务商连通性
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderReachability {
+    pub base_url: String,
+
+    /// 能否建立 HTTP 连接（任何 HTTP 响应都算可达）
+    pub reachable: bool,
+
+    pub status_code: Option<u16>,
+
+    pub latency_ms: Option<u64>,
+
+    pub error: Option<String>,
+}
+
+/// MCP 配置校验结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpConfigCheck {
+    pub config_path: Option<String>,
+
+    /// 配置文件存在且所有服务器配置有效
+    pub valid: bool,
+
+    pub server_count: usize,
+
+    pub errors: Vec<String>,
+}
+
+/// 单个引擎的健康状况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineHealth {
+    pub status: UnifiedEngineStatus,
+    pub auth: EngineAuthStatus,
+    pub provider: ProviderReachability,
+    pub mcp: McpConfigCheck,
+}
+
+/// WSL 状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WslHealth {
+    pub enabled: bool,
+    pub distro: Option<String>,
+    pub codex_path_in_wsl: Option<String>,
+    pub available_distros: Vec<String>,
+}
+
+/// 所有引擎的健康报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineHealthReport {
+    pub claude: EngineHealth,
+    pub codex: EngineHealth,
+    pub gemini: EngineHealth,
+    pub wsl: WslHealth,
+
+    /// 检查时间戳
+    pub checked_at: i64,
+
+    /// 是否来自缓存
+    pub cached: bool,
+}
+
+static HEALTH_CACHE: Lazy<tokio::sync::Mutex<Option<(Instant, EngineHealthReport)>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+/// 并发检查所有引擎的可用性、版本、认证、服务商连通性、MCP 配置和 WSL 状态
+///
+/// 结果缓存 60 秒，`force_refresh` 为 true 时强制重新检查
+#[tauri::command]
+pub async fn check_all_engines(
+    app: AppHandle,
+    force_refresh: Option<bool>,
+) -> Result<EngineHealthReport, String> {
+    // 持有锁直到检查完成，避免并发请求重复探测
+    let mut cache = HEALTH_CACHE.lock().await;
+    if !force_refresh.unwrap_or(false) {
+        if let Some((at, report)) = cache.as_ref() {
+            if at.elapsed() < HEALTH_CACHE_TTL {
+                let mut report = report.clone();
+                report.cached = true;
+                return Ok(report);
+            }
+        }
+    }
+
+    log::info!("[EngineStatus] Running health check for all engines...");
+    let now = chrono::Utc::now().timestamp();
+
+    let (claude, codex, gemini, wsl) = tokio::join!(
+        check_claude_health(app, now),
+        check_codex_health(now),
+        check_gemini_health(now),
+        tokio::task::spawn_blocking(check_wsl_health),
+    );
+
+    let report = EngineHealthReport {
+        claude,
+        codex,
+        gemini,
+        wsl: wsl.unwrap_or_default(),
+        checked_at: now,
+        cached: false,
+    };
+
+    *cache = Some((Instant::now(), report.clone()));
+    Ok(report)
+}
+
+async fn check_claude_health(app: AppHandle, timestamp: i64) -> EngineHealth {
+    let home = dirs::home_dir().unwrap_or_default();
+    let settings = read_json_file(&home.join(".claude").join("settings.json"));
+    let claude_json = read_json_file(&home.join(".claude.json"));
+
+    let setting_env = |key: &str| -> Option<String> {
+        settings
+            .as_ref()
+            .and_then(|s| s.get("env"))
+            .and_then(|env| env.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or_else(|| std::env::var(key).ok())
+            .filter(|v| !v.trim().is_empty())
+    };
+
+    let auth = if setting_env("ANTHROPIC_API_KEY").is_some() || setting_env("ANTHROPIC_AUTH_TOKEN").is_some() {
+        EngineAuthStatus {
+            authenticated: Some(true),
+            method: Some("api_key".to_string()),
+            detail: None,
+        }
+    } else if claude_json.as_ref().and_then(|c| c.get("oauthAccount")).is_some()
+        || home.join(".claude").join(".credentials.json").exists()
+    {
+        EngineAuthStatus {
+            authenticated: Some(true),
+            method: Some("oauth".to_string()),
+            detail: None,
+        }
+    } else if cfg!(target_os = "macos") {
+        EngineAuthStatus {
+            authenticated: None,
+            method: None,
+            detail: Some("凭据可能保存在 macOS 钥匙串中，无法直接检测".to_string()),
+        }
+    } else {
+        EngineAuthStatus {
+            authenticated: Some(false),
+            method: None,
+            detail: Some("未找到 API Key 或 OAuth 登录信息".to_string()),
+        }
+    };
+
+    let base_url = setting_env("ANTHROPIC_BASE_URL").unwrap_or_else(|| "https://api.anthropic.com".to_string());
+    let mcp_path = home.join(".claude.json");
+
+    let (status, provider) = tokio::join!(
+        check_claude_status(app, timestamp),
+        probe_provider(base_url),
+    );
+
+    EngineHealth {
+        status: status.unwrap_or_else(|e| failed_status("claude", e, timestamp)),
+        auth,
+        provider,
+        mcp: check_json_mcp_config(&mcp_path, claude_json.as_ref()),
+    }
+}
+
+async fn check_codex_health(timestamp: i64) -> EngineHealth {
+    let current = crate::commands::codex::get_current_codex_config().await.ok();
+
+    let auth = match &current {
+        Some(config) if config.auth.get("tokens").is_some() => EngineAuthStatus {
+            authenticated: Some(true),
+            method: Some("oauth".to_string()),
+            detail: None,
+        },
+        Some(config) if config.api_key.is_some() => EngineAuthStatus {
+            authenticated: Some(true),
+            method: Some("api_key".to_string()),
+            detail: None,
+        },
+        Some(_) => EngineAuthStatus {
+            authenticated: Some(false),
+            method: None,
+            detail: Some("auth.json 中没有 OAuth Token 或 API Key".to_string()),
+        },
+        None => EngineAuthStatus {
+            authenticated: Some(false),
+            method: None,
+            detail: Some("无法读取 Codex 配置".to_string()),
+        },
+    };
+
+    let base_url = current
+        .as_ref()
+        .and_then(|c| c.base_url.clone())
+        .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+
+    let (status, provider) = tokio::join!(check_codex_status(timestamp), probe_provider(base_url));
+
+    let config_path = crate::commands::codex::mcp::get_codex_config_path().ok();
+    let mcp = match crate::commands::codex::mcp::parse_codex_mcp_config() {
+        Ok(servers) => {
+            let errors: Vec<String> = servers
+                .iter()
+                .filter(|s| s.command.as_deref().unwrap_or("").trim().is_empty() && s.url.is_none())
+                .map(|s| format!("{}: 缺少 command 或 url", s.name))
+                .collect();
+            McpConfigCheck {
+                config_path: config_path.map(|p| p.to_string_lossy().to_string()),
+                valid: errors.is_empty(),
+                server_count: servers.len(),
+                errors,
+            }
+        }
+        Err(e) => McpConfigCheck {
+            config_path: config_path.map(|p| p.to_string_lossy().to_string()),
+            valid: false,
+            server_count: 0,
+            errors: vec![format!("{:#}", e)],
+        },
+    };
+
+    EngineHealth {
+        status: status.unwrap_or_else(|e| failed_status("codex", e, timestamp)),
+        auth,
+        provider,
+        mcp,
+    }
+}
+
+async fn check_gemini_health(timestamp: i64) -> EngineHealth {
+    use crate::commands::gemini::config::{get_gemini_dir, load_gemini_config, GeminiAuthMethod};
+
+    let gemini_dir = get_gemini_dir().unwrap_or_default();
+    let config = load_gemini_config().unwrap_or_default();
+    let env_or = |key: &str| -> Option<String> {
+        config
+            .env
+            .get(key)
+            .cloned()
+            .or_else(|| std::env::var(key).ok())
+            .filter(|v| !v.trim().is_empty())
+    };
+
+    let (auth, default_url) = match config.auth_method {
+        GeminiAuthMethod::ApiKey => {
+            let has_key = config.api_key.as_deref().is_some_and(|k| !k.trim().is_empty())
+                || env_or("GEMINI_API_KEY").is_some();
+            (
+                EngineAuthStatus {
+                    authenticated: Some(has_key),
+                    method: Some("api_key".to_string()),
+                    detail: (!has_key).then(|| "未配置 GEMINI_API_KEY".to_string()),
+                },
+                "https://generativelanguage.googleapis.com",
+            )
+        }
+        GeminiAuthMethod::GoogleOauth => {
+            let logged_in = gemini_dir.join("oauth_creds.json").exists();
+            (
+                EngineAuthStatus {
+                    authenticated: Some(logged_in),
+                    method: Some("oauth".to_string()),
+                    detail: (!logged_in).then(|| "未找到 ~/.gemini/oauth_creds.json，请先登录".to_string()),
+                },
+                "https://generativelanguage.googleapis.com",
+            )
+        }
+        GeminiAuthMethod::VertexAi => {
+            let has_project = config.google_cloud_project.is_some() || env_or("GOOGLE_CLOUD_PROJECT").is_some();
+            (
+                EngineAuthStatus {
+                    authenticated: Some(has_project),
+                    method: Some("vertex_ai".to_string()),
+                    detail: (!has_project).then(|| "未配置 Google Cloud Project".to_string()),
+                },
+                "https://aiplatform.googleapis.com",
+            )
+        }
+    };
+
+    let base_url = env_or("GOOGLE_GEMINI_BASE_URL").unwrap_or_else(|| default_url.to_string());
+    let settings_path = gemini_dir.join("settings.json");
+    let settings = read_json_file(&settings_path);
+
+    let (status, provider) = tokio::join!(check_gemini_status(timestamp), probe_provider(base_url));
+
+    EngineHealth {
+        status: status.unwrap_or_else(|e| failed_status("gemini", e, timestamp)),
+        auth,
+        provider,
+        mcp: check_json_mcp_config(&settings_path, settings.as_ref()),
+    }
+}
+
+fn check_wsl_health() -> WslHealth {
+    let config = crate::commands::wsl_utils::get_wsl_config();
+
+    #[cfg(target_os = "windows")]
+    let available_distros = crate::commands::wsl_utils::get_wsl_distros();
+    #[cfg(not(target_os = "windows"))]
+    let available_distros = Vec::new();
+
+    WslHealth {
+        enabled: config.enabled,
+        distro: config.distro.clone(),
+        codex_path_in_wsl: config.codex_path_in_wsl.clone(),
+        available_distros,
+    }
+}
+
+fn failed_status(engine: &str, error: String, timestamp: i64) -> UnifiedEngineStatus {
+    UnifiedEngineStatus {
+        engine: engine.to_string(),
+        is_installed: false,
+        version: None,
+        environment: "native".to_string(),
+        wsl_distro: None,
+        path: None,
+        error: Some(error),
+        last_checked: Some(timestamp),
+        latest_version: None,
+        update_available: None,
+    }
+}
+
+fn read_json_file(path: &std::path::Path) -> Option<serde_json::Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 探测服务商地址是否可达（任何 HTTP 响应都视为可达）
+async fn probe_provider(base_url: String) -> ProviderReachability {
+    let client = match reqwest::Client::builder().timeout(PROVIDER_PROBE_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            return ProviderReachability {
+                base_url,
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    };
+
+    let started = Instant::now();
+    match client.get(&base_url).send().await {
+        Ok(resp) => ProviderReachability {
+            reachable: true,
+            status_code: Some(resp.status().as_u16()),
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            error: None,
+            base_url,
+        },
+        Err(e) => ProviderReachability {
+            reachable: false,
+            status_code: None,
+            latency_ms: None,
+            error: Some(if e.is_timeout() { "连接超时".to_string() } else { e.to_string() }),
+            base_url,
+        },
+    }
+}
+
+/// 校验 JSON 配置中的 `mcpServers`（Claude 的 ~/.claude.json、Gemini 的 settings.json）
+fn check_json_mcp_config(path: &std::path::Path, config: Option<&serde_json::Value>) -> McpConfigCheck {
+    let config_path = Some(path.to_string_lossy().to_string());
+
+    if !path.exists() {
+        // 没有配置文件 = 没有 MCP 服务器，不算错误
+        return McpConfigCheck {
+            config_path,
+            valid: true,
+            ..Default::default()
+        };
+    }
+
+    let Some(config) = config else {
+        return McpConfigCheck {
+            config_path,
+            valid: false,
+            server_count: 0,
+            errors: vec!["配置文件不是有效的 JSON".to_string()],
+        };
+    };
+
+    let (server_count, errors) = validate_mcp_servers(config.get("mcpServers"));
+    McpConfigCheck {
+        config_path,
+        valid: errors.is_empty(),
+        server_count,
+        errors,
+    }
+}
+
+/// 校验每个 MCP 服务器至少有 command（stdio）或 url（sse/http）
+fn validate_mcp_servers(servers: Option<&serde_json::Value>) -> (usize, Vec<String>) {
+    let Some(servers) = servers else {
+        return (0, Vec::new());
+    };
+    let Some(servers) = servers.as_object() else {
+        return (0, vec!["mcpServers 必须是对象".to_string()]);
+    };
+
+    let mut errors = Vec::new();
+    for (name, server) in servers {
+        let has_command = server.get("command").and_then(|c| c.as_str()).is_some_and(|c| !c.trim().is_empty());
+        let has_url = server
+            .get("url")
+            .or_else(|| server.get("httpUrl"))
+            .and_then(|u| u.as_str())
+            .is_some_and(|u| !u.trim().is_empty());
+        if !has_command && !has_url {
+            errors.push(format!("{}: 缺少 command 或 url", name));
+        }
+        if let Some(args) = server.get("args") {
+            if !args.is_array() {
+                errors.push(format!("{}: args 必须是数组", name));
+            }
+        }
+    }
+    (servers.len(), errors)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -731,4 +1332,27 @@ mod tests {
             "1.2.3"
         );
     }
+    
+    #[test]
+    fn test_validate_mcp_servers() {
+        let servers = serde_json::json!({
+            "fs": { "command": "npx", "args": ["-y", "@modelcontextprotocol/server-filesystem"] },
+            "remote": { "url": "https://example.com/sse" },
+            "broken": { "args": "not-an-array" }
+        });
+        let (count, errors) = validate_mcp_servers(Some(&servers));
+        assert_eq!(count, 3);
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.starts_with("broken")));
+        
+        assert_eq!(validate_mcp_servers(None), (0, Vec::new()));
+    }
+    
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("2.0.75 (Claude Code)", "2.0.76"));
+        assert!(is_newer_version("codex-cli 0.9.0", "0.10.0"));
+        assert!(!is_newer_version("0.72.0", "0.72.0"));
+        assert!(!is_newer_version("WSL: 1.2.3", "1.2.2"));
+    }
 }
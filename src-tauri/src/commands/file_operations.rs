@@ -1,4 +1,10 @@
+use crate::commands::codex::{init_change_tracker, record_file_change, ChangeSource, ChangeType};
+use crate::commands::trash;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command as StdCommand;
+use tauri::{AppHandle, Emitter};
+use super::data_root::anycode_dir;
 
 /// Open a directory in the system file explorer (cross-platform)
 #[tauri::command]
@@ -64,3 +70,514 @@ pub async fn open_file_with_default_app(file_path: String) -> Result<(), String>
 
     Ok(())
 }
+
+// ============================================================================
+// Batch file operations
+// ============================================================================
+//
+// Rename / move / delete several paths as one transaction: every operation is
+// validated up front, and if one fails the completed ones are rolled back.
+// Deleted paths go to the workspace trash (see `trash.rs`) instead of being
+// removed, and the journal is kept under ~/.anycode/file_batches/<id>/, so a
+// batch can be reverted later with `revert_file_batch`. When a session is given, each affected text file is also
+// recorded in the change tracker as a Tool change so it shows up in the same
+// change history as the engine's own edits.
+
+/// Text files larger than this are moved but not recorded in the change tracker
+const MAX_TRACKED_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Tool name used for change tracker records
+const BATCH_TOOL_NAME: &str = "file_operations";
+
+/// A single operation of a batch (paths are relative to the project or absolute inside it)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum BatchFileOperation {
+    /// Rename in place; `to` is the new file name
+    Rename { from: String, to: String },
+    /// Move into the destination directory `to`, keeping the name
+    Move { from: String, to: String },
+    Delete { path: String },
+}
+
+/// Session whose change history the batch is recorded into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeTracking {
+    pub session_id: String,
+    pub prompt_index: i32,
+}
+
+/// An executed operation (absolute paths; deleted paths point into the batch directory)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchEntry {
+    pub op: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Journal of an executed batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileBatch {
+    pub id: String,
+    pub project_path: String,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracking: Option<ChangeTracking>,
+    pub entries: Vec<BatchEntry>,
+    #[serde(default)]
+    pub reverted: bool,
+    /// Change tracker record IDs created by the batch (and its revert)
+    #[serde(default)]
+    pub change_ids: Vec<String>,
+}
+
+fn get_batches_dir() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("file_batches"))
+}
+
+fn journal_path(batch_dir: &Path) -> PathBuf {
+    batch_dir.join("batch.json")
+}
+
+fn save_batch(batch_dir: &Path, batch: &FileBatch) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(batch).map_err(|e| format!("Failed to serialize batch: {}", e))?;
+    std::fs::write(journal_path(batch_dir), content).map_err(|e| format!("Failed to save batch journal: {}", e))
+}
+
+fn load_batch(batch_dir: &Path) -> Result<FileBatch, String> {
+    let content =
+        std::fs::read_to_string(journal_path(batch_dir)).map_err(|e| format!("Failed to read batch journal: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid batch journal: {}", e))
+}
+
+/// Resolves `path` against the project root, rejecting paths that escape it
+fn resolve_in_project(root: &Path, path: &str) -> Result<PathBuf, String> {
+    let candidate = Path::new(path);
+    let joined = if candidate.is_absolute() { candidate.to_path_buf() } else { root.join(candidate) };
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    if !resolved.starts_with(root) || resolved == root {
+        return Err(format!("Path is outside the project: {}", path));
+    }
+    Ok(resolved)
+}
+
+/// Plans the batch: absolute (from, to) pairs, validated before anything is touched
+fn plan_batch(
+    root: &Path,
+    operations: &[BatchFileOperation],
+) -> Result<Vec<BatchEntry>, String> {
+    let mut entries: Vec<BatchEntry> = Vec::new();
+    for operation in operations {
+        let (op, from, to) = match operation {
+            BatchFileOperation::Rename { from, to } => {
+                if to.is_empty() || to.contains(['/', '\\']) {
+                    return Err(format!("Invalid file name: {}", to));
+                }
+                let from = resolve_in_project(root, from)?;
+                let to = from.with_file_name(to);
+                ("rename", from, to)
+            }
+            BatchFileOperation::Move { from, to } => {
+                let from = resolve_in_project(root, from)?;
+                let dest_dir = if to.is_empty() || to == "." { root.to_path_buf() } else { resolve_in_project(root, to)? };
+                if !dest_dir.is_dir() {
+                    return Err(format!("Destination is not a directory: {}", dest_dir.display()));
+                }
+                let name = from.file_name().ok_or_else(|| format!("Invalid path: {}", from.display()))?;
+                let to = dest_dir.join(name);
+                ("move", from, to)
+            }
+            BatchFileOperation::Delete { path } => {
+                let from = resolve_in_project(root, path)?;
+                let (_, to) = trash::new_trash_slot(root)?;
+                ("delete", from, to)
+            }
+        };
+
+        if !from.exists() {
+            return Err(format!("Path does not exist: {}", from.display()));
+        }
+        if to.exists() {
+            return Err(format!("Destination already exists: {}", to.display()));
+        }
+        if to.starts_with(&from) {
+            return Err(format!("Cannot move {} into itself", from.display()));
+        }
+        let (from, to) = (from.to_string_lossy().to_string(), to.to_string_lossy().to_string());
+        if entries.iter().any(|e| e.from == from || e.to == to) {
+            return Err(format!("Path appears more than once in the batch: {}", from));
+        }
+        entries.push(BatchEntry { op: op.to_string(), from, to });
+    }
+    Ok(entries)
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Moves a file or directory, falling back to copy + remove across file systems
+pub(crate) fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to).map_err(|e| format!("Failed to move {}: {}", from.display(), e))?;
+    let removed = if from.is_dir() { std::fs::remove_dir_all(from) } else { std::fs::remove_file(from) };
+    removed.map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+/// Writes `contents` to a temp file next to `path` and renames it into place,
+/// so a crash or concurrent reader never sees a half-written file
+pub(crate) fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    use std::io::Write;
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    temp.write_all(contents.as_ref())?;
+    temp.as_file().sync_all()?;
+    temp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Applies `(from, to)` moves in order; on failure the completed ones are undone
+fn apply_moves(moves: &[(PathBuf, PathBuf)]) -> Result<(), String> {
+    for (done, (from, to)) in moves.iter().enumerate() {
+        if let Err(e) = move_path(from, to) {
+            for (undo_from, undo_to) in moves[..done].iter().rev() {
+                if let Err(rollback) = move_path(undo_to, undo_from) {
+                    log::error!("[FileBatch] Rollback of {} failed: {}", undo_to.display(), rollback);
+                }
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Text files under `path` (a file or directory) as (relative path, content)
+fn collect_text_files(root: &Path, path: &Path) -> Vec<(String, String)> {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.metadata().map(|m| m.len() <= MAX_TRACKED_FILE_BYTES).unwrap_or(false))
+        .filter_map(|e| {
+            let content = std::fs::read_to_string(e.path()).ok()?;
+            let relative = e.path().strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+            Some((relative, content))
+        })
+        .collect()
+}
+
+/// Rebases a relative path from `from` onto `to` (both relative to the project root)
+fn rebase_relative(relative: &str, from: &str, to: &str) -> String {
+    match relative.strip_prefix(from) {
+        Some(rest) => format!("{}{}", to, rest),
+        None => relative.to_string(),
+    }
+}
+
+fn relative_to(root: &Path, path: &str) -> Option<String> {
+    Path::new(path)
+        .strip_prefix(root)
+        .ok()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+/// Records the effect of `moves` (after they were applied) in the change tracker.
+/// `snapshots` holds the text files under each source, taken before moving.
+fn record_moves(
+    app: &AppHandle,
+    root: &Path,
+    tracking: &ChangeTracking,
+    batch_id: &str,
+    moves: &[(PathBuf, PathBuf)],
+    snapshots: Vec<Vec<(String, String)>>,
+) -> Vec<String> {
+    let mut change_ids = Vec::new();
+    let mut record = |file_path: &str, change_type: ChangeType, old: Option<String>, new: Option<String>| {
+        match record_file_change(
+            &tracking.session_id,
+            tracking.prompt_index,
+            file_path,
+            change_type,
+            ChangeSource::Tool,
+            old,
+            new,
+            Some(BATCH_TOOL_NAME.to_string()),
+            Some(batch_id.to_string()),
+            None,
+            None,
+        ) {
+            Ok(change_id) => change_ids.push(change_id),
+            Err(e) => log::warn!("[FileBatch] Failed to record change for {}: {}", file_path, e),
+        }
+    };
+
+    for ((from, to), files) in moves.iter().zip(snapshots) {
+        let from_rel = relative_to(root, &from.to_string_lossy());
+        // Paths outside the project (the trash) count as deleted / restored
+        let to_rel = relative_to(root, &to.to_string_lossy());
+        for (relative, content) in files {
+            if from_rel.is_some() {
+                record(&relative, ChangeType::Delete, Some(content.clone()), None);
+            }
+            if let Some(to_rel) = &to_rel {
+                let target = match &from_rel {
+                    Some(from_rel) => rebase_relative(&relative, from_rel, to_rel),
+                    None => to_rel.clone(),
+                };
+                record(&target, ChangeType::Create, None, Some(content));
+            }
+        }
+    }
+
+    let payload = serde_json::json!({
+        "session_id": tracking.session_id,
+        "prompt_index": tracking.prompt_index,
+        "batch_id": batch_id,
+        "change_ids": change_ids,
+        "source": "tool",
+    });
+    let _ = app.emit(&format!("codex-change-recorded:{}", tracking.session_id), &payload);
+    let _ = app.emit("codex-change-recorded", &payload);
+    change_ids
+}
+
+/// Snapshots text files under each source; sources in the trash are keyed by their original path
+fn snapshot_sources(root: &Path, moves: &[(PathBuf, PathBuf)], original: &[PathBuf]) -> Vec<Vec<(String, String)>> {
+    moves
+        .iter()
+        .zip(original)
+        .map(|((from, _), original)| {
+            if from.starts_with(root) {
+                collect_text_files(root, from)
+            } else {
+                // Restoring from the trash: report the files under their project path
+                collect_text_files(from, from)
+                    .into_iter()
+                    .filter_map(|(relative, content)| {
+                        let path = if relative.is_empty() { original.clone() } else { original.join(&relative) };
+                        Some((relative_to(root, &path.to_string_lossy())?, content))
+                    })
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Runs blocking batch work off the async runtime
+async fn run_blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("File batch task failed: {}", e))?
+}
+
+/// Directory of a batch; the ID must be one we generated (a UUID), so it cannot point elsewhere
+fn batch_dir(batch_id: &str) -> Result<PathBuf, String> {
+    if uuid::Uuid::parse_str(batch_id).is_err() {
+        return Err(format!("Invalid batch ID: {}", batch_id));
+    }
+    Ok(get_batches_dir()?.join(batch_id))
+}
+
+fn apply_batch(
+    app: &AppHandle,
+    project_path: String,
+    operations: &[BatchFileOperation],
+    tracking: Option<ChangeTracking>,
+) -> Result<FileBatch, String> {
+    let root = std::fs::canonicalize(&project_path).map_err(|e| format!("Invalid project path {}: {}", project_path, e))?;
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let batch_dir = get_batches_dir()?.join(&batch_id);
+
+    let entries = plan_batch(&root, operations)?;
+    let moves: Vec<(PathBuf, PathBuf)> = entries.iter().map(|e| (PathBuf::from(&e.from), PathBuf::from(&e.to))).collect();
+    let originals: Vec<PathBuf> = moves.iter().map(|(from, _)| from.clone()).collect();
+    let snapshots = tracking.as_ref().map(|_| snapshot_sources(&root, &moves, &originals));
+
+    std::fs::create_dir_all(&batch_dir).map_err(|e| format!("Failed to create batch directory: {}", e))?;
+    if let Err(e) = apply_moves(&moves) {
+        let _ = std::fs::remove_dir_all(&batch_dir);
+        entries.iter().filter(|e| e.op == "delete").for_each(|e| trash::discard_trash_slot(Path::new(&e.to)));
+        return Err(e);
+    }
+    for entry in entries.iter().filter(|e| e.op == "delete") {
+        if let Err(e) = trash::commit_trash_entry(&root, Path::new(&entry.to), Path::new(&entry.from), Some("batch")) {
+            log::warn!("[FileBatch] Failed to record trash entry for {}: {}", entry.from, e);
+        }
+    }
+
+    let mut batch = FileBatch {
+        id: batch_id,
+        project_path,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        tracking,
+        entries,
+        reverted: false,
+        change_ids: Vec::new(),
+    };
+    if let (Some(tracking), Some(snapshots)) = (&batch.tracking, snapshots) {
+        init_change_tracker(&tracking.session_id, &batch.project_path);
+        batch.change_ids = record_moves(app, &root, tracking, &batch.id, &moves, snapshots);
+    }
+    save_batch(&batch_dir, &batch)?;
+    log::info!("[FileBatch] Applied batch {} ({} operations)", batch.id, batch.entries.len());
+    Ok(batch)
+}
+
+fn revert_batch(app: &AppHandle, batch_id: &str) -> Result<FileBatch, String> {
+    let batch_dir = batch_dir(batch_id)?;
+    let mut batch = load_batch(&batch_dir)?;
+    if batch.reverted {
+        return Err(format!("Batch {} has already been reverted", batch_id));
+    }
+    let root = std::fs::canonicalize(&batch.project_path)
+        .map_err(|e| format!("Invalid project path {}: {}", batch.project_path, e))?;
+
+    let moves: Vec<(PathBuf, PathBuf)> = batch
+        .entries
+        .iter()
+        .rev()
+        .map(|e| (PathBuf::from(&e.to), PathBuf::from(&e.from)))
+        .collect();
+    for (from, to) in &moves {
+        if !from.exists() {
+            // Deleted paths may have been restored or purged from the trash in the meantime
+            return Err(format!("Cannot revert: {} no longer exists", from.display()));
+        }
+        if to.exists() {
+            return Err(format!("Cannot revert: {} already exists", to.display()));
+        }
+    }
+    let originals: Vec<PathBuf> = moves.iter().map(|(_, to)| to.clone()).collect();
+    let snapshots = batch.tracking.as_ref().map(|_| snapshot_sources(&root, &moves, &originals));
+
+    apply_moves(&moves)?;
+    batch.entries.iter().filter(|e| e.op == "delete").for_each(|e| trash::discard_trash_slot(Path::new(&e.to)));
+    batch.reverted = true;
+    if let (Some(tracking), Some(snapshots)) = (&batch.tracking, snapshots) {
+        init_change_tracker(&tracking.session_id, &batch.project_path);
+        let change_ids = record_moves(app, &root, tracking, &batch.id, &moves, snapshots);
+        batch.change_ids.extend(change_ids);
+    }
+    save_batch(&batch_dir, &batch)?;
+    log::info!("[FileBatch] Reverted batch {}", batch.id);
+    Ok(batch)
+}
+
+/// Executes rename / move / delete operations as one transaction and returns the batch journal
+#[tauri::command]
+pub async fn batch_file_operations(
+    app: AppHandle,
+    project_path: String,
+    operations: Vec<BatchFileOperation>,
+    tracking: Option<ChangeTracking>,
+) -> Result<FileBatch, String> {
+    if operations.is_empty() {
+        return Err("No file operations given".to_string());
+    }
+    run_blocking(move || apply_batch(&app, project_path, &operations, tracking)).await
+}
+
+/// Reverts a batch: moves every path back (restoring deleted ones) in reverse order
+#[tauri::command]
+pub async fn revert_file_batch(app: AppHandle, batch_id: String) -> Result<FileBatch, String> {
+    run_blocking(move || revert_batch(&app, &batch_id)).await
+}
+
+/// Lists batches of a project, newest first
+#[tauri::command]
+pub async fn list_file_batches(project_path: String) -> Result<Vec<FileBatch>, String> {
+    let dir = get_batches_dir()?;
+    run_blocking(move || {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return Ok(Vec::new());
+        };
+        let mut batches: Vec<FileBatch> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| load_batch(&e.path()).ok())
+            .filter(|b| b.project_path == project_path)
+            .collect();
+        batches.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(batches)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_atomic_replaces_contents_without_leftovers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "old = true\n").unwrap();
+        write_atomic(&path, "new = true\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new = true\n");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn plans_and_rolls_back_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src/util")).unwrap();
+        std::fs::write(root.join("src/a.rs"), "a").unwrap();
+        std::fs::write(root.join("src/util/b.rs"), "b").unwrap();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+
+        let operations = vec![
+            BatchFileOperation::Rename { from: "src/a.rs".into(), to: "main.rs".into() },
+            BatchFileOperation::Move { from: "src/util".into(), to: "lib".into() },
+            BatchFileOperation::Delete { path: "src/../src/util/b.rs".into() },
+        ];
+        let entries = plan_batch(&root, &operations[..2]).unwrap();
+        assert_eq!(entries[0].to, root.join("src/main.rs").to_string_lossy());
+        assert_eq!(entries[1].to, root.join("lib/util").to_string_lossy());
+        assert_eq!(plan_batch(&root, &operations).unwrap()[2].from, root.join("src/util/b.rs").to_string_lossy());
+
+        let escape = [BatchFileOperation::Delete { path: "../outside".into() }];
+        assert!(plan_batch(&root, &escape).unwrap_err().contains("outside"));
+        let into_self = [BatchFileOperation::Move { from: "src".into(), to: "src/util".into() }];
+        assert!(plan_batch(&root, &into_self).is_err());
+
+        // The second move fails (source missing), so the first one is rolled back
+        let moves = vec![
+            (root.join("src/a.rs"), root.join("lib/a.rs")),
+            (root.join("missing.rs"), root.join("lib/missing.rs")),
+        ];
+        assert!(apply_moves(&moves).is_err());
+        assert!(root.join("src/a.rs").exists() && !root.join("lib/a.rs").exists());
+
+        let snapshots = collect_text_files(&root, &root.join("src/util"));
+        assert_eq!(snapshots, vec![("src/util/b.rs".to_string(), "b".to_string())]);
+        assert_eq!(rebase_relative("src/util/b.rs", "src/util", "lib/util"), "lib/util/b.rs");
+
+        assert!(batch_dir("../../.ssh").unwrap_err().starts_with("Invalid batch ID"));
+    }
+}
@@ -3,10 +3,13 @@
 //! Handles Gemini CLI configuration including authentication methods,
 //! model selection, and user preferences.
 
+use crate::tr;
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use tauri::async_runtime;
+use crate::commands::data_root::anycode_dir;
 
 // ============================================================================
 // Configuration Types
@@ -90,8 +93,7 @@ pub fn get_gemini_dir() -> Result<PathBuf, String> {
 
 /// Get the Any Code Gemini configuration path
 fn get_anycode_gemini_config_path() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
-    Ok(home.join(".anycode").join("gemini.json"))
+    Ok(anycode_dir()?.join("gemini.json"))
 }
 
 /// Load Gemini configuration from file
@@ -132,19 +134,19 @@ pub fn save_gemini_config(config: &GeminiConfig) -> Result<(), String> {
 
 /// Get Gemini configuration
 #[tauri::command]
-pub async fn get_gemini_config() -> Result<GeminiConfig, String> {
-    load_gemini_config()
+pub async fn get_gemini_config() -> Result<GeminiConfig, AnyCodeError> {
+    load_gemini_config().map_err(AnyCodeError::Config)
 }
 
 /// Update Gemini configuration
 #[tauri::command]
-pub async fn update_gemini_config(config: GeminiConfig) -> Result<(), String> {
-    save_gemini_config(&config)
+pub async fn update_gemini_config(config: GeminiConfig) -> Result<(), AnyCodeError> {
+    save_gemini_config(&config).map_err(AnyCodeError::Config)
 }
 
 /// Get available Gemini models
 #[tauri::command]
-pub async fn get_gemini_models() -> Result<Vec<GeminiModelInfo>, String> {
+pub async fn get_gemini_models() -> Result<Vec<GeminiModelInfo>, AnyCodeError> {
     Ok(vec![
         GeminiModelInfo {
             id: "gemini-3-pro-preview".to_string(),
@@ -226,6 +228,7 @@ pub fn build_gemini_env(config: &GeminiConfig) -> std::collections::HashMap<Stri
 
 use sha2::{Sha256, Digest};
 use crate::commands::gemini::types::{GeminiSessionLog, GeminiSessionDetail, GeminiSessionInfo};
+use crate::commands::session_metadata::{attach_session_metadata, SessionFilter, SessionMetadata};
 
 /// Generate SHA256 hash for project path (matching Gemini CLI behavior)
 pub fn hash_project_path(project_path: &str) -> String {
@@ -301,6 +304,7 @@ pub fn list_session_files(project_path: &str) -> Result<Vec<GeminiSessionInfo>,
                     file_name,
                     start_time: detail.start_time,
                     first_message,
+                    metadata: SessionMetadata::default(),
                 });
             }
         }
@@ -342,7 +346,7 @@ pub fn read_session_detail(project_path: &str, session_id: &str) -> Result<Gemin
 }
 
 /// Helper function to read session detail from a specific file path
-fn read_session_detail_from_path(path: &PathBuf) -> Result<GeminiSessionDetail, String> {
+pub fn read_session_detail_from_path(path: &PathBuf) -> Result<GeminiSessionDetail, String> {
     let content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read session file: {}", e))?;
 
@@ -356,16 +360,24 @@ fn read_session_detail_from_path(path: &PathBuf) -> Result<GeminiSessionDetail,
 
 /// Get session logs for a project
 #[tauri::command]
-pub async fn get_gemini_session_logs(project_path: String) -> Result<Vec<GeminiSessionLog>, String> {
-    read_session_logs(&project_path)
+pub async fn get_gemini_session_logs(project_path: String) -> Result<Vec<GeminiSessionLog>, AnyCodeError> {
+    read_session_logs(&project_path).map_err(AnyCodeError::Io)
 }
 
 /// List all sessions for a project
+/// `filter` selects by tag / pinned / archived state (archived sessions are hidden by default)
 #[tauri::command]
-pub async fn list_gemini_sessions(project_path: String) -> Result<Vec<GeminiSessionInfo>, String> {
-    async_runtime::spawn_blocking(move || list_session_files(&project_path))
-        .await
-        .map_err(|e| format!("Failed to load Gemini sessions: {}", e))?
+pub async fn list_gemini_sessions(
+    project_path: String,
+    filter: Option<SessionFilter>,
+) -> Result<Vec<GeminiSessionInfo>, AnyCodeError> {
+    async_runtime::spawn_blocking(move || {
+        let mut sessions = list_session_files(&project_path).map_err(AnyCodeError::Io)?;
+        attach_session_metadata("gemini", &mut sessions, filter.as_ref());
+        Ok(sessions)
+    })
+    .await
+    .map_err(|e| AnyCodeError::Internal(format!("Failed to load Gemini sessions: {}", e)))?
 }
 
 /// Get detailed session information
@@ -373,8 +385,8 @@ pub async fn list_gemini_sessions(project_path: String) -> Result<Vec<GeminiSess
 pub async fn get_gemini_session_detail(
     project_path: String,
     session_id: String,
-) -> Result<GeminiSessionDetail, String> {
-    read_session_detail(&project_path, &session_id)
+) -> Result<GeminiSessionDetail, AnyCodeError> {
+    read_session_detail(&project_path, &session_id).map_err(AnyCodeError::Io)
 }
 
 /// Delete a Gemini session
@@ -382,8 +394,8 @@ pub async fn get_gemini_session_detail(
 pub async fn delete_gemini_session(
     project_path: String,
     session_id: String,
-) -> Result<(), String> {
-    delete_session(&project_path, &session_id)
+) -> Result<(), AnyCodeError> {
+    delete_session(&project_path, &session_id).map_err(AnyCodeError::Io)
 }
 
 // ============================================================================
@@ -392,10 +404,10 @@ pub async fn delete_gemini_session(
 
 /// Reads the GEMINI.md system prompt file from ~/.gemini directory
 #[tauri::command]
-pub async fn get_gemini_system_prompt() -> Result<String, String> {
+pub async fn get_gemini_system_prompt() -> Result<String, AnyCodeError> {
     log::info!("Reading GEMINI.md system prompt");
 
-    let gemini_dir = get_gemini_dir()?;
+    let gemini_dir = get_gemini_dir().map_err(AnyCodeError::Io)?;
     let gemini_md_path = gemini_dir.join("GEMINI.md");
 
     if !gemini_md_path.exists() {
@@ -406,20 +418,20 @@ pub async fn get_gemini_system_prompt() -> Result<String, String> {
     fs::read_to_string(&gemini_md_path).map_err(|e| {
         log::error!("Failed to read GEMINI.md: {}", e);
         format!("读取 GEMINI.md 失败: {}", e)
-    })
+    }).map_err(AnyCodeError::Io)
 }
 
 /// Saves the GEMINI.md system prompt file to ~/.gemini directory
 #[tauri::command]
-pub async fn save_gemini_system_prompt(content: String) -> Result<String, String> {
+pub async fn save_gemini_system_prompt(content: String) -> Result<String, AnyCodeError> {
     log::info!("Saving GEMINI.md system prompt");
 
-    let gemini_dir = get_gemini_dir()?;
+    let gemini_dir = get_gemini_dir().map_err(AnyCodeError::Io)?;
 
     // Ensure directory exists
     if !gemini_dir.exists() {
         fs::create_dir_all(&gemini_dir).map_err(|e| {
-            format!("创建 ~/.gemini 目录失败: {}", e)
+            AnyCodeError::Io(format!("创建 ~/.gemini 目录失败: {}", e))
         })?;
     }
 
@@ -427,12 +439,371 @@ pub async fn save_gemini_system_prompt(content: String) -> Result<String, String
 
     fs::write(&gemini_md_path, content).map_err(|e| {
         log::error!("Failed to write GEMINI.md: {}", e);
-        format!("保存 GEMINI.md 失败: {}", e)
+        AnyCodeError::Io(format!("保存 GEMINI.md 失败: {}", e))
     })?;
 
     Ok("Gemini 系统提示词保存成功".to_string())
 }
 
+// ============================================================================
+// Multi-Prompt Management (GEMINI.md templates)
+// ============================================================================
+
+use crate::commands::claude::{
+    find_latest_backup, generate_backup_filename, has_timestamped_backup, ActivationResult, AgentsMdStatus,
+};
+
+/// Gemini prompt template metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiPromptTemplate {
+    /// Unique identifier (filename without extension)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// First markdown heading of the template
+    pub description: Option<String>,
+    /// Whether this template is currently active in ~/.gemini/GEMINI.md
+    pub is_active: bool,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Gemini prompts configuration (~/.gemini/prompts_config.json)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPromptsConfig {
+    /// Currently active prompt template ID
+    active_prompt_id: Option<String>,
+}
+
+/// Gets the prompt templates directory (~/.gemini/prompts), creating it if needed
+fn get_gemini_prompts_dir() -> Result<PathBuf, String> {
+    let prompts_dir = get_gemini_dir()?.join("prompts");
+    if !prompts_dir.exists() {
+        fs::create_dir_all(&prompts_dir).map_err(|e| format!("无法创建提示词目录: {}", e))?;
+    }
+    Ok(prompts_dir)
+}
+
+fn get_gemini_prompt_path(id: &str) -> Result<PathBuf, String> {
+    // Templates added by hand may use other characters; only path components are rejected
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("无效的提示词ID: {}", id));
+    }
+    let prompt_path = get_gemini_prompts_dir()?.join(format!("{}.md", id));
+    if !prompt_path.exists() {
+        return Err(tr!("prompt_template.not_found", id = id));
+    }
+    Ok(prompt_path)
+}
+
+fn load_gemini_prompts_config() -> Result<GeminiPromptsConfig, String> {
+    let config_path = get_gemini_dir()?.join("prompts_config.json");
+    if !config_path.exists() {
+        return Ok(GeminiPromptsConfig::default());
+    }
+    let content = fs::read_to_string(&config_path).map_err(|e| format!("读取提示词配置失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析提示词配置失败: {}", e))
+}
+
+fn save_gemini_prompts_config(config: &GeminiPromptsConfig) -> Result<(), String> {
+    let config_path = get_gemini_dir()?.join("prompts_config.json");
+    let content = serde_json::to_string_pretty(config).map_err(|e| format!("序列化提示词配置失败: {}", e))?;
+    fs::write(&config_path, content).map_err(|e| format!("保存提示词配置失败: {}", e))
+}
+
+/// Template IDs are used as filenames: only alphanumeric, dash and underscore
+fn is_valid_prompt_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Reads all `*.md` templates in `prompts_dir`, newest first
+fn read_prompt_templates(prompts_dir: &std::path::Path, active_id: Option<&str>) -> Vec<GeminiPromptTemplate> {
+    let to_secs = |time: std::io::Result<std::time::SystemTime>| {
+        time.ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    };
+
+    let mut templates: Vec<GeminiPromptTemplate> = fs::read_dir(prompts_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("md"))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_string();
+            let metadata = fs::metadata(&path).ok()?;
+            let description = fs::read_to_string(&path).ok().and_then(|content| {
+                content
+                    .lines()
+                    .next()
+                    .filter(|line| line.starts_with("# ") || line.starts_with("## "))
+                    .map(|line| line.trim_start_matches('#').trim().to_string())
+            });
+            Some(GeminiPromptTemplate {
+                is_active: active_id == Some(stem.as_str()),
+                id: stem.clone(),
+                name: stem,
+                description,
+                created_at: to_secs(metadata.created()),
+                updated_at: to_secs(metadata.modified()),
+            })
+        })
+        .collect();
+    templates.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then_with(|| a.id.cmp(&b.id)));
+    templates
+}
+
+/// Writes `content` to ~/.gemini/GEMINI.md
+fn write_global_gemini_md(content: &str) -> Result<(), String> {
+    let gemini_md_path = get_gemini_dir()?.join("GEMINI.md");
+    fs::write(&gemini_md_path, content).map_err(|e| format!("写入 GEMINI.md 失败: {}", e))
+}
+
+/// Validates and returns a project directory
+fn get_project_dir(project_path: &str) -> Result<PathBuf, String> {
+    if project_path.trim().is_empty() {
+        return Err("项目路径不能为空".to_string());
+    }
+    let project_dir = PathBuf::from(project_path);
+    if !project_dir.exists() {
+        return Err(tr!("project.path_not_found", path = project_path));
+    }
+    if !project_dir.is_dir() {
+        return Err(format!("项目路径不是目录: {}", project_path));
+    }
+    Ok(project_dir)
+}
+
+/// Lists all Gemini prompt templates
+#[tauri::command]
+pub async fn list_gemini_prompts() -> Result<Vec<GeminiPromptTemplate>, AnyCodeError> {
+    let prompts_dir = get_gemini_prompts_dir().map_err(AnyCodeError::Io)?;
+    let config = load_gemini_prompts_config().map_err(AnyCodeError::Config)?;
+    let templates = read_prompt_templates(&prompts_dir, config.active_prompt_id.as_deref());
+    log::info!("Found {} Gemini prompt templates", templates.len());
+    Ok(templates)
+}
+
+/// Gets a specific Gemini prompt template content
+#[tauri::command]
+pub async fn get_gemini_prompt(id: String) -> Result<String, AnyCodeError> {
+    let prompt_path = get_gemini_prompt_path(&id).map_err(AnyCodeError::Io)?;
+    fs::read_to_string(&prompt_path).map_err(|e| AnyCodeError::Io(format!("读取提示词模板失败: {}", e)))
+}
+
+/// Creates or updates a Gemini prompt template
+#[tauri::command]
+pub async fn save_gemini_prompt(id: String, content: String) -> Result<String, AnyCodeError> {
+    if !is_valid_prompt_id(&id) {
+        return Err(AnyCodeError::Validation("提示词ID只能包含字母、数字、横线和下划线".to_string()));
+    }
+    let prompt_path = get_gemini_prompts_dir().map_err(AnyCodeError::Io)?.join(format!("{}.md", id));
+    fs::write(&prompt_path, &content).map_err(|e| AnyCodeError::Io(format!("保存提示词模板失败: {}", e)))?;
+
+    // Keep GEMINI.md in sync when editing the active template
+    if load_gemini_prompts_config().map_err(AnyCodeError::Config)?.active_prompt_id.as_deref() == Some(id.as_str()) {
+        write_global_gemini_md(&content).map_err(AnyCodeError::Io)?;
+    }
+
+    log::info!("Saved Gemini prompt template: {}", id);
+    Ok(format!("提示词模板 '{}' 保存成功", id))
+}
+
+/// Renames a Gemini prompt template (changes the template ID / filename)
+#[tauri::command]
+pub async fn rename_gemini_prompt(old_id: String, new_id: String) -> Result<String, AnyCodeError> {
+    let old_id = old_id.trim().to_string();
+    let new_id = new_id.trim().to_string();
+
+    if old_id.is_empty() || new_id.is_empty() {
+        return Err(AnyCodeError::Validation("提示词名称不能为空".to_string()));
+    }
+    if old_id == new_id {
+        return Ok(format!("提示词模板 '{}' 名称未变更", old_id));
+    }
+    if !is_valid_prompt_id(&new_id) {
+        return Err(AnyCodeError::Validation("提示词名称只能包含字母、数字、横线和下划线".to_string()));
+    }
+
+    let old_path = get_gemini_prompt_path(&old_id).map_err(AnyCodeError::Io)?;
+    let prompts_dir = get_gemini_prompts_dir().map_err(AnyCodeError::Io)?;
+    let new_path = prompts_dir.join(format!("{}.md", new_id));
+    let case_only = old_id.eq_ignore_ascii_case(&new_id);
+
+    // On case-insensitive file systems a case-only rename "exists" as the same file
+    if new_path.exists() {
+        let same_file = matches!(
+            (fs::canonicalize(&old_path), fs::canonicalize(&new_path)),
+            (Ok(a), Ok(b)) if a == b
+        );
+        if !(case_only && same_file) {
+            return Err(AnyCodeError::Validation(format!("目标名称已存在: {}", new_id)));
+        }
+    }
+
+    if case_only {
+        let temp_path = prompts_dir.join(format!("__rename_tmp_{}.md", uuid::Uuid::new_v4()));
+        fs::rename(&old_path, &temp_path)
+            .and_then(|_| fs::rename(&temp_path, &new_path))
+            .map_err(|e| AnyCodeError::Io(format!("重命名提示词模板失败: {}", e)))?;
+    } else {
+        fs::rename(&old_path, &new_path).map_err(|e| AnyCodeError::Io(format!("重命名提示词模板失败: {}", e)))?;
+    }
+
+    let mut config = load_gemini_prompts_config().map_err(AnyCodeError::Config)?;
+    if config.active_prompt_id.as_deref() == Some(old_id.as_str()) {
+        config.active_prompt_id = Some(new_id.clone());
+        save_gemini_prompts_config(&config).map_err(AnyCodeError::Io)?;
+    }
+
+    log::info!("Renamed Gemini prompt template: {} -> {}", old_id, new_id);
+    Ok(format!("提示词模板 '{}' 已重命名为 '{}'", old_id, new_id))
+}
+
+/// Deletes a Gemini prompt template (clears GEMINI.md if it was active)
+#[tauri::command]
+pub async fn delete_gemini_prompt(id: String) -> Result<String, AnyCodeError> {
+    let prompt_path = get_gemini_prompt_path(&id).map_err(AnyCodeError::Io)?;
+
+    let mut config = load_gemini_prompts_config().map_err(AnyCodeError::Config)?;
+    if config.active_prompt_id.as_deref() == Some(id.as_str()) {
+        config.active_prompt_id = None;
+        save_gemini_prompts_config(&config).map_err(AnyCodeError::Io)?;
+        write_global_gemini_md("").map_err(AnyCodeError::Io)?;
+    }
+
+    fs::remove_file(&prompt_path).map_err(|e| AnyCodeError::Io(format!("删除提示词模板失败: {}", e)))?;
+
+    log::info!("Deleted Gemini prompt template: {}", id);
+    Ok(format!("提示词模板 '{}' 删除成功", id))
+}
+
+/// Activates a Gemini prompt template (copies it to ~/.gemini/GEMINI.md)
+#[tauri::command]
+pub async fn activate_gemini_prompt(id: String) -> Result<String, AnyCodeError> {
+    let prompt_path = get_gemini_prompt_path(&id).map_err(AnyCodeError::Io)?;
+    let content = fs::read_to_string(&prompt_path).map_err(|e| AnyCodeError::Io(format!("读取提示词模板失败: {}", e)))?;
+    write_global_gemini_md(&content).map_err(AnyCodeError::Io)?;
+
+    let mut config = load_gemini_prompts_config().map_err(AnyCodeError::Config)?;
+    config.active_prompt_id = Some(id.clone());
+    save_gemini_prompts_config(&config).map_err(AnyCodeError::Io)?;
+
+    log::info!("Activated Gemini prompt template: {}", id);
+    Ok(format!("提示词模板 '{}' 已激活", id))
+}
+
+/// Deactivates the current Gemini prompt (clears ~/.gemini/GEMINI.md)
+#[tauri::command]
+pub async fn deactivate_gemini_prompt() -> Result<String, AnyCodeError> {
+    if get_gemini_dir().map_err(AnyCodeError::Io)?.join("GEMINI.md").exists() {
+        write_global_gemini_md("").map_err(AnyCodeError::Io)?;
+    }
+
+    let mut config = load_gemini_prompts_config().map_err(AnyCodeError::Config)?;
+    config.active_prompt_id = None;
+    save_gemini_prompts_config(&config).map_err(AnyCodeError::Io)?;
+
+    log::info!("Deactivated Gemini prompt");
+    Ok("已停用当前提示词".to_string())
+}
+
+/// Gets the currently active Gemini prompt ID
+#[tauri::command]
+pub async fn get_active_gemini_prompt_id() -> Result<Option<String>, AnyCodeError> {
+    Ok(load_gemini_prompts_config().map_err(AnyCodeError::Config)?.active_prompt_id)
+}
+
+// ============================================================================
+// Project-Level GEMINI.md Management
+// ============================================================================
+
+/// Check if GEMINI.md exists in the project directory
+#[tauri::command]
+pub async fn check_project_gemini_md(project_path: String) -> Result<AgentsMdStatus, AnyCodeError> {
+    let project_dir = get_project_dir(&project_path).map_err(AnyCodeError::Io)?;
+    let gemini_md_path = project_dir.join("GEMINI.md");
+
+    let exists = gemini_md_path.exists();
+    let has_backup =
+        project_dir.join("GEMINI.md.backup").exists() || has_timestamped_backup(&project_dir, "GEMINI.md");
+    let content_preview = if exists {
+        fs::read_to_string(&gemini_md_path).ok().map(|content| {
+            let preview: String = content.chars().take(200).collect();
+            if content.len() > 200 {
+                format!("{}...", preview)
+            } else {
+                preview
+            }
+        })
+    } else {
+        None
+    };
+
+    Ok(AgentsMdStatus { exists, has_backup, content_preview })
+}
+
+/// Activate a Gemini prompt template to a project directory (writes <project>/GEMINI.md)
+#[tauri::command]
+pub async fn activate_gemini_prompt_to_project(
+    id: String,
+    project_path: String,
+    backup_existing: bool,
+) -> Result<ActivationResult, AnyCodeError> {
+    log::info!("Activating Gemini prompt '{}' to project: {}", id, project_path);
+
+    let project_dir = get_project_dir(&project_path).map_err(AnyCodeError::Io)?;
+    let prompt_path = get_gemini_prompt_path(&id).map_err(AnyCodeError::Io)?;
+    let content = fs::read_to_string(&prompt_path).map_err(|e| AnyCodeError::Io(format!("读取提示词模板失败: {}", e)))?;
+
+    let gemini_md_path = project_dir.join("GEMINI.md");
+    let mut backup_path_result: Option<String> = None;
+    if backup_existing && gemini_md_path.exists() {
+        let backup_path = project_dir.join(generate_backup_filename(&project_dir, "GEMINI.md"));
+        fs::copy(&gemini_md_path, &backup_path).map_err(|e| AnyCodeError::Io(format!("备份文件失败: {}", e)))?;
+        log::info!("Created backup at: {:?}", backup_path);
+        backup_path_result = Some(backup_path.to_string_lossy().to_string());
+    }
+
+    fs::write(&gemini_md_path, &content).map_err(|e| AnyCodeError::Io(format!("写入 GEMINI.md 失败: {}", e)))?;
+
+    let message = match &backup_path_result {
+        Some(backup) => format!("提示词已激活到项目，原文件已备份到: {}", backup),
+        None => "提示词已激活到项目".to_string(),
+    };
+    Ok(ActivationResult { success: true, message, backup_path: backup_path_result })
+}
+
+/// Deactivate Gemini prompt from a project directory (restore the latest backup or clear GEMINI.md)
+#[tauri::command]
+pub async fn deactivate_gemini_prompt_from_project(
+    project_path: String,
+    restore_backup: bool,
+) -> Result<String, AnyCodeError> {
+    log::info!("Deactivating Gemini prompt from project: {}, restore_backup: {}", project_path, restore_backup);
+
+    let project_dir = get_project_dir(&project_path).map_err(AnyCodeError::Io)?;
+    let gemini_md_path = project_dir.join("GEMINI.md");
+
+    if restore_backup {
+        let backup_path = find_latest_backup(&project_dir, "GEMINI.md").ok_or_else(|| AnyCodeError::NotFound("未找到备份文件".to_string()))?;
+        let backup_content = fs::read_to_string(&backup_path).map_err(|e| AnyCodeError::Io(format!("读取备份文件失败: {}", e)))?;
+        fs::write(&gemini_md_path, &backup_content).map_err(|e| AnyCodeError::Io(format!("恢复备份失败: {}", e)))?;
+        let _ = fs::remove_file(&backup_path);
+        log::info!("Restored backup from: {:?}", backup_path);
+        return Ok("已恢复备份文件".to_string());
+    }
+
+    if gemini_md_path.exists() {
+        fs::write(&gemini_md_path, "").map_err(|e| AnyCodeError::Io(format!("清空 GEMINI.md 失败: {}", e)))?;
+    }
+    Ok("已清空 GEMINI.md".to_string())
+}
+
 /// Delete a session file by session_id
 pub fn delete_session(project_path: &str, session_id: &str) -> Result<(), String> {
     let session_dir = get_project_session_dir(project_path)?;
@@ -464,3 +835,42 @@ pub fn delete_session(project_path: &str, session_id: &str) -> Result<(), String
 
     Err(format!("Session {} not found", session_id))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_templates_and_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("review.md"), "# Code review rules\n\nBe strict.").unwrap();
+        fs::write(dir.path().join("plain.md"), "no heading").unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let mut templates = read_prompt_templates(dir.path(), Some("review"));
+        templates.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(templates.len(), 2);
+        assert_eq!(templates[0].id, "plain");
+        assert!(!templates[0].is_active && templates[0].description.is_none());
+        assert_eq!(templates[1].description.as_deref(), Some("Code review rules"));
+        assert!(templates[1].is_active);
+
+        assert!(is_valid_prompt_id("team-rules_v2"));
+        assert!(!is_valid_prompt_id("../escape") && !is_valid_prompt_id(""));
+
+        assert_eq!(generate_backup_filename(dir.path(), "GEMINI.md"), "GEMINI.md.backup");
+        assert!(find_latest_backup(dir.path(), "GEMINI.md").is_none());
+        fs::write(dir.path().join("GEMINI.md.backup"), "old").unwrap();
+        assert!(generate_backup_filename(dir.path(), "GEMINI.md").starts_with("GEMINI.md.backup."));
+        assert_eq!(find_latest_backup(dir.path(), "GEMINI.md"), Some(dir.path().join("GEMINI.md.backup")));
+        assert!(!has_timestamped_backup(dir.path(), "GEMINI.md"));
+    }
+
+    #[test]
+    fn prompt_path_rejects_path_components() {
+        for id in ["", "../settings", "..", "a/b", "a\\b", "/etc/passwd"] {
+            let err = get_gemini_prompt_path(id).unwrap_err();
+            assert!(err.contains("无效的提示词ID"), "{}: {}", id, err);
+        }
+    }
+}
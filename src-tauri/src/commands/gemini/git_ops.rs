@@ -8,15 +8,16 @@
  * - Session truncation and revert operations
  */
 
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use chrono::Utc;
 
 // Import simple_git for rewind operations
 use super::super::simple_git;
 // Import rewind helpers/types shared with Claude
-use super::super::prompt_tracker::{RewindMode, RewindCapabilities, PromptRecord as ClaudePromptRecord, load_execution_config};
+use super::super::prompt_tracker::{RewindMode, RewindCapabilities, RewindStrategy, PromptRecord as ClaudePromptRecord, load_execution_config};
 // Import Gemini config helpers
 use super::config::get_gemini_dir;
 
@@ -75,18 +76,28 @@ pub fn get_gemini_sessions_dir(project_path: &str) -> Result<PathBuf, String> {
     Ok(gemini_dir.join("tmp").join(project_hash).join("chats"))
 }
 
+/// First 8 characters of a session ID, as used in Gemini session file names
+fn session_file_prefix(session_id: &str) -> String {
+    session_id.chars().take(8).collect()
+}
+
+/// File name for a forked session: the original name with the session ID prefix swapped
+fn forked_file_name(file_name: &str, session_id: &str, new_session_id: &str) -> String {
+    let prefix = session_file_prefix(session_id);
+    if prefix.is_empty() {
+        return file_name.to_string();
+    }
+    file_name.replace(&prefix, &session_file_prefix(new_session_id))
+}
+
 /// Find Gemini session file by session ID
 /// Gemini CLI stores session files with format: session-<date>-<session_id_prefix>.json
 /// where session_id_prefix is the first 8 characters of the full UUID
 /// This function searches by prefix and verifies by reading the internal sessionId field
-fn find_gemini_session_file(sessions_dir: &PathBuf, session_id: &str) -> Result<PathBuf, String> {
+pub fn find_gemini_session_file(sessions_dir: &Path, session_id: &str) -> Result<PathBuf, String> {
     // Extract the first 8 characters of session_id for filename matching
     // Gemini CLI uses this prefix in the filename
-    let session_prefix = if session_id.len() >= 8 {
-        &session_id[..8]
-    } else {
-        session_id
-    };
+    let session_prefix = session_file_prefix(session_id);
 
     log::debug!("[Gemini] Searching for session file with prefix: {} in {:?}", session_prefix, sessions_dir);
 
@@ -100,7 +111,7 @@ fn find_gemini_session_file(sessions_dir: &PathBuf, session_id: &str) -> Result<
         if path.is_file() {
             if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
                 // Check if filename contains the session_id prefix
-                if filename.contains(session_prefix) {
+                if filename.contains(&session_prefix) {
                     candidates.push(path);
                 }
             }
@@ -132,7 +143,10 @@ fn find_gemini_session_file(sessions_dir: &PathBuf, session_id: &str) -> Result<
 
 /// Load Git records for a Gemini session
 pub fn load_gemini_git_records(session_id: &str) -> Result<GeminiGitRecords, String> {
-    let records_dir = get_gemini_git_records_dir()?;
+    load_gemini_git_records_in(&get_gemini_git_records_dir()?, session_id)
+}
+
+fn load_gemini_git_records_in(records_dir: &Path, session_id: &str) -> Result<GeminiGitRecords, String> {
     let records_file = records_dir.join(format!("{}.json", session_id));
 
     if !records_file.exists() {
@@ -152,7 +166,10 @@ pub fn load_gemini_git_records(session_id: &str) -> Result<GeminiGitRecords, Str
 
 /// Save Git records for a Gemini session
 pub fn save_gemini_git_records(session_id: &str, records: &GeminiGitRecords) -> Result<(), String> {
-    let records_dir = get_gemini_git_records_dir()?;
+    save_gemini_git_records_in(&get_gemini_git_records_dir()?, session_id, records)
+}
+
+fn save_gemini_git_records_in(records_dir: &Path, session_id: &str, records: &GeminiGitRecords) -> Result<(), String> {
     let records_file = records_dir.join(format!("{}.json", session_id));
 
     let content = serde_json::to_string_pretty(records)
@@ -275,8 +292,8 @@ fn extract_gemini_prompts(session_id: &str, project_path: &str) -> Result<Vec<Pr
 
 /// Get prompt list for Gemini sessions (for revert picker)
 #[tauri::command]
-pub async fn get_gemini_prompt_list(session_id: String, project_path: String) -> Result<Vec<PromptRecord>, String> {
-    extract_gemini_prompts(&session_id, &project_path)
+pub async fn get_gemini_prompt_list(session_id: String, project_path: String) -> Result<Vec<PromptRecord>, AnyCodeError> {
+    extract_gemini_prompts(&session_id, &project_path).map_err(AnyCodeError::Io)
 }
 
 // ============================================================================
@@ -289,7 +306,7 @@ pub async fn check_gemini_rewind_capabilities(
     session_id: String,
     project_path: String,
     prompt_index: usize,
-) -> Result<RewindCapabilities, String> {
+) -> Result<RewindCapabilities, AnyCodeError> {
     log::info!(
         "[Gemini Rewind] Checking capabilities for session {} prompt #{}",
         session_id,
@@ -298,14 +315,14 @@ pub async fn check_gemini_rewind_capabilities(
 
     // Respect global execution config for git operations
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
     let git_operations_disabled = execution_config.disable_rewind_git_operations;
 
     // Extract prompts to validate index
-    let prompts = extract_gemini_prompts(&session_id, &project_path)?;
+    let prompts = extract_gemini_prompts(&session_id, &project_path).map_err(AnyCodeError::Io)?;
     let prompt = prompts
         .get(prompt_index)
-        .ok_or_else(|| format!("Prompt #{} not found", prompt_index))?;
+        .ok_or_else(|| AnyCodeError::NotFound(format!("Prompt #{} not found", prompt_index)))?;
 
     if git_operations_disabled {
         return Ok(RewindCapabilities {
@@ -314,11 +331,13 @@ pub async fn check_gemini_rewind_capabilities(
             both: false,
             warning: Some("Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string()),
             source: prompt.source.clone(),
+            mixed: false,
+            strategy: None,
         });
     }
 
     // Look up git record for this prompt index
-    let git_records = load_gemini_git_records(&session_id)?;
+    let git_records = load_gemini_git_records(&session_id).map_err(AnyCodeError::Io)?;
     let git_record = git_records
         .records
         .iter()
@@ -343,6 +362,8 @@ pub async fn check_gemini_rewind_capabilities(
                 None
             },
             source: "project".to_string(),
+            mixed: false,
+            strategy: has_valid_commit.then_some(RewindStrategy::Git),
         })
     } else {
         log::warn!("[Gemini Rewind] ⚠️ No git record found for prompt #{}", prompt_index);
@@ -352,6 +373,8 @@ pub async fn check_gemini_rewind_capabilities(
             both: false,
             warning: Some("此提示词没有关联的 Git 记录，只能删除消息".to_string()),
             source: "project".to_string(),
+            mixed: false,
+            strategy: None,
         })
     }
 }
@@ -366,17 +389,17 @@ pub async fn record_gemini_prompt_sent(
     session_id: String,
     project_path: String,
     _prompt_text: String,
-) -> Result<usize, String> {
+) -> Result<usize, AnyCodeError> {
     log::info!("[Gemini Record] Recording prompt sent for session: {}", session_id);
 
     // Check if Git operations are disabled in config
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
 
     if execution_config.disable_rewind_git_operations {
         log::info!("[Gemini Record] Git operations disabled, skipping git record");
         // Still need to return a prompt_index for tracking purposes
-        let git_records = load_gemini_git_records(&session_id)?;
+        let git_records = load_gemini_git_records(&session_id).map_err(AnyCodeError::Io)?;
         let prompt_index = git_records.records.len();
         log::info!("[Gemini Record] Returning prompt index #{} (no git record)", prompt_index);
         return Ok(prompt_index);
@@ -384,14 +407,14 @@ pub async fn record_gemini_prompt_sent(
 
     // Ensure Git repository is initialized
     simple_git::ensure_git_repo(&project_path)
-        .map_err(|e| format!("Failed to ensure Git repo: {}", e))?;
+        .map_err(|e| AnyCodeError::Git(format!("Failed to ensure Git repo: {}", e)))?;
 
     // Get current commit (state before prompt execution)
     let commit_before = simple_git::git_current_commit(&project_path)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+        .map_err(|e| AnyCodeError::Git(format!("Failed to get current commit: {}", e)))?;
 
     // Load existing records
-    let mut git_records = load_gemini_git_records(&session_id)?;
+    let mut git_records = load_gemini_git_records(&session_id).map_err(AnyCodeError::Io)?;
 
     // Update project path if needed
     if git_records.project_path.is_empty() {
@@ -410,7 +433,7 @@ pub async fn record_gemini_prompt_sent(
     };
 
     git_records.records.push(record);
-    save_gemini_git_records(&session_id, &git_records)?;
+    save_gemini_git_records(&session_id, &git_records).map_err(AnyCodeError::Io)?;
 
     log::info!("[Gemini Record] Recorded prompt #{} with commit_before: {}",
         prompt_index, &commit_before[..8.min(commit_before.len())]);
@@ -421,16 +444,17 @@ pub async fn record_gemini_prompt_sent(
 /// Record a Gemini prompt completion (called after AI response)
 #[tauri::command]
 pub async fn record_gemini_prompt_completed(
+    app_handle: tauri::AppHandle,
     session_id: String,
     project_path: String,
     prompt_index: usize,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     log::info!("[Gemini Record] Recording prompt #{} completed for session: {}",
         prompt_index, session_id);
 
     // Check if Git operations are disabled in config
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
 
     if execution_config.disable_rewind_git_operations {
         log::info!("[Gemini Record] Git operations disabled, skipping git commit and record update");
@@ -438,7 +462,8 @@ pub async fn record_gemini_prompt_completed(
     }
 
     // Auto-commit any changes made by AI
-    let commit_message = format!("[Gemini] After prompt #{}", prompt_index);
+    let commit_message =
+        super::super::commit_message::auto_commit_message(&app_handle, &project_path, "gemini", prompt_index).await;
     match simple_git::git_commit_changes(&project_path, &commit_message) {
         Ok(true) => {
             log::info!("[Gemini Record] Auto-committed changes after prompt #{}", prompt_index);
@@ -454,14 +479,14 @@ pub async fn record_gemini_prompt_completed(
 
     // Get current commit (state after AI completion)
     let commit_after = simple_git::git_current_commit(&project_path)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+        .map_err(|e| AnyCodeError::Git(format!("Failed to get current commit: {}", e)))?;
 
     // Update the record
-    let mut git_records = load_gemini_git_records(&session_id)?;
+    let mut git_records = load_gemini_git_records(&session_id).map_err(AnyCodeError::Io)?;
 
     if let Some(record) = git_records.records.iter_mut().find(|r| r.prompt_index == prompt_index) {
         record.commit_after = Some(commit_after.clone());
-        save_gemini_git_records(&session_id, &git_records)?;
+        save_gemini_git_records(&session_id, &git_records).map_err(AnyCodeError::Io)?;
 
         log::info!("[Gemini Record] Updated prompt #{} with commit_after: {}",
             prompt_index, &commit_after[..8.min(commit_after.len())]);
@@ -495,9 +520,12 @@ pub fn truncate_gemini_session_to_prompt(
 
     // Find session file using helper function (handles Gemini's 8-char prefix naming)
     let session_file = find_gemini_session_file(&sessions_dir, session_id)?;
+    truncate_session_file(&session_file, prompt_index)
+}
 
+fn truncate_session_file(session_file: &Path, prompt_index: usize) -> Result<(), String> {
     // Read session JSON
-    let content = fs::read_to_string(&session_file)
+    let content = fs::read_to_string(session_file)
         .map_err(|e| format!("Failed to read session file: {}", e))?;
 
     let mut session_data: serde_json::Value = serde_json::from_str(&content)
@@ -540,13 +568,67 @@ pub fn truncate_gemini_session_to_prompt(
     let new_content = serde_json::to_string_pretty(&session_data)
         .map_err(|e| format!("Failed to serialize session: {}", e))?;
 
-    fs::write(&session_file, new_content)
+    fs::write(session_file, new_content)
         .map_err(|e| format!("Failed to write session file: {}", e))?;
 
     log::info!("[Gemini Rewind] Truncated session to before prompt #{}", prompt_index);
     Ok(())
 }
 
+/// Fork a Gemini session: copy its history before `prompt_index` into a new session file
+/// Git records up to the fork point are copied as well. Returns the new session ID.
+pub fn fork_gemini_session(
+    session_id: &str,
+    project_path: &str,
+    prompt_index: usize,
+) -> Result<String, String> {
+    let sessions_dir = get_gemini_sessions_dir(project_path)?;
+    let new_session_id = fork_session_file(&sessions_dir, &get_gemini_git_records_dir()?, session_id, prompt_index)?;
+    log::info!("[Gemini Fork] Forked session {} at prompt #{} -> {}", session_id, prompt_index, new_session_id);
+    Ok(new_session_id)
+}
+
+/// Copies the session file and git records up to the fork point; returns the new session ID
+fn fork_session_file(
+    sessions_dir: &Path,
+    records_dir: &Path,
+    session_id: &str,
+    prompt_index: usize,
+) -> Result<String, String> {
+    let session_file = find_gemini_session_file(sessions_dir, session_id)?;
+    let content = fs::read_to_string(&session_file)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let mut session_data: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse session JSON: {}", e))?;
+
+    let new_session_id = uuid::Uuid::new_v4().to_string();
+    session_data["sessionId"] = serde_json::Value::String(new_session_id.clone());
+
+    let file_name = session_file.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let new_file = sessions_dir.join(forked_file_name(file_name, session_id, &new_session_id));
+    if new_file == session_file {
+        return Err("Failed to derive a file name for the forked session".to_string());
+    }
+    let new_content = serde_json::to_string_pretty(&session_data)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    fs::write(&new_file, new_content)
+        .map_err(|e| format!("Failed to write forked session: {}", e))?;
+    if let Err(e) = truncate_session_file(&new_file, prompt_index) {
+        let _ = fs::remove_file(&new_file);
+        return Err(e);
+    }
+
+    // Like Claude and Codex, the fork point's own record is kept (its commit is where the fork starts)
+    let mut git_records = load_gemini_git_records_in(records_dir, session_id)?;
+    if !git_records.records.is_empty() {
+        git_records.session_id = new_session_id.clone();
+        git_records.records.retain(|r| r.prompt_index <= prompt_index);
+        save_gemini_git_records_in(records_dir, &new_session_id, &git_records)?;
+    }
+
+    Ok(new_session_id)
+}
+
 // ============================================================================
 // Revert Operations
 // ============================================================================
@@ -558,13 +640,13 @@ pub async fn revert_gemini_to_prompt(
     project_path: String,
     prompt_index: usize,
     mode: RewindMode,
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     log::info!("[Gemini Rewind] Reverting session {} to prompt #{} with mode: {:?}",
         session_id, prompt_index, mode);
 
     // Load execution config to check if Git operations are disabled
     let execution_config = load_execution_config()
-        .map_err(|e| format!("Failed to load execution config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to load execution config: {}", e)))?;
 
     let git_operations_disabled = execution_config.disable_rewind_git_operations;
 
@@ -573,28 +655,28 @@ pub async fn revert_gemini_to_prompt(
     }
 
     // Extract prompts to validate index and get the prompt text for return
-    let prompts = extract_gemini_prompts(&session_id, &project_path)?;
+    let prompts = extract_gemini_prompts(&session_id, &project_path).map_err(AnyCodeError::Io)?;
     let prompt = prompts
         .get(prompt_index)
-        .ok_or_else(|| format!("Prompt #{} not found in session", prompt_index))?;
+        .ok_or_else(|| AnyCodeError::NotFound(format!("Prompt #{} not found in session", prompt_index)))?;
 
     // Load Git records
-    let git_records = load_gemini_git_records(&session_id)?;
+    let git_records = load_gemini_git_records(&session_id).map_err(AnyCodeError::Io)?;
     let git_record = git_records.records.iter().find(|r| r.prompt_index == prompt_index);
 
     // Validate mode compatibility
     match mode {
         RewindMode::CodeOnly | RewindMode::Both => {
             if git_operations_disabled {
-                return Err(
-                    "无法回滚代码：Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".into()
-                );
+                return Err(AnyCodeError::Config(
+                    "无法回滚代码：Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string(),
+                ));
             }
             if git_record.is_none() {
-                return Err(format!(
+                return Err(AnyCodeError::Git(format!(
                     "无法回滚代码：提示词 #{} 没有关联的 Git 记录",
                     prompt_index
-                ));
+                )));
             }
         }
         RewindMode::ConversationOnly => {}
@@ -606,11 +688,11 @@ pub async fn revert_gemini_to_prompt(
             log::info!("[Gemini Rewind] Reverting conversation only");
 
             // Truncate session messages
-            truncate_gemini_session_to_prompt(&session_id, &project_path, prompt_index)?;
+            truncate_gemini_session_to_prompt(&session_id, &project_path, prompt_index).map_err(AnyCodeError::Io)?;
 
             // Truncate git records
             if !git_operations_disabled {
-                truncate_gemini_git_records(&session_id, prompt_index)?;
+                truncate_gemini_git_records(&session_id, prompt_index).map_err(AnyCodeError::Io)?;
             }
 
             log::info!("[Gemini Rewind] Successfully reverted conversation to prompt #{}", prompt_index);
@@ -624,11 +706,11 @@ pub async fn revert_gemini_to_prompt(
             // Stash uncommitted changes
             simple_git::git_stash_save(&project_path,
                 &format!("Auto-stash before Gemini code revert to prompt #{}", prompt_index))
-                .map_err(|e| format!("Failed to stash changes: {}", e))?;
+                .map_err(|e| AnyCodeError::Git(format!("Failed to stash changes: {}", e)))?;
 
             // Reset to commit before this prompt
             simple_git::git_reset_hard(&project_path, &record.commit_before)
-                .map_err(|e| format!("Failed to reset code: {}", e))?;
+                .map_err(|e| AnyCodeError::Git(format!("Failed to reset code: {}", e)))?;
 
             log::info!("[Gemini Rewind] Successfully reverted code to prompt #{}", prompt_index);
         }
@@ -641,18 +723,18 @@ pub async fn revert_gemini_to_prompt(
             // Stash uncommitted changes
             simple_git::git_stash_save(&project_path,
                 &format!("Auto-stash before Gemini full revert to prompt #{}", prompt_index))
-                .map_err(|e| format!("Failed to stash changes: {}", e))?;
+                .map_err(|e| AnyCodeError::Git(format!("Failed to stash changes: {}", e)))?;
 
             // Reset code
             simple_git::git_reset_hard(&project_path, &record.commit_before)
-                .map_err(|e| format!("Failed to reset code: {}", e))?;
+                .map_err(|e| AnyCodeError::Git(format!("Failed to reset code: {}", e)))?;
 
             // Truncate session
-            truncate_gemini_session_to_prompt(&session_id, &project_path, prompt_index)?;
+            truncate_gemini_session_to_prompt(&session_id, &project_path, prompt_index).map_err(AnyCodeError::Io)?;
 
             // Truncate git records
             if !git_operations_disabled {
-                truncate_gemini_git_records(&session_id, prompt_index)?;
+                truncate_gemini_git_records(&session_id, prompt_index).map_err(AnyCodeError::Io)?;
             }
 
             log::info!("[Gemini Rewind] Successfully reverted both to prompt #{}", prompt_index);
@@ -662,3 +744,89 @@ pub async fn revert_gemini_to_prompt(
     // Return the prompt text for restoring to input (same as Claude's behavior)
     Ok(prompt.text.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_file_prefix_handles_short_and_multibyte_ids() {
+        assert_eq!(session_file_prefix("0a1b2c3d-4e5f-6789"), "0a1b2c3d");
+        assert_eq!(session_file_prefix("abc"), "abc");
+        assert_eq!(session_file_prefix("会话标识符一二三四五"), "会话标识符一二三");
+        assert_eq!(session_file_prefix(""), "");
+    }
+
+    #[test]
+    fn forked_file_name_swaps_the_session_prefix() {
+        assert_eq!(
+            forked_file_name("session-2026-01-01T10-00-0a1b2c3d.json", "0a1b2c3d-4e5f", "ffee0011-2233"),
+            "session-2026-01-01T10-00-ffee0011.json"
+        );
+        assert_eq!(forked_file_name("session-x-é.json", "é", "ffee0011-2233"), "session-x-ffee0011.json");
+        // An empty ID keeps the name (the caller rejects it as identical to the original)
+        assert_eq!(forked_file_name("session-x.json", "", "ffee0011"), "session-x.json");
+    }
+
+    fn git_record(prompt_index: usize) -> GeminiPromptGitRecord {
+        GeminiPromptGitRecord {
+            prompt_index,
+            commit_before: format!("commit{}", prompt_index),
+            commit_after: None,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn fork_keeps_history_and_records_up_to_the_fork_point() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions_dir = dir.path().join("chats");
+        let records_dir = dir.path().join("git-records");
+        fs::create_dir_all(&sessions_dir).unwrap();
+        fs::create_dir_all(&records_dir).unwrap();
+
+        let session_id = "0a1b2c3d-4e5f-6789";
+        let session_file = sessions_dir.join("session-2026-01-01T10-00-0a1b2c3d.json");
+        let message = |kind: &str, text: &str| serde_json::json!({"type": kind, "content": text});
+        let source = serde_json::to_string_pretty(&serde_json::json!({
+            "sessionId": session_id,
+            "messages": [
+                message("user", "first"),
+                message("gemini", "one"),
+                message("user", "second"),
+                message("gemini", "two"),
+                message("user", "third"),
+            ]
+        }))
+        .unwrap();
+        fs::write(&session_file, &source).unwrap();
+        let records = GeminiGitRecords {
+            session_id: session_id.to_string(),
+            project_path: "/home/dev/app".to_string(),
+            records: (0..3).map(git_record).collect(),
+        };
+        save_gemini_git_records_in(&records_dir, session_id, &records).unwrap();
+
+        let forked = fork_session_file(&sessions_dir, &records_dir, session_id, 1).unwrap();
+
+        // 分叉点的提示词本身不在新会话中
+        let forked_file = find_gemini_session_file(&sessions_dir, &forked).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&fs::read_to_string(&forked_file).unwrap()).unwrap();
+        let texts: Vec<&str> = data["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(texts, vec!["first", "one"]);
+
+        let forked_records = load_gemini_git_records_in(&records_dir, &forked).unwrap();
+        assert_eq!(forked_records.session_id, forked);
+        let indices: Vec<usize> = forked_records.records.iter().map(|r| r.prompt_index).collect();
+        assert_eq!(indices, vec![0, 1]);
+
+        // 原会话不受影响
+        assert_eq!(fs::read_to_string(&session_file).unwrap(), source);
+        assert_eq!(load_gemini_git_records_in(&records_dir, session_id).unwrap().records.len(), 3);
+    }
+}
@@ -0,0 +1,197 @@
+//! Gemini Project-Level MCP Configuration
+//!
+//! Gemini CLI merges `<project>/.gemini/settings.json` over the user settings,
+//! so project-level disabling is written there as `mcp.excluded` (the legacy
+//! top-level `excludeMcpServers` is read as well).
+//!
+//! Gemini keys its own project data by hashed path, so the projects shown in
+//! the UI are tracked in ~/.gemini/workbench_mcp_projects.json, merged with the
+//! projects Claude already knows about.
+
+use crate::tr;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Tracked project paths
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct GeminiMCPProjectsConfig {
+    #[serde(default)]
+    projects: Vec<String>,
+}
+
+fn get_projects_config_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?;
+    Ok(home_dir.join(".gemini").join("workbench_mcp_projects.json"))
+}
+
+fn load_projects_config() -> GeminiMCPProjectsConfig {
+    get_projects_config_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn track_project(project_path: &str) -> Result<(), String> {
+    let mut config = load_projects_config();
+    if config.projects.iter().any(|p| p == project_path) {
+        return Ok(());
+    }
+    config.projects.push(project_path.to_string());
+
+    let path = get_projects_config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create Gemini config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize Gemini MCP projects: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write Gemini MCP projects: {}", e))
+}
+
+fn project_settings_path(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(".gemini").join("settings.json")
+}
+
+fn read_project_settings(project_path: &str) -> Result<serde_json::Value, String> {
+    let path = project_settings_path(project_path);
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read project Gemini settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse project Gemini settings: {}", e))
+}
+
+/// Servers excluded by a settings object (`mcp.excluded` and legacy `excludeMcpServers`)
+fn excluded_servers(settings: &serde_json::Value) -> Vec<String> {
+    let lists = [
+        settings.get("mcp").and_then(|m| m.get("excluded")),
+        settings.get("excludeMcpServers"),
+    ];
+    let mut excluded: Vec<String> = lists
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_array())
+        .flatten()
+        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+        .collect();
+    excluded.sort();
+    excluded.dedup();
+    excluded
+}
+
+/// Adds or removes `server_name` from the settings' exclusion lists
+fn set_server_excluded(settings: &mut serde_json::Value, server_name: &str, excluded: bool) -> Result<(), String> {
+    let settings = settings
+        .as_object_mut()
+        .ok_or_else(|| "Settings is not an object".to_string())?;
+
+    if let Some(legacy) = settings.get_mut("excludeMcpServers").and_then(|v| v.as_array_mut()) {
+        legacy.retain(|v| v.as_str() != Some(server_name));
+    }
+
+    let list = settings
+        .entry("mcp")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| "mcp is not an object".to_string())?
+        .entry("excluded")
+        .or_insert_with(|| serde_json::json!([]))
+        .as_array_mut()
+        .ok_or_else(|| "mcp.excluded is not an array".to_string())?;
+
+    list.retain(|v| v.as_str() != Some(server_name));
+    if excluded {
+        list.push(serde_json::json!(server_name));
+    }
+    Ok(())
+}
+
+/// Gets the list of MCP servers disabled for a specific project
+pub fn get_gemini_disabled_mcp_servers_for_project(project_path: &str) -> Vec<String> {
+    read_project_settings(project_path)
+        .map(|settings| excluded_servers(&settings))
+        .unwrap_or_default()
+}
+
+/// Sets enabled/disabled status for a Gemini MCP server for a specific project
+pub fn set_gemini_mcp_enabled_for_project(
+    server_name: &str,
+    project_path: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    if !Path::new(project_path).is_dir() {
+        return Err(format!("Project path does not exist: {}", project_path));
+    }
+
+    let mut settings = read_project_settings(project_path)?;
+    set_server_excluded(&mut settings, server_name, !enabled)?;
+
+    let path = project_settings_path(project_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create project .gemini directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write project Gemini settings: {}", e))?;
+
+    track_project(project_path)?;
+    info!(
+        "[Gemini MCP] Set server '{}' enabled={} for project '{}'",
+        server_name, enabled, project_path
+    );
+    Ok(())
+}
+
+/// Gets all known projects with the server's disabled status
+pub fn get_gemini_project_list(server_name: &str) -> Vec<serde_json::Value> {
+    let mut paths = load_projects_config().projects;
+
+    // Projects opened through Claude are the same workspaces the UI lists
+    let claude_projects = dirs::home_dir()
+        .and_then(|home| fs::read_to_string(home.join(".claude.json")).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|config| config.get("projects").and_then(|p| p.as_object()).map(|p| p.keys().cloned().collect::<Vec<_>>()))
+        .unwrap_or_default();
+    for path in claude_projects {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    paths
+        .into_iter()
+        .filter(|path| Path::new(path).is_dir())
+        .map(|path| {
+            let disabled = get_gemini_disabled_mcp_servers_for_project(&path)
+                .iter()
+                .any(|s| s == server_name);
+            serde_json::json!({ "path": path, "disabled": disabled })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_project_exclusions() {
+        let mut settings = serde_json::json!({
+            "theme": "dark",
+            "excludeMcpServers": ["legacy", "github"]
+        });
+        assert_eq!(excluded_servers(&settings), vec!["github", "legacy"]);
+
+        set_server_excluded(&mut settings, "playwright", true).unwrap();
+        set_server_excluded(&mut settings, "playwright", true).unwrap();
+        assert_eq!(settings["mcp"]["excluded"], serde_json::json!(["playwright"]));
+
+        // Enabling also clears the legacy list
+        set_server_excluded(&mut settings, "github", false).unwrap();
+        assert_eq!(excluded_servers(&settings), vec!["legacy", "playwright"]);
+        assert_eq!(settings["theme"], "dark");
+    }
+}
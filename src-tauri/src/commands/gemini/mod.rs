@@ -12,6 +12,7 @@
 
 pub mod config;
 pub mod git_ops;
+pub mod mcp;
 pub mod parser;
 pub mod provider;
 pub mod session;
@@ -33,8 +34,23 @@ pub use config::{
     // System prompt commands
     get_gemini_system_prompt,
     save_gemini_system_prompt,
+    // Multi-prompt management
+    list_gemini_prompts,
+    get_gemini_prompt,
+    save_gemini_prompt,
+    rename_gemini_prompt,
+    delete_gemini_prompt,
+    activate_gemini_prompt,
+    deactivate_gemini_prompt,
+    get_active_gemini_prompt_id,
+    // Project-level GEMINI.md management
+    check_project_gemini_md,
+    activate_gemini_prompt_to_project,
+    deactivate_gemini_prompt_from_project,
+};
+pub use session::{
+    build_headless_gemini_command, cancel_gemini, check_gemini_installed, execute_gemini,
 };
-pub use session::{cancel_gemini, check_gemini_installed, execute_gemini};
 
 // Re-export Gemini Rewind commands
 pub use git_ops::{
@@ -43,6 +59,7 @@ pub use git_ops::{
     record_gemini_prompt_sent,
     record_gemini_prompt_completed,
     revert_gemini_to_prompt,
+    fork_gemini_session,
 };
 
 // Re-export Gemini Provider commands
@@ -6,12 +6,16 @@
 //! - Settings.json management
 //! - Provider switching
 
+use crate::tr;
+use crate::error::AnyCodeError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
 use super::config::get_gemini_dir;
+use crate::commands::provider_metrics;
+use crate::commands::data_root::anycode_dir;
 
 // ============================================================================
 // Type Definitions
@@ -60,8 +64,7 @@ fn get_gemini_settings_path() -> Result<PathBuf, String> {
 
 /// Get Gemini providers.json path (for custom presets storage)
 fn get_gemini_providers_path() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
-    Ok(home.join(".anycode").join("gemini_providers.json"))
+    Ok(anycode_dir()?.join("gemini_providers.json"))
 }
 
 // ============================================================================
@@ -170,11 +173,7 @@ fn set_auth_type_in_settings(settings: &mut serde_json::Value, auth_type: &str)
 // Tauri Commands
 // ============================================================================
 
-/// Get custom Gemini provider presets
-#[tauri::command]
-pub async fn get_gemini_provider_presets() -> Result<Vec<GeminiProviderConfig>, String> {
-    log::info!("[Gemini Provider] Getting provider presets");
-
+fn load_gemini_provider_presets() -> Result<Vec<GeminiProviderConfig>, String> {
     let providers_path = get_gemini_providers_path()?;
 
     if !providers_path.exists() {
@@ -184,25 +183,49 @@ pub async fn get_gemini_provider_presets() -> Result<Vec<GeminiProviderConfig>,
     let content = fs::read_to_string(&providers_path)
         .map_err(|e| format!("Failed to read providers.json: {}", e))?;
 
-    let providers: Vec<GeminiProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse providers.json: {}", e))
+}
+
+/// Replace all Gemini provider presets at once (atomic write, used by bundle import)
+pub(crate) fn save_gemini_provider_presets(providers: &[GeminiProviderConfig]) -> Result<(), String> {
+    let providers_path = get_gemini_providers_path()?;
+    if let Some(parent) = providers_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(providers)
+        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+    crate::commands::file_operations::write_atomic(&providers_path, content)
+        .map_err(|e| format!("Failed to write providers.json: {}", e))
+}
+
+/// Find a Gemini provider preset by ID
+pub fn load_gemini_provider(id: &str) -> Result<GeminiProviderConfig, String> {
+    load_gemini_provider_presets()?
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("未找到ID为 '{}' 的 Gemini 代理商配置", id))
+}
 
-    Ok(providers)
+/// Get custom Gemini provider presets
+#[tauri::command]
+pub async fn get_gemini_provider_presets() -> Result<Vec<GeminiProviderConfig>, AnyCodeError> {
+    log::info!("[Gemini Provider] Getting provider presets");
+    load_gemini_provider_presets().map_err(AnyCodeError::Config)
 }
 
 /// Get current Gemini configuration
 #[tauri::command]
-pub async fn get_current_gemini_provider_config() -> Result<CurrentGeminiProviderConfig, String> {
+pub async fn get_current_gemini_provider_config() -> Result<CurrentGeminiProviderConfig, AnyCodeError> {
     log::info!("[Gemini Provider] Getting current config");
 
-    let env_path = get_gemini_env_path()?;
-    let settings_path = get_gemini_settings_path()?;
+    let env_path = get_gemini_env_path().map_err(AnyCodeError::Io)?;
+    let settings_path = get_gemini_settings_path().map_err(AnyCodeError::Io)?;
 
     // Read .env
-    let env = read_env_file(&env_path)?;
+    let env = read_env_file(&env_path).map_err(AnyCodeError::Config)?;
 
     // Read settings.json
-    let settings = read_settings_file(&settings_path)?;
+    let settings = read_settings_file(&settings_path).map_err(AnyCodeError::Config)?;
 
     // Extract values
     let api_key = env.get("GEMINI_API_KEY")
@@ -232,21 +255,22 @@ pub async fn get_current_gemini_provider_config() -> Result<CurrentGeminiProvide
 
 /// Switch to a Gemini provider configuration
 #[tauri::command]
-pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<String, String> {
+pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<String, AnyCodeError> {
     log::info!("[Gemini Provider] Switching to provider: {}", config.name);
+    crate::commands::capabilities::invalidate_capability_cache();
 
-    let gemini_dir = get_gemini_dir()?;
-    let env_path = get_gemini_env_path()?;
-    let settings_path = get_gemini_settings_path()?;
+    let gemini_dir = get_gemini_dir().map_err(AnyCodeError::Io)?;
+    let env_path = get_gemini_env_path().map_err(AnyCodeError::Io)?;
+    let settings_path = get_gemini_settings_path().map_err(AnyCodeError::Io)?;
 
     // Ensure config directory exists
     if !gemini_dir.exists() {
         fs::create_dir_all(&gemini_dir)
-            .map_err(|e| format!("Failed to create .gemini directory: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to create .gemini directory: {}", e)))?;
     }
 
     // Read existing settings to preserve mcpServers and other user configs
-    let mut settings = read_settings_file(&settings_path)?;
+    let mut settings = read_settings_file(&settings_path).map_err(AnyCodeError::Config)?;
 
     // Determine if this is official (OAuth) or third-party (API Key)
     let is_official = config.is_official.unwrap_or(false) ||
@@ -259,7 +283,7 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
         log::info!("[Gemini Provider] Setting up for OAuth mode");
 
         // Clear .env (or write empty)
-        write_env_file(&env_path, &HashMap::new())?;
+        write_env_file(&env_path, &HashMap::new()).map_err(AnyCodeError::Io)?;
 
         // Set auth type to oauth-personal
         set_auth_type_in_settings(&mut settings, "oauth-personal");
@@ -268,14 +292,14 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
         log::info!("[Gemini Provider] Setting up for API Key mode");
 
         // Write .env
-        write_env_file(&env_path, &config.env)?;
+        write_env_file(&env_path, &config.env).map_err(AnyCodeError::Io)?;
 
         // Set auth type to gemini-api-key
         set_auth_type_in_settings(&mut settings, "gemini-api-key");
     }
 
     // Write settings.json
-    write_settings_file(&settings_path, &settings)?;
+    write_settings_file(&settings_path, &settings).map_err(AnyCodeError::Io)?;
 
     log::info!("[Gemini Provider] Successfully switched to: {}", config.name);
     Ok(format!("成功切换到 Gemini 供应商: {}", config.name))
@@ -283,23 +307,23 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
 
 /// Add a new Gemini provider configuration
 #[tauri::command]
-pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<String, String> {
+pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<String, AnyCodeError> {
     log::info!("[Gemini Provider] Adding provider: {}", config.name);
 
-    let providers_path = get_gemini_providers_path()?;
+    let providers_path = get_gemini_providers_path().map_err(AnyCodeError::Io)?;
 
     // Ensure parent directory exists
     if let Some(parent) = providers_path.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
+                .map_err(|e| AnyCodeError::Io(format!("Failed to create directory: {}", e)))?;
         }
     }
 
     // Load existing providers
     let mut providers: Vec<GeminiProviderConfig> = if providers_path.exists() {
         let content = fs::read_to_string(&providers_path)
-            .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
         serde_json::from_str(&content).unwrap_or_default()
     } else {
         vec![]
@@ -307,16 +331,16 @@ pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<
 
     // Check for duplicate ID
     if providers.iter().any(|p| p.id == config.id) {
-        return Err(format!("Provider with ID '{}' already exists", config.id));
+        return Err(AnyCodeError::Provider(format!("Provider with ID '{}' already exists", config.id)));
     }
 
     providers.push(config.clone());
 
     // Save providers
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     log::info!("[Gemini Provider] Successfully added provider: {}", config.name);
     Ok(format!("成功添加 Gemini 供应商: {}", config.name))
@@ -324,31 +348,31 @@ pub async fn add_gemini_provider_config(config: GeminiProviderConfig) -> Result<
 
 /// Update an existing Gemini provider configuration
 #[tauri::command]
-pub async fn update_gemini_provider_config(config: GeminiProviderConfig) -> Result<String, String> {
+pub async fn update_gemini_provider_config(config: GeminiProviderConfig) -> Result<String, AnyCodeError> {
     log::info!("[Gemini Provider] Updating provider: {}", config.name);
 
-    let providers_path = get_gemini_providers_path()?;
+    let providers_path = get_gemini_providers_path().map_err(AnyCodeError::Io)?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", config.id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = config.id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<GeminiProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     // Find and update the provider
     let index = providers.iter().position(|p| p.id == config.id)
-        .ok_or_else(|| format!("Provider with ID '{}' not found", config.id))?;
+        .ok_or_else(|| AnyCodeError::Provider(tr!("provider.not_found", id = config.id)))?;
 
     providers[index] = config.clone();
 
     // Save providers
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     log::info!("[Gemini Provider] Successfully updated provider: {}", config.name);
     Ok(format!("成功更新 Gemini 供应商: {}", config.name))
@@ -356,33 +380,33 @@ pub async fn update_gemini_provider_config(config: GeminiProviderConfig) -> Resu
 
 /// Delete a Gemini provider configuration
 #[tauri::command]
-pub async fn delete_gemini_provider_config(id: String) -> Result<String, String> {
+pub async fn delete_gemini_provider_config(id: String) -> Result<String, AnyCodeError> {
     log::info!("[Gemini Provider] Deleting provider: {}", id);
 
-    let providers_path = get_gemini_providers_path()?;
+    let providers_path = get_gemini_providers_path().map_err(AnyCodeError::Io)?;
 
     if !providers_path.exists() {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     let content = fs::read_to_string(&providers_path)
-        .map_err(|e| format!("Failed to read providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read providers.json: {}", e)))?;
     let mut providers: Vec<GeminiProviderConfig> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse providers.json: {}", e)))?;
 
     // Find and remove the provider
     let initial_len = providers.len();
     providers.retain(|p| p.id != id);
 
     if providers.len() == initial_len {
-        return Err(format!("Provider with ID '{}' not found", id));
+        return Err(AnyCodeError::Provider(tr!("provider.not_found", id = id)));
     }
 
     // Save providers
     let content = serde_json::to_string_pretty(&providers)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize providers: {}", e)))?;
     fs::write(&providers_path, content)
-        .map_err(|e| format!("Failed to write providers.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write providers.json: {}", e)))?;
 
     log::info!("[Gemini Provider] Successfully deleted provider: {}", id);
     Ok(format!("成功删除 Gemini 供应商: {}", id))
@@ -390,19 +414,19 @@ pub async fn delete_gemini_provider_config(id: String) -> Result<String, String>
 
 /// Clear Gemini provider configuration (reset to official OAuth)
 #[tauri::command]
-pub async fn clear_gemini_provider_config() -> Result<String, String> {
+pub async fn clear_gemini_provider_config() -> Result<String, AnyCodeError> {
     log::info!("[Gemini Provider] Clearing config");
 
-    let env_path = get_gemini_env_path()?;
-    let settings_path = get_gemini_settings_path()?;
+    let env_path = get_gemini_env_path().map_err(AnyCodeError::Io)?;
+    let settings_path = get_gemini_settings_path().map_err(AnyCodeError::Io)?;
 
     // Clear .env
-    write_env_file(&env_path, &HashMap::new())?;
+    write_env_file(&env_path, &HashMap::new()).map_err(AnyCodeError::Io)?;
 
     // Reset auth type to OAuth in settings
-    let mut settings = read_settings_file(&settings_path)?;
+    let mut settings = read_settings_file(&settings_path).map_err(AnyCodeError::Config)?;
     set_auth_type_in_settings(&mut settings, "oauth-personal");
-    write_settings_file(&settings_path, &settings)?;
+    write_settings_file(&settings_path, &settings).map_err(AnyCodeError::Io)?;
 
     log::info!("[Gemini Provider] Successfully cleared config");
     Ok("成功清理 Gemini 配置，已切换回官方 OAuth 模式".to_string())
@@ -410,14 +434,20 @@ pub async fn clear_gemini_provider_config() -> Result<String, String> {
 
 /// Test Gemini provider connection
 #[tauri::command]
-pub async fn test_gemini_provider_connection(base_url: String, api_key: Option<String>) -> Result<String, String> {
+pub async fn test_gemini_provider_connection(
+    base_url: String,
+    api_key: Option<String>,
+    provider_id: Option<String>,
+) -> Result<String, AnyCodeError> {
     log::info!("[Gemini Provider] Testing connection to: {}", base_url);
+    let metrics_id = provider_id.unwrap_or_else(|| base_url.clone());
+    let started = std::time::Instant::now();
 
     // Simple connectivity test
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        .map_err(|e| AnyCodeError::Network(format!("Failed to create HTTP client: {}", e)))?;
 
     let test_url = format!("{}/models", base_url.trim_end_matches('/'));
 
@@ -431,6 +461,7 @@ pub async fn test_gemini_provider_connection(base_url: String, api_key: Option<S
     match request.send().await {
         Ok(response) => {
             let status = response.status();
+            provider_metrics::record_connection_test("gemini", &metrics_id, started, provider_metrics::classify_status(status.as_u16()));
             if status.is_success() || status.as_u16() == 401 {
                 Ok(format!("连接测试成功: 端点可达 (状态: {})", status))
             } else {
@@ -438,7 +469,8 @@ pub async fn test_gemini_provider_connection(base_url: String, api_key: Option<S
             }
         }
         Err(e) => {
-            Err(format!("连接测试失败: {}", e))
+            provider_metrics::record_connection_test("gemini", &metrics_id, started, Some(provider_metrics::classify_request_error(&e)));
+            Err(AnyCodeError::Network(format!("连接测试失败: {}", e)))
         }
     }
 }
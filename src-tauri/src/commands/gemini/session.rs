@@ -3,6 +3,7 @@
 //! Handles Gemini CLI execution, streaming output, and process management.
 //! Uses --output-format stream-json for real-time JSONL output.
 
+use crate::error::AnyCodeError;
 use std::process::Stdio;
 
 use tauri::{AppHandle, Emitter, Manager};
@@ -10,9 +11,26 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
 use super::config::{build_gemini_env, load_gemini_config};
+use super::provider::load_gemini_provider;
 use super::parser::{convert_to_unified_message, parse_gemini_line, parse_gemini_line_flexible, convert_raw_to_unified_message};
 use super::types::{GeminiExecutionOptions, GeminiInstallStatus, GeminiProcessState};
-use crate::commands::claude::apply_no_window_async;
+use crate::commands::attachments;
+use crate::commands::auto_fix;
+use crate::commands::claude::{apply_no_window_async, kill_process_tree};
+use crate::commands::docker_backend;
+use crate::commands::model_routing;
+use crate::commands::permission_config::apply_permission_profile;
+use crate::commands::project_defaults;
+use crate::commands::project_memory;
+use crate::commands::prompt_history;
+use crate::commands::prompt_library::apply_prompt_template;
+use crate::commands::prompt_translation::{self, ResponseTranslator};
+use crate::commands::provider_metrics::{ErrorCategory, StreamMetrics};
+use crate::commands::notifications;
+use crate::commands::output_batcher::OutputBatcher;
+use crate::commands::rate_limit::{self, RateLimitWatcher};
+use crate::process::{kill_process_tree_verified_async, ExecutionWatchdog, ProcessKillReport};
+use std::sync::Arc;
 
 // ============================================================================
 // Binary Detection
@@ -103,24 +121,13 @@ pub fn find_gemini_binary() -> Result<String, String> {
 }
 
 /// Get Gemini CLI version
+///
+/// 结果按 (路径, 修改时间) 缓存，状态轮询不会反复启动子进程
 pub fn get_gemini_version(gemini_path: &str) -> Option<String> {
-    let mut cmd = std::process::Command::new(gemini_path);
-    cmd.arg("--version");
+    let output = crate::claude_binary::probe_version_cached(gemini_path).ok()?;
 
-    // Add CREATE_NO_WINDOW flag on Windows to prevent terminal window popup
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-    }
-
-    let output = cmd.output().ok()?;
-
-    if output.status.success() {
-        let version = String::from_utf8_lossy(&output.stdout)
-            .trim()
-            .to_string();
-        Some(version)
+    if output.success {
+        Some(output.stdout.trim().to_string())
     } else {
         None
     }
@@ -132,7 +139,7 @@ pub fn get_gemini_version(gemini_path: &str) -> Option<String> {
 
 /// Check if Gemini CLI is installed
 #[tauri::command]
-pub async fn check_gemini_installed() -> Result<GeminiInstallStatus, String> {
+pub async fn check_gemini_installed() -> Result<GeminiInstallStatus, AnyCodeError> {
     match find_gemini_binary() {
         Ok(path) => {
             let version = get_gemini_version(&path);
@@ -161,9 +168,101 @@ pub async fn check_gemini_installed() -> Result<GeminiInstallStatus, String> {
 pub async fn execute_gemini(
     options: GeminiExecutionOptions,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     log::info!("execute_gemini called with options: {:?}", options);
+    let options = resolve_execution_options(options).await.map_err(AnyCodeError::Config)?;
+
+    execute_gemini_process(options, 0, app_handle).await.map_err(AnyCodeError::Engine)
+}
+
+/// Renders the prompt template and fills options not given explicitly
+/// from the project's Gemini defaults
+///
+/// Runs once per request (not per timeout retry) so templates are only
+/// applied once, and the system prompt only to the first prompt of a new session.
+async fn resolve_execution_options(
+    mut options: GeminiExecutionOptions,
+) -> Result<GeminiExecutionOptions, String> {
+    if !options.internal {
+        prompt_history::record_prompt("gemini", &options.project_path, &options.prompt).await;
+        let (prompt, translation) = prompt_translation::prepare_prompt("gemini", options.prompt).await;
+        options.prompt = prompt;
+        options.prompt_translation = translation;
+    }
+    if let Some(template) = options.prompt_template.take() {
+        options.prompt = apply_prompt_template("gemini", options.prompt, Some(&template)).await?;
+    }
+
+    let defaults = project_defaults::resolve_engine_defaults(&options.project_path, "gemini");
+    options.model = project_defaults::or_default(options.model, &defaults.model);
+    options.permission_profile =
+        project_defaults::or_default(options.permission_profile, &defaults.permission_profile);
+    options.provider = project_defaults::or_default(options.provider, &defaults.provider);
+    options.model =
+        model_routing::resolve_model("gemini", options.model, &options.prompt, options.provider.as_deref()).await;
+
+    if options.session_id.is_none() && !options.internal {
+        // Notes are matched against the user's prompt, before any system instructions are added
+        let memory = if project_memory::should_include(options.include_memory, defaults.include_memory) {
+            project_memory::memory_for_prompt(&options.project_path, &options.prompt).await
+        } else {
+            None
+        };
+        let context = project_defaults::context_block(&options.project_path, &options.prompt, &defaults).await;
+        if let Some(template_id) = defaults.system_prompt_template.as_deref() {
+            let template = project_defaults::load_system_prompt_template(template_id).await?;
+            options.prompt = project_defaults::prepend_system_prompt(&template, &options.prompt);
+        }
+        if let Some(context) = context {
+            options.prompt = format!("{}\n\n{}", context, options.prompt);
+        }
+        if let Some(memory) = memory {
+            options.prompt = format!("{}\n\n{}", memory, options.prompt);
+        }
+    }
+
+    if !options.attachments.is_empty() {
+        options.prompt = attachments::mention_attachments(&options.prompt, &options.attachments);
+        // Gemini only reads `@` paths inside the workspace or included directories
+        let mut dirs = options.include_directories.take().unwrap_or_default();
+        for dir in attachments::attachment_dirs(&options.attachments) {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        options.include_directories = Some(dirs);
+    }
+    Ok(options)
+}
 
+/// Builds a one-shot Gemini command for backend orchestration (e.g. pipelines)
+///
+/// Resolves options like `execute_gemini`; returns the command and the prompt
+/// the caller must write to stdin. `internal` marks backend helper runs.
+pub async fn build_headless_gemini_command(
+    project_path: String,
+    prompt: String,
+    model: Option<String>,
+    permission_profile: Option<String>,
+    internal: bool,
+) -> Result<(Command, String), String> {
+    let options = GeminiExecutionOptions {
+        project_path,
+        prompt,
+        model,
+        permission_profile,
+        internal,
+        ..Default::default()
+    };
+    let options = resolve_execution_options(options).await?;
+    let (cmd, _) = build_gemini_command(&options)?;
+    Ok((cmd, options.prompt))
+}
+
+/// Build the Gemini CLI command for the given options
+///
+/// Returns the command and the resolved model name.
+fn build_gemini_command(options: &GeminiExecutionOptions) -> Result<(Command, String), String> {
     // Find Gemini binary
     let gemini_path = find_gemini_binary()?;
 
@@ -190,13 +289,17 @@ pub async fn execute_gemini(
     args.push("--model".to_string());
     args.push(model.clone());
 
-    // Add approval mode
-    let approval_mode = options.approval_mode.as_ref().unwrap_or(&config.approval_mode);
-    if approval_mode == "yolo" {
-        args.push("--yolo".to_string());
-    } else if approval_mode != "default" {
-        args.push("--approval-mode".to_string());
-        args.push(approval_mode.clone());
+    // Add approval mode (a permission profile takes precedence)
+    if let Some(profile_id) = options.permission_profile.as_deref() {
+        args.extend(apply_permission_profile("gemini", profile_id)?);
+    } else {
+        let approval_mode = options.approval_mode.as_ref().unwrap_or(&config.approval_mode);
+        if approval_mode == "yolo" {
+            args.push("--yolo".to_string());
+        } else if approval_mode != "default" {
+            args.push("--approval-mode".to_string());
+            args.push(approval_mode.clone());
+        }
     }
 
     // Add include directories if specified
@@ -228,60 +331,99 @@ pub async fn execute_gemini(
         cmd.env(&key, &value);
     }
 
-    // Execute process with prompt via stdin
-    execute_gemini_process(cmd, options.project_path, model.clone(), Some(options.prompt), app_handle).await
+    // Per-project provider preset (process env takes precedence over ~/.gemini/.env)
+    if let Some(provider_id) = options.provider.as_deref() {
+        cmd.envs(load_gemini_provider(provider_id)?.env);
+    }
+
+    // Run inside the project's container when enabled in project settings
+    let cmd = docker_backend::wrap_command_for_project(&options.project_path, "gemini", &args, cmd)?;
+
+    Ok((cmd, model.clone()))
 }
 
 /// Cancel a running Gemini execution
+///
+/// Kills the whole process tree, verifies that no descendant survived and
+/// returns a report per cancelled process.
 #[tauri::command]
 pub async fn cancel_gemini(
     session_id: Option<String>,
     app_handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<Vec<ProcessKillReport>, AnyCodeError> {
     log::info!("cancel_gemini called for session: {:?}", session_id);
 
     let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
-    let mut processes = state.processes.lock().await;
-
-    if let Some(sid) = session_id {
-        // Cancel specific session
-        if let Some(mut child) = processes.remove(&sid) {
-            child.kill().await.map_err(|e| format!("Failed to kill process: {}", e))?;
-            log::info!("Killed Gemini process for session: {}", sid);
-
-            // Emit cancellation event
-            let _ = app_handle.emit(&format!("gemini-cancelled:{}", sid), true);
-            let _ = app_handle.emit("gemini-cancelled", true);
+    let cancel_all = session_id.is_none();
+    let targets: Vec<(String, tokio::process::Child)> = {
+        let mut processes = state.processes.lock().await;
+        if let Some(sid) = session_id {
+            // Cancel specific session
+            match processes.remove(&sid) {
+                Some(child) => vec![(sid, child)],
+                None => {
+                    log::warn!("No running process found for session: {}", sid);
+                    Vec::new()
+                }
+            }
         } else {
-            log::warn!("No running process found for session: {}", sid);
+            // Cancel all processes
+            processes.drain().collect()
         }
-    } else {
-        // Cancel all processes
-        for (sid, mut child) in processes.drain() {
-            if let Err(e) = child.kill().await {
-                log::error!("Failed to kill process for session {}: {}", sid, e);
-            } else {
-                log::info!("Killed Gemini process for session: {}", sid);
+    };
+
+    let mut reports = Vec::new();
+    for (sid, mut child) in targets {
+        if let Some(pid) = child.id() {
+            let report = kill_process_tree_verified_async(pid).await;
+            if !report.fully_terminated() {
+                log::error!("Gemini session {} left {} surviving process(es)", sid, report.survivors.len());
             }
+            reports.push(report);
+        }
+
+        // Reap the direct child (also covers a missing PID)
+        if let Err(e) = child.kill().await {
+            log::debug!("Gemini child for session {} already gone: {}", sid, e);
+        }
+        log::info!("Killed Gemini process for session: {}", sid);
+
+        // Emit cancellation event
+        if !cancel_all {
+            let _ = app_handle.emit(&format!("gemini-cancelled:{}", sid), true);
         }
-        let _ = app_handle.emit("gemini-cancelled", true);
     }
 
-    Ok(())
+    let _ = app_handle.emit("gemini-cancelled", true);
+
+    Ok(reports)
 }
 
 // ============================================================================
 // Process Execution
 // ============================================================================
 
+/// Re-runs a timed-out Gemini execution (boxed to break the async recursion)
+fn retry_gemini_run(
+    options: GeminiExecutionOptions,
+    attempt: u32,
+    app_handle: AppHandle,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send>> {
+    Box::pin(async move { execute_gemini_process(options, attempt, app_handle).await })
+}
+
 /// Execute a Gemini process and stream output to frontend
 async fn execute_gemini_process(
-    mut cmd: Command,
-    project_path: String,
-    model: String,
-    prompt: Option<String>,
+    options: GeminiExecutionOptions,
+    attempt: u32,
     app_handle: AppHandle,
 ) -> Result<(), String> {
+    // Build command (prompt is passed via stdin)
+    let (mut cmd, model) = build_gemini_command(&options)?;
+    let project_path = options.project_path.clone();
+    let prompt = Some(options.prompt.clone());
+    let watchdog = Arc::new(ExecutionWatchdog::new(options.timeout.clone()));
+
     // Setup stdio - use piped stdin to pass prompt (supports multiline content)
     cmd.stdin(Stdio::piped());
     cmd.stdout(Stdio::piped());
@@ -324,6 +466,13 @@ async fn execute_gemini_process(
     // Generate session ID
     let session_id = format!("gemini-{}", uuid::Uuid::new_v4());
 
+    // Persist PID so a crashed app can detect the orphan on next launch
+    let tracked_pid = child.id();
+    if let Some(pid) = tracked_pid {
+        crate::process::orphans::track_process(pid, "gemini", Some(&session_id), &project_path);
+        docker_backend::track_container_run(pid, &cmd);
+    }
+
     // Store process in state
     let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
     {
@@ -343,7 +492,8 @@ async fn execute_gemini_process(
         "project_path": project_path,
         "geminiMetadata": {
             "provider": "gemini",
-            "eventType": "session_init"
+            "eventType": "session_init",
+            "attempt": attempt
         }
     });
 
@@ -357,6 +507,7 @@ async fn execute_gemini_process(
     let _ = app_handle.emit("gemini-output", &init_line);
 
     log::info!("Gemini session initialized with ID: {}", session_id);
+    attachments::record_attachments("gemini", &session_id, &options.attachments);
 
     // Clone handles for async tasks
     let app_handle_stdout = app_handle.clone();
@@ -366,12 +517,43 @@ async fn execute_gemini_process(
     let session_id_stderr = session_id.clone();
     let session_id_complete = session_id.clone();
 
+    // Tee raw output to ~/.anycode/logs/gemini/<session_id>.log
+    let session_log = crate::commands::session_log::SessionLogWriter::open("gemini", &session_id);
+    let session_log_stdout = session_log.clone();
+    let session_log_stderr = session_log;
+    let command_auditor = crate::commands::command_audit::CommandAuditor::new("gemini", &session_id, &project_path);
+    let write_guard = crate::commands::guardrails::WriteGuard::new(&app_handle, "gemini", &session_id, &project_path);
+    // Timeout retries and rate-limit requeues resend the same prompt; its translation is only recorded once
+    let prompt_record = options
+        .prompt_translation
+        .clone()
+        .filter(|_| attempt == 0 && options.rate_limit_requeues == 0);
+    let response_translator = ResponseTranslator::new(&app_handle, "gemini", &session_id, prompt_record).await;
+    let stream_metrics = StreamMetrics::new("gemini", &session_id, options.provider.as_deref()).await;
+    let stream_metrics_stdout = stream_metrics.clone();
+    let stream_metrics_stderr = stream_metrics.clone();
+    let rate_limit_watcher = RateLimitWatcher::new(&app_handle, "gemini", &session_id);
+    let rate_limit_stdout = rate_limit_watcher.clone();
+    let rate_limit_stderr = rate_limit_watcher.clone();
+    let output_batcher = OutputBatcher::new(&app_handle, "gemini-output").await;
+    let output_batcher_complete = output_batcher.clone();
+
     // Spawn task to read stdout (JSONL events)
+    let watchdog_stdout = watchdog.clone();
     tokio::spawn(async move {
         let mut reader = BufReader::new(stdout).lines();
         let mut real_cli_session_id_emitted = false;
 
         while let Ok(Some(line)) = reader.next_line().await {
+            watchdog_stdout.touch();
+            if let Some(log) = &session_log_stdout {
+                log.write_line("stdout", &line);
+            }
+            command_auditor.observe(&line);
+            write_guard.observe(&line);
+            response_translator.observe(&line);
+            stream_metrics_stdout.observe(&line);
+            rate_limit_stdout.observe(&line);
             if line.trim().is_empty() {
                 continue;
             }
@@ -428,31 +610,27 @@ async fn execute_gemini_process(
             };
 
             let unified_line = serde_json::to_string(&unified_message).unwrap_or(line.clone());
-
-            // Emit to session-specific channel
-            if let Err(e) = app_handle_stdout.emit(
-                &format!("gemini-output:{}", session_id_stdout),
-                &unified_line,
-            ) {
-                log::error!("Failed to emit gemini-output (session): {}", e);
-            }
-
-            // Also emit to global channel
-            if let Err(e) = app_handle_stdout.emit("gemini-output", &unified_line) {
-                log::error!("Failed to emit gemini-output (global): {}", e);
-            }
+            output_batcher.push(Some(&session_id_stdout), unified_line);
         }
+        output_batcher.flush();
 
         log::info!("Gemini stdout reader finished for session: {}", session_id_stdout);
     });
 
     // Spawn task to read stderr
+    let watchdog_stderr = watchdog.clone();
     tokio::spawn(async move {
         let mut reader = BufReader::new(stderr).lines();
 
         while let Ok(Some(line)) = reader.next_line().await {
+            watchdog_stderr.touch();
+            if let Some(log) = &session_log_stderr {
+                log.write_line("stderr", &line);
+            }
             if !line.trim().is_empty() {
                 log::warn!("Gemini stderr: {}", line);
+                stream_metrics_stderr.observe_stderr(&line);
+                rate_limit_stderr.observe_stderr(&line);
 
                 // Emit stderr as error event
                 let error_message = serde_json::json!({
@@ -486,53 +664,176 @@ async fn execute_gemini_process(
         // Wait a bit for stdout/stderr to be processed
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-        // Get child from processes map
-        let mut processes = processes_complete.lock().await;
-        if let Some(mut child) = processes.remove(&session_id_complete) {
-            match child.wait().await {
-                Ok(status) => {
-                    let success = status.success();
-                    log::info!(
-                        "Gemini process exited with status: {} (success: {})",
-                        status,
-                        success
-                    );
-
-                    // Emit completion event
-                    let complete_payload = serde_json::json!({
-                        "type": "result",
-                        "status": if success { "success" } else { "error" },
-                        "geminiMetadata": {
-                            "provider": "gemini",
-                            "eventType": "complete",
-                            "exitCode": status.code()
+        // Poll with try_wait() so cancel_gemini can still find the child while it runs,
+        // and so the watchdog can kill hung executions.
+        let mut timed_out = None;
+        let wait_result: Option<Result<std::process::ExitStatus, String>> = loop {
+            let mut processes = processes_complete.lock().await;
+            let Some(child) = processes.get_mut(&session_id_complete) else {
+                log::info!("Gemini process {} was cancelled, stopping wait task", session_id_complete);
+                break None;
+            };
+
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    processes.remove(&session_id_complete);
+                    break Some(Ok(status));
+                }
+                Ok(None) => {
+                    if let Some(kind) = watchdog.check() {
+                        log::warn!("Gemini session {} timed out ({:?}), killing process tree", session_id_complete, kind);
+                        if let Some(pid) = child.id() {
+                            if let Err(e) = kill_process_tree(pid) {
+                                log::warn!("Failed to kill Gemini process tree {}: {}", pid, e);
+                            }
                         }
-                    });
+                        let _ = child.start_kill();
+                        processes.remove(&session_id_complete);
+                        timed_out = Some(kind);
+                        break None;
+                    }
+                    drop(processes);
+                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                }
+                Err(e) => {
+                    processes.remove(&session_id_complete);
+                    break Some(Err(e.to_string()));
+                }
+            }
+        };
+
+        if let Some(pid) = tracked_pid {
+            crate::process::orphans::untrack_process(pid);
+            docker_backend::release_container_run(pid);
+        }
+        // 缓存的输出先于完成/超时事件发送
+        output_batcher_complete.flush();
+
+        // Cancelled runs say nothing about the provider and are not recorded
+        if timed_out.is_some() {
+            stream_metrics.finish(Some(ErrorCategory::Timeout));
+        } else if let Some(Ok(status)) = &wait_result {
+            stream_metrics.finish((!status.success()).then_some(ErrorCategory::Other));
+        }
+        let exited_with_error = matches!(&wait_result, Some(Ok(status)) if !status.success());
+        // Cancelled runs are not notified
+        let outcome = match &wait_result {
+            Some(Ok(status)) => Some(status.success()),
+            Some(Err(_)) => Some(false),
+            None => timed_out.map(|_| false),
+        };
+        let mut retrying = false;
+
+        match wait_result {
+            Some(Ok(status)) => {
+                let success = status.success();
+                log::info!(
+                    "Gemini process exited with status: {} (success: {})",
+                    status,
+                    success
+                );
 
-                    let complete_line = serde_json::to_string(&complete_payload).unwrap_or_default();
+                // Emit completion event
+                let complete_payload = serde_json::json!({
+                    "type": "result",
+                    "status": if success { "success" } else { "error" },
+                    "geminiMetadata": {
+                        "provider": "gemini",
+                        "eventType": "complete",
+                        "exitCode": status.code()
+                    }
+                });
 
-                    let _ = app_handle_complete.emit(
-                        &format!("gemini-output:{}", session_id_complete),
-                        &complete_line,
-                    );
-                    let _ = app_handle_complete.emit("gemini-output", &complete_line);
+                let complete_line = serde_json::to_string(&complete_payload).unwrap_or_default();
+                // 先发送缓存的输出，保证完成事件在最后
+                output_batcher_complete.push(Some(&session_id_complete), complete_line);
+                output_batcher_complete.flush();
 
-                    let _ = app_handle_complete.emit(
-                        &format!("gemini-complete:{}", session_id_complete),
-                        success,
-                    );
-                    let _ = app_handle_complete.emit("gemini-complete", success);
-                }
-                Err(e) => {
-                    log::error!("Failed to wait for Gemini process: {}", e);
+                let _ = app_handle_complete.emit(
+                    &format!("gemini-complete:{}", session_id_complete),
+                    success,
+                );
+                let _ = app_handle_complete.emit("gemini-complete", success);
+            }
+            Some(Err(e)) => {
+                log::error!("Failed to wait for Gemini process: {}", e);
+
+                let _ = app_handle_complete.emit(
+                    &format!("gemini-complete:{}", session_id_complete),
+                    false,
+                );
+                let _ = app_handle_complete.emit("gemini-complete", false);
+            }
+            None => {}
+        }
 
-                    let _ = app_handle_complete.emit(
-                        &format!("gemini-complete:{}", session_id_complete),
-                        false,
-                    );
-                    let _ = app_handle_complete.emit("gemini-complete", false);
+        if let Some(kind) = timed_out {
+            let will_retry = watchdog.should_retry(attempt);
+            let payload = watchdog.timeout_payload(kind, &session_id_complete, attempt, will_retry);
+            let _ = app_handle_complete.emit(&format!("gemini-timeout:{}", session_id_complete), &payload);
+            let _ = app_handle_complete.emit("gemini-timeout", &payload);
+
+            let _ = app_handle_complete.emit(
+                &format!("gemini-complete:{}", session_id_complete),
+                false,
+            );
+            let _ = app_handle_complete.emit("gemini-complete", false);
+
+            retrying = will_retry;
+            if will_retry {
+                log::info!("Retrying Gemini execution (attempt {}/{})", attempt + 1, options.timeout.max_retries);
+                if let Err(e) = retry_gemini_run(options, attempt + 1, app_handle_complete.clone()).await {
+                    log::error!("Gemini retry failed to start: {}", e);
                 }
             }
+        } else if exited_with_error {
+            // Resubmit the prompt after the backoff when the run failed on a rate limit
+            if let Some(signal) = rate_limit_watcher.signal() {
+                let requeues = options.rate_limit_requeues;
+                let mut next = options;
+                next.rate_limit_requeues += 1;
+                let app = app_handle_complete.clone();
+                retrying = rate_limit::schedule_requeue(
+                    &app_handle_complete,
+                    "gemini",
+                    &rate_limit_watcher.session_id(),
+                    &rate_limit_watcher.channel_id(),
+                    &signal,
+                    requeues,
+                    retry_gemini_run(next, 0, app),
+                );
+            }
+        } else if outcome == Some(true) {
+            // Auto-fix loop: verify, then resume the latest session with the failure output
+            if let Some(auto_fix_options) = options.auto_fix.clone() {
+                let cli_session_id = rate_limit_watcher.session_id();
+                let project_path = options.project_path.clone();
+                let iteration = options.auto_fix_iteration;
+                let app = app_handle_complete.clone();
+                let target = cli_session_id.clone();
+                retrying = auto_fix::schedule_auto_fix(
+                    &app_handle_complete,
+                    "gemini",
+                    &cli_session_id,
+                    &project_path,
+                    &auto_fix_options,
+                    iteration,
+                    move |prompt| {
+                        let mut next = options;
+                        next.prompt = prompt;
+                        next.attachments.clear();
+                        next.prompt_translation = None;
+                        next.rate_limit_requeues = 0;
+                        next.session_id = Some(target);
+                        next.auto_fix_iteration += 1;
+                        retry_gemini_run(next, 0, app)
+                    },
+                );
+            }
+        }
+
+        if let (Some(success), false) = (outcome, retrying) {
+            notifications::notify_run_finished(&app_handle_complete, "gemini", &session_id_complete, success);
         }
     });
 
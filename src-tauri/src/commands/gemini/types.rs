@@ -5,6 +5,13 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::commands::attachments::StagedAttachment;
+use crate::commands::auto_fix::AutoFixOptions;
+use crate::commands::prompt_library::PromptTemplateRef;
+use crate::commands::prompt_translation::TranslationRecord;
+use crate::commands::session_metadata::SessionMetadata;
+use crate::process::ExecutionTimeoutOptions;
+
 // ============================================================================
 // Stream Event Types (from --output-format stream-json)
 // ============================================================================
@@ -148,15 +155,60 @@ pub struct GeminiExecutionOptions {
     /// Approval mode: "auto_edit" or "yolo"
     pub approval_mode: Option<String>,
 
+    /// Permission profile ID (overrides `approval_mode` when set)
+    #[serde(default)]
+    pub permission_profile: Option<String>,
+
+    /// Provider preset ID (its env vars are set for this execution only)
+    #[serde(default)]
+    pub provider: Option<String>,
+
+    /// Prompt library template rendered into the prompt before execution
+    #[serde(default)]
+    pub prompt_template: Option<PromptTemplateRef>,
+
+    /// Prepend relevant project memory notes to a new session (falls back to project defaults)
+    #[serde(default)]
+    pub include_memory: Option<bool>,
+
     /// Additional directories to include in context
     pub include_directories: Option<Vec<String>>,
 
+    /// Staged attachments (referenced via `@path` in the prompt)
+    #[serde(default)]
+    pub attachments: Vec<StagedAttachment>,
+
+    /// Original prompt when `prompt` was translated before sending (set by the backend)
+    #[serde(skip)]
+    pub prompt_translation: Option<TranslationRecord>,
+
+    /// Times this prompt was resubmitted after a rate limit (set by the backend)
+    #[serde(skip)]
+    pub rate_limit_requeues: u32,
+
+    /// Backend helper run (summaries, commit messages): no prompt history, translation,
+    /// memory or system prompt (set by the backend)
+    #[serde(skip)]
+    pub internal: bool,
+
+    /// Verify after completion and send fix prompts on failure (auto-fix loop)
+    #[serde(default)]
+    pub auto_fix: Option<AutoFixOptions>,
+
+    /// Fix prompts already sent by the auto-fix loop (set by the backend)
+    #[serde(skip)]
+    pub auto_fix_iteration: u32,
+
     /// Session ID for resuming (if supported)
     pub session_id: Option<String>,
 
     /// Enable debug mode
     #[serde(default)]
     pub debug: bool,
+
+    /// Idle / max-duration timeout and retry policy
+    #[serde(default, flatten)]
+    pub timeout: ExecutionTimeoutOptions,
 }
 
 impl Default for GeminiExecutionOptions {
@@ -166,9 +218,20 @@ impl Default for GeminiExecutionOptions {
             prompt: String::new(),
             model: Some("gemini-2.5-pro".to_string()),
             approval_mode: Some("auto_edit".to_string()),
+            permission_profile: None,
+            provider: None,
+            prompt_template: None,
+            include_memory: None,
             include_directories: None,
+            attachments: Vec::new(),
+            prompt_translation: None,
+            rate_limit_requeues: 0,
+            internal: false,
+            auto_fix: None,
+            auto_fix_iteration: 0,
             session_id: None,
             debug: false,
+            timeout: ExecutionTimeoutOptions::default(),
         }
     }
 }
@@ -276,4 +339,7 @@ pub struct GeminiSessionInfo {
     pub file_name: String,
     pub start_time: String,
     pub first_message: Option<String>,
+    /// Tags / pinned / archived state managed by AnyCode
+    #[serde(default)]
+    pub metadata: SessionMetadata,
 }
@@ -1,5 +1,18 @@
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::process::Command as StdCommand;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::claude::{encode_project_path, get_claude_dir};
+use super::codex::git_ops::{get_codex_git_records_dir, CodexGitRecords};
+use super::gemini::git_ops::{get_gemini_git_records_dir, GeminiGitRecords};
+use super::commit_message::PROMPT_TRAILER;
+use super::prompt_tracker::GitRecord;
+use super::simple_git::run_git;
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
 
 /// Git 代码变更统计
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,28 +36,7 @@ pub async fn get_git_diff_stats(
     let to_ref = to_commit.unwrap_or_else(|| "HEAD".to_string());
 
     // 使用 git diff --numstat 获取统计
-    let mut cmd = StdCommand::new("git");
-    cmd.current_dir(&project_path);
-    cmd.args(&["diff", "--numstat", &from_commit, &to_ref]);
-
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-    }
-
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to execute git diff: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "Git diff failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = run_git(&project_path, &["diff", "--numstat", &from_commit, &to_ref])?;
 
     // 解析 git diff --numstat 输出
     // 格式：<added>\t<removed>\t<filename>
@@ -84,3 +76,722 @@ pub async fn get_session_code_changes(
 ) -> Result<GitDiffStats, String> {
     get_git_diff_stats(project_path, session_start_commit, None).await
 }
+
+// ============================================================================
+// 历史趋势（增量统计）
+// ============================================================================
+//
+// 每个提交的统计写入 agents.db 的 `git_commit_stats` 表，并记录已统计到的 HEAD。
+// 之后只遍历 `last_head..HEAD` 的新提交；HEAD 不再包含上次的提交（rebase、切换分支）时整体重建。
+
+/// 正在后台刷新的项目
+static REFRESHING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 提交记录分隔符（git log 的 %x1e / %x1f）
+const RECORD_SEP: char = '\u{1e}';
+const FIELD_SEP: char = '\u{1f}';
+
+/// 单个提交的统计
+#[derive(Debug, Clone, PartialEq)]
+struct CommitStat {
+    hash: String,
+    author_name: String,
+    author_email: String,
+    /// 提交时间（unix 秒）
+    timestamp: i64,
+    /// 作者时区下的日期 YYYY-MM-DD
+    day: String,
+    files_changed: usize,
+    lines_added: usize,
+    lines_removed: usize,
+}
+
+/// 趋势中的一个时间段
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitTrendPoint {
+    /// 时间段起始日期（day: YYYY-MM-DD，week: 周一，month: YYYY-MM）
+    pub bucket: String,
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+/// 单个作者的统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitAuthorStats {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitTrends {
+    pub period: String,
+    pub granularity: String,
+    pub total_commits: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub points: Vec<GitTrendPoint>,
+    /// 按提交数降序
+    pub authors: Vec<GitAuthorStats>,
+    /// 统计数据对应的 HEAD
+    pub head: Option<String>,
+}
+
+/// 解析 `git log --numstat --format=%x1e%H%x1f%an%x1f%ae%x1f%at%x1f%ad --date=short` 的输出
+fn parse_git_log(output: &str) -> Vec<CommitStat> {
+    output
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let fields: Vec<&str> = lines.next()?.split(FIELD_SEP).collect();
+            if fields.len() < 5 {
+                return None;
+            }
+            let mut stat = CommitStat {
+                hash: fields[0].to_string(),
+                author_name: fields[1].to_string(),
+                author_email: fields[2].to_string(),
+                timestamp: fields[3].parse().ok()?,
+                day: fields[4].to_string(),
+                files_changed: 0,
+                lines_added: 0,
+                lines_removed: 0,
+            };
+            // 格式：<added>\t<removed>\t<filename>（二进制文件为 "-"）
+            for line in lines {
+                let parts: Vec<&str> = line.split('\t').collect();
+                if parts.len() >= 3 {
+                    stat.files_changed += 1;
+                    stat.lines_added += parts[0].parse::<usize>().unwrap_or(0);
+                    stat.lines_removed += parts[1].parse::<usize>().unwrap_or(0);
+                }
+            }
+            Some(stat)
+        })
+        .collect()
+}
+
+/// 创建统计表（幂等）
+fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS git_commit_stats (
+            project_path TEXT NOT NULL,
+            commit_hash TEXT NOT NULL,
+            author_name TEXT NOT NULL,
+            author_email TEXT NOT NULL,
+            committed_at INTEGER NOT NULL,
+            day TEXT NOT NULL,
+            files_changed INTEGER NOT NULL,
+            lines_added INTEGER NOT NULL,
+            lines_removed INTEGER NOT NULL,
+            PRIMARY KEY (project_path, commit_hash)
+        );
+        CREATE INDEX IF NOT EXISTS idx_git_commit_stats_time
+            ON git_commit_stats(project_path, committed_at);
+        CREATE TABLE IF NOT EXISTS git_stats_state (
+            project_path TEXT PRIMARY KEY,
+            head TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );",
+    )
+    .map_err(|e| format!("创建 Git 统计表失败: {}", e))
+}
+
+fn open_stats_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+fn stored_head(conn: &Connection, project_path: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT head FROM git_stats_state WHERE project_path = ?1",
+        params![project_path],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("读取 Git 统计状态失败: {}", e))
+}
+
+/// 写入新提交并更新 HEAD；`rebuild` 时先清空该项目的旧数据
+fn store_commits(
+    conn: &mut Connection,
+    project_path: &str,
+    head: &str,
+    commits: &[CommitStat],
+    rebuild: bool,
+) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| format!("开启事务失败: {}", e))?;
+    if rebuild {
+        tx.execute("DELETE FROM git_commit_stats WHERE project_path = ?1", params![project_path])
+            .map_err(|e| format!("清理 Git 统计失败: {}", e))?;
+    }
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT OR REPLACE INTO git_commit_stats
+                    (project_path, commit_hash, author_name, author_email, committed_at, day,
+                     files_changed, lines_added, lines_removed)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )
+            .map_err(|e| format!("写入 Git 统计失败: {}", e))?;
+        for c in commits {
+            stmt.execute(params![
+                project_path,
+                c.hash,
+                c.author_name,
+                c.author_email,
+                c.timestamp,
+                c.day,
+                c.files_changed as i64,
+                c.lines_added as i64,
+                c.lines_removed as i64,
+            ])
+            .map_err(|e| format!("写入 Git 统计失败: {}", e))?;
+        }
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO git_stats_state (project_path, head, updated_at) VALUES (?1, ?2, ?3)",
+        params![project_path, head, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("更新 Git 统计状态失败: {}", e))?;
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+}
+
+/// 增量刷新项目的提交统计，返回新统计的提交数
+fn refresh_project_stats(conn: &mut Connection, project_path: &str) -> Result<usize, String> {
+    let head = match run_git(project_path, &["rev-parse", "HEAD"]) {
+        Ok(head) => head.trim().to_string(),
+        // 没有任何提交的仓库
+        Err(_) if run_git(project_path, &["rev-parse", "--git-dir"]).is_ok() => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    let last = stored_head(conn, project_path)?;
+    if last.as_deref() == Some(head.as_str()) {
+        return Ok(0);
+    }
+    let incremental = last
+        .as_deref()
+        .filter(|last| run_git(project_path, &["merge-base", "--is-ancestor", last, &head]).is_ok());
+
+    let range = match incremental {
+        Some(last) => format!("{}..{}", last, head),
+        None => head.clone(),
+    };
+    let output = run_git(
+        project_path,
+        &[
+            "log",
+            "--no-merges",
+            "--numstat",
+            "--date=short",
+            "--format=%x1e%H%x1f%an%x1f%ae%x1f%at%x1f%ad",
+            &range,
+        ],
+    )?;
+    let commits = parse_git_log(&output);
+    store_commits(conn, project_path, &head, &commits, incremental.is_none())?;
+
+    log::info!(
+        "[GitStats] {} {} commits for {}",
+        if incremental.is_some() { "Added" } else { "Rebuilt with" },
+        commits.len(),
+        project_path
+    );
+    Ok(commits.len())
+}
+
+/// 统计周期对应的天数（None 表示全部历史）
+fn period_days(period: &str) -> Result<Option<i64>, String> {
+    match period {
+        "week" => Ok(Some(7)),
+        "month" => Ok(Some(30)),
+        "quarter" => Ok(Some(90)),
+        "year" => Ok(Some(365)),
+        "all" => Ok(None),
+        _ => Err(format!("不支持的统计周期: {}", period)),
+    }
+}
+
+/// 按粒度分组的 SQL 表达式
+fn bucket_expr(granularity: &str) -> Result<&'static str, String> {
+    match granularity {
+        "day" => Ok("day"),
+        // 当周周一
+        "week" => Ok("date(day, 'weekday 0', '-6 days')"),
+        "month" => Ok("substr(day, 1, 7)"),
+        _ => Err(format!("不支持的统计粒度: {}", granularity)),
+    }
+}
+
+fn query_trends(
+    conn: &Connection,
+    project_path: &str,
+    period: &str,
+    granularity: &str,
+    now: i64,
+) -> Result<GitTrends, String> {
+    let since = period_days(period)?.map_or(0, |days| now - days * 86_400);
+    let bucket = bucket_expr(granularity)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} AS bucket, COUNT(*), SUM(lines_added), SUM(lines_removed)
+             FROM git_commit_stats WHERE project_path = ?1 AND committed_at >= ?2
+             GROUP BY bucket ORDER BY bucket",
+            bucket
+        ))
+        .map_err(|e| format!("查询 Git 趋势失败: {}", e))?;
+    let points = stmt
+        .query_map(params![project_path, since], |row| {
+            Ok(GitTrendPoint {
+                bucket: row.get(0)?,
+                commits: row.get::<_, i64>(1)? as usize,
+                lines_added: row.get::<_, i64>(2)? as usize,
+                lines_removed: row.get::<_, i64>(3)? as usize,
+            })
+        })
+        .map_err(|e| format!("查询 Git 趋势失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取 Git 趋势失败: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT author_name, author_email, COUNT(*), SUM(lines_added), SUM(lines_removed)
+             FROM git_commit_stats WHERE project_path = ?1 AND committed_at >= ?2
+             GROUP BY author_email ORDER BY COUNT(*) DESC, author_email",
+        )
+        .map_err(|e| format!("查询作者统计失败: {}", e))?;
+    let authors = stmt
+        .query_map(params![project_path, since], |row| {
+            Ok(GitAuthorStats {
+                name: row.get(0)?,
+                email: row.get(1)?,
+                commits: row.get::<_, i64>(2)? as usize,
+                lines_added: row.get::<_, i64>(3)? as usize,
+                lines_removed: row.get::<_, i64>(4)? as usize,
+            })
+        })
+        .map_err(|e| format!("查询作者统计失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取作者统计失败: {}", e))?;
+
+    Ok(GitTrends {
+        period: period.to_string(),
+        granularity: granularity.to_string(),
+        total_commits: points.iter().map(|p| p.commits).sum(),
+        lines_added: points.iter().map(|p| p.lines_added).sum(),
+        lines_removed: points.iter().map(|p| p.lines_removed).sum(),
+        points,
+        authors,
+        head: stored_head(conn, project_path)?,
+    })
+}
+
+/// 后台刷新项目的提交统计（立即返回；同一项目已在刷新时忽略）
+#[tauri::command]
+pub async fn refresh_git_stats(project_path: String) -> Result<(), String> {
+    if !REFRESHING.lock().unwrap().insert(project_path.clone()) {
+        return Ok(());
+    }
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = open_stats_db().and_then(|mut conn| refresh_project_stats(&mut conn, &project_path)) {
+            log::warn!("[GitStats] Background refresh failed for {}: {}", project_path, e);
+        }
+        REFRESHING.lock().unwrap().remove(&project_path);
+    });
+    Ok(())
+}
+
+/// 获取项目的提交趋势
+///
+/// 查询前先增量统计 HEAD 上的新提交。`period`: week | month | quarter | year | all；
+/// `granularity`: day | week | month。
+#[tauri::command]
+pub async fn get_git_trends(
+    project_path: String,
+    period: String,
+    granularity: String,
+) -> Result<GitTrends, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut conn = open_stats_db()?;
+        refresh_project_stats(&mut conn, &project_path)?;
+        query_trends(&conn, &project_path, &period, &granularity, chrono::Utc::now().timestamp())
+    })
+    .await
+    .map_err(|e| format!("查询 Git 趋势失败: {}", e))?
+}
+
+// ============================================================================
+// AI 贡献归因
+// ============================================================================
+//
+// 提示词 Git 记录保存了每个提示词执行前后的提交（commit_before / commit_after），
+// 区间内的提交都是 Agent 会话中产生的；此外各引擎完成后的自动提交带有固定标题，
+// 或在使用生成的提交信息时带有 `Any-Code-Prompt` trailer（记录被删除后仍可识别）。
+// 其余提交视为手动提交。
+
+/// 自动提交标题前缀 -> 引擎
+const AUTO_COMMIT_PREFIXES: [(&str, &str); 3] = [
+    ("[Claude Code] After prompt #", "claude"),
+    ("[Codex] After prompt #", "codex"),
+    ("[Gemini] After prompt #", "gemini"),
+];
+
+/// 从自动提交的标题或 trailer（`<engine> #<index>`）识别引擎
+fn auto_commit_engine(subject: &str, trailer: &str) -> Option<&'static str> {
+    AUTO_COMMIT_PREFIXES
+        .iter()
+        .find(|(prefix, _)| subject.starts_with(prefix))
+        .or_else(|| {
+            let engine = trailer.split_whitespace().next()?;
+            AUTO_COMMIT_PREFIXES.iter().find(|(_, e)| *e == engine)
+        })
+        .map(|(_, engine)| *engine)
+}
+
+/// 某个提示词执行前后的提交区间
+#[derive(Debug, Clone)]
+struct AgentSpan {
+    engine: &'static str,
+    session_id: String,
+    commit_before: String,
+    commit_after: String,
+}
+
+/// 统计周期内的提交
+#[derive(Debug, Clone)]
+struct PeriodCommit {
+    hash: String,
+    /// 从自动提交标题 / trailer 识别出的引擎
+    auto_engine: Option<&'static str>,
+    lines_added: usize,
+    lines_removed: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionTotals {
+    pub commits: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl AttributionTotals {
+    fn add(&mut self, commit: &PeriodCommit) {
+        self.commits += 1;
+        self.lines_added += commit.lines_added;
+        self.lines_removed += commit.lines_removed;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineAttribution {
+    pub engine: String,
+    /// 产生过提交的会话数（仅能从 Git 记录识别）
+    pub sessions: usize,
+    #[serde(flatten)]
+    pub totals: AttributionTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiAttributionStats {
+    pub period: String,
+    pub total: AttributionTotals,
+    pub ai: AttributionTotals,
+    pub human: AttributionTotals,
+    /// AI 新增行数占比（0-1）
+    pub ai_line_share: f64,
+    /// 按 AI 新增行数降序
+    pub engines: Vec<EngineAttribution>,
+}
+
+fn same_project(a: &str, b: &str) -> bool {
+    let normalize = |p: &str| p.replace('\\', "/").trim_end_matches('/').to_string();
+    normalize(a) == normalize(b)
+}
+
+/// 目录下的 JSON 文件（文件名去掉 `suffix` 后作为会话 ID）
+fn read_record_files(dir: &Path, suffix: &str) -> Vec<(String, String)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let session_id = name.strip_suffix(suffix)?.to_string();
+            let content = std::fs::read_to_string(entry.path()).ok()?;
+            Some((session_id, content))
+        })
+        .collect()
+}
+
+/// 读取项目在各引擎下的提示词 Git 记录
+fn load_agent_spans(project_path: &str) -> Vec<AgentSpan> {
+    let mut spans = Vec::new();
+    let mut push = |engine: &'static str, session_id: &str, before: &str, after: Option<&String>| {
+        if let Some(after) = after.filter(|after| after.as_str() != before) {
+            spans.push(AgentSpan {
+                engine,
+                session_id: session_id.to_string(),
+                commit_before: before.to_string(),
+                commit_after: after.clone(),
+            });
+        }
+    };
+
+    if let Ok(claude_dir) = get_claude_dir() {
+        let dir = claude_dir.join("projects").join(encode_project_path(project_path)).join("sessions");
+        for (session_id, content) in read_record_files(&dir, ".git-records.json") {
+            let records: HashMap<String, GitRecord> = serde_json::from_str(&content).unwrap_or_default();
+            for record in records.values() {
+                push("claude", &session_id, &record.commit_before, record.commit_after.as_ref());
+            }
+        }
+    }
+    if let Ok(dir) = get_codex_git_records_dir() {
+        for (session_id, content) in read_record_files(&dir, ".json") {
+            let Ok(records) = serde_json::from_str::<CodexGitRecords>(&content) else { continue };
+            if same_project(&records.project_path, project_path) {
+                for record in &records.records {
+                    push("codex", &session_id, &record.commit_before, record.commit_after.as_ref());
+                }
+            }
+        }
+    }
+    if let Ok(dir) = get_gemini_git_records_dir() {
+        for (session_id, content) in read_record_files(&dir, ".json") {
+            let Ok(records) = serde_json::from_str::<GeminiGitRecords>(&content) else { continue };
+            if same_project(&records.project_path, project_path) {
+                for record in &records.records {
+                    push("gemini", &session_id, &record.commit_before, record.commit_after.as_ref());
+                }
+            }
+        }
+    }
+    spans
+}
+
+/// 提交 -> (引擎, 会话)；已不存在的提交（如回滚后）直接跳过
+fn resolve_agent_commits(project_path: &str, spans: &[AgentSpan]) -> HashMap<String, (&'static str, String)> {
+    let mut commits = HashMap::new();
+    for span in spans {
+        let range = format!("{}..{}", span.commit_before, span.commit_after);
+        let Ok(output) = run_git(project_path, &["rev-list", "--no-merges", &range]) else {
+            continue;
+        };
+        for hash in output.lines().filter(|l| !l.is_empty()) {
+            commits
+                .entry(hash.to_string())
+                .or_insert_with(|| (span.engine, span.session_id.clone()));
+        }
+    }
+    commits
+}
+
+fn query_period_commits(
+    conn: &Connection,
+    project_path: &str,
+    since: i64,
+    auto_engines: &HashMap<String, &'static str>,
+) -> Result<Vec<PeriodCommit>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT commit_hash, lines_added, lines_removed FROM git_commit_stats
+             WHERE project_path = ?1 AND committed_at >= ?2",
+        )
+        .map_err(|e| format!("查询提交统计失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![project_path, since], |row| {
+            let hash: String = row.get(0)?;
+            Ok(PeriodCommit {
+                auto_engine: auto_engines.get(&hash).copied(),
+                hash,
+                lines_added: row.get::<_, i64>(1)? as usize,
+                lines_removed: row.get::<_, i64>(2)? as usize,
+            })
+        })
+        .map_err(|e| format!("查询提交统计失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取提交统计失败: {}", e));
+    rows
+}
+
+/// 按 Git 记录和自动提交标记把提交分到各引擎，其余算作手动提交
+fn attribute_commits(
+    period: &str,
+    commits: &[PeriodCommit],
+    agent_commits: &HashMap<String, (&'static str, String)>,
+) -> AiAttributionStats {
+    let mut total = AttributionTotals::default();
+    let mut ai = AttributionTotals::default();
+    let mut human = AttributionTotals::default();
+    let mut engines: HashMap<&str, (AttributionTotals, HashSet<&str>)> = HashMap::new();
+
+    for commit in commits {
+        total.add(commit);
+        let attributed = match agent_commits.get(&commit.hash) {
+            Some((engine, session_id)) => Some((*engine, Some(session_id.as_str()))),
+            None => commit.auto_engine.map(|engine| (engine, None)),
+        };
+        match attributed {
+            Some((engine, session_id)) => {
+                ai.add(commit);
+                let (totals, sessions) = engines.entry(engine).or_default();
+                totals.add(commit);
+                sessions.extend(session_id);
+            }
+            None => human.add(commit),
+        }
+    }
+
+    let mut engines: Vec<EngineAttribution> = engines
+        .into_iter()
+        .map(|(engine, (totals, sessions))| EngineAttribution {
+            engine: engine.to_string(),
+            sessions: sessions.len(),
+            totals,
+        })
+        .collect();
+    engines.sort_by(|a, b| b.totals.lines_added.cmp(&a.totals.lines_added).then(a.engine.cmp(&b.engine)));
+
+    AiAttributionStats {
+        period: period.to_string(),
+        ai_line_share: if total.lines_added == 0 {
+            0.0
+        } else {
+            ai.lines_added as f64 / total.lines_added as f64
+        },
+        total,
+        ai,
+        human,
+        engines,
+    }
+}
+
+/// 统计项目中 AI 与手动提交的贡献（按引擎细分）
+///
+/// `period`: week | month | quarter | year | all
+#[tauri::command]
+pub async fn get_ai_attribution_stats(project_path: String, period: String) -> Result<AiAttributionStats, String> {
+    tokio::task::spawn_blocking(move || {
+        let since = period_days(&period)?.map_or(0, |days| chrono::Utc::now().timestamp() - days * 86_400);
+
+        let mut conn = open_stats_db()?;
+        refresh_project_stats(&mut conn, &project_path)?;
+
+        let since_arg = format!("--since=@{}", since);
+        let trailer_format = format!("--format=%x1e%H%x1f%s%x1f%(trailers:key={},valueonly)", PROMPT_TRAILER);
+        let auto_engines: HashMap<String, &'static str> =
+            run_git(&project_path, &["log", "--no-merges", &since_arg, &trailer_format])
+                .unwrap_or_default()
+                .split(RECORD_SEP)
+                .filter_map(|record| {
+                    let mut fields = record.trim().splitn(3, FIELD_SEP);
+                    let hash = fields.next()?;
+                    let engine = auto_commit_engine(fields.next()?, fields.next().unwrap_or("").trim())?;
+                    Some((hash.to_string(), engine))
+                })
+                .collect();
+        let commits = query_period_commits(&conn, &project_path, since, &auto_engines)?;
+        let agent_commits = resolve_agent_commits(&project_path, &load_agent_spans(&project_path));
+
+        let stats = attribute_commits(&period, &commits, &agent_commits);
+        log::info!(
+            "[GitStats] AI attribution for {}: {}/{} commits",
+            project_path,
+            stats.ai.commits,
+            stats.total.commits
+        );
+        Ok(stats)
+    })
+    .await
+    .map_err(|e| format!("统计 AI 贡献失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_log_and_aggregates_trends() {
+        let log = "\u{1e}aaa\u{1f}Ann\u{1f}ann@x.io\u{1f}1717200000\u{1f}2024-06-01\n\n3\t1\tsrc/a.rs\n-\t-\tlogo.png\n\
+                   \u{1e}bbb\u{1f}Bob\u{1f}bob@x.io\u{1f}1717372800\u{1f}2024-06-03\n\n10\t0\tREADME.md\n\
+                   \u{1e}ccc\u{1f}Ann\u{1f}ann@x.io\u{1f}1717459200\u{1f}2024-06-04\n";
+        let commits = parse_git_log(log);
+        assert_eq!(commits.len(), 3);
+        assert_eq!((commits[0].files_changed, commits[0].lines_added, commits[0].lines_removed), (2, 3, 1));
+        assert_eq!(commits[2].files_changed, 0);
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        store_commits(&mut conn, "/p", "ccc", &commits, true).unwrap();
+        store_commits(&mut conn, "/other", "zzz", &commits[..1], true).unwrap();
+
+        let now = 1717459200 + 3600;
+        let days = query_trends(&conn, "/p", "all", "day", now).unwrap();
+        assert_eq!(days.points.len(), 3);
+        assert_eq!((days.total_commits, days.lines_added, days.lines_removed), (3, 13, 1));
+        assert_eq!(days.head.as_deref(), Some("ccc"));
+        assert_eq!((days.authors[0].email.as_str(), days.authors[0].commits), ("ann@x.io", 2));
+
+        // 2024-06-01 是周六，属于 05-27 那周
+        let weeks = query_trends(&conn, "/p", "all", "week", now).unwrap();
+        assert_eq!(
+            weeks.points.iter().map(|p| (p.bucket.as_str(), p.commits)).collect::<Vec<_>>(),
+            vec![("2024-05-27", 1), ("2024-06-03", 2)]
+        );
+        assert_eq!(query_trends(&conn, "/p", "week", "month", now).unwrap().total_commits, 3);
+
+        // 重建时清掉旧提交
+        store_commits(&mut conn, "/p", "bbb", &commits[1..2], true).unwrap();
+        assert_eq!(query_trends(&conn, "/p", "all", "month", now).unwrap().total_commits, 1);
+        assert!(query_trends(&conn, "/p", "decade", "day", now).is_err());
+    }
+
+    #[test]
+    fn attributes_commits_to_engines() {
+        let commit = |hash: &str, subject: &str, added: usize| PeriodCommit {
+            hash: hash.to_string(),
+            auto_engine: auto_commit_engine(subject, ""),
+            lines_added: added,
+            lines_removed: 1,
+        };
+        let commits = vec![
+            commit("a1", "feat: agent committed itself", 30),
+            commit("a2", "[Codex] After prompt #3", 50),
+            commit("a3", "[Claude Code] After prompt #0", 10),
+            commit("h1", "fix typo", 10),
+        ];
+        let agent_commits = HashMap::from([
+            ("a1".to_string(), ("claude", "s1".to_string())),
+            ("a3".to_string(), ("claude", "s2".to_string())),
+        ]);
+
+        let stats = attribute_commits("all", &commits, &agent_commits);
+        assert_eq!((stats.total.commits, stats.ai.commits, stats.human.commits), (4, 3, 1));
+        assert_eq!((stats.ai.lines_added, stats.human.lines_added), (90, 10));
+        assert!((stats.ai_line_share - 0.9).abs() < f64::EPSILON);
+        let engines: Vec<_> = stats
+            .engines
+            .iter()
+            .map(|e| (e.engine.as_str(), e.totals.commits, e.sessions))
+            .collect();
+        assert_eq!(engines, vec![("codex", 1, 0), ("claude", 2, 2)]);
+
+        assert_eq!(auto_commit_engine("feat: add x", "gemini #2"), Some("gemini"));
+        assert_eq!(auto_commit_engine("feat: add x", ""), None);
+        assert!(same_project("C:\\work\\app\\", "C:/work/app"));
+        assert_eq!(attribute_commits("week", &[], &HashMap::new()).ai_line_share, 0.0);
+    }
+}
@@ -0,0 +1,543 @@
+//! 文件系统护栏
+//!
+//! 按项目配置的文件系统策略（未配置时使用默认策略），在 Rust 侧强制执行（不依赖 CLI 自身的权限系统）：
+//! 执行过程中每次文件写入都会检查目标路径，若写到项目目录之外或命中
+//! 禁止路径（如 `~/.ssh`），立即终止对应会话，或通知前端确认。
+//!
+//! Codex 的变更在变更追踪器记录时检查；Claude / Gemini 没有变更记录，
+//! 由 [`WriteGuard`] 在 stdout 读取任务中解析写文件的工具调用后检查。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use super::project_settings::load_project_settings;
+
+/// 违规后的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViolationAction {
+    /// 立即终止会话
+    #[default]
+    Cancel,
+    /// 仅通知前端，由用户决定是否终止
+    Prompt,
+}
+
+/// 项目的文件系统策略
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsPolicy {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 允许写入项目目录之外的路径
+    #[serde(default)]
+    pub allow_outside_project: bool,
+    /// 项目目录之外额外允许写入的路径（支持 `~/`）
+    #[serde(default = "default_allowed_paths")]
+    pub allowed_paths: Vec<String>,
+    /// 禁止触碰的路径（支持 `~/`，相对路径按项目目录解析）
+    #[serde(default = "default_forbidden_paths")]
+    pub forbidden_paths: Vec<String>,
+    #[serde(default)]
+    pub action: ViolationAction,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Claude 计划模式把计划写到 `~/.claude/plans`
+fn default_allowed_paths() -> Vec<String> {
+    vec!["~/.claude/plans".to_string()]
+}
+
+fn default_forbidden_paths() -> Vec<String> {
+    ["~/.ssh", "~/.aws", "~/.gnupg", "~/.kube", "~/.docker/config.json"]
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+
+impl Default for FsPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_outside_project: false,
+            allowed_paths: default_allowed_paths(),
+            forbidden_paths: default_forbidden_paths(),
+            action: ViolationAction::Cancel,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// 写到了项目目录之外
+    OutsideProject,
+    /// 命中禁止路径
+    ForbiddenPath,
+}
+
+/// 一次策略违规
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyViolation {
+    pub kind: ViolationKind,
+    pub path: String,
+    /// 命中的禁止路径规则
+    pub rule: Option<String>,
+}
+
+/// 词法归一化：统一分隔符、消解 `.` / `..`（不访问文件系统，目标文件可能已被删除）
+fn normalize_for_compare(path: &str) -> String {
+    let unified = path.replace('\\', "/");
+    let absolute = unified.starts_with('/');
+    let mut parts: Vec<&str> = Vec::new();
+    for part in unified.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            other => parts.push(other),
+        }
+    }
+    let joined = parts.join("/");
+    let normalized = if absolute { format!("/{}", joined) } else { joined };
+    if cfg!(target_os = "windows") {
+        normalized.to_lowercase()
+    } else {
+        normalized
+    }
+}
+
+/// 去掉 Windows 规范化路径的 `\\?\` 前缀
+fn strip_verbatim_prefix(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    }
+}
+
+/// 解析符号链接后的归一化路径
+///
+/// 规范化最深的已存在祖先目录，不存在的尾部（新建或已删除的文件）按词法拼接，
+/// 这样经由符号链接目录的写入会按真实位置比较。
+fn resolve_real_path(path: &str) -> String {
+    let lexical = normalize_for_compare(path);
+    if !Path::new(&lexical).is_absolute() {
+        return lexical;
+    }
+    let mut existing = PathBuf::from(&lexical);
+    let mut tail = Vec::new();
+    loop {
+        if let Ok(real) = std::fs::canonicalize(&existing) {
+            let mut resolved = PathBuf::from(strip_verbatim_prefix(&real.to_string_lossy()));
+            resolved.extend(tail.iter().rev());
+            return normalize_for_compare(&resolved.to_string_lossy());
+        }
+        match (existing.file_name(), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                tail.push(name.to_os_string());
+                existing = parent.to_path_buf();
+            }
+            _ => return lexical,
+        }
+    }
+}
+
+fn is_within(path: &str, root: &str) -> bool {
+    path == root || path.starts_with(&format!("{}/", root.trim_end_matches('/')))
+}
+
+/// 把策略中的路径规则解析为归一化的绝对路径
+fn resolve_rule(rule: &str, project_root: &str, home: Option<&str>) -> String {
+    let unified = rule.replace('\\', "/");
+    let expanded = match (unified.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", home, rest)
+        }
+        _ if Path::new(&unified).is_absolute() || unified.starts_with('/') => unified,
+        _ => format!("{}/{}", project_root, unified),
+    };
+    resolve_real_path(&expanded)
+}
+
+/// 检查一个被修改的文件是否违反策略
+///
+/// # Arguments
+/// * `project_root` - 项目根目录（与 `full_path` 同一路径风格）
+/// * `full_path` - 被修改文件的完整路径
+/// * `home` - 用户主目录，用于展开 `~`
+pub fn check_path(
+    policy: &FsPolicy,
+    project_root: &str,
+    full_path: &str,
+    home: Option<&str>,
+) -> Option<PolicyViolation> {
+    if !policy.enabled {
+        return None;
+    }
+
+    let path = resolve_real_path(full_path);
+    let root = resolve_real_path(project_root);
+
+    for rule in &policy.forbidden_paths {
+        if is_within(&path, &resolve_rule(rule, &root, home)) {
+            return Some(PolicyViolation {
+                kind: ViolationKind::ForbiddenPath,
+                path: full_path.to_string(),
+                rule: Some(rule.clone()),
+            });
+        }
+    }
+
+    if policy.allow_outside_project || is_within(&path, &root) {
+        return None;
+    }
+    if policy
+        .allowed_paths
+        .iter()
+        .any(|allowed| is_within(&path, &resolve_rule(allowed, &root, home)))
+    {
+        return None;
+    }
+
+    Some(PolicyViolation {
+        kind: ViolationKind::OutsideProject,
+        path: full_path.to_string(),
+        rule: None,
+    })
+}
+
+fn home_dir() -> Option<String> {
+    dirs::home_dir().map(|h| h.to_string_lossy().to_string())
+}
+
+/// 项目的文件系统策略，未单独配置时使用默认策略
+fn project_fs_policy(project_path: &str) -> FsPolicy {
+    load_project_settings(project_path).fs_policy.unwrap_or_default()
+}
+
+/// 检查记录的文件变更，违规时通知前端并按策略终止会话
+///
+/// # Arguments
+/// * `engine` - "claude" | "codex" | "gemini"
+/// * `session_id` - 变更所属的会话 ID（用于事件通道）
+/// * `project_path` - 项目路径（用于读取策略、定位运行中的进程）
+/// * `project_root` / `full_path` - 同一路径风格的项目根目录与文件完整路径
+pub async fn enforce_file_change(
+    app_handle: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    project_path: &str,
+    project_root: &str,
+    full_path: &str,
+) -> Option<PolicyViolation> {
+    let policy = project_fs_policy(project_path);
+    let violation = check_path(&policy, project_root, full_path, home_dir().as_deref())?;
+    report_violation(app_handle, engine, session_id, project_path, &policy, &violation).await;
+    Some(violation)
+}
+
+/// 记录违规、按策略终止会话并通知前端
+async fn report_violation(
+    app_handle: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    project_path: &str,
+    policy: &FsPolicy,
+    violation: &PolicyViolation,
+) {
+    log::warn!(
+        "[Guardrails] {} session {} violated fs policy ({:?}): {}",
+        engine,
+        session_id,
+        violation.kind,
+        violation.path
+    );
+
+    let cancelled = if policy.action == ViolationAction::Cancel {
+        cancel_runs(app_handle, engine, session_id, project_path).await
+    } else {
+        0
+    };
+
+    let payload = serde_json::json!({
+        "engine": engine,
+        "session_id": session_id,
+        "project_path": project_path,
+        "violation": violation,
+        "action": policy.action,
+        "cancelled": cancelled,
+    });
+    if let Err(e) = app_handle.emit(&format!("guardrail-violation:{}", session_id), &payload) {
+        log::warn!("[Guardrails] Failed to emit guardrail-violation (session-specific): {}", e);
+    }
+    if let Err(e) = app_handle.emit("guardrail-violation", &payload) {
+        log::warn!("[Guardrails] Failed to emit guardrail-violation (global): {}", e);
+    }
+}
+
+/// 终止违规的会话，返回终止数量
+async fn cancel_runs(app_handle: &AppHandle, engine: &str, session_id: &str, project_path: &str) -> usize {
+    let result = match engine {
+        "codex" => return cancel_codex_runs_for_project(app_handle, project_path).await,
+        "claude" => {
+            super::claude::cancel_claude_execution(
+                app_handle.clone(),
+                Some(session_id.to_string()).filter(|s| !s.is_empty()),
+            )
+            .await
+        }
+        "gemini" => super::gemini::cancel_gemini(Some(session_id.to_string()), app_handle.clone()).await,
+        _ => return 0,
+    };
+    match result {
+        Ok(_) => 1,
+        Err(e) => {
+            log::error!("[Guardrails] Failed to cancel {} session {}: {}", engine, session_id, e);
+            0
+        }
+    }
+}
+
+/// 终止该项目下所有正在运行的 Codex 进程，返回终止数量
+///
+/// 变更记录使用 Codex 线程 ID，而进程表以内部执行 ID 为键，
+/// 因此按项目路径从进程登记表中定位。
+async fn cancel_codex_runs_for_project(app_handle: &AppHandle, project_path: &str) -> usize {
+    let project = normalize_for_compare(project_path);
+    let run_ids: Vec<String> = crate::process::orphans::running_processes()
+        .into_iter()
+        .filter(|p| p.engine == "codex" && normalize_for_compare(&p.project_path) == project)
+        .filter_map(|p| p.session_id)
+        .collect();
+
+    let mut cancelled = 0;
+    for run_id in run_ids {
+        match super::codex::cancel_codex(Some(run_id.clone()), app_handle.clone()).await {
+            Ok(_) => cancelled += 1,
+            Err(e) => log::error!("[Guardrails] Failed to cancel Codex run {}: {}", run_id, e),
+        }
+    }
+    cancelled
+}
+
+// ============================================================================
+// 流式输出中的写文件工具调用
+// ============================================================================
+
+/// 从一条流事件中提取写文件工具调用的目标路径（Claude / Gemini）
+fn extract_written_paths(engine: &str, event: &Value) -> Vec<String> {
+    match engine {
+        "claude" => {
+            let Some(blocks) = event["message"]["content"].as_array() else {
+                return Vec::new();
+            };
+            blocks
+                .iter()
+                .filter(|block| block["type"] == "tool_use")
+                .filter_map(|block| match block["name"].as_str() {
+                    Some("Write" | "Edit" | "MultiEdit") => block["input"]["file_path"].as_str(),
+                    Some("NotebookEdit") => block["input"]["notebook_path"].as_str(),
+                    _ => None,
+                })
+                .map(str::to_string)
+                .collect()
+        }
+        "gemini" => match event["tool_name"].as_str() {
+            Some("write_file" | "replace") if event["type"] == "tool_use" => {
+                let params = &event["parameters"];
+                params["file_path"]
+                    .as_str()
+                    .or(params["absolute_path"].as_str())
+                    .map(|p| vec![p.to_string()])
+                    .unwrap_or_default()
+            }
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+struct GuardState {
+    session_id: String,
+    /// 已处理过一次违规（会话已终止或已通知），不再重复上报
+    tripped: bool,
+}
+
+/// 单个进程的写入护栏（在 stdout 读取任务中逐行调用）
+///
+/// CLI 在执行工具前先输出 tool_use 事件，因此违规写入通常能在落盘前被拦下。
+pub struct WriteGuard {
+    app: AppHandle,
+    engine: String,
+    project_path: String,
+    policy: Option<FsPolicy>,
+    state: Mutex<GuardState>,
+}
+
+impl WriteGuard {
+    /// `session_id` 为执行时已知的 ID，收到 CLI 的 init 事件后替换为真实会话 ID
+    pub fn new(app: &AppHandle, engine: &str, session_id: &str, project_path: &str) -> Arc<Self> {
+        Arc::new(Self {
+            app: app.clone(),
+            engine: engine.to_string(),
+            project_path: project_path.to_string(),
+            policy: Some(project_fs_policy(project_path)).filter(|p| p.enabled),
+            state: Mutex::new(GuardState {
+                session_id: session_id.to_string(),
+                tripped: false,
+            }),
+        })
+    }
+
+    /// 处理一行原始流输出
+    pub fn observe(&self, line: &str) {
+        let Some(policy) = &self.policy else {
+            return;
+        };
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.tripped {
+            return;
+        }
+        // Gemini 以内部执行 ID 登记进程，只有 Claude 需要换成真实会话 ID
+        if self.engine == "claude" {
+            if let Some(session_id) = super::command_audit::extract_session_id(&self.engine, &event) {
+                state.session_id = session_id;
+                return;
+            }
+        }
+
+        let home = home_dir();
+        let violation = extract_written_paths(&self.engine, &event).into_iter().find_map(|path| {
+            let full_path = Path::new(&self.project_path).join(&path);
+            check_path(policy, &self.project_path, &full_path.to_string_lossy(), home.as_deref())
+        });
+        let Some(violation) = violation else {
+            return;
+        };
+        state.tripped = true;
+
+        let app = self.app.clone();
+        let engine = self.engine.clone();
+        let session_id = state.session_id.clone();
+        let project_path = self.project_path.clone();
+        let policy = policy.clone();
+        tauri::async_runtime::spawn(async move {
+            report_violation(&app, &engine, &session_id, &project_path, &policy, &violation).await;
+        });
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 默认文件系统策略（供前端初始化表单）
+#[tauri::command]
+pub async fn get_default_fs_policy() -> Result<FsPolicy, String> {
+    Ok(FsPolicy::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_path_flags_outside_and_forbidden_writes() {
+        let policy = FsPolicy {
+            allowed_paths: vec!["/tmp/scratch".to_string()],
+            ..FsPolicy::default()
+        };
+        let home = Some("/home/dev");
+        let root = "/home/dev/app";
+
+        assert_eq!(check_path(&policy, root, "/home/dev/app/src/main.rs", home), None);
+        assert_eq!(check_path(&policy, root, "/tmp/scratch/out.txt", home), None);
+
+        let escaped = check_path(&policy, root, "/home/dev/app/../other/x.rs", home).unwrap();
+        assert_eq!(escaped.kind, ViolationKind::OutsideProject);
+
+        let forbidden = check_path(&policy, root, "/home/dev/.ssh/authorized_keys", home).unwrap();
+        assert_eq!(forbidden.kind, ViolationKind::ForbiddenPath);
+        assert_eq!(forbidden.rule.as_deref(), Some("~/.ssh"));
+
+        // 相似前缀不算在项目内
+        assert!(check_path(&policy, root, "/home/dev/app2/x.rs", home).is_some());
+
+        let disabled = FsPolicy { enabled: false, ..FsPolicy::default() };
+        assert_eq!(check_path(&disabled, root, "/etc/passwd", home), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn check_path_resolves_symlinked_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        let project = home.join("app");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(home.join(".ssh")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, project.join("escape")).unwrap();
+        std::os::unix::fs::symlink(home.join(".ssh"), project.join("keys")).unwrap();
+
+        let policy = FsPolicy::default();
+        let root = project.to_string_lossy().to_string();
+        let home = home.to_string_lossy().to_string();
+        let check = |path: &Path| check_path(&policy, &root, &path.to_string_lossy(), Some(&home));
+
+        let escaped = check(&project.join("escape/new/file.txt")).unwrap();
+        assert_eq!(escaped.kind, ViolationKind::OutsideProject);
+
+        let forbidden = check(&project.join("keys/authorized_keys")).unwrap();
+        assert_eq!(forbidden.kind, ViolationKind::ForbiddenPath);
+
+        // 不存在的尾部（新建或已删除的文件）仍按项目内处理
+        assert_eq!(check(&project.join("src/deleted/lib.rs")), None);
+    }
+
+    #[test]
+    fn extracts_written_paths_from_claude_and_gemini_tool_calls() {
+        let claude: Value = serde_json::json!({
+            "type": "assistant",
+            "message": {"content": [
+                {"type": "text", "text": "writing"},
+                {"type": "tool_use", "id": "t1", "name": "Write", "input": {"file_path": "/home/dev/.ssh/config"}},
+                {"type": "tool_use", "id": "t2", "name": "Read", "input": {"file_path": "/etc/passwd"}},
+                {"type": "tool_use", "id": "t3", "name": "NotebookEdit", "input": {"notebook_path": "nb.ipynb"}}
+            ]}
+        });
+        assert_eq!(
+            extract_written_paths("claude", &claude),
+            vec!["/home/dev/.ssh/config".to_string(), "nb.ipynb".to_string()]
+        );
+
+        let gemini: Value = serde_json::json!({
+            "type": "tool_use",
+            "tool_name": "replace",
+            "tool_id": "g1",
+            "parameters": {"file_path": "src/lib.rs", "old_string": "a", "new_string": "b"}
+        });
+        assert_eq!(extract_written_paths("gemini", &gemini), vec!["src/lib.rs".to_string()]);
+
+        let shell: Value = serde_json::json!({
+            "type": "tool_use",
+            "tool_name": "run_shell_command",
+            "parameters": {"command": "ls"}
+        });
+        assert!(extract_written_paths("gemini", &shell).is_empty());
+        assert!(extract_written_paths("codex", &claude).is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! 一次性（headless）引擎执行
+//!
+//! 供后端编排功能（流水线、多引擎对比等）使用：以新会话运行引擎 CLI，
+//! 不向前端推送流式输出，只收集最终回复和 token 用量。
+//! 命令由各引擎的 `build_headless_*_command` 构建，因此项目默认值、
+//! 权限档案、代理商和 Docker 容器等设置与正常执行一致。
+
+use crate::tr;
+use serde::Serialize;
+use serde_json::Value;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+use super::claude::apply_no_window_async;
+
+/// 失败时附带的 stderr 最大长度
+const MAX_STDERR_CHARS: usize = 2000;
+
+/// 一次执行请求
+#[derive(Debug, Clone)]
+pub struct HeadlessRequest {
+    /// "claude" | "codex" | "gemini"
+    pub engine: String,
+    /// 执行目录（同时用于读取项目默认值）
+    pub project_path: String,
+    pub prompt: String,
+    /// 为空时使用项目默认值
+    pub model: Option<String>,
+    /// 为空时使用项目默认值
+    pub permission_profile: Option<String>,
+    /// 后端内部的辅助执行（会话总结、记忆提取、压缩、提交信息）：不记录提示词历史、
+    /// 不翻译、不注入项目记忆和系统提示词；Codex 跳过 git 仓库检查
+    pub internal: bool,
+}
+
+/// Token 用量（各引擎的字段统一后）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_input_tokens: u64,
+}
+
+/// 一次执行的结果
+#[derive(Debug, Clone, Default)]
+pub struct HeadlessOutput {
+    /// 引擎的最终回复
+    pub text: Option<String>,
+    pub usage: TokenUsage,
+}
+
+/// 从引擎的流式输出中收集最终回复和用量
+#[derive(Debug, Default)]
+struct OutputCollector {
+    engine: String,
+    text: String,
+    usage: TokenUsage,
+}
+
+fn u64_field(value: &Value, key: &str) -> u64 {
+    value[key].as_u64().unwrap_or(0)
+}
+
+impl OutputCollector {
+    fn new(engine: &str) -> Self {
+        Self { engine: engine.to_string(), ..Default::default() }
+    }
+
+    fn observe(&mut self, line: &str) {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+        match (self.engine.as_str(), event["type"].as_str()) {
+            // 最后一条 agent_message
+            ("codex", Some("item.completed")) if event["item"]["type"] == "agent_message" => {
+                self.text = event["item"]["text"].as_str().unwrap_or_default().to_string();
+            }
+            // 每轮结束时报告该轮用量
+            ("codex", Some("turn.completed")) => {
+                let usage = &event["usage"];
+                self.usage.input_tokens += u64_field(usage, "input_tokens");
+                self.usage.output_tokens += u64_field(usage, "output_tokens");
+                self.usage.cached_input_tokens += u64_field(usage, "cached_input_tokens");
+            }
+            // result 事件携带最终回复和总用量
+            ("claude", Some("result")) => {
+                self.text = event["result"].as_str().unwrap_or_default().to_string();
+                let usage = &event["usage"];
+                self.usage = TokenUsage {
+                    input_tokens: u64_field(usage, "input_tokens"),
+                    output_tokens: u64_field(usage, "output_tokens"),
+                    cached_input_tokens: u64_field(usage, "cache_read_input_tokens"),
+                };
+            }
+            // 最后一次工具调用之后的 assistant 消息（可能分多段 delta）
+            ("gemini", Some("tool_use")) => self.text.clear(),
+            ("gemini", Some("message")) if event["role"] == "assistant" => {
+                if event["delta"].as_bool() != Some(true) {
+                    self.text.clear();
+                }
+                self.text.push_str(event["content"].as_str().unwrap_or_default());
+            }
+            ("gemini", Some("result")) => {
+                let stats = &event["stats"];
+                self.usage.input_tokens = u64_field(stats, "input_tokens");
+                self.usage.output_tokens = u64_field(stats, "output_tokens");
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> HeadlessOutput {
+        let text = self.text.trim();
+        HeadlessOutput {
+            text: (!text.is_empty()).then(|| text.to_string()),
+            usage: self.usage,
+        }
+    }
+}
+
+/// 执行一次并等待结束
+///
+/// `pid_slot` 在进程运行期间保存其 PID，调用方可据此中途终止进程树。
+pub async fn run_headless(
+    app_handle: &AppHandle,
+    request: &HeadlessRequest,
+    pid_slot: &Mutex<Option<u32>>,
+) -> Result<HeadlessOutput, String> {
+    let project_path = request.project_path.clone();
+    let model = request.model.clone();
+    let profile = request.permission_profile.clone();
+    let prompt = request.prompt.clone();
+    let internal = request.internal;
+    let (mut cmd, stdin_prompt) = match request.engine.as_str() {
+        "claude" => {
+            let (cmd, prompt) = super::claude::build_headless_claude_command(
+                app_handle,
+                project_path,
+                prompt,
+                model,
+                profile,
+                internal,
+            )
+            .await?;
+            (cmd, Some(prompt))
+        }
+        "codex" => super::codex::build_headless_codex_command(project_path, prompt, model, profile, internal).await?,
+        "gemini" => {
+            let (cmd, prompt) =
+                super::gemini::build_headless_gemini_command(project_path, prompt, model, profile, internal).await?;
+            (cmd, Some(prompt))
+        }
+        other => return Err(tr!("engine.unsupported", engine = other)),
+    };
+
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    apply_no_window_async(&mut cmd);
+
+    let engine = request.engine.as_str();
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("启动 {} 失败: {}", engine, e))?;
+    let pid = child.id();
+    if let Some(pid) = pid {
+        crate::process::orphans::track_process(pid, engine, None, &request.project_path);
+    }
+    *pid_slot.lock().unwrap() = pid;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(prompt) = stdin_prompt {
+            if let Err(e) = stdin.write_all(prompt.as_bytes()).await {
+                log::error!("[Headless] Failed to write prompt to {} stdin: {}", engine, e);
+            }
+        }
+        drop(stdin);
+    }
+
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let mut stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf).await;
+        buf
+    });
+
+    let mut collector = OutputCollector::new(engine);
+    let mut reader = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = reader.next_line().await {
+        collector.observe(&line);
+    }
+    let status = child.wait().await;
+    let stderr_output = stderr_task.await.unwrap_or_default();
+
+    *pid_slot.lock().unwrap() = None;
+    if let Some(pid) = pid {
+        crate::process::orphans::untrack_process(pid);
+    }
+
+    let status = status.map_err(|e| format!("等待 {} 结束失败: {}", engine, e))?;
+    if !status.success() {
+        let tail: String = stderr_output
+            .chars()
+            .rev()
+            .take(MAX_STDERR_CHARS)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        return Err(format!("{} 退出码 {:?}: {}", engine, status.code(), tail.trim()));
+    }
+    Ok(collector.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_final_reply_and_usage_per_engine() {
+        let mut codex = OutputCollector::new("codex");
+        codex.observe(r#"{"type":"item.completed","item":{"type":"agent_message","text":"thinking"}}"#);
+        codex.observe(r#"{"type":"item.completed","item":{"type":"command_execution","command":"ls"}}"#);
+        codex.observe(r#"{"type":"item.completed","item":{"type":"agent_message","text":"done"}}"#);
+        codex.observe(r#"{"type":"turn.completed","usage":{"input_tokens":120,"cached_input_tokens":100,"output_tokens":30}}"#);
+        let codex = codex.finish();
+        assert_eq!(codex.text.as_deref(), Some("done"));
+        assert_eq!(
+            codex.usage,
+            TokenUsage { input_tokens: 120, output_tokens: 30, cached_input_tokens: 100 }
+        );
+
+        let mut gemini = OutputCollector::new("gemini");
+        gemini.observe(r#"{"type":"message","role":"assistant","content":"Let me look","delta":true}"#);
+        gemini.observe(r#"{"type":"tool_use","tool_name":"read_file"}"#);
+        gemini.observe(r#"{"type":"message","role":"assistant","content":"Plan: ","delta":true}"#);
+        gemini.observe(r#"{"type":"message","role":"assistant","content":"add cache","delta":true}"#);
+        gemini.observe(r#"{"type":"result","status":"success","stats":{"input_tokens":50,"output_tokens":8}}"#);
+        let gemini = gemini.finish();
+        assert_eq!(gemini.text.as_deref(), Some("Plan: add cache"));
+        assert_eq!(gemini.usage.output_tokens, 8);
+
+        let mut claude = OutputCollector::new("claude");
+        claude.observe("not json");
+        assert_eq!(claude.finish().text, None);
+    }
+}
@@ -0,0 +1,203 @@
+//! 无界面 CLI 伴随模式（`--headless`）
+//!
+//! 以 `any-code --headless` 启动时不创建窗口，而是在 stdin/stdout 上按行收发 JSON，
+//! 便于脚本复用 AnyCode 的引擎编排逻辑（项目默认值、权限档案、代理商等与界面执行一致）。
+//!
+//! 请求：`{"id": 1, "command": "execute", "params": {...}}`
+//! 响应：`{"id": 1, "ok": true, "result": ...}` 或 `{"id": 1, "ok": false, "error": "..."}`
+//!
+//! 支持的命令：
+//! - `execute` `{engine, projectPath, prompt, model?, permissionProfile?}` → `{text, usage}`
+//! - `list_sessions` `{engine, projectPath, filter?}` → 会话列表
+//! - `export_patch` `{sessionId, format?, outputPath?}` → 补丁文本，或写入文件后返回路径
+//!
+//! 请求并发处理，响应顺序可能与请求不同，以 `id` 对应。stdin 关闭后等待进行中的请求完成再退出。
+//! 日志写到 stderr，stdout 只输出协议消息。
+
+use crate::tr;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use super::codex::change_tracker::PatchFormat;
+use super::headless::{run_headless, HeadlessRequest};
+use super::session_metadata::SessionFilter;
+
+/// 一行请求
+#[derive(Debug, Deserialize)]
+struct CliRequest {
+    #[serde(default)]
+    id: Value,
+    command: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecuteParams {
+    engine: String,
+    project_path: String,
+    prompt: String,
+    model: Option<String>,
+    permission_profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListSessionsParams {
+    engine: String,
+    project_path: String,
+    filter: Option<SessionFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportPatchParams {
+    session_id: String,
+    format: Option<PatchFormat>,
+    output_path: Option<String>,
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, String> {
+    serde_json::from_value(params).map_err(|e| format!("参数无效: {}", e))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+/// 构造一行响应
+fn response(id: Value, result: Result<Value, String>) -> Value {
+    match result {
+        Ok(result) => json!({ "id": id, "ok": true, "result": result }),
+        Err(error) => json!({ "id": id, "ok": false, "error": error }),
+    }
+}
+
+async fn dispatch(app: &AppHandle, command: &str, params: Value) -> Result<Value, String> {
+    match command {
+        "execute" => {
+            let params: ExecuteParams = parse_params(params)?;
+            let request = HeadlessRequest {
+                engine: params.engine,
+                project_path: params.project_path,
+                prompt: params.prompt,
+                model: params.model,
+                permission_profile: params.permission_profile,
+                internal: false,
+            };
+            let output = run_headless(app, &request, &Mutex::new(None)).await?;
+            Ok(json!({ "text": output.text, "usage": output.usage }))
+        }
+        "list_sessions" => {
+            let params: ListSessionsParams = parse_params(params)?;
+            match params.engine.as_str() {
+                "claude" => {
+                    let project_id = super::claude::encode_project_path(&params.project_path);
+                    to_value(super::claude::get_project_sessions(project_id, params.filter).await?)
+                }
+                "codex" => to_value(super::codex::list_codex_sessions_for_project(params.project_path, params.filter).await?),
+                "gemini" => to_value(super::gemini::list_gemini_sessions(params.project_path, params.filter).await?),
+                other => Err(tr!("engine.unsupported", engine = other)),
+            }
+        }
+        "export_patch" => {
+            let params: ExportPatchParams = parse_params(params)?;
+            match params.output_path {
+                Some(output_path) => to_value(
+                    super::codex::codex_export_patch(params.session_id, output_path, params.format).await?,
+                ),
+                None => to_value(super::codex::change_tracker::export_session_patch(
+                    &params.session_id,
+                    params.format.unwrap_or_default(),
+                )?),
+            }
+        }
+        other => Err(format!("未知命令: {}", other)),
+    }
+}
+
+/// 处理一行输入，返回对应的响应
+async fn handle_line(app: &AppHandle, line: &str) -> Value {
+    match serde_json::from_str::<CliRequest>(line) {
+        Ok(request) => {
+            log::info!("[HeadlessCli] {} (id {})", request.command, request.id);
+            let result = dispatch(app, &request.command, request.params).await;
+            response(request.id, result)
+        }
+        Err(e) => response(Value::Null, Err(format!("无效的请求: {}", e))),
+    }
+}
+
+/// 启动 stdin/stdout 循环；stdin 关闭且请求处理完后退出应用
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+
+        // 所有响应经由同一个写任务输出，避免多行交错
+        let writer = tokio::spawn(async move {
+            let mut stdout = tokio::io::stdout();
+            while let Some(message) = rx.recv().await {
+                let line = format!("{}\n", message);
+                if stdout.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+                let _ = stdout.flush().await;
+            }
+        });
+
+        let _ = tx.send(json!({ "event": "ready", "version": env!("CARGO_PKG_VERSION") }));
+
+        let mut tasks = tokio::task::JoinSet::new();
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let app = app.clone();
+            let tx = tx.clone();
+            tasks.spawn(async move {
+                let _ = tx.send(handle_line(&app, &line).await);
+            });
+        }
+
+        while tasks.join_next().await.is_some() {}
+        drop(tx);
+        let _ = writer.await;
+
+        log::info!("[HeadlessCli] stdin closed, exiting");
+        app.exit(0);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_and_responses_use_the_line_protocol() {
+        let request: CliRequest =
+            serde_json::from_str(r#"{"id":"a1","command":"export_patch","params":{"sessionId":"s1","format":"idea"}}"#)
+                .unwrap();
+        assert_eq!(request.id, json!("a1"));
+        let params: ExportPatchParams = parse_params(request.params).unwrap();
+        assert_eq!(params.session_id, "s1");
+        assert!(matches!(params.format, Some(PatchFormat::Idea)));
+        assert!(params.output_path.is_none());
+
+        assert!(parse_params::<ExecuteParams>(json!({ "engine": "codex" })).is_err());
+
+        assert_eq!(
+            response(json!(7), Ok(json!(["x"]))),
+            json!({ "id": 7, "ok": true, "result": ["x"] })
+        );
+        assert_eq!(
+            response(Value::Null, Err("boom".into())),
+            json!({ "id": null, "ok": false, "error": "boom" })
+        );
+    }
+}
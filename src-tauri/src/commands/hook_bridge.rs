@@ -0,0 +1,522 @@
+//! Claude Code hook 事件桥接
+//!
+//! 在用户级 settings.json 中安装一个随附的 hook 脚本（`~/.anycode/hooks/anycode-hook-bridge.sh`），
+//! Claude Code 触发 PreToolUse / PostToolUse / Stop / Notification 时，脚本把 stdin 中的事件 JSON
+//! 通过 curl 发到 AnyCode 在 `127.0.0.1` 上监听的端口（端口和 token 每次启动写入
+//! `~/.anycode/hook_bridge.json`）。AnyCode 未运行时脚本静默退出，不影响 Claude Code。
+//!
+//! 收到的事件按会话追加到 `~/.anycode/hook_events/{session_id}.jsonl`，并重新发送为
+//! `claude-hook-event:{session_id}` / `claude-hook-event` 事件，因此在 AnyCode 之外启动的
+//! 会话同样可以触发通知和变更跟踪。
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use super::anycode_mcp_server::{parse_request_head, write_response};
+use super::claude::get_claude_dir;
+use super::data_root::anycode_dir;
+
+/// 转发的 hook 事件
+const BRIDGED_EVENTS: &[&str] = &["PreToolUse", "PostToolUse", "Stop", "Notification"];
+
+/// settings.json 中识别桥接 hook 的标记（脚本文件名）
+const SCRIPT_NAME: &str = "anycode-hook-bridge.sh";
+
+/// 请求体上限
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+const DEFAULT_EVENT_LIMIT: usize = 200;
+
+/// 桥接收到的一条 hook 事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookBridgeEvent {
+    pub session_id: String,
+    /// hook_event_name，如 PostToolUse
+    pub event: String,
+    pub tool_name: Option<String>,
+    /// 工具操作的文件（Edit / Write / MultiEdit / NotebookEdit）
+    pub file_path: Option<String>,
+    pub cwd: Option<String>,
+    pub received_at: String,
+    /// Claude Code 传给 hook 的原始 JSON
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookBridgeStatus {
+    /// settings.json 中已安装桥接 hook
+    pub installed: bool,
+    pub running: bool,
+    pub port: Option<u16>,
+    pub script_path: String,
+}
+
+/// 写给 hook 脚本读取的连接信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BridgeEndpoint {
+    port: u16,
+    token: String,
+}
+
+struct RunningBridge {
+    endpoint: BridgeEndpoint,
+    task: JoinHandle<()>,
+}
+
+static BRIDGE: Lazy<Mutex<Option<RunningBridge>>> = Lazy::new(|| Mutex::new(None));
+
+fn endpoint_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("hook_bridge.json"))
+}
+
+fn script_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("hooks").join(SCRIPT_NAME))
+}
+
+fn events_path(session_id: &str) -> Result<PathBuf, String> {
+    // 会话 ID 来自外部输入，只保留安全字符作为文件名
+    let name: String = session_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if name.is_empty() {
+        return Err(format!("无效的会话 ID: {}", session_id));
+    }
+    Ok(anycode_dir()?.join("hook_events").join(format!("{}.jsonl", name)))
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(get_claude_dir().map_err(|e| e.to_string())?.join("settings.json"))
+}
+
+/// bash 中使用的路径（Windows 下 Git Bash 接受正斜杠）
+fn bash_path(path: &std::path::Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// 随附的 hook 脚本：读取连接信息，把 stdin 原样 POST 给 AnyCode，任何失败都以 0 退出
+fn bridge_script(endpoint_file: &str) -> String {
+    format!(
+        r#"#!/usr/bin/env bash
+# AnyCode hook bridge: forwards Claude Code hook events to AnyCode.
+# Installed and managed by AnyCode; exits silently when AnyCode is not running.
+ENDPOINT_FILE="{endpoint_file}"
+[ -f "$ENDPOINT_FILE" ] || exit 0
+PORT=$(sed -n 's/.*"port": *\([0-9][0-9]*\).*/\1/p' "$ENDPOINT_FILE")
+TOKEN=$(sed -n 's/.*"token": *"\([^"]*\)".*/\1/p' "$ENDPOINT_FILE")
+[ -n "$PORT" ] || exit 0
+curl -s -m 2 -X POST \
+  -H "Authorization: Bearer $TOKEN" \
+  -H "Content-Type: application/json" \
+  --data-binary @- "http://127.0.0.1:$PORT/hook" >/dev/null 2>&1
+exit 0
+"#
+    )
+}
+
+fn is_bridge_hook(hook: &Value) -> bool {
+    hook.get("command")
+        .and_then(|c| c.as_str())
+        .is_some_and(|c| c.contains(SCRIPT_NAME))
+}
+
+/// 从 hooks 配置中移除桥接 hook，返回是否有改动
+fn remove_bridge_hooks(hooks: &mut Value) -> bool {
+    let Some(events) = hooks.as_object_mut() else {
+        return false;
+    };
+    let mut changed = false;
+    for groups in events.values_mut() {
+        let Some(groups) = groups.as_array_mut() else {
+            continue;
+        };
+        for group in groups.iter_mut() {
+            if let Some(commands) = group.get_mut("hooks").and_then(|h| h.as_array_mut()) {
+                let before = commands.len();
+                commands.retain(|hook| !is_bridge_hook(hook));
+                changed |= commands.len() != before;
+            }
+        }
+        groups.retain(|group| group.get("hooks").and_then(|h| h.as_array()).is_none_or(|h| !h.is_empty()));
+    }
+    events.retain(|_, groups| groups.as_array().is_none_or(|g| !g.is_empty()));
+    changed
+}
+
+/// 为每个转发事件添加桥接 hook（已存在的先移除，保证只有一份）
+fn add_bridge_hooks(hooks: &mut Value, command: &str) {
+    remove_bridge_hooks(hooks);
+    if !hooks.is_object() {
+        *hooks = serde_json::json!({});
+    }
+    for event in BRIDGED_EVENTS {
+        let mut group = serde_json::json!({
+            "hooks": [{ "type": "command", "command": command, "timeout": 5 }]
+        });
+        if event.ends_with("ToolUse") {
+            group["matcher"] = serde_json::json!("*");
+        }
+        let groups = hooks
+            .as_object_mut()
+            .map(|events| events.entry(event.to_string()).or_insert_with(|| serde_json::json!([])));
+        if let Some(groups) = groups.and_then(|g| g.as_array_mut()) {
+            groups.push(group);
+        }
+    }
+}
+
+fn read_settings() -> Result<Value, String> {
+    let path = settings_path()?;
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))
+}
+
+fn write_settings(settings: &Value) -> Result<(), String> {
+    let path = settings_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+fn is_installed() -> bool {
+    read_settings()
+        .ok()
+        .and_then(|settings| settings.get("hooks").cloned())
+        .and_then(|hooks| {
+            hooks.as_object().map(|events| {
+                events
+                    .values()
+                    .filter_map(|groups| groups.as_array())
+                    .flatten()
+                    .filter_map(|group| group.get("hooks").and_then(|h| h.as_array()))
+                    .flatten()
+                    .any(is_bridge_hook)
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// 把 hook payload 转成桥接事件；缺少会话 ID 或事件名时返回 None
+fn parse_hook_payload(payload: Value) -> Option<HookBridgeEvent> {
+    let session_id = payload.get("session_id")?.as_str()?.to_string();
+    let event = payload.get("hook_event_name")?.as_str()?.to_string();
+    let str_field = |key: &str| payload.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let file_path = payload
+        .get("tool_input")
+        .and_then(|input| input.get("file_path").or_else(|| input.get("notebook_path")))
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    Some(HookBridgeEvent {
+        tool_name: str_field("tool_name"),
+        cwd: str_field("cwd"),
+        file_path,
+        session_id,
+        event,
+        received_at: Utc::now().to_rfc3339(),
+        payload,
+    })
+}
+
+fn record_event(event: &HookBridgeEvent) -> Result<(), String> {
+    let path = events_path(&event.session_id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn dispatch_event(app: &AppHandle, payload: Value) {
+    let Some(event) = parse_hook_payload(payload) else {
+        log::debug!("[HookBridge] Ignoring payload without session_id / hook_event_name");
+        return;
+    };
+    if let Err(e) = record_event(&event) {
+        log::warn!("[HookBridge] Failed to record event: {}", e);
+    }
+    if let Err(e) = app.emit(&format!("claude-hook-event:{}", event.session_id), &event) {
+        log::error!("Failed to emit claude-hook-event (session-specific): {}", e);
+    }
+    if let Err(e) = app.emit("claude-hook-event", &event) {
+        log::error!("Failed to emit claude-hook-event (global): {}", e);
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, token: String, app: AppHandle) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > 64 * 1024 {
+            return write_response(&mut stream, "431 Request Header Fields Too Large", None).await;
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let Some(request) = parse_request_head(&String::from_utf8_lossy(&buffer[..head_end])) else {
+        return write_response(&mut stream, "400 Bad Request", None).await;
+    };
+    if request.path != "/hook" {
+        return write_response(&mut stream, "404 Not Found", None).await;
+    }
+    if request.headers.get("authorization").map(String::as_str) != Some(format!("Bearer {}", token).as_str()) {
+        return write_response(&mut stream, "401 Unauthorized", None).await;
+    }
+    if request.method != "POST" {
+        return write_response(&mut stream, "405 Method Not Allowed", None).await;
+    }
+
+    let content_length: usize = request
+        .headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return write_response(&mut stream, "413 Payload Too Large", None).await;
+    }
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    // 先应答，hook 脚本不必等待事件处理
+    let payload = serde_json::from_slice::<Value>(&body);
+    write_response(&mut stream, "202 Accepted", None).await?;
+    match payload {
+        Ok(payload) => dispatch_event(&app, payload),
+        Err(e) => log::debug!("[HookBridge] Invalid payload: {}", e),
+    }
+    Ok(())
+}
+
+/// 启动监听并写入连接信息（已在运行时直接返回端口）
+async fn start_bridge(app: AppHandle) -> Result<u16, String> {
+    if let Some(running) = BRIDGE.lock().unwrap().as_ref() {
+        return Ok(running.endpoint.port);
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .map_err(|e| format!("绑定端口失败: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let endpoint = BridgeEndpoint {
+        port,
+        token: uuid::Uuid::new_v4().simple().to_string(),
+    };
+
+    let server_token = endpoint.token.clone();
+    let task = tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("[HookBridge] Accept failed: {}", e);
+                    continue;
+                }
+            };
+            let token = server_token.clone();
+            let app = app.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, token, app).await {
+                    log::debug!("[HookBridge] Connection error: {}", e);
+                }
+            });
+        }
+    });
+
+    let mut bridge = BRIDGE.lock().unwrap();
+    if let Some(running) = bridge.as_ref() {
+        // 并发启动时保留先完成的那个
+        task.abort();
+        return Ok(running.endpoint.port);
+    }
+    let path = endpoint_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&endpoint).map_err(|e| e.to_string())?;
+    if let Err(e) = fs::write(&path, content) {
+        task.abort();
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
+    log::info!("[HookBridge] Listening on 127.0.0.1:{}", port);
+    *bridge = Some(RunningBridge { endpoint, task });
+    Ok(port)
+}
+
+fn stop_bridge() {
+    if let Some(running) = BRIDGE.lock().unwrap().take() {
+        running.task.abort();
+        if let Ok(path) = endpoint_path() {
+            let _ = fs::remove_file(path);
+        }
+        log::info!("[HookBridge] Stopped listener on port {}", running.endpoint.port);
+    }
+}
+
+/// 应用启动时调用：已安装桥接 hook 时开始监听
+pub async fn start_if_installed(app: AppHandle) {
+    if !is_installed() {
+        return;
+    }
+    if let Err(e) = start_bridge(app).await {
+        log::warn!("[HookBridge] Failed to start: {}", e);
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get whether the hook bridge is installed in settings.json and listening
+#[tauri::command]
+pub async fn get_hook_bridge_status() -> Result<HookBridgeStatus, String> {
+    let port = BRIDGE.lock().unwrap().as_ref().map(|running| running.endpoint.port);
+    Ok(HookBridgeStatus {
+        installed: is_installed(),
+        running: port.is_some(),
+        port,
+        script_path: script_path()?.to_string_lossy().to_string(),
+    })
+}
+
+/// Install the bridge script into ~/.claude/settings.json and start listening
+#[tauri::command]
+pub async fn install_hook_bridge(app: AppHandle) -> Result<HookBridgeStatus, String> {
+    let script = script_path()?;
+    if let Some(parent) = script.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&script, bridge_script(&bash_path(&endpoint_path()?)))
+        .map_err(|e| format!("Failed to write {}: {}", script.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&script, fs::Permissions::from_mode(0o755));
+    }
+
+    let mut settings = read_settings()?;
+    let mut hooks = settings.get("hooks").cloned().unwrap_or_else(|| serde_json::json!({}));
+    add_bridge_hooks(&mut hooks, &format!("bash \"{}\"", bash_path(&script)));
+    settings["hooks"] = hooks;
+    write_settings(&settings)?;
+    log::info!("[HookBridge] Installed bridge hooks for {:?}", BRIDGED_EVENTS);
+
+    start_bridge(app).await?;
+    get_hook_bridge_status().await
+}
+
+/// Remove the bridge hooks from ~/.claude/settings.json and stop listening
+#[tauri::command]
+pub async fn uninstall_hook_bridge() -> Result<HookBridgeStatus, String> {
+    let mut settings = read_settings()?;
+    if let Some(hooks) = settings.get_mut("hooks") {
+        if remove_bridge_hooks(hooks) {
+            if hooks.as_object().is_some_and(|events| events.is_empty()) {
+                if let Some(map) = settings.as_object_mut() {
+                    map.remove("hooks");
+                }
+            }
+            write_settings(&settings)?;
+        }
+    }
+    stop_bridge();
+    if let Ok(script) = script_path() {
+        let _ = fs::remove_file(script);
+    }
+    log::info!("[HookBridge] Uninstalled bridge hooks");
+    get_hook_bridge_status().await
+}
+
+/// List hook events recorded for a session, oldest first (last `limit`, default 200)
+#[tauri::command]
+pub async fn list_hook_bridge_events(session_id: String, limit: Option<usize>) -> Result<Vec<HookBridgeEvent>, String> {
+    let path = events_path(&session_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let events: Vec<HookBridgeEvent> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let limit = limit.unwrap_or(DEFAULT_EVENT_LIMIT);
+    Ok(events[events.len().saturating_sub(limit)..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installs_and_removes_bridge_hooks() {
+        let mut hooks = serde_json::json!({
+            "PreToolUse": [{ "matcher": "Bash", "hooks": [{ "type": "command", "command": "./lint.sh" }] }]
+        });
+        let command = "bash \"/home/u/.anycode/hooks/anycode-hook-bridge.sh\"";
+        add_bridge_hooks(&mut hooks, command);
+        add_bridge_hooks(&mut hooks, command);
+        assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 2);
+        assert_eq!(hooks["PreToolUse"][1]["matcher"], "*");
+        assert!(hooks["Stop"][0].get("matcher").is_none());
+        assert_eq!(hooks["Notification"][0]["hooks"][0]["command"], command);
+
+        assert!(remove_bridge_hooks(&mut hooks));
+        assert!(!remove_bridge_hooks(&mut hooks));
+        assert_eq!(hooks, serde_json::json!({
+            "PreToolUse": [{ "matcher": "Bash", "hooks": [{ "type": "command", "command": "./lint.sh" }] }]
+        }));
+
+        let event = parse_hook_payload(serde_json::json!({
+            "session_id": "abc-123",
+            "hook_event_name": "PostToolUse",
+            "cwd": "/repo",
+            "tool_name": "Edit",
+            "tool_input": { "file_path": "/repo/src/main.rs", "old_string": "a", "new_string": "b" }
+        }))
+        .unwrap();
+        assert_eq!(event.event, "PostToolUse");
+        assert_eq!(event.tool_name.as_deref(), Some("Edit"));
+        assert_eq!(event.file_path.as_deref(), Some("/repo/src/main.rs"));
+        assert!(parse_hook_payload(serde_json::json!({ "hook_event_name": "Stop" })).is_none());
+        assert!(events_path("../../etc").unwrap().ends_with("etc.jsonl"));
+        assert!(events_path("..").is_err());
+
+        let script = bridge_script("/home/u/.anycode/hook_bridge.json");
+        assert!(script.contains("ENDPOINT_FILE=\"/home/u/.anycode/hook_bridge.json\""));
+        assert!(script.contains("http://127.0.0.1:$PORT/hook"));
+    }
+}
@@ -0,0 +1,373 @@
+//! 全局快捷键
+//!
+//! 在应用不在前台时也能响应的系统级快捷键：
+//! - `quick_capture`：弹出/隐藏一个置顶的小窗口，输入提示词后直接在当前项目新建会话执行
+//! - `rerun_last_prompt`：在当前活动项目中重跑最近一次发送的提示词
+//!
+//! 绑定保存在 ~/.anycode/hotkeys.json，启动时注册所有已启用的绑定。
+//! 当前活动项目由前端在切换标签页时通过 `set_active_project` 上报。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use super::data_root::anycode_dir;
+
+/// 快速输入窗口的 label
+pub const QUICK_CAPTURE_LABEL: &str = "quick-capture";
+
+/// 快捷键对应的动作
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    QuickCapture,
+    RerunLastPrompt,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 2] = [HotkeyAction::QuickCapture, HotkeyAction::RerunLastPrompt];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HotkeyAction::QuickCapture => "quick_capture",
+            HotkeyAction::RerunLastPrompt => "rerun_last_prompt",
+        }
+    }
+
+    fn default_binding(&self) -> HotkeyBinding {
+        match self {
+            HotkeyAction::QuickCapture => HotkeyBinding {
+                action: *self,
+                shortcut: "CommandOrControl+Shift+Space".to_string(),
+                enabled: true,
+            },
+            HotkeyAction::RerunLastPrompt => HotkeyBinding {
+                action: *self,
+                shortcut: "CommandOrControl+Shift+R".to_string(),
+                enabled: false,
+            },
+        }
+    }
+}
+
+/// 单个快捷键绑定
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyBinding {
+    pub action: HotkeyAction,
+    /// 如 "CommandOrControl+Shift+Space"
+    pub shortcut: String,
+    pub enabled: bool,
+}
+
+/// 返回给前端的绑定状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyBindingStatus {
+    #[serde(flatten)]
+    pub binding: HotkeyBinding,
+    /// 是否已成功向系统注册（可能被其他应用占用）
+    pub registered: bool,
+}
+
+/// 当前活动项目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveProject {
+    pub project_path: String,
+    pub engine: Option<String>,
+}
+
+static ACTIVE_PROJECT: Lazy<Mutex<Option<ActiveProject>>> = Lazy::new(|| Mutex::new(None));
+
+/// 快捷键触发事件载荷
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HotkeyTriggered {
+    action: HotkeyAction,
+    project_path: Option<String>,
+    error: Option<String>,
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("hotkeys.json"))
+}
+
+/// 读取绑定；缺失的动作补上默认值
+pub fn load_hotkey_bindings() -> Vec<HotkeyBinding> {
+    let saved: Vec<HotkeyBinding> = get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    merge_with_defaults(saved)
+}
+
+fn merge_with_defaults(saved: Vec<HotkeyBinding>) -> Vec<HotkeyBinding> {
+    HotkeyAction::ALL
+        .iter()
+        .map(|action| {
+            saved
+                .iter()
+                .find(|b| b.action == *action)
+                .cloned()
+                .unwrap_or_else(|| action.default_binding())
+        })
+        .collect()
+}
+
+fn save_hotkey_bindings(bindings: &[HotkeyBinding]) -> Result<(), String> {
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(bindings)
+        .map_err(|e| format!("Failed to serialize hotkey bindings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write hotkeys.json: {}", e))
+}
+
+fn parse_shortcut(value: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(value.trim()).map_err(|e| format!("无效的快捷键 \"{}\": {}", value, e))
+}
+
+/// 检查新快捷键是否与其他动作冲突
+fn find_conflict(bindings: &[HotkeyBinding], action: HotkeyAction, shortcut: &Shortcut) -> Option<HotkeyAction> {
+    bindings
+        .iter()
+        .filter(|b| b.action != action && b.enabled)
+        .find(|b| parse_shortcut(&b.shortcut).map(|s| s == *shortcut).unwrap_or(false))
+        .map(|b| b.action)
+}
+
+fn register_binding(app: &AppHandle, binding: &HotkeyBinding) -> Result<(), String> {
+    let shortcut = parse_shortcut(&binding.shortcut)?;
+    let action = binding.action;
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                let app = app.clone();
+                // 窗口创建不能阻塞事件循环线程（Windows 上会死锁）
+                tauri::async_runtime::spawn(async move {
+                    trigger_action(app, action).await;
+                });
+            }
+        })
+        .map_err(|e| format!("注册快捷键 {} 失败: {}", binding.shortcut, e))
+}
+
+fn unregister_binding(app: &AppHandle, binding: &HotkeyBinding) {
+    if let Ok(shortcut) = parse_shortcut(&binding.shortcut) {
+        if app.global_shortcut().is_registered(shortcut) {
+            if let Err(e) = app.global_shortcut().unregister(shortcut) {
+                log::warn!("[Hotkeys] Failed to unregister {}: {}", binding.shortcut, e);
+            }
+        }
+    }
+}
+
+/// 启动时注册所有已启用的快捷键
+pub fn init(app: &AppHandle) {
+    for binding in load_hotkey_bindings().iter().filter(|b| b.enabled) {
+        match register_binding(app, binding) {
+            Ok(()) => log::info!("[Hotkeys] Registered {} -> {}", binding.shortcut, binding.action.as_str()),
+            Err(e) => log::warn!("[Hotkeys] {}", e),
+        }
+    }
+}
+
+async fn trigger_action(app: AppHandle, action: HotkeyAction) {
+    let project_path = ACTIVE_PROJECT
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|p| p.project_path.clone()));
+    log::info!("[Hotkeys] Triggered {} (project: {:?})", action.as_str(), project_path);
+
+    let result = match action {
+        HotkeyAction::QuickCapture => toggle_quick_capture_window(&app, project_path.as_deref()),
+        HotkeyAction::RerunLastPrompt => rerun_last_prompt(&app, project_path.clone()).await,
+    };
+    if let Err(e) = &result {
+        log::warn!("[Hotkeys] {} failed: {}", action.as_str(), e);
+    }
+
+    let _ = app.emit(
+        "hotkey-triggered",
+        HotkeyTriggered {
+            action,
+            project_path,
+            error: result.err(),
+        },
+    );
+}
+
+/// 显示（或隐藏已显示的）快速输入窗口
+fn toggle_quick_capture_window(app: &AppHandle, project_path: Option<&str>) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(QUICK_CAPTURE_LABEL) {
+        if window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false) {
+            return window.hide().map_err(|e| format!("Failed to hide window: {}", e));
+        }
+        window.show().map_err(|e| format!("Failed to show window: {}", e))?;
+        window.set_focus().map_err(|e| format!("Failed to focus window: {}", e))?;
+        let _ = window.emit("quick-capture-opened", project_path);
+        return Ok(());
+    }
+
+    let mut url = format!("/?window={}", QUICK_CAPTURE_LABEL);
+    if let Some(project_path) = project_path {
+        url.push_str(&format!("&project_path={}", urlencoding::encode(project_path)));
+    }
+
+    let window = WebviewWindowBuilder::new(app, QUICK_CAPTURE_LABEL, WebviewUrl::App(url.into()))
+        .title("Quick Prompt")
+        .inner_size(640.0, 180.0)
+        .resizable(false)
+        .maximizable(false)
+        .minimizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .visible(true)
+        .build()
+        .map_err(|e| format!("Failed to create window: {}", e))?;
+    window.set_focus().map_err(|e| format!("Failed to focus new window: {}", e))?;
+    Ok(())
+}
+
+async fn rerun_last_prompt(app: &AppHandle, project_path: Option<String>) -> Result<(), String> {
+    let project_path = project_path.ok_or("没有活动项目，无法重跑上一条提示词")?;
+    let entry = super::prompt_history::last_prompt(Some(project_path.clone()))
+        .await?
+        .ok_or_else(|| format!("项目 {} 没有提示词历史", project_path))?;
+    log::info!("[Hotkeys] Re-running last prompt {} with {} in {}", entry.id, entry.engine, entry.project_path);
+
+    super::prompt_library::execute_in_new_session(app.clone(), &entry.engine, entry.project_path, entry.prompt, None)
+        .await
+}
+
+/// 获取所有快捷键绑定及注册状态
+#[tauri::command]
+pub async fn get_hotkey_bindings(app: AppHandle) -> Result<Vec<HotkeyBindingStatus>, String> {
+    Ok(load_hotkey_bindings()
+        .into_iter()
+        .map(|binding| {
+            let registered = binding.enabled
+                && parse_shortcut(&binding.shortcut)
+                    .map(|s| app.global_shortcut().is_registered(s))
+                    .unwrap_or(false);
+            HotkeyBindingStatus { binding, registered }
+        })
+        .collect())
+}
+
+/// 为动作设置（或更换）快捷键并立即注册
+#[tauri::command]
+pub async fn register_hotkey(app: AppHandle, action: HotkeyAction, shortcut: String) -> Result<HotkeyBinding, String> {
+    let parsed = parse_shortcut(&shortcut)?;
+    let mut bindings = load_hotkey_bindings();
+    if let Some(other) = find_conflict(&bindings, action, &parsed) {
+        return Err(format!("快捷键 {} 已被 {} 使用", shortcut, other.as_str()));
+    }
+
+    let index = bindings
+        .iter()
+        .position(|b| b.action == action)
+        .ok_or_else(|| format!("未知的快捷键动作: {}", action.as_str()))?;
+    unregister_binding(&app, &bindings[index]);
+
+    let binding = HotkeyBinding {
+        action,
+        shortcut: shortcut.trim().to_string(),
+        enabled: true,
+    };
+    if let Err(e) = register_binding(&app, &binding) {
+        // 恢复原绑定，避免注册失败后快捷键整体失效
+        if bindings[index].enabled {
+            let _ = register_binding(&app, &bindings[index]);
+        }
+        return Err(e);
+    }
+
+    bindings[index] = binding.clone();
+    save_hotkey_bindings(&bindings)?;
+    log::info!("[Hotkeys] Bound {} -> {}", binding.shortcut, action.as_str());
+    Ok(binding)
+}
+
+/// 注销动作的快捷键（保留快捷键文本，标记为未启用）
+#[tauri::command]
+pub async fn unregister_hotkey(app: AppHandle, action: HotkeyAction) -> Result<(), String> {
+    let mut bindings = load_hotkey_bindings();
+    if let Some(binding) = bindings.iter_mut().find(|b| b.action == action) {
+        unregister_binding(&app, binding);
+        binding.enabled = false;
+    }
+    save_hotkey_bindings(&bindings)?;
+    log::info!("[Hotkeys] Unbound {}", action.as_str());
+    Ok(())
+}
+
+/// 前端上报当前活动项目（切换标签页时调用，传 None 表示没有活动项目）
+#[tauri::command]
+pub async fn set_active_project(project_path: Option<String>, engine: Option<String>) -> Result<(), String> {
+    let mut guard = ACTIVE_PROJECT.lock().map_err(|e| e.to_string())?;
+    *guard = project_path
+        .filter(|p| !p.trim().is_empty())
+        .map(|project_path| ActiveProject { project_path, engine });
+    Ok(())
+}
+
+/// 快速输入窗口提交：在指定（或当前活动）项目中新建会话执行
+#[tauri::command]
+pub async fn submit_quick_capture(
+    app: AppHandle,
+    prompt: String,
+    project_path: Option<String>,
+    engine: Option<String>,
+) -> Result<(), String> {
+    if prompt.trim().is_empty() {
+        return Err("提示词不能为空".to_string());
+    }
+    let active = ACTIVE_PROJECT.lock().map_err(|e| e.to_string())?.clone();
+    let project_path = project_path
+        .or_else(|| active.as_ref().map(|p| p.project_path.clone()))
+        .ok_or("没有活动项目，请先在主窗口打开一个项目")?;
+    let engine = engine
+        .or_else(|| active.and_then(|p| p.engine))
+        .unwrap_or_else(|| "claude".to_string());
+
+    if let Some(window) = app.get_webview_window(QUICK_CAPTURE_LABEL) {
+        let _ = window.hide();
+    }
+    log::info!("[Hotkeys] Quick capture submitted to {} in {}", engine, project_path);
+    super::prompt_library::execute_in_new_session(app, &engine, project_path, prompt, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_actions_fall_back_to_defaults_and_conflicts_are_detected() {
+        let saved = vec![HotkeyBinding {
+            action: HotkeyAction::RerunLastPrompt,
+            shortcut: "Alt+R".to_string(),
+            enabled: true,
+        }];
+        let bindings = merge_with_defaults(saved);
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0], HotkeyAction::QuickCapture.default_binding());
+        assert_eq!(bindings[1].shortcut, "Alt+R");
+
+        let alt_r = parse_shortcut("alt+r").unwrap();
+        assert_eq!(
+            find_conflict(&bindings, HotkeyAction::QuickCapture, &alt_r),
+            Some(HotkeyAction::RerunLastPrompt)
+        );
+        assert_eq!(find_conflict(&bindings, HotkeyAction::RerunLastPrompt, &alt_r), None);
+        assert!(parse_shortcut("Ctrl+Nope").is_err());
+    }
+}
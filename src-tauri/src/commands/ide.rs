@@ -8,12 +8,20 @@
  * - URL 协议和命令行两种打开方式
  * - 跨平台路径处理（Windows、Unix、WSL）
  * - IDE 自动检测
+ * - 编辑器注册表（VS Code、JetBrains、Zed、Sublime Text、Neovim），按 id 打开文件
+ * - 在编辑器的对比视图中查看 Agent 的文件变更
+ * - 导出 IDEA 格式的 patch 并交给编辑器打开
  */
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use tauri::{AppHandle, Manager};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::app_settings::{notify_setting_changed, read_setting, write_setting};
+use super::storage::open_agent_db;
 
 // ================================
 // 数据结构定义
@@ -53,6 +61,9 @@ pub struct IDEConfig {
     /// 是否优先使用 URL 协议
     #[serde(default = "default_use_url_protocol")]
     pub use_url_protocol: bool,
+    /// 注册表中各编辑器的可执行文件路径覆盖（editor id -> 路径）
+    #[serde(default)]
+    pub editor_paths: HashMap<String, String>,
 }
 
 fn default_use_url_protocol() -> bool {
@@ -68,6 +79,7 @@ impl Default for IDEConfig {
             custom_ide_path: None,
             custom_ide_args: None,
             use_url_protocol: true,
+            editor_paths: HashMap::new(),
         }
     }
 }
@@ -116,80 +128,18 @@ pub struct IDEResult {
 const IDE_CONFIG_KEY: &str = "ide_config";
 
 /// 获取 IDE 配置
-fn load_ide_config(app: &AppHandle) -> Result<IDEConfig, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("无法获取应用数据目录: {}", e))?;
-
-    let db_path = app_data_dir.join("agents.db");
-
-    if !db_path.exists() {
-        return Ok(IDEConfig::default());
-    }
-
-    let conn = rusqlite::Connection::open(&db_path)
-        .map_err(|e| format!("无法打开数据库: {}", e))?;
-
-    // 确保表存在
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS app_settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        )",
-        [],
-    )
-    .map_err(|e| format!("无法创建设置表: {}", e))?;
-
-    // 读取配置
-    let config_json: Result<String, _> = conn.query_row(
-        "SELECT value FROM app_settings WHERE key = ?1",
-        rusqlite::params![IDE_CONFIG_KEY],
-        |row| row.get(0),
-    );
-
-    match config_json {
-        Ok(json) => serde_json::from_str(&json)
-            .map_err(|e| format!("无法解析 IDE 配置: {}", e)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(IDEConfig::default()),
-        Err(e) => Err(format!("无法读取 IDE 配置: {}", e)),
-    }
+fn load_ide_config() -> Result<IDEConfig, String> {
+    let conn = open_agent_db()?;
+    Ok(read_setting(&conn, IDE_CONFIG_KEY)
+        .map_err(|e| format!("无法读取 IDE 配置: {}", e))?
+        .unwrap_or_default())
 }
 
 /// 保存 IDE 配置
 fn save_ide_config(app: &AppHandle, config: &IDEConfig) -> Result<(), String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("无法获取应用数据目录: {}", e))?;
-
-    std::fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("无法创建应用数据目录: {}", e))?;
-
-    let db_path = app_data_dir.join("agents.db");
-
-    let conn = rusqlite::Connection::open(&db_path)
-        .map_err(|e| format!("无法打开数据库: {}", e))?;
-
-    // 确保表存在
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS app_settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        )",
-        [],
-    )
-    .map_err(|e| format!("无法创建设置表: {}", e))?;
-
-    // 序列化并保存配置
-    let config_json = serde_json::to_string(config)
-        .map_err(|e| format!("无法序列化 IDE 配置: {}", e))?;
-
-    conn.execute(
-        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-        rusqlite::params![IDE_CONFIG_KEY, config_json],
-    )
-    .map_err(|e| format!("无法保存 IDE 配置: {}", e))?;
+    let conn = open_agent_db()?;
+    write_setting(&conn, IDE_CONFIG_KEY, config).map_err(|e| format!("无法保存 IDE 配置: {}", e))?;
+    notify_setting_changed(app, IDE_CONFIG_KEY, serde_json::to_value(config).ok());
 
     log::info!("IDE 配置已保存: {:?}", config.ide_type);
     Ok(())
@@ -320,6 +270,11 @@ fn open_via_url_protocol(ide_type: &IDEType, file_path: &str, line: Option<u32>,
         }
     };
 
+    open_url(&url)
+}
+
+/// 使用系统默认方式打开 URL
+fn open_url(url: &str) -> Result<(), String> {
     log::info!("通过 URL 协议打开: {}", url);
 
     // 使用系统默认方式打开 URL
@@ -327,7 +282,7 @@ fn open_via_url_protocol(ide_type: &IDEType, file_path: &str, line: Option<u32>,
     {
         // 使用 explorer.exe 打开 URL 协议，比 cmd /C start 更可靠
         let result = Command::new("explorer.exe")
-            .arg(&url)
+            .arg(url)
             .spawn();
         
         match result {
@@ -338,7 +293,7 @@ fn open_via_url_protocol(ide_type: &IDEType, file_path: &str, line: Option<u32>,
                 log::warn!("explorer.exe 打开失败，尝试 cmd: {}", e);
                 // 备选方案：使用 cmd /C start
                 Command::new("cmd")
-                    .args(["/C", "start", "", &url])
+                    .args(["/C", "start", "", url])
                     .spawn()
                     .map_err(|e| format!("无法打开 URL: {}", e))?;
             }
@@ -348,7 +303,7 @@ fn open_via_url_protocol(ide_type: &IDEType, file_path: &str, line: Option<u32>,
     #[cfg(target_os = "macos")]
     {
         Command::new("open")
-            .arg(&url)
+            .arg(url)
             .spawn()
             .map_err(|e| format!("无法打开 URL: {}", e))?;
     }
@@ -356,7 +311,7 @@ fn open_via_url_protocol(ide_type: &IDEType, file_path: &str, line: Option<u32>,
     #[cfg(target_os = "linux")]
     {
         Command::new("xdg-open")
-            .arg(&url)
+            .arg(url)
             .spawn()
             .map_err(|e| format!("无法打开 URL: {}", e))?;
     }
@@ -545,14 +500,344 @@ fn detect_installed_ides() -> Vec<DetectedIDE> {
     detected
 }
 
+// ================================
+// 编辑器注册表
+// ================================
+
+/// 编辑器定义
+///
+/// 模板占位符: `{file}`、`{file_encoded}`（URL 编码）、`{file_vim}`（用于 Vim 单引号字符串）、`{line}`、`{column}`、
+/// `{server}`（Neovim 服务器地址）；对比模板使用 `{left}` / `{right}`（修改前 / 修改后的文件，
+/// Vim 字符串中使用 `{left_vim}` / `{right_vim}`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditorDefinition {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// 可执行文件候选名（按顺序在 PATH 中查找）
+    pub executables: &'static [&'static str],
+    /// 命令行参数模板
+    pub args: &'static [&'static str],
+    /// URL 协议模板（找不到可执行文件时使用）
+    pub url: Option<&'static str>,
+    /// REST 接口模板（IDE 运行中时优先使用，例如 JetBrains 内置服务器）
+    pub rest: Option<&'static str>,
+    /// 对比视图的命令行参数模板（不支持时为 None）
+    pub diff_args: Option<&'static [&'static str]>,
+}
+
+const JETBRAINS_ARGS: &[&str] = &["--line", "{line}", "--column", "{column}", "{file}"];
+const JETBRAINS_REST: Option<&str> =
+    Some("http://127.0.0.1:63342/api/file?file={file_encoded}&line={line}&column={column}");
+const JETBRAINS_DIFF: Option<&[&str]> = Some(&["diff", "{left}", "{right}"]);
+const VSCODE_DIFF: Option<&[&str]> = Some(&["--diff", "{left}", "{right}"]);
+
+/// 内置编辑器注册表
+pub const EDITORS: &[EditorDefinition] = &[
+    EditorDefinition {
+        id: "vscode",
+        name: "Visual Studio Code",
+        executables: &["code"],
+        args: &["--goto", "{file}:{line}:{column}"],
+        url: Some("vscode://file/{file}:{line}:{column}"),
+        rest: None,
+        diff_args: VSCODE_DIFF,
+    },
+    EditorDefinition {
+        id: "cursor",
+        name: "Cursor",
+        executables: &["cursor"],
+        args: &["--goto", "{file}:{line}:{column}"],
+        url: Some("cursor://file/{file}:{line}:{column}"),
+        rest: None,
+        diff_args: VSCODE_DIFF,
+    },
+    EditorDefinition {
+        id: "idea",
+        name: "IntelliJ IDEA",
+        executables: &["idea", "idea64.exe", "idea.sh"],
+        args: JETBRAINS_ARGS,
+        url: Some("idea://open?file={file_encoded}&line={line}&column={column}"),
+        rest: JETBRAINS_REST,
+        diff_args: JETBRAINS_DIFF,
+    },
+    EditorDefinition {
+        id: "webstorm",
+        name: "WebStorm",
+        executables: &["webstorm", "webstorm64.exe", "webstorm.sh"],
+        args: JETBRAINS_ARGS,
+        url: None,
+        rest: JETBRAINS_REST,
+        diff_args: JETBRAINS_DIFF,
+    },
+    EditorDefinition {
+        id: "pycharm",
+        name: "PyCharm",
+        executables: &["pycharm", "pycharm64.exe", "pycharm.sh"],
+        args: JETBRAINS_ARGS,
+        url: None,
+        rest: JETBRAINS_REST,
+        diff_args: JETBRAINS_DIFF,
+    },
+    EditorDefinition {
+        id: "goland",
+        name: "GoLand",
+        executables: &["goland", "goland64.exe", "goland.sh"],
+        args: JETBRAINS_ARGS,
+        url: None,
+        rest: JETBRAINS_REST,
+        diff_args: JETBRAINS_DIFF,
+    },
+    EditorDefinition {
+        id: "rustrover",
+        name: "RustRover",
+        executables: &["rustrover", "rustrover64.exe", "rustrover.sh"],
+        args: JETBRAINS_ARGS,
+        url: None,
+        rest: JETBRAINS_REST,
+        diff_args: JETBRAINS_DIFF,
+    },
+    EditorDefinition {
+        id: "zed",
+        name: "Zed",
+        executables: &["zed", "zeditor"],
+        args: &["{file}:{line}:{column}"],
+        url: Some("zed://file/{file}:{line}:{column}"),
+        rest: None,
+        diff_args: Some(&["--diff", "{left}", "{right}"]),
+    },
+    EditorDefinition {
+        id: "sublime",
+        name: "Sublime Text",
+        executables: &["subl", "sublime_text"],
+        args: &["{file}:{line}:{column}"],
+        url: None,
+        rest: None,
+        diff_args: None,
+    },
+    EditorDefinition {
+        id: "neovim",
+        name: "Neovim",
+        executables: &["nvim"],
+        // 在已运行的实例中打开（需要 $NVIM 或 $NVIM_LISTEN_ADDRESS）
+        args: &[
+            "--server",
+            "{server}",
+            "--remote-expr",
+            "execute('drop ' . fnameescape('{file_vim}')) . cursor({line}, {column})",
+        ],
+        url: None,
+        rest: None,
+        diff_args: Some(&[
+            "--server",
+            "{server}",
+            "--remote-expr",
+            "execute('tabedit ' . fnameescape('{left_vim}') . ' | diffthis | vsplit ' . fnameescape('{right_vim}') . ' | diffthis')",
+        ]),
+    },
+];
+
+/// 编辑器信息（含检测结果）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditorInfo {
+    pub id: String,
+    pub name: String,
+    /// 检测到的可执行文件路径
+    pub path: Option<String>,
+    /// 可以打开文件（找到可执行文件，或可通过 URL 协议 / REST 接口打开）
+    pub available: bool,
+    /// 支持对比视图（需要可执行文件）
+    pub supports_diff: bool,
+}
+
+fn find_editor(editor_id: &str) -> Option<&'static EditorDefinition> {
+    EDITORS.iter().find(|editor| editor.id == editor_id)
+}
+
+/// Neovim 服务器地址（从 Neovim 内部终端启动时为 $NVIM）
+fn neovim_server() -> Option<String> {
+    ["NVIM", "NVIM_LISTEN_ADDRESS"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+}
+
+/// 编辑器需要的服务器地址（目前只有 Neovim 需要）
+fn editor_server(editor: &EditorDefinition) -> Result<String, String> {
+    if editor.id != "neovim" {
+        return Ok(String::new());
+    }
+    neovim_server().ok_or_else(|| "未找到运行中的 Neovim（需要设置 NVIM_LISTEN_ADDRESS）".to_string())
+}
+
+/// 转义为 Vim 单引号字符串的内容（单引号写两次）
+fn vim_quote(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+/// 替换模板中的占位符；行列号缺省为 1
+fn fill_template(template: &str, file: &str, line: Option<u32>, column: Option<u32>, server: &str) -> String {
+    template
+        .replace("{file_encoded}", &urlencoding::encode(file))
+        .replace("{file_vim}", &vim_quote(file))
+        .replace("{file}", file)
+        .replace("{line}", &line.unwrap_or(1).to_string())
+        .replace("{column}", &column.unwrap_or(1).to_string())
+        .replace("{server}", server)
+}
+
+/// 替换对比参数中的左右文件与服务器占位符
+fn fill_diff_args(diff_args: &[&str], left: &str, right: &str, server: &str) -> Vec<String> {
+    diff_args
+        .iter()
+        .map(|arg| {
+            arg.replace("{left_vim}", &vim_quote(left))
+                .replace("{right_vim}", &vim_quote(right))
+                .replace("{left}", left)
+                .replace("{right}", right)
+                .replace("{server}", server)
+        })
+        .collect()
+}
+
+/// 查找编辑器的可执行文件：配置中的路径优先，其次是 PATH
+fn resolve_editor_executable(editor: &EditorDefinition, config: &IDEConfig) -> Option<String> {
+    if let Some(path) = config.editor_paths.get(editor.id).filter(|p| !p.is_empty()) {
+        return Some(path.clone());
+    }
+    // 兼容旧配置中的 IDEA / VSCode 路径
+    let legacy = match editor.id {
+        "idea" => config.idea_path.as_ref(),
+        "vscode" => config.vscode_path.as_ref(),
+        _ => None,
+    };
+    if let Some(path) = legacy.filter(|p| !p.is_empty()) {
+        return Some(path.clone());
+    }
+    editor
+        .executables
+        .iter()
+        .find_map(|exe| which::which(exe).ok())
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// 检测注册表中的编辑器
+fn detect_editors(config: &IDEConfig) -> Vec<EditorInfo> {
+    EDITORS
+        .iter()
+        .map(|editor| {
+            let path = resolve_editor_executable(editor, config);
+            let available = match editor.id {
+                "neovim" => path.is_some() && neovim_server().is_some(),
+                _ => path.is_some() || editor.url.is_some(),
+            };
+            EditorInfo {
+                id: editor.id.to_string(),
+                name: editor.name.to_string(),
+                supports_diff: editor.diff_args.is_some() && path.is_some(),
+                path,
+                available,
+            }
+        })
+        .collect()
+}
+
+/// 通过 REST 接口打开（IDE 未运行时返回 Err）
+async fn open_via_rest(url: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let resp = client.get(url).send().await.map_err(|e| format!("请求失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("返回 {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// 用注册表中的编辑器打开文件：REST 接口 -> 命令行 -> URL 协议
+async fn open_with_editor(
+    editor: &EditorDefinition,
+    config: &IDEConfig,
+    file: &str,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Result<(), String> {
+    if let Some(rest) = editor.rest {
+        let url = fill_template(rest, file, line, column, "");
+        match open_via_rest(&url).await {
+            Ok(()) => return Ok(()),
+            Err(e) => log::info!("{} REST 接口不可用，改用命令行: {}", editor.name, e),
+        }
+    }
+
+    if let Some(executable) = resolve_editor_executable(editor, config) {
+        let server = editor_server(editor)?;
+        let mut cmd = Command::new(&executable);
+        cmd.args(editor.args.iter().map(|arg| fill_template(arg, file, line, column, &server)));
+        log::info!("通过命令行打开: {:?}", cmd);
+        cmd.spawn().map_err(|e| format!("无法启动 {}: {}", editor.name, e))?;
+        return Ok(());
+    }
+
+    match editor.url {
+        Some(url) => open_url(&fill_template(url, file, line, column, "")),
+        None => Err(format!("未检测到 {}", editor.name)),
+    }
+}
+
+/// 把变更前后的内容写入临时文件，返回 (修改前, 修改后) 路径
+///
+/// 两个文件保留原文件名（放在 before/after 子目录中），编辑器可以按扩展名高亮。
+fn write_diff_files(
+    change_id: &str,
+    file_path: &str,
+    old_content: Option<&str>,
+    new_content: Option<&str>,
+) -> Result<(PathBuf, PathBuf), String> {
+    let file_name = Path::new(&file_path.replace('\\', "/"))
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    let dir = std::env::temp_dir().join("anycode-ide-diff").join(change_id);
+
+    let mut paths = Vec::with_capacity(2);
+    for (side, content) in [("before", old_content), ("after", new_content)] {
+        let side_dir = dir.join(side);
+        std::fs::create_dir_all(&side_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+        let path = side_dir.join(&file_name);
+        std::fs::write(&path, content.unwrap_or_default()).map_err(|e| format!("写入临时文件失败: {}", e))?;
+        paths.push(path);
+    }
+    let after = paths.pop().unwrap_or_default();
+    let before = paths.pop().unwrap_or_default();
+    Ok((before, after))
+}
+
+/// 用编辑器的对比视图打开两个文件
+fn open_diff_with_editor(editor: &EditorDefinition, config: &IDEConfig, left: &Path, right: &Path) -> Result<(), String> {
+    let diff_args = editor
+        .diff_args
+        .ok_or_else(|| format!("{} 不支持对比视图", editor.name))?;
+    let executable =
+        resolve_editor_executable(editor, config).ok_or_else(|| format!("未检测到 {}", editor.name))?;
+    let server = editor_server(editor)?;
+
+    let mut cmd = Command::new(&executable);
+    cmd.args(fill_diff_args(diff_args, &left.to_string_lossy(), &right.to_string_lossy(), &server));
+    log::info!("通过命令行打开对比: {:?}", cmd);
+    cmd.spawn().map_err(|e| format!("无法启动 {}: {}", editor.name, e))?;
+    Ok(())
+}
+
 // ================================
 // Tauri 命令
 // ================================
 
 /// 获取 IDE 配置
 #[tauri::command]
-pub fn get_ide_config(app: AppHandle) -> Result<IDEConfig, String> {
-    load_ide_config(&app)
+pub fn get_ide_config() -> Result<IDEConfig, String> {
+    load_ide_config()
 }
 
 /// 保存 IDE 配置
@@ -569,11 +854,11 @@ pub fn detect_ides() -> Vec<DetectedIDE> {
 
 /// 在 IDE 中打开文件
 #[tauri::command]
-pub fn open_file_in_ide(app: AppHandle, options: OpenFileOptions) -> Result<IDEResult, String> {
+pub fn open_file_in_ide(options: OpenFileOptions) -> Result<IDEResult, String> {
     log::info!("open_file_in_ide 被调用: file_path={}, project_path={:?}, line={:?}", 
         options.file_path, options.project_path, options.line);
     
-    let config = load_ide_config(&app)?;
+    let config = load_ide_config()?;
     log::info!("IDE 配置: ide_type={:?}, use_url_protocol={}", config.ide_type, config.use_url_protocol);
 
     // 解析文件路径
@@ -651,6 +936,148 @@ pub fn open_file_in_ide(app: AppHandle, options: OpenFileOptions) -> Result<IDER
     }
 }
 
+/// 列出注册表中的编辑器及检测结果
+#[tauri::command]
+pub fn list_editors() -> Result<Vec<EditorInfo>, String> {
+    let config = load_ide_config()?;
+    Ok(detect_editors(&config))
+}
+
+/// 用指定编辑器打开文件
+#[tauri::command]
+pub async fn open_in_ide(
+    editor_id: String,
+    file: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    project_path: Option<String>,
+) -> Result<IDEResult, String> {
+    let editor = find_editor(&editor_id).ok_or_else(|| format!("未知的编辑器: {}", editor_id))?;
+    let config = load_ide_config()?;
+    let resolved_path = resolve_file_path(&file, project_path.as_deref())?;
+
+    match open_with_editor(editor, &config, &resolved_path, line, column).await {
+        Ok(()) => {
+            log::info!("成功用 {} 打开文件: {}", editor.name, resolved_path);
+            Ok(IDEResult {
+                success: true,
+                message: format!("已在 {} 中打开: {}", editor.name, resolved_path),
+                error: None,
+            })
+        }
+        Err(e) => {
+            log::error!("用 {} 打开文件失败: {}", editor.name, e);
+            Ok(IDEResult {
+                success: false,
+                message: format!("无法打开文件: {}", e),
+                error: Some(e),
+            })
+        }
+    }
+}
+
+/// 在编辑器的对比视图中打开一条 Codex 变更记录
+#[tauri::command]
+pub async fn open_change_in_ide_diff(
+    change_id: String,
+    editor_id: String,
+) -> Result<IDEResult, String> {
+    use super::codex::change_store;
+
+    let editor = find_editor(&editor_id).ok_or_else(|| format!("未知的编辑器: {}", editor_id))?;
+    let config = load_ide_config()?;
+
+    let lookup_id = change_id.clone();
+    let session_id = tokio::task::spawn_blocking(move || {
+        let conn = change_store::open_change_db()?;
+        change_store::find_change_session(&conn, &lookup_id)
+    })
+    .await
+    .map_err(|e| format!("查询变更记录失败: {}", e))??
+    .ok_or_else(|| format!("变更 {} 未找到", change_id))?;
+    let change = super::codex::change_tracker::codex_get_change_detail(session_id, change_id.clone()).await?;
+
+    let (before, after) = write_diff_files(
+        &change_id,
+        &change.file_path,
+        change.old_content.as_deref(),
+        change.new_content.as_deref(),
+    )?;
+
+    match open_diff_with_editor(editor, &config, &before, &after) {
+        Ok(()) => {
+            log::info!("成功用 {} 打开变更对比: {}", editor.name, change.file_path);
+            Ok(IDEResult {
+                success: true,
+                message: format!("已在 {} 中打开对比: {}", editor.name, change.file_path),
+                error: None,
+            })
+        }
+        Err(e) => {
+            log::error!("用 {} 打开变更对比失败: {}", editor.name, e);
+            Ok(IDEResult {
+                success: false,
+                message: format!("无法打开对比: {}", e),
+                error: Some(e),
+            })
+        }
+    }
+}
+
+/// 以 IDEA 格式导出会话变更并用指定编辑器打开
+///
+/// JetBrains IDE 通过命令行打开 .patch 文件时会弹出 Apply Patch 对话框；
+/// 其他编辑器按普通文件打开。
+#[tauri::command]
+pub async fn export_and_open_patch(
+    session_id: String,
+    target: String,
+) -> Result<IDEResult, String> {
+    use super::codex::change_tracker::{export_session_patch, PatchFormat};
+
+    let editor = find_editor(&target).ok_or_else(|| format!("未知的编辑器: {}", target))?;
+    let config = load_ide_config()?;
+
+    let patch = export_session_patch(&session_id, PatchFormat::Idea)?;
+    if patch.is_empty() {
+        return Err("该会话没有可导出的变更".to_string());
+    }
+    let dir = std::env::temp_dir().join("anycode-patches");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let patch_path = dir.join(format!("{}.patch", session_id));
+    std::fs::write(&patch_path, &patch).map_err(|e| format!("写入文件失败: {}", e))?;
+    let patch_path = patch_path.to_string_lossy().to_string();
+
+    let result = match resolve_editor_executable(editor, &config) {
+        // REST 接口只会把 patch 当作普通文件打开，JetBrains 直接走命令行
+        Some(executable) if editor.rest.is_some() => Command::new(&executable)
+            .arg(&patch_path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("无法启动 {}: {}", editor.name, e)),
+        _ => open_with_editor(editor, &config, &patch_path, None, None).await,
+    };
+
+    match result {
+        Ok(()) => {
+            log::info!("成功用 {} 打开 patch: {}", editor.name, patch_path);
+            Ok(IDEResult {
+                success: true,
+                message: format!("已在 {} 中打开 patch: {}", editor.name, patch_path),
+                error: None,
+            })
+        }
+        Err(e) => {
+            log::error!("用 {} 打开 patch 失败: {}", editor.name, e);
+            Ok(IDEResult {
+                success: false,
+                message: format!("patch 已导出到 {}，但无法打开: {}", patch_path, e),
+                error: Some(e),
+            })
+        }
+    }
+}
+
 /// 验证 IDE 路径是否有效
 #[tauri::command]
 pub fn validate_ide_path(path: String) -> Result<bool, String> {
@@ -676,3 +1103,73 @@ pub fn validate_ide_path(path: String) -> Result<bool, String> {
 
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_editor_templates() {
+        let mut ids: Vec<_> = EDITORS.iter().map(|e| e.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), EDITORS.len());
+
+        let vscode = find_editor("vscode").unwrap();
+        assert_eq!(
+            fill_template(vscode.url.unwrap(), "/tmp/a b.rs", Some(12), None, ""),
+            "vscode://file//tmp/a b.rs:12:1"
+        );
+        let idea = find_editor("idea").unwrap();
+        assert_eq!(
+            fill_template(idea.rest.unwrap(), "/tmp/a b.rs", Some(3), Some(7), ""),
+            "http://127.0.0.1:63342/api/file?file=%2Ftmp%2Fa%20b.rs&line=3&column=7"
+        );
+        let args: Vec<_> = find_editor("neovim")
+            .unwrap()
+            .args
+            .iter()
+            .map(|arg| fill_template(arg, "/tmp/x.rs", Some(5), Some(2), "/run/nvim.sock"))
+            .collect();
+        assert_eq!(args[1], "/run/nvim.sock");
+        assert_eq!(args[3], "execute('drop ' . fnameescape('/tmp/x.rs')) . cursor(5, 2)");
+        let quoted = fill_template(find_editor("neovim").unwrap().args[3], "/tmp/it's.rs", None, None, "");
+        assert_eq!(quoted, "execute('drop ' . fnameescape('/tmp/it''s.rs')) . cursor(1, 1)");
+        assert!(find_editor("notepad").is_none());
+
+        let (before, after) = write_diff_files("test-change", "src\\main.rs", None, Some("fn main() {}\n")).unwrap();
+        assert!(before.ends_with("before/main.rs") && after.ends_with("after/main.rs"));
+        assert_eq!(std::fs::read_to_string(&before).unwrap(), "");
+        assert_eq!(std::fs::read_to_string(&after).unwrap(), "fn main() {}\n");
+    }
+
+    #[test]
+    fn fills_diff_args_per_editor() {
+        let args = |id: &str, server: &str| {
+            fill_diff_args(find_editor(id).unwrap().diff_args.unwrap(), "/tmp/old.rs", "/tmp/it's.rs", server)
+        };
+        assert_eq!(args("vscode", ""), ["--diff", "/tmp/old.rs", "/tmp/it's.rs"]);
+        assert_eq!(args("idea", ""), ["diff", "/tmp/old.rs", "/tmp/it's.rs"]);
+        assert_eq!(args("zed", ""), ["--diff", "/tmp/old.rs", "/tmp/it's.rs"]);
+
+        let nvim = args("neovim", "/run/nvim.sock");
+        assert_eq!(nvim[1], "/run/nvim.sock");
+        assert_eq!(
+            nvim[3],
+            "execute('tabedit ' . fnameescape('/tmp/old.rs') . ' | diffthis | vsplit ' . fnameescape('/tmp/it''s.rs') . ' | diffthis')"
+        );
+
+        let sublime = find_editor("sublime").unwrap();
+        assert!(sublime.diff_args.is_none());
+        let err = open_diff_with_editor(sublime, &IDEConfig::default(), Path::new("/a"), Path::new("/b")).unwrap_err();
+        assert!(err.contains("不支持对比视图"));
+    }
+
+    #[test]
+    fn writes_deleted_file_with_empty_after_side() {
+        let (before, after) = write_diff_files("test-delete", "src/lib.rs", Some("pub fn a() {}\n"), None).unwrap();
+        assert!(before.ends_with("before/lib.rs") && after.ends_with("after/lib.rs"));
+        assert_eq!(std::fs::read_to_string(&before).unwrap(), "pub fn a() {}\n");
+        assert_eq!(std::fs::read_to_string(&after).unwrap(), "");
+    }
+}
@@ -0,0 +1,263 @@
+//! 项目指令文件同步
+//!
+//! 以 `<project>/.anycode/instructions.md` 为唯一来源，生成各引擎读取的指令文件
+//! （CLAUDE.md / AGENTS.md / GEMINI.md）。只对某个引擎生效的内容写在覆盖段落中：
+//!
+//! ```text
+//! <!-- anycode:claude -->
+//! 仅写入 CLAUDE.md 的内容
+//! <!-- /anycode:claude -->
+//! ```
+//!
+//! 生成的文件带有标记头；覆盖手写的文件前会先备份（与激活提示词模板的备份规则相同）。
+
+use crate::tr;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::claude::generate_backup_filename;
+use super::codex::change_tracker::{generate_create_diff, generate_unified_diff};
+
+/// 规范指令文件（相对项目根目录）
+const CANONICAL_FILE: &str = ".anycode/instructions.md";
+
+/// 生成文件的标记头，存在时视为由本功能管理，可直接覆盖
+const GENERATED_HEADER: &str =
+    "<!-- Generated by Any Code from .anycode/instructions.md. Edit that file and sync instead. -->";
+
+/// (引擎, 指令文件名)
+const ENGINE_FILES: [(&str, &str); 3] = [("claude", "CLAUDE.md"), ("codex", "AGENTS.md"), ("gemini", "GEMINI.md")];
+
+/// 单个引擎文件的同步结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionFileSync {
+    pub engine: String,
+    pub path: String,
+    /// "created" | "updated" | "unchanged"
+    pub status: String,
+    /// 现有文件不是生成的（同步时会先备份）
+    pub overwrites_manual: bool,
+    /// unified diff（未变化时为空）
+    pub diff: String,
+    /// 同步时创建的备份文件
+    pub backup_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionSyncResult {
+    pub dry_run: bool,
+    pub files: Vec<InstructionFileSync>,
+}
+
+/// 生成某个引擎的文件内容：保留公共内容和该引擎的覆盖段落，去掉其他引擎的段落
+fn render_for_engine(canonical: &str, engine: &str) -> Result<String, String> {
+    let mut body = String::new();
+    let mut section: Option<String> = None;
+
+    for (index, line) in canonical.lines().enumerate() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed
+            .strip_prefix("<!-- /anycode:")
+            .and_then(|rest| rest.strip_suffix("-->"))
+            .map(str::trim)
+        {
+            if section.as_deref() != Some(name) {
+                return Err(format!("第 {} 行: 结束标记 {} 与开始标记不匹配", index + 1, name));
+            }
+            section = None;
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix("<!-- anycode:")
+            .and_then(|rest| rest.strip_suffix("-->"))
+            .map(str::trim)
+        {
+            if let Some(open) = &section {
+                return Err(format!("第 {} 行: 段落 {} 尚未结束", index + 1, open));
+            }
+            if !ENGINE_FILES.iter().any(|(e, _)| *e == name) {
+                return Err(format!("第 {} 行: 未知引擎 {}", index + 1, name));
+            }
+            section = Some(name.to_string());
+            continue;
+        }
+        if section.as_deref().is_none_or(|s| s == engine) {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    if let Some(open) = section {
+        return Err(format!("段落 {} 缺少结束标记", open));
+    }
+
+    Ok(format!("{}\n\n{}\n", GENERATED_HEADER, body.trim()))
+}
+
+fn canonical_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(CANONICAL_FILE)
+}
+
+fn get_project_dir(project_path: &str) -> Result<PathBuf, String> {
+    let project_dir = PathBuf::from(project_path);
+    if !project_dir.is_dir() {
+        return Err(tr!("project.path_not_found", path = project_path));
+    }
+    Ok(project_dir)
+}
+
+/// 对比（并在非 dry-run 时写入）一个引擎文件
+fn sync_engine_file(
+    project_dir: &Path,
+    engine: &str,
+    file_name: &str,
+    content: &str,
+    dry_run: bool,
+) -> Result<InstructionFileSync, String> {
+    let path = project_dir.join(file_name);
+    let existing = fs::read_to_string(&path).ok();
+    let overwrites_manual = existing
+        .as_deref()
+        .is_some_and(|text| !text.trim().is_empty() && !text.starts_with(GENERATED_HEADER));
+
+    let (status, diff) = match &existing {
+        None => ("created", generate_create_diff(file_name, content)),
+        Some(old) if old == content => ("unchanged", String::new()),
+        Some(old) => ("updated", generate_unified_diff(file_name, old, content)),
+    };
+
+    let mut backup_path = None;
+    if !dry_run && status != "unchanged" {
+        if overwrites_manual {
+            let backup = project_dir.join(generate_backup_filename(project_dir, file_name));
+            fs::copy(&path, &backup).map_err(|e| format!("备份 {} 失败: {}", file_name, e))?;
+            backup_path = Some(backup.to_string_lossy().to_string());
+        }
+        fs::write(&path, content).map_err(|e| format!("写入 {} 失败: {}", file_name, e))?;
+    }
+
+    Ok(InstructionFileSync {
+        engine: engine.to_string(),
+        path: path.to_string_lossy().to_string(),
+        status: status.to_string(),
+        overwrites_manual,
+        diff,
+        backup_path,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 读取项目的规范指令文件；不存在时返回 None
+#[tauri::command]
+pub async fn get_project_instructions(project_path: String) -> Result<Option<String>, String> {
+    let path = canonical_path(&get_project_dir(&project_path)?);
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read_to_string(&path)
+        .map(Some)
+        .map_err(|e| format!("读取 {} 失败: {}", CANONICAL_FILE, e))
+}
+
+/// 保存项目的规范指令文件（先校验覆盖段落格式）
+#[tauri::command]
+pub async fn save_project_instructions(project_path: String, content: String) -> Result<(), String> {
+    render_for_engine(&content, "claude")?;
+    let path = canonical_path(&get_project_dir(&project_path)?);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建 .anycode 目录失败: {}", e))?;
+    }
+    fs::write(&path, content).map_err(|e| format!("保存 {} 失败: {}", CANONICAL_FILE, e))
+}
+
+/// 用现有的某个引擎文件初始化规范指令文件（已存在时报错）
+#[tauri::command]
+pub async fn import_project_instructions(project_path: String, engine: String) -> Result<String, String> {
+    let project_dir = get_project_dir(&project_path)?;
+    let (_, file_name) = ENGINE_FILES
+        .iter()
+        .find(|(e, _)| *e == engine)
+        .ok_or_else(|| tr!("engine.unsupported", engine = engine))?;
+
+    let path = canonical_path(&project_dir);
+    if path.exists() {
+        return Err(format!("{} 已存在", CANONICAL_FILE));
+    }
+    let content = fs::read_to_string(project_dir.join(file_name))
+        .map_err(|e| format!("读取 {} 失败: {}", file_name, e))?;
+    let content = content.strip_prefix(GENERATED_HEADER).unwrap_or(&content).trim_start().to_string();
+    save_project_instructions(project_path, content.clone()).await?;
+    Ok(content)
+}
+
+/// 从规范指令文件生成各引擎的指令文件
+///
+/// `engines` 为空时同步全部引擎；`dry_run` 为 true 时只返回 diff，不写入文件。
+#[tauri::command]
+pub async fn sync_project_instructions(
+    project_path: String,
+    engines: Option<Vec<String>>,
+    dry_run: bool,
+) -> Result<InstructionSyncResult, String> {
+    let project_dir = get_project_dir(&project_path)?;
+    let canonical = fs::read_to_string(canonical_path(&project_dir))
+        .map_err(|e| format!("读取 {} 失败: {}", CANONICAL_FILE, e))?;
+
+    let mut files = Vec::new();
+    for (engine, file_name) in ENGINE_FILES {
+        if engines.as_ref().is_some_and(|list| !list.iter().any(|e| e == engine)) {
+            continue;
+        }
+        let content = render_for_engine(&canonical, engine)?;
+        files.push(sync_engine_file(&project_dir, engine, file_name, &content, dry_run)?);
+    }
+
+    log::info!(
+        "[InstructionSync] {} {} files for {}",
+        if dry_run { "Previewed" } else { "Synced" },
+        files.iter().filter(|f| f.status != "unchanged").count(),
+        project_path
+    );
+    Ok(InstructionSyncResult { dry_run, files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_engine_sections_and_syncs_with_backup() {
+        let canonical = "# Rules\n\nUse tabs.\n\n<!-- anycode:claude -->\nPrefer subagents.\n<!-- /anycode:claude -->\n<!-- anycode:codex -->\nRun cargo test.\n<!-- /anycode:codex -->\n";
+        let claude = render_for_engine(canonical, "claude").unwrap();
+        assert!(claude.starts_with(GENERATED_HEADER));
+        assert!(claude.contains("Use tabs.") && claude.contains("Prefer subagents."));
+        assert!(!claude.contains("cargo test") && !claude.contains("anycode:"));
+        let gemini = render_for_engine(canonical, "gemini").unwrap();
+        assert!(gemini.ends_with("Use tabs.\n"));
+
+        assert!(render_for_engine("<!-- anycode:claude -->\nx\n", "claude").is_err());
+        assert!(render_for_engine("<!-- anycode:vim -->\nx\n<!-- /anycode:vim -->", "claude").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("AGENTS.md"), "hand written\n").unwrap();
+        let content = render_for_engine(canonical, "codex").unwrap();
+
+        let preview = sync_engine_file(dir.path(), "codex", "AGENTS.md", &content, true).unwrap();
+        assert_eq!(preview.status, "updated");
+        assert!(preview.overwrites_manual && preview.diff.contains("-hand written"));
+        assert_eq!(fs::read_to_string(dir.path().join("AGENTS.md")).unwrap(), "hand written\n");
+
+        let synced = sync_engine_file(dir.path(), "codex", "AGENTS.md", &content, false).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("AGENTS.md.backup")).unwrap(), "hand written\n");
+        assert!(synced.backup_path.is_some());
+
+        let again = sync_engine_file(dir.path(), "codex", "AGENTS.md", &content, false).unwrap();
+        assert_eq!(again.status, "unchanged");
+        assert!(!again.overwrites_manual && again.diff.is_empty());
+    }
+}
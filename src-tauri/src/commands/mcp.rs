@@ -1,11 +1,14 @@
+use crate::tr;
+use crate::error::AnyCodeError;
 use anyhow::{Context, Result};
 use dirs;
-use log::{error, info};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
+use crate::commands::file_operations::write_atomic;
 use tauri::AppHandle;
 
 /// Helper function to create a std::process::Command with proper environment variables
@@ -44,7 +47,7 @@ pub struct MCPServer {
 }
 
 /// Server status information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ServerStatus {
     /// Whether the server is running
     pub running: bool,
@@ -52,6 +55,20 @@ pub struct ServerStatus {
     pub error: Option<String>,
     /// Last checked timestamp
     pub last_checked: Option<u64>,
+    /// Protocol version negotiated in the initialize handshake
+    #[serde(default)]
+    pub protocol_version: Option<String>,
+    /// Tools advertised by the server (empty when not checked or not enumerable)
+    #[serde(default)]
+    pub tools: Vec<MCPToolInfo>,
+}
+
+/// A tool advertised by an MCP server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MCPToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 /// MCP configuration for project scope (.mcp.json)
@@ -123,7 +140,185 @@ fn execute_claude_mcp_command(app_handle: &AppHandle, args: Vec<&str>) -> Result
     }
 }
 
+// ============================================================================
+// Native Claude MCP Config
+// ============================================================================
+
+/// Claude MCP scopes in lookup precedence order
+const CLAUDE_MCP_SCOPES: [&str; 3] = ["local", "project", "user"];
+
+/// Files backing Claude's MCP scopes, resolved the same way as `claude mcp`:
+/// "user" is `mcpServers` in ~/.claude.json, "local" is the current directory's
+/// entry under `projects` in ~/.claude.json, "project" is ./.mcp.json
+struct ClaudeMcpFiles {
+    claude_json: PathBuf,
+    mcp_json: PathBuf,
+    project_key: String,
+}
+
+impl ClaudeMcpFiles {
+    fn current() -> Result<Self, String> {
+        let home_dir = dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?;
+        let cwd = std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+        Ok(Self {
+            claude_json: home_dir.join(".claude.json"),
+            mcp_json: cwd.join(".mcp.json"),
+            project_key: cwd.to_string_lossy().to_string(),
+        })
+    }
+
+    fn path(&self, scope: &str) -> &PathBuf {
+        if scope == "project" {
+            &self.mcp_json
+        } else {
+            &self.claude_json
+        }
+    }
+
+    /// Reads the config file for `scope`; a missing file is an empty object
+    fn read(&self, scope: &str) -> Result<serde_json::Value, String> {
+        let path = self.path(scope);
+        if !path.exists() {
+            return Ok(serde_json::json!({}));
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    fn write(&self, scope: &str, config: &serde_json::Value) -> Result<(), String> {
+        let path = self.path(scope);
+        let content = serde_json::to_string_pretty(config)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        write_atomic(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    fn servers<'a>(
+        &self,
+        config: &'a serde_json::Value,
+        scope: &str,
+    ) -> Option<&'a serde_json::Map<String, serde_json::Value>> {
+        let owner = if scope == "local" {
+            config.get("projects")?.get(&self.project_key)?
+        } else {
+            config
+        };
+        owner.get("mcpServers")?.as_object()
+    }
+
+    fn servers_mut<'a>(
+        &self,
+        config: &'a mut serde_json::Value,
+        scope: &str,
+    ) -> Result<&'a mut serde_json::Map<String, serde_json::Value>, String> {
+        let mut owner = config;
+        if scope == "local" {
+            for key in ["projects", self.project_key.as_str()] {
+                owner = owner
+                    .as_object_mut()
+                    .ok_or_else(|| format!("'{}' parent is not an object", key))?
+                    .entry(key)
+                    .or_insert_with(|| serde_json::json!({}));
+            }
+        }
+        owner
+            .as_object_mut()
+            .ok_or_else(|| "Config is not an object".to_string())?
+            .entry("mcpServers")
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .ok_or_else(|| "mcpServers is not an object".to_string())
+    }
+
+    /// All servers as (scope, name, config); a name shadowed by a higher-precedence
+    /// scope is reported once
+    fn list(&self) -> Result<Vec<(String, String, serde_json::Value)>, String> {
+        let mut servers: Vec<(String, String, serde_json::Value)> = Vec::new();
+        for scope in CLAUDE_MCP_SCOPES {
+            let config = self.read(scope)?;
+            for (name, server) in self.servers(&config, scope).into_iter().flatten() {
+                if !servers.iter().any(|(_, existing, _)| existing == name) {
+                    servers.push((scope.to_string(), name.clone(), server.clone()));
+                }
+            }
+        }
+        Ok(servers)
+    }
+
+    /// Adds a server to `scope`; returns false if the name already exists there
+    fn add(&self, scope: &str, name: &str, server: serde_json::Value) -> Result<bool, String> {
+        let mut config = self.read(scope)?;
+        let servers = self.servers_mut(&mut config, scope)?;
+        if servers.contains_key(name) {
+            return Ok(false);
+        }
+        servers.insert(name.to_string(), server);
+        self.write(scope, &config)?;
+        Ok(true)
+    }
+
+    /// Adds or replaces a server in `scope`; returns whether it already existed
+    fn set(&self, scope: &str, name: &str, server: serde_json::Value) -> Result<bool, String> {
+        let mut config = self.read(scope)?;
+        let existed = self.servers_mut(&mut config, scope)?.insert(name.to_string(), server).is_some();
+        self.write(scope, &config)?;
+        Ok(existed)
+    }
+
+    /// Removes a server from every scope that defines it; returns the scopes touched
+    fn remove(&self, name: &str) -> Result<Vec<String>, String> {
+        let mut removed = Vec::new();
+        for scope in CLAUDE_MCP_SCOPES {
+            let mut config = self.read(scope)?;
+            if self.servers(&config, scope).is_some_and(|s| s.contains_key(name)) {
+                self.servers_mut(&mut config, scope)?.remove(name);
+                self.write(scope, &config)?;
+                removed.push(scope.to_string());
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Builds a server entry in the format `claude mcp add` writes
+fn claude_mcp_server_json(
+    transport: &str,
+    command: Option<&str>,
+    args: &[String],
+    env: &HashMap<String, String>,
+    url: Option<&str>,
+) -> serde_json::Value {
+    match (transport, command, url) {
+        ("stdio", Some(command), _) => serde_json::json!({
+            "type": "stdio",
+            "command": command,
+            "args": args,
+            "env": env,
+        }),
+        (_, _, url) => serde_json::json!({ "type": transport, "url": url }),
+    }
+}
+
+/// Converts a native config entry into the MCPServer shape returned by the commands
+fn claude_mcp_server_from_json(scope: &str, name: &str, config: &serde_json::Value) -> MCPServer {
+    let server = parse_claude_mcp_server_config(name, config, scope, &load_claude_disabled_mcp_servers());
+    MCPServer {
+        name: server.name,
+        transport: server.transport,
+        command: server.command,
+        args: server.args,
+        env: server.env,
+        url: server.url,
+        scope: server.scope,
+        is_active: server.is_active,
+        status: server.status,
+    }
+}
+
 /// Adds a new MCP server
+///
+/// Written directly into the Claude config files; `claude mcp add` is only used
+/// when those files cannot be read or written.
 #[tauri::command]
 pub async fn mcp_add(
     app: AppHandle,
@@ -134,7 +329,7 @@ pub async fn mcp_add(
     env: HashMap<String, String>,
     url: Option<String>,
     scope: String,
-) -> Result<AddServerResult, String> {
+) -> Result<AddServerResult, AnyCodeError> {
     info!("Adding MCP server: {} with transport: {}", name, transport);
 
     // Prepare owned strings for environment variables
@@ -195,6 +390,34 @@ pub async fn mcp_add(
         }
     }
 
+    if !CLAUDE_MCP_SCOPES.contains(&scope.as_str()) {
+        return Ok(AddServerResult {
+            success: false,
+            message: format!("Invalid scope: {}. Must be one of: local, project, user", scope),
+            server_name: None,
+        });
+    }
+
+    let server_json = claude_mcp_server_json(&transport, command.as_deref(), &args, &env, url.as_deref());
+    match ClaudeMcpFiles::current().and_then(|files| files.add(&scope, &name, server_json)) {
+        Ok(true) => {
+            info!("Added MCP server '{}' to {} config", name, scope);
+            return Ok(AddServerResult {
+                success: true,
+                message: format!("Added {} MCP server {} to {} config", transport, name, scope),
+                server_name: Some(name),
+            });
+        }
+        Ok(false) => {
+            return Ok(AddServerResult {
+                success: false,
+                message: format!("MCP server {} already exists in {} config", name, scope),
+                server_name: None,
+            });
+        }
+        Err(e) => warn!("Failed to write MCP config directly, falling back to claude CLI: {}", e),
+    }
+
     match execute_claude_mcp_command(&app, cmd_args) {
         Ok(output) => {
             info!("Successfully added MCP server: {}", name);
@@ -216,10 +439,23 @@ pub async fn mcp_add(
 }
 
 /// Lists all configured MCP servers
+///
+/// Read from the Claude config files; falls back to parsing `claude mcp list`.
 #[tauri::command]
-pub async fn mcp_list(app: AppHandle) -> Result<Vec<MCPServer>, String> {
+pub async fn mcp_list(app: AppHandle) -> Result<Vec<MCPServer>, AnyCodeError> {
     info!("Listing MCP servers");
 
+    match ClaudeMcpFiles::current().and_then(|files| files.list()) {
+        Ok(entries) => {
+            info!("Found {} MCP servers in config files", entries.len());
+            return Ok(entries
+                .iter()
+                .map(|(scope, name, config)| claude_mcp_server_from_json(scope, name, config))
+                .collect());
+        }
+        Err(e) => warn!("Failed to read MCP config directly, falling back to claude CLI: {}", e),
+    }
+
     match execute_claude_mcp_command(&app, vec!["list"]) {
         Ok(output) => {
             info!("Raw output from 'claude mcp list': {:?}", output);
@@ -302,11 +538,7 @@ pub async fn mcp_list(app: AppHandle) -> Result<Vec<MCPServer>, String> {
                             url: None,
                             scope: "local".to_string(), // Default assumption
                             is_active: false,
-                            status: ServerStatus {
-                                running: false,
-                                error: None,
-                                last_checked: None,
-                            },
+                            status: ServerStatus::default(),
                         });
                         info!("Added server: {:?}", name);
 
@@ -332,16 +564,27 @@ pub async fn mcp_list(app: AppHandle) -> Result<Vec<MCPServer>, String> {
         }
         Err(e) => {
             error!("Failed to list MCP servers: {}", e);
-            Err(e.to_string())
+            Err(AnyCodeError::Engine(e.to_string()))
         }
     }
 }
 
-/// Gets details for a specific MCP server
+/// Gets details for a specific MCP server (local > project > user precedence)
 #[tauri::command]
-pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, String> {
+pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, AnyCodeError> {
     info!("Getting MCP server details for: {}", name);
 
+    match ClaudeMcpFiles::current().and_then(|files| files.list()) {
+        Ok(entries) => {
+            return entries
+                .iter()
+                .find(|(_, server_name, _)| *server_name == name)
+                .map(|(scope, server_name, config)| claude_mcp_server_from_json(scope, server_name, config))
+                .ok_or_else(|| AnyCodeError::NotFound(format!("No MCP server found with name: {}", name)));
+        }
+        Err(e) => warn!("Failed to read MCP config directly, falling back to claude CLI: {}", e),
+    }
+
     match execute_claude_mcp_command(&app, vec!["get", &name]) {
         Ok(output) => {
             // Parse the structured text output
@@ -392,25 +635,30 @@ pub async fn mcp_get(app: AppHandle, name: String) -> Result<MCPServer, String>
                 url,
                 scope,
                 is_active: false,
-                status: ServerStatus {
-                    running: false,
-                    error: None,
-                    last_checked: None,
-                },
+                status: ServerStatus::default(),
             })
         }
         Err(e) => {
             error!("Failed to get MCP server: {}", e);
-            Err(e.to_string())
+            Err(AnyCodeError::Engine(e.to_string()))
         }
     }
 }
 
-/// Removes an MCP server
+/// Removes an MCP server from every Claude scope that defines it
 #[tauri::command]
-pub async fn mcp_remove(app: AppHandle, name: String) -> Result<String, String> {
+pub async fn mcp_remove(app: AppHandle, name: String) -> Result<String, AnyCodeError> {
     info!("Removing MCP server: {}", name);
 
+    match ClaudeMcpFiles::current().and_then(|files| files.remove(&name)) {
+        Ok(scopes) if scopes.is_empty() => return Err(AnyCodeError::NotFound(format!("No MCP server found with name: {}", name))),
+        Ok(scopes) => {
+            info!("Removed MCP server '{}' from {} config", name, scopes.join(", "));
+            return Ok(format!("Removed MCP server {} from {} config", name, scopes.join(", ")));
+        }
+        Err(e) => warn!("Failed to update MCP config directly, falling back to claude CLI: {}", e),
+    }
+
     match execute_claude_mcp_command(&app, vec!["remove", &name]) {
         Ok(output) => {
             info!("Successfully removed MCP server: {}", name);
@@ -418,7 +666,7 @@ pub async fn mcp_remove(app: AppHandle, name: String) -> Result<String, String>
         }
         Err(e) => {
             error!("Failed to remove MCP server: {}", e);
-            Err(e.to_string())
+            Err(AnyCodeError::Engine(e.to_string()))
         }
     }
 }
@@ -430,7 +678,7 @@ pub async fn mcp_add_json(
     name: String,
     json_config: String,
     scope: String,
-) -> Result<AddServerResult, String> {
+) -> Result<AddServerResult, AnyCodeError> {
     info!(
         "Adding MCP server from JSON: {} with scope: {}",
         name, scope
@@ -469,7 +717,7 @@ pub async fn mcp_add_json(
 pub async fn mcp_add_from_claude_desktop(
     app: AppHandle,
     scope: String,
-) -> Result<ImportResult, String> {
+) -> Result<ImportResult, AnyCodeError> {
     info!(
         "Importing MCP servers from Claude Desktop with scope: {}",
         scope
@@ -477,7 +725,7 @@ pub async fn mcp_add_from_claude_desktop(
 
     // ⚡ 正确修复：所有平台的 Claude Code CLI 配置都在同一位置
     // Windows, macOS, Linux 都使用 ~/.claude/ 目录
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
+    let home_dir = dirs::home_dir().ok_or_else(|| AnyCodeError::Io(tr!("common.home_dir_unavailable")))?;
 
     let possible_paths = vec![
         // Claude Code CLI 配置文件（所有平台统一）
@@ -488,23 +736,23 @@ pub async fn mcp_add_from_claude_desktop(
     let config_path = possible_paths
         .into_iter()
         .find(|path| path.exists())
-        .ok_or_else(|| {
+        .ok_or_else(|| AnyCodeError::NotFound({
             "Claude Code configuration not found. Please make sure Claude Code is installed and configured.\n\
              Expected: ~/.claude/settings.json or ~/.claude.json".to_string()
-        })?;
+        }))?;
 
     // Read and parse the config file
     let config_content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read Claude Desktop config: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read Claude Desktop config: {}", e)))?;
 
     let config: serde_json::Value = serde_json::from_str(&config_content)
-        .map_err(|e| format!("Failed to parse Claude Desktop config: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse Claude Desktop config: {}", e)))?;
 
     // Extract MCP servers
     let mcp_servers = config
         .get("mcpServers")
         .and_then(|v| v.as_object())
-        .ok_or_else(|| "No MCP servers found in Claude Desktop config".to_string())?;
+        .ok_or_else(|| AnyCodeError::NotFound("No MCP servers found in Claude Desktop config".to_string()))?;
 
     let mut imported_count = 0;
     let mut failed_count = 0;
@@ -558,7 +806,7 @@ pub async fn mcp_add_from_claude_desktop(
 
         // Convert to JSON string
         let json_str = serde_json::to_string(&json_config)
-            .map_err(|e| format!("Failed to serialize config for {}: {}", name, e))?;
+            .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize config for {}: {}", name, e)))?;
 
         // Call add-json command
         match mcp_add_json(app.clone(), name.clone(), json_str, scope.clone()).await {
@@ -588,7 +836,7 @@ pub async fn mcp_add_from_claude_desktop(
                 server_results.push(ImportServerResult {
                     name: name.clone(),
                     success: false,
-                    error: Some(e),
+                    error: Some(e.to_string()),
                 });
                 error!("Error importing server {}: {}", name, error_msg);
             }
@@ -609,7 +857,7 @@ pub async fn mcp_add_from_claude_desktop(
 
 /// Starts Claude Code as an MCP server
 #[tauri::command]
-pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
+pub async fn mcp_serve(app: AppHandle) -> Result<String, AnyCodeError> {
     info!("Starting Claude Code as MCP server");
 
     // Start the server in a separate process
@@ -617,7 +865,7 @@ pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
         Ok(path) => path,
         Err(e) => {
             error!("Failed to find claude binary: {}", e);
-            return Err(e.to_string());
+            return Err(AnyCodeError::Engine(e.to_string()));
         }
     };
 
@@ -631,26 +879,26 @@ pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
         }
         Err(e) => {
             error!("Failed to start MCP server: {}", e);
-            Err(e.to_string())
+            Err(AnyCodeError::Engine(e.to_string()))
         }
     }
 }
 
 /// Tests connection to an MCP server
 #[tauri::command]
-pub async fn mcp_test_connection(app: AppHandle, name: String) -> Result<String, String> {
+pub async fn mcp_test_connection(app: AppHandle, name: String) -> Result<String, AnyCodeError> {
     info!("Testing connection to MCP server: {}", name);
 
     // For now, we'll use the get command to test if the server exists
     match execute_claude_mcp_command(&app, vec!["get", &name]) {
         Ok(_) => Ok(format!("Connection to {} successful", name)),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(AnyCodeError::Engine(e.to_string())),
     }
 }
 
 /// Resets project-scoped server approval choices
 #[tauri::command]
-pub async fn mcp_reset_project_choices(app: AppHandle) -> Result<String, String> {
+pub async fn mcp_reset_project_choices(app: AppHandle) -> Result<String, AnyCodeError> {
     info!("Resetting MCP project choices");
 
     match execute_claude_mcp_command(&app, vec!["reset-project-choices"]) {
@@ -660,47 +908,68 @@ pub async fn mcp_reset_project_choices(app: AppHandle) -> Result<String, String>
         }
         Err(e) => {
             error!("Failed to reset project choices: {}", e);
-            Err(e.to_string())
+            Err(AnyCodeError::Engine(e.to_string()))
         }
     }
 }
 
 /// Gets the status of MCP servers
+///
+/// Every enabled server configured for `engine` (default "claude") is checked in parallel:
+/// stdio servers are spawned and go through the MCP initialize handshake, URL
+/// servers are probed over streamable HTTP (falling back to legacy SSE). Each
+/// check is bounded by `timeout_ms` (default 10s).
 #[tauri::command]
-pub async fn mcp_get_server_status() -> Result<HashMap<String, ServerStatus>, String> {
-    info!("Getting MCP server status");
+pub async fn mcp_get_server_status(
+    app: AppHandle,
+    engine: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<HashMap<String, ServerStatus>, AnyCodeError> {
+    let engine = engine.unwrap_or_else(|| "claude".to_string());
+    info!("Getting MCP server status for engine: {}", engine);
+
+    let servers = mcp_list_by_engine(app, engine).await?;
+    let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_HEALTH_TIMEOUT_MS));
+    let checks = servers.into_iter().map(|server| async move {
+        let status = check_server_health(&server, timeout).await;
+        (server.name, status)
+    });
+    let statuses: HashMap<String, ServerStatus> = futures::future::join_all(checks).await.into_iter().collect();
 
-    // TODO: Implement actual status checking
-    // For now, return empty status
-    Ok(HashMap::new())
+    info!(
+        "MCP health check finished: {}/{} servers running",
+        statuses.values().filter(|s| s.running).count(),
+        statuses.len()
+    );
+    Ok(statuses)
 }
 
 /// Exports MCP server configuration from .claude.json
 #[tauri::command]
-pub async fn mcp_export_config() -> Result<String, String> {
+pub async fn mcp_export_config() -> Result<String, AnyCodeError> {
     info!("Exporting MCP server configuration from .claude.json");
 
     // Get the .claude.json path from home directory
-    let home_dir = dirs::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?;
+    let home_dir = dirs::home_dir().ok_or_else(|| AnyCodeError::Io(tr!("common.home_dir_unavailable")))?;
 
     let claude_config_path = home_dir.join(".claude.json");
 
     if !claude_config_path.exists() {
-        return Err("未找到 .claude.json 配置文件".to_string());
+        return Err(AnyCodeError::NotFound("未找到 .claude.json 配置文件".to_string()));
     }
 
     // Read the .claude.json file
     let config_content = fs::read_to_string(&claude_config_path)
-        .map_err(|e| format!("读取 .claude.json 文件失败: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("读取 .claude.json 文件失败: {}", e)))?;
 
     // Parse as JSON
     let config: serde_json::Value = serde_json::from_str(&config_content)
-        .map_err(|e| format!("解析 .claude.json 文件失败: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("解析 .claude.json 文件失败: {}", e)))?;
 
     // Extract mcpServers section
     let mcp_servers = config
         .get("mcpServers")
-        .ok_or_else(|| "在 .claude.json 中未找到 mcpServers 配置".to_string())?;
+        .ok_or_else(|| AnyCodeError::NotFound("在 .claude.json 中未找到 mcpServers 配置".to_string()))?;
 
     // Create export format matching Claude Desktop format
     let export_data = serde_json::json!({
@@ -709,7 +978,7 @@ pub async fn mcp_export_config() -> Result<String, String> {
 
     // Convert to pretty JSON string
     let export_json = serde_json::to_string_pretty(&export_data)
-        .map_err(|e| format!("序列化导出数据失败: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("序列化导出数据失败: {}", e)))?;
 
     info!("Successfully exported MCP configuration");
     Ok(export_json)
@@ -717,7 +986,7 @@ pub async fn mcp_export_config() -> Result<String, String> {
 
 /// Reads .mcp.json from the current project
 #[tauri::command]
-pub async fn mcp_read_project_config(project_path: String) -> Result<MCPProjectConfig, String> {
+pub async fn mcp_read_project_config(project_path: String) -> Result<MCPProjectConfig, AnyCodeError> {
     info!("Reading .mcp.json from project: {}", project_path);
 
     let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
@@ -733,12 +1002,12 @@ pub async fn mcp_read_project_config(project_path: String) -> Result<MCPProjectC
             Ok(config) => Ok(config),
             Err(e) => {
                 error!("Failed to parse .mcp.json: {}", e);
-                Err(format!("Failed to parse .mcp.json: {}", e))
+                Err(AnyCodeError::Config(format!("Failed to parse .mcp.json: {}", e)))
             }
         },
         Err(e) => {
             error!("Failed to read .mcp.json: {}", e);
-            Err(format!("Failed to read .mcp.json: {}", e))
+            Err(AnyCodeError::Io(format!("Failed to read .mcp.json: {}", e)))
         }
     }
 }
@@ -748,16 +1017,16 @@ pub async fn mcp_read_project_config(project_path: String) -> Result<MCPProjectC
 pub async fn mcp_save_project_config(
     project_path: String,
     config: MCPProjectConfig,
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     info!("Saving .mcp.json to project: {}", project_path);
 
     let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
 
     let json_content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize config: {}", e)))?;
 
-    fs::write(&mcp_json_path, json_content)
-        .map_err(|e| format!("Failed to write .mcp.json: {}", e))?;
+    write_atomic(&mcp_json_path, json_content)
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write .mcp.json: {}", e)))?;
 
     Ok("Project MCP configuration saved".to_string())
 }
@@ -793,8 +1062,78 @@ pub struct MCPServerExtended {
     pub engine: String,
     /// Startup timeout in seconds (Codex specific)
     pub startup_timeout_sec: Option<u64>,
-    /// Tool timeout in seconds (Codex specific)
+    /// Tool timeout in seconds (Codex `tool_timeout_sec`, Gemini `timeout`)
     pub tool_timeout_sec: Option<u64>,
+    /// Engine-specific fields
+    #[serde(flatten, default)]
+    pub extras: MCPServerExtras,
+}
+
+/// Engine-specific server fields beyond command/args/env/url
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MCPServerExtras {
+    /// Working directory for stdio servers (Codex, Gemini)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// HTTP headers for URL servers (Claude/Gemini `headers`, Codex `http_headers`)
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Skip tool call confirmations (Gemini `trust`)
+    #[serde(default)]
+    pub trust: Option<bool>,
+    /// Tool allowlist (Gemini `includeTools`, Codex `enabled_tools`)
+    #[serde(default)]
+    pub include_tools: Vec<String>,
+    /// Tool denylist (Gemini `excludeTools`, Codex `disabled_tools`)
+    #[serde(default)]
+    pub exclude_tools: Vec<String>,
+}
+
+impl MCPServerExtras {
+    /// Reads the fields from a Claude/Gemini JSON server entry
+    fn from_json(config: &serde_json::Value) -> Self {
+        let strings = |key: &str| -> Vec<String> {
+            config
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default()
+        };
+        Self {
+            cwd: config.get("cwd").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            headers: config
+                .get("headers")
+                .and_then(|v| v.as_object())
+                .map(|obj| {
+                    obj.iter()
+                        .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            trust: config.get("trust").and_then(|v| v.as_bool()),
+            include_tools: strings("includeTools"),
+            exclude_tools: strings("excludeTools"),
+        }
+    }
+
+    /// Writes the fields supported by `engine` into a JSON server entry; empty values remove the key
+    fn apply_json(&self, config: &mut serde_json::Map<String, serde_json::Value>, engine: &str) {
+        let mut set = |key: &str, value: Option<serde_json::Value>| match value {
+            Some(value) => {
+                config.insert(key.to_string(), value);
+            }
+            None => {
+                config.remove(key);
+            }
+        };
+        set("headers", (!self.headers.is_empty()).then(|| serde_json::json!(self.headers)));
+        if engine == "gemini" {
+            set("cwd", self.cwd.as_ref().map(|cwd| serde_json::json!(cwd)));
+            set("trust", self.trust.map(serde_json::Value::Bool));
+            set("includeTools", (!self.include_tools.is_empty()).then(|| serde_json::json!(self.include_tools)));
+            set("excludeTools", (!self.exclude_tools.is_empty()).then(|| serde_json::json!(self.exclude_tools)));
+        }
+    }
 }
 
 /// Lists MCP servers for a specific engine
@@ -802,14 +1141,14 @@ pub struct MCPServerExtended {
 pub async fn mcp_list_by_engine(
     app: AppHandle,
     engine: String,
-) -> Result<Vec<MCPServerExtended>, String> {
+) -> Result<Vec<MCPServerExtended>, AnyCodeError> {
     info!("[MCP] Listing servers for engine: {}", engine);
     
     match engine.as_str() {
-        "claude" => list_claude_mcp_servers(&app).await,
-        "codex" => list_codex_mcp_servers().await,
-        "gemini" => list_gemini_mcp_servers().await,
-        _ => Err(format!("Unknown engine: {}", engine)),
+        "claude" => list_claude_mcp_servers(&app).await.map_err(AnyCodeError::Config),
+        "codex" => list_codex_mcp_servers().await.map_err(AnyCodeError::Config),
+        "gemini" => list_gemini_mcp_servers().await.map_err(AnyCodeError::Config),
+        _ => Err(AnyCodeError::Validation(tr!("engine.unsupported", engine = engine))),
     }
 }
 
@@ -818,7 +1157,7 @@ async fn list_claude_mcp_servers(_app: &AppHandle) -> Result<Vec<MCPServerExtend
     info!("[MCP] Reading Claude MCP servers from config files");
     
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     // Load disabled servers list from settings.json
     let disabled_servers = load_claude_disabled_mcp_servers();
@@ -904,15 +1243,15 @@ fn parse_claude_mcp_server_config(
         url,
         scope: scope.to_string(),
         is_active: is_enabled,
-        status: ServerStatus {
-            running: false,
-            error: None,
-            last_checked: None,
-        },
+        status: ServerStatus::default(),
         enabled: is_enabled,
         engine: "claude".to_string(),
         startup_timeout_sec: None,
         tool_timeout_sec: None,
+        extras: MCPServerExtras {
+            headers: MCPServerExtras::from_json(config).headers,
+            ..Default::default()
+        },
     }
 }
 
@@ -970,12 +1309,22 @@ fn load_claude_disabled_mcp_servers() -> Vec<String> {
 }
 
 /// Gets list of all projects with their MCP server disabled status
+///
+/// `engine` defaults to Claude; "gemini" reads each project's `.gemini/settings.json`.
 #[tauri::command]
-pub async fn mcp_get_project_list(server_name: String) -> Result<Vec<serde_json::Value>, String> {
+pub async fn mcp_get_project_list(
+    server_name: String,
+    engine: Option<String>,
+) -> Result<Vec<serde_json::Value>, AnyCodeError> {
     info!("[MCP] Getting project list for server '{}'", server_name);
 
+    if engine.as_deref() == Some("gemini") {
+        use super::gemini::mcp::get_gemini_project_list;
+        return Ok(get_gemini_project_list(&server_name));
+    }
+
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| AnyCodeError::Io(tr!("common.home_dir_unavailable")))?;
 
     let claude_json_path = home_dir.join(".claude.json");
 
@@ -985,10 +1334,10 @@ pub async fn mcp_get_project_list(server_name: String) -> Result<Vec<serde_json:
     }
 
     let content = fs::read_to_string(&claude_json_path)
-        .map_err(|e| format!("Failed to read .claude.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Io(format!("Failed to read .claude.json: {}", e)))?;
 
     let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse .claude.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Config(format!("Failed to parse .claude.json: {}", e)))?;
 
     let mut projects = Vec::new();
 
@@ -1014,24 +1363,29 @@ pub async fn mcp_get_project_list(server_name: String) -> Result<Vec<serde_json:
 /// Sets enabled/disabled status for an MCP server for a specific project
 #[tauri::command]
 pub async fn mcp_set_enabled_for_project(
-    _engine: String,
+    engine: String,
     server_name: String,
     project_path: String,
     enabled: bool,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     info!("[MCP] Setting server '{}' enabled={} for project '{}'", server_name, enabled, project_path);
 
+    if engine == "gemini" {
+        use super::gemini::mcp::set_gemini_mcp_enabled_for_project;
+        return set_gemini_mcp_enabled_for_project(&server_name, &project_path, enabled).map_err(AnyCodeError::Config);
+    }
+
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| AnyCodeError::Io(tr!("common.home_dir_unavailable")))?;
 
     let claude_json_path = home_dir.join(".claude.json");
 
     // Read existing config or create new
     let mut config: serde_json::Value = if claude_json_path.exists() {
         let content = fs::read_to_string(&claude_json_path)
-            .map_err(|e| format!("Failed to read .claude.json: {}", e))?;
+            .map_err(|e| AnyCodeError::Io(format!("Failed to read .claude.json: {}", e)))?;
         serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse .claude.json: {}", e))?
+            .map_err(|e| AnyCodeError::Config(format!("Failed to parse .claude.json: {}", e)))?
     } else {
         serde_json::json!({})
     };
@@ -1039,27 +1393,27 @@ pub async fn mcp_set_enabled_for_project(
     // Get or create projects object
     let projects = config
         .as_object_mut()
-        .ok_or_else(|| "Config is not an object".to_string())?
+        .ok_or_else(|| AnyCodeError::Config("Config is not an object".to_string()))?
         .entry("projects")
         .or_insert_with(|| serde_json::json!({}));
 
     // Get or create project entry
     let project = projects
         .as_object_mut()
-        .ok_or_else(|| "Projects is not an object".to_string())?
+        .ok_or_else(|| AnyCodeError::Config("Projects is not an object".to_string()))?
         .entry(&project_path)
         .or_insert_with(|| serde_json::json!({}));
 
     // Get or create disabledMcpServers array for this project
     let disabled_servers = project
         .as_object_mut()
-        .ok_or_else(|| "Project is not an object".to_string())?
+        .ok_or_else(|| AnyCodeError::Config("Project is not an object".to_string()))?
         .entry("disabledMcpServers")
         .or_insert_with(|| serde_json::json!([]));
 
     let arr = disabled_servers
         .as_array_mut()
-        .ok_or_else(|| "disabledMcpServers is not an array".to_string())?;
+        .ok_or_else(|| AnyCodeError::Config("disabledMcpServers is not an array".to_string()))?;
 
     if enabled {
         // Remove from disabled list
@@ -1075,9 +1429,9 @@ pub async fn mcp_set_enabled_for_project(
 
     // Write back to .claude.json
     let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&claude_json_path, content)
-        .map_err(|e| format!("Failed to write .claude.json: {}", e))?;
+        .map_err(|e| AnyCodeError::Internal(format!("Failed to serialize config: {}", e)))?;
+    write_atomic(&claude_json_path, content)
+        .map_err(|e| AnyCodeError::Io(format!("Failed to write .claude.json: {}", e)))?;
 
     Ok(())
 }
@@ -1199,15 +1553,12 @@ async fn list_codex_mcp_servers() -> Result<Vec<MCPServerExtended>, String> {
             url: s.url,
             scope: "user".to_string(),
             is_active: !s.disabled,
-            status: ServerStatus {
-                running: false,
-                error: None,
-                last_checked: None,
-            },
+            status: ServerStatus::default(),
             enabled: !s.disabled,
             engine: "codex".to_string(),
             startup_timeout_sec: s.startup_timeout_sec,
             tool_timeout_sec: s.tool_timeout_sec,
+            extras: s.extras,
         })
         .collect();
     
@@ -1217,7 +1568,7 @@ async fn list_codex_mcp_servers() -> Result<Vec<MCPServerExtended>, String> {
 /// Lists Gemini MCP servers from settings.json
 async fn list_gemini_mcp_servers() -> Result<Vec<MCPServerExtended>, String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     let settings_path = home_dir.join(".gemini").join("settings.json");
     
@@ -1274,7 +1625,14 @@ async fn list_gemini_mcp_servers() -> Result<Vec<MCPServerExtended>, String> {
                     .collect()
             })
             .unwrap_or_default();
-        let url = config.get("url").and_then(|v| v.as_str()).map(|s| s.to_string());
+        // Gemini uses "url" for SSE and "httpUrl" for streamable HTTP
+        let url = config
+            .get("url")
+            .or_else(|| config.get("httpUrl"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        // Per-server request timeout in milliseconds
+        let tool_timeout_sec = config.get("timeout").and_then(|v| v.as_u64()).map(|ms| ms / 1000);
         
         let transport = if url.is_some() { "sse" } else { "stdio" }.to_string();
         
@@ -1287,15 +1645,12 @@ async fn list_gemini_mcp_servers() -> Result<Vec<MCPServerExtended>, String> {
             url,
             scope: "user".to_string(),
             is_active: !disabled_servers.contains(name),
-            status: ServerStatus {
-                running: false,
-                error: None,
-                last_checked: None,
-            },
+            status: ServerStatus::default(),
             enabled: !disabled_servers.contains(name),
             engine: "gemini".to_string(),
             startup_timeout_sec: None,
-            tool_timeout_sec: None,
+            tool_timeout_sec,
+            extras: MCPServerExtras::from_json(config),
         });
     }
     
@@ -1310,17 +1665,17 @@ pub async fn mcp_set_enabled(
     engine: String,
     server_name: String,
     enabled: bool,
-) -> Result<(), String> {
+) -> Result<(), AnyCodeError> {
     info!("[MCP] Setting {} server '{}' enabled={}", engine, server_name, enabled);
     
     match engine.as_str() {
-        "claude" => set_claude_mcp_enabled(&server_name, enabled),
+        "claude" => set_claude_mcp_enabled(&server_name, enabled).map_err(AnyCodeError::Config),
         "codex" => {
             use super::codex::mcp::set_codex_mcp_enabled;
-            set_codex_mcp_enabled(&server_name, enabled).map_err(|e| e.to_string())
+            set_codex_mcp_enabled(&server_name, enabled).map_err(|e| AnyCodeError::Config(e.to_string()))
         }
-        "gemini" => set_gemini_mcp_enabled(&server_name, enabled),
-        _ => Err(format!("Unknown engine: {}", engine)),
+        "gemini" => set_gemini_mcp_enabled(&server_name, enabled).map_err(AnyCodeError::Config),
+        _ => Err(AnyCodeError::Validation(tr!("engine.unsupported", engine = engine))),
     }
 }
 
@@ -1330,7 +1685,7 @@ fn set_claude_mcp_enabled(server_name: &str, enabled: bool) -> Result<(), String
     info!("[Claude MCP] Setting server '{}' enabled={} (project-level)", server_name, enabled);
 
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
 
     // Use .claude.json for project configuration
     let claude_json_path = home_dir.join(".claude.json");
@@ -1409,7 +1764,7 @@ fn set_claude_mcp_enabled(server_name: &str, enabled: bool) -> Result<(), String
             error!("[Claude MCP] Failed to serialize config: {}", e);
             format!("Failed to serialize config: {}", e)
         })?;
-    fs::write(&claude_json_path, content)
+    write_atomic(&claude_json_path, content)
         .map_err(|e| {
             error!("[Claude MCP] Failed to write .claude.json: {}", e);
             format!("Failed to write .claude.json: {}", e)
@@ -1422,7 +1777,7 @@ fn set_claude_mcp_enabled(server_name: &str, enabled: bool) -> Result<(), String
 /// Sets enabled/disabled status for a Gemini MCP server
 fn set_gemini_mcp_enabled(server_name: &str, enabled: bool) -> Result<(), String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     let settings_path = home_dir.join(".gemini").join("settings.json");
     
@@ -1466,7 +1821,7 @@ fn set_gemini_mcp_enabled(server_name: &str, enabled: bool) -> Result<(), String
     // Write back
     let content = serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&settings_path, content)
+    write_atomic(&settings_path, content)
         .map_err(|e| format!("Failed to write Gemini settings: {}", e))?;
     
     info!("[Gemini MCP] Set server '{}' enabled={}", server_name, enabled);
@@ -1485,7 +1840,7 @@ pub async fn mcp_add_by_engine(
     env: HashMap<String, String>,
     url: Option<String>,
     scope: String,
-) -> Result<AddServerResult, String> {
+) -> Result<AddServerResult, AnyCodeError> {
     info!("[MCP] Adding server '{}' to engine '{}'", name, engine);
     
     match engine.as_str() {
@@ -1504,9 +1859,10 @@ pub async fn mcp_add_by_engine(
                 args,
                 env,
                 url,
-                startup_timeout_sec: Some(20000),
-                tool_timeout_sec: Some(20000),
+                startup_timeout_sec: Some(CODEX_DEFAULT_TIMEOUT_SEC),
+                tool_timeout_sec: Some(CODEX_DEFAULT_TIMEOUT_SEC),
                 disabled: false,
+                extras: MCPServerExtras::default(),
             };
             
             match add_codex_mcp_server(&server) {
@@ -1522,8 +1878,8 @@ pub async fn mcp_add_by_engine(
                 }),
             }
         }
-        "gemini" => add_gemini_mcp_server(name, transport, command, args, env, url),
-        _ => Err(format!("Unknown engine: {}", engine)),
+        "gemini" => add_gemini_mcp_server(name, transport, command, args, env, url).map_err(AnyCodeError::Config),
+        _ => Err(AnyCodeError::Validation(tr!("engine.unsupported", engine = engine))),
     }
 }
 
@@ -1537,7 +1893,7 @@ fn add_gemini_mcp_server(
     url: Option<String>,
 ) -> Result<AddServerResult, String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     let settings_path = home_dir.join(".gemini").join("settings.json");
     
@@ -1597,7 +1953,7 @@ fn add_gemini_mcp_server(
     // Write back
     let content = serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&settings_path, content)
+    write_atomic(&settings_path, content)
         .map_err(|e| format!("Failed to write Gemini settings: {}", e))?;
     
     info!("[Gemini MCP] Added server '{}'", name);
@@ -1614,7 +1970,7 @@ pub async fn mcp_remove_by_engine(
     app: AppHandle,
     engine: String,
     server_name: String,
-) -> Result<String, String> {
+) -> Result<String, AnyCodeError> {
     info!("[MCP] Removing server '{}' from engine '{}'", server_name, engine);
     
     match engine.as_str() {
@@ -1623,17 +1979,17 @@ pub async fn mcp_remove_by_engine(
             use super::codex::mcp::remove_codex_mcp_server;
             remove_codex_mcp_server(&server_name)
                 .map(|_| format!("Server '{}' removed from Codex", server_name))
-                .map_err(|e| e.to_string())
+                .map_err(|e| AnyCodeError::Config(e.to_string()))
         }
-        "gemini" => remove_gemini_mcp_server(&server_name),
-        _ => Err(format!("Unknown engine: {}", engine)),
+        "gemini" => remove_gemini_mcp_server(&server_name).map_err(AnyCodeError::Config),
+        _ => Err(AnyCodeError::Validation(tr!("engine.unsupported", engine = engine))),
     }
 }
 
 /// Removes an MCP server from Gemini settings
 fn remove_gemini_mcp_server(server_name: &str) -> Result<String, String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     let settings_path = home_dir.join(".gemini").join("settings.json");
     
@@ -1662,7 +2018,7 @@ fn remove_gemini_mcp_server(server_name: &str) -> Result<String, String> {
     // Write back
     let content = serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&settings_path, content)
+    write_atomic(&settings_path, content)
         .map_err(|e| format!("Failed to write Gemini settings: {}", e))?;
     
     info!("[Gemini MCP] Removed server '{}'", server_name);
@@ -1671,6 +2027,10 @@ fn remove_gemini_mcp_server(server_name: &str) -> Result<String, String> {
 
 
 /// Updates an MCP server configuration for a specific engine
+///
+/// Timeouts are applied where the engine supports them (Codex: startup + tool,
+/// Gemini: tool timeout as `timeout`); `None` leaves them unchanged. `extras`
+/// replaces the engine-specific fields when given.
 #[tauri::command]
 pub async fn mcp_update_by_engine(
     _app: AppHandle,
@@ -1681,18 +2041,41 @@ pub async fn mcp_update_by_engine(
     env: HashMap<String, String>,
     url: Option<String>,
     enabled: bool,
-) -> Result<(), String> {
+    startup_timeout_sec: Option<u64>,
+    tool_timeout_sec: Option<u64>,
+    extras: Option<MCPServerExtras>,
+) -> Result<(), AnyCodeError> {
     info!("[MCP] Updating server '{}' for engine '{}'", server_name, engine);
     
     match engine.as_str() {
-        "claude" => update_claude_mcp_server(&server_name, command, args, env, url, enabled),
+        "claude" => update_claude_mcp_server(&server_name, command, args, env, url, enabled, extras.as_ref()).map_err(AnyCodeError::Config),
         "codex" => {
             use super::codex::mcp::update_codex_mcp_server;
-            update_codex_mcp_server(&server_name, command, args, env, url, enabled)
-                .map_err(|e| e.to_string())
+            update_codex_mcp_server(
+                &server_name,
+                command,
+                args,
+                env,
+                url,
+                enabled,
+                startup_timeout_sec,
+                tool_timeout_sec,
+                extras.as_ref(),
+            )
+            .map_err(|e| AnyCodeError::Config(e.to_string()))
         }
-        "gemini" => update_gemini_mcp_server(&server_name, command, args, env, url, enabled),
-        _ => Err(format!("Unknown engine: {}", engine)),
+        "gemini" => update_gemini_mcp_server(
+            &server_name,
+            command,
+            args,
+            env,
+            url,
+            enabled,
+            tool_timeout_sec,
+            extras.as_ref(),
+        )
+        .map_err(AnyCodeError::Config),
+        _ => Err(AnyCodeError::Validation(tr!("engine.unsupported", engine = engine))),
     }
 }
 
@@ -1704,9 +2087,10 @@ fn update_claude_mcp_server(
     env: HashMap<String, String>,
     url: Option<String>,
     enabled: bool,
+    extras: Option<&MCPServerExtras>,
 ) -> Result<(), String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     let config_path = home_dir.join(".claude.json");
     
@@ -1746,11 +2130,14 @@ fn update_claude_mcp_server(
     if let Some(u) = url {
         server_config.insert("url".to_string(), serde_json::json!(u));
     }
+    if let Some(extras) = extras {
+        extras.apply_json(server_config, "claude");
+    }
     
     // Write back config
     let content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&config_path, content)
+    write_atomic(&config_path, content)
         .map_err(|e| format!("Failed to write Claude config: {}", e))?;
     
     // Update enabled status in settings.json
@@ -1761,6 +2148,7 @@ fn update_claude_mcp_server(
 }
 
 /// Updates a Gemini MCP server configuration
+#[allow(clippy::too_many_arguments)]
 fn update_gemini_mcp_server(
     server_name: &str,
     command: Option<String>,
@@ -1768,9 +2156,11 @@ fn update_gemini_mcp_server(
     env: HashMap<String, String>,
     url: Option<String>,
     enabled: bool,
+    tool_timeout_sec: Option<u64>,
+    extras: Option<&MCPServerExtras>,
 ) -> Result<(), String> {
     let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not find home directory".to_string())?;
+        .ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     
     let settings_path = home_dir.join(".gemini").join("settings.json");
     
@@ -1812,13 +2202,23 @@ fn update_gemini_mcp_server(
         server_config.remove("env");
     }
     if let Some(u) = url {
-        server_config.insert("url".to_string(), serde_json::json!(u));
+        // Keep the key the server was configured with (httpUrl = streamable HTTP)
+        let key = if server_config.contains_key("httpUrl") { "httpUrl" } else { "url" };
+        server_config.insert(key.to_string(), serde_json::json!(u));
+    }
+    // Listed timeouts are whole seconds; only rewrite when the value actually changed
+    let current_timeout_sec = server_config.get("timeout").and_then(|v| v.as_u64()).map(|ms| ms / 1000);
+    if let Some(timeout) = tool_timeout_sec.filter(|t| Some(*t) != current_timeout_sec) {
+        server_config.insert("timeout".to_string(), serde_json::json!(timeout * 1000));
+    }
+    if let Some(extras) = extras {
+        extras.apply_json(server_config, "gemini");
     }
     
     // Write back settings
     let content = serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&settings_path, content)
+    write_atomic(&settings_path, content)
         .map_err(|e| format!("Failed to write Gemini settings: {}", e))?;
     
     // Update enabled status
@@ -1827,3 +2227,660 @@ fn update_gemini_mcp_server(
     info!("[Gemini MCP] Updated server '{}'", server_name);
     Ok(())
 }
+
+// ============================================================================
+// Cross-Engine MCP Sync
+// ============================================================================
+
+/// Codex timeouts used when the source engine has none (same as mcp_add_by_engine)
+const CODEX_DEFAULT_TIMEOUT_SEC: u64 = 20000;
+
+/// Outcome of syncing one server to one engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPSyncItem {
+    pub server_name: String,
+    pub target_engine: String,
+    /// "added" | "updated" | "skipped" | "failed"
+    pub status: String,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPSyncResult {
+    pub items: Vec<MCPSyncItem>,
+}
+
+/// Legacy SSE endpoints conventionally end with /sse; anything else is streamable HTTP
+fn is_sse_url(url: &str) -> bool {
+    url.trim_end_matches('/').ends_with("/sse")
+}
+
+/// Claude `.claude.json` entry; Claude has no per-server timeouts
+fn claude_sync_json(server: &MCPServerExtended) -> serde_json::Value {
+    let mut config = match (&server.command, &server.url) {
+        (Some(_), _) => claude_mcp_server_json("stdio", server.command.as_deref(), &server.args, &server.env, None),
+        (None, Some(url)) => {
+            let transport = if is_sse_url(url) { "sse" } else { "http" };
+            claude_mcp_server_json(transport, None, &[], &server.env, Some(url))
+        }
+        (None, None) => serde_json::json!({}),
+    };
+    if let Some(obj) = config.as_object_mut() {
+        server.extras.apply_json(obj, "claude");
+    }
+    config
+}
+
+/// Gemini settings.json entry; `timeout` is in milliseconds
+fn gemini_sync_json(server: &MCPServerExtended) -> serde_json::Value {
+    let mut config = serde_json::Map::new();
+    if let Some(command) = &server.command {
+        config.insert("command".to_string(), serde_json::json!(command));
+        if !server.args.is_empty() {
+            config.insert("args".to_string(), serde_json::json!(server.args));
+        }
+    } else if let Some(url) = &server.url {
+        let key = if is_sse_url(url) { "url" } else { "httpUrl" };
+        config.insert(key.to_string(), serde_json::json!(url));
+    }
+    if !server.env.is_empty() {
+        config.insert("env".to_string(), serde_json::json!(server.env));
+    }
+    if let Some(timeout) = server.tool_timeout_sec {
+        config.insert("timeout".to_string(), serde_json::json!(timeout * 1000));
+    }
+    server.extras.apply_json(&mut config, "gemini");
+    serde_json::Value::Object(config)
+}
+
+/// Codex `[mcp_servers]` entry
+fn codex_sync_server(server: &MCPServerExtended) -> super::codex::mcp::CodexMCPServer {
+    let is_stdio = server.command.is_some();
+    super::codex::mcp::CodexMCPServer {
+        name: server.name.clone(),
+        transport: if is_stdio { "stdio" } else { "sse" }.to_string(),
+        server_type: is_stdio.then(|| "stdio".to_string()),
+        command: server.command.clone(),
+        args: if is_stdio { server.args.clone() } else { Vec::new() },
+        env: server.env.clone(),
+        url: if is_stdio { None } else { server.url.clone() },
+        startup_timeout_sec: Some(server.startup_timeout_sec.unwrap_or(CODEX_DEFAULT_TIMEOUT_SEC)),
+        tool_timeout_sec: Some(server.tool_timeout_sec.unwrap_or(CODEX_DEFAULT_TIMEOUT_SEC)),
+        disabled: false,
+        extras: MCPServerExtras { trust: None, ..server.extras.clone() },
+    }
+}
+
+/// Adds or replaces a server in ~/.gemini/settings.json
+fn set_gemini_mcp_server(name: &str, server: serde_json::Value) -> Result<(), String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?;
+    let settings_path = home_dir.join(".gemini").join("settings.json");
+
+    let mut settings: serde_json::Value = if settings_path.exists() {
+        let content = fs::read_to_string(&settings_path)
+            .map_err(|e| format!("Failed to read Gemini settings: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse Gemini settings: {}", e))?
+    } else {
+        serde_json::json!({})
+    };
+
+    settings
+        .as_object_mut()
+        .ok_or_else(|| "Settings is not an object".to_string())?
+        .entry("mcpServers")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| "mcpServers is not an object".to_string())?
+        .insert(name.to_string(), server);
+
+    if let Some(parent) = settings_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create Gemini config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    write_atomic(&settings_path, content).map_err(|e| format!("Failed to write Gemini settings: {}", e))
+}
+
+/// Writes one translated server to the target engine's config
+fn write_synced_server(target: &str, server: &MCPServerExtended, exists: bool) -> Result<(), String> {
+    match target {
+        "claude" => ClaudeMcpFiles::current()
+            .and_then(|files| files.set("user", &server.name, claude_sync_json(server)))
+            .map(|_| ()),
+        "codex" => {
+            use super::codex::mcp::{add_codex_mcp_server, remove_codex_mcp_server};
+            if exists {
+                remove_codex_mcp_server(&server.name).map_err(|e| e.to_string())?;
+            }
+            add_codex_mcp_server(&codex_sync_server(server)).map_err(|e| e.to_string())
+        }
+        "gemini" => set_gemini_mcp_server(&server.name, gemini_sync_json(server)),
+        _ => Err(tr!("engine.unsupported", engine = target)),
+    }
+}
+
+/// Copies MCP servers from one engine to others, translating between
+/// `.claude.json`, Codex `config.toml` and Gemini `settings.json`
+///
+/// `server_names` limits the sync to those servers (all when omitted). Servers
+/// already present on a target are skipped unless `overwrite` is true.
+#[tauri::command]
+pub async fn mcp_sync_servers(
+    app: AppHandle,
+    source_engine: String,
+    target_engines: Vec<String>,
+    server_names: Option<Vec<String>>,
+    overwrite: Option<bool>,
+) -> Result<MCPSyncResult, AnyCodeError> {
+    info!("[MCP] Syncing servers from '{}' to {:?}", source_engine, target_engines);
+    let overwrite = overwrite.unwrap_or(false);
+
+    let servers: Vec<MCPServerExtended> = mcp_list_by_engine(app.clone(), source_engine.clone())
+        .await?
+        .into_iter()
+        .filter(|s| server_names.as_ref().is_none_or(|names| names.contains(&s.name)))
+        .collect();
+
+    let mut items = Vec::new();
+    for target in target_engines.iter().filter(|t| **t != source_engine) {
+        let existing: Vec<String> = mcp_list_by_engine(app.clone(), target.clone())
+            .await?
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+
+        for server in &servers {
+            let exists = existing.contains(&server.name);
+            let (status, message) = if exists && !overwrite {
+                ("skipped", Some("Server already exists".to_string()))
+            } else {
+                match write_synced_server(target, server, exists) {
+                    Ok(()) if exists => ("updated", None),
+                    Ok(()) => ("added", None),
+                    Err(e) => {
+                        error!("[MCP] Failed to sync '{}' to {}: {}", server.name, target, e);
+                        ("failed", Some(e))
+                    }
+                }
+            };
+            items.push(MCPSyncItem {
+                server_name: server.name.clone(),
+                target_engine: target.clone(),
+                status: status.to_string(),
+                message,
+            });
+        }
+    }
+
+    info!(
+        "[MCP] Sync finished: {} written, {} skipped, {} failed",
+        items.iter().filter(|i| i.status == "added" || i.status == "updated").count(),
+        items.iter().filter(|i| i.status == "skipped").count(),
+        items.iter().filter(|i| i.status == "failed").count()
+    );
+    Ok(MCPSyncResult { items })
+}
+
+// ============================================================================
+// MCP Health Checks
+// ============================================================================
+
+/// Protocol version offered in the initialize handshake
+pub(crate) const MCP_PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Default timeout for a single server health check
+const DEFAULT_HEALTH_TIMEOUT_MS: u64 = 10_000;
+
+/// Outcome of a successful handshake
+struct Handshake {
+    protocol_version: Option<String>,
+    tools: Vec<MCPToolInfo>,
+}
+
+fn jsonrpc_request(id: u64, method: &str, params: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn initialize_request() -> serde_json::Value {
+    jsonrpc_request(
+        1,
+        "initialize",
+        serde_json::json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "anycode", "version": env!("CARGO_PKG_VERSION") }
+        }),
+    )
+}
+
+fn initialized_notification() -> serde_json::Value {
+    serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+}
+
+/// Finds the JSON-RPC response with `id` among complete lines of newline-delimited
+/// JSON or SSE `data:` lines
+fn find_jsonrpc_response(text: &str, id: u64) -> Option<serde_json::Value> {
+    let complete = &text[..text.rfind('\n').map_or(0, |i| i + 1)];
+    complete.lines().find_map(|line| {
+        let payload = line.strip_prefix("data:").unwrap_or(line).trim();
+        let value: serde_json::Value = serde_json::from_str(payload).ok()?;
+        (value.get("id").and_then(|v| v.as_u64()) == Some(id)).then_some(value)
+    })
+}
+
+/// Extracts the message endpoint from a legacy SSE stream (`event: endpoint`)
+fn find_sse_endpoint(text: &str) -> Option<String> {
+    let complete = &text[..text.rfind('\n').map_or(0, |i| i + 1)];
+    let mut in_endpoint_event = false;
+    for line in complete.lines() {
+        if let Some(event) = line.strip_prefix("event:") {
+            in_endpoint_event = event.trim() == "endpoint";
+        } else if let Some(data) = line.strip_prefix("data:") {
+            if in_endpoint_event {
+                return Some(data.trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Returns the `result` of a JSON-RPC response, or its error message
+fn jsonrpc_result(response: serde_json::Value) -> Result<serde_json::Value, String> {
+    if let Some(error) = response.get("error") {
+        let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+        return Err(format!("MCP error: {}", message));
+    }
+    Ok(response.get("result").cloned().unwrap_or(serde_json::Value::Null))
+}
+
+fn parse_tools(result: &serde_json::Value) -> Vec<MCPToolInfo> {
+    result
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|tool| {
+                    Some(MCPToolInfo {
+                        name: tool.get("name")?.as_str()?.to_string(),
+                        description: tool.get("description").and_then(|d| d.as_str()).map(|d| d.to_string()),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Builds the handshake outcome from the initialize and tools/list responses.
+/// Servers without the tools capability report an empty tool list.
+fn handshake_from(initialize: serde_json::Value, tools: Option<serde_json::Value>) -> Result<Handshake, String> {
+    let init = jsonrpc_result(initialize)?;
+    Ok(Handshake {
+        protocol_version: init.get("protocolVersion").and_then(|v| v.as_str()).map(|v| v.to_string()),
+        tools: tools.and_then(|t| jsonrpc_result(t).ok()).map(|t| parse_tools(&t)).unwrap_or_default(),
+    })
+}
+
+/// Spawns a stdio server and performs initialize + tools/list over stdin/stdout
+async fn probe_stdio_server(
+    command: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> Result<Handshake, String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let mut std_cmd = create_command_with_env(command);
+    std_cmd.args(args).envs(env);
+    let mut cmd = tokio::process::Command::from(std_cmd);
+    cmd.stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+    super::claude::apply_no_window_async(&mut cmd);
+
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to start '{}': {}", command, e))?;
+    let mut stdin = child.stdin.take().ok_or("Failed to open server stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to open server stdout")?;
+    let mut lines = BufReader::new(stdout).lines();
+
+    async fn stdin_write(stdin: &mut tokio::process::ChildStdin, line: String) -> Result<(), String> {
+        stdin.write_all(line.as_bytes()).await.map_err(|e| format!("Failed to write to server: {}", e))?;
+        stdin.flush().await.map_err(|e| format!("Failed to write to server: {}", e))
+    }
+
+    async fn read_response(
+        lines: &mut tokio::io::Lines<BufReader<tokio::process::ChildStdout>>,
+        id: u64,
+    ) -> Result<serde_json::Value, String> {
+        while let Some(line) = lines.next_line().await.map_err(|e| format!("Failed to read from server: {}", e))? {
+            if let Some(response) = find_jsonrpc_response(&format!("{}\n", line), id) {
+                return Ok(response);
+            }
+        }
+        Err("Server exited before completing the handshake".to_string())
+    }
+
+    stdin_write(&mut stdin, format!("{}\n", initialize_request())).await?;
+    let initialize = read_response(&mut lines, 1).await?;
+    stdin_write(&mut stdin, format!("{}\n", initialized_notification())).await?;
+    stdin_write(&mut stdin, format!("{}\n", jsonrpc_request(2, "tools/list", serde_json::json!({})))).await?;
+    let tools = read_response(&mut lines, 2).await.ok();
+
+    let _ = child.kill().await;
+    handshake_from(initialize, tools)
+}
+
+
+/// Reads response chunks until `find` succeeds on the accumulated body
+async fn read_until<T>(
+    response: &mut reqwest::Response,
+    buffer: &mut String,
+    find: impl Fn(&str) -> Option<T>,
+) -> Result<T, String> {
+    loop {
+        if let Some(found) = find(buffer) {
+            return Ok(found);
+        }
+        match response.chunk().await.map_err(|e| format!("Failed to read response: {}", e))? {
+            Some(chunk) => buffer.push_str(&String::from_utf8_lossy(&chunk)),
+            // A plain JSON body may end without a trailing newline
+            None => return find(&format!("{}\n", buffer)).ok_or_else(|| "Connection closed before response".to_string()),
+        }
+    }
+}
+
+/// Expands `${VAR}` references from the environment, as the CLIs do for configured headers
+fn expand_env_refs(value: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + 2 + len];
+        expanded.push_str(&std::env::var(name).unwrap_or_default());
+        rest = &rest[start + 3 + len..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// The server's configured headers (e.g. `Authorization`), sent with every probe request
+fn probe_headers(headers: &HashMap<String, String>) -> Result<reqwest::header::HeaderMap, String> {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| format!("Invalid header name '{}': {}", name, e))?;
+        let value = reqwest::header::HeaderValue::from_str(&expand_env_refs(value))
+            .map_err(|e| format!("Invalid value for header '{}': {}", name, e))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Probes a streamable HTTP server, falling back to the legacy SSE transport
+async fn probe_http_server(url: &str, headers: &HashMap<String, String>) -> Result<Handshake, String> {
+    let client = reqwest::Client::builder()
+        .default_headers(probe_headers(headers)?)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    if url.trim_end_matches('/').ends_with("/sse") {
+        return probe_sse_server(&client, url).await;
+    }
+
+    let post = |body: serde_json::Value, session: Option<&str>| {
+        let mut request = client
+            .post(url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(&body);
+        if let Some(session) = session {
+            request = request.header("mcp-session-id", session);
+        }
+        request.send()
+    };
+
+    let mut response = post(initialize_request(), None)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+        return probe_sse_server(&client, url).await;
+    }
+    if !status.is_success() {
+        return Err(format!("Server responded with HTTP {}", status));
+    }
+
+    let session = response
+        .headers()
+        .get("mcp-session-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let initialize = read_until(&mut response, &mut String::new(), |text| find_jsonrpc_response(text, 1)).await?;
+
+    let _ = post(initialized_notification(), session.as_deref()).await;
+    let tools = match post(jsonrpc_request(2, "tools/list", serde_json::json!({})), session.as_deref()).await {
+        Ok(mut response) if response.status().is_success() => {
+            read_until(&mut response, &mut String::new(), |text| find_jsonrpc_response(text, 2))
+                .await
+                .ok()
+        }
+        _ => None,
+    };
+
+    handshake_from(initialize, tools)
+}
+
+/// Legacy HTTP+SSE transport: responses arrive on the GET stream, requests are
+/// POSTed to the endpoint announced by the server
+async fn probe_sse_server(client: &reqwest::Client, url: &str) -> Result<Handshake, String> {
+    let mut stream = client
+        .get(url)
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    if !stream.status().is_success() {
+        return Err(format!("Server responded with HTTP {}", stream.status()));
+    }
+
+    let mut buffer = String::new();
+    let endpoint = read_until(&mut stream, &mut buffer, find_sse_endpoint).await?;
+    let endpoint = reqwest::Url::parse(url)
+        .and_then(|base| base.join(&endpoint))
+        .map_err(|e| format!("Invalid SSE endpoint '{}': {}", endpoint, e))?;
+
+    let post = |body: serde_json::Value| client.post(endpoint.clone()).json(&body).send();
+
+    post(initialize_request())
+        .await
+        .map_err(|e| format!("Failed to send initialize: {}", e))?;
+    let initialize = read_until(&mut stream, &mut buffer, |text| find_jsonrpc_response(text, 1)).await?;
+
+    let _ = post(initialized_notification()).await;
+    let tools = match post(jsonrpc_request(2, "tools/list", serde_json::json!({}))).await {
+        Ok(_) => read_until(&mut stream, &mut buffer, |text| find_jsonrpc_response(text, 2)).await.ok(),
+        Err(_) => None,
+    };
+
+    handshake_from(initialize, tools)
+}
+
+/// Runs the handshake for one server within `timeout`
+async fn check_server_health(server: &MCPServerExtended, timeout: std::time::Duration) -> ServerStatus {
+    if !server.enabled {
+        return ServerStatus { error: Some("Server is disabled".to_string()), ..Default::default() };
+    }
+
+    let probe = async {
+        match (&server.command, &server.url) {
+            (Some(command), _) if server.transport == "stdio" => {
+                probe_stdio_server(command, &server.args, &server.env).await
+            }
+            (_, Some(url)) => probe_http_server(url, &server.extras.headers).await,
+            (Some(command), None) => probe_stdio_server(command, &server.args, &server.env).await,
+            (None, None) => Err("Server has neither a command nor a URL".to_string()),
+        }
+    };
+
+    let result = match tokio::time::timeout(timeout, probe).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Timed out after {}ms", timeout.as_millis())),
+    };
+
+    let last_checked = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .ok();
+
+    match result {
+        Ok(handshake) => ServerStatus {
+            running: true,
+            error: None,
+            last_checked,
+            protocol_version: handshake.protocol_version,
+            tools: handshake.tools,
+        },
+        Err(e) => {
+            info!("MCP server '{}' health check failed: {}", server.name, e);
+            ServerStatus { error: Some(e), last_checked, ..Default::default() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_jsonrpc_responses_and_tools() {
+        let sse = "event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"protocolVersion\":\"2025-03-26\"}}\n\n";
+        let init = find_jsonrpc_response(sse, 1).unwrap();
+        assert!(find_jsonrpc_response(sse, 2).is_none());
+        // Incomplete lines are not parsed until the newline arrives
+        assert!(find_jsonrpc_response("{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}", 1).is_none());
+
+        let tools = serde_json::json!({
+            "jsonrpc": "2.0", "id": 2,
+            "result": { "tools": [{ "name": "read_file", "description": "Read a file" }, { "name": "ls" }, { "description": "nameless" }] }
+        });
+        let handshake = handshake_from(init, Some(tools)).unwrap();
+        assert_eq!(handshake.protocol_version.as_deref(), Some("2025-03-26"));
+        assert_eq!(
+            handshake.tools,
+            vec![
+                MCPToolInfo { name: "read_file".to_string(), description: Some("Read a file".to_string()) },
+                MCPToolInfo { name: "ls".to_string(), description: None },
+            ]
+        );
+
+        let error = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32602, "message": "bad version" } });
+        assert_eq!(handshake_from(error, None).err().as_deref(), Some("MCP error: bad version"));
+
+        let stream = "event: endpoint\ndata: /messages?session_id=abc\n\n";
+        assert_eq!(find_sse_endpoint(stream).as_deref(), Some("/messages?session_id=abc"));
+        assert!(find_sse_endpoint("data: /messages\n").is_none());
+    }
+
+    #[test]
+    fn builds_probe_headers_with_env_refs() {
+        std::env::set_var("ANYCODE_TEST_MCP_TOKEN", "secret");
+        let headers = HashMap::from([
+            ("Authorization".to_string(), "Bearer ${ANYCODE_TEST_MCP_TOKEN}".to_string()),
+            ("X-Literal".to_string(), "a ${UNTERMINATED".to_string()),
+        ]);
+        let map = probe_headers(&headers).unwrap();
+        assert_eq!(map.get("authorization").unwrap(), "Bearer secret");
+        assert_eq!(map.get("x-literal").unwrap(), "a ${UNTERMINATED");
+
+        let invalid = HashMap::from([("Bad Header".to_string(), "x".to_string())]);
+        assert!(probe_headers(&invalid).is_err());
+    }
+
+    #[test]
+    fn translates_servers_between_engine_formats() {
+        let server = MCPServerExtended {
+            name: "docs".to_string(),
+            transport: "sse".to_string(),
+            command: None,
+            args: vec![],
+            env: HashMap::from([("TOKEN".to_string(), "x".to_string())]),
+            url: Some("https://example.com/mcp".to_string()),
+            scope: "user".to_string(),
+            is_active: true,
+            status: ServerStatus::default(),
+            enabled: true,
+            engine: "codex".to_string(),
+            startup_timeout_sec: Some(30),
+            tool_timeout_sec: Some(120),
+            extras: MCPServerExtras {
+                headers: HashMap::from([("Authorization".to_string(), "Bearer t".to_string())]),
+                trust: Some(true),
+                ..Default::default()
+            },
+        };
+
+        let claude = claude_sync_json(&server);
+        assert_eq!(claude["type"], "http");
+        assert_eq!(claude["url"], "https://example.com/mcp");
+        assert_eq!(claude["headers"]["Authorization"], "Bearer t");
+        assert!(claude.get("timeout").is_none() && claude.get("trust").is_none());
+
+        let gemini = gemini_sync_json(&server);
+        assert_eq!(gemini["httpUrl"], "https://example.com/mcp");
+        assert_eq!(gemini["timeout"], 120_000);
+        assert_eq!(gemini["env"]["TOKEN"], "x");
+        assert_eq!(gemini["trust"], true);
+        assert_eq!(MCPServerExtras::from_json(&gemini), server.extras);
+
+        let stdio = MCPServerExtended {
+            command: Some("npx".to_string()),
+            args: vec!["-y".to_string(), "pkg".to_string()],
+            url: None,
+            startup_timeout_sec: None,
+            tool_timeout_sec: None,
+            engine: "claude".to_string(),
+            ..server
+        };
+        let codex = codex_sync_server(&stdio);
+        assert_eq!(codex.server_type.as_deref(), Some("stdio"));
+        assert_eq!(codex.startup_timeout_sec, Some(CODEX_DEFAULT_TIMEOUT_SEC));
+        assert_eq!(claude_sync_json(&stdio)["args"], serde_json::json!(["-y", "pkg"]));
+        assert!(gemini_sync_json(&stdio).get("timeout").is_none());
+    }
+
+    #[test]
+    fn manages_claude_mcp_scopes_natively() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = ClaudeMcpFiles {
+            claude_json: dir.path().join(".claude.json"),
+            mcp_json: dir.path().join("repo").join(".mcp.json"),
+            project_key: dir.path().join("repo").to_string_lossy().to_string(),
+        };
+        fs::create_dir_all(dir.path().join("repo")).unwrap();
+        fs::write(&files.claude_json, r#"{"numStartups": 3, "mcpServers": {"fs": {"command": "npx"}}}"#).unwrap();
+
+        let env = HashMap::from([("TOKEN".to_string(), "x".to_string())]);
+        let stdio = claude_mcp_server_json("stdio", Some("node"), &["a.js".to_string()], &env, None);
+        assert!(files.add("local", "fs", stdio.clone()).unwrap());
+        assert!(!files.add("local", "fs", stdio).unwrap());
+        let sse = claude_mcp_server_json("sse", None, &[], &HashMap::new(), Some("http://localhost:3000/sse"));
+        assert!(files.add("project", "docs", sse).unwrap());
+
+        let listed: Vec<(String, String)> = files.list().unwrap().into_iter().map(|(s, n, _)| (s, n)).collect();
+        assert_eq!(
+            listed,
+            vec![("local".to_string(), "fs".to_string()), ("project".to_string(), "docs".to_string())]
+        );
+
+        // Unrelated keys survive the rewrite
+        let config: serde_json::Value = serde_json::from_str(&fs::read_to_string(&files.claude_json).unwrap()).unwrap();
+        assert_eq!(config["numStartups"], 3);
+        assert_eq!(config["projects"][&files.project_key]["mcpServers"]["fs"]["env"]["TOKEN"], "x");
+
+        assert_eq!(files.remove("fs").unwrap(), vec!["local".to_string(), "user".to_string()]);
+        assert!(files.remove("fs").unwrap().is_empty());
+        assert_eq!(files.list().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,262 @@
+//! MCP 服务器市场
+//!
+//! 内置一份常用 MCP 服务器清单（`mcp_registry.json`），可从远端刷新并缓存到
+//! `~/.anycode/mcp_registry.json`。安装时按引擎写入对应配置（Claude / Codex / Gemini），
+//! 缺少必填环境变量时返回缺失列表，由前端提示用户填写后再次安装。
+//! 从团队共享配置仓库同步的服务器（见 `config_sync`）追加在清单末尾。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::config_sync::shared_mcp_servers;
+use super::mcp::mcp_add_by_engine;
+use super::data_root::anycode_dir;
+
+/// 内置清单
+const BUNDLED_REGISTRY: &str = include_str!("mcp_registry.json");
+
+/// 远端清单地址（与内置清单同一文件）
+const REMOTE_REGISTRY_URL: &str =
+    "https://raw.githubusercontent.com/anyme123/Any-code/main/src-tauri/src/commands/mcp_registry.json";
+
+const REMOTE_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 服务器需要的环境变量（或参数占位符）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryEnvVar {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+    /// 敏感值（前端以密码框输入）
+    #[serde(default)]
+    pub secret: bool,
+    /// 只用于替换参数中的 `${NAME}`，不写入 env
+    #[serde(default)]
+    pub arg_only: bool,
+}
+
+/// 清单中的一个服务器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryServer {
+    pub name: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// "stdio" | "sse" | "http"
+    pub transport: String,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub env: Vec<RegistryEnvVar>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpRegistry {
+    pub version: u32,
+    pub servers: Vec<RegistryServer>,
+}
+
+/// 安装结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryInstallResult {
+    pub success: bool,
+    pub message: String,
+    /// 缺少的必填变量（非空时未安装，前端应提示填写）
+    pub missing_env: Vec<RegistryEnvVar>,
+}
+
+fn get_cache_path() -> Result<std::path::PathBuf, String> {
+    Ok(anycode_dir()?.join("mcp_registry.json"))
+}
+
+fn bundled_registry() -> McpRegistry {
+    serde_json::from_str(BUNDLED_REGISTRY).expect("bundled mcp_registry.json is valid")
+}
+
+/// 读取缓存的远端清单（版本不高于内置清单时忽略），再追加共享仓库中的服务器
+pub(crate) fn load_registry() -> McpRegistry {
+    let bundled = bundled_registry();
+    let cached = get_cache_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<McpRegistry>(&content).ok());
+    let mut registry = match cached {
+        Some(cached) if cached.version >= bundled.version => cached,
+        _ => bundled,
+    };
+    for server in shared_mcp_servers() {
+        if !registry.servers.iter().any(|s| s.name == server.name) {
+            registry.servers.push(server);
+        }
+    }
+    registry
+}
+
+async fn fetch_remote_registry() -> Result<McpRegistry, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REMOTE_FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let resp = client
+        .get(REMOTE_REGISTRY_URL)
+        .send()
+        .await
+        .map_err(|e| format!("请求 MCP 清单失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("MCP 清单地址返回 {}", resp.status()));
+    }
+    let registry: McpRegistry = resp.json().await.map_err(|e| format!("解析 MCP 清单失败: {}", e))?;
+
+    let path = get_cache_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&registry).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("写入 MCP 清单缓存失败: {}", e))?;
+    Ok(registry)
+}
+
+/// 用用户填写的值展开参数和环境变量；返回 (args, env, 缺失的必填变量)
+fn resolve_install(
+    server: &RegistryServer,
+    values: &HashMap<String, String>,
+) -> (Vec<String>, HashMap<String, String>, Vec<RegistryEnvVar>) {
+    let value_of = |name: &str| values.get(name).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+    let missing = server
+        .env
+        .iter()
+        .filter(|var| var.required && value_of(&var.name).is_none())
+        .cloned()
+        .collect();
+
+    let args = server
+        .args
+        .iter()
+        .map(|arg| {
+            server.env.iter().fold(arg.clone(), |arg, var| {
+                arg.replace(&format!("${{{}}}", var.name), value_of(&var.name).unwrap_or(""))
+            })
+        })
+        .collect();
+
+    let env = server
+        .env
+        .iter()
+        .filter(|var| !var.arg_only)
+        .filter_map(|var| value_of(&var.name).map(|v| (var.name.clone(), v.to_string())))
+        .collect();
+
+    (args, env, missing)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出 MCP 服务器清单；`refresh` 为 true 时先从远端更新（失败则使用本地清单）
+#[tauri::command]
+pub async fn mcp_registry_list(refresh: Option<bool>) -> Result<Vec<RegistryServer>, String> {
+    if refresh.unwrap_or(false) {
+        match fetch_remote_registry().await {
+            Ok(registry) => {
+                log::info!("[McpRegistry] Refreshed {} servers from remote", registry.servers.len());
+                return Ok(registry.servers);
+            }
+            Err(e) => log::warn!("[McpRegistry] Remote refresh failed, using local registry: {}", e),
+        }
+    }
+    Ok(load_registry().servers)
+}
+
+/// 把清单中的服务器安装到指定引擎
+///
+/// `env` 为用户填写的变量值；缺少必填项时不写入配置，返回 `missing_env`。
+#[tauri::command]
+pub async fn mcp_registry_install(
+    app: AppHandle,
+    name: String,
+    engine: String,
+    scope: Option<String>,
+    env: Option<HashMap<String, String>>,
+) -> Result<RegistryInstallResult, String> {
+    let server = load_registry()
+        .servers
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("清单中没有 MCP 服务器: {}", name))?;
+
+    let (args, env, missing_env) = resolve_install(&server, &env.unwrap_or_default());
+    if !missing_env.is_empty() {
+        return Ok(RegistryInstallResult {
+            success: false,
+            message: format!(
+                "缺少必填变量: {}",
+                missing_env.iter().map(|v| v.name.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+            missing_env,
+        });
+    }
+
+    log::info!("[McpRegistry] Installing '{}' for {}", name, engine);
+    let result = mcp_add_by_engine(
+        app,
+        engine,
+        server.name,
+        server.transport,
+        server.command,
+        args,
+        env,
+        server.url,
+        scope.unwrap_or_else(|| "user".to_string()),
+    )
+    .await?;
+
+    Ok(RegistryInstallResult {
+        success: result.success,
+        message: result.message,
+        missing_env: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_registry_parses_and_resolves_placeholders() {
+        let registry = bundled_registry();
+        assert!(registry.servers.iter().all(|s| s.command.is_some() || s.url.is_some()));
+
+        let filesystem = registry.servers.iter().find(|s| s.name == "filesystem").unwrap();
+        let (_, _, missing) = resolve_install(filesystem, &HashMap::new());
+        assert_eq!(missing.len(), 1);
+
+        let values = HashMap::from([("ALLOWED_DIR".to_string(), " /work ".to_string())]);
+        let (args, env, missing) = resolve_install(filesystem, &values);
+        assert!(missing.is_empty());
+        assert_eq!(args.last().map(String::as_str), Some("/work"));
+        assert!(env.is_empty(), "argOnly values stay out of env");
+
+        let github = registry.servers.iter().find(|s| s.name == "github").unwrap();
+        let values = HashMap::from([("GITHUB_PERSONAL_ACCESS_TOKEN".to_string(), "ghp_x".to_string())]);
+        let (_, env, _) = resolve_install(github, &values);
+        assert_eq!(env.get("GITHUB_PERSONAL_ACCESS_TOKEN").map(String::as_str), Some("ghp_x"));
+    }
+}
@@ -1,24 +1,86 @@
 pub mod acemcp;
+pub mod anycode_mcp_server;  // 内置 MCP 服务器（向外部 CLI 暴露会话、提示词历史和变更记录）
+pub mod app_settings;  // 通用键值设置（app_settings 表）
+pub mod attachments;  // 提示词附件（暂存、转换为各 CLI 的附件语法）
+pub mod auto_fix;  // 自动修复循环（验证失败时自动发送修复提示词）
+pub mod backup;  // 数据备份与恢复
+pub mod branch_compare;  // 会话改动与其他分支的对比（合并冲突预检）
+pub mod capabilities;  // 引擎 / 代理商 / 模型能力矩阵
 pub mod claude;
 pub mod clipboard;
 pub mod codex;  // OpenAI Codex integration
+pub mod command_audit;  // Agent 执行命令的审计日志
+pub mod command_metrics;  // 命令调用耗时统计（可选，仅本地）
+pub mod commit_message;  // 提交信息生成（Conventional Commits）
+pub mod compare;  // 多引擎对比（隔离 worktree 并行执行）
+pub mod config_sync;  // 团队共享配置同步（只读 git 仓库）
+pub mod custom_commands;  // 自定义斜杠命令（~/.anycode/commands）
+pub mod data_root;  // AnyCode 数据目录（自定义目录、便携模式与迁移）
 pub mod engine_status;  // 统一的引擎状态检查
 pub mod gemini;  // Google Gemini CLI integration
 pub mod context_commands;
 pub mod context_manager;
+pub mod db_migrations;  // agents.db 版本化迁移
+pub mod db_pool;  // agents.db 连接池
+pub mod deep_link;  // anycode:// 深度链接与单实例转发
+pub mod diagnostics;  // 诊断信息导出
+pub mod docker_backend;  // Docker 容器执行后端
 pub mod enhanced_hooks;
 pub mod extensions;
 pub mod file_operations;
 pub mod git_stats;
+pub mod guardrails;  // 文件系统护栏（按项目的写入策略）
+pub mod headless;  // 一次性引擎执行（供流水线等后端编排使用）
+pub mod headless_cli;  // 无界面 CLI 伴随模式（--headless，stdin/stdout JSON）
+pub mod hook_bridge;  // Claude Code hook 事件桥接（外部启动的会话也能通知和跟踪变更）
+pub mod hotkeys;  // 全局快捷键（快速输入提示词、重跑上一条）
 pub mod ide;  // IDE 集成（文件跳转）
+pub mod instruction_sync;  // 统一指令文件同步（CLAUDE.md / AGENTS.md / GEMINI.md）
 pub mod mcp;
+pub mod mcp_registry;  // MCP 服务器市场（内置 + 远端清单，一键安装到各引擎）
+pub mod model_routing;  // 模型别名与路由规则
+pub mod notification_channels;  // 外发通知渠道（提示音、webhook、Slack / Discord）
+pub mod notifications;  // 桌面通知（完成、失败、预算上限、等待审批）与通知历史
+pub mod output_batcher;  // 流式输出批量发送（合并增量、限流）
 pub mod permission_config;
+pub mod pipeline;  // 多引擎流水线编排
+pub mod post_processors;  // 提示词完成后的后处理（格式化 / Lint / 测试）
+pub mod preflight;  // 发送前的上下文窗口预估
+pub mod project_defaults;  // 项目级默认执行选项
+pub mod project_memory;  // 项目记忆（跨会话的决策、约定笔记）
+pub mod project_registry;  // 项目注册表（与引擎无关的项目列表）
+pub mod project_settings;  // 项目级设置（WSL 发行版等）
+pub mod prompt_history;  // 提示词历史（全局去重、收藏、标签）
+pub mod prompt_library;  // 提示词库（模板变量、执行时套用）
 pub mod prompt_tracker;
+pub mod prompt_translation;  // 提示词双向翻译（发送前翻译提示词、回复翻译回用户语言）
 pub mod provider;
+pub mod provider_bundle;  // 代理商预设导入导出（团队共享，不含密钥）
+pub mod provider_metrics;  // 代理商延迟与错误率统计
+pub mod rate_limit;  // 限流检测与退避重排队
+pub mod recent_files;  // 最近修改的文件（git 状态、修改时间、变更记录）
+pub mod pty;  // 内嵌终端（伪终端会话）
+pub mod repo_map;  // 仓库符号大纲（供提示词注入）
+pub mod retention;  // 会话保留策略与自动清理
+pub mod semantic_index;  // 本地语义索引（向量检索相关代码）
+pub mod session_compaction;  // 恢复会话前的历史压缩（总结 + 最近几轮）
+pub mod session_fork;  // 会话分叉（在某条提示词处复制出新会话）
+pub mod session_log;  // 按会话落盘的执行日志
+pub mod session_pages;  // 会话历史分页加载（惰性行索引）
+pub mod session_metadata;  // 会话标签、置顶、归档
+pub mod session_summary;  // 会话总结（目标、文件、决策、待办）
 pub mod session_watcher;  // 会话文件监听（实时同步外部工具的消息）
 pub mod simple_git;
+pub mod ssh_remote;  // 远程 SSH 执行
 pub mod storage;
+pub mod storage_report;  // AI 工具数据的磁盘占用统计
+pub mod terminal;  // 外部终端启动（可配置终端模拟器）
+pub mod test_watch;  // 会话中的持续测试（随 Agent 的文件变更重跑）
+pub mod tokenizer;  // Token 计数（tiktoken 词表，按模型选择）
+pub mod tool_approval;  // 工具调用审批（转发引擎的权限请求给前端）
+pub mod trash;  // 工作区回收站（删除的文件可恢复）
 pub mod translator;
+pub mod tray;  // 系统托盘（关闭主窗口后在后台继续执行）
 pub mod url_utils;  // API URL 规范化工具
 pub mod usage;
 pub mod window;  // 多窗口管理
@@ -0,0 +1,355 @@
+//! 模型别名与路由规则
+//!
+//! 别名（如 "fast"、"smart"）按引擎和代理商映射到具体模型；路由规则按提示词长度把请求的
+//! 模型改写为另一个别名或模型（例如提示词超过 20k token 时使用 "smart"）。执行命令在解析完
+//! 项目默认值后调用 `resolve_model`，因此前端和项目默认值里都可以直接使用别名。
+//!
+//! 配置保存在 app_settings 的 `model_routing` 键中。规则按顺序匹配，第一条命中的生效；
+//! 别名的目标优先匹配本次执行使用的代理商预设，其次是未指定代理商的目标。
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::app_settings::{get_setting, set_setting};
+use super::tokenizer::count_text;
+
+pub const MODEL_ROUTING_KEY: &str = "model_routing";
+
+const ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 别名在某个引擎（可选：某个代理商）下对应的模型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasTarget {
+    pub engine: String,
+    /// 代理商预设 ID；为空时作为该引擎的默认目标
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelAlias {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub targets: Vec<AliasTarget>,
+}
+
+/// 路由规则：条件全部满足时把模型改为 `use_model`（别名或具体模型）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutingRule {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 只对该引擎生效（为空时对所有引擎生效）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    /// 只在请求的模型（或别名）为该值时生效（为空时不限）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_prompt_tokens: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_prompt_tokens: Option<usize>,
+    pub use_model: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModelRoutingConfig {
+    pub aliases: Vec<ModelAlias>,
+    pub rules: Vec<RoutingRule>,
+}
+
+/// 模型解析结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelResolution {
+    /// 传给 CLI 的模型（为空时使用 CLI 默认模型）
+    pub model: Option<String>,
+    /// 命中的路由规则
+    pub rule_id: Option<String>,
+    /// 展开的别名
+    pub alias: Option<String>,
+}
+
+impl RoutingRule {
+    fn applies_to(&self, engine: &str, requested: Option<&str>) -> bool {
+        self.enabled
+            && self.engine.as_deref().is_none_or(|e| e == engine)
+            && self.when_model.as_deref().is_none_or(|m| Some(m) == requested)
+    }
+
+    fn has_length_condition(&self) -> bool {
+        self.min_prompt_tokens.is_some() || self.max_prompt_tokens.is_some()
+    }
+
+    fn matches_length(&self, tokens: usize) -> bool {
+        self.min_prompt_tokens.is_none_or(|min| tokens >= min) && self.max_prompt_tokens.is_none_or(|max| tokens <= max)
+    }
+}
+
+impl ModelAlias {
+    /// 优先选择指定了当前代理商的目标
+    fn target_for(&self, engine: &str, provider: Option<&str>) -> Option<&AliasTarget> {
+        let for_engine = || self.targets.iter().filter(move |t| t.engine == engine);
+        for_engine()
+            .find(|t| provider.is_some() && t.provider.as_deref() == provider)
+            .or_else(|| for_engine().find(|t| t.provider.is_none()))
+    }
+}
+
+/// 先应用路由规则，再展开别名；`prompt_tokens` 只在有规则需要时计算
+pub fn resolve(
+    config: &ModelRoutingConfig,
+    engine: &str,
+    model: Option<&str>,
+    provider: Option<&str>,
+    prompt_tokens: impl FnOnce() -> usize,
+) -> ModelResolution {
+    let requested = model.map(str::trim).filter(|m| !m.is_empty());
+    let mut resolution = ModelResolution::default();
+
+    let mut tokens = None;
+    let mut prompt_tokens = Some(prompt_tokens);
+    let mut current = requested.map(str::to_string);
+    for rule in config.rules.iter().filter(|r| r.applies_to(engine, requested)) {
+        if rule.has_length_condition() {
+            let tokens = *tokens.get_or_insert_with(|| prompt_tokens.take().map_or(0, |count| count()));
+            if !rule.matches_length(tokens) {
+                continue;
+            }
+        }
+        current = Some(rule.use_model.clone());
+        resolution.rule_id = Some(rule.id.clone());
+        break;
+    }
+
+    resolution.model = match current {
+        Some(name) => match config.aliases.iter().find(|a| a.name == name) {
+            Some(alias) => {
+                resolution.alias = Some(alias.name.clone());
+                let target = alias.target_for(engine, provider).map(|t| t.model.clone());
+                if target.is_none() {
+                    log::warn!("[ModelRouting] Alias '{}' has no target for {}, using the CLI default", name, engine);
+                }
+                target
+            }
+            None => Some(name),
+        },
+        None => None,
+    };
+    resolution
+}
+
+async fn load_config() -> ModelRoutingConfig {
+    match get_setting(MODEL_ROUTING_KEY).await {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("[ModelRouting] Failed to load model routing config: {}", e);
+            ModelRoutingConfig::default()
+        }
+    }
+}
+
+/// 执行前解析模型（别名、路由规则）；未配置别名和规则时原样返回
+pub async fn resolve_model(engine: &str, model: Option<String>, prompt: &str, provider: Option<&str>) -> Option<String> {
+    let config = load_config().await;
+    if config.aliases.is_empty() && config.rules.is_empty() {
+        return model;
+    }
+    let resolution = resolve(&config, engine, model.as_deref(), provider, || {
+        count_text(model.as_deref().unwrap_or_default(), prompt)
+    });
+    if resolution.model != model {
+        log::info!(
+            "[ModelRouting] {} model {:?} -> {:?} (rule: {:?}, alias: {:?})",
+            engine,
+            model,
+            resolution.model,
+            resolution.rule_id,
+            resolution.alias
+        );
+    }
+    resolution.model
+}
+
+fn validate_alias(alias: &ModelAlias) -> Result<(), String> {
+    if alias.name.trim().is_empty() || alias.name.chars().any(char::is_whitespace) {
+        return Err("别名不能为空或包含空格".to_string());
+    }
+    for target in &alias.targets {
+        if !ENGINES.contains(&target.engine.as_str()) {
+            return Err(format!("不支持的引擎: {}", target.engine));
+        }
+        if target.model.trim().is_empty() {
+            return Err(format!("别名 {} 在 {} 下的模型不能为空", alias.name, target.engine));
+        }
+    }
+    Ok(())
+}
+
+fn validate_rule(rule: &RoutingRule) -> Result<(), String> {
+    if rule.use_model.trim().is_empty() {
+        return Err("规则的目标模型不能为空".to_string());
+    }
+    if let Some(engine) = rule.engine.as_deref() {
+        if !ENGINES.contains(&engine) {
+            return Err(format!("不支持的引擎: {}", engine));
+        }
+    }
+    if let (Some(min), Some(max)) = (rule.min_prompt_tokens, rule.max_prompt_tokens) {
+        if min > max {
+            return Err("minPromptTokens 不能大于 maxPromptTokens".to_string());
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 读取全部别名和路由规则
+#[tauri::command]
+pub async fn get_model_routing() -> Result<ModelRoutingConfig, String> {
+    Ok(load_config().await)
+}
+
+/// 新增或更新别名（按名称）
+#[tauri::command]
+pub async fn save_model_alias(app: AppHandle, alias: ModelAlias) -> Result<ModelRoutingConfig, String> {
+    validate_alias(&alias)?;
+    let mut config = load_config().await;
+    match config.aliases.iter_mut().find(|a| a.name == alias.name) {
+        Some(existing) => *existing = alias,
+        None => config.aliases.push(alias),
+    }
+    set_setting(&app, MODEL_ROUTING_KEY, &config).await?;
+    Ok(config)
+}
+
+/// 删除别名
+#[tauri::command]
+pub async fn delete_model_alias(app: AppHandle, name: String) -> Result<ModelRoutingConfig, String> {
+    let mut config = load_config().await;
+    config.aliases.retain(|a| a.name != name);
+    set_setting(&app, MODEL_ROUTING_KEY, &config).await?;
+    Ok(config)
+}
+
+/// 新增或更新路由规则（按 ID；ID 为空时新建并追加到末尾）
+#[tauri::command]
+pub async fn save_routing_rule(app: AppHandle, mut rule: RoutingRule) -> Result<ModelRoutingConfig, String> {
+    validate_rule(&rule)?;
+    let mut config = load_config().await;
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+    match config.rules.iter_mut().find(|r| r.id == rule.id) {
+        Some(existing) => *existing = rule,
+        None => config.rules.push(rule),
+    }
+    set_setting(&app, MODEL_ROUTING_KEY, &config).await?;
+    Ok(config)
+}
+
+/// 删除路由规则
+#[tauri::command]
+pub async fn delete_routing_rule(app: AppHandle, id: String) -> Result<ModelRoutingConfig, String> {
+    let mut config = load_config().await;
+    config.rules.retain(|r| r.id != id);
+    set_setting(&app, MODEL_ROUTING_KEY, &config).await?;
+    Ok(config)
+}
+
+/// 调整路由规则的顺序（未列出的规则保持原顺序排在后面）
+#[tauri::command]
+pub async fn reorder_routing_rules(app: AppHandle, ids: Vec<String>) -> Result<ModelRoutingConfig, String> {
+    let mut config = load_config().await;
+    config
+        .rules
+        .sort_by_key(|r| ids.iter().position(|id| *id == r.id).unwrap_or(usize::MAX));
+    set_setting(&app, MODEL_ROUTING_KEY, &config).await?;
+    Ok(config)
+}
+
+/// 预览某次执行会使用的模型
+#[tauri::command]
+pub async fn preview_model_resolution(
+    engine: String,
+    model: Option<String>,
+    prompt: String,
+    provider: Option<String>,
+) -> Result<ModelResolution, String> {
+    let config = load_config().await;
+    Ok(resolve(&config, &engine, model.as_deref(), provider.as_deref(), || {
+        count_text(model.as_deref().unwrap_or_default(), &prompt)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(engine: &str, provider: Option<&str>, model: &str) -> AliasTarget {
+        AliasTarget { engine: engine.into(), provider: provider.map(Into::into), model: model.into() }
+    }
+
+    #[test]
+    fn applies_rules_then_expands_aliases() {
+        let config = ModelRoutingConfig {
+            aliases: vec![
+                ModelAlias {
+                    name: "fast".into(),
+                    description: None,
+                    targets: vec![target("claude", None, "haiku"), target("codex", None, "gpt-5.1-codex-mini")],
+                },
+                ModelAlias {
+                    name: "smart".into(),
+                    description: None,
+                    targets: vec![
+                        target("claude", None, "opus"),
+                        target("claude", Some("kimi"), "kimi-k2"),
+                        target("codex", None, "gpt-5.2-codex"),
+                    ],
+                },
+            ],
+            rules: vec![
+                serde_json::from_str(r#"{"id":"off","enabled":false,"useModel":"fast"}"#).unwrap(),
+                serde_json::from_str(r#"{"id":"long","minPromptTokens":1000,"useModel":"smart"}"#).unwrap(),
+            ],
+        };
+        let resolve_with = |engine, model, provider, tokens| resolve(&config, engine, model, provider, || tokens);
+
+        let short = resolve_with("claude", Some("fast"), None, 10);
+        assert_eq!(short, ModelResolution { model: Some("haiku".into()), rule_id: None, alias: Some("fast".into()) });
+        let long = resolve_with("claude", Some("fast"), None, 5000);
+        assert_eq!((long.model.as_deref(), long.rule_id.as_deref()), (Some("opus"), Some("long")));
+        assert_eq!(resolve_with("claude", None, Some("kimi"), 5000).model.as_deref(), Some("kimi-k2"));
+        assert_eq!(resolve_with("codex", Some("gpt-5.2"), None, 10).model.as_deref(), Some("gpt-5.2"));
+        assert_eq!(resolve_with("codex", None, None, 10).model, None);
+        // 别名在该引擎下没有目标时使用 CLI 默认模型
+        assert_eq!(resolve_with("gemini", Some("fast"), None, 10).model, None);
+
+        // 没有长度条件的规则不需要计算 token
+        let config = ModelRoutingConfig {
+            aliases: Vec::new(),
+            rules: vec![serde_json::from_str(r#"{"engine":"gemini","whenModel":"auto","useModel":"gemini-2.5-pro"}"#).unwrap()],
+        };
+        let resolution = resolve(&config, "gemini", Some("auto"), None, || panic!("tokens not needed"));
+        assert_eq!(resolution.model.as_deref(), Some("gemini-2.5-pro"));
+
+        assert!(validate_alias(&ModelAlias { name: "my alias".into(), description: None, targets: Vec::new() }).is_err());
+        assert!(validate_rule(&serde_json::from_str(r#"{"minPromptTokens":5,"maxPromptTokens":1,"useModel":"x"}"#).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+//! 外发通知渠道
+//!
+//! 除桌面通知外，执行完成或失败时还可以：播放提示音、调用自定义 webhook、
+//! 发送到 Slack / Discord（incoming webhook）。渠道配置保存在 agents.db 的
+//! `notification_channels` 表，每个渠道可选择订阅的通知类型（默认完成和失败）。
+//!
+//! 提示音在免打扰时段内不播放；webhook 类渠道不受窗口焦点和免打扰影响。
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::claude::apply_no_window_async;
+use super::notifications::{NotificationKind, NotificationRecord};
+use super::storage::open_agent_db;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const SOUND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 渠道类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelType {
+    Sound,
+    Webhook,
+    Slack,
+    Discord,
+}
+
+impl ChannelType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ChannelType::Sound => "sound",
+            ChannelType::Webhook => "webhook",
+            ChannelType::Slack => "slack",
+            ChannelType::Discord => "discord",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sound" => Some(ChannelType::Sound),
+            "webhook" => Some(ChannelType::Webhook),
+            "slack" => Some(ChannelType::Slack),
+            "discord" => Some(ChannelType::Discord),
+            _ => None,
+        }
+    }
+}
+
+fn default_events() -> Vec<NotificationKind> {
+    vec![NotificationKind::Completed, NotificationKind::Failed]
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 已保存的通知渠道
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannel {
+    pub id: String,
+    pub name: String,
+    pub channel_type: ChannelType,
+    pub enabled: bool,
+    /// 订阅的通知类型
+    pub events: Vec<NotificationKind>,
+    /// webhook / Slack / Discord 的地址
+    pub url: Option<String>,
+    /// 提示音文件路径，为空时使用系统默认提示音
+    pub sound: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// 新建或更新渠道的参数（`id` 为空时新建）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannelInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub channel_type: ChannelType,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_events")]
+    pub events: Vec<NotificationKind>,
+    pub url: Option<String>,
+    pub sound: Option<String>,
+}
+
+async fn with_channel_db<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> Result<T, String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&*open_agent_db()?))
+        .await
+        .map_err(|e| format!("访问通知渠道失败: {}", e))?
+}
+
+fn row_to_channel(row: &rusqlite::Row) -> rusqlite::Result<NotificationChannel> {
+    let channel_type: String = row.get(2)?;
+    let events: String = row.get(4)?;
+    Ok(NotificationChannel {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        channel_type: ChannelType::parse(&channel_type).unwrap_or(ChannelType::Webhook),
+        enabled: row.get(3)?,
+        events: serde_json::from_str(&events).unwrap_or_else(|_| default_events()),
+        url: row.get(5)?,
+        sound: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+fn query_channels(conn: &Connection) -> Result<Vec<NotificationChannel>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, channel_type, enabled, events, url, sound, created_at, updated_at
+             FROM notification_channels ORDER BY created_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], row_to_channel).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn validate_input(input: &NotificationChannelInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("渠道名称不能为空".to_string());
+    }
+    if input.events.is_empty() {
+        return Err("至少需要订阅一种通知类型".to_string());
+    }
+    match input.channel_type {
+        ChannelType::Sound => {
+            if let Some(sound) = input.sound.as_deref().filter(|s| !s.trim().is_empty()) {
+                if !std::path::Path::new(sound).is_file() {
+                    return Err(format!("提示音文件不存在: {}", sound));
+                }
+            }
+        }
+        _ => {
+            let url = input.url.as_deref().map(str::trim).unwrap_or_default();
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                return Err(format!("{} 渠道需要 http(s) 地址", input.channel_type.as_str()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn upsert_channel(conn: &Connection, input: NotificationChannelInput) -> Result<NotificationChannel, String> {
+    let now = Utc::now().to_rfc3339();
+    let id = input.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let events = serde_json::to_string(&input.events).map_err(|e| e.to_string())?;
+    let url = input.url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    let sound = input.sound.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    conn.execute(
+        "INSERT INTO notification_channels
+            (id, name, channel_type, enabled, events, url, sound, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            channel_type = excluded.channel_type,
+            enabled = excluded.enabled,
+            events = excluded.events,
+            url = excluded.url,
+            sound = excluded.sound,
+            updated_at = excluded.updated_at",
+        params![id, input.name.trim(), input.channel_type.as_str(), input.enabled, events, url, sound, now],
+    )
+    .map_err(|e| format!("保存通知渠道失败: {}", e))?;
+    conn.query_row(
+        "SELECT id, name, channel_type, enabled, events, url, sound, created_at, updated_at
+         FROM notification_channels WHERE id = ?1",
+        params![id],
+        row_to_channel,
+    )
+    .map_err(|e| format!("读取通知渠道失败: {}", e))
+}
+
+// ============================================================================
+// 发送
+// ============================================================================
+
+/// 各渠道的请求体
+fn webhook_body(channel_type: ChannelType, record: &NotificationRecord) -> serde_json::Value {
+    match channel_type {
+        ChannelType::Slack => serde_json::json!({ "text": format!("*{}*\n{}", record.title, record.body) }),
+        ChannelType::Discord => serde_json::json!({ "content": format!("**{}**\n{}", record.title, record.body) }),
+        _ => serde_json::json!({
+            "event": record.kind,
+            "engine": record.engine,
+            "session_id": record.session_id,
+            "title": record.title,
+            "body": record.body,
+            "created_at": record.created_at,
+        }),
+    }
+}
+
+async fn post_webhook(channel_type: ChannelType, url: &str, record: &NotificationRecord) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .post(url)
+        .json(&webhook_body(channel_type, record))
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {}", e))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let text = response.text().await.unwrap_or_default();
+    Err(format!("HTTP {}: {}", status, text.chars().take(200).collect::<String>()))
+}
+
+/// 播放提示音的系统命令
+fn sound_command(sound: Option<&str>) -> Command {
+    #[cfg(target_os = "macos")]
+    {
+        let mut cmd = Command::new("afplay");
+        cmd.arg(sound.unwrap_or("/System/Library/Sounds/Glass.aiff"));
+        cmd
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let path = sound.unwrap_or(r"C:\Windows\Media\chimes.wav").replace('\'', "''");
+        let mut cmd = Command::new("powershell");
+        cmd.args([
+            "-NoProfile",
+            "-Command",
+            &format!("(New-Object Media.SoundPlayer '{}').PlaySync()", path),
+        ]);
+        cmd
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let player = if which::which("paplay").is_ok() { "paplay" } else { "aplay" };
+        let mut cmd = Command::new(player);
+        cmd.arg(sound.unwrap_or("/usr/share/sounds/freedesktop/stereo/complete.oga"));
+        cmd
+    }
+}
+
+async fn play_sound(sound: Option<&str>) -> Result<(), String> {
+    let mut cmd = sound_command(sound);
+    cmd.kill_on_drop(true);
+    apply_no_window_async(&mut cmd);
+    let output = tokio::time::timeout(SOUND_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| "播放提示音超时".to_string())?
+        .map_err(|e| format!("播放提示音失败: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("播放提示音失败: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+async fn send(channel: &NotificationChannel, record: &NotificationRecord) -> Result<(), String> {
+    match channel.channel_type {
+        ChannelType::Sound => play_sound(channel.sound.as_deref()).await,
+        channel_type => {
+            let url = channel.url.as_deref().ok_or("渠道未配置地址")?;
+            post_webhook(channel_type, url, record).await
+        }
+    }
+}
+
+/// 把通知分发给订阅了该类型的已启用渠道（后台执行，不阻塞调用方）
+///
+/// `quiet` 为 true 时处于免打扰时段，跳过提示音渠道。
+pub fn dispatch(record: &NotificationRecord, quiet: bool) {
+    let record = record.clone();
+    tauri::async_runtime::spawn(async move {
+        let channels = match with_channel_db(query_channels).await {
+            Ok(channels) => channels,
+            Err(e) => {
+                log::warn!("[NotificationChannels] {}", e);
+                return;
+            }
+        };
+        for channel in channels {
+            if !channel.enabled || !channel.events.contains(&record.kind) {
+                continue;
+            }
+            if quiet && channel.channel_type == ChannelType::Sound {
+                continue;
+            }
+            if let Err(e) = send(&channel, &record).await {
+                log::warn!("[NotificationChannels] {} ({}) failed: {}", channel.name, channel.channel_type.as_str(), e);
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List configured notification channels
+#[tauri::command]
+pub async fn list_notification_channels() -> Result<Vec<NotificationChannel>, String> {
+    with_channel_db(query_channels).await
+}
+
+/// Create a notification channel, or update it when `id` is set
+#[tauri::command]
+pub async fn save_notification_channel(channel: NotificationChannelInput) -> Result<NotificationChannel, String> {
+    validate_input(&channel)?;
+    let saved = with_channel_db(move |conn| upsert_channel(conn, channel)).await?;
+    log::info!("[NotificationChannels] Saved {} ({})", saved.name, saved.channel_type.as_str());
+    Ok(saved)
+}
+
+/// Delete a notification channel
+#[tauri::command]
+pub async fn delete_notification_channel(id: String) -> Result<(), String> {
+    with_channel_db(move |conn| {
+        conn.execute("DELETE FROM notification_channels WHERE id = ?1", params![id])
+            .map(|_| ())
+            .map_err(|e| format!("删除通知渠道失败: {}", e))
+    })
+    .await
+}
+
+/// Send a sample notification through a channel (saved or not) and report the result
+#[tauri::command]
+pub async fn test_notification_channel(channel: NotificationChannelInput) -> Result<String, String> {
+    validate_input(&channel)?;
+    let now = Utc::now().to_rfc3339();
+    let target = NotificationChannel {
+        id: channel.id.unwrap_or_default(),
+        name: channel.name,
+        channel_type: channel.channel_type,
+        enabled: true,
+        events: channel.events,
+        url: channel.url.map(|u| u.trim().to_string()),
+        sound: channel.sound.filter(|s| !s.trim().is_empty()),
+        created_at: now.clone(),
+        updated_at: now.clone(),
+    };
+    let record = NotificationRecord {
+        id: 0,
+        kind: NotificationKind::Completed,
+        engine: "anycode".to_string(),
+        session_id: "test".to_string(),
+        title: "AnyCode 测试通知".to_string(),
+        body: format!("渠道「{}」配置正确", target.name),
+        created_at: now,
+        delivered: true,
+        suppressed_reason: None,
+        read: false,
+    };
+    send(&target, &record).await?;
+    Ok(format!("已通过 {} 发送测试通知", target.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(channel_type: ChannelType, url: Option<&str>) -> NotificationChannelInput {
+        NotificationChannelInput {
+            id: None,
+            name: "team".to_string(),
+            channel_type,
+            enabled: true,
+            events: default_events(),
+            url: url.map(str::to_string),
+            sound: None,
+        }
+    }
+
+    #[test]
+    fn validates_stores_and_formats_channels() {
+        assert!(validate_input(&input(ChannelType::Sound, None)).is_ok());
+        assert!(validate_input(&input(ChannelType::Slack, None)).is_err());
+        assert!(validate_input(&input(ChannelType::Discord, Some("ftp://x"))).is_err());
+        let mut no_events = input(ChannelType::Webhook, Some("https://example.com/hook"));
+        no_events.events.clear();
+        assert!(validate_input(&no_events).is_err());
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::commands::db_migrations::run_migrations(&mut conn).unwrap();
+        let saved = upsert_channel(&conn, input(ChannelType::Slack, Some(" https://hooks.slack.com/x "))).unwrap();
+        assert_eq!(saved.url.as_deref(), Some("https://hooks.slack.com/x"));
+        assert_eq!(saved.events, default_events());
+
+        let mut update = input(ChannelType::Discord, Some("https://discord.com/api/webhooks/1"));
+        update.id = Some(saved.id.clone());
+        update.events = vec![NotificationKind::Failed];
+        let updated = upsert_channel(&conn, update).unwrap();
+        assert_eq!(updated.channel_type, ChannelType::Discord);
+        assert_eq!(updated.created_at, saved.created_at);
+        assert_eq!(query_channels(&conn).unwrap().len(), 1);
+
+        let record = NotificationRecord {
+            id: 1,
+            kind: NotificationKind::Failed,
+            engine: "codex".to_string(),
+            session_id: "s1".to_string(),
+            title: "Codex 任务执行失败".to_string(),
+            body: "会话 s1".to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            delivered: true,
+            suppressed_reason: None,
+            read: false,
+        };
+        assert_eq!(webhook_body(ChannelType::Slack, &record)["text"], "*Codex 任务执行失败*\n会话 s1");
+        assert_eq!(webhook_body(ChannelType::Discord, &record)["content"], "**Codex 任务执行失败**\n会话 s1");
+        let generic = webhook_body(ChannelType::Webhook, &record);
+        assert_eq!(generic["event"], "failed");
+        assert_eq!(generic["session_id"], "s1");
+    }
+}
@@ -0,0 +1,478 @@
+//! 桌面通知
+//!
+//! 执行完成、执行失败、触达预算上限（Claude `--max-budget-usd` / 最大轮数）或等待工具审批时，
+//! 若应用窗口都不在前台，发送系统原生通知。每类事件可单独开关，并支持免打扰时段
+//! （本地时间，可跨午夜）。设置保存在 ~/.anycode/notifications.json。
+//!
+//! 所有通知（包括因窗口在前台、免打扰等原因未弹出的）都记录在 agents.db 的
+//! `notification_history` 表中，并发送 `notification-recorded` 事件供前端刷新。
+//! 提示音、webhook 等外发渠道见 `notification_channels`。
+
+use chrono::{Local, NaiveTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use super::notification_channels;
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+use super::data_root::anycode_dir;
+
+/// 历史记录最多保留的条数
+const MAX_HISTORY: i64 = 500;
+
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+
+/// 通知类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Completed,
+    Failed,
+    Budget,
+    Approval,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Completed => "completed",
+            NotificationKind::Failed => "failed",
+            NotificationKind::Budget => "budget",
+            NotificationKind::Approval => "approval",
+        }
+    }
+}
+
+/// 通知设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// 仅在所有窗口都不在前台时弹出
+    pub only_when_unfocused: bool,
+    pub on_completed: bool,
+    pub on_failed: bool,
+    pub on_budget: bool,
+    pub on_approval: bool,
+    pub quiet_hours_enabled: bool,
+    /// "HH:MM"，本地时间
+    pub quiet_start: String,
+    pub quiet_end: String,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            only_when_unfocused: true,
+            on_completed: true,
+            on_failed: true,
+            on_budget: true,
+            on_approval: true,
+            quiet_hours_enabled: false,
+            quiet_start: "22:00".to_string(),
+            quiet_end: "08:00".to_string(),
+        }
+    }
+}
+
+impl NotificationSettings {
+    fn kind_enabled(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::Completed => self.on_completed,
+            NotificationKind::Failed => self.on_failed,
+            NotificationKind::Budget => self.on_budget,
+            NotificationKind::Approval => self.on_approval,
+        }
+    }
+
+    /// `time` 是否落在免打扰时段内（开始时间晚于结束时间表示跨午夜）
+    fn in_quiet_hours(&self, time: NaiveTime) -> bool {
+        if !self.quiet_hours_enabled {
+            return false;
+        }
+        let (Some(start), Some(end)) = (parse_hhmm(&self.quiet_start), parse_hhmm(&self.quiet_end)) else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// 一条通知记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    pub id: i64,
+    pub kind: NotificationKind,
+    pub engine: String,
+    pub session_id: String,
+    pub title: String,
+    pub body: String,
+    pub created_at: String,
+    /// 是否实际弹出了系统通知
+    pub delivered: bool,
+    /// 未弹出的原因：disabled / focused / quiet_hours / error
+    pub suppressed_reason: Option<String>,
+    pub read: bool,
+}
+
+fn parse_hhmm(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("notifications.json"))
+}
+
+pub fn load_notification_settings() -> NotificationSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 创建通知历史表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS notification_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            engine TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            delivered INTEGER NOT NULL DEFAULT 0,
+            suppressed_reason TEXT,
+            read INTEGER NOT NULL DEFAULT 0
+        );",
+    )
+    .map_err(|e| format!("创建通知历史表失败: {}", e))
+}
+
+fn open_notification_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+fn kind_from_str(value: &str) -> NotificationKind {
+    match value {
+        "failed" => NotificationKind::Failed,
+        "budget" => NotificationKind::Budget,
+        "approval" => NotificationKind::Approval,
+        _ => NotificationKind::Completed,
+    }
+}
+
+fn insert_record(conn: &Connection, record: &mut NotificationRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO notification_history
+            (kind, engine, session_id, title, body, created_at, delivered, suppressed_reason)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            record.kind.as_str(),
+            record.engine,
+            record.session_id,
+            record.title,
+            record.body,
+            record.created_at,
+            record.delivered,
+            record.suppressed_reason,
+        ],
+    )
+    .map_err(|e| format!("写入通知历史失败: {}", e))?;
+    record.id = conn.last_insert_rowid();
+    conn.execute(
+        "DELETE FROM notification_history WHERE id <= ?1",
+        params![record.id - MAX_HISTORY],
+    )
+    .map_err(|e| format!("清理通知历史失败: {}", e))?;
+    Ok(())
+}
+
+fn query_history(conn: &Connection, limit: u32, unread_only: bool) -> Result<Vec<NotificationRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, kind, engine, session_id, title, body, created_at, delivered, suppressed_reason, read
+             FROM notification_history
+             WHERE (?1 = 0 OR read = 0)
+             ORDER BY id DESC
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![unread_only, limit], |row| {
+            Ok(NotificationRecord {
+                id: row.get(0)?,
+                kind: kind_from_str(&row.get::<_, String>(1)?),
+                engine: row.get(2)?,
+                session_id: row.get(3)?,
+                title: row.get(4)?,
+                body: row.get(5)?,
+                created_at: row.get(6)?,
+                delivered: row.get(7)?,
+                suppressed_reason: row.get(8)?,
+                read: row.get(9)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn any_window_focused(app: &AppHandle) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false))
+}
+
+/// 按设置决定是否弹出通知，返回未弹出的原因
+fn suppression_reason(
+    settings: &NotificationSettings,
+    kind: NotificationKind,
+    focused: bool,
+    now: NaiveTime,
+) -> Option<&'static str> {
+    if !settings.enabled || !settings.kind_enabled(kind) {
+        Some("disabled")
+    } else if settings.only_when_unfocused && focused {
+        Some("focused")
+    } else if settings.in_quiet_hours(now) {
+        Some("quiet_hours")
+    } else {
+        None
+    }
+}
+
+fn engine_label(engine: &str) -> &str {
+    match engine {
+        "claude" => "Claude",
+        "codex" => "Codex",
+        "gemini" => "Gemini",
+        other => other,
+    }
+}
+
+/// 发送（或按设置抑制）一条通知并记录到历史
+pub fn notify(app: &AppHandle, kind: NotificationKind, engine: &str, session_id: &str, title: String, body: String) {
+    let settings = load_notification_settings();
+    let now = Local::now().time();
+    let mut reason = suppression_reason(&settings, kind, any_window_focused(app), now);
+
+    if reason.is_none() {
+        if let Err(e) = app.notification().builder().title(&title).body(&body).show() {
+            log::warn!("[Notifications] Failed to show notification: {}", e);
+            reason = Some("error");
+        }
+    }
+
+    let mut record = NotificationRecord {
+        id: 0,
+        kind,
+        engine: engine.to_string(),
+        session_id: session_id.to_string(),
+        title,
+        body,
+        created_at: Utc::now().to_rfc3339(),
+        delivered: reason.is_none(),
+        suppressed_reason: reason.map(str::to_string),
+        read: false,
+    };
+    log::info!(
+        "[Notifications] {} {} ({}): {}",
+        engine,
+        kind.as_str(),
+        reason.unwrap_or("delivered"),
+        record.title
+    );
+    match open_notification_db().and_then(|conn| insert_record(&conn, &mut record)) {
+        Ok(()) => {
+            let _ = app.emit("notification-recorded", &record);
+        }
+        Err(e) => log::warn!("[Notifications] {}", e),
+    }
+    notification_channels::dispatch(&record, settings.in_quiet_hours(now));
+}
+
+/// 执行结束（取消的执行不调用）
+pub fn notify_run_finished(app: &AppHandle, engine: &str, session_id: &str, success: bool) {
+    let label = engine_label(engine);
+    let (kind, title) = if success {
+        (NotificationKind::Completed, format!("{} 任务已完成", label))
+    } else {
+        (NotificationKind::Failed, format!("{} 任务执行失败", label))
+    };
+    notify(app, kind, engine, session_id, title, format!("会话 {}", session_id));
+}
+
+/// 执行因预算或轮数上限而停止
+pub fn notify_budget_reached(app: &AppHandle, engine: &str, session_id: &str, detail: &str) {
+    let title = format!("{} 已达到执行上限", engine_label(engine));
+    notify(app, NotificationKind::Budget, engine, session_id, title, detail.to_string());
+}
+
+/// 工具调用等待审批
+pub fn notify_approval_needed(app: &AppHandle, engine: &str, session_id: &str, tool_name: &str) {
+    let title = format!("{} 等待审批", engine_label(engine));
+    let body = format!("工具 {} 需要你的确认", tool_name);
+    notify(app, NotificationKind::Approval, engine, session_id, title, body);
+}
+
+/// Claude `result` 消息中的上限类结束原因（`error_max_budget_usd` / `error_max_turns`）
+pub fn claude_limit_reason(msg: &serde_json::Value) -> Option<&'static str> {
+    if msg["type"] != "result" {
+        return None;
+    }
+    match msg["subtype"].as_str()? {
+        "error_max_budget_usd" => Some("已达到本次执行的费用预算上限"),
+        "error_max_turns" => Some("已达到本次执行的最大轮数"),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get desktop notification settings
+#[tauri::command]
+pub async fn get_notification_settings() -> Result<NotificationSettings, String> {
+    Ok(load_notification_settings())
+}
+
+/// Save desktop notification settings
+#[tauri::command]
+pub async fn set_notification_settings(settings: NotificationSettings) -> Result<(), String> {
+    for value in [&settings.quiet_start, &settings.quiet_end] {
+        if parse_hhmm(value).is_none() {
+            return Err(format!("无效的时间格式（应为 HH:MM）: {}", value));
+        }
+    }
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize notification settings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write notifications.json: {}", e))?;
+    log::info!("[Notifications] Saved settings (enabled: {})", settings.enabled);
+    Ok(())
+}
+
+/// List recorded notifications, newest first
+#[tauri::command]
+pub async fn list_notification_history(
+    limit: Option<u32>,
+    unread_only: Option<bool>,
+) -> Result<Vec<NotificationRecord>, String> {
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    tokio::task::spawn_blocking(move || query_history(&*open_notification_db()?, limit, unread_only.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("查询通知历史失败: {}", e))?
+}
+
+/// Mark notifications as read (all of them when `ids` is empty)
+#[tauri::command]
+pub async fn mark_notifications_read(ids: Option<Vec<i64>>) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = open_notification_db()?;
+        let ids = ids.unwrap_or_default();
+        if ids.is_empty() {
+            return conn
+                .execute("UPDATE notification_history SET read = 1 WHERE read = 0", [])
+                .map_err(|e| format!("更新通知状态失败: {}", e));
+        }
+        let mut updated = 0;
+        for id in ids {
+            updated += conn
+                .execute("UPDATE notification_history SET read = 1 WHERE id = ?1", params![id])
+                .map_err(|e| format!("更新通知状态失败: {}", e))?;
+        }
+        Ok(updated)
+    })
+    .await
+    .map_err(|e| format!("更新通知状态失败: {}", e))?
+}
+
+/// Delete all recorded notifications
+#[tauri::command]
+pub async fn clear_notification_history() -> Result<usize, String> {
+    tokio::task::spawn_blocking(|| {
+        open_notification_db()?
+            .execute("DELETE FROM notification_history", [])
+            .map_err(|e| format!("清空通知历史失败: {}", e))
+    })
+    .await
+    .map_err(|e| format!("清空通知历史失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hhmm: &str) -> NaiveTime {
+        parse_hhmm(hhmm).unwrap()
+    }
+
+    #[test]
+    fn applies_toggles_focus_and_quiet_hours() {
+        let mut settings = NotificationSettings::default();
+        assert_eq!(suppression_reason(&settings, NotificationKind::Completed, false, at("12:00")), None);
+        assert_eq!(suppression_reason(&settings, NotificationKind::Completed, true, at("12:00")), Some("focused"));
+
+        settings.on_completed = false;
+        assert_eq!(suppression_reason(&settings, NotificationKind::Completed, false, at("12:00")), Some("disabled"));
+        assert_eq!(suppression_reason(&settings, NotificationKind::Failed, false, at("12:00")), None);
+
+        // 跨午夜的免打扰时段
+        settings.quiet_hours_enabled = true;
+        assert_eq!(suppression_reason(&settings, NotificationKind::Failed, false, at("23:30")), Some("quiet_hours"));
+        assert_eq!(suppression_reason(&settings, NotificationKind::Failed, false, at("07:59")), Some("quiet_hours"));
+        assert_eq!(suppression_reason(&settings, NotificationKind::Failed, false, at("08:00")), None);
+        settings.quiet_start = "13:00".to_string();
+        settings.quiet_end = "14:00".to_string();
+        assert!(settings.in_quiet_hours(at("13:30")));
+        assert!(!settings.in_quiet_hours(at("23:30")));
+
+        let result = serde_json::json!({ "type": "result", "subtype": "error_max_turns" });
+        assert!(claude_limit_reason(&result).is_some());
+        assert!(claude_limit_reason(&serde_json::json!({ "type": "result", "subtype": "success" })).is_none());
+    }
+
+    #[test]
+    fn records_and_trims_history() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        for i in 0..3 {
+            let mut record = NotificationRecord {
+                id: 0,
+                kind: if i == 0 { NotificationKind::Approval } else { NotificationKind::Completed },
+                engine: "claude".to_string(),
+                session_id: format!("s{}", i),
+                title: "title".to_string(),
+                body: "body".to_string(),
+                created_at: Utc::now().to_rfc3339(),
+                delivered: i != 1,
+                suppressed_reason: (i == 1).then(|| "focused".to_string()),
+                read: false,
+            };
+            insert_record(&conn, &mut record).unwrap();
+        }
+        let history = query_history(&conn, 10, false).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].session_id, "s2");
+        assert_eq!(history[2].kind, NotificationKind::Approval);
+        assert_eq!(history[1].suppressed_reason.as_deref(), Some("focused"));
+
+        conn.execute("UPDATE notification_history SET read = 1 WHERE session_id = 's0'", []).unwrap();
+        assert_eq!(query_history(&conn, 10, true).unwrap().len(), 2);
+    }
+}
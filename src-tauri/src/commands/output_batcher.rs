@@ -0,0 +1,316 @@
+//! 流式输出批量发送
+//!
+//! 引擎的 stdout 以前每行 JSONL 发送一个 Tauri 事件，输出非常密集时（逐 token 的增量消息）
+//! 前端会被大量事件卡住。这里把同一进程的输出先缓存起来，每 `flushIntervalMs` 毫秒或攒够
+//! `maxBatch` 行时合并为一个 `<engine>-output-batch[:session_id]` 事件（payload 为行数组），
+//! 同一条消息的连续增量（Claude `content_block_delta`、Codex `*_delta`、Gemini `delta: true`）合并为一行。
+//!
+//! 配置保存在 app_settings 的 `stream_batching` 键中；关闭后仍按行发送 `<engine>-output[:session_id]`。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::app_settings::{get_setting, set_setting};
+
+pub const STREAM_BATCHING_KEY: &str = "stream_batching";
+
+/// 批量发送配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StreamBatchingConfig {
+    pub enabled: bool,
+    /// 最长缓存时间（毫秒）
+    pub flush_interval_ms: u64,
+    /// 单批最多行数，达到后立即发送
+    pub max_batch: usize,
+    /// 合并同一条消息的连续增量
+    pub coalesce_deltas: bool,
+}
+
+impl Default for StreamBatchingConfig {
+    fn default() -> Self {
+        StreamBatchingConfig {
+            enabled: true,
+            flush_interval_ms: 50,
+            max_batch: 100,
+            coalesce_deltas: true,
+        }
+    }
+}
+
+/// 增量消息的文本字段，以及判断两条增量属于同一条消息的字段（JSON Pointer）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DeltaShape {
+    text: &'static str,
+    identity: &'static [&'static str],
+}
+
+const CLAUDE_DELTA_IDENTITY: &[&str] = &["/type", "/event/index", "/event/delta/type", "/parent_tool_use_id"];
+const CODEX_DELTA_IDENTITY: &[&str] = &["/id", "/msg/type"];
+const GEMINI_DELTA_IDENTITY: &[&str] = &["/type", "/message/role", "/geminiMetadata/delta"];
+
+fn delta_shape(value: &Value) -> Option<DeltaShape> {
+    if value["type"] == "stream_event" && value["event"]["type"] == "content_block_delta" {
+        let text = match value["event"]["delta"]["type"].as_str()? {
+            "text_delta" => "/event/delta/text",
+            "thinking_delta" => "/event/delta/thinking",
+            "input_json_delta" => "/event/delta/partial_json",
+            _ => return None,
+        };
+        return Some(DeltaShape { text, identity: CLAUDE_DELTA_IDENTITY });
+    }
+    if matches!(value["msg"]["type"].as_str(), Some("agent_message_delta" | "agent_reasoning_delta")) {
+        return Some(DeltaShape { text: "/msg/delta", identity: CODEX_DELTA_IDENTITY });
+    }
+    if value["geminiMetadata"]["delta"] == true {
+        return Some(DeltaShape { text: "/message/content/0/text", identity: GEMINI_DELTA_IDENTITY });
+    }
+    None
+}
+
+/// 解析增量消息（不是增量时返回 None，避免每行都做 JSON 解析）
+fn parse_delta(line: &str) -> Option<(Value, DeltaShape)> {
+    if !line.contains("delta") {
+        return None;
+    }
+    let value: Value = serde_json::from_str(line).ok()?;
+    let shape = delta_shape(&value)?;
+    value.pointer(shape.text)?.as_str()?;
+    Some((value, shape))
+}
+
+struct PendingLine {
+    session_id: Option<String>,
+    line: String,
+    delta: Option<(Value, DeltaShape)>,
+    merged: bool,
+}
+
+impl PendingLine {
+    /// 把同一条消息的下一段增量追加到本行
+    fn coalesce(&mut self, session_id: Option<&str>, next: &(Value, DeltaShape)) -> bool {
+        let Some((value, shape)) = self.delta.as_mut() else {
+            return false;
+        };
+        let (next_value, next_shape) = next;
+        if self.session_id.as_deref() != session_id
+            || shape != next_shape
+            || shape.identity.iter().any(|p| value.pointer(p) != next_value.pointer(p))
+        {
+            return false;
+        }
+        let Some(text) = next_value.pointer(shape.text).and_then(|v| v.as_str()) else {
+            return false;
+        };
+        match value.pointer_mut(shape.text) {
+            Some(Value::String(current)) => current.push_str(text),
+            _ => return false,
+        }
+        self.merged = true;
+        true
+    }
+
+    fn into_line(self) -> String {
+        match (self.merged, self.delta) {
+            (true, Some((value, _))) => value.to_string(),
+            _ => self.line,
+        }
+    }
+}
+
+/// 单个进程的输出批量发送器（stdout 读取任务持有，释放时发送剩余内容）
+pub struct OutputBatcher {
+    app: AppHandle,
+    /// 逐行事件名，如 "claude-output"
+    event: String,
+    config: StreamBatchingConfig,
+    buffer: Mutex<Vec<PendingLine>>,
+}
+
+impl OutputBatcher {
+    pub async fn new(app: &AppHandle, event: &str) -> Arc<Self> {
+        let config = load_config().await;
+        let batcher = Arc::new(OutputBatcher {
+            app: app.clone(),
+            event: event.to_string(),
+            config,
+            buffer: Mutex::new(Vec::new()),
+        });
+        if config.enabled {
+            let weak = Arc::downgrade(&batcher);
+            let interval = Duration::from_millis(config.flush_interval_ms.max(1));
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    match weak.upgrade() {
+                        Some(batcher) => batcher.flush(),
+                        None => break,
+                    }
+                }
+            });
+        }
+        batcher
+    }
+
+    /// 发送一行输出；`session_id` 为空时只发送到全局事件
+    pub fn push(&self, session_id: Option<&str>, line: String) {
+        if !self.config.enabled {
+            if let Some(session_id) = session_id {
+                let _ = self.app.emit(&format!("{}:{}", self.event, session_id), &line);
+            }
+            let _ = self.app.emit(&self.event, &line);
+            return;
+        }
+
+        let full = {
+            let mut buffer = self.buffer.lock().unwrap();
+            let delta = if self.config.coalesce_deltas { parse_delta(&line) } else { None };
+            let coalesced = match (&delta, buffer.last_mut()) {
+                (Some(delta), Some(last)) => last.coalesce(session_id, delta),
+                _ => false,
+            };
+            if !coalesced {
+                buffer.push(PendingLine {
+                    session_id: session_id.map(|s| s.to_string()),
+                    line,
+                    delta,
+                    merged: false,
+                });
+            }
+            buffer.len() >= self.config.max_batch
+        };
+        if full {
+            self.flush();
+        }
+    }
+
+    /// 立即发送缓存的输出
+    pub fn flush(&self) {
+        // 发送期间持有锁，保证多个 flush 之间的顺序
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.is_empty() {
+            return;
+        }
+        let batch_event = format!("{}-batch", self.event);
+        let mut all_lines = Vec::with_capacity(buffer.len());
+        let mut run: Option<(Option<String>, Vec<String>)> = None;
+        for pending in buffer.drain(..) {
+            let session_id = pending.session_id.clone();
+            let line = pending.into_line();
+            all_lines.push(line.clone());
+            match run.as_mut() {
+                Some((current, lines)) if *current == session_id => lines.push(line),
+                _ => {
+                    if let Some((current, lines)) = run.replace((session_id, vec![line])) {
+                        self.emit_session_batch(&batch_event, current, lines);
+                    }
+                }
+            }
+        }
+        if let Some((current, lines)) = run {
+            self.emit_session_batch(&batch_event, current, lines);
+        }
+        let _ = self.app.emit(&batch_event, &all_lines);
+    }
+
+    fn emit_session_batch(&self, batch_event: &str, session_id: Option<String>, lines: Vec<String>) {
+        if let Some(session_id) = session_id {
+            let _ = self.app.emit(&format!("{}:{}", batch_event, session_id), &lines);
+        }
+    }
+}
+
+impl Drop for OutputBatcher {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+async fn load_config() -> StreamBatchingConfig {
+    match get_setting(STREAM_BATCHING_KEY).await {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("[OutputBatcher] Failed to load stream batching config: {}", e);
+            StreamBatchingConfig::default()
+        }
+    }
+}
+
+/// 读取流式输出批量发送配置
+#[tauri::command]
+pub async fn get_stream_batching_config() -> Result<StreamBatchingConfig, String> {
+    Ok(load_config().await)
+}
+
+/// 保存流式输出批量发送配置（新启动的会话生效）
+#[tauri::command]
+pub async fn set_stream_batching_config(app: AppHandle, config: StreamBatchingConfig) -> Result<(), String> {
+    if config.max_batch == 0 {
+        return Err("maxBatch 必须大于 0".to_string());
+    }
+    if !(1..=5000).contains(&config.flush_interval_ms) {
+        return Err("flushIntervalMs 必须在 1-5000 之间".to_string());
+    }
+    set_setting(&app, STREAM_BATCHING_KEY, &config).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pending(session_id: Option<&str>, line: &str) -> PendingLine {
+        PendingLine {
+            session_id: session_id.map(|s| s.to_string()),
+            line: line.to_string(),
+            delta: parse_delta(line),
+            merged: false,
+        }
+    }
+
+    #[test]
+    fn coalesces_consecutive_deltas_of_the_same_message() {
+        let claude = |index: u32, text: &str| {
+            json!({
+                "type": "stream_event",
+                "event": { "type": "content_block_delta", "index": index, "delta": { "type": "text_delta", "text": text } }
+            })
+            .to_string()
+        };
+        let mut line = pending(Some("s1"), &claude(0, "Hel"));
+        assert!(line.coalesce(Some("s1"), &parse_delta(&claude(0, "lo")).unwrap()));
+        // 不同内容块、不同会话不合并
+        assert!(!line.coalesce(Some("s1"), &parse_delta(&claude(1, "x")).unwrap()));
+        assert!(!line.coalesce(Some("s2"), &parse_delta(&claude(0, "x")).unwrap()));
+        let merged: Value = serde_json::from_str(&line.into_line()).unwrap();
+        assert_eq!(merged["event"]["delta"]["text"], "Hello");
+
+        let gemini = |text: &str, ts: &str| {
+            json!({
+                "type": "assistant",
+                "message": { "content": [{ "type": "text", "text": text }], "role": "assistant" },
+                "timestamp": ts,
+                "geminiMetadata": { "provider": "gemini", "eventType": "message", "delta": true }
+            })
+            .to_string()
+        };
+        let mut line = pending(None, &gemini("a", "t1"));
+        assert!(line.coalesce(None, &parse_delta(&gemini("b", "t2")).unwrap()));
+        let merged: Value = serde_json::from_str(&line.into_line()).unwrap();
+        assert_eq!(merged["message"]["content"][0]["text"], "ab");
+
+        let codex = r#"{"id":"1","msg":{"type":"agent_message_delta","delta":"x"}}"#;
+        assert!(pending(None, codex).coalesce(None, &parse_delta(codex).unwrap()));
+
+        // 非增量消息原样保留
+        let plain = r#"{"type":"assistant","message":{"content":"delta"}}"#;
+        assert!(parse_delta(plain).is_none());
+        assert_eq!(pending(None, plain).into_line(), plain);
+
+        let config: StreamBatchingConfig = serde_json::from_str(r#"{"flushIntervalMs":20}"#).unwrap();
+        assert_eq!(config, StreamBatchingConfig { flush_interval_ms: 20, ..Default::default() });
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use super::data_root::anycode_dir;
 
 /// Claude权限管理配置结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +71,9 @@ pub struct ClaudeExecutionConfig {
     pub permissions: ClaudePermissionConfig,
     #[serde(default)]
     pub disable_rewind_git_operations: bool,
+    /// 工具权限请求交给界面审批（`--permission-prompt-tool stdio`）
+    #[serde(default)]
+    pub interactive_approvals: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +93,7 @@ impl Default for ClaudeExecutionConfig {
             verbose: true,
             permissions: ClaudePermissionConfig::default(),
             disable_rewind_git_operations: false,
+            interactive_approvals: false,
         }
     }
 }
@@ -135,10 +140,13 @@ pub fn build_permission_args(config: &ClaudePermissionConfig) -> Vec<String> {
 /// 执行参数构建函数
 /// 注意：prompt 不再通过命令行参数传递，而是通过 stdin 管道传递
 /// 这样可以避免操作系统命令行长度限制（Windows ~8KB, Linux/macOS ~128KB-2MB）
+///
+/// `permission_args` 为权限档案生成的参数，提供时替代 `config.permissions`
 pub fn build_execution_args(
     config: &ClaudeExecutionConfig,
     model: &str,
     project_path: &str,
+    permission_args: Option<Vec<String>>,
 ) -> Vec<String> {
     let mut args = Vec::new();
 
@@ -178,7 +186,7 @@ pub fn build_execution_args(
     }
 
     // 添加权限参数
-    args.extend(build_permission_args(&config.permissions));
+    args.extend(permission_args.unwrap_or_else(|| build_permission_args(&config.permissions)));
 
     // 🔥 新增：添加禁用的 MCP 服务器参数
     // 注意：--disable-mcp-server 参数需要 Claude CLI 支持
@@ -263,3 +271,278 @@ impl ClaudePermissionConfig {
         }
     }
 }
+
+// ============================================================================
+// 跨引擎权限档案
+// ============================================================================
+//
+// 档案只描述「能访问哪些文件」和「何时需要确认」，再由各引擎映射为自己的参数：
+// - Claude: --allowedTools / --disallowedTools / --permission-mode
+// - Codex:  --sandbox / approval_policy
+// - Gemini: --approval-mode / --yolo
+// 内置档案之外的自定义档案保存在 `~/.anycode/permission_profiles.json`。
+
+/// 文件访问范围
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileAccess {
+    ReadOnly,
+    WorkspaceWrite,
+    FullAccess,
+}
+
+/// 确认策略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApprovalPolicy {
+    /// 修改文件和执行命令前都需要确认
+    Ask,
+    /// 自动批准文件修改，命令仍需确认
+    AutoEdit,
+    /// 从不确认
+    Never,
+}
+
+/// 引擎无关的权限档案
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub file_access: FileAccess,
+    pub approval: ApprovalPolicy,
+    /// 额外允许的工具（仅 Claude 支持按工具授权）
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// 禁止的工具（仅 Claude）
+    #[serde(default)]
+    pub disallowed_tools: Vec<String>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+fn tools(names: &[&str]) -> Vec<String> {
+    names.iter().map(|s| s.to_string()).collect()
+}
+
+/// 内置档案
+pub fn builtin_permission_profiles() -> Vec<PermissionProfile> {
+    let profile = |id: &str, name: &str, description: &str, file_access, approval, allowed: &[&str]| {
+        PermissionProfile {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            file_access,
+            approval,
+            allowed_tools: tools(allowed),
+            disallowed_tools: vec![],
+            builtin: true,
+        }
+    };
+    vec![
+        profile("read-only", "只读", "只允许分析代码，不修改文件、不执行命令",
+            FileAccess::ReadOnly, ApprovalPolicy::Ask, &[]),
+        profile("ask", "逐项确认", "可修改工作区，所有修改和命令都需要确认",
+            FileAccess::WorkspaceWrite, ApprovalPolicy::Ask, &["Read", "Write", "Edit"]),
+        profile("auto-edit", "自动编辑", "自动批准工作区内的文件修改，命令仍需确认",
+            FileAccess::WorkspaceWrite, ApprovalPolicy::AutoEdit, DEVELOPMENT_TOOLS),
+        profile("full-access", "完全访问", "不限制文件访问，不做任何确认",
+            FileAccess::FullAccess, ApprovalPolicy::Never, &[]),
+    ]
+}
+
+impl PermissionProfile {
+    /// 映射为 Claude 权限配置
+    pub fn to_claude_permissions(&self) -> ClaudePermissionConfig {
+        let permission_mode = match (self.file_access, self.approval) {
+            (FileAccess::ReadOnly, _) => PermissionMode::Plan,
+            (_, ApprovalPolicy::Ask) => PermissionMode::Interactive,
+            _ => PermissionMode::AcceptEdits,
+        };
+        ClaudePermissionConfig {
+            allowed_tools: self.allowed_tools.clone(),
+            disallowed_tools: self.disallowed_tools.clone(),
+            auto_approve_edits: permission_mode == PermissionMode::AcceptEdits,
+            permission_mode,
+            enable_dangerous_skip: self.file_access == FileAccess::FullAccess
+                && self.approval == ApprovalPolicy::Never,
+        }
+    }
+
+    fn codex_args(&self) -> Vec<String> {
+        let sandbox = match self.file_access {
+            FileAccess::ReadOnly => "read-only",
+            FileAccess::WorkspaceWrite => "workspace-write",
+            FileAccess::FullAccess => "danger-full-access",
+        };
+        let approval = match self.approval {
+            ApprovalPolicy::Ask => "untrusted",
+            ApprovalPolicy::AutoEdit => "on-request",
+            ApprovalPolicy::Never => "never",
+        };
+        vec![
+            "--sandbox".to_string(),
+            sandbox.to_string(),
+            "-c".to_string(),
+            format!("approval_policy=\"{}\"", approval),
+        ]
+    }
+
+    fn gemini_args(&self) -> Vec<String> {
+        // Gemini 没有文件范围控制，只读档案退回到默认确认模式
+        match (self.file_access, self.approval) {
+            (FileAccess::ReadOnly, _) | (_, ApprovalPolicy::Ask) => vec![],
+            (_, ApprovalPolicy::AutoEdit) => {
+                vec!["--approval-mode".to_string(), "auto_edit".to_string()]
+            }
+            (_, ApprovalPolicy::Never) => vec!["--yolo".to_string()],
+        }
+    }
+
+    /// 该档案在指定引擎上对应的 CLI 参数
+    pub fn args_for_engine(&self, engine: &str) -> Result<Vec<String>, String> {
+        match engine {
+            "claude" => Ok(build_permission_args(&self.to_claude_permissions())),
+            "codex" => Ok(self.codex_args()),
+            "gemini" => Ok(self.gemini_args()),
+            other => Err(format!("未知的引擎: {}", other)),
+        }
+    }
+}
+
+fn get_custom_profiles_path() -> Result<std::path::PathBuf, String> {
+    Ok(anycode_dir()?.join("permission_profiles.json"))
+}
+
+fn load_custom_profiles() -> Vec<PermissionProfile> {
+    let Ok(path) = get_custom_profiles_path() else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!("[PermissionProfile] Failed to parse {:?}: {}", path, e);
+        Vec::new()
+    })
+}
+
+fn save_custom_profiles(profiles: &[PermissionProfile]) -> Result<(), String> {
+    let path = get_custom_profiles_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(profiles)
+        .map_err(|e| format!("Failed to serialize permission profiles: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write permission profiles: {}", e))
+}
+
+/// 内置档案 + 自定义档案
+pub fn list_all_permission_profiles() -> Vec<PermissionProfile> {
+    let mut profiles = builtin_permission_profiles();
+    profiles.extend(load_custom_profiles());
+    profiles
+}
+
+/// 按 ID 查找档案
+pub fn find_permission_profile(profile_id: &str) -> Result<PermissionProfile, String> {
+    list_all_permission_profiles()
+        .into_iter()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| format!("权限档案不存在: {}", profile_id))
+}
+
+/// 把权限档案应用到指定引擎，返回需要追加的 CLI 参数
+///
+/// 所有引擎的执行命令都通过此函数解析 `permission_profile`。
+pub fn apply_permission_profile(engine: &str, profile_id: &str) -> Result<Vec<String>, String> {
+    let args = find_permission_profile(profile_id)?.args_for_engine(engine)?;
+    log::info!("[PermissionProfile] Applying '{}' to {}: {:?}", profile_id, engine, args);
+    Ok(args)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出所有权限档案
+#[tauri::command]
+pub async fn list_permission_profiles() -> Result<Vec<PermissionProfile>, String> {
+    Ok(list_all_permission_profiles())
+}
+
+/// 新建或更新自定义权限档案
+#[tauri::command]
+pub async fn save_permission_profile(profile: PermissionProfile) -> Result<(), String> {
+    if profile.id.trim().is_empty() {
+        return Err("权限档案 ID 不能为空".to_string());
+    }
+    if builtin_permission_profiles().iter().any(|p| p.id == profile.id) {
+        return Err(format!("不能修改内置权限档案: {}", profile.id));
+    }
+
+    let mut profiles = load_custom_profiles();
+    let profile = PermissionProfile { builtin: false, ..profile };
+    match profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    save_custom_profiles(&profiles)
+}
+
+/// 删除自定义权限档案
+#[tauri::command]
+pub async fn delete_permission_profile(profile_id: String) -> Result<(), String> {
+    let mut profiles = load_custom_profiles();
+    let before = profiles.len();
+    profiles.retain(|p| p.id != profile_id);
+    if profiles.len() == before {
+        return Err(format!("权限档案不存在: {}", profile_id));
+    }
+    save_custom_profiles(&profiles)
+}
+
+/// 预览档案在指定引擎上生成的 CLI 参数
+#[tauri::command]
+pub async fn preview_permission_profile(
+    engine: String,
+    profile_id: String,
+) -> Result<Vec<String>, String> {
+    apply_permission_profile(&engine, &profile_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin(id: &str) -> PermissionProfile {
+        builtin_permission_profiles().into_iter().find(|p| p.id == id).unwrap()
+    }
+
+    #[test]
+    fn profiles_map_to_each_engine() {
+        let read_only = builtin("read-only");
+        assert_eq!(
+            read_only.args_for_engine("claude").unwrap(),
+            vec!["--permission-mode", "plan"]
+        );
+        assert_eq!(
+            read_only.args_for_engine("codex").unwrap(),
+            vec!["--sandbox", "read-only", "-c", "approval_policy=\"untrusted\""]
+        );
+        assert!(read_only.args_for_engine("gemini").unwrap().is_empty());
+
+        let full = builtin("full-access");
+        assert_eq!(full.args_for_engine("claude").unwrap(), vec!["--dangerously-skip-permissions"]);
+        assert_eq!(full.args_for_engine("gemini").unwrap(), vec!["--yolo"]);
+
+        assert_eq!(
+            builtin("auto-edit").args_for_engine("gemini").unwrap(),
+            vec!["--approval-mode", "auto_edit"]
+        );
+        assert!(full.args_for_engine("unknown").is_err());
+    }
+}
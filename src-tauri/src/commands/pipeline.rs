@@ -0,0 +1,438 @@
+//! 多引擎流水线
+//!
+//! 把多个引擎串联执行，例如 Gemini 起草方案 → Codex 实现 → Claude 审查。
+//! 流水线定义保存在 `~/.anycode/pipelines.json`，由后端逐步执行：
+//! - 每一步以一次性会话运行对应引擎 CLI（见 `headless`），取其最终回复作为该步输出
+//! - 步骤提示词是模板：`{{input}}` 为该步输入（见 [`StepInput`]），
+//!   `{{task}}` 为流水线初始输入，`{{steps.<name>}}` 为之前某一步的输出
+//! - 每步状态通过 `pipeline-step(:run_id)` 事件通知前端，结束时发送 `pipeline-complete(:run_id)`
+//! - 步骤失败按 `max_retries` 重试；`abort_pipeline` 可随时终止当前步骤并停止后续步骤
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use super::claude::kill_process_tree;
+use super::headless::{run_headless, HeadlessRequest};
+use super::prompt_library::render_with_input;
+use super::data_root::anycode_dir;
+
+/// 步骤输入来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StepInput {
+    /// 上一步的输出（第一步为初始输入）
+    #[default]
+    Previous,
+    /// 流水线的初始输入
+    Initial,
+    /// 不传入输入，只使用步骤提示词
+    None,
+}
+
+/// 流水线中的一步
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStep {
+    /// 步骤名（在同一流水线中唯一，用于 `{{steps.<name>}}`）
+    pub name: String,
+    /// "claude" | "codex" | "gemini"
+    pub engine: String,
+    /// 模型，为空时使用项目默认值
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 权限档案，为空时使用项目默认值（实现类步骤需要可写权限）
+    #[serde(default)]
+    pub permission_profile: Option<String>,
+    /// 步骤提示词模板
+    pub prompt: String,
+    #[serde(default)]
+    pub input: StepInput,
+    /// 失败后的重试次数
+    #[serde(default)]
+    pub max_retries: u32,
+}
+
+/// 流水线定义
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pipeline {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<PipelineStep>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Running,
+    Retrying,
+    Completed,
+    Failed,
+    Aborted,
+}
+
+/// 步骤状态事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStepEvent {
+    pub run_id: String,
+    pub step_index: usize,
+    pub step_name: String,
+    pub engine: String,
+    pub status: StepStatus,
+    /// 1-based 尝试次数
+    pub attempt: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 运行中流水线的控制句柄
+#[derive(Default)]
+struct PipelineRunControl {
+    aborted: AtomicBool,
+    /// 当前步骤的进程 PID
+    current_pid: Mutex<Option<u32>>,
+}
+
+impl PipelineRunControl {
+    fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+}
+
+static PIPELINE_RUNS: Lazy<Mutex<HashMap<String, Arc<PipelineRunControl>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// ============================================================================
+// 存储
+// ============================================================================
+
+fn get_pipelines_path() -> Result<std::path::PathBuf, String> {
+    Ok(anycode_dir()?.join("pipelines.json"))
+}
+
+fn load_pipelines() -> Vec<Pipeline> {
+    let Ok(path) = get_pipelines_path() else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!("[Pipeline] Failed to parse {:?}: {}", path, e);
+        Vec::new()
+    })
+}
+
+fn save_pipelines(pipelines: &[Pipeline]) -> Result<(), String> {
+    let path = get_pipelines_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(pipelines)
+        .map_err(|e| format!("Failed to serialize pipelines: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write pipelines: {}", e))
+}
+
+fn validate_pipeline(pipeline: &Pipeline) -> Result<(), String> {
+    if pipeline.id.trim().is_empty() {
+        return Err("流水线 ID 不能为空".to_string());
+    }
+    if pipeline.steps.is_empty() {
+        return Err("流水线至少需要一个步骤".to_string());
+    }
+    let mut names: Vec<&str> = Vec::new();
+    for step in &pipeline.steps {
+        if !["claude", "codex", "gemini"].contains(&step.engine.as_str()) {
+            return Err(format!("步骤 '{}' 使用了不支持的引擎: {}", step.name, step.engine));
+        }
+        if step.name.trim().is_empty() || names.contains(&step.name.as_str()) {
+            return Err(format!("步骤名不能为空且不能重复: '{}'", step.name));
+        }
+        names.push(&step.name);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// 提示词与输出
+// ============================================================================
+
+/// 渲染步骤提示词
+///
+/// `outputs` 为之前各步骤的 (步骤名, 输出)，按执行顺序排列。
+fn render_step_prompt(step: &PipelineStep, task: &str, outputs: &[(String, String)]) -> Result<String, String> {
+    let mut variables = HashMap::new();
+    variables.insert("task".to_string(), task.to_string());
+    for (name, output) in outputs {
+        variables.insert(format!("steps.{}", name), output.clone());
+    }
+
+    let input = match step.input {
+        StepInput::Previous => outputs.last().map(|(_, o)| o.as_str()).unwrap_or(task),
+        StepInput::Initial => task,
+        StepInput::None => "",
+    };
+    render_with_input(&step.prompt, &variables, input)
+}
+
+// ============================================================================
+// 执行
+// ============================================================================
+
+/// 运行一次步骤，返回引擎的最终回复
+async fn run_step_once(
+    app_handle: &AppHandle,
+    control: &PipelineRunControl,
+    step: &PipelineStep,
+    project_path: &str,
+    prompt: String,
+) -> Result<String, String> {
+    let request = HeadlessRequest {
+        engine: step.engine.clone(),
+        project_path: project_path.to_string(),
+        prompt,
+        model: step.model.clone(),
+        permission_profile: step.permission_profile.clone(),
+        internal: false,
+    };
+    let result = run_headless(app_handle, &request, &control.current_pid).await;
+    if control.is_aborted() {
+        return Err("流水线已中止".to_string());
+    }
+    result?
+        .text
+        .ok_or_else(|| format!("{} 没有返回任何输出", step.engine))
+}
+
+fn emit_pipeline_event<S: Serialize + Clone>(app_handle: &AppHandle, name: &str, run_id: &str, payload: &S) {
+    if let Err(e) = app_handle.emit(&format!("{}:{}", name, run_id), payload) {
+        log::warn!("[Pipeline] Failed to emit {} (run-specific): {}", name, e);
+    }
+    if let Err(e) = app_handle.emit(name, payload) {
+        log::warn!("[Pipeline] Failed to emit {} (global): {}", name, e);
+    }
+}
+
+/// 逐步执行流水线，返回各步骤输出
+async fn execute_pipeline(
+    app_handle: &AppHandle,
+    control: &PipelineRunControl,
+    run_id: &str,
+    pipeline: &Pipeline,
+    project_path: &str,
+    task: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let mut outputs: Vec<(String, String)> = Vec::new();
+
+    for (index, step) in pipeline.steps.iter().enumerate() {
+        let prompt = render_step_prompt(step, task, &outputs)?;
+        let event = |status: StepStatus, attempt: u32, output: Option<String>, error: Option<String>| {
+            PipelineStepEvent {
+                run_id: run_id.to_string(),
+                step_index: index,
+                step_name: step.name.clone(),
+                engine: step.engine.clone(),
+                status,
+                attempt,
+                output,
+                error,
+            }
+        };
+
+        let mut attempt = 1;
+        let output = loop {
+            if control.is_aborted() {
+                emit_pipeline_event(app_handle, "pipeline-step", run_id, &event(StepStatus::Aborted, attempt, None, None));
+                return Err("流水线已中止".to_string());
+            }
+
+            log::info!("[Pipeline] Run {} step {} ({}) attempt {}", run_id, step.name, step.engine, attempt);
+            emit_pipeline_event(app_handle, "pipeline-step", run_id, &event(StepStatus::Running, attempt, None, None));
+
+            match run_step_once(app_handle, control, step, project_path, prompt.clone()).await {
+                Ok(output) => break output,
+                Err(e) if control.is_aborted() => {
+                    emit_pipeline_event(app_handle, "pipeline-step", run_id, &event(StepStatus::Aborted, attempt, None, Some(e.clone())));
+                    return Err(e);
+                }
+                Err(e) if attempt <= step.max_retries => {
+                    log::warn!("[Pipeline] Step {} failed, retrying: {}", step.name, e);
+                    emit_pipeline_event(app_handle, "pipeline-step", run_id, &event(StepStatus::Retrying, attempt, None, Some(e)));
+                    attempt += 1;
+                }
+                Err(e) => {
+                    emit_pipeline_event(app_handle, "pipeline-step", run_id, &event(StepStatus::Failed, attempt, None, Some(e.clone())));
+                    return Err(format!("步骤 '{}' 失败: {}", step.name, e));
+                }
+            }
+        };
+
+        emit_pipeline_event(
+            app_handle,
+            "pipeline-step",
+            run_id,
+            &event(StepStatus::Completed, attempt, Some(output.clone()), None),
+        );
+        outputs.push((step.name.clone(), output));
+    }
+    Ok(outputs)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出所有流水线
+#[tauri::command]
+pub async fn list_pipelines() -> Result<Vec<Pipeline>, String> {
+    Ok(load_pipelines())
+}
+
+/// 新建或更新流水线
+#[tauri::command]
+pub async fn save_pipeline(pipeline: Pipeline) -> Result<(), String> {
+    validate_pipeline(&pipeline)?;
+    let mut pipelines = load_pipelines();
+    match pipelines.iter_mut().find(|p| p.id == pipeline.id) {
+        Some(existing) => *existing = pipeline,
+        None => pipelines.push(pipeline),
+    }
+    save_pipelines(&pipelines)
+}
+
+/// 删除流水线
+#[tauri::command]
+pub async fn delete_pipeline(pipeline_id: String) -> Result<(), String> {
+    let mut pipelines = load_pipelines();
+    let before = pipelines.len();
+    pipelines.retain(|p| p.id != pipeline_id);
+    if pipelines.len() == before {
+        return Err(format!("流水线不存在: {}", pipeline_id));
+    }
+    save_pipelines(&pipelines)
+}
+
+/// 在项目中后台运行流水线，返回运行 ID（用于订阅事件和中止）
+#[tauri::command]
+pub async fn run_pipeline(
+    pipeline_id: String,
+    project_path: String,
+    input: String,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let pipeline = load_pipelines()
+        .into_iter()
+        .find(|p| p.id == pipeline_id)
+        .ok_or_else(|| format!("流水线不存在: {}", pipeline_id))?;
+    validate_pipeline(&pipeline)?;
+
+    let run_id = format!("pipeline-{}", uuid::Uuid::new_v4());
+    let control = Arc::new(PipelineRunControl::default());
+    PIPELINE_RUNS.lock().unwrap().insert(run_id.clone(), control.clone());
+    log::info!("[Pipeline] Starting '{}' ({} steps) as {}", pipeline.name, pipeline.steps.len(), run_id);
+
+    let run_id_task = run_id.clone();
+    tokio::spawn(async move {
+        let result = execute_pipeline(&app_handle, &control, &run_id_task, &pipeline, &project_path, &input).await;
+        PIPELINE_RUNS.lock().unwrap().remove(&run_id_task);
+
+        let status = match &result {
+            Ok(_) => "completed",
+            Err(_) if control.is_aborted() => "aborted",
+            Err(_) => "failed",
+        };
+        log::info!("[Pipeline] Run {} {}", run_id_task, status);
+        let (outputs, error) = match result {
+            Ok(outputs) => (outputs.into_iter().collect::<HashMap<_, _>>(), None),
+            Err(e) => (HashMap::new(), Some(e)),
+        };
+        let payload = serde_json::json!({
+            "run_id": run_id_task,
+            "pipeline_id": pipeline.id,
+            "status": status,
+            "outputs": outputs,
+            "error": error,
+        });
+        emit_pipeline_event(&app_handle, "pipeline-complete", &run_id_task, &payload);
+    });
+
+    Ok(run_id)
+}
+
+/// 中止运行中的流水线（终止当前步骤的进程，不再执行后续步骤）
+#[tauri::command]
+pub async fn abort_pipeline(run_id: String) -> Result<(), String> {
+    let control = PIPELINE_RUNS
+        .lock()
+        .unwrap()
+        .get(&run_id)
+        .cloned()
+        .ok_or_else(|| format!("流水线未在运行: {}", run_id))?;
+    control.aborted.store(true, Ordering::SeqCst);
+
+    let pid = *control.current_pid.lock().unwrap();
+    if let Some(pid) = pid {
+        log::info!("[Pipeline] Aborting {} (killing process tree {})", run_id, pid);
+        kill_process_tree(pid)?;
+    }
+    Ok(())
+}
+
+/// 正在运行的流水线 ID
+#[tauri::command]
+pub async fn list_running_pipelines() -> Result<Vec<String>, String> {
+    Ok(PIPELINE_RUNS.lock().unwrap().keys().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(name: &str, prompt: &str, input: StepInput) -> PipelineStep {
+        PipelineStep {
+            name: name.into(),
+            engine: "claude".into(),
+            model: None,
+            permission_profile: None,
+            prompt: prompt.into(),
+            input,
+            max_retries: 0,
+        }
+    }
+
+    #[test]
+    fn step_prompts_receive_previous_outputs() {
+        let outputs = vec![("plan".to_string(), "1. add cache".to_string())];
+
+        let implement = step("implement", "Implement this plan:\n{{input}}", StepInput::Previous);
+        assert_eq!(
+            render_step_prompt(&implement, "speed up builds", &outputs).unwrap(),
+            "Implement this plan:\n1. add cache"
+        );
+
+        let review = step("review", "Review the change for: {{task}}", StepInput::None);
+        assert_eq!(
+            render_step_prompt(&review, "speed up builds", &outputs).unwrap(),
+            "Review the change for: speed up builds"
+        );
+
+        let draft = step("draft", "Draft a plan.", StepInput::Previous);
+        assert_eq!(
+            render_step_prompt(&draft, "speed up builds", &[]).unwrap(),
+            "Draft a plan.\n\nspeed up builds"
+        );
+
+        let bad = step("x", "{{steps.later}}", StepInput::None);
+        assert!(render_step_prompt(&bad, "t", &outputs).is_err());
+    }
+}
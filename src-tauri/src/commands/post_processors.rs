@@ -0,0 +1,422 @@
+//! 提示词完成后的后处理（格式化 / Lint / 测试）
+//!
+//! 按项目配置一组命令（如 `cargo fmt`、`cargo clippy`、`npm test`），每个提示词完成后由前端
+//! 调用 `run_post_processors`，在项目目录中按顺序执行。各命令的结果合成一条「验证记录」，
+//! 写入 agents.db 的 `verification_records` 表，并通过 `verification-recorded:<session_id>`
+//! 事件通知前端。开启 `autoFollowUp` 时，失败的结果会生成一条跟进提示词，由前端作为下一条
+//! 提示词发送给引擎。
+//!
+//! 配置保存在项目设置（`project_settings.json`）的 `postProcessors` 中。命令在本机的项目目录中
+//! 执行，不经过 WSL / SSH / 容器。
+//!
+//! 后处理命令、持续测试（`test_watch.rs`）和自动修复的验证（`auto_fix.rs`）在同一项目中
+//! 通过 [`lock_project`] 串行执行，避免格式化和测试等命令同时改动或读取同一个工作区。
+
+use chrono::{Duration as ChronoDuration, Utc};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::process::Command;
+use tokio::sync::OwnedMutexGuard;
+
+use super::claude::{apply_no_window_async, kill_process_tree};
+use super::project_settings::{load_project_settings, normalize_project_key};
+use super::storage::open_agent_db;
+
+/// 未配置超时时间时的默认值
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+/// 每个命令保留的输出（取末尾，失败信息通常在最后）
+const MAX_OUTPUT_BYTES: usize = 16 * 1024;
+/// 跟进提示词中每个失败命令附带的输出
+const MAX_FOLLOW_UP_OUTPUT_BYTES: usize = 4 * 1024;
+/// 验证记录保留天数
+const RETENTION_DAYS: i64 = 90;
+
+/// 项目的运行锁（归一化的项目路径 -> 锁）
+static PROJECT_RUN_LOCKS: Lazy<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 后处理命令的类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessorKind {
+    Format,
+    Lint,
+    #[default]
+    Test,
+}
+
+impl ProcessorKind {
+    fn label(&self) -> &'static str {
+        match self {
+            ProcessorKind::Format => "format",
+            ProcessorKind::Lint => "lint",
+            ProcessorKind::Test => "test",
+        }
+    }
+}
+
+/// 一个后处理命令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostProcessor {
+    pub name: String,
+    #[serde(default)]
+    pub kind: ProcessorKind,
+    /// 在项目目录中通过 shell 执行的命令
+    pub command: String,
+    /// 超时时间（秒），留空使用 300 秒
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// 项目的后处理设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostProcessorSettings {
+    /// 按顺序执行的命令
+    #[serde(default)]
+    pub processors: Vec<PostProcessor>,
+    /// 有命令失败时生成跟进提示词
+    #[serde(default)]
+    pub auto_follow_up: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessorStatus {
+    Passed,
+    Failed,
+    TimedOut,
+    /// 命令无法启动
+    Error,
+}
+
+/// 单个命令的执行结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessorResult {
+    pub name: String,
+    pub kind: ProcessorKind,
+    pub command: String,
+    pub status: ProcessorStatus,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// stdout + stderr 的末尾部分
+    pub output: String,
+}
+
+/// 一次提示词完成后的验证记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationRecord {
+    pub id: i64,
+    pub session_id: String,
+    pub engine: String,
+    pub project_path: String,
+    pub prompt_index: usize,
+    /// 所有命令都通过
+    pub passed: bool,
+    pub results: Vec<ProcessorResult>,
+    pub created_at: String,
+}
+
+/// `run_post_processors` 的返回值
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationOutcome {
+    pub record: VerificationRecord,
+    /// 开启 `autoFollowUp` 且有失败时，需要发送给引擎的跟进提示词
+    pub follow_up_prompt: Option<String>,
+}
+
+fn insert_record(conn: &Connection, record: &mut VerificationRecord) -> Result<(), String> {
+    let results = serde_json::to_string(&record.results).map_err(|e| format!("序列化验证结果失败: {}", e))?;
+    conn.execute(
+        "INSERT INTO verification_records
+            (session_id, engine, project_path, prompt_index, passed, results, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            record.session_id,
+            record.engine,
+            record.project_path,
+            record.prompt_index as i64,
+            record.passed,
+            results,
+            record.created_at,
+        ],
+    )
+    .map_err(|e| format!("写入验证记录失败: {}", e))?;
+    record.id = conn.last_insert_rowid();
+
+    let cutoff = (Utc::now() - ChronoDuration::days(RETENTION_DAYS)).to_rfc3339();
+    conn.execute("DELETE FROM verification_records WHERE created_at < ?1", params![cutoff])
+        .map_err(|e| format!("清理验证记录失败: {}", e))?;
+    Ok(())
+}
+
+fn load_records(conn: &Connection, session_id: &str) -> Result<Vec<VerificationRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, session_id, engine, project_path, prompt_index, passed, results, created_at
+             FROM verification_records WHERE session_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| format!("查询验证记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![session_id], |row| {
+            let results: String = row.get(6)?;
+            Ok(VerificationRecord {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                engine: row.get(2)?,
+                project_path: row.get(3)?,
+                prompt_index: row.get::<_, i64>(4)? as usize,
+                passed: row.get(5)?,
+                results: serde_json::from_str(&results).unwrap_or_default(),
+                created_at: row.get(7)?,
+            })
+        })
+        .map_err(|e| format!("查询验证记录失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取验证记录失败: {}", e))
+}
+
+/// 保留文本末尾的 `max_bytes` 字节（按字符边界截断）
+fn tail_text(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("... (truncated)\n{}", &text[start..])
+}
+
+/// 等待并持有项目的运行锁，一组命令执行完之前不释放
+pub async fn lock_project(project_path: &str) -> OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = PROJECT_RUN_LOCKS.lock().unwrap();
+        // 没有人持有或等待的锁不再保留
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(normalize_project_key(project_path)).or_default().clone()
+    };
+    lock.lock_owned().await
+}
+
+/// 在项目目录中执行一个命令（超时后终止进程树）
+pub async fn run_processor(project_path: &str, processor: &PostProcessor) -> ProcessorResult {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", &processor.command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", &processor.command]);
+        cmd
+    };
+    cmd.current_dir(project_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    apply_no_window_async(&mut cmd);
+
+    let started = Instant::now();
+    let timeout_secs = processor.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let (status, exit_code, output) = match cmd.spawn() {
+        Err(e) => (ProcessorStatus::Error, None, format!("启动命令失败: {}", e)),
+        Ok(child) => {
+            let pid = child.id();
+            match tokio::time::timeout(Duration::from_secs(timeout_secs), child.wait_with_output()).await {
+                Ok(Ok(output)) => {
+                    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+                    text.push_str(&String::from_utf8_lossy(&output.stderr));
+                    let status = if output.status.success() { ProcessorStatus::Passed } else { ProcessorStatus::Failed };
+                    (status, output.status.code(), text)
+                }
+                Ok(Err(e)) => (ProcessorStatus::Error, None, format!("等待命令结束失败: {}", e)),
+                Err(_) => {
+                    // 超时：结束整个进程树（测试命令通常会再启动子进程）
+                    if let Some(pid) = pid {
+                        let _ = kill_process_tree(pid);
+                    }
+                    (ProcessorStatus::TimedOut, None, format!("超过 {} 秒未完成，已终止", timeout_secs))
+                }
+            }
+        }
+    };
+
+    ProcessorResult {
+        name: processor.name.clone(),
+        kind: processor.kind,
+        command: processor.command.clone(),
+        status,
+        exit_code,
+        duration_ms: started.elapsed().as_millis() as u64,
+        output: tail_text(&output, MAX_OUTPUT_BYTES),
+    }
+}
+
+/// 根据失败的结果生成跟进提示词；全部通过时返回 None
+pub fn follow_up_prompt(results: &[ProcessorResult]) -> Option<String> {
+    let failed: Vec<&ProcessorResult> = results.iter().filter(|r| r.status != ProcessorStatus::Passed).collect();
+    if failed.is_empty() {
+        return None;
+    }
+    let mut prompt = String::from(
+        "The following checks failed after your last changes. \
+         Fix the problems so that all of them pass, without disabling or skipping the checks.\n",
+    );
+    for result in failed {
+        let status = match (result.status, result.exit_code) {
+            (ProcessorStatus::TimedOut, _) => "timed out".to_string(),
+            (ProcessorStatus::Error, _) => "could not be started".to_string(),
+            (_, Some(code)) => format!("exit code {}", code),
+            (_, None) => "failed".to_string(),
+        };
+        prompt.push_str(&format!(
+            "\n## {} ({}): `{}` — {}\n\n```text\n{}\n```\n",
+            result.name,
+            result.kind.label(),
+            result.command,
+            status,
+            tail_text(result.output.trim_end(), MAX_FOLLOW_UP_OUTPUT_BYTES)
+        ));
+    }
+    Some(prompt)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 提示词完成后执行项目配置的后处理命令并保存验证记录
+///
+/// 项目没有配置后处理命令时返回 None。
+#[tauri::command]
+pub async fn run_post_processors(
+    app: AppHandle,
+    session_id: String,
+    engine: String,
+    project_path: String,
+    prompt_index: usize,
+) -> Result<Option<VerificationOutcome>, String> {
+    let Some(settings) = load_project_settings(&project_path)
+        .post_processors
+        .filter(|s| !s.processors.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    log::info!(
+        "[PostProcessors] Running {} processors after {} prompt #{} in {}",
+        settings.processors.len(),
+        engine,
+        prompt_index,
+        project_path
+    );
+    let mut results = Vec::with_capacity(settings.processors.len());
+    let guard = lock_project(&project_path).await;
+    for processor in &settings.processors {
+        let result = run_processor(&project_path, processor).await;
+        log::info!(
+            "[PostProcessors] {} ({}) -> {:?} in {} ms",
+            result.name,
+            result.command,
+            result.status,
+            result.duration_ms
+        );
+        results.push(result);
+    }
+    drop(guard);
+
+    let mut record = VerificationRecord {
+        id: 0,
+        session_id: session_id.clone(),
+        engine,
+        project_path,
+        prompt_index,
+        passed: results.iter().all(|r| r.status == ProcessorStatus::Passed),
+        results,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    let record = tokio::task::spawn_blocking(move || {
+        let conn = open_agent_db()?;
+        insert_record(&conn, &mut record)?;
+        Ok::<_, String>(record)
+    })
+    .await
+    .map_err(|e| format!("保存验证记录失败: {}", e))??;
+
+    if let Err(e) = app.emit(&format!("verification-recorded:{}", session_id), &record) {
+        log::warn!("[PostProcessors] Failed to emit verification-recorded: {}", e);
+    }
+
+    let follow_up_prompt = if settings.auto_follow_up { follow_up_prompt(&record.results) } else { None };
+    Ok(Some(VerificationOutcome { record, follow_up_prompt }))
+}
+
+/// 会话的验证记录（按时间顺序）
+#[tauri::command]
+pub async fn list_verification_records(session_id: String) -> Result<Vec<VerificationRecord>, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = open_agent_db()?;
+        load_records(&conn, &session_id)
+    })
+        .await
+        .map_err(|e| format!("读取验证记录失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn processor(name: &str, kind: ProcessorKind, command: &str) -> PostProcessor {
+        PostProcessor { name: name.to_string(), kind, command: command.to_string(), timeout_secs: Some(5) }
+    }
+
+    #[tokio::test]
+    async fn runs_processors_and_builds_follow_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().to_str().unwrap();
+        let (ok, failing) = if cfg!(target_os = "windows") {
+            ("echo ok", "echo broken & exit /b 3")
+        } else {
+            ("echo ok", "echo broken; exit 3")
+        };
+
+        let passed = run_processor(project, &processor("fmt", ProcessorKind::Format, ok)).await;
+        assert_eq!(passed.status, ProcessorStatus::Passed);
+        assert_eq!(passed.output.trim(), "ok");
+        let failed = run_processor(project, &processor("tests", ProcessorKind::Test, failing)).await;
+        assert_eq!((failed.status, failed.exit_code), (ProcessorStatus::Failed, Some(3)));
+
+        assert_eq!(follow_up_prompt(std::slice::from_ref(&passed)), None);
+        let prompt = follow_up_prompt(&[passed, failed.clone()]).unwrap();
+        assert!(prompt.contains(&format!("## tests (test): `{}` — exit code 3", failing)));
+        assert!(prompt.contains("broken") && !prompt.contains("fmt"));
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::commands::db_migrations::run_migrations(&mut conn).unwrap();
+        let mut record = VerificationRecord {
+            id: 0,
+            session_id: "s1".to_string(),
+            engine: "codex".to_string(),
+            project_path: project.to_string(),
+            prompt_index: 2,
+            passed: false,
+            results: vec![failed],
+            created_at: Utc::now().to_rfc3339(),
+        };
+        insert_record(&conn, &mut record).unwrap();
+        assert_eq!(load_records(&conn, "s1").unwrap(), vec![record]);
+        assert_eq!(tail_text("abcdef", 3), "... (truncated)\ndef");
+    }
+}
@@ -0,0 +1,489 @@
+//! 发送前的上下文窗口预估
+//!
+//! 执行前估算本次请求的上下文大小：CLI 自带的系统提示词和工具定义、指令文件
+//! （CLAUDE.md / AGENTS.md / GEMINI.md）、项目默认的系统提示词模板、项目记忆、恢复会话的历史、
+//! 附件、仓库地图和提示词本身，与所选模型的上下文窗口比较，返回分项统计、警告级别和建议
+//! （精简历史、先总结会话、移除附件等）。
+//!
+//! 恢复会话时优先使用 CLI 记录的最近一轮用量（已包含系统提示词和全部历史），没有记录时按会话内容估算。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use super::attachments::{AttachmentKind, StagedAttachment};
+use super::gemini::config::read_session_detail;
+use super::project_defaults;
+use super::project_memory;
+use super::prompt_library::{apply_prompt_template, PromptTemplateRef};
+use super::repo_map::{build_repo_map, DEFAULT_MAP_TOKENS};
+use super::session_pages::locate_session_file;
+use super::tokenizer::{count_content, count_text, DOCUMENT_TOKENS, IMAGE_TOKENS};
+
+/// 达到上下文窗口的该比例时警告（与前端上下文指示器一致）
+const WARNING_PERCENT: f64 = 80.0;
+const CRITICAL_PERCENT: f64 = 90.0;
+
+/// 超过此大小的指令文件、文本附件不读取，按字节数估算
+const MAX_READ_BYTES: u64 = 2 * 1024 * 1024;
+
+/// 执行前检查的选项，字段与各引擎的执行选项同名，可直接传入执行选项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PreflightOptions {
+    pub project_path: String,
+    pub prompt: String,
+    pub model: Option<String>,
+    /// 恢复的会话
+    pub session_id: Option<String>,
+    /// Claude 的项目 ID（省略时在所有项目中查找会话）
+    pub project_id: Option<String>,
+    pub prompt_template: Option<PromptTemplateRef>,
+    pub include_memory: Option<bool>,
+    pub attachments: Vec<StagedAttachment>,
+    /// 是否会附带仓库地图
+    pub include_repo_map: bool,
+    pub repo_map_tokens: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextSource {
+    /// CLI 自带的系统提示词和工具定义（按引擎粗略估算）
+    CliBase,
+    /// CLAUDE.md / AGENTS.md / GEMINI.md
+    Instructions,
+    /// 项目默认的系统提示词模板
+    SystemPrompt,
+    Memory,
+    History,
+    Attachments,
+    RepoMap,
+    Prompt,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextComponent {
+    pub source: ContextSource,
+    pub tokens: usize,
+    /// 历史是否来自 CLI 记录的用量（否则为估算）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reported: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightLevel {
+    Ok,
+    Warning,
+    Critical,
+    Overflow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightAction {
+    /// 先总结当前会话（/compact）再继续
+    SummarizeSession,
+    /// 开始新会话，不带历史
+    TrimHistory,
+    RemoveAttachments,
+    ShrinkRepoMap,
+    ShortenPrompt,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightSuggestion {
+    pub action: PreflightAction,
+    pub message: String,
+    /// 预计可减少的 token 数
+    pub saves_tokens: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+    pub engine: String,
+    pub model: String,
+    pub context_window: usize,
+    pub estimated_tokens: usize,
+    pub usage_percent: f64,
+    pub level: PreflightLevel,
+    pub warning: Option<String>,
+    pub components: Vec<ContextComponent>,
+    pub suggestions: Vec<PreflightSuggestion>,
+}
+
+/// 模型的上下文窗口（与前端 ENGINE_CONTEXT_LIMITS 保持一致）
+pub fn context_window(engine: &str, model: &str) -> usize {
+    let model = model.to_lowercase();
+    match engine {
+        "codex" => {
+            if model.starts_with("gpt-4.1") {
+                1_000_000
+            } else if model.starts_with("gpt-4o") {
+                128_000
+            } else if model.starts_with("gpt-5") {
+                272_000
+            } else {
+                200_000
+            }
+        }
+        "gemini" => {
+            if model.starts_with("gemini-1.5-pro") {
+                2_000_000
+            } else {
+                1_000_000
+            }
+        }
+        _ => {
+            // 前端的模型 ID 为 sonnet1m，传给 CLI 时为 sonnet[1m]
+            if model.contains("[1m]") || model.ends_with("1m") {
+                1_000_000
+            } else {
+                200_000
+            }
+        }
+    }
+}
+
+/// CLI 自带的系统提示词和工具定义（粗略估算）
+fn cli_base_tokens(engine: &str) -> usize {
+    match engine {
+        "codex" => 6_000,
+        "gemini" => 9_000,
+        _ => 14_000,
+    }
+}
+
+fn default_model(engine: &str) -> &'static str {
+    match engine {
+        "codex" => "gpt-5-codex",
+        "gemini" => "gemini-2.5-pro",
+        _ => "sonnet",
+    }
+}
+
+/// CLI 启动时自动加载的指令文件
+fn instruction_files(engine: &str, project_path: &Path) -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+    match engine {
+        "codex" => vec![home.join(".codex").join("AGENTS.md"), project_path.join("AGENTS.md")],
+        "gemini" => vec![home.join(".gemini").join("GEMINI.md"), project_path.join("GEMINI.md")],
+        _ => vec![
+            home.join(".claude").join("CLAUDE.md"),
+            project_path.join("CLAUDE.md"),
+            project_path.join(".claude").join("CLAUDE.md"),
+            project_path.join("CLAUDE.local.md"),
+        ],
+    }
+}
+
+/// 文件内容的 token 数；过大的文件按字节数估算
+fn file_tokens(model: &str, path: &Path) -> usize {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_file() {
+        return 0;
+    }
+    if metadata.len() > MAX_READ_BYTES {
+        return (metadata.len() / 4) as usize;
+    }
+    std::fs::read_to_string(path)
+        .map(|text| count_text(model, &text))
+        .unwrap_or((metadata.len() / 4) as usize)
+}
+
+fn attachment_tokens(model: &str, attachments: &[StagedAttachment]) -> usize {
+    attachments
+        .iter()
+        .map(|attachment| match attachment.kind {
+            AttachmentKind::Image => IMAGE_TOKENS,
+            AttachmentKind::Pdf => DOCUMENT_TOKENS,
+            AttachmentKind::Text => file_tokens(model, Path::new(&attachment.path)),
+        })
+        .sum()
+}
+
+/// 会话记录中最近一轮的上下文用量
+fn reported_usage(engine: &str, entry: &Value) -> Option<usize> {
+    let tokens = |value: &Value, keys: &[&str]| -> u64 { keys.iter().filter_map(|k| value[*k].as_u64()).sum() };
+    let total = match engine {
+        "codex" => {
+            if entry["payload"]["type"] != "token_count" {
+                return None;
+            }
+            let usage = &entry["payload"]["info"]["last_token_usage"];
+            usage.as_object()?;
+            tokens(usage, &["input_tokens", "output_tokens"])
+        }
+        _ => {
+            let usage = &entry["message"]["usage"];
+            usage.as_object()?;
+            tokens(
+                usage,
+                &["input_tokens", "cache_creation_input_tokens", "cache_read_input_tokens", "output_tokens"],
+            )
+        }
+    };
+    Some(total as usize)
+}
+
+/// JSONL 会话的历史大小：(token 数, 是否为 CLI 记录的用量)
+fn jsonl_history_tokens(engine: &str, model: &str, path: &Path) -> Result<(usize, bool), String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("打开会话文件失败: {}", e))?;
+    let mut reported = None;
+    let mut estimated = 0;
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if let Some(usage) = reported_usage(engine, &entry) {
+            reported = Some(usage);
+        }
+        if reported.is_none() {
+            estimated += match engine {
+                "codex" if entry["type"] == "response_item" => count_content(model, &entry["payload"]),
+                "codex" => 0,
+                _ => count_content(model, &entry["message"]["content"]),
+            };
+        }
+    }
+    Ok(match reported {
+        Some(usage) => (usage, true),
+        None => (estimated, false),
+    })
+}
+
+fn history_tokens(engine: &str, model: &str, options: &PreflightOptions) -> Result<Option<(usize, bool)>, String> {
+    let Some(session_id) = options.session_id.as_deref().filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    if engine == "gemini" {
+        let detail = read_session_detail(&options.project_path, session_id)?;
+        let tokens = detail.messages.iter().map(|m| count_content(model, &m["content"])).sum();
+        return Ok(Some((tokens, false)));
+    }
+    let path = locate_session_file(engine, session_id, options.project_id.as_deref())?;
+    jsonl_history_tokens(engine, model, &path).map(Some)
+}
+
+fn component(source: ContextSource, tokens: usize) -> ContextComponent {
+    ContextComponent { source, tokens, reported: None }
+}
+
+fn tokens_of(components: &[ContextComponent], source: ContextSource) -> usize {
+    components.iter().filter(|c| c.source == source).map(|c| c.tokens).sum()
+}
+
+/// 根据分项统计给出级别、警告和建议
+fn assess(engine: &str, model: &str, window: usize, components: Vec<ContextComponent>) -> PreflightReport {
+    let estimated_tokens: usize = components.iter().map(|c| c.tokens).sum();
+    let usage_percent = estimated_tokens as f64 * 100.0 / window.max(1) as f64;
+    let level = if usage_percent > 100.0 {
+        PreflightLevel::Overflow
+    } else if usage_percent >= CRITICAL_PERCENT {
+        PreflightLevel::Critical
+    } else if usage_percent >= WARNING_PERCENT {
+        PreflightLevel::Warning
+    } else {
+        PreflightLevel::Ok
+    };
+
+    let mut suggestions = Vec::new();
+    if level != PreflightLevel::Ok {
+        let history = tokens_of(&components, ContextSource::History);
+        let attachments = tokens_of(&components, ContextSource::Attachments);
+        let repo_map = tokens_of(&components, ContextSource::RepoMap);
+        let prompt = tokens_of(&components, ContextSource::Prompt);
+        if history > 0 {
+            suggestions.push(PreflightSuggestion {
+                action: PreflightAction::SummarizeSession,
+                message: "先总结当前会话（/compact）再发送".to_string(),
+                saves_tokens: history * 4 / 5,
+            });
+            suggestions.push(PreflightSuggestion {
+                action: PreflightAction::TrimHistory,
+                message: "开始新会话，不带之前的历史".to_string(),
+                saves_tokens: history,
+            });
+        }
+        if attachments > 0 {
+            suggestions.push(PreflightSuggestion {
+                action: PreflightAction::RemoveAttachments,
+                message: "移除部分附件".to_string(),
+                saves_tokens: attachments,
+            });
+        }
+        if repo_map > 0 {
+            suggestions.push(PreflightSuggestion {
+                action: PreflightAction::ShrinkRepoMap,
+                message: "减小或不附带仓库地图".to_string(),
+                saves_tokens: repo_map,
+            });
+        }
+        if prompt * 5 >= window {
+            suggestions.push(PreflightSuggestion {
+                action: PreflightAction::ShortenPrompt,
+                message: "缩短提示词，或把大段内容改为附件".to_string(),
+                saves_tokens: prompt / 2,
+            });
+        }
+        suggestions.sort_by_key(|s| std::cmp::Reverse(s.saves_tokens));
+    }
+
+    let warning = match level {
+        PreflightLevel::Ok => None,
+        PreflightLevel::Overflow => Some(format!(
+            "预计上下文 {} tokens，超出模型上限 {} tokens",
+            estimated_tokens, window
+        )),
+        _ => Some(format!(
+            "预计上下文 {} / {} tokens（{:.0}%），接近模型上限",
+            estimated_tokens, window, usage_percent
+        )),
+    };
+
+    PreflightReport {
+        engine: engine.to_string(),
+        model: model.to_string(),
+        context_window: window,
+        estimated_tokens,
+        usage_percent,
+        level,
+        warning,
+        components,
+        suggestions,
+    }
+}
+
+/// 执行前估算上下文大小并与模型的上下文窗口比较
+#[tauri::command]
+pub async fn preflight_execution(engine: String, options: PreflightOptions) -> Result<PreflightReport, String> {
+    if !matches!(engine.as_str(), "claude" | "codex" | "gemini") {
+        return Err(format!("不支持的引擎: {}", engine));
+    }
+    let defaults = project_defaults::resolve_engine_defaults(&options.project_path, &engine);
+    let model = project_defaults::or_default(options.model.clone(), &defaults.model)
+        .unwrap_or_else(|| default_model(&engine).to_string());
+    let is_new_session = options.session_id.as_deref().filter(|s| !s.is_empty()).is_none();
+
+    let prompt = apply_prompt_template(&engine, options.prompt.clone(), options.prompt_template.as_ref()).await?;
+    let system_prompt = match defaults.system_prompt_template.as_deref() {
+        Some(template_id) if is_new_session => project_defaults::load_system_prompt_template(template_id)
+            .await
+            .map(|template| count_text(&model, &template))
+            .unwrap_or(0),
+        _ => 0,
+    };
+    let memory = if is_new_session && project_memory::should_include(options.include_memory, defaults.include_memory) {
+        project_memory::memory_for_prompt(&options.project_path, &prompt)
+            .await
+            .map_or(0, |block| count_text(&model, &block))
+    } else {
+        0
+    };
+
+    let (engine_blocking, model_blocking) = (engine.clone(), model.clone());
+    let components = tokio::task::spawn_blocking(move || -> Result<Vec<ContextComponent>, String> {
+        let (engine, model) = (engine_blocking, model_blocking);
+        let project_path = Path::new(&options.project_path);
+        let cli_base = cli_base_tokens(&engine);
+        let instructions: usize = instruction_files(&engine, project_path)
+            .iter()
+            .map(|path| file_tokens(&model, path))
+            .sum();
+        let repo_map = if options.include_repo_map {
+            build_repo_map(&options.project_path, options.repo_map_tokens.unwrap_or(DEFAULT_MAP_TOKENS))?.tokens
+        } else {
+            0
+        };
+
+        let mut components = vec![
+            component(ContextSource::CliBase, cli_base),
+            component(ContextSource::Instructions, instructions),
+            component(ContextSource::SystemPrompt, system_prompt),
+            component(ContextSource::Memory, memory),
+        ];
+        if let Some((tokens, reported)) = history_tokens(&engine, &model, &options)? {
+            // 记录的用量已包含系统提示词和指令文件
+            let tokens = if reported { tokens.saturating_sub(cli_base + instructions) } else { tokens };
+            components.push(ContextComponent { source: ContextSource::History, tokens, reported: Some(reported) });
+        }
+        components.push(component(ContextSource::Attachments, attachment_tokens(&model, &options.attachments)));
+        components.push(component(ContextSource::RepoMap, repo_map));
+        components.push(component(ContextSource::Prompt, count_text(&model, &prompt)));
+        Ok(components)
+    })
+    .await
+    .map_err(|e| format!("预估上下文失败: {}", e))??;
+
+    let report = assess(&engine, &model, context_window(&engine, &model), components);
+    if report.level != PreflightLevel::Ok {
+        log::info!(
+            "[Preflight] {} {}: {} / {} tokens ({:?})",
+            engine,
+            model,
+            report.estimated_tokens,
+            report.context_window,
+            report.level
+        );
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn assesses_context_against_model_window() {
+        assert_eq!(context_window("claude", "sonnet"), 200_000);
+        assert_eq!(context_window("claude", "sonnet[1m]"), 1_000_000);
+        assert_eq!(context_window("claude", "sonnet1m"), 1_000_000);
+        assert_eq!(context_window("codex", "gpt-5-codex"), 272_000);
+        assert_eq!(context_window("codex", "gpt-4o"), 128_000);
+        assert_eq!(context_window("gemini", "gemini-2.5-pro"), 1_000_000);
+
+        let ok = assess("claude", "sonnet", 200_000, vec![component(ContextSource::Prompt, 1_000)]);
+        assert_eq!(ok.level, PreflightLevel::Ok);
+        assert!(ok.warning.is_none() && ok.suggestions.is_empty());
+
+        let report = assess(
+            "claude",
+            "sonnet",
+            200_000,
+            vec![
+                component(ContextSource::CliBase, 14_000),
+                ContextComponent { source: ContextSource::History, tokens: 170_000, reported: Some(true) },
+                component(ContextSource::Attachments, IMAGE_TOKENS),
+                component(ContextSource::Prompt, 500),
+            ],
+        );
+        assert_eq!(report.level, PreflightLevel::Critical);
+        assert!(report.warning.is_some());
+        let actions: Vec<PreflightAction> = report.suggestions.iter().map(|s| s.action).collect();
+        assert_eq!(
+            actions,
+            vec![PreflightAction::TrimHistory, PreflightAction::SummarizeSession, PreflightAction::RemoveAttachments]
+        );
+        let overflow = assess("codex", "gpt-4o", 128_000, vec![component(ContextSource::Prompt, 130_000)]);
+        assert_eq!(overflow.level, PreflightLevel::Overflow);
+        assert_eq!(overflow.suggestions[0].action, PreflightAction::ShortenPrompt);
+
+        let claude_turn = json!({ "type": "assistant", "message": { "usage": {
+            "input_tokens": 10, "cache_read_input_tokens": 5000, "cache_creation_input_tokens": 200, "output_tokens": 90
+        } } });
+        assert_eq!(reported_usage("claude", &claude_turn), Some(5300));
+        let codex_turn = json!({ "type": "event_msg", "payload": { "type": "token_count", "info": {
+            "last_token_usage": { "input_tokens": 1200, "cached_input_tokens": 1000, "output_tokens": 30 }
+        } } });
+        assert_eq!(reported_usage("codex", &codex_turn), Some(1230));
+        assert_eq!(reported_usage("claude", &json!({ "type": "user", "message": { "content": "hi" } })), None);
+    }
+}
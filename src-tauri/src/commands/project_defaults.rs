@@ -0,0 +1,109 @@
+//! 项目级默认执行选项
+//!
+//! 为每个项目、每个引擎保存默认的模型、推理强度、权限档案、系统提示词模板、代理商
+//! 是否加入项目记忆以及自动选取的项目文件上下文预算，存放在项目设置（`project_settings.json`）中。执行命令解析顺序：
+//! 调用时显式传入的值 > 项目默认值 > 全局配置。
+
+use serde::{Deserialize, Serialize};
+
+use super::project_settings::{load_project_settings, save_project_settings};
+
+/// 单个引擎的默认选项
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 推理强度（仅 Codex，如 "medium" / "high"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+    /// 权限档案 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_profile: Option<String>,
+    /// 系统提示词模板 ID（提示词库中的模板）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt_template: Option<String>,
+    /// 代理商配置 ID（对应引擎的代理商预设）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// 是否加入相关的项目记忆笔记
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_memory: Option<bool>,
+    /// 新会话自动附带的相关项目文件的 token 预算（未设置时不附带）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_budget: Option<usize>,
+}
+
+/// 项目的默认执行选项（按引擎区分）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDefaults {
+    #[serde(default)]
+    pub claude: EngineDefaults,
+    #[serde(default)]
+    pub codex: EngineDefaults,
+    #[serde(default)]
+    pub gemini: EngineDefaults,
+}
+
+impl ProjectDefaults {
+    pub fn for_engine(&self, engine: &str) -> EngineDefaults {
+        match engine {
+            "claude" => self.claude.clone(),
+            "codex" => self.codex.clone(),
+            "gemini" => self.gemini.clone(),
+            _ => EngineDefaults::default(),
+        }
+    }
+}
+
+/// 显式值为空时使用项目默认值
+pub fn or_default(explicit: Option<String>, default: &Option<String>) -> Option<String> {
+    explicit.filter(|v| !v.is_empty()).or_else(|| default.clone())
+}
+
+/// 读取项目在某个引擎上的默认选项
+pub fn resolve_engine_defaults(project_path: &str, engine: &str) -> EngineDefaults {
+    load_project_settings(project_path)
+        .defaults
+        .map(|d| d.for_engine(engine))
+        .unwrap_or_default()
+}
+
+/// 读取系统提示词模板内容（不含 frontmatter）
+pub async fn load_system_prompt_template(template_id: &str) -> Result<String, String> {
+    super::prompt_library::load_template_body(template_id).await
+}
+
+/// 新会话附带的项目文件上下文（按 `context_budget` 选取，未设置时为 None）
+pub async fn context_block(project_path: &str, prompt: &str, defaults: &EngineDefaults) -> Option<String> {
+    let budget = defaults.context_budget.filter(|b| *b > 0)?;
+    super::context_manager::context_for_prompt(project_path, prompt, budget).await
+}
+
+/// 不支持追加系统提示词的引擎：把模板放在首条提示词之前
+pub fn prepend_system_prompt(template: &str, prompt: &str) -> String {
+    format!("<system-instructions>\n{}\n</system-instructions>\n\n{}", template.trim(), prompt)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 获取项目默认执行选项
+#[tauri::command]
+pub async fn get_project_defaults(project_path: String) -> Result<ProjectDefaults, String> {
+    Ok(load_project_settings(&project_path).defaults.unwrap_or_default())
+}
+
+/// 更新项目默认执行选项
+#[tauri::command]
+pub async fn set_project_defaults(
+    project_path: String,
+    defaults: ProjectDefaults,
+) -> Result<(), String> {
+    log::info!("[ProjectDefaults] Updating defaults for {}: {:?}", project_path, defaults);
+    let mut settings = load_project_settings(&project_path);
+    settings.defaults = Some(defaults).filter(|d| *d != ProjectDefaults::default());
+    save_project_settings(&project_path, settings)
+}
@@ -0,0 +1,380 @@
+//! 项目记忆
+//!
+//! 按项目保存跨会话的知识笔记（决策、约定、其他备注），持久化在 agents.db 的
+//! `project_notes` 表中。笔记可以手动添加，也可以让引擎从某个会话中提取。
+//!
+//! 执行时开启 `include_memory`（或在项目默认值中开启）后，与本次提示词相关的笔记
+//! 会作为 `<project-memory>` 块加入系统提示词：Claude 通过 `--append-system-prompt`，
+//! Codex / Gemini 在新会话的首条提示词之前。约定类笔记始终加入，其余按与提示词的
+//! 关键词重合度挑选。与 `context_manager`（单个会话内的上下文压缩）互为补充。
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+use super::session_summary::{extract_json_object, load_transcript, run_transcript_prompt};
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+
+/// 单次执行最多加入的笔记数
+const MAX_INJECTED_NOTES: usize = 20;
+
+const EXTRACT_PROMPT: &str = "Extract durable project knowledge from the coding session transcript below: \
+decisions that were made (and why) and conventions the code should follow. \
+Skip anything that only mattered for this session.\n\
+Reply with a single JSON object and nothing else: \
+{\"notes\": [{\"category\": \"decision\" | \"convention\" | \"note\", \"content\": \"...\"}]}\n\
+Keep each note to one or two sentences, written in the same language the user used. Do not use any tools.";
+
+/// 笔记类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteCategory {
+    Decision,
+    Convention,
+    Note,
+}
+
+impl NoteCategory {
+    fn as_str(&self) -> &'static str {
+        match self {
+            NoteCategory::Decision => "decision",
+            NoteCategory::Convention => "convention",
+            NoteCategory::Note => "note",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "decision" => NoteCategory::Decision,
+            "convention" => NoteCategory::Convention,
+            _ => NoteCategory::Note,
+        }
+    }
+}
+
+/// 一条项目笔记
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectNote {
+    pub id: i64,
+    pub project_path: String,
+    pub category: NoteCategory,
+    pub content: String,
+    /// 从会话中提取时的来源
+    pub source_engine: Option<String>,
+    pub source_session_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExtractedNote {
+    #[serde(default)]
+    category: Option<String>,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExtractedNotes {
+    #[serde(default)]
+    notes: Vec<ExtractedNote>,
+}
+
+/// 创建项目笔记表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS project_notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT NOT NULL,
+            category TEXT NOT NULL,
+            content TEXT NOT NULL,
+            source_engine TEXT,
+            source_session_id TEXT,
+            created_at TEXT NOT NULL,
+            UNIQUE (project_path, content)
+        );",
+    )
+    .map_err(|e| format!("创建项目笔记表失败: {}", e))
+}
+
+fn open_memory_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+fn row_to_note(row: &rusqlite::Row) -> rusqlite::Result<ProjectNote> {
+    let category: String = row.get(2)?;
+    Ok(ProjectNote {
+        id: row.get(0)?,
+        project_path: row.get(1)?,
+        category: NoteCategory::parse(&category),
+        content: row.get(3)?,
+        source_engine: row.get(4)?,
+        source_session_id: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+const SELECT_COLUMNS: &str =
+    "id, project_path, category, content, source_engine, source_session_id, created_at";
+
+fn query_notes(conn: &Connection, project_path: &str) -> Result<Vec<ProjectNote>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM project_notes WHERE project_path = ?1 ORDER BY created_at DESC, id DESC",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| format!("查询项目笔记失败: {}", e))?;
+    let notes = stmt
+        .query_map(params![project_path], row_to_note)
+        .map_err(|e| format!("查询项目笔记失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取项目笔记失败: {}", e));
+    notes
+}
+
+/// 插入笔记；内容重复时返回 None
+fn insert_note(
+    conn: &Connection,
+    project_path: &str,
+    category: NoteCategory,
+    content: &str,
+    source: Option<(&str, &str)>,
+) -> Result<Option<ProjectNote>, String> {
+    let inserted = conn
+        .execute(
+            "INSERT OR IGNORE INTO project_notes
+                (project_path, category, content, source_engine, source_session_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                project_path,
+                category.as_str(),
+                content,
+                source.map(|s| s.0),
+                source.map(|s| s.1),
+                Utc::now().to_rfc3339()
+            ],
+        )
+        .map_err(|e| format!("保存项目笔记失败: {}", e))?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+    conn.query_row(
+        &format!("SELECT {} FROM project_notes WHERE id = ?1", SELECT_COLUMNS),
+        params![conn.last_insert_rowid()],
+        row_to_note,
+    )
+    .optional()
+    .map_err(|e| format!("读取项目笔记失败: {}", e))
+}
+
+async fn with_memory_db<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> Result<T, String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&*open_memory_db()?))
+        .await
+        .map_err(|e| format!("访问项目笔记失败: {}", e))?
+}
+
+// ============================================================================
+// 注入
+// ============================================================================
+
+/// 关键词：小写的字母数字词（至少 3 个字符），中日韩文字按相邻两字切分
+pub fn keywords(text: &str) -> HashSet<String> {
+    let mut words = HashSet::new();
+    let mut current = String::new();
+    let mut previous_cjk: Option<char> = None;
+    for c in text.chars() {
+        let is_cjk = ('\u{4e00}'..='\u{9fff}').contains(&c)
+            || ('\u{3040}'..='\u{30ff}').contains(&c)
+            || ('\u{ac00}'..='\u{d7af}').contains(&c);
+        if is_cjk {
+            if let Some(prev) = previous_cjk {
+                words.insert(format!("{}{}", prev, c));
+            }
+            previous_cjk = Some(c);
+        } else {
+            previous_cjk = None;
+        }
+        if c.is_ascii_alphanumeric() || c == '_' {
+            current.push(c.to_ascii_lowercase());
+        } else {
+            if current.len() >= 3 {
+                words.insert(std::mem::take(&mut current));
+            }
+            current.clear();
+        }
+    }
+    if current.len() >= 3 {
+        words.insert(current);
+    }
+    words
+}
+
+/// 挑选与提示词相关的笔记：约定始终保留，其余按关键词重合数排序（同分时较新的在前）
+fn select_relevant_notes<'a>(notes: &'a [ProjectNote], prompt: &str, limit: usize) -> Vec<&'a ProjectNote> {
+    let prompt_words = keywords(prompt);
+    let mut scored: Vec<(bool, usize, &ProjectNote)> = notes
+        .iter()
+        .map(|note| {
+            let overlap = keywords(&note.content).intersection(&prompt_words).count();
+            (note.category == NoteCategory::Convention, overlap, note)
+        })
+        .filter(|(is_convention, overlap, _)| *is_convention || *overlap > 0)
+        .collect();
+    // notes 已按时间倒序，稳定排序保持同分笔记的先后
+    scored.sort_by_key(|(is_convention, overlap, _)| std::cmp::Reverse((*is_convention, *overlap)));
+    scored.into_iter().take(limit).map(|(_, _, note)| note).collect()
+}
+
+fn render_memory_block(notes: &[&ProjectNote]) -> String {
+    let lines: Vec<String> = notes
+        .iter()
+        .map(|note| format!("- [{}] {}", note.category.as_str(), note.content))
+        .collect();
+    format!(
+        "<project-memory>\nNotes recorded in earlier sessions of this project:\n{}\n</project-memory>",
+        lines.join("\n")
+    )
+}
+
+/// 与提示词相关的项目记忆块；没有相关笔记或读取失败时返回 None
+pub async fn memory_for_prompt(project_path: &str, prompt: &str) -> Option<String> {
+    let path = project_path.to_string();
+    let notes = match with_memory_db(move |conn| query_notes(conn, &path)).await {
+        Ok(notes) => notes,
+        Err(e) => {
+            log::warn!("[ProjectMemory] Failed to load notes for {}: {}", project_path, e);
+            return None;
+        }
+    };
+    let relevant = select_relevant_notes(&notes, prompt, MAX_INJECTED_NOTES);
+    if relevant.is_empty() {
+        return None;
+    }
+    log::info!("[ProjectMemory] Injecting {} of {} notes for {}", relevant.len(), notes.len(), project_path);
+    Some(render_memory_block(&relevant))
+}
+
+/// 是否加入项目记忆：调用时显式传入的值 > 项目默认值（默认关闭）
+pub fn should_include(explicit: Option<bool>, default: Option<bool>) -> bool {
+    explicit.or(default).unwrap_or(false)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出项目笔记（最新的在前）
+#[tauri::command]
+pub async fn list_project_notes(
+    project_path: String,
+    category: Option<NoteCategory>,
+) -> Result<Vec<ProjectNote>, String> {
+    let notes = with_memory_db(move |conn| query_notes(conn, &project_path)).await?;
+    Ok(notes
+        .into_iter()
+        .filter(|note| category.is_none_or(|c| note.category == c))
+        .collect())
+}
+
+/// 手动添加一条笔记
+#[tauri::command]
+pub async fn add_project_note(
+    project_path: String,
+    category: NoteCategory,
+    content: String,
+) -> Result<ProjectNote, String> {
+    let content = content.trim().to_string();
+    if content.is_empty() {
+        return Err("笔记内容不能为空".to_string());
+    }
+    with_memory_db(move |conn| insert_note(conn, &project_path, category, &content, None))
+        .await?
+        .ok_or_else(|| "相同内容的笔记已存在".to_string())
+}
+
+/// 删除一条笔记
+#[tauri::command]
+pub async fn delete_project_note(id: i64) -> Result<(), String> {
+    with_memory_db(move |conn| {
+        conn.execute("DELETE FROM project_notes WHERE id = ?1", params![id])
+            .map(|_| ())
+            .map_err(|e| format!("删除项目笔记失败: {}", e))
+    })
+    .await
+}
+
+/// 让会话所用的引擎从会话中提取笔记，返回新增的笔记（已存在的内容会被跳过）
+#[tauri::command]
+pub async fn extract_project_notes(
+    engine: String,
+    session_id: String,
+    project_path: String,
+    app_handle: AppHandle,
+) -> Result<Vec<ProjectNote>, String> {
+    let lines = load_transcript(&engine, &session_id, &project_path).await?;
+    if lines.is_empty() {
+        return Err("会话中没有可提取的内容".to_string());
+    }
+    let reply = run_transcript_prompt(&app_handle, &engine, EXTRACT_PROMPT, &lines).await?;
+    let json = extract_json_object(&reply).ok_or("引擎未返回 JSON 格式的笔记")?;
+    let extracted: ExtractedNotes =
+        serde_json::from_str(json).map_err(|e| format!("解析提取的笔记失败: {}", e))?;
+
+    let notes = with_memory_db(move |conn| {
+        let mut added = Vec::new();
+        for note in extracted.notes {
+            let content = note.content.trim();
+            if content.is_empty() {
+                continue;
+            }
+            let category = NoteCategory::parse(note.category.as_deref().unwrap_or_default());
+            if let Some(note) = insert_note(conn, &project_path, category, content, Some((&engine, &session_id)))? {
+                added.push(note);
+            }
+        }
+        Ok(added)
+    })
+    .await?;
+    log::info!("[ProjectMemory] Extracted {} new notes", notes.len());
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_conventions_and_matching_notes() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        let add = |category, content| insert_note(&conn, "/p", category, content, None).unwrap();
+        add(NoteCategory::Convention, "Errors are returned as Result<T, String>");
+        add(NoteCategory::Decision, "Session metadata lives in agents.db, not in CLI files");
+        add(NoteCategory::Decision, "缓存层使用 LRU 淘汰策略");
+        add(NoteCategory::Note, "The release script needs Node 20");
+        assert!(add(NoteCategory::Note, "The release script needs Node 20").is_none());
+
+        let notes = query_notes(&conn, "/p").unwrap();
+        assert_eq!(notes.len(), 4);
+
+        let picked = select_relevant_notes(&notes, "Where is session metadata stored?", 10);
+        let contents: Vec<&str> = picked.iter().map(|n| n.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec!["Errors are returned as Result<T, String>", "Session metadata lives in agents.db, not in CLI files"]
+        );
+
+        let picked = select_relevant_notes(&notes, "给缓存层加上过期时间", 10);
+        assert_eq!(picked.len(), 2);
+        assert_eq!(picked[1].content, "缓存层使用 LRU 淘汰策略");
+        assert!(render_memory_block(&picked).starts_with("<project-memory>\n"));
+    }
+}
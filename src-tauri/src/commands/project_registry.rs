@@ -0,0 +1,369 @@
+//! 项目注册表
+//!
+//! 各引擎的项目列表都是从各自的会话目录推导出来的，还没运行过的项目不会出现。
+//! 这里在 agents.db 的 `registered_projects` 表中维护一份与引擎无关的项目列表：
+//! 可以手动注册单个目录，也可以拖入一个父目录，扫描其中的 Git 仓库批量注册。
+//! 列表返回时附带 Git 状态，以及每个引擎的最近会话时间和项目默认代理商。
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::project_defaults;
+use super::simple_git::{self, run_git};
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+
+/// 扫描父目录时的默认深度
+const DEFAULT_SCAN_DEPTH: usize = 2;
+
+/// 扫描时跳过的目录
+const SKIPPED_DIRS: [&str; 5] = ["node_modules", "target", "dist", "build", "vendor"];
+
+const ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 项目的 Git 状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectGitInfo {
+    /// 当前分支（HEAD 游离时为空）
+    pub branch: Option<String>,
+    /// HEAD 的短哈希（空仓库时为空）
+    pub head: Option<String>,
+    /// 工作区有未提交的改动
+    pub dirty: bool,
+    pub remote_url: Option<String>,
+}
+
+/// 项目在某个引擎上的活动
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineActivity {
+    pub engine: String,
+    pub session_count: usize,
+    /// 最近会话时间（Unix 秒）
+    pub last_session_at: Option<u64>,
+    /// 项目默认代理商（未设置时使用引擎的全局配置）
+    pub provider: Option<String>,
+}
+
+/// 已注册的项目
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredProject {
+    pub path: String,
+    pub name: String,
+    pub added_at: String,
+    /// 目录已不存在（移动或删除）
+    pub missing: bool,
+    /// 非 Git 仓库时为空
+    pub git: Option<ProjectGitInfo>,
+    pub engines: Vec<EngineActivity>,
+}
+
+/// 创建项目注册表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS registered_projects (
+            path TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            added_at TEXT NOT NULL
+        );",
+    )
+    .map_err(|e| format!("创建项目注册表失败: {}", e))
+}
+
+fn open_registry_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+/// 与各引擎项目列表匹配时使用的路径形式
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+/// 规范化为绝对路径（去掉 Windows 的 `\\?\` 前缀）
+fn canonical_dir(path: &str) -> Result<PathBuf, String> {
+    let dir = Path::new(path.trim());
+    if !dir.is_dir() {
+        return Err(format!("目录不存在: {}", path));
+    }
+    let canonical = dir.canonicalize().map_err(|e| format!("无法解析路径 {}: {}", path, e))?;
+    let display = canonical.to_string_lossy();
+    Ok(match display.strip_prefix(r"\\?\") {
+        Some(stripped) => PathBuf::from(stripped),
+        None => canonical,
+    })
+}
+
+fn project_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// 查找 `root` 下的 Git 仓库（`root` 本身是仓库时只返回它，不进入仓库内部）
+fn find_git_repos(root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    if root.join(".git").exists() {
+        return vec![root.to_path_buf()];
+    }
+    if max_depth == 0 {
+        return Vec::new();
+    }
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut children: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    children.sort();
+    children
+        .iter()
+        .flat_map(|child| find_git_repos(child, max_depth - 1))
+        .collect()
+}
+
+fn insert_project(conn: &Connection, path: &Path) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO registered_projects (path, name, added_at) VALUES (?1, ?2, ?3)",
+        params![path.to_string_lossy(), project_name(path), Utc::now().to_rfc3339()],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("注册项目失败: {}", e))
+}
+
+/// 读取已注册的项目，返回 (path, name, added_at)
+fn load_projects(conn: &Connection) -> Result<Vec<(String, String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, name, added_at FROM registered_projects ORDER BY added_at DESC")
+        .map_err(|e| format!("查询项目注册表失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| format!("查询项目注册表失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取项目注册表失败: {}", e))?;
+    Ok(rows)
+}
+
+fn git_info(path: &str) -> Option<ProjectGitInfo> {
+    if !simple_git::is_git_repo(path) {
+        return None;
+    }
+    let output = |args: &[&str]| {
+        run_git(path, args)
+            .ok()
+            .map(|out| out.trim().to_string())
+            .filter(|out| !out.is_empty())
+    };
+    Some(ProjectGitInfo {
+        branch: simple_git::git_current_branch(path).ok().flatten(),
+        head: output(&["rev-parse", "--short", "HEAD"]),
+        dirty: output(&["status", "--porcelain"]).is_some(),
+        remote_url: output(&["remote", "get-url", "origin"]),
+    })
+}
+
+/// 各引擎按项目汇总的会话数与最近会话时间（键为规范化路径）
+type SessionIndex = HashMap<String, (usize, u64)>;
+
+/// 从各引擎的会话目录汇总 Claude / Codex 的项目活动（Gemini 按项目单独读取）
+async fn load_session_indexes() -> HashMap<&'static str, SessionIndex> {
+    let mut indexes = HashMap::new();
+    match super::claude::list_projects().await {
+        Ok(projects) => {
+            let index = projects
+                .into_iter()
+                .map(|p| (normalize_path(&p.path), (p.sessions.len(), p.created_at)))
+                .collect();
+            indexes.insert("claude", index);
+        }
+        Err(e) => log::warn!("[ProjectRegistry] Failed to load Claude projects: {}", e),
+    }
+    match super::codex::list_codex_projects().await {
+        Ok(projects) => {
+            let index = projects
+                .into_iter()
+                .map(|p| (normalize_path(&p.project_path), (p.session_count, p.last_activity)))
+                .collect();
+            indexes.insert("codex", index);
+        }
+        Err(e) => log::warn!("[ProjectRegistry] Failed to load Codex projects: {}", e),
+    }
+    indexes
+}
+
+fn gemini_activity(path: &str) -> (usize, u64) {
+    let sessions = super::gemini::config::list_session_files(path).unwrap_or_default();
+    let last = sessions
+        .iter()
+        .filter_map(|s| chrono::DateTime::parse_from_rfc3339(&s.start_time).ok())
+        .map(|t| t.timestamp().max(0) as u64)
+        .max()
+        .unwrap_or(0);
+    (sessions.len(), last)
+}
+
+fn engine_activity(path: &str, indexes: &HashMap<&'static str, SessionIndex>) -> Vec<EngineActivity> {
+    let key = normalize_path(path);
+    ENGINES
+        .iter()
+        .map(|engine| {
+            let (session_count, last) = match *engine {
+                "gemini" => gemini_activity(path),
+                _ => indexes
+                    .get(engine)
+                    .and_then(|index| index.get(&key))
+                    .copied()
+                    .unwrap_or((0, 0)),
+            };
+            EngineActivity {
+                engine: engine.to_string(),
+                session_count,
+                last_session_at: (last > 0).then_some(last),
+                provider: project_defaults::resolve_engine_defaults(path, engine).provider,
+            }
+        })
+        .collect()
+}
+
+/// 组装项目详情（Git 状态、引擎活动）
+fn describe_project(
+    (path, name, added_at): (String, String, String),
+    indexes: &HashMap<&'static str, SessionIndex>,
+) -> RegisteredProject {
+    let missing = !Path::new(&path).is_dir();
+    RegisteredProject {
+        git: if missing { None } else { git_info(&path) },
+        engines: engine_activity(&path, indexes),
+        path,
+        name,
+        added_at,
+        missing,
+    }
+}
+
+async fn describe_projects(rows: Vec<(String, String, String)>) -> Result<Vec<RegisteredProject>, String> {
+    let indexes = load_session_indexes().await;
+    tokio::task::spawn_blocking(move || rows.into_iter().map(|row| describe_project(row, &indexes)).collect())
+        .await
+        .map_err(|e| format!("读取项目信息失败: {}", e))
+}
+
+/// 注册若干目录，返回新注册和已存在的项目行
+async fn register_dirs(dirs: Vec<PathBuf>) -> Result<Vec<(String, String, String)>, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = open_registry_db()?;
+        for dir in &dirs {
+            insert_project(&conn, dir)?;
+        }
+        let wanted: Vec<String> = dirs.iter().map(|d| d.to_string_lossy().to_string()).collect();
+        Ok(load_projects(&conn)?
+            .into_iter()
+            .filter(|(path, _, _)| wanted.contains(path))
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("注册项目失败: {}", e))?
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 注册项目目录（已注册时直接返回）
+#[tauri::command]
+pub async fn register_project(path: String) -> Result<RegisteredProject, String> {
+    let dir = canonical_dir(&path)?;
+    let rows = register_dirs(vec![dir.clone()]).await?;
+    log::info!("[ProjectRegistry] Registered {}", dir.display());
+    describe_projects(rows)
+        .await?
+        .pop()
+        .ok_or_else(|| format!("注册项目失败: {}", dir.display()))
+}
+
+/// 扫描目录中的 Git 仓库并批量注册（用于拖入文件夹）
+///
+/// 目录本身是仓库时只注册它；否则向下查找 `max_depth` 层（默认 2），跳过隐藏目录和
+/// `node_modules` 等依赖目录。没有找到仓库时把目录本身注册为项目。
+#[tauri::command]
+pub async fn scan_projects(root: String, max_depth: Option<usize>) -> Result<Vec<RegisteredProject>, String> {
+    let root = canonical_dir(&root)?;
+    let depth = max_depth.unwrap_or(DEFAULT_SCAN_DEPTH);
+    let scan_root = root.clone();
+    let mut repos = tokio::task::spawn_blocking(move || find_git_repos(&scan_root, depth))
+        .await
+        .map_err(|e| format!("扫描目录失败: {}", e))?;
+    if repos.is_empty() {
+        repos.push(root.clone());
+    }
+    log::info!("[ProjectRegistry] Scanned {}: {} project(s)", root.display(), repos.len());
+    describe_projects(register_dirs(repos).await?).await
+}
+
+/// 列出已注册的项目（附带 Git 状态和各引擎的活动）
+#[tauri::command]
+pub async fn list_registered_projects() -> Result<Vec<RegisteredProject>, String> {
+    let rows = tokio::task::spawn_blocking(|| load_projects(&*open_registry_db()?))
+        .await
+        .map_err(|e| format!("读取项目注册表失败: {}", e))??;
+    describe_projects(rows).await
+}
+
+/// 从注册表中移除项目（不删除任何文件）
+#[tauri::command]
+pub async fn remove_project(path: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = open_registry_db()?;
+        let removed = conn
+            .execute("DELETE FROM registered_projects WHERE path = ?1", params![path])
+            .map_err(|e| format!("移除项目失败: {}", e))?;
+        if removed == 0 {
+            return Err(format!("项目未注册: {}", path));
+        }
+        log::info!("[ProjectRegistry] Removed {}", path);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("移除项目失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nested_repos_and_registers_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for repo in ["app/.git", "libs/core/.git", "app/sub/.git", "node_modules/pkg/.git", ".hidden/x/.git"] {
+            std::fs::create_dir_all(root.join(repo)).unwrap();
+        }
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+
+        let repos = find_git_repos(root, 2);
+        assert_eq!(repos, vec![root.join("app"), root.join("libs").join("core")]);
+        assert!(find_git_repos(root, 1).contains(&root.join("app")));
+        assert_eq!(find_git_repos(&root.join("app"), 2), vec![root.join("app")]);
+
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        insert_project(&conn, &repos[0]).unwrap();
+        insert_project(&conn, &repos[0]).unwrap();
+        let rows = load_projects(&conn).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].1, "app");
+
+        assert_eq!(normalize_path(r"C:\Work\App\"), "c:/work/app");
+    }
+}
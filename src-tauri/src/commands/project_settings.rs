@@ -0,0 +1,143 @@
+//! 项目级设置
+//!
+//! 按项目路径保存的设置（如 WSL 发行版），持久化在 `~/.anycode/project_settings.json`。
+//! 未配置的项目使用全局设置。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::commit_message::CommitMessageSettings;
+use super::docker_backend::ContainerConfig;
+use super::guardrails::FsPolicy;
+use super::post_processors::PostProcessorSettings;
+use super::test_watch::TestWatchSettings;
+use super::project_defaults::ProjectDefaults;
+use super::data_root::anycode_dir;
+
+/// 单个项目的设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettings {
+    /// 在该项目中执行引擎时使用的 WSL 发行版（仅 Windows，留空则使用全局配置）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wsl_distro: Option<String>,
+    /// 远程 SSH 模式下该项目在远程主机上的目录（留空则使用相同路径）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_path: Option<String>,
+    /// 在 Docker 容器中执行引擎（镜像、挂载等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerConfig>,
+    /// 文件系统护栏策略（留空则不检查）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_policy: Option<FsPolicy>,
+    /// 默认执行选项（模型、权限档案等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<ProjectDefaults>,
+    /// 提交信息生成（引擎、模板、是否用于自动提交）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_message: Option<CommitMessageSettings>,
+    /// 提示词完成后执行的格式化 / Lint / 测试命令
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_processors: Option<PostProcessorSettings>,
+    /// Agent 修改文件后自动重跑的测试命令
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_watch: Option<TestWatchSettings>,
+}
+
+impl ProjectSettings {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn get_project_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("project_settings.json"))
+}
+
+/// 项目路径归一化（与会话列表的路径比较规则一致）
+pub(crate) fn normalize_project_key(project_path: &str) -> String {
+    project_path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+fn load_all() -> HashMap<String, ProjectSettings> {
+    let Ok(path) = get_project_settings_path() else {
+        return HashMap::new();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return HashMap::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!("[ProjectSettings] Failed to parse {:?}: {}", path, e);
+        HashMap::new()
+    })
+}
+
+fn save_all(all: &HashMap<String, ProjectSettings>) -> Result<(), String> {
+    let path = get_project_settings_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(all)
+        .map_err(|e| format!("Failed to serialize project settings: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write project settings: {}", e))
+}
+
+/// 读取项目设置（未配置时返回默认值）
+pub fn load_project_settings(project_path: &str) -> ProjectSettings {
+    load_all()
+        .remove(&normalize_project_key(project_path))
+        .unwrap_or_default()
+}
+
+/// 所有已配置的项目设置
+pub fn load_all_project_settings() -> Vec<ProjectSettings> {
+    load_all().into_values().collect()
+}
+
+/// 保存项目设置（全部为默认值时删除该项目的记录）
+pub fn save_project_settings(project_path: &str, settings: ProjectSettings) -> Result<(), String> {
+    let mut all = load_all();
+    let key = normalize_project_key(project_path);
+    if settings.is_empty() {
+        all.remove(&key);
+    } else {
+        all.insert(key, settings);
+    }
+    save_all(&all)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 获取项目设置
+#[tauri::command]
+pub async fn get_project_settings(project_path: String) -> Result<ProjectSettings, String> {
+    Ok(load_project_settings(&project_path))
+}
+
+/// 更新项目设置
+#[tauri::command]
+pub async fn set_project_settings(
+    project_path: String,
+    settings: ProjectSettings,
+) -> Result<(), String> {
+    log::info!("[ProjectSettings] Updating settings for {}: {:?}", project_path, settings);
+    save_project_settings(&project_path, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_keys_ignore_separators_and_case() {
+        assert_eq!(
+            normalize_project_key("C:\\Users\\Dev\\App\\"),
+            normalize_project_key("c:/users/dev/app")
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! 提示词历史
+//!
+//! 记录每次发送给引擎的提示词（按引擎、项目区分），持久化在 agents.db 的
+//! `prompt_history` 表中。与 `prompt_tracker`（按会话记录、用于回滚）不同，
+//! 这里是全局可查询的历史：同一引擎、项目下相同的提示词只保留一条，
+//! 重复发送时更新使用次数与时间；支持搜索、收藏、打标签和重新执行。
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+
+/// 搜索默认返回条数
+const DEFAULT_SEARCH_LIMIT: u32 = 100;
+
+/// 一条提示词历史
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptHistoryEntry {
+    pub id: i64,
+    pub engine: String,
+    pub project_path: String,
+    pub prompt: String,
+    pub favorite: bool,
+    pub tags: Vec<String>,
+    /// 发送次数（去重后累计）
+    pub use_count: i64,
+    pub created_at: String,
+    pub last_used_at: String,
+}
+
+/// 搜索条件（均为可选）
+#[derive(Debug, Clone, Default)]
+struct HistoryQuery {
+    text: Option<String>,
+    engine: Option<String>,
+    project_path: Option<String>,
+    favorites_only: bool,
+    tag: Option<String>,
+    limit: u32,
+}
+
+/// 创建提示词历史表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS prompt_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            engine TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            prompt TEXT NOT NULL,
+            favorite INTEGER NOT NULL DEFAULT 0,
+            tags TEXT NOT NULL DEFAULT '[]',
+            use_count INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT NOT NULL,
+            UNIQUE (engine, project_path, prompt)
+        );
+        CREATE INDEX IF NOT EXISTS idx_prompt_history_last_used
+            ON prompt_history(last_used_at);",
+    )
+    .map_err(|e| format!("创建提示词历史表失败: {}", e))
+}
+
+fn open_history_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// ============================================================================
+// 写入
+// ============================================================================
+
+fn upsert_prompt(conn: &Connection, engine: &str, project_path: &str, prompt: &str) -> rusqlite::Result<usize> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO prompt_history (engine, project_path, prompt, created_at, last_used_at)
+         VALUES (?1, ?2, ?3, ?4, ?4)
+         ON CONFLICT (engine, project_path, prompt)
+         DO UPDATE SET use_count = use_count + 1, last_used_at = excluded.last_used_at",
+        params![engine, project_path, prompt, now],
+    )
+}
+
+/// 记录一次发送的提示词；失败只记录日志，不影响执行
+pub async fn record_prompt(engine: &str, project_path: &str, prompt: &str) {
+    if prompt.trim().is_empty() {
+        return;
+    }
+    let (engine_owned, project_path, prompt) = (engine.to_string(), project_path.to_string(), prompt.to_string());
+    let result = with_history_db(move |conn| {
+        upsert_prompt(conn, &engine_owned, &project_path, &prompt).map_err(|e| e.to_string())
+    })
+    .await;
+    if let Err(e) = result {
+        log::warn!("[PromptHistory] Failed to record {} prompt: {}", engine, e);
+    }
+}
+
+fn update_entry(conn: &Connection, id: i64, column: &str, value: &dyn rusqlite::ToSql) -> Result<(), String> {
+    let updated = conn
+        .execute(&format!("UPDATE prompt_history SET {} = ?1 WHERE id = ?2", column), params![value, id])
+        .map_err(|e| format!("更新提示词历史失败: {}", e))?;
+    if updated == 0 {
+        return Err(format!("提示词历史不存在: {}", id));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// 查询
+// ============================================================================
+
+const SELECT_COLUMNS: &str =
+    "id, engine, project_path, prompt, favorite, tags, use_count, created_at, last_used_at";
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<PromptHistoryEntry> {
+    let tags: String = row.get(5)?;
+    Ok(PromptHistoryEntry {
+        id: row.get(0)?,
+        engine: row.get(1)?,
+        project_path: row.get(2)?,
+        prompt: row.get(3)?,
+        favorite: row.get(4)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        use_count: row.get(6)?,
+        created_at: row.get(7)?,
+        last_used_at: row.get(8)?,
+    })
+}
+
+fn query_history(conn: &Connection, query: &HistoryQuery) -> Result<Vec<PromptHistoryEntry>, String> {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+    if let Some(text) = query.text.as_deref().filter(|t| !t.is_empty()) {
+        values.push(format!("%{}%", escape_like(text)));
+        conditions.push(format!("prompt LIKE ?{} ESCAPE '\\'", values.len()));
+    }
+    if let Some(engine) = &query.engine {
+        values.push(engine.clone());
+        conditions.push(format!("engine = ?{}", values.len()));
+    }
+    if let Some(project_path) = &query.project_path {
+        values.push(project_path.clone());
+        conditions.push(format!("project_path = ?{}", values.len()));
+    }
+    if let Some(tag) = &query.tag {
+        // 标签以 JSON 数组存储，按带引号的元素匹配
+        let quoted = serde_json::to_string(tag).unwrap_or_default();
+        values.push(format!("%{}%", escape_like(&quoted)));
+        conditions.push(format!("tags LIKE ?{} ESCAPE '\\'", values.len()));
+    }
+    if query.favorites_only {
+        conditions.push("favorite = 1".to_string());
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    let sql = format!(
+        "SELECT {} FROM prompt_history {} ORDER BY last_used_at DESC, id DESC LIMIT {}",
+        SELECT_COLUMNS, where_clause, query.limit
+    );
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("查询提示词历史失败: {}", e))?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(values.iter()), row_to_entry)
+        .map_err(|e| format!("查询提示词历史失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取提示词历史失败: {}", e));
+    rows
+}
+
+fn get_entry(conn: &Connection, id: i64) -> Result<PromptHistoryEntry, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM prompt_history WHERE id = ?1", SELECT_COLUMNS),
+        params![id],
+        row_to_entry,
+    )
+    .optional()
+    .map_err(|e| format!("查询提示词历史失败: {}", e))?
+    .ok_or_else(|| format!("提示词历史不存在: {}", id))
+}
+
+async fn with_history_db<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> Result<T, String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&*open_history_db()?))
+        .await
+        .map_err(|e| format!("访问提示词历史失败: {}", e))?
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 搜索提示词历史（最近使用的在前）
+#[tauri::command]
+pub async fn search_prompt_history(
+    query: Option<String>,
+    engine: Option<String>,
+    project_path: Option<String>,
+    favorites_only: Option<bool>,
+    tag: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<PromptHistoryEntry>, String> {
+    let query = HistoryQuery {
+        text: query,
+        engine,
+        project_path,
+        favorites_only: favorites_only.unwrap_or(false),
+        tag,
+        limit: limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+    };
+    with_history_db(move |conn| query_history(conn, &query)).await
+}
+
+/// 收藏 / 取消收藏
+#[tauri::command]
+pub async fn set_prompt_favorite(id: i64, favorite: bool) -> Result<(), String> {
+    with_history_db(move |conn| update_entry(conn, id, "favorite", &favorite)).await
+}
+
+/// 设置标签（整体替换）
+#[tauri::command]
+pub async fn set_prompt_tags(id: i64, tags: Vec<String>) -> Result<(), String> {
+    let mut unique: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !unique.iter().any(|t| t == tag) {
+            unique.push(tag.to_string());
+        }
+    }
+    let tags = unique;
+    let json = serde_json::to_string(&tags).map_err(|e| format!("序列化标签失败: {}", e))?;
+    with_history_db(move |conn| update_entry(conn, id, "tags", &json)).await
+}
+
+/// 删除一条提示词历史
+#[tauri::command]
+pub async fn delete_prompt_history(id: i64) -> Result<(), String> {
+    with_history_db(move |conn| {
+        conn.execute("DELETE FROM prompt_history WHERE id = ?1", params![id])
+            .map(|_| ())
+            .map_err(|e| format!("删除提示词历史失败: {}", e))
+    })
+    .await
+}
+
+/// 在原项目中用原引擎重新执行（新会话，其余选项使用项目默认值）
+#[tauri::command]
+pub async fn rerun_prompt(id: i64, app_handle: AppHandle) -> Result<(), String> {
+    let entry = with_history_db(move |conn| get_entry(conn, id)).await?;
+    log::info!("[PromptHistory] Re-running prompt {} with {} in {}", id, entry.engine, entry.project_path);
+
+    super::prompt_library::execute_in_new_session(
+        app_handle,
+        &entry.engine,
+        entry.project_path,
+        entry.prompt,
+        None,
+    )
+    .await
+}
+
+/// 最近一次发送的提示词（可限定项目），供全局快捷键“重跑上一条”使用
+pub async fn last_prompt(project_path: Option<String>) -> Result<Option<PromptHistoryEntry>, String> {
+    let query = HistoryQuery {
+        project_path,
+        limit: 1,
+        ..Default::default()
+    };
+    with_history_db(move |conn| Ok(query_history(conn, &query)?.into_iter().next())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_prompts_are_deduplicated_per_engine_and_project() {
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        upsert_prompt(&conn, "codex", "/p", "fix the tests").unwrap();
+        upsert_prompt(&conn, "codex", "/p", "fix the tests").unwrap();
+        upsert_prompt(&conn, "claude", "/p", "fix the tests").unwrap();
+        upsert_prompt(&conn, "codex", "/q", "add 100% coverage").unwrap();
+
+        let all = query_history(&conn, &HistoryQuery { limit: 10, ..Default::default() }).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let codex_p = query_history(
+            &conn,
+            &HistoryQuery {
+                engine: Some("codex".into()),
+                project_path: Some("/p".into()),
+                limit: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(codex_p.len(), 1);
+        assert_eq!(codex_p[0].use_count, 2);
+
+        let id = codex_p[0].id;
+        update_entry(&conn, id, "favorite", &true).unwrap();
+        update_entry(&conn, id, "tags", &r#"["tests"]"#).unwrap();
+        let favorites = query_history(
+            &conn,
+            &HistoryQuery { favorites_only: true, tag: Some("tests".into()), limit: 10, ..Default::default() },
+        )
+        .unwrap();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].tags, vec!["tests"]);
+
+        // % 按字面匹配
+        let literal = query_history(&conn, &HistoryQuery { text: Some("100%".into()), limit: 10, ..Default::default() })
+            .unwrap();
+        assert_eq!(literal.len(), 1);
+        assert!(update_entry(&conn, 9999, "favorite", &true).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! 提示词库
+//!
+//! 在 Codex 提示词模板（`~/.codex/prompts/*.md`）的基础上，支持执行时直接使用模板：
+//! - YAML frontmatter 描述名称、适用引擎（`engines`）和标签（`tags`）
+//! - 正文中的 `{{变量}}` 在执行前渲染；`{{input}}` 为用户本次输入
+//! - `execute_codex` / `execute_claude_code` / `execute_gemini` 可直接传入模板
+//!
+//! ```markdown
+//! ---
+//! name: Code Review
+//! engines: [claude, codex]
+//! tags: [review]
+//! ---
+//! 请审查 {{path}} 中的改动，重点关注 {{focus}}。
+//! ```
+
+use crate::tr;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+/// 保留变量：用户本次输入的提示词
+const INPUT_VARIABLE: &str = "input";
+
+static VARIABLE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.-]*)\s*\}\}").unwrap());
+
+/// 模板 frontmatter
+#[derive(Debug, Clone, Default, Deserialize)]
+struct TemplateMeta {
+    name: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    engines: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// 提示词库中的模板
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptLibraryEntry {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// 适用引擎（为空表示全部适用）
+    pub engines: Vec<String>,
+    pub tags: Vec<String>,
+    /// 正文中出现的变量（不含 `input`）
+    pub variables: Vec<String>,
+}
+
+/// 执行时引用的模板
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplateRef {
+    pub id: String,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+/// 拆分 frontmatter 与正文
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let body = &rest[offset + line.len()..];
+            return (Some(&rest[..offset]), body);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// 正文中的变量（按出现顺序去重）
+pub fn extract_variables(body: &str) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    for caps in VARIABLE_RE.captures_iter(body) {
+        let name = caps[1].to_string();
+        if name != INPUT_VARIABLE && !variables.contains(&name) {
+            variables.push(name);
+        }
+    }
+    variables
+}
+
+/// 渲染模板；缺少的变量会一并报错
+pub fn render_template(body: &str, variables: &HashMap<String, String>) -> Result<String, String> {
+    let missing: Vec<String> = extract_variables(body)
+        .into_iter()
+        .filter(|name| !variables.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("缺少模板变量: {}", missing.join(", ")));
+    }
+
+    Ok(VARIABLE_RE
+        .replace_all(body, |caps: &regex::Captures| {
+            variables.get(&caps[1]).cloned().unwrap_or_default()
+        })
+        .trim()
+        .to_string())
+}
+
+/// 渲染模板并放入输入：模板含 `{{input}}` 时替换，否则把输入追加在模板之后
+pub fn render_with_input(
+    body: &str,
+    variables: &HashMap<String, String>,
+    input: &str,
+) -> Result<String, String> {
+    let has_input = VARIABLE_RE.captures_iter(body).any(|caps| &caps[1] == INPUT_VARIABLE);
+    let mut variables = variables.clone();
+    variables.insert(INPUT_VARIABLE.to_string(), input.to_string());
+
+    let rendered = render_template(body, &variables)?;
+    if has_input || input.trim().is_empty() {
+        Ok(rendered)
+    } else {
+        Ok(format!("{}\n\n{}", rendered, input))
+    }
+}
+
+struct LoadedTemplate {
+    entry: PromptLibraryEntry,
+    body: String,
+}
+
+fn parse_template(id: &str, content: &str) -> LoadedTemplate {
+    let (frontmatter, body) = split_frontmatter(content);
+    let meta: TemplateMeta = frontmatter
+        .and_then(|yaml| {
+            serde_yaml::from_str(yaml)
+                .map_err(|e| log::warn!("[PromptLibrary] Invalid frontmatter in {}: {}", id, e))
+                .ok()
+        })
+        .unwrap_or_default();
+
+    // 没有 frontmatter 描述时沿用旧规则：首行 Markdown 标题
+    let description = meta.description.or_else(|| {
+        body.lines()
+            .next()
+            .filter(|line| line.starts_with('#'))
+            .map(|line| line.trim_start_matches('#').trim().to_string())
+    });
+
+    LoadedTemplate {
+        entry: PromptLibraryEntry {
+            id: id.to_string(),
+            name: meta.name.unwrap_or_else(|| id.to_string()),
+            description,
+            engines: meta.engines,
+            tags: meta.tags,
+            variables: extract_variables(body),
+        },
+        body: body.to_string(),
+    }
+}
+
+async fn load_template(template_id: &str) -> Result<LoadedTemplate, String> {
+    let content = super::claude::get_codex_prompt(template_id.to_string()).await?;
+    Ok(parse_template(template_id, &content))
+}
+
+/// 模板正文（去掉 frontmatter，用作系统提示词等）
+pub async fn load_template_body(template_id: &str) -> Result<String, String> {
+    Ok(load_template(template_id).await?.body.trim().to_string())
+}
+
+/// 把模板应用到本次输入
+pub async fn apply_prompt_template(
+    engine: &str,
+    prompt: String,
+    template: Option<&PromptTemplateRef>,
+) -> Result<String, String> {
+    let Some(template) = template else {
+        return Ok(prompt);
+    };
+    let loaded = load_template(&template.id).await?;
+    let engines = &loaded.entry.engines;
+    if !engines.is_empty() && !engines.iter().any(|e| e == engine) {
+        return Err(format!("提示词模板 '{}' 不适用于 {}", template.id, engine));
+    }
+
+    let rendered = render_with_input(&loaded.body, &template.variables, &prompt)?;
+    log::info!("[PromptLibrary] Applied template '{}' for {}", template.id, engine);
+    Ok(rendered)
+}
+
+/// 以新会话在指定引擎上执行提示词（其余选项使用项目默认值）
+///
+/// 供重新执行历史提示词、自定义命令等入口复用。
+pub async fn execute_in_new_session(
+    app_handle: AppHandle,
+    engine: &str,
+    project_path: String,
+    prompt: String,
+    model: Option<String>,
+) -> Result<(), String> {
+    match engine {
+        "claude" => {
+            super::claude::execute_claude_code(
+                app_handle,
+                project_path,
+                prompt,
+                model,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .map_err(String::from)
+        }
+        "codex" => {
+            let options = super::codex::CodexExecutionOptions {
+                model,
+                ..super::codex::CodexExecutionOptions::new(project_path, prompt)
+            };
+            super::codex::execute_codex(options, app_handle).await.map_err(String::from)
+        }
+        "gemini" => {
+            let options = super::gemini::types::GeminiExecutionOptions {
+                project_path,
+                prompt,
+                model,
+                ..Default::default()
+            };
+            super::gemini::execute_gemini(options, app_handle).await.map_err(String::from)
+        }
+        other => Err(tr!("engine.unsupported", engine = other)),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出提示词库（可按引擎过滤）
+#[tauri::command]
+pub async fn list_prompt_library(engine: Option<String>) -> Result<Vec<PromptLibraryEntry>, String> {
+    let mut entries = Vec::new();
+    for template in super::claude::list_codex_prompts().await? {
+        let loaded = load_template(&template.id).await?;
+        let applicable = match &engine {
+            Some(engine) => loaded.entry.engines.is_empty() || loaded.entry.engines.contains(engine),
+            None => true,
+        };
+        if applicable {
+            entries.push(loaded.entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// 渲染模板（预览或由前端自行发送）
+#[tauri::command]
+pub async fn render_prompt(
+    template_id: String,
+    vars: HashMap<String, String>,
+) -> Result<String, String> {
+    render_template(&load_template(&template_id).await?.body, &vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_frontmatter_and_renders_variables() {
+        let content = "---\nname: Review\nengines: [codex]\ntags: [review]\n---\nReview {{ path }} for {{focus}}.\n{{input}}\n";
+        let loaded = parse_template("review", content);
+        assert_eq!(loaded.entry.name, "Review");
+        assert_eq!(loaded.entry.engines, vec!["codex"]);
+        assert_eq!(loaded.entry.variables, vec!["path", "focus"]);
+
+        let mut vars = HashMap::new();
+        vars.insert("path".to_string(), "src/lib.rs".to_string());
+        assert_eq!(
+            render_template(&loaded.body, &vars).unwrap_err(),
+            "缺少模板变量: focus"
+        );
+
+        vars.insert("focus".to_string(), "panics".to_string());
+        vars.insert("input".to_string(), "thanks".to_string());
+        assert_eq!(
+            render_template(&loaded.body, &vars).unwrap(),
+            "Review src/lib.rs for panics.\nthanks"
+        );
+    }
+
+    #[test]
+    fn content_without_frontmatter_is_all_body() {
+        let (meta, body) = split_frontmatter("# Title\n---\ntext");
+        assert!(meta.is_none());
+        assert_eq!(body, "# Title\n---\ntext");
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use chrono::Utc;
 use log;
@@ -22,6 +22,16 @@ pub enum RewindMode {
     Both,
 }
 
+/// How code is restored when rewinding a prompt
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RewindStrategy {
+    /// Reset to the commit recorded before the prompt
+    Git,
+    /// Restore the file snapshot taken before the prompt (projects without git)
+    Snapshot,
+}
+
 /// Capabilities for rewinding a specific prompt
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -36,6 +46,13 @@ pub struct RewindCapabilities {
     pub warning: Option<String>,
     /// Prompt source indicator
     pub source: String,  // "project" or "cli"
+    /// Code changes from this prompt onwards are mixed with manual edits,
+    /// so a commit-based revert would also discard those edits
+    #[serde(default)]
+    pub mixed: bool,
+    /// Strategy used to revert code (None when code can't be reverted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<RewindStrategy>,
 }
 
 /// A record of a user prompt (legacy structure, kept for compatibility)
@@ -89,16 +106,25 @@ pub fn load_execution_config() -> Result<ClaudeExecutionConfig> {
 /// Get path to git records file
 fn get_git_records_path(session_id: &str, project_id: &str) -> Result<PathBuf> {
     let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
-    let records_path = claude_dir
+    Ok(git_records_path_in(&claude_dir, session_id, project_id))
+}
+
+fn git_records_path_in(claude_dir: &Path, session_id: &str, project_id: &str) -> PathBuf {
+    claude_dir
         .join("projects")
         .join(project_id)
         .join("sessions")
-        .join(format!("{}.git-records.json", session_id));
-    Ok(records_path)
+        .join(format!("{}.git-records.json", session_id))
 }
+
 /// Load git records from .git-records.json (using prompt_index as key)
-fn load_git_records(session_id: &str, project_id: &str) -> Result<HashMap<usize, GitRecord>> {
-    let records_path = get_git_records_path(session_id, project_id)?;
+pub(crate) fn load_git_records(session_id: &str, project_id: &str) -> Result<HashMap<usize, GitRecord>> {
+    let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
+    load_git_records_in(&claude_dir, session_id, project_id)
+}
+
+fn load_git_records_in(claude_dir: &Path, session_id: &str, project_id: &str) -> Result<HashMap<usize, GitRecord>> {
+    let records_path = git_records_path_in(claude_dir, session_id, project_id);
 
     if !records_path.exists() {
         return Ok(HashMap::new());
@@ -125,7 +151,17 @@ fn load_git_records(session_id: &str, project_id: &str) -> Result<HashMap<usize,
 
 /// Save git records to .git-records.json (using prompt_index as key)
 fn save_git_records(session_id: &str, project_id: &str, records: &HashMap<usize, GitRecord>) -> Result<()> {
-    let records_path = get_git_records_path(session_id, project_id)?;
+    let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
+    save_git_records_in(&claude_dir, session_id, project_id, records)
+}
+
+fn save_git_records_in(
+    claude_dir: &Path,
+    session_id: &str,
+    project_id: &str,
+    records: &HashMap<usize, GitRecord>,
+) -> Result<()> {
+    let records_path = git_records_path_in(claude_dir, session_id, project_id);
 
     // Ensure directory exists
     if let Some(parent) = records_path.parent() {
@@ -151,6 +187,15 @@ fn save_git_record(session_id: &str, project_id: &str, prompt_index: usize, reco
     Ok(())
 }
 
+/// Remove all git records of a session (e.g. after its history was rewritten)
+pub fn clear_git_records(session_id: &str, project_id: &str) -> Result<()> {
+    let records_path = get_git_records_path(session_id, project_id)?;
+    if records_path.exists() {
+        fs::remove_file(&records_path).context("Failed to remove git records file")?;
+    }
+    Ok(())
+}
+
 /// Get a git record by prompt_index
 fn get_git_record(session_id: &str, project_id: &str, prompt_index: usize) -> Result<Option<GitRecord>> {
     let records = load_git_records(session_id, project_id)?;
@@ -186,6 +231,15 @@ fn truncate_session_to_prompt(
     prompt_index: usize,
 ) -> Result<()> {
     let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
+    truncate_session_to_prompt_in(&claude_dir, session_id, project_id, prompt_index)
+}
+
+fn truncate_session_to_prompt_in(
+    claude_dir: &Path,
+    session_id: &str,
+    project_id: &str,
+    prompt_index: usize,
+) -> Result<()> {
     let project_dir = claude_dir.join("projects").join(project_id);
     let session_path = project_dir.join(format!("{}.jsonl", session_id));
     
@@ -419,6 +473,50 @@ fn truncate_session_to_prompt(
     Ok(())
 }
 
+/// Fork a session: copy its history before `prompt_index` into a new session file
+/// Git records up to the fork point are copied as well, so the fork can be rewound independently.
+/// Returns the new session ID.
+pub fn fork_claude_session(session_id: &str, project_id: &str, prompt_index: usize) -> Result<String> {
+    let claude_dir = get_claude_dir().context("Failed to get claude dir")?;
+    fork_claude_session_in(&claude_dir, session_id, project_id, prompt_index)
+}
+
+fn fork_claude_session_in(claude_dir: &Path, session_id: &str, project_id: &str, prompt_index: usize) -> Result<String> {
+    let project_dir = claude_dir.join("projects").join(project_id);
+    let session_path = project_dir.join(format!("{}.jsonl", session_id));
+    let content = fs::read_to_string(&session_path)
+        .with_context(|| format!("Session file not found: {}", session_id))?;
+
+    let new_session_id = uuid::Uuid::new_v4().to_string();
+    let new_content: String = content
+        .lines()
+        .map(|line| match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(mut msg) if msg.get("sessionId").is_some() => {
+                msg["sessionId"] = serde_json::Value::String(new_session_id.clone());
+                msg.to_string()
+            }
+            _ => line.to_string(),
+        })
+        .map(|line| line + "\n")
+        .collect();
+
+    let new_path = project_dir.join(format!("{}.jsonl", new_session_id));
+    fs::write(&new_path, new_content).context("Failed to write forked session")?;
+    if let Err(e) = truncate_session_to_prompt_in(claude_dir, &new_session_id, project_id, prompt_index) {
+        let _ = fs::remove_file(&new_path);
+        return Err(e);
+    }
+
+    let mut records = load_git_records_in(claude_dir, session_id, project_id)?;
+    records.retain(|index, _| *index <= prompt_index);
+    if !records.is_empty() {
+        save_git_records_in(claude_dir, &new_session_id, project_id, &records)?;
+    }
+
+    log::info!("[Fork] Forked Claude session {} at prompt #{} -> {}", session_id, prompt_index, new_session_id);
+    Ok(new_session_id)
+}
+
 /// Record a prompt being sent
 #[tauri::command]
 pub async fn record_prompt_sent(
@@ -484,6 +582,7 @@ pub async fn record_prompt_sent(
 /// Mark a prompt as completed (after AI finishes)
 #[tauri::command]
 pub async fn mark_prompt_completed(
+    app_handle: tauri::AppHandle,
     session_id: String,
     project_id: String,
     project_path: String,
@@ -502,7 +601,8 @@ pub async fn mark_prompt_completed(
 
     // Auto-commit any changes made by AI
     // This ensures each prompt has a distinct git state
-    let commit_message = format!("[Claude Code] After prompt #{}", prompt_index);
+    let commit_message =
+        super::commit_message::auto_commit_message(&app_handle, &project_path, "claude", prompt_index).await;
     match simple_git::git_commit_changes(&project_path, &commit_message) {
         Ok(true) => {
             log::info!("Auto-committed changes after prompt #{}", prompt_index);
@@ -708,6 +808,8 @@ pub async fn check_rewind_capabilities(
             both: false,
             warning: Some("Git 操作已在配置中禁用。只能撤回对话历史，无法回滚代码变更。".to_string()),
             source: prompt.source.clone(),
+            mixed: false,
+            strategy: None,
         });
     }
 
@@ -733,6 +835,8 @@ pub async fn check_rewind_capabilities(
                     None
                 },
                 source: "project".to_string(),
+                mixed: false,
+                strategy: has_valid_commit.then_some(RewindStrategy::Git),
             })
         } else {
             // Project prompt but no git record (edge case: record_prompt_sent might have failed)
@@ -743,6 +847,8 @@ pub async fn check_rewind_capabilities(
                 both: false,
                 warning: Some("此提示词来自项目界面，但没有找到 Git 记录，只能删除消息".to_string()),
                 source: "project".to_string(),
+                mixed: false,
+                strategy: None,
             })
         }
     } else {
@@ -754,6 +860,8 @@ pub async fn check_rewind_capabilities(
             both: false,
             warning: Some("此提示词来自 CLI 终端，只能删除消息，无法回滚代码".to_string()),
             source: "cli".to_string(),
+            mixed: false,
+            strategy: None,
         })
     }
 }
@@ -964,3 +1072,67 @@ pub async fn get_unified_prompt_list(
     Ok(prompts)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT_ID: &str = "-home-dev-app";
+
+    fn message(kind: &str, session_id: &str, text: &str) -> String {
+        serde_json::json!({
+            "type": kind,
+            "sessionId": session_id,
+            "message": {"role": kind, "content": text}
+        })
+        .to_string()
+    }
+
+    fn git_record(commit: &str) -> GitRecord {
+        GitRecord { commit_before: commit.to_string(), commit_after: None, timestamp: 0 }
+    }
+
+    #[test]
+    fn fork_keeps_history_and_records_up_to_the_fork_point() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude_dir = dir.path();
+        let project_dir = claude_dir.join("projects").join(PROJECT_ID);
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let source: String = [
+            message("user", "source", "first"),
+            message("assistant", "source", "one"),
+            message("user", "source", "second"),
+            message("assistant", "source", "two"),
+            message("user", "source", "third"),
+            message("assistant", "source", "three"),
+        ]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect();
+        let source_path = project_dir.join("source.jsonl");
+        fs::write(&source_path, &source).unwrap();
+        let records: HashMap<usize, GitRecord> =
+            (0..3).map(|i| (i, git_record(&format!("commit{}", i)))).collect();
+        save_git_records_in(claude_dir, "source", PROJECT_ID, &records).unwrap();
+
+        let forked = fork_claude_session_in(claude_dir, "source", PROJECT_ID, 1).unwrap();
+
+        // 分叉点的提示词本身不在新会话中
+        let content = fs::read_to_string(project_dir.join(format!("{}.jsonl", forked))).unwrap();
+        let messages: Vec<serde_json::Value> =
+            content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let texts: Vec<&str> = messages.iter().map(|m| m["message"]["content"].as_str().unwrap()).collect();
+        assert_eq!(texts, vec!["first", "one"]);
+        assert!(messages.iter().all(|m| m["sessionId"] == forked.as_str()));
+
+        let mut forked_indices: Vec<usize> =
+            load_git_records_in(claude_dir, &forked, PROJECT_ID).unwrap().into_keys().collect();
+        forked_indices.sort_unstable();
+        assert_eq!(forked_indices, vec![0, 1]);
+
+        // 原会话不受影响
+        assert_eq!(fs::read_to_string(&source_path).unwrap(), source);
+        assert_eq!(load_git_records_in(claude_dir, "source", PROJECT_ID).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,417 @@
+//! 提示词双向翻译
+//!
+//! 开启后，用户用中文（`user_lang`）写的提示词在发送给引擎前翻译成英文（`engine_lang`），
+//! 引擎的回复再翻译回中文。会话文件里只有发给引擎的英文，两种语言的原文都记录在
+//! agents.db 的 `prompt_translations` 表中，并通过 `prompt-translation:{session_id}`
+//! 事件推送给前端。
+//!
+//! 各引擎的执行入口调用 `prepare_prompt`；stdout 读取任务中的 `ResponseTranslator`
+//! 逐行提取助手回复：
+//! - Claude: `assistant` 消息中的 text 块
+//! - Codex: `item.completed`（`agent_message`）
+//! - Gemini: `message`（role = assistant，增量输出）累积到下一个非消息事件
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use super::command_audit::extract_session_id;
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+use super::translator;
+
+/// 翻译方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationDirection {
+    #[default]
+    Both,
+    PromptOnly,
+    ResponseOnly,
+}
+
+/// 双向翻译设置（保存在翻译配置的 `pipeline` 字段中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptTranslationSettings {
+    pub enabled: bool,
+    pub direction: TranslationDirection,
+    /// 用户使用的语言
+    pub user_lang: String,
+    /// 发送给引擎的语言
+    pub engine_lang: String,
+    /// 短于该长度（字符）的提示词不翻译
+    pub min_prompt_chars: usize,
+    /// 长于该长度的提示词原样发送（通常是粘贴的大段代码或日志）
+    pub max_prompt_chars: usize,
+    /// 长于该长度的回复不自动翻译
+    pub max_response_chars: usize,
+}
+
+impl Default for PromptTranslationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            direction: TranslationDirection::Both,
+            user_lang: "zh".to_string(),
+            engine_lang: "en".to_string(),
+            min_prompt_chars: 2,
+            max_prompt_chars: 8000,
+            max_response_chars: 6000,
+        }
+    }
+}
+
+impl PromptTranslationSettings {
+    fn translates_prompt(&self) -> bool {
+        self.direction != TranslationDirection::ResponseOnly
+    }
+
+    fn translates_response(&self) -> bool {
+        self.direction != TranslationDirection::PromptOnly
+    }
+}
+
+/// 一条翻译记录（提示词或助手回复）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationRecord {
+    pub engine: String,
+    /// 提示词在会话 ID 确定前为空
+    pub session_id: String,
+    /// "prompt" | "response"
+    pub kind: String,
+    pub original_text: String,
+    pub translated_text: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub created_at: String,
+}
+
+/// 创建翻译记录表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS prompt_translations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            engine TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            original_text TEXT NOT NULL,
+            translated_text TEXT NOT NULL,
+            source_lang TEXT NOT NULL,
+            target_lang TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_prompt_translations_session
+            ON prompt_translations(engine, session_id, created_at);",
+    )
+    .map_err(|e| format!("创建翻译记录表失败: {}", e))
+}
+
+fn open_translation_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+fn insert_record(conn: &Connection, record: &TranslationRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO prompt_translations
+            (engine, session_id, kind, original_text, translated_text, source_lang, target_lang, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            record.engine,
+            record.session_id,
+            record.kind,
+            record.original_text,
+            record.translated_text,
+            record.source_lang,
+            record.target_lang,
+            record.created_at
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("保存翻译记录失败: {}", e))
+}
+
+fn load_records(conn: &Connection, engine: &str, session_id: &str) -> Result<Vec<TranslationRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT engine, session_id, kind, original_text, translated_text, source_lang, target_lang, created_at
+             FROM prompt_translations WHERE engine = ?1 AND session_id = ?2
+             ORDER BY created_at, id",
+        )
+        .map_err(|e| format!("查询翻译记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![engine, session_id], |row| {
+            Ok(TranslationRecord {
+                engine: row.get(0)?,
+                session_id: row.get(1)?,
+                kind: row.get(2)?,
+                original_text: row.get(3)?,
+                translated_text: row.get(4)?,
+                source_lang: row.get(5)?,
+                target_lang: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })
+        .map_err(|e| format!("查询翻译记录失败: {}", e))?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// 推送给前端并在后台保存记录（不阻塞 stdout 读取）；保存失败只记录日志
+fn publish(app: &AppHandle, record: &TranslationRecord) {
+    let stored = record.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = open_translation_db().and_then(|conn| insert_record(&conn, &stored)) {
+            log::warn!("[PromptTranslation] {}", e);
+        }
+    });
+    let _ = app.emit(&format!("prompt-translation:{}", record.session_id), record);
+    let _ = app.emit("prompt-translation", record);
+}
+
+/// 发送前翻译提示词
+///
+/// 返回发送给引擎的提示词，以及需要在会话 ID 确定后保存的翻译记录（未翻译时为空）。
+/// 未开启双向翻译时直接返回；斜杠命令、过短或过长的提示词、已经是引擎语言的提示词原样发送。
+pub async fn prepare_prompt(engine: &str, prompt: String) -> (String, Option<TranslationRecord>) {
+    let Some(settings) = translator::pipeline_settings().filter(|s| s.translates_prompt()) else {
+        return (prompt, None);
+    };
+    let trimmed = prompt.trim();
+    let length = trimmed.chars().count();
+    if trimmed.starts_with('/') || length < settings.min_prompt_chars || length > settings.max_prompt_chars {
+        return (prompt, None);
+    }
+    if translator::detect_language(&prompt).await != settings.user_lang {
+        return (prompt, None);
+    }
+
+    match translator::translate_text(&prompt, Some(&settings.engine_lang)).await {
+        // 翻译失败时 translate_text 返回原文
+        Ok(translated) if translated.trim() != trimmed => {
+            log::info!("[PromptTranslation] Translated {} prompt ({} chars)", engine, length);
+            let record = TranslationRecord {
+                engine: engine.to_string(),
+                session_id: String::new(),
+                kind: "prompt".to_string(),
+                original_text: prompt,
+                translated_text: translated.clone(),
+                source_lang: settings.user_lang,
+                target_lang: settings.engine_lang,
+                created_at: Utc::now().to_rfc3339(),
+            };
+            (translated, Some(record))
+        }
+        _ => (prompt, None),
+    }
+}
+
+/// Claude 消息内容中的文本块
+fn claude_text(content: &Value) -> String {
+    content
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|b| b["type"] == "text")
+                .filter_map(|b| b["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// 从流事件中提取完整的助手回复（Gemini 的增量消息先累积到 `buffer`）
+fn assistant_texts(engine: &str, event: &Value, buffer: &mut String) -> Vec<String> {
+    let text = match engine {
+        "claude" if event["type"] == "assistant" => claude_text(&event["message"]["content"]),
+        "codex" if event["type"] == "item.completed" && event["item"]["type"] == "agent_message" => {
+            event["item"]["text"].as_str().unwrap_or_default().to_string()
+        }
+        "gemini" if event["type"] == "message" => {
+            if event["role"] != "assistant" {
+                return Vec::new();
+            }
+            let content = event["content"].as_str().unwrap_or_default();
+            if event["delta"].as_bool().unwrap_or(false) {
+                buffer.push_str(content);
+                return Vec::new();
+            }
+            content.to_string()
+        }
+        // 其他事件（工具调用、结果）结束当前的增量回复
+        "gemini" => std::mem::take(buffer),
+        _ => String::new(),
+    };
+    if text.trim().is_empty() {
+        Vec::new()
+    } else {
+        vec![text]
+    }
+}
+
+struct TranslatorState {
+    session_id: String,
+    /// 等待会话 ID 确定后保存的提示词翻译
+    pending_prompt: Option<TranslationRecord>,
+    /// Gemini 增量回复
+    buffer: String,
+}
+
+/// 单个进程的回复翻译器（在 stdout 读取任务中逐行调用）
+pub struct ResponseTranslator {
+    app: AppHandle,
+    engine: String,
+    /// 不翻译回复时为空
+    settings: Option<PromptTranslationSettings>,
+    state: Mutex<TranslatorState>,
+}
+
+impl ResponseTranslator {
+    /// `session_id` 为执行时已知的 ID，收到 CLI 的 init 事件后替换为真实会话 ID
+    pub async fn new(
+        app: &AppHandle,
+        engine: &str,
+        session_id: &str,
+        prompt: Option<TranslationRecord>,
+    ) -> Arc<Self> {
+        let settings = translator::pipeline_settings().filter(|s| s.translates_response());
+        Arc::new(Self {
+            app: app.clone(),
+            engine: engine.to_string(),
+            settings,
+            state: Mutex::new(TranslatorState {
+                session_id: session_id.to_string(),
+                pending_prompt: prompt,
+                buffer: String::new(),
+            }),
+        })
+    }
+
+    /// 处理一行原始流输出；翻译在后台任务中进行，不阻塞输出
+    pub fn observe(&self, line: &str) {
+        let mut state = self.state.lock().unwrap();
+        if self.settings.is_none() && state.pending_prompt.is_none() {
+            return;
+        }
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+
+        if let Some(session_id) = extract_session_id(&self.engine, &event) {
+            state.session_id = session_id;
+        }
+        if !state.session_id.is_empty() {
+            if let Some(mut record) = state.pending_prompt.take() {
+                record.session_id = state.session_id.clone();
+                publish(&self.app, &record);
+            }
+        }
+
+        let Some(settings) = &self.settings else {
+            return;
+        };
+        for text in assistant_texts(&self.engine, &event, &mut state.buffer) {
+            if text.chars().count() > settings.max_response_chars {
+                continue;
+            }
+            let app = self.app.clone();
+            let engine = self.engine.clone();
+            let session_id = state.session_id.clone();
+            let settings = settings.clone();
+            let created_at = Utc::now().to_rfc3339();
+            tokio::spawn(async move {
+                // 引擎直接用用户的语言回复时不需要翻译
+                if translator::detect_language(&text).await == settings.user_lang {
+                    return;
+                }
+                if let Ok(translated) = translator::translate_text(&text, Some(&settings.user_lang)).await {
+                    if translated.trim() != text.trim() {
+                        let record = TranslationRecord {
+                            engine,
+                            session_id,
+                            kind: "response".to_string(),
+                            original_text: text,
+                            translated_text: translated,
+                            source_lang: settings.engine_lang,
+                            target_lang: settings.user_lang,
+                            created_at,
+                        };
+                        publish(&app, &record);
+                    }
+                }
+            });
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 会话的翻译记录（提示词原文和回复译文，按时间排序）
+#[tauri::command]
+pub async fn get_prompt_translations(engine: String, session_id: String) -> Result<Vec<TranslationRecord>, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = open_translation_db()?;
+        load_records(&conn, &engine, &session_id)
+    })
+    .await
+    .map_err(|e| format!("读取翻译记录失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn extracts_assistant_replies_and_stores_records() {
+        let mut buffer = String::new();
+        let claude = json!({
+            "type": "assistant",
+            "message": {"content": [
+                {"type": "text", "text": "Done."},
+                {"type": "tool_use", "name": "Bash", "input": {}},
+                {"type": "text", "text": "Next step."}
+            ]}
+        });
+        assert_eq!(assistant_texts("claude", &claude, &mut buffer), vec!["Done.\nNext step."]);
+
+        let codex = json!({"type": "item.completed", "item": {"type": "agent_message", "text": "Fixed"}});
+        assert_eq!(assistant_texts("codex", &codex, &mut buffer), vec!["Fixed"]);
+        let reasoning = json!({"type": "item.completed", "item": {"type": "reasoning", "text": "hmm"}});
+        assert!(assistant_texts("codex", &reasoning, &mut buffer).is_empty());
+
+        for chunk in ["Hel", "lo"] {
+            let delta = json!({"type": "message", "role": "assistant", "content": chunk, "delta": true});
+            assert!(assistant_texts("gemini", &delta, &mut buffer).is_empty());
+        }
+        let user = json!({"type": "message", "role": "user", "content": "hi"});
+        assert!(assistant_texts("gemini", &user, &mut buffer).is_empty());
+        let result = json!({"type": "result", "status": "success"});
+        assert_eq!(assistant_texts("gemini", &result, &mut buffer), vec!["Hello"]);
+        assert!(buffer.is_empty());
+
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        let record = TranslationRecord {
+            engine: "codex".to_string(),
+            session_id: "s1".to_string(),
+            kind: "prompt".to_string(),
+            original_text: "修复登录".to_string(),
+            translated_text: "Fix login".to_string(),
+            source_lang: "zh".to_string(),
+            target_lang: "en".to_string(),
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+        };
+        insert_record(&conn, &record).unwrap();
+        assert_eq!(load_records(&conn, "codex", "s1").unwrap(), vec![record]);
+        assert!(load_records(&conn, "claude", "s1").unwrap().is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::tr;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
@@ -34,7 +35,7 @@ pub struct CurrentConfig {
 
 // 获取Claude设置文件路径
 fn get_settings_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?;
+    let home_dir = dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?;
 
     let config_dir = home_dir.join(".claude");
 
@@ -48,7 +49,7 @@ fn get_settings_path() -> Result<PathBuf, String> {
 
 // 获取遗留的providers.json路径（用于迁移）
 fn get_legacy_providers_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?;
+    let home_dir = dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     Ok(home_dir.join(".claude").join("providers.json"))
 }
 
@@ -112,6 +113,17 @@ fn load_legacy_providers() -> Result<Vec<ProviderConfig>, String> {
     Ok(providers)
 }
 
+/// 一次性写入全部预设（临时文件 + 重命名，供预设包导入使用）
+pub(crate) fn save_provider_presets(providers: &[ProviderConfig]) -> Result<(), String> {
+    let legacy_path = get_legacy_providers_path()?;
+    if let Some(parent) = legacy_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    let content =
+        serde_json::to_string_pretty(providers).map_err(|e| format!("序列化配置失败: {}", e))?;
+    super::file_operations::write_atomic(&legacy_path, content).map_err(|e| format!("写入配置文件失败: {}", e))
+}
+
 // CRUD 操作 - 获取所有代理商预设（从遗留文件读取）
 #[command]
 pub fn get_provider_presets() -> Result<Vec<ProviderConfig>, String> {
@@ -257,6 +269,7 @@ pub async fn switch_provider_config(
         config.name,
         config.description
     );
+    super::capabilities::invalidate_capability_cache();
 
     // 验证第三方API配置
     validate_third_party_config(&config)?;
@@ -393,6 +406,39 @@ pub async fn switch_provider_config(
     ))
 }
 
+/// 单次执行使用的代理商配置（不修改 settings.json）
+pub struct ProviderOverride {
+    /// 非敏感项，通过 `--settings` 传给 Claude CLI
+    pub settings: Value,
+    /// 令牌和 API 密钥，通过子进程环境变量传递，避免出现在进程命令行中
+    pub secret_env: Vec<(String, String)>,
+}
+
+/// 解析代理商预设为单次执行的配置覆盖
+pub fn provider_settings_override(id: &str) -> Result<ProviderOverride, String> {
+    let config = get_provider_config(id.to_string())?;
+    let mut env = serde_json::Map::new();
+    env.insert(
+        "ANTHROPIC_BASE_URL".to_string(),
+        Value::String(normalize_base_url(&config.base_url)),
+    );
+    let non_empty = |v: &Option<String>| v.clone().filter(|s| !s.is_empty());
+    if let Some(model) = non_empty(&config.model) {
+        env.insert("ANTHROPIC_MODEL".to_string(), Value::String(model));
+    }
+    let mut secret_env = Vec::new();
+    if let Some(token) = non_empty(&config.auth_token) {
+        secret_env.push(("ANTHROPIC_AUTH_TOKEN".to_string(), token));
+    }
+    if let Some(api_key) = non_empty(&config.api_key) {
+        secret_env.push(("ANTHROPIC_API_KEY".to_string(), api_key));
+    }
+    Ok(ProviderOverride {
+        settings: serde_json::json!({ "env": env }),
+        secret_env,
+    })
+}
+
 // 验证第三方API配置的兼容性（Claude Code 2025标准）
 fn validate_third_party_config(config: &ProviderConfig) -> Result<(), String> {
     // 检查是否为第三方API
@@ -434,6 +480,7 @@ fn validate_third_party_config(config: &ProviderConfig) -> Result<(), String> {
 #[command]
 pub async fn clear_provider_config(_app: AppHandle) -> Result<String, String> {
     log::info!("开始清理代理商配置");
+    super::capabilities::invalidate_capability_cache();
 
     let mut settings = load_settings()?;
 
@@ -0,0 +1,565 @@
+//! 代理商预设导入导出
+//!
+//! 把 Claude / Codex / Gemini 的代理商预设打包成一个 JSON 文件，方便团队共享一份
+//! 验证过的代理商列表。导出时移除密钥：
+//! - Claude：`authToken`、`apiKey`（`apiKeyHelper` 保留，它通常是从钥匙串等处读取密钥的命令，
+//!   但命令里直接写了密钥时同样移除）
+//! - Codex：auth.json 中的所有凭据，config.toml 中名称像密钥的字符串值（按 TOML 语法遍历，
+//!   含引号键、字面量字符串、多行字符串和内联表；`env_key` 只是环境变量名，保留）。
+//!   config.toml 无法解析时整段移除
+//! - Gemini：名称像密钥的环境变量
+//!
+//! 被移除的字段记录在 `redacted` 中。导入覆盖已有预设时沿用本地的密钥，其余需要用户重新填写。
+//! 导入先在内存中合并全部预设，都成功后再分别原子写入各引擎的预设文件。
+
+use crate::tr;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+use super::codex::config::{get_codex_provider_presets, save_codex_provider_presets, CodexProviderConfig};
+use super::gemini::provider::{get_gemini_provider_presets, save_gemini_provider_presets, GeminiProviderConfig};
+use super::provider::{get_provider_presets, save_provider_presets, ProviderConfig};
+
+const BUNDLE_VERSION: u32 = 1;
+const ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 代理商预设包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderPresetBundle {
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default)]
+    pub claude: Vec<ProviderConfig>,
+    #[serde(default)]
+    pub codex: Vec<CodexProviderConfig>,
+    #[serde(default)]
+    pub gemini: Vec<GeminiProviderConfig>,
+    /// 导出时移除的密钥字段
+    #[serde(default)]
+    pub redacted: Vec<RedactedSecret>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactedSecret {
+    pub engine: String,
+    pub preset_id: String,
+    /// 如 `apiKey`、`auth.OPENAI_API_KEY`、`config.experimental_bearer_token`、`env.GEMINI_API_KEY`
+    pub field: String,
+}
+
+/// 导入时 ID 冲突的处理方式
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// 保留本地预设
+    #[default]
+    Skip,
+    /// 用包中的预设替换（沿用本地密钥）
+    Overwrite,
+    /// 以新 ID 另存一份
+    Rename,
+}
+
+/// 单个预设的导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedPreset {
+    pub engine: String,
+    pub id: String,
+    pub name: String,
+    /// added | overwritten | renamed | skipped
+    pub action: String,
+    /// 重命名前的 ID
+    pub original_id: Option<String>,
+    /// 仍需用户填写的密钥字段
+    pub missing_secrets: Vec<String>,
+}
+
+// ============================================================================
+// 密钥处理
+// ============================================================================
+
+/// 直接写在命令里的密钥
+static SECRET_LITERAL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(sk|key|AIza)[-_A-Za-z0-9]{16,}").unwrap());
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    if key.ends_with("env_key") {
+        return false;
+    }
+    ["key", "token", "secret", "password", "authorization"]
+        .iter()
+        .any(|word| key.contains(word))
+}
+
+/// `visit(键, 路径, 值)`：路径形如 `model_providers.relay.http_headers.Authorization`，数组元素带 `[i]`
+type TomlStringVisitor<'a> = dyn FnMut(&str, &str, &mut toml_edit::Value) + 'a;
+
+/// 遍历 TOML 表中的所有字符串值（普通表、内联表、表数组和数组）
+fn walk_toml_table(table: &mut dyn TableLike, prefix: &str, visit: &mut TomlStringVisitor) {
+    for (key, item) in table.iter_mut() {
+        let key = key.get().to_string();
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match item {
+            Item::Value(value) => walk_toml_value(value, &key, &path, visit),
+            Item::Table(table) => walk_toml_table(table, &path, visit),
+            Item::ArrayOfTables(tables) => {
+                for (i, table) in tables.iter_mut().enumerate() {
+                    walk_toml_table(table, &format!("{}[{}]", path, i), visit);
+                }
+            }
+            Item::None => {}
+        }
+    }
+}
+
+fn walk_toml_value(value: &mut toml_edit::Value, key: &str, path: &str, visit: &mut TomlStringVisitor) {
+    match value {
+        toml_edit::Value::String(_) => visit(key, path, value),
+        toml_edit::Value::InlineTable(table) => walk_toml_table(table, path, visit),
+        toml_edit::Value::Array(array) => {
+            for (i, value) in array.iter_mut().enumerate() {
+                walk_toml_value(value, key, &format!("{}[{}]", path, i), visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 替换字符串值，保留两侧的空白和注释
+fn set_toml_string(value: &mut toml_edit::Value, new: &str) {
+    let decor = value.decor().clone();
+    *value = toml_edit::Value::from(new);
+    *value.decor_mut() = decor;
+}
+
+/// config.toml 中非空的密钥值（路径 -> 值）
+fn toml_secret_values(config: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    if let Ok(mut doc) = config.parse::<DocumentMut>() {
+        walk_toml_table(doc.as_table_mut(), "", &mut |key, path, value| {
+            if let Some(s) = value.as_str().filter(|s| is_secret_key(key) && !s.is_empty()) {
+                values.insert(path.to_string(), s.to_string());
+            }
+        });
+    }
+    values
+}
+
+/// 移除预设中的密钥，返回被移除的字段
+pub(crate) fn redact_claude(preset: &mut ProviderConfig) -> Vec<String> {
+    let mut fields = Vec::new();
+    if preset.auth_token.take().is_some_and(|v| !v.is_empty()) {
+        fields.push("authToken".to_string());
+    }
+    if preset.api_key.take().is_some_and(|v| !v.is_empty()) {
+        fields.push("apiKey".to_string());
+    }
+    if preset.api_key_helper.as_deref().is_some_and(|helper| SECRET_LITERAL_RE.is_match(helper)) {
+        preset.api_key_helper = None;
+        fields.push("apiKeyHelper".to_string());
+    }
+    fields
+}
+
+pub(crate) fn redact_codex(preset: &mut CodexProviderConfig) -> Vec<String> {
+    let mut fields = Vec::new();
+    if let Some(auth) = preset.auth.as_object_mut() {
+        for (key, value) in auth.iter_mut() {
+            let is_set = match value {
+                Value::Null => false,
+                Value::String(s) => !s.is_empty(),
+                _ => true,
+            };
+            if is_set {
+                *value = if value.is_string() { Value::String(String::new()) } else { Value::Null };
+                fields.push(format!("auth.{}", key));
+            }
+        }
+    }
+    match preset.config.parse::<DocumentMut>() {
+        Ok(mut doc) => {
+            walk_toml_table(doc.as_table_mut(), "", &mut |key, path, value| {
+                if is_secret_key(key) && value.as_str().is_some_and(|s| !s.is_empty()) {
+                    set_toml_string(value, "");
+                    fields.push(format!("config.{}", path));
+                }
+            });
+            preset.config = doc.to_string();
+        }
+        Err(_) if preset.config.trim().is_empty() => {}
+        Err(e) => {
+            // 无法确认哪些值是密钥，整段移除
+            log::warn!("[Provider Bundle] Codex preset {} has an unparsable config.toml, removing it: {}", preset.id, e);
+            preset.config.clear();
+            fields.push("config".to_string());
+        }
+    }
+    fields
+}
+
+pub(crate) fn redact_gemini(preset: &mut GeminiProviderConfig) -> Vec<String> {
+    let mut fields = Vec::new();
+    for (key, value) in preset.env.iter_mut() {
+        if is_secret_key(key) && !value.is_empty() {
+            value.clear();
+            fields.push(format!("env.{}", key));
+        }
+    }
+    fields.sort();
+    fields
+}
+
+/// 用本地预设的值填回被移除的密钥
+pub(crate) fn restore_claude(preset: &mut ProviderConfig, local: &ProviderConfig, fields: &[String]) {
+    for field in fields {
+        match field.as_str() {
+            "authToken" => preset.auth_token = local.auth_token.clone(),
+            "apiKey" => preset.api_key = local.api_key.clone(),
+            "apiKeyHelper" => preset.api_key_helper = local.api_key_helper.clone(),
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn restore_codex(preset: &mut CodexProviderConfig, local: &CodexProviderConfig, fields: &[String]) {
+    let mut config_fields = Vec::new();
+    for field in fields {
+        if let Some(key) = field.strip_prefix("auth.") {
+            if let (Some(auth), Some(value)) = (preset.auth.as_object_mut(), local.auth.get(key)) {
+                auth.insert(key.to_string(), value.clone());
+            }
+        } else if let Some(path) = field.strip_prefix("config.") {
+            config_fields.push(path);
+        } else if field == "config" && preset.config.is_empty() {
+            preset.config = local.config.clone();
+        }
+    }
+    if config_fields.is_empty() {
+        return;
+    }
+    let local_config = toml_secret_values(&local.config);
+    if let Ok(mut doc) = preset.config.parse::<DocumentMut>() {
+        walk_toml_table(doc.as_table_mut(), "", &mut |_, path, value| {
+            if value.as_str() == Some("") && config_fields.contains(&path) {
+                if let Some(local_value) = local_config.get(path) {
+                    set_toml_string(value, local_value);
+                }
+            }
+        });
+        preset.config = doc.to_string();
+    }
+}
+
+pub(crate) fn restore_gemini(preset: &mut GeminiProviderConfig, local: &GeminiProviderConfig, fields: &[String]) {
+    for key in fields.iter().filter_map(|f| f.strip_prefix("env.")) {
+        if let Some(value) = local.env.get(key) {
+            preset.env.insert(key.to_string(), value.clone());
+        }
+    }
+}
+
+// ============================================================================
+// 冲突处理
+// ============================================================================
+
+enum Placement {
+    Add(String),
+    Overwrite,
+    Skip,
+}
+
+fn place(id: &str, local_ids: &[String], strategy: ConflictStrategy) -> Placement {
+    if !local_ids.iter().any(|local| local == id) {
+        return Placement::Add(id.to_string());
+    }
+    match strategy {
+        ConflictStrategy::Skip => Placement::Skip,
+        ConflictStrategy::Overwrite => Placement::Overwrite,
+        ConflictStrategy::Rename => {
+            let new_id = (2..)
+                .map(|n| format!("{}-{}", id, n))
+                .find(|candidate| !local_ids.contains(candidate))
+                .unwrap();
+            Placement::Add(new_id)
+        }
+    }
+}
+
+/// 包中该预设被移除、且导入后仍为空的密钥字段
+fn still_missing(redacted: &[String], present: &[String]) -> Vec<String> {
+    redacted.iter().filter(|f| !present.contains(f)).cloned().collect()
+}
+
+fn redacted_fields(bundle: &ProviderPresetBundle, engine: &str, id: &str) -> Vec<String> {
+    bundle
+        .redacted
+        .iter()
+        .filter(|r| r.engine == engine && r.preset_id == id)
+        .map(|r| r.field.clone())
+        .collect()
+}
+
+fn result(engine: &str, id: &str, name: &str, action: &str, original_id: Option<&str>, missing: Vec<String>) -> ImportedPreset {
+    ImportedPreset {
+        engine: engine.to_string(),
+        id: id.to_string(),
+        name: name.to_string(),
+        action: action.to_string(),
+        original_id: original_id.filter(|original| *original != id).map(|s| s.to_string()),
+        missing_secrets: missing,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 导出代理商预设（不含密钥）
+///
+/// `engines` 为空时导出全部引擎。
+#[tauri::command]
+pub async fn export_provider_presets(engines: Option<Vec<String>>) -> Result<ProviderPresetBundle, String> {
+    let engines = engines.unwrap_or_else(|| ENGINES.iter().map(|e| e.to_string()).collect());
+    if let Some(unknown) = engines.iter().find(|e| !ENGINES.contains(&e.as_str())) {
+        return Err(tr!("engine.unsupported", engine = unknown));
+    }
+    let wants = |engine: &str| engines.iter().any(|e| e == engine);
+
+    let mut bundle = ProviderPresetBundle {
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        claude: Vec::new(),
+        codex: Vec::new(),
+        gemini: Vec::new(),
+        redacted: Vec::new(),
+    };
+    let mut redacted = Vec::new();
+
+    if wants("claude") {
+        for mut preset in get_provider_presets()? {
+            redacted.extend(redact_claude(&mut preset).into_iter().map(|f| ("claude", preset.id.clone(), f)));
+            bundle.claude.push(preset);
+        }
+    }
+    if wants("codex") {
+        for mut preset in get_codex_provider_presets().await? {
+            redacted.extend(redact_codex(&mut preset).into_iter().map(|f| ("codex", preset.id.clone(), f)));
+            bundle.codex.push(preset);
+        }
+    }
+    if wants("gemini") {
+        for mut preset in get_gemini_provider_presets().await? {
+            redacted.extend(redact_gemini(&mut preset).into_iter().map(|f| ("gemini", preset.id.clone(), f)));
+            bundle.gemini.push(preset);
+        }
+    }
+
+    bundle.redacted = redacted
+        .into_iter()
+        .map(|(engine, preset_id, field)| RedactedSecret { engine: engine.to_string(), preset_id, field })
+        .collect();
+    log::info!(
+        "[Provider Bundle] Exported {} Claude, {} Codex, {} Gemini presets ({} secrets removed)",
+        bundle.claude.len(),
+        bundle.codex.len(),
+        bundle.gemini.len(),
+        bundle.redacted.len()
+    );
+    Ok(bundle)
+}
+
+/// 导入代理商预设包
+#[tauri::command]
+pub async fn import_provider_presets(
+    bundle: ProviderPresetBundle,
+    conflict_strategy: Option<ConflictStrategy>,
+) -> Result<Vec<ImportedPreset>, String> {
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "代理商预设包版本 {} 高于当前支持的版本 {}，请升级应用后再导入",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+    let strategy = conflict_strategy.unwrap_or_default();
+    let mut results = Vec::new();
+
+    // 先在内存中合并，全部成功后再写入，避免导入到一半留下部分预设
+    let mut claude = get_provider_presets()?;
+    let mut local_ids: Vec<String> = claude.iter().map(|p| p.id.clone()).collect();
+    for mut preset in bundle.claude.clone() {
+        let original_id = preset.id.clone();
+        let fields = redacted_fields(&bundle, "claude", &original_id);
+        match place(&original_id, &local_ids, strategy) {
+            Placement::Skip => results.push(result("claude", &original_id, &preset.name, "skipped", None, Vec::new())),
+            Placement::Overwrite => {
+                if let Some(existing) = claude.iter_mut().find(|p| p.id == original_id) {
+                    restore_claude(&mut preset, existing, &fields);
+                    let missing = still_missing(&fields, &redact_claude(&mut preset.clone()));
+                    results.push(result("claude", &preset.id, &preset.name, "overwritten", None, missing));
+                    *existing = preset;
+                }
+            }
+            Placement::Add(id) => {
+                let action = if id == original_id { "added" } else { "renamed" };
+                preset.id = id;
+                local_ids.push(preset.id.clone());
+                results.push(result("claude", &preset.id, &preset.name, action, Some(&original_id), fields));
+                claude.push(preset);
+            }
+        }
+    }
+
+    let mut codex = get_codex_provider_presets().await?;
+    let mut local_ids: Vec<String> = codex.iter().map(|p| p.id.clone()).collect();
+    for mut preset in bundle.codex.clone() {
+        let original_id = preset.id.clone();
+        let fields = redacted_fields(&bundle, "codex", &original_id);
+        match place(&original_id, &local_ids, strategy) {
+            Placement::Skip => results.push(result("codex", &original_id, &preset.name, "skipped", None, Vec::new())),
+            Placement::Overwrite => {
+                if let Some(existing) = codex.iter_mut().find(|p| p.id == original_id) {
+                    restore_codex(&mut preset, existing, &fields);
+                    let missing = still_missing(&fields, &redact_codex(&mut preset.clone()));
+                    results.push(result("codex", &preset.id, &preset.name, "overwritten", None, missing));
+                    *existing = preset;
+                }
+            }
+            Placement::Add(id) => {
+                let action = if id == original_id { "added" } else { "renamed" };
+                preset.id = id;
+                local_ids.push(preset.id.clone());
+                results.push(result("codex", &preset.id, &preset.name, action, Some(&original_id), fields));
+                codex.push(preset);
+            }
+        }
+    }
+
+    let mut gemini = get_gemini_provider_presets().await?;
+    let mut local_ids: Vec<String> = gemini.iter().map(|p| p.id.clone()).collect();
+    for mut preset in bundle.gemini.clone() {
+        let original_id = preset.id.clone();
+        let fields = redacted_fields(&bundle, "gemini", &original_id);
+        match place(&original_id, &local_ids, strategy) {
+            Placement::Skip => results.push(result("gemini", &original_id, &preset.name, "skipped", None, Vec::new())),
+            Placement::Overwrite => {
+                if let Some(existing) = gemini.iter_mut().find(|p| p.id == original_id) {
+                    restore_gemini(&mut preset, existing, &fields);
+                    let missing = still_missing(&fields, &redact_gemini(&mut preset.clone()));
+                    results.push(result("gemini", &preset.id, &preset.name, "overwritten", None, missing));
+                    *existing = preset;
+                }
+            }
+            Placement::Add(id) => {
+                let action = if id == original_id { "added" } else { "renamed" };
+                preset.id = id;
+                local_ids.push(preset.id.clone());
+                results.push(result("gemini", &preset.id, &preset.name, action, Some(&original_id), fields));
+                gemini.push(preset);
+            }
+        }
+    }
+
+    let changed = |engine: &str| results.iter().any(|r| r.engine == engine && r.action != "skipped");
+    if changed("claude") {
+        save_provider_presets(&claude)?;
+    }
+    if changed("codex") {
+        save_codex_provider_presets(&codex)?;
+    }
+    if changed("gemini") {
+        save_gemini_provider_presets(&gemini)?;
+    }
+
+    log::info!(
+        "[Provider Bundle] Imported bundle from {}: {} presets processed (strategy: {:?})",
+        bundle.exported_at,
+        results.len(),
+        strategy
+    );
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_and_restores_secrets() {
+        let mut codex = CodexProviderConfig {
+            id: "relay".to_string(),
+            name: "Relay".to_string(),
+            description: None,
+            website_url: None,
+            category: None,
+            auth: serde_json::json!({ "OPENAI_API_KEY": "sk-live", "tokens": null }),
+            config: concat!(
+                "model_provider = \"relay\"\n",
+                "[model_providers.relay]\n",
+                "base_url = \"https://relay.example/v1\"\n",
+                "env_key = \"RELAY_KEY\"\n",
+                "http_headers = { Authorization = \"Bearer abc\" }\n",
+                "\"x-api-key\" = 'literal-secret' # relay key\n",
+                "bearer_token = \"\"\"\nmulti\nline\"\"\"\n",
+            )
+            .to_string(),
+            is_official: None,
+            is_partner: None,
+            created_at: None,
+        };
+        let local = codex.clone();
+        let fields = redact_codex(&mut codex);
+        assert_eq!(
+            fields,
+            vec![
+                "auth.OPENAI_API_KEY",
+                "config.model_providers.relay.http_headers.Authorization",
+                "config.model_providers.relay.x-api-key",
+                "config.model_providers.relay.bearer_token",
+            ]
+        );
+        assert_eq!(codex.auth["OPENAI_API_KEY"], "");
+        assert!(codex.config.contains("env_key = \"RELAY_KEY\""));
+        assert!(codex.config.contains("Authorization = \"\""));
+        assert!(codex.config.contains("# relay key"));
+        for secret in ["Bearer abc", "literal-secret", "multi"] {
+            assert!(!codex.config.contains(secret), "{} leaked", secret);
+        }
+
+        restore_codex(&mut codex, &local, &fields);
+        assert_eq!(codex.auth, local.auth);
+        let parsed = |config: &str| config.parse::<toml::Table>().unwrap();
+        assert_eq!(parsed(&codex.config), parsed(&local.config));
+
+        let mut broken = CodexProviderConfig { config: "token = \"unterminated".to_string(), ..local.clone() };
+        assert_eq!(redact_codex(&mut broken), vec!["auth.OPENAI_API_KEY", "config"]);
+        assert!(broken.config.is_empty());
+
+        let mut claude = ProviderConfig {
+            id: "c".to_string(),
+            name: "C".to_string(),
+            description: String::new(),
+            base_url: "https://c.example".to_string(),
+            auth_token: Some("tok".to_string()),
+            api_key: None,
+            api_key_helper: Some("security find-generic-password -s relay -w".to_string()),
+            model: None,
+            enable_auto_api_key_helper: None,
+        };
+        assert_eq!(redact_claude(&mut claude), vec!["authToken"]);
+        assert!(claude.api_key_helper.is_some());
+        claude.api_key_helper = Some("echo sk-ant-REDACTED".to_string());
+        assert_eq!(redact_claude(&mut claude), vec!["apiKeyHelper"]);
+
+        let local_ids = vec!["a".to_string(), "a-2".to_string()];
+        assert!(matches!(place("b", &local_ids, ConflictStrategy::Skip), Placement::Add(id) if id == "b"));
+        assert!(matches!(place("a", &local_ids, ConflictStrategy::Skip), Placement::Skip));
+        assert!(matches!(place("a", &local_ids, ConflictStrategy::Rename), Placement::Add(id) if id == "a-3"));
+    }
+}
@@ -0,0 +1,554 @@
+//! 代理商延迟与错误率统计
+//!
+//! 连接测试和实际会话的请求耗时（首 token 延迟、流持续时间）及失败分类按代理商写入
+//! agents.db 的 `provider_metrics` 表，`get_provider_metrics` 按时间段汇总，
+//! 用于比较各第三方中转的实际速度和稳定性。
+//!
+//! 会话未显式指定代理商时，按当前配置的 base_url 匹配已保存的代理商预设；
+//! 匹配不到时以 base_url 本身作为代理商标识，未配置 base_url 视为 "official"。
+
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::command_audit::extract_session_id;
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+
+/// 请求失败的分类
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Auth,
+    RateLimit,
+    Connection,
+    Timeout,
+    Server,
+    Other,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Auth => "auth",
+            ErrorCategory::RateLimit => "rate_limit",
+            ErrorCategory::Connection => "connection",
+            ErrorCategory::Timeout => "timeout",
+            ErrorCategory::Server => "server",
+            ErrorCategory::Other => "other",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "auth" => ErrorCategory::Auth,
+            "rate_limit" => ErrorCategory::RateLimit,
+            "connection" => ErrorCategory::Connection,
+            "timeout" => ErrorCategory::Timeout,
+            "server" => ErrorCategory::Server,
+            _ => ErrorCategory::Other,
+        }
+    }
+}
+
+/// 按错误文本归类（CLI 的 stderr / error 事件、HTTP 客户端错误）
+pub fn classify_error(message: &str) -> ErrorCategory {
+    let text = message.to_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| text.contains(n));
+    if has(&["401", "403", "unauthorized", "forbidden", "invalid api key", "invalid_api_key", "authentication"]) {
+        ErrorCategory::Auth
+    } else if has(&["429", "rate limit", "rate_limit", "too many requests", "quota"]) {
+        ErrorCategory::RateLimit
+    } else if has(&["timed out", "timeout"]) {
+        ErrorCategory::Timeout
+    } else if has(&["connection refused", "connection reset", "connection closed", "error sending request", "dns", "failed to connect", "network"]) {
+        ErrorCategory::Connection
+    } else if has(&["500", "502", "503", "504", "bad gateway", "service unavailable", "internal server error", "overloaded"]) {
+        ErrorCategory::Server
+    } else {
+        ErrorCategory::Other
+    }
+}
+
+/// HTTP 客户端错误的分类（连接测试）
+pub fn classify_request_error(error: &reqwest::Error) -> ErrorCategory {
+    if error.is_timeout() {
+        ErrorCategory::Timeout
+    } else if error.is_connect() {
+        ErrorCategory::Connection
+    } else {
+        classify_error(&error.to_string())
+    }
+}
+
+/// 按 HTTP 状态码归类，成功返回 None
+pub fn classify_status(status: u16) -> Option<ErrorCategory> {
+    match status {
+        200..=299 => None,
+        401 | 403 => Some(ErrorCategory::Auth),
+        429 => Some(ErrorCategory::RateLimit),
+        408 | 504 => Some(ErrorCategory::Timeout),
+        500..=599 => Some(ErrorCategory::Server),
+        _ => Some(ErrorCategory::Other),
+    }
+}
+
+/// 一次请求的测量结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSample {
+    pub engine: String,
+    pub provider_id: String,
+    /// "test"（连接测试）| "session"（实际会话）
+    pub source: String,
+    pub session_id: Option<String>,
+    /// 首 token 延迟（仅会话）
+    pub ttft_ms: Option<u64>,
+    /// 连接测试为请求耗时，会话为整个流的持续时间
+    pub duration_ms: u64,
+    pub success: bool,
+    pub error_category: Option<ErrorCategory>,
+    pub recorded_at: String,
+}
+
+/// 代理商在某个时间段内的汇总指标
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderMetrics {
+    pub provider_id: String,
+    pub period: String,
+    pub total_requests: u64,
+    pub failed_requests: u64,
+    pub error_rate: f64,
+    pub avg_ttft_ms: Option<f64>,
+    pub p50_ttft_ms: Option<u64>,
+    pub p95_ttft_ms: Option<u64>,
+    pub avg_stream_ms: Option<f64>,
+    pub avg_test_latency_ms: Option<f64>,
+    /// 失败次数按分类统计
+    pub errors_by_category: BTreeMap<String, u64>,
+    pub last_error_at: Option<String>,
+}
+
+/// 创建代理商指标表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS provider_metrics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            engine TEXT NOT NULL,
+            provider_id TEXT NOT NULL,
+            source TEXT NOT NULL,
+            session_id TEXT,
+            ttft_ms INTEGER,
+            duration_ms INTEGER NOT NULL,
+            success INTEGER NOT NULL,
+            error_category TEXT,
+            recorded_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_provider_metrics_provider
+            ON provider_metrics(provider_id, recorded_at);",
+    )
+    .map_err(|e| format!("创建代理商指标表失败: {}", e))
+}
+
+fn open_metrics_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+fn insert_sample(conn: &Connection, sample: &ProviderSample) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO provider_metrics
+            (engine, provider_id, source, session_id, ttft_ms, duration_ms, success, error_category, recorded_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            sample.engine,
+            sample.provider_id,
+            sample.source,
+            sample.session_id,
+            sample.ttft_ms.map(|v| v as i64),
+            sample.duration_ms as i64,
+            sample.success,
+            sample.error_category.map(|c| c.as_str()),
+            sample.recorded_at,
+        ],
+    )
+    .map_err(|e| format!("写入代理商指标失败: {}", e))?;
+    Ok(())
+}
+
+fn load_samples(conn: &Connection, provider_id: &str, since: Option<&str>) -> Result<Vec<ProviderSample>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT engine, provider_id, source, session_id, ttft_ms, duration_ms, success, error_category, recorded_at
+             FROM provider_metrics
+             WHERE provider_id = ?1 AND (?2 IS NULL OR recorded_at >= ?2)
+             ORDER BY recorded_at",
+        )
+        .map_err(|e| format!("查询代理商指标失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![provider_id, since], |row| {
+            Ok(ProviderSample {
+                engine: row.get(0)?,
+                provider_id: row.get(1)?,
+                source: row.get(2)?,
+                session_id: row.get(3)?,
+                ttft_ms: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+                duration_ms: row.get::<_, i64>(5)? as u64,
+                success: row.get(6)?,
+                error_category: row.get::<_, Option<String>>(7)?.map(|c| ErrorCategory::parse(&c)),
+                recorded_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| format!("查询代理商指标失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取代理商指标失败: {}", e))
+}
+
+/// 写入一条样本；统计失败只记录日志，不影响请求本身
+fn record_sample(sample: ProviderSample) {
+    if let Err(e) = open_metrics_db().and_then(|conn| insert_sample(&conn, &sample)) {
+        log::warn!("[ProviderMetrics] Failed to record {} sample: {}", sample.provider_id, e);
+    }
+}
+
+/// 记录一次连接测试的结果
+pub fn record_connection_test(engine: &str, provider_id: &str, started: Instant, failure: Option<ErrorCategory>) {
+    record_sample(ProviderSample {
+        engine: engine.to_string(),
+        provider_id: provider_id.to_string(),
+        source: "test".to_string(),
+        session_id: None,
+        ttft_ms: None,
+        duration_ms: started.elapsed().as_millis() as u64,
+        success: failure.is_none(),
+        error_category: failure,
+        recorded_at: Utc::now().to_rfc3339(),
+    });
+}
+
+/// "24h" / "7d" / "all" 等时间段对应的起始时间（RFC 3339），"all" 返回 None
+pub(crate) fn period_start(period: &str) -> Result<Option<String>, String> {
+    if period == "all" {
+        return Ok(None);
+    }
+    let (amount, unit) = period.split_at(period.len().saturating_sub(1));
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("无效的时间段: {}（示例: 24h、7d、all）", period))?;
+    let span = match unit {
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => return Err(format!("无效的时间段: {}（示例: 24h、7d、all）", period)),
+    };
+    Ok(Some((Utc::now() - span).to_rfc3339()))
+}
+
+pub(crate) fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    Some(sorted[index])
+}
+
+pub(crate) fn average(values: impl Iterator<Item = u64>) -> Option<f64> {
+    let (sum, count) = values.fold((0u64, 0u64), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum as f64 / count as f64)
+}
+
+fn summarize(provider_id: &str, period: &str, samples: &[ProviderSample]) -> ProviderMetrics {
+    let failed: Vec<&ProviderSample> = samples.iter().filter(|s| !s.success).collect();
+    let sessions: Vec<&ProviderSample> = samples.iter().filter(|s| s.source == "session").collect();
+
+    let mut ttfts: Vec<u64> = sessions.iter().filter_map(|s| s.ttft_ms).collect();
+    ttfts.sort_unstable();
+
+    let mut errors_by_category = BTreeMap::new();
+    for sample in &failed {
+        let category = sample.error_category.unwrap_or(ErrorCategory::Other);
+        *errors_by_category.entry(category.as_str().to_string()).or_insert(0) += 1;
+    }
+
+    ProviderMetrics {
+        provider_id: provider_id.to_string(),
+        period: period.to_string(),
+        total_requests: samples.len() as u64,
+        failed_requests: failed.len() as u64,
+        error_rate: if samples.is_empty() {
+            0.0
+        } else {
+            failed.len() as f64 / samples.len() as f64
+        },
+        avg_ttft_ms: average(ttfts.iter().copied()),
+        p50_ttft_ms: percentile(&ttfts, 0.5),
+        p95_ttft_ms: percentile(&ttfts, 0.95),
+        avg_stream_ms: average(sessions.iter().filter(|s| s.success).map(|s| s.duration_ms)),
+        avg_test_latency_ms: average(
+            samples
+                .iter()
+                .filter(|s| s.source == "test" && s.success)
+                .map(|s| s.duration_ms),
+        ),
+        errors_by_category,
+        last_error_at: failed.last().map(|s| s.recorded_at.clone()),
+    }
+}
+
+// ============================================================================
+// 会话流测量
+// ============================================================================
+
+/// 当前生效的代理商标识（见模块说明）
+async fn active_provider_id(engine: &str) -> String {
+    let (base_url, presets): (Option<String>, Vec<(String, Option<String>)>) = match engine {
+        "codex" => return super::codex::config::active_codex_provider_id().unwrap_or_else(|| "official".to_string()),
+        "gemini" => (
+            super::gemini::provider::get_current_gemini_provider_config()
+                .await
+                .ok()
+                .and_then(|c| c.base_url),
+            super::gemini::provider::get_gemini_provider_presets()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| (p.id, p.env.get("GOOGLE_GEMINI_BASE_URL").cloned()))
+                .collect(),
+        ),
+        _ => (
+            super::provider::get_current_provider_config()
+                .ok()
+                .and_then(|c| c.anthropic_base_url),
+            super::provider::get_provider_presets()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| (p.id, Some(p.base_url)))
+                .collect(),
+        ),
+    };
+    match base_url {
+        Some(url) => presets
+            .into_iter()
+            .find(|(_, preset_url)| preset_url.as_deref().map(|u| u.trim_end_matches('/')) == Some(url.trim_end_matches('/')))
+            .map(|(id, _)| id)
+            .unwrap_or(url),
+        None => "official".to_string(),
+    }
+}
+
+/// 模型开始输出的事件（用于首 token 延迟）
+fn is_model_output(engine: &str, event: &Value) -> bool {
+    let kind = event["type"].as_str().unwrap_or_default();
+    match engine {
+        "codex" => {
+            kind.starts_with("item.")
+                || event["msg"]["type"].as_str().is_some_and(|t| t.starts_with("agent_"))
+        }
+        "claude" => kind == "assistant" || kind == "stream_event",
+        "gemini" => (kind == "message" && event["role"] == "assistant") || kind == "tool_use",
+        _ => false,
+    }
+}
+
+/// 流中的错误事件文本
+pub(crate) fn stream_error(engine: &str, event: &Value) -> Option<String> {
+    let kind = event["type"].as_str().unwrap_or_default();
+    let text = match engine {
+        "codex" if kind == "error" => event["message"].as_str(),
+        "codex" if kind == "turn.failed" => event["error"]["message"].as_str(),
+        "codex" if event["msg"]["type"] == "error" => event["msg"]["message"].as_str(),
+        "claude" if kind == "result" && event["is_error"] == true => event["result"].as_str(),
+        "gemini" if kind == "error" => event["message"].as_str().or(event["error"]["message"].as_str()),
+        "gemini" if kind == "result" && event["status"] == "error" => event["error"]["message"].as_str(),
+        _ => return None,
+    };
+    Some(text.unwrap_or("unknown error").to_string())
+}
+
+struct StreamState {
+    session_id: String,
+    ttft_ms: Option<u64>,
+    error: Option<ErrorCategory>,
+    /// 首次出现错误的时间
+    error_at: Option<Instant>,
+    finished: bool,
+}
+
+/// 单次执行的流测量：首 token 延迟、持续时间和失败分类
+pub struct StreamMetrics {
+    engine: String,
+    provider_id: String,
+    started: Instant,
+    state: Mutex<StreamState>,
+}
+
+impl StreamMetrics {
+    /// `provider` 为本次执行显式指定的代理商预设，未指定时使用当前生效的配置
+    pub async fn new(engine: &str, session_id: &str, provider: Option<&str>) -> Arc<Self> {
+        let provider_id = match provider {
+            Some(id) => id.to_string(),
+            None => active_provider_id(engine).await,
+        };
+        Arc::new(Self {
+            engine: engine.to_string(),
+            provider_id,
+            started: Instant::now(),
+            state: Mutex::new(StreamState {
+                session_id: session_id.to_string(),
+                ttft_ms: None,
+                error: None,
+                error_at: None,
+                finished: false,
+            }),
+        })
+    }
+
+    /// 处理一行 stdout 输出
+    pub fn observe(&self, line: &str) {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        if let Some(session_id) = extract_session_id(&self.engine, &event) {
+            state.session_id = session_id;
+        } else if let Some(message) = stream_error(&self.engine, &event) {
+            state.error = Some(classify_error(&message));
+            state.error_at.get_or_insert_with(Instant::now);
+        } else if state.ttft_ms.is_none() && is_model_output(&self.engine, &event) {
+            state.ttft_ms = Some(self.started.elapsed().as_millis() as u64);
+        }
+    }
+
+    /// 处理一行 stderr 输出，只关心看起来像错误的行
+    pub fn observe_stderr(&self, line: &str) {
+        let lower = line.to_lowercase();
+        if !lower.contains("error") && !lower.contains("failed") {
+            return;
+        }
+        let category = classify_error(line);
+        let mut state = self.state.lock().unwrap();
+        // 已知分类优先，避免后续的泛化错误行覆盖
+        if state.error.is_none() || state.error == Some(ErrorCategory::Other) {
+            state.error = Some(category);
+        }
+        state.error_at.get_or_insert_with(Instant::now);
+    }
+
+    pub fn provider_id(&self) -> &str {
+        &self.provider_id
+    }
+
+    /// 开始输出之前遇到的错误
+    pub fn startup_error(&self) -> Option<ErrorCategory> {
+        let state = self.state.lock().unwrap();
+        state.error.filter(|_| state.ttft_ms.is_none())
+    }
+
+    /// 仍未开始输出、且首个错误已持续超过 `secs` 秒时返回该错误
+    pub fn stalled_error(&self, secs: u64) -> Option<ErrorCategory> {
+        let state = self.state.lock().unwrap();
+        let stalled = state.ttft_ms.is_none() && state.error_at.is_some_and(|at| at.elapsed().as_secs() >= secs);
+        state.error.filter(|_| stalled)
+    }
+
+    /// 进程结束时调用一次；`failure` 为进程层面的失败（超时、非零退出）
+    pub fn finish(&self, failure: Option<ErrorCategory>) {
+        let mut state = self.state.lock().unwrap();
+        if state.finished {
+            return;
+        }
+        state.finished = true;
+        // 进程正常退出但流中出现过错误事件，同样算作失败
+        let error = match failure {
+            Some(ErrorCategory::Other) | None if state.error.is_some() => state.error,
+            other => other,
+        };
+        record_sample(ProviderSample {
+            engine: self.engine.clone(),
+            provider_id: self.provider_id.clone(),
+            source: "session".to_string(),
+            session_id: Some(state.session_id.clone()).filter(|s| !s.is_empty()),
+            ttft_ms: state.ttft_ms,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            success: error.is_none(),
+            error_category: error,
+            recorded_at: Utc::now().to_rfc3339(),
+        });
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 代理商在时间段内的延迟与错误率（period: "24h" / "7d" / "30d" / "all"，默认 "7d"）
+#[tauri::command]
+pub async fn get_provider_metrics(provider_id: String, period: Option<String>) -> Result<ProviderMetrics, String> {
+    let period = period.unwrap_or_else(|| "7d".to_string());
+    tokio::task::spawn_blocking(move || {
+        let since = period_start(&period)?;
+        let conn = open_metrics_db()?;
+        let samples = load_samples(&conn, &provider_id, since.as_deref())?;
+        Ok(summarize(&provider_id, &period, &samples))
+    })
+    .await
+    .map_err(|e| format!("读取代理商指标失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(source: &str, ttft_ms: Option<u64>, duration_ms: u64, error: Option<ErrorCategory>) -> ProviderSample {
+        ProviderSample {
+            engine: "codex".to_string(),
+            provider_id: "relay".to_string(),
+            source: source.to_string(),
+            session_id: None,
+            ttft_ms,
+            duration_ms,
+            success: error.is_none(),
+            error_category: error,
+            recorded_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn classifies_errors_and_summarizes_samples() {
+        assert_eq!(classify_error("unexpected status 401 Unauthorized"), ErrorCategory::Auth);
+        assert_eq!(classify_error("stream error: 429 Too Many Requests"), ErrorCategory::RateLimit);
+        assert_eq!(classify_error("error sending request: Connection refused"), ErrorCategory::Connection);
+        assert_eq!(classify_status(502), Some(ErrorCategory::Server));
+        assert!(period_start("7x").is_err());
+        assert_eq!(period_start("all").unwrap(), None);
+
+        let conn = Connection::open_in_memory().unwrap();
+        ensure_schema(&conn).unwrap();
+        for s in [
+            sample("session", Some(300), 4000, None),
+            sample("session", Some(900), 6000, None),
+            sample("session", None, 100, Some(ErrorCategory::RateLimit)),
+            sample("test", None, 120, None),
+        ] {
+            insert_sample(&conn, &s).unwrap();
+        }
+        let since = period_start("24h").unwrap();
+        let samples = load_samples(&conn, "relay", since.as_deref()).unwrap();
+        assert_eq!(samples.len(), 4);
+        assert!(load_samples(&conn, "other", None).unwrap().is_empty());
+
+        let metrics = summarize("relay", "24h", &samples);
+        assert_eq!((metrics.total_requests, metrics.failed_requests), (4, 1));
+        assert_eq!(metrics.error_rate, 0.25);
+        assert_eq!((metrics.p50_ttft_ms, metrics.p95_ttft_ms), (Some(900), Some(900)));
+        assert_eq!(metrics.avg_ttft_ms, Some(600.0));
+        assert_eq!(metrics.avg_stream_ms, Some(5000.0));
+        assert_eq!(metrics.avg_test_latency_ms, Some(120.0));
+        assert_eq!(metrics.errors_by_category.get("rate_limit"), Some(&1));
+    }
+}
@@ -0,0 +1,279 @@
+//! 内嵌终端（PTY）
+//!
+//! 在项目目录中通过伪终端启动交互式 shell 或 CLI（例如 `codex` 的 TUI 模式），
+//! 供前端嵌入终端使用。输出以 `pty-output:{id}` 事件推送（UTF-8 文本），
+//! 进程退出时发送 `pty-exit:{id}`（退出码）。输入和窗口大小通过 `pty_write` /
+//! `pty_resize` 传入。
+
+use once_cell::sync::Lazy;
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// 单次读取的缓冲区大小
+const READ_BUFFER_SIZE: usize = 8192;
+
+/// PTY 会话信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtySessionInfo {
+    pub id: String,
+    pub project_path: String,
+    /// 启动的程序（默认 shell 时为 None）
+    pub command: Option<String>,
+    pub pid: Option<u32>,
+    pub cols: u16,
+    pub rows: u16,
+    pub started_at: String,
+}
+
+/// 终端的输入端；写入可能阻塞（进程不读取输入时），只在 `spawn_blocking` 中使用
+struct PtyIo {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+}
+
+struct PtySession {
+    /// 每个终端独立的锁（先到先得），写入和调整大小按调用顺序执行，不阻塞其他终端
+    io: Arc<tokio::sync::Mutex<PtyIo>>,
+    killer: Arc<Mutex<Box<dyn ChildKiller + Send + Sync>>>,
+    info: PtySessionInfo,
+}
+
+/// 运行中的 PTY 会话（按 id 索引）
+static PTY_SESSIONS: Lazy<Mutex<HashMap<String, PtySession>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn get_session<T>(id: &str, f: impl FnOnce(&mut PtySession) -> T) -> Result<T, String> {
+    let mut sessions = PTY_SESSIONS.lock().unwrap();
+    let session = sessions.get_mut(id).ok_or_else(|| format!("终端会话 {} 不存在", id))?;
+    Ok(f(session))
+}
+
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows: rows.max(1),
+        cols: cols.max(1),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// 取出缓冲区中完整的 UTF-8 文本，末尾不完整的多字节字符留到下次读取
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        // 末尾字符被截断：保留剩余字节
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        // 真正的非法字节：整体有损转换，避免卡住
+        Err(_) => pending.len(),
+    };
+    let text = String::from_utf8_lossy(&pending[..valid]).to_string();
+    pending.drain(..valid);
+    text
+}
+
+/// 读取 PTY 输出并推送事件，进程结束后清理会话
+fn spawn_reader(
+    app: AppHandle,
+    id: String,
+    mut reader: Box<dyn Read + Send>,
+    mut child: Box<dyn portable_pty::Child + Send + Sync>,
+) {
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; READ_BUFFER_SIZE];
+        let mut pending = Vec::new();
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => {
+                    pending.extend_from_slice(&buffer[..n]);
+                    let text = take_utf8(&mut pending);
+                    if !text.is_empty() {
+                        let _ = app.emit(&format!("pty-output:{}", id), text);
+                    }
+                }
+                Err(e) => {
+                    log::debug!("[PTY] Reader for {} stopped: {}", id, e);
+                    break;
+                }
+            }
+        }
+        if !pending.is_empty() {
+            let _ = app.emit(&format!("pty-output:{}", id), String::from_utf8_lossy(&pending).to_string());
+        }
+
+        let exit_code = child.wait().ok().map(|status| status.exit_code());
+        PTY_SESSIONS.lock().unwrap().remove(&id);
+        log::info!("[PTY] Session {} exited with {:?}", id, exit_code);
+        let _ = app.emit(&format!("pty-exit:{}", id), exit_code);
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 在项目目录中启动 PTY 会话
+///
+/// `command` 为空时启动用户默认 shell，否则直接启动该程序（如 `codex`）。
+#[tauri::command]
+pub fn pty_spawn(
+    app: AppHandle,
+    project_path: String,
+    command: Option<String>,
+    args: Option<Vec<String>>,
+    cols: u16,
+    rows: u16,
+) -> Result<PtySessionInfo, String> {
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!("项目目录不存在: {}", project_path));
+    }
+    let command = command.filter(|c| !c.trim().is_empty());
+
+    let size = pty_size(cols, rows);
+    let pair = native_pty_system()
+        .openpty(size)
+        .map_err(|e| format!("创建伪终端失败: {}", e))?;
+
+    let mut cmd = match &command {
+        Some(program) => {
+            let mut cmd = CommandBuilder::new(program);
+            cmd.args(args.unwrap_or_default());
+            cmd
+        }
+        None => CommandBuilder::new_default_prog(),
+    };
+    cmd.cwd(&project_path);
+    cmd.env("TERM", "xterm-256color");
+    cmd.env("COLORTERM", "truecolor");
+
+    let child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("启动进程失败: {}", e))?;
+    // 子进程持有 slave 端，这里释放，进程退出后读取端才能收到 EOF
+    drop(pair.slave);
+
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("获取终端输出失败: {}", e))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("获取终端输入失败: {}", e))?;
+
+    let info = PtySessionInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        project_path,
+        command,
+        pid: child.process_id(),
+        cols: size.cols,
+        rows: size.rows,
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let session = PtySession {
+        io: Arc::new(tokio::sync::Mutex::new(PtyIo { master: pair.master, writer })),
+        killer: Arc::new(Mutex::new(child.clone_killer())),
+        info: info.clone(),
+    };
+    PTY_SESSIONS.lock().unwrap().insert(info.id.clone(), session);
+    spawn_reader(app, info.id.clone(), reader, child);
+
+    log::info!(
+        "[PTY] Started session {} ({:?}) in {}",
+        info.id,
+        info.command.as_deref().unwrap_or("shell"),
+        info.project_path
+    );
+    Ok(info)
+}
+
+/// 向 PTY 会话写入输入（按键、粘贴内容等）
+#[tauri::command]
+pub async fn pty_write(id: String, data: String) -> Result<(), String> {
+    let io = get_session(&id, |session| session.io.clone())?;
+    let mut io = io.lock_owned().await;
+    tokio::task::spawn_blocking(move || {
+        io.writer
+            .write_all(data.as_bytes())
+            .and_then(|_| io.writer.flush())
+            .map_err(|e| format!("写入终端失败: {}", e))
+    })
+    .await
+    .map_err(|e| format!("写入终端失败: {}", e))?
+}
+
+/// 调整 PTY 窗口大小
+#[tauri::command]
+pub async fn pty_resize(id: String, cols: u16, rows: u16) -> Result<(), String> {
+    let io = get_session(&id, |session| session.io.clone())?;
+    let io = io.lock_owned().await;
+    let size = pty_size(cols, rows);
+    tokio::task::spawn_blocking(move || io.master.resize(size).map_err(|e| format!("调整终端大小失败: {}", e)))
+        .await
+        .map_err(|e| format!("调整终端大小失败: {}", e))??;
+    // 会话可能已在此期间退出
+    let _ = get_session(&id, |session| {
+        session.info.cols = size.cols;
+        session.info.rows = size.rows;
+    });
+    Ok(())
+}
+
+/// 结束 PTY 会话（退出事件由读取线程发送）
+///
+/// 不等待输入锁，写入阻塞时也能结束进程。
+#[tauri::command]
+pub async fn pty_kill(id: String) -> Result<(), String> {
+    let killer = get_session(&id, |session| session.killer.clone())?;
+    tokio::task::spawn_blocking(move || {
+        killer
+            .lock()
+            .unwrap()
+            .kill()
+            .map_err(|e| format!("结束终端进程失败: {}", e))
+    })
+    .await
+    .map_err(|e| format!("结束终端进程失败: {}", e))??;
+    log::info!("[PTY] Killed session {}", id);
+    Ok(())
+}
+
+/// 列出运行中的 PTY 会话
+#[tauri::command]
+pub fn pty_list() -> Vec<PtySessionInfo> {
+    let mut sessions: Vec<_> = PTY_SESSIONS
+        .lock()
+        .unwrap()
+        .values()
+        .map(|session| session.info.clone())
+        .collect();
+    sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_incomplete_utf8_for_next_read() {
+        let bytes = "终端".as_bytes();
+        let mut pending = bytes[..4].to_vec();
+        assert_eq!(take_utf8(&mut pending), "终");
+        assert_eq!(pending, bytes[3..4]);
+
+        pending.extend_from_slice(&bytes[4..]);
+        assert_eq!(take_utf8(&mut pending), "端");
+        assert!(pending.is_empty());
+
+        let mut invalid = vec![b'a', 0xff, b'b'];
+        assert_eq!(take_utf8(&mut invalid), "a\u{fffd}b");
+        assert!(invalid.is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! 限流检测与退避重排队
+//!
+//! 从各引擎的流式输出（错误事件、stderr）中识别限流信号（429、overloaded、用量上限），
+//! 发送结构化的 `rate-limited:{session_id}` / `rate-limited` 事件，能解析到时附带 retry-after。
+//!
+//! 开启自动重排队后，因限流而失败的执行会在退避后用同一提示词重新提交
+//! （复用各引擎的超时重试路径），并发送 `rate-limit-requeued` 事件；
+//! 等待期间可通过 `cancel_rate_limit_requeue` 按引擎会话 ID 取消。设置保存在 ~/.anycode/rate_limit.json。
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::task::AbortHandle;
+
+use super::command_audit::extract_session_id;
+use super::provider_metrics::stream_error;
+use super::data_root::anycode_dir;
+
+/// 相同的限流提示在该时间内只通知一次（CLI 内部重试会反复打印）
+const EMIT_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// 自动重排队设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RateLimitSettings {
+    pub auto_requeue: bool,
+    /// 没有 retry-after 时的初始退避，之后每次翻倍
+    pub default_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    /// 同一提示词最多重新提交的次数
+    pub max_requeues: u32,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            auto_requeue: false,
+            default_backoff_secs: 60,
+            max_backoff_secs: 900,
+            max_requeues: 3,
+        }
+    }
+}
+
+/// 从输出中识别出的限流信号
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateLimitSignal {
+    pub message: String,
+    /// 429（限流）或 529（服务过载）
+    pub status: u16,
+    pub retry_after_secs: Option<u64>,
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("rate_limit.json"))
+}
+
+pub fn load_rate_limit_settings() -> RateLimitSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// 信号解析
+// ============================================================================
+
+/// 状态码只在错误上下文中计数（`status: 429`、`API Error: 429`、`429 Too Many Requests`），
+/// 避免 stderr 中的普通数字（如 "wrote 429 lines"）被当成限流
+static RATE_LIMIT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:status|error|code|http)\W{0,3}(?:code\W{0,3})?429\b|\b429 too many requests|rate[ _-]?limit|too many requests|quota exceeded|resource[_ ]exhausted|usage limit reached").unwrap()
+});
+static OVERLOADED_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:status|error|code|http)\W{0,3}(?:code\W{0,3})?529\b|overloaded").unwrap()
+});
+static RETRY_AFTER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(?:retry[-_ ]after["']?\s*[:=]?\s*|(?:try again|retry(?:ing)?|resets?) in\s+)(\d+(?:\.\d+)?)\s*(ms|milliseconds?|s|secs?|seconds?|m|mins?|minutes?|h|hours?)?\b"#).unwrap()
+});
+/// Claude 订阅用量上限：`Claude AI usage limit reached|<重置时间戳>`
+static USAGE_RESET_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)usage limit reached\|(\d{10})").unwrap());
+
+fn parse_retry_after(text: &str, now_epoch: i64) -> Option<u64> {
+    if let Some(caps) = USAGE_RESET_RE.captures(text) {
+        let reset_at: i64 = caps[1].parse().ok()?;
+        return Some((reset_at - now_epoch).max(0) as u64);
+    }
+    let caps = RETRY_AFTER_RE.captures(text)?;
+    let amount: f64 = caps[1].parse().ok()?;
+    let unit = caps.get(2).map_or("s", |m| m.as_str()).to_lowercase();
+    let secs = if unit.starts_with("ms") || unit.starts_with("milli") {
+        amount / 1000.0
+    } else if unit.starts_with('m') {
+        amount * 60.0
+    } else if unit.starts_with('h') {
+        amount * 3600.0
+    } else {
+        amount
+    };
+    Some(secs.ceil() as u64)
+}
+
+/// 识别一段错误文本中的限流信号
+pub fn detect_rate_limit(text: &str, now_epoch: i64) -> Option<RateLimitSignal> {
+    let status = if OVERLOADED_RE.is_match(text) {
+        529
+    } else if RATE_LIMIT_RE.is_match(text) {
+        429
+    } else {
+        return None;
+    };
+    Some(RateLimitSignal {
+        message: text.trim().chars().take(500).collect(),
+        status,
+        retry_after_secs: parse_retry_after(text, now_epoch),
+    })
+}
+
+/// 第 `requeues` 次重排队前的等待时间：优先使用 retry-after，否则指数退避
+pub fn backoff_secs(settings: &RateLimitSettings, signal: &RateLimitSignal, requeues: u32) -> u64 {
+    let exponential = settings
+        .default_backoff_secs
+        .saturating_mul(1u64 << requeues.min(16));
+    signal
+        .retry_after_secs
+        .unwrap_or(exponential)
+        .clamp(1, settings.max_backoff_secs.max(1))
+}
+
+// ============================================================================
+// 流监听
+// ============================================================================
+
+struct WatcherState {
+    session_id: String,
+    last: Option<RateLimitSignal>,
+    last_emit: Option<(String, Instant)>,
+}
+
+/// 单次执行的限流监听
+pub struct RateLimitWatcher {
+    app: AppHandle,
+    engine: String,
+    /// 引擎其他事件所用的会话 ID（`rate-limited:{channel_id}`），为空时使用真实会话 ID
+    channel_id: String,
+    state: Mutex<WatcherState>,
+}
+
+impl RateLimitWatcher {
+    /// `session_id` 为执行时已知的 ID，收到 CLI 的 init 事件后事件内容中改用真实会话 ID
+    pub fn new(app: &AppHandle, engine: &str, session_id: &str) -> Arc<Self> {
+        Arc::new(Self {
+            app: app.clone(),
+            engine: engine.to_string(),
+            channel_id: session_id.to_string(),
+            state: Mutex::new(WatcherState {
+                session_id: session_id.to_string(),
+                last: None,
+                last_emit: None,
+            }),
+        })
+    }
+
+    /// 处理一行 stdout 输出，只检查错误事件，避免把回复正文里的 "429" 当成限流
+    pub fn observe(&self, line: &str) {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+        if let Some(session_id) = extract_session_id(&self.engine, &event) {
+            self.state.lock().unwrap().session_id = session_id;
+        } else if let Some(message) = stream_error(&self.engine, &event) {
+            self.check(&message);
+        }
+    }
+
+    /// 处理一行 stderr 输出
+    pub fn observe_stderr(&self, line: &str) {
+        self.check(line);
+    }
+
+    fn check(&self, text: &str) {
+        let Some(signal) = detect_rate_limit(text, Utc::now().timestamp()) else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        state.last = Some(signal.clone());
+        if state
+            .last_emit
+            .as_ref()
+            .is_some_and(|(message, at)| *message == signal.message && at.elapsed() < EMIT_COOLDOWN)
+        {
+            return;
+        }
+        state.last_emit = Some((signal.message.clone(), Instant::now()));
+
+        log::warn!("[RateLimit] {} session {} rate limited: {}", self.engine, state.session_id, signal.message);
+        let payload = serde_json::json!({
+            "engine": self.engine,
+            "session_id": state.session_id,
+            "status": signal.status,
+            "message": signal.message,
+            "retry_after_secs": signal.retry_after_secs,
+            "detected_at": Utc::now().to_rfc3339(),
+        });
+        let channel_id = if self.channel_id.is_empty() { &state.session_id } else { &self.channel_id };
+        if !channel_id.is_empty() {
+            let _ = self.app.emit(&format!("rate-limited:{}", channel_id), &payload);
+        }
+        let _ = self.app.emit("rate-limited", &payload);
+    }
+
+    pub fn channel_id(&self) -> String {
+        if self.channel_id.is_empty() {
+            self.state.lock().unwrap().session_id.clone()
+        } else {
+            self.channel_id.clone()
+        }
+    }
+
+    /// 引擎的真实会话 ID（尚未收到 init 事件时为执行时已知的 ID）
+    pub fn session_id(&self) -> String {
+        self.state.lock().unwrap().session_id.clone()
+    }
+
+    /// 本次执行最后一次遇到的限流信号
+    pub fn signal(&self) -> Option<RateLimitSignal> {
+        self.state.lock().unwrap().last.clone()
+    }
+}
+
+// ============================================================================
+// 自动重排队
+// ============================================================================
+
+/// 等待中的重排队（引擎会话 ID -> (任务编号, 定时任务)）
+static PENDING_REQUEUES: Lazy<Mutex<HashMap<String, (u64, AbortHandle)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_REQUEUE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 开启了自动重排队且次数未用完时，在退避后执行 `resubmit`；返回是否已安排
+///
+/// 以引擎的真实会话 ID 作为键（同一会话之前等待中的重排队会被取消），事件发到 `channel_id`。
+/// `requeues` 为该提示词已经重新提交过的次数。
+pub fn schedule_requeue<F>(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    channel_id: &str,
+    signal: &RateLimitSignal,
+    requeues: u32,
+    resubmit: F,
+) -> bool
+where
+    F: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let settings = load_rate_limit_settings();
+    if !settings.auto_requeue || requeues >= settings.max_requeues {
+        return false;
+    }
+    if session_id.is_empty() {
+        // 没有会话 ID 就无法取消，不自动重排队
+        log::warn!("[RateLimit] {} run was rate limited before a session was assigned, not requeueing", engine);
+        return false;
+    }
+
+    let delay = backoff_secs(&settings, signal, requeues);
+    log::info!(
+        "[RateLimit] Requeueing {} session {} in {}s ({}/{})",
+        engine,
+        session_id,
+        delay,
+        requeues + 1,
+        settings.max_requeues
+    );
+    let payload = serde_json::json!({
+        "engine": engine,
+        "session_id": session_id,
+        "delay_secs": delay,
+        "retry_at": (Utc::now() + chrono::Duration::seconds(delay as i64)).to_rfc3339(),
+        "requeue": requeues + 1,
+        "max_requeues": settings.max_requeues,
+    });
+    let channel_id = if channel_id.is_empty() { session_id } else { channel_id };
+    let _ = app.emit(&format!("rate-limit-requeued:{}", channel_id), &payload);
+    let _ = app.emit("rate-limit-requeued", &payload);
+
+    let id = NEXT_REQUEUE_ID.fetch_add(1, Ordering::Relaxed);
+    let key = session_id.to_string();
+    let engine = engine.to_string();
+    let mut pending = PENDING_REQUEUES.lock().unwrap();
+    let task = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(delay)).await;
+        {
+            let mut pending = PENDING_REQUEUES.lock().unwrap();
+            if pending.get(&key).is_some_and(|(current, _)| *current == id) {
+                pending.remove(&key);
+            }
+        }
+        if let Err(e) = resubmit.await {
+            log::error!("[RateLimit] Failed to resubmit {} session {}: {}", engine, key, e);
+        }
+    });
+    if let Some((_, previous)) = pending.insert(session_id.to_string(), (id, task.abort_handle())) {
+        log::info!("[RateLimit] Replacing pending requeue for session {}", session_id);
+        previous.abort();
+    }
+    true
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_rate_limit_settings() -> Result<RateLimitSettings, String> {
+    Ok(load_rate_limit_settings())
+}
+
+#[tauri::command]
+pub async fn set_rate_limit_settings(settings: RateLimitSettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize rate limit settings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write rate limit settings: {}", e))
+}
+
+/// 取消等待中的重排队，返回是否存在
+#[tauri::command]
+pub async fn cancel_rate_limit_requeue(session_id: String) -> Result<bool, String> {
+    let handle = PENDING_REQUEUES.lock().unwrap().remove(&session_id);
+    if let Some((_, handle)) = &handle {
+        handle.abort();
+        log::info!("[RateLimit] Cancelled requeue for session {}", session_id);
+    }
+    Ok(handle.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_signals_and_computes_backoff() {
+        let now = 1_700_000_000;
+        let signal = detect_rate_limit("stream error: 429 Too Many Requests, retry-after: 12", now).unwrap();
+        assert_eq!((signal.status, signal.retry_after_secs), (429, Some(12)));
+
+        let signal = detect_rate_limit(r#"API Error: 529 {"type":"overloaded_error"}"#, now).unwrap();
+        assert_eq!((signal.status, signal.retry_after_secs), (529, None));
+
+        let signal = detect_rate_limit("Rate limit reached for gpt-5. Please try again in 1.5s.", now).unwrap();
+        assert_eq!(signal.retry_after_secs, Some(2));
+        let signal = detect_rate_limit("quota exceeded, retrying in 2 minutes", now).unwrap();
+        assert_eq!(signal.retry_after_secs, Some(120));
+        let signal = detect_rate_limit("Claude AI usage limit reached|1700000300", now).unwrap();
+        assert_eq!(signal.retry_after_secs, Some(300));
+
+        assert!(detect_rate_limit("error: connection refused", now).is_none());
+        assert_eq!(detect_rate_limit("API Error: 429 {\"type\":\"error\"}", now).unwrap().status, 429);
+        assert_eq!(detect_rate_limit("HTTP status code 429", now).unwrap().status, 429);
+        // 错误上下文之外的数字不是限流
+        assert!(detect_rate_limit("indexed 429 files in 529ms", now).is_none());
+
+        let settings = RateLimitSettings::default();
+        let no_hint = RateLimitSignal { message: String::new(), status: 429, retry_after_secs: None };
+        assert_eq!(backoff_secs(&settings, &no_hint, 0), 60);
+        assert_eq!(backoff_secs(&settings, &no_hint, 2), 240);
+        assert_eq!(backoff_secs(&settings, &no_hint, 5), 900);
+        let hinted = RateLimitSignal { retry_after_secs: Some(0), ..no_hint };
+        assert_eq!(backoff_secs(&settings, &hinted, 0), 1);
+    }
+}
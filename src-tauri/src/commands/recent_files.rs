@@ -0,0 +1,224 @@
+//! 最近修改的文件
+//!
+//! 合并三个来源：git 工作区状态（未提交的改动）、文件修改时间、变更追踪记录
+//! （Agent 改过的文件）。候选文件来自 `git ls-files`（含未忽略的新文件），只对这些
+//! 路径取 mtime，不遍历整个目录树；非 git 目录退回到 `list_candidate_paths`。
+//!
+//! 供快速打开列表使用，`build_context_bundle` 也会把最近修改的文件排在候选列表前面。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::codex::change_store;
+use super::context_manager::list_candidate_paths;
+use super::simple_git::run_git;
+
+/// 未指定时的时间窗口（秒）
+const DEFAULT_WINDOW_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// 未指定时返回的条数
+const DEFAULT_LIMIT: usize = 50;
+
+/// 最近修改的文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFile {
+    /// 相对项目根目录的路径
+    pub path: String,
+    /// 文件修改时间（Unix 秒）
+    pub modified_at: u64,
+    /// git 状态码（如 "M"、"A"、"??"），没有未提交改动时为空
+    pub git_status: Option<String>,
+    /// 变更追踪中最近一次 Agent 修改的时间（Unix 秒）
+    pub agent_changed_at: Option<u64>,
+}
+
+impl RecentFile {
+    fn last_activity(&self) -> u64 {
+        self.modified_at.max(self.agent_changed_at.unwrap_or(0))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+/// 解析 `git status --porcelain=v1 -z`，返回 路径 -> 状态码（重命名取新路径）
+fn parse_status_z(output: &str) -> HashMap<String, String> {
+    let mut status = HashMap::new();
+    let mut entries = output.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        let code = code.trim().to_string();
+        // 重命名 / 复制条目后面紧跟原路径
+        if code.starts_with('R') || code.starts_with('C') {
+            entries.next();
+        }
+        status.insert(path.to_string(), code);
+    }
+    status
+}
+
+fn file_mtime(root: &Path, path: &str) -> Option<u64> {
+    std::fs::metadata(root.join(path))
+        .ok()
+        .filter(|m| m.is_file())?
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// 变更追踪中该项目各文件最近一次修改的时间
+fn agent_changes(project_path: &str, since: u64) -> HashMap<String, u64> {
+    let load = || -> Result<HashMap<String, u64>, String> {
+        let conn = change_store::open_change_db()?;
+        let project = normalize_path(project_path);
+        let mut stmt = conn
+            .prepare(
+                "SELECT s.project_path, r.file_path, MAX(r.timestamp)
+                 FROM codex_change_records r
+                 JOIN codex_change_sessions s ON s.session_id = r.session_id
+                 GROUP BY s.project_path, r.file_path",
+            )
+            .map_err(|e| format!("查询变更记录失败: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+            .map_err(|e| format!("查询变更记录失败: {}", e))?;
+
+        let mut changes = HashMap::new();
+        for (session_project, file_path, timestamp) in rows.filter_map(|r| r.ok()) {
+            if normalize_path(&session_project) != project {
+                continue;
+            }
+            let Ok(changed_at) = chrono::DateTime::parse_from_rfc3339(&timestamp) else {
+                continue;
+            };
+            let changed_at = changed_at.timestamp().max(0) as u64;
+            if changed_at >= since {
+                let entry = changes.entry(file_path.replace('\\', "/")).or_insert(0);
+                *entry = (*entry).max(changed_at);
+            }
+        }
+        Ok(changes)
+    };
+    load().unwrap_or_else(|e| {
+        log::warn!("[RecentFiles] Failed to read change records: {}", e);
+        HashMap::new()
+    })
+}
+
+/// 合并各来源并排序（最近的在前）
+///
+/// 有未提交改动的文件不受时间窗口限制。
+fn merge_recent(
+    mtimes: Vec<(String, u64)>,
+    mut status: HashMap<String, String>,
+    mut agent: HashMap<String, u64>,
+    since: u64,
+    limit: usize,
+) -> Vec<RecentFile> {
+    let mut files: Vec<RecentFile> = mtimes
+        .into_iter()
+        .filter_map(|(path, modified_at)| {
+            let git_status = status.remove(&path);
+            let agent_changed_at = agent.remove(&path);
+            let file = RecentFile { path, modified_at, git_status, agent_changed_at };
+            (file.git_status.is_some() || file.last_activity() >= since).then_some(file)
+        })
+        .collect();
+    files.sort_by(|a, b| b.last_activity().cmp(&a.last_activity()).then_with(|| a.path.cmp(&b.path)));
+    files.truncate(limit);
+    files
+}
+
+/// 最近修改的文件（阻塞：在阻塞线程中调用）
+///
+/// `since` 为 Unix 秒，默认最近 7 天；`limit` 默认 50。
+pub fn recently_modified_files(project_path: &str, since: Option<u64>, limit: Option<usize>) -> Result<Vec<RecentFile>, String> {
+    let root = Path::new(project_path);
+    if !root.is_dir() {
+        return Err(format!("项目目录不存在: {}", project_path));
+    }
+    let since = since.unwrap_or_else(|| now_secs().saturating_sub(DEFAULT_WINDOW_SECS));
+
+    let (paths, status) = match run_git(root, &["ls-files", "--cached", "--others", "--exclude-standard", "-z"]) {
+        Ok(output) => {
+            let paths: Vec<String> = output.split('\0').filter(|p| !p.is_empty()).map(str::to_string).collect();
+            let status = run_git(root, &["status", "--porcelain=v1", "-z", "--untracked-files=all"])
+                .map(|out| parse_status_z(&out))
+                .unwrap_or_default();
+            (paths, status)
+        }
+        Err(_) => (list_candidate_paths(root), HashMap::new()),
+    };
+
+    // 已删除的文件（无法取到 mtime）不列出
+    let mtimes: Vec<(String, u64)> = paths
+        .into_iter()
+        .filter_map(|path| file_mtime(root, &path).map(|mtime| (path, mtime)))
+        .collect();
+    Ok(merge_recent(
+        mtimes,
+        status,
+        agent_changes(project_path, since),
+        since,
+        limit.unwrap_or(DEFAULT_LIMIT),
+    ))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 列出项目中最近修改的文件（git 状态 + 修改时间 + Agent 变更记录）
+#[tauri::command]
+pub async fn get_recently_modified_files(
+    project_path: String,
+    since: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<RecentFile>, String> {
+    tokio::task::spawn_blocking(move || recently_modified_files(&project_path, since, limit))
+        .await
+        .map_err(|e| format!("读取最近修改的文件失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_git_status_mtime_and_agent_changes() {
+        let status = parse_status_z(" M src/a.rs\0?? new.txt\0R  src/b.rs\0src/old_b.rs\0");
+        assert_eq!(status.get("src/a.rs").map(String::as_str), Some("M"));
+        assert_eq!(status.get("new.txt").map(String::as_str), Some("??"));
+        assert_eq!(status.get("src/b.rs").map(String::as_str), Some("R"));
+        assert!(!status.contains_key("src/old_b.rs"));
+
+        let mtimes = vec![
+            ("src/a.rs".to_string(), 100),
+            ("new.txt".to_string(), 900),
+            ("src/b.rs".to_string(), 50),
+            ("README.md".to_string(), 800),
+            ("old.rs".to_string(), 10),
+            ("agent.rs".to_string(), 20),
+        ];
+        let agent = HashMap::from([("agent.rs".to_string(), 1000)]);
+        let files = merge_recent(mtimes, status, agent, 500, 10);
+        let order: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        // 未提交的改动总会列出；old.rs 超出时间窗口
+        assert_eq!(order, vec!["agent.rs", "new.txt", "README.md", "src/a.rs", "src/b.rs"]);
+        assert_eq!(files[0].agent_changed_at, Some(1000));
+        assert_eq!(files[2].git_status, None);
+    }
+}
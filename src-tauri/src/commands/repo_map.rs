@@ -0,0 +1,376 @@
+//! 仓库地图（repo map）
+//!
+//! 把项目整理成紧凑的符号大纲（每个文件的类、函数、类型等及其行号），
+//! 在 token 预算内注入提示词，供没有内置项目索引的引擎快速了解代码结构。
+//!
+//! - 安装了 universal-ctags 时用它解析（支持的语言最多）；否则对常见语言
+//!   使用正则提取顶层定义
+//! - 候选文件与 `build_context` 相同（git 未忽略的文件）；按 git 改动频率排序，
+//!   超出预算的文件只计数不展示
+//! - 大纲按项目缓存在内存中，文件修改时间或数量变化时重新生成
+
+use crate::tr;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use super::context_manager::{estimate_tokens, git_churn, list_candidate_paths};
+
+/// 未指定时的 token 预算
+pub const DEFAULT_MAP_TOKENS: usize = 1024;
+
+/// 超过此大小的文件不解析
+const MAX_MAP_FILE_BYTES: u64 = 256 * 1024;
+
+/// 纳入大纲的 ctags 符号种类
+const CTAGS_KINDS: [&str; 14] = [
+    "class", "struct", "interface", "trait", "enum", "function", "method", "module",
+    "namespace", "type", "typedef", "implementation", "macro", "union",
+];
+
+/// 一个符号定义
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapSymbol {
+    pub name: String,
+    pub kind: String,
+    pub line: usize,
+}
+
+/// 单个文件的大纲
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOutline {
+    pub path: String,
+    pub symbols: Vec<MapSymbol>,
+}
+
+/// `get_repo_map` 的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoMap {
+    /// "ctags" | "regex"
+    pub source: String,
+    /// 预算内展示的文件
+    pub files: Vec<FileOutline>,
+    /// 因预算不足未展示的文件数
+    pub omitted_files: usize,
+    pub tokens: usize,
+    /// 可直接加入提示词的文本
+    pub rendered: String,
+}
+
+/// 缓存的完整大纲（未按预算裁剪）
+#[derive(Debug, Clone)]
+struct CachedOutline {
+    fingerprint: (usize, u64),
+    source: String,
+    files: Vec<FileOutline>,
+}
+
+static OUTLINE_CACHE: Lazy<Mutex<HashMap<String, CachedOutline>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// ============================================================================
+// 符号提取
+// ============================================================================
+
+/// 文件数量与最新修改时间，用于判断缓存是否失效
+fn fingerprint(root: &Path, paths: &[String]) -> (usize, u64) {
+    let latest = paths
+        .iter()
+        .filter_map(|p| std::fs::metadata(root.join(p)).ok()?.modified().ok())
+        .filter_map(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .max()
+        .unwrap_or(0);
+    (paths.len(), latest)
+}
+
+fn ctags_binary() -> Option<std::path::PathBuf> {
+    // 只接受 universal-ctags（支持 JSON 输出），exuberant-ctags 等不支持
+    let path = which::which("ctags").ok()?;
+    let mut cmd = Command::new(&path);
+    cmd.arg("--version");
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    let output = cmd.output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .contains("Universal Ctags")
+        .then_some(path)
+}
+
+/// 解析 ctags `--output-format=json` 输出
+fn parse_ctags_output(output: &str) -> HashMap<String, Vec<MapSymbol>> {
+    let mut symbols: HashMap<String, Vec<MapSymbol>> = HashMap::new();
+    for line in output.lines() {
+        let Ok(tag) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        let (Some(name), Some(path), Some(kind)) = (tag["name"].as_str(), tag["path"].as_str(), tag["kind"].as_str())
+        else {
+            continue;
+        };
+        if tag["_type"] != "tag" || !CTAGS_KINDS.contains(&kind) {
+            continue;
+        }
+        symbols.entry(path.replace('\\', "/")).or_default().push(MapSymbol {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line: tag["line"].as_u64().unwrap_or(0) as usize,
+        });
+    }
+    symbols
+}
+
+fn run_ctags(ctags: &Path, root: &Path, paths: &[String]) -> Result<HashMap<String, Vec<MapSymbol>>, String> {
+    let mut cmd = Command::new(ctags);
+    cmd.args(["--output-format=json", "--fields=+nK", "-f", "-", "-L", "-"])
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let mut child = cmd.spawn().map_err(|e| format!("启动 ctags 失败: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let list = paths.join("\n");
+        stdin.write_all(list.as_bytes()).map_err(|e| format!("写入 ctags 文件列表失败: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("等待 ctags 结束失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ctags 退出码 {:?}", output.status.code()));
+    }
+    Ok(parse_ctags_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 常见语言的顶层定义（(扩展名, 正则)，正则的第 1 组为种类、第 2 组为名称）
+static DEFINITION_PATTERNS: Lazy<Vec<(&'static [&'static str], Regex)>> = Lazy::new(|| {
+    vec![
+        (
+            &["rs"][..],
+            Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:async\s+|const\s+|unsafe\s+)*(fn|struct|enum|trait|mod|type|union)\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap(),
+        ),
+        (
+            &["ts", "tsx", "js", "jsx", "mjs", "cjs"][..],
+            Regex::new(r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(function|class|interface|type|enum)\*?\s+([A-Za-z_$][A-Za-z0-9_$]*)").unwrap(),
+        ),
+        (
+            &["py"][..],
+            Regex::new(r"^(?:\s{0,4})(?:async\s+)?(def|class)\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap(),
+        ),
+        (
+            &["go"][..],
+            Regex::new(r"^(func|type)\s+(?:\([^)]*\)\s*)?([A-Za-z_][A-Za-z0-9_]*)").unwrap(),
+        ),
+        (
+            &["java", "kt", "cs", "swift", "scala"][..],
+            Regex::new(r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|sealed|data|open|partial)\s+)*(class|interface|enum|record|object|struct|protocol)\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap(),
+        ),
+    ]
+});
+
+/// 用正则提取定义；不支持的语言返回 None
+fn extract_definitions(path: &str, content: &str) -> Option<Vec<MapSymbol>> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    let (_, pattern) = DEFINITION_PATTERNS
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension.as_str()))?;
+    Some(
+        content
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let captures = pattern.captures(line)?;
+                Some(MapSymbol {
+                    name: captures[2].to_string(),
+                    kind: captures[1].to_string(),
+                    line: index + 1,
+                })
+            })
+            .collect(),
+    )
+}
+
+fn regex_outline(root: &Path, paths: &[String]) -> HashMap<String, Vec<MapSymbol>> {
+    paths
+        .iter()
+        .filter(|path| {
+            std::fs::metadata(root.join(path))
+                .map(|m| m.is_file() && m.len() <= MAX_MAP_FILE_BYTES)
+                .unwrap_or(false)
+        })
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(root.join(path)).ok()?;
+            Some((path.clone(), extract_definitions(path, &content)?))
+        })
+        .collect()
+}
+
+/// 生成完整大纲（优先 ctags），文件按改动频率和路径深度排序
+fn generate_outline(root: &Path, paths: &[String]) -> (String, Vec<FileOutline>) {
+    let (source, symbols) = match ctags_binary().map(|ctags| run_ctags(&ctags, root, paths)) {
+        Some(Ok(symbols)) => ("ctags", symbols),
+        Some(Err(e)) => {
+            log::warn!("[RepoMap] ctags failed, falling back to regex outline: {}", e);
+            ("regex", regex_outline(root, paths))
+        }
+        None => ("regex", regex_outline(root, paths)),
+    };
+
+    let churn = git_churn(root);
+    let mut files: Vec<FileOutline> = symbols
+        .into_iter()
+        .filter(|(_, symbols)| !symbols.is_empty())
+        .map(|(path, mut symbols)| {
+            symbols.sort_by_key(|s| s.line);
+            FileOutline { path, symbols }
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        let churn_a = churn.get(&a.path).copied().unwrap_or(0);
+        let churn_b = churn.get(&b.path).copied().unwrap_or(0);
+        churn_b
+            .cmp(&churn_a)
+            .then_with(|| a.path.matches('/').count().cmp(&b.path.matches('/').count()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    (source.to_string(), files)
+}
+
+// ============================================================================
+// 渲染
+// ============================================================================
+
+fn render_file(file: &FileOutline) -> String {
+    let mut text = format!("{}:\n", file.path);
+    for symbol in &file.symbols {
+        text.push_str(&format!("  {} {} (L{})\n", symbol.kind, symbol.name, symbol.line));
+    }
+    text
+}
+
+/// 按顺序放入预算内能完整展示的文件
+fn render_within_budget(source: String, files: &[FileOutline], max_tokens: usize) -> RepoMap {
+    let mut rendered = String::new();
+    let mut tokens = 0;
+    let mut shown = Vec::new();
+    for file in files {
+        let text = render_file(file);
+        let cost = estimate_tokens(&text);
+        if tokens + cost > max_tokens {
+            continue;
+        }
+        tokens += cost;
+        rendered.push_str(&text);
+        shown.push(file.clone());
+    }
+    RepoMap {
+        source,
+        omitted_files: files.len() - shown.len(),
+        files: shown,
+        tokens,
+        rendered,
+    }
+}
+
+/// 生成（或从缓存读取）项目的仓库地图；阻塞调用
+pub fn build_repo_map(project_path: &str, max_tokens: usize) -> Result<RepoMap, String> {
+    let root = Path::new(project_path);
+    if !root.is_dir() {
+        return Err(tr!("project.path_not_found", path = project_path));
+    }
+    let paths = list_candidate_paths(root);
+    let fingerprint = fingerprint(root, &paths);
+
+    let cached = OUTLINE_CACHE
+        .lock()
+        .unwrap()
+        .get(project_path)
+        .filter(|c| c.fingerprint == fingerprint)
+        .cloned();
+    let outline = match cached {
+        Some(outline) => outline,
+        None => {
+            let (source, files) = generate_outline(root, &paths);
+            log::info!("[RepoMap] Generated {} outline for {}: {} files", source, project_path, files.len());
+            let outline = CachedOutline { fingerprint, source, files };
+            OUTLINE_CACHE.lock().unwrap().insert(project_path.to_string(), outline.clone());
+            outline
+        }
+    };
+    Ok(render_within_budget(outline.source, &outline.files, max_tokens))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 获取项目的仓库地图（符号大纲），`max_tokens` 默认 1024
+#[tauri::command]
+pub async fn get_repo_map(project_path: String, max_tokens: Option<usize>) -> Result<RepoMap, String> {
+    let max_tokens = max_tokens.unwrap_or(DEFAULT_MAP_TOKENS);
+    tokio::task::spawn_blocking(move || build_repo_map(&project_path, max_tokens))
+        .await
+        .map_err(|e| format!("生成仓库地图失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_definitions_and_renders_within_budget() {
+        let rust = "pub struct Loader {\n}\n\nimpl Loader {\n    pub async fn load(&self) {}\n}\npub(crate) enum Mode { A }\n";
+        let names: Vec<(String, String)> = extract_definitions("src/loader.rs", rust)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.kind, s.name))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("struct".to_string(), "Loader".to_string()),
+                ("fn".to_string(), "load".to_string()),
+                ("enum".to_string(), "Mode".to_string()),
+            ]
+        );
+
+        let ts = "export default class App {}\nexport interface Props {}\nconst x = 1;\n";
+        assert_eq!(extract_definitions("src/App.tsx", ts).unwrap().len(), 2);
+        assert!(extract_definitions("README.md", "# Title").is_none());
+
+        let files = vec![
+            FileOutline { path: "src/a.rs".into(), symbols: vec![MapSymbol { name: "a".into(), kind: "fn".into(), line: 1 }] },
+            FileOutline {
+                path: "src/big.rs".into(),
+                symbols: (1..100).map(|i| MapSymbol { name: format!("f{}", i), kind: "fn".into(), line: i }).collect(),
+            },
+            FileOutline { path: "src/b.rs".into(), symbols: vec![MapSymbol { name: "b".into(), kind: "fn".into(), line: 3 }] },
+        ];
+        let map = render_within_budget("regex".into(), &files, 50);
+        assert_eq!(map.rendered, "src/a.rs:\n  fn a (L1)\nsrc/b.rs:\n  fn b (L3)\n");
+        assert_eq!(map.omitted_files, 1);
+        assert!(map.tokens <= 50);
+    }
+
+    #[test]
+    fn parses_ctags_json_lines() {
+        let output = r#"{"_type": "tag", "name": "Loader", "path": "src/loader.rs", "line": 3, "kind": "struct"}
+{"_type": "tag", "name": "count", "path": "src/loader.rs", "line": 9, "kind": "variable"}
+{"_type": "ptag", "name": "JSON_OUTPUT_VERSION"}"#;
+        let symbols = parse_ctags_output(output);
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols["src/loader.rs"], vec![MapSymbol { name: "Loader".into(), kind: "struct".into(), line: 3 }]);
+    }
+}
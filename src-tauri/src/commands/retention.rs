@@ -0,0 +1,749 @@
+//! 会话保留策略与自动清理
+//!
+//! 按策略清理超过保留天数、或超出总大小上限的会话、Codex 变更记录和执行日志：
+//! 会话可以归档（只在 `session_metadata` 中标记，CLI 的会话文件保留）或删除，变更记录和日志直接删除。
+//! 删除的会话文件和日志移入回收站（见 `trash.rs`），在回收站清理前仍可恢复。
+//! 超出大小上限时从最旧的开始清理；归档不释放空间，所以归档模式下只统计未归档的会话。
+//!
+//! 置顶会话、排除的引擎和排除的项目不参与清理（它们的变更记录和日志也保留）。
+//! Codex 的文件快照（见 `codex/snapshot_store.rs`）不单独设规则：每次清理时删除不再被 Git 记录引用、
+//! 或所属会话本次被删除的快照。
+//! 策略保存在 app_settings 的 `retention_policy` 键中，开启 `autoCleanup` 后每次启动执行一次。
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use super::app_settings::{get_setting, set_setting};
+use super::codex::change_store;
+use super::codex::git_ops::referenced_snapshot_ids;
+use super::codex::snapshot_store::{self, SnapshotPruneReport};
+use super::session_log::logs_root;
+use super::session_metadata;
+use super::storage::with_agent_db;
+use super::trash;
+
+pub const RETENTION_POLICY_KEY: &str = "retention_policy";
+
+const ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 会话的清理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    #[default]
+    Archive,
+    Delete,
+}
+
+/// 单类数据的保留规则（都为空表示不清理）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionRule {
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// 总大小上限（MB）
+    #[serde(default)]
+    pub max_total_mb: Option<u64>,
+}
+
+/// 保留策略
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    /// 启动时自动清理
+    #[serde(default)]
+    pub auto_cleanup: bool,
+    /// 会话的处理方式（变更记录和日志总是删除）
+    #[serde(default)]
+    pub session_action: RetentionAction,
+    #[serde(default)]
+    pub sessions: RetentionRule,
+    #[serde(default)]
+    pub change_records: RetentionRule,
+    #[serde(default)]
+    pub logs: RetentionRule,
+    #[serde(default)]
+    pub excluded_engines: Vec<String>,
+    #[serde(default)]
+    pub excluded_projects: Vec<String>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            auto_cleanup: false,
+            session_action: RetentionAction::Archive,
+            sessions: RetentionRule { max_age_days: Some(90), max_total_mb: None },
+            change_records: RetentionRule { max_age_days: Some(90), max_total_mb: None },
+            logs: RetentionRule { max_age_days: Some(30), max_total_mb: Some(500) },
+            excluded_engines: Vec::new(),
+            excluded_projects: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CleanupKind {
+    Session,
+    ChangeRecords,
+    Log,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CleanupReason {
+    /// 超过保留天数
+    Age,
+    /// 超出总大小上限
+    SizeCap,
+}
+
+impl RetentionPolicy {
+    fn rule(&self, kind: CleanupKind) -> &RetentionRule {
+        match kind {
+            CleanupKind::Session => &self.sessions,
+            CleanupKind::ChangeRecords => &self.change_records,
+            CleanupKind::Log => &self.logs,
+        }
+    }
+
+    fn action(&self, kind: CleanupKind) -> RetentionAction {
+        match kind {
+            CleanupKind::Session => self.session_action,
+            _ => RetentionAction::Delete,
+        }
+    }
+
+    fn excludes(&self, candidate: &Candidate) -> bool {
+        if self.excluded_engines.iter().any(|e| e.eq_ignore_ascii_case(&candidate.engine)) {
+            return true;
+        }
+        match candidate.project_path.as_deref() {
+            Some(project) => {
+                let project = normalize_path(project);
+                self.excluded_projects.iter().any(|p| normalize_path(p) == project)
+            }
+            None => false,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(engine) = self.excluded_engines.iter().find(|e| !ENGINES.contains(&e.as_str())) {
+            return Err(format!("不支持的引擎: {}", engine));
+        }
+        Ok(())
+    }
+}
+
+/// 与项目路径比较时使用的形式
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+/// 一条可清理的数据
+#[derive(Debug, Clone)]
+struct Candidate {
+    kind: CleanupKind,
+    engine: String,
+    session_id: String,
+    project_path: Option<String>,
+    /// Claude 项目目录名（删除会话时使用）
+    project_id: Option<String>,
+    /// 会话文件 / 日志文件（含滚动文件）；变更记录在数据库中，为空
+    files: Vec<PathBuf>,
+    size_bytes: u64,
+    modified: DateTime<Utc>,
+    archived: bool,
+}
+
+struct Planned {
+    candidate: Candidate,
+    reason: CleanupReason,
+    action: RetentionAction,
+}
+
+/// 预览中的一条
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupItem {
+    pub kind: CleanupKind,
+    pub engine: String,
+    pub session_id: String,
+    pub project_path: Option<String>,
+    pub size_bytes: u64,
+    pub modified_at: String,
+    pub action: RetentionAction,
+    pub reason: CleanupReason,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupPreview {
+    pub items: Vec<CleanupItem>,
+    pub archived_sessions: usize,
+    pub deleted_sessions: usize,
+    pub change_records: usize,
+    pub logs: usize,
+    /// 不再被引用的文件快照（清单数）
+    pub snapshots: usize,
+    /// 删除后释放的空间（归档不释放）
+    pub freed_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupResult {
+    pub archived: usize,
+    pub deleted: usize,
+    pub freed_bytes: u64,
+    pub errors: Vec<String>,
+}
+
+/// 按策略选出要清理的数据（每类从最旧的开始）
+fn plan(
+    policy: &RetentionPolicy,
+    candidates: Vec<Candidate>,
+    pinned: &HashSet<(String, String)>,
+    now: DateTime<Utc>,
+) -> Vec<Planned> {
+    let mut planned = Vec::new();
+    for kind in [CleanupKind::Session, CleanupKind::ChangeRecords, CleanupKind::Log] {
+        let rule = policy.rule(kind);
+        let action = policy.action(kind);
+        let mut items: Vec<&Candidate> = candidates
+            .iter()
+            .filter(|c| c.kind == kind)
+            .filter(|c| !(action == RetentionAction::Archive && c.archived))
+            .collect();
+        items.sort_by_key(|c| c.modified);
+
+        let cutoff = rule.max_age_days.map(|days| now - Duration::days(days as i64));
+        let cap = rule.max_total_mb.map(|mb| mb * 1024 * 1024);
+        let mut total: u64 = items.iter().map(|c| c.size_bytes).sum();
+
+        for candidate in items {
+            if pinned.contains(&(candidate.engine.clone(), candidate.session_id.clone())) || policy.excludes(candidate) {
+                continue;
+            }
+            let reason = if cutoff.is_some_and(|cutoff| candidate.modified < cutoff) {
+                CleanupReason::Age
+            } else if cap.is_some_and(|cap| total > cap) {
+                CleanupReason::SizeCap
+            } else {
+                continue;
+            };
+            total = total.saturating_sub(candidate.size_bytes);
+            planned.push(Planned {
+                candidate: candidate.clone(),
+                reason,
+                action,
+            });
+        }
+    }
+    planned
+}
+
+fn to_preview(planned: &[Planned]) -> CleanupPreview {
+    let mut preview = CleanupPreview::default();
+    for p in planned {
+        let c = &p.candidate;
+        match (c.kind, p.action) {
+            (CleanupKind::Session, RetentionAction::Archive) => preview.archived_sessions += 1,
+            (CleanupKind::Session, RetentionAction::Delete) => preview.deleted_sessions += 1,
+            (CleanupKind::ChangeRecords, _) => preview.change_records += 1,
+            (CleanupKind::Log, _) => preview.logs += 1,
+        }
+        if p.action == RetentionAction::Delete {
+            preview.freed_bytes += c.size_bytes;
+        }
+        preview.items.push(CleanupItem {
+            kind: c.kind,
+            engine: c.engine.clone(),
+            session_id: c.session_id.clone(),
+            project_path: c.project_path.clone(),
+            size_bytes: c.size_bytes,
+            modified_at: c.modified.to_rfc3339(),
+            action: p.action,
+            reason: p.reason,
+        });
+    }
+    preview
+}
+
+// ============================================================================
+// 收集
+// ============================================================================
+
+fn file_info(path: &PathBuf) -> Option<(u64, DateTime<Utc>)> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    Some((meta.len(), DateTime::<Utc>::from(modified)))
+}
+
+fn session_candidate(
+    engine: &str,
+    session_id: String,
+    project_path: Option<String>,
+    project_id: Option<String>,
+    path: PathBuf,
+) -> Option<Candidate> {
+    let (size_bytes, modified) = file_info(&path)?;
+    Some(Candidate {
+        kind: CleanupKind::Session,
+        engine: engine.to_string(),
+        session_id,
+        project_path,
+        project_id,
+        files: vec![path],
+        size_bytes,
+        modified,
+        archived: false,
+    })
+}
+
+fn collect_claude(projects: Vec<super::claude::Project>) -> Result<Vec<Candidate>, String> {
+    let projects_dir = super::claude::get_claude_dir()
+        .map_err(|e| format!("无法获取 Claude 目录: {}", e))?
+        .join("projects");
+    Ok(projects
+        .into_iter()
+        .flat_map(|project| {
+            let dir = projects_dir.join(&project.id);
+            project.sessions.into_iter().filter_map(move |session_id| {
+                let path = dir.join(format!("{}.jsonl", session_id));
+                session_candidate("claude", session_id, Some(project.path.clone()), Some(project.id.clone()), path)
+            })
+        })
+        .collect())
+}
+
+fn collect_codex() -> Result<Vec<Candidate>, String> {
+    Ok(super::codex::config::get_all_codex_sessions_dirs()?
+        .iter()
+        .flat_map(|dir| walkdir::WalkDir::new(dir).min_depth(4).max_depth(4))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
+        .filter_map(|e| {
+            let session = super::codex::parse_codex_session_file(e.path())?;
+            session_candidate("codex", session.id, Some(session.project_path), None, e.into_path())
+        })
+        .collect())
+}
+
+/// Gemini 的会话目录以项目路径的哈希命名，只能通过已知的项目路径反查
+fn collect_gemini(known_projects: &HashSet<String>) -> Result<Vec<Candidate>, String> {
+    let tmp_dir = super::gemini::config::get_gemini_dir()?.join("tmp");
+    if !tmp_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let by_hash: HashMap<String, &String> = known_projects
+        .iter()
+        .map(|path| (super::gemini::config::hash_project_path(path), path))
+        .collect();
+
+    Ok(walkdir::WalkDir::new(&tmp_dir)
+        .min_depth(3)
+        .max_depth(3)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|e| {
+            let path = e.into_path();
+            let project_hash = path.parent()?.parent()?.file_name()?.to_string_lossy().to_string();
+            let detail = super::gemini::config::read_session_detail_from_path(&path).ok()?;
+            let project_path = by_hash.get(&project_hash).map(|p| p.to_string());
+            session_candidate("gemini", detail.session_id, project_path, None, path)
+        })
+        .collect())
+}
+
+/// 执行日志按会话分组（`<id>.log` 与滚动的 `<id>.log.N`）
+fn collect_logs(projects: &HashMap<(String, String), String>) -> Result<Vec<Candidate>, String> {
+    let root = logs_root()?;
+    let mut candidates = Vec::new();
+    for engine in ENGINES {
+        let Ok(entries) = fs::read_dir(root.join(engine)) else {
+            continue;
+        };
+        let mut groups: HashMap<String, Candidate> = HashMap::new();
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let Some(index) = name.find(".log") else {
+                continue;
+            };
+            let Some((size, modified)) = file_info(&path) else {
+                continue;
+            };
+            let session_id = name[..index].to_string();
+            let group = groups.entry(session_id.clone()).or_insert_with(|| Candidate {
+                kind: CleanupKind::Log,
+                engine: engine.to_string(),
+                project_path: projects.get(&(engine.to_string(), session_id.clone())).cloned(),
+                session_id,
+                project_id: None,
+                files: Vec::new(),
+                size_bytes: 0,
+                modified,
+                archived: false,
+            });
+            group.files.push(path);
+            group.size_bytes += size;
+            group.modified = group.modified.max(modified);
+        }
+        candidates.extend(groups.into_values());
+    }
+    Ok(candidates)
+}
+
+fn collect_change_records(conn: &rusqlite::Connection) -> Result<Vec<Candidate>, String> {
+    change_store::ensure_schema(conn)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.session_id, s.project_path, s.updated_at, COALESCE(SUM(LENGTH(r.payload)), 0)
+             FROM codex_change_sessions s
+             LEFT JOIN codex_change_records r ON r.session_id = s.session_id
+             GROUP BY s.session_id",
+        )
+        .map_err(|e| format!("查询变更记录失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| format!("查询变更记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取变更记录失败: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(session_id, project_path, updated_at, size)| Candidate {
+            kind: CleanupKind::ChangeRecords,
+            engine: "codex".to_string(),
+            session_id,
+            project_path: Some(project_path),
+            project_id: None,
+            files: Vec::new(),
+            size_bytes: size.max(0) as u64,
+            modified: DateTime::parse_from_rfc3339(&updated_at)
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_default(),
+            archived: false,
+        })
+        .collect())
+}
+
+/// 收集所有候选数据，返回 (候选, 置顶会话)
+async fn collect(policy: &RetentionPolicy) -> Result<(Vec<Candidate>, HashSet<(String, String)>), String> {
+    let claude_projects = super::claude::list_projects().await.map_err(|e| e.to_string())?;
+    let excluded_projects = policy.excluded_projects.clone();
+
+    let (mut candidates, flags) = with_agent_db(move |conn| {
+        session_metadata::ensure_schema(conn)?;
+        let flags = session_metadata::load_session_flags(conn)?;
+        Ok((collect_change_records(conn)?, flags))
+    })
+    .await?;
+
+    let sessions = tokio::task::spawn_blocking(move || -> Result<Vec<Candidate>, String> {
+        let mut sessions = collect_claude(claude_projects)?;
+        sessions.extend(collect_codex()?);
+        let known_projects: HashSet<String> = sessions
+            .iter()
+            .filter_map(|c| c.project_path.clone())
+            .chain(excluded_projects)
+            .collect();
+        sessions.extend(collect_gemini(&known_projects)?);
+
+        let projects = sessions
+            .iter()
+            .filter_map(|c| Some(((c.engine.clone(), c.session_id.clone()), c.project_path.clone()?)))
+            .collect();
+        sessions.extend(collect_logs(&projects)?);
+        Ok(sessions)
+    })
+    .await
+    .map_err(|e| format!("收集会话失败: {}", e))??;
+    candidates.extend(sessions);
+
+    let mut pinned = HashSet::new();
+    for candidate in candidates.iter_mut() {
+        if let Some((is_pinned, archived)) = flags.get(&(candidate.engine.clone(), candidate.session_id.clone())) {
+            candidate.archived = *archived;
+            if *is_pinned {
+                pinned.insert((candidate.engine.clone(), candidate.session_id.clone()));
+            }
+        }
+    }
+    Ok((candidates, pinned))
+}
+
+/// 清理不再被引用的 Codex 文件快照；本次删除的 Codex 会话的快照视为不再引用
+async fn prune_snapshots(planned: &[Planned], dry_run: bool) -> Result<SnapshotPruneReport, String> {
+    let deleted_sessions: HashSet<String> = planned
+        .iter()
+        .filter(|p| p.candidate.kind == CleanupKind::Session && p.action == RetentionAction::Delete)
+        .filter(|p| p.candidate.engine == "codex")
+        .map(|p| p.candidate.session_id.clone())
+        .collect();
+    tokio::task::spawn_blocking(move || {
+        let referenced = referenced_snapshot_ids(&deleted_sessions)?;
+        snapshot_store::prune_snapshots(&referenced, dry_run)
+    })
+    .await
+    .map_err(|e| format!("清理文件快照失败: {}", e))?
+}
+
+// ============================================================================
+// 执行
+// ============================================================================
+
+async fn execute(planned: Vec<Planned>) -> CleanupResult {
+    let mut result = CleanupResult::default();
+    let mut to_archive = Vec::new();
+    let mut change_sessions = Vec::new();
+
+    for Planned { candidate, action, .. } in planned {
+        match (candidate.kind, action) {
+            (CleanupKind::Session, RetentionAction::Archive) => {
+                to_archive.push((candidate.engine, candidate.session_id));
+            }
+            (CleanupKind::ChangeRecords, _) => change_sessions.push((candidate.session_id, candidate.size_bytes)),
+            (CleanupKind::Session, RetentionAction::Delete) if candidate.engine == "claude" => {
+                // 同时删除 TODO 和 Git 记录
+                let project_id = candidate.project_id.clone().unwrap_or_default();
+                match super::claude::delete_session(candidate.session_id.clone(), project_id).await {
+                    Ok(_) => {
+                        result.deleted += 1;
+                        result.freed_bytes += candidate.size_bytes;
+                    }
+                    Err(e) => result.errors.push(format!("{}: {}", candidate.session_id, e)),
+                }
+            }
+            _ => match candidate
+                .files
+                .iter()
+                .try_for_each(|path| trash::trash_data_file(path, Some("retention")).map(|_| ()))
+            {
+                Ok(()) => {
+                    result.deleted += 1;
+                    result.freed_bytes += candidate.size_bytes;
+                }
+                Err(e) => result.errors.push(format!("{}: {}", candidate.session_id, e)),
+            },
+        }
+    }
+
+    if !to_archive.is_empty() || !change_sessions.is_empty() {
+        let outcome = with_agent_db(move |conn| {
+            let mut outcome = CleanupResult::default();
+            for (engine, session_id) in &to_archive {
+                match session_metadata::set_archived(conn, engine, session_id, true) {
+                    Ok(()) => outcome.archived += 1,
+                    Err(e) => outcome.errors.push(format!("{}: {}", session_id, e)),
+                }
+            }
+            for (session_id, size) in &change_sessions {
+                match change_store::delete_session(conn, session_id) {
+                    Ok(()) => {
+                        outcome.deleted += 1;
+                        outcome.freed_bytes += size;
+                    }
+                    Err(e) => outcome.errors.push(format!("{}: {}", session_id, e)),
+                }
+            }
+            Ok(outcome)
+        })
+        .await;
+        match outcome {
+            Ok(outcome) => {
+                result.archived += outcome.archived;
+                result.deleted += outcome.deleted;
+                result.freed_bytes += outcome.freed_bytes;
+                result.errors.extend(outcome.errors);
+            }
+            Err(e) => result.errors.push(e),
+        }
+    }
+    result
+}
+
+async fn resolve_policy(policy: Option<RetentionPolicy>) -> Result<RetentionPolicy, String> {
+    let policy = match policy {
+        Some(policy) => policy,
+        None => get_retention_policy().await?,
+    };
+    policy.validate()?;
+    Ok(policy)
+}
+
+async fn cleanup(policy: &RetentionPolicy) -> Result<CleanupResult, String> {
+    let (candidates, pinned) = collect(policy).await?;
+    let planned = plan(policy, candidates, &pinned, Utc::now());
+    let snapshots = prune_snapshots(&planned, false).await;
+    let mut result = execute(planned).await;
+    match snapshots {
+        Ok(snapshots) => {
+            result.deleted += snapshots.manifests;
+            result.freed_bytes += snapshots.freed_bytes;
+        }
+        Err(e) => result.errors.push(e),
+    }
+    log::info!(
+        "[Retention] Archived {} sessions, deleted {} items, freed {} bytes ({} errors)",
+        result.archived,
+        result.deleted,
+        result.freed_bytes,
+        result.errors.len()
+    );
+    Ok(result)
+}
+
+/// 启动时按保存的策略自动清理（未开启时不做任何事）
+pub async fn run_auto_cleanup() {
+    let policy = match get_retention_policy().await {
+        Ok(policy) => policy,
+        Err(e) => {
+            log::warn!("[Retention] Failed to load retention policy: {}", e);
+            return;
+        }
+    };
+    if !policy.auto_cleanup {
+        return;
+    }
+    if let Err(e) = cleanup(&policy).await {
+        log::warn!("[Retention] Auto cleanup failed: {}", e);
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 读取保留策略（未保存过时返回默认策略）
+#[tauri::command]
+pub async fn get_retention_policy() -> Result<RetentionPolicy, String> {
+    Ok(get_setting(RETENTION_POLICY_KEY).await?.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn save_retention_policy(app: AppHandle, policy: RetentionPolicy) -> Result<(), String> {
+    policy.validate()?;
+    set_setting(&app, RETENTION_POLICY_KEY, &policy).await
+}
+
+/// 预览将被清理的数据（不做任何改动）；不传策略时使用已保存的策略
+#[tauri::command]
+pub async fn preview_cleanup(policy: Option<RetentionPolicy>) -> Result<CleanupPreview, String> {
+    let policy = resolve_policy(policy).await?;
+    let (candidates, pinned) = collect(&policy).await?;
+    let planned = plan(&policy, candidates, &pinned, Utc::now());
+    let mut preview = to_preview(&planned);
+    let snapshots = prune_snapshots(&planned, true).await?;
+    preview.snapshots = snapshots.manifests;
+    preview.freed_bytes += snapshots.freed_bytes;
+    Ok(preview)
+}
+
+/// 立即按策略清理；不传策略时使用已保存的策略
+#[tauri::command]
+pub async fn run_cleanup(policy: Option<RetentionPolicy>) -> Result<CleanupResult, String> {
+    let policy = resolve_policy(policy).await?;
+    cleanup(&policy).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(kind: CleanupKind, engine: &str, id: &str, project: &str, days_old: i64, mb: u64) -> Candidate {
+        Candidate {
+            kind,
+            engine: engine.to_string(),
+            session_id: id.to_string(),
+            project_path: Some(project.to_string()),
+            project_id: None,
+            files: Vec::new(),
+            size_bytes: mb * 1024 * 1024,
+            modified: Utc::now() - Duration::days(days_old),
+            archived: false,
+        }
+    }
+
+    fn planned_ids(planned: &[Planned], kind: CleanupKind) -> Vec<(&str, CleanupReason)> {
+        planned
+            .iter()
+            .filter(|p| p.candidate.kind == kind)
+            .map(|p| (p.candidate.session_id.as_str(), p.reason))
+            .collect()
+    }
+
+    #[test]
+    fn plans_by_age_and_size_cap_with_exclusions() {
+        let policy = RetentionPolicy {
+            session_action: RetentionAction::Archive,
+            sessions: RetentionRule { max_age_days: Some(30), max_total_mb: Some(10) },
+            change_records: RetentionRule::default(),
+            logs: RetentionRule { max_age_days: None, max_total_mb: Some(5) },
+            excluded_engines: vec!["gemini".into()],
+            excluded_projects: vec!["C:\\work\\keep\\".into()],
+            ..Default::default()
+        };
+        let mut already_archived = candidate(CleanupKind::Session, "claude", "archived", "/p", 100, 4);
+        already_archived.archived = true;
+        let candidates = vec![
+            candidate(CleanupKind::Session, "claude", "old", "/p", 60, 1),
+            candidate(CleanupKind::Session, "claude", "pinned", "/p", 60, 1),
+            candidate(CleanupKind::Session, "codex", "kept-project", "c:/work/keep", 60, 1),
+            candidate(CleanupKind::Session, "gemini", "kept-engine", "/p", 60, 1),
+            already_archived,
+            candidate(CleanupKind::Session, "codex", "big", "/p", 10, 6),
+            candidate(CleanupKind::Session, "codex", "recent", "/p", 1, 4),
+            candidate(CleanupKind::ChangeRecords, "codex", "c1", "/p", 400, 1),
+            candidate(CleanupKind::Log, "codex", "l1", "/p", 3, 3),
+            candidate(CleanupKind::Log, "codex", "l2", "/p", 2, 3),
+            candidate(CleanupKind::Log, "codex", "pinned", "/p", 9, 3),
+        ];
+        let pinned = HashSet::from([
+            ("claude".to_string(), "pinned".to_string()),
+            ("codex".to_string(), "pinned".to_string()),
+        ]);
+
+        let planned = plan(&policy, candidates, &pinned, Utc::now());
+        // 活跃会话共 14MB：old 按时间归档后 13MB，big 按上限归档后 7MB；已归档的不再计入
+        assert_eq!(
+            planned_ids(&planned, CleanupKind::Session),
+            vec![("old", CleanupReason::Age), ("big", CleanupReason::SizeCap)]
+        );
+        assert!(planned
+            .iter()
+            .filter(|p| p.candidate.kind == CleanupKind::Session)
+            .all(|p| p.action == RetentionAction::Archive));
+        assert!(planned_ids(&planned, CleanupKind::ChangeRecords).is_empty());
+        // 日志共 9MB：置顶会话的日志保留但计入总量
+        assert_eq!(
+            planned_ids(&planned, CleanupKind::Log),
+            vec![("l1", CleanupReason::SizeCap), ("l2", CleanupReason::SizeCap)]
+        );
+
+        let preview = to_preview(&planned);
+        assert_eq!((preview.archived_sessions, preview.deleted_sessions, preview.logs), (2, 0, 2));
+        assert_eq!(preview.freed_bytes, 6 * 1024 * 1024);
+
+        // 删除模式下已归档的会话同样会被清理
+        let deleting = RetentionPolicy { session_action: RetentionAction::Delete, ..policy };
+        let mut archived = candidate(CleanupKind::Session, "claude", "archived", "/p", 100, 4);
+        archived.archived = true;
+        let planned = plan(&deleting, vec![archived], &HashSet::new(), Utc::now());
+        assert_eq!(planned_ids(&planned, CleanupKind::Session), vec![("archived", CleanupReason::Age)]);
+    }
+}
@@ -0,0 +1,721 @@
+//! 本地语义索引（可选）
+//!
+//! 把项目文件按行切块，通过 OpenAI 兼容的 embeddings 接口（云端服务，或 Ollama
+//! 等本地模型）计算向量，存入 agents.db；`semantic_search` 按余弦相似度返回最相关的代码块。
+//!
+//! - 增量索引：按修改时间和大小判断文件是否变化，只处理变化的文件；内容未变的代码块复用已有向量
+//! - 搜索时在后台触发增量更新（同一项目 30 秒内最多一次），搜索本身只读已有索引，
+//!   首次使用时建立索引期间结果为空；也可以启动文件监听，保存后在后台更新
+//! - 启用后 `build_context` 会用搜索结果补充关键词相关度
+//!
+//! 配置保存在 `~/.anycode/semantic_index.json`，默认关闭。
+
+use crate::tr;
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebouncedEvent, Debouncer};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::context_manager::list_candidate_paths;
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+use super::url_utils::{normalize_api_url, ApiEndpointType};
+use super::data_root::anycode_dir;
+
+/// 每个代码块的行数及相邻块的重叠行数
+const CHUNK_LINES: usize = 40;
+const CHUNK_OVERLAP: usize = 8;
+
+/// 单个代码块的最大字符数（超长行较多时截断）
+const MAX_CHUNK_CHARS: usize = 4000;
+
+/// 超过此大小的文件不建索引
+const MAX_INDEX_FILE_BYTES: u64 = 256 * 1024;
+
+/// 搜索前自动增量更新的最小间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 文件监听的防抖间隔
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// 为 `build_context` 提供相关度时检索的代码块数
+const CONTEXT_SEARCH_K: usize = 20;
+
+/// 语义索引配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticIndexConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// OpenAI 兼容的 API 地址（如 `http://localhost:11434/v1` 使用本地 Ollama）
+    pub api_base_url: String,
+    /// 本地模型可留空
+    #[serde(default)]
+    pub api_key: String,
+    pub model: String,
+    /// 每次请求的代码块数
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_batch_size() -> usize {
+    32
+}
+
+impl Default for SemanticIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_base_url: "http://localhost:11434/v1".to_string(),
+            api_key: String::new(),
+            model: "nomic-embed-text".to_string(),
+            batch_size: default_batch_size(),
+        }
+    }
+}
+
+/// 切分出的代码块
+#[derive(Debug, Clone, PartialEq)]
+struct Chunk {
+    start_line: usize,
+    end_line: usize,
+    content: String,
+}
+
+/// 搜索命中的代码块
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticMatch {
+    /// 相对项目根目录的路径
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// 余弦相似度（-1.0 ~ 1.0）
+    pub score: f32,
+    pub content: String,
+}
+
+/// 一次（增量）索引的统计
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStats {
+    pub files_indexed: usize,
+    pub files_removed: usize,
+    /// 本次新计算向量的代码块数
+    pub chunks_embedded: usize,
+    /// 复用已有向量的代码块数
+    pub chunks_reused: usize,
+    /// 索引中的文件总数
+    pub total_files: usize,
+}
+
+/// 索引中一个文件的状态（修改时间秒数、大小）
+type FileStamp = (i64, i64);
+
+static LAST_REFRESH: Lazy<std::sync::Mutex<HashMap<String, Instant>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// 每个项目同一时间只运行一次索引，避免重复计算向量（不同项目互不阻塞）
+static INDEX_LOCKS: Lazy<std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+static WATCHERS: Lazy<std::sync::Mutex<HashMap<String, Debouncer<RecommendedWatcher>>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+// ============================================================================
+// 配置
+// ============================================================================
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("semantic_index.json"))
+}
+
+pub fn load_config() -> SemanticIndexConfig {
+    let Ok(path) = get_config_path() else {
+        return SemanticIndexConfig::default();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return SemanticIndexConfig::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!("[SemanticIndex] Failed to parse {:?}: {}", path, e);
+        SemanticIndexConfig::default()
+    })
+}
+
+fn save_config(config: &SemanticIndexConfig) -> Result<(), String> {
+    let path = get_config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let content =
+        serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize semantic index config: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write semantic index config: {}", e))
+}
+
+// ============================================================================
+// 存储
+// ============================================================================
+
+/// 创建语义索引表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS semantic_files (
+            project_path TEXT NOT NULL,
+            path TEXT NOT NULL,
+            mtime INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            PRIMARY KEY (project_path, path)
+        );
+        CREATE TABLE IF NOT EXISTS semantic_chunks (
+            project_path TEXT NOT NULL,
+            path TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            model TEXT NOT NULL,
+            embedding BLOB NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_semantic_chunks_project ON semantic_chunks(project_path, path);",
+    )
+    .map_err(|e| format!("创建语义索引表失败: {}", e))
+}
+
+fn open_index_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// 归一化为单位向量，之后余弦相似度即点积
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+fn load_file_stamps(conn: &Connection, project_path: &str) -> Result<HashMap<String, FileStamp>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, mtime, size FROM semantic_files WHERE project_path = ?1")
+        .map_err(|e| format!("查询语义索引失败: {}", e))?;
+    let stamps = stmt
+        .query_map(params![project_path], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+        .map_err(|e| format!("查询语义索引失败: {}", e))?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("读取语义索引失败: {}", e))?;
+    Ok(stamps)
+}
+
+/// 索引中是否有其他模型生成的向量（切换模型后需要全部重建）
+fn has_stale_model(conn: &Connection, project_path: &str, model: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM semantic_chunks WHERE project_path = ?1 AND model != ?2)",
+        params![project_path, model],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("查询语义索引失败: {}", e))
+}
+
+/// 当前模型下已有的向量（按内容哈希），用于复用未变化的代码块
+fn load_known_embeddings(conn: &Connection, project_path: &str, model: &str) -> Result<HashMap<String, Vec<u8>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT content_hash, embedding FROM semantic_chunks WHERE project_path = ?1 AND model = ?2")
+        .map_err(|e| format!("查询语义索引失败: {}", e))?;
+    let known = stmt
+        .query_map(params![project_path, model], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("查询语义索引失败: {}", e))?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("读取语义索引失败: {}", e))?;
+    Ok(known)
+}
+
+// ============================================================================
+// 切块与增量计划
+// ============================================================================
+
+/// 按行切分为有重叠的代码块，跳过空白块
+fn chunk_content(content: &str) -> Vec<Chunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + CHUNK_LINES).min(lines.len());
+        let text: String = lines[start..end].join("\n").chars().take(MAX_CHUNK_CHARS).collect();
+        if !text.trim().is_empty() {
+            chunks.push(Chunk { start_line: start + 1, end_line: end, content: text });
+        }
+        if end == lines.len() {
+            break;
+        }
+        start = end - CHUNK_OVERLAP;
+    }
+    chunks
+}
+
+/// 对比索引中的文件状态与当前文件，返回 (需要重新索引的文件, 已删除的文件)
+fn plan_reindex(
+    indexed: &HashMap<String, FileStamp>,
+    current: &HashMap<String, FileStamp>,
+    full: bool,
+) -> (Vec<String>, Vec<String>) {
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(path, stamp)| full || indexed.get(*path) != Some(*stamp))
+        .map(|(path, _)| path.clone())
+        .collect();
+    let mut removed: Vec<String> = indexed.keys().filter(|path| !current.contains_key(*path)).cloned().collect();
+    changed.sort();
+    removed.sort();
+    (changed, removed)
+}
+
+fn current_file_stamps(root: &Path) -> HashMap<String, FileStamp> {
+    list_candidate_paths(root)
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = fs::metadata(root.join(&path)).ok()?;
+            if !metadata.is_file() || metadata.len() > MAX_INDEX_FILE_BYTES {
+                return None;
+            }
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            Some((path, (mtime, metadata.len() as i64)))
+        })
+        .collect()
+}
+
+/// 读取文本文件并切块；二进制、非 UTF-8 文件返回空
+fn load_chunks(root: &Path, path: &str) -> Vec<Chunk> {
+    let Ok(bytes) = fs::read(root.join(path)) else {
+        return Vec::new();
+    };
+    if bytes.iter().take(8192).any(|b| *b == 0) {
+        return Vec::new();
+    }
+    String::from_utf8(bytes).map(|content| chunk_content(&content)).unwrap_or_default()
+}
+
+// ============================================================================
+// Embeddings
+// ============================================================================
+
+/// 调用 embeddings 接口，返回与输入一一对应的单位向量
+async fn embed_texts(config: &SemanticIndexConfig, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let url = normalize_api_url(&config.api_base_url, ApiEndpointType::Embeddings);
+
+    let mut vectors = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(config.batch_size.max(1)) {
+        let mut request = client.post(&url).json(&serde_json::json!({
+            "model": config.model,
+            "input": batch,
+        }));
+        if !config.api_key.is_empty() {
+            request = request.bearer_auth(&config.api_key);
+        }
+        let response = request.send().await.map_err(|e| format!("请求 embeddings 接口失败: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Embeddings API error: {} - {}", status, body));
+        }
+        let body: serde_json::Value = response.json().await.map_err(|e| format!("解析 embeddings 响应失败: {}", e))?;
+        let mut data: Vec<(usize, Vec<f32>)> = body["data"]
+            .as_array()
+            .ok_or("Invalid embeddings response format")?
+            .iter()
+            .enumerate()
+            .map(|(position, item)| {
+                let index = item["index"].as_u64().map(|i| i as usize).unwrap_or(position);
+                let vector = item["embedding"]
+                    .as_array()
+                    .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+                    .unwrap_or_default();
+                (index, vector)
+            })
+            .collect();
+        if data.len() != batch.len() {
+            return Err(format!("Embeddings 数量不匹配: 请求 {} 个，返回 {} 个", batch.len(), data.len()));
+        }
+        data.sort_by_key(|(index, _)| *index);
+        vectors.extend(data.into_iter().map(|(_, vector)| normalize(vector)));
+    }
+    Ok(vectors)
+}
+
+// ============================================================================
+// 索引与搜索
+// ============================================================================
+
+/// 待写入的文件：路径、状态和代码块（含已有或待计算的向量）
+struct PendingFile {
+    path: String,
+    stamp: FileStamp,
+    chunks: Vec<(Chunk, String, Option<Vec<u8>>)>,
+}
+
+async fn with_index_db<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&mut *open_index_db()?))
+        .await
+        .map_err(|e| format!("访问语义索引失败: {}", e))?
+}
+
+fn project_lock(project_path: &str) -> Arc<tokio::sync::Mutex<()>> {
+    INDEX_LOCKS
+        .lock()
+        .unwrap()
+        .entry(project_path.to_string())
+        .or_default()
+        .clone()
+}
+
+/// 在后台增量更新索引（该项目正在索引时排在其后）
+fn refresh_in_background(project_path: &str) {
+    let project = project_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = reindex_project(&project, false).await {
+            log::warn!("[SemanticIndex] Background reindex failed for {}: {}", project, e);
+        }
+    });
+}
+
+/// 增量更新项目索引；`full` 为 true 时重新处理所有文件（仍复用内容未变的向量）
+pub async fn reindex_project(project_path: &str, full: bool) -> Result<IndexStats, String> {
+    let config = load_config();
+    if !config.enabled {
+        return Err("语义索引未启用".to_string());
+    }
+    if !Path::new(project_path).is_dir() {
+        return Err(tr!("project.path_not_found", path = project_path));
+    }
+    let lock = project_lock(project_path);
+    let _guard = lock.lock().await;
+
+    let project = project_path.to_string();
+    let model = config.model.clone();
+    let (mut pending, removed, total_files) = with_index_db(move |conn| {
+        let root = Path::new(&project);
+        let current = current_file_stamps(root);
+        let indexed = load_file_stamps(conn, &project)?;
+        let full = full || has_stale_model(conn, &project, &model)?;
+        let (changed, removed) = plan_reindex(&indexed, &current, full);
+        let known = if changed.is_empty() {
+            HashMap::new()
+        } else {
+            load_known_embeddings(conn, &project, &model)?
+        };
+        let pending: Vec<PendingFile> = changed
+            .into_iter()
+            .map(|path| {
+                let chunks = load_chunks(root, &path)
+                    .into_iter()
+                    .map(|chunk| {
+                        let hash = content_hash(&chunk.content);
+                        let embedding = known.get(&hash).cloned();
+                        (chunk, hash, embedding)
+                    })
+                    .collect();
+                PendingFile { stamp: current[&path], path, chunks }
+            })
+            .collect();
+        Ok((pending, removed, current.len()))
+    })
+    .await?;
+
+    let mut stats = IndexStats { files_indexed: pending.len(), files_removed: removed.len(), total_files, ..Default::default() };
+    if pending.is_empty() && removed.is_empty() {
+        return Ok(stats);
+    }
+
+    // 只为新的代码块计算向量
+    let missing: Vec<String> = pending
+        .iter()
+        .flat_map(|file| file.chunks.iter())
+        .filter(|(_, _, embedding)| embedding.is_none())
+        .map(|(chunk, _, _)| format!("{}\n", chunk.content))
+        .collect();
+    stats.chunks_embedded = missing.len();
+    stats.chunks_reused = pending.iter().map(|f| f.chunks.len()).sum::<usize>() - missing.len();
+    let mut vectors = embed_texts(&config, &missing).await?.into_iter();
+    for (_, _, embedding) in pending.iter_mut().flat_map(|file| file.chunks.iter_mut()) {
+        if embedding.is_none() {
+            *embedding = vectors.next().map(|v| encode_vector(&v));
+        }
+    }
+
+    let project = project_path.to_string();
+    let model = config.model.clone();
+    with_index_db(move |conn| {
+        let tx = conn.transaction().map_err(|e| format!("开启事务失败: {}", e))?;
+        for path in removed.iter().chain(pending.iter().map(|f| &f.path)) {
+            tx.execute("DELETE FROM semantic_chunks WHERE project_path = ?1 AND path = ?2", params![project, path])
+                .and_then(|_| {
+                    tx.execute("DELETE FROM semantic_files WHERE project_path = ?1 AND path = ?2", params![project, path])
+                })
+                .map_err(|e| format!("更新语义索引失败: {}", e))?;
+        }
+        for file in &pending {
+            tx.execute(
+                "INSERT INTO semantic_files (project_path, path, mtime, size) VALUES (?1, ?2, ?3, ?4)",
+                params![project, file.path, file.stamp.0, file.stamp.1],
+            )
+            .map_err(|e| format!("更新语义索引失败: {}", e))?;
+            for (chunk, hash, embedding) in &file.chunks {
+                let Some(embedding) = embedding else { continue };
+                tx.execute(
+                    "INSERT INTO semantic_chunks (project_path, path, start_line, end_line, content, content_hash, model, embedding)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![project, file.path, chunk.start_line, chunk.end_line, chunk.content, hash, model, embedding],
+                )
+                .map_err(|e| format!("更新语义索引失败: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("提交语义索引失败: {}", e))
+    })
+    .await?;
+
+    log::info!(
+        "[SemanticIndex] Reindexed {}: {} files updated, {} removed, {} chunks embedded, {} reused",
+        project_path,
+        stats.files_indexed,
+        stats.files_removed,
+        stats.chunks_embedded,
+        stats.chunks_reused
+    );
+    Ok(stats)
+}
+
+/// 按与查询向量的相似度排序，返回前 k 个
+fn rank_chunks(query: &[f32], chunks: Vec<(String, usize, usize, String, Vec<u8>)>, k: usize) -> Vec<SemanticMatch> {
+    let mut matches: Vec<SemanticMatch> = chunks
+        .into_iter()
+        .map(|(path, start_line, end_line, content, embedding)| {
+            let score = decode_vector(&embedding).iter().zip(query).map(|(a, b)| a * b).sum();
+            SemanticMatch { path, start_line, end_line, score, content }
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    matches.truncate(k);
+    matches
+}
+
+/// 语义搜索；按需在后台增量更新索引，本次搜索使用已有索引
+pub async fn search(project_path: &str, query: &str, k: usize) -> Result<Vec<SemanticMatch>, String> {
+    let config = load_config();
+    if !config.enabled {
+        return Err("语义索引未启用".to_string());
+    }
+    let stale = {
+        let mut last_refresh = LAST_REFRESH.lock().unwrap();
+        let stale = last_refresh
+            .get(project_path)
+            .is_none_or(|last| last.elapsed() >= REFRESH_INTERVAL);
+        if stale {
+            last_refresh.insert(project_path.to_string(), Instant::now());
+        }
+        stale
+    };
+    if stale {
+        refresh_in_background(project_path);
+    }
+
+    let query_vector = embed_texts(&config, &[query.to_string()])
+        .await?
+        .pop()
+        .ok_or("Embeddings 接口未返回查询向量")?;
+    let project = project_path.to_string();
+    let model = config.model.clone();
+    let chunks = with_index_db(move |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, start_line, end_line, content, embedding FROM semantic_chunks
+                 WHERE project_path = ?1 AND model = ?2",
+            )
+            .map_err(|e| format!("查询语义索引失败: {}", e))?;
+        let rows = stmt
+            .query_map(params![project, model], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })
+            .map_err(|e| format!("查询语义索引失败: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("读取语义索引失败: {}", e))?;
+        Ok(rows)
+    })
+    .await?;
+    Ok(rank_chunks(&query_vector, chunks, k))
+}
+
+/// 供 `build_context` 使用：每个文件的最高相似度（0.0-1.0）。未启用或失败时返回 None
+pub async fn file_relevance(project_path: &str, prompt: &str) -> Option<HashMap<String, f64>> {
+    if !load_config().enabled {
+        return None;
+    }
+    match search(project_path, prompt, CONTEXT_SEARCH_K).await {
+        Ok(matches) => {
+            let mut scores: HashMap<String, f64> = HashMap::new();
+            for m in matches {
+                let score = (m.score as f64).clamp(0.0, 1.0);
+                let entry = scores.entry(m.path).or_insert(0.0);
+                *entry = entry.max(score);
+            }
+            Some(scores)
+        }
+        Err(e) => {
+            log::warn!("[SemanticIndex] Search failed for {}, using keyword relevance only: {}", project_path, e);
+            None
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_semantic_index_config() -> Result<SemanticIndexConfig, String> {
+    Ok(load_config())
+}
+
+#[tauri::command]
+pub async fn update_semantic_index_config(config: SemanticIndexConfig) -> Result<(), String> {
+    save_config(&config)?;
+    // 模型或地址变化后下次搜索立即刷新
+    LAST_REFRESH.lock().unwrap().clear();
+    log::info!("[SemanticIndex] Config updated (enabled: {}, model: {})", config.enabled, config.model);
+    Ok(())
+}
+
+/// 语义搜索项目代码，返回最相关的 k 个代码块（默认 10）
+#[tauri::command]
+pub async fn semantic_search(project_path: String, query: String, k: Option<usize>) -> Result<Vec<SemanticMatch>, String> {
+    search(&project_path, &query, k.unwrap_or(10)).await
+}
+
+/// 手动（重新）建立索引
+#[tauri::command]
+pub async fn reindex_semantic_index(project_path: String, full: Option<bool>) -> Result<IndexStats, String> {
+    reindex_project(&project_path, full.unwrap_or(false)).await
+}
+
+/// 删除项目的语义索引
+#[tauri::command]
+pub async fn clear_semantic_index(project_path: String) -> Result<(), String> {
+    LAST_REFRESH.lock().unwrap().remove(&project_path);
+    with_index_db(move |conn| {
+        conn.execute("DELETE FROM semantic_chunks WHERE project_path = ?1", params![project_path])
+            .and_then(|_| conn.execute("DELETE FROM semantic_files WHERE project_path = ?1", params![project_path]))
+            .map(|_| ())
+            .map_err(|e| format!("删除语义索引失败: {}", e))
+    })
+    .await
+}
+
+/// 监听项目文件变化，保存后在后台增量更新索引
+#[tauri::command]
+pub async fn start_semantic_watcher(project_path: String) -> Result<(), String> {
+    if !load_config().enabled {
+        return Err("语义索引未启用".to_string());
+    }
+    let mut watchers = WATCHERS.lock().unwrap();
+    if watchers.contains_key(&project_path) {
+        return Ok(());
+    }
+
+    let project = project_path.clone();
+    let mut debouncer = new_debouncer(WATCH_DEBOUNCE, move |res: Result<Vec<DebouncedEvent>, notify::Error>| {
+        let relevant = match res {
+            // .git 内部的变化（提交、索引刷新）不影响文件内容
+            Ok(events) => events.iter().any(|e| !e.path.components().any(|c| c.as_os_str() == ".git")),
+            Err(e) => {
+                log::error!("[SemanticIndex] Watch error: {:?}", e);
+                false
+            }
+        };
+        if relevant {
+            refresh_in_background(&project);
+        }
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    debouncer
+        .watcher()
+        .watch(Path::new(&project_path), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch project: {}", e))?;
+
+    log::info!("[SemanticIndex] Watching {}", project_path);
+    watchers.insert(project_path, debouncer);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_semantic_watcher(project_path: String) -> Result<(), String> {
+    if WATCHERS.lock().unwrap().remove(&project_path).is_some() {
+        log::info!("[SemanticIndex] Stopped watching {}", project_path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_plans_and_ranks() {
+        let content = (1..=90).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let ranges: Vec<(usize, usize)> = chunk_content(&content).iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(ranges, vec![(1, 40), (33, 72), (65, 90)]);
+        assert!(chunk_content("\n\n  \n").is_empty());
+
+        let indexed: HashMap<String, FileStamp> =
+            [("a.rs".to_string(), (1, 10)), ("b.rs".to_string(), (1, 10)), ("gone.rs".to_string(), (1, 10))].into();
+        let current: HashMap<String, FileStamp> =
+            [("a.rs".to_string(), (1, 10)), ("b.rs".to_string(), (2, 12)), ("new.rs".to_string(), (3, 5))].into();
+        assert_eq!(
+            plan_reindex(&indexed, &current, false),
+            (vec!["b.rs".to_string(), "new.rs".to_string()], vec!["gone.rs".to_string()])
+        );
+        assert_eq!(plan_reindex(&indexed, &current, true).0.len(), 3);
+
+        let chunk = |path: &str, vector: Vec<f32>| (path.to_string(), 1, 40, String::new(), encode_vector(&normalize(vector)));
+        let query = normalize(vec![1.0, 0.0]);
+        let matches = rank_chunks(&query, vec![chunk("far.rs", vec![0.0, 1.0]), chunk("near.rs", vec![3.0, 0.5])], 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, "near.rs");
+        assert!(matches[0].score > 0.9);
+    }
+}
@@ -0,0 +1,539 @@
+//! 恢复会话前的历史压缩
+//!
+//! 恢复长会话时 CLI 会把全部历史重新发给模型，费用高且容易超出上下文窗口。开启后，恢复前把
+//! 较早的轮次交给会话所用的引擎生成总结，会话文件改写为「总结 + 最近 N 轮」再交给 CLI 恢复，
+//! 会话 ID 不变。原文件备份到数据目录的 `compactions/` 下，每次压缩记录在 agents.db 的
+//! `session_compactions` 表中，会话列表通过 `SessionMetadata::compactions` 带出。
+//!
+//! 被总结的轮次不再存在，提示词序号随之变化，因此会清除该会话已有的回滚记录，
+//! 避免回滚到错位的提交；之后发送的提示词照常记录。
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::app_settings::{get_setting, set_setting};
+use super::data_root::anycode_dir;
+use super::session_pages::locate_session_file;
+use super::session_summary::{claude_transcript, codex_transcript, gemini_transcript, run_transcript_prompt};
+use super::storage::open_agent_db;
+use super::tokenizer::count_content;
+
+pub const RESUME_COMPACTION_KEY: &str = "resume_compaction";
+
+const COMPACTION_PROMPT: &str = "The transcript below is the earlier part of a coding session that is about to be resumed.\n\
+Write a summary that lets the assistant continue the work without the full history: \
+the user's goals and constraints, what has been done so far (files created or modified and why), \
+important decisions, errors that were hit and how they were resolved, and anything still in progress.\n\
+Reply with the summary as plain text and nothing else. \
+Write in the same language the user used in the transcript. Do not use any tools.";
+
+/// 恢复前压缩配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResumeCompactionConfig {
+    pub enabled: bool,
+    /// 历史估算超过该 token 数时才压缩
+    pub min_tokens: usize,
+    /// 原样保留的最近轮数
+    pub keep_turns: usize,
+}
+
+impl Default for ResumeCompactionConfig {
+    fn default() -> Self {
+        ResumeCompactionConfig {
+            enabled: false,
+            min_tokens: 80_000,
+            keep_turns: 4,
+        }
+    }
+}
+
+/// 一次压缩记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCompaction {
+    pub compacted_at: String,
+    /// 被总结的轮数
+    pub summarized_turns: usize,
+    pub kept_turns: usize,
+    /// 压缩前后的历史估算 token 数
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+    pub summary: String,
+    /// 生成总结的引擎
+    pub generated_by: String,
+    /// 原会话文件的备份
+    pub backup_path: String,
+}
+
+/// 读取某个引擎的全部压缩记录（按时间先后）
+pub fn load_engine_compactions(
+    conn: &Connection,
+    engine: &str,
+) -> Result<HashMap<String, Vec<SessionCompaction>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT session_id, compaction FROM session_compactions WHERE engine = ?1 ORDER BY id")
+        .map_err(|e| format!("查询会话压缩记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![engine], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("查询会话压缩记录失败: {}", e))?
+        .filter_map(|row| row.ok());
+
+    let mut compactions: HashMap<String, Vec<SessionCompaction>> = HashMap::new();
+    for (session_id, json) in rows {
+        if let Ok(compaction) = serde_json::from_str(&json) {
+            compactions.entry(session_id).or_default().push(compaction);
+        }
+    }
+    Ok(compactions)
+}
+
+fn save_compaction(conn: &Connection, engine: &str, session_id: &str, compaction: &SessionCompaction) -> Result<(), String> {
+    let json = serde_json::to_string(compaction).map_err(|e| format!("序列化压缩记录失败: {}", e))?;
+    conn.execute(
+        "INSERT INTO session_compactions (engine, session_id, compaction, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![engine, session_id, json, compaction.compacted_at],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("保存压缩记录失败: {}", e))
+}
+
+// ============================================================================
+// 会话解析与改写
+// ============================================================================
+
+/// Claude：真实的用户输入（排除工具结果、子代理消息、Warmup 和 Skills 消息）
+fn is_claude_prompt(entry: &Value) -> bool {
+    if entry["type"] != "user" || entry["isSidechain"] == true || !entry["parent_tool_use_id"].is_null() {
+        return false;
+    }
+    let text = match &entry["message"]["content"] {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect(),
+        _ => String::new(),
+    };
+    !text.trim().is_empty()
+        && !text.contains("Warmup")
+        && !text.contains("<command-name>")
+        && !text.contains("Launching skill:")
+        && !text.contains("skill is running")
+}
+
+/// Codex：用户消息（排除注入的环境信息与 AGENTS.md 指令）
+fn is_codex_prompt(entry: &Value) -> bool {
+    entry["type"] == "response_item"
+        && entry["payload"]["role"] == "user"
+        && entry["payload"]["content"].as_array().into_iter().flatten().any(|item| {
+            let text = item["text"].as_str().unwrap_or_default();
+            item["type"] == "input_text"
+                && !text.trim().is_empty()
+                && !text.contains("<environment_context>")
+                && !text.contains("# AGENTS.md instructions")
+        })
+}
+
+fn is_prompt(engine: &str, entry: &Value) -> bool {
+    match engine {
+        "claude" => is_claude_prompt(entry),
+        "codex" => is_codex_prompt(entry),
+        _ => entry["type"] == "user",
+    }
+}
+
+/// 历史的估算 token 数（不使用 CLI 记录的用量：压缩后保留的轮次中仍带着压缩前的用量）
+fn estimate_tokens(engine: &str, entries: &[Value]) -> usize {
+    entries
+        .iter()
+        .map(|entry| match engine {
+            "claude" => count_content("", &entry["message"]["content"]),
+            "codex" if entry["type"] == "response_item" => count_content("", &entry["payload"]),
+            "codex" => 0,
+            _ => count_content("", &entry["content"]),
+        })
+        .sum()
+}
+
+/// 已读取的会话
+struct LoadedSession {
+    path: PathBuf,
+    /// 读取时的文件大小，写回前用来确认会话没有在压缩期间继续写入
+    len: u64,
+    /// Gemini 会话文件的 JSON（`messages` 之外的字段原样写回）
+    document: Option<Value>,
+    /// JSONL 的每一行或 Gemini 的每条消息
+    entries: Vec<Value>,
+    /// 每轮起始条目的位置
+    turn_starts: Vec<usize>,
+}
+
+fn load_session(engine: &str, session_id: &str, project_path: &str) -> Result<LoadedSession, String> {
+    let path = match engine {
+        "gemini" => {
+            let sessions_dir = super::gemini::git_ops::get_gemini_sessions_dir(project_path)?;
+            super::gemini::git_ops::find_gemini_session_file(&sessions_dir, session_id)?
+        }
+        _ => locate_session_file(engine, session_id, None)?,
+    };
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取会话文件失败: {}", e))?;
+    let (document, entries) = if engine == "gemini" {
+        let mut document: Value = serde_json::from_str(&content).map_err(|e| format!("解析会话文件失败: {}", e))?;
+        let messages = match document.get_mut("messages").map(Value::take) {
+            Some(Value::Array(messages)) => messages,
+            _ => return Err("会话文件中没有消息".to_string()),
+        };
+        (Some(document), messages)
+    } else {
+        let entries = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str::<Value>(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::warn!("[SessionCompaction] Skipping unparsable line in {:?}: {}", path, e);
+                    None
+                }
+            })
+            .collect();
+        (None, entries)
+    };
+    let turn_starts = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| is_prompt(engine, entry))
+        .map(|(i, _)| i)
+        .collect();
+    Ok(LoadedSession {
+        len: content.len() as u64,
+        path,
+        document,
+        entries,
+        turn_starts,
+    })
+}
+
+/// 保留最近 `keep_turns` 轮时的分界：(第一条保留的条目, 被总结的轮数)
+fn split_point(turn_starts: &[usize], keep_turns: usize) -> Option<(usize, usize)> {
+    let summarized = turn_starts.len().checked_sub(keep_turns.max(1)).filter(|n| *n > 0)?;
+    Some((turn_starts[summarized], summarized))
+}
+
+fn summary_text(summary: &str, summarized_turns: usize) -> String {
+    format!(
+        "This session was compacted to save context. The earlier {} turns are summarized below; \
+         the most recent turns follow unchanged.\n\n<conversation-summary>\n{}\n</conversation-summary>",
+        summarized_turns,
+        summary.trim()
+    )
+}
+
+/// Claude：总结作为一条用户消息放在最前，指向被删除条目的 parentUuid 改为指向它
+fn rewrite_claude(entries: &[Value], boundary: usize, session_id: &str, text: &str) -> Vec<Value> {
+    let kept = &entries[boundary..];
+    let summary_uuid = uuid::Uuid::new_v4().to_string();
+    let first = &kept[0];
+    let summary = json!({
+        "parentUuid": null,
+        "isSidechain": false,
+        "userType": "external",
+        "cwd": first["cwd"],
+        "sessionId": session_id,
+        "version": first["version"],
+        "gitBranch": first["gitBranch"],
+        "type": "user",
+        "message": { "role": "user", "content": text },
+        "isCompactSummary": true,
+        "uuid": summary_uuid,
+        "timestamp": Utc::now().to_rfc3339(),
+    });
+
+    let kept_uuids: HashSet<&str> = kept.iter().filter_map(|e| e["uuid"].as_str()).collect();
+    let mut rewritten = vec![summary];
+    for entry in kept {
+        let mut entry = entry.clone();
+        let dangling = entry["parentUuid"].as_str().is_some_and(|parent| !kept_uuids.contains(parent));
+        if dangling {
+            entry["parentUuid"] = Value::String(summary_uuid.clone());
+        }
+        rewritten.push(entry);
+    }
+    rewritten
+}
+
+/// Codex：保留会话开头的元数据和注入的上下文，总结作为一条用户消息插在最近几轮之前
+fn rewrite_codex(entries: &[Value], first_turn: usize, boundary: usize, text: &str) -> Vec<Value> {
+    let summary = json!({
+        "timestamp": Utc::now().to_rfc3339(),
+        "type": "response_item",
+        "payload": {
+            "type": "message",
+            "role": "user",
+            "content": [{ "type": "input_text", "text": text }],
+        },
+    });
+    entries[..first_turn]
+        .iter()
+        .cloned()
+        .chain(std::iter::once(summary))
+        .chain(entries[boundary..].iter().cloned())
+        .collect()
+}
+
+fn rewrite_gemini(entries: &[Value], boundary: usize, text: &str) -> Vec<Value> {
+    let summary = json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "timestamp": Utc::now().to_rfc3339(),
+        "type": "user",
+        "content": text,
+    });
+    std::iter::once(summary).chain(entries[boundary..].iter().cloned()).collect()
+}
+
+fn transcript(engine: &str, entries: &[Value]) -> Vec<String> {
+    match engine {
+        "claude" => claude_transcript(entries),
+        "codex" => codex_transcript(entries),
+        _ => gemini_transcript(entries),
+    }
+}
+
+fn backup_session_file(engine: &str, session_id: &str, path: &Path) -> Result<PathBuf, String> {
+    let dir = anycode_dir()?.join("compactions").join(engine);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("jsonl");
+    let backup = dir.join(format!("{}-{}.{}", session_id, Utc::now().format("%Y%m%d%H%M%S"), extension));
+    fs::copy(path, &backup).map_err(|e| format!("备份会话文件失败: {}", e))?;
+    Ok(backup)
+}
+
+fn write_session(session: &LoadedSession, entries: Vec<Value>) -> Result<(), String> {
+    let content = match &session.document {
+        Some(document) => {
+            let mut document = document.clone();
+            document["messages"] = Value::Array(entries);
+            document["lastUpdated"] = Value::String(Utc::now().to_rfc3339());
+            serde_json::to_string_pretty(&document).map_err(|e| format!("序列化会话失败: {}", e))?
+        }
+        None => entries.iter().map(|entry| entry.to_string() + "\n").collect(),
+    };
+    fs::write(&session.path, content).map_err(|e| format!("写入会话文件失败: {}", e))
+}
+
+/// 提示词序号已经变化，清除旧的回滚记录
+fn clear_rewind_records(engine: &str, session_id: &str, path: &Path) -> Result<(), String> {
+    match engine {
+        "claude" => {
+            let project_id = path
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            super::prompt_tracker::clear_git_records(session_id, &project_id).map_err(|e| e.to_string())
+        }
+        "codex" => {
+            let mut records = super::codex::load_codex_git_records(session_id)?;
+            if records.records.is_empty() {
+                return Ok(());
+            }
+            records.records.clear();
+            super::codex::save_codex_git_records(session_id, &records)
+        }
+        _ => {
+            let mut records = super::gemini::git_ops::load_gemini_git_records(session_id)?;
+            if records.records.is_empty() {
+                return Ok(());
+            }
+            records.records.clear();
+            super::gemini::git_ops::save_gemini_git_records(session_id, &records)
+        }
+    }
+}
+
+async fn load_config() -> ResumeCompactionConfig {
+    match get_setting(RESUME_COMPACTION_KEY).await {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("[SessionCompaction] Failed to load resume compaction config: {}", e);
+            ResumeCompactionConfig::default()
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 读取恢复前压缩配置
+#[tauri::command]
+pub async fn get_resume_compaction_config() -> Result<ResumeCompactionConfig, String> {
+    Ok(load_config().await)
+}
+
+/// 保存恢复前压缩配置
+#[tauri::command]
+pub async fn set_resume_compaction_config(app: AppHandle, config: ResumeCompactionConfig) -> Result<(), String> {
+    if config.keep_turns == 0 {
+        return Err("keepTurns 必须大于 0".to_string());
+    }
+    set_setting(&app, RESUME_COMPACTION_KEY, &config).await
+}
+
+/// 恢复会话前按配置压缩历史
+///
+/// 未开启、历史未达到阈值或轮数不超过保留轮数时返回 None；`force` 为 true 时忽略开关和阈值
+/// （用于手动压缩）。
+#[tauri::command]
+pub async fn compact_session_before_resume(
+    app: AppHandle,
+    engine: String,
+    session_id: String,
+    project_path: String,
+    force: Option<bool>,
+) -> Result<Option<SessionCompaction>, String> {
+    if !matches!(engine.as_str(), "claude" | "codex" | "gemini") {
+        return Err(format!("不支持的引擎: {}", engine));
+    }
+    let force = force.unwrap_or(false);
+    let config = load_config().await;
+    if !config.enabled && !force {
+        return Ok(None);
+    }
+
+    let session = tokio::task::spawn_blocking({
+        let (engine, session_id) = (engine.clone(), session_id.clone());
+        move || load_session(&engine, &session_id, &project_path)
+    })
+    .await
+    .map_err(|e| format!("读取会话失败: {}", e))??;
+
+    let tokens_before = estimate_tokens(&engine, &session.entries);
+    if tokens_before < config.min_tokens && !force {
+        return Ok(None);
+    }
+    let Some((boundary, summarized_turns)) = split_point(&session.turn_starts, config.keep_turns) else {
+        return Ok(None);
+    };
+    let first_turn = session.turn_starts[0];
+    let lines = transcript(&engine, &session.entries[first_turn..boundary]);
+    if lines.is_empty() {
+        return Ok(None);
+    }
+    log::info!(
+        "[SessionCompaction] Compacting {} session {}: summarizing {} of {} turns (~{} tokens)",
+        engine,
+        session_id,
+        summarized_turns,
+        session.turn_starts.len(),
+        tokens_before
+    );
+
+    let summary = run_transcript_prompt(&app, &engine, COMPACTION_PROMPT, &lines).await?;
+    let summary = summary.trim().to_string();
+    if summary.is_empty() {
+        return Err(format!("{} 未返回总结", engine));
+    }
+    let text = summary_text(&summary, summarized_turns);
+    let entries = match engine.as_str() {
+        "claude" => rewrite_claude(&session.entries, boundary, &session_id, &text),
+        "codex" => rewrite_codex(&session.entries, first_turn, boundary, &text),
+        _ => rewrite_gemini(&session.entries, boundary, &text),
+    };
+    let tokens_after = estimate_tokens(&engine, &entries);
+
+    tokio::task::spawn_blocking(move || {
+        let len = fs::metadata(&session.path).map(|m| m.len()).unwrap_or_default();
+        if len != session.len {
+            return Err("会话在生成总结期间有新的内容，已取消压缩".to_string());
+        }
+        let backup = backup_session_file(&engine, &session_id, &session.path)?;
+        write_session(&session, entries)?;
+        if let Err(e) = clear_rewind_records(&engine, &session_id, &session.path) {
+            log::warn!("[SessionCompaction] Failed to clear rewind records of {}: {}", session_id, e);
+        }
+
+        let compaction = SessionCompaction {
+            compacted_at: Utc::now().to_rfc3339(),
+            summarized_turns,
+            kept_turns: session.turn_starts.len() - summarized_turns,
+            tokens_before,
+            tokens_after,
+            summary,
+            generated_by: engine.clone(),
+            backup_path: backup.to_string_lossy().to_string(),
+        };
+        let conn = open_agent_db()?;
+        save_compaction(&conn, &engine, &session_id, &compaction)?;
+        log::info!(
+            "[SessionCompaction] Compacted {} session {}: ~{} -> ~{} tokens",
+            engine,
+            session_id,
+            tokens_before,
+            tokens_after
+        );
+        Ok(Some(compaction))
+    })
+    .await
+    .map_err(|e| format!("压缩会话失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_history_to_summary_and_recent_turns() {
+        let claude = vec![
+            json!({"type": "summary", "summary": "old", "leafUuid": "a2"}),
+            json!({"type": "user", "uuid": "a1", "parentUuid": null, "message": {"content": "first task"}}),
+            json!({"type": "assistant", "uuid": "a2", "parentUuid": "a1", "message": {"content": [{"type": "text", "text": "done"}]}}),
+            json!({"type": "user", "uuid": "a3", "parentUuid": "a2", "message": {"content": [{"type": "tool_result", "content": "ok"}]}}),
+            json!({"type": "user", "uuid": "b1", "parentUuid": "a3", "cwd": "/p", "message": {"content": "second task"}}),
+            json!({"type": "assistant", "uuid": "b2", "parentUuid": "b1", "message": {"content": "ok"}}),
+        ];
+        let turns: Vec<usize> = (0..claude.len()).filter(|i| is_claude_prompt(&claude[*i])).collect();
+        assert_eq!(turns, vec![1, 4]);
+        assert_eq!(split_point(&turns, 2), None);
+        let (boundary, summarized) = split_point(&turns, 1).unwrap();
+        assert_eq!((boundary, summarized), (4, 1));
+
+        let rewritten = rewrite_claude(&claude, boundary, "s1", "summary");
+        assert_eq!(rewritten.len(), 3);
+        assert_eq!(rewritten[0]["message"]["content"], "summary");
+        assert_eq!(rewritten[0]["cwd"], "/p");
+        assert_eq!(rewritten[1]["parentUuid"], rewritten[0]["uuid"]);
+        assert_eq!(rewritten[2]["parentUuid"], "b1");
+        // 总结本身算作一轮，之后发送的提示词序号与记录保持一致
+        assert!(is_claude_prompt(&rewritten[0]));
+
+        let codex = vec![
+            json!({"type": "session_meta", "payload": {"id": "s2"}}),
+            json!({"type": "response_item", "payload": {"role": "user", "content": [{"type": "input_text", "text": "<environment_context>...</environment_context>"}]}}),
+            json!({"type": "response_item", "payload": {"role": "user", "content": [{"type": "input_text", "text": "one"}]}}),
+            json!({"type": "response_item", "payload": {"role": "assistant", "content": [{"type": "output_text", "text": "1"}]}}),
+            json!({"type": "response_item", "payload": {"role": "user", "content": [{"type": "input_text", "text": "two"}]}}),
+        ];
+        let turns: Vec<usize> = (0..codex.len()).filter(|i| is_codex_prompt(&codex[*i])).collect();
+        assert_eq!(turns, vec![2, 4]);
+        let rewritten = rewrite_codex(&codex, turns[0], 4, "summary");
+        let types: Vec<&str> = rewritten
+            .iter()
+            .map(|e| e["payload"]["content"][0]["text"].as_str().unwrap_or("meta"))
+            .collect();
+        assert_eq!(types, vec!["meta", "<environment_context>...</environment_context>", "summary", "two"]);
+        assert!(is_codex_prompt(&rewritten[2]));
+
+        let gemini = vec![json!({"type": "user", "content": "a"}), json!({"type": "user", "content": "b"})];
+        let rewritten = rewrite_gemini(&gemini, 1, "summary");
+        assert_eq!(rewritten[0]["content"], "summary");
+        assert_eq!(rewritten[1]["content"], "b");
+    }
+}
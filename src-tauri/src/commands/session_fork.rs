@@ -0,0 +1,78 @@
+//! 会话分叉
+//!
+//! 在某条提示词处把会话拆成一条新分支：复制该提示词之前的对话历史到新的会话文件，
+//! 同时复制到该点为止的 git 记录（以及 Codex 的文件变更记录），使新会话可以
+//! 独立继续、回滚。原会话与代码工作区均不受影响。
+//!
+//! 截断语义与回滚一致：分叉点的提示词本身不在新会话中，可以在新会话里重新发送
+//! 另一版本的提示词，从而在同一位置尝试不同的方向。
+
+use crate::tr;
+use serde::Serialize;
+
+/// 分叉出的新会话
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkedSession {
+    pub engine: String,
+    /// 新会话 ID
+    pub session_id: String,
+    /// 原会话 ID
+    pub forked_from: String,
+    pub at_prompt_index: usize,
+}
+
+fn fork_for_engine(
+    engine: &str,
+    session_id: &str,
+    project_path: &str,
+    at_prompt_index: usize,
+) -> Result<String, String> {
+    match engine {
+        "claude" => {
+            let project_id = super::claude::encode_project_path(project_path);
+            super::prompt_tracker::fork_claude_session(session_id, &project_id, at_prompt_index)
+                .map_err(|e| format!("分叉 Claude 会话失败: {}", e))
+        }
+        "codex" => super::codex::fork_codex_session(session_id, at_prompt_index),
+        "gemini" => super::gemini::fork_gemini_session(session_id, project_path, at_prompt_index),
+        other => Err(tr!("engine.unsupported", engine = other)),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 在第 `at_prompt_index` 条提示词处分叉会话
+///
+/// 新会话包含该提示词之前的全部历史，返回新会话 ID 供前端恢复执行。
+#[tauri::command]
+pub async fn fork_session(
+    engine: String,
+    session_id: String,
+    at_prompt_index: usize,
+    project_path: String,
+) -> Result<ForkedSession, String> {
+    log::info!(
+        "[SessionFork] Forking {} session {} at prompt #{}",
+        engine,
+        session_id,
+        at_prompt_index
+    );
+
+    let new_session_id = tokio::task::spawn_blocking({
+        let engine = engine.clone();
+        let session_id = session_id.clone();
+        move || fork_for_engine(&engine, &session_id, &project_path, at_prompt_index)
+    })
+    .await
+    .map_err(|e| format!("分叉会话失败: {}", e))??;
+
+    Ok(ForkedSession {
+        engine,
+        session_id: new_session_id,
+        forked_from: session_id,
+        at_prompt_index,
+    })
+}
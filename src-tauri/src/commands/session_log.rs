@@ -0,0 +1,299 @@
+//! 引擎执行日志（按会话落盘）
+//!
+//! 子进程的 stdout/stderr 除了推送给前端外，还会原样写入
+//! `~/.anycode/logs/<engine>/<session_id>.log`，便于事后排查。
+//! 单个日志超过 `MAX_LOG_BYTES` 时滚动为 `.log.1`、`.log.2`……
+//!
+//! 应用自身的 `log` 输出除 stderr 外也写入 `~/.anycode/logs/app.log`（见 [`AppLogWriter`]）。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::ide::{open_file_in_ide, OpenFileOptions};
+use super::data_root::anycode_dir;
+
+/// 单个日志文件上限（超过后滚动）
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// 每个会话最多保留的滚动文件数
+const MAX_ROTATED_FILES: usize = 3;
+/// 默认返回的尾部行数
+const DEFAULT_TAIL_LINES: usize = 200;
+
+const ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 日志根目录 `~/.anycode/logs`
+pub fn logs_root() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("logs"))
+}
+
+/// 应用日志 `~/.anycode/logs/app.log`
+pub fn app_log_path() -> Result<PathBuf, String> {
+    Ok(logs_root()?.join("app.log"))
+}
+
+fn log_path(engine: &str, session_id: &str) -> Result<PathBuf, String> {
+    Ok(logs_root()?.join(engine).join(format!("{}.log", session_id)))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// `foo.log` -> `foo.log.1` -> `foo.log.2` ...，最旧的被删除
+fn rotate(path: &Path) {
+    let _ = fs::remove_file(rotated_path(path, MAX_ROTATED_FILES));
+    for index in (1..MAX_ROTATED_FILES).rev() {
+        let from = rotated_path(path, index);
+        if from.exists() {
+            let _ = fs::rename(&from, rotated_path(path, index + 1));
+        }
+    }
+    let _ = fs::rename(path, rotated_path(path, 1));
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// 会话 ID 尚未确定（Claude 新会话在 init 消息之前）
+    pending: bool,
+}
+
+impl LogFile {
+    fn open(path: PathBuf, pending: bool) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { path, file, size, pending })
+    }
+}
+
+/// 应用日志写入器：`log` 的输出同时写入 stderr 和 app.log
+///
+/// 作为 env_logger 的输出目标；日志文件无法打开时只写 stderr。
+pub struct AppLogWriter {
+    file: Option<LogFile>,
+}
+
+impl AppLogWriter {
+    pub fn open() -> Self {
+        let file = app_log_path().ok().and_then(|path| LogFile::open(path, false).ok());
+        Self { file }
+    }
+}
+
+impl Write for AppLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let _ = std::io::stderr().write_all(buf);
+        if let Some(log) = self.file.as_mut() {
+            if log.size + buf.len() as u64 > MAX_LOG_BYTES {
+                rotate(&log.path);
+                if let Ok(reopened) = LogFile::open(log.path.clone(), false) {
+                    *log = reopened;
+                }
+            }
+            if log.file.write_all(buf).is_ok() {
+                log.size += buf.len() as u64;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let _ = std::io::stderr().flush();
+        match self.file.as_mut() {
+            Some(log) => log.file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 单个进程的执行日志写入器（stdout/stderr 读取任务共享）
+pub struct SessionLogWriter {
+    engine: String,
+    inner: Mutex<Option<LogFile>>,
+}
+
+impl SessionLogWriter {
+    /// 为已知会话 ID 创建日志；失败时返回 None，不影响执行
+    pub fn open(engine: &str, session_id: &str) -> Option<Arc<Self>> {
+        Self::open_inner(engine, session_id, false)
+    }
+
+    /// 会话 ID 未知时先写入 `pending-<pid>.log`，之后用 [`Self::assign_session`] 改名
+    pub fn open_pending(engine: &str, pid: u32) -> Option<Arc<Self>> {
+        Self::open_inner(engine, &format!("pending-{}", pid), true)
+    }
+
+    fn open_inner(engine: &str, key: &str, pending: bool) -> Option<Arc<Self>> {
+        let path = match log_path(engine, key) {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("[SessionLog] {}", e);
+                return None;
+            }
+        };
+        match LogFile::open(path.clone(), pending) {
+            Ok(file) => Some(Arc::new(Self {
+                engine: engine.to_string(),
+                inner: Mutex::new(Some(file)),
+            })),
+            Err(e) => {
+                log::warn!("[SessionLog] Failed to open {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// 会话 ID 确定后，把 pending 日志重命名为 `<session_id>.log`
+    pub fn assign_session(&self, session_id: &str) {
+        let mut guard = self.inner.lock().unwrap();
+        let Some(current) = guard.as_ref() else {
+            return;
+        };
+        if !current.pending {
+            return;
+        }
+        let Ok(target) = log_path(&self.engine, session_id) else {
+            return;
+        };
+
+        let old_path = current.path.clone();
+        // 同一会话（resume）已有日志：追加到原文件
+        let result = if target.exists() {
+            fs::read(&old_path).and_then(|content| {
+                let mut file = OpenOptions::new().append(true).open(&target)?;
+                file.write_all(&content)?;
+                fs::remove_file(&old_path)
+            })
+        } else {
+            fs::rename(&old_path, &target)
+        };
+
+        if let Err(e) = result {
+            log::warn!("[SessionLog] Failed to move {:?} to {:?}: {}", old_path, target, e);
+            return;
+        }
+
+        *guard = LogFile::open(target, false)
+            .map_err(|e| log::warn!("[SessionLog] Failed to reopen log: {}", e))
+            .ok();
+    }
+
+    /// 写入一行原始输出
+    pub fn write_line(&self, stream: &str, line: &str) {
+        let mut guard = self.inner.lock().unwrap();
+        let Some(log_file) = guard.as_mut() else {
+            return;
+        };
+
+        if log_file.size >= MAX_LOG_BYTES {
+            rotate(&log_file.path);
+            match LogFile::open(log_file.path.clone(), log_file.pending) {
+                Ok(file) => *log_file = file,
+                Err(e) => {
+                    log::warn!("[SessionLog] Failed to rotate {:?}: {}", log_file.path, e);
+                    *guard = None;
+                    return;
+                }
+            }
+        }
+
+        let entry = format!(
+            "{} [{}] {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            stream,
+            line
+        );
+        if log_file.file.write_all(entry.as_bytes()).is_ok() {
+            log_file.size += entry.len() as u64;
+        }
+    }
+}
+
+/// 在各引擎目录下查找会话日志
+fn find_session_log(session_id: &str) -> Result<PathBuf, String> {
+    if session_id.is_empty() || session_id.contains(['/', '\\']) || session_id.contains("..") {
+        return Err(format!("无效的会话 ID: {}", session_id));
+    }
+    ENGINES
+        .iter()
+        .filter_map(|engine| log_path(engine, session_id).ok())
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("未找到会话 {} 的执行日志", session_id))
+}
+
+/// 取文本最后 `n` 行
+fn tail(content: &str, n: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(n)..].join("\n")
+}
+
+/// 读取会话执行日志的最后若干行
+#[tauri::command]
+pub async fn get_session_log(session_id: String, tail_lines: Option<usize>) -> Result<String, String> {
+    let path = find_session_log(&session_id)?;
+    let bytes = fs::read(&path).map_err(|e| format!("读取日志失败: {}", e))?;
+    Ok(tail(&String::from_utf8_lossy(&bytes), tail_lines.unwrap_or(DEFAULT_TAIL_LINES)))
+}
+
+/// 在已配置的编辑器中打开会话执行日志（失败时使用系统默认程序）
+#[tauri::command]
+pub async fn open_session_log_in_editor(session_id: String) -> Result<String, String> {
+    let path = find_session_log(&session_id)?;
+    let path_str = path.to_string_lossy().to_string();
+
+    let line_count = fs::read(&path)
+        .map(|bytes| bytes.iter().filter(|b| **b == b'\n').count() as u32)
+        .unwrap_or(0);
+
+    let result = open_file_in_ide(OpenFileOptions {
+        file_path: path_str.clone(),
+        project_path: None,
+        line: Some(line_count.max(1)),
+        column: None,
+    })?;
+
+    if !result.success {
+        log::warn!("[SessionLog] IDE open failed ({}), falling back to default app", result.message);
+        super::file_operations::open_file_with_default_app(path_str.clone()).await?;
+    }
+
+    Ok(path_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_returns_last_lines() {
+        assert_eq!(tail("a\nb\nc\n", 2), "b\nc");
+        assert_eq!(tail("a\nb", 10), "a\nb");
+        assert_eq!(tail("", 5), "");
+    }
+
+    #[test]
+    fn rotate_shifts_files_and_drops_oldest() {
+        let dir = std::env::temp_dir().join(format!("anycode-log-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("s.log");
+
+        for i in 0..=MAX_ROTATED_FILES + 1 {
+            fs::write(&path, i.to_string()).unwrap();
+            rotate(&path);
+        }
+
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), (MAX_ROTATED_FILES + 1).to_string());
+        assert!(!rotated_path(&path, MAX_ROTATED_FILES + 1).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
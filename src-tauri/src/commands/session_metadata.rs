@@ -0,0 +1,355 @@
+//! 会话元数据（标签、置顶、归档）
+//!
+//! 元数据保存在 agents.db 的 `session_metadata` 表中，以 (engine, session_id) 为键，
+//! 不修改各 CLI 自己的会话文件。会话列表命令读取后附加到每个会话上，并按
+//! `SessionFilter` 过滤：默认隐藏已归档会话，置顶会话排在最前。
+
+use crate::tr;
+use chrono::Utc;
+use rusqlite::{params, types::Value, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::attachments::{self, StagedAttachment};
+use super::session_compaction::{self, SessionCompaction};
+use super::session_summary::{self, SessionSummary};
+use super::db_pool::PooledConnection;
+use super::storage::open_agent_db;
+
+const SUPPORTED_ENGINES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 单个会话的元数据
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMetadata {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub archived: bool,
+    /// `summarize_session` 生成的总结
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SessionSummary>,
+    /// 随提示词发送过的附件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<StagedAttachment>,
+    /// 恢复前的历史压缩记录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compactions: Vec<SessionCompaction>,
+}
+
+/// 会话列表过滤条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionFilter {
+    /// 只返回带有该标签的会话
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub pinned_only: bool,
+    /// 包含已归档会话（默认隐藏）
+    #[serde(default)]
+    pub include_archived: bool,
+    /// 只返回已归档会话
+    #[serde(default)]
+    pub archived_only: bool,
+}
+
+impl SessionFilter {
+    fn matches(&self, metadata: &SessionMetadata) -> bool {
+        if self.archived_only {
+            if !metadata.archived {
+                return false;
+            }
+        } else if metadata.archived && !self.include_archived {
+            return false;
+        }
+        if self.pinned_only && !metadata.pinned {
+            return false;
+        }
+        match &self.tag {
+            Some(tag) => metadata.tags.iter().any(|t| t == tag),
+            None => true,
+        }
+    }
+}
+
+/// 可附加元数据的会话列表项
+pub trait SessionWithMetadata {
+    fn session_id(&self) -> &str;
+    fn metadata(&self) -> &SessionMetadata;
+    fn metadata_mut(&mut self) -> &mut SessionMetadata;
+}
+
+impl SessionWithMetadata for super::claude::Session {
+    fn session_id(&self) -> &str {
+        &self.id
+    }
+    fn metadata(&self) -> &SessionMetadata {
+        &self.metadata
+    }
+    fn metadata_mut(&mut self) -> &mut SessionMetadata {
+        &mut self.metadata
+    }
+}
+
+impl SessionWithMetadata for super::codex::CodexSession {
+    fn session_id(&self) -> &str {
+        &self.id
+    }
+    fn metadata(&self) -> &SessionMetadata {
+        &self.metadata
+    }
+    fn metadata_mut(&mut self) -> &mut SessionMetadata {
+        &mut self.metadata
+    }
+}
+
+impl SessionWithMetadata for super::gemini::types::GeminiSessionInfo {
+    fn session_id(&self) -> &str {
+        &self.session_id
+    }
+    fn metadata(&self) -> &SessionMetadata {
+        &self.metadata
+    }
+    fn metadata_mut(&mut self) -> &mut SessionMetadata {
+        &mut self.metadata
+    }
+}
+
+/// 创建会话元数据表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_metadata (
+            engine TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            tags TEXT NOT NULL DEFAULT '[]',
+            pinned INTEGER NOT NULL DEFAULT 0,
+            archived INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (engine, session_id)
+        );",
+    )
+    .map_err(|e| format!("创建会话元数据表失败: {}", e))
+}
+
+fn open_metadata_db() -> Result<PooledConnection, String> {
+    let conn = open_agent_db()?;
+    ensure_schema(&conn)?;
+    session_summary::ensure_schema(&conn)?;
+    attachments::ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+/// 读取某个引擎的全部会话元数据（含总结、附件和压缩记录）
+fn load_engine_metadata(conn: &Connection, engine: &str) -> Result<HashMap<String, SessionMetadata>, String> {
+    let mut stmt = conn
+        .prepare("SELECT session_id, tags, pinned, archived FROM session_metadata WHERE engine = ?1")
+        .map_err(|e| format!("查询会话元数据失败: {}", e))?;
+    let mut metadata = stmt
+        .query_map(params![engine], |row| {
+            let tags: String = row.get(1)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                SessionMetadata {
+                    tags: serde_json::from_str(&tags).unwrap_or_default(),
+                    pinned: row.get(2)?,
+                    archived: row.get(3)?,
+                    summary: None,
+                    attachments: Vec::new(),
+                    compactions: Vec::new(),
+                },
+            ))
+        })
+        .map_err(|e| format!("查询会话元数据失败: {}", e))?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("读取会话元数据失败: {}", e))?;
+
+    for (session_id, summary) in session_summary::load_engine_summaries(conn, engine)? {
+        metadata.entry(session_id).or_default().summary = Some(summary);
+    }
+    for (session_id, session_attachments) in attachments::load_engine_attachments(conn, engine)? {
+        metadata.entry(session_id).or_default().attachments = session_attachments;
+    }
+    for (session_id, compactions) in session_compaction::load_engine_compactions(conn, engine)? {
+        metadata.entry(session_id).or_default().compactions = compactions;
+    }
+    Ok(metadata)
+}
+
+fn update_metadata(
+    conn: &Connection,
+    engine: &str,
+    session_id: &str,
+    column: &str,
+    value: &dyn rusqlite::ToSql,
+) -> Result<(), String> {
+    conn.execute(
+        &format!(
+            "INSERT INTO session_metadata (engine, session_id, {col}, updated_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (engine, session_id)
+             DO UPDATE SET {col} = excluded.{col}, updated_at = excluded.updated_at",
+            col = column
+        ),
+        params![engine, session_id, value, Utc::now().to_rfc3339()],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("更新会话元数据失败: {}", e))
+}
+
+/// (engine, session_id) → (置顶, 已归档)
+pub type SessionFlags = HashMap<(String, String), (bool, bool)>;
+
+/// 所有引擎会话的置顶 / 归档状态
+pub fn load_session_flags(conn: &Connection) -> Result<SessionFlags, String> {
+    let mut stmt = conn
+        .prepare("SELECT engine, session_id, pinned, archived FROM session_metadata")
+        .map_err(|e| format!("查询会话元数据失败: {}", e))?;
+    let flags = stmt
+        .query_map([], |row| Ok(((row.get(0)?, row.get(1)?), (row.get(2)?, row.get(3)?))))
+        .map_err(|e| format!("查询会话元数据失败: {}", e))?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("读取会话元数据失败: {}", e))?;
+    Ok(flags)
+}
+
+/// 标记会话为已归档 / 未归档
+pub fn set_archived(conn: &Connection, engine: &str, session_id: &str, archived: bool) -> Result<(), String> {
+    update_metadata(conn, engine, session_id, "archived", &archived)
+}
+
+/// 为会话列表附加元数据、按条件过滤，并把置顶会话移到最前（其余保持原顺序）
+fn apply_metadata<T: SessionWithMetadata>(
+    sessions: &mut Vec<T>,
+    metadata: &HashMap<String, SessionMetadata>,
+    filter: &SessionFilter,
+) {
+    for session in sessions.iter_mut() {
+        if let Some(meta) = metadata.get(session.session_id()) {
+            *session.metadata_mut() = meta.clone();
+        }
+    }
+    sessions.retain(|s| filter.matches(s.metadata()));
+    sessions.sort_by_key(|s| !s.metadata().pinned);
+}
+
+/// 供会话列表命令使用；读取元数据失败时只记录日志，不影响列表
+pub fn attach_session_metadata<T: SessionWithMetadata>(
+    engine: &str,
+    sessions: &mut Vec<T>,
+    filter: Option<&SessionFilter>,
+) {
+    let metadata = match open_metadata_db().and_then(|conn| load_engine_metadata(&conn, engine)) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            log::warn!("[SessionMetadata] Failed to load {} session metadata: {}", engine, e);
+            HashMap::new()
+        }
+    };
+    apply_metadata(sessions, &metadata, filter.unwrap_or(&SessionFilter::default()));
+}
+
+async fn update_session(engine: String, session_id: String, column: &'static str, value: Value) -> Result<(), String> {
+    if !SUPPORTED_ENGINES.contains(&engine.as_str()) {
+        return Err(tr!("engine.unsupported", engine = engine));
+    }
+    tokio::task::spawn_blocking(move || {
+        let conn = open_metadata_db()?;
+        update_metadata(&conn, &engine, &session_id, column, &value)
+    })
+    .await
+    .map_err(|e| format!("更新会话元数据失败: {}", e))?
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 设置会话标签（整体替换）
+#[tauri::command]
+pub async fn tag_session(engine: String, session_id: String, tags: Vec<String>) -> Result<(), String> {
+    let mut unique: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !unique.iter().any(|t| t == tag) {
+            unique.push(tag.to_string());
+        }
+    }
+    let json = serde_json::to_string(&unique).map_err(|e| format!("序列化标签失败: {}", e))?;
+    update_session(engine, session_id, "tags", Value::Text(json)).await
+}
+
+/// 置顶 / 取消置顶
+#[tauri::command]
+pub async fn pin_session(engine: String, session_id: String, pinned: bool) -> Result<(), String> {
+    update_session(engine, session_id, "pinned", Value::Integer(pinned as i64)).await
+}
+
+/// 归档 / 取消归档
+#[tauri::command]
+pub async fn archive_session(engine: String, session_id: String, archived: bool) -> Result<(), String> {
+    update_session(engine, session_id, "archived", Value::Integer(archived as i64)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Item {
+        id: String,
+        metadata: SessionMetadata,
+    }
+
+    impl SessionWithMetadata for Item {
+        fn session_id(&self) -> &str {
+            &self.id
+        }
+        fn metadata(&self) -> &SessionMetadata {
+            &self.metadata
+        }
+        fn metadata_mut(&mut self) -> &mut SessionMetadata {
+            &mut self.metadata
+        }
+    }
+
+    fn ids(items: &[Item]) -> Vec<&str> {
+        items.iter().map(|i| i.id.as_str()).collect()
+    }
+
+    #[test]
+    fn metadata_is_upserted_and_filters_listing() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::commands::db_migrations::run_migrations(&mut conn).unwrap();
+        ensure_schema(&conn).unwrap();
+        session_summary::ensure_schema(&conn).unwrap();
+        attachments::ensure_schema(&conn).unwrap();
+        update_metadata(&conn, "codex", "c", "pinned", &true).unwrap();
+        update_metadata(&conn, "codex", "c", "tags", &r#"["bug"]"#).unwrap();
+        update_metadata(&conn, "codex", "d", "archived", &true).unwrap();
+        update_metadata(&conn, "claude", "a", "pinned", &true).unwrap();
+
+        let metadata = load_engine_metadata(&conn, "codex").unwrap();
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(
+            metadata["c"],
+            SessionMetadata { tags: vec!["bug".into()], pinned: true, archived: false, summary: None, attachments: Vec::new(), compactions: Vec::new() }
+        );
+
+        let listing = || -> Vec<Item> {
+            ["a", "b", "c", "d"].iter().map(|id| Item { id: id.to_string(), ..Default::default() }).collect()
+        };
+
+        let mut sessions = listing();
+        apply_metadata(&mut sessions, &metadata, &SessionFilter::default());
+        assert_eq!(ids(&sessions), vec!["c", "a", "b"]);
+
+        let mut archived = listing();
+        apply_metadata(&mut archived, &metadata, &SessionFilter { archived_only: true, ..Default::default() });
+        assert_eq!(ids(&archived), vec!["d"]);
+
+        let mut tagged = listing();
+        apply_metadata(&mut tagged, &metadata, &SessionFilter { tag: Some("bug".into()), ..Default::default() });
+        assert_eq!(ids(&tagged), vec!["c"]);
+    }
+}
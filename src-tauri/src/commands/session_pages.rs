@@ -0,0 +1,546 @@
+//! 会话历史分页加载
+//!
+//! `load_session_history` / `load_codex_session_history` 会把整个会话读入内存，超大会话会拖慢界面。
+//! 这里为每个会话文件惰性建立行索引（每条非空行的字节偏移），只解析请求的那一页；
+//! `reverse` 为 true 时从最新的消息开始分页（offset 0 为最后一条）。
+//!
+//! JSONL 会话只会追加写入：文件变大时只为新增部分补建索引，变小或被改写时重建。
+//! Claude 子代理消息（agent-*.jsonl）不在分页结果中，需要时仍使用完整加载。
+//! Gemini 会话是单个 JSON 文件：扫描一次记录 `messages` 数组中每个元素的字节范围，
+//! 只解析请求的那一页；查找会话文件时只读到顶层的 `sessionId` 为止。Gemini 会整体改写会话文件，
+//! 文件大小或修改时间变化时重建索引。
+//!
+//! 会话 ID 和 Claude 项目 ID 会拼进文件路径，不能包含路径分隔符或 `..`。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
+
+use super::claude::get_claude_dir;
+use super::codex::session::find_session_file_in_all_dirs;
+use super::gemini::config::get_project_session_dir;
+
+/// 单页最多消息数
+const MAX_PAGE_SIZE: usize = 1000;
+/// 最多缓存的会话索引数
+const MAX_CACHED_INDEXES: usize = 64;
+
+/// 一页会话消息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMessagePage {
+    /// 按请求的方向排列（reverse 时最新的在前）
+    pub messages: Vec<Value>,
+    /// 会话中的消息总数（JSONL 为非空行数，无法解析的行会在读取时跳过）
+    pub total: usize,
+    pub offset: usize,
+    pub has_more: bool,
+}
+
+/// 会话文件的行索引
+#[derive(Debug, Clone, Default)]
+struct LineIndex {
+    len: u64,
+    modified: Option<SystemTime>,
+    /// 已换行结束的非空行的起始偏移
+    offsets: Vec<u64>,
+    /// 最后一个完整行之后的位置，追加时从这里继续建立索引
+    indexed_to: u64,
+    /// 末尾尚未换行的非空行（可能仍在写入）
+    tail: Option<u64>,
+}
+
+impl LineIndex {
+    fn total(&self) -> usize {
+        self.offsets.len() + usize::from(self.tail.is_some())
+    }
+
+    fn line_offset(&self, index: usize) -> Option<u64> {
+        match self.offsets.get(index) {
+            Some(offset) => Some(*offset),
+            None if index == self.offsets.len() => self.tail,
+            None => None,
+        }
+    }
+
+    /// 从 `indexed_to` 开始扫描到文件末尾
+    fn extend(&mut self, path: &Path) -> Result<(), String> {
+        let file = File::open(path).map_err(|e| format!("打开会话文件失败: {}", e))?;
+        let metadata = file.metadata().map_err(|e| format!("读取会话文件信息失败: {}", e))?;
+        let mut reader = BufReader::new(file);
+        reader
+            .seek(SeekFrom::Start(self.indexed_to))
+            .map_err(|e| format!("读取会话文件失败: {}", e))?;
+
+        self.tail = None;
+        let mut pos = self.indexed_to;
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let read = reader
+                .read_until(b'\n', &mut buf)
+                .map_err(|e| format!("读取会话文件失败: {}", e))?;
+            if read == 0 {
+                break;
+            }
+            let non_empty = buf.iter().any(|b| !b.is_ascii_whitespace());
+            if buf.ends_with(b"\n") {
+                if non_empty {
+                    self.offsets.push(pos);
+                }
+                self.indexed_to = pos + read as u64;
+            } else if non_empty {
+                self.tail = Some(pos);
+            }
+            pos += read as u64;
+        }
+        self.len = pos;
+        self.modified = metadata.modified().ok();
+        Ok(())
+    }
+
+    /// 让索引与文件当前内容一致
+    fn refresh(&mut self, path: &Path) -> Result<(), String> {
+        let metadata = std::fs::metadata(path).map_err(|e| format!("读取会话文件信息失败: {}", e))?;
+        let modified = metadata.modified().ok();
+        if metadata.len() == self.len && modified == self.modified && self.len > 0 {
+            return Ok(());
+        }
+        if metadata.len() < self.len || (metadata.len() == self.len && modified != self.modified) {
+            *self = LineIndex::default();
+        }
+        self.extend(path)
+    }
+}
+
+struct CachedIndex {
+    path: PathBuf,
+    index: LineIndex,
+    last_used: Instant,
+}
+
+/// (engine, session_id) → 会话文件及其行索引
+static INDEXES: Lazy<Mutex<HashMap<(String, String), CachedIndex>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Gemini 会话 JSON 的扫描结果
+#[derive(Debug, Clone, Default, PartialEq)]
+struct GeminiScan {
+    session_id: Option<String>,
+    /// `messages` 数组中每个元素的字节范围 [start, end)
+    messages: Vec<(u64, u64)>,
+}
+
+struct CachedGeminiIndex {
+    path: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+    messages: Vec<(u64, u64)>,
+    last_used: Instant,
+}
+
+/// (项目路径, session_id) → Gemini 会话文件及其消息索引
+static GEMINI_INDEXES: Lazy<Mutex<HashMap<(String, String), CachedGeminiIndex>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 会话 ID / 项目 ID 会拼进文件路径
+fn validate_path_component(value: &str, label: &str) -> Result<(), String> {
+    if value.is_empty() || value.contains(['/', '\\']) || value.contains("..") {
+        return Err(format!("无效的{}: {}", label, value));
+    }
+    Ok(())
+}
+
+/// 逐字节扫描 Gemini 会话 JSON：记录顶层 `sessionId` 和 `messages` 数组元素的位置，不解析内容
+///
+/// `stop_at_session_id` 为 true 时读到 `sessionId` 即返回（用于查找会话文件）。
+fn scan_gemini_session(reader: impl Read, stop_at_session_id: bool) -> Result<GeminiScan, String> {
+    let mut scan = GeminiScan::default();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    // 顶层对象中下一个字符串是键
+    let mut expect_key = false;
+    let mut key: Vec<u8> = Vec::new();
+    // 正在读取的顶层键（true）或 sessionId 的值（false）
+    let mut capture: Option<(bool, Vec<u8>)> = None;
+    let mut in_messages = false;
+    let mut element_start: Option<u64> = None;
+
+    for (pos, byte) in BufReader::new(reader).bytes().enumerate() {
+        let byte = byte.map_err(|e| format!("读取会话文件失败: {}", e))?;
+        let pos = pos as u64;
+        if in_string {
+            if let Some((_, buf)) = capture.as_mut() {
+                buf.push(byte);
+            }
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+                match capture.take() {
+                    Some((true, buf)) => key = buf,
+                    Some((false, buf)) => {
+                        scan.session_id = serde_json::from_slice(&buf).ok();
+                        if stop_at_session_id {
+                            return Ok(scan);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            continue;
+        }
+        if in_messages && depth == 2 && element_start.is_none() && !byte.is_ascii_whitespace() && !matches!(byte, b',' | b']') {
+            element_start = Some(pos);
+        }
+        match byte {
+            b'"' => {
+                in_string = true;
+                if depth == 1 && expect_key {
+                    expect_key = false;
+                    capture = Some((true, vec![byte]));
+                } else if depth == 1 && key == b"\"sessionId\"" {
+                    capture = Some((false, vec![byte]));
+                }
+            }
+            b'{' | b'[' => {
+                if depth == 1 && byte == b'[' && key == b"\"messages\"" {
+                    in_messages = true;
+                }
+                depth += 1;
+                expect_key = depth == 1;
+            }
+            b'}' | b']' => {
+                // 元素内部的括号在更深的层级闭合，depth 为 2 时只可能是 messages 数组结束
+                if in_messages && depth == 2 {
+                    if let Some(start) = element_start.take() {
+                        scan.messages.push((start, pos));
+                    }
+                    in_messages = false;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            b',' => {
+                expect_key = depth == 1;
+                if in_messages && depth == 2 {
+                    if let Some(start) = element_start.take() {
+                        scan.messages.push((start, pos));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(scan)
+}
+
+/// 在项目的 chats 目录中查找 Gemini 会话文件（每个文件只读到 sessionId）
+fn find_gemini_session_file(project_path: &str, session_id: &str) -> Result<PathBuf, String> {
+    let chats_dir = get_project_session_dir(project_path)?.join("chats");
+    let entries = std::fs::read_dir(&chats_dir).map_err(|e| format!("读取 Gemini 会话目录失败: {}", e))?;
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .find(|path| {
+            File::open(path)
+                .ok()
+                .and_then(|file| scan_gemini_session(file, true).ok())
+                .and_then(|scan| scan.session_id)
+                .is_some_and(|id| id == session_id)
+        })
+        .ok_or_else(|| format!("Session {} not found", session_id))
+}
+
+fn read_ranges(path: &Path, ranges: &[(u64, u64)], indices: &[usize]) -> Result<Vec<Value>, String> {
+    let mut file = File::open(path).map_err(|e| format!("打开会话文件失败: {}", e))?;
+    let mut messages = Vec::with_capacity(indices.len());
+    for &i in indices {
+        let Some(&(start, end)) = ranges.get(i) else {
+            continue;
+        };
+        file.seek(SeekFrom::Start(start))
+            .map_err(|e| format!("读取会话文件失败: {}", e))?;
+        let mut buf = vec![0u8; (end - start) as usize];
+        file.read_exact(&mut buf).map_err(|e| format!("读取会话文件失败: {}", e))?;
+        match serde_json::from_slice::<Value>(&buf) {
+            Ok(message) => messages.push(message),
+            Err(e) => log::warn!("[SessionPages] Skipping unparsable message {} in {:?}: {}", i, path, e),
+        }
+    }
+    Ok(messages)
+}
+
+/// 计算要读取的行号（按返回顺序）
+fn page_indices(total: usize, offset: usize, limit: usize, reverse: bool) -> Vec<usize> {
+    let end = offset.saturating_add(limit).min(total);
+    if offset >= end {
+        return Vec::new();
+    }
+    if reverse {
+        (offset..end).map(|i| total - 1 - i).collect()
+    } else {
+        (offset..end).collect()
+    }
+}
+
+fn read_lines(path: &Path, index: &LineIndex, indices: &[usize]) -> Result<Vec<Value>, String> {
+    let file = File::open(path).map_err(|e| format!("打开会话文件失败: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut messages = Vec::with_capacity(indices.len());
+    let mut buf = Vec::new();
+    for &i in indices {
+        let Some(offset) = index.line_offset(i) else {
+            continue;
+        };
+        reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| format!("读取会话文件失败: {}", e))?;
+        buf.clear();
+        reader
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| format!("读取会话文件失败: {}", e))?;
+        match serde_json::from_slice::<Value>(&buf) {
+            Ok(message) => messages.push(message),
+            Err(e) => log::warn!("[SessionPages] Skipping unparsable line {} in {:?}: {}", i + 1, path, e),
+        }
+    }
+    Ok(messages)
+}
+
+fn find_claude_session_file(session_id: &str, project_id: Option<&str>) -> Result<PathBuf, String> {
+    let projects_dir = get_claude_dir().map_err(|e| e.to_string())?.join("projects");
+    let file_name = format!("{}.jsonl", session_id);
+    if let Some(project_id) = project_id {
+        let path = projects_dir.join(project_id).join(&file_name);
+        return if path.is_file() {
+            Ok(path)
+        } else {
+            Err(format!("Session file not found: {}", session_id))
+        };
+    }
+    std::fs::read_dir(&projects_dir)
+        .map_err(|e| format!("读取 Claude 项目目录失败: {}", e))?
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("Session file not found: {}", session_id))
+}
+
+/// 查找 Claude / Codex 会话的 JSONL 文件
+pub fn locate_session_file(engine: &str, session_id: &str, project: Option<&str>) -> Result<PathBuf, String> {
+    match engine {
+        "claude" => find_claude_session_file(session_id, project),
+        "codex" => find_session_file_in_all_dirs(session_id),
+        _ => Err(format!("不支持的引擎: {}", engine)),
+    }
+}
+
+fn load_jsonl_page(
+    engine: &str,
+    session_id: &str,
+    project: Option<&str>,
+    offset: usize,
+    limit: usize,
+    reverse: bool,
+) -> Result<SessionMessagePage, String> {
+    let key = (engine.to_string(), session_id.to_string());
+    let cached = INDEXES.lock().map_err(|e| e.to_string())?.remove(&key);
+    let mut cached = match cached {
+        Some(cached) if cached.path.is_file() => cached,
+        _ => CachedIndex {
+            path: locate_session_file(engine, session_id, project)?,
+            index: LineIndex::default(),
+            last_used: Instant::now(),
+        },
+    };
+    cached.index.refresh(&cached.path)?;
+
+    let total = cached.index.total();
+    let messages = read_lines(&cached.path, &cached.index, &page_indices(total, offset, limit, reverse))?;
+    cached.last_used = Instant::now();
+
+    let mut indexes = INDEXES.lock().map_err(|e| e.to_string())?;
+    if indexes.len() >= MAX_CACHED_INDEXES {
+        if let Some(oldest) = indexes.iter().min_by_key(|(_, c)| c.last_used).map(|(k, _)| k.clone()) {
+            indexes.remove(&oldest);
+        }
+    }
+    indexes.insert(key, cached);
+
+    Ok(SessionMessagePage {
+        messages,
+        total,
+        offset,
+        has_more: offset.saturating_add(limit) < total,
+    })
+}
+
+fn load_gemini_page(
+    session_id: &str,
+    project_path: Option<&str>,
+    offset: usize,
+    limit: usize,
+    reverse: bool,
+) -> Result<SessionMessagePage, String> {
+    let project_path = project_path.ok_or("加载 Gemini 会话需要提供项目路径")?;
+    let key = (project_path.to_string(), session_id.to_string());
+    let cached = GEMINI_INDEXES.lock().map_err(|e| e.to_string())?.remove(&key);
+    let path = match cached.as_ref().filter(|c| c.path.is_file()) {
+        Some(cached) => cached.path.clone(),
+        None => find_gemini_session_file(project_path, session_id)?,
+    };
+    let metadata = std::fs::metadata(&path).map_err(|e| format!("读取会话文件信息失败: {}", e))?;
+    let modified = metadata.modified().ok();
+    let mut cached = match cached.filter(|c| c.path == path && c.len == metadata.len() && c.modified == modified) {
+        Some(cached) => cached,
+        None => {
+            let file = File::open(&path).map_err(|e| format!("打开会话文件失败: {}", e))?;
+            CachedGeminiIndex {
+                messages: scan_gemini_session(file, false)?.messages,
+                path,
+                len: metadata.len(),
+                modified,
+                last_used: Instant::now(),
+            }
+        }
+    };
+
+    let total = cached.messages.len();
+    let messages = read_ranges(&cached.path, &cached.messages, &page_indices(total, offset, limit, reverse))?;
+    cached.last_used = Instant::now();
+
+    let mut indexes = GEMINI_INDEXES.lock().map_err(|e| e.to_string())?;
+    if indexes.len() >= MAX_CACHED_INDEXES {
+        if let Some(oldest) = indexes.iter().min_by_key(|(_, c)| c.last_used).map(|(k, _)| k.clone()) {
+            indexes.remove(&oldest);
+        }
+    }
+    indexes.insert(key, cached);
+
+    Ok(SessionMessagePage {
+        messages,
+        total,
+        offset,
+        has_more: offset.saturating_add(limit) < total,
+    })
+}
+
+/// 分页加载会话消息
+///
+/// `project` 为 Claude 的项目 ID（省略时在所有项目中查找）或 Gemini 的项目路径（必填），Codex 忽略。
+#[tauri::command]
+pub async fn load_session_messages(
+    engine: String,
+    session_id: String,
+    offset: usize,
+    limit: usize,
+    reverse: Option<bool>,
+    project: Option<String>,
+) -> Result<SessionMessagePage, String> {
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(format!("limit 必须在 1-{} 之间", MAX_PAGE_SIZE));
+    }
+    validate_path_component(&session_id, "会话 ID")?;
+    if engine == "claude" {
+        if let Some(project_id) = project.as_deref() {
+            validate_path_component(project_id, "项目 ID")?;
+        }
+    }
+    let reverse = reverse.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || match engine.as_str() {
+        "gemini" => load_gemini_page(&session_id, project.as_deref(), offset, limit, reverse),
+        _ => load_jsonl_page(&engine, &session_id, project.as_deref(), offset, limit, reverse),
+    })
+    .await
+    .map_err(|e| format!("加载会话消息失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn indexes_lines_lazily_and_pages_both_ways() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        std::fs::write(&path, "{\"n\":0}\n\n{\"n\":1}\n{\"n\":2}\n{\"n\":").unwrap();
+
+        let mut index = LineIndex::default();
+        index.refresh(&path).unwrap();
+        // 末尾未完成的行也算一条，读取时解析失败会被跳过
+        assert_eq!(index.total(), 4);
+        let read = |index: &LineIndex, indices: Vec<usize>| -> Vec<i64> {
+            read_lines(&path, index, &indices)
+                .unwrap()
+                .iter()
+                .map(|m| m["n"].as_i64().unwrap())
+                .collect()
+        };
+        assert_eq!(read(&index, page_indices(index.total(), 0, 2, false)), vec![0, 1]);
+
+        // 追加写入时只补建新增部分
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"3}\n{\"n\":4}\n").unwrap();
+        drop(file);
+        let (offsets, indexed_to) = (index.offsets.clone(), index.indexed_to);
+        index.refresh(&path).unwrap();
+        assert_eq!(index.offsets[..offsets.len()], offsets[..]);
+        assert!(index.indexed_to > indexed_to);
+        assert_eq!(index.total(), 5);
+        assert_eq!(index.tail, None);
+        assert_eq!(read(&index, page_indices(5, 0, 2, true)), vec![4, 3]);
+        assert_eq!(read(&index, page_indices(5, 4, 2, true)), vec![0]);
+        assert!(page_indices(5, 5, 2, false).is_empty());
+
+        // 文件被改写（变小）时重建
+        std::fs::write(&path, "{\"n\":9}\n").unwrap();
+        index.refresh(&path).unwrap();
+        assert_eq!(index.total(), 1);
+        assert_eq!(read(&index, vec![0]), vec![9]);
+    }
+
+    #[test]
+    fn scans_gemini_messages_without_parsing_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let content = r#"{
+  "sessionId": "g-1",
+  "note": "a \"quoted\" [string], with {braces}",
+  "messages": [
+    {"type": "user", "content": "hi ] , }"},
+    {"type": "gemini", "toolCalls": [{"args": {"a": [1, 2]}}]},
+    "odd"
+  ],
+  "lastUpdated": "now"
+}"#;
+        std::fs::write(&path, content).unwrap();
+
+        let scan = scan_gemini_session(File::open(&path).unwrap(), false).unwrap();
+        assert_eq!(scan.session_id.as_deref(), Some("g-1"));
+        assert_eq!(scan.messages.len(), 3);
+        let messages = read_ranges(&path, &scan.messages, &page_indices(3, 0, 2, true)).unwrap();
+        assert_eq!(messages[0], Value::String("odd".into()));
+        assert_eq!(messages[1]["toolCalls"][0]["args"]["a"][1], 2);
+        let first = read_ranges(&path, &scan.messages, &[0]).unwrap();
+        assert_eq!(first[0]["content"], "hi ] , }");
+
+        // 查找文件时读到 sessionId 即停止
+        let head = scan_gemini_session(File::open(&path).unwrap(), true).unwrap();
+        assert_eq!(head, GeminiScan { session_id: Some("g-1".into()), messages: Vec::new() });
+
+        assert!(validate_path_component("abc-123", "会话 ID").is_ok());
+        for bad in ["", "../x", "a/b", "a\\b", ".."] {
+            assert!(validate_path_component(bad, "会话 ID").is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! 会话总结
+//!
+//! 把会话记录整理成文字稿交给会话所用的引擎（当前代理商配置），生成结构化总结：
+//! 概述、目标、涉及的文件、关键决策和待办。总结以 (engine, session_id) 为键保存在
+//! agents.db 的 `session_summaries` 表中，会话列表通过 `SessionMetadata::summary` 带出。
+//!
+//! 文字稿的读取与执行（`load_transcript` / `run_transcript_prompt`）也供项目记忆的笔记提取使用。
+
+use crate::tr;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use super::headless::{run_headless, HeadlessRequest};
+use super::storage::open_agent_db;
+
+/// 交给引擎的文字稿最大长度（字符），超出时保留开头和结尾
+const MAX_TRANSCRIPT_CHARS: usize = 60_000;
+
+/// 单条消息在文字稿中的最大长度（字符）
+const MAX_MESSAGE_CHARS: usize = 4_000;
+
+const SUMMARY_PROMPT: &str = "Summarize the coding session transcript below.\n\
+Reply with a single JSON object and nothing else, using these keys:\n\
+- \"overview\": one or two sentences describing what the session was about\n\
+- \"goals\": what the user wanted to achieve\n\
+- \"filesTouched\": paths of files that were created, modified or deleted\n\
+- \"decisions\": notable technical decisions and their reasons\n\
+- \"todos\": work that was left unfinished or suggested as follow-up\n\
+All keys except \"overview\" are arrays of short strings. \
+Write in the same language the user used in the transcript. Do not use any tools.";
+
+/// 会话总结
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    #[serde(default)]
+    pub overview: String,
+    #[serde(default)]
+    pub goals: Vec<String>,
+    #[serde(default)]
+    pub files_touched: Vec<String>,
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    #[serde(default)]
+    pub todos: Vec<String>,
+    /// 生成总结的引擎
+    #[serde(default)]
+    pub generated_by: String,
+    #[serde(default)]
+    pub generated_at: String,
+}
+
+/// 创建会话总结表（幂等）
+pub fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_summaries (
+            engine TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            PRIMARY KEY (engine, session_id)
+        );",
+    )
+    .map_err(|e| format!("创建会话总结表失败: {}", e))
+}
+
+/// 读取某个引擎的全部会话总结
+pub fn load_engine_summaries(conn: &Connection, engine: &str) -> Result<HashMap<String, SessionSummary>, String> {
+    let mut stmt = conn
+        .prepare("SELECT session_id, summary FROM session_summaries WHERE engine = ?1")
+        .map_err(|e| format!("查询会话总结失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![engine], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("查询会话总结失败: {}", e))?
+        .filter_map(|row| row.ok())
+        .filter_map(|(session_id, json)| Some((session_id, serde_json::from_str(&json).ok()?)))
+        .collect();
+    Ok(rows)
+}
+
+fn save_summary(conn: &Connection, engine: &str, session_id: &str, summary: &SessionSummary) -> Result<(), String> {
+    let json = serde_json::to_string(summary).map_err(|e| format!("序列化会话总结失败: {}", e))?;
+    conn.execute(
+        "INSERT INTO session_summaries (engine, session_id, summary) VALUES (?1, ?2, ?3)
+         ON CONFLICT (engine, session_id) DO UPDATE SET summary = excluded.summary",
+        params![engine, session_id, json],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("保存会话总结失败: {}", e))
+}
+
+// ============================================================================
+// 文字稿
+// ============================================================================
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(max).collect();
+    format!("{}…", head)
+}
+
+/// 工具调用的简短描述（工具名 + 主要参数）
+fn describe_tool(name: &str, input: &Value) -> String {
+    let target = ["file_path", "path", "command", "pattern", "url"]
+        .iter()
+        .find_map(|key| match &input[*key] {
+            Value::String(s) => Some(s.clone()),
+            Value::Array(parts) => Some(
+                parts.iter().filter_map(|p| p.as_str()).collect::<Vec<_>>().join(" "),
+            ),
+            _ => None,
+        });
+    match target {
+        Some(target) => format!("Tool: {} {}", name, truncate_chars(&target, 200)),
+        None => format!("Tool: {}", name),
+    }
+}
+
+fn push_message(lines: &mut Vec<String>, role: &str, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        lines.push(format!("{}: {}", role, truncate_chars(text, MAX_MESSAGE_CHARS)));
+    }
+}
+
+/// Claude：`type` 为 user / assistant，`message.content` 为字符串或内容块数组
+pub fn claude_transcript(entries: &[Value]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for entry in entries {
+        let role = match entry["type"].as_str() {
+            Some("user") => "User",
+            Some("assistant") => "Assistant",
+            _ => continue,
+        };
+        match &entry["message"]["content"] {
+            Value::String(text) => push_message(&mut lines, role, text),
+            Value::Array(blocks) => {
+                for block in blocks {
+                    match block["type"].as_str() {
+                        Some("text") => push_message(&mut lines, role, block["text"].as_str().unwrap_or_default()),
+                        Some("tool_use") => {
+                            lines.push(describe_tool(block["name"].as_str().unwrap_or("tool"), &block["input"]))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// Codex：`response_item` 事件中的 message 与 function_call
+pub fn codex_transcript(events: &[Value]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for event in events.iter().filter(|e| e["type"] == "response_item") {
+        let payload = &event["payload"];
+        match payload["type"].as_str() {
+            Some("message") => {
+                let role = match payload["role"].as_str() {
+                    Some("user") => "User",
+                    Some("assistant") => "Assistant",
+                    _ => continue,
+                };
+                for item in payload["content"].as_array().into_iter().flatten() {
+                    let text = item["text"].as_str().unwrap_or_default();
+                    // 跳过注入的环境信息与指令
+                    if role == "User" && text.trim_start().starts_with('<') {
+                        continue;
+                    }
+                    push_message(&mut lines, role, text);
+                }
+            }
+            Some("function_call") => {
+                let arguments = payload["arguments"]
+                    .as_str()
+                    .and_then(|a| serde_json::from_str::<Value>(a).ok())
+                    .unwrap_or(Value::Null);
+                lines.push(describe_tool(payload["name"].as_str().unwrap_or("tool"), &arguments));
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// Gemini：`type` 为 user / gemini，工具调用在 `toolCalls` 中
+pub fn gemini_transcript(messages: &[Value]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for message in messages {
+        let role = match message["type"].as_str() {
+            Some("user") => "User",
+            Some("gemini") => "Assistant",
+            _ => continue,
+        };
+        push_message(&mut lines, role, message["content"].as_str().unwrap_or_default());
+        for call in message["toolCalls"].as_array().into_iter().flatten() {
+            lines.push(describe_tool(call["name"].as_str().unwrap_or("tool"), &call["args"]));
+        }
+    }
+    lines
+}
+
+/// 拼接文字稿；过长时保留开头四分之一和结尾四分之三
+fn build_transcript(lines: &[String]) -> String {
+    let transcript = lines.join("\n\n");
+    let total = transcript.chars().count();
+    if total <= MAX_TRANSCRIPT_CHARS {
+        return transcript;
+    }
+    let head_len = MAX_TRANSCRIPT_CHARS / 4;
+    let tail_len = MAX_TRANSCRIPT_CHARS - head_len;
+    let head: String = transcript.chars().take(head_len).collect();
+    let tail: String = transcript.chars().skip(total - tail_len).collect();
+    format!("{}\n\n[... {} characters omitted ...]\n\n{}", head, total - head_len - tail_len, tail)
+}
+
+/// 读取会话并整理为文字稿条目（每条消息或工具调用一条）
+pub async fn load_transcript(engine: &str, session_id: &str, project_path: &str) -> Result<Vec<String>, String> {
+    match engine {
+        "claude" => {
+            let project_id = super::claude::encode_project_path(project_path);
+            let entries = super::claude::load_session_history(session_id.to_string(), project_id).await?;
+            Ok(claude_transcript(&entries))
+        }
+        "codex" => {
+            let events = super::codex::load_codex_session_history(session_id.to_string()).await?;
+            Ok(codex_transcript(&events))
+        }
+        "gemini" => {
+            let detail =
+                super::gemini::get_gemini_session_detail(project_path.to_string(), session_id.to_string()).await?;
+            Ok(gemini_transcript(&detail.messages))
+        }
+        other => Err(tr!("engine.unsupported", engine = other)),
+    }
+}
+
+/// 从引擎回复中取出 JSON 对象（允许被代码块或说明文字包裹）
+pub fn extract_json_object(reply: &str) -> Option<&str> {
+    match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => Some(&reply[start..=end]),
+        _ => None,
+    }
+}
+
+fn parse_summary_reply(reply: &str) -> Result<SessionSummary, String> {
+    let json = extract_json_object(reply).ok_or("引擎未返回 JSON 格式的总结")?;
+    serde_json::from_str(json).map_err(|e| format!("解析会话总结失败: {}", e))
+}
+
+/// 把指令和会话文字稿交给引擎，返回最终回复
+///
+/// 在临时目录中以只读权限运行，不会在项目的会话列表里留下额外的会话。
+pub async fn run_transcript_prompt(
+    app_handle: &AppHandle,
+    engine: &str,
+    instructions: &str,
+    lines: &[String],
+) -> Result<String, String> {
+    let work_dir = std::env::temp_dir().join("anycode-summaries");
+    std::fs::create_dir_all(&work_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let request = HeadlessRequest {
+        engine: engine.to_string(),
+        project_path: work_dir.to_string_lossy().to_string(),
+        prompt: format!("{}\n\n<transcript>\n{}\n</transcript>", instructions, build_transcript(lines)),
+        model: None,
+        permission_profile: Some("read-only".to_string()),
+        internal: true,
+    };
+    let output = run_headless(app_handle, &request, &Mutex::new(None)).await?;
+    output.text.ok_or_else(|| format!("{} 未返回内容", engine))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 生成会话总结并保存（覆盖已有总结）
+#[tauri::command]
+pub async fn summarize_session(
+    engine: String,
+    session_id: String,
+    project_path: String,
+    app_handle: AppHandle,
+) -> Result<SessionSummary, String> {
+    let lines = load_transcript(&engine, &session_id, &project_path).await?;
+    if lines.is_empty() {
+        return Err("会话中没有可总结的内容".to_string());
+    }
+    log::info!("[SessionSummary] Summarizing {} session {} ({} entries)", engine, session_id, lines.len());
+
+    let reply = run_transcript_prompt(&app_handle, &engine, SUMMARY_PROMPT, &lines).await?;
+    let mut summary = parse_summary_reply(&reply)?;
+    summary.generated_by = engine.clone();
+    summary.generated_at = Utc::now().to_rfc3339();
+
+    let saved = summary.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = open_agent_db()?;
+        ensure_schema(&conn)?;
+        save_summary(&conn, &engine, &session_id, &saved)
+    })
+    .await
+    .map_err(|e| format!("保存会话总结失败: {}", e))??;
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn builds_transcript_and_parses_fenced_reply() {
+        let entries = vec![
+            json!({"type": "user", "message": {"content": "Add a cache to the loader"}}),
+            json!({"type": "assistant", "message": {"content": [
+                {"type": "text", "text": "I'll add an LRU cache."},
+                {"type": "tool_use", "name": "Edit", "input": {"file_path": "src/loader.rs"}}
+            ]}}),
+            json!({"type": "summary", "summary": "ignored"}),
+        ];
+        assert_eq!(
+            claude_transcript(&entries),
+            vec!["User: Add a cache to the loader", "Assistant: I'll add an LRU cache.", "Tool: Edit src/loader.rs"]
+        );
+
+        let events = vec![
+            json!({"type": "response_item", "payload": {"type": "message", "role": "user", "content": [
+                {"type": "input_text", "text": "<environment_context>...</environment_context>"},
+                {"type": "input_text", "text": "run the tests"}
+            ]}}),
+            json!({"type": "response_item", "payload": {"type": "function_call", "name": "shell",
+                "arguments": "{\"command\":[\"cargo\",\"test\"]}"}}),
+        ];
+        assert_eq!(codex_transcript(&events), vec!["User: run the tests", "Tool: shell cargo test"]);
+
+        let reply = "Here is the summary:\n```json\n{\"overview\":\"Added a cache\",\"filesTouched\":[\"src/loader.rs\"],\"todos\":[]}\n```";
+        let summary = parse_summary_reply(reply).unwrap();
+        assert_eq!(summary.overview, "Added a cache");
+        assert_eq!(summary.files_touched, vec!["src/loader.rs"]);
+        assert!(parse_summary_reply("no json here").is_err());
+    }
+}
@@ -5,6 +5,7 @@
  * This enables real-time synchronization when using external tools (e.g., VSCode Codex plugin).
  */
 
+use crate::tr;
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebouncedEvent, Debouncer};
 use std::collections::HashMap;
@@ -352,8 +353,7 @@ fn find_session_file_path(session_id: &str, engine: &str) -> Result<PathBuf, Str
     match engine {
         "codex" => {
             // Use the existing Codex session finder
-            let sessions_dir = super::codex::config::get_codex_sessions_dir()?;
-            super::codex::session::find_session_file(&sessions_dir, session_id)
+            super::codex::session::find_session_file_in_all_dirs(session_id)
         }
         "claude" => {
             // Claude sessions are stored in ~/.claude/projects/{project_id}/sessions/{session_id}.jsonl
@@ -410,7 +410,7 @@ fn find_session_file_path(session_id: &str, engine: &str) -> Result<PathBuf, Str
 
             Err(format!("Gemini session file not found for ID: {}", session_id))
         }
-        _ => Err(format!("Unknown engine: {}", engine)),
+        _ => Err(tr!("engine.unsupported", engine = engine)),
     }
 }
 
@@ -1,6 +1,9 @@
 use log;
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -275,6 +278,653 @@ pub fn git_stash_save(project_path: &str, message: &str) -> Result<(), String> {
     Ok(())
 }
 
+// ============================================================================
+// Git command runner
+// ============================================================================
+
+/// Typed git failure, so callers can react to common cases (e.g. a dirty tree on switch)
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitError {
+    /// git could not be started
+    Spawn(String),
+    NotARepository(String),
+    InvalidBranchName(String),
+    BranchNotFound(String),
+    BranchExists(String),
+    /// Local changes would be overwritten by the operation
+    UncommittedChanges(String),
+    /// Branch has commits not merged into HEAD/upstream (use force to delete)
+    NotFullyMerged(String),
+    CannotDeleteCurrentBranch(String),
+    /// Any other non-zero exit
+    CommandFailed { command: String, stderr: String },
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::Spawn(e) => write!(f, "Failed to execute git: {}", e),
+            GitError::NotARepository(path) => write!(f, "Not a git repository: {}", path),
+            GitError::InvalidBranchName(name) => write!(f, "Invalid branch name: {}", name),
+            GitError::BranchNotFound(name) => write!(f, "Branch not found: {}", name),
+            GitError::BranchExists(name) => write!(f, "Branch already exists: {}", name),
+            GitError::UncommittedChanges(detail) => {
+                write!(f, "Uncommitted changes would be overwritten: {}", detail)
+            }
+            GitError::NotFullyMerged(name) => write!(f, "Branch '{}' is not fully merged", name),
+            GitError::CannotDeleteCurrentBranch(name) => {
+                write!(f, "Cannot delete the checked out branch '{}'", name)
+            }
+            GitError::CommandFailed { command, stderr } => write!(f, "git {} failed: {}", command, stderr),
+        }
+    }
+}
+
+impl From<GitError> for String {
+    fn from(e: GitError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Run git in `dir` and return stdout
+pub fn run_git(dir: impl AsRef<Path>, args: &[&str]) -> Result<String, GitError> {
+    run_git_with_input(dir, args, None)
+}
+
+/// Run git in `dir`, writing `input` to its stdin (e.g. a patch for `git apply -`)
+pub fn run_git_with_input(dir: impl AsRef<Path>, args: &[&str], input: Option<&str>) -> Result<String, GitError> {
+    run_git_command(dir.as_ref(), args, input, None)
+}
+
+fn run_git_command(dir: &Path, args: &[&str], input: Option<&str>, index_file: Option<&Path>) -> Result<String, GitError> {
+    let mut cmd = Command::new("git");
+    cmd.args(args);
+    cmd.current_dir(dir);
+    // Errors are classified by stderr text, so keep git's messages untranslated
+    cmd.env("LC_ALL", "C");
+    if let Some(index_file) = index_file {
+        cmd.env("GIT_INDEX_FILE", index_file);
+    }
+    cmd.stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() });
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let mut child = cmd.spawn().map_err(|e| GitError::Spawn(e.to_string()))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes()).map_err(|e| GitError::Spawn(e.to_string()))?;
+    }
+    let output = child.wait_with_output().map_err(|e| GitError::Spawn(e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotARepository(dir.to_string_lossy().to_string()));
+        }
+        return Err(GitError::CommandFailed {
+            command: args.join(" "),
+            stderr,
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// ============================================================================
+// Branch management
+// ============================================================================
+
+/// A local or remote-tracking branch
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitBranch {
+    /// Short name, e.g. `main` or `origin/main`
+    pub name: String,
+    pub is_remote: bool,
+    pub is_current: bool,
+    pub commit: String,
+    pub upstream: Option<String>,
+    /// Commits ahead/behind upstream (0 without upstream)
+    pub ahead: usize,
+    pub behind: usize,
+    /// Upstream branch was deleted on the remote
+    pub upstream_gone: bool,
+    /// Unix seconds of the tip commit
+    pub last_commit_time: i64,
+    pub last_commit_subject: String,
+}
+
+/// Ahead/behind status of a branch
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchStatus {
+    /// None when HEAD is detached
+    pub branch: Option<String>,
+    /// Base compared against (upstream by default)
+    pub base: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    pub has_uncommitted_changes: bool,
+}
+
+const BRANCH_FORMAT: &str = "%(refname)%1f%(refname:short)%1f%(objectname)%1f%(upstream:short)%1f%(upstream:track,nobracket)%1f%(HEAD)%1f%(committerdate:unix)%1f%(contents:subject)";
+
+/// Parses `%(upstream:track,nobracket)`: "ahead 1, behind 2" / "gone" / ""
+fn parse_track(track: &str) -> (usize, usize, bool) {
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in track.split(',').map(str::trim) {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.parse().unwrap_or(0);
+        }
+    }
+    (ahead, behind, track.trim() == "gone")
+}
+
+fn parse_branches(output: &str) -> Vec<GitBranch> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\u{1f}').collect();
+            if fields.len() < 8 || fields[0].ends_with("/HEAD") {
+                return None;
+            }
+            let (ahead, behind, upstream_gone) = parse_track(fields[4]);
+            Some(GitBranch {
+                name: fields[1].to_string(),
+                is_remote: fields[0].starts_with("refs/remotes/"),
+                is_current: fields[5] == "*",
+                commit: fields[2].to_string(),
+                upstream: Some(fields[3].to_string()).filter(|u| !u.is_empty()),
+                ahead,
+                behind,
+                upstream_gone,
+                last_commit_time: fields[6].parse().unwrap_or(0),
+                last_commit_subject: fields[7].to_string(),
+            })
+        })
+        .collect()
+}
+
+fn branch_exists(project_path: &str, name: &str) -> bool {
+    run_git(project_path, &["show-ref", "--verify", "--quiet", &format!("refs/heads/{}", name)]).is_ok()
+}
+
+fn validate_branch_name(project_path: &str, name: &str) -> Result<(), GitError> {
+    if name.trim().is_empty() || name.starts_with('-') {
+        return Err(GitError::InvalidBranchName(name.to_string()));
+    }
+    run_git(project_path, &["check-ref-format", "--branch", name])
+        .map(|_| ())
+        .map_err(|_| GitError::InvalidBranchName(name.to_string()))
+}
+
+/// Currently checked out branch (None when HEAD is detached)
+pub fn git_current_branch(project_path: &str) -> Result<Option<String>, GitError> {
+    match run_git(project_path, &["symbolic-ref", "--short", "-q", "HEAD"]) {
+        Ok(name) => Ok(Some(name.trim().to_string())),
+        Err(GitError::CommandFailed { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// List local branches, then remote-tracking branches
+pub fn git_list_branches(project_path: &str) -> Result<Vec<GitBranch>, GitError> {
+    let output = run_git(
+        project_path,
+        &["for-each-ref", &format!("--format={}", BRANCH_FORMAT), "refs/heads", "refs/remotes"],
+    )?;
+    Ok(parse_branches(&output))
+}
+
+/// Create a branch from `start_point` (HEAD by default), optionally switching to it
+pub fn git_create_branch(
+    project_path: &str,
+    name: &str,
+    start_point: Option<&str>,
+    checkout: bool,
+) -> Result<(), GitError> {
+    validate_branch_name(project_path, name)?;
+    if branch_exists(project_path, name) {
+        return Err(GitError::BranchExists(name.to_string()));
+    }
+    let start_point = start_point.unwrap_or("HEAD");
+    if run_git(project_path, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", start_point)]).is_err() {
+        return Err(GitError::BranchNotFound(start_point.to_string()));
+    }
+
+    run_git(project_path, &["branch", "--", name, start_point])?;
+    log::info!("Created branch '{}' at {}", name, start_point);
+    if checkout {
+        git_switch_branch(project_path, name)?;
+    }
+    Ok(())
+}
+
+/// Switch to a local branch
+///
+/// A name without a local branch that matches exactly one remote branch (e.g. `main` for
+/// `origin/main`) creates a tracking branch; switching to `origin/main` itself is rejected.
+pub fn git_switch_branch(project_path: &str, name: &str) -> Result<(), GitError> {
+    validate_branch_name(project_path, name)?;
+    match run_git(project_path, &["switch", name]) {
+        Ok(_) => {
+            log::info!("Switched to branch '{}'", name);
+            Ok(())
+        }
+        Err(GitError::CommandFailed { stderr, .. }) if stderr.contains("would be overwritten") => {
+            Err(GitError::UncommittedChanges(stderr))
+        }
+        Err(GitError::CommandFailed { stderr, .. }) if stderr.contains("invalid reference") => {
+            Err(GitError::BranchNotFound(name.to_string()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Delete a local branch; `force` also deletes unmerged branches
+pub fn git_delete_branch(project_path: &str, name: &str, force: bool) -> Result<(), GitError> {
+    if !branch_exists(project_path, name) {
+        return Err(GitError::BranchNotFound(name.to_string()));
+    }
+    if git_current_branch(project_path)?.as_deref() == Some(name) {
+        return Err(GitError::CannotDeleteCurrentBranch(name.to_string()));
+    }
+    match run_git(project_path, &["branch", if force { "-D" } else { "-d" }, "--", name]) {
+        Ok(_) => {
+            log::info!("Deleted branch '{}'", name);
+            Ok(())
+        }
+        Err(GitError::CommandFailed { stderr, .. }) if stderr.contains("not fully merged") => {
+            Err(GitError::NotFullyMerged(name.to_string()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Ahead/behind of `branch` (current by default) against `base` (its upstream by default)
+pub fn git_branch_status(
+    project_path: &str,
+    branch: Option<&str>,
+    base: Option<&str>,
+) -> Result<BranchStatus, GitError> {
+    let branch = match branch {
+        Some(name) => Some(name.to_string()),
+        None => git_current_branch(project_path)?,
+    };
+    let has_uncommitted_changes = !run_git(project_path, &["status", "--porcelain"])?.trim().is_empty();
+
+    let tip = branch.clone().unwrap_or_else(|| "HEAD".to_string());
+    let base = match base {
+        Some(base) => Some(base.to_string()),
+        None => run_git(
+            project_path,
+            &["rev-parse", "--abbrev-ref", "--symbolic-full-name", &format!("{}@{{upstream}}", tip)],
+        )
+        .ok()
+        .map(|upstream| upstream.trim().to_string()),
+    };
+
+    let (ahead, behind) = match &base {
+        Some(base) => {
+            let counts = run_git(project_path, &["rev-list", "--left-right", "--count", &format!("{}...{}", base, tip)])
+                .map_err(|e| match e {
+                    GitError::CommandFailed { .. } => GitError::BranchNotFound(base.clone()),
+                    e => e,
+                })?;
+            let mut counts = counts.split_whitespace().map(|n| n.parse::<usize>().unwrap_or(0));
+            let behind = counts.next().unwrap_or(0);
+            (counts.next().unwrap_or(0), behind)
+        }
+        None => (0, 0),
+    };
+
+    Ok(BranchStatus {
+        branch,
+        base,
+        ahead,
+        behind,
+        has_uncommitted_changes,
+    })
+}
+
+// ============================================================================
+// Hunk staging
+// ============================================================================
+
+/// One hunk of a file's unstaged diff
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    /// Content hash; changes whenever the hunk changes, so stale selections are rejected
+    pub id: String,
+    /// The `@@ -a,b +c,d @@` line
+    pub header: String,
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Hunk body lines with their ' ', '+', '-' or '\' prefix
+    pub lines: Vec<String>,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Unstaged changes of a single file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHunks {
+    pub file: String,
+    /// New file (untracked)
+    pub is_new: bool,
+    /// Binary changes cannot be staged by hunk
+    pub is_binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Parsed `git diff` output of one file: header lines (diff --git ... +++) and hunks
+struct ParsedDiff {
+    header: Vec<String>,
+    hunks: Vec<DiffHunk>,
+    is_binary: bool,
+}
+
+/// Parses "-a,b" / "+c" ranges (count defaults to 1)
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let range = &range[1..];
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize, usize)> {
+    let mut parts = header.strip_prefix("@@ ")?.split_whitespace();
+    let (old_start, old_lines) = parse_range(parts.next()?)?;
+    let (new_start, new_lines) = parse_range(parts.next()?)?;
+    Some((old_start, old_lines, new_start, new_lines))
+}
+
+fn parse_file_diff(diff: &str) -> ParsedDiff {
+    let mut parsed = ParsedDiff {
+        header: Vec::new(),
+        hunks: Vec::new(),
+        is_binary: false,
+    };
+    for line in diff.lines() {
+        if line.starts_with("@@ ") {
+            if let Some((old_start, old_lines, new_start, new_lines)) = parse_hunk_header(line) {
+                parsed.hunks.push(DiffHunk {
+                    id: String::new(),
+                    header: line.to_string(),
+                    old_start,
+                    old_lines,
+                    new_start,
+                    new_lines,
+                    lines: Vec::new(),
+                    added: 0,
+                    removed: 0,
+                });
+                continue;
+            }
+        }
+        match parsed.hunks.last_mut() {
+            Some(hunk) => {
+                if line.starts_with('+') {
+                    hunk.added += 1;
+                } else if line.starts_with('-') {
+                    hunk.removed += 1;
+                }
+                hunk.lines.push(line.to_string());
+            }
+            None => {
+                parsed.is_binary |= line.starts_with("Binary files ") || line == "GIT binary patch";
+                parsed.header.push(line.to_string());
+            }
+        }
+    }
+    for hunk in &mut parsed.hunks {
+        let digest = md5::compute(format!("{}\n{}", hunk.header, hunk.lines.join("\n")));
+        hunk.id = format!("{:x}", digest)[..12].to_string();
+    }
+    parsed
+}
+
+/// Patch containing only the selected hunks, with new-side positions shifted for the skipped ones
+fn build_partial_patch(parsed: &ParsedDiff, hunk_ids: &[String]) -> Result<String, GitError> {
+    if let Some(missing) = hunk_ids.iter().find(|id| !parsed.hunks.iter().any(|h| &h.id == *id)) {
+        return Err(GitError::CommandFailed {
+            command: "apply".to_string(),
+            stderr: format!("hunk {} no longer matches the working tree, refresh and retry", missing),
+        });
+    }
+
+    let mut patch = parsed.header.join("\n");
+    patch.push('\n');
+    let mut offset: isize = 0;
+    for hunk in parsed.hunks.iter().filter(|h| hunk_ids.contains(&h.id)) {
+        let new_start = (hunk.old_start as isize + offset).max(0) as usize;
+        // Keep the trailing function context after the second "@@"
+        let context = hunk.header[2..].split_once("@@").map(|(_, c)| c).unwrap_or("");
+        patch.push_str(&format!(
+            "@@ -{},{} +{},{} @@{}\n",
+            hunk.old_start, hunk.old_lines, new_start, hunk.new_lines, context
+        ));
+        for line in &hunk.lines {
+            patch.push_str(line);
+            patch.push('\n');
+        }
+        offset += hunk.new_lines as isize - hunk.old_lines as isize;
+    }
+    Ok(patch)
+}
+
+fn is_untracked(project_path: &str, file: &str) -> Result<bool, GitError> {
+    let output = run_git(project_path, &["ls-files", "--others", "--exclude-standard", "--", file])?;
+    Ok(!output.trim().is_empty())
+}
+
+/// Unstaged diff of a file; untracked files are diffed as new files
+///
+/// Untracked files are marked intent-to-add in a copy of the index, so the user's index is untouched.
+fn unstaged_diff(project_path: &str, file: &str) -> Result<String, GitError> {
+    let args = ["diff", "--no-color", "--no-ext-diff", "-U3", "--", file];
+    if !is_untracked(project_path, file)? {
+        return run_git(project_path, &args);
+    }
+    let dir = Path::new(project_path);
+    let index = temp_index(dir)?;
+    run_git_command(dir, &["add", "--intent-to-add", "--", file], None, Some(index.path()))?;
+    run_git_command(dir, &args, None, Some(index.path()))
+}
+
+/// Hunks of a file's unstaged changes (untracked files show as a single new-file hunk)
+pub fn git_unstaged_hunks(project_path: &str, file: &str) -> Result<FileHunks, GitError> {
+    let parsed = parse_file_diff(&unstaged_diff(project_path, file)?);
+    Ok(FileHunks {
+        file: file.to_string(),
+        is_new: parsed.header.iter().any(|l| l.starts_with("new file mode")),
+        is_binary: parsed.is_binary,
+        hunks: parsed.hunks,
+    })
+}
+
+/// Stage only the given hunks (ids from `git_unstaged_hunks`)
+pub fn git_stage_hunks(project_path: &str, file: &str, hunk_ids: &[String]) -> Result<(), GitError> {
+    if hunk_ids.is_empty() {
+        return Ok(());
+    }
+    let parsed = parse_file_diff(&unstaged_diff(project_path, file)?);
+    if parsed.is_binary {
+        run_git(project_path, &["add", "--", file])?;
+        return Ok(());
+    }
+    let patch = build_partial_patch(&parsed, hunk_ids)?;
+    run_git_with_input(
+        project_path,
+        &["apply", "--cached", "--whitespace=nowarn", "-"],
+        Some(&patch),
+    )?;
+    log::info!("Staged {} hunk(s) of {}", hunk_ids.len(), file);
+    Ok(())
+}
+
+/// Remove a file's staged changes, keeping the working tree
+pub fn git_unstage_file(project_path: &str, file: &str) -> Result<(), GitError> {
+    if git_current_commit(project_path).is_ok() {
+        run_git(project_path, &["reset", "-q", "HEAD", "--", file])?;
+    } else {
+        // No commits yet: nothing to reset to
+        run_git(project_path, &["rm", "-q", "--cached", "--", file])?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Working tree snapshots
+// ============================================================================
+
+/// Tree hash of the working tree, including uncommitted and untracked (not ignored) files
+///
+/// Stages into a copy of the index so the user's staging area is left untouched.
+pub fn git_worktree_tree(project_path: &str) -> Result<String, GitError> {
+    let dir = Path::new(project_path);
+    let temp = temp_index(dir)?;
+    run_git_command(dir, &["add", "-A"], None, Some(temp.path()))?;
+    let tree = run_git_command(dir, &["write-tree"], None, Some(temp.path()))?;
+    Ok(tree.trim().to_string())
+}
+
+/// Copy of the repository's index for use as `GIT_INDEX_FILE`
+fn temp_index(dir: &Path) -> Result<tempfile::NamedTempFile, GitError> {
+    let index_path = run_git(dir, &["rev-parse", "--git-path", "index"])?;
+    let index_path = dir.join(index_path.trim());
+    let temp = tempfile::NamedTempFile::new().map_err(|e| GitError::Spawn(e.to_string()))?;
+    if index_path.is_file() {
+        // Reusing the index keeps its stat cache, so unchanged files are not re-hashed
+        std::fs::copy(&index_path, temp.path()).map_err(|e| GitError::Spawn(e.to_string()))?;
+    } else {
+        std::fs::remove_file(temp.path()).map_err(|e| GitError::Spawn(e.to_string()))?;
+    }
+    Ok(temp)
+}
+
+/// Paths (relative to `project_path`) that differ between two commits or trees
+pub fn git_changed_paths(project_path: &str, from: &str, to: &str) -> Result<Vec<String>, GitError> {
+    let output = run_git(
+        project_path,
+        &["diff", "--name-only", "--no-renames", "--relative", "-z", from, to, "--"],
+    )?;
+    Ok(output.split('\0').filter(|p| !p.is_empty()).map(str::to_string).collect())
+}
+
+/// Three-way merge of file contents: applies the change `base` -> `other` on top of `current`
+///
+/// Returns None when the changes conflict.
+pub fn git_merge_file(current: &str, base: &str, other: &str) -> Result<Option<String>, GitError> {
+    let dir = tempfile::tempdir().map_err(|e| GitError::Spawn(e.to_string()))?;
+    let write = |name: &str, content: &str| {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content)
+            .map(|_| path)
+            .map_err(|e| GitError::Spawn(e.to_string()))
+    };
+    let (current, base, other) = (write("current", current)?, write("base", base)?, write("other", other)?);
+
+    let mut cmd = Command::new("git");
+    cmd.arg("merge-file").arg("-p").arg("-q").arg(&current).arg(&base).arg(&other);
+    cmd.stdin(Stdio::null());
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd.output().map_err(|e| GitError::Spawn(e.to_string()))?;
+    match output.status.code() {
+        Some(0) => Ok(Some(String::from_utf8_lossy(&output.stdout).to_string())),
+        // Positive exit codes are the number of conflicts
+        Some(code) if code > 0 => Ok(None),
+        _ => Err(GitError::CommandFailed {
+            command: "merge-file".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }),
+    }
+}
+
+// ============================================================================
+// Tauri commands
+// ============================================================================
+
+/// Runs a blocking git operation off the async runtime
+async fn blocking<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, GitError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("git task failed: {}", e))?
+        .map_err(String::from)
+}
+
+/// Tauri command: List local and remote-tracking branches
+#[tauri::command]
+pub async fn list_branches(project_path: String) -> Result<Vec<GitBranch>, String> {
+    blocking(move || git_list_branches(&project_path)).await
+}
+
+/// Tauri command: Create a branch (optionally switching to it)
+#[tauri::command]
+pub async fn create_branch(
+    project_path: String,
+    name: String,
+    start_point: Option<String>,
+    checkout: Option<bool>,
+) -> Result<(), String> {
+    blocking(move || git_create_branch(&project_path, &name, start_point.as_deref(), checkout.unwrap_or(false))).await
+}
+
+/// Tauri command: Switch to a branch
+#[tauri::command]
+pub async fn switch_branch(project_path: String, name: String) -> Result<(), String> {
+    blocking(move || git_switch_branch(&project_path, &name)).await
+}
+
+/// Tauri command: Delete a local branch
+#[tauri::command]
+pub async fn delete_branch(project_path: String, name: String, force: Option<bool>) -> Result<(), String> {
+    blocking(move || git_delete_branch(&project_path, &name, force.unwrap_or(false))).await
+}
+
+/// Tauri command: Get ahead/behind status of a branch
+#[tauri::command]
+pub async fn get_branch_status(
+    project_path: String,
+    branch: Option<String>,
+    base: Option<String>,
+) -> Result<BranchStatus, String> {
+    blocking(move || git_branch_status(&project_path, branch.as_deref(), base.as_deref())).await
+}
+
+/// Tauri command: Get the unstaged hunks of a file
+#[tauri::command]
+pub async fn get_unstaged_hunks(project_path: String, file: String) -> Result<FileHunks, String> {
+    blocking(move || git_unstaged_hunks(&project_path, &file)).await
+}
+
+/// Tauri command: Stage selected hunks of a file
+#[tauri::command]
+pub async fn stage_hunks(project_path: String, file: String, hunk_ids: Vec<String>) -> Result<(), String> {
+    blocking(move || git_stage_hunks(&project_path, &file, &hunk_ids)).await
+}
+
+/// Tauri command: Unstage a file
+#[tauri::command]
+pub async fn unstage_file(project_path: String, file: String) -> Result<(), String> {
+    blocking(move || git_unstage_file(&project_path, &file)).await
+}
+
 /// Tauri command: Check and initialize Git repository
 #[tauri::command]
 pub fn check_and_init_git(project_path: String) -> Result<bool, String> {
@@ -285,3 +935,118 @@ pub fn check_and_init_git(project_path: String) -> Result<bool, String> {
 
     Ok(was_not_initialized)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        run_git(dir, args).unwrap();
+    }
+
+    #[test]
+    fn manages_branches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        git(dir.path(), &["init", "-q", "-b", "main"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        git(dir.path(), &["config", "user.email", "t@t"]);
+        git(dir.path(), &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+        git_create_branch(path, "feature/x", None, true).unwrap();
+        assert_eq!(git_current_branch(path).unwrap().as_deref(), Some("feature/x"));
+        assert_eq!(git_create_branch(path, "main", None, false), Err(GitError::BranchExists("main".into())));
+        assert_eq!(git_create_branch(path, "bad..name", None, false), Err(GitError::InvalidBranchName("bad..name".into())));
+        assert_eq!(git_create_branch(path, "y", Some("nope"), false), Err(GitError::BranchNotFound("nope".into())));
+
+        git(dir.path(), &["commit", "-q", "--allow-empty", "-m", "work"]);
+        let status = git_branch_status(path, None, Some("main")).unwrap();
+        assert_eq!((status.ahead, status.behind, status.has_uncommitted_changes), (1, 0, false));
+        assert_eq!(git_branch_status(path, None, None).unwrap().base, None);
+
+        let branches = git_list_branches(path).unwrap();
+        let names: Vec<_> = branches.iter().map(|b| (b.name.as_str(), b.is_current)).collect();
+        assert_eq!(names, vec![("feature/x", true), ("main", false)]);
+        assert_eq!(branches[0].last_commit_subject, "work");
+
+        assert_eq!(git_delete_branch(path, "feature/x", false), Err(GitError::CannotDeleteCurrentBranch("feature/x".into())));
+        git_switch_branch(path, "main").unwrap();
+        assert_eq!(git_delete_branch(path, "feature/x", false), Err(GitError::NotFullyMerged("feature/x".into())));
+        git_delete_branch(path, "feature/x", true).unwrap();
+        assert_eq!(git_switch_branch(path, "feature/x"), Err(GitError::BranchNotFound("feature/x".into())));
+
+        assert_eq!(parse_track("ahead 2, behind 3"), (2, 3, false));
+        assert_eq!(parse_track("gone"), (0, 0, true));
+    }
+
+    #[test]
+    fn stages_selected_hunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        git(dir.path(), &["config", "user.email", "t@t"]);
+        let original: Vec<String> = (1..=30).map(|i| format!("line {}", i)).collect();
+        std::fs::write(dir.path().join("a.txt"), original.join("\n") + "\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-q", "-m", "init"]);
+
+        let mut changed = original.clone();
+        changed.insert(2, "agent: first".to_string());
+        changed[25] = "agent: second".to_string();
+        std::fs::write(dir.path().join("a.txt"), changed.join("\n") + "\n").unwrap();
+
+        let file = git_unstaged_hunks(path, "a.txt").unwrap();
+        assert_eq!(file.hunks.len(), 2);
+        assert!(!file.is_new && !file.is_binary);
+        assert_eq!((file.hunks[1].added, file.hunks[1].removed), (1, 1));
+
+        // Stage only the second hunk: its new-side position must ignore the skipped insert
+        git_stage_hunks(path, "a.txt", &[file.hunks[1].id.clone()]).unwrap();
+        let staged = run_git(dir.path(), &["diff", "--cached"]).unwrap();
+        assert!(staged.contains("+agent: second") && !staged.contains("agent: first"));
+        let remaining = git_unstaged_hunks(path, "a.txt").unwrap();
+        assert_eq!(remaining.hunks.len(), 1);
+        assert!(git_stage_hunks(path, "a.txt", &[file.hunks[1].id.clone()]).is_err());
+
+        git_unstage_file(path, "a.txt").unwrap();
+        assert!(run_git(dir.path(), &["diff", "--cached"]).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("new.txt"), "hello\n").unwrap();
+        let new_file = git_unstaged_hunks(path, "new.txt").unwrap();
+        assert!(new_file.is_new);
+        // Listing hunks must not add the file to the index
+        assert_eq!(run_git(dir.path(), &["status", "--porcelain", "--", "new.txt"]).unwrap(), "?? new.txt\n");
+        git_stage_hunks(path, "new.txt", &[new_file.hunks[0].id.clone()]).unwrap();
+        assert!(run_git(dir.path(), &["diff", "--cached"]).unwrap().contains("+hello"));
+    }
+
+    #[test]
+    fn snapshots_working_tree_without_touching_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        git(dir.path(), &["config", "user.email", "t@t"]);
+        std::fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-q", "-m", "init"]);
+        let head = git_current_commit(path).unwrap();
+
+        let clean = git_worktree_tree(path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "b\n").unwrap();
+        std::fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+        let dirty = git_worktree_tree(path).unwrap();
+        assert_ne!(clean, dirty);
+        assert!(run_git(dir.path(), &["diff", "--cached"]).unwrap().is_empty());
+        assert!(git_changed_paths(path, &head, &clean).unwrap().is_empty());
+        assert_eq!(git_changed_paths(path, &clean, &dirty).unwrap(), vec!["a.txt", "new.txt"]);
+
+        let base = "1\n2\n3\n4\n5\n6\n";
+        let agent = "1\nagent\n3\n4\n5\n6\n";
+        let manual = "1\nagent\n3\n4\n5\nmanual\n";
+        // Undo the agent's edit (agent -> base) while keeping the manual one
+        assert_eq!(git_merge_file(manual, agent, base).unwrap().as_deref(), Some("1\n2\n3\n4\n5\nmanual\n"));
+        assert_eq!(git_merge_file("1\nmanual\n3\n4\n5\n6\n", agent, base).unwrap(), None);
+    }
+}
@@ -0,0 +1,439 @@
+//! 远程 SSH 执行支持
+//!
+//! 在远程开发机上通过 `ssh host codex ...` 执行 Codex（开启 `runClaude` 时 Claude 同样），
+//! 输出经 SSH 回传，沿用本地的事件流水线。远程 `~/.codex/sessions` 通过 sftp 镜像到
+//! `~/.anycode/remote/<host>/sessions`，供历史记录、撤回等功能读取；
+//! 本地修改的会话文件（如撤回截断）再通过 sftp 写回远程。Claude 的项目会话目录在每次执行
+//! 结束后同步到本地 `~/.claude/projects` 下对应的项目目录。
+//!
+//! 同步均在后台线程或 `spawn_blocking` 中进行，读取会话目录的调用不会等待 sftp。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::claude::apply_no_window;
+use super::data_root::anycode_dir;
+
+/// 自动同步的最小间隔（列表/历史查询会频繁触发）
+const SYNC_INTERVAL: Duration = Duration::from_secs(15);
+/// SSH 连接超时（秒）
+const CONNECT_TIMEOUT_SECS: u32 = 10;
+/// 远程会话目录（相对远程 home）
+const REMOTE_SESSIONS_DIR: &str = ".codex/sessions";
+/// 远程 Claude 项目目录（相对远程 home）
+const REMOTE_CLAUDE_PROJECTS_DIR: &str = ".claude/projects";
+
+/// 远程主机配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConfig {
+    /// 主机名、IP 或 ~/.ssh/config 中的 Host 别名
+    pub host: String,
+    /// 登录用户（留空使用 ssh 默认）
+    #[serde(default)]
+    pub user: Option<String>,
+    /// 端口（留空为 22 或 ssh config 中的设置）
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// 私钥路径
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    /// 远程 Codex 可执行文件（留空使用远程 PATH 中的 `codex`）
+    #[serde(default)]
+    pub codex_path: Option<String>,
+    /// Claude 也在该主机上执行
+    #[serde(default)]
+    pub run_claude: bool,
+    /// 远程 Claude 可执行文件（留空使用远程 PATH 中的 `claude`）
+    #[serde(default)]
+    pub claude_path: Option<String>,
+}
+
+impl RemoteConfig {
+    /// `user@host` 或 `host`
+    pub fn destination(&self) -> String {
+        match self.user.as_deref().filter(|u| !u.is_empty()) {
+            Some(user) => format!("{}@{}", user, self.host),
+            None => self.host.clone(),
+        }
+    }
+
+    fn codex_program(&self) -> &str {
+        self.codex_path
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .unwrap_or("codex")
+    }
+
+    fn claude_program(&self) -> &str {
+        self.claude_path
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .unwrap_or("claude")
+    }
+
+    /// ssh / sftp 共用的非交互选项（端口参数两者大小写不同）
+    fn common_args(&self, port_flag: &str) -> Vec<String> {
+        let mut args = vec![
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-o".to_string(),
+            format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS),
+        ];
+        if let Some(port) = self.port {
+            args.push(port_flag.to_string());
+            args.push(port.to_string());
+        }
+        if let Some(identity) = self.identity_file.as_deref().filter(|i| !i.is_empty()) {
+            args.push("-i".to_string());
+            args.push(expand_home(identity));
+        }
+        args
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("远程主机不能为空".to_string());
+        }
+        if self.host.starts_with('-') {
+            return Err(format!("无效的远程主机: {}", self.host));
+        }
+        Ok(())
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// POSIX shell 单引号转义
+fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c))
+    {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 拼接在远程 shell 中执行的命令行
+fn build_remote_command_line(program: &str, args: &[String], remote_dir: Option<&str>) -> String {
+    let mut line = String::new();
+    if let Some(dir) = remote_dir {
+        line.push_str(&format!("cd {} && ", shell_quote(dir)));
+    }
+    // exec：ssh 连接断开时远程进程随之收到 SIGHUP
+    line.push_str("exec ");
+    line.push_str(&shell_quote(program));
+    for arg in args {
+        line.push(' ');
+        line.push_str(&shell_quote(arg));
+    }
+    line
+}
+
+/// Claude 在远程执行时的主机配置（Remote 模式且开启了 `runClaude`）
+pub fn active_claude_remote() -> Option<RemoteConfig> {
+    super::wsl_utils::get_codex_config()
+        .active_remote()
+        .filter(|remote| remote.run_claude)
+        .cloned()
+}
+
+/// 项目在远程主机上的目录（项目设置中的 `remotePath`，留空则使用相同路径）
+pub fn remote_project_dir(project_path: &str) -> String {
+    super::project_settings::load_project_settings(project_path)
+        .remote_path
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| project_path.replace('\\', "/"))
+}
+
+/// 构建通过 SSH 执行 Codex 的异步命令 (tokio)
+///
+/// # Arguments
+/// * `config` - 远程主机配置
+/// * `args` - Codex 参数
+/// * `remote_dir` - 远程工作目录
+pub fn build_ssh_codex_command_async(
+    config: &RemoteConfig,
+    args: &[String],
+    remote_dir: Option<&str>,
+) -> tokio::process::Command {
+    build_ssh_command_async(config, config.codex_program(), args, remote_dir)
+}
+
+/// 构建通过 SSH 执行 Claude 的异步命令 (tokio)
+pub fn build_ssh_claude_command_async(
+    config: &RemoteConfig,
+    args: &[String],
+    remote_dir: Option<&str>,
+) -> tokio::process::Command {
+    build_ssh_command_async(config, config.claude_program(), args, remote_dir)
+}
+
+fn build_ssh_command_async(
+    config: &RemoteConfig,
+    program: &str,
+    args: &[String],
+    remote_dir: Option<&str>,
+) -> tokio::process::Command {
+    let remote_line = build_remote_command_line(program, args, remote_dir);
+
+    let mut cmd = tokio::process::Command::new("ssh");
+    cmd.args(config.common_args("-p"));
+    cmd.arg("-T");
+    cmd.arg(config.destination());
+    cmd.arg("--");
+    cmd.arg(&remote_line);
+
+    log::debug!("[SSH] Built async command: ssh {} -- {}", config.destination(), remote_line);
+    cmd
+}
+
+/// 检测远程 Codex，返回版本号
+pub fn check_remote_codex(config: &RemoteConfig) -> Result<String, String> {
+    config.validate()?;
+
+    let mut cmd = Command::new("ssh");
+    cmd.args(config.common_args("-p"));
+    cmd.arg(config.destination());
+    cmd.arg("--");
+    cmd.arg(build_remote_command_line(config.codex_program(), &["--version".to_string()], None));
+    apply_no_window(&mut cmd);
+
+    let output = cmd.output().map_err(|e| format!("无法执行 ssh: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if output.status.success() {
+        Ok(if stdout.is_empty() { stderr } else { stdout })
+    } else {
+        Err(format!("远程 Codex 检测失败 ({}): {}", output.status, stderr))
+    }
+}
+
+// ============================================================================
+// 会话目录镜像 (sftp)
+// ============================================================================
+
+/// 每个主机最近一次同步时间
+static LAST_SYNC: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// 正在后台同步的主机
+static SYNCING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 远程主机在本地的镜像根目录 `~/.anycode/remote/<host>`
+fn mirror_root(config: &RemoteConfig) -> Result<PathBuf, String> {
+    let host_dir: String = config
+        .destination()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "-_.@".contains(c) { c } else { '_' })
+        .collect();
+    Ok(anycode_dir()?.join("remote").join(host_dir))
+}
+
+/// 远程会话目录的本地镜像路径
+pub fn remote_sessions_mirror_dir(config: &RemoteConfig) -> Result<PathBuf, String> {
+    Ok(mirror_root(config)?.join("sessions"))
+}
+
+/// 执行 sftp 批处理脚本
+fn run_sftp_batch(config: &RemoteConfig, script: &str) -> Result<(), String> {
+    let mut cmd = Command::new("sftp");
+    cmd.args(config.common_args("-P"));
+    cmd.args(["-q", "-b", "-"]);
+    cmd.arg(config.destination());
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::piped());
+    apply_no_window(&mut cmd);
+
+    let mut child = cmd.spawn().map_err(|e| format!("无法执行 sftp: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(script.as_bytes())
+            .map_err(|e| format!("写入 sftp 脚本失败: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("等待 sftp 失败: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "sftp 执行失败 ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// sftp 批处理中的路径参数（双引号转义）
+fn sftp_quote(path: &str) -> String {
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 把远程 `~/.codex/sessions` 同步到本地镜像
+///
+/// `force` 为 false 时，距上次同步不足 `SYNC_INTERVAL` 则跳过。
+pub fn sync_remote_sessions(config: &RemoteConfig, force: bool) -> Result<PathBuf, String> {
+    config.validate()?;
+    let root = mirror_root(config)?;
+    let sessions_dir = root.join("sessions");
+    let key = config.destination();
+
+    if !force {
+        if let Some(last) = LAST_SYNC.lock().unwrap().get(&key) {
+            if last.elapsed() < SYNC_INTERVAL && sessions_dir.exists() {
+                return Ok(sessions_dir);
+            }
+        }
+    }
+
+    std::fs::create_dir_all(&root).map_err(|e| format!("创建镜像目录失败: {}", e))?;
+    let script = format!(
+        "lcd {}\nget -r {}\n",
+        sftp_quote(&root.to_string_lossy()),
+        REMOTE_SESSIONS_DIR
+    );
+    let started = Instant::now();
+    run_sftp_batch(config, &script)?;
+    LAST_SYNC.lock().unwrap().insert(key, Instant::now());
+
+    log::info!(
+        "[SSH] Synced remote sessions from {} in {:?}",
+        config.destination(),
+        started.elapsed()
+    );
+    Ok(sessions_dir)
+}
+
+/// 镜像过期时在后台线程中同步远程会话目录，立即返回本地镜像路径
+///
+/// 供可能在 async 命令中调用的路径查询使用；同一主机同时只有一个同步在进行。
+pub fn sync_remote_sessions_in_background(config: &RemoteConfig) -> Result<PathBuf, String> {
+    let sessions_dir = remote_sessions_mirror_dir(config)?;
+    let key = config.destination();
+    let fresh = LAST_SYNC
+        .lock()
+        .unwrap()
+        .get(&key)
+        .is_some_and(|last| last.elapsed() < SYNC_INTERVAL);
+    if fresh || !SYNCING.lock().unwrap().insert(key.clone()) {
+        return Ok(sessions_dir);
+    }
+
+    let config = config.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = sync_remote_sessions(&config, false) {
+            log::warn!("[SSH] Background sync from {} failed: {}", key, e);
+        }
+        SYNCING.lock().unwrap().remove(&key);
+    });
+    Ok(sessions_dir)
+}
+
+/// 把远程项目的 Claude 会话目录同步到本地 `~/.claude/projects/<编码后的本地路径>`
+pub fn sync_remote_claude_project(config: &RemoteConfig, project_path: &str) -> Result<PathBuf, String> {
+    config.validate()?;
+    let remote_dir = remote_project_dir(project_path);
+    let local_dir = super::claude::get_claude_dir()
+        .map_err(|e| e.to_string())?
+        .join("projects")
+        .join(super::claude::encode_project_path(project_path));
+    std::fs::create_dir_all(&local_dir).map_err(|e| format!("创建 Claude 项目目录失败: {}", e))?;
+
+    let script = format!(
+        "get -r {} {}\n",
+        sftp_quote(&format!(
+            "{}/{}",
+            REMOTE_CLAUDE_PROJECTS_DIR,
+            super::claude::encode_project_path(&remote_dir)
+        )),
+        sftp_quote(&local_dir.to_string_lossy())
+    );
+    run_sftp_batch(config, &script)?;
+    log::info!("[SSH] Synced Claude sessions of {} from {}", remote_dir, config.destination());
+    Ok(local_dir)
+}
+
+/// 把本地镜像中修改过的会话文件写回远程
+pub fn upload_session_file(config: &RemoteConfig, local_file: &Path) -> Result<(), String> {
+    let sessions_dir = remote_sessions_mirror_dir(config)?;
+    let relative = local_file
+        .strip_prefix(&sessions_dir)
+        .map_err(|_| format!("{:?} 不在远程会话镜像目录中", local_file))?;
+    let remote_path = format!(
+        "{}/{}",
+        REMOTE_SESSIONS_DIR,
+        relative.to_string_lossy().replace('\\', "/")
+    );
+
+    let script = format!(
+        "put {} {}\n",
+        sftp_quote(&local_file.to_string_lossy()),
+        sftp_quote(&remote_path)
+    );
+    run_sftp_batch(config, &script)?;
+    log::info!("[SSH] Uploaded {:?} to {}:{}", local_file, config.destination(), remote_path);
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 测试远程连接并返回远程 Codex 版本
+#[tauri::command]
+pub async fn test_remote_connection(config: RemoteConfig) -> Result<String, String> {
+    log::info!("[SSH] Testing connection to {}", config.destination());
+    tokio::task::spawn_blocking(move || check_remote_codex(&config))
+        .await
+        .map_err(|e| format!("远程连接测试失败: {}", e))?
+}
+
+/// 立即从远程同步会话目录
+#[tauri::command]
+pub async fn sync_remote_codex_sessions() -> Result<String, String> {
+    let config = super::wsl_utils::get_codex_config()
+        .remote
+        .clone()
+        .ok_or_else(|| "未配置远程主机".to_string())?;
+    tokio::task::spawn_blocking(move || sync_remote_sessions(&config, true))
+        .await
+        .map_err(|e| format!("同步远程会话失败: {}", e))?
+        .map(|dir| dir.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_quote_escapes_only_when_needed() {
+        assert_eq!(shell_quote("--json"), "--json");
+        assert_eq!(shell_quote("/home/dev/app"), "/home/dev/app");
+        assert_eq!(shell_quote("my project"), "'my project'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn remote_command_line_changes_directory_first() {
+        let line = build_remote_command_line(
+            "codex",
+            &["exec".to_string(), "--json".to_string(), "-".to_string()],
+            Some("/srv/my app"),
+        );
+        assert_eq!(line, "cd '/srv/my app' && exec codex exec --json -");
+    }
+}
@@ -0,0 +1,276 @@
+//! AI 工具数据的磁盘占用统计
+//!
+//! 遍历 ~/.claude、~/.codex、~/.gemini、AnyCode 数据目录和应用数据目录（agents.db），
+//! 按类别（会话、日志、变更记录、备份、数据库、其他）汇总大小，供设置页的存储面板和保留策略使用。
+//! Windows 上启用了 WSL 模式（全局或项目级发行版）时，WSL 中的 ~/.codex 通过 UNC 路径一并统计。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use tauri::AppHandle;
+
+use super::codex::change_store;
+use super::data_root::{anycode_dir, app_data_dir};
+use super::storage::with_agent_db;
+use super::wsl_utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageCategory {
+    Sessions,
+    Logs,
+    ChangeRecords,
+    Backups,
+    Database,
+    Other,
+}
+
+const CATEGORIES: [StorageCategory; 6] = [
+    StorageCategory::Sessions,
+    StorageCategory::Logs,
+    StorageCategory::ChangeRecords,
+    StorageCategory::Backups,
+    StorageCategory::Database,
+    StorageCategory::Other,
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryUsage {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// 单个目录的占用
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageLocation {
+    /// "claude" / "codex" / "gemini" / "anycode"
+    pub tool: String,
+    pub path: String,
+    /// WSL 中的目录对应的发行版
+    pub wsl_distro: Option<String>,
+    pub exists: bool,
+    pub total_bytes: u64,
+    pub categories: HashMap<StorageCategory, CategoryUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    pub locations: Vec<StorageLocation>,
+    /// 所有目录按类别合计
+    pub totals: HashMap<StorageCategory, CategoryUsage>,
+    pub total_bytes: u64,
+    pub generated_at: String,
+}
+
+/// 要统计的目录
+struct Root {
+    tool: &'static str,
+    path: PathBuf,
+    wsl_distro: Option<String>,
+}
+
+fn first_component(rel: &Path) -> String {
+    match rel.components().next() {
+        Some(Component::Normal(name)) => name.to_string_lossy().to_string(),
+        _ => String::new(),
+    }
+}
+
+/// 按工具目录的布局判断文件类别（`rel` 为相对该工具根目录的路径）
+fn categorize(tool: &str, rel: &Path) -> StorageCategory {
+    let name = rel.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if name.ends_with(".backup") || name.contains(".backup.") || name.ends_with(".bak") {
+        return StorageCategory::Backups;
+    }
+    let top = first_component(rel);
+    match (tool, top.as_str()) {
+        ("claude", "projects" | "todos") => StorageCategory::Sessions,
+        ("claude", "sessions" | "file-history") => StorageCategory::ChangeRecords,
+        ("claude", "debug" | "logs") => StorageCategory::Logs,
+        ("codex", "sessions" | "archived_sessions" | "history.jsonl") => StorageCategory::Sessions,
+        ("codex", "change-records" | "git-records") => StorageCategory::ChangeRecords,
+        ("codex", "log" | "logs") => StorageCategory::Logs,
+        ("gemini", "git-records") => StorageCategory::ChangeRecords,
+        ("gemini", "tmp") => {
+            // tmp/<项目哈希>/chats/*.json 为会话，tmp/<项目哈希>/logs.json 为日志
+            if rel.components().any(|c| c.as_os_str() == "chats") {
+                StorageCategory::Sessions
+            } else if name == "logs.json" {
+                StorageCategory::Logs
+            } else {
+                StorageCategory::Other
+            }
+        }
+        ("anycode", "logs" | "diagnostics" | "hook_events") => StorageCategory::Logs,
+        ("anycode", "backups" | "compactions") => StorageCategory::Backups,
+        // Codex 非 Git 项目的文件快照，和 Git 记录一样用于代码回滚
+        ("anycode", "snapshots") => StorageCategory::ChangeRecords,
+        ("anycode", "remote") => StorageCategory::Sessions,
+        ("anycode", _) if name.starts_with("agents.db") => StorageCategory::Database,
+        _ => StorageCategory::Other,
+    }
+}
+
+fn scan_location(root: &Root) -> StorageLocation {
+    let mut categories: HashMap<StorageCategory, CategoryUsage> = HashMap::new();
+    let exists = root.path.is_dir();
+    if exists {
+        for entry in walkdir::WalkDir::new(&root.path).into_iter().filter_map(|e| e.ok()) {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if !meta.is_file() {
+                continue;
+            }
+            let rel = entry.path().strip_prefix(&root.path).unwrap_or(entry.path());
+            let usage = categories.entry(categorize(root.tool, rel)).or_default();
+            usage.bytes += meta.len();
+            usage.files += 1;
+        }
+    }
+    StorageLocation {
+        tool: root.tool.to_string(),
+        path: root.path.to_string_lossy().to_string(),
+        wsl_distro: root.wsl_distro.clone(),
+        exists,
+        total_bytes: categories.values().map(|u| u.bytes).sum(),
+        categories,
+    }
+}
+
+/// 变更记录保存在 agents.db 中，把这部分从数据库挪到变更记录类别
+fn move_db_change_records(location: &mut StorageLocation, bytes: u64) {
+    let Some(database) = location.categories.get_mut(&StorageCategory::Database) else {
+        return;
+    };
+    let moved = bytes.min(database.bytes);
+    database.bytes -= moved;
+    location.categories.entry(StorageCategory::ChangeRecords).or_default().bytes += moved;
+}
+
+fn detect_roots(app: &AppHandle) -> Result<Vec<Root>, String> {
+    let home = dirs::home_dir().ok_or("无法获取用户目录")?;
+    let mut roots = vec![
+        Root { tool: "claude", path: home.join(".claude"), wsl_distro: None },
+        Root { tool: "codex", path: home.join(".codex"), wsl_distro: None },
+        Root { tool: "gemini", path: home.join(".gemini"), wsl_distro: None },
+        Root { tool: "anycode", path: anycode_dir()?, wsl_distro: None },
+    ];
+    let app_dir = app_data_dir(app)?;
+    if !roots.iter().any(|r| r.path == app_dir) {
+        roots.push(Root { tool: "anycode", path: app_dir, wsl_distro: None });
+    }
+
+    let global = wsl_utils::get_wsl_config();
+    let wsl_configs = std::iter::once(global.clone())
+        .filter(|c| c.enabled)
+        .chain(wsl_utils::get_project_wsl_configs());
+    for config in wsl_configs {
+        if let Some(codex_dir) = config.codex_dir_unc {
+            if !roots.iter().any(|r| r.path == codex_dir) {
+                roots.push(Root { tool: "codex", path: codex_dir, wsl_distro: config.distro });
+            }
+        }
+    }
+    Ok(roots)
+}
+
+/// 统计各 AI 工具目录的磁盘占用
+#[tauri::command]
+pub async fn get_storage_report(app: AppHandle) -> Result<StorageReport, String> {
+    let roots = detect_roots(&app)?;
+    let db_change_bytes = with_agent_db(|conn| {
+        change_store::ensure_schema(conn)?;
+        conn.query_row(
+            "SELECT COALESCE(SUM(LENGTH(payload)), 0) FROM codex_change_records",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|e| format!("统计变更记录失败: {}", e))
+    })
+    .await
+    .unwrap_or(0)
+    .max(0) as u64;
+
+    let mut locations = tokio::task::spawn_blocking(move || roots.iter().map(scan_location).collect::<Vec<_>>())
+        .await
+        .map_err(|e| format!("统计磁盘占用失败: {}", e))?;
+    if let Some(location) = locations
+        .iter_mut()
+        .find(|l| l.categories.contains_key(&StorageCategory::Database))
+    {
+        move_db_change_records(location, db_change_bytes);
+    }
+
+    let mut totals: HashMap<StorageCategory, CategoryUsage> =
+        CATEGORIES.iter().map(|c| (*c, CategoryUsage::default())).collect();
+    for location in &locations {
+        for (category, usage) in &location.categories {
+            let total = totals.entry(*category).or_default();
+            total.bytes += usage.bytes;
+            total.files += usage.files;
+        }
+    }
+    Ok(StorageReport {
+        total_bytes: locations.iter().map(|l| l.total_bytes).sum(),
+        locations,
+        totals,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn categorizes_files_by_tool_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("projects/-home-me-app/abc.jsonl", 10),
+            ("todos/abc.json", 2),
+            ("sessions/-home-me-app/abc.git-records.json", 3),
+            ("CLAUDE.md.backup", 4),
+            ("settings.json", 5),
+        ];
+        for (path, size) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![b'x'; size]).unwrap();
+        }
+        let location = scan_location(&Root { tool: "claude", path: dir.path().to_path_buf(), wsl_distro: None });
+        assert!(location.exists);
+        assert_eq!(location.total_bytes, 24);
+        assert_eq!(location.categories[&StorageCategory::Sessions].bytes, 12);
+        assert_eq!(location.categories[&StorageCategory::Sessions].files, 2);
+        assert_eq!(location.categories[&StorageCategory::ChangeRecords].bytes, 3);
+        assert_eq!(location.categories[&StorageCategory::Backups].bytes, 4);
+        assert_eq!(location.categories[&StorageCategory::Other].bytes, 5);
+
+        let cat = |tool, path: &str| categorize(tool, Path::new(path));
+        assert_eq!(cat("codex", "sessions/2024/01/02/rollout-x.jsonl"), StorageCategory::Sessions);
+        assert_eq!(cat("codex", "git-records/x.json"), StorageCategory::ChangeRecords);
+        assert_eq!(cat("gemini", "tmp/abcd/chats/session-1.json"), StorageCategory::Sessions);
+        assert_eq!(cat("gemini", "tmp/abcd/logs.json"), StorageCategory::Logs);
+        assert_eq!(cat("anycode", "logs/codex/s.log.1"), StorageCategory::Logs);
+        assert_eq!(cat("anycode", "agents.db-wal"), StorageCategory::Database);
+        assert_eq!(cat("anycode", "backups/anycode-backup-1.zip"), StorageCategory::Backups);
+        assert_eq!(cat("anycode", "snapshots/objects/ab/cdef"), StorageCategory::ChangeRecords);
+
+        let mut app_dir = StorageLocation {
+            tool: "anycode".into(),
+            path: String::new(),
+            wsl_distro: None,
+            exists: true,
+            total_bytes: 100,
+            categories: HashMap::from([(StorageCategory::Database, CategoryUsage { bytes: 100, files: 1 })]),
+        };
+        move_db_change_records(&mut app_dir, 30);
+        assert_eq!(app_dir.categories[&StorageCategory::Database].bytes, 70);
+        assert_eq!(app_dir.categories[&StorageCategory::ChangeRecords].bytes, 30);
+    }
+}
@@ -0,0 +1,331 @@
+//! 外部终端启动
+//!
+//! 在系统终端中执行命令（引擎认证登录、打开项目终端等）。终端模拟器可在
+//! `~/.anycode/terminal.json` 中配置：`terminal` 为内置预设 id 或任意程序，
+//! `args` 为参数模板。留空时按平台自动选择第一个可用的预设。
+//!
+//! 参数模板占位符：
+//! - `{cwd}`：工作目录
+//! - `{command}`：要执行的命令（原样）
+//! - `{script}`：`cd <cwd> && <command>` 后保留交互式 shell（仅 macOS / Linux）
+//! - `{applescript}`：转义后可放进 AppleScript 字符串的 `cd <cwd> && <command>`
+
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use super::data_root::anycode_dir;
+
+/// 终端设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSettings {
+    /// 预设 id 或终端程序路径（留空自动检测）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    /// 参数模板（留空时使用预设的参数；自定义程序未设置时只传入命令）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+}
+
+/// 内置终端预设
+struct TerminalPreset {
+    id: &'static str,
+    name: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+    /// 需要存在的应用（macOS .app），为空时检查 program 是否在 PATH 中
+    app: Option<&'static str>,
+}
+
+#[cfg(target_os = "windows")]
+const PRESETS: &[TerminalPreset] = &[
+    TerminalPreset {
+        id: "windows-terminal",
+        name: "Windows Terminal",
+        program: "wt",
+        args: &["-d", "{cwd}", "powershell", "-NoExit", "-Command", "{command}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "powershell",
+        name: "PowerShell",
+        program: "cmd",
+        args: &["/c", "start", "", "powershell", "-NoExit", "-Command", "{command}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "cmd",
+        name: "Command Prompt",
+        program: "cmd",
+        args: &["/c", "start", "", "cmd", "/k", "{command}"],
+        app: None,
+    },
+];
+
+#[cfg(target_os = "macos")]
+const PRESETS: &[TerminalPreset] = &[
+    TerminalPreset {
+        id: "terminal",
+        name: "Terminal",
+        program: "osascript",
+        args: &["-e", "tell application \"Terminal\"\nactivate\ndo script \"{applescript}\"\nend tell"],
+        app: Some("/System/Applications/Utilities/Terminal.app"),
+    },
+    TerminalPreset {
+        id: "iterm",
+        name: "iTerm2",
+        program: "osascript",
+        args: &["-e", "tell application \"iTerm\"\nactivate\ncreate window with default profile command \"bash -lc '{applescript}; exec $SHELL -l'\"\nend tell"],
+        app: Some("/Applications/iTerm.app"),
+    },
+    TerminalPreset {
+        id: "wezterm",
+        name: "WezTerm",
+        program: "wezterm",
+        args: &["start", "--cwd", "{cwd}", "--", "bash", "-lc", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "kitty",
+        name: "kitty",
+        program: "kitty",
+        args: &["--directory", "{cwd}", "bash", "-lc", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "alacritty",
+        name: "Alacritty",
+        program: "alacritty",
+        args: &["--working-directory", "{cwd}", "-e", "bash", "-lc", "{script}"],
+        app: None,
+    },
+];
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const PRESETS: &[TerminalPreset] = &[
+    TerminalPreset {
+        id: "gnome-terminal",
+        name: "GNOME Terminal",
+        program: "gnome-terminal",
+        args: &["--working-directory={cwd}", "--", "bash", "-c", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "konsole",
+        name: "Konsole",
+        program: "konsole",
+        args: &["--workdir", "{cwd}", "-e", "bash", "-c", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "xfce4-terminal",
+        name: "Xfce Terminal",
+        program: "xfce4-terminal",
+        args: &["--working-directory={cwd}", "-x", "bash", "-c", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "wezterm",
+        name: "WezTerm",
+        program: "wezterm",
+        args: &["start", "--cwd", "{cwd}", "--", "bash", "-c", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "kitty",
+        name: "kitty",
+        program: "kitty",
+        args: &["--directory", "{cwd}", "bash", "-c", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "alacritty",
+        name: "Alacritty",
+        program: "alacritty",
+        args: &["--working-directory", "{cwd}", "-e", "bash", "-c", "{script}"],
+        app: None,
+    },
+    TerminalPreset {
+        id: "xterm",
+        name: "XTerm",
+        program: "xterm",
+        args: &["-e", "bash", "-c", "{script}"],
+        app: None,
+    },
+];
+
+/// 可用终端信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalInfo {
+    pub id: String,
+    pub name: String,
+    pub available: bool,
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("terminal.json"))
+}
+
+pub fn load_terminal_settings() -> TerminalSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn preset_available(preset: &TerminalPreset) -> bool {
+    match preset.app {
+        Some(app) => Path::new(app).exists(),
+        None => which::which(preset.program).is_ok(),
+    }
+}
+
+/// POSIX shell 单引号转义
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 替换参数模板中的占位符
+fn fill_args(template: &[String], cwd: &str, command: &str) -> Vec<String> {
+    let cd_line = match command.trim() {
+        "" => format!("cd {}", shell_quote(cwd)),
+        command => format!("cd {} && {}", shell_quote(cwd), command),
+    };
+    let script = format!("{}; exec \"${{SHELL:-bash}}\"", cd_line);
+    let applescript = cd_line.replace('\\', "\\\\").replace('"', "\\\"");
+
+    template
+        .iter()
+        .map(|arg| {
+            arg.replace("{cwd}", cwd)
+                .replace("{script}", &script)
+                .replace("{applescript}", &applescript)
+                .replace("{command}", command)
+        })
+        .collect()
+}
+
+/// 根据设置确定终端程序和参数模板，返回 (名称, 程序, 参数模板)
+fn resolve_terminal(settings: &TerminalSettings) -> Result<(String, String, Vec<String>), String> {
+    let to_vec = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+    let configured = settings.terminal.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    let (name, program, args) = match configured {
+        Some(terminal) => match PRESETS.iter().find(|p| p.id == terminal) {
+            Some(preset) => (preset.name.to_string(), preset.program.to_string(), to_vec(preset.args)),
+            None => (terminal.to_string(), terminal.to_string(), vec!["{command}".to_string()]),
+        },
+        None => {
+            let preset = PRESETS
+                .iter()
+                .find(|p| preset_available(p))
+                .ok_or_else(|| "未找到可用的终端，请在设置中指定终端程序".to_string())?;
+            (preset.name.to_string(), preset.program.to_string(), to_vec(preset.args))
+        }
+    };
+
+    let args = settings.args.clone().filter(|a| !a.is_empty()).unwrap_or(args);
+    Ok((name, program, args))
+}
+
+/// 在外部终端中执行命令（`command` 为空时只打开终端），返回使用的终端名称
+pub fn launch_terminal(command: &str, cwd: Option<&str>) -> Result<String, String> {
+    let cwd = match cwd.filter(|c| !c.trim().is_empty()) {
+        Some(cwd) => PathBuf::from(cwd),
+        None => dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?,
+    };
+    if !cwd.is_dir() {
+        return Err(format!("工作目录不存在: {}", cwd.display()));
+    }
+    let cwd_str = cwd.to_string_lossy().to_string();
+
+    let (name, program, template) = resolve_terminal(&load_terminal_settings())?;
+    let args = fill_args(&template, &cwd_str, command);
+
+    let mut cmd = Command::new(&program);
+    cmd.args(&args).current_dir(&cwd);
+    log::info!("[Terminal] Launching {}: {:?}", name, cmd);
+    cmd.spawn().map_err(|e| format!("无法启动终端 {}: {}", name, e))?;
+    Ok(name)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 读取终端设置
+#[tauri::command]
+pub async fn get_terminal_settings() -> Result<TerminalSettings, String> {
+    Ok(load_terminal_settings())
+}
+
+/// 保存终端设置
+#[tauri::command]
+pub async fn save_terminal_settings(settings: TerminalSettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| format!("序列化终端设置失败: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("保存终端设置失败: {}", e))?;
+    log::info!("[Terminal] Saved settings: {:?}", settings.terminal);
+    Ok(())
+}
+
+/// 列出当前平台的内置终端预设
+#[tauri::command]
+pub async fn list_terminals() -> Result<Vec<TerminalInfo>, String> {
+    Ok(PRESETS
+        .iter()
+        .map(|preset| TerminalInfo {
+            id: preset.id.to_string(),
+            name: preset.name.to_string(),
+            available: preset_available(preset),
+        })
+        .collect())
+}
+
+/// 在外部终端中执行命令
+#[tauri::command]
+pub async fn open_terminal(command: Option<String>, cwd: Option<String>) -> Result<String, String> {
+    let name = launch_terminal(command.as_deref().unwrap_or_default(), cwd.as_deref())?;
+    Ok(format!("已在 {} 中打开终端", name))
+}
+
+/// 在项目目录中打开终端
+#[tauri::command]
+pub async fn open_project_terminal(project_path: String) -> Result<String, String> {
+    let name = launch_terminal("", Some(&project_path))?;
+    Ok(format!("已在 {} 中打开终端", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_terminal_args() {
+        let template: Vec<String> = ["--working-directory={cwd}", "-c", "{script}", "{applescript}", "{command}"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let args = fill_args(&template, "/home/me/it's", "codex auth login");
+        assert_eq!(args[0], "--working-directory=/home/me/it's");
+        assert_eq!(args[2], r#"cd '/home/me/it'\''s' && codex auth login; exec "${SHELL:-bash}""#);
+        assert_eq!(args[3], r"cd '/home/me/it'\\''s' && codex auth login");
+        assert_eq!(args[4], "codex auth login");
+
+        let args = fill_args(&template, "/tmp", "");
+        assert_eq!(args[2], r#"cd '/tmp'; exec "${SHELL:-bash}""#);
+
+        // 自定义程序未设置参数时只传入命令
+        let settings = TerminalSettings { terminal: Some("foot".to_string()), args: None };
+        let (name, program, args) = resolve_terminal(&settings).unwrap();
+        assert_eq!((name.as_str(), program.as_str()), ("foot", "foot"));
+        assert_eq!(args, vec!["{command}"]);
+    }
+}
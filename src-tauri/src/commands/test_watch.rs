@@ -0,0 +1,387 @@
+//! 会话中的持续测试（随 Agent 的文件变更自动重跑）
+//!
+//! 项目设置中配置了 `testWatch` 时，变更追踪每记录一批 Agent 的文件变更就调用 [`notify_changes`]：
+//! - 防抖：最后一次变更后等待 `debounceMs`（默认 1.5 秒）再执行测试命令
+//! - 同一会话同时只跑一次测试，运行期间的新变更在结束后合并重跑一次；没有待跑的变更后清除会话的状态
+//! - 与同一项目的后处理命令和自动修复验证共用运行锁（见 `post_processors::lock_project`）
+//! - 开始和结束分别发送 `test-watch-started:<session_id>` / `test-watch-result:<session_id>` 事件
+//! - 每次结果（含通过 / 失败数）写入 agents.db 的 `test_watch_runs` 表，
+//!   `list_test_watch_runs` 返回整个序列以及测试从哪条提示词开始失败
+
+use chrono::{Duration as ChronoDuration, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::post_processors::{lock_project, run_processor, PostProcessor, ProcessorKind, ProcessorStatus};
+use super::project_settings::load_project_settings;
+use super::storage::open_agent_db;
+
+const DEFAULT_DEBOUNCE_MS: u64 = 1_500;
+/// 测试记录保留天数
+const RETENTION_DAYS: i64 = 30;
+
+/// 项目的持续测试设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestWatchSettings {
+    /// 测试命令（如 `cargo test`、`npm test -- --watch=false`）
+    pub command: String,
+    /// 最后一次变更后等待的毫秒数，留空使用 1500
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debounce_ms: Option<u64>,
+    /// 超时时间（秒），留空使用后处理命令的默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// 一次测试运行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestWatchRun {
+    pub id: i64,
+    pub session_id: String,
+    /// 触发本次运行的最后一条变更所属的提示词
+    pub prompt_index: i32,
+    pub status: ProcessorStatus,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// 从输出中识别出的通过 / 失败数（无法识别时为空）
+    #[serde(default)]
+    pub passed_count: Option<u32>,
+    #[serde(default)]
+    pub failed_count: Option<u32>,
+    /// 一行摘要，如 "12 passed, 1 failed"
+    pub summary: String,
+    pub output: String,
+    pub created_at: String,
+}
+
+/// 会话的测试序列
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestWatchSeries {
+    /// 按时间顺序
+    pub runs: Vec<TestWatchRun>,
+    /// 最近一次运行失败时，连续失败开始的提示词（即测试从哪条提示词开始坏掉）
+    pub broken_since_prompt: Option<i32>,
+}
+
+/// 会话的监听状态
+#[derive(Debug, Default)]
+struct WatchState {
+    /// 每次变更递增，防抖等待结束时不一致说明之后又有变更
+    generation: u64,
+    running: bool,
+    /// 运行期间又有变更，结束后需要重跑
+    rerun: bool,
+    prompt_index: i32,
+}
+
+static WATCHES: Lazy<Mutex<HashMap<String, WatchState>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn insert_run(conn: &Connection, run: &mut TestWatchRun) -> Result<(), String> {
+    let status = serde_json::to_value(run.status)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    conn.execute(
+        "INSERT INTO test_watch_runs
+            (session_id, prompt_index, status, exit_code, duration_ms, passed_count, failed_count,
+             summary, output, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            run.session_id,
+            run.prompt_index,
+            status,
+            run.exit_code,
+            run.duration_ms as i64,
+            run.passed_count,
+            run.failed_count,
+            run.summary,
+            run.output,
+            run.created_at,
+        ],
+    )
+    .map_err(|e| format!("写入测试记录失败: {}", e))?;
+    run.id = conn.last_insert_rowid();
+
+    let cutoff = (Utc::now() - ChronoDuration::days(RETENTION_DAYS)).to_rfc3339();
+    conn.execute("DELETE FROM test_watch_runs WHERE created_at < ?1", params![cutoff])
+        .map_err(|e| format!("清理测试记录失败: {}", e))?;
+    Ok(())
+}
+
+fn load_runs(conn: &Connection, session_id: &str) -> Result<Vec<TestWatchRun>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, session_id, prompt_index, status, exit_code, duration_ms, passed_count, failed_count,
+                    summary, output, created_at
+             FROM test_watch_runs WHERE session_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| format!("查询测试记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![session_id], |row| {
+            let status: String = row.get(3)?;
+            Ok(TestWatchRun {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                prompt_index: row.get(2)?,
+                status: serde_json::from_value(serde_json::Value::String(status))
+                    .unwrap_or(ProcessorStatus::Error),
+                exit_code: row.get(4)?,
+                duration_ms: row.get::<_, i64>(5)? as u64,
+                passed_count: row.get(6)?,
+                failed_count: row.get(7)?,
+                summary: row.get(8)?,
+                output: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })
+        .map_err(|e| format!("查询测试记录失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取测试记录失败: {}", e))
+}
+
+static CARGO_RESULT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"test result: \w+\. (\d+) passed; (\d+) failed").unwrap());
+static PASSED: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+) passed").unwrap());
+static FAILED: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+) failed").unwrap());
+
+/// 从测试输出中识别通过 / 失败数
+///
+/// cargo test 按每个测试二进制输出一行 `test result:`，全部相加；其他框架（jest、vitest、
+/// pytest 等）取最后一行包含 passed / failed 的汇总行。
+pub fn parse_test_counts(output: &str) -> (Option<u32>, Option<u32>) {
+    let cargo: Vec<(u32, u32)> = CARGO_RESULT
+        .captures_iter(output)
+        .filter_map(|c| Some((c[1].parse().ok()?, c[2].parse().ok()?)))
+        .collect();
+    if !cargo.is_empty() {
+        let (passed, failed) = cargo.iter().fold((0, 0), |(p, f), (cp, cf)| (p + cp, f + cf));
+        return (Some(passed), Some(failed));
+    }
+
+    let Some(line) = output.lines().rev().find(|l| PASSED.is_match(l) || FAILED.is_match(l)) else {
+        return (None, None);
+    };
+    let count = |re: &Regex| re.captures(line).and_then(|c| c[1].parse().ok());
+    match (count(&PASSED), count(&FAILED)) {
+        (Some(passed), None) => (Some(passed), Some(0)),
+        (None, Some(failed)) => (Some(0), Some(failed)),
+        counts => counts,
+    }
+}
+
+fn summarize(status: ProcessorStatus, exit_code: Option<i32>, counts: (Option<u32>, Option<u32>)) -> String {
+    match (status, counts) {
+        (ProcessorStatus::TimedOut, _) => "timed out".to_string(),
+        (ProcessorStatus::Error, _) => "could not be started".to_string(),
+        (_, (Some(passed), Some(failed))) => format!("{} passed, {} failed", passed, failed),
+        (ProcessorStatus::Passed, _) => "passed".to_string(),
+        (_, _) => match exit_code {
+            Some(code) => format!("failed (exit code {})", code),
+            None => "failed".to_string(),
+        },
+    }
+}
+
+/// 最近一次失败时，连续失败开始的提示词
+pub fn broken_since_prompt(runs: &[TestWatchRun]) -> Option<i32> {
+    runs.iter()
+        .rev()
+        .take_while(|r| r.status != ProcessorStatus::Passed)
+        .last()
+        .map(|r| r.prompt_index)
+}
+
+/// Agent 的文件变更已记录：防抖后重跑测试（项目未配置 `testWatch` 时什么都不做）
+pub fn notify_changes(app: &AppHandle, session_id: &str, project_path: &str, prompt_index: i32) {
+    let Some(settings) = load_project_settings(project_path)
+        .test_watch
+        .filter(|s| !s.command.trim().is_empty())
+    else {
+        return;
+    };
+
+    let generation = {
+        let mut watches = WATCHES.lock().unwrap();
+        let state = watches.entry(session_id.to_string()).or_default();
+        state.generation += 1;
+        state.prompt_index = prompt_index;
+        state.generation
+    };
+
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    let project_path = project_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(settings.debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS))).await;
+        {
+            let mut watches = WATCHES.lock().unwrap();
+            let Some(state) = watches.get_mut(&session_id) else {
+                return;
+            };
+            if state.generation != generation {
+                return;
+            }
+            if state.running {
+                state.rerun = true;
+                return;
+            }
+            state.running = true;
+        }
+        run_until_settled(&app, &session_id, &project_path, &settings).await;
+    });
+}
+
+/// 执行测试；运行期间又有变更时再跑一次，直到没有新的变更
+async fn run_until_settled(app: &AppHandle, session_id: &str, project_path: &str, settings: &TestWatchSettings) {
+    let processor = PostProcessor {
+        name: "test-watch".to_string(),
+        kind: ProcessorKind::Test,
+        command: settings.command.clone(),
+        timeout_secs: settings.timeout_secs,
+    };
+
+    loop {
+        let guard = lock_project(project_path).await;
+        // 本次运行覆盖的最后一次变更
+        let (generation, prompt_index) = WATCHES
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|s| (s.generation, s.prompt_index))
+            .unwrap_or_default();
+        let _ = app.emit(
+            &format!("test-watch-started:{}", session_id),
+            serde_json::json!({ "sessionId": session_id, "promptIndex": prompt_index }),
+        );
+
+        let result = run_processor(project_path, &processor).await;
+        drop(guard);
+        let counts = parse_test_counts(&result.output);
+        let mut run = TestWatchRun {
+            id: 0,
+            session_id: session_id.to_string(),
+            prompt_index,
+            status: result.status,
+            exit_code: result.exit_code,
+            duration_ms: result.duration_ms,
+            passed_count: counts.0,
+            failed_count: counts.1,
+            summary: summarize(result.status, result.exit_code, counts),
+            output: result.output,
+            created_at: Utc::now().to_rfc3339(),
+        };
+        log::info!(
+            "[TestWatch] {} after prompt #{} in {}: {} ({} ms)",
+            settings.command,
+            prompt_index,
+            session_id,
+            run.summary,
+            run.duration_ms
+        );
+
+        let stored = tokio::task::spawn_blocking(move || {
+            let result = open_agent_db().and_then(|conn| insert_run(&conn, &mut run));
+            (run, result)
+        })
+        .await;
+        match stored {
+            Ok((run, result)) => {
+                if let Err(e) = result {
+                    log::warn!("[TestWatch] Failed to store test run: {}", e);
+                }
+                let _ = app.emit(&format!("test-watch-result:{}", session_id), &run);
+            }
+            Err(e) => log::warn!("[TestWatch] Failed to store test run: {}", e),
+        }
+
+        let mut watches = WATCHES.lock().unwrap();
+        let Some(state) = watches.get_mut(session_id) else {
+            return;
+        };
+        if !state.rerun {
+            state.running = false;
+            // 之后没有新的变更（也就没有等待中的防抖任务）时清除会话的状态
+            if state.generation == generation {
+                watches.remove(session_id);
+            }
+            return;
+        }
+        state.rerun = false;
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 会话的测试序列
+#[tauri::command]
+pub async fn list_test_watch_runs(session_id: String) -> Result<TestWatchSeries, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = open_agent_db()?;
+        let runs = load_runs(&conn, &session_id)?;
+        Ok(TestWatchSeries { broken_since_prompt: broken_since_prompt(&runs), runs })
+    })
+    .await
+    .map_err(|e| format!("读取测试记录失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(prompt_index: i32, status: ProcessorStatus) -> TestWatchRun {
+        TestWatchRun {
+            id: 0,
+            session_id: "s1".to_string(),
+            prompt_index,
+            status,
+            exit_code: None,
+            duration_ms: 10,
+            passed_count: None,
+            failed_count: None,
+            summary: String::new(),
+            output: String::new(),
+            created_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn parses_test_counts() {
+        let cargo = "test result: ok. 3 passed; 0 failed; 0 ignored\n\
+                     test result: FAILED. 2 passed; 1 failed; 0 ignored\n";
+        assert_eq!(parse_test_counts(cargo), (Some(5), Some(1)));
+        let jest = "Test Suites: 1 failed, 2 passed, 3 total\nTests:       1 failed, 9 passed, 10 total\nTime: 1s";
+        assert_eq!(parse_test_counts(jest), (Some(9), Some(1)));
+        assert_eq!(parse_test_counts("===== 4 passed in 0.12s ====="), (Some(4), Some(0)));
+        assert_eq!(parse_test_counts("ok  \tpkg\t0.1s"), (None, None));
+        assert_eq!(summarize(ProcessorStatus::Failed, Some(2), (None, None)), "failed (exit code 2)");
+    }
+
+    #[test]
+    fn finds_breaking_prompt() {
+        use ProcessorStatus::*;
+        let series = [run(0, Passed), run(1, Failed), run(2, Passed), run(3, Failed), run(4, TimedOut)];
+        assert_eq!(broken_since_prompt(&series), Some(3));
+        assert_eq!(broken_since_prompt(&series[..3]), None);
+    }
+
+    #[test]
+    fn stores_and_loads_runs() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::commands::db_migrations::run_migrations(&mut conn).unwrap();
+        let mut stored = run(4, ProcessorStatus::TimedOut);
+        insert_run(&conn, &mut stored).unwrap();
+        assert_eq!(load_runs(&conn, "s1").unwrap(), vec![stored]);
+    }
+}
@@ -0,0 +1,184 @@
+//! Token 计数
+//!
+//! 基于 tiktoken 词表计算 token 数，供上下文预算、提示词长度提示和用量估算使用：
+//! OpenAI / Codex 模型按模型选择 o200k_base 或 cl100k_base；Claude、Gemini 的词表未公开，
+//! 用 cl100k_base 近似（比按字符数估算准确得多）。
+//! 词表在第一次使用时加载，加载失败时退回到约 4 个字符一个 token 的估算。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tiktoken_rs::CoreBPE;
+
+/// 每条消息的格式开销（角色标记、分隔符）
+const TOKENS_PER_MESSAGE: usize = 3;
+/// 回复的起始标记
+const TOKENS_PER_REPLY: usize = 3;
+/// 图片、PDF 的估算值（与前端 tokenCounter 一致）
+pub const IMAGE_TOKENS: usize = 1551;
+pub const DOCUMENT_TOKENS: usize = 2188;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Vocab {
+    O200kBase,
+    Cl100kBase,
+}
+
+static O200K: Lazy<Option<CoreBPE>> = Lazy::new(|| load_bpe(Vocab::O200kBase));
+static CL100K: Lazy<Option<CoreBPE>> = Lazy::new(|| load_bpe(Vocab::Cl100kBase));
+
+fn load_bpe(vocab: Vocab) -> Option<CoreBPE> {
+    let result = match vocab {
+        Vocab::O200kBase => tiktoken_rs::o200k_base(),
+        Vocab::Cl100kBase => tiktoken_rs::cl100k_base(),
+    };
+    result
+        .map_err(|e| log::warn!("[Tokenizer] Failed to load {:?} vocabulary: {}", vocab, e))
+        .ok()
+}
+
+/// 按模型名选择词表（未知模型使用 cl100k_base）
+pub fn vocab_for_model(model: &str) -> Vocab {
+    let model = model.trim().to_lowercase();
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    if let Some(tiktoken_rs::tokenizer::Tokenizer::O200kBase) = tiktoken_rs::tokenizer::get_tokenizer(model) {
+        return Vocab::O200kBase;
+    }
+    // 较新的 OpenAI 模型（gpt-4o 之后、o 系列、Codex）都使用 o200k_base
+    let is_o_series = model.len() > 1
+        && model.starts_with('o')
+        && model[1..].starts_with(|c: char| c.is_ascii_digit());
+    let is_legacy_gpt = model.starts_with("gpt-4-") || model == "gpt-4" || model.starts_with("gpt-3.5");
+    if is_o_series || model.contains("codex") || (model.starts_with("gpt-") && !is_legacy_gpt) {
+        Vocab::O200kBase
+    } else {
+        Vocab::Cl100kBase
+    }
+}
+
+fn bpe(vocab: Vocab) -> Option<&'static CoreBPE> {
+    match vocab {
+        Vocab::O200kBase => O200K.as_ref(),
+        Vocab::Cl100kBase => CL100K.as_ref(),
+    }
+}
+
+/// 计算一段文本的 token 数（`model` 为空时使用默认词表）
+pub fn count_text(model: &str, text: &str) -> usize {
+    if text.is_empty() {
+        return 0;
+    }
+    match bpe(vocab_for_model(model)) {
+        Some(bpe) => bpe.encode_ordinary(text).len(),
+        None => text.chars().count().div_ceil(4),
+    }
+}
+
+/// 参与上下文计数的一条消息；`content` 为字符串或内容块数组（Claude / OpenAI 格式）
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContextMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextTokenCount {
+    pub total: usize,
+    /// 每条消息的 token 数（含格式开销）
+    pub messages: Vec<usize>,
+    pub vocab: Vocab,
+}
+
+/// 消息内容（字符串或内容块）的 token 数
+pub fn count_content(model: &str, content: &Value) -> usize {
+    match content {
+        Value::Null => 0,
+        Value::String(text) => count_text(model, text),
+        Value::Array(blocks) => blocks.iter().map(|block| count_content(model, block)).sum(),
+        Value::Object(block) => match block.get("type").and_then(|t| t.as_str()) {
+            Some("image" | "image_url" | "input_image") => IMAGE_TOKENS,
+            Some("document") => DOCUMENT_TOKENS,
+            Some("tool_use") => {
+                let name = block.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+                let input = block.get("input").map(|i| i.to_string()).unwrap_or_default();
+                count_text(model, name) + count_text(model, &input)
+            }
+            Some("tool_result") => block.get("content").map_or(0, |c| count_content(model, c)),
+            _ => ["text", "thinking", "content"]
+                .iter()
+                .find_map(|key| block.get(*key))
+                .map_or(0, |value| count_content(model, value)),
+        },
+        other => count_text(model, &other.to_string()),
+    }
+}
+
+/// 计算一组对话消息占用的上下文 token 数
+pub fn count_messages(model: &str, messages: &[ContextMessage]) -> ContextTokenCount {
+    let counts: Vec<usize> = messages
+        .iter()
+        .map(|m| TOKENS_PER_MESSAGE + count_text(model, &m.role) + count_content(model, &m.content))
+        .collect();
+    let total = counts.iter().sum::<usize>() + if counts.is_empty() { 0 } else { TOKENS_PER_REPLY };
+    ContextTokenCount {
+        total,
+        messages: counts,
+        vocab: vocab_for_model(model),
+    }
+}
+
+/// 计算文本的 token 数
+#[tauri::command]
+pub async fn count_tokens(model: Option<String>, text: String) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || count_text(model.as_deref().unwrap_or_default(), &text))
+        .await
+        .map_err(|e| format!("计算 token 数失败: {}", e))
+}
+
+/// 计算一组消息的上下文 token 数
+#[tauri::command]
+pub async fn count_context_tokens(
+    model: Option<String>,
+    messages: Vec<ContextMessage>,
+) -> Result<ContextTokenCount, String> {
+    tokio::task::spawn_blocking(move || count_messages(model.as_deref().unwrap_or_default(), &messages))
+        .await
+        .map_err(|e| format!("计算 token 数失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn counts_text_and_messages_with_model_vocab() {
+        assert_eq!(vocab_for_model("gpt-5-codex"), Vocab::O200kBase);
+        assert_eq!(vocab_for_model("o3"), Vocab::O200kBase);
+        assert_eq!(vocab_for_model("openai/gpt-4o-mini"), Vocab::O200kBase);
+        assert_eq!(vocab_for_model("gpt-4-turbo"), Vocab::Cl100kBase);
+        assert_eq!(vocab_for_model("claude-sonnet-4-5"), Vocab::Cl100kBase);
+        assert_eq!(vocab_for_model("opus"), Vocab::Cl100kBase);
+        assert_eq!(vocab_for_model(""), Vocab::Cl100kBase);
+
+        assert_eq!(count_text("", ""), 0);
+        assert_eq!(count_text("gpt-4", "hello world"), 2);
+        assert_eq!(count_text("gpt-4o", "hello world"), 2);
+
+        let messages: Vec<ContextMessage> = serde_json::from_value(json!([
+            { "role": "user", "content": "hello world" },
+            { "role": "assistant", "content": [
+                { "type": "text", "text": "hello world" },
+                { "type": "image", "source": {} }
+            ] }
+        ]))
+        .unwrap();
+        let count = count_messages("gpt-4", &messages);
+        let role = |r| count_text("gpt-4", r);
+        assert_eq!(count.messages, vec![3 + role("user") + 2, 3 + role("assistant") + 2 + IMAGE_TOKENS]);
+        assert_eq!(count.total, count.messages.iter().sum::<usize>() + 3);
+    }
+}
@@ -0,0 +1,251 @@
+//! 工具调用审批通道
+//!
+//! 开启交互式审批后，Claude 以 `--input-format stream-json --permission-prompt-tool stdio`
+//! 启动，需要权限的工具调用会以 `control_request`（`can_use_tool`）的形式出现在输出中。
+//! 这里把请求转发给前端（`tool-approval-request:{session_id}` 事件），CLI 在收到回复前
+//! 暂停执行；前端调用 `respond_to_approval` 后把 `control_response` 写回 CLI 的 stdin，
+//! 并发出 `tool-approval-resolved:{session_id}`。
+//!
+//! Codex 以 `codex exec` 非交互方式执行，没有审批协议，仍由权限档案决定是否放行。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::notifications;
+
+/// 待审批的工具调用
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRequest {
+    pub session_id: String,
+    pub request_id: String,
+    pub engine: String,
+    pub tool_name: String,
+    /// 工具参数
+    pub input: Value,
+    pub created_at: String,
+}
+
+/// 审批结果（随 `tool-approval-resolved` 事件发出）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalResolution {
+    pub session_id: String,
+    pub request_id: String,
+    pub allow: bool,
+    /// 会话结束或 CLI 取消了请求
+    pub cancelled: bool,
+}
+
+struct ApprovalSession {
+    engine: String,
+    /// 写入 CLI stdin 的行（每行一条 JSON 消息）
+    stdin: UnboundedSender<String>,
+    pending: HashMap<String, ApprovalRequest>,
+}
+
+/// 可以接收审批回复的会话（按会话 ID 索引）
+static SESSIONS: Lazy<Mutex<HashMap<String, ApprovalSession>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 登记会话的 stdin 通道，之后的审批回复通过它写回 CLI
+pub fn register_session(session_id: &str, engine: &str, stdin: UnboundedSender<String>) {
+    SESSIONS.lock().unwrap().insert(
+        session_id.to_string(),
+        ApprovalSession {
+            engine: engine.to_string(),
+            stdin,
+            pending: HashMap::new(),
+        },
+    );
+    log::info!("[ToolApproval] Registered {} session {}", engine, session_id);
+}
+
+/// 注销会话并释放 stdin 通道；仍在等待的请求视为取消
+pub fn close_session(app: &AppHandle, session_id: &str) {
+    let Some(session) = SESSIONS.lock().unwrap().remove(session_id) else {
+        return;
+    };
+    for request_id in session.pending.into_keys() {
+        emit_resolution(app, session_id, &request_id, false, true);
+    }
+    log::info!("[ToolApproval] Closed session {}", session_id);
+}
+
+fn emit_resolution(app: &AppHandle, session_id: &str, request_id: &str, allow: bool, cancelled: bool) {
+    let resolution = ApprovalResolution {
+        session_id: session_id.to_string(),
+        request_id: request_id.to_string(),
+        allow,
+        cancelled,
+    };
+    let _ = app.emit(&format!("tool-approval-resolved:{}", session_id), &resolution);
+    let _ = app.emit("tool-approval-resolved", &resolution);
+}
+
+/// 从 Claude 的 `control_request` 中解析审批请求
+fn parse_claude_request(session_id: &str, msg: &Value) -> Option<ApprovalRequest> {
+    let request = msg.get("request")?;
+    if request.get("subtype")?.as_str()? != "can_use_tool" {
+        return None;
+    }
+    Some(ApprovalRequest {
+        session_id: session_id.to_string(),
+        request_id: msg.get("request_id")?.as_str()?.to_string(),
+        engine: "claude".to_string(),
+        tool_name: request.get("tool_name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        input: request.get("input").cloned().unwrap_or_else(|| json!({})),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Claude 控制协议的审批回复
+fn claude_response(request: &ApprovalRequest, allow: bool, message: Option<&str>) -> Value {
+    let decision = if allow {
+        json!({ "behavior": "allow", "updatedInput": request.input })
+    } else {
+        json!({ "behavior": "deny", "message": message.unwrap_or("用户拒绝了该工具调用") })
+    };
+    json!({
+        "type": "control_response",
+        "response": {
+            "subtype": "success",
+            "request_id": request.request_id,
+            "response": decision,
+        }
+    })
+}
+
+/// 处理 Claude 输出中的控制消息，返回 true 表示该行属于控制协议（不需要再转发给前端）
+pub fn handle_claude_control_message(app: &AppHandle, session_id: &str, msg: &Value) -> bool {
+    match msg.get("type").and_then(|t| t.as_str()) {
+        Some("control_request") => {
+            let Some(request) = parse_claude_request(session_id, msg) else {
+                log::warn!("[ToolApproval] Unsupported control request: {}", msg);
+                return true;
+            };
+            let mut sessions = SESSIONS.lock().unwrap();
+            let Some(session) = sessions.get_mut(session_id) else {
+                log::warn!("[ToolApproval] Approval requested for unregistered session {}", session_id);
+                return true;
+            };
+            log::info!(
+                "[ToolApproval] {} requests approval for {} ({})",
+                session_id,
+                request.tool_name,
+                request.request_id
+            );
+            session.pending.insert(request.request_id.clone(), request.clone());
+            drop(sessions);
+            let _ = app.emit(&format!("tool-approval-request:{}", session_id), &request);
+            let _ = app.emit("tool-approval-request", &request);
+            notifications::notify_approval_needed(app, &request.engine, session_id, &request.tool_name);
+            true
+        }
+        Some("control_cancel_request") => {
+            if let Some(request_id) = msg.get("request_id").and_then(|v| v.as_str()) {
+                let removed = SESSIONS
+                    .lock()
+                    .unwrap()
+                    .get_mut(session_id)
+                    .and_then(|session| session.pending.remove(request_id));
+                if removed.is_some() {
+                    emit_resolution(app, session_id, request_id, false, true);
+                }
+            }
+            true
+        }
+        Some("control_response") => true,
+        _ => false,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 回复工具调用审批
+///
+/// `message` 为拒绝时告诉模型的原因（可选）。
+#[tauri::command]
+pub async fn respond_to_approval(
+    app: AppHandle,
+    session_id: String,
+    request_id: String,
+    allow: bool,
+    message: Option<String>,
+) -> Result<(), String> {
+    {
+        let mut sessions = SESSIONS.lock().unwrap();
+        let session = sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| format!("会话 {} 没有等待中的审批", session_id))?;
+        let request = session
+            .pending
+            .get(&request_id)
+            .ok_or_else(|| format!("审批请求 {} 不存在或已处理", request_id))?;
+
+        let response = match session.engine.as_str() {
+            "claude" => claude_response(request, allow, message.as_deref()),
+            other => return Err(format!("{} 不支持交互式审批", other)),
+        };
+        session
+            .stdin
+            .send(response.to_string())
+            .map_err(|_| "引擎进程已退出，无法发送审批结果".to_string())?;
+        session.pending.remove(&request_id);
+    }
+
+    log::info!("[ToolApproval] {} {} ({})", if allow { "Allowed" } else { "Denied" }, request_id, session_id);
+    emit_resolution(&app, &session_id, &request_id, allow, false);
+    Ok(())
+}
+
+/// 列出等待中的审批请求（`session_id` 为空时返回所有会话）
+#[tauri::command]
+pub async fn list_pending_approvals(session_id: Option<String>) -> Result<Vec<ApprovalRequest>, String> {
+    let sessions = SESSIONS.lock().unwrap();
+    let mut requests: Vec<ApprovalRequest> = sessions
+        .iter()
+        .filter(|(id, _)| session_id.as_ref().is_none_or(|s| s == *id))
+        .flat_map(|(_, session)| session.pending.values().cloned())
+        .collect();
+    requests.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(requests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_claude_control_responses() {
+        let msg = json!({
+            "type": "control_request",
+            "request_id": "req-1",
+            "request": {
+                "subtype": "can_use_tool",
+                "tool_name": "Bash",
+                "input": { "command": "ls" }
+            }
+        });
+        let request = parse_claude_request("s1", &msg).unwrap();
+        assert_eq!((request.request_id.as_str(), request.tool_name.as_str()), ("req-1", "Bash"));
+
+        let allow = claude_response(&request, true, None);
+        assert_eq!(allow["type"], "control_response");
+        assert_eq!(allow["response"]["request_id"], "req-1");
+        assert_eq!(allow["response"]["response"]["behavior"], "allow");
+        assert_eq!(allow["response"]["response"]["updatedInput"]["command"], "ls");
+
+        let deny = claude_response(&request, false, Some("not now"));
+        assert_eq!(deny["response"]["response"], json!({ "behavior": "deny", "message": "not now" }));
+
+        let other = json!({ "type": "control_request", "request_id": "x", "request": { "subtype": "interrupt" } });
+        assert!(parse_claude_request("s1", &other).is_none());
+    }
+}
@@ -1,7 +1,10 @@
+use crate::tr;
 use anyhow::{Context, Result};
 use log::{debug, error, info, warn};
+use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -9,8 +12,82 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+use super::prompt_translation::PromptTranslationSettings;
 use super::url_utils::{normalize_api_url, ApiEndpointType};
 
+/// 翻译服务提供方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationProvider {
+    /// OpenAI 兼容的 Chat Completions 接口（Silicon Flow 等）
+    #[default]
+    OpenAi,
+    DeepL,
+    /// Google Cloud Translation (v2)
+    Google,
+    /// 本地模型（Ollama、LM Studio 等 OpenAI 兼容服务，无需 API 密钥）
+    Local,
+}
+
+impl TranslationProvider {
+    const ALL: [TranslationProvider; 4] = [Self::OpenAi, Self::DeepL, Self::Google, Self::Local];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::OpenAi => "OpenAI Compatible",
+            Self::DeepL => "DeepL",
+            Self::Google => "Google Translate",
+            Self::Local => "Local Model",
+        }
+    }
+
+    /// 未填写 API 地址时使用的默认地址
+    fn default_base_url(self) -> &'static str {
+        match self {
+            Self::OpenAi => "https://api.siliconflow.cn/v1",
+            Self::DeepL => "https://api.deepl.com",
+            Self::Google => "https://translation.googleapis.com",
+            Self::Local => "http://localhost:11434/v1",
+        }
+    }
+
+    fn requires_api_key(self) -> bool {
+        self != Self::Local
+    }
+
+    /// 是否使用模型（DeepL / Google 忽略 model 配置）
+    fn uses_model(self) -> bool {
+        matches!(self, Self::OpenAi | Self::Local)
+    }
+}
+
+/// 提供方信息（供设置界面使用）
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationProviderInfo {
+    pub id: TranslationProvider,
+    pub label: String,
+    pub default_base_url: String,
+    pub requires_api_key: bool,
+    pub uses_model: bool,
+}
+
+/// 术语表条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryTerm {
+    /// 原文术语（区分大小写）
+    pub term: String,
+    /// 固定译法；为空时原样保留
+    #[serde(default)]
+    pub translation: Option<String>,
+    /// 只在翻译为该语言时生效（如 "en"）；为空时对所有方向生效
+    #[serde(default)]
+    pub target_lang: Option<String>,
+}
+
+fn default_protect_code() -> bool {
+    true
+}
+
 /// 翻译配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationConfig {
@@ -26,6 +103,18 @@ pub struct TranslationConfig {
     pub timeout_seconds: u64,
     /// 缓存有效期（秒）
     pub cache_ttl_seconds: u64,
+    /// 翻译服务提供方
+    #[serde(default)]
+    pub provider: TranslationProvider,
+    /// 术语表
+    #[serde(default)]
+    pub glossary: Vec<GlossaryTerm>,
+    /// 保护代码片段和标识符（代码块、行内代码、URL、snake_case / camelCase 等）不被翻译
+    #[serde(default = "default_protect_code")]
+    pub protect_code: bool,
+    /// 提示词双向翻译（发送前翻译提示词、回复翻译回用户语言）
+    #[serde(default)]
+    pub pipeline: PromptTranslationSettings,
 }
 
 impl Default for TranslationConfig {
@@ -37,8 +126,109 @@ impl Default for TranslationConfig {
             model: "tencent/Hunyuan-MT-7B".to_string(),
             timeout_seconds: 30,
             cache_ttl_seconds: 3600, // 1小时
+            provider: TranslationProvider::OpenAi,
+            glossary: Vec::new(),
+            protect_code: true,
+            pipeline: PromptTranslationSettings::default(),
+        }
+    }
+}
+
+/// 受保护片段的占位符：模型和翻译接口都会原样保留
+fn placeholder(index: usize) -> String {
+    format!("⟦{}⟧", index)
+}
+
+static PLACEHOLDER_RE: once_cell::sync::Lazy<Regex> =
+    once_cell::sync::Lazy::new(|| Regex::new(r"⟦\s*(\d+)\s*⟧").unwrap());
+
+/// 代码块、行内代码、URL 和标识符候选
+static CODE_RE: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+    Regex::new(r"(?s)```.*?```|`[^`\n]+`|https?://[^\s]+|[A-Za-z_][A-Za-z0-9_]*(?:(?:::|\.|/)[A-Za-z_][A-Za-z0-9_]*)*(?:\(\))?")
+        .unwrap()
+});
+
+/// 标识符候选是否像代码（普通英文单词照常翻译）
+fn is_code_like(token: &str) -> bool {
+    if token.starts_with('`') || (token.starts_with("http") && token.contains("://")) {
+        return true;
+    }
+    let bytes = token.as_bytes();
+    let camel_case = bytes.windows(2).any(|w| w[0].is_ascii_lowercase() && w[1].is_ascii_uppercase());
+    camel_case
+        || token.ends_with("()")
+        || token.contains("::")
+        || token.contains(['.', '/'])
+        || (token.contains('_') && token.chars().any(|c| c.is_ascii_alphanumeric()))
+}
+
+/// 替换为占位符后的文本
+#[derive(Debug, Clone, PartialEq)]
+struct ProtectedText {
+    text: String,
+    /// 按占位符序号排列的还原内容
+    segments: Vec<String>,
+}
+
+/// 把术语和代码片段替换为占位符
+fn protect_terms(text: &str, glossary: &[GlossaryTerm], to_lang: &str, protect_code: bool) -> ProtectedText {
+    // (start, end, 还原内容)
+    let mut spans: Vec<(usize, usize, String)> = Vec::new();
+    for term in glossary {
+        if term.term.is_empty() || term.target_lang.as_deref().is_some_and(|lang| lang != to_lang) {
+            continue;
+        }
+        let replacement = term.translation.clone().unwrap_or_else(|| term.term.clone());
+        for (start, matched) in text.match_indices(term.term.as_str()) {
+            spans.push((start, start + matched.len(), replacement.clone()));
+        }
+    }
+    if protect_code {
+        for m in CODE_RE.find_iter(text).filter(|m| is_code_like(m.as_str())) {
+            spans.push((m.start(), m.end(), m.as_str().to_string()));
         }
     }
+
+    // 重叠时保留靠前、较长的片段（术语先于代码加入，同位置同长度时术语优先）
+    spans.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    let mut protected = ProtectedText { text: String::with_capacity(text.len()), segments: Vec::new() };
+    let mut cursor = 0;
+    for (start, end, replacement) in spans {
+        if start < cursor {
+            continue;
+        }
+        protected.text.push_str(&text[cursor..start]);
+        protected.text.push_str(&placeholder(protected.segments.len()));
+        protected.segments.push(replacement);
+        cursor = end;
+    }
+    protected.text.push_str(&text[cursor..]);
+    protected
+}
+
+/// 把占位符还原为原文片段或术语译法
+fn restore_terms(text: &str, segments: &[String]) -> String {
+    PLACEHOLDER_RE
+        .replace_all(text, |caps: &regex::Captures| {
+            caps[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| segments.get(index))
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+/// 待翻译的文本：(序号, 占位后的文本, 缓存键)，按 (源语言, 目标语言) 分组
+type PendingText = (usize, ProtectedText, String);
+
+/// 未指定目标语言时的默认方向
+fn default_target_lang(from_lang: &str) -> String {
+    match from_lang {
+        "zh" => "en".to_string(), // 中文翻译为英文
+        _ => "zh".to_string(),    // 其他语言翻译为中文
+    }
 }
 
 /// 翻译缓存条目
@@ -63,7 +253,8 @@ impl CacheEntry {
     }
 }
 
-/// 翻译服务
+/// 翻译服务（克隆开销很小：HTTP 客户端和缓存都是共享的）
+#[derive(Clone)]
 pub struct TranslationService {
     config: TranslationConfig,
     client: Client,
@@ -171,9 +362,11 @@ impl TranslationService {
         "en".to_string()
     }
 
-    /// 生成缓存键
+    /// 生成缓存键（提供方、模型、语言方向和文本内容的哈希）
     fn cache_key(&self, text: &str, from_lang: &str, to_lang: &str) -> String {
-        format!("{}:{}:{}", from_lang, to_lang, text)
+        let model = if self.config.provider.uses_model() { self.config.model.as_str() } else { "" };
+        let content = format!("{:?}\n{}\n{}\n{}\n{}", self.config.provider, model, from_lang, to_lang, text);
+        format!("{:x}", Sha256::digest(content.as_bytes()))
     }
 
     /// 从缓存获取翻译结果
@@ -208,23 +401,176 @@ impl TranslationService {
         debug!("Cleaned up expired cache entries");
     }
 
-    /// 翻译API请求
+    /// API 基础地址（未填写时使用提供方的默认地址）
+    fn base_url(&self) -> String {
+        let configured = self.config.api_base_url.trim();
+        if !configured.is_empty() {
+            return configured.trim_end_matches('/').to_string();
+        }
+        match self.config.provider {
+            // DeepL 免费版密钥以 ":fx" 结尾，使用单独的域名
+            TranslationProvider::DeepL if self.config.api_key.ends_with(":fx") => {
+                "https://api-free.deepl.com".to_string()
+            }
+            provider => provider.default_base_url().to_string(),
+        }
+    }
+
+    /// 翻译API请求：按提供方分发，返回与输入一一对应的译文
     async fn call_translation_api(
         &self,
-        text: &str,
+        texts: &[String],
         from_lang: &str,
         to_lang: &str,
-    ) -> Result<String> {
+    ) -> Result<Vec<String>> {
         // 检查API密钥是否已配置
-        if self.config.api_key.is_empty() {
+        if self.config.provider.requires_api_key() && self.config.api_key.is_empty() {
             return Err(anyhow::anyhow!(
-                "API密钥未配置，请在设置中填写您的Silicon Flow API密钥"
+                "API密钥未配置，请在设置中填写{}的API密钥",
+                self.config.provider.label()
             ));
         }
+
+        let translated = match self.config.provider {
+            TranslationProvider::OpenAi | TranslationProvider::Local => {
+                let mut results = Vec::with_capacity(texts.len());
+                for text in texts {
+                    results.push(self.call_chat_completion(text, from_lang, to_lang).await?);
+                }
+                results
+            }
+            TranslationProvider::DeepL => self.call_deepl(texts, from_lang, to_lang).await?,
+            TranslationProvider::Google => self.call_google(texts, from_lang, to_lang).await?,
+        };
+
+        if translated.len() != texts.len() {
+            return Err(anyhow::anyhow!(
+                "Translation API returned {} results for {} texts",
+                translated.len(),
+                texts.len()
+            ));
+        }
+        Ok(translated)
+    }
+
+    /// 发送请求并解析 JSON 响应
+    async fn send_json(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        let response = request
+            .send()
+            .await
+            .context("Failed to send translation request")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(anyhow::anyhow!(
+                "Translation API error: {} - {}",
+                status,
+                error_text
+            ));
+        }
+
+        response.json().await.context("Failed to parse API response")
+    }
+
+    /// DeepL：一次请求翻译多段文本
+    async fn call_deepl(&self, texts: &[String], from_lang: &str, to_lang: &str) -> Result<Vec<String>> {
+        let deepl_lang = |lang: &str, target: bool| match lang {
+            "en" if target => "EN-US".to_string(),
+            other => other.to_uppercase(),
+        };
+        let base_url = self.base_url();
+        let api_url = if base_url.ends_with("/translate") {
+            base_url
+        } else {
+            format!("{}/v2/translate", base_url)
+        };
+        let request_body = serde_json::json!({
+            "text": texts,
+            "source_lang": deepl_lang(from_lang, false),
+            "target_lang": deepl_lang(to_lang, true),
+        });
+
+        let response_json = self
+            .send_json(
+                self.client
+                    .post(&api_url)
+                    .header("Authorization", format!("DeepL-Auth-Key {}", self.config.api_key))
+                    .json(&request_body),
+            )
+            .await?;
+
+        response_json
+            .get("translations")
+            .and_then(|t| t.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid API response format"))?
+            .iter()
+            .map(|t| {
+                t.get("text")
+                    .and_then(|text| text.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid API response format"))
+            })
+            .collect()
+    }
+
+    /// Google Cloud Translation v2：一次请求翻译多段文本
+    async fn call_google(&self, texts: &[String], from_lang: &str, to_lang: &str) -> Result<Vec<String>> {
+        let google_lang = |lang: &str| match lang {
+            "zh" => "zh-CN".to_string(),
+            other => other.to_string(),
+        };
+        let base_url = self.base_url();
+        let api_url = if base_url.ends_with("/v2") {
+            base_url
+        } else {
+            format!("{}/language/translate/v2", base_url)
+        };
+        let request_body = serde_json::json!({
+            "q": texts,
+            "source": google_lang(from_lang),
+            "target": google_lang(to_lang),
+            "format": "text",
+        });
+
+        let response_json = self
+            .send_json(
+                self.client
+                    .post(&api_url)
+                    .query(&[("key", self.config.api_key.as_str())])
+                    .json(&request_body),
+            )
+            .await?;
+
+        response_json
+            .get("data")
+            .and_then(|d| d.get("translations"))
+            .and_then(|t| t.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid API response format"))?
+            .iter()
+            .map(|t| {
+                t.get("translatedText")
+                    .and_then(|text| text.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid API response format"))
+            })
+            .collect()
+    }
+
+    /// OpenAI 兼容接口（含本地模型）
+    async fn call_chat_completion(
+        &self,
+        text: &str,
+        from_lang: &str,
+        to_lang: &str,
+    ) -> Result<String> {
         let system_prompt = match (from_lang, to_lang) {
-            ("zh", "en") => "You are a professional Chinese to English translator. Translate the following Chinese text to natural, fluent English while preserving the original meaning and tone. Only return the translated text, nothing else.",
-            ("en", "zh") => "You are a professional English to Chinese translator. Translate the following English text to natural, fluent Chinese while preserving the original meaning and tone. Only return the translated text, nothing else.",
-            _ => "You are a professional translator. Translate the text to the target language while preserving the original meaning and tone. Only return the translated text, nothing else.",
+            ("zh", "en") => "You are a professional Chinese to English translator. Translate the following Chinese text to natural, fluent English while preserving the original meaning and tone. Keep placeholders like ⟦0⟧ unchanged. Only return the translated text, nothing else.",
+            ("en", "zh") => "You are a professional English to Chinese translator. Translate the following English text to natural, fluent Chinese while preserving the original meaning and tone. Keep placeholders like ⟦0⟧ unchanged. Only return the translated text, nothing else.",
+            _ => "You are a professional translator. Translate the text to the target language while preserving the original meaning and tone. Keep placeholders like ⟦0⟧ unchanged. Only return the translated text, nothing else.",
         };
 
         let request_body = serde_json::json!({
@@ -247,36 +593,19 @@ impl TranslationService {
         debug!("Sending translation request for text: {}", text);
 
         // 智能规范化 API URL（支持用户输入简化的基础 URL）
-        let api_url = normalize_api_url(&self.config.api_base_url, ApiEndpointType::OpenAI);
+        let api_url = normalize_api_url(&self.base_url(), ApiEndpointType::OpenAI);
         debug!("Using normalized API URL: {}", api_url);
 
-        let response = self
+        let mut request = self
             .client
             .post(&api_url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .context("Failed to send translation request")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(anyhow::anyhow!(
-                "Translation API error: {} - {}",
-                status,
-                error_text
-            ));
+            .json(&request_body);
+        // 本地模型通常不需要密钥
+        if !self.config.api_key.is_empty() {
+            request = request.header("Authorization", format!("Bearer {}", self.config.api_key));
         }
-
-        let response_json: serde_json::Value = response
-            .json()
-            .await
-            .context("Failed to parse API response")?;
+        let response_json = self.send_json(request).await?;
 
         // 提取翻译结果
         let translated_text = response_json
@@ -301,73 +630,82 @@ impl TranslationService {
             return Ok(text.to_string());
         }
 
-        if text.trim().is_empty() {
-            return Ok(text.to_string());
+        let mut results = self.translate_many(std::slice::from_ref(&text.to_string()), target_lang).await;
+        Ok(results.remove(0))
+    }
+
+    /// 批量翻译
+    pub async fn translate_batch(
+        &self,
+        texts: &[String],
+        target_lang: Option<&str>,
+    ) -> Result<Vec<String>> {
+        if !self.config.enabled {
+            debug!("Translation disabled, returning original texts");
+            return Ok(texts.to_vec());
         }
 
-        // 检测源语言
-        let from_lang = self.detect_language(text);
+        Ok(self.translate_many(texts, target_lang).await)
+    }
+
+    /// 翻译多段文本：先查缓存，未命中的按语言方向分组，每组只请求一次提供方
+    ///
+    /// 失败时对应文本返回原文（降级策略）。
+    async fn translate_many(&self, texts: &[String], target_lang: Option<&str>) -> Vec<String> {
+        let mut results = texts.to_vec();
+        let mut pending: HashMap<(String, String), Vec<PendingText>> = HashMap::new();
 
-        // 确定目标语言
-        let to_lang = target_lang.unwrap_or_else(|| {
-            match from_lang.as_str() {
-                "zh" => "en", // 中文翻译为英文
-                _ => "zh",    // 其他语言翻译为中文
+        for (index, text) in texts.iter().enumerate() {
+            if text.trim().is_empty() {
+                continue;
             }
-        });
 
-        // 如果源语言和目标语言相同，直接返回
-        if from_lang == to_lang {
-            debug!("Source and target languages are the same, skipping translation");
-            return Ok(text.to_string());
-        }
+            // 检测源语言，确定目标语言
+            let from_lang = self.detect_language(text);
+            let to_lang = target_lang
+                .map(str::to_string)
+                .unwrap_or_else(|| default_target_lang(&from_lang));
 
-        // 生成缓存键
-        let cache_key = self.cache_key(text, &from_lang, to_lang);
+            // 如果源语言和目标语言相同，直接返回
+            if from_lang == to_lang {
+                debug!("Source and target languages are the same, skipping translation");
+                continue;
+            }
 
-        // 尝试从缓存获取
-        if let Some(cached_result) = self.get_cached_translation(&cache_key).await {
-            info!("Using cached translation");
-            return Ok(cached_result);
-        }
+            let protected = protect_terms(text, &self.config.glossary, &to_lang, self.config.protect_code);
+            let cache_key = self.cache_key(&protected.text, &from_lang, &to_lang);
 
-        // 调用翻译API
-        match self.call_translation_api(text, &from_lang, to_lang).await {
-            Ok(translated_text) => {
-                // 缓存结果
-                self.cache_translation(cache_key, translated_text.clone())
-                    .await;
-                info!("Translation completed: {} -> {}", from_lang, to_lang);
-                Ok(translated_text)
-            }
-            Err(e) => {
-                error!("Translation failed: {}", e);
-                // 降级策略：返回原文
-                warn!("Using fallback: returning original text due to translation failure");
-                Ok(text.to_string())
+            // 尝试从缓存获取
+            if let Some(cached_result) = self.get_cached_translation(&cache_key).await {
+                info!("Using cached translation");
+                results[index] = restore_terms(&cached_result, &protected.segments);
+                continue;
             }
+            pending.entry((from_lang, to_lang)).or_default().push((index, protected, cache_key));
         }
-    }
 
-    /// 批量翻译
-    pub async fn translate_batch(
-        &self,
-        texts: &[String],
-        target_lang: Option<&str>,
-    ) -> Result<Vec<String>> {
-        let mut results = Vec::new();
-
-        for text in texts {
-            match self.translate(text, target_lang).await {
-                Ok(translated) => results.push(translated),
-                Err(_) => {
-                    // 单个翻译失败时使用原文
-                    results.push(text.clone());
+        for ((from_lang, to_lang), items) in pending {
+            let inputs: Vec<String> = items.iter().map(|(_, protected, _)| protected.text.clone()).collect();
+
+            // 调用翻译API
+            match self.call_translation_api(&inputs, &from_lang, &to_lang).await {
+                Ok(outputs) => {
+                    for ((index, protected, cache_key), translated_text) in items.into_iter().zip(outputs) {
+                        results[index] = restore_terms(&translated_text, &protected.segments);
+                        // 缓存结果（保留占位符，术语表修改后仍可复用）
+                        self.cache_translation(cache_key, translated_text).await;
+                    }
+                    info!("Translation completed: {} -> {} ({} texts)", from_lang, to_lang, inputs.len());
+                }
+                Err(e) => {
+                    error!("Translation failed: {}", e);
+                    // 降级策略：返回原文
+                    warn!("Using fallback: returning original text due to translation failure");
                 }
             }
         }
 
-        Ok(results)
+        results
     }
 
     /// 更新配置
@@ -413,10 +751,21 @@ static TRANSLATION_SERVICE: once_cell::sync::Lazy<Arc<Mutex<TranslationService>>
         )))
     });
 
+/// 双向翻译设置的副本，执行入口读取时不需要等待翻译服务的锁
+static PIPELINE_SETTINGS: once_cell::sync::Lazy<std::sync::RwLock<Option<PromptTranslationSettings>>> =
+    once_cell::sync::Lazy::new(|| std::sync::RwLock::new(None));
+
+/// 替换全局服务的配置
+fn install_config(service: &mut TranslationService, config: TranslationConfig) {
+    *PIPELINE_SETTINGS.write().unwrap() =
+        (config.enabled && config.pipeline.enabled).then(|| config.pipeline.clone());
+    *service = TranslationService::new(config);
+}
+
 /// 初始化翻译服务
 pub async fn init_translation_service(config: TranslationConfig) {
     let mut service = TRANSLATION_SERVICE.lock().await;
-    *service = TranslationService::new(config);
+    install_config(&mut service, config);
     info!("Translation service initialized");
 }
 
@@ -442,11 +791,26 @@ fn get_translation_service() -> Arc<Mutex<TranslationService>> {
     TRANSLATION_SERVICE.clone()
 }
 
+/// 当前服务的副本，翻译请求期间不持有全局锁
+async fn current_service() -> TranslationService {
+    get_translation_service().lock().await.clone()
+}
+
 /// 翻译文本（公共接口）
 pub async fn translate_text(text: &str, target_lang: Option<&str>) -> Result<String> {
+    current_service().await.translate(text, target_lang).await
+}
+
+/// 检测文本语言（公共接口）
+pub async fn detect_language(text: &str) -> String {
     let service_arc = get_translation_service();
     let service = service_arc.lock().await;
-    service.translate(text, target_lang).await
+    service.detect_language(text)
+}
+
+/// 双向翻译设置（翻译功能或双向翻译未启用时为空）
+pub fn pipeline_settings() -> Option<PromptTranslationSettings> {
+    PIPELINE_SETTINGS.read().unwrap().clone()
 }
 
 /// Tauri命令：翻译文本
@@ -465,8 +829,7 @@ pub async fn translate_batch(
     texts: Vec<String>,
     target_lang: Option<String>,
 ) -> Result<Vec<String>, String> {
-    let service_arc = get_translation_service();
-    let service = service_arc.lock().await;
+    let service = current_service().await;
     let target = target_lang.as_deref();
 
     service
@@ -483,7 +846,7 @@ pub async fn get_translation_config() -> Result<TranslationConfig, String> {
         Ok(config) => {
             // 同时更新内存中的配置
             let mut service = TRANSLATION_SERVICE.lock().await;
-            *service = TranslationService::new(config.clone());
+            install_config(&mut service, config.clone());
             Ok(config)
         }
         Err(_) => {
@@ -526,6 +889,21 @@ pub async fn get_translation_cache_stats() -> Result<CacheStats, String> {
     Ok(service.get_cache_stats().await)
 }
 
+/// Tauri命令：列出可用的翻译服务提供方
+#[tauri::command]
+pub async fn list_translation_providers() -> Result<Vec<TranslationProviderInfo>, String> {
+    Ok(TranslationProvider::ALL
+        .iter()
+        .map(|&provider| TranslationProviderInfo {
+            id: provider,
+            label: provider.label().to_string(),
+            default_base_url: provider.default_base_url().to_string(),
+            requires_api_key: provider.requires_api_key(),
+            uses_model: provider.uses_model(),
+        })
+        .collect())
+}
+
 /// Tauri命令：检测文本语言
 #[tauri::command]
 pub async fn detect_text_language(text: String) -> Result<String, String> {
@@ -542,7 +920,7 @@ fn get_translation_config_path() -> Result<PathBuf, String> {
 
 /// 获取Claude目录路径
 fn get_claude_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
+    let home_dir = dirs::home_dir().ok_or_else(|| tr!("common.home_dir_unavailable"))?;
     let claude_dir = home_dir.join(".claude");
 
     // 确保目录存在
@@ -602,3 +980,36 @@ pub async fn init_translation_service_command(
     init_translation_service(final_config).await;
     Ok("Translation service initialized successfully".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protects_code_and_glossary_terms() {
+        let glossary = vec![
+            GlossaryTerm { term: "会话".to_string(), translation: Some("session".to_string()), target_lang: Some("en".to_string()) },
+            GlossaryTerm { term: "AnyCode".to_string(), translation: None, target_lang: None },
+            GlossaryTerm { term: "仅中文".to_string(), translation: Some("zh only".to_string()), target_lang: Some("zh".to_string()) },
+        ];
+        let text = "在 AnyCode 中修改 `fn main` 和 session_id，调用 getUser() 后重启会话，参见 src/main.rs 与 https://example.com/a?b=1 仅中文";
+        let protected = protect_terms(text, &glossary, "en", true);
+        assert_eq!(
+            protected.text,
+            "在 ⟦0⟧ 中修改 ⟦1⟧ 和 ⟦2⟧，调用 ⟦3⟧ 后重启⟦4⟧，参见 ⟦5⟧ 与 ⟦6⟧ 仅中文"
+        );
+        assert_eq!(protected.segments[4], "session");
+
+        // 模型可能在占位符里加空格
+        let translated = "Change ⟦1⟧ and ⟦2⟧ in ⟦0⟧, call ⟦3⟧ and restart the ⟦ 4 ⟧, see ⟦5⟧ and ⟦6⟧ ⟦9⟧";
+        assert_eq!(
+            restore_terms(translated, &protected.segments),
+            "Change `fn main` and session_id in AnyCode, call getUser() and restart the session, see src/main.rs and https://example.com/a?b=1 ⟦9⟧"
+        );
+
+        // 普通英文单词照常翻译
+        let plain = protect_terms("Please fix the bug", &[], "zh", true);
+        assert!(plain.segments.is_empty());
+        assert_eq!(protect_terms("session_id", &[], "zh", false).text, "session_id");
+    }
+}
@@ -0,0 +1,379 @@
+//! 工作区回收站
+//!
+//! 通过 AnyCode 删除项目文件时不直接删除，而是移动到
+//! `~/.anycode/trash/<项目哈希>/<id>/data`，同目录的 `entry.json` 记录原路径等信息，
+//! 可以随时恢复。每次放入回收站时按保留时长和总大小自动清理最旧的条目。
+//!
+//! 回滚、快照恢复和保留策略清理删除文件时也走回收站；不属于任何项目的数据文件
+//! （会话文件、执行日志）按所在目录归组。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::file_operations::move_path;
+use super::data_root::anycode_dir;
+
+/// 默认保留时长（天）
+const DEFAULT_MAX_AGE_DAYS: u64 = 30;
+
+/// 每个项目回收站的默认容量上限（MB）
+const DEFAULT_MAX_SIZE_MB: u64 = 1024;
+
+const ENTRY_FILE: &str = "entry.json";
+const DATA_NAME: &str = "data";
+
+/// 回收站条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    pub id: String,
+    pub project_path: String,
+    /// 删除前的绝对路径
+    pub original_path: String,
+    /// 相对项目根目录的路径
+    pub relative_path: String,
+    pub deleted_at: String,
+    pub size_bytes: u64,
+    pub is_dir: bool,
+    /// 删除来源（如 "manual"、"batch"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// 清理结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashPurgeResult {
+    pub removed: usize,
+    pub freed_bytes: u64,
+}
+
+pub fn get_trash_root() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("trash"))
+}
+
+/// 项目回收站目录名（规范化路径的 SHA256 前 16 位）
+fn project_hash(project_root: &Path) -> String {
+    let normalized = project_root.to_string_lossy().replace('\\', "/").to_lowercase();
+    let digest = format!("{:x}", Sha256::digest(normalized.as_bytes()));
+    digest[..16].to_string()
+}
+
+fn project_trash_dir(project_root: &Path) -> Result<PathBuf, String> {
+    Ok(project_trash_dir_in(&get_trash_root()?, project_root))
+}
+
+fn project_trash_dir_in(trash_root: &Path, project_root: &Path) -> PathBuf {
+    trash_root.join(project_hash(project_root))
+}
+
+fn path_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+fn read_entry(entry_dir: &Path) -> Option<TrashEntry> {
+    let content = fs::read_to_string(entry_dir.join(ENTRY_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 目录中的条目（最新的在前）；数据已不存在的条目跳过
+fn read_entries(trash_dir: &Path) -> Vec<(PathBuf, TrashEntry)> {
+    let Ok(dirs) = fs::read_dir(trash_dir) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<(PathBuf, TrashEntry)> = dirs
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|dir| dir.join(DATA_NAME).exists())
+        .filter_map(|dir| read_entry(&dir).map(|entry| (dir, entry)))
+        .collect();
+    entries.sort_by(|a, b| b.1.deleted_at.cmp(&a.1.deleted_at));
+    entries
+}
+
+/// 分配一个回收站位置：返回 (条目 ID, 数据路径)，此时尚未创建任何文件
+pub fn new_trash_slot(project_root: &Path) -> Result<(String, PathBuf), String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let data_path = project_trash_dir(project_root)?.join(&id).join(DATA_NAME);
+    Ok((id, data_path))
+}
+
+/// 文件已移动到 `data_path` 后写入条目信息
+pub fn commit_trash_entry(
+    project_root: &Path,
+    data_path: &Path,
+    original: &Path,
+    source: Option<&str>,
+) -> Result<TrashEntry, String> {
+    let entry_dir = data_path.parent().ok_or("回收站路径无效")?;
+    let id = entry_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("回收站路径无效")?;
+    let entry = TrashEntry {
+        id,
+        project_path: project_root.to_string_lossy().to_string(),
+        original_path: original.to_string_lossy().to_string(),
+        relative_path: original
+            .strip_prefix(project_root)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| original.to_string_lossy().to_string()),
+        deleted_at: Utc::now().to_rfc3339(),
+        size_bytes: path_size(data_path),
+        is_dir: data_path.is_dir(),
+        source: source.map(str::to_string),
+    };
+    let content = serde_json::to_string_pretty(&entry).map_err(|e| format!("序列化回收站条目失败: {}", e))?;
+    fs::write(entry_dir.join(ENTRY_FILE), content).map_err(|e| format!("写入回收站条目失败: {}", e))?;
+    Ok(entry)
+}
+
+/// 丢弃条目目录（数据已移回原处或需要彻底删除时）
+pub fn discard_trash_slot(data_path: &Path) {
+    if let Some(entry_dir) = data_path.parent() {
+        let _ = fs::remove_dir_all(entry_dir);
+    }
+}
+
+/// 按保留时长和总大小清理，最旧的先删
+fn purge_dir(trash_dir: &Path, max_age_days: u64, max_bytes: u64) -> TrashPurgeResult {
+    let mut result = TrashPurgeResult::default();
+    let Ok(dirs) = fs::read_dir(trash_dir) else {
+        return result;
+    };
+
+    let cutoff = Utc::now() - chrono::Duration::days(max_age_days as i64);
+    let mut remove = |dir: &Path, size: u64| {
+        if fs::remove_dir_all(dir).is_ok() {
+            result.removed += 1;
+            result.freed_bytes += size;
+        }
+    };
+
+    // 没有条目信息或数据的目录（写入中断）直接清掉
+    let mut kept: Vec<(PathBuf, TrashEntry)> = Vec::new();
+    for dir in dirs.filter_map(|e| e.ok()).map(|e| e.path()) {
+        match read_entry(&dir).filter(|_| dir.join(DATA_NAME).exists()) {
+            Some(entry) => {
+                let expired = DateTime::parse_from_rfc3339(&entry.deleted_at)
+                    .map(|t| t.with_timezone(&Utc) < cutoff)
+                    .unwrap_or(true);
+                if expired {
+                    remove(&dir, entry.size_bytes);
+                } else {
+                    kept.push((dir, entry));
+                }
+            }
+            None => remove(&dir, 0),
+        }
+    }
+
+    kept.sort_by(|a, b| b.1.deleted_at.cmp(&a.1.deleted_at));
+    let mut total: u64 = kept.iter().map(|(_, e)| e.size_bytes).sum();
+    while total > max_bytes {
+        let Some((dir, entry)) = kept.pop() else { break };
+        total -= entry.size_bytes;
+        remove(&dir, entry.size_bytes);
+    }
+    result
+}
+
+/// 把项目中的文件或目录移入回收站（阻塞）
+pub fn trash_path(project_root: &Path, path: &Path, source: Option<&str>) -> Result<TrashEntry, String> {
+    trash_path_in(&get_trash_root()?, project_root, path, source)
+}
+
+/// 同 `trash_path`，回收站根目录由调用方指定
+pub fn trash_path_in(
+    trash_root: &Path,
+    project_root: &Path,
+    path: &Path,
+    source: Option<&str>,
+) -> Result<TrashEntry, String> {
+    if !path.starts_with(project_root) || path == project_root {
+        return Err(format!("路径不在项目内: {}", path.display()));
+    }
+    if !path.exists() {
+        return Err(format!("路径不存在: {}", path.display()));
+    }
+
+    let trash_dir = project_trash_dir_in(trash_root, project_root);
+    let data_path = trash_dir.join(uuid::Uuid::new_v4().to_string()).join(DATA_NAME);
+    if let Err(e) = move_path(path, &data_path) {
+        discard_trash_slot(&data_path);
+        return Err(e);
+    }
+    let entry = commit_trash_entry(project_root, &data_path, path, source)?;
+
+    let purged = purge_dir(&trash_dir, DEFAULT_MAX_AGE_DAYS, DEFAULT_MAX_SIZE_MB * 1024 * 1024);
+    if purged.removed > 0 {
+        log::info!("[Trash] Purged {} old entries ({} bytes)", purged.removed, purged.freed_bytes);
+    }
+    Ok(entry)
+}
+
+/// 把项目中的文件移入回收站，项目路径和文件路径都先规范化
+pub fn trash_project_file(project_path: &str, path: &Path, source: Option<&str>) -> Result<TrashEntry, String> {
+    let root = canonical_project(project_path)?;
+    let target = fs::canonicalize(path).map_err(|e| format!("路径不存在 {}: {}", path.display(), e))?;
+    trash_path(&root, &target, source)
+}
+
+/// 把不属于项目的数据文件（会话文件、执行日志等）移入回收站，按所在目录归组
+pub fn trash_data_file(path: &Path, source: Option<&str>) -> Result<TrashEntry, String> {
+    let parent = path.parent().ok_or_else(|| format!("路径无效: {}", path.display()))?;
+    trash_path(parent, path, source)
+}
+
+fn find_entry_dir(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\', '.']) {
+        return Err(format!("无效的回收站条目: {}", id));
+    }
+    let root = get_trash_root()?;
+    fs::read_dir(&root)
+        .map_err(|_| format!("回收站条目不存在: {}", id))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path().join(id))
+        .find(|dir| dir.join(ENTRY_FILE).exists())
+        .ok_or_else(|| format!("回收站条目不存在: {}", id))
+}
+
+fn canonical_project(project_path: &str) -> Result<PathBuf, String> {
+    fs::canonicalize(project_path).map_err(|e| format!("项目目录无效 {}: {}", project_path, e))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// 删除项目中的文件（移入回收站），路径可以是相对项目根目录的路径
+#[tauri::command]
+pub async fn trash_files(project_path: String, paths: Vec<String>) -> Result<Vec<TrashEntry>, String> {
+    let root = canonical_project(&project_path)?;
+    let mut entries = Vec::new();
+    for path in paths {
+        let target = root.join(&path);
+        let target = fs::canonicalize(&target).map_err(|e| format!("路径不存在 {}: {}", path, e))?;
+        entries.push(trash_path(&root, &target, Some("manual"))?);
+    }
+    log::info!("[Trash] Moved {} paths to trash for {}", entries.len(), project_path);
+    Ok(entries)
+}
+
+/// 列出项目回收站中的条目（最新的在前）
+#[tauri::command]
+pub async fn list_trash(project_path: String) -> Result<Vec<TrashEntry>, String> {
+    let root = canonical_project(&project_path)?;
+    Ok(read_entries(&project_trash_dir(&root)?).into_iter().map(|(_, e)| e).collect())
+}
+
+/// 恢复回收站条目到原位置（原位置已存在同名文件时失败）
+#[tauri::command]
+pub async fn restore_trashed(id: String) -> Result<TrashEntry, String> {
+    let entry_dir = find_entry_dir(&id)?;
+    let entry = read_entry(&entry_dir).ok_or_else(|| format!("回收站条目损坏: {}", id))?;
+    let original = PathBuf::from(&entry.original_path);
+    if original.exists() {
+        return Err(format!("原位置已存在文件: {}", entry.original_path));
+    }
+    let data_path = entry_dir.join(DATA_NAME);
+    move_path(&data_path, &original)?;
+    discard_trash_slot(&data_path);
+    log::info!("[Trash] Restored {}", entry.original_path);
+    Ok(entry)
+}
+
+/// 彻底删除回收站条目
+#[tauri::command]
+pub async fn delete_trashed(id: String) -> Result<(), String> {
+    let entry_dir = find_entry_dir(&id)?;
+    fs::remove_dir_all(&entry_dir).map_err(|e| format!("删除回收站条目失败: {}", e))
+}
+
+/// 清理回收站：未指定项目时清理所有项目
+#[tauri::command]
+pub async fn purge_trash(
+    project_path: Option<String>,
+    max_age_days: Option<u64>,
+    max_size_mb: Option<u64>,
+) -> Result<TrashPurgeResult, String> {
+    let max_age_days = max_age_days.unwrap_or(DEFAULT_MAX_AGE_DAYS);
+    let max_bytes = max_size_mb.unwrap_or(DEFAULT_MAX_SIZE_MB) * 1024 * 1024;
+    let dirs: Vec<PathBuf> = match project_path {
+        Some(project_path) => vec![project_trash_dir(&canonical_project(&project_path)?)?],
+        None => fs::read_dir(get_trash_root()?)
+            .map(|dirs| dirs.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default(),
+    };
+
+    let mut result = TrashPurgeResult::default();
+    for dir in dirs {
+        let purged = purge_dir(&dir, max_age_days, max_bytes);
+        result.removed += purged.removed;
+        result.freed_bytes += purged.freed_bytes;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_entry(trash_dir: &Path, id: &str, deleted_at: DateTime<Utc>, size: usize) {
+        let dir = trash_dir.join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(DATA_NAME), vec![b'x'; size]).unwrap();
+        let entry = TrashEntry {
+            id: id.to_string(),
+            project_path: "/p".to_string(),
+            original_path: format!("/p/{}", id),
+            relative_path: id.to_string(),
+            deleted_at: deleted_at.to_rfc3339(),
+            size_bytes: size as u64,
+            is_dir: false,
+            source: None,
+        };
+        fs::write(dir.join(ENTRY_FILE), serde_json::to_string(&entry).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn purges_by_age_then_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        write_entry(dir.path(), "expired", now - chrono::Duration::days(40), 10);
+        write_entry(dir.path(), "old", now - chrono::Duration::days(3), 60);
+        write_entry(dir.path(), "new", now, 50);
+        fs::create_dir_all(dir.path().join("orphan")).unwrap();
+
+        let result = purge_dir(dir.path(), 30, 100);
+        assert_eq!(result.removed, 3);
+        assert_eq!(result.freed_bytes, 70);
+        let ids: Vec<String> = read_entries(dir.path()).into_iter().map(|(_, e)| e.id).collect();
+        assert_eq!(ids, vec!["new"]);
+    }
+
+    #[test]
+    fn commits_entry_next_to_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src/a.rs"), "fn a() {}").unwrap();
+
+        let data_path = dir.path().join("trash").join("abc").join(DATA_NAME);
+        move_path(&project.join("src/a.rs"), &data_path).unwrap();
+        let entry = commit_trash_entry(&project, &data_path, &project.join("src/a.rs"), Some("manual")).unwrap();
+        assert_eq!(entry.id, "abc");
+        assert_eq!(entry.relative_path, "src/a.rs");
+        assert_eq!(entry.size_bytes, 9);
+        assert_eq!(read_entries(&dir.path().join("trash")).len(), 1);
+    }
+}
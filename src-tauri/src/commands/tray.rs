@@ -0,0 +1,270 @@
+//! 系统托盘与后台执行模式
+//!
+//! 启用“关闭到托盘”后，关闭主窗口只会隐藏窗口，正在运行的 Claude / Codex / Gemini
+//! 执行继续在后台进行（进程归应用所有，而非窗口）。托盘菜单列出运行中的会话并可逐个停止，
+//! 单击托盘图标恢复主窗口；只有选择“退出”才会真正结束应用并终止所有会话。
+//!
+//! 设置保存在 ~/.anycode/tray.json。
+
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager};
+
+use crate::process::orphans::{self, TrackedProcess};
+use super::data_root::anycode_dir;
+
+const TRAY_ID: &str = "main-tray";
+
+/// 刷新托盘菜单中运行会话列表的间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+const MENU_SHOW: &str = "tray:show";
+const MENU_QUIT: &str = "tray:quit";
+const MENU_CANCEL_PREFIX: &str = "tray:cancel:";
+
+/// 托盘设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TraySettings {
+    /// 关闭主窗口时隐藏到托盘，执行在后台继续
+    pub close_to_tray: bool,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self { close_to_tray: true }
+    }
+}
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("tray.json"))
+}
+
+pub fn load_tray_settings() -> TraySettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 主窗口关闭请求是否应改为隐藏到托盘
+pub fn should_hide_on_close(app: &AppHandle) -> bool {
+    app.tray_by_id(TRAY_ID).is_some() && load_tray_settings().close_to_tray
+}
+
+/// 创建托盘图标并启动菜单刷新循环
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let menu = build_menu(app, &[])?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Any Code")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(handle_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app).map_err(|e| format!("Failed to create tray icon: {}", e))?;
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last: Vec<(u32, Option<String>)> = Vec::new();
+        loop {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+            let running = orphans::running_processes();
+            let fingerprint: Vec<(u32, Option<String>)> =
+                running.iter().map(|p| (p.pid, p.session_id.clone())).collect();
+            if fingerprint != last {
+                refresh_tray(&app_handle, &running);
+                last = fingerprint;
+            }
+        }
+    });
+
+    log::info!("[Tray] Tray icon created");
+    Ok(())
+}
+
+fn project_name(project_path: &str) -> String {
+    Path::new(project_path.trim_end_matches(['/', '\\']))
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_path.to_string())
+}
+
+/// 菜单项 ID：`tray:cancel:{engine}:{session_id}`
+fn cancel_menu_id(process: &TrackedProcess) -> Option<String> {
+    process
+        .session_id
+        .as_ref()
+        .map(|sid| format!("{}{}:{}", MENU_CANCEL_PREFIX, process.engine, sid))
+}
+
+fn parse_cancel_menu_id(id: &str) -> Option<(&str, &str)> {
+    id.strip_prefix(MENU_CANCEL_PREFIX)?.split_once(':')
+}
+
+fn build_menu(app: &AppHandle, running: &[TrackedProcess]) -> Result<Menu<tauri::Wry>, String> {
+    let to_err = |e: tauri::Error| format!("Failed to build tray menu: {}", e);
+    let menu = Menu::new(app).map_err(to_err)?;
+
+    let show = MenuItem::with_id(app, MENU_SHOW, "显示主窗口", true, None::<&str>).map_err(to_err)?;
+    menu.append(&show).map_err(to_err)?;
+    menu.append(&PredefinedMenuItem::separator(app).map_err(to_err)?).map_err(to_err)?;
+
+    let header = if running.is_empty() {
+        "没有运行中的会话".to_string()
+    } else {
+        format!("运行中的会话 ({})", running.len())
+    };
+    menu.append(&MenuItem::new(app, header, false, None::<&str>).map_err(to_err)?)
+        .map_err(to_err)?;
+    for process in running {
+        // 没有会话 ID 的进程无法按会话取消，只展示
+        let label = format!("停止 {} · {}", process.engine, project_name(&process.project_path));
+        let item = match cancel_menu_id(process) {
+            Some(id) => MenuItem::with_id(app, id, label, true, None::<&str>),
+            None => MenuItem::new(app, label, false, None::<&str>),
+        }
+        .map_err(to_err)?;
+        menu.append(&item).map_err(to_err)?;
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app).map_err(to_err)?).map_err(to_err)?;
+    let quit = MenuItem::with_id(app, MENU_QUIT, "退出", true, None::<&str>).map_err(to_err)?;
+    menu.append(&quit).map_err(to_err)?;
+    Ok(menu)
+}
+
+fn refresh_tray(app: &AppHandle, running: &[TrackedProcess]) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app, running) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                log::warn!("[Tray] Failed to update menu: {}", e);
+            }
+        }
+        Err(e) => log::warn!("[Tray] {}", e),
+    }
+    let tooltip = if running.is_empty() {
+        "Any Code".to_string()
+    } else {
+        format!("Any Code — {} 个会话运行中", running.len())
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    if id == MENU_SHOW {
+        show_main_window(app);
+    } else if id == MENU_QUIT {
+        quit(app.clone());
+    } else if let Some((engine, session_id)) = parse_cancel_menu_id(id) {
+        let app = app.clone();
+        let engine = engine.to_string();
+        let session_id = session_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = cancel_engine_session(&app, &engine, &session_id).await {
+                log::error!("[Tray] Failed to cancel {} session {}: {}", engine, session_id, e);
+            }
+            refresh_tray(&app, &orphans::running_processes());
+        });
+    }
+}
+
+/// 显示并聚焦主窗口
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        if let Err(e) = window.show() {
+            log::error!("[Tray] Failed to show main window: {}", e);
+        }
+        let _ = window.set_focus();
+    }
+}
+
+/// 按引擎取消一个运行中的会话
+pub async fn cancel_engine_session(app: &AppHandle, engine: &str, session_id: &str) -> Result<(), String> {
+    log::info!("[Tray] Cancelling {} session {}", engine, session_id);
+    let session_id = Some(session_id.to_string());
+    match engine {
+        "claude" => super::claude::cancel_claude_execution(app.clone(), session_id).await.map(|_| ()).map_err(String::from),
+        "codex" => super::codex::cancel_codex(session_id, app.clone()).await.map(|_| ()).map_err(String::from),
+        "gemini" => super::gemini::cancel_gemini(session_id, app.clone()).await.map(|_| ()).map_err(String::from),
+        other => Err(tr!("engine.unsupported", engine = other)),
+    }
+}
+
+/// 真正退出应用：记录窗口状态、终止所有后台会话后退出
+fn quit(app: AppHandle) {
+    super::window::handle_window_close_requested(&app, "main");
+    tauri::async_runtime::spawn(async move {
+        for process in orphans::running_processes() {
+            if let Some(session_id) = process.session_id.as_deref() {
+                if let Err(e) = cancel_engine_session(&app, &process.engine, session_id).await {
+                    log::warn!("[Tray] Failed to stop {} session {} on exit: {}", process.engine, session_id, e);
+                }
+            }
+        }
+        log::info!("[Tray] Exiting application");
+        app.exit(0);
+    });
+}
+
+#[tauri::command]
+pub async fn get_tray_settings() -> Result<TraySettings, String> {
+    Ok(load_tray_settings())
+}
+
+#[tauri::command]
+pub async fn set_tray_settings(settings: TraySettings) -> Result<(), String> {
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize tray settings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write tray.json: {}", e))?;
+    log::info!("[Tray] Saved settings (close to tray: {})", settings.close_to_tray);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_menu_ids_round_trip() {
+        let process = TrackedProcess {
+            pid: 42,
+            start_time: 0,
+            engine: "codex".into(),
+            session_id: Some("run:with:colons".into()),
+            project_path: "/work/demo/".into(),
+            owner_pid: 1,
+            owner_start_time: None,
+            registered_at: chrono::Utc::now(),
+        };
+        let id = cancel_menu_id(&process).unwrap();
+        assert_eq!(parse_cancel_menu_id(&id), Some(("codex", "run:with:colons")));
+        assert_eq!(parse_cancel_menu_id(MENU_QUIT), None);
+        assert_eq!(project_name(&process.project_path), "demo");
+    }
+}
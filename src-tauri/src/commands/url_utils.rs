@@ -1,6 +1,7 @@
 //! API URL 规范化工具模块
 //!
-//! 提供智能 URL 识别与自动补全功能，支持 OpenAI 和 Anthropic 两种 API 格式。
+//! 提供智能 URL 识别与自动补全功能，支持 OpenAI 和 Anthropic 两种 API 格式，
+//! 以及 OpenAI 兼容的 embeddings 端点。
 
 use log::debug;
 
@@ -11,6 +12,8 @@ pub enum ApiEndpointType {
     OpenAI,
     /// Anthropic 格式 - 使用 /v1/messages 端点
     Anthropic,
+    /// OpenAI 兼容的向量接口 - 使用 /v1/embeddings 端点
+    Embeddings,
 }
 
 /// 已知的 API 端点后缀
@@ -18,6 +21,8 @@ const OPENAI_COMPLETIONS_SUFFIX: &str = "/chat/completions";
 const OPENAI_V1_COMPLETIONS_SUFFIX: &str = "/v1/chat/completions";
 const ANTHROPIC_MESSAGES_SUFFIX: &str = "/messages";
 const ANTHROPIC_V1_MESSAGES_SUFFIX: &str = "/v1/messages";
+const EMBEDDINGS_SUFFIX: &str = "/embeddings";
+const V1_EMBEDDINGS_SUFFIX: &str = "/v1/embeddings";
 const V1_SUFFIX: &str = "/v1";
 
 /// 需要移除的所有已知端点后缀（用于提取基础 URL）
@@ -26,6 +31,8 @@ const ALL_ENDPOINT_SUFFIXES: &[&str] = &[
     OPENAI_COMPLETIONS_SUFFIX,
     ANTHROPIC_V1_MESSAGES_SUFFIX,
     ANTHROPIC_MESSAGES_SUFFIX,
+    V1_EMBEDDINGS_SUFFIX,
+    EMBEDDINGS_SUFFIX,
     V1_SUFFIX,
 ];
 
@@ -68,6 +75,7 @@ pub fn normalize_api_url(base_url: &str, endpoint_type: ApiEndpointType) -> Stri
     match endpoint_type {
         ApiEndpointType::OpenAI => normalize_openai_url(url),
         ApiEndpointType::Anthropic => normalize_anthropic_url(url),
+        ApiEndpointType::Embeddings => normalize_embeddings_url(url),
     }
 }
 
@@ -115,6 +123,25 @@ fn normalize_anthropic_url(url: &str) -> String {
     result
 }
 
+/// 规范化 embeddings 接口的 URL
+fn normalize_embeddings_url(url: &str) -> String {
+    if url.ends_with(EMBEDDINGS_SUFFIX) {
+        debug!("Embeddings URL already contains /embeddings: {}", url);
+        return url.to_string();
+    }
+
+    if url.ends_with(V1_SUFFIX) {
+        let result = format!("{}{}", url, EMBEDDINGS_SUFFIX);
+        debug!("Embeddings URL with /v1, appending /embeddings: {}", result);
+        return result;
+    }
+
+    let base = extract_base_url(url);
+    let result = format!("{}{}", base, V1_EMBEDDINGS_SUFFIX);
+    debug!("Embeddings URL normalized from '{}' to '{}'", url, result);
+    result
+}
+
 /// 提取基础 URL（移除所有已知的端点后缀）
 ///
 /// # 参数
@@ -178,6 +205,7 @@ pub fn needs_normalization(url: &str, endpoint_type: ApiEndpointType) -> bool {
     match endpoint_type {
         ApiEndpointType::OpenAI => !url.ends_with(OPENAI_COMPLETIONS_SUFFIX),
         ApiEndpointType::Anthropic => !url.ends_with(ANTHROPIC_MESSAGES_SUFFIX),
+        ApiEndpointType::Embeddings => !url.ends_with(EMBEDDINGS_SUFFIX),
     }
 }
 
@@ -300,6 +328,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_embeddings_url() {
+        assert_eq!(
+            normalize_api_url("http://localhost:11434", ApiEndpointType::Embeddings),
+            "http://localhost:11434/v1/embeddings"
+        );
+        assert_eq!(
+            normalize_api_url("https://api.siliconflow.cn/v1/", ApiEndpointType::Embeddings),
+            "https://api.siliconflow.cn/v1/embeddings"
+        );
+        assert_eq!(
+            normalize_api_url("https://api.openai.com/v1/embeddings", ApiEndpointType::Embeddings),
+            "https://api.openai.com/v1/embeddings"
+        );
+        assert_eq!(normalize_base_url("http://localhost:11434/v1/embeddings"), "http://localhost:11434");
+    }
+
     #[test]
     fn test_needs_normalization() {
         assert!(needs_normalization("http://localhost:3001", ApiEndpointType::OpenAI));
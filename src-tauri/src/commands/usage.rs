@@ -2,6 +2,7 @@
 // Source: https://github.com/meistrari/opcode
 
 use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::{HashMap, HashSet};
@@ -9,6 +10,8 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::{async_runtime, command};
 
+use super::storage::open_agent_db;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
     timestamp: String,
@@ -20,6 +23,12 @@ pub struct UsageEntry {
     cost: f64,
     session_id: String,
     project_path: String,
+    /// `message.id:requestId`, used to dedupe against AnyCode's own records
+    #[serde(skip)]
+    request_id: Option<String>,
+    /// Cost reported by the CLI itself (`costUSD`), if any
+    #[serde(skip)]
+    native_cost: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -358,12 +367,14 @@ fn parse_jsonl_file(
                 if let Ok(entry) = serde_json::from_value::<JsonlEntry>(json_value) {
                     if let Some(message) = &entry.message {
                         // Deduplication based on message ID and request ID
+                        let mut request_id = None;
                         if let (Some(msg_id), Some(req_id)) = (&message.id, &entry.request_id) {
                             let unique_hash = format!("{}:{}", msg_id, req_id);
                             if processed_hashes.contains(&unique_hash) {
                                 continue; // Skip duplicate entry
                             }
-                            processed_hashes.insert(unique_hash);
+                            processed_hashes.insert(unique_hash.clone());
+                            request_id = Some(unique_hash);
                         }
 
                         if let Some(usage) = &message.usage {
@@ -404,6 +415,8 @@ fn parse_jsonl_file(
                                 cost,
                                 session_id: entry.session_id.unwrap_or_else(|| session_id.clone()),
                                 project_path,
+                                request_id,
+                                native_cost: entry.cost_usd,
                             });
                         }
                     }
@@ -480,7 +493,7 @@ fn get_usage_stats_sync(days: Option<u32>) -> Result<UsageStats, String> {
         .ok_or("Failed to get home directory")?
         .join(".claude");
 
-    let all_entries = get_all_usage_entries(&claude_path);
+    let all_entries = claude_stats_entries(&claude_path);
 
     if all_entries.is_empty() {
         return Ok(UsageStats {
@@ -654,7 +667,7 @@ fn get_usage_by_date_range_sync(start_date: String, end_date: String) -> Result<
         .ok_or("Failed to get home directory")?
         .join(".claude");
 
-    let all_entries = get_all_usage_entries(&claude_path);
+    let all_entries = claude_stats_entries(&claude_path);
 
     // Parse dates
     let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").or_else(|_| {
@@ -829,7 +842,7 @@ fn get_session_stats_sync(
         .ok_or("Failed to get home directory")?
         .join(".claude");
 
-    let all_entries = get_all_usage_entries(&claude_path);
+    let all_entries = claude_stats_entries(&claude_path);
 
     // Filter by date range if provided
     // 🚀 修复时区问题：转换为本地时区后进行日期比较
@@ -1214,6 +1227,8 @@ struct GeminiSessionFile {
     stats: Option<GeminiStatsData>,
     #[serde(rename = "createdAt")]
     created_at: Option<String>,
+    #[serde(default)]
+    messages: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1223,6 +1238,24 @@ struct GeminiStatsData {
     total_tokens: Option<u64>,
 }
 
+/// Estimates (input, output) tokens from the transcript when the CLI recorded no stats:
+/// user messages count as input, model replies as output
+fn estimate_gemini_usage(model: &str, messages: &[serde_json::Value]) -> (u64, u64) {
+    let mut usage = (0, 0);
+    for message in messages {
+        let Some(content) = message.get("content").and_then(|c| c.as_str()) else {
+            continue;
+        };
+        let tokens = super::tokenizer::count_text(model, content) as u64;
+        match message.get("type").and_then(|t| t.as_str()) {
+            Some("user") => usage.0 += tokens,
+            Some("gemini" | "model" | "assistant") => usage.1 += tokens,
+            _ => {}
+        }
+    }
+    usage
+}
+
 /// Get Gemini usage entries from ~/.gemini/tmp/
 fn get_gemini_usage_entries() -> Vec<UsageEntryWithEngine> {
     let mut entries = Vec::new();
@@ -1268,36 +1301,39 @@ fn get_gemini_usage_entries() -> Vec<UsageEntryWithEngine> {
                     
                     if let Ok(content) = fs::read_to_string(&path) {
                         if let Ok(session) = serde_json::from_str::<GeminiSessionFile>(&content) {
-                            if let Some(stats) = session.stats {
-                                let input_tokens = stats.input_tokens.unwrap_or(0);
-                                let output_tokens = stats.output_tokens.unwrap_or(0);
-                                
-                                // Skip entries without meaningful usage
-                                if input_tokens == 0 && output_tokens == 0 {
-                                    continue;
-                                }
-                                
-                                let model = session.model.unwrap_or_else(|| "gemini-2.5-pro".to_string());
-                                let cost = calculate_gemini_cost(&model, input_tokens, output_tokens);
-                                let session_id = session.session_id.unwrap_or_else(|| "unknown".to_string());
-                                let timestamp = session.created_at.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-                                
-                                // Extract project path from directory name (it's a hash, so we use it as-is)
-                                let project_hash = project_entry.file_name().to_string_lossy().to_string();
-                                
-                                entries.push(UsageEntryWithEngine {
-                                    engine: "gemini".to_string(),
-                                    timestamp,
-                                    model,
-                                    input_tokens,
-                                    output_tokens,
-                                    cache_creation_tokens: 0,
-                                    cache_read_tokens: 0,
-                                    cost,
-                                    session_id,
-                                    project_path: project_hash,
-                                });
+                            let model = session.model.unwrap_or_else(|| "gemini-2.5-pro".to_string());
+                            let reported = session
+                                .stats
+                                .map(|stats| (stats.input_tokens.unwrap_or(0), stats.output_tokens.unwrap_or(0)))
+                                .filter(|usage| *usage != (0, 0));
+                            // Sessions without recorded stats are estimated from the transcript
+                            let (input_tokens, output_tokens) =
+                                reported.unwrap_or_else(|| estimate_gemini_usage(&model, &session.messages));
+
+                            // Skip entries without meaningful usage
+                            if input_tokens == 0 && output_tokens == 0 {
+                                continue;
                             }
+
+                            let cost = calculate_gemini_cost(&model, input_tokens, output_tokens);
+                            let session_id = session.session_id.unwrap_or_else(|| "unknown".to_string());
+                            let timestamp = session.created_at.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+                            
+                            // Extract project path from directory name (it's a hash, so we use it as-is)
+                            let project_hash = project_entry.file_name().to_string_lossy().to_string();
+                            
+                            entries.push(UsageEntryWithEngine {
+                                engine: "gemini".to_string(),
+                                timestamp,
+                                model,
+                                input_tokens,
+                                output_tokens,
+                                cache_creation_tokens: 0,
+                                cache_read_tokens: 0,
+                                cost,
+                                session_id,
+                                project_path: project_hash,
+                            });
                         }
                     }
                 }
@@ -1319,7 +1355,7 @@ fn get_claude_usage_entries_with_engine() -> Vec<UsageEntryWithEngine> {
         None => return Vec::new(),
     };
     
-    get_all_usage_entries(&claude_path)
+    claude_stats_entries(&claude_path)
         .into_iter()
         .map(|e| UsageEntryWithEngine {
             engine: "claude".to_string(),
@@ -1354,12 +1390,12 @@ fn get_multi_engine_usage_stats_sync(
         all_entries.extend(claude_entries);
     }
     if engine_filter == "all" || engine_filter == "codex" {
-        let codex_entries = get_codex_usage_entries();
+        let codex_entries = with_imported_usage("codex", get_codex_usage_entries());
         log::info!("[Multi-Engine Usage] Codex entries: {}", codex_entries.len());
         all_entries.extend(codex_entries);
     }
     if engine_filter == "all" || engine_filter == "gemini" {
-        let gemini_entries = get_gemini_usage_entries();
+        let gemini_entries = with_imported_usage("gemini", get_gemini_usage_entries());
         log::info!("[Multi-Engine Usage] Gemini entries: {}", gemini_entries.len());
         all_entries.extend(gemini_entries);
     }
@@ -1520,6 +1556,363 @@ pub async fn get_multi_engine_usage_stats(
         .map_err(|e| format!("获取使用统计失败: {}", e))?
 }
 
+// ============================================================================
+// Native Usage Import & Reconciliation
+// ============================================================================
+//
+// 各 CLI 自己记录的用量（Claude projects/*.jsonl 的 usage/costUSD、Codex 的 token_count、
+// Gemini 会话 stats）导入 agents.db 的 usage_entries，与 AnyCode 自己记录的条目合并：
+// 按 (engine, request_id) 去重，同一请求两边数值不一致时记为差异。
+//
+// 统计视图以 CLI 当前文件为准，再补上已导入、但原始文件已被 CLI 清理的记录
+// （`claude_stats_entries` / `with_imported_usage`），两边使用同一套 request_id。
+
+/// 成本差异的相对容差（1%）
+const RECONCILE_COST_TOLERANCE: f64 = 0.01;
+
+/// A usage record read from a CLI's own files
+#[derive(Debug, Clone)]
+struct NativeUsageRecord {
+    request_id: String,
+    entry: UsageEntryWithEngine,
+    /// Cost reported by the CLI itself (Claude `costUSD`)
+    native_cost: Option<f64>,
+    /// Cost computed from AnyCode's pricing table
+    priced_cost: f64,
+}
+
+/// A mismatch found while reconciling native usage with AnyCode's records
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UsageDiscrepancy {
+    pub engine: String,
+    pub request_id: String,
+    pub session_id: String,
+    pub model: String,
+    /// "tokens" | "cost"（与 AnyCode 记录不一致）| "pricing"（CLI 报告的成本与价格表不一致）
+    pub kind: String,
+    pub tracked: f64,
+    pub native: f64,
+}
+
+/// Result of a native usage import
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UsageReconciliation {
+    pub scanned: u64,
+    pub imported: u64,
+    pub updated: u64,
+    pub duplicates: u64,
+    pub discrepancies: Vec<UsageDiscrepancy>,
+}
+
+fn costs_differ(a: f64, b: f64) -> bool {
+    let diff = (a - b).abs();
+    diff > 0.0001 && diff > a.abs().max(b.abs()) * RECONCILE_COST_TOLERANCE
+}
+
+/// Collect usage records from the native files of the given engines
+fn collect_native_usage(engines: &[String]) -> Vec<NativeUsageRecord> {
+    let mut records = Vec::new();
+
+    if engines.iter().any(|e| e == "claude") {
+        if let Some(home) = dirs::home_dir() {
+            for e in get_all_usage_entries(&home.join(".claude")) {
+                let priced_cost = calculate_cost(
+                    &e.model,
+                    &UsageData {
+                        input_tokens: Some(e.input_tokens),
+                        output_tokens: Some(e.output_tokens),
+                        cache_creation_input_tokens: Some(e.cache_creation_tokens),
+                        cache_read_input_tokens: Some(e.cache_read_tokens),
+                    },
+                );
+                records.push(NativeUsageRecord {
+                    request_id: claude_request_key(&e),
+                    native_cost: e.native_cost,
+                    priced_cost,
+                    entry: UsageEntryWithEngine {
+                        engine: "claude".to_string(),
+                        timestamp: e.timestamp,
+                        model: e.model,
+                        input_tokens: e.input_tokens,
+                        output_tokens: e.output_tokens,
+                        cache_creation_tokens: e.cache_creation_tokens,
+                        cache_read_tokens: e.cache_read_tokens,
+                        cost: e.cost,
+                        session_id: e.session_id,
+                        project_path: e.project_path,
+                    },
+                });
+            }
+        }
+    }
+
+    // Codex / Gemini 只有会话级累计用量，以会话作为请求标识
+    let mut per_session = Vec::new();
+    if engines.iter().any(|e| e == "codex") {
+        per_session.extend(get_codex_usage_entries());
+    }
+    if engines.iter().any(|e| e == "gemini") {
+        per_session.extend(get_gemini_usage_entries());
+    }
+    for entry in per_session {
+        records.push(NativeUsageRecord {
+            request_id: session_request_key(&entry),
+            native_cost: None,
+            priced_cost: entry.cost,
+            entry,
+        });
+    }
+
+    records
+}
+
+/// Claude 条目的去重键：`message.id:requestId`，缺失时退回会话和时间戳
+fn claude_request_key(entry: &UsageEntry) -> String {
+    entry
+        .request_id
+        .clone()
+        .unwrap_or_else(|| format!("{}:{}", entry.session_id, entry.timestamp))
+}
+
+/// Codex / Gemini 只有会话级累计用量，以会话作为请求标识
+fn session_request_key(entry: &UsageEntryWithEngine) -> String {
+    format!("session:{}", entry.session_id)
+}
+
+/// Previously imported native records of an engine, keyed by request_id
+fn load_imported_usage(conn: &Connection, engine: &str) -> Result<Vec<(String, UsageEntryWithEngine)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT request_id, session_id, timestamp, model, input_tokens, output_tokens,
+                    cache_creation_tokens, cache_read_tokens, cost, project_path
+             FROM usage_entries
+             WHERE engine = ?1 AND source = 'native' AND request_id IS NOT NULL",
+        )
+        .map_err(|e| format!("查询已导入用量失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![engine], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                UsageEntryWithEngine {
+                    engine: engine.to_string(),
+                    session_id: row.get(1)?,
+                    timestamp: row.get(2)?,
+                    model: row.get(3)?,
+                    input_tokens: row.get::<_, i64>(4)? as u64,
+                    output_tokens: row.get::<_, i64>(5)? as u64,
+                    cache_creation_tokens: row.get::<_, i64>(6)? as u64,
+                    cache_read_tokens: row.get::<_, i64>(7)? as u64,
+                    cost: row.get(8)?,
+                    project_path: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
+                },
+            ))
+        })
+        .map_err(|e| format!("查询已导入用量失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取已导入用量失败: {}", e))
+}
+
+/// 读取已导入的记录；数据库不可用时统计只使用 CLI 文件
+fn imported_usage(engine: &str) -> Vec<(String, UsageEntryWithEngine)> {
+    match open_agent_db().and_then(|conn| load_imported_usage(&conn, engine)) {
+        Ok(imported) => imported,
+        Err(e) => {
+            log::warn!("[Usage] Failed to load imported {} usage: {}", engine, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Claude entries for the stats views: the CLI's files plus imported records no longer on disk
+fn claude_stats_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
+    let mut entries = get_all_usage_entries(claude_path);
+    let mut seen: HashSet<String> = entries.iter().map(claude_request_key).collect();
+    let before = entries.len();
+    for (request_id, e) in imported_usage("claude") {
+        if seen.insert(request_id.clone()) {
+            entries.push(UsageEntry {
+                timestamp: e.timestamp,
+                model: e.model,
+                input_tokens: e.input_tokens,
+                output_tokens: e.output_tokens,
+                cache_creation_tokens: e.cache_creation_tokens,
+                cache_read_tokens: e.cache_read_tokens,
+                cost: e.cost,
+                session_id: e.session_id,
+                project_path: e.project_path,
+                request_id: Some(request_id),
+                native_cost: None,
+            });
+        }
+    }
+    if entries.len() > before {
+        entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    }
+    entries
+}
+
+/// Codex / Gemini entries for the stats views plus imported sessions no longer on disk
+fn with_imported_usage(engine: &str, mut entries: Vec<UsageEntryWithEngine>) -> Vec<UsageEntryWithEngine> {
+    let mut seen: HashSet<String> = entries.iter().map(session_request_key).collect();
+    entries.extend(
+        imported_usage(engine)
+            .into_iter()
+            .filter(|(request_id, _)| seen.insert(request_id.clone()))
+            .map(|(_, entry)| entry),
+    );
+    entries
+}
+
+fn discrepancy(record: &NativeUsageRecord, kind: &str, tracked: f64, native: f64) -> UsageDiscrepancy {
+    UsageDiscrepancy {
+        engine: record.entry.engine.clone(),
+        request_id: record.request_id.clone(),
+        session_id: record.entry.session_id.clone(),
+        model: record.entry.model.clone(),
+        kind: kind.to_string(),
+        tracked,
+        native,
+    }
+}
+
+/// Merge native records into usage_entries and report discrepancies
+fn reconcile_usage(conn: &Connection, records: &[NativeUsageRecord]) -> Result<UsageReconciliation, String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    let mut report = UsageReconciliation::default();
+
+    for record in records {
+        let e = &record.entry;
+        let total_tokens = e.input_tokens + e.output_tokens + e.cache_creation_tokens + e.cache_read_tokens;
+        report.scanned += 1;
+
+        if let Some(native) = record.native_cost {
+            if costs_differ(record.priced_cost, native) {
+                report.discrepancies.push(discrepancy(record, "pricing", record.priced_cost, native));
+            }
+        }
+
+        // 先按 request_id 查找；找不到时匹配 AnyCode 自己记录、尚未关联 request_id 的同一条用量
+        let existing = tx
+            .query_row(
+                "SELECT id, source, total_tokens, cost FROM usage_entries
+                 WHERE engine = ?1 AND request_id = ?2",
+                params![e.engine, record.request_id],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?, row.get::<_, f64>(3)?)),
+            )
+            .optional()
+            .and_then(|found| match found {
+                Some(found) => Ok(Some(found)),
+                None => tx
+                    .query_row(
+                        "SELECT id, source, total_tokens, cost FROM usage_entries
+                         WHERE engine = ?1 AND request_id IS NULL AND session_id = ?2
+                           AND timestamp = ?3 AND model = ?4
+                         LIMIT 1",
+                        params![e.engine, e.session_id, e.timestamp, e.model],
+                        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                    )
+                    .optional(),
+            })
+            .map_err(|e| format!("查询用量记录失败: {}", e))?;
+
+        match existing {
+            None => {
+                tx.execute(
+                    "INSERT INTO usage_entries (
+                        session_id, timestamp, model, input_tokens, output_tokens,
+                        cache_creation_tokens, cache_read_tokens, total_tokens, cost,
+                        project_path, engine, request_id, source
+                     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, 'native')",
+                    params![
+                        e.session_id,
+                        e.timestamp,
+                        e.model,
+                        e.input_tokens as i64,
+                        e.output_tokens as i64,
+                        e.cache_creation_tokens as i64,
+                        e.cache_read_tokens as i64,
+                        total_tokens as i64,
+                        e.cost,
+                        e.project_path,
+                        e.engine,
+                        record.request_id,
+                    ],
+                )
+                .map_err(|e| format!("写入用量记录失败: {}", e))?;
+                report.imported += 1;
+            }
+            Some((id, source, tokens, cost)) if source == "native" => {
+                // 之前导入过：Codex/Gemini 的会话累计值会继续增长，直接刷新
+                if tokens != total_tokens as i64 || costs_differ(cost, e.cost) {
+                    tx.execute(
+                        "UPDATE usage_entries SET timestamp = ?2, input_tokens = ?3, output_tokens = ?4,
+                            cache_creation_tokens = ?5, cache_read_tokens = ?6, total_tokens = ?7, cost = ?8
+                         WHERE id = ?1",
+                        params![
+                            id,
+                            e.timestamp,
+                            e.input_tokens as i64,
+                            e.output_tokens as i64,
+                            e.cache_creation_tokens as i64,
+                            e.cache_read_tokens as i64,
+                            total_tokens as i64,
+                            e.cost,
+                        ],
+                    )
+                    .map_err(|e| format!("更新用量记录失败: {}", e))?;
+                    report.updated += 1;
+                } else {
+                    report.duplicates += 1;
+                }
+            }
+            Some((id, _, tokens, cost)) => {
+                // AnyCode 自己的记录保持不变，只关联 request_id 并标记差异
+                tx.execute(
+                    "UPDATE usage_entries SET request_id = ?2 WHERE id = ?1",
+                    params![id, record.request_id],
+                )
+                .map_err(|e| format!("更新用量记录失败: {}", e))?;
+                if tokens != total_tokens as i64 {
+                    report.discrepancies.push(discrepancy(record, "tokens", tokens as f64, total_tokens as f64));
+                }
+                if costs_differ(cost, e.cost) {
+                    report.discrepancies.push(discrepancy(record, "cost", cost, e.cost));
+                }
+                report.duplicates += 1;
+            }
+        }
+    }
+
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))?;
+    Ok(report)
+}
+
+/// 导入各 CLI 的原生用量数据并与 AnyCode 记录对账
+#[command]
+pub async fn import_native_usage(engines: Option<Vec<String>>) -> Result<UsageReconciliation, String> {
+    async_runtime::spawn_blocking(move || {
+        let engines = engines.unwrap_or_else(|| {
+            vec!["claude".to_string(), "codex".to_string(), "gemini".to_string()]
+        });
+        let records = collect_native_usage(&engines);
+        let conn = open_agent_db()?;
+        let report = reconcile_usage(&conn, &records)?;
+        log::info!(
+            "[Usage Import] scanned={} imported={} updated={} duplicates={} discrepancies={}",
+            report.scanned,
+            report.imported,
+            report.updated,
+            report.duplicates,
+            report.discrepancies.len()
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("导入原生用量失败: {}", e))?
+}
+
 // ============================================================================
 // Codex Rate Limits API
 // ============================================================================
@@ -1697,3 +2090,63 @@ pub fn get_codex_rate_limits() -> Result<CodexRateLimits, String> {
         credits,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(request_id: &str, session_id: &str, input_tokens: u64, cost: f64) -> NativeUsageRecord {
+        NativeUsageRecord {
+            request_id: request_id.to_string(),
+            entry: UsageEntryWithEngine {
+                engine: "claude".to_string(),
+                timestamp: "2025-12-01T10:00:00Z".to_string(),
+                model: "claude-sonnet-4-5".to_string(),
+                input_tokens,
+                output_tokens: 100,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+                cost,
+                session_id: session_id.to_string(),
+                project_path: "/tmp/project".to_string(),
+            },
+            native_cost: Some(cost),
+            priced_cost: cost,
+        }
+    }
+
+    #[test]
+    fn imports_dedupes_and_flags_discrepancies() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::commands::db_migrations::run_migrations(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO usage_entries (session_id, timestamp, model, input_tokens, output_tokens, total_tokens, cost)
+             VALUES ('s1', '2025-12-01T10:00:00Z', 'claude-sonnet-4-5', 900, 100, 1000, 0.5)",
+            [],
+        )
+        .unwrap();
+
+        let mut pricing_off = record("m2:r2", "s2", 10, 0.2);
+        pricing_off.priced_cost = 0.1;
+        let records = vec![record("m1:r1", "s1", 1000, 0.5), pricing_off];
+
+        let report = reconcile_usage(&conn, &records).unwrap();
+        assert_eq!((report.scanned, report.imported, report.duplicates), (2, 1, 1));
+        let kinds: Vec<_> = report.discrepancies.iter().map(|d| d.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["tokens", "pricing"]);
+        assert_eq!(report.discrepancies[0].tracked, 1000.0);
+
+        // 再次导入：已关联 request_id 的条目不会重复写入
+        let again = reconcile_usage(&conn, &records).unwrap();
+        assert_eq!((again.imported, again.updated, again.duplicates), (0, 0, 2));
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM usage_entries", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 2);
+
+        // 统计视图读取的是导入的原生记录（AnyCode 自己的记录已由 CLI 文件覆盖）
+        let imported = load_imported_usage(&conn, "claude").unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].0, "m2:r2");
+        assert_eq!(imported[0].1.session_id, "s2");
+        assert!(load_imported_usage(&conn, "codex").unwrap().is_empty());
+    }
+}
@@ -3,10 +3,21 @@
  *
  * Provides commands for creating and managing independent session windows.
  * Supports detaching tabs into separate windows and cross-window communication.
+ *
+ * Each session window's bound project, engine, size/position and whether it was
+ * still open at exit are persisted to ~/.anycode/windows.json, so windows can be
+ * reopened on the next startup.
  */
 
-use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use super::data_root::anycode_dir;
+
+/// Set once the main window starts closing, so session windows closed as part of
+/// app exit are remembered as "reopen on startup"
+static APP_EXITING: AtomicBool = AtomicBool::new(false);
 
 /// Parameters for creating a new session window
 #[derive(Debug, Deserialize)]
@@ -23,6 +34,37 @@ pub struct CreateSessionWindowParams {
     pub engine: Option<String>,
 }
 
+/// Persisted layout and project binding of a session window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionWindowState {
+    pub window_label: String,
+    pub tab_id: String,
+    pub session_id: Option<String>,
+    pub project_path: Option<String>,
+    /// Execution engine tab: 'claude' | 'codex' | 'gemini'
+    pub engine: Option<String>,
+    pub title: String,
+    /// Logical position / size of the window
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub maximized: bool,
+    /// Whether the window was open when the app exited
+    pub reopen: bool,
+}
+
+/// Parameters for saving a session window's state (omitted fields are kept)
+#[derive(Debug, Deserialize)]
+pub struct SaveWindowStateParams {
+    pub window_label: String,
+    pub session_id: Option<String>,
+    pub project_path: Option<String>,
+    pub engine: Option<String>,
+    pub title: Option<String>,
+}
+
 /// Result of window creation
 #[derive(Debug, Serialize)]
 pub struct WindowCreationResult {
@@ -49,69 +91,99 @@ pub async fn create_session_window(
     let window_label = format!("session-window-{}", params.tab_id);
 
     // Check if window already exists
-    if app.get_webview_window(&window_label).is_some() {
+    if let Some(window) = app.get_webview_window(&window_label) {
         // Focus existing window instead of creating a new one
-        if let Some(window) = app.get_webview_window(&window_label) {
-            window.set_focus().map_err(|e| format!("Failed to focus window: {}", e))?;
-        }
+        window.set_focus().map_err(|e| format!("Failed to focus window: {}", e))?;
         return Ok(WindowCreationResult {
             window_label,
             success: true,
         });
     }
 
-    // Build URL with query parameters
-    let mut url = String::from("/");
+    let state = SessionWindowState {
+        window_label: window_label.clone(),
+        tab_id: params.tab_id,
+        session_id: params.session_id,
+        project_path: params.project_path,
+        engine: params.engine,
+        title: params.title,
+        reopen: true,
+        ..Default::default()
+    };
+    open_session_window(&app, state)?;
+
+    Ok(WindowCreationResult {
+        window_label,
+        success: true,
+    })
+}
+
+/// Builds the frontend URL for a session window
+fn session_window_url(state: &SessionWindowState) -> String {
     let mut query_parts: Vec<String> = vec![
-        format!("window=session"),
-        format!("tab_id={}", params.tab_id),
+        "window=session".to_string(),
+        format!("tab_id={}", state.tab_id),
     ];
 
-    if let Some(ref session_id) = params.session_id {
+    if let Some(ref session_id) = state.session_id {
         query_parts.push(format!("session_id={}", session_id));
     }
 
-    if let Some(ref project_path) = params.project_path {
+    if let Some(ref project_path) = state.project_path {
         // URL encode the project path
         let encoded_path = urlencoding::encode(project_path);
         query_parts.push(format!("project_path={}", encoded_path));
     }
 
-    if let Some(ref engine) = params.engine {
+    if let Some(ref engine) = state.engine {
         query_parts.push(format!("engine={}", engine));
     }
 
-    url = format!("{}?{}", url, query_parts.join("&"));
+    format!("/?{}", query_parts.join("&"))
+}
 
-    log::info!("[Window] Creating session window: {} with URL: {}", window_label, url);
+/// Creates a session window from its (saved) state and records it as open
+fn open_session_window(app: &AppHandle, mut state: SessionWindowState) -> Result<WebviewWindow, String> {
+    let url = session_window_url(&state);
+    log::info!("[Window] Creating session window: {} with URL: {}", state.window_label, url);
 
     // Create new window (frameless with custom title bar)
-    let window = WebviewWindowBuilder::new(
-        &app,
-        &window_label,
+    let mut builder = WebviewWindowBuilder::new(
+        app,
+        &state.window_label,
         WebviewUrl::App(url.into()),
     )
-    .title(&params.title)
-    .inner_size(1000.0, 700.0)
+    .title(&state.title)
+    .inner_size(state.width.unwrap_or(1000.0), state.height.unwrap_or(700.0))
     .min_inner_size(600.0, 400.0)
     .resizable(true)
     .maximizable(true)
     .minimizable(true)
     .visible(true)
-    .decorations(false)  // Disable system title bar, use custom title bar in frontend
-    .center()
-    .build()
-    .map_err(|e| format!("Failed to create window: {}", e))?;
+    .decorations(false);  // Disable system title bar, use custom title bar in frontend
+
+    builder = match (state.x, state.y) {
+        (Some(x), Some(y)) => builder.position(x, y),
+        _ => builder.center(),
+    };
+
+    let window = builder
+        .build()
+        .map_err(|e| format!("Failed to create window: {}", e))?;
+
+    if state.maximized {
+        let _ = window.maximize();
+    }
 
     // Focus the new window
     window.set_focus().map_err(|e| format!("Failed to focus new window: {}", e))?;
 
-    log::info!("[Window] Session window created successfully: {}", window_label);
+    log::info!("[Window] Session window created successfully: {}", state.window_label);
 
-    Ok(WindowCreationResult {
-        window_label,
-        success: true,
-    })
+    state.reopen = true;
+    update_window_states(|states| upsert_window_state(states, state))?;
+
+    Ok(window)
 }
 
 /// Closes an independent session window
@@ -232,3 +304,328 @@ pub async fn broadcast_to_session_windows(
 
     Ok(count)
 }
+
+// ============================================================================
+// Window state persistence
+// ============================================================================
+
+fn window_states_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("windows.json"))
+}
+
+/// Loads all saved session window states
+pub fn load_window_states() -> Vec<SessionWindowState> {
+    window_states_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_window_states(states: &[SessionWindowState]) -> Result<(), String> {
+    let path = window_states_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(states)
+        .map_err(|e| format!("Failed to serialize window states: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write windows.json: {}", e))
+}
+
+fn update_window_states<F>(f: F) -> Result<(), String>
+where
+    F: FnOnce(&mut Vec<SessionWindowState>),
+{
+    let mut states = load_window_states();
+    f(&mut states);
+    prune_window_states(&mut states);
+    save_window_states(&states)
+}
+
+/// Replaces the state with the same label and moves it to the end (most recent)
+fn upsert_window_state(states: &mut Vec<SessionWindowState>, state: SessionWindowState) {
+    states.retain(|s| s.window_label != state.window_label);
+    states.push(state);
+}
+
+/// Keeps every window that should be reopened, plus only the most recently closed
+/// window of each project (its layout is reused by `open_project_window`)
+fn prune_window_states(states: &mut Vec<SessionWindowState>) {
+    let mut seen_projects: Vec<String> = Vec::new();
+    let mut kept: Vec<SessionWindowState> = Vec::new();
+    for state in states.drain(..).rev() {
+        let project = state.project_path.as_deref().map(normalize_project_path);
+        if state.reopen {
+            if let Some(project) = project {
+                seen_projects.push(project);
+            }
+            kept.push(state);
+        } else if let Some(project) = project {
+            if !seen_projects.contains(&project) {
+                seen_projects.push(project);
+                kept.push(state);
+            }
+        }
+    }
+    kept.reverse();
+    *states = kept;
+}
+
+fn normalize_project_path(path: &str) -> String {
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    if trimmed.is_empty() { path.to_string() } else { trimmed.to_string() }
+}
+
+/// Reads the current logical position / size of a window into its state
+fn capture_geometry(window: &WebviewWindow, state: &mut SessionWindowState) {
+    let scale = window.scale_factor().unwrap_or(1.0);
+    state.maximized = window.is_maximized().unwrap_or(false);
+    // Keep the restored (non-maximized) geometry
+    if state.maximized {
+        return;
+    }
+    if let Ok(position) = window.outer_position() {
+        let position = position.to_logical::<f64>(scale);
+        state.x = Some(position.x);
+        state.y = Some(position.y);
+    }
+    if let Ok(size) = window.inner_size() {
+        let size = size.to_logical::<f64>(scale);
+        state.width = Some(size.width);
+        state.height = Some(size.height);
+    }
+}
+
+/// Records the final geometry of a closing window.
+///
+/// Called from the app's `CloseRequested` handler. Session windows closed by the
+/// user are not reopened on startup; those closed because the app is exiting are.
+pub fn handle_window_close_requested(app: &AppHandle, window_label: &str) {
+    if window_label == "main" {
+        APP_EXITING.store(true, Ordering::SeqCst);
+    }
+    let reopen = APP_EXITING.load(Ordering::SeqCst);
+
+    let windows: Vec<WebviewWindow> = if window_label == "main" {
+        app.webview_windows()
+            .into_iter()
+            .filter(|(label, _)| label.starts_with("session-window-"))
+            .map(|(_, window)| window)
+            .collect()
+    } else if window_label.starts_with("session-window-") {
+        app.get_webview_window(window_label).into_iter().collect()
+    } else {
+        return;
+    };
+    if windows.is_empty() {
+        return;
+    }
+
+    let result = update_window_states(|states| {
+        for window in windows {
+            let mut state = states
+                .iter()
+                .find(|s| s.window_label == window.label())
+                .cloned()
+                .unwrap_or_else(|| SessionWindowState {
+                    window_label: window.label().to_string(),
+                    tab_id: window.label().trim_start_matches("session-window-").to_string(),
+                    title: window.title().unwrap_or_default(),
+                    ..Default::default()
+                });
+            capture_geometry(&window, &mut state);
+            state.reopen = reopen;
+            upsert_window_state(states, state);
+        }
+    });
+    if let Err(e) = result {
+        log::warn!("[Window] Failed to save window state: {}", e);
+    }
+}
+
+/// Saves a session window's layout and binding (project, session, engine tab)
+///
+/// # Arguments
+/// * `app` - The Tauri app handle
+/// * `params` - The window label and any binding fields that changed
+///
+/// # Returns
+/// * `Result<SessionWindowState, String>` - The saved state
+#[tauri::command]
+pub async fn save_window_state(
+    app: AppHandle,
+    params: SaveWindowStateParams,
+) -> Result<SessionWindowState, String> {
+    let window = app
+        .get_webview_window(&params.window_label)
+        .ok_or_else(|| format!("Window not found: {}", params.window_label))?;
+
+    let mut state = load_window_states()
+        .into_iter()
+        .find(|s| s.window_label == params.window_label)
+        .unwrap_or_else(|| SessionWindowState {
+            window_label: params.window_label.clone(),
+            tab_id: params.window_label.trim_start_matches("session-window-").to_string(),
+            ..Default::default()
+        });
+    if params.session_id.is_some() {
+        state.session_id = params.session_id;
+    }
+    if params.project_path.is_some() {
+        state.project_path = params.project_path;
+    }
+    if params.engine.is_some() {
+        state.engine = params.engine;
+    }
+    if let Some(title) = params.title {
+        state.title = title;
+    }
+    capture_geometry(&window, &mut state);
+    state.reopen = true;
+
+    let saved = state.clone();
+    update_window_states(|states| upsert_window_state(states, state))?;
+    Ok(saved)
+}
+
+/// Reopens the session windows that were still open when the app last exited
+///
+/// Windows that are already open are skipped, so calling this more than once is safe.
+///
+/// # Returns
+/// * `Result<Vec<WindowCreationResult>, String>` - The restored windows
+#[tauri::command]
+pub async fn restore_windows_on_startup(
+    app: AppHandle,
+) -> Result<Vec<WindowCreationResult>, String> {
+    APP_EXITING.store(false, Ordering::SeqCst);
+
+    let mut restored = Vec::new();
+    for state in load_window_states().into_iter().filter(|s| s.reopen) {
+        if app.get_webview_window(&state.window_label).is_some() {
+            continue;
+        }
+        let window_label = state.window_label.clone();
+        match open_session_window(&app, state) {
+            Ok(_) => restored.push(WindowCreationResult {
+                window_label,
+                success: true,
+            }),
+            Err(e) => log::warn!("[Window] Failed to restore {}: {}", window_label, e),
+        }
+    }
+
+    log::info!("[Window] Restored {} session window(s)", restored.len());
+    Ok(restored)
+}
+
+/// Opens a window bound to a project, reusing an existing one if present
+///
+/// # Arguments
+/// * `app` - The Tauri app handle
+/// * `project_path` - The project to bind the window to
+/// * `engine` - Optional engine tab for a newly created window
+///
+/// # Returns
+/// * `Result<WindowCreationResult, String>` - The label of the focused or created window
+#[tauri::command]
+pub async fn open_project_window(
+    app: AppHandle,
+    project_path: String,
+    engine: Option<String>,
+) -> Result<WindowCreationResult, String> {
+    let project = normalize_project_path(&project_path);
+    let states = load_window_states();
+    let matches_project = |s: &&SessionWindowState| {
+        s.project_path.as_deref().map(normalize_project_path).as_deref() == Some(project.as_str())
+    };
+
+    // Reuse an open window bound to the project
+    if let Some(state) = states
+        .iter()
+        .filter(matches_project)
+        .find(|s| app.get_webview_window(&s.window_label).is_some())
+    {
+        if let Some(window) = app.get_webview_window(&state.window_label) {
+            let _ = window.unminimize();
+            window.set_focus().map_err(|e| format!("Failed to focus window: {}", e))?;
+        }
+        return Ok(WindowCreationResult {
+            window_label: state.window_label.clone(),
+            success: true,
+        });
+    }
+
+    // Otherwise reopen with the project's last layout, or create a fresh window
+    let state = match states.iter().rev().find(matches_project).cloned() {
+        Some(mut state) => {
+            if engine.is_some() {
+                state.engine = engine;
+            }
+            state
+        }
+        None => {
+            let tab_id = format!("project-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+            let title = std::path::Path::new(&project)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| project.clone());
+            SessionWindowState {
+                window_label: format!("session-window-{}", tab_id),
+                tab_id,
+                project_path: Some(project_path),
+                engine,
+                title,
+                ..Default::default()
+            }
+        }
+    };
+
+    let window_label = state.window_label.clone();
+    open_session_window(&app, state)?;
+    Ok(WindowCreationResult {
+        window_label,
+        success: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(label: &str, project: Option<&str>, reopen: bool) -> SessionWindowState {
+        SessionWindowState {
+            window_label: label.to_string(),
+            project_path: project.map(str::to_string),
+            reopen,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn prune_keeps_open_windows_and_latest_closed_window_per_project() {
+        let mut states = vec![
+            state("a", Some("/p"), false),
+            state("b", Some("/q/"), false),
+            state("c", None, false),
+            state("d", None, true),
+            state("e", Some("/q"), false),
+        ];
+        upsert_window_state(&mut states, state("a", Some("/p"), true));
+        prune_window_states(&mut states);
+
+        let labels: Vec<&str> = states.iter().map(|s| s.window_label.as_str()).collect();
+        assert_eq!(labels, vec!["d", "e", "a"]);
+    }
+
+    #[test]
+    fn session_window_url_encodes_project_path() {
+        let url = session_window_url(&SessionWindowState {
+            tab_id: "t1".into(),
+            project_path: Some("/my project".into()),
+            engine: Some("codex".into()),
+            ..Default::default()
+        });
+        assert_eq!(url, "/?window=session&tab_id=t1&project_path=%2Fmy%20project&engine=codex");
+    }
+}
@@ -7,6 +7,13 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::OnceLock;
 
+use super::ssh_remote::RemoteConfig;
+
+#[cfg(target_os = "windows")]
+use std::collections::HashMap;
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+
 #[cfg(target_os = "windows")]
 use std::process::Command;
 
@@ -35,6 +42,8 @@ pub enum CodexMode {
     Native,
     /// 强制使用 WSL Codex
     Wsl,
+    /// 通过 SSH 在远程主机上执行 Codex
+    Remote,
 }
 
 /// Codex 配置结构
@@ -46,6 +55,9 @@ pub struct CodexConfig {
     pub mode: CodexMode,
     /// WSL 发行版名称（可选，留空则使用默认）
     pub wsl_distro: Option<String>,
+    /// 远程主机配置（Remote 模式使用）
+    #[serde(default)]
+    pub remote: Option<RemoteConfig>,
 }
 
 impl Default for CodexConfig {
@@ -53,7 +65,18 @@ impl Default for CodexConfig {
         Self {
             mode: CodexMode::Auto,
             wsl_distro: None,
+            remote: None,
+        }
+    }
+}
+
+impl CodexConfig {
+    /// Remote 模式且已配置主机时返回远程配置
+    pub fn active_remote(&self) -> Option<&RemoteConfig> {
+        if self.mode != CodexMode::Remote {
+            return None;
         }
+        self.remote.as_ref().filter(|r| !r.host.trim().is_empty())
     }
 }
 
@@ -153,9 +176,9 @@ impl WslConfig {
         info!("[WSL] Detecting Codex configuration (mode: {:?})...", codex_config.mode);
 
         match codex_config.mode {
-            CodexMode::Native => {
-                // 强制原生模式，不启用 WSL
-                info!("[WSL] Mode set to Native, WSL disabled");
+            CodexMode::Native | CodexMode::Remote => {
+                // 原生 / 远程模式，不启用 WSL
+                info!("[WSL] Mode set to {:?}, WSL disabled", codex_config.mode);
                 return Self::default();
             }
             CodexMode::Wsl => {
@@ -262,6 +285,68 @@ pub fn get_wsl_config() -> &'static WslConfig {
     })
 }
 
+/// 按发行版缓存的 WSL 配置（项目级发行版使用）
+#[cfg(target_os = "windows")]
+static DISTRO_WSL_CONFIGS: OnceLock<Mutex<HashMap<String, WslConfig>>> = OnceLock::new();
+
+/// 获取指定发行版的 WSL 配置（带缓存）
+#[cfg(target_os = "windows")]
+fn get_wsl_config_for_distro(distro: &str) -> WslConfig {
+    let cache = DISTRO_WSL_CONFIGS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(config) = cache.lock().unwrap().get(distro) {
+        return config.clone();
+    }
+
+    let config = WslConfig::detect_wsl_config(Some(distro));
+    cache
+        .lock()
+        .unwrap()
+        .insert(distro.to_string(), config.clone());
+    config
+}
+
+#[cfg(not(target_os = "windows"))]
+fn get_wsl_config_for_distro(_distro: &str) -> WslConfig {
+    WslConfig::default()
+}
+
+/// 获取项目使用的 WSL 配置
+///
+/// 项目设置中指定了发行版时，该项目固定在此发行版中执行（不受全局模式影响）；
+/// 否则使用全局 WSL 配置。
+pub fn get_wsl_config_for_project(project_path: &str) -> WslConfig {
+    let settings = crate::commands::project_settings::load_project_settings(project_path);
+    match settings.wsl_distro.as_deref().filter(|d| !d.is_empty()) {
+        Some(distro) => {
+            let config = get_wsl_config_for_distro(distro);
+            log::debug!(
+                "[WSL] Project {} uses distro {} (enabled={})",
+                project_path,
+                distro,
+                config.enabled
+            );
+            config
+        }
+        None => get_wsl_config().clone(),
+    }
+}
+
+/// 所有项目级发行版的 WSL 配置（用于跨发行版查找会话文件）
+pub fn get_project_wsl_configs() -> Vec<WslConfig> {
+    let mut distros: Vec<String> = crate::commands::project_settings::load_all_project_settings()
+        .into_iter()
+        .filter_map(|s| s.wsl_distro)
+        .filter(|d| !d.is_empty())
+        .collect();
+    distros.sort();
+    distros.dedup();
+    distros
+        .iter()
+        .map(|d| get_wsl_config_for_distro(d))
+        .filter(|c| c.enabled)
+        .collect()
+}
+
 /// 重置 WSL 配置缓存（用于测试或重新检测）
 #[allow(dead_code)]
 pub fn reset_wsl_config() {
@@ -0,0 +1,172 @@
+//! 统一的命令错误类型
+//!
+//! Tauri 命令以 `{code, title, message, details}` 的固定结构返回错误，前端可按 `code` 分支处理
+//! （例如引擎未安装时引导安装、WSL 错误时提示检查发行版）；`title` 是按当前语言渲染的类别标题。
+//!
+//! 内部函数大多仍返回 `Result<T, String>`，命令在出错的位置显式构造对应变体
+//! （如 `.map_err(AnyCodeError::Io)?`）；不提供 `From<String>`，避免按错误信息猜测类别。
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// 命令错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnyCodeError {
+    /// 文件读写、目录操作等
+    Io(String),
+    /// 配置文件缺失或内容错误
+    Config(String),
+    /// 引擎 CLI 未安装（找不到可执行文件）、启动失败或异常退出
+    Engine(String),
+    Git(String),
+    Wsl(String),
+    /// 代理商 / API 配置
+    Provider(String),
+    /// 参数校验失败
+    Validation(String),
+    NotFound(String),
+    Database(String),
+    Network(String),
+    /// 无法归类的错误
+    Internal(String),
+    /// 附带结构化详情的错误
+    Detailed {
+        error: Box<AnyCodeError>,
+        details: Value,
+    },
+}
+
+impl AnyCodeError {
+    /// 稳定的错误码（前端据此分支）
+    pub fn code(&self) -> &'static str {
+        match self {
+            AnyCodeError::Io(_) => "io",
+            AnyCodeError::Config(_) => "config",
+            AnyCodeError::Engine(_) => "engine",
+            AnyCodeError::Git(_) => "git",
+            AnyCodeError::Wsl(_) => "wsl",
+            AnyCodeError::Provider(_) => "provider",
+            AnyCodeError::Validation(_) => "validation",
+            AnyCodeError::NotFound(_) => "not_found",
+            AnyCodeError::Database(_) => "database",
+            AnyCodeError::Network(_) => "network",
+            AnyCodeError::Internal(_) => "internal",
+            AnyCodeError::Detailed { error, .. } => error.code(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AnyCodeError::Io(m)
+            | AnyCodeError::Config(m)
+            | AnyCodeError::Engine(m)
+            | AnyCodeError::Git(m)
+            | AnyCodeError::Wsl(m)
+            | AnyCodeError::Provider(m)
+            | AnyCodeError::Validation(m)
+            | AnyCodeError::NotFound(m)
+            | AnyCodeError::Database(m)
+            | AnyCodeError::Network(m)
+            | AnyCodeError::Internal(m) => m,
+            AnyCodeError::Detailed { error, .. } => error.message(),
+        }
+    }
+
+    /// 本地化的类别标题（消息目录中的 `error.{code}`）
+    pub fn title(&self) -> String {
+        crate::i18n::translate(&format!("error.{}", self.code()), &[])
+    }
+
+    pub fn details(&self) -> Option<&Value> {
+        match self {
+            AnyCodeError::Detailed { details, .. } => Some(details),
+            _ => None,
+        }
+    }
+
+    /// 附加结构化详情（如退出码、文件路径）
+    pub fn with_details(self, details: Value) -> Self {
+        let error = match self {
+            AnyCodeError::Detailed { error, .. } => error,
+            other => Box::new(other),
+        };
+        AnyCodeError::Detailed { error, details }
+    }
+}
+
+impl fmt::Display for AnyCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AnyCodeError {}
+
+impl Serialize for AnyCodeError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AnyCodeError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("title", &self.title())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+
+impl From<std::io::Error> for AnyCodeError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => AnyCodeError::NotFound(error.to_string()),
+            _ => AnyCodeError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AnyCodeError {
+    fn from(error: serde_json::Error) -> Self {
+        AnyCodeError::Validation(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AnyCodeError {
+    fn from(error: rusqlite::Error) -> Self {
+        AnyCodeError::Database(error.to_string())
+    }
+}
+
+/// 让仍返回 `Result<T, String>` 的调用方可以直接 `?` 命令函数的结果
+impl From<AnyCodeError> for String {
+    fn from(error: AnyCodeError) -> Self {
+        error.message().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_with_a_stable_shape() {
+        let error = AnyCodeError::Engine("codex 未安装".into());
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], "engine");
+        assert_eq!(value["message"], "codex 未安装");
+        assert_eq!(value["details"], Value::Null);
+        assert_eq!(value["title"], error.title());
+
+        let detailed = AnyCodeError::Git("merge conflict".into()).with_details(json!({ "files": ["a.rs"] }));
+        assert_eq!(detailed.code(), "git");
+        assert_eq!(
+            serde_json::to_value(&detailed).unwrap()["details"],
+            json!({ "files": ["a.rs"] })
+        );
+
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        assert_eq!(AnyCodeError::from(missing).code(), "not_found");
+        assert_eq!(String::from(error), "codex 未安装");
+    }
+}
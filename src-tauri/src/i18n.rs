@@ -0,0 +1,187 @@
+//! 后端消息本地化
+//!
+//! 面向用户的命令结果和错误信息按消息 ID 登记在 `CATALOG` 中（zh-CN / en-US），
+//! 通过 `tr!("engine.unsupported", engine = name)` 按当前语言渲染，参数以 `{name}` 占位。
+//!
+//! 当前语言由前端在切换界面语言时同步（`set_backend_locale`），保存在 ~/.anycode/locale.json，
+//! 默认与前端一致为 zh-CN。未登记的 ID 原样返回。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use crate::commands::data_root::anycode_dir;
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    /// 解析语言标签（"zh" / "zh-CN" / "en" / "en-US" 等，只看主语言）
+    pub fn parse(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        match primary.as_str() {
+            "zh" => Some(Locale::ZhCn),
+            "en" => Some(Locale::EnUs),
+            _ => None,
+        }
+    }
+}
+
+/// 消息目录：(ID, zh-CN, en-US)
+const CATALOG: &[(&str, &str, &str)] = &[
+    ("common.home_dir_unavailable", "无法获取用户主目录", "Could not find home directory"),
+    ("engine.unsupported", "不支持的引擎: {engine}", "Unsupported engine: {engine}"),
+    ("project.path_not_found", "项目路径不存在: {path}", "Project path does not exist: {path}"),
+    ("provider.not_found", "未找到 ID 为 '{id}' 的代理商", "Provider with ID '{id}' not found"),
+    ("prompt_template.not_found", "提示词模板不存在: {id}", "Prompt template not found: {id}"),
+    ("config.written", "✅ 已写入 {path}", "✅ Written to {path}"),
+    ("config.written_both", "✅ 已写入 {first} 和 {second}", "✅ Written to {first} and {second}"),
+    // 命令错误的类别标题（`error.{code}`，见 AnyCodeError::title）
+    ("error.io", "文件操作失败", "File operation failed"),
+    ("error.config", "配置错误", "Configuration error"),
+    ("error.engine", "引擎执行失败", "Engine error"),
+    ("error.git", "Git 操作失败", "Git operation failed"),
+    ("error.wsl", "WSL 错误", "WSL error"),
+    ("error.provider", "代理商配置错误", "Provider error"),
+    ("error.validation", "参数无效", "Invalid input"),
+    ("error.not_found", "未找到", "Not found"),
+    ("error.database", "数据库错误", "Database error"),
+    ("error.network", "网络错误", "Network error"),
+    ("error.internal", "内部错误", "Internal error"),
+];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LocaleSettings {
+    locale: Locale,
+}
+
+static CURRENT: Lazy<RwLock<Locale>> = Lazy::new(|| RwLock::new(load_locale()));
+
+fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(anycode_dir()?.join("locale.json"))
+}
+
+fn load_locale() -> Locale {
+    get_settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<LocaleSettings>(&content).ok())
+        .map(|settings| settings.locale)
+        .unwrap_or_default()
+}
+
+pub fn current_locale() -> Locale {
+    *CURRENT.read().unwrap()
+}
+
+/// 按指定语言渲染消息
+pub fn translate_in(locale: Locale, id: &str, params: &[(&str, String)]) -> String {
+    let Some(&(_, zh, en)) = CATALOG.iter().find(|(key, _, _)| *key == id) else {
+        return id.to_string();
+    };
+    let template = match locale {
+        Locale::ZhCn => zh,
+        Locale::EnUs => en,
+    };
+    params.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// 按当前语言渲染消息（通常通过 `tr!` 调用）
+pub fn translate(id: &str, params: &[(&str, String)]) -> String {
+    translate_in(current_locale(), id, params)
+}
+
+/// `tr!("id")` / `tr!("id", name = value, ...)`，参数需实现 Display
+#[macro_export]
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::i18n::translate($id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate($id, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+#[tauri::command]
+pub async fn get_backend_locale() -> Result<Locale, String> {
+    Ok(current_locale())
+}
+
+/// 设置后端消息语言（接受 "zh" / "zh-CN" / "en" / "en-US"）
+#[tauri::command]
+pub async fn set_backend_locale(locale: String) -> Result<Locale, String> {
+    let parsed = Locale::parse(&locale).ok_or_else(|| format!("不支持的语言: {}", locale))?;
+    if parsed == current_locale() {
+        return Ok(parsed);
+    }
+
+    let path = get_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&LocaleSettings { locale: parsed })
+        .map_err(|e| format!("Failed to serialize locale settings: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write locale.json: {}", e))?;
+
+    *CURRENT.write().unwrap() = parsed;
+    log::info!("[I18n] Backend locale set to {:?}", parsed);
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_catalog_messages_per_locale() {
+        let params = [("engine", "cursor".to_string())];
+        assert_eq!(translate_in(Locale::ZhCn, "engine.unsupported", &params), "不支持的引擎: cursor");
+        assert_eq!(translate_in(Locale::EnUs, "engine.unsupported", &params), "Unsupported engine: cursor");
+        assert_eq!(translate_in(Locale::EnUs, "missing.id", &[]), "missing.id");
+
+        assert_eq!(Locale::parse("en"), Some(Locale::EnUs));
+        assert_eq!(Locale::parse("zh_TW"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("fr-FR"), None);
+        assert_eq!(serde_json::to_string(&Locale::EnUs).unwrap(), "\"en-US\"");
+
+        // 目录中的 ID 不重复，且两种语言的占位符一致
+        for (i, (id, zh, en)) in CATALOG.iter().enumerate() {
+            assert!(CATALOG[i + 1..].iter().all(|(other, _, _)| other != id), "duplicate id {}", id);
+            let placeholders = |s: &str| s.matches('{').count();
+            assert_eq!(placeholders(zh), placeholders(en), "{}", id);
+        }
+    }
+
+    #[test]
+    fn every_error_code_has_a_title() {
+        use crate::error::AnyCodeError;
+        let errors = [
+            AnyCodeError::Io(String::new()),
+            AnyCodeError::Config(String::new()),
+            AnyCodeError::Engine(String::new()),
+            AnyCodeError::Git(String::new()),
+            AnyCodeError::Wsl(String::new()),
+            AnyCodeError::Provider(String::new()),
+            AnyCodeError::Validation(String::new()),
+            AnyCodeError::NotFound(String::new()),
+            AnyCodeError::Database(String::new()),
+            AnyCodeError::Network(String::new()),
+            AnyCodeError::Internal(String::new()),
+        ];
+        for error in errors {
+            let id = format!("error.{}", error.code());
+            assert!(CATALOG.iter().any(|(key, _, _)| *key == id), "missing {}", id);
+        }
+        assert_eq!(translate_in(Locale::EnUs, "error.not_found", &[]), "Not found");
+    }
+}
@@ -3,16 +3,19 @@
 
 mod claude_binary;
 mod commands;
+mod error;
+mod i18n;
 mod process;
 
 use claude_binary::init_shell_environment;
 
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 use commands::acemcp::{
     enhance_prompt_with_context, test_acemcp_availability,
     save_acemcp_config, load_acemcp_config, preindex_project,
-    export_acemcp_sidecar, get_extracted_sidecar_path
+    export_acemcp_sidecar, get_extracted_sidecar_path,
+    acemcp_list_tool_traces, acemcp_replay_tool_trace
 };
 use commands::claude::{
     cancel_claude_execution, check_claude_version, clear_custom_claude_path, continue_claude_code,
@@ -26,11 +29,18 @@ use commands::claude::{
     save_claude_md_file, save_claude_settings, save_codex_system_prompt, save_system_prompt, search_files,
     set_custom_claude_path, update_claude_execution_config, update_claude_permission_config,
     update_hooks_config, update_thinking_mode, validate_hook_command, validate_permission_config,
+    list_claude_hooks, validate_hooks_config, save_claude_hook, remove_claude_hook, test_hook,
     // Multi-prompt management
     list_codex_prompts, get_codex_prompt, save_codex_prompt, rename_codex_prompt, delete_codex_prompt,
     activate_codex_prompt, deactivate_codex_prompt, get_active_codex_prompt_id,
     // Project-level AGENTS.md management
     check_project_agents_md, activate_codex_prompt_to_project, deactivate_codex_prompt_from_project,
+    // Claude sub-agents (~/.claude/agents)
+    list_claude_agents, validate_claude_agent, save_claude_agent, delete_claude_agent,
+    activate_claude_agent_to_project, deactivate_claude_agent_from_project,
+    // CLAUDE.md template library
+    list_claude_md_templates, get_claude_md_template, save_claude_md_template, delete_claude_md_template,
+    check_project_claude_md, activate_claude_md_template_to_project, deactivate_claude_md_template_from_project,
     // settings.json file switching (AnyCode)
     read_claude_settings_json_text, write_claude_settings_json_text,
     read_claude_json_text, write_claude_json_text, write_claude_config_files,
@@ -44,11 +54,17 @@ use commands::mcp::{
     mcp_reset_project_choices, mcp_save_project_config, mcp_serve, mcp_test_connection,
     // Multi-engine MCP support
     mcp_list_by_engine, mcp_set_enabled, mcp_add_by_engine, mcp_remove_by_engine, mcp_update_by_engine,
-    mcp_get_project_list, mcp_set_enabled_for_project,
+    mcp_get_project_list, mcp_set_enabled_for_project, mcp_sync_servers,
+};
+use commands::mcp_registry::{mcp_registry_install, mcp_registry_list};
+use commands::anycode_mcp_server::{
+    get_anycode_mcp_server_status, start_anycode_mcp_server, stop_anycode_mcp_server,
 };
 use commands::storage::{init_database, AgentDb};
 
-use commands::clipboard::{read_from_clipboard, save_clipboard_image, write_to_clipboard};
+use commands::clipboard::{
+    cleanup_clipboard_images, read_from_clipboard, save_clipboard_image, save_system_clipboard_image, write_to_clipboard,
+};
 use commands::prompt_tracker::{
     check_rewind_capabilities, get_prompt_list, get_unified_prompt_list, mark_prompt_completed,
     record_prompt_sent, revert_to_prompt,
@@ -58,7 +74,10 @@ use commands::provider::{
     get_current_provider_config, get_provider_config, get_provider_presets, switch_provider_config,
     test_provider_connection, update_provider_config,
 };
-use commands::simple_git::check_and_init_git;
+use commands::simple_git::{
+    check_and_init_git, create_branch, delete_branch, get_branch_status, get_unstaged_hunks, list_branches,
+    stage_hunks, switch_branch, unstage_file,
+};
 use commands::storage::{
     storage_analyze_query, storage_delete_row, storage_execute_sql,
     storage_get_performance_stats, storage_insert_row, storage_list_tables,
@@ -66,13 +85,14 @@ use commands::storage::{
 };
 use commands::translator::{
     clear_translation_cache, detect_text_language, get_translation_cache_stats,
-    get_translation_config, init_translation_service_command, translate, translate_batch,
-    update_translation_config,
+    get_translation_config, init_translation_service_command, list_translation_providers, translate,
+    translate_batch, update_translation_config,
 };
-use commands::usage::{get_session_stats, get_usage_by_date_range, get_usage_stats, get_multi_engine_usage_stats, get_codex_rate_limits};
+use commands::usage::{get_session_stats, get_usage_by_date_range, get_usage_stats, get_multi_engine_usage_stats, get_codex_rate_limits, import_native_usage};
 use commands::window::{
     create_session_window, close_session_window, list_session_windows,
     focus_session_window, emit_to_window, broadcast_to_session_windows,
+    save_window_state, restore_windows_on_startup, open_project_window,
 };
 
 use commands::enhanced_hooks::{
@@ -83,7 +103,9 @@ use commands::extensions::{
     open_agents_directory, open_plugins_directory, open_skills_directory, read_skill, read_subagent,
 };
 use commands::file_operations::{open_directory_in_explorer, open_file_with_default_app};
-use commands::git_stats::{get_git_diff_stats, get_session_code_changes};
+use commands::git_stats::{
+    get_ai_attribution_stats, get_git_diff_stats, get_git_trends, get_session_code_changes, refresh_git_stats,
+};
 use commands::codex::{
     execute_codex, resume_codex, resume_last_codex, cancel_codex,
     list_codex_sessions, list_codex_sessions_for_project, list_codex_projects,
@@ -119,18 +141,27 @@ use commands::codex::{
     // Codex change tracker
     codex_record_file_change, codex_list_file_changes, codex_get_change_detail,
     codex_export_patch, codex_export_single_change, codex_clear_change_records, codex_repair_change_records,
+    codex_export_change_records_json, codex_detect_command_changes,
     CodexProcessState,
 };
 use commands::engine_status::{
     check_engine_status,
+    invalidate_engine_cache,
     update_engine,
     check_engine_update,
+    check_all_engines,
+    check_all_engine_updates,
+    upgrade_engine,
 };
 use commands::gemini::{
     execute_gemini, cancel_gemini, check_gemini_installed,
     get_gemini_config, update_gemini_config, get_gemini_models,
     get_gemini_session_logs, list_gemini_sessions, get_gemini_session_detail,
     delete_gemini_session, get_gemini_system_prompt, save_gemini_system_prompt,
+    list_gemini_prompts, get_gemini_prompt, save_gemini_prompt, rename_gemini_prompt,
+    delete_gemini_prompt, activate_gemini_prompt, deactivate_gemini_prompt,
+    get_active_gemini_prompt_id, check_project_gemini_md, activate_gemini_prompt_to_project,
+    deactivate_gemini_prompt_from_project,
     // Gemini Rewind commands
     get_gemini_prompt_list, check_gemini_rewind_capabilities,
     record_gemini_prompt_sent, record_gemini_prompt_completed,
@@ -146,14 +177,32 @@ use commands::session_watcher::{
     SessionWatcherState,
 };
 use process::ProcessRegistryState;
-use tauri::{Manager, WindowEvent};
+use tauri::{Emitter, Manager, WindowEvent};
 use tauri_plugin_window_state::Builder as WindowStatePlugin;
 
 fn main() {
-    // Initialize logger
-    env_logger::init();
+    // Initialize logger (stderr + ~/.anycode/logs/app.log)
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn,any_code=info"))
+        .target(env_logger::Target::Pipe(Box::new(commands::session_log::AppLogWriter::open())))
+        .init();
+
+    let launch_args: Vec<String> = std::env::args().collect();
+    // `--headless`: no windows, scriptable JSON over stdin/stdout (always its own instance)
+    let headless = launch_args.iter().any(|arg| arg == "--headless");
+
+    let mut builder = tauri::Builder::default();
+    if !headless {
+        // Single instance: later launches (anycode:// links, project paths) are handed to
+        // the running instance and exit. Must be registered before the other plugins.
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            log::info!("[DeepLink] Received launch from another instance: {:?}", args);
+            commands::tray::show_main_window(app);
+            commands::deep_link::handle_launch_args(app, args, Some(cwd));
+        }));
+    }
 
-    tauri::Builder::default()
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
@@ -164,23 +213,52 @@ fn main() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(
             WindowStatePlugin::default()
                 .with_state_flags(tauri_plugin_window_state::StateFlags::all())
                 .build(),
         )
-        .setup(|app| {
+        .setup(move |app| {
             // Initialize shell environment for macOS GUI applications
             // This must be done early to ensure CLI tools (claude, codex, etc.) can be found
             init_shell_environment();
 
+            // The main window is declared with `create: false` so headless mode never loads
+            // the frontend; GUI launches create it (hidden until the frontend shows it)
+            if !headless {
+                if let Some(config) = app.config().app.windows.iter().find(|w| w.label == "main") {
+                    tauri::WebviewWindowBuilder::from_config(app.handle(), config)?.build()?;
+                }
+            }
+
+            // Data root migrations are staged and copied here, before agents.db is opened
+            if !headless {
+                commands::data_root::apply_pending_migration();
+            }
+
             // Initialize database for storage operations
-            let conn = init_database(&app.handle()).expect("Failed to initialize database");
-            app.manage(AgentDb(Mutex::new(conn)));
+            let pool = init_database(&app.handle()).expect("Failed to initialize database");
+            app.manage(AgentDb(pool));
 
             // Initialize process registry
             app.manage(ProcessRegistryState::default());
 
+            // Detect engine CLIs left running by a previous (crashed) run. Headless
+            // companions leave the on-disk registry to the GUI instance.
+            let orphans_dir = if headless { None } else { commands::data_root::app_data_dir(app.handle()).ok() };
+            if let Some(app_data_dir) = orphans_dir {
+                let orphans = process::orphans::init(&app_data_dir);
+                if !orphans.is_empty() {
+                    let app_handle = app.handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        // Give the frontend time to register its listeners
+                        tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+                        let _ = app_handle.emit("orphaned-processes-detected", &orphans);
+                    });
+                }
+            }
+
             // Initialize Claude process state
             app.manage(ClaudeProcessState::default());
 
@@ -190,6 +268,9 @@ fn main() {
             // Initialize Gemini process state
             app.manage(GeminiProcessState::default());
 
+            // Stream CPU / memory usage of running engine processes
+            process::monitor::start_process_monitor(app.handle().clone());
+
             // Initialize session watcher state (for real-time sync with external tools)
             app.manage(SessionWatcherState::default());
 
@@ -199,20 +280,73 @@ fn main() {
             let app_handle_for_monitor = app.handle().clone();
             let manager_for_monitor = auto_compact_manager.clone();
 
-            // Start monitoring in background
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = manager_for_monitor
-                    .start_monitoring(app_handle_for_monitor)
-                    .await
-                {
-                    log::error!("Failed to start auto-compact monitoring: {}", e);
-                }
-            });
+            // Start monitoring in background (the GUI instance does the compaction)
+            if !headless {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = manager_for_monitor
+                        .start_monitoring(app_handle_for_monitor)
+                        .await
+                    {
+                        log::error!("Failed to start auto-compact monitoring: {}", e);
+                    }
+                });
+            }
 
             app.manage(commands::context_manager::AutoCompactState(
                 auto_compact_manager,
             ));
 
+            // Headless companion mode: no windows, serve requests on stdin/stdout
+            if headless {
+                commands::headless_cli::start(app.handle().clone());
+                return Ok(());
+            }
+
+            // Forward Claude Code hook events when the bridge hooks are installed
+            tauri::async_runtime::spawn(commands::hook_bridge::start_if_installed(app.handle().clone()));
+
+            // Reopen session windows that were still open when the app last exited
+            let app_handle_for_windows = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                // Let the main window load first so it can receive window-sync events
+                tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+                if let Err(e) = commands::window::restore_windows_on_startup(app_handle_for_windows).await {
+                    log::error!("[Window] Failed to restore session windows: {}", e);
+                }
+            });
+
+            // macOS delivers anycode:// links as system events; Windows/Linux pass them as
+            // launch arguments (handled below and by the single-instance plugin)
+            #[cfg(target_os = "macos")]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                let app_handle_for_links = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    let urls = event.urls().into_iter().map(|u| u.to_string()).collect();
+                    commands::deep_link::handle_urls(&app_handle_for_links, urls);
+                });
+            }
+            let app_handle_for_scheme = app.handle().clone();
+            std::thread::spawn(move || commands::deep_link::register_url_scheme(&app_handle_for_scheme));
+            let app_handle_for_launch = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                // Give the frontend time to register its listeners
+                tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+                let cwd = std::env::current_dir().ok().map(|p| p.to_string_lossy().to_string());
+                commands::deep_link::handle_launch_args(&app_handle_for_launch, launch_args, cwd);
+            });
+
+            // System tray (restore window, cancel background sessions, quit)
+            if let Err(e) = commands::tray::init(app.handle()) {
+                log::error!("{}", e);
+            }
+
+            // Register saved global hotkeys (quick prompt capture, re-run last prompt)
+            commands::hotkeys::init(app.handle());
+
+            // Apply the session retention policy when auto cleanup is enabled
+            tauri::async_runtime::spawn(commands::retention::run_auto_cleanup());
+
             // Initialize translation service with saved configuration
             tauri::async_runtime::spawn(async move {
                 commands::translator::init_translation_service_with_saved_config().await;
@@ -240,9 +374,23 @@ fn main() {
         })
         .on_window_event(|window, event| {
             // Handle main window close - close all session windows
-            if let WindowEvent::CloseRequested { .. } = event {
+            if let WindowEvent::CloseRequested { api, .. } = event {
                 let window_label = window.label();
 
+                // Background execution mode: hide the main window to the tray and keep
+                // running sessions alive (they are owned by the app, not the window)
+                if window_label == "main" && commands::tray::should_hide_on_close(window.app_handle()) {
+                    api.prevent_close();
+                    if let Err(e) = window.hide() {
+                        log::error!("[Tray] Failed to hide main window: {}", e);
+                    }
+                    log::info!("[Tray] Main window hidden, executions keep running in the background");
+                    return;
+                }
+
+                // Remember layout / project binding of session windows for the next startup
+                commands::window::handle_window_close_requested(window.app_handle(), window_label);
+
                 // If main window is closing, close all session windows
                 if window_label == "main" {
                     log::info!("[Window] Main window closing, closing all session windows");
@@ -292,8 +440,12 @@ fn main() {
             get_codex_system_prompt,
             check_claude_version,
             check_engine_status,  // 统一的引擎状态检查
+            invalidate_engine_cache,  // 清除引擎检测缓存
             update_engine,  // 引擎更新
             check_engine_update,  // 检查引擎更新
+            check_all_engines,  // 所有引擎健康检查（带缓存）
+            check_all_engine_updates,  // 并发检查所有引擎更新
+            upgrade_engine,  // 升级引擎（流式输出）
             save_system_prompt,
             save_codex_system_prompt,
             // Multi-prompt management
@@ -309,11 +461,26 @@ fn main() {
             check_project_agents_md,
             activate_codex_prompt_to_project,
             deactivate_codex_prompt_from_project,
+            // Claude sub-agents (~/.claude/agents)
+            list_claude_agents,
+            validate_claude_agent,
+            save_claude_agent,
+            delete_claude_agent,
+            activate_claude_agent_to_project,
+            deactivate_claude_agent_from_project,
             save_claude_settings,
             update_thinking_mode,
             find_claude_md_files,
             read_claude_md_file,
             save_claude_md_file,
+            // CLAUDE.md template library
+            list_claude_md_templates,
+            get_claude_md_template,
+            save_claude_md_template,
+            delete_claude_md_template,
+            check_project_claude_md,
+            activate_claude_md_template_to_project,
+            deactivate_claude_md_template_from_project,
             load_session_history,
             execute_claude_code,
             continue_claude_code,
@@ -326,6 +493,11 @@ fn main() {
             get_hooks_config,
             update_hooks_config,
             validate_hook_command,
+            list_claude_hooks,
+            validate_hooks_config,
+            save_claude_hook,
+            remove_claude_hook,
+            test_hook,
             // 权限管理命令
             get_claude_execution_config,
             update_claude_execution_config,
@@ -346,6 +518,8 @@ fn main() {
             preindex_project,
             export_acemcp_sidecar,
             get_extracted_sidecar_path,
+            acemcp_list_tool_traces,
+            acemcp_replay_tool_trace,
             // Enhanced Hooks Automation
             trigger_hook_event,
             test_hook_condition,
@@ -356,6 +530,7 @@ fn main() {
             get_session_stats,
             get_multi_engine_usage_stats,
             get_codex_rate_limits,
+            import_native_usage,
             // MCP (Model Context Protocol)
             mcp_add,
             mcp_list,
@@ -378,6 +553,14 @@ fn main() {
             mcp_update_by_engine,
             mcp_get_project_list,
             mcp_set_enabled_for_project,
+            mcp_sync_servers,
+            // MCP Registry
+            mcp_registry_list,
+            mcp_registry_install,
+            // Built-in MCP Server
+            start_anycode_mcp_server,
+            stop_anycode_mcp_server,
+            get_anycode_mcp_server_status,
             // Storage Management
             storage_list_tables,
             storage_read_table,
@@ -390,6 +573,8 @@ fn main() {
             storage_analyze_query,
             // Clipboard
             save_clipboard_image,
+            save_system_clipboard_image,
+            cleanup_clipboard_images,
             write_to_clipboard,
             read_from_clipboard,
             // Provider Management
@@ -410,6 +595,7 @@ fn main() {
             clear_translation_cache,
             get_translation_cache_stats,
             detect_text_language,
+            list_translation_providers,
             init_translation_service_command,
             // Auto-Compact Context Management
             commands::context_commands::init_auto_compact_manager,
@@ -424,6 +610,22 @@ fn main() {
             commands::context_commands::stop_auto_compact_monitoring,
             commands::context_commands::start_auto_compact_monitoring,
             commands::context_commands::get_auto_compact_status,
+            commands::context_commands::build_context,
+            // Instruction Sync
+            commands::instruction_sync::get_project_instructions,
+            commands::instruction_sync::save_project_instructions,
+            commands::instruction_sync::import_project_instructions,
+            commands::instruction_sync::sync_project_instructions,
+            // Repo Map
+            commands::repo_map::get_repo_map,
+            // Semantic Index
+            commands::semantic_index::get_semantic_index_config,
+            commands::semantic_index::update_semantic_index_config,
+            commands::semantic_index::semantic_search,
+            commands::semantic_index::reindex_semantic_index,
+            commands::semantic_index::clear_semantic_index,
+            commands::semantic_index::start_semantic_watcher,
+            commands::semantic_index::stop_semantic_watcher,
             // Prompt Revert System
             check_and_init_git,
             record_prompt_sent,
@@ -449,6 +651,18 @@ fn main() {
             // Git Statistics
             get_git_diff_stats,
             get_session_code_changes,
+            get_git_trends,
+            refresh_git_stats,
+            get_ai_attribution_stats,
+            // Git Branches
+            list_branches,
+            create_branch,
+            switch_branch,
+            delete_branch,
+            get_branch_status,
+            get_unstaged_hunks,
+            stage_hunks,
+            unstage_file,
             // OpenAI Codex Integration
             execute_codex,
             resume_codex,
@@ -482,6 +696,9 @@ fn main() {
             delete_codex_provider_config,
             clear_codex_provider_config,
             test_codex_provider_connection,
+            // Codex Provider Failover
+            commands::codex::failover::get_codex_failover_config,
+            commands::codex::failover::set_codex_failover_config,
             // Codex Provider Mode Switching
             get_codex_provider_mode,
             backup_third_party_auth,
@@ -522,6 +739,7 @@ fn main() {
             get_available_codex_models,
             refresh_codex_capabilities,
             force_refresh_codex_capabilities,
+            commands::codex::reasoning_stats::suggest_reasoning_mode,
             // Codex Change Tracker
             codex_record_file_change,
             codex_list_file_changes,
@@ -530,6 +748,8 @@ fn main() {
             codex_export_single_change,
             codex_clear_change_records,
             codex_repair_change_records,
+            codex_export_change_records_json,
+            codex_detect_command_changes,
             // Window Management (Multi-window support)
             create_session_window,
             close_session_window,
@@ -537,6 +757,9 @@ fn main() {
             focus_session_window,
             emit_to_window,
             broadcast_to_session_windows,
+            save_window_state,
+            restore_windows_on_startup,
+            open_project_window,
             // Google Gemini CLI Integration
             execute_gemini,
             cancel_gemini,
@@ -552,6 +775,18 @@ fn main() {
             // Gemini System Prompt
             get_gemini_system_prompt,
             save_gemini_system_prompt,
+            list_gemini_prompts,
+            get_gemini_prompt,
+            save_gemini_prompt,
+            rename_gemini_prompt,
+            delete_gemini_prompt,
+            activate_gemini_prompt,
+            deactivate_gemini_prompt,
+            get_active_gemini_prompt_id,
+            // Project-level GEMINI.md management
+            check_project_gemini_md,
+            activate_gemini_prompt_to_project,
+            deactivate_gemini_prompt_from_project,
             // Gemini Rewind Commands
             get_gemini_prompt_list,
             check_gemini_rewind_capabilities,
@@ -573,11 +808,239 @@ fn main() {
             commands::ide::detect_ides,
             commands::ide::open_file_in_ide,
             commands::ide::validate_ide_path,
+            commands::ide::list_editors,
+            commands::ide::open_in_ide,
+            commands::ide::open_change_in_ide_diff,
+            commands::ide::export_and_open_patch,
+            // External Terminal
+            commands::terminal::get_terminal_settings,
+            commands::terminal::save_terminal_settings,
+            commands::terminal::list_terminals,
+            commands::terminal::open_terminal,
+            commands::terminal::open_project_terminal,
+            // Embedded terminal (PTY)
+            commands::pty::pty_spawn,
+            commands::pty::pty_write,
+            commands::pty::pty_resize,
+            commands::pty::pty_kill,
+            commands::pty::pty_list,
+            // Interactive tool approvals
+            commands::tool_approval::respond_to_approval,
+            commands::tool_approval::list_pending_approvals,
+            // Prompt attachments
+            commands::attachments::stage_attachment,
+            commands::attachments::remove_staged_attachment,
+            // Project registry
+            commands::project_registry::register_project,
+            commands::project_registry::scan_projects,
+            commands::project_registry::list_registered_projects,
+            commands::project_registry::remove_project,
+            // Recently modified files
+            commands::recent_files::get_recently_modified_files,
+            // Batch file operations
+            commands::file_operations::batch_file_operations,
+            commands::file_operations::revert_file_batch,
+            commands::file_operations::list_file_batches,
+            // Workspace trash
+            commands::trash::trash_files,
+            commands::trash::list_trash,
+            commands::trash::restore_trashed,
+            commands::trash::delete_trashed,
+            commands::trash::purge_trash,
+            // Bidirectional prompt translation
+            commands::prompt_translation::get_prompt_translations,
+            // Provider latency & error metrics
+            commands::provider_metrics::get_provider_metrics,
+            // Command invocation metrics (opt-in, local only)
+            commands::command_metrics::record_command_metrics,
+            commands::command_metrics::get_command_metrics,
+            commands::command_metrics::get_command_metrics_settings,
+            commands::command_metrics::set_command_metrics_settings,
+            commands::command_metrics::clear_command_metrics,
+            // Provider preset sharing
+            commands::provider_bundle::export_provider_presets,
+            commands::provider_bundle::import_provider_presets,
+            // Team-shared configuration sync
+            commands::config_sync::get_shared_config_settings,
+            commands::config_sync::set_shared_config_settings,
+            commands::config_sync::get_shared_config_status,
+            commands::config_sync::sync_shared_config,
+            // Rate limit detection & requeue
+            commands::rate_limit::get_rate_limit_settings,
+            commands::rate_limit::set_rate_limit_settings,
+            commands::rate_limit::cancel_rate_limit_requeue,
+            // Claude Code hook event bridge
+            commands::hook_bridge::get_hook_bridge_status,
+            commands::hook_bridge::install_hook_bridge,
+            commands::hook_bridge::uninstall_hook_bridge,
+            commands::hook_bridge::list_hook_bridge_events,
+            // Desktop notifications
+            commands::notifications::get_notification_settings,
+            commands::notifications::set_notification_settings,
+            commands::notifications::list_notification_history,
+            commands::notifications::mark_notifications_read,
+            commands::notifications::clear_notification_history,
+            commands::notification_channels::list_notification_channels,
+            commands::notification_channels::save_notification_channel,
+            commands::notification_channels::delete_notification_channel,
+            commands::notification_channels::test_notification_channel,
+            // Global hotkeys
+            commands::hotkeys::get_hotkey_bindings,
+            commands::hotkeys::register_hotkey,
+            commands::hotkeys::unregister_hotkey,
+            commands::hotkeys::set_active_project,
+            commands::hotkeys::submit_quick_capture,
+            // System tray / background execution
+            commands::tray::get_tray_settings,
+            commands::tray::set_tray_settings,
+            // anycode:// deep links
+            commands::deep_link::get_deep_link_settings,
+            commands::deep_link::set_deep_link_settings,
+            commands::deep_link::run_deep_link_request,
             // Session File Watcher (Real-time sync with external tools)
             start_session_watcher,
             stop_session_watcher,
             stop_all_session_watchers,
+            // Orphaned engine process recovery
+            process::orphans::list_orphaned_processes,
+            process::orphans::kill_orphaned_processes,
+            // Engine process resource monitoring
+            process::monitor::get_process_stats,
+            // AnyCode data root & portable mode
+            commands::data_root::get_data_root_info,
+            commands::data_root::migrate_data_root,
+            // Backend message locale
+            i18n::get_backend_locale,
+            i18n::set_backend_locale,
+            // Data backup & restore
+            commands::backup::create_backup,
+            commands::backup::preview_restore,
+            commands::backup::restore_backup,
+            // Key-value settings
+            commands::app_settings::settings_get,
+            commands::app_settings::settings_set,
+            commands::app_settings::settings_delete,
+            commands::app_settings::settings_watch,
+            commands::app_settings::settings_unwatch,
+            // Session retention & cleanup
+            commands::retention::get_retention_policy,
+            commands::retention::save_retention_policy,
+            commands::retention::preview_cleanup,
+            commands::retention::run_cleanup,
+            // Disk usage report
+            commands::storage_report::get_storage_report,
+            // Stream output batching
+            commands::output_batcher::get_stream_batching_config,
+            commands::output_batcher::set_stream_batching_config,
+            // Paginated session history
+            commands::session_pages::load_session_messages,
+            // Token counting
+            commands::tokenizer::count_tokens,
+            commands::tokenizer::count_context_tokens,
+            // Context preflight
+            commands::preflight::preflight_execution,
+            // Engine capability matrix
+            commands::capabilities::get_capability_matrix,
+            commands::capabilities::invalidate_capability_matrix,
+            // Model aliases & routing
+            commands::model_routing::get_model_routing,
+            commands::model_routing::save_model_alias,
+            commands::model_routing::delete_model_alias,
+            commands::model_routing::save_routing_rule,
+            commands::model_routing::delete_routing_rule,
+            commands::model_routing::reorder_routing_rules,
+            commands::model_routing::preview_model_resolution,
+            // History compaction before resume
+            commands::session_compaction::get_resume_compaction_config,
+            commands::session_compaction::set_resume_compaction_config,
+            commands::session_compaction::compact_session_before_resume,
+            // Per-session execution logs
+            commands::session_log::get_session_log,
+            commands::session_log::open_session_log_in_editor,
+            // Diagnostics bundle export
+            commands::diagnostics::export_diagnostics,
+            // Per-project settings
+            commands::project_settings::get_project_settings,
+            commands::project_settings::set_project_settings,
+            // Remote SSH execution
+            commands::ssh_remote::test_remote_connection,
+            commands::ssh_remote::sync_remote_codex_sessions,
+            // Docker execution backend
+            commands::docker_backend::get_docker_status,
+            commands::docker_backend::list_managed_containers,
+            commands::docker_backend::remove_managed_container,
+            commands::docker_backend::cleanup_managed_containers,
+            commands::docker_backend::pull_docker_image,
+            // Filesystem guardrails
+            commands::guardrails::get_default_fs_policy,
+            // Command audit log
+            commands::command_audit::list_executed_commands,
+            commands::command_audit::search_commands,
+            // Cross-engine permission profiles
+            commands::permission_config::list_permission_profiles,
+            commands::permission_config::save_permission_profile,
+            commands::permission_config::delete_permission_profile,
+            commands::permission_config::preview_permission_profile,
+            // Per-project default execution options
+            commands::project_defaults::get_project_defaults,
+            commands::project_defaults::set_project_defaults,
+            // Prompt library
+            commands::prompt_library::list_prompt_library,
+            commands::prompt_library::render_prompt,
+            // Session fork
+            commands::session_fork::fork_session,
+            // Session metadata
+            commands::session_metadata::tag_session,
+            commands::session_metadata::pin_session,
+            commands::session_metadata::archive_session,
+            // Session summary
+            commands::session_summary::summarize_session,
+            // Commit message generation
+            commands::commit_message::generate_commit_message,
+            // Project memory
+            commands::project_memory::list_project_notes,
+            commands::project_memory::add_project_note,
+            commands::project_memory::delete_project_note,
+            commands::project_memory::extract_project_notes,
+            // Prompt history
+            commands::prompt_history::search_prompt_history,
+            commands::prompt_history::set_prompt_favorite,
+            commands::prompt_history::set_prompt_tags,
+            commands::prompt_history::delete_prompt_history,
+            commands::prompt_history::rerun_prompt,
+            // Custom commands
+            commands::custom_commands::list_custom_commands,
+            commands::custom_commands::save_custom_command,
+            commands::custom_commands::delete_custom_command,
+            commands::custom_commands::run_custom_command,
+            // Multi-engine pipelines
+            commands::pipeline::list_pipelines,
+            commands::pipeline::save_pipeline,
+            commands::pipeline::delete_pipeline,
+            commands::pipeline::run_pipeline,
+            commands::pipeline::abort_pipeline,
+            commands::pipeline::list_running_pipelines,
+            // Engine compare mode
+            commands::compare::compare_engines,
+            commands::compare::cleanup_compare_worktrees,
+            // Post-prompt verification (format / lint / test)
+            commands::post_processors::run_post_processors,
+            commands::post_processors::list_verification_records,
+            // Continuous test watch
+            commands::test_watch::list_test_watch_runs,
+            // Auto-fix loop
+            commands::auto_fix::cancel_auto_fix,
+            // Session vs branch comparison
+            commands::branch_compare::compare_session_with_branch,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(move |_app_handle, event| {
+            // Headless mode has no windows; only exit when stdin closes (explicit exit code)
+            if let tauri::RunEvent::ExitRequested { api, code, .. } = &event {
+                if headless && code.is_none() {
+                    api.prevent_exit();
+                }
+            }
+        });
 }
@@ -1,5 +1,11 @@
 pub mod job_object;
+pub mod monitor;
+pub mod orphans;
 pub mod registry;
+pub mod tree;
+pub mod watchdog;
 
 pub use job_object::JobObject;
 pub use registry::*;
+pub use tree::{kill_process_tree_verified_async, ProcessKillReport};
+pub use watchdog::{ExecutionTimeoutOptions, ExecutionWatchdog, TimeoutKind};
@@ -0,0 +1,259 @@
+//! Live resource monitoring of engine processes
+//!
+//! Samples CPU, memory and child-process count of every running Claude / Codex /
+//! Gemini process (including MCP servers and other descendants) every few
+//! seconds and streams the results as `process-stats` events.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::tree::tree_pids;
+use super::ProcessRegistryState;
+use crate::commands::codex::CodexProcessState;
+use crate::commands::gemini::GeminiProcessState;
+
+/// Interval between two samples
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Resource usage of a single descendant process
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildProcessStats {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+/// Resource usage of an engine process and its whole tree
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStats {
+    pub session_id: String,
+    /// "claude" | "codex" | "gemini"
+    pub engine: String,
+    pub pid: u32,
+    /// CPU usage of the whole tree (100 = one full core)
+    pub cpu_percent: f32,
+    /// Resident memory of the whole tree
+    pub memory_bytes: u64,
+    /// CPU usage of the engine process alone
+    pub root_cpu_percent: f32,
+    /// Resident memory of the engine process alone
+    pub root_memory_bytes: u64,
+    pub child_process_count: usize,
+    pub children: Vec<ChildProcessStats>,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// A running engine process to sample
+struct MonitorTarget {
+    session_id: String,
+    engine: &'static str,
+    pid: u32,
+}
+
+/// Shared between the background loop and on-demand queries so CPU deltas stay valid
+static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| Mutex::new(System::new()));
+
+/// Latest sample per session ID
+static LATEST: Lazy<Mutex<HashMap<String, ProcessStats>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Collect the PIDs of all running engine processes
+async fn collect_targets(app: &AppHandle) -> Vec<MonitorTarget> {
+    let mut targets = Vec::new();
+
+    if let Some(state) = app.try_state::<CodexProcessState>() {
+        let processes = state.processes.lock().await;
+        targets.extend(processes.iter().filter_map(|(sid, child)| {
+            child.id().map(|pid| MonitorTarget {
+                session_id: sid.clone(),
+                engine: "codex",
+                pid,
+            })
+        }));
+    }
+
+    if let Some(state) = app.try_state::<GeminiProcessState>() {
+        let processes = state.processes.lock().await;
+        targets.extend(processes.iter().filter_map(|(sid, child)| {
+            child.id().map(|pid| MonitorTarget {
+                session_id: sid.clone(),
+                engine: "gemini",
+                pid,
+            })
+        }));
+    }
+
+    // Claude keeps its child locked while waiting on it, use the registry instead
+    if let Some(registry) = app.try_state::<ProcessRegistryState>() {
+        match registry.0.get_running_claude_sessions() {
+            Ok(sessions) => {
+                targets.extend(sessions.into_iter().filter_map(|info| match info.process_type {
+                    super::ProcessType::ClaudeSession { session_id } => Some(MonitorTarget {
+                        session_id,
+                        engine: "claude",
+                        pid: info.pid,
+                    }),
+                    _ => None,
+                }));
+            }
+            Err(e) => log::debug!("[ProcessMonitor] Failed to read Claude sessions: {}", e),
+        }
+    }
+
+    targets
+}
+
+/// Sample every target's process tree
+fn sample(targets: &[MonitorTarget]) -> Vec<ProcessStats> {
+    let mut system = SYSTEM.lock().unwrap();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    let now = Utc::now();
+
+    targets
+        .iter()
+        .filter_map(|target| {
+            let root = Pid::from_u32(target.pid);
+            let root_process = system.process(root)?;
+
+            let children: Vec<ChildProcessStats> = tree_pids(&system, root)
+                .into_iter()
+                .skip(1)
+                .filter_map(|pid| {
+                    system.process(pid).map(|p| ChildProcessStats {
+                        pid: pid.as_u32(),
+                        name: p.name().to_string_lossy().to_string(),
+                        cpu_percent: p.cpu_usage(),
+                        memory_bytes: p.memory(),
+                    })
+                })
+                .collect();
+
+            Some(ProcessStats {
+                session_id: target.session_id.clone(),
+                engine: target.engine.to_string(),
+                pid: target.pid,
+                cpu_percent: root_process.cpu_usage() + children.iter().map(|c| c.cpu_percent).sum::<f32>(),
+                memory_bytes: root_process.memory() + children.iter().map(|c| c.memory_bytes).sum::<u64>(),
+                root_cpu_percent: root_process.cpu_usage(),
+                root_memory_bytes: root_process.memory(),
+                child_process_count: children.len(),
+                children,
+                sampled_at: now,
+            })
+        })
+        .collect()
+}
+
+/// Start the background sampling loop (called once during app setup)
+pub fn start_process_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        log::info!("[ProcessMonitor] Started (interval: {:?})", SAMPLE_INTERVAL);
+        loop {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+
+            let targets = collect_targets(&app).await;
+            if targets.is_empty() {
+                LATEST.lock().unwrap().clear();
+                continue;
+            }
+
+            let stats = match tokio::task::spawn_blocking(move || sample(&targets)).await {
+                Ok(stats) => stats,
+                Err(e) => {
+                    log::warn!("[ProcessMonitor] Sampling task failed: {}", e);
+                    continue;
+                }
+            };
+
+            {
+                let mut latest = LATEST.lock().unwrap();
+                latest.clear();
+                for s in &stats {
+                    latest.insert(s.session_id.clone(), s.clone());
+                }
+            }
+
+            for s in &stats {
+                let _ = app.emit(&format!("process-stats:{}", s.session_id), s);
+            }
+            let _ = app.emit("process-stats", &stats);
+        }
+    });
+}
+
+/// Get the latest resource usage of an engine session
+///
+/// Returns `None` if the session has no running process.
+#[tauri::command]
+pub async fn get_process_stats(
+    app: AppHandle,
+    session_id: String,
+) -> Result<Option<ProcessStats>, String> {
+    if let Some(stats) = LATEST.lock().unwrap().get(&session_id) {
+        return Ok(Some(stats.clone()));
+    }
+
+    // Not sampled yet (just started), sample on demand; CPU is 0 on the first sample
+    let targets: Vec<MonitorTarget> = collect_targets(&app)
+        .await
+        .into_iter()
+        .filter(|t| t.session_id == session_id)
+        .collect();
+    if targets.is_empty() {
+        return Ok(None);
+    }
+
+    let stats = tokio::task::spawn_blocking(move || sample(&targets))
+        .await
+        .map_err(|e| format!("Failed to sample process stats: {}", e))?;
+    Ok(stats.into_iter().next())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn samples_the_whole_process_tree() {
+        let mut child = Command::new("sh").args(["-c", "sleep 30 & sleep 30 & wait"]).spawn().unwrap();
+        let targets = vec![
+            MonitorTarget { session_id: "live".to_string(), engine: "codex", pid: child.id() },
+            // Exited processes are skipped
+            MonitorTarget { session_id: "gone".to_string(), engine: "gemini", pid: u32::MAX },
+        ];
+
+        let mut stats = Vec::new();
+        for _ in 0..50 {
+            stats = sample(&targets);
+            if stats.first().is_some_and(|s| s.child_process_count >= 2) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert_eq!(stats.len(), 1);
+        let live = &stats[0];
+        assert_eq!((live.session_id.as_str(), live.engine.as_str(), live.pid), ("live", "codex", child.id()));
+        assert_eq!(live.child_process_count, 2);
+        assert!(live.children.iter().all(|c| c.name.contains("sleep")));
+        assert_eq!(
+            live.memory_bytes,
+            live.root_memory_bytes + live.children.iter().map(|c| c.memory_bytes).sum::<u64>()
+        );
+
+        let json = serde_json::to_value(live).unwrap();
+        assert_eq!(json["childProcessCount"], 2);
+        assert!(json["rootMemoryBytes"].is_u64());
+    }
+}
@@ -0,0 +1,297 @@
+//! Disk-persisted registry of spawned engine CLIs
+//!
+//! If AnyCode crashes (or is force-quit) mid-execution the in-memory process
+//! states are lost, but the Claude/Codex/Gemini CLIs keep running. Every spawned
+//! engine process is recorded in `<app_data_dir>/engine-processes.json` together
+//! with its start time; on the next launch the entries that are still alive are
+//! reported as orphans so the UI can offer to kill them.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, System};
+
+use super::tree::{kill_process_tree_verified_async, ProcessKillReport};
+
+const REGISTRY_FILE: &str = "engine-processes.json";
+
+/// An engine process recorded on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedProcess {
+    pub pid: u32,
+    /// Process start time (seconds since epoch) – guards against PID reuse
+    pub start_time: u64,
+    /// "claude" | "codex" | "gemini"
+    pub engine: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    pub project_path: String,
+    /// PID of the AnyCode instance that spawned it
+    pub owner_pid: u32,
+    /// Start time of the owning instance – guards against owner PID reuse
+    #[serde(default)]
+    pub owner_start_time: Option<u64>,
+    pub registered_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct PersistedRegistry {
+    path: Option<PathBuf>,
+    entries: Vec<TrackedProcess>,
+}
+
+impl PersistedRegistry {
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        match serde_json::to_string_pretty(&self.entries) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    log::warn!("[Orphans] Failed to write {:?}: {}", path, e);
+                }
+            }
+            Err(e) => log::warn!("[Orphans] Failed to serialize registry: {}", e),
+        }
+    }
+}
+
+static REGISTRY: Lazy<Mutex<PersistedRegistry>> = Lazy::new(|| Mutex::new(PersistedRegistry::default()));
+
+/// Start time of this AnyCode instance
+static OWN_START_TIME: Lazy<Option<u64>> =
+    Lazy::new(|| process_start_time(&mut System::new(), std::process::id()));
+
+/// Start time of a live (non-zombie) process
+fn process_start_time(system: &mut System, pid: u32) -> Option<u64> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system
+        .process(pid)
+        .filter(|p| p.status() != ProcessStatus::Zombie)
+        .map(|p| p.start_time())
+}
+
+/// Split other instances' entries into orphans (alive, owner gone) and processes still
+/// owned by another running AnyCode instance; dead ones are dropped
+fn reconcile<F>(entries: Vec<TrackedProcess>, mut start_time_of: F) -> (Vec<TrackedProcess>, Vec<TrackedProcess>)
+where
+    F: FnMut(u32) -> Option<u64>,
+{
+    let own_pid = std::process::id();
+    let mut orphans = Vec::new();
+    let mut owned = Vec::new();
+    for entry in entries {
+        if start_time_of(entry.pid) != Some(entry.start_time) {
+            continue;
+        }
+        let owner_start_time = start_time_of(entry.owner_pid);
+        let owner_alive = entry.owner_pid != own_pid
+            && owner_start_time.is_some()
+            && entry.owner_start_time.is_none_or(|t| owner_start_time == Some(t));
+        if owner_alive {
+            owned.push(entry);
+        } else {
+            orphans.push(entry);
+        }
+    }
+    (orphans, owned)
+}
+
+/// Load the registry left by the previous run and detect orphaned engine processes
+///
+/// Called once during app setup. Returns the orphans that are still running.
+pub fn init(app_data_dir: &Path) -> Vec<TrackedProcess> {
+    let path = app_data_dir.join(REGISTRY_FILE);
+    let previous: Vec<TrackedProcess> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| match serde_json::from_str(&content) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                log::warn!("[Orphans] Ignoring malformed {:?}: {}", path, e);
+                None
+            }
+        })
+        .unwrap_or_default();
+
+    let mut system = System::new();
+    let (orphans, owned) = reconcile(previous, |pid| process_start_time(&mut system, pid));
+
+    if orphans.is_empty() {
+        log::info!("[Orphans] No orphaned engine processes from previous run");
+    } else {
+        log::warn!(
+            "[Orphans] Found {} orphaned engine process(es) from previous run: {:?}",
+            orphans.len(),
+            orphans.iter().map(|o| (o.engine.as_str(), o.pid)).collect::<Vec<_>>()
+        );
+    }
+
+    let mut registry = REGISTRY.lock().unwrap();
+    registry.path = Some(path);
+    // Orphans stay on disk until they are killed or exit on their own; processes of
+    // another running instance are kept for that instance
+    registry.entries = orphans.iter().cloned().chain(owned).collect();
+    registry.save();
+
+    orphans
+}
+
+/// Record a freshly spawned engine process
+pub fn track_process(pid: u32, engine: &str, session_id: Option<&str>, project_path: &str) {
+    let Some(start_time) = process_start_time(&mut System::new(), pid) else {
+        log::debug!("[Orphans] PID {} exited before it could be tracked", pid);
+        return;
+    };
+
+    let mut registry = REGISTRY.lock().unwrap();
+    registry.entries.retain(|e| e.pid != pid);
+    registry.entries.push(TrackedProcess {
+        pid,
+        start_time,
+        engine: engine.to_string(),
+        session_id: session_id.map(str::to_string),
+        project_path: project_path.to_string(),
+        owner_pid: std::process::id(),
+        owner_start_time: *OWN_START_TIME,
+        registered_at: Utc::now(),
+    });
+    registry.save();
+}
+
+/// Forget an engine process (it exited or was cancelled)
+pub fn untrack_process(pid: u32) {
+    let mut registry = REGISTRY.lock().unwrap();
+    let before = registry.entries.len();
+    registry.entries.retain(|e| e.pid != pid);
+    if registry.entries.len() != before {
+        registry.save();
+    }
+}
+
+/// Engine processes spawned by this AnyCode instance
+pub fn running_processes() -> Vec<TrackedProcess> {
+    let own_pid = std::process::id();
+    REGISTRY
+        .lock()
+        .unwrap()
+        .entries
+        .iter()
+        .filter(|e| e.owner_pid == own_pid)
+        .cloned()
+        .collect()
+}
+
+/// Orphans left by a previous run that are still alive
+fn current_orphans() -> Vec<TrackedProcess> {
+    let own_pid = std::process::id();
+    let candidates: Vec<TrackedProcess> = REGISTRY
+        .lock()
+        .unwrap()
+        .entries
+        .iter()
+        .filter(|e| e.owner_pid != own_pid)
+        .cloned()
+        .collect();
+
+    let mut system = System::new();
+    let (orphans, owned) = reconcile(candidates.clone(), |pid| process_start_time(&mut system, pid));
+
+    // Drop processes that exited on their own since startup
+    if orphans.len() + owned.len() != candidates.len() {
+        let mut registry = REGISTRY.lock().unwrap();
+        registry.entries.retain(|e| {
+            e.owner_pid == own_pid || orphans.iter().chain(&owned).any(|a| a.pid == e.pid)
+        });
+        registry.save();
+    }
+
+    orphans
+}
+
+/// List engine processes orphaned by a previous AnyCode run
+#[tauri::command]
+pub async fn list_orphaned_processes() -> Result<Vec<TrackedProcess>, String> {
+    tokio::task::spawn_blocking(current_orphans)
+        .await
+        .map_err(|e| format!("Failed to list orphaned processes: {}", e))
+}
+
+/// Kill every engine process orphaned by a previous AnyCode run
+#[tauri::command]
+pub async fn kill_orphaned_processes() -> Result<Vec<ProcessKillReport>, String> {
+    let orphans = tokio::task::spawn_blocking(current_orphans)
+        .await
+        .map_err(|e| format!("Failed to list orphaned processes: {}", e))?;
+
+    let mut reports = Vec::with_capacity(orphans.len());
+    for orphan in orphans {
+        log::info!(
+            "[Orphans] Killing orphaned {} process {} (session: {:?})",
+            orphan.engine,
+            orphan.pid,
+            orphan.session_id
+        );
+        let report = kill_process_tree_verified_async(orphan.pid).await;
+        if report.fully_terminated() {
+            untrack_process(orphan.pid);
+        }
+        reports.push(report);
+    }
+
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, start_time: u64) -> TrackedProcess {
+        TrackedProcess {
+            pid,
+            start_time,
+            engine: "codex".to_string(),
+            session_id: None,
+            project_path: "/tmp/project".to_string(),
+            owner_pid: 1,
+            owner_start_time: Some(50),
+            registered_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn reconcile_keeps_only_live_processes_with_matching_start_time() {
+        let entries = vec![entry(10, 100), entry(11, 200), entry(12, 300)];
+        // 10: alive, 11: exited, 12: PID reused by another process; owner 1 exited
+        let (orphans, owned) = reconcile(entries, |pid| match pid {
+            10 => Some(100),
+            12 => Some(999),
+            _ => None,
+        });
+
+        let pids: Vec<u32> = orphans.iter().map(|o| o.pid).collect();
+        assert_eq!(pids, vec![10]);
+        assert!(owned.is_empty());
+    }
+
+    #[test]
+    fn reconcile_skips_processes_of_a_running_instance() {
+        let mut reused_owner = entry(11, 200);
+        reused_owner.owner_pid = 2;
+        let entries = vec![entry(10, 100), reused_owner];
+        // Owner 1 is still running; owner 2's PID now belongs to another process
+        let (orphans, owned) = reconcile(entries, |pid| match pid {
+            1 => Some(50),
+            2 => Some(999),
+            10 => Some(100),
+            11 => Some(200),
+            _ => None,
+        });
+
+        assert_eq!(owned.iter().map(|o| o.pid).collect::<Vec<_>>(), vec![10]);
+        assert_eq!(orphans.iter().map(|o| o.pid).collect::<Vec<_>>(), vec![11]);
+    }
+}
@@ -0,0 +1,286 @@
+//! Verified process tree termination
+//!
+//! `kill_process_tree` alone is best effort: on Unix it only signals the root
+//! PID, and on Windows grandchildren (e.g. MCP servers started through `cmd`)
+//! occasionally survive `taskkill /T`. This module snapshots the descendants
+//! before killing, verifies which of them are still alive afterwards, and
+//! escalates to a forced kill for each survivor.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, System};
+
+use crate::commands::claude::kill_process_tree;
+
+/// Time given to processes to exit after SIGTERM before forcing
+const GRACE_PERIOD: Duration = Duration::from_millis(500);
+/// Time to let the OS tear processes down before verifying
+const VERIFY_DELAY: Duration = Duration::from_millis(300);
+
+/// A process that was part of the killed tree
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeProcess {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// Result of a verified process tree termination
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessKillReport {
+    /// Root PID that was requested to be killed
+    pub root_pid: u32,
+    /// Processes confirmed gone (root + descendants)
+    pub terminated: Vec<TreeProcess>,
+    /// Processes that survived the first pass and needed a forced kill
+    pub escalated: Vec<TreeProcess>,
+    /// Processes still alive after escalation
+    pub survivors: Vec<TreeProcess>,
+}
+
+impl ProcessKillReport {
+    /// True when every process of the tree is gone
+    pub fn fully_terminated(&self) -> bool {
+        self.survivors.is_empty()
+    }
+}
+
+/// Identity of a snapshotted process (start time guards against PID reuse)
+#[derive(Debug, Clone)]
+struct Snapshot {
+    pid: Pid,
+    name: String,
+    start_time: u64,
+}
+
+fn refresh(system: &mut System) {
+    system.refresh_processes(ProcessesToUpdate::All, true);
+}
+
+/// PIDs of `root` and all of its descendants (root first, threads excluded)
+pub(crate) fn tree_pids(system: &System, root: Pid) -> Vec<Pid> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        // On Linux sysinfo also lists threads as tasks of their process
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut queue = vec![root];
+    let mut out = Vec::new();
+    while let Some(pid) = queue.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        if system.process(pid).is_some() {
+            out.push(pid);
+        }
+        if let Some(kids) = children.get(&pid) {
+            queue.extend(kids.iter().copied());
+        }
+    }
+    out
+}
+
+/// Collect `root` and all of its descendants (root first)
+fn snapshot_tree(system: &System, root: Pid) -> Vec<Snapshot> {
+    tree_pids(system, root)
+        .into_iter()
+        .filter_map(|pid| {
+            system.process(pid).map(|process| Snapshot {
+                pid,
+                name: process.name().to_string_lossy().to_string(),
+                start_time: process.start_time(),
+            })
+        })
+        .collect()
+}
+
+fn is_alive(system: &System, snap: &Snapshot) -> bool {
+    match system.process(snap.pid) {
+        // Zombies are already dead, they are just waiting to be reaped by their parent.
+        Some(p) => p.start_time() == snap.start_time && p.status() != ProcessStatus::Zombie,
+        None => false,
+    }
+}
+
+fn to_tree_process(snap: &Snapshot) -> TreeProcess {
+    TreeProcess {
+        pid: snap.pid.as_u32(),
+        name: snap.name.clone(),
+    }
+}
+
+/// Kill a process tree and verify that no descendant survived
+///
+/// 1. Snapshot root + descendants
+/// 2. (Unix) SIGTERM everything and give it a short grace period
+/// 3. `kill_process_tree` on the root (taskkill /F /T or SIGKILL)
+/// 4. Re-enumerate; force-kill each survivor individually
+///
+/// Blocking: call from `spawn_blocking` in async contexts.
+pub fn kill_process_tree_verified(pid: u32) -> ProcessKillReport {
+    let root = Pid::from_u32(pid);
+    let mut system = System::new();
+    refresh(&mut system);
+
+    let tree = snapshot_tree(&system, root);
+    let mut report = ProcessKillReport {
+        root_pid: pid,
+        ..Default::default()
+    };
+
+    if tree.is_empty() {
+        log::info!("[ProcessTree] PID {} already exited", pid);
+        return report;
+    }
+
+    log::info!(
+        "[ProcessTree] Killing PID {} with {} descendant(s)",
+        pid,
+        tree.len().saturating_sub(1)
+    );
+
+    // Graceful pass: let CLIs flush session files before being forced
+    #[cfg(unix)]
+    {
+        for snap in &tree {
+            if let Some(p) = system.process(snap.pid) {
+                let _ = p.kill_with(sysinfo::Signal::Term);
+            }
+        }
+        std::thread::sleep(GRACE_PERIOD);
+        refresh(&mut system);
+    }
+
+    if tree.iter().any(|s| is_alive(&system, s)) {
+        if let Err(e) = kill_process_tree(pid) {
+            // Root may already be gone while children survive; verification handles that.
+            log::debug!("[ProcessTree] kill_process_tree({}) failed: {}", pid, e);
+        }
+        std::thread::sleep(VERIFY_DELAY);
+        refresh(&mut system);
+    }
+
+    // Escalation: force-kill every survivor individually (children first)
+    let survivors: Vec<&Snapshot> = tree.iter().rev().filter(|s| is_alive(&system, s)).collect();
+    if !survivors.is_empty() {
+        log::warn!(
+            "[ProcessTree] {} process(es) survived killing PID {}, escalating",
+            survivors.len(),
+            pid
+        );
+        for snap in &survivors {
+            report.escalated.push(to_tree_process(snap));
+            if let Err(e) = kill_process_tree(snap.pid.as_u32()) {
+                log::debug!("[ProcessTree] Forced kill of {} failed: {}", snap.pid, e);
+                if let Some(p) = system.process(snap.pid) {
+                    p.kill();
+                }
+            }
+        }
+        std::thread::sleep(VERIFY_DELAY);
+        refresh(&mut system);
+    }
+
+    for snap in &tree {
+        if is_alive(&system, snap) {
+            report.survivors.push(to_tree_process(snap));
+        } else {
+            report.terminated.push(to_tree_process(snap));
+        }
+    }
+
+    if report.fully_terminated() {
+        log::info!(
+            "[ProcessTree] PID {} terminated ({} process(es), {} escalated)",
+            pid,
+            report.terminated.len(),
+            report.escalated.len()
+        );
+    } else {
+        log::error!(
+            "[ProcessTree] PID {} left {} survivor(s): {:?}",
+            pid,
+            report.survivors.len(),
+            report.survivors
+        );
+    }
+
+    report
+}
+
+/// Async wrapper around [`kill_process_tree_verified`]
+pub async fn kill_process_tree_verified_async(pid: u32) -> ProcessKillReport {
+    tokio::task::spawn_blocking(move || kill_process_tree_verified(pid))
+        .await
+        .unwrap_or_else(|e| {
+            log::error!("[ProcessTree] Verification task failed for PID {}: {}", pid, e);
+            ProcessKillReport {
+                root_pid: pid,
+                ..Default::default()
+            }
+        })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+
+    /// Spawn `sh -c script` and wait until its tree has `expected` processes
+    fn spawn_tree(script: &str, expected: usize) -> Child {
+        let child = Command::new("sh").args(["-c", script]).spawn().unwrap();
+        let root = Pid::from_u32(child.id());
+        let mut system = System::new();
+        for _ in 0..50 {
+            refresh(&mut system);
+            if tree_pids(&system, root).len() >= expected {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        child
+    }
+
+    #[test]
+    fn kills_root_and_descendants() {
+        let mut child = spawn_tree("sleep 30 & sleep 30 & wait", 3);
+
+        let report = kill_process_tree_verified(child.id());
+        let _ = child.wait();
+
+        assert!(report.fully_terminated(), "survivors: {:?}", report.survivors);
+        assert_eq!(report.root_pid, child.id());
+        assert!(report.terminated.len() >= 3, "terminated: {:?}", report.terminated);
+        assert!(report.terminated.iter().any(|p| p.name.contains("sleep")));
+    }
+
+    #[test]
+    fn escalates_descendants_that_ignore_sigterm() {
+        // Ignored signals are inherited, so the orphaned sleep outlives SIGTERM and the root's SIGKILL
+        let mut child = spawn_tree("trap '' TERM; sleep 30 & wait", 2);
+
+        let report = kill_process_tree_verified(child.id());
+        let _ = child.wait();
+
+        assert!(report.fully_terminated(), "survivors: {:?}", report.survivors);
+        assert!(report.escalated.iter().any(|p| p.name.contains("sleep")), "escalated: {:?}", report.escalated);
+    }
+
+    #[test]
+    fn exited_process_yields_an_empty_report() {
+        let mut child = Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+
+        let report = kill_process_tree_verified(child.id());
+        assert!(report.fully_terminated());
+        assert!(report.terminated.is_empty());
+        assert!(report.escalated.is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+//! Execution watchdog for engine CLI processes
+//!
+//! Tracks output activity and total runtime of a spawned CLI so the caller can
+//! kill hung executions (no output for N seconds) or runaway ones (running
+//! longer than M minutes) and optionally retry the same prompt.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Per-execution timeout and retry options (shared by Claude / Codex / Gemini)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionTimeoutOptions {
+    /// Kill the process if it produces no output for this many seconds
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+
+    /// Kill the process if it runs longer than this many minutes
+    #[serde(default)]
+    pub max_duration_mins: Option<u64>,
+
+    /// How many times to retry the same prompt after a timeout
+    #[serde(default)]
+    pub max_retries: u32,
+}
+
+impl ExecutionTimeoutOptions {
+    /// Whether any timeout is configured
+    pub fn is_enabled(&self) -> bool {
+        self.idle_timeout_secs.is_some_and(|s| s > 0) || self.max_duration_mins.is_some_and(|m| m > 0)
+    }
+}
+
+/// Reason an execution was killed by the watchdog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutKind {
+    /// No stdout/stderr output within `idle_timeout_secs`
+    Idle,
+    /// Total runtime exceeded `max_duration_mins`
+    MaxDuration,
+}
+
+/// Watches a single execution attempt
+pub struct ExecutionWatchdog {
+    options: ExecutionTimeoutOptions,
+    started: Instant,
+    /// Milliseconds since `started` at which output was last seen
+    last_activity_ms: AtomicU64,
+}
+
+impl ExecutionWatchdog {
+    pub fn new(options: ExecutionTimeoutOptions) -> Self {
+        Self {
+            options,
+            started: Instant::now(),
+            last_activity_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Record that the process produced output
+    pub fn touch(&self) {
+        self.last_activity_ms
+            .store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the timeout that fired, if any
+    pub fn check(&self) -> Option<TimeoutKind> {
+        self.check_at(self.started.elapsed())
+    }
+
+    fn check_at(&self, elapsed: Duration) -> Option<TimeoutKind> {
+        if let Some(mins) = self.options.max_duration_mins.filter(|m| *m > 0) {
+            if elapsed >= Duration::from_secs(mins * 60) {
+                return Some(TimeoutKind::MaxDuration);
+            }
+        }
+
+        if let Some(secs) = self.options.idle_timeout_secs.filter(|s| *s > 0) {
+            let last = Duration::from_millis(self.last_activity_ms.load(Ordering::Relaxed));
+            if elapsed.saturating_sub(last) >= Duration::from_secs(secs) {
+                return Some(TimeoutKind::Idle);
+            }
+        }
+
+        None
+    }
+
+    /// Whether another attempt is allowed after `attempt` (0-based) timed out
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        attempt < self.options.max_retries
+    }
+
+    /// Payload for `<engine>-timeout` events
+    pub fn timeout_payload(
+        &self,
+        kind: TimeoutKind,
+        session_id: &str,
+        attempt: u32,
+        will_retry: bool,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "session_id": session_id,
+            "kind": kind,
+            "idle_timeout_secs": self.options.idle_timeout_secs,
+            "max_duration_mins": self.options.max_duration_mins,
+            "elapsed_secs": self.started.elapsed().as_secs(),
+            "attempt": attempt,
+            "max_retries": self.options.max_retries,
+            "will_retry": will_retry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watchdog(idle: Option<u64>, max_mins: Option<u64>, retries: u32) -> ExecutionWatchdog {
+        ExecutionWatchdog::new(ExecutionTimeoutOptions {
+            idle_timeout_secs: idle,
+            max_duration_mins: max_mins,
+            max_retries: retries,
+        })
+    }
+
+    #[test]
+    fn disabled_options_never_fire() {
+        let w = watchdog(None, Some(0), 0);
+        assert!(!w.options.is_enabled());
+        assert_eq!(w.check_at(Duration::from_secs(100_000)), None);
+    }
+
+    #[test]
+    fn idle_timeout_is_measured_from_last_output() {
+        let w = watchdog(Some(30), None, 0);
+        assert_eq!(w.check_at(Duration::from_secs(29)), None);
+        assert_eq!(w.check_at(Duration::from_secs(30)), Some(TimeoutKind::Idle));
+
+        w.last_activity_ms.store(20_000, Ordering::Relaxed);
+        assert_eq!(w.check_at(Duration::from_secs(45)), None);
+        assert_eq!(w.check_at(Duration::from_secs(50)), Some(TimeoutKind::Idle));
+    }
+
+    #[test]
+    fn max_duration_wins_over_idle() {
+        let w = watchdog(Some(10), Some(1), 0);
+        w.last_activity_ms.store(59_000, Ordering::Relaxed);
+        assert_eq!(w.check_at(Duration::from_secs(60)), Some(TimeoutKind::MaxDuration));
+    }
+
+    #[test]
+    fn retries_are_bounded() {
+        let w = watchdog(Some(10), None, 2);
+        assert!(w.should_retry(0));
+        assert!(w.should_retry(1));
+        assert!(!w.should_retry(2));
+    }
+}
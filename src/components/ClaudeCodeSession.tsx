@@ -140,7 +140,6 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
     const defaultConfig = {
       engine: 'claude' as const,
       codexMode: 'read-only' as const,
-      codexModel: 'gpt-5.2',
       codexReasoningMode: 'medium',
     };
     
@@ -480,7 +479,7 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
   useEffect(() => {
     // 创建一个简化的发送函数，只需要 prompt 参数
     const simpleSendPrompt = (prompt: string) => {
-      handleSendPrompt(prompt, 'sonnet'); // 使用默认模型
+      handleSendPrompt(prompt, 'sonnet', undefined, false); // 使用项目默认模型
     };
     setSendPromptCallback(simpleSendPrompt);
 
@@ -493,7 +492,7 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
   // 🆕 设置 UserQuestion 的发送消息回调，用于答案提交后自动发送
   useEffect(() => {
     const simpleSendMessage = (message: string) => {
-      handleSendPrompt(message, 'sonnet'); // 使用默认模型
+      handleSendPrompt(message, 'sonnet', undefined, false); // 使用项目默认模型
     };
     setSendMessageCallback(simpleSendMessage);
 
@@ -928,7 +927,7 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
     });
   }, [messages]);
 
-  const handleRevert = useCallback(async (promptIndex: number, mode: import('@/lib/api').RewindMode = 'both') => {
+  const handleRevert = useCallback(async (promptIndex: number, mode: import('@/lib/api').RewindMode = 'both', partial?: boolean) => {
     if (!effectiveSession) return;
 
     try {
@@ -1252,7 +1251,8 @@ const ClaudeCodeSessionInner: React.FC<ClaudeCodeSessionProps> = ({
               effectiveSession.id,
               projectPath,
               promptIndex,
-              mode
+              mode,
+              partial
             )
           : isGemini
           ? await api.revertGeminiToPrompt(
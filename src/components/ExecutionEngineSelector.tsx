@@ -18,7 +18,7 @@ import {
 import { Popover } from '@/components/ui/popover';
 import { Label } from '@/components/ui/label';
 import { Input } from '@/components/ui/input';
-import { api } from '@/lib/api';
+import { api, type RemoteConfig } from '@/lib/api';
 import { relaunchApp } from '@/lib/updater';
 import { ask, message } from '@tauri-apps/plugin-dialog';
 import type { CodexExecutionMode } from '@/types/codex';
@@ -28,7 +28,7 @@ import type { CodexExecutionMode } from '@/types/codex';
 // ============================================================================
 
 export type ExecutionEngine = 'claude' | 'codex' | 'gemini';
-export type CodexRuntimeMode = 'auto' | 'native' | 'wsl';
+export type CodexRuntimeMode = 'auto' | 'native' | 'wsl' | 'remote';
 
 export interface ExecutionEngineConfig {
   engine: ExecutionEngine;
@@ -45,10 +45,11 @@ export interface ExecutionEngineConfig {
 interface CodexModeConfig {
   mode: CodexRuntimeMode;
   wslDistro: string | null;
-  actualMode: 'native' | 'wsl';
+  actualMode: 'native' | 'wsl' | 'remote';
   nativeAvailable: boolean;
   wslAvailable: boolean;
   availableDistros: string[];
+  remote: RemoteConfig | null;
 }
 
 interface ExecutionEngineSelectorProps {
@@ -466,7 +467,12 @@ export const ExecutionEngineSelector: React.FC<ExecutionEngineSelectorProps> = (
                     <div className="flex items-center gap-2">
                       <span className="text-muted-foreground">当前运行环境:</span>
                       <span className="font-medium">
-                        {codexModeConfig.actualMode === 'wsl' ? (
+                        {codexModeConfig.actualMode === 'remote' ? (
+                          <span className="flex items-center gap-1">
+                            <Terminal className="h-3 w-3" />
+                            SSH: {codexModeConfig.remote?.host}
+                          </span>
+                        ) : codexModeConfig.actualMode === 'wsl' ? (
                           <span className="flex items-center gap-1">
                             <Terminal className="h-3 w-3" />
                             WSL
@@ -143,6 +143,8 @@ export const ControlBar: React.FC<ControlBarProps> = ({
             });
           }}
           disabled={disabled || isLoading}
+          projectPath={projectPath}
+          prompt={prompt}
         />
       )}
 
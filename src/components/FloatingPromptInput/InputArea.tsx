@@ -5,6 +5,7 @@ import { Maximize2 } from "lucide-react";
 import { cn } from "@/lib/utils";
 import { AnimatePresence } from "framer-motion";
 import { FilePicker } from "../FilePicker";
+import { PROMPT_TOKEN_WARNING } from "./hooks/usePromptTokenCount";
 
 interface InputAreaProps {
   prompt: string;
@@ -23,6 +24,8 @@ interface InputAreaProps {
   onExpand: () => void;
   onFileSelect: (file: any) => void;
   onFilePickerClose: () => void;
+  /** 提示词 token 数（未计算时为 null） */
+  tokenCount?: number | null;
   // 🔧 Mac 输入法兼容：composition 事件
   onCompositionStart?: () => void;
   onCompositionEnd?: () => void;
@@ -45,6 +48,7 @@ export const InputArea = forwardRef<HTMLTextAreaElement, InputAreaProps>(({
   onExpand,
   onFileSelect,
   onFilePickerClose,
+  tokenCount,
   onCompositionStart,
   onCompositionEnd,
 }, ref) => {
@@ -85,6 +89,12 @@ export const InputArea = forwardRef<HTMLTextAreaElement, InputAreaProps>(({
         <Maximize2 className="h-4 w-4" aria-hidden="true" />
       </Button>
 
+      {tokenCount != null && tokenCount >= PROMPT_TOKEN_WARNING && (
+        <p className="mt-1 text-xs text-amber-600 dark:text-amber-400">
+          提示词约 {tokenCount.toLocaleString()} tokens，会占用较多上下文
+        </p>
+      )}
+
       {/* File Picker */}
       <AnimatePresence>
         {showFilePicker && projectPath && projectPath.trim() && (
@@ -0,0 +1,38 @@
+import { useEffect, useState } from "react";
+import { api } from "@/lib/api";
+
+/**
+ * 提示词超过该 token 数时在输入框下方提示
+ */
+export const PROMPT_TOKEN_WARNING = 8000;
+
+/**
+ * 输入停止后计算提示词的 token 数（后端 tokenizer，按模型选择词表）
+ */
+export function usePromptTokenCount(prompt: string, model: string): number | null {
+  const [tokens, setTokens] = useState<number | null>(null);
+
+  useEffect(() => {
+    if (!prompt.trim()) {
+      setTokens(null);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      api
+        .countTokens(prompt, model)
+        .then((count) => {
+          if (!cancelled) setTokens(count);
+        })
+        .catch(() => {
+          if (!cancelled) setTokens(null);
+        });
+    }, 400);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [prompt, model]);
+
+  return tokens;
+}
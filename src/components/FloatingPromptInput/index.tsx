@@ -8,6 +8,7 @@ import { useImageHandling } from "./hooks/useImageHandling";
 import { useFileSelection } from "./hooks/useFileSelection";
 import { usePromptEnhancement } from "./hooks/usePromptEnhancement";
 import { useEnhancementHistory } from "./hooks/useEnhancementHistory";
+import { usePromptTokenCount } from "./hooks/usePromptTokenCount";
 import { api } from "@/lib/api";
 import { getEnabledProviders } from "@/lib/promptEnhancementService";
 import { inputReducer, initialState } from "./reducer";
@@ -67,6 +68,7 @@ const FloatingPromptInputInner = (
   const [state, dispatch] = useReducer(inputReducer, {
     ...initialState,
     selectedModel: parseSessionModel(sessionModel) || defaultModel,
+    modelExplicit: parseSessionModel(sessionModel) !== null,
     executionEngineConfig: externalEngineConfig || initialState.executionEngineConfig,
   });
 
@@ -200,6 +202,14 @@ const FloatingPromptInputInner = (
     clearHistory,
   } = useEnhancementHistory();
 
+  // 提示词 token 数（按当前引擎的模型选择词表）
+  const engineConfig = state.executionEngineConfig;
+  const tokenModel =
+    engineConfig.engine === 'codex' ? engineConfig.codexModel || 'gpt-5-codex'
+    : engineConfig.engine === 'gemini' ? engineConfig.geminiModel || 'gemini'
+    : state.selectedModel;
+  const promptTokens = usePromptTokenCount(state.prompt, tokenModel);
+
   // 🆕 历史面板状态
   const [showHistoryPanel, setShowHistoryPanel] = useState(false);
 
@@ -402,7 +412,8 @@ const FloatingPromptInputInner = (
 
         finalPrompt = finalPrompt + (finalPrompt.endsWith(' ') || finalPrompt === '' ? '' : ' ') + imagePathMentions;
       }
-      onSend(finalPrompt, state.selectedModel, undefined);
+      // Leave the model to the project defaults unless the user picked one
+      onSend(finalPrompt, state.selectedModel, undefined, state.modelExplicit);
       dispatch({ type: "RESET_INPUT" });
       setImageAttachments([]);
       setEmbeddedImages([]);
@@ -548,6 +559,7 @@ const FloatingPromptInputInner = (
             onExpand={() => dispatch({ type: "SET_EXPANDED", payload: true })}
             onFileSelect={handleFileSelect}
             onFilePickerClose={handleFilePickerClose}
+            tokenCount={promptTokens}
             // 🔧 Mac 输入法兼容
             onCompositionStart={() => setIsComposing(true)}
             onCompositionEnd={() => {
@@ -3,6 +3,8 @@ import { ModelType, ThinkingMode, ExecutionEngineConfig } from "./types";
 export interface InputState {
   prompt: string;
   selectedModel: ModelType;
+  /** Whether the model was picked by the user (or restored from the session) rather than the default */
+  modelExplicit: boolean;
   selectedThinkingMode: ThinkingMode;
   isExpanded: boolean;
   showCostPopover: boolean;
@@ -25,6 +27,7 @@ export type InputAction =
 export const initialState: InputState = {
   prompt: "",
   selectedModel: "sonnet",
+  modelExplicit: false,
   selectedThinkingMode: "on",
   isExpanded: false,
   showCostPopover: false,
@@ -42,7 +45,7 @@ export function inputReducer(state: InputState, action: InputAction): InputState
     case "SET_PROMPT":
       return { ...state, prompt: action.payload };
     case "SET_MODEL":
-      return { ...state, selectedModel: action.payload };
+      return { ...state, selectedModel: action.payload, modelExplicit: true };
     case "SET_THINKING_MODE":
       return { ...state, selectedThinkingMode: action.payload };
     case "SET_EXPANDED":
@@ -53,9 +53,10 @@ export type ExecutionEngineConfig = import('@/components/ExecutionEngineSelector
  */
 export interface FloatingPromptInputProps {
   /**
-   * Callback when prompt is sent - includes maxThinkingTokens separately
+   * Callback when prompt is sent - includes maxThinkingTokens separately.
+   * `modelExplicit` is false when the model is only the selector default.
    */
-  onSend: (prompt: string, model: ModelType, maxThinkingTokens?: number) => void;
+  onSend: (prompt: string, model: ModelType, maxThinkingTokens?: number, modelExplicit?: boolean) => void;
   /**
    * Whether the input is loading
    */
@@ -133,7 +133,7 @@ export default function GeminiProviderManager({ onBack }: GeminiProviderManagerP
       setTesting(config.id);
       const baseUrl = extractBaseUrlFromEnv(config.env);
       const apiKey = extractApiKeyFromEnv(config.env);
-      const message = await api.testGeminiProviderConnection(baseUrl, apiKey);
+      const message = await api.testGeminiProviderConnection(baseUrl, apiKey, config.id);
       setToastMessage({ message, type: 'success' });
     } catch (error) {
       console.error('Failed to test Gemini connection:', error);
@@ -59,7 +59,7 @@ export const MCPMultiProjectDialog: React.FC<MCPMultiProjectDialogProps> = ({
 
   // 加载项目列表
   useEffect(() => {
-    if (open && (engine === "claude" || engine === "codex")) {
+    if (open) {
       loadProjects();
     }
   }, [open, serverName, engine]);
@@ -33,7 +33,7 @@ export const MCPServerEditDialog: React.FC<MCPServerEditDialogProps> = ({
   open,
   onOpenChange,
   onSave,
-  selectedEngine: _selectedEngine,
+  selectedEngine,
 }) => {
   const [activeTab, setActiveTab] = useState("general");
   const [name, setName] = useState("");
@@ -42,6 +42,10 @@ export const MCPServerEditDialog: React.FC<MCPServerEditDialogProps> = ({
   const [url, setUrl] = useState("");
   const [envVars, setEnvVars] = useState<EnvVar[]>([]);
   const [enabled, setEnabled] = useState(true);
+  const [toolTimeout, setToolTimeout] = useState("");
+
+  // Claude 不支持按服务器设置超时
+  const supportsTimeout = selectedEngine === "codex" || selectedEngine === "gemini";
 
   // Reset form when server changes
   useEffect(() => {
@@ -51,6 +55,7 @@ export const MCPServerEditDialog: React.FC<MCPServerEditDialogProps> = ({
       setArgs(server.args || []);
       setUrl(server.url || "");
       setEnabled(server.enabled);
+      setToolTimeout(server.tool_timeout_sec != null ? String(server.tool_timeout_sec) : "");
       setEnvVars(
         Object.entries(server.env || {}).map(([key, value], idx) => ({
           id: `env-${idx}`,
@@ -66,6 +71,7 @@ export const MCPServerEditDialog: React.FC<MCPServerEditDialogProps> = ({
       setUrl("");
       setEnvVars([]);
       setEnabled(true);
+      setToolTimeout("");
     }
   }, [server]);
 
@@ -113,6 +119,7 @@ export const MCPServerEditDialog: React.FC<MCPServerEditDialogProps> = ({
         return acc;
       }, {} as Record<string, string>),
       enabled,
+      tool_timeout_sec: toolTimeout.trim() ? Number(toolTimeout) : server.tool_timeout_sec,
     };
 
     onSave(updatedServer);
@@ -241,6 +248,23 @@ export const MCPServerEditDialog: React.FC<MCPServerEditDialogProps> = ({
               </div>
             </div>
 
+            {/* Tool Timeout (Codex / Gemini) */}
+            {supportsTimeout && (
+              <div className="space-y-2">
+                <Label className="flex items-center gap-2">
+                  <span className="text-muted-foreground">⏱</span>
+                  工具调用超时（秒）
+                </Label>
+                <Input
+                  type="number"
+                  min={1}
+                  value={toolTimeout}
+                  onChange={(e) => setToolTimeout(e.target.value)}
+                  placeholder={selectedEngine === "gemini" ? "600" : "60"}
+                />
+              </div>
+            )}
+
             {/* Environment Variables */}
             <div className="space-y-2">
               <div className="flex items-center justify-between">
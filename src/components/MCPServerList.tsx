@@ -303,21 +303,19 @@ export const MCPServerList: React.FC<MCPServerListProps> = ({
               />
             </div>
 
-            {/* Multi-Project Button (for Claude and Codex engines) */}
-            {(selectedEngine === "claude" || selectedEngine === "codex") && (
-              <Button
-                variant="outline"
-                size="sm"
-                onClick={(e) => {
-                  e.stopPropagation();
-                  handleOpenMultiProject(server.name);
-                }}
-                className="h-8 gap-1 text-xs hover:bg-blue-500/10 hover:text-blue-600 hover:border-blue-500/50"
-              >
-                <FolderGit2 className="h-3 w-3" />
-                多项目
-              </Button>
-            )}
+            {/* Multi-Project Button */}
+            <Button
+              variant="outline"
+              size="sm"
+              onClick={(e) => {
+                e.stopPropagation();
+                handleOpenMultiProject(server.name);
+              }}
+              className="h-8 gap-1 text-xs hover:bg-blue-500/10 hover:text-blue-600 hover:border-blue-500/50"
+            >
+              <FolderGit2 className="h-3 w-3" />
+              多项目
+            </Button>
 
             {/* Test Button */}
             <Button
@@ -132,7 +132,7 @@ export const MultiEngineStatusIndicator: React.FC<MultiEngineStatusIndicatorProp
                             <Button
                               variant="ghost"
                               size="sm"
-                              onClick={() => refreshEngine(engine.type)}
+                              onClick={() => refreshEngine(engine.type, true)}
                               disabled={isCurrentlyRefreshing}
                               className="h-7 w-7 p-0"
                             >
@@ -6,7 +6,15 @@ import { Input } from './ui/input';
 import { Label } from './ui/label';
 import { Badge } from './ui/badge';
 import { Alert, AlertDescription } from './ui/alert';
-import { api, type TranslationConfig, type TranslationCacheStats } from '@/lib/api';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from './ui/select';
+import {
+  api,
+  type PromptTranslationSettings,
+  type TranslationConfig,
+  type TranslationCacheStats,
+  type TranslationProvider,
+  type TranslationProviderInfo,
+} from '@/lib/api';
 import { translationMiddleware } from '@/lib/translationMiddleware';
 import { Loader2, RefreshCw, Settings, Languages, Database, AlertTriangle } from 'lucide-react';
 
@@ -17,6 +25,7 @@ interface TranslationSettingsProps {
 export const TranslationSettings: React.FC<TranslationSettingsProps> = ({ onClose }) => {
   const [config, setConfig] = useState<TranslationConfig | null>(null);
   const [cacheStats, setCacheStats] = useState<TranslationCacheStats | null>(null);
+  const [providers, setProviders] = useState<TranslationProviderInfo[]>([]);
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const [testingConnection, setTestingConnection] = useState(false);
@@ -34,13 +43,15 @@ export const TranslationSettings: React.FC<TranslationSettingsProps> = ({ onClos
       setLoading(true);
       setError(null);
       
-      const [configData, statsData] = await Promise.all([
+      const [configData, statsData, providerList] = await Promise.all([
         api.getTranslationConfig(),
-        api.getTranslationCacheStats().catch(() => null) // 缓存统计可能失败
+        api.getTranslationCacheStats().catch(() => null), // 缓存统计可能失败
+        api.listTranslationProviders().catch(() => [])
       ]);
       
       setConfig(configData);
       setCacheStats(statsData);
+      setProviders(providerList);
     } catch (err) {
       setError(err instanceof Error ? err.message : '加载翻译设置失败');
       console.error('Failed to load translation settings:', err);
@@ -120,6 +131,35 @@ export const TranslationSettings: React.FC<TranslationSettingsProps> = ({ onClos
     setConfig({ ...config, [key]: value });
   };
 
+  // 切换提供方时同时切换到该提供方的默认 API 地址
+  const handleProviderChange = (provider: TranslationProvider) => {
+    if (!config) return;
+    const info = providers.find((p) => p.id === provider);
+    setConfig({ ...config, provider, api_base_url: info?.default_base_url ?? config.api_base_url });
+  };
+
+  const defaultPipeline: PromptTranslationSettings = {
+    enabled: false,
+    direction: 'both',
+    user_lang: 'zh',
+    engine_lang: 'en',
+    min_prompt_chars: 2,
+    max_prompt_chars: 8000,
+    max_response_chars: 6000,
+  };
+
+  const handlePipelineChange = <K extends keyof PromptTranslationSettings>(
+    key: K,
+    value: PromptTranslationSettings[K]
+  ) => {
+    if (!config) return;
+    setConfig({ ...config, pipeline: { ...defaultPipeline, ...config.pipeline, [key]: value } });
+  };
+
+  const pipeline = { ...defaultPipeline, ...config?.pipeline };
+
+  const currentProvider = providers.find((p) => p.id === (config?.provider ?? 'openai'));
+
   if (loading) {
     return (
       <div className="flex items-center justify-center p-8">
@@ -188,14 +228,49 @@ export const TranslationSettings: React.FC<TranslationSettingsProps> = ({ onClos
             />
           </div>
 
+          <div className="flex items-center justify-between">
+            <div>
+              <Label htmlFor="translation-protect-code" className="text-sm font-medium">
+                保护代码和标识符
+              </Label>
+              <p className="text-xs text-muted-foreground">代码块、行内代码、URL、变量名等保持原样不翻译</p>
+            </div>
+            <Switch
+              id="translation-protect-code"
+              checked={config.protect_code ?? true}
+              onCheckedChange={(protect) => handleConfigChange('protect_code', protect)}
+            />
+          </div>
+
           <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+            {providers.length > 0 && (
+              <div className="space-y-2 md:col-span-2">
+                <Label htmlFor="translation-provider">翻译服务</Label>
+                <Select
+                  value={config.provider ?? 'openai'}
+                  onValueChange={(value) => handleProviderChange(value as TranslationProvider)}
+                >
+                  <SelectTrigger id="translation-provider">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {providers.map((provider) => (
+                      <SelectItem key={provider.id} value={provider.id}>
+                        {provider.label}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+            )}
+
             <div className="space-y-2">
               <Label htmlFor="api-base-url">API 基础URL</Label>
               <Input
                 id="api-base-url"
                 value={config.api_base_url}
                 onChange={(e) => handleConfigChange('api_base_url', e.target.value)}
-                placeholder="https://api.siliconflow.cn/v1"
+                placeholder={currentProvider?.default_base_url ?? "https://api.siliconflow.cn/v1"}
               />
             </div>
 
@@ -206,6 +281,7 @@ export const TranslationSettings: React.FC<TranslationSettingsProps> = ({ onClos
                 value={config.model}
                 onChange={(e) => handleConfigChange('model', e.target.value)}
                 placeholder="tencent/Hunyuan-MT-7B"
+                disabled={currentProvider ? !currentProvider.uses_model : false}
               />
             </div>
 
@@ -302,6 +378,85 @@ export const TranslationSettings: React.FC<TranslationSettingsProps> = ({ onClos
         </CardContent>
       </Card>
 
+      {/* 双向翻译 */}
+      <Card>
+        <CardHeader>
+          <CardTitle className="flex items-center space-x-2">
+            <Languages className="h-5 w-5" />
+            <span>提示词双向翻译</span>
+          </CardTitle>
+          <CardDescription>
+            发送前把中文提示词翻译成英文，引擎回复再翻译回中文；原文和译文都保存在会话记录中
+          </CardDescription>
+        </CardHeader>
+        <CardContent className="space-y-4">
+          <div className="flex items-center justify-between">
+            <Label htmlFor="pipeline-enabled" className="text-sm font-medium">
+              启用双向翻译
+            </Label>
+            <Switch
+              id="pipeline-enabled"
+              checked={pipeline.enabled}
+              onCheckedChange={(enabled) => handlePipelineChange('enabled', enabled)}
+            />
+          </div>
+
+          <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+            <div className="space-y-2 md:col-span-2">
+              <Label htmlFor="pipeline-direction">翻译方向</Label>
+              <Select
+                value={pipeline.direction}
+                onValueChange={(value) =>
+                  handlePipelineChange('direction', value as PromptTranslationSettings['direction'])
+                }
+              >
+                <SelectTrigger id="pipeline-direction">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="both">提示词和回复</SelectItem>
+                  <SelectItem value="prompt_only">仅提示词</SelectItem>
+                  <SelectItem value="response_only">仅回复</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="pipeline-min-prompt">提示词最短长度（字符）</Label>
+              <Input
+                id="pipeline-min-prompt"
+                type="number"
+                value={pipeline.min_prompt_chars}
+                onChange={(e) => handlePipelineChange('min_prompt_chars', parseInt(e.target.value) || 0)}
+                min="0"
+              />
+            </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="pipeline-max-prompt">提示词最大长度（字符）</Label>
+              <Input
+                id="pipeline-max-prompt"
+                type="number"
+                value={pipeline.max_prompt_chars}
+                onChange={(e) => handlePipelineChange('max_prompt_chars', parseInt(e.target.value) || 8000)}
+                min="100"
+              />
+            </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="pipeline-max-response">回复最大长度（字符）</Label>
+              <Input
+                id="pipeline-max-response"
+                type="number"
+                value={pipeline.max_response_chars}
+                onChange={(e) => handlePipelineChange('max_response_chars', parseInt(e.target.value) || 6000)}
+                min="100"
+              />
+            </div>
+          </div>
+        </CardContent>
+      </Card>
+
       {/* 缓存管理 */}
       <Card>
         <CardHeader>
@@ -8,7 +8,7 @@ import {
   SelectValue,
 } from '@/components/ui/select';
 import { api } from '@/lib/api';
-import type { ReasoningModeOption, CodexModelOption } from '@/types/codex-selector';
+import type { ReasoningModeOption, CodexModelOption, ReasoningSuggestion } from '@/types/codex-selector';
 
 interface CodexCompactSelectorProps {
   /** 当前配置 */
@@ -20,8 +20,15 @@ interface CodexCompactSelectorProps {
   onConfigChange: (config: { model: string; reasoningMode: string }) => void;
   /** 是否禁用 */
   disabled?: boolean;
+  /** 项目路径（用于推荐推理模式） */
+  projectPath?: string;
+  /** 当前输入的提示词（用于推荐推理模式） */
+  prompt?: string;
 }
 
+/** 输入停止后多久请求推荐 */
+const SUGGESTION_DEBOUNCE_MS = 800;
+
 /**
  * Codex 紧凑选择器组件
  * 在底部工具栏显示模型和推理模式选择
@@ -30,16 +37,42 @@ export const CodexCompactSelector: React.FC<CodexCompactSelectorProps> = ({
   config,
   onConfigChange,
   disabled = false,
+  projectPath,
+  prompt,
 }) => {
   const [reasoningModes, setReasoningModes] = useState<ReasoningModeOption[]>([]);
   const [models, setModels] = useState<CodexModelOption[]>([]);
   const [loading, setLoading] = useState(true);
+  const [suggestion, setSuggestion] = useState<ReasoningSuggestion | null>(null);
 
   // 加载配置选项
   useEffect(() => {
     loadCapabilities();
   }, []);
 
+  // 根据历史执行结果推荐推理模式（输入停止后再请求）
+  useEffect(() => {
+    const text = prompt?.trim();
+    if (!projectPath || !text) {
+      setSuggestion(null);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      api.suggestReasoningMode(projectPath, text, config.model)
+        .then((result) => {
+          if (!cancelled) setSuggestion(result);
+        })
+        .catch(() => {
+          if (!cancelled) setSuggestion(null);
+        });
+    }, SUGGESTION_DEBOUNCE_MS);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [projectPath, prompt, config.model]);
+
   const loadCapabilities = async () => {
     try {
       setLoading(true);
@@ -176,8 +209,16 @@ export const CodexCompactSelector: React.FC<CodexCompactSelectorProps> = ({
         </SelectTrigger>
         <SelectContent>
           {availableReasoningModes.map((mode) => (
-            <SelectItem key={mode.value} value={mode.value} className="text-xs">
+            <SelectItem
+              key={mode.value}
+              value={mode.value}
+              className="text-xs"
+              title={suggestion?.mode === mode.value ? suggestion.reason : undefined}
+            >
               {mode.label}
+              {suggestion?.mode === mode.value && (
+                <span className="ml-1.5 text-[10px] text-primary">推荐</span>
+              )}
             </SelectItem>
           ))}
         </SelectContent>
@@ -0,0 +1,114 @@
+/**
+ * DeepLinkConfirmDialog - 深度链接确认对话框
+ *
+ * anycode:// 链接带有提示词时，后端不会直接执行，而是发送 deep-link-pending 事件，
+ * 由此对话框展示项目、引擎和提示词，用户确认后再执行（链接可能来自浏览器中的任意网页）
+ */
+
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { message } from "@tauri-apps/plugin-dialog";
+import { Link2, Play, XCircle } from "lucide-react";
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { api, type DeepLinkRequest } from "@/lib/api";
+
+interface DeepLinkEventPayload {
+  request: DeepLinkRequest;
+  error?: string | null;
+}
+
+/**
+ * 监听 deep-link-pending 并在确认后执行请求
+ */
+export function DeepLinkConfirmDialog() {
+  const [request, setRequest] = useState<DeepLinkRequest | null>(null);
+  const [running, setRunning] = useState(false);
+
+  useEffect(() => {
+    const unlisten = listen<DeepLinkEventPayload>("deep-link-pending", (event) => {
+      setRequest(event.payload.request);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleClose = () => {
+    if (!running) setRequest(null);
+  };
+
+  const handleRun = async () => {
+    if (!request) return;
+    setRunning(true);
+    try {
+      await api.runDeepLinkRequest(request);
+      setRequest(null);
+    } catch (error) {
+      await message(String(error), { title: "执行链接请求失败", kind: "error" });
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <Dialog open={request !== null} onOpenChange={(isOpen) => !isOpen && handleClose()}>
+      <DialogContent className="sm:max-w-xl max-h-[80vh] flex flex-col">
+        <DialogHeader>
+          <div className="flex items-center gap-2">
+            <div className="h-10 w-10 rounded-full bg-amber-500/10 flex items-center justify-center">
+              <Link2 className="h-5 w-5 text-amber-500" />
+            </div>
+            <div>
+              <DialogTitle>执行来自链接的提示词？</DialogTitle>
+              <DialogDescription>
+                该请求由 anycode:// 链接发起，请确认内容后再执行
+              </DialogDescription>
+            </div>
+          </div>
+        </DialogHeader>
+
+        {request && (
+          <div className="space-y-3 text-sm min-h-0 flex flex-col">
+            <div className="grid grid-cols-[5rem_1fr] gap-x-3 gap-y-1">
+              <span className="text-muted-foreground">项目</span>
+              <span className="font-mono break-all">{request.projectPath || "-"}</span>
+              <span className="text-muted-foreground">引擎</span>
+              <span>{request.engine || "claude"}</span>
+              {request.model && (
+                <>
+                  <span className="text-muted-foreground">模型</span>
+                  <span>{request.model}</span>
+                </>
+              )}
+            </div>
+            <ScrollArea className="max-h-[40vh] rounded-md border bg-muted/30">
+              <pre className="p-3 whitespace-pre-wrap break-words font-mono text-xs">
+                {request.prompt}
+              </pre>
+            </ScrollArea>
+          </div>
+        )}
+
+        <DialogFooter className="gap-2">
+          <Button variant="outline" onClick={handleClose} disabled={running}>
+            <XCircle className="h-4 w-4 mr-2" />
+            忽略
+          </Button>
+          <Button onClick={handleRun} disabled={running}>
+            <Play className="h-4 w-4 mr-2" />
+            {running ? "执行中..." : "执行"}
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -5,6 +5,7 @@ import { useUpdate } from '@/contexts/UpdateContext';
 import { message } from '@tauri-apps/plugin-dialog';
 import { UpdateDialog } from '@/components/dialogs/UpdateDialog';
 import { AboutDialog } from '@/components/dialogs/AboutDialog';
+import { DeepLinkConfirmDialog } from '@/components/dialogs/DeepLinkConfirmDialog';
 
 interface AppLayoutProps {
   children: ReactNode;
@@ -108,6 +109,8 @@ export const AppLayout: React.FC<AppLayoutProps> = ({ children }) => {
         onClose={() => setShowAboutDialog(false)}
         onCheckUpdate={handleCheckUpdate}
       />
+
+      <DeepLinkConfirmDialog />
     </div>
   );
 };
@@ -21,7 +21,7 @@ interface StreamMessageV2Props {
   sessionId?: string;
   projectId?: string;
   projectPath?: string;
-  onRevert?: (promptIndex: number, mode: RewindMode) => void;
+  onRevert?: (promptIndex: number, mode: RewindMode, partial?: boolean) => void;
 }
 
 // Message renderer strategy map
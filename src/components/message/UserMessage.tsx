@@ -6,6 +6,7 @@ import { Button } from "@/components/ui/button";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
 import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription, DialogFooter } from "@/components/ui/dialog";
 import { Alert, AlertDescription } from "@/components/ui/alert";
+import { Checkbox } from "@/components/ui/checkbox";
 import { cn } from "@/lib/utils";
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { RewindCapabilities, RewindMode } from '@/lib/api';
@@ -27,7 +28,7 @@ interface UserMessageProps {
   /** Project Path (for Gemini rewind) */
   projectPath?: string;
   /** 撤回回调 */
-  onRevert?: (promptIndex: number, mode: RewindMode) => void;
+  onRevert?: (promptIndex: number, mode: RewindMode, partial?: boolean) => void;
 }
 
 /**
@@ -225,6 +226,8 @@ export const UserMessage: React.FC<UserMessageProps> = ({
   const [showConfirmDialog, setShowConfirmDialog] = useState(false);
   const [capabilities, setCapabilities] = useState<RewindCapabilities | null>(null);
   const [isLoadingCapabilities, setIsLoadingCapabilities] = useState(false);
+  // 提示词混有手动修改时，默认只撤销 Codex 的修改
+  const [partialRevert, setPartialRevert] = useState(true);
 
   // 🆕 折叠功能相关状态
   const [isExpanded, setIsExpanded] = useState(false);
@@ -308,6 +311,7 @@ export const UserMessage: React.FC<UserMessageProps> = ({
           ? await api.checkGeminiRewindCapabilities(sessionId, projectPath!, promptIndex)
           : await api.checkRewindCapabilities(sessionId, projectId!, promptIndex);
         setCapabilities(caps);
+        setPartialRevert(true);
       } catch (error) {
         console.error('Failed to check rewind capabilities:', error);
       } finally {
@@ -329,7 +333,8 @@ export const UserMessage: React.FC<UserMessageProps> = ({
   const handleConfirmRevert = (mode: RewindMode) => {
     if (promptIndex !== undefined && onRevert) {
       setShowConfirmDialog(false);
-      onRevert(promptIndex, mode);
+      const partial = mode !== "conversation_only" && !!capabilities?.mixed && partialRevert;
+      onRevert(promptIndex, mode, partial || undefined);
     }
   };
 
@@ -527,6 +532,11 @@ export const UserMessage: React.FC<UserMessageProps> = ({
               {!isLoadingCapabilities && capabilities && (
                 <div className="space-y-3">
                   <div className="text-sm font-medium">选择撤回内容：</div>
+                  {capabilities.strategy === "snapshot" && (
+                    <div className="text-xs text-muted-foreground">
+                      此项目不是 Git 仓库，代码将从发送此提示词前的文件快照恢复
+                    </div>
+                  )}
 
                   {/* 模式1: 仅对话 */}
                   <div className={cn(
@@ -602,6 +612,23 @@ export const UserMessage: React.FC<UserMessageProps> = ({
                       </div>
                     </div>
                   </div>
+
+                  {/* 混有手动修改时：按变更记录部分回滚 */}
+                  {capabilities.mixed && (
+                    <label className="flex items-start gap-2 text-sm cursor-pointer">
+                      <Checkbox
+                        className="mt-0.5"
+                        checked={partialRevert}
+                        onCheckedChange={(v) => setPartialRevert(!!v)}
+                      />
+                      <span>
+                        仅撤销 Codex 的修改（保留手动修改）
+                        <span className="block text-xs text-muted-foreground">
+                          按变更记录逆向撤销，而不是重置到提交；无法自动合并的文件会中止回滚
+                        </span>
+                      </span>
+                    </label>
+                  )}
                 </div>
               )}
 
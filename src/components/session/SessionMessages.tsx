@@ -16,7 +16,7 @@ interface SessionMessagesProps {
   effectiveSession: any;
   getPromptIndexForMessage: (index: number) => number;
   handleLinkDetected: (url: string) => void;
-  handleRevert: (promptIndex: number, mode: RewindMode) => void;
+  handleRevert: (promptIndex: number, mode: RewindMode, partial?: boolean) => void;
   error?: string | null;
   parentRef: React.RefObject<HTMLDivElement>;
 }
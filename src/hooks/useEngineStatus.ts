@@ -25,8 +25,8 @@ export interface UseEngineStatusReturn {
   /** 更新状态映射 */
   isUpdating: Record<EngineType, boolean>;
   
-  /** 刷新指定引擎 */
-  refreshEngine: (engine: EngineType) => Promise<void>;
+  /** 刷新指定引擎（force 时先清除后端检测缓存） */
+  refreshEngine: (engine: EngineType, force?: boolean) => Promise<void>;
   
   /** 刷新所有引擎 */
   refreshAllEngines: () => Promise<void>;
@@ -176,7 +176,7 @@ export function useEngineStatus(): UseEngineStatusReturn {
   /**
    * 刷新指定引擎
    */
-  const refreshEngine = useCallback(async (engine: EngineType) => {
+  const refreshEngine = useCallback(async (engine: EngineType, force = false) => {
     // 防止重复刷新
     if (isRefreshing[engine]) {
       console.log(`[useEngineStatus] ${engine} is already refreshing`);
@@ -190,6 +190,11 @@ export function useEngineStatus(): UseEngineStatusReturn {
     }));
     
     try {
+      if (force) {
+        await api.invalidateEngineCache(engine).catch(error => {
+          console.warn(`[useEngineStatus] Failed to invalidate ${engine} cache:`, error);
+        });
+      }
       const status = await checkEngine(engine);
       
       setEngineStatuses(prev => ({
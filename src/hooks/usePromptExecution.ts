@@ -12,9 +12,10 @@
  * Extracted from ClaudeCodeSession component (296 lines)
  */
 
-import { useCallback, useRef, useEffect } from 'react';
+import { useCallback, useRef, useEffect, useState } from 'react';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
-import { api, type Session } from '@/lib/api';
+import { api, type AutoFixEvent, type AutoFixOptions, type Session } from '@/lib/api';
+import { listenOutputLines } from '@/lib/streamEvents';
 import { translationMiddleware, isSlashCommand, type TranslationResult } from '@/lib/translationMiddleware';
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { ModelType } from '@/components/FloatingPromptInput/types';
@@ -55,6 +56,7 @@ interface QueuedPrompt {
   id: string;
   prompt: string;
   model: ModelType;
+  modelExplicit?: boolean;
 }
 
 interface UsePromptExecutionConfig {
@@ -77,6 +79,7 @@ interface UsePromptExecutionConfig {
   codexReasoningMode?: string;          // Codex 推理模式 (e.g., 'medium', 'high')
   geminiModel?: string;                 // Gemini 模型 (e.g., 'gemini-2.5-pro')
   geminiApprovalMode?: 'auto_edit' | 'yolo' | 'default'; // Gemini 审批模式
+  autoFix?: AutoFixOptions;             // 自动修复循环（完成后由后端验证并发送修复提示词）
 
   // Refs
   hasActiveSessionRef: React.MutableRefObject<boolean>;
@@ -101,7 +104,11 @@ interface UsePromptExecutionConfig {
 }
 
 interface UsePromptExecutionReturn {
-  handleSendPrompt: (prompt: string, model: ModelType, maxThinkingTokens?: number) => Promise<void>;
+  handleSendPrompt: (prompt: string, model: ModelType, maxThinkingTokens?: number, modelExplicit?: boolean) => Promise<void>;
+  /** 当前会话自动修复循环的最新阶段（没有循环时为 null） */
+  autoFixEvent: AutoFixEvent | null;
+  /** 取消当前会话的自动修复循环 */
+  cancelAutoFix: () => Promise<void>;
 }
 
 // ============================================================================
@@ -125,6 +132,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     codexReasoningMode,          // 🆕 Codex 推理模式
     geminiModel,                 // 🆕 Gemini 模型
     geminiApprovalMode,          // 🆕 Gemini 审批模式
+    autoFix,
     hasActiveSessionRef,
     unlistenRefs,
     isMountedRef,
@@ -151,6 +159,111 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     isPlanModeRef.current = isPlanMode;
   }, [isPlanMode]);
 
+  // ============================================================================
+  // 🆕 自动修复循环（后端编排）
+  // 引擎完成后后端执行验证命令，失败时续接同一会话发送修复提示词，阶段通过 `auto-fix` 事件通知。
+  // 循环进行中新的提示词进入队列，循环结束后再发送。
+  // ============================================================================
+  const [autoFixEvent, setAutoFixEvent] = useState<AutoFixEvent | null>(null);
+  const autoFixActiveRef = useRef(false);
+  // 本会话开启了自动修复的引擎会话 ID（完成时登记）
+  const autoFixSessionIdsRef = useRef(new Set<string>());
+  // 登记之前收到的事件（验证可能在前端处理完成事件之前开始）
+  const unclaimedAutoFixEventsRef = useRef(new Map<string, AutoFixEvent>());
+
+  const applyAutoFixEvent = useCallback((event: AutoFixEvent) => {
+    if (!isMountedRef.current) return;
+    const active = event.phase === 'verifying' || event.phase === 'fixing';
+    autoFixActiveRef.current = active;
+    setAutoFixEvent(active ? event : null);
+    if (!active) {
+      autoFixSessionIdsRef.current.delete(event.sessionId);
+    }
+  }, [isMountedRef]);
+
+  useEffect(() => {
+    let unlisten: UnlistenFn | undefined;
+    let disposed = false;
+    listen<AutoFixEvent>('auto-fix', (evt) => {
+      const event = evt.payload;
+      if (autoFixSessionIdsRef.current.has(event.sessionId)) {
+        applyAutoFixEvent(event);
+      } else if (autoFix) {
+        unclaimedAutoFixEventsRef.current.set(event.sessionId, event);
+      }
+    }).then((fn) => {
+      if (disposed) fn();
+      else unlisten = fn;
+    });
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [autoFix, applyAutoFixEvent]);
+
+  const claimAutoFixSession = useCallback((sessionId: string) => {
+    autoFixSessionIdsRef.current.add(sessionId);
+    const early = unclaimedAutoFixEventsRef.current.get(sessionId);
+    unclaimedAutoFixEventsRef.current.delete(sessionId);
+    if (early) applyAutoFixEvent(early);
+  }, [applyAutoFixEvent]);
+
+  const cancelAutoFix = useCallback(async () => {
+    const sessionId = autoFixEvent?.sessionId;
+    if (!sessionId) return;
+    try {
+      await api.cancelAutoFix(sessionId);
+    } catch (err) {
+      console.warn('[usePromptExecution] Failed to cancel auto-fix loop:', err);
+    }
+  }, [autoFixEvent]);
+
+  // ============================================================================
+  // 🆕 提示词完成后的后处理（格式化 / Lint / 测试）
+  // 有命令失败且项目开启 autoFollowUp 时，把跟进提示词插到队列最前面；
+  // 跟进提示词本身完成后不再自动跟进，避免循环。
+  // 开启自动修复时由后端的验证代替（验证命令默认就是后处理命令），这里只登记会话。
+  // ============================================================================
+  const pendingFollowUpRef = useRef<string | null>(null);
+
+  const runPostProcessors = useCallback(async (
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    promptIndex: number,
+    model: ModelType
+  ) => {
+    if (autoFix) {
+      claimAutoFixSession(sessionId);
+      return;
+    }
+    const wasFollowUp = pendingFollowUpRef.current !== null;
+    pendingFollowUpRef.current = null;
+
+    try {
+      const outcome = await api.runPostProcessors(sessionId, engine, projectPath, promptIndex);
+      if (!outcome) return;
+      console.log('[usePromptExecution] Post-processors finished:', {
+        engine,
+        promptIndex,
+        passed: outcome.record.passed,
+      });
+
+      if (outcome.followUpPrompt && !wasFollowUp) {
+        const followUp: QueuedPrompt = {
+          id: `${Date.now()}-${Math.random().toString(36).substr(2, 9)}`,
+          prompt: outcome.followUpPrompt,
+          model
+        };
+        // Update the ref synchronously so the completion handler picks it up right away
+        queuedPromptsRef.current = [followUp, ...queuedPromptsRef.current];
+        setQueuedPrompts(queuedPromptsRef.current);
+        pendingFollowUpRef.current = followUp.prompt;
+      }
+    } catch (err) {
+      console.warn('[usePromptExecution] Failed to run post-processors:', err);
+    }
+  }, [projectPath, queuedPromptsRef, setQueuedPrompts, autoFix, claimAutoFixSession]);
+
   // ============================================================================
   // Main Prompt Execution Function
   // ============================================================================
@@ -158,7 +271,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
   const handleSendPrompt = useCallback(async (
     prompt: string,
     model: ModelType,
-    maxThinkingTokens?: number
+    maxThinkingTokens?: number,
+    modelExplicit?: boolean
   ) => {
     console.log('[usePromptExecution] handleSendPrompt called with:', {
       prompt,
@@ -193,12 +307,13 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
 
     console.log('[usePromptExecution] Using model:', model);
 
-    // If already loading, queue the prompt
-    if (isLoading || externalIsStreaming) {
+    // If already loading (or an auto-fix loop is running), queue the prompt
+    if (isLoading || externalIsStreaming || autoFixActiveRef.current) {
       const newPrompt: QueuedPrompt = {
         id: `${Date.now()}-${Math.random().toString(36).substr(2, 9)}`,
         prompt,
-        model
+        model,
+        modelExplicit
       };
       setQueuedPrompts(prev => [...prev, newPrompt]);
       return;
@@ -231,6 +346,19 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         promptIndex: undefined as number | undefined,
       } : undefined;
       
+      // 恢复长会话前按配置压缩历史（需在记录提示词之前完成，压缩会改变提示词序号）
+      if (effectiveSession && !isFirstPrompt && isUserInitiated) {
+        try {
+          const compaction = await api.compactSessionBeforeResume(executionEngine, effectiveSession.id, projectPath);
+          if (compaction) {
+            console.log('[usePromptExecution] Compacted session history:', compaction.summarizedTurns, 'turns,',
+              compaction.tokensBefore, '->', compaction.tokensAfter, 'tokens');
+          }
+        } catch (err) {
+          console.warn('[usePromptExecution] History compaction failed, resuming with full history:', err);
+        }
+      }
+
       // 对于已有会话，立即记录；对于新会话，在收到 session_id 后记录
       if (effectiveSession && isUserInitiated) {
         try {
@@ -499,6 +627,42 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             Array.from(trackedCodexFileTools.keys()).forEach((id) => finalizeToolChange(id));
           };
 
+          // Codex shell commands: the backend snapshots the files a command may touch when it starts
+          // (before the start event reaches us); record the files it changed when it completes
+          // (large repos may report "unknown").
+          const detectedCodexCommandIds = new Set<string>();
+
+          const trackCodexCommand = (toolUseId: string, command: string, completed: boolean) => {
+            const codexThreadId = codexPendingInfo?.sessionId;
+            if (!completed || !codexThreadId || detectedCodexCommandIds.has(toolUseId)) return;
+
+            detectedCodexCommandIds.add(toolUseId);
+            const callId = toolUseId.replace(/^codex_cmd_/, '');
+
+            (async () => {
+              try {
+                if (pendingPromptRecordingPromise) {
+                  await pendingPromptRecordingPromise;
+                }
+                const promptIndex = codexPendingInfo?.promptIndex;
+                if (promptIndex === undefined) return;
+
+                const result = await api.codexDetectCommandChanges(codexThreadId, callId, projectPath, promptIndex, command);
+                if (result.unknownReason) {
+                  console.warn('[CodexChangeTracker] Command changes unknown:', { toolUseId, reason: result.unknownReason });
+                } else if (result.changeIds.length > 0) {
+                  console.log('[CodexChangeTracker] Recorded command changes:', {
+                    toolUseId,
+                    changeIds: result.changeIds,
+                    promptIndex,
+                  });
+                }
+              } catch (err) {
+                console.warn('[CodexChangeTracker] Failed to detect command changes:', err);
+              }
+            })();
+          };
+
           // Helper function to generate a stable ID for deduplication.
           // Global + session-specific channels may deliver the same event with slightly different serialization,
           // so prefer fields from parsed JSON over raw string hashing.
@@ -723,6 +887,19 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
 
                   finalizeToolChange(toolUseId);
                 }
+
+                // 3) shell commands -> detect side effects
+                for (const block of contentBlocks) {
+                  if (block?.type !== 'tool_use' || typeof block?.id !== 'string') continue;
+                  if (!block.id.startsWith('codex_cmd_')) continue;
+                  const command = typeof block?.input?.command === 'string' ? block.input.command : '';
+                  if (!command) continue;
+
+                  const completed = contentBlocks.some(
+                    (b) => b?.type === 'tool_result' && b?.tool_use_id === block.id
+                  );
+                  trackCodexCommand(block.id, command, completed);
+                }
               }
 
               // Extract and save Codex thread_id from thread.started for session resuming
@@ -876,6 +1053,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             // 🆕 Record prompt completion for rewind support
             if (window.__codexPendingPrompt) {
               const pendingPrompt = window.__codexPendingPrompt;
+              // Run before recording completion so formatter edits belong to this prompt
+              await runPostProcessors('codex', pendingPrompt.sessionId, pendingPrompt.promptIndex, model);
               try {
                 await api.recordCodexPromptCompleted(
                   pendingPrompt.sessionId,
@@ -891,12 +1070,12 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             }
 
             // Process queued prompts
-            if (queuedPromptsRef.current.length > 0) {
+            if (queuedPromptsRef.current.length > 0 && !autoFixActiveRef.current) {
               const [nextPrompt, ...remainingPrompts] = queuedPromptsRef.current;
               setQueuedPrompts(remainingPrompts);
 
               setTimeout(() => {
-                handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
+                handleSendPrompt(nextPrompt.prompt, nextPrompt.model, undefined, nextPrompt.modelExplicit);
               }, 100);
             }
           };
@@ -905,7 +1084,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           const attachCodexSessionListeners = async (sessionId: string) => {
             console.log('[usePromptExecution] Attaching Codex session-specific listeners for:', sessionId);
 
-            const specificOutputUnlisten = await listen<string>(`codex-output:${sessionId}`, (evt) => {
+            const specificOutputUnlisten = await listenOutputLines(`codex-output:${sessionId}`, (evt) => {
               processCodexOutput(evt.payload);
             });
 
@@ -939,7 +1118,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           });
 
           // Global fallback listener: process only events that belong to this Codex thread
-          const codexOutputUnlisten = await listen<string>('codex-output', (evt) => {
+          const codexOutputUnlisten = await listenOutputLines('codex-output', (evt) => {
             if (!hasActiveSessionRef.current) return;
 
             // Once we have a session-specific channel, ignore the global fallback entirely to avoid duplicates.
@@ -1185,6 +1364,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             // 🆕 Record prompt completion for rewind support
             if (window.__geminiPendingPrompt) {
               const pendingPrompt = window.__geminiPendingPrompt;
+              // Run before recording completion so formatter edits belong to this prompt
+              await runPostProcessors('gemini', pendingPrompt.sessionId, pendingPrompt.promptIndex, model);
               try {
                 await api.recordGeminiPromptCompleted(
                   pendingPrompt.sessionId,
@@ -1203,12 +1384,12 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             delete window.__geminiPendingSession;
 
             // Process queued prompts
-            if (queuedPromptsRef.current.length > 0) {
+            if (queuedPromptsRef.current.length > 0 && !autoFixActiveRef.current) {
               const [nextPrompt, ...remainingPrompts] = queuedPromptsRef.current;
               setQueuedPrompts(remainingPrompts);
 
               setTimeout(() => {
-                handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
+                handleSendPrompt(nextPrompt.prompt, nextPrompt.model, undefined, nextPrompt.modelExplicit);
               }, 100);
             }
           };
@@ -1217,7 +1398,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           const attachGeminiSessionListeners = async (sessionId: string) => {
             console.log('[usePromptExecution] Attaching Gemini session-specific listeners for:', sessionId);
 
-            const specificOutputUnlisten = await listen<string>(`gemini-output:${sessionId}`, (evt) => {
+            const specificOutputUnlisten = await listenOutputLines(`gemini-output:${sessionId}`, (evt) => {
               processGeminiOutput(evt.payload);
             });
 
@@ -1290,7 +1471,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           });
 
           // Global fallback listener: process only events that match this Gemini backend session
-          const geminiOutputUnlisten = await listen<string>('gemini-output', (evt) => {
+          const geminiOutputUnlisten = await listenOutputLines('gemini-output', (evt) => {
             if (!hasActiveSessionRef.current) return;
 
             const payloadSessionId = extractGeminiSessionId(evt.payload);
@@ -1390,7 +1571,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           // 🔧 FIX: Mark that we've attached session-specific listeners
           hasAttachedSessionListeners = true;
 
-          const specificOutputUnlisten = await listen<string>(`claude-output:${sid}`, async (evt) => {
+          const specificOutputUnlisten = await listenOutputLines(`claude-output:${sid}`, async (evt) => {
             handleStreamMessage(evt.payload, userInputTranslation || undefined);
             
             // Handle user message recording in session-specific listener
@@ -1510,6 +1691,8 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             const projectId = effectiveSession?.project_id || extractedSessionInfo?.projectId || projectPath.replace(/[^a-zA-Z0-9]/g, '-');
             
             if (sessionId && projectId) {
+              // Run before marking completion so formatter edits belong to this prompt
+              await runPostProcessors('claude', sessionId, recordedPromptIndex, model);
               api.markPromptCompleted(
                 sessionId,
                 projectId,
@@ -1538,13 +1721,13 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
           console.log('[usePromptExecution] Session completed - reset session state for new input');
 
           // Process queued prompts after completion
-          if (queuedPromptsRef.current.length > 0) {
+          if (queuedPromptsRef.current.length > 0 && !autoFixActiveRef.current) {
             const [nextPrompt, ...remainingPrompts] = queuedPromptsRef.current;
             setQueuedPrompts(remainingPrompts);
 
             // Small delay to ensure UI updates
             setTimeout(() => {
-              handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
+              handleSendPrompt(nextPrompt.prompt, nextPrompt.model, undefined, nextPrompt.modelExplicit);
             }, 100);
           }
         };
@@ -1555,7 +1738,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         // ====================================================================
         // Generic Listeners (Catch-all) - FIXED to prevent cross-session data leakage
         // ====================================================================
-        const genericOutputUnlisten = await listen<string>('claude-output', async (event) => {
+        const genericOutputUnlisten = await listenOutputLines('claude-output', async (event) => {
           // 🔧 CRITICAL FIX: 只在尚未收到会话ID时处理全局事件
           if (!hasActiveSessionRef.current) return;
 
@@ -1791,10 +1974,11 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
               projectPath,
               prompt: processedPrompt,
               mode: codexMode || 'read-only',
-              model: codexModel || model,
+              model: codexModel,
               reasoningMode: codexReasoningMode,
               json: true,
-              skipGitRepoCheck: true
+              skipGitRepoCheck: true,
+              autoFix
             });
           } catch (resumeError) {
             // Fallback to resume last if specific resume fails
@@ -1802,10 +1986,11 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
               projectPath,
               prompt: processedPrompt,
               mode: codexMode || 'read-only',
-              model: codexModel || model,
+              model: codexModel,
               reasoningMode: codexReasoningMode,
               json: true,
-              skipGitRepoCheck: true
+              skipGitRepoCheck: true,
+              autoFix
             });
           }
         } else {
@@ -1815,10 +2000,11 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
             projectPath,
             prompt: processedPrompt,
             mode: codexMode || 'read-only',
-            model: codexModel || model,
+            model: codexModel,
             reasoningMode: codexReasoningMode,
             json: true,
-            skipGitRepoCheck: true
+            skipGitRepoCheck: true,
+            autoFix
           });
         }
 
@@ -1847,7 +2033,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         console.log('[usePromptExecution] Executing Gemini with:', {
           projectPath,
           prompt: processedPrompt.substring(0, 100) + '...',
-          model: geminiModel || 'gemini-2.5-pro',
+          model: geminiModel,
           approvalMode: geminiApprovalMode || 'auto_edit',
           resumingSession,
           sessionId
@@ -1863,10 +2049,11 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         await api.executeGemini({
           projectPath,
           prompt: processedPrompt,
-          model: geminiModel || 'gemini-2.5-pro',
+          model: geminiModel,
           approvalMode: geminiApprovalMode || 'auto_edit',
           sessionId: sessionId,  // 🔑 Pass session ID for resumption
-          debug: false
+          debug: false,
+          autoFix
         });
 
         // 🆕 Store pending prompt info for completion recording
@@ -1890,22 +2077,24 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
         // 🔧 Fix: 使用 isPlanModeRef.current 获取最新值，确保批准计划后不带 --plan
         const currentPlanMode = isPlanModeRef.current;
         console.log('[usePromptExecution] Using plan mode:', currentPlanMode);
+        // Without an explicit pick the backend applies the project's default model
+        const claudeModel = modelExplicit === false ? undefined : model;
 
         if (effectiveSession && !isFirstPrompt) {
           // Resume existing session
           console.log('[usePromptExecution] Resuming session:', effectiveSession.id);
           try {
-            await api.resumeClaudeCode(projectPath, effectiveSession.id, processedPrompt, model, currentPlanMode, maxThinkingTokens);
+            await api.resumeClaudeCode(projectPath, effectiveSession.id, processedPrompt, claudeModel, currentPlanMode, maxThinkingTokens, undefined, autoFix);
           } catch (resumeError) {
             console.warn('[usePromptExecution] Resume failed, falling back to continue mode:', resumeError);
             // Fallback to continue mode if resume fails
-            await api.continueClaudeCode(projectPath, processedPrompt, model, currentPlanMode, maxThinkingTokens);
+            await api.continueClaudeCode(projectPath, processedPrompt, claudeModel, currentPlanMode, maxThinkingTokens, undefined, autoFix);
           }
         } else {
           // Start new session
           console.log('[usePromptExecution] Starting new session');
           setIsFirstPrompt(false);
-          await api.executeClaudeCode(projectPath, processedPrompt, model, currentPlanMode, maxThinkingTokens);
+          await api.executeClaudeCode(projectPath, processedPrompt, claudeModel, currentPlanMode, maxThinkingTokens, undefined, autoFix);
         }
       }
 
@@ -1935,6 +2124,7 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     codexModel,       // 🆕 Codex integration
     geminiModel,      // 🆕 Gemini integration
     geminiApprovalMode, // 🆕 Gemini integration
+    autoFix,
     hasActiveSessionRef,
     unlistenRefs,
     isMountedRef,
@@ -1949,28 +2139,31 @@ export function usePromptExecution(config: UsePromptExecutionConfig): UsePromptE
     setRawJsonlOutput,
     setExtractedSessionInfo,
     setIsFirstPrompt,
-    processMessageWithTranslation
+    processMessageWithTranslation,
+    runPostProcessors
   ]);
 
   // When external streaming finishes, drain one queued prompt (if any) to avoid interrupting
   useEffect(() => {
-    if (externalIsStreaming || isLoading) return;
+    if (externalIsStreaming || isLoading || autoFixEvent) return;
     if (queuedPromptsRef.current.length === 0) return;
     const [nextPrompt, ...remaining] = queuedPromptsRef.current;
     setQueuedPrompts(remaining);
 
     const timer = setTimeout(() => {
-      handleSendPrompt(nextPrompt.prompt, nextPrompt.model);
+      handleSendPrompt(nextPrompt.prompt, nextPrompt.model, undefined, nextPrompt.modelExplicit);
     }, 100);
 
     return () => clearTimeout(timer);
-  }, [externalIsStreaming, isLoading, queuedPromptsRef, setQueuedPrompts, handleSendPrompt]);
+  }, [externalIsStreaming, isLoading, autoFixEvent, queuedPromptsRef, setQueuedPrompts, handleSendPrompt]);
 
   // ============================================================================
   // Return Hook Interface
   // ============================================================================
 
   return {
-    handleSendPrompt
+    handleSendPrompt,
+    autoFixEvent,
+    cancelAutoFix
   };
 }
@@ -1,9 +1,122 @@
 import { useCallback } from 'react';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { api, type Session } from '@/lib/api';
+import { listenOutputLines } from '@/lib/streamEvents';
 import { normalizeUsageData } from '@/lib/utils';
 import type { ClaudeStreamMessage } from '@/types/claude';
 import { codexConverter } from '@/lib/codexConverter';
+import type { GeminiSessionDetail } from '@/types/gemini';
+
+/** 分页加载历史时每页的消息数 */
+const HISTORY_PAGE_SIZE = 500;
+
+type HistoryEngine = 'claude' | 'codex' | 'gemini';
+
+/**
+ * 把 Gemini 会话文件中的消息转换为 ClaudeStreamMessage
+ */
+function geminiToStreamMessages(rawMessages: any[]): ClaudeStreamMessage[] {
+  return (rawMessages as GeminiSessionDetail['messages']).flatMap((msg) => {
+    const messages: ClaudeStreamMessage[] = [];
+
+    if (msg.type === 'user') {
+      messages.push({
+        type: 'user' as const,
+        message: {
+          content: msg.content ? [{ type: 'text', text: msg.content }] : []
+        },
+        timestamp: msg.timestamp,
+        engine: 'gemini' as const,
+      });
+    } else {
+      // Gemini assistant message
+      const content: any[] = [];
+
+      // Add tool calls if present
+      if (msg.toolCalls && msg.toolCalls.length > 0) {
+        for (const toolCall of msg.toolCalls) {
+          // Add tool_use content block
+          content.push({
+            type: 'tool_use',
+            id: toolCall.id,
+            name: toolCall.name,
+            input: toolCall.args,
+          });
+
+          // If there's a result, add it as a separate user message (tool_result)
+          if (toolCall.result !== undefined) {
+            // 使用实际的 result 数据，而不是 resultDisplay（摘要文本）
+            // Gemini result 格式: [{functionResponse: {response: {output: "..."}}}]
+            let resultContent = toolCall.result;
+
+            // 尝试提取 Gemini functionResponse 格式的实际输出
+            if (Array.isArray(toolCall.result)) {
+              const firstResult = toolCall.result[0];
+              if (firstResult?.functionResponse?.response?.output !== undefined) {
+                resultContent = firstResult.functionResponse.response.output;
+              }
+            }
+
+            messages.push({
+              type: 'user' as const,
+              message: {
+                content: [{
+                  type: 'tool_result',
+                  tool_use_id: toolCall.id,
+                  content: typeof resultContent === 'string' ? resultContent : JSON.stringify(resultContent),
+                  is_error: toolCall.status === 'error',
+                }]
+              },
+              timestamp: toolCall.timestamp || msg.timestamp,
+              engine: 'gemini' as const,
+            });
+          }
+        }
+      }
+
+      // Add text content if present
+      if (msg.content) {
+        content.push({
+          type: 'text',
+          text: msg.content,
+        });
+      }
+
+      // Add assistant message
+      messages.push({
+        type: 'assistant' as const,
+        message: {
+          content: content.length > 0 ? content : [{ type: 'text', text: '' }],
+          role: 'assistant'
+        },
+        timestamp: msg.timestamp,
+        engine: 'gemini' as const,
+        model: msg.model,
+      });
+    }
+
+    return messages;
+  });
+}
+
+/**
+ * 把分页读取的原始记录转换为消息（Codex 事件需要按顺序经过转换器）
+ */
+function toStreamMessages(engine: HistoryEngine, raw: any[]): ClaudeStreamMessage[] {
+  if (engine === 'gemini') {
+    return geminiToStreamMessages(raw);
+  }
+  if (engine === 'codex') {
+    codexConverter.reset();
+    const converted: ClaudeStreamMessage[] = [];
+    for (const event of raw) {
+      const msg = codexConverter.convertEventObject(event);
+      if (msg) converted.push(msg);
+    }
+    return converted;
+  }
+  return raw;
+}
 
 /**
  * useSessionLifecycle Hook
@@ -69,165 +182,61 @@ export function useSessionLifecycle(config: UseSessionLifecycleConfig): UseSessi
       setIsLoading(true);
       setError(null);
 
-      console.log('[useSessionLifecycle] Loading session:', session.id, 'engine:', (session as any).engine);
-      const engine = (session as any).engine;
-
-      let history: ClaudeStreamMessage[] = [];
-
-      // Handle Gemini sessions differently
-      if (engine === 'gemini') {
-        console.log('[useSessionLifecycle] Loading Gemini session detail...');
-        try {
-          const geminiDetail = await api.getGeminiSessionDetail(session.project_path, session.id);
-
-          // Convert Gemini messages to ClaudeStreamMessage format
-          history = geminiDetail.messages.flatMap((msg) => {
-            const messages: ClaudeStreamMessage[] = [];
-
-            if (msg.type === 'user') {
-              messages.push({
-                type: 'user' as const,
-                message: {
-                  content: msg.content ? [{ type: 'text', text: msg.content }] : []
-                },
-                timestamp: msg.timestamp,
-                engine: 'gemini' as const,
-              });
-            } else {
-              // Gemini assistant message
-              const content: any[] = [];
-
-              // Add tool calls if present
-              if (msg.toolCalls && msg.toolCalls.length > 0) {
-                for (const toolCall of msg.toolCalls) {
-                  // Add tool_use content block
-                  content.push({
-                    type: 'tool_use',
-                    id: toolCall.id,
-                    name: toolCall.name,
-                    input: toolCall.args,
-                  });
-
-                  // If there's a result, add it as a separate user message (tool_result)
-                  if (toolCall.result !== undefined) {
-                    // 使用实际的 result 数据，而不是 resultDisplay（摘要文本）
-                    // Gemini result 格式: [{functionResponse: {response: {output: "..."}}}]
-                    let resultContent = toolCall.result;
-
-                    // 尝试提取 Gemini functionResponse 格式的实际输出
-                    if (Array.isArray(toolCall.result)) {
-                      const firstResult = toolCall.result[0];
-                      if (firstResult?.functionResponse?.response?.output !== undefined) {
-                        resultContent = firstResult.functionResponse.response.output;
-                      }
-                    }
-
-                    messages.push({
-                      type: 'user' as const,
-                      message: {
-                        content: [{
-                          type: 'tool_result',
-                          tool_use_id: toolCall.id,
-                          content: typeof resultContent === 'string' ? resultContent : JSON.stringify(resultContent),
-                          is_error: toolCall.status === 'error',
-                        }]
-                      },
-                      timestamp: toolCall.timestamp || msg.timestamp,
-                      engine: 'gemini' as const,
-                    });
-                  }
-                }
-              }
-
-              // Add text content if present
-              if (msg.content) {
-                content.push({
-                  type: 'text',
-                  text: msg.content,
-                });
-              }
-
-              // Add assistant message
-              messages.push({
-                type: 'assistant' as const,
-                message: {
-                  content: content.length > 0 ? content : [{ type: 'text', text: '' }],
-                  role: 'assistant'
-                },
-                timestamp: msg.timestamp,
-                engine: 'gemini' as const,
-                model: msg.model,
-              });
+      const engine: HistoryEngine = (session as any).engine ?? 'claude';
+      console.log('[useSessionLifecycle] Loading session:', session.id, 'engine:', engine);
+      // Claude 按项目 ID 查找会话文件，Gemini 需要项目路径
+      const project = engine === 'gemini' ? session.project_path : session.project_id;
+      const loadPage = (offset: number) =>
+        api.loadSessionMessages(engine, session.id, offset, HISTORY_PAGE_SIZE, { reverse: true, project });
+
+      const showHistory = (history: ClaudeStreamMessage[]) => {
+        // Filter out invalid message types like 'queue-operation'
+        const validTypes = ['user', 'assistant', 'system', 'result', 'summary', 'thinking', 'tool_use'];
+        const processedMessages = history
+          .filter(entry => !entry.type || validTypes.includes(entry.type))
+          .map(entry => {
+            const msg = { ...entry, type: entry.type || "assistant" };
+            // ✨ Normalize usage data for historical messages
+            if (msg.message?.usage) {
+              msg.message.usage = normalizeUsageData(msg.message.usage);
             }
-
-            return messages;
+            return msg;
           });
-          console.log('[useSessionLifecycle] Loaded Gemini messages:', history.length);
-        } catch (geminiErr) {
-          console.error('[useSessionLifecycle] Failed to load Gemini session:', geminiErr);
-          throw geminiErr;
-        }
-      } else {
-        // Load Claude/Codex sessions
-        history = await api.loadSessionHistory(
-          session.id,
-          session.project_id,
-          engine
-        );
-
-        // If Codex, convert events to messages
-        if (engine === 'codex') {
-          console.log('[useSessionLifecycle] Converting Codex events:', history.length);
-          codexConverter.reset();
-          const convertedMessages: ClaudeStreamMessage[] = [];
-          let skippedCount = 0;
-
-          for (const event of history) {
-              const msg = codexConverter.convertEventObject(event);
-              if (msg) {
-                  convertedMessages.push(msg);
-              } else {
-                  skippedCount++;
-              }
-          }
-          history = convertedMessages;
-          console.log('[useSessionLifecycle] Converted to messages:', history.length, 'skipped:', skippedCount);
-        }
-      }
+        setMessages(processedMessages);
+        setRawJsonlOutput(history.map(h => JSON.stringify(h)));
+      };
+
+      // 先显示最新的一页（分页接口只解析请求的消息），大会话不必等整个文件解析完
+      const latest = await loadPage(0);
+      const latestRaw = [...latest.messages].reverse();
+      showHistory(toStreamMessages(engine, latestRaw));
+      // ⚡ CRITICAL: Set loading to false IMMEDIATELY after messages are set
+      setIsLoading(false);
+      console.log('[useSessionLifecycle] 🚀 Displayed latest page:', latestRaw.length, 'of', latest.total);
 
-      // Convert history to messages format
-      const loadedMessages: ClaudeStreamMessage[] = history
-        .filter(entry => {
-          // Filter out invalid message types like 'queue-operation'
-          const type = entry.type;
-          const validTypes = ['user', 'assistant', 'system', 'result', 'summary', 'thinking', 'tool_use'];
-          if (type && !validTypes.includes(type)) {
-            console.warn('[useSessionLifecycle] Filtering out invalid message type:', type);
-            return false;
+      // 其余部分在后台补齐：Codex / Gemini 继续向前分页；
+      // Claude 的子代理消息（agent-*.jsonl）不在分页结果中，引用了子代理时使用完整加载
+      // 补齐失败时保留已显示的最新一页
+      const hasSubagents = engine === 'claude' && latestRaw.some(entry => entry?.toolUseResult?.agentId);
+      try {
+        if (engine === 'claude' && (latest.hasMore || hasSubagents)) {
+          const full = await api.loadSessionHistory(session.id, session.project_id, 'claude');
+          if (isMountedRef.current) showHistory(full);
+        } else if (latest.hasMore) {
+          const pages: any[][] = [latestRaw];
+          let offset = HISTORY_PAGE_SIZE;
+          let hasMore: boolean = latest.hasMore;
+          while (hasMore && isMountedRef.current) {
+            const page = await loadPage(offset);
+            pages.unshift([...page.messages].reverse());
+            offset += HISTORY_PAGE_SIZE;
+            hasMore = page.hasMore;
           }
-          return true;
-        })
-        .map(entry => ({
-          ...entry,
-          type: entry.type || "assistant"
-        }));
-
-      // ✨ NEW: Normalize usage data for historical messages
-      const processedMessages = loadedMessages.map(msg => {
-        if (msg.message?.usage) {
-          msg.message.usage = normalizeUsageData(msg.message.usage);
+          if (isMountedRef.current) showHistory(toStreamMessages(engine, pages.flat()));
         }
-        return msg;
-      });
-
-      // ✨ NEW: Immediate display - no more blocking on translation
-      console.log('[useSessionLifecycle] 🚀 Displaying messages immediately:', loadedMessages.length);
-      setMessages(processedMessages);
-      setRawJsonlOutput(history.map(h => JSON.stringify(h)));
-      
-      // ⚡ CRITICAL: Set loading to false IMMEDIATELY after messages are set
-      // This prevents the "Loading..." screen from showing unnecessarily
-      setIsLoading(false);
+      } catch (err) {
+        console.warn('[useSessionLifecycle] Failed to load earlier messages:', err);
+      }
 
       // ⚡ PERFORMANCE: 完全禁用后台翻译初始化，避免性能问题
       // 翻译功能已有独立的懒加载机制，不需要在会话加载时初始化
@@ -249,7 +258,7 @@ export function useSessionLifecycle(config: UseSessionLifecycleConfig): UseSessi
       setError("加载会话历史记录失败");
       setIsLoading(false);
     }
-  }, [session, setIsLoading, setError, setMessages, setRawJsonlOutput, initializeProgressiveTranslation]);
+  }, [session, isMountedRef, setIsLoading, setError, setMessages, setRawJsonlOutput, initializeProgressiveTranslation]);
 
   /**
    * 检查会话是否仍在活跃状态
@@ -314,7 +323,7 @@ export function useSessionLifecycle(config: UseSessionLifecycleConfig): UseSessi
     isListeningRef.current = true;
 
     // Set up session-specific listeners
-    const outputUnlisten = await listen<string>(`claude-output:${sessionId}`, async (event) => {
+    const outputUnlisten = await listenOutputLines(`claude-output:${sessionId}`, async (event) => {
       try {
         console.log('[useSessionLifecycle] Received claude-output on reconnect:', event.payload);
 
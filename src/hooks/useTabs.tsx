@@ -1,5 +1,5 @@
 import { useState, useCallback, useRef, useContext, createContext, ReactNode, useEffect } from 'react';
-import type { Session } from '@/lib/api';
+import { api, type Session } from '@/lib/api';
 import { createSessionWindow, emitWindowSyncEvent, onWindowSyncEvent, isSessionWindow } from '@/lib/windowManager';
 
 /**
@@ -140,6 +140,15 @@ export const TabProvider: React.FC<TabProviderProps> = ({ children }) => {
     }
   }, [tabs, activeTabId]);
 
+  // 上报当前活动项目，供全局快捷键（快速输入、重跑上一条提示词）使用；独立会话窗口不上报
+  const activeTab = tabs.find(tab => tab.id === activeTabId);
+  const activeProjectPath = activeTab?.session?.project_path || activeTab?.projectPath || null;
+  const activeEngine = activeTab?.session?.engine;
+  useEffect(() => {
+    if (isSessionWindow()) return;
+    api.setActiveProject(activeProjectPath, activeEngine).catch(() => {});
+  }, [activeProjectPath, activeEngine]);
+
   // ✨ REFACTORED: Compute TabSession with isActive (simplified)
   const tabsWithActive: TabSession[] = tabs.map(tab => ({
     ...tab,
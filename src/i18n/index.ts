@@ -1,6 +1,7 @@
 import i18n from 'i18next';
 import { initReactI18next } from 'react-i18next';
 import LanguageDetector from 'i18next-browser-languagedetector';
+import { api } from '@/lib/api';
 
 // Import language resources
 import en from './locales/en.json';
@@ -34,4 +35,13 @@ i18n
     },
   });
 
+// 后端返回的错误信息与命令结果跟随界面语言
+const syncBackendLocale = (lng: string) => {
+  api.setBackendLocale(lng).catch(() => {});
+};
+i18n.on('languageChanged', syncBackendLocale);
+if (i18n.language) {
+  syncBackendLocale(i18n.language);
+}
+
 export default i18n;
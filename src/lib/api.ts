@@ -1,7 +1,129 @@
-import { invoke } from "@tauri-apps/api/core";
+import { invoke as tauriInvoke, type InvokeArgs } from "@tauri-apps/api/core";
 import type { HooksConfiguration } from '@/types/hooks';
 import { HooksManager } from '@/lib/hooksManager';
 
+/**
+ * 后端命令错误码（与 src-tauri/src/error.rs 中的 AnyCodeError 对应）
+ */
+export type AnyCodeErrorCode =
+  | 'io'
+  | 'config'
+  | 'engine'
+  | 'git'
+  | 'wsl'
+  | 'provider'
+  | 'validation'
+  | 'not_found'
+  | 'database'
+  | 'network'
+  | 'internal';
+
+/**
+ * 结构化的命令错误
+ *
+ * 后端以 `{code, title, message, details}` 返回错误；这里包装成 Error，
+ * `message` / `toString()` 仍是原来的错误文本，已有的字符串展示逻辑无需修改。
+ * `title` 是按后端语言渲染的类别标题（如「Git 操作失败」），可用作提示框标题。
+ */
+export class AnyCodeError extends Error {
+  code: AnyCodeErrorCode;
+  title: string;
+  details: unknown;
+
+  constructor(code: AnyCodeErrorCode, message: string, details?: unknown, title?: string) {
+    super(message);
+    this.name = 'AnyCodeError';
+    this.code = code;
+    this.title = title ?? code;
+    this.details = details ?? null;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+function isStructuredError(
+  error: unknown
+): error is { code: AnyCodeErrorCode; message: string; details?: unknown; title?: string } {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as { code?: unknown }).code === 'string' &&
+    typeof (error as { message?: unknown }).message === 'string'
+  );
+}
+
+/** 命令耗时样本的批量上报间隔 */
+const COMMAND_METRICS_FLUSH_MS = 5000;
+
+/** 是否记录命令耗时（对应 ~/.anycode/command_metrics.json，默认关闭） */
+let commandMetricsEnabled = false;
+let pendingCommandSamples: CommandSample[] = [];
+let commandMetricsFlushTimer: ReturnType<typeof setTimeout> | null = null;
+
+tauriInvoke<CommandMetricsSettings>("get_command_metrics_settings")
+  .then((settings) => {
+    commandMetricsEnabled = settings.enabled;
+  })
+  .catch(() => {});
+
+function flushCommandSamples() {
+  commandMetricsFlushTimer = null;
+  const samples = pendingCommandSamples;
+  pendingCommandSamples = [];
+  if (samples.length === 0) return;
+  // 直接调用原始 invoke，上报本身不计入统计
+  tauriInvoke("record_command_metrics", { samples }).catch((error) => {
+    console.warn("Failed to record command metrics:", error);
+  });
+}
+
+function recordCommandSample(sample: CommandSample) {
+  pendingCommandSamples.push(sample);
+  if (!commandMetricsFlushTimer) {
+    commandMetricsFlushTimer = setTimeout(flushCommandSamples, COMMAND_METRICS_FLUSH_MS);
+  }
+}
+
+/**
+ * 调用后端命令，把结构化错误转换为 AnyCodeError（字符串错误原样抛出）
+ *
+ * 开启命令耗时统计时，同时记录调用耗时与结果（仅保存在本地）。
+ */
+async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  const startedAt = commandMetricsEnabled ? performance.now() : null;
+  const record = (success: boolean, errorCode?: string) => {
+    if (startedAt === null) return;
+    recordCommandSample({
+      command: cmd,
+      durationMs: Math.round(performance.now() - startedAt),
+      success,
+      errorCode: errorCode ?? null,
+    });
+  };
+
+  try {
+    const result = await tauriInvoke<T>(cmd, args);
+    record(true);
+    return result;
+  } catch (error) {
+    if (isStructuredError(error)) {
+      record(false, error.code);
+      throw new AnyCodeError(error.code, error.message, error.details, error.title);
+    }
+    record(false);
+    throw error;
+  }
+}
+
+/**
+ * 获取错误码；非结构化错误返回 undefined
+ */
+export function getErrorCode(error: unknown): AnyCodeErrorCode | undefined {
+  return error instanceof AnyCodeError ? error.code : undefined;
+}
+
 /** Process type for tracking in ProcessRegistry */
 export type ProcessType = 
   | { AgentRun: { agent_id: number; agent_name: string } }
@@ -186,6 +308,8 @@ export interface ClaudeExecutionConfig {
   verbose: boolean;
   permissions: ClaudePermissionConfig;
   disable_rewind_git_operations: boolean;
+  /** Ask in the UI before tools run (emits `tool-approval-request:{sessionId}`) */
+  interactive_approvals?: boolean;
 }
 
 /**
@@ -230,6 +354,9 @@ export interface FileEntry {
  */
 export type RewindMode = "conversation_only" | "code_only" | "both";
 
+/** How code is restored: git commits, or file snapshots for projects without git */
+export type RewindStrategy = "git" | "snapshot";
+
 /**
  * Capabilities for rewinding a specific prompt
  */
@@ -244,6 +371,10 @@ export interface RewindCapabilities {
   warning?: string;
   /** Prompt source indicator */
   source: "project" | "cli";
+  /** Code changes from this prompt onwards are mixed with manual edits (Codex only) */
+  mixed?: boolean;
+  /** Strategy used to revert code (absent when code can't be reverted) */
+  strategy?: RewindStrategy;
 }
 
 /**
@@ -383,6 +514,26 @@ export interface ProjectUsageWithEngine {
   last_used: string;
 }
 
+// Native usage import & reconciliation
+export interface UsageDiscrepancy {
+  engine: string;
+  request_id: string;
+  session_id: string;
+  model: string;
+  /** "tokens" | "cost" differ from AnyCode's record; "pricing": CLI cost differs from pricing table */
+  kind: 'tokens' | 'cost' | 'pricing';
+  tracked: number;
+  native: number;
+}
+
+export interface UsageReconciliation {
+  scanned: number;
+  imported: number;
+  updated: number;
+  duplicates: number;
+  discrepancies: UsageDiscrepancy[];
+}
+
 // Codex Rate Limits
 export interface CodexRateLimits {
   primary: RateLimitInfo | null;
@@ -578,8 +729,18 @@ export interface MCPServerExtended extends MCPServer {
   engine: 'claude' | 'codex' | 'gemini';
   /** Startup timeout in seconds (Codex specific) */
   startup_timeout_sec?: number;
-  /** Tool timeout in seconds (Codex specific) */
+  /** Tool timeout in seconds (Codex tool_timeout_sec, Gemini timeout) */
   tool_timeout_sec?: number;
+  /** Working directory (Codex, Gemini) */
+  cwd?: string;
+  /** HTTP headers for URL servers */
+  headers?: Record<string, string>;
+  /** Skip tool call confirmations (Gemini) */
+  trust?: boolean;
+  /** Tool allowlist (Gemini includeTools, Codex enabled_tools) */
+  include_tools?: string[];
+  /** Tool denylist (Gemini excludeTools, Codex disabled_tools) */
+  exclude_tools?: string[];
 }
 
 /**
@@ -597,6 +758,18 @@ export interface ServerStatus {
   error?: string;
   /** Last checked timestamp */
   last_checked?: number;
+  /** Protocol version negotiated in the initialize handshake */
+  protocol_version?: string;
+  /** Tools advertised by the server */
+  tools?: MCPToolInfo[];
+}
+
+/**
+ * A tool advertised by an MCP server
+ */
+export interface MCPToolInfo {
+  name: string;
+  description?: string;
 }
 
 /**
@@ -624,6 +797,8 @@ export interface SavedImageResult {
   success: boolean;
   file_path?: string;
   error?: string;
+  width?: number;
+  height?: number;
 }
 
 /**
@@ -635,6 +810,206 @@ export interface AddServerResult {
   server_name?: string;
 }
 
+/**
+ * Outcome of syncing one MCP server to one engine
+ */
+export interface MCPSyncItem {
+  server_name: string;
+  target_engine: MCPEngineType;
+  status: 'added' | 'updated' | 'skipped' | 'failed';
+  message?: string;
+}
+
+/**
+ * Variable required by an MCP registry server (env var or argument placeholder)
+ */
+export interface RegistryEnvVar {
+  name: string;
+  description: string;
+  required: boolean;
+  secret: boolean;
+  argOnly: boolean;
+}
+
+/**
+ * Curated MCP server from the registry
+ */
+export interface RegistryServer {
+  name: string;
+  displayName: string;
+  description: string;
+  category: string;
+  homepage?: string;
+  transport: string;
+  command?: string;
+  args: string[];
+  url?: string;
+  env: RegistryEnvVar[];
+}
+
+/**
+ * Result of installing a registry server; missingEnv lists variables to prompt for
+ */
+export interface RegistryInstallResult {
+  success: boolean;
+  message: string;
+  missingEnv: RegistryEnvVar[];
+}
+
+/**
+ * Built-in AnyCode MCP server info
+ */
+export interface AnycodeMcpServerInfo {
+  port: number;
+  url: string;
+  token: string;
+  /** Ready-to-use mcpServers entry */
+  config: Record<string, any>;
+}
+
+export type GitTrendPeriod = 'week' | 'month' | 'quarter' | 'year' | 'all';
+export type GitTrendGranularity = 'day' | 'week' | 'month';
+
+export interface GitTrendPoint {
+  /** Bucket start: YYYY-MM-DD (day/week) or YYYY-MM (month) */
+  bucket: string;
+  commits: number;
+  linesAdded: number;
+  linesRemoved: number;
+}
+
+export interface GitAuthorStats {
+  name: string;
+  email: string;
+  commits: number;
+  linesAdded: number;
+  linesRemoved: number;
+}
+
+export interface GitTrends {
+  period: GitTrendPeriod;
+  granularity: GitTrendGranularity;
+  totalCommits: number;
+  linesAdded: number;
+  linesRemoved: number;
+  points: GitTrendPoint[];
+  authors: GitAuthorStats[];
+  head?: string;
+}
+
+export interface AttributionTotals {
+  commits: number;
+  linesAdded: number;
+  linesRemoved: number;
+}
+
+export interface EngineAttribution extends AttributionTotals {
+  engine: 'claude' | 'codex' | 'gemini';
+  /** Sessions that produced commits (known only from prompt git records) */
+  sessions: number;
+}
+
+export interface AiAttributionStats {
+  period: GitTrendPeriod;
+  total: AttributionTotals;
+  ai: AttributionTotals;
+  human: AttributionTotals;
+  /** Share of added lines introduced by agents (0-1) */
+  aiLineShare: number;
+  engines: EngineAttribution[];
+}
+
+export interface GitBranch {
+  /** Short name, e.g. main or origin/main */
+  name: string;
+  isRemote: boolean;
+  isCurrent: boolean;
+  commit: string;
+  upstream?: string;
+  ahead: number;
+  behind: number;
+  upstreamGone: boolean;
+  lastCommitTime: number;
+  lastCommitSubject: string;
+}
+
+export interface BranchStatus {
+  /** Undefined when HEAD is detached */
+  branch?: string;
+  base?: string;
+  ahead: number;
+  behind: number;
+  hasUncommittedChanges: boolean;
+}
+
+/** A file changed by a session, compared with another branch */
+export interface BranchCompareFile {
+  path: string;
+  /** identical: same as the branch; conflict: both sides changed and can't be merged automatically */
+  status: "identical" | "clean" | "conflict";
+  /** The branch also changed this file since the merge base */
+  changedOnBranch: boolean;
+  linesAdded: number;
+  linesRemoved: number;
+}
+
+export interface SessionBranchComparison {
+  sessionId: string;
+  branch: string;
+  /** Commit before the session's first prompt */
+  sessionBase: string;
+  mergeBase: string;
+  /** Conflicting files first */
+  files: BranchCompareFile[];
+  conflicts: number;
+  /** Unified diff from the branch to the session's current content (session files only) */
+  diff: string;
+}
+
+export interface DiffHunk {
+  id: string;
+  header: string;
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
+  /** Body lines with their ' ', '+', '-' prefix */
+  lines: string[];
+  added: number;
+  removed: number;
+}
+
+export interface FileHunks {
+  file: string;
+  isNew: boolean;
+  isBinary: boolean;
+  hunks: DiffHunk[];
+}
+
+export interface GeneratedCommitMessage {
+  message: string;
+  subject: string;
+  body?: string;
+  engine: string;
+}
+
+/**
+ * Recorded acemcp tool call
+ */
+export interface AcemcpToolTrace {
+  id: number;
+  sessionId?: string;
+  toolName: string;
+  params: any;
+  /** Raw result JSON (truncated when large) */
+  result?: string;
+  error?: string;
+  latencyMs: number;
+  createdAt: string;
+  /** Original trace id when this is a replay */
+  replayOf?: number;
+}
+
 /**
  * Translation configuration interface
  */
@@ -645,6 +1020,67 @@ export interface TranslationConfig {
   model: string;
   timeout_seconds: number;
   cache_ttl_seconds: number;
+  provider?: TranslationProvider;
+  glossary?: GlossaryTerm[];
+  /** Keep code blocks, inline code, URLs and identifiers untranslated (default true) */
+  protect_code?: boolean;
+  /** Bidirectional prompt translation done by the backend */
+  pipeline?: PromptTranslationSettings;
+}
+
+/**
+ * Bidirectional prompt translation settings
+ */
+export interface PromptTranslationSettings {
+  enabled: boolean;
+  direction: "both" | "prompt_only" | "response_only";
+  /** Language the user writes in (e.g. "zh") */
+  user_lang: string;
+  /** Language sent to the engine (e.g. "en") */
+  engine_lang: string;
+  /** Shorter prompts are sent as-is */
+  min_prompt_chars: number;
+  /** Longer prompts (pasted code, logs) are sent as-is */
+  max_prompt_chars: number;
+  /** Longer replies are not translated automatically */
+  max_response_chars: number;
+}
+
+/**
+ * Stored prompt / reply translation of a session
+ */
+export interface PromptTranslationRecord {
+  engine: string;
+  sessionId: string;
+  kind: "prompt" | "response";
+  originalText: string;
+  translatedText: string;
+  sourceLang: string;
+  targetLang: string;
+  createdAt: string;
+}
+
+export type TranslationProvider = "openai" | "deepl" | "google" | "local";
+
+/**
+ * Translation provider info
+ */
+export interface TranslationProviderInfo {
+  id: TranslationProvider;
+  label: string;
+  default_base_url: string;
+  requires_api_key: boolean;
+  uses_model: boolean;
+}
+
+/**
+ * Glossary term (kept as-is, or replaced by a fixed translation)
+ */
+export interface GlossaryTerm {
+  term: string;
+  translation?: string | null;
+  /** Only applies when translating into this language (e.g. "en") */
+  target_lang?: string | null;
 }
 
 /**
@@ -766,6 +1202,8 @@ export interface IDEConfig {
   customIdeArgs?: string;
   /** Whether to use URL protocol */
   useUrlProtocol: boolean;
+  /** Executable path overrides for registry editors (editor id -> path) */
+  editorPaths?: Record<string, string>;
 }
 
 /**
@@ -797,4104 +1235,7213 @@ export interface DetectedIDE {
 }
 
 /**
- * IDE operation result
+ * Editor from the built-in registry with detection result
  */
-export interface IDEResult {
-  success: boolean;
-  message: string;
-  error?: string;
+export interface EditorInfo {
+  /** Editor id, e.g. 'vscode', 'idea', 'zed', 'sublime', 'neovim' */
+  id: string;
+  name: string;
+  /** Detected executable path */
+  path?: string;
+  /** Whether files can be opened with this editor */
+  available: boolean;
+  /** Whether the editor can show a recorded change in its diff viewer */
+  supportsDiff: boolean;
 }
 
 /**
- * API client for interacting with the Rust backend
+ * External terminal settings (~/.anycode/terminal.json)
  */
-export const api = {
-  /**
-   * Lists all projects in the ~/.claude/projects directory
-   * @returns Promise resolving to an array of projects
-   */
-  async listProjects(): Promise<Project[]> {
-    try {
-      return await invoke<Project[]>("list_projects");
-    } catch (error) {
-      console.error("Failed to list projects:", error);
-      throw error;
-    }
-  },
+export interface TerminalSettings {
+  /** Preset id or terminal program (auto-detected when empty) */
+  terminal?: string;
+  /** Argument template with {cwd}, {command}, {script}, {applescript} placeholders */
+  args?: string[];
+}
 
-  /**
-   * Retrieves sessions for a specific project (both Claude and Codex)
-   * @param projectId - The ID of the project to retrieve sessions for
-   * @param projectPath - Optional project path to filter Codex sessions (if not provided, tries to infer from Claude sessions)
-   * @returns Promise resolving to an array of sessions
-   */
-  async getProjectSessions(projectId: string, projectPath?: string): Promise<Session[]> {
-    try {
-      // Always fetch Claude sessions; if projectPath is known, load Codex sessions in parallel.
-      const claudePromise = invoke<Session[]>('get_project_sessions', { projectId });
-      const codexPromise = projectPath
-        ? this.listCodexSessionsForProject(projectPath).catch(err => {
-            console.warn('[SessionList] Failed to load Codex sessions:', err);
-            return [];
-          })
-        : Promise.resolve([] as import('@/types/codex').CodexSession[]);
+/**
+ * Built-in terminal preset for the current platform
+ */
+export interface TerminalInfo {
+  id: string;
+  name: string;
+  available: boolean;
+}
 
-      const [claudeSessions, codexResults] = await Promise.all([claudePromise, codexPromise]);
-      console.log('[SessionList] Claude sessions:', claudeSessions.length);
+/**
+ * Embedded terminal (PTY) session.
+ * Output is streamed as `pty-output:{id}` events, exit as `pty-exit:{id}` (exit code).
+ */
+export interface PtySessionInfo {
+  id: string;
+  projectPath: string;
+  /** Program started in the PTY (default shell when absent) */
+  command?: string;
+  pid?: number;
+  cols: number;
+  rows: number;
+  startedAt: string;
+}
 
-      const targetPath = projectPath || claudeSessions[0]?.project_path;
+/**
+ * Tool call waiting for approval (`tool-approval-request:{sessionId}` event)
+ */
+export interface ToolApprovalRequest {
+  sessionId: string;
+  requestId: string;
+  engine: string;
+  toolName: string;
+  input: Record<string, any>;
+  createdAt: string;
+}
 
-      // If projectPath wasn't provided and we now have it, fetch Codex sessions (fallback)
-      const resolvedCodexResults = codexResults.length
-        ? codexResults
-        : targetPath
-          ? await this.listCodexSessionsForProject(targetPath).catch(err => {
-              console.warn('[SessionList] Failed to load Codex sessions:', err);
-              return [];
-            })
-          : [];
+/**
+ * Approval outcome (`tool-approval-resolved:{sessionId}` event)
+ */
+export interface ToolApprovalResolution {
+  sessionId: string;
+  requestId: string;
+  allow: boolean;
+  /** The session ended or the CLI withdrew the request */
+  cancelled: boolean;
+}
 
-      const codexSessions: Session[] = resolvedCodexResults.map(cs => ({
-        id: cs.id,
-        project_id: projectId,
-        project_path: cs.projectPath,
-        created_at: cs.createdAt,
-        model: cs.model || 'gpt-5.1-codex-max',
-        engine: 'codex' as const,
-        first_message: cs.firstMessage || `Codex Session`,
-        last_assistant_message: cs.lastAssistantMessage,
-        last_message_timestamp: cs.lastMessageTimestamp,
-      }));
+/**
+ * File staged as a prompt attachment (see `stageAttachment`)
+ */
+export interface StagedAttachment {
+  id: string;
+  fileName: string;
+  /** Path of the staged copy passed to the CLI */
+  path: string;
+  originalPath: string;
+  kind: "image" | "pdf" | "text";
+  sizeBytes: number;
+  createdAt: string;
+}
 
-      // Merge and sort by creation time
-      const allSessions = [...claudeSessions.map(s => ({ ...s, engine: 'claude' as const })), ...codexSessions];
-      allSessions.sort((a, b) => b.created_at - a.created_at);
+/**
+ * Git state of a registered project
+ */
+export interface ProjectGitInfo {
+  /** Current branch (absent when HEAD is detached) */
+  branch?: string;
+  /** Short HEAD hash */
+  head?: string;
+  dirty: boolean;
+  remoteUrl?: string;
+}
 
-      return allSessions;
-    } catch (error) {
-      console.error("Failed to get project sessions:", error);
-      throw error;
-    }
-  },
+/**
+ * Per-engine activity of a registered project
+ */
+export interface EngineActivity {
+  engine: "claude" | "codex" | "gemini";
+  sessionCount: number;
+  /** Unix seconds of the latest session */
+  lastSessionAt?: number;
+  /** Project default provider (global config when absent) */
+  provider?: string;
+}
 
-  /**
-   * Deletes a session and all its associated data
-   * @param sessionId - The session ID to delete
-   * @param projectId - The project ID this session belongs to
-   * @returns Promise resolving to success message
-   */
-  async deleteSession(sessionId: string, projectId: string): Promise<string> {
-    try {
-      return await invoke<string>('delete_session', { sessionId, projectId });
-    } catch (error) {
-      console.error("Failed to delete session:", error);
-      throw error;
-    }
-  },
+/**
+ * Project in the engine-independent project registry
+ */
+export interface RegisteredProject {
+  path: string;
+  name: string;
+  addedAt: string;
+  /** The directory no longer exists */
+  missing: boolean;
+  /** Absent for non-git directories */
+  git?: ProjectGitInfo;
+  engines: EngineActivity[];
+}
 
-  /**
-   * Deletes multiple sessions in batch
-   * @param sessionIds - Array of session IDs to delete
-   * @param projectId - The project ID these sessions belong to
-   * @returns Promise resolving to success message
-   */
-  async deleteSessionsBatch(sessionIds: string[], projectId: string): Promise<string> {
-    try {
+/**
+ * Recently modified file (git status + mtime + change tracker records)
+ */
+export interface RecentFile {
+  /** Path relative to the project root */
+  path: string;
+  /** Unix seconds */
+  modifiedAt: number;
+  /** Porcelain status code (e.g. "M", "A", "??") when the file has uncommitted changes */
+  gitStatus?: string;
+  /** Unix seconds of the latest agent change recorded by the change tracker */
+  agentChangedAt?: number;
+}
+
+/**
+ * Batch file operation (paths relative to the project root)
+ */
+export type BatchFileOperation =
+  | { op: "rename"; from: string; to: string }
+  | { op: "move"; from: string; to: string }
+  | { op: "delete"; path: string };
+
+/**
+ * Session whose change history a file batch is recorded into
+ */
+export interface FileBatchTracking {
+  sessionId: string;
+  promptIndex: number;
+}
+
+/**
+ * Journal of an executed file batch
+ */
+export interface FileBatch {
+  id: string;
+  projectPath: string;
+  createdAt: string;
+  tracking?: FileBatchTracking;
+  entries: { op: string; from: string; to: string }[];
+  reverted: boolean;
+  changeIds: string[];
+}
+
+/**
+ * Workspace trash entry
+ */
+export interface TrashEntry {
+  id: string;
+  projectPath: string;
+  /** Absolute path before deletion */
+  originalPath: string;
+  /** Path relative to the project root */
+  relativePath: string;
+  deletedAt: string;
+  sizeBytes: number;
+  isDir: boolean;
+  /** "manual" or "batch" */
+  source?: string;
+}
+
+export interface TrashPurgeResult {
+  removed: number;
+  freedBytes: number;
+}
+
+/**
+ * 代理商延迟与错误率指标
+ */
+export type ProviderErrorCategory = 'auth' | 'rate_limit' | 'connection' | 'timeout' | 'server' | 'other';
+
+export interface ProviderMetrics {
+  providerId: string;
+  period: string;
+  totalRequests: number;
+  failedRequests: number;
+  errorRate: number;
+  avgTtftMs?: number | null;
+  p50TtftMs?: number | null;
+  p95TtftMs?: number | null;
+  avgStreamMs?: number | null;
+  avgTestLatencyMs?: number | null;
+  errorsByCategory: Partial<Record<ProviderErrorCategory, number>>;
+  lastErrorAt?: string | null;
+}
+
+/**
+ * AnyCode 数据目录（默认 ~/.anycode，可自定义或使用便携模式）
+ */
+export type DataRootMode = 'default' | 'custom' | 'portable' | 'env';
+
+export interface DataRootInfo {
+  mode: DataRootMode;
+  path: string;
+  /** agents.db 等应用数据所在目录 */
+  appDataPath?: string | null;
+  defaultPath?: string | null;
+  /** 便携模式将使用的目录 */
+  portablePath?: string | null;
+  /** 等待下次启动执行的迁移 */
+  pendingMigration?: PendingDataRootMigration | null;
+  /** 本次启动时执行的迁移 */
+  lastMigration?: DataRootMigrationReport | null;
+}
+
+/** 暂存的数据目录迁移（重启应用后、打开数据库之前执行） */
+export interface PendingDataRootMigration {
+  mode: DataRootMode;
+  from: string;
+  to: string;
+  fromAppData: string;
+  toAppData: string;
+}
+
+export interface DataRootMigrationReport {
+  mode: DataRootMode;
+  from: string;
+  to: string;
+  copiedFiles: number;
+  updatedReferences: number;
+  warnings: string[];
+}
+
+/**
+ * 数据备份与恢复
+ */
+export type BackupCategory = 'database' | 'settings' | 'prompts' | 'providers';
+
+export interface BackupEntry {
+  path: string;
+  category: BackupCategory;
+  size: number;
+  /** 保存在加密的 secrets.enc 中（需要备份密码才能恢复） */
+  encrypted?: boolean;
+}
+
+export interface BackupManifest {
+  version: number;
+  createdAt: string;
+  appVersion: string;
+  includesSecrets: boolean;
+  entries: BackupEntry[];
+}
+
+export interface BackupResult {
+  path: string;
+  createdAt: string;
+  fileCount: number;
+  size: number;
+  includesSecrets: boolean;
+}
+
+export interface RestorePreviewEntry {
+  path: string;
+  category: BackupCategory;
+  /** 恢复到的本地路径 */
+  target?: string | null;
+  action: 'create' | 'overwrite' | 'skip';
+  reason?: string | null;
+}
+
+export interface RestorePreview {
+  manifest: BackupManifest;
+  entries: RestorePreviewEntry[];
+  overwriteCount: number;
+  /** 备份包含加密的代理商密钥，但未提供密码 */
+  requiresPassphrase: boolean;
+}
+
+export interface RestoreReport {
+  restored: number;
+  skipped: string[];
+  /** 数据库在重启后生效 */
+  restartRequired: boolean;
+}
+
+/**
+ * 通用键值设置变更事件（settings-changed，value 为 null 表示已删除）
+ */
+export interface SettingChange {
+  key: string;
+  value: unknown | null;
+}
+
+/**
+ * 会话保留策略
+ */
+export type RetentionAction = 'archive' | 'delete';
+
+export interface RetentionRule {
+  maxAgeDays?: number | null;
+  /** 总大小上限（MB），超出时从最旧的开始清理 */
+  maxTotalMb?: number | null;
+}
+
+export interface RetentionPolicy {
+  /** 启动时自动清理 */
+  autoCleanup: boolean;
+  /** 会话的处理方式（变更记录和日志总是删除） */
+  sessionAction: RetentionAction;
+  sessions: RetentionRule;
+  changeRecords: RetentionRule;
+  logs: RetentionRule;
+  excludedEngines: string[];
+  excludedProjects: string[];
+}
+
+export interface CleanupItem {
+  kind: 'session' | 'changeRecords' | 'log';
+  engine: string;
+  sessionId: string;
+  projectPath?: string | null;
+  sizeBytes: number;
+  modifiedAt: string;
+  action: RetentionAction;
+  reason: 'age' | 'sizeCap';
+}
+
+export interface CleanupPreview {
+  items: CleanupItem[];
+  archivedSessions: number;
+  deletedSessions: number;
+  changeRecords: number;
+  logs: number;
+  /** 不再被引用的文件快照（清单数） */
+  snapshots: number;
+  /** 删除后释放的空间（归档不释放） */
+  freedBytes: number;
+}
+
+export interface CleanupResult {
+  archived: number;
+  deleted: number;
+  freedBytes: number;
+  errors: string[];
+}
+
+/**
+ * 磁盘占用统计
+ */
+export type StorageCategory = 'sessions' | 'logs' | 'changeRecords' | 'backups' | 'database' | 'other';
+
+export interface CategoryUsage {
+  bytes: number;
+  files: number;
+}
+
+export interface StorageLocation {
+  tool: 'claude' | 'codex' | 'gemini' | 'anycode';
+  path: string;
+  /** WSL 中的目录对应的发行版 */
+  wslDistro?: string | null;
+  exists: boolean;
+  totalBytes: number;
+  categories: Partial<Record<StorageCategory, CategoryUsage>>;
+}
+
+export interface StorageReport {
+  locations: StorageLocation[];
+  totals: Record<StorageCategory, CategoryUsage>;
+  totalBytes: number;
+  generatedAt: string;
+}
+
+/**
+ * 一页会话消息
+ */
+export interface SessionMessagePage {
+  messages: any[];
+  /** 会话中的消息总数 */
+  total: number;
+  offset: number;
+  hasMore: boolean;
+}
+
+/**
+ * 参与上下文 token 计数的消息（content 为字符串或内容块数组）
+ */
+export interface TokenCountMessage {
+  role: string;
+  content: string | any[];
+}
+
+export interface ContextTokenCount {
+  total: number;
+  /** 每条消息的 token 数（含格式开销） */
+  messages: number[];
+  vocab: 'o200k_base' | 'cl100k_base';
+}
+
+/**
+ * 发送前上下文预估的选项（可直接传入各引擎的执行选项）
+ */
+export interface PreflightOptions {
+  projectPath: string;
+  prompt: string;
+  model?: string;
+  /** 恢复的会话 */
+  sessionId?: string;
+  /** Claude 项目 ID */
+  projectId?: string;
+  promptTemplate?: { id: string; variables?: Record<string, string> };
+  includeMemory?: boolean;
+  attachments?: StagedAttachment[];
+  includeRepoMap?: boolean;
+  repoMapTokens?: number;
+}
+
+export type ContextSource =
+  | 'cli_base'
+  | 'instructions'
+  | 'system_prompt'
+  | 'memory'
+  | 'history'
+  | 'attachments'
+  | 'repo_map'
+  | 'prompt';
+
+export type PreflightLevel = 'ok' | 'warning' | 'critical' | 'overflow';
+
+export interface PreflightSuggestion {
+  action: 'summarize_session' | 'trim_history' | 'remove_attachments' | 'shrink_repo_map' | 'shorten_prompt';
+  message: string;
+  /** 预计可减少的 token 数 */
+  savesTokens: number;
+}
+
+export interface PreflightReport {
+  engine: string;
+  model: string;
+  contextWindow: number;
+  estimatedTokens: number;
+  usagePercent: number;
+  level: PreflightLevel;
+  warning?: string | null;
+  components: { source: ContextSource; tokens: number; reported?: boolean }[];
+  suggestions: PreflightSuggestion[];
+}
+
+/**
+ * 单个模型的能力
+ */
+export interface ModelCapabilities {
+  id: string;
+  label: string;
+  /** 支持图片输入 */
+  vision: boolean;
+  toolUse: boolean;
+  /** 可选的推理强度（空表示不可调） */
+  reasoningEfforts: string[];
+  /** 支持扩展思考预算（Claude maxThinkingTokens） */
+  extendedThinking: boolean;
+  maxContext: number;
+  isDefault: boolean;
+  /** static: 内置表，probe: 引擎探测，provider: 当前代理商的自定义模型（能力为推断值） */
+  source: 'static' | 'probe' | 'provider';
+}
+
+export interface EngineCapabilities {
+  engine: 'claude' | 'codex' | 'gemini';
+  /** 当前代理商的接口地址（官方默认配置时为空） */
+  provider?: string | null;
+  defaultModel: string;
+  models: ModelCapabilities[];
+  cliVersion?: string | null;
+  probeError?: string | null;
+}
+
+export interface CapabilityMatrix {
+  engines: EngineCapabilities[];
+  generatedAt: string;
+}
+
+/**
+ * 模型别名在某个引擎（可选：某个代理商预设）下对应的模型
+ */
+export interface AliasTarget {
+  engine: 'claude' | 'codex' | 'gemini';
+  /** 代理商预设 ID；为空时作为该引擎的默认目标 */
+  provider?: string;
+  model: string;
+}
+
+export interface ModelAlias {
+  /** 如 "fast"、"smart"，执行时可直接作为模型名使用 */
+  name: string;
+  description?: string;
+  targets: AliasTarget[];
+}
+
+/**
+ * 路由规则：按顺序匹配，第一条命中的规则把模型改为 useModel（别名或具体模型）
+ */
+export interface RoutingRule {
+  /** 为空时由后端生成 */
+  id: string;
+  enabled: boolean;
+  engine?: 'claude' | 'codex' | 'gemini';
+  /** 只在请求的模型（或别名）为该值时生效 */
+  whenModel?: string;
+  minPromptTokens?: number;
+  maxPromptTokens?: number;
+  useModel: string;
+}
+
+export interface ModelRoutingConfig {
+  aliases: ModelAlias[];
+  rules: RoutingRule[];
+}
+
+export interface ModelResolution {
+  /** 传给 CLI 的模型（为空时使用 CLI 默认模型） */
+  model: string | null;
+  ruleId: string | null;
+  alias: string | null;
+}
+
+/**
+ * 恢复会话前的历史压缩配置
+ */
+export interface ResumeCompactionConfig {
+  enabled: boolean;
+  /** 历史估算超过该 token 数时才压缩 */
+  minTokens: number;
+  /** 原样保留的最近轮数 */
+  keepTurns: number;
+}
+
+/**
+ * 一次历史压缩记录
+ */
+export interface SessionCompaction {
+  compactedAt: string;
+  summarizedTurns: number;
+  keptTurns: number;
+  tokensBefore: number;
+  tokensAfter: number;
+  summary: string;
+  generatedBy: string;
+  /** 原会话文件的备份 */
+  backupPath: string;
+}
+
+/**
+ * 流式输出批量发送配置（新启动的会话生效）
+ */
+export interface StreamBatchingConfig {
+  enabled: boolean;
+  /** 最长缓存时间（毫秒，1-5000） */
+  flushIntervalMs: number;
+  /** 单批最多行数 */
+  maxBatch: number;
+  /** 合并同一条消息的连续增量 */
+  coalesceDeltas: boolean;
+}
+
+/**
+ * 后端消息语言
+ */
+export type BackendLocale = 'zh-CN' | 'en-US';
+
+/**
+ * 命令调用耗时统计（可选，仅本地）
+ */
+export interface CommandMetricsSettings {
+  enabled: boolean;
+}
+
+export interface CommandSample {
+  command: string;
+  durationMs: number;
+  success: boolean;
+  errorCode?: string | null;
+  recordedAt?: string;
+}
+
+export interface CommandMetrics {
+  command: string;
+  calls: number;
+  failures: number;
+  errorRate: number;
+  avgMs?: number | null;
+  p50Ms?: number | null;
+  p95Ms?: number | null;
+  maxMs?: number | null;
+  errorsByCode: Record<string, number>;
+  lastCalledAt?: string | null;
+}
+
+export interface CommandMetricsReport {
+  enabled: boolean;
+  period: string;
+  /** 按总耗时从高到低排序 */
+  commands: CommandMetrics[];
+  /** 最近的调用，最新的在前 */
+  recent: CommandSample[];
+}
+
+/**
+ * Codex 代理商备用链（自动故障转移）
+ */
+export interface CodexFailoverConfig {
+  enabled: boolean;
+  /** 按优先级排列的代理商预设 ID */
+  chain: string[];
+  /** 出现鉴权/连接/限流错误后，超过该秒数仍未开始输出即切换代理商 */
+  startTimeoutSecs: number;
+}
+
+/** `codex-failover` / `codex-failover:{sessionId}` 事件载荷 */
+export interface CodexFailoverEvent {
+  session_id: string;
+  from_provider: string;
+  to_provider: string;
+  reason: ProviderErrorCategory;
+  attempt: number;
+  /** 是否改写了 ~/.codex/config.toml（指定代理商预设的执行不会改写） */
+  config_rewritten: boolean;
+}
+
+/**
+ * 代理商预设包（团队共享，导出时已移除密钥）
+ */
+export interface ProviderPresetBundle {
+  version: number;
+  exportedAt: string;
+  claude: ProviderConfig[];
+  codex: CodexProviderConfig[];
+  gemini: GeminiProviderConfig[];
+  /** 导出时移除的密钥字段，如 apiKey、auth.OPENAI_API_KEY、env.GEMINI_API_KEY */
+  redacted: { engine: string; presetId: string; field: string }[];
+}
+
+/** 导入时 ID 冲突的处理方式 */
+export type PresetConflictStrategy = "skip" | "overwrite" | "rename";
+
+export interface ImportedPreset {
+  engine: "claude" | "codex" | "gemini";
+  id: string;
+  name: string;
+  action: "added" | "overwritten" | "renamed" | "skipped";
+  /** 重命名前的 ID */
+  originalId?: string | null;
+  /** 仍需填写的密钥字段 */
+  missingSecrets: string[];
+}
+
+/**
+ * anycode:// 深度链接请求
+ */
+export interface DeepLinkRequest {
+  projectPath?: string | null;
+  prompt?: string | null;
+  engine?: string | null;
+  model?: string | null;
+}
+
+export interface DeepLinkSettings {
+  /** 链接中的提示词直接执行，不在界面中确认 */
+  autoRunPrompts: boolean;
+}
+
+/**
+ * 系统托盘设置
+ */
+export interface TraySettings {
+  /** 关闭主窗口时隐藏到托盘，执行在后台继续 */
+  closeToTray: boolean;
+}
+
+export type HotkeyAction = 'quick_capture' | 'rerun_last_prompt';
+
+/**
+ * 全局快捷键绑定
+ */
+export interface HotkeyBinding {
+  action: HotkeyAction;
+  /** 如 "CommandOrControl+Shift+Space" */
+  shortcut: string;
+  enabled: boolean;
+}
+
+export interface HotkeyBindingStatus extends HotkeyBinding {
+  /** 是否已成功向系统注册（可能被其他应用占用） */
+  registered: boolean;
+}
+
+/**
+ * 桌面通知设置
+ */
+export interface NotificationSettings {
+  enabled: boolean;
+  /** 仅在应用窗口都不在前台时弹出 */
+  onlyWhenUnfocused: boolean;
+  onCompleted: boolean;
+  onFailed: boolean;
+  onBudget: boolean;
+  onApproval: boolean;
+  quietHoursEnabled: boolean;
+  /** HH:MM，本地时间；开始晚于结束表示跨午夜 */
+  quietStart: string;
+  quietEnd: string;
+}
+
+export type NotificationKind = 'completed' | 'failed' | 'budget' | 'approval';
+
+/**
+ * 通知历史记录（也通过 `notification-recorded` 事件推送）
+ */
+export interface NotificationRecord {
+  id: number;
+  kind: NotificationKind;
+  engine: string;
+  sessionId: string;
+  title: string;
+  body: string;
+  createdAt: string;
+  /** 是否实际弹出了系统通知 */
+  delivered: boolean;
+  /** 未弹出的原因：disabled / focused / quiet_hours / error */
+  suppressedReason?: string | null;
+  read: boolean;
+}
+
+export type NotificationChannelType = 'sound' | 'webhook' | 'slack' | 'discord';
+
+/**
+ * 外发通知渠道（提示音、webhook、Slack / Discord）
+ */
+export interface NotificationChannel {
+  id: string;
+  name: string;
+  channelType: NotificationChannelType;
+  enabled: boolean;
+  /** 订阅的通知类型，默认完成和失败 */
+  events: NotificationKind[];
+  /** webhook / Slack / Discord 地址 */
+  url?: string | null;
+  /** 提示音文件路径，为空时使用系统默认提示音 */
+  sound?: string | null;
+  createdAt: string;
+  updatedAt: string;
+}
+
+/**
+ * 新建或更新通知渠道的参数（不传 id 时新建）
+ */
+export interface NotificationChannelInput {
+  id?: string;
+  name: string;
+  channelType: NotificationChannelType;
+  enabled?: boolean;
+  events?: NotificationKind[];
+  url?: string;
+  sound?: string;
+}
+
+/**
+ * Hook 桥接收到的 Claude Code hook 事件（也通过 `claude-hook-event[:sessionId]` 事件推送）
+ */
+export interface HookBridgeEvent {
+  sessionId: string;
+  /** hook_event_name：PreToolUse / PostToolUse / Stop / Notification */
+  event: string;
+  toolName?: string | null;
+  /** Edit / Write 等工具操作的文件 */
+  filePath?: string | null;
+  cwd?: string | null;
+  receivedAt: string;
+  /** Claude Code 传给 hook 的原始 JSON */
+  payload: Record<string, any>;
+}
+
+export interface HookBridgeStatus {
+  installed: boolean;
+  running: boolean;
+  port?: number | null;
+  scriptPath: string;
+}
+
+/**
+ * settings.json 中的单条 hook（按 scope/事件/matcher 展开）
+ */
+export interface ClaudeHookEntry {
+  /** `{scope}:{event}:{group}:{index}` */
+  id: string;
+  scope: 'user' | 'project' | 'local';
+  event: string;
+  matcher?: string | null;
+  type: string;
+  command: string;
+  timeout?: number | null;
+  settingsPath: string;
+}
+
+export interface HooksSchemaValidation {
+  valid: boolean;
+  errors: string[];
+  warnings: string[];
+}
+
+/**
+ * Hook 测试结果
+ */
+export interface HookTestResult {
+  hookId: string;
+  event: string;
+  command: string;
+  /** 通过 stdin 传入的事件 JSON */
+  payload: Record<string, any>;
+  exitCode?: number | null;
+  stdout: string;
+  stderr: string;
+  jsonOutput?: Record<string, any> | null;
+  /** 退出码为 2，Claude Code 会阻止本次操作 */
+  blocking: boolean;
+  timedOut: boolean;
+  durationMs: number;
+}
+
+/**
+ * Claude 子代理（~/.claude/agents 或项目 .claude/agents 下的 Markdown 文件）
+ */
+export interface ClaudeAgent {
+  name: string;
+  description: string;
+  /** null 表示继承全部工具 */
+  tools?: string[] | null;
+  model?: string | null;
+  color?: string | null;
+  /** 正文（子代理的系统提示词） */
+  prompt: string;
+  /** 文件原始内容 */
+  content: string;
+  path: string;
+  scope: "user" | "project";
+  /** 用户级子代理是否已在当前项目启用 */
+  activeInProject: boolean;
+  /** frontmatter 校验错误 */
+  errors: string[];
+}
+
+export interface ClaudeAgentValidation {
+  valid: boolean;
+  name?: string | null;
+  errors: string[];
+  warnings: string[];
+}
+
+/**
+ * 团队共享配置仓库（只读 git 仓库）
+ */
+export interface SharedConfigSettings {
+  /** 为空表示未启用 */
+  repoUrl: string;
+  /** 为空时使用远端默认分支 */
+  branch?: string | null;
+}
+
+export type SharedConfigKind =
+  | "claude_provider"
+  | "codex_provider"
+  | "gemini_provider"
+  | "prompt_template"
+  | "claude_prompt_template"
+  | "gemini_prompt_template"
+  | "mcp_server"
+  | "permission_profile";
+
+/** 来源为共享仓库的一项配置 */
+export interface SharedConfigItem {
+  kind: SharedConfigKind;
+  id: string;
+}
+
+export interface SharedConfigConflict {
+  kind: SharedConfigKind;
+  id: string;
+  reason: string;
+}
+
+/** 用户批准应用的共享配置（按内容哈希） */
+export interface SharedConfigApproval {
+  kind: SharedConfigKind;
+  id: string;
+  hash: string;
+}
+
+/** 等待批准的代理商预设或权限档案 */
+export interface PendingSharedConfigItem extends SharedConfigApproval {
+  /** false 表示会替换本地已有的同名配置 */
+  isNew: boolean;
+  /** 共享版本内容（代理商已去掉密钥），供审阅 */
+  content: unknown;
+}
+
+export interface SharedConfigSyncReport {
+  commit: string;
+  syncedAt: string;
+  added: SharedConfigItem[];
+  updated: SharedConfigItem[];
+  removed: SharedConfigItem[];
+  unchanged: number;
+  conflicts: SharedConfigConflict[];
+  /** 需要逐项批准后才会应用 */
+  pending: PendingSharedConfigItem[];
+}
+
+export interface SharedConfigStatus {
+  settings: SharedConfigSettings;
+  commit?: string | null;
+  syncedAt?: string | null;
+  /** 来源为共享仓库的配置 */
+  items: SharedConfigItem[];
+  /** 上次同步的冲突 */
+  conflicts: SharedConfigConflict[];
+  /** 等待批准的配置 */
+  pending: PendingSharedConfigItem[];
+}
+
+/**
+ * 限流自动重排队设置
+ */
+export interface RateLimitSettings {
+  autoRequeue: boolean;
+  /** 没有 retry-after 时的初始退避秒数，之后每次翻倍 */
+  defaultBackoffSecs: number;
+  maxBackoffSecs: number;
+  /** 同一提示词最多重新提交的次数 */
+  maxRequeues: number;
+}
+
+/** `rate-limited` / `rate-limited:{sessionId}` 事件载荷 */
+export interface RateLimitedEvent {
+  engine: "claude" | "codex" | "gemini";
+  session_id: string;
+  /** 429（限流）或 529（服务过载） */
+  status: number;
+  message: string;
+  retry_after_secs?: number | null;
+  detected_at: string;
+}
+
+/** `rate-limit-requeued` / `rate-limit-requeued:{sessionId}` 事件载荷 */
+export interface RateLimitRequeuedEvent {
+  engine: "claude" | "codex" | "gemini";
+  session_id: string;
+  delay_secs: number;
+  retry_at: string;
+  requeue: number;
+  max_requeues: number;
+}
+
+/** 后处理命令执行结果（`run_post_processors`） */
+export interface ProcessorResult {
+  name: string;
+  kind: "format" | "lint" | "test";
+  command: string;
+  status: "passed" | "failed" | "timed_out" | "error";
+  exitCode?: number | null;
+  durationMs: number;
+  /** stdout + stderr 的末尾部分 */
+  output: string;
+}
+
+/** 提示词完成后的验证记录（`verification-recorded:{sessionId}` 事件载荷） */
+export interface VerificationRecord {
+  id: number;
+  sessionId: string;
+  engine: "claude" | "codex" | "gemini";
+  projectPath: string;
+  promptIndex: number;
+  passed: boolean;
+  results: ProcessorResult[];
+  createdAt: string;
+}
+
+export interface VerificationOutcome {
+  record: VerificationRecord;
+  /** 项目开启 autoFollowUp 且有失败时，需要发送给引擎的跟进提示词 */
+  followUpPrompt?: string | null;
+}
+
+/** 持续测试的一次运行（`test-watch-result:{sessionId}` 事件载荷） */
+export interface TestWatchRun {
+  id: number;
+  sessionId: string;
+  /** 触发本次运行的变更所属的提示词 */
+  promptIndex: number;
+  status: ProcessorResult["status"];
+  exitCode?: number | null;
+  durationMs: number;
+  passedCount?: number | null;
+  failedCount?: number | null;
+  /** 一行摘要，如 "12 passed, 1 failed" */
+  summary: string;
+  output: string;
+  createdAt: string;
+}
+
+export interface TestWatchSeries {
+  runs: TestWatchRun[];
+  /** 最近一次失败时，测试从哪条提示词开始失败 */
+  brokenSincePrompt?: number | null;
+}
+
+/** 远程 SSH 执行模式的主机配置 */
+export interface RemoteConfig {
+  /** 主机名、IP 或 ~/.ssh/config 中的 Host 别名 */
+  host: string;
+  user?: string | null;
+  port?: number | null;
+  identityFile?: string | null;
+  /** 远程 Codex 可执行文件，留空使用远程 PATH 中的 codex */
+  codexPath?: string | null;
+  /** Claude 也在该主机上执行 */
+  runClaude?: boolean;
+  /** 远程 Claude 可执行文件，留空使用远程 PATH 中的 claude */
+  claudePath?: string | null;
+}
+
+/** 执行选项中的自动修复循环设置 */
+export interface AutoFixOptions {
+  /** 验证命令，留空时使用项目的后处理命令 */
+  verifyCommand?: string;
+  /** 最多发送的修复提示词数（默认 3） */
+  maxIterations?: number;
+  /** 验证命令的超时时间（秒），默认 300 */
+  timeoutSecs?: number;
+}
+
+/** 自动修复循环的阶段事件（`auto-fix:{sessionId}` 事件载荷） */
+export interface AutoFixEvent {
+  engine: "claude" | "codex" | "gemini";
+  /** 引擎的真实会话 ID */
+  sessionId: string;
+  phase: "verifying" | "passed" | "fixing" | "exhausted" | "cancelled";
+  /** 已发送的修复提示词数 */
+  iteration: number;
+  maxIterations: number;
+  results: ProcessorResult[];
+}
+
+/**
+ * IDE operation result
+ */
+export interface IDEResult {
+  success: boolean;
+  message: string;
+  error?: string;
+}
+
+/**
+ * API client for interacting with the Rust backend
+ */
+export const api = {
+  /**
+   * Lists all projects in the ~/.claude/projects directory
+   * @returns Promise resolving to an array of projects
+   */
+  async listProjects(): Promise<Project[]> {
+    try {
+      return await invoke<Project[]>("list_projects");
+    } catch (error) {
+      console.error("Failed to list projects:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Retrieves sessions for a specific project (both Claude and Codex)
+   * @param projectId - The ID of the project to retrieve sessions for
+   * @param projectPath - Optional project path to filter Codex sessions (if not provided, tries to infer from Claude sessions)
+   * @returns Promise resolving to an array of sessions
+   */
+  async getProjectSessions(projectId: string, projectPath?: string): Promise<Session[]> {
+    try {
+      // Always fetch Claude sessions; if projectPath is known, load Codex sessions in parallel.
+      const claudePromise = invoke<Session[]>('get_project_sessions', { projectId });
+      const codexPromise = projectPath
+        ? this.listCodexSessionsForProject(projectPath).catch(err => {
+            console.warn('[SessionList] Failed to load Codex sessions:', err);
+            return [];
+          })
+        : Promise.resolve([] as import('@/types/codex').CodexSession[]);
+
+      const [claudeSessions, codexResults] = await Promise.all([claudePromise, codexPromise]);
+      console.log('[SessionList] Claude sessions:', claudeSessions.length);
+
+      const targetPath = projectPath || claudeSessions[0]?.project_path;
+
+      // If projectPath wasn't provided and we now have it, fetch Codex sessions (fallback)
+      const resolvedCodexResults = codexResults.length
+        ? codexResults
+        : targetPath
+          ? await this.listCodexSessionsForProject(targetPath).catch(err => {
+              console.warn('[SessionList] Failed to load Codex sessions:', err);
+              return [];
+            })
+          : [];
+
+      const codexSessions: Session[] = resolvedCodexResults.map(cs => ({
+        id: cs.id,
+        project_id: projectId,
+        project_path: cs.projectPath,
+        created_at: cs.createdAt,
+        model: cs.model || 'gpt-5.1-codex-max',
+        engine: 'codex' as const,
+        first_message: cs.firstMessage || `Codex Session`,
+        last_assistant_message: cs.lastAssistantMessage,
+        last_message_timestamp: cs.lastMessageTimestamp,
+      }));
+
+      // Merge and sort by creation time
+      const allSessions = [...claudeSessions.map(s => ({ ...s, engine: 'claude' as const })), ...codexSessions];
+      allSessions.sort((a, b) => b.created_at - a.created_at);
+
+      return allSessions;
+    } catch (error) {
+      console.error("Failed to get project sessions:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a session and all its associated data
+   * @param sessionId - The session ID to delete
+   * @param projectId - The project ID this session belongs to
+   * @returns Promise resolving to success message
+   */
+  async deleteSession(sessionId: string, projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_session', { sessionId, projectId });
+    } catch (error) {
+      console.error("Failed to delete session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes multiple sessions in batch
+   * @param sessionIds - Array of session IDs to delete
+   * @param projectId - The project ID these sessions belong to
+   * @returns Promise resolving to success message
+   */
+  async deleteSessionsBatch(sessionIds: string[], projectId: string): Promise<string> {
+    try {
       return await invoke<string>('delete_sessions_batch', { sessionIds, projectId });
     } catch (error) {
-      console.error("Failed to batch delete sessions:", error);
+      console.error("Failed to batch delete sessions:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes a project from the project list (without deleting files)
+   * @param projectId - The ID of the project to remove from list
+   * @returns Promise resolving to success message
+   */
+  async deleteProject(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_project', { projectId });
+    } catch (error) {
+      console.error("Failed to remove project from list:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Restores a hidden project back to the project list
+   * @param projectId - The ID of the project to restore
+   * @returns Promise resolving to success message
+   */
+  async restoreProject(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('restore_project', { projectId });
+    } catch (error) {
+      console.error("Failed to restore project:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all hidden projects
+   * @returns Promise resolving to array of hidden project IDs
+   */
+  async listHiddenProjects(): Promise<string[]> {
+    try {
+      return await invoke<string[]>('list_hidden_projects');
+    } catch (error) {
+      console.error("Failed to list hidden projects:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Permanently delete a project and all its files
+   * @param projectId - The project ID to permanently delete
+   * @returns Promise resolving to success message
+   */
+  async deleteProjectPermanently(projectId: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_project_permanently', { projectId });
+    } catch (error) {
+      console.error("Failed to permanently delete project:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the Claude settings file
+   * @returns Promise resolving to the settings object
+   */
+  async getClaudeSettings(): Promise<ClaudeSettings> {
+    try {
+      const result = await invoke<ClaudeSettings>("get_claude_settings");
+      console.log("Raw result from get_claude_settings:", result);
+      
+      // Due to #[serde(flatten)] in Rust, the result is directly the settings object
+      return result;
+    } catch (error) {
+      console.error("Failed to get Claude settings:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // CLAUDE SETTINGS.JSON FILE PROVIDERS (AnyCode)
+  // ============================================================================
+
+  /**
+   * Reads raw ~/.claude/settings.json as text
+   */
+  async readClaudeSettingsJsonText(): Promise<string> {
+    try {
+      return await invoke<string>("read_claude_settings_json_text");
+    } catch (error) {
+      console.error("Failed to read Claude settings.json:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Writes raw ~/.claude/settings.json as text (expects valid JSON object)
+   */
+  async writeClaudeSettingsJsonText(content: string): Promise<string> {
+    try {
+      return await invoke<string>("write_claude_settings_json_text", { content });
+    } catch (error) {
+      console.error("Failed to write Claude settings.json:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads raw ~/.claude.json as text
+   */
+  async readClaudeJsonText(): Promise<string> {
+    try {
+      return await invoke<string>("read_claude_json_text");
+    } catch (error) {
+      console.error("Failed to read .claude.json:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Writes raw ~/.claude.json as text (expects valid JSON object)
+   */
+  async writeClaudeJsonText(content: string): Promise<string> {
+    try {
+      return await invoke<string>("write_claude_json_text", { content });
+    } catch (error) {
+      console.error("Failed to write .claude.json:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Writes both ~/.claude/settings.json and ~/.claude.json
+   */
+  async writeClaudeConfigFiles(settingsJson: string, claudeJson: string): Promise<string> {
+    try {
+      return await invoke<string>("write_claude_config_files", { settingsJson, claudeJson });
+    } catch (error) {
+      console.error("Failed to write Claude config files:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets list of saved Claude settings.json presets
+   */
+  async getClaudeSettingsFileProviders(): Promise<ClaudeSettingsFileProvider[]> {
+    try {
+      return await invoke<ClaudeSettingsFileProvider[]>("get_claude_settings_file_providers");
+    } catch (error) {
+      console.error("Failed to get Claude settings.json presets:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds a Claude settings.json preset
+   */
+  async addClaudeSettingsFileProvider(config: Omit<ClaudeSettingsFileProvider, 'id'>): Promise<string> {
+    const id = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
+
+    const fullConfig: ClaudeSettingsFileProvider = {
+      ...config,
+      id,
+      createdAt: Date.now(),
+    };
+
+    try {
+      return await invoke<string>("add_claude_settings_file_provider", { config: fullConfig });
+    } catch (error) {
+      console.error("Failed to add Claude settings.json preset:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates a Claude settings.json preset
+   */
+  async updateClaudeSettingsFileProvider(config: ClaudeSettingsFileProvider): Promise<string> {
+    try {
+      return await invoke<string>("update_claude_settings_file_provider", { config });
+    } catch (error) {
+      console.error("Failed to update Claude settings.json preset:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a Claude settings.json preset
+   */
+  async deleteClaudeSettingsFileProvider(id: string): Promise<string> {
+    try {
+      return await invoke<string>("delete_claude_settings_file_provider", { id });
+    } catch (error) {
+      console.error("Failed to delete Claude settings.json preset:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Opens a new Claude Code session
+   * @param path - Optional path to open the session in
+   * @returns Promise resolving when the session is opened
+   */
+  async openNewSession(path?: string): Promise<string> {
+    try {
+      return await invoke<string>("open_new_session", { path });
+    } catch (error) {
+      console.error("Failed to open new session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the CLAUDE.md system prompt file
+   * @returns Promise resolving to the system prompt content
+   */
+  async getSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_system_prompt");
+    } catch (error) {
+      console.error("Failed to get system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks if Claude Code is installed and gets its version
+   * @returns Promise resolving to the version status
+   */
+  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+    try {
+      return await invoke<ClaudeVersionStatus>("check_claude_version");
+    } catch (error) {
+      console.error("Failed to check Claude version:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the CLAUDE.md system prompt file
+   * @param content - The new content for the system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the AGENTS.md system prompt file from Codex directory
+   * @returns Promise resolving to the Codex system prompt content
+   */
+  async getCodexSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_codex_system_prompt");
+    } catch (error) {
+      console.error("Failed to get Codex system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the AGENTS.md system prompt file to Codex directory
+   * @param content - The new content for the Codex system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveCodexSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_codex_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save Codex system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the GEMINI.md system prompt file from Gemini directory
+   * @returns Promise resolving to the content of GEMINI.md
+   */
+  async getGeminiSystemPrompt(): Promise<string> {
+    try {
+      return await invoke<string>("get_gemini_system_prompt");
+    } catch (error) {
+      console.error("Failed to get Gemini system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the GEMINI.md system prompt file to Gemini directory
+   * @param content - The new content for the Gemini system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveGeminiSystemPrompt(content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_gemini_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save Gemini system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the Claude settings file
+   * @param settings - The settings object to save
+   * @returns Promise resolving when the settings are saved
+   */
+  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+    try {
+      console.log("Saving Claude settings:", settings);
+      return await invoke<string>("save_claude_settings", { settings });
+    } catch (error) {
+      console.error("Failed to save Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates the thinking mode by modifying MAX_THINKING_TOKENS in settings.json
+   * @param enabled - Whether to enable thinking mode
+   * @param tokens - Optional token limit (defaults to 10000)
+   * @returns Promise resolving when the settings are updated
+   */
+  async updateThinkingMode(enabled: boolean, tokens?: number): Promise<string> {
+    try {
+      console.log("Updating thinking mode:", { enabled, tokens });
+      return await invoke<string>("update_thinking_mode", { enabled, tokens });
+    } catch (error) {
+      console.error("Failed to update thinking mode:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get Claude execution configuration
+   * @returns Promise resolving to the current execution config
+   */
+  async getClaudeExecutionConfig(): Promise<ClaudeExecutionConfig> {
+    try {
+      return await invoke<ClaudeExecutionConfig>("get_claude_execution_config");
+    } catch (error) {
+      console.error("Failed to get Claude execution config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Update Claude execution configuration
+   * @param config - The new execution configuration
+   * @returns Promise resolving when the config is saved
+   */
+  async updateClaudeExecutionConfig(config: ClaudeExecutionConfig): Promise<void> {
+    try {
+      console.log("Updating Claude execution config:", config);
+      return await invoke<void>("update_claude_execution_config", { config });
+    } catch (error) {
+      console.error("Failed to update Claude execution config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Finds all CLAUDE.md files in a project directory
+   * @param projectPath - The absolute path to the project
+   * @returns Promise resolving to an array of CLAUDE.md files
+   */
+  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+    try {
+      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+    } catch (error) {
+      console.error("Failed to find CLAUDE.md files:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @returns Promise resolving to the file content
+   */
+  async readClaudeMdFile(filePath: string): Promise<string> {
+    try {
+      return await invoke<string>("read_claude_md_file", { filePath });
+    } catch (error) {
+      console.error("Failed to read CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @param content - The new content for the file
+   * @returns Promise resolving when the file is saved
+   */
+  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+    try {
+      return await invoke<string>("save_claude_md_file", { filePath, content });
+    } catch (error) {
+      console.error("Failed to save CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+
+  /**
+   * Loads the JSONL history for a specific session (Claude or Codex)
+   */
+  async loadSessionHistory(sessionId: string, projectId: string, engine?: 'claude' | 'codex'): Promise<any[]> {
+    // For Codex sessions, read directly from .codex/sessions
+    if (engine === 'codex') {
+      return this.loadCodexSessionHistory(sessionId);
+    }
+    // For Claude sessions, use existing backend
+    return invoke("load_session_history", { sessionId, projectId });
+  },
+
+  /**
+   * 🆕 Loads Codex session history from JSONL file
+   */
+  async loadCodexSessionHistory(sessionId: string): Promise<any[]> {
+    try {
+      return await invoke("load_codex_session_history", { sessionId });
+    } catch (error) {
+      console.error("Failed to load Codex session history:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 分页加载会话消息（reverse 时 offset 0 为最新一条，结果按最新在前排列）
+   * @param project - Claude 项目 ID（可省略）或 Gemini 项目路径（必填）
+   */
+  async loadSessionMessages(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    offset: number,
+    limit: number,
+    options?: { reverse?: boolean; project?: string }
+  ): Promise<SessionMessagePage> {
+    try {
+      return await invoke<SessionMessagePage>("load_session_messages", {
+        engine,
+        sessionId,
+        offset,
+        limit,
+        reverse: options?.reverse,
+        project: options?.project,
+      });
+    } catch (error) {
+      console.error("Failed to load session messages:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes a new interactive Claude Code session with streaming output
+   * @param planMode - Enable Plan Mode for read-only research and planning
+   */
+  async executeClaudeCode(projectPath: string, prompt: string, model: string | undefined, planMode?: boolean, maxThinkingTokens?: number, attachments?: StagedAttachment[], autoFix?: AutoFixOptions): Promise<void> {
+    return invoke("execute_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens, attachments, autoFix });
+  },
+
+  /**
+   * Continues an existing Claude Code conversation with streaming output
+   * @param planMode - Enable Plan Mode for read-only research and planning
+   */
+  async continueClaudeCode(projectPath: string, prompt: string, model: string | undefined, planMode?: boolean, maxThinkingTokens?: number, attachments?: StagedAttachment[], autoFix?: AutoFixOptions): Promise<void> {
+    return invoke("continue_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens, attachments, autoFix });
+  },
+
+  /**
+   * Resumes an existing Claude Code session by ID with streaming output
+   * @param planMode - Enable Plan Mode for read-only research and planning
+   */
+  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string | undefined, planMode?: boolean, maxThinkingTokens?: number, attachments?: StagedAttachment[], autoFix?: AutoFixOptions): Promise<void> {
+    return invoke("resume_claude_code", { projectPath, sessionId, prompt, model, planMode, maxThinkingTokens, attachments, autoFix });
+  },
+
+  /**
+   * Cancels the currently running Claude Code execution
+   * @param sessionId - Optional session ID to cancel a specific session
+   */
+  async cancelClaudeExecution(sessionId?: string): Promise<void> {
+    return invoke("cancel_claude_execution", { sessionId });
+  },
+
+  /**
+   * Lists all currently running Claude sessions
+   * @returns Promise resolving to list of running Claude sessions
+   */
+  async listRunningClaudeSessions(): Promise<any[]> {
+    return invoke("list_running_claude_sessions");
+  },
+
+  /**
+   * Gets live output from a Claude session
+   * @param sessionId - The session ID to get output for
+   * @returns Promise resolving to the current live output
+   */
+  async getClaudeSessionOutput(sessionId: string): Promise<string> {
+    return invoke("get_claude_session_output", { sessionId });
+  },
+
+  /**
+   * Lists files and directories in a given path
+   */
+  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
+    return invoke("list_directory_contents", { directoryPath });
+  },
+
+  /**
+   * Searches for files and directories matching a pattern
+   */
+  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
+    return invoke("search_files", { basePath, query });
+  },
+
+  /**
+   * Gets overall usage statistics
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageStats(): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_stats");
+    } catch (error) {
+      console.error("Failed to get usage stats:", error);
+      throw error;
+    }
+  },
+
+
+  /**
+   * Gets usage statistics filtered by date range
+   * @param startDate - Start date (ISO format)
+   * @param endDate - End date (ISO format)
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+    try {
+      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+    } catch (error) {
+      console.error("Failed to get usage by date range:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics grouped by session
+   * @param since - Optional start date (YYYYMMDD)
+   * @param until - Optional end date (YYYYMMDD)
+   * @param order - Optional sort order ('asc' or 'desc')
+   * @returns Promise resolving to an array of session usage data
+   */
+  async getSessionStats(
+    since?: string,
+    until?: string,
+    order?: "asc" | "desc"
+  ): Promise<ProjectUsage[]> {
+    try {
+      return await invoke<ProjectUsage[]>("get_session_stats", {
+        since,
+        until,
+        order,
+      });
+    } catch (error) {
+      console.error("Failed to get session stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets multi-engine usage statistics (Claude, Codex, Gemini)
+   * @param engine - Engine filter ('all', 'claude', 'codex', 'gemini')
+   * @param startDate - Optional start date (YYYY-MM-DD)
+   * @param endDate - Optional end date (YYYY-MM-DD)
+   * @returns Promise resolving to multi-engine usage statistics
+   */
+  async getMultiEngineUsageStats(
+    engine?: EngineType,
+    startDate?: string,
+    endDate?: string
+  ): Promise<MultiEngineUsageStats> {
+    try {
+      return await invoke<MultiEngineUsageStats>("get_multi_engine_usage_stats", {
+        engine: engine || 'all',
+        startDate,
+        endDate,
+      });
+    } catch (error) {
+      console.error("Failed to get multi-engine usage stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets cache tokens for a specific session
+   * @param sessionId - The session ID to get cache tokens for
+   * @returns Promise resolving to session cache tokens
+   */
+  async getSessionCacheTokens(sessionId: string): Promise<SessionCacheTokens> {
+    try {
+      return await invoke<SessionCacheTokens>("get_session_cache_tokens", { sessionId });
+    } catch (error) {
+      console.error("Failed to get session cache tokens:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 导入各 CLI 的原生用量数据（Claude/Codex/Gemini），与 AnyCode 记录去重合并并对账
+   * @param engines - 要导入的引擎，默认全部
+   * @returns Promise resolving to the reconciliation report
+   */
+  async importNativeUsage(engines?: Exclude<EngineType, 'all'>[]): Promise<UsageReconciliation> {
+    try {
+      return await invoke<UsageReconciliation>("import_native_usage", { engines });
+    } catch (error) {
+      console.error("Failed to import native usage:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取代理商的延迟与错误率指标（连接测试 + 实际会话）
+   * @param providerId - 代理商预设 ID（未匹配预设时为 base_url，官方为 "official"）
+   * @param period - 时间段，如 "24h"、"7d"、"30d"、"all"，默认 "7d"
+   */
+  async getProviderMetrics(providerId: string, period?: string): Promise<ProviderMetrics> {
+    try {
+      return await invoke<ProviderMetrics>("get_provider_metrics", { providerId, period });
+    } catch (error) {
+      console.error("Failed to get provider metrics:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取 AnyCode 数据目录信息
+   */
+  async getDataRootInfo(): Promise<DataRootInfo> {
+    try {
+      return await invoke<DataRootInfo>("get_data_root_info");
+    } catch (error) {
+      console.error("Failed to get data root info:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 暂存到新数据目录的迁移，重启应用后复制数据；目标为当前目录时取消暂存的迁移（返回 null）
+   * @param mode - "default" / "custom" / "portable"
+   * @param path - 自定义目录（仅 custom 模式）
+   */
+  async migrateDataRoot(
+    mode: Exclude<DataRootMode, 'env'>,
+    path?: string
+  ): Promise<PendingDataRootMigration | null> {
+    try {
+      return await invoke<PendingDataRootMigration | null>("migrate_data_root", { mode, path });
+    } catch (error) {
+      console.error("Failed to migrate data root:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 创建数据备份（默认保存到 ~/.anycode/backups）
+   * @param includeSecrets - 是否包含代理商密钥（需设置密码，加密保存）
+   * @param passphrase - 密钥加密密码
+   * @param outputPath - 自定义备份文件路径
+   */
+  async createBackup(includeSecrets: boolean, passphrase?: string, outputPath?: string): Promise<BackupResult> {
+    try {
+      return await invoke<BackupResult>("create_backup", { includeSecrets, passphrase, outputPath });
+    } catch (error) {
+      console.error("Failed to create backup:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 预览恢复：列出将新建或覆盖的文件
+   */
+  async previewRestore(path: string, passphrase?: string): Promise<RestorePreview> {
+    try {
+      return await invoke<RestorePreview>("preview_restore", { path, passphrase });
+    } catch (error) {
+      console.error("Failed to preview restore:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 从备份恢复（数据库在重启后生效）
+   */
+  async restoreBackup(path: string, passphrase?: string): Promise<RestoreReport> {
+    try {
+      return await invoke<RestoreReport>("restore_backup", { path, passphrase });
+    } catch (error) {
+      console.error("Failed to restore backup:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 读取通用设置，未设置时返回 null
+   */
+  async settingsGet<T = unknown>(key: string): Promise<T | null> {
+    try {
+      return await invoke<T | null>("settings_get", { key });
+    } catch (error) {
+      console.error("Failed to get setting:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存通用设置（任意可序列化的值），订阅了该键的窗口会收到 settings-changed 事件
+   */
+  async settingsSet(key: string, value: unknown): Promise<void> {
+    try {
+      await invoke("settings_set", { key, value });
+    } catch (error) {
+      console.error("Failed to set setting:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 删除通用设置，返回该键之前是否存在
+   */
+  async settingsDelete(key: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("settings_delete", { key });
+    } catch (error) {
+      console.error("Failed to delete setting:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 当前窗口订阅设置变更，返回这些键的当前值（未设置的键不在结果中）
+   */
+  async settingsWatch(keys: string[]): Promise<Record<string, unknown>> {
+    try {
+      return await invoke<Record<string, unknown>>("settings_watch", { keys });
+    } catch (error) {
+      console.error("Failed to watch settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 取消订阅（不传 keys 时取消当前窗口的全部订阅）
+   */
+  async settingsUnwatch(keys?: string[]): Promise<void> {
+    try {
+      await invoke("settings_unwatch", { keys });
+    } catch (error) {
+      console.error("Failed to unwatch settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取会话保留策略（未保存过时返回默认策略）
+   */
+  async getRetentionPolicy(): Promise<RetentionPolicy> {
+    try {
+      return await invoke<RetentionPolicy>("get_retention_policy");
+    } catch (error) {
+      console.error("Failed to get retention policy:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存会话保留策略
+   */
+  async saveRetentionPolicy(policy: RetentionPolicy): Promise<void> {
+    try {
+      await invoke("save_retention_policy", { policy });
+    } catch (error) {
+      console.error("Failed to save retention policy:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 预览将被清理的会话、变更记录和日志（不做改动）
+   * @param policy - 不传时使用已保存的策略
+   */
+  async previewCleanup(policy?: RetentionPolicy): Promise<CleanupPreview> {
+    try {
+      return await invoke<CleanupPreview>("preview_cleanup", { policy });
+    } catch (error) {
+      console.error("Failed to preview cleanup:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 立即按保留策略清理
+   * @param policy - 不传时使用已保存的策略
+   */
+  async runCleanup(policy?: RetentionPolicy): Promise<CleanupResult> {
+    try {
+      return await invoke<CleanupResult>("run_cleanup", { policy });
+    } catch (error) {
+      console.error("Failed to run cleanup:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 统计 ~/.claude、~/.codex、~/.gemini 和 AnyCode 数据的磁盘占用（按类别）
+   */
+  async getStorageReport(): Promise<StorageReport> {
+    try {
+      return await invoke<StorageReport>("get_storage_report");
+    } catch (error) {
+      console.error("Failed to get storage report:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 计算文本的 token 数（按模型选择词表，未知模型使用 cl100k_base）
+   */
+  async countTokens(text: string, model?: string): Promise<number> {
+    try {
+      return await invoke<number>("count_tokens", { model, text });
+    } catch (error) {
+      console.error("Failed to count tokens:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 计算一组对话消息占用的上下文 token 数
+   */
+  async countContextTokens(messages: TokenCountMessage[], model?: string): Promise<ContextTokenCount> {
+    try {
+      return await invoke<ContextTokenCount>("count_context_tokens", { model, messages });
+    } catch (error) {
+      console.error("Failed to count context tokens:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 发送前估算上下文大小，接近或超出模型上下文窗口时返回警告和建议
+   */
+  async preflightExecution(
+    engine: 'claude' | 'codex' | 'gemini',
+    options: PreflightOptions
+  ): Promise<PreflightReport> {
+    try {
+      return await invoke<PreflightReport>("preflight_execution", { engine, options });
+    } catch (error) {
+      console.error("Failed to run execution preflight:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取各引擎的模型能力矩阵（结果缓存 10 分钟，refresh 为 true 时重新探测）
+   */
+  async getCapabilityMatrix(refresh?: boolean): Promise<CapabilityMatrix> {
+    try {
+      return await invoke<CapabilityMatrix>("get_capability_matrix", { refresh });
+    } catch (error) {
+      console.error("Failed to get capability matrix:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 清除能力矩阵缓存
+   */
+  async invalidateCapabilityMatrix(): Promise<void> {
+    try {
+      await invoke("invalidate_capability_matrix");
+    } catch (error) {
+      console.error("Failed to invalidate capability matrix:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取模型别名与路由规则
+   */
+  async getModelRouting(): Promise<ModelRoutingConfig> {
+    try {
+      return await invoke<ModelRoutingConfig>("get_model_routing");
+    } catch (error) {
+      console.error("Failed to get model routing:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 新增或更新模型别名（按名称）
+   */
+  async saveModelAlias(alias: ModelAlias): Promise<ModelRoutingConfig> {
+    try {
+      return await invoke<ModelRoutingConfig>("save_model_alias", { alias });
+    } catch (error) {
+      console.error("Failed to save model alias:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 删除模型别名
+   */
+  async deleteModelAlias(name: string): Promise<ModelRoutingConfig> {
+    try {
+      return await invoke<ModelRoutingConfig>("delete_model_alias", { name });
+    } catch (error) {
+      console.error("Failed to delete model alias:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 新增或更新路由规则（id 为空时新建）
+   */
+  async saveRoutingRule(rule: RoutingRule): Promise<ModelRoutingConfig> {
+    try {
+      return await invoke<ModelRoutingConfig>("save_routing_rule", { rule });
+    } catch (error) {
+      console.error("Failed to save routing rule:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 删除路由规则
+   */
+  async deleteRoutingRule(id: string): Promise<ModelRoutingConfig> {
+    try {
+      return await invoke<ModelRoutingConfig>("delete_routing_rule", { id });
+    } catch (error) {
+      console.error("Failed to delete routing rule:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 调整路由规则的匹配顺序
+   */
+  async reorderRoutingRules(ids: string[]): Promise<ModelRoutingConfig> {
+    try {
+      return await invoke<ModelRoutingConfig>("reorder_routing_rules", { ids });
+    } catch (error) {
+      console.error("Failed to reorder routing rules:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 预览一次执行会使用的模型（应用路由规则和别名）
+   */
+  async previewModelResolution(
+    engine: 'claude' | 'codex' | 'gemini',
+    prompt: string,
+    model?: string,
+    provider?: string
+  ): Promise<ModelResolution> {
+    try {
+      return await invoke<ModelResolution>("preview_model_resolution", { engine, model, prompt, provider });
+    } catch (error) {
+      console.error("Failed to preview model resolution:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取恢复会话前的历史压缩配置
+   */
+  async getResumeCompactionConfig(): Promise<ResumeCompactionConfig> {
+    try {
+      return await invoke<ResumeCompactionConfig>("get_resume_compaction_config");
+    } catch (error) {
+      console.error("Failed to get resume compaction config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存恢复会话前的历史压缩配置
+   */
+  async setResumeCompactionConfig(config: ResumeCompactionConfig): Promise<void> {
+    try {
+      await invoke("set_resume_compaction_config", { config });
+    } catch (error) {
+      console.error("Failed to save resume compaction config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 恢复会话前按配置压缩历史（总结较早的轮次，保留最近几轮）
+   * 未压缩时返回 null；force 为 true 时忽略开关和阈值
+   */
+  async compactSessionBeforeResume(
+    engine: 'claude' | 'codex' | 'gemini',
+    sessionId: string,
+    projectPath: string,
+    force?: boolean
+  ): Promise<SessionCompaction | null> {
+    try {
+      return await invoke<SessionCompaction | null>("compact_session_before_resume", {
+        engine,
+        sessionId,
+        projectPath,
+        force,
+      });
+    } catch (error) {
+      console.error("Failed to compact session before resume:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取流式输出批量发送配置
+   */
+  async getStreamBatchingConfig(): Promise<StreamBatchingConfig> {
+    try {
+      return await invoke<StreamBatchingConfig>("get_stream_batching_config");
+    } catch (error) {
+      console.error("Failed to get stream batching config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存流式输出批量发送配置
+   */
+  async setStreamBatchingConfig(config: StreamBatchingConfig): Promise<void> {
+    try {
+      await invoke("set_stream_batching_config", { config });
+    } catch (error) {
+      console.error("Failed to save stream batching config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取后端消息语言
+   */
+  async getBackendLocale(): Promise<BackendLocale> {
+    try {
+      return await invoke<BackendLocale>("get_backend_locale");
+    } catch (error) {
+      console.error("Failed to get backend locale:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 设置后端消息语言（错误信息、命令结果），随界面语言切换同步
+   * @param locale - "zh" / "zh-CN" / "en" / "en-US"
+   */
+  async setBackendLocale(locale: string): Promise<BackendLocale> {
+    try {
+      return await invoke<BackendLocale>("set_backend_locale", { locale });
+    } catch (error) {
+      console.error("Failed to set backend locale:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取命令调用统计（性能面板）
+   * @param period - 时间段，如 "1h"、"24h"、"7d"、"all"，默认 "24h"
+   */
+  async getCommandMetrics(period?: string): Promise<CommandMetricsReport> {
+    try {
+      return await invoke<CommandMetricsReport>("get_command_metrics", { period });
+    } catch (error) {
+      console.error("Failed to get command metrics:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取命令耗时统计设置
+   */
+  async getCommandMetricsSettings(): Promise<CommandMetricsSettings> {
+    try {
+      return await invoke<CommandMetricsSettings>("get_command_metrics_settings");
+    } catch (error) {
+      console.error("Failed to get command metrics settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存命令耗时统计设置（开启后立即生效）
+   */
+  async setCommandMetricsSettings(settings: CommandMetricsSettings): Promise<void> {
+    try {
+      await invoke("set_command_metrics_settings", { settings });
+      commandMetricsEnabled = settings.enabled;
+      if (!settings.enabled) {
+        pendingCommandSamples = [];
+      }
+    } catch (error) {
+      console.error("Failed to save command metrics settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 清空命令调用统计
+   */
+  async clearCommandMetrics(): Promise<void> {
+    try {
+      await invoke("clear_command_metrics");
+    } catch (error) {
+      console.error("Failed to clear command metrics:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取限流自动重排队设置
+   */
+  async getRateLimitSettings(): Promise<RateLimitSettings> {
+    try {
+      return await invoke<RateLimitSettings>("get_rate_limit_settings");
+    } catch (error) {
+      console.error("Failed to get rate limit settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存限流自动重排队设置
+   */
+  async setRateLimitSettings(settings: RateLimitSettings): Promise<void> {
+    try {
+      return await invoke("set_rate_limit_settings", { settings });
+    } catch (error) {
+      console.error("Failed to save rate limit settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 取消等待中的限流重排队
+   * @param sessionId - `rate-limit-requeued` 事件中的会话 ID
+   * @returns 是否存在等待中的重排队
+   */
+  async cancelRateLimitRequeue(sessionId: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("cancel_rate_limit_requeue", { sessionId });
+    } catch (error) {
+      console.error("Failed to cancel rate limit requeue:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 提示词完成后执行项目配置的后处理命令（格式化 / Lint / 测试）
+   * @returns 验证结果；项目未配置后处理命令时返回 null
+   */
+  async runPostProcessors(
+    sessionId: string,
+    engine: "claude" | "codex" | "gemini",
+    projectPath: string,
+    promptIndex: number
+  ): Promise<VerificationOutcome | null> {
+    try {
+      return await invoke<VerificationOutcome | null>("run_post_processors", {
+        sessionId,
+        engine,
+        projectPath,
+        promptIndex,
+      });
+    } catch (error) {
+      console.error("Failed to run post-processors:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取会话的验证记录（按时间顺序）
+   */
+  async listVerificationRecords(sessionId: string): Promise<VerificationRecord[]> {
+    try {
+      return await invoke<VerificationRecord[]>("list_verification_records", { sessionId });
+    } catch (error) {
+      console.error("Failed to list verification records:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取会话的持续测试序列（以及测试从哪条提示词开始失败）
+   */
+  async listTestWatchRuns(sessionId: string): Promise<TestWatchSeries> {
+    try {
+      return await invoke<TestWatchSeries>("list_test_watch_runs", { sessionId });
+    } catch (error) {
+      console.error("Failed to list test watch runs:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 取消会话的自动修复循环
+   * @param sessionId - `auto-fix` 事件中的会话 ID
+   * @returns 是否存在进行中的循环
+   */
+  async cancelAutoFix(sessionId: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("cancel_auto_fix", { sessionId });
+    } catch (error) {
+      console.error("Failed to cancel auto-fix loop:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取桌面通知设置
+   */
+  async getNotificationSettings(): Promise<NotificationSettings> {
+    try {
+      return await invoke<NotificationSettings>("get_notification_settings");
+    } catch (error) {
+      console.error("Failed to get notification settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存桌面通知设置
+   */
+  async setNotificationSettings(settings: NotificationSettings): Promise<void> {
+    try {
+      return await invoke("set_notification_settings", { settings });
+    } catch (error) {
+      console.error("Failed to save notification settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取通知历史（最新的在前）
+   */
+  async listNotificationHistory(limit?: number, unreadOnly?: boolean): Promise<NotificationRecord[]> {
+    try {
+      return await invoke<NotificationRecord[]>("list_notification_history", { limit, unreadOnly });
+    } catch (error) {
+      console.error("Failed to list notification history:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 标记通知为已读（不传 ids 时标记全部）
+   */
+  async markNotificationsRead(ids?: number[]): Promise<number> {
+    try {
+      return await invoke<number>("mark_notifications_read", { ids });
+    } catch (error) {
+      console.error("Failed to mark notifications read:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 清空通知历史
+   */
+  async clearNotificationHistory(): Promise<number> {
+    try {
+      return await invoke<number>("clear_notification_history");
+    } catch (error) {
+      console.error("Failed to clear notification history:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 列出外发通知渠道
+   */
+  async listNotificationChannels(): Promise<NotificationChannel[]> {
+    try {
+      return await invoke<NotificationChannel[]>("list_notification_channels");
+    } catch (error) {
+      console.error("Failed to list notification channels:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 新建或更新通知渠道
+   */
+  async saveNotificationChannel(channel: NotificationChannelInput): Promise<NotificationChannel> {
+    try {
+      return await invoke<NotificationChannel>("save_notification_channel", { channel });
+    } catch (error) {
+      console.error("Failed to save notification channel:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 删除通知渠道
+   */
+  async deleteNotificationChannel(id: string): Promise<void> {
+    try {
+      return await invoke("delete_notification_channel", { id });
+    } catch (error) {
+      console.error("Failed to delete notification channel:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 通过渠道发送一条测试通知（可用于保存前验证配置）
+   */
+  async testNotificationChannel(channel: NotificationChannelInput): Promise<string> {
+    try {
+      return await invoke<string>("test_notification_channel", { channel });
+    } catch (error) {
+      console.error("Failed to test notification channel:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取全局快捷键绑定及注册状态
+   */
+  async getHotkeyBindings(): Promise<HotkeyBindingStatus[]> {
+    try {
+      return await invoke<HotkeyBindingStatus[]>("get_hotkey_bindings");
+    } catch (error) {
+      console.error("Failed to get hotkey bindings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 为动作设置快捷键并立即注册
+   */
+  async registerHotkey(action: HotkeyAction, shortcut: string): Promise<HotkeyBinding> {
+    try {
+      return await invoke<HotkeyBinding>("register_hotkey", { action, shortcut });
+    } catch (error) {
+      console.error("Failed to register hotkey:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 注销动作的快捷键
+   */
+  async unregisterHotkey(action: HotkeyAction): Promise<void> {
+    try {
+      return await invoke<void>("unregister_hotkey", { action });
+    } catch (error) {
+      console.error("Failed to unregister hotkey:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 上报当前活动项目（供快捷键使用）
+   */
+  async setActiveProject(projectPath: string | null, engine?: string): Promise<void> {
+    try {
+      return await invoke<void>("set_active_project", { projectPath, engine });
+    } catch (error) {
+      console.error("Failed to set active project:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 快速输入窗口提交提示词
+   */
+  async submitQuickCapture(prompt: string, projectPath?: string, engine?: string): Promise<void> {
+    try {
+      return await invoke<void>("submit_quick_capture", { prompt, projectPath, engine });
+    } catch (error) {
+      console.error("Failed to submit quick capture:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取系统托盘设置
+   */
+  async getTraySettings(): Promise<TraySettings> {
+    try {
+      return await invoke<TraySettings>("get_tray_settings");
+    } catch (error) {
+      console.error("Failed to get tray settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存系统托盘设置
+   */
+  async setTraySettings(settings: TraySettings): Promise<void> {
+    try {
+      return await invoke<void>("set_tray_settings", { settings });
+    } catch (error) {
+      console.error("Failed to set tray settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 获取深度链接设置
+   */
+  async getDeepLinkSettings(): Promise<DeepLinkSettings> {
+    try {
+      return await invoke<DeepLinkSettings>("get_deep_link_settings");
+    } catch (error) {
+      console.error("Failed to get deep link settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 保存深度链接设置
+   */
+  async setDeepLinkSettings(settings: DeepLinkSettings): Promise<void> {
+    try {
+      return await invoke<void>("set_deep_link_settings", { settings });
+    } catch (error) {
+      console.error("Failed to set deep link settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * 执行用户确认后的深度链接请求（来自 deep-link-pending 事件）
+   */
+  async runDeepLinkRequest(request: DeepLinkRequest): Promise<void> {
+    try {
+      return await invoke<void>("run_deep_link_request", { request });
+    } catch (error) {
+      console.error("Failed to run deep link request:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets Codex rate limits from the latest session
+   * @returns Promise resolving to Codex rate limits
+   */
+  async getCodexRateLimits(): Promise<CodexRateLimits> {
+    try {
+      return await invoke<CodexRateLimits>("get_codex_rate_limits");
+    } catch (error) {
+      console.error("Failed to get Codex rate limits:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // MCP SERVER OPERATIONS
+  // ============================================================================
+
+  /**
+   * Adds a new MCP server
+   */
+  async mcpAdd(
+    name: string,
+    transport: string,
+    command?: string,
+    args: string[] = [],
+    env: Record<string, string> = {},
+    url?: string,
+    scope: string = "local"
+  ): Promise<AddServerResult> {
+    try {
+      return await invoke<AddServerResult>("mcp_add", {
+        name,
+        transport,
+        command,
+        args,
+        env,
+        url,
+        scope
+      });
+    } catch (error) {
+      console.error("Failed to add MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all configured MCP servers
+   */
+  async mcpList(): Promise<MCPServer[]> {
+    try {
+      console.log("API: Calling mcp_list...");
+      const result = await invoke<MCPServer[]>("mcp_list");
+      console.log("API: mcp_list returned:", result);
+      return result;
+    } catch (error) {
+      console.error("API: Failed to list MCP servers:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets details for a specific MCP server
+   */
+  async mcpGet(name: string): Promise<MCPServer> {
+    try {
+      return await invoke<MCPServer>("mcp_get", { name });
+    } catch (error) {
+      console.error("Failed to get MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes an MCP server
+   */
+  async mcpRemove(name: string): Promise<string> {
+    try {
+      return await invoke<string>("mcp_remove", { name });
+    } catch (error) {
+      console.error("Failed to remove MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds an MCP server from JSON configuration
+   */
+  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+    try {
+      return await invoke<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+    } catch (error) {
+      console.error("Failed to add MCP server from JSON:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports MCP servers from Claude Desktop
+   */
+  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+    try {
+      return await invoke<ImportResult>("mcp_add_from_claude_desktop", { scope });
+    } catch (error) {
+      console.error("Failed to import from Claude Desktop:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Starts Claude Code as an MCP server
+   */
+  async mcpServe(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_serve");
+    } catch (error) {
+      console.error("Failed to start MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tests connection to an MCP server
+   */
+  async mcpTestConnection(name: string): Promise<string> {
+    try {
+      return await invoke<string>("mcp_test_connection", { name });
+    } catch (error) {
+      console.error("Failed to test MCP connection:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports MCP server configuration from .claude.json
+   */
+  async mcpExportConfig(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_export_config");
+    } catch (error) {
+      console.error("Failed to export MCP configuration:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resets project-scoped server approval choices
+   */
+  async mcpResetProjectChoices(): Promise<string> {
+    try {
+      return await invoke<string>("mcp_reset_project_choices");
+    } catch (error) {
+      console.error("Failed to reset project choices:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks MCP servers of an engine (initialize handshake + tools/list)
+   */
+  async mcpGetServerStatus(engine?: MCPEngineType, timeoutMs?: number): Promise<Record<string, ServerStatus>> {
+    try {
+      return await invoke<Record<string, ServerStatus>>("mcp_get_server_status", { engine, timeoutMs });
+    } catch (error) {
+      console.error("Failed to get server status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads .mcp.json from the current project
+   */
+  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+    try {
+      return await invoke<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+    } catch (error) {
+      console.error("Failed to read project MCP config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves .mcp.json to the current project
+   */
+  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+    try {
+      return await invoke<string>("mcp_save_project_config", { projectPath, config });
+    } catch (error) {
+      console.error("Failed to save project MCP config:", error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // Multi-Engine MCP Support
+  // ============================================================================
+
+  /**
+   * Lists MCP servers for a specific engine
+   * @param engine - The engine to list servers for ('claude', 'codex', 'gemini')
+   */
+  async mcpListByEngine(engine: MCPEngineType): Promise<MCPServerExtended[]> {
+    try {
+      console.log(`API: Calling mcp_list_by_engine for ${engine}...`);
+      const result = await invoke<MCPServerExtended[]>("mcp_list_by_engine", { engine });
+      console.log(`API: mcp_list_by_engine returned ${result.length} servers`);
+      return result;
+    } catch (error) {
+      console.error(`Failed to list MCP servers for ${engine}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets enabled/disabled status for an MCP server
+   * @param engine - The engine the server belongs to
+   * @param serverName - The name of the server
+   * @param enabled - Whether to enable or disable the server
+   */
+  async mcpSetEnabled(engine: MCPEngineType, serverName: string, enabled: boolean): Promise<void> {
+    try {
+      console.log(`API: Setting ${engine} server '${serverName}' enabled=${enabled}`);
+      await invoke<void>("mcp_set_enabled", { engine, serverName, enabled });
+    } catch (error) {
+      console.error(`Failed to set MCP server enabled status:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds an MCP server for a specific engine
+   * @param engine - The engine to add the server to
+   * @param name - Server name
+   * @param transport - Transport type ('stdio' or 'sse')
+   * @param command - Command to execute (for stdio)
+   * @param args - Command arguments
+   * @param env - Environment variables
+   * @param url - URL endpoint (for SSE)
+   * @param scope - Configuration scope
+   */
+  async mcpAddByEngine(
+    engine: MCPEngineType,
+    name: string,
+    transport: string,
+    command: string | null,
+    args: string[],
+    env: Record<string, string>,
+    url: string | null,
+    scope: string = "local"
+  ): Promise<AddServerResult> {
+    try {
+      console.log(`API: Adding server '${name}' to ${engine}`);
+      return await invoke<AddServerResult>("mcp_add_by_engine", {
+        engine,
+        name,
+        transport,
+        command,
+        args,
+        env,
+        url,
+        scope,
+      });
+    } catch (error) {
+      console.error(`Failed to add MCP server to ${engine}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes an MCP server for a specific engine
+   * @param engine - The engine to remove the server from
+   * @param serverName - The name of the server to remove
+   */
+  async mcpRemoveByEngine(engine: MCPEngineType, serverName: string): Promise<string> {
+    try {
+      console.log(`API: Removing server '${serverName}' from ${engine}`);
+      return await invoke<string>("mcp_remove_by_engine", { engine, serverName });
+    } catch (error) {
+      console.error(`Failed to remove MCP server from ${engine}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates an MCP server configuration for a specific engine
+   * @param engine - The engine to update the server for
+   * @param server - The updated server configuration
+   */
+  async mcpUpdateByEngine(engine: MCPEngineType, server: MCPServerExtended): Promise<void> {
+    try {
+      console.log(`API: Updating server '${server.name}' for ${engine}`);
+      return await invoke<void>("mcp_update_by_engine", {
+        engine,
+        serverName: server.name,
+        command: server.command,
+        args: server.args || [],
+        env: server.env || {},
+        url: server.url,
+        enabled: server.enabled,
+        startupTimeoutSec: server.startup_timeout_sec,
+        toolTimeoutSec: server.tool_timeout_sec,
+        extras: {
+          cwd: server.cwd,
+          headers: server.headers || {},
+          trust: server.trust,
+          include_tools: server.include_tools || [],
+          exclude_tools: server.exclude_tools || [],
+        },
+      });
+    } catch (error) {
+      console.error(`Failed to update MCP server for ${engine}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets list of all projects with MCP server disabled status
+   * @param serverName - The name of the MCP server to check
+   * @param engine - The engine type (claude, codex, gemini)
+   * @returns Promise resolving to array of projects with their disabled status
+   */
+  async mcpGetProjectList(serverName: string, engine: MCPEngineType = "claude"): Promise<{ path: string; disabled: boolean }[]> {
+    try {
+      console.log(`API: Getting project list for ${engine} MCP server '${serverName}'`);
+      
+      if (engine === "codex") {
+        // Use Codex-specific command
+        return await invoke<{ path: string; disabled: boolean }[]>("codex_mcp_get_project_list", { serverName });
+      }
+      
+      // Claude and Gemini share the command (engine selects the config source)
+      return await invoke<{ path: string; disabled: boolean }[]>("mcp_get_project_list", { serverName, engine });
+    } catch (error) {
+      console.error(`Failed to get project list for MCP server:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Sets enabled/disabled status for an MCP server for a specific project
+   * @param engine - The engine the server belongs to
+   * @param serverName - The name of the server
+   * @param projectPath - The project path to update
+   * @param enabled - Whether to enable or disable the server
+   */
+  async mcpSetEnabledForProject(engine: MCPEngineType, serverName: string, projectPath: string, enabled: boolean): Promise<void> {
+    try {
+      console.log(`API: Setting ${engine} server '${serverName}' enabled=${enabled} for project '${projectPath}'`);
+      
+      if (engine === "codex") {
+        // Use Codex-specific command
+        await invoke<void>("codex_mcp_set_enabled_for_project", { serverName, projectPath, enabled });
+      } else {
+        // Default to Claude command
+        await invoke<void>("mcp_set_enabled_for_project", { engine, serverName, projectPath, enabled });
+      }
+    } catch (error) {
+      console.error(`Failed to set MCP server enabled status for project:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Copies MCP servers from one engine to others, translating config formats
+   * @param serverNames - Servers to sync (all when omitted)
+   * @param overwrite - Replace servers that already exist on a target
+   */
+  async mcpSyncServers(
+    sourceEngine: MCPEngineType,
+    targetEngines: MCPEngineType[],
+    serverNames?: string[],
+    overwrite: boolean = false
+  ): Promise<{ items: MCPSyncItem[] }> {
+    try {
+      return await invoke<{ items: MCPSyncItem[] }>("mcp_sync_servers", {
+        sourceEngine,
+        targetEngines,
+        serverNames,
+        overwrite,
+      });
+    } catch (error) {
+      console.error(`Failed to sync MCP servers from ${sourceEngine}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists the curated MCP server registry
+   * @param refresh - Fetch the latest registry from remote first
+   */
+  async mcpRegistryList(refresh: boolean = false): Promise<RegistryServer[]> {
+    try {
+      return await invoke<RegistryServer[]>("mcp_registry_list", { refresh });
+    } catch (error) {
+      console.error("Failed to list MCP registry:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Installs a registry server for an engine
+   * @param env - Values for the server's variables; missing required ones are returned in missingEnv
+   */
+  async mcpRegistryInstall(
+    name: string,
+    engine: MCPEngineType,
+    scope?: string,
+    env?: Record<string, string>
+  ): Promise<RegistryInstallResult> {
+    try {
+      return await invoke<RegistryInstallResult>("mcp_registry_install", { name, engine, scope, env });
+    } catch (error) {
+      console.error(`Failed to install MCP server '${name}' for ${engine}:`, error);
+      throw error;
+    }
+  },
+
+  /**
+   * Starts the built-in AnyCode MCP server (returns the running one if already started)
+   * @param port - Optional port; a free port is chosen when omitted
+   */
+  async startAnycodeMcpServer(port?: number): Promise<AnycodeMcpServerInfo> {
+    try {
+      return await invoke<AnycodeMcpServerInfo>("start_anycode_mcp_server", { port });
+    } catch (error) {
+      console.error("Failed to start AnyCode MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Stops the built-in AnyCode MCP server
+   */
+  async stopAnycodeMcpServer(): Promise<void> {
+    try {
+      await invoke<void>("stop_anycode_mcp_server");
+    } catch (error) {
+      console.error("Failed to stop AnyCode MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the built-in AnyCode MCP server status (null when not running)
+   */
+  async getAnycodeMcpServerStatus(): Promise<AnycodeMcpServerInfo | null> {
+    try {
+      return await invoke<AnycodeMcpServerInfo | null>("get_anycode_mcp_server_status");
+    } catch (error) {
+      console.error("Failed to get AnyCode MCP server status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds a project to Codex MCP tracking
+   * @param projectPath - The project path to add
+   */
+  async codexMcpAddProject(projectPath: string): Promise<void> {
+    try {
+      console.log(`API: Adding project '${projectPath}' to Codex MCP tracking`);
+      await invoke<void>("codex_mcp_add_project", { projectPath });
+    } catch (error) {
+      console.error(`Failed to add project to Codex MCP tracking:`, error);
+      throw error;
+    }
+  },
+
+  // ============================================================================
+  // Codex Change Tracker API
+  // ============================================================================
+
+  /**
+   * Record a file change in Codex session
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param filePath - The file path
+   * @param changeType - The type of change (create, update, delete)
+   * @param source - The source of change (tool, command)
+   * @param promptText - The prompt that triggered this change
+   * @param newContent - The new content (for create/update)
+   * @param oldContent - The old content (for update, optional)
+   * @returns Promise resolving when the change is recorded
+   */
+  async codexRecordFileChange(
+    sessionId: string,
+    projectPath: string,
+    filePath: string,
+    changeType: 'create' | 'update' | 'delete',
+    source: 'tool' | 'command',
+    promptIndex: number,
+    promptText: string,
+    newContent: string,
+    oldContent: string | null,
+    toolName?: string | null,
+    toolCallId?: string | null,
+    diffHint?: string | null
+  ): Promise<string> {
+    try {
+      return await invoke<string>("codex_record_file_change", {
+        sessionId,
+        projectPath,
+        filePath,
+        changeType,
+        source,
+        promptIndex,
+        promptText,
+        newContent,
+        oldContent,
+        toolName,
+        toolCallId,
+        diffHint,
+      });
+    } catch (error) {
+      console.error("Failed to record Codex file change:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Detect and record file changes made by a Codex shell command
+   * (the backend snapshots files when the command starts)
+   * @param sessionId - The Codex session ID
+   * @param callId - The Codex command item ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index the command belongs to
+   * @param command - The shell command that ran
+   * @returns Recorded change IDs, or the reason changes are unknown (large repo time budget)
+   */
+  async codexDetectCommandChanges(
+    sessionId: string,
+    callId: string,
+    projectPath: string,
+    promptIndex: number,
+    command: string
+  ): Promise<import('@/types/codex-changes').CommandChangeDetection> {
+    try {
+      return await invoke<import('@/types/codex-changes').CommandChangeDetection>("codex_detect_command_changes", {
+        sessionId,
+        callId,
+        projectPath,
+        promptIndex,
+        command,
+      });
+    } catch (error) {
+      console.error("Failed to detect Codex command changes:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * List all file changes for a Codex session
+   * @param sessionId - The Codex session ID
+   * @returns Promise resolving to array of file changes
+   */
+  async codexListFileChanges(sessionId: string): Promise<import('@/types/codex-changes').CodexFileChange[]> {
+    try {
+      return await invoke<import('@/types/codex-changes').CodexFileChange[]>("codex_list_file_changes", { sessionId });
+    } catch (error) {
+      console.error("Failed to list Codex file changes:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get details of a specific file change
+   * @param sessionId - The Codex session ID
+   * @param changeId - The change ID
+   * @returns Promise resolving to the change detail
+   */
+  async codexGetChangeDetail(sessionId: string, changeId: string): Promise<import('@/types/codex-changes').CodexFileChange> {
+    try {
+      return await invoke<import('@/types/codex-changes').CodexFileChange>("codex_get_change_detail", { sessionId, changeId });
+    } catch (error) {
+      console.error("Failed to get Codex change detail:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Export all session changes as a patch file
+   * @param sessionId - The Codex session ID
+   * @param outputPath - The output file path
+   * @param format - 'idea' merges each file into one git-style diff that IDEA can apply (default 'raw')
+   * @returns Promise resolving to the output path
+   */
+  async codexExportPatch(
+    sessionId: string,
+    outputPath: string,
+    format?: import('@/types/codex-changes').PatchFormat
+  ): Promise<string> {
+    try {
+      return await invoke<string>("codex_export_patch", { sessionId, outputPath, format });
+    } catch (error) {
+      console.error("Failed to export Codex patch:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Export a single change as a patch file
+   * @param sessionId - The Codex session ID
+   * @param changeId - The change ID
+   * @param outputPath - The output file path
+   * @returns Promise resolving to the output path
+   */
+  async codexExportSingleChange(sessionId: string, changeId: string, outputPath: string): Promise<string> {
+    try {
+      return await invoke<string>("codex_export_single_change", { sessionId, changeId, outputPath });
+    } catch (error) {
+      console.error("Failed to export Codex single change:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Clear all change records for a session
+   * @param sessionId - The Codex session ID
+   */
+  async codexClearChangeRecords(sessionId: string): Promise<void> {
+    try {
+      await invoke<void>("codex_clear_change_records", { sessionId });
+    } catch (error) {
+      console.error("Failed to clear Codex change records:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Repair/upgrade change records for a session (recompute diffs, backfill old/new when possible)
+   * @param sessionId - The Codex session ID
+   * @returns Promise resolving to whether any records were upgraded
+   */
+  async codexRepairChangeRecords(sessionId: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("codex_repair_change_records", { sessionId });
+    } catch (error) {
+      console.error("Failed to repair Codex change records:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get the stored Claude binary path from settings
+   * @returns Promise resolving to the path if set, null otherwise
+   */
+  async getClaudeBinaryPath(): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_claude_binary_path");
+    } catch (error) {
+      console.error("Failed to get Claude binary path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Set the Claude binary path in settings
+   * @param path - The absolute path to the Claude binary
+   * @returns Promise resolving when the path is saved
+   */
+  async setClaudeBinaryPath(path: string): Promise<void> {
+    try {
+      return await invoke<void>("set_claude_binary_path", { path });
+    } catch (error) {
+      console.error("Failed to set Claude binary path:", error);
       throw error;
     }
   },
 
+
+  // Storage API methods
+
   /**
-   * Removes a project from the project list (without deleting files)
-   * @param projectId - The ID of the project to remove from list
-   * @returns Promise resolving to success message
+   * Lists all tables in the SQLite database
+   * @returns Promise resolving to an array of table information
    */
-  async deleteProject(projectId: string): Promise<string> {
+  async storageListTables(): Promise<any[]> {
     try {
-      return await invoke<string>('delete_project', { projectId });
+      return await invoke<any[]>("storage_list_tables");
     } catch (error) {
-      console.error("Failed to remove project from list:", error);
+      console.error("Failed to list tables:", error);
       throw error;
     }
   },
 
   /**
-   * Restores a hidden project back to the project list
-   * @param projectId - The ID of the project to restore
-   * @returns Promise resolving to success message
+   * Reads table data with pagination
+   * @param tableName - Name of the table to read
+   * @param page - Page number (1-indexed)
+   * @param pageSize - Number of rows per page
+   * @param searchQuery - Optional search query
+   * @returns Promise resolving to table data with pagination info
    */
-  async restoreProject(projectId: string): Promise<string> {
+  async storageReadTable(
+    tableName: string,
+    page: number,
+    pageSize: number,
+    searchQuery?: string
+  ): Promise<any> {
     try {
-      return await invoke<string>('restore_project', { projectId });
+      return await invoke<any>("storage_read_table", {
+        tableName,
+        page,
+        pageSize,
+        searchQuery,
+      });
     } catch (error) {
-      console.error("Failed to restore project:", error);
+      console.error("Failed to read table:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all hidden projects
-   * @returns Promise resolving to array of hidden project IDs
+   * Updates a row in a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @param updates - Map of column names to new values
+   * @returns Promise resolving when the row is updated
    */
-  async listHiddenProjects(): Promise<string[]> {
+  async storageUpdateRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>,
+    updates: Record<string, any>
+  ): Promise<void> {
     try {
-      return await invoke<string[]>('list_hidden_projects');
+      return await invoke<void>("storage_update_row", {
+        tableName,
+        primaryKeyValues,
+        updates,
+      });
     } catch (error) {
-      console.error("Failed to list hidden projects:", error);
+      console.error("Failed to update row:", error);
       throw error;
     }
   },
 
   /**
-   * Permanently delete a project and all its files
-   * @param projectId - The project ID to permanently delete
-   * @returns Promise resolving to success message
+   * Deletes a row from a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @returns Promise resolving when the row is deleted
    */
-  async deleteProjectPermanently(projectId: string): Promise<string> {
+  async storageDeleteRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>
+  ): Promise<void> {
     try {
-      return await invoke<string>('delete_project_permanently', { projectId });
+      return await invoke<void>("storage_delete_row", {
+        tableName,
+        primaryKeyValues,
+      });
     } catch (error) {
-      console.error("Failed to permanently delete project:", error);
+      console.error("Failed to delete row:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the Claude settings file
-   * @returns Promise resolving to the settings object
+   * Inserts a new row into a table
+   * @param tableName - Name of the table
+   * @param values - Map of column names to values
+   * @returns Promise resolving to the last insert row ID
    */
-  async getClaudeSettings(): Promise<ClaudeSettings> {
+  async storageInsertRow(
+    tableName: string,
+    values: Record<string, any>
+  ): Promise<number> {
     try {
-      const result = await invoke<ClaudeSettings>("get_claude_settings");
-      console.log("Raw result from get_claude_settings:", result);
-      
-      // Due to #[serde(flatten)] in Rust, the result is directly the settings object
-      return result;
+      return await invoke<number>("storage_insert_row", {
+        tableName,
+        values,
+      });
     } catch (error) {
-      console.error("Failed to get Claude settings:", error);
+      console.error("Failed to insert row:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // CLAUDE SETTINGS.JSON FILE PROVIDERS (AnyCode)
-  // ============================================================================
+  /**
+   * Executes a raw SQL query
+   * @param query - SQL query string
+   * @returns Promise resolving to query result
+   */
+  async storageExecuteSql(query: string): Promise<any> {
+    try {
+      return await invoke<any>("storage_execute_sql", { query });
+    } catch (error) {
+      console.error("Failed to execute SQL:", error);
+      throw error;
+    }
+  },
 
   /**
-   * Reads raw ~/.claude/settings.json as text
+   * Resets the entire database
+   * @returns Promise resolving when the database is reset
    */
-  async readClaudeSettingsJsonText(): Promise<string> {
+  async storageResetDatabase(): Promise<void> {
     try {
-      return await invoke<string>("read_claude_settings_json_text");
+      return await invoke<void>("storage_reset_database");
     } catch (error) {
-      console.error("Failed to read Claude settings.json:", error);
+      console.error("Failed to reset database:", error);
       throw error;
     }
   },
 
   /**
-   * Writes raw ~/.claude/settings.json as text (expects valid JSON object)
+   * Get hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param projectPath - Project path (required for project and local scopes)
+   * @returns Promise resolving to the hooks configuration
    */
-  async writeClaudeSettingsJsonText(content: string): Promise<string> {
+  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
     try {
-      return await invoke<string>("write_claude_settings_json_text", { content });
+      return await invoke<HooksConfiguration>("get_hooks_config", { scope, projectPath });
     } catch (error) {
-      console.error("Failed to write Claude settings.json:", error);
+      console.error("Failed to get hooks config:", error);
       throw error;
     }
   },
 
   /**
-   * Reads raw ~/.claude.json as text
+   * Update hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param hooks - The hooks configuration to save
+   * @param projectPath - Project path (required for project and local scopes)
+   * @returns Promise resolving to success message
    */
-  async readClaudeJsonText(): Promise<string> {
+  async updateHooksConfig(
+    scope: 'user' | 'project' | 'local',
+    hooks: HooksConfiguration,
+    projectPath?: string
+  ): Promise<string> {
     try {
-      return await invoke<string>("read_claude_json_text");
+      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
     } catch (error) {
-      console.error("Failed to read .claude.json:", error);
+      console.error("Failed to update hooks config:", error);
       throw error;
     }
   },
 
   /**
-   * Writes raw ~/.claude.json as text (expects valid JSON object)
+   * Validate a hook command syntax
+   * @param command - The shell command to validate
+   * @returns Promise resolving to validation result
    */
-  async writeClaudeJsonText(content: string): Promise<string> {
+  async validateHookCommand(command: string): Promise<{ valid: boolean; message: string }> {
     try {
-      return await invoke<string>("write_claude_json_text", { content });
+      return await invoke<{ valid: boolean; message: string }>("validate_hook_command", { command });
     } catch (error) {
-      console.error("Failed to write .claude.json:", error);
+      console.error("Failed to validate hook command:", error);
       throw error;
     }
   },
 
   /**
-   * Writes both ~/.claude/settings.json and ~/.claude.json
+   * 列出 user / project / local 三个作用域中配置的全部 hook
    */
-  async writeClaudeConfigFiles(settingsJson: string, claudeJson: string): Promise<string> {
+  async listClaudeHooks(projectPath?: string): Promise<ClaudeHookEntry[]> {
     try {
-      return await invoke<string>("write_claude_config_files", { settingsJson, claudeJson });
+      return await invoke<ClaudeHookEntry[]>("list_claude_hooks", { projectPath });
     } catch (error) {
-      console.error("Failed to write Claude config files:", error);
+      console.error("Failed to list Claude hooks:", error);
       throw error;
     }
   },
 
   /**
-   * Gets list of saved Claude settings.json presets
+   * 按 Claude Code hooks 格式校验配置
    */
-  async getClaudeSettingsFileProviders(): Promise<ClaudeSettingsFileProvider[]> {
+  async validateHooksConfig(hooks: HooksConfiguration): Promise<HooksSchemaValidation> {
     try {
-      return await invoke<ClaudeSettingsFileProvider[]>("get_claude_settings_file_providers");
+      return await invoke<HooksSchemaValidation>("validate_hooks_config", { hooks });
     } catch (error) {
-      console.error("Failed to get Claude settings.json presets:", error);
+      console.error("Failed to validate hooks config:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a Claude settings.json preset
+   * 新增 hook，或编辑 hookId 对应的 hook
    */
-  async addClaudeSettingsFileProvider(config: Omit<ClaudeSettingsFileProvider, 'id'>): Promise<string> {
-    const id = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
+  async saveClaudeHook(params: {
+    scope: 'user' | 'project' | 'local';
+    projectPath?: string;
+    hookId?: string;
+    event: string;
+    matcher?: string;
+    command: string;
+    timeout?: number;
+  }): Promise<ClaudeHookEntry> {
+    try {
+      return await invoke<ClaudeHookEntry>("save_claude_hook", params);
+    } catch (error) {
+      console.error("Failed to save Claude hook:", error);
+      throw error;
+    }
+  },
 
-    const fullConfig: ClaudeSettingsFileProvider = {
-      ...config,
-      id,
-      createdAt: Date.now(),
-    };
+  /**
+   * 删除 hook
+   */
+  async removeClaudeHook(hookId: string, projectPath?: string): Promise<string> {
+    try {
+      return await invoke<string>("remove_claude_hook", { hookId, projectPath });
+    } catch (error) {
+      console.error("Failed to remove Claude hook:", error);
+      throw error;
+    }
+  },
 
+  /**
+   * 使用模拟事件运行一次 hook，返回输出和退出码
+   * @param sampleEvent - 覆盖默认 payload 的字段（如 tool_name、tool_input）
+   */
+  async testHook(hookId: string, sampleEvent?: Record<string, any>, projectPath?: string): Promise<HookTestResult> {
     try {
-      return await invoke<string>("add_claude_settings_file_provider", { config: fullConfig });
+      return await invoke<HookTestResult>("test_hook", { hookId, sampleEvent, projectPath });
     } catch (error) {
-      console.error("Failed to add Claude settings.json preset:", error);
+      console.error("Failed to test hook:", error);
       throw error;
     }
   },
 
   /**
-   * Updates a Claude settings.json preset
+   * 获取 hook 事件桥接状态
    */
-  async updateClaudeSettingsFileProvider(config: ClaudeSettingsFileProvider): Promise<string> {
+  async getHookBridgeStatus(): Promise<HookBridgeStatus> {
     try {
-      return await invoke<string>("update_claude_settings_file_provider", { config });
+      return await invoke<HookBridgeStatus>("get_hook_bridge_status");
     } catch (error) {
-      console.error("Failed to update Claude settings.json preset:", error);
+      console.error("Failed to get hook bridge status:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a Claude settings.json preset
+   * 在 ~/.claude/settings.json 中安装桥接 hook，外部启动的 Claude 会话事件也会转发到 AnyCode
    */
-  async deleteClaudeSettingsFileProvider(id: string): Promise<string> {
+  async installHookBridge(): Promise<HookBridgeStatus> {
     try {
-      return await invoke<string>("delete_claude_settings_file_provider", { id });
+      return await invoke<HookBridgeStatus>("install_hook_bridge");
     } catch (error) {
-      console.error("Failed to delete Claude settings.json preset:", error);
+      console.error("Failed to install hook bridge:", error);
       throw error;
     }
   },
 
   /**
-   * Opens a new Claude Code session
-   * @param path - Optional path to open the session in
-   * @returns Promise resolving when the session is opened
+   * 移除桥接 hook 并停止监听
    */
-  async openNewSession(path?: string): Promise<string> {
+  async uninstallHookBridge(): Promise<HookBridgeStatus> {
     try {
-      return await invoke<string>("open_new_session", { path });
+      return await invoke<HookBridgeStatus>("uninstall_hook_bridge");
     } catch (error) {
-      console.error("Failed to open new session:", error);
+      console.error("Failed to uninstall hook bridge:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the CLAUDE.md system prompt file
-   * @returns Promise resolving to the system prompt content
+   * 获取某个会话通过桥接记录的 hook 事件（按时间顺序，默认最近 200 条）
    */
-  async getSystemPrompt(): Promise<string> {
+  async listHookBridgeEvents(sessionId: string, limit?: number): Promise<HookBridgeEvent[]> {
     try {
-      return await invoke<string>("get_system_prompt");
+      return await invoke<HookBridgeEvent[]>("list_hook_bridge_events", { sessionId, limit });
     } catch (error) {
-      console.error("Failed to get system prompt:", error);
+      console.error("Failed to list hook bridge events:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Claude Code is installed and gets its version
-   * @returns Promise resolving to the version status
+   * Get merged hooks configuration (respecting priority)
+   * @param projectPath - The project path
+   * @returns Promise resolving to merged hooks configuration
    */
-  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+  async getMergedHooksConfig(projectPath: string): Promise<HooksConfiguration> {
+    try {
+      const [userHooks, projectHooks, localHooks] = await Promise.all([
+        this.getHooksConfig('user'),
+        this.getHooksConfig('project', projectPath),
+        this.getHooksConfig('local', projectPath)
+      ]);
+
+      return HooksManager.mergeConfigs(userHooks, projectHooks, localHooks);
+    } catch (error) {
+      console.error("Failed to get merged hooks config:", error);
+      throw error;
+    }
+  },
+
+
+  /**
+   * Set custom Claude CLI path
+   * @param customPath - Path to custom Claude CLI executable
+   * @returns Promise resolving when path is set successfully
+   */
+  async setCustomClaudePath(customPath: string): Promise<void> {
     try {
-      return await invoke<ClaudeVersionStatus>("check_claude_version");
+      return await invoke<void>("set_custom_claude_path", { customPath });
     } catch (error) {
-      console.error("Failed to check Claude version:", error);
+      console.error("Failed to set custom Claude path:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the CLAUDE.md system prompt file
-   * @param content - The new content for the system prompt
-   * @returns Promise resolving when the file is saved
+   * Get current Claude CLI path (custom or auto-detected)
+   * @returns Promise resolving to current Claude CLI path
    */
-  async saveSystemPrompt(content: string): Promise<string> {
+  async getClaudePath(): Promise<string> {
     try {
-      return await invoke<string>("save_system_prompt", { content });
+      return await invoke<string>("get_claude_path");
     } catch (error) {
-      console.error("Failed to save system prompt:", error);
+      console.error("Failed to get Claude path:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the AGENTS.md system prompt file from Codex directory
-   * @returns Promise resolving to the Codex system prompt content
+   * Clear custom Claude CLI path and revert to auto-detection
+   * @returns Promise resolving when custom path is cleared
    */
-  async getCodexSystemPrompt(): Promise<string> {
+  async clearCustomClaudePath(): Promise<void> {
     try {
-      return await invoke<string>("get_codex_system_prompt");
+      return await invoke<void>("clear_custom_claude_path");
     } catch (error) {
-      console.error("Failed to get Codex system prompt:", error);
+      console.error("Failed to clear custom Claude path:", error);
       throw error;
     }
   },
 
+
+
+  // Clipboard API methods
+
   /**
-   * Saves the AGENTS.md system prompt file to Codex directory
-   * @param content - The new content for the Codex system prompt
-   * @returns Promise resolving when the file is saved
+   * Saves clipboard image data to a temporary file
+   * @param base64Data - Base64 encoded image data
+   * @param format - Optional image format
+   * @returns Promise resolving to saved image result
    */
-  async saveCodexSystemPrompt(content: string): Promise<string> {
+  async saveClipboardImage(base64Data: string, format?: string): Promise<SavedImageResult> {
     try {
-      return await invoke<string>("save_codex_system_prompt", { content });
+      return await invoke<SavedImageResult>("save_clipboard_image", { base64Data, format });
     } catch (error) {
-      console.error("Failed to save Codex system prompt:", error);
+      console.error("Failed to save clipboard image:", error);
       throw error;
     }
   },
 
   /**
-   * Reads the GEMINI.md system prompt file from Gemini directory
-   * @returns Promise resolving to the content of GEMINI.md
+   * Saves the image currently on the system clipboard as a PNG temp file
+   * @returns Promise resolving to the saved file path and dimensions
    */
-  async getGeminiSystemPrompt(): Promise<string> {
+  async saveSystemClipboardImage(): Promise<SavedImageResult> {
     try {
-      return await invoke<string>("get_gemini_system_prompt");
+      return await invoke<SavedImageResult>("save_system_clipboard_image");
     } catch (error) {
-      console.error("Failed to get Gemini system prompt:", error);
+      console.error("Failed to save system clipboard image:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the GEMINI.md system prompt file to Gemini directory
-   * @param content - The new content for the Gemini system prompt
-   * @returns Promise resolving when the file is saved
+   * Removes clipboard temp images older than the given age (default 24h)
+   * @returns Promise resolving to the number of removed files
    */
-  async saveGeminiSystemPrompt(content: string): Promise<string> {
+  async cleanupClipboardImages(maxAgeHours?: number): Promise<number> {
     try {
-      return await invoke<string>("save_gemini_system_prompt", { content });
+      return await invoke<number>("cleanup_clipboard_images", { maxAgeHours });
     } catch (error) {
-      console.error("Failed to save Gemini system prompt:", error);
+      console.error("Failed to cleanup clipboard images:", error);
       throw error;
     }
   },
 
+  // Provider Management API methods
+
   /**
-   * Saves the Claude settings file
-   * @param settings - The settings object to save
-   * @returns Promise resolving when the settings are saved
+   * Gets the list of preset provider configurations
+   * @returns Promise resolving to array of provider configurations
    */
-  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+  async getProviderPresets(): Promise<ProviderConfig[]> {
     try {
-      console.log("Saving Claude settings:", settings);
-      return await invoke<string>("save_claude_settings", { settings });
+      return await invoke<ProviderConfig[]>("get_provider_presets");
     } catch (error) {
-      console.error("Failed to save Claude settings:", error);
+      console.error("Failed to get provider presets:", error);
       throw error;
     }
   },
 
   /**
-   * Updates the thinking mode by modifying MAX_THINKING_TOKENS in settings.json
-   * @param enabled - Whether to enable thinking mode
-   * @param tokens - Optional token limit (defaults to 10000)
-   * @returns Promise resolving when the settings are updated
+   * Gets the current provider configuration from environment variables
+   * @returns Promise resolving to current configuration
    */
-  async updateThinkingMode(enabled: boolean, tokens?: number): Promise<string> {
+  async getCurrentProviderConfig(): Promise<CurrentProviderConfig> {
     try {
-      console.log("Updating thinking mode:", { enabled, tokens });
-      return await invoke<string>("update_thinking_mode", { enabled, tokens });
+      return await invoke<CurrentProviderConfig>("get_current_provider_config");
     } catch (error) {
-      console.error("Failed to update thinking mode:", error);
+      console.error("Failed to get current provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Get Claude execution configuration
-   * @returns Promise resolving to the current execution config
+   * Switches to a new provider configuration
+   * @param config - The provider configuration to switch to
+   * @returns Promise resolving to success message
    */
-  async getClaudeExecutionConfig(): Promise<ClaudeExecutionConfig> {
+  async switchProviderConfig(config: ProviderConfig): Promise<string> {
     try {
-      return await invoke<ClaudeExecutionConfig>("get_claude_execution_config");
+      return await invoke<string>("switch_provider_config", { config });
     } catch (error) {
-      console.error("Failed to get Claude execution config:", error);
+      console.error("Failed to switch provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Update Claude execution configuration
-   * @param config - The new execution configuration
-   * @returns Promise resolving when the config is saved
+   * Clears all provider-related environment variables
+   * @returns Promise resolving to success message
    */
-  async updateClaudeExecutionConfig(config: ClaudeExecutionConfig): Promise<void> {
+  async clearProviderConfig(): Promise<string> {
     try {
-      console.log("Updating Claude execution config:", config);
-      return await invoke<void>("update_claude_execution_config", { config });
+      return await invoke<string>("clear_provider_config");
     } catch (error) {
-      console.error("Failed to update Claude execution config:", error);
+      console.error("Failed to clear provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Finds all CLAUDE.md files in a project directory
-   * @param projectPath - The absolute path to the project
-   * @returns Promise resolving to an array of CLAUDE.md files
+   * Tests connection to a provider endpoint
+   * @param baseUrl - The base URL to test
+   * @returns Promise resolving to test result message
    */
-  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+  async testProviderConnection(baseUrl: string): Promise<string> {
     try {
-      return await invoke<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+      return await invoke<string>("test_provider_connection", { baseUrl });
     } catch (error) {
-      console.error("Failed to find CLAUDE.md files:", error);
+      console.error("Failed to test provider connection:", error);
       throw error;
     }
   },
 
   /**
-   * Reads a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @returns Promise resolving to the file content
+   * Adds a new provider configuration
+   * @param config - The provider configuration to add
+   * @returns Promise resolving to success message
    */
-  async readClaudeMdFile(filePath: string): Promise<string> {
+  async addProviderConfig(config: Omit<ProviderConfig, 'id'>): Promise<string> {
+    // Generate ID from name
+    const id = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
+      
+    const fullConfig: ProviderConfig = {
+      ...config,
+      id
+    };
+    
     try {
-      return await invoke<string>("read_claude_md_file", { filePath });
+      return await invoke<string>("add_provider_config", { config: fullConfig });
     } catch (error) {
-      console.error("Failed to read CLAUDE.md file:", error);
+      console.error("Failed to add provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Saves a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @param content - The new content for the file
-   * @returns Promise resolving when the file is saved
+   * Updates an existing provider configuration
+   * @param config - The provider configuration to update (with id)
+   * @returns Promise resolving to success message
    */
-  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+  async updateProviderConfig(config: ProviderConfig): Promise<string> {
     try {
-      return await invoke<string>("save_claude_md_file", { filePath, content });
+      return await invoke<string>("update_provider_config", { config });
     } catch (error) {
-      console.error("Failed to save CLAUDE.md file:", error);
+      console.error("Failed to update provider config:", error);
       throw error;
     }
   },
 
-
   /**
-   * Loads the JSONL history for a specific session (Claude or Codex)
+   * 导出代理商预设包（不含密钥）
+   * @param engines - 要导出的引擎，默认全部
    */
-  async loadSessionHistory(sessionId: string, projectId: string, engine?: 'claude' | 'codex'): Promise<any[]> {
-    // For Codex sessions, read directly from .codex/sessions
-    if (engine === 'codex') {
-      return this.loadCodexSessionHistory(sessionId);
+  async exportProviderPresets(engines?: ("claude" | "codex" | "gemini")[]): Promise<ProviderPresetBundle> {
+    try {
+      return await invoke<ProviderPresetBundle>("export_provider_presets", { engines });
+    } catch (error) {
+      console.error("Failed to export provider presets:", error);
+      throw error;
     }
-    // For Claude sessions, use existing backend
-    return invoke("load_session_history", { sessionId, projectId });
   },
 
   /**
-   * 🆕 Loads Codex session history from JSONL file
+   * 导入代理商预设包
+   * @param bundle - exportProviderPresets 导出的预设包
+   * @param conflictStrategy - ID 冲突时跳过（默认）、覆盖（沿用本地密钥）或重命名
    */
-  async loadCodexSessionHistory(sessionId: string): Promise<any[]> {
+  async importProviderPresets(
+    bundle: ProviderPresetBundle,
+    conflictStrategy?: PresetConflictStrategy
+  ): Promise<ImportedPreset[]> {
     try {
-      return await invoke("load_codex_session_history", { sessionId });
+      return await invoke<ImportedPreset[]>("import_provider_presets", { bundle, conflictStrategy });
     } catch (error) {
-      console.error("Failed to load Codex session history:", error);
+      console.error("Failed to import provider presets:", error);
       throw error;
     }
   },
 
   /**
-   * Executes a new interactive Claude Code session with streaming output
-   * @param planMode - Enable Plan Mode for read-only research and planning
-   */
-  async executeClaudeCode(projectPath: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number): Promise<void> {
-    return invoke("execute_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens });
-  },
-
-  /**
-   * Continues an existing Claude Code conversation with streaming output
-   * @param planMode - Enable Plan Mode for read-only research and planning
-   */
-  async continueClaudeCode(projectPath: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number): Promise<void> {
-    return invoke("continue_claude_code", { projectPath, prompt, model, planMode, maxThinkingTokens });
-  },
-
-  /**
-   * Resumes an existing Claude Code session by ID with streaming output
-   * @param planMode - Enable Plan Mode for read-only research and planning
-   */
-  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string, planMode?: boolean, maxThinkingTokens?: number): Promise<void> {
-    return invoke("resume_claude_code", { projectPath, sessionId, prompt, model, planMode, maxThinkingTokens });
-  },
-
-  /**
-   * Cancels the currently running Claude Code execution
-   * @param sessionId - Optional session ID to cancel a specific session
-   */
-  async cancelClaudeExecution(sessionId?: string): Promise<void> {
-    return invoke("cancel_claude_execution", { sessionId });
-  },
-
-  /**
-   * Lists all currently running Claude sessions
-   * @returns Promise resolving to list of running Claude sessions
-   */
-  async listRunningClaudeSessions(): Promise<any[]> {
-    return invoke("list_running_claude_sessions");
-  },
-
-  /**
-   * Gets live output from a Claude session
-   * @param sessionId - The session ID to get output for
-   * @returns Promise resolving to the current live output
+   * 获取团队共享配置仓库设置
    */
-  async getClaudeSessionOutput(sessionId: string): Promise<string> {
-    return invoke("get_claude_session_output", { sessionId });
+  async getSharedConfigSettings(): Promise<SharedConfigSettings> {
+    try {
+      return await invoke<SharedConfigSettings>("get_shared_config_settings");
+    } catch (error) {
+      console.error("Failed to get shared config settings:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists files and directories in a given path
+   * 保存团队共享配置仓库设置
    */
-  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
-    return invoke("list_directory_contents", { directoryPath });
+  async setSharedConfigSettings(settings: SharedConfigSettings): Promise<void> {
+    try {
+      return await invoke("set_shared_config_settings", { settings });
+    } catch (error) {
+      console.error("Failed to save shared config settings:", error);
+      throw error;
+    }
   },
 
   /**
-   * Searches for files and directories matching a pattern
+   * 获取共享配置同步状态（来源标记与上次的冲突）
    */
-  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
-    return invoke("search_files", { basePath, query });
+  async getSharedConfigStatus(): Promise<SharedConfigStatus> {
+    try {
+      return await invoke<SharedConfigStatus>("get_shared_config_status");
+    } catch (error) {
+      console.error("Failed to get shared config status:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets overall usage statistics
-   * @returns Promise resolving to usage statistics
+   * 拉取共享仓库并与本地代理商预设、提示词模板、MCP 清单和权限档案合并
+   * @param approved - 批准应用的代理商预设和权限档案（取自上次结果的 `pending`）
    */
-  async getUsageStats(): Promise<UsageStats> {
+  async syncSharedConfig(approved?: SharedConfigApproval[]): Promise<SharedConfigSyncReport> {
     try {
-      return await invoke<UsageStats>("get_usage_stats");
+      return await invoke<SharedConfigSyncReport>("sync_shared_config", { approved });
     } catch (error) {
-      console.error("Failed to get usage stats:", error);
+      console.error("Failed to sync shared config:", error);
       throw error;
     }
   },
 
-
   /**
-   * Gets usage statistics filtered by date range
-   * @param startDate - Start date (ISO format)
-   * @param endDate - End date (ISO format)
-   * @returns Promise resolving to usage statistics
+   * Deletes a provider configuration by ID
+   * @param id - The ID of the provider configuration to delete
+   * @returns Promise resolving to success message
    */
-  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+  async deleteProviderConfig(id: string): Promise<string> {
     try {
-      return await invoke<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+      return await invoke<string>("delete_provider_config", { id });
     } catch (error) {
-      console.error("Failed to get usage by date range:", error);
+      console.error("Failed to delete provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics grouped by session
-   * @param since - Optional start date (YYYYMMDD)
-   * @param until - Optional end date (YYYYMMDD)
-   * @param order - Optional sort order ('asc' or 'desc')
-   * @returns Promise resolving to an array of session usage data
+   * Gets a single provider configuration by ID
+   * @param id - The ID of the provider configuration to get
+   * @returns Promise resolving to provider configuration
    */
-  async getSessionStats(
-    since?: string,
-    until?: string,
-    order?: "asc" | "desc"
-  ): Promise<ProjectUsage[]> {
+  async getProviderConfig(id: string): Promise<ProviderConfig> {
     try {
-      return await invoke<ProjectUsage[]>("get_session_stats", {
-        since,
-        until,
-        order,
-      });
+      return await invoke<ProviderConfig>("get_provider_config", { id });
     } catch (error) {
-      console.error("Failed to get session stats:", error);
+      console.error("Failed to get provider config:", error);
       throw error;
     }
   },
 
+
+  // ============================================================================
+  // ACEMCP INTEGRATION
+  // ============================================================================
+
   /**
-   * Gets multi-engine usage statistics (Claude, Codex, Gemini)
-   * @param engine - Engine filter ('all', 'claude', 'codex', 'gemini')
-   * @param startDate - Optional start date (YYYY-MM-DD)
-   * @param endDate - Optional end date (YYYY-MM-DD)
-   * @returns Promise resolving to multi-engine usage statistics
+   * Enhances a prompt by adding project context from acemcp semantic search
+   * 🆕 v2: 支持历史上下文感知和多轮搜索
+   *
+   * @param prompt - The original prompt to enhance
+   * @param projectPath - Path to the project directory
+   * @param sessionId - 🆕 Optional session ID for history-aware search
+   * @param projectId - 🆕 Optional project ID for history-aware search
+   * @param maxContextLength - Maximum length of context to include (default: 3000)
+   * @param enableMultiRound - 🆕 Enable multi-round search for better coverage (default: true)
+   * @returns Promise resolving to enhancement result
    */
-  async getMultiEngineUsageStats(
-    engine?: EngineType,
-    startDate?: string,
-    endDate?: string
-  ): Promise<MultiEngineUsageStats> {
+  async enhancePromptWithContext(
+    prompt: string,
+    projectPath: string,
+    sessionId?: string,
+    projectId?: string,
+    maxContextLength?: number,
+    enableMultiRound?: boolean
+  ): Promise<{
+    originalPrompt: string;
+    enhancedPrompt: string;
+    contextCount: number;
+    acemcpUsed: boolean;
+    error?: string;
+  }> {
     try {
-      return await invoke<MultiEngineUsageStats>("get_multi_engine_usage_stats", {
-        engine: engine || 'all',
-        startDate,
-        endDate,
+      return await invoke("enhance_prompt_with_context", {
+        prompt,
+        projectPath,
+        sessionId,
+        projectId,
+        maxContextLength,
+        enableMultiRound,
       });
     } catch (error) {
-      console.error("Failed to get multi-engine usage stats:", error);
+      console.error("Failed to enhance prompt with context:", error);
       throw error;
     }
   },
 
   /**
-   * Gets cache tokens for a specific session
-   * @param sessionId - The session ID to get cache tokens for
-   * @returns Promise resolving to session cache tokens
+   * Tests if acemcp is available and can be used
+   * @returns Promise resolving to true if acemcp is available
    */
-  async getSessionCacheTokens(sessionId: string): Promise<SessionCacheTokens> {
+  async testAcemcpAvailability(): Promise<boolean> {
     try {
-      return await invoke<SessionCacheTokens>("get_session_cache_tokens", { sessionId });
+      return await invoke<boolean>("test_acemcp_availability");
     } catch (error) {
-      console.error("Failed to get session cache tokens:", error);
-      throw error;
+      console.error("Failed to test acemcp availability:", error);
+      return false;
     }
   },
 
   /**
-   * Gets Codex rate limits from the latest session
-   * @returns Promise resolving to Codex rate limits
+   * Saves acemcp configuration to ~/.acemcp/settings.toml
    */
-  async getCodexRateLimits(): Promise<CodexRateLimits> {
+  async saveAcemcpConfig(
+    baseUrl: string,
+    token: string,
+    batchSize?: number,
+    maxLinesPerBlob?: number
+  ): Promise<void> {
     try {
-      return await invoke<CodexRateLimits>("get_codex_rate_limits");
+      return await invoke("save_acemcp_config", {
+        baseUrl,
+        token,
+        batchSize,
+        maxLinesPerBlob,
+      });
     } catch (error) {
-      console.error("Failed to get Codex rate limits:", error);
+      console.error("Failed to save acemcp config:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // MCP SERVER OPERATIONS
-  // ============================================================================
-
   /**
-   * Adds a new MCP server
+   * Loads acemcp configuration from ~/.acemcp/settings.toml
    */
-  async mcpAdd(
-    name: string,
-    transport: string,
-    command?: string,
-    args: string[] = [],
-    env: Record<string, string> = {},
-    url?: string,
-    scope: string = "local"
-  ): Promise<AddServerResult> {
+  async loadAcemcpConfig(): Promise<{
+    baseUrl: string;
+    token: string;
+    batchSize?: number;
+    maxLinesPerBlob?: number;
+  }> {
     try {
-      return await invoke<AddServerResult>("mcp_add", {
-        name,
-        transport,
-        command,
-        args,
-        env,
-        url,
-        scope
-      });
+      return await invoke("load_acemcp_config");
     } catch (error) {
-      console.error("Failed to add MCP server:", error);
-      throw error;
+      console.error("Failed to load acemcp config:", error);
+      // 返回默认配置
+      return {
+        baseUrl: '',
+        token: '',
+        batchSize: 10,
+        maxLinesPerBlob: 800,
+      };
     }
   },
 
   /**
-   * Lists all configured MCP servers
+   * Pre-indexes a project in background (non-blocking)
+   * Automatically triggered when user selects a project
    */
-  async mcpList(): Promise<MCPServer[]> {
+  async preindexProject(projectPath: string): Promise<void> {
     try {
-      console.log("API: Calling mcp_list...");
-      const result = await invoke<MCPServer[]>("mcp_list");
-      console.log("API: mcp_list returned:", result);
-      return result;
+      // 后台执行，不等待结果
+      invoke("preindex_project", { projectPath }).catch((error) => {
+        console.warn("Background pre-indexing failed:", error);
+      });
     } catch (error) {
-      console.error("API: Failed to list MCP servers:", error);
-      throw error;
+      console.warn("Failed to start pre-indexing:", error);
     }
   },
 
   /**
-   * Gets details for a specific MCP server
+   * Exports the embedded acemcp sidecar to a specified path
+   * For CLI configuration
    */
-  async mcpGet(name: string): Promise<MCPServer> {
+  async exportAcemcpSidecar(targetPath: string): Promise<string> {
     try {
-      return await invoke<MCPServer>("mcp_get", { name });
+      return await invoke<string>("export_acemcp_sidecar", { targetPath });
     } catch (error) {
-      console.error("Failed to get MCP server:", error);
+      console.error("Failed to export sidecar:", error);
       throw error;
     }
   },
 
   /**
-   * Removes an MCP server
+   * Gets the path of extracted sidecar in temp directory (if exists)
    */
-  async mcpRemove(name: string): Promise<string> {
+  async getExtractedSidecarPath(): Promise<string | null> {
     try {
-      return await invoke<string>("mcp_remove", { name });
+      return await invoke<string | null>("get_extracted_sidecar_path");
     } catch (error) {
-      console.error("Failed to remove MCP server:", error);
-      throw error;
+      console.error("Failed to get extracted sidecar path:", error);
+      return null;
     }
   },
 
   /**
-   * Adds an MCP server from JSON configuration
+   * Lists acemcp tool calls recorded for a session (newest first)
    */
-  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+  async acemcpListToolTraces(sessionId: string, limit?: number): Promise<AcemcpToolTrace[]> {
     try {
-      return await invoke<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+      return await invoke<AcemcpToolTrace[]>("acemcp_list_tool_traces", { sessionId, limit });
     } catch (error) {
-      console.error("Failed to add MCP server from JSON:", error);
+      console.error("Failed to list acemcp tool traces:", error);
       throw error;
     }
   },
 
   /**
-   * Imports MCP servers from Claude Desktop
+   * Replays a recorded acemcp tool call with the same parameters
+   * @returns The new trace (its replayOf points to the original)
    */
-  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+  async acemcpReplayToolTrace(traceId: number): Promise<AcemcpToolTrace> {
     try {
-      return await invoke<ImportResult>("mcp_add_from_claude_desktop", { scope });
+      return await invoke<AcemcpToolTrace>("acemcp_replay_tool_trace", { traceId });
     } catch (error) {
-      console.error("Failed to import from Claude Desktop:", error);
+      console.error(`Failed to replay acemcp tool trace ${traceId}:`, error);
       throw error;
     }
   },
 
+  // Translation API methods
+
   /**
-   * Starts Claude Code as an MCP server
+   * Translates text using the translation service
+   * @param text - The text to translate
+   * @param targetLang - Optional target language (defaults to auto-detection)
+   * @returns Promise resolving to translated text
    */
-  async mcpServe(): Promise<string> {
+  async translateText(text: string, targetLang?: string): Promise<string> {
     try {
-      return await invoke<string>("mcp_serve");
+      return await invoke<string>("translate", { text, targetLang });
     } catch (error) {
-      console.error("Failed to start MCP server:", error);
+      console.error("Failed to translate text:", error);
       throw error;
     }
   },
 
   /**
-   * Tests connection to an MCP server
+   * Translates multiple texts in batch
+   * @param texts - Array of texts to translate
+   * @param targetLang - Optional target language
+   * @returns Promise resolving to array of translated texts
    */
-  async mcpTestConnection(name: string): Promise<string> {
+  async translateBatch(texts: string[], targetLang?: string): Promise<string[]> {
     try {
-      return await invoke<string>("mcp_test_connection", { name });
+      return await invoke<string[]>("translate_batch", { texts, targetLang });
     } catch (error) {
-      console.error("Failed to test MCP connection:", error);
+      console.error("Failed to batch translate texts:", error);
       throw error;
     }
   },
 
   /**
-   * Exports MCP server configuration from .claude.json
+   * Gets the current translation configuration
+   * @returns Promise resolving to translation configuration
    */
-  async mcpExportConfig(): Promise<string> {
+  async getTranslationConfig(): Promise<TranslationConfig> {
     try {
-      return await invoke<string>("mcp_export_config");
+      return await invoke<TranslationConfig>("get_translation_config");
     } catch (error) {
-      console.error("Failed to export MCP configuration:", error);
+      console.error("Failed to get translation config:", error);
       throw error;
     }
   },
 
   /**
-   * Resets project-scoped server approval choices
+   * Updates the translation configuration
+   * @param config - New translation configuration
+   * @returns Promise resolving to success message
    */
-  async mcpResetProjectChoices(): Promise<string> {
+  async updateTranslationConfig(config: TranslationConfig): Promise<string> {
     try {
-      return await invoke<string>("mcp_reset_project_choices");
+      return await invoke<string>("update_translation_config", { config });
     } catch (error) {
-      console.error("Failed to reset project choices:", error);
+      console.error("Failed to update translation config:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the status of MCP servers
+   * Clears the translation cache
+   * @returns Promise resolving to success message
    */
-  async mcpGetServerStatus(): Promise<Record<string, ServerStatus>> {
+  async clearTranslationCache(): Promise<string> {
     try {
-      return await invoke<Record<string, ServerStatus>>("mcp_get_server_status");
+      return await invoke<string>("clear_translation_cache");
     } catch (error) {
-      console.error("Failed to get server status:", error);
+      console.error("Failed to clear translation cache:", error);
       throw error;
     }
   },
 
   /**
-   * Reads .mcp.json from the current project
+   * Gets translation cache statistics
+   * @returns Promise resolving to cache statistics
    */
-  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+  async getTranslationCacheStats(): Promise<TranslationCacheStats> {
     try {
-      return await invoke<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+      return await invoke<TranslationCacheStats>("get_translation_cache_stats");
     } catch (error) {
-      console.error("Failed to read project MCP config:", error);
+      console.error("Failed to get translation cache stats:", error);
       throw error;
     }
   },
 
   /**
-   * Saves .mcp.json to the current project
+   * 列出可用的翻译服务提供方
    */
-  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+  async listTranslationProviders(): Promise<TranslationProviderInfo[]> {
     try {
-      return await invoke<string>("mcp_save_project_config", { projectPath, config });
+      return await invoke<TranslationProviderInfo[]>("list_translation_providers");
     } catch (error) {
-      console.error("Failed to save project MCP config:", error);
+      console.error("Failed to list translation providers:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Multi-Engine MCP Support
-  // ============================================================================
-
   /**
-   * Lists MCP servers for a specific engine
-   * @param engine - The engine to list servers for ('claude', 'codex', 'gemini')
+   * 获取会话的双向翻译记录（提示词原文、回复译文）
    */
-  async mcpListByEngine(engine: MCPEngineType): Promise<MCPServerExtended[]> {
+  async getPromptTranslations(engine: string, sessionId: string): Promise<PromptTranslationRecord[]> {
     try {
-      console.log(`API: Calling mcp_list_by_engine for ${engine}...`);
-      const result = await invoke<MCPServerExtended[]>("mcp_list_by_engine", { engine });
-      console.log(`API: mcp_list_by_engine returned ${result.length} servers`);
-      return result;
+      return await invoke<PromptTranslationRecord[]>("get_prompt_translations", { engine, sessionId });
     } catch (error) {
-      console.error(`Failed to list MCP servers for ${engine}:`, error);
+      console.error("Failed to get prompt translations:", error);
       throw error;
     }
   },
 
-  /**
-   * Sets enabled/disabled status for an MCP server
-   * @param engine - The engine the server belongs to
-   * @param serverName - The name of the server
-   * @param enabled - Whether to enable or disable the server
+  /**
+   * Detects the language of the given text
+   * @param text - The text to analyze
+   * @returns Promise resolving to detected language code
    */
-  async mcpSetEnabled(engine: MCPEngineType, serverName: string, enabled: boolean): Promise<void> {
+  async detectTextLanguage(text: string): Promise<string> {
     try {
-      console.log(`API: Setting ${engine} server '${serverName}' enabled=${enabled}`);
-      await invoke<void>("mcp_set_enabled", { engine, serverName, enabled });
+      return await invoke<string>("detect_text_language", { text });
     } catch (error) {
-      console.error(`Failed to set MCP server enabled status:`, error);
+      console.error("Failed to detect text language:", error);
       throw error;
     }
   },
 
   /**
-   * Adds an MCP server for a specific engine
-   * @param engine - The engine to add the server to
-   * @param name - Server name
-   * @param transport - Transport type ('stdio' or 'sse')
-   * @param command - Command to execute (for stdio)
-   * @param args - Command arguments
-   * @param env - Environment variables
-   * @param url - URL endpoint (for SSE)
-   * @param scope - Configuration scope
+   * Initializes the translation service
+   * @param config - Optional translation configuration
+   * @returns Promise resolving to success message
    */
-  async mcpAddByEngine(
-    engine: MCPEngineType,
-    name: string,
-    transport: string,
-    command: string | null,
-    args: string[],
-    env: Record<string, string>,
-    url: string | null,
-    scope: string = "local"
-  ): Promise<AddServerResult> {
+  async initTranslationService(config?: TranslationConfig): Promise<string> {
     try {
-      console.log(`API: Adding server '${name}' to ${engine}`);
-      return await invoke<AddServerResult>("mcp_add_by_engine", {
-        engine,
-        name,
-        transport,
-        command,
-        args,
-        env,
-        url,
-        scope,
-      });
+      return await invoke<string>("init_translation_service_command", { config });
     } catch (error) {
-      console.error(`Failed to add MCP server to ${engine}:`, error);
+      console.error("Failed to initialize translation service:", error);
       throw error;
     }
   },
 
+  // Auto-Compact Context Management API methods
+
   /**
-   * Removes an MCP server for a specific engine
-   * @param engine - The engine to remove the server from
-   * @param serverName - The name of the server to remove
+   * Initializes the auto-compact manager
+   * @returns Promise resolving when manager is initialized
    */
-  async mcpRemoveByEngine(engine: MCPEngineType, serverName: string): Promise<string> {
+  async initAutoCompactManager(): Promise<void> {
     try {
-      console.log(`API: Removing server '${serverName}' from ${engine}`);
-      return await invoke<string>("mcp_remove_by_engine", { engine, serverName });
+      return await invoke<void>("init_auto_compact_manager");
     } catch (error) {
-      console.error(`Failed to remove MCP server from ${engine}:`, error);
+      console.error("Failed to initialize auto-compact manager:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an MCP server configuration for a specific engine
-   * @param engine - The engine to update the server for
-   * @param server - The updated server configuration
+   * Registers a Claude session for auto-compact monitoring
+   * @param sessionId - The session ID to register
+   * @param projectPath - The project path
+   * @param model - The model being used
+   * @returns Promise resolving when session is registered
    */
-  async mcpUpdateByEngine(engine: MCPEngineType, server: MCPServerExtended): Promise<void> {
+  async registerAutoCompactSession(sessionId: string, projectPath: string, model: string): Promise<void> {
     try {
-      console.log(`API: Updating server '${server.name}' for ${engine}`);
-      return await invoke<void>("mcp_update_by_engine", {
-        engine,
-        serverName: server.name,
-        command: server.command,
-        args: server.args || [],
-        env: server.env || {},
-        url: server.url,
-        enabled: server.enabled,
-      });
+      return await invoke<void>("register_auto_compact_session", { sessionId, projectPath, model });
     } catch (error) {
-      console.error(`Failed to update MCP server for ${engine}:`, error);
+      console.error("Failed to register auto-compact session:", error);
       throw error;
     }
   },
 
   /**
-   * Gets list of all projects with MCP server disabled status
-   * @param serverName - The name of the MCP server to check
-   * @param engine - The engine type (claude, codex, gemini)
-   * @returns Promise resolving to array of projects with their disabled status
+   * Updates session token count and checks for auto-compact trigger
+   * @param sessionId - The session ID
+   * @param tokenCount - Current token count
+   * @returns Promise resolving to whether compaction was triggered
    */
-  async mcpGetProjectList(serverName: string, engine: MCPEngineType = "claude"): Promise<{ path: string; disabled: boolean }[]> {
+  async updateSessionContext(sessionId: string, tokenCount: number): Promise<boolean> {
     try {
-      console.log(`API: Getting project list for ${engine} MCP server '${serverName}'`);
-      
-      if (engine === "codex") {
-        // Use Codex-specific command
-        return await invoke<{ path: string; disabled: boolean }[]>("codex_mcp_get_project_list", { serverName });
-      }
-      
-      // Default to Claude command
-      return await invoke<{ path: string; disabled: boolean }[]>("mcp_get_project_list", { serverName });
+      return await invoke<boolean>("update_session_context", { sessionId, tokenCount });
     } catch (error) {
-      console.error(`Failed to get project list for MCP server:`, error);
+      console.error("Failed to update session context:", error);
       throw error;
     }
   },
 
   /**
-   * Sets enabled/disabled status for an MCP server for a specific project
-   * @param engine - The engine the server belongs to
-   * @param serverName - The name of the server
-   * @param projectPath - The project path to update
-   * @param enabled - Whether to enable or disable the server
+   * Manually triggers compaction for a session
+   * @param sessionId - The session ID
+   * @param customInstructions - Optional custom compaction instructions
+   * @returns Promise resolving when compaction is complete
    */
-  async mcpSetEnabledForProject(engine: MCPEngineType, serverName: string, projectPath: string, enabled: boolean): Promise<void> {
+  async triggerManualCompaction(sessionId: string, customInstructions?: string): Promise<void> {
     try {
-      console.log(`API: Setting ${engine} server '${serverName}' enabled=${enabled} for project '${projectPath}'`);
-      
-      if (engine === "codex") {
-        // Use Codex-specific command
-        await invoke<void>("codex_mcp_set_enabled_for_project", { serverName, projectPath, enabled });
-      } else {
-        // Default to Claude command
-        await invoke<void>("mcp_set_enabled_for_project", { engine, serverName, projectPath, enabled });
-      }
+      return await invoke<void>("trigger_manual_compaction", { sessionId, customInstructions });
     } catch (error) {
-      console.error(`Failed to set MCP server enabled status for project:`, error);
+      console.error("Failed to trigger manual compaction:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a project to Codex MCP tracking
-   * @param projectPath - The project path to add
+   * Gets the current auto-compact configuration
+   * @returns Promise resolving to the configuration
    */
-  async codexMcpAddProject(projectPath: string): Promise<void> {
+  async getAutoCompactConfig(): Promise<AutoCompactConfig> {
     try {
-      console.log(`API: Adding project '${projectPath}' to Codex MCP tracking`);
-      await invoke<void>("codex_mcp_add_project", { projectPath });
+      return await invoke<AutoCompactConfig>("get_auto_compact_config");
     } catch (error) {
-      console.error(`Failed to add project to Codex MCP tracking:`, error);
+      console.error("Failed to get auto-compact config:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Codex Change Tracker API
-  // ============================================================================
-
   /**
-   * Record a file change in Codex session
-   * @param sessionId - The Codex session ID
-   * @param projectPath - The project path
-   * @param filePath - The file path
-   * @param changeType - The type of change (create, update, delete)
-   * @param source - The source of change (tool, command)
-   * @param promptText - The prompt that triggered this change
-   * @param newContent - The new content (for create/update)
-   * @param oldContent - The old content (for update, optional)
-   * @returns Promise resolving when the change is recorded
+   * Updates the auto-compact configuration
+   * @param config - The new configuration
+   * @returns Promise resolving when configuration is updated
    */
-  async codexRecordFileChange(
-    sessionId: string,
-    projectPath: string,
-    filePath: string,
-    changeType: 'create' | 'update' | 'delete',
-    source: 'tool' | 'command',
-    promptIndex: number,
-    promptText: string,
-    newContent: string,
-    oldContent: string | null,
-    toolName?: string | null,
-    toolCallId?: string | null,
-    diffHint?: string | null
-  ): Promise<string> {
+  async updateAutoCompactConfig(config: AutoCompactConfig): Promise<void> {
     try {
-      return await invoke<string>("codex_record_file_change", {
-        sessionId,
-        projectPath,
-        filePath,
-        changeType,
-        source,
-        promptIndex,
-        promptText,
-        newContent,
-        oldContent,
-        toolName,
-        toolCallId,
-        diffHint,
-      });
+      return await invoke<void>("update_auto_compact_config", { config });
     } catch (error) {
-      console.error("Failed to record Codex file change:", error);
+      console.error("Failed to update auto-compact config:", error);
       throw error;
     }
   },
 
   /**
-   * List all file changes for a Codex session
-   * @param sessionId - The Codex session ID
-   * @returns Promise resolving to array of file changes
+   * Gets session context statistics
+   * @param sessionId - The session ID
+   * @returns Promise resolving to session context information
    */
-  async codexListFileChanges(sessionId: string): Promise<import('@/types/codex-changes').CodexFileChange[]> {
+  async getSessionContextStats(sessionId: string): Promise<SessionContext | null> {
     try {
-      return await invoke<import('@/types/codex-changes').CodexFileChange[]>("codex_list_file_changes", { sessionId });
+      return await invoke<SessionContext | null>("get_session_context_stats", { sessionId });
     } catch (error) {
-      console.error("Failed to list Codex file changes:", error);
+      console.error("Failed to get session context stats:", error);
       throw error;
     }
   },
 
   /**
-   * Get details of a specific file change
-   * @param sessionId - The Codex session ID
-   * @param changeId - The change ID
-   * @returns Promise resolving to the change detail
+   * Gets all monitored sessions
+   * @returns Promise resolving to array of session contexts
    */
-  async codexGetChangeDetail(sessionId: string, changeId: string): Promise<import('@/types/codex-changes').CodexFileChange> {
+  async getAllMonitoredSessions(): Promise<SessionContext[]> {
     try {
-      return await invoke<import('@/types/codex-changes').CodexFileChange>("codex_get_change_detail", { sessionId, changeId });
+      return await invoke<SessionContext[]>("get_all_monitored_sessions");
     } catch (error) {
-      console.error("Failed to get Codex change detail:", error);
+      console.error("Failed to get monitored sessions:", error);
       throw error;
     }
   },
 
   /**
-   * Export all session changes as a patch file
-   * @param sessionId - The Codex session ID
-   * @param outputPath - The output file path
-   * @returns Promise resolving to the output path
+   * Unregisters session from auto-compact monitoring
+   * @param sessionId - The session ID to unregister
+   * @returns Promise resolving when session is unregistered
    */
-  async codexExportPatch(sessionId: string, outputPath: string): Promise<string> {
+  async unregisterAutoCompactSession(sessionId: string): Promise<void> {
     try {
-      return await invoke<string>("codex_export_patch", { sessionId, outputPath });
+      return await invoke<void>("unregister_auto_compact_session", { sessionId });
     } catch (error) {
-      console.error("Failed to export Codex patch:", error);
+      console.error("Failed to unregister auto-compact session:", error);
       throw error;
     }
   },
 
   /**
-   * Export a single change as a patch file
-   * @param sessionId - The Codex session ID
-   * @param changeId - The change ID
-   * @param outputPath - The output file path
-   * @returns Promise resolving to the output path
+   * Stops auto-compact monitoring
+   * @returns Promise resolving when monitoring is stopped
    */
-  async codexExportSingleChange(sessionId: string, changeId: string, outputPath: string): Promise<string> {
+  async stopAutoCompactMonitoring(): Promise<void> {
     try {
-      return await invoke<string>("codex_export_single_change", { sessionId, changeId, outputPath });
+      return await invoke<void>("stop_auto_compact_monitoring");
     } catch (error) {
-      console.error("Failed to export Codex single change:", error);
+      console.error("Failed to stop auto-compact monitoring:", error);
       throw error;
     }
   },
 
   /**
-   * Clear all change records for a session
-   * @param sessionId - The Codex session ID
+   * Starts auto-compact monitoring
+   * @returns Promise resolving when monitoring is started
    */
-  async codexClearChangeRecords(sessionId: string): Promise<void> {
+  async startAutoCompactMonitoring(): Promise<void> {
     try {
-      await invoke<void>("codex_clear_change_records", { sessionId });
+      return await invoke<void>("start_auto_compact_monitoring");
     } catch (error) {
-      console.error("Failed to clear Codex change records:", error);
+      console.error("Failed to start auto-compact monitoring:", error);
       throw error;
     }
   },
 
   /**
-   * Repair/upgrade change records for a session (recompute diffs, backfill old/new when possible)
-   * @param sessionId - The Codex session ID
-   * @returns Promise resolving to whether any records were upgraded
+   * Gets auto-compact status and statistics
+   * @returns Promise resolving to status information
    */
-  async codexRepairChangeRecords(sessionId: string): Promise<boolean> {
+  async getAutoCompactStatus(): Promise<AutoCompactStatus> {
     try {
-      return await invoke<boolean>("codex_repair_change_records", { sessionId });
+      return await invoke<AutoCompactStatus>("get_auto_compact_status");
     } catch (error) {
-      console.error("Failed to repair Codex change records:", error);
+      console.error("Failed to get auto-compact status:", error);
       throw error;
     }
   },
 
   /**
-   * Get the stored Claude binary path from settings
-   * @returns Promise resolving to the path if set, null otherwise
+   * Gets active sessions information
+   * @returns Promise resolving to array of active session info
    */
-  async getClaudeBinaryPath(): Promise<string | null> {
+  async getActiveSessions(): Promise<any[]> {
     try {
-      return await invoke<string | null>("get_claude_binary_path");
+      return await invoke("get_active_sessions");
     } catch (error) {
-      console.error("Failed to get Claude binary path:", error);
+      console.error('Failed to get active sessions:', error);
       throw error;
     }
   },
 
+  // Subagent Management & Specialization API methods
+
+
+
+
+
+
+
+
+  // Enhanced Hooks Automation API methods
+
   /**
-   * Set the Claude binary path in settings
-   * @param path - The absolute path to the Claude binary
-   * @returns Promise resolving when the path is saved
+   * Triggers a hook event with context
+   * @param event - The hook event name
+   * @param context - The hook execution context
+   * @returns Promise resolving to hook chain execution result
    */
-  async setClaudeBinaryPath(path: string): Promise<void> {
+  async triggerHookEvent(event: string, context: any): Promise<any> {
     try {
-      return await invoke<void>("set_claude_binary_path", { path });
+      return await invoke<any>("trigger_hook_event", { event, context });
     } catch (error) {
-      console.error("Failed to set Claude binary path:", error);
+      console.error("Failed to trigger hook event:", error);
       throw error;
     }
   },
 
-
-  // Storage API methods
-
   /**
-   * Lists all tables in the SQLite database
-   * @returns Promise resolving to an array of table information
+   * Tests a hook condition expression
+   * @param condition - The condition expression to test
+   * @param context - The hook context for evaluation
+   * @returns Promise resolving to whether condition is true
    */
-  async storageListTables(): Promise<any[]> {
+  async testHookCondition(condition: string, context: any): Promise<boolean> {
     try {
-      return await invoke<any[]>("storage_list_tables");
+      return await invoke<boolean>("test_hook_condition", { condition, context });
     } catch (error) {
-      console.error("Failed to list tables:", error);
+      console.error("Failed to test hook condition:", error);
       throw error;
     }
   },
 
   /**
-   * Reads table data with pagination
-   * @param tableName - Name of the table to read
-   * @param page - Page number (1-indexed)
-   * @param pageSize - Number of rows per page
-   * @param searchQuery - Optional search query
-   * @returns Promise resolving to table data with pagination info
+   * Executes pre-commit code review hook with intelligent decision making
+   * @param projectPath - The project path to review
+   * @param config - Optional configuration for the review hook
+   * @returns Promise resolving to commit decision
    */
-  async storageReadTable(
-    tableName: string,
-    page: number,
-    pageSize: number,
-    searchQuery?: string
-  ): Promise<any> {
+  async executePreCommitReview(
+    projectPath: string,
+    config?: import('@/types/enhanced-hooks').PreCommitCodeReviewConfig
+  ): Promise<import('@/types/enhanced-hooks').CommitDecision> {
     try {
-      return await invoke<any>("storage_read_table", {
-        tableName,
-        page,
-        pageSize,
-        searchQuery,
+      return await invoke<import('@/types/enhanced-hooks').CommitDecision>("execute_pre_commit_review", {
+        projectPath,
+        config
       });
     } catch (error) {
-      console.error("Failed to read table:", error);
+      console.error("Failed to execute pre-commit review:", error);
       throw error;
     }
   },
 
+  // ==================== Checkpoint API Methods ====================
+
   /**
-   * Updates a row in a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @param updates - Map of column names to new values
-   * @returns Promise resolving when the row is updated
+  /**
+   * Tracks a batch of messages for a session for checkpointing
    */
-  async storageUpdateRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>,
-    updates: Record<string, any>
+  async trackSessionMessages(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messages: string[]
   ): Promise<void> {
     try {
-      return await invoke<void>("storage_update_row", {
-        tableName,
-        primaryKeyValues,
-        updates,
+      return await invoke<void>("track_session_messages", {
+        sessionId,
+        projectId,
+        projectPath,
+        messages
       });
     } catch (error) {
-      console.error("Failed to update row:", error);
+      console.error("Failed to track session messages:", error);
       throw error;
     }
   },
 
+  // ==================== Prompt Revert System ====================
+
   /**
-   * Deletes a row from a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @returns Promise resolving when the row is deleted
+   * Check and initialize Git repository
    */
-  async storageDeleteRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>
-  ): Promise<void> {
+  async checkAndInitGit(projectPath: string): Promise<boolean> {
     try {
-      return await invoke<void>("storage_delete_row", {
-        tableName,
-        primaryKeyValues,
-      });
+      return await invoke<boolean>("check_and_init_git", { projectPath });
     } catch (error) {
-      console.error("Failed to delete row:", error);
-      throw error;
+      console.error("Failed to check/init Git:", error);
+      return false;
     }
   },
 
   /**
-   * Inserts a new row into a table
-   * @param tableName - Name of the table
-   * @param values - Map of column names to values
-   * @returns Promise resolving to the last insert row ID
+   * Record a prompt being sent
    */
-  async storageInsertRow(
-    tableName: string,
-    values: Record<string, any>
+  async recordPromptSent(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    promptText: string
   ): Promise<number> {
     try {
-      return await invoke<number>("storage_insert_row", {
-        tableName,
-        values,
+      return await invoke<number>("record_prompt_sent", {
+        sessionId,
+        projectId,
+        projectPath,
+        promptText
       });
     } catch (error) {
-      console.error("Failed to insert row:", error);
+      console.error("Failed to record prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Executes a raw SQL query
-   * @param query - SQL query string
-   * @returns Promise resolving to query result
+   * Mark a prompt as completed
    */
-  async storageExecuteSql(query: string): Promise<any> {
+  async markPromptCompleted(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    promptIndex: number
+  ): Promise<void> {
     try {
-      return await invoke<any>("storage_execute_sql", { query });
+      return await invoke<void>("mark_prompt_completed", {
+        sessionId,
+        projectId,
+        projectPath,
+        promptIndex
+      });
     } catch (error) {
-      console.error("Failed to execute SQL:", error);
+      console.error("Failed to mark prompt completed:", error);
       throw error;
     }
   },
 
   /**
-   * Resets the entire database
-   * @returns Promise resolving when the database is reset
+   * Revert to a specific prompt with support for different rewind modes
    */
-  async storageResetDatabase(): Promise<void> {
+  async revertToPrompt(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    promptIndex: number,
+    mode: RewindMode = "both"
+  ): Promise<string> {
     try {
-      return await invoke<void>("storage_reset_database");
+      return await invoke<string>("revert_to_prompt", {
+        sessionId,
+        projectId,
+        projectPath,
+        promptIndex,
+        mode
+      });
     } catch (error) {
-      console.error("Failed to reset database:", error);
+      console.error("Failed to revert to prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Get hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to the hooks configuration
+   * Get list of all prompts for a session
+   * Extracts all prompts from .jsonl (single source of truth)
    */
-  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
+  async getPromptList(
+    sessionId: string,
+    projectId: string
+  ): Promise<PromptRecord[]> {
     try {
-      return await invoke<HooksConfiguration>("get_hooks_config", { scope, projectPath });
+      return await invoke<PromptRecord[]>("get_prompt_list", {
+        sessionId,
+        projectId
+      });
     } catch (error) {
-      console.error("Failed to get hooks config:", error);
-      throw error;
+      console.error("Failed to get prompt list:", error);
+      return [];
     }
   },
 
   /**
-   * Update hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param hooks - The hooks configuration to save
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to success message
+   * Get unified prompt list with git records enriched from .git-records.json
+   * Combines .jsonl prompts (all messages) with git records (hash-based mapping)
+   * This includes both project interface prompts (with git records) and CLI prompts (without git records)
    */
-  async updateHooksConfig(
-    scope: 'user' | 'project' | 'local',
-    hooks: HooksConfiguration,
-    projectPath?: string
-  ): Promise<string> {
+  async getUnifiedPromptList(
+    sessionId: string,
+    projectId: string
+  ): Promise<PromptRecord[]> {
     try {
-      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
+      return await invoke<PromptRecord[]>("get_unified_prompt_list", {
+        sessionId,
+        projectId
+      });
+    } catch (error) {
+      console.error("Failed to get unified prompt list:", error);
+      return [];
+    }
+  },
+
+  /**
+   * Check rewind capabilities for a specific prompt
+   * Determines whether a prompt can be reverted fully (conversation + code) or partially (conversation only)
+   */
+  async checkRewindCapabilities(
+    sessionId: string,
+    projectId: string,
+    promptIndex: number
+  ): Promise<RewindCapabilities> {
+    try {
+      return await invoke<RewindCapabilities>("check_rewind_capabilities", {
+        sessionId,
+        projectId,
+        promptIndex
+      });
     } catch (error) {
-      console.error("Failed to update hooks config:", error);
+      console.error("Failed to check rewind capabilities:", error);
       throw error;
     }
   },
 
+  // ==================== Claude Extensions (Plugins, Subagents & Skills) ====================
+
   /**
-   * Validate a hook command syntax
-   * @param command - The shell command to validate
-   * @returns Promise resolving to validation result
+   * List all installed plugins
    */
-  async validateHookCommand(command: string): Promise<{ valid: boolean; message: string }> {
+  async listPlugins(projectPath?: string): Promise<any[]> {
     try {
-      return await invoke<{ valid: boolean; message: string }>("validate_hook_command", { command });
+      return await invoke<any[]>("list_plugins", { projectPath });
     } catch (error) {
-      console.error("Failed to validate hook command:", error);
-      throw error;
+      console.error("Failed to list plugins:", error);
+      return [];
     }
   },
 
   /**
-   * Get merged hooks configuration (respecting priority)
-   * @param projectPath - The project path
-   * @returns Promise resolving to merged hooks configuration
+   * Open plugins directory
    */
-  async getMergedHooksConfig(projectPath: string): Promise<HooksConfiguration> {
+  async openPluginsDirectory(projectPath?: string): Promise<string> {
     try {
-      const [userHooks, projectHooks, localHooks] = await Promise.all([
-        this.getHooksConfig('user'),
-        this.getHooksConfig('project', projectPath),
-        this.getHooksConfig('local', projectPath)
-      ]);
-
-      return HooksManager.mergeConfigs(userHooks, projectHooks, localHooks);
+      return await invoke<string>("open_plugins_directory", { projectPath });
     } catch (error) {
-      console.error("Failed to get merged hooks config:", error);
+      console.error("Failed to open plugins directory:", error);
       throw error;
     }
   },
 
-
   /**
-   * Set custom Claude CLI path
-   * @param customPath - Path to custom Claude CLI executable
-   * @returns Promise resolving when path is set successfully
+   * List all subagents
    */
-  async setCustomClaudePath(customPath: string): Promise<void> {
+  async listSubagents(projectPath?: string): Promise<any[]> {
     try {
-      return await invoke<void>("set_custom_claude_path", { customPath });
+      return await invoke<any[]>("list_subagents", { projectPath });
     } catch (error) {
-      console.error("Failed to set custom Claude path:", error);
-      throw error;
+      console.error("Failed to list subagents:", error);
+      return [];
     }
   },
 
   /**
-   * Get current Claude CLI path (custom or auto-detected)
-   * @returns Promise resolving to current Claude CLI path
+   * List all agent skills
    */
-  async getClaudePath(): Promise<string> {
+  async listAgentSkills(projectPath?: string): Promise<any[]> {
     try {
-      return await invoke<string>("get_claude_path");
+      return await invoke<any[]>("list_agent_skills", { projectPath });
     } catch (error) {
-      console.error("Failed to get Claude path:", error);
-      throw error;
+      console.error("Failed to list agent skills:", error);
+      return [];
     }
   },
 
   /**
-   * Clear custom Claude CLI path and revert to auto-detection
-   * @returns Promise resolving when custom path is cleared
+   * Read a subagent file
    */
-  async clearCustomClaudePath(): Promise<void> {
+  async readSubagent(filePath: string): Promise<string> {
     try {
-      return await invoke<void>("clear_custom_claude_path");
+      return await invoke<string>("read_subagent", { filePath });
     } catch (error) {
-      console.error("Failed to clear custom Claude path:", error);
+      console.error("Failed to read subagent:", error);
       throw error;
     }
   },
 
-
-
-  // Clipboard API methods
-
   /**
-   * Saves clipboard image data to a temporary file
-   * @param base64Data - Base64 encoded image data
-   * @param format - Optional image format
-   * @returns Promise resolving to saved image result
+   * Read a skill file
    */
-  async saveClipboardImage(base64Data: string, format?: string): Promise<SavedImageResult> {
+  async readSkill(filePath: string): Promise<string> {
     try {
-      return await invoke<SavedImageResult>("save_clipboard_image", { base64Data, format });
+      return await invoke<string>("read_skill", { filePath });
     } catch (error) {
-      console.error("Failed to save clipboard image:", error);
+      console.error("Failed to read skill:", error);
       throw error;
     }
   },
 
-  // Provider Management API methods
-
   /**
-   * Gets the list of preset provider configurations
-   * @returns Promise resolving to array of provider configurations
+   * Open agents directory in file explorer
    */
-  async getProviderPresets(): Promise<ProviderConfig[]> {
+  async openAgentsDirectory(projectPath?: string): Promise<string> {
     try {
-      return await invoke<ProviderConfig[]>("get_provider_presets");
+      return await invoke<string>("open_agents_directory", { projectPath });
     } catch (error) {
-      console.error("Failed to get provider presets:", error);
+      console.error("Failed to open agents directory:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the current provider configuration from environment variables
-   * @returns Promise resolving to current configuration
+   * Open skills directory in file explorer
    */
-  async getCurrentProviderConfig(): Promise<CurrentProviderConfig> {
+  async openSkillsDirectory(projectPath?: string): Promise<string> {
     try {
-      return await invoke<CurrentProviderConfig>("get_current_provider_config");
+      return await invoke<string>("open_skills_directory", { projectPath });
     } catch (error) {
-      console.error("Failed to get current provider config:", error);
+      console.error("Failed to open skills directory:", error);
       throw error;
     }
   },
 
   /**
-   * Switches to a new provider configuration
-   * @param config - The provider configuration to switch to
-   * @returns Promise resolving to success message
+   * Create a new subagent
+   * @param name - Agent name (alphanumeric, hyphens, underscores only)
+   * @param description - Short description of the agent
+   * @param content - Agent system prompt content
+   * @param scope - "project" or "user"
+   * @param projectPath - Required for project scope
    */
-  async switchProviderConfig(config: ProviderConfig): Promise<string> {
+  async createSubagent(
+    name: string,
+    description: string,
+    content: string,
+    scope: 'project' | 'user',
+    projectPath?: string
+  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
     try {
-      return await invoke<string>("switch_provider_config", { config });
+      return await invoke("create_subagent", { name, description, content, scope, projectPath });
     } catch (error) {
-      console.error("Failed to switch provider config:", error);
+      console.error("Failed to create subagent:", error);
       throw error;
     }
   },
 
   /**
-   * Clears all provider-related environment variables
-   * @returns Promise resolving to success message
+   * Create a new Agent Skill
+   * @param name - Skill name (alphanumeric, hyphens, underscores only)
+   * @param description - Short description of what this skill does
+   * @param content - Skill instructions content
+   * @param scope - "project" or "user"
+   * @param projectPath - Required for project scope
    */
-  async clearProviderConfig(): Promise<string> {
+  async createSkill(
+    name: string,
+    description: string,
+    content: string,
+    scope: 'project' | 'user',
+    projectPath?: string
+  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
     try {
-      return await invoke<string>("clear_provider_config");
+      return await invoke("create_skill", { name, description, content, scope, projectPath });
     } catch (error) {
-      console.error("Failed to clear provider config:", error);
+      console.error("Failed to create skill:", error);
       throw error;
     }
   },
 
   /**
-   * Tests connection to a provider endpoint
-   * @param baseUrl - The base URL to test
-   * @returns Promise resolving to test result message
+   * Open a directory in system file explorer (cross-platform)
    */
-  async testProviderConnection(baseUrl: string): Promise<string> {
+  async openDirectoryInExplorer(directoryPath: string): Promise<void> {
     try {
-      return await invoke<string>("test_provider_connection", { baseUrl });
+      return await invoke<void>("open_directory_in_explorer", { directoryPath });
     } catch (error) {
-      console.error("Failed to test provider connection:", error);
+      console.error("Failed to open directory in explorer:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a new provider configuration
-   * @param config - The provider configuration to add
-   * @returns Promise resolving to success message
+   * Open a file with system default application (cross-platform)
    */
-  async addProviderConfig(config: Omit<ProviderConfig, 'id'>): Promise<string> {
-    // Generate ID from name
-    const id = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
-      
-    const fullConfig: ProviderConfig = {
-      ...config,
-      id
-    };
-    
+  async openFileWithDefaultApp(filePath: string): Promise<void> {
     try {
-      return await invoke<string>("add_provider_config", { config: fullConfig });
+      return await invoke<void>("open_file_with_default_app", { filePath });
     } catch (error) {
-      console.error("Failed to add provider config:", error);
+      console.error("Failed to open file with default app:", error);
       throw error;
     }
   },
 
+  // ==================== Git Statistics ====================
+
   /**
-   * Updates an existing provider configuration
-   * @param config - The provider configuration to update (with id)
-   * @returns Promise resolving to success message
+   * Get Git diff statistics between commits
    */
-  async updateProviderConfig(config: ProviderConfig): Promise<string> {
+  async getGitDiffStats(
+    projectPath: string,
+    fromCommit: string,
+    toCommit?: string
+  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
     try {
-      return await invoke<string>("update_provider_config", { config });
+      return await invoke("get_git_diff_stats", { projectPath, fromCommit, toCommit });
     } catch (error) {
-      console.error("Failed to update provider config:", error);
+      console.error("Failed to get git diff stats:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a provider configuration by ID
-   * @param id - The ID of the provider configuration to delete
-   * @returns Promise resolving to success message
+   * Get code changes for current session
    */
-  async deleteProviderConfig(id: string): Promise<string> {
+  async getSessionCodeChanges(
+    projectPath: string,
+    sessionStartCommit: string
+  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
     try {
-      return await invoke<string>("delete_provider_config", { id });
+      return await invoke("get_session_code_changes", { projectPath, sessionStartCommit });
     } catch (error) {
-      console.error("Failed to delete provider config:", error);
+      console.error("Failed to get session code changes:", error);
       throw error;
     }
   },
 
   /**
-   * Gets a single provider configuration by ID
-   * @param id - The ID of the provider configuration to get
-   * @returns Promise resolving to provider configuration
+   * Get commit trends for a project (new commits are counted incrementally)
+   * @param period - week | month | quarter | year | all
+   * @param granularity - day | week | month
    */
-  async getProviderConfig(id: string): Promise<ProviderConfig> {
+  async getGitTrends(
+    projectPath: string,
+    period: GitTrendPeriod,
+    granularity: GitTrendGranularity
+  ): Promise<GitTrends> {
     try {
-      return await invoke<ProviderConfig>("get_provider_config", { id });
+      return await invoke<GitTrends>("get_git_trends", { projectPath, period, granularity });
     } catch (error) {
-      console.error("Failed to get provider config:", error);
+      console.error("Failed to get git trends:", error);
       throw error;
     }
   },
 
-
-  // ============================================================================
-  // ACEMCP INTEGRATION
-  // ============================================================================
+  /**
+   * Refresh git statistics in the background (returns immediately)
+   */
+  async refreshGitStats(projectPath: string): Promise<void> {
+    try {
+      await invoke("refresh_git_stats", { projectPath });
+    } catch (error) {
+      console.warn("Failed to start git stats refresh:", error);
+    }
+  },
 
   /**
-   * Enhances a prompt by adding project context from acemcp semantic search
-   * 🆕 v2: 支持历史上下文感知和多轮搜索
-   *
-   * @param prompt - The original prompt to enhance
-   * @param projectPath - Path to the project directory
-   * @param sessionId - 🆕 Optional session ID for history-aware search
-   * @param projectId - 🆕 Optional project ID for history-aware search
-   * @param maxContextLength - Maximum length of context to include (default: 3000)
-   * @param enableMultiRound - 🆕 Enable multi-round search for better coverage (default: true)
-   * @returns Promise resolving to enhancement result
+   * Get AI vs manual contribution stats for a project, broken down by engine
+   * @param period - week | month | quarter | year | all
    */
-  async enhancePromptWithContext(
-    prompt: string,
-    projectPath: string,
-    sessionId?: string,
-    projectId?: string,
-    maxContextLength?: number,
-    enableMultiRound?: boolean
-  ): Promise<{
-    originalPrompt: string;
-    enhancedPrompt: string;
-    contextCount: number;
-    acemcpUsed: boolean;
-    error?: string;
-  }> {
+  async getAiAttributionStats(projectPath: string, period: GitTrendPeriod): Promise<AiAttributionStats> {
     try {
-      return await invoke("enhance_prompt_with_context", {
-        prompt,
-        projectPath,
-        sessionId,
-        projectId,
-        maxContextLength,
-        enableMultiRound,
-      });
+      return await invoke<AiAttributionStats>("get_ai_attribution_stats", { projectPath, period });
     } catch (error) {
-      console.error("Failed to enhance prompt with context:", error);
+      console.error("Failed to get AI attribution stats:", error);
       throw error;
     }
   },
 
   /**
-   * Tests if acemcp is available and can be used
-   * @returns Promise resolving to true if acemcp is available
+   * List local and remote-tracking branches
    */
-  async testAcemcpAvailability(): Promise<boolean> {
+  async listBranches(projectPath: string): Promise<GitBranch[]> {
     try {
-      return await invoke<boolean>("test_acemcp_availability");
+      return await invoke<GitBranch[]>("list_branches", { projectPath });
     } catch (error) {
-      console.error("Failed to test acemcp availability:", error);
-      return false;
+      console.error("Failed to list branches:", error);
+      throw error;
     }
   },
 
   /**
-   * Saves acemcp configuration to ~/.acemcp/settings.toml
+   * Create a branch from startPoint (HEAD by default)
+   * @param checkout - Switch to the new branch after creating it
    */
-  async saveAcemcpConfig(
-    baseUrl: string,
-    token: string,
-    batchSize?: number,
-    maxLinesPerBlob?: number
-  ): Promise<void> {
+  async createBranch(projectPath: string, name: string, startPoint?: string, checkout?: boolean): Promise<void> {
     try {
-      return await invoke("save_acemcp_config", {
-        baseUrl,
-        token,
-        batchSize,
-        maxLinesPerBlob,
-      });
+      await invoke("create_branch", { projectPath, name, startPoint, checkout });
     } catch (error) {
-      console.error("Failed to save acemcp config:", error);
+      console.error(`Failed to create branch '${name}':`, error);
       throw error;
     }
   },
 
   /**
-   * Loads acemcp configuration from ~/.acemcp/settings.toml
+   * Switch to a branch
    */
-  async loadAcemcpConfig(): Promise<{
-    baseUrl: string;
-    token: string;
-    batchSize?: number;
-    maxLinesPerBlob?: number;
-  }> {
+  async switchBranch(projectPath: string, name: string): Promise<void> {
     try {
-      return await invoke("load_acemcp_config");
+      await invoke("switch_branch", { projectPath, name });
     } catch (error) {
-      console.error("Failed to load acemcp config:", error);
-      // 返回默认配置
-      return {
-        baseUrl: '',
-        token: '',
-        batchSize: 10,
-        maxLinesPerBlob: 800,
-      };
+      console.error(`Failed to switch to branch '${name}':`, error);
+      throw error;
     }
   },
 
   /**
-   * Pre-indexes a project in background (non-blocking)
-   * Automatically triggered when user selects a project
+   * Delete a local branch
+   * @param force - Also delete branches that are not fully merged
    */
-  async preindexProject(projectPath: string): Promise<void> {
+  async deleteBranch(projectPath: string, name: string, force?: boolean): Promise<void> {
     try {
-      // 后台执行，不等待结果
-      invoke("preindex_project", { projectPath }).catch((error) => {
-        console.warn("Background pre-indexing failed:", error);
-      });
+      await invoke("delete_branch", { projectPath, name, force });
     } catch (error) {
-      console.warn("Failed to start pre-indexing:", error);
+      console.error(`Failed to delete branch '${name}':`, error);
+      throw error;
     }
   },
 
   /**
-   * Exports the embedded acemcp sidecar to a specified path
-   * For CLI configuration
+   * Get ahead/behind of a branch (current by default) against base (its upstream by default)
    */
-  async exportAcemcpSidecar(targetPath: string): Promise<string> {
+  async getBranchStatus(projectPath: string, branch?: string, base?: string): Promise<BranchStatus> {
     try {
-      return await invoke<string>("export_acemcp_sidecar", { targetPath });
+      return await invoke<BranchStatus>("get_branch_status", { projectPath, branch, base });
     } catch (error) {
-      console.error("Failed to export sidecar:", error);
+      console.error("Failed to get branch status:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the path of extracted sidecar in temp directory (if exists)
+   * Compare a session's net changes (including uncommitted ones) with another branch
+   * and flag files that would conflict on merge
    */
-  async getExtractedSidecarPath(): Promise<string | null> {
+  async compareSessionWithBranch(
+    projectPath: string,
+    engine: "claude" | "codex" | "gemini",
+    sessionId: string,
+    branch: string
+  ): Promise<SessionBranchComparison> {
     try {
-      return await invoke<string | null>("get_extracted_sidecar_path");
+      return await invoke<SessionBranchComparison>("compare_session_with_branch", {
+        projectPath,
+        engine,
+        sessionId,
+        branch,
+      });
     } catch (error) {
-      console.error("Failed to get extracted sidecar path:", error);
-      return null;
+      console.error("Failed to compare session with branch:", error);
+      throw error;
     }
   },
 
-  // Translation API methods
-
   /**
-   * Translates text using the translation service
-   * @param text - The text to translate
-   * @param targetLang - Optional target language (defaults to auto-detection)
-   * @returns Promise resolving to translated text
+   * Get the unstaged hunks of a file (untracked files show as a new-file hunk)
    */
-  async translateText(text: string, targetLang?: string): Promise<string> {
+  async getUnstagedHunks(projectPath: string, file: string): Promise<FileHunks> {
     try {
-      return await invoke<string>("translate", { text, targetLang });
+      return await invoke<FileHunks>("get_unstaged_hunks", { projectPath, file });
     } catch (error) {
-      console.error("Failed to translate text:", error);
+      console.error(`Failed to get unstaged hunks of ${file}:`, error);
       throw error;
     }
   },
 
   /**
-   * Translates multiple texts in batch
-   * @param texts - Array of texts to translate
-   * @param targetLang - Optional target language
-   * @returns Promise resolving to array of translated texts
+   * Stage selected hunks of a file
+   * @param hunkIds - Ids from getUnstagedHunks; fails if the file changed since
    */
-  async translateBatch(texts: string[], targetLang?: string): Promise<string[]> {
+  async stageHunks(projectPath: string, file: string, hunkIds: string[]): Promise<void> {
     try {
-      return await invoke<string[]>("translate_batch", { texts, targetLang });
+      await invoke("stage_hunks", { projectPath, file, hunkIds });
     } catch (error) {
-      console.error("Failed to batch translate texts:", error);
+      console.error(`Failed to stage hunks of ${file}:`, error);
       throw error;
     }
   },
 
   /**
-   * Gets the current translation configuration
-   * @returns Promise resolving to translation configuration
+   * Unstage a file (keeps working tree changes)
    */
-  async getTranslationConfig(): Promise<TranslationConfig> {
+  async unstageFile(projectPath: string, file: string): Promise<void> {
     try {
-      return await invoke<TranslationConfig>("get_translation_config");
+      await invoke("unstage_file", { projectPath, file });
     } catch (error) {
-      console.error("Failed to get translation config:", error);
+      console.error(`Failed to unstage ${file}:`, error);
       throw error;
     }
   },
 
   /**
-   * Updates the translation configuration
-   * @param config - New translation configuration
-   * @returns Promise resolving to success message
+   * Generate a Conventional Commits message for the current changes
+   * @param stagedOnly - Only describe staged changes (otherwise all uncommitted changes)
+   * @param engine - Engine to use (defaults to the project's commit message settings)
    */
-  async updateTranslationConfig(config: TranslationConfig): Promise<string> {
+  async generateCommitMessage(
+    projectPath: string,
+    stagedOnly: boolean,
+    engine?: string
+  ): Promise<GeneratedCommitMessage> {
     try {
-      return await invoke<string>("update_translation_config", { config });
+      return await invoke<GeneratedCommitMessage>("generate_commit_message", { projectPath, stagedOnly, engine });
     } catch (error) {
-      console.error("Failed to update translation config:", error);
+      console.error("Failed to generate commit message:", error);
       throw error;
     }
   },
 
+  // ==================== OpenAI Codex Integration ====================
+
   /**
-   * Clears the translation cache
-   * @returns Promise resolving to success message
+   * Executes a Codex task in non-interactive mode with streaming output
+   * @param options - Codex execution options
+   * @returns Promise resolving when execution starts (events are streamed via event listeners)
    */
-  async clearTranslationCache(): Promise<string> {
+  async executeCodex(options: import('@/types/codex').CodexExecutionOptions): Promise<void> {
     try {
-      return await invoke<string>("clear_translation_cache");
+      return await invoke("execute_codex", { options });
     } catch (error) {
-      console.error("Failed to clear translation cache:", error);
+      console.error("Failed to execute Codex:", error);
       throw error;
     }
   },
 
   /**
-   * Gets translation cache statistics
-   * @returns Promise resolving to cache statistics
+   * Resumes a previous Codex session
+   * @param sessionId - The session ID to resume
+   * @param options - Codex execution options (prompt, mode, etc.)
+   * @returns Promise resolving when execution starts
    */
-  async getTranslationCacheStats(): Promise<TranslationCacheStats> {
+  async resumeCodex(
+    sessionId: string,
+    options: Omit<import('@/types/codex').CodexExecutionOptions, 'sessionId'>
+  ): Promise<void> {
     try {
-      return await invoke<TranslationCacheStats>("get_translation_cache_stats");
+      return await invoke("resume_codex", { sessionId, options });
     } catch (error) {
-      console.error("Failed to get translation cache stats:", error);
+      console.error("Failed to resume Codex session:", error);
       throw error;
     }
   },
 
   /**
-   * Detects the language of the given text
-   * @param text - The text to analyze
-   * @returns Promise resolving to detected language code
+   * Resumes the last Codex session
+   * @param options - Codex execution options
+   * @returns Promise resolving when execution starts
    */
-  async detectTextLanguage(text: string): Promise<string> {
+  async resumeLastCodex(
+    options: Omit<import('@/types/codex').CodexExecutionOptions, 'resumeLast'>
+  ): Promise<void> {
     try {
-      return await invoke<string>("detect_text_language", { text });
+      return await invoke("resume_last_codex", { options });
     } catch (error) {
-      console.error("Failed to detect text language:", error);
+      console.error("Failed to resume last Codex session:", error);
       throw error;
     }
   },
 
   /**
-   * Initializes the translation service
-   * @param config - Optional translation configuration
-   * @returns Promise resolving to success message
+   * Cancels a running Codex execution
+   * @param sessionId - Optional session ID to cancel a specific session
+   * @returns Promise resolving when cancellation is complete
    */
-  async initTranslationService(config?: TranslationConfig): Promise<string> {
+  async cancelCodex(sessionId?: string): Promise<void> {
     try {
-      return await invoke<string>("init_translation_service_command", { config });
+      return await invoke("cancel_codex", { sessionId });
     } catch (error) {
-      console.error("Failed to initialize translation service:", error);
+      console.error("Failed to cancel Codex execution:", error);
       throw error;
     }
   },
 
-  // Auto-Compact Context Management API methods
-
   /**
-   * Initializes the auto-compact manager
-   * @returns Promise resolving when manager is initialized
+   * Gets a list of all Codex sessions
+   * @returns Promise resolving to array of Codex sessions
    */
-  async initAutoCompactManager(): Promise<void> {
+  async listCodexSessions(): Promise<import('@/types/codex').CodexSession[]> {
     try {
-      return await invoke<void>("init_auto_compact_manager");
+      return await invoke<import('@/types/codex').CodexSession[]>("list_codex_sessions");
     } catch (error) {
-      console.error("Failed to initialize auto-compact manager:", error);
+      console.error("Failed to list Codex sessions:", error);
       throw error;
     }
   },
 
   /**
-   * Registers a Claude session for auto-compact monitoring
-   * @param sessionId - The session ID to register
-   * @param projectPath - The project path
-   * @param model - The model being used
-   * @returns Promise resolving when session is registered
+   * Gets Codex sessions filtered by project path (optimized)
+   * @param projectPath - The project path to filter by
+   * @returns Promise resolving to array of Codex sessions for the project
    */
-  async registerAutoCompactSession(sessionId: string, projectPath: string, model: string): Promise<void> {
+  async listCodexSessionsForProject(projectPath: string): Promise<import('@/types/codex').CodexSession[]> {
     try {
-      return await invoke<void>("register_auto_compact_session", { sessionId, projectPath, model });
+      return await invoke<import('@/types/codex').CodexSession[]>("list_codex_sessions_for_project", { projectPath });
     } catch (error) {
-      console.error("Failed to register auto-compact session:", error);
+      console.error("Failed to list Codex sessions for project:", error);
       throw error;
     }
   },
 
   /**
-   * Updates session token count and checks for auto-compact trigger
-   * @param sessionId - The session ID
-   * @param tokenCount - Current token count
-   * @returns Promise resolving to whether compaction was triggered
+   * Lists all Codex projects (grouped by project path)
+   * @returns Promise resolving to array of Codex projects
    */
-  async updateSessionContext(sessionId: string, tokenCount: number): Promise<boolean> {
+  async listCodexProjects(): Promise<CodexProject[]> {
     try {
-      return await invoke<boolean>("update_session_context", { sessionId, tokenCount });
+      return await invoke<CodexProject[]>("list_codex_projects");
     } catch (error) {
-      console.error("Failed to update session context:", error);
-      throw error;
+      console.error("Failed to list Codex projects:", error);
+      // Return empty array on error to allow graceful degradation
+      return [];
     }
   },
 
   /**
-   * Manually triggers compaction for a session
-   * @param sessionId - The session ID
-   * @param customInstructions - Optional custom compaction instructions
-   * @returns Promise resolving when compaction is complete
+   * Deletes a Codex session
+   * @param sessionId - The session ID to delete
+   * @returns Promise resolving to success message
    */
-  async triggerManualCompaction(sessionId: string, customInstructions?: string): Promise<void> {
+  async deleteCodexSession(sessionId: string): Promise<string> {
     try {
-      return await invoke<void>("trigger_manual_compaction", { sessionId, customInstructions });
+      return await invoke<string>("delete_codex_session", { sessionId });
     } catch (error) {
-      console.error("Failed to trigger manual compaction:", error);
+      console.error("Failed to delete Codex session:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the current auto-compact configuration
-   * @returns Promise resolving to the configuration
+   * Checks if Codex is available and properly configured
+   * @returns Promise resolving to availability status
    */
-  async getAutoCompactConfig(): Promise<AutoCompactConfig> {
+  async checkCodexAvailability(): Promise<{
+    available: boolean;
+    version?: string;
+    error?: string;
+  }> {
     try {
-      return await invoke<AutoCompactConfig>("get_auto_compact_config");
+      return await invoke("check_codex_availability");
     } catch (error) {
-      console.error("Failed to get auto-compact config:", error);
-      throw error;
+      console.error("Failed to check Codex availability:", error);
+      return {
+        available: false,
+        error: error instanceof Error ? error.message : String(error)
+      };
     }
   },
 
+  // ============================================================================
+  // Codex Mode Configuration (WSL Support)
+  // ============================================================================
+
   /**
-   * Updates the auto-compact configuration
-   * @param config - The new configuration
-   * @returns Promise resolving when configuration is updated
+   * Gets Codex mode configuration
+   * @returns Promise resolving to mode configuration info
    */
-  async updateAutoCompactConfig(config: AutoCompactConfig): Promise<void> {
+  async getCodexModeConfig(): Promise<{
+    mode: 'auto' | 'native' | 'wsl' | 'remote';
+    wslDistro: string | null;
+    actualMode: 'native' | 'wsl' | 'remote';
+    nativeAvailable: boolean;
+    wslAvailable: boolean;
+    availableDistros: string[];
+    remote: RemoteConfig | null;
+  }> {
     try {
-      return await invoke<void>("update_auto_compact_config", { config });
+      return await invoke("get_codex_mode_config");
     } catch (error) {
-      console.error("Failed to update auto-compact config:", error);
+      console.error("Failed to get Codex mode config:", error);
       throw error;
     }
   },
 
   /**
-   * Gets session context statistics
-   * @param sessionId - The session ID
-   * @returns Promise resolving to session context information
+   * Sets Codex mode configuration
+   * @param mode - The mode to set: 'auto', 'native', 'wsl' or 'remote'
+   * @param wslDistro - Optional WSL distro name
+   * @param remote - Remote SSH host (required for 'remote', kept when omitted)
+   * @returns Promise resolving to success message
    */
-  async getSessionContextStats(sessionId: string): Promise<SessionContext | null> {
+  async setCodexModeConfig(
+    mode: 'auto' | 'native' | 'wsl' | 'remote',
+    wslDistro?: string | null,
+    remote?: RemoteConfig | null
+  ): Promise<string> {
     try {
-      return await invoke<SessionContext | null>("get_session_context_stats", { sessionId });
+      return await invoke<string>("set_codex_mode_config", {
+        mode,
+        wslDistro: wslDistro || null,
+        remote: remote || null
+      });
     } catch (error) {
-      console.error("Failed to get session context stats:", error);
+      console.error("Failed to set Codex mode config:", error);
       throw error;
     }
   },
 
   /**
-   * Gets all monitored sessions
-   * @returns Promise resolving to array of session contexts
+   * Tests the SSH connection and returns the remote Codex version
+   */
+  async testRemoteConnection(config: RemoteConfig): Promise<string> {
+    return await invoke<string>("test_remote_connection", { config });
+  },
+
+  /**
+   * Syncs the remote Codex sessions directory into the local mirror now
+   * @returns Local mirror directory
    */
-  async getAllMonitoredSessions(): Promise<SessionContext[]> {
+  async syncRemoteCodexSessions(): Promise<string> {
+    return await invoke<string>("sync_remote_codex_sessions");
+  },
+
+  /**
+   * Sets custom Codex CLI path
+   * @param path - Path to custom Codex CLI executable (null to clear)
+   * @returns Promise resolving to success message
+   */
+  async setCodexCustomPath(path: string | null): Promise<string> {
     try {
-      return await invoke<SessionContext[]>("get_all_monitored_sessions");
+      return await invoke<string>("set_codex_custom_path", { path });
     } catch (error) {
-      console.error("Failed to get monitored sessions:", error);
+      console.error("Failed to set custom Codex path:", error);
       throw error;
     }
   },
 
   /**
-   * Unregisters session from auto-compact monitoring
-   * @param sessionId - The session ID to unregister
-   * @returns Promise resolving when session is unregistered
+   * Validates a Codex path
+   * @param path - Path to validate
+   * @returns Promise resolving to whether the path is valid
    */
-  async unregisterAutoCompactSession(sessionId: string): Promise<void> {
+  async validateCodexPath(path: string): Promise<boolean> {
     try {
-      return await invoke<void>("unregister_auto_compact_session", { sessionId });
+      return await invoke<boolean>("validate_codex_path_cmd", { path });
     } catch (error) {
-      console.error("Failed to unregister auto-compact session:", error);
-      throw error;
+      console.error("Failed to validate Codex path:", error);
+      return false;
     }
   },
 
   /**
-   * Stops auto-compact monitoring
-   * @returns Promise resolving when monitoring is stopped
+   * Scans for all possible Codex installation paths
+   * @returns Promise resolving to array of found paths
    */
-  async stopAutoCompactMonitoring(): Promise<void> {
+  async scanCodexPaths(): Promise<string[]> {
     try {
-      return await invoke<void>("stop_auto_compact_monitoring");
+      return await invoke<string[]>("scan_codex_paths");
     } catch (error) {
-      console.error("Failed to stop auto-compact monitoring:", error);
-      throw error;
+      console.error("Failed to scan Codex paths:", error);
+      return [];
     }
   },
 
+  // ============================================================================
+  // Codex Rewind Commands
+  // ============================================================================
+
   /**
-   * Starts auto-compact monitoring
-   * @returns Promise resolving when monitoring is started
+   * Records a Codex prompt being sent (called before execution)
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param promptText - The prompt text
+   * @returns Promise resolving to the prompt index
    */
-  async startAutoCompactMonitoring(): Promise<void> {
+  async recordCodexPromptSent(
+    sessionId: string,
+    projectPath: string,
+    promptText: string
+  ): Promise<number> {
     try {
-      return await invoke<void>("start_auto_compact_monitoring");
+      return await invoke<number>("record_codex_prompt_sent", {
+        sessionId,
+        projectPath,
+        promptText
+      });
     } catch (error) {
-      console.error("Failed to start auto-compact monitoring:", error);
+      console.error("Failed to record Codex prompt sent:", error);
       throw error;
     }
   },
 
   /**
-   * Gets auto-compact status and statistics
-   * @returns Promise resolving to status information
+   * Records a Codex prompt completion (called after AI response)
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to complete
    */
-  async getAutoCompactStatus(): Promise<AutoCompactStatus> {
+  async recordCodexPromptCompleted(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number
+  ): Promise<void> {
     try {
-      return await invoke<AutoCompactStatus>("get_auto_compact_status");
+      await invoke("record_codex_prompt_completed", {
+        sessionId,
+        projectPath,
+        promptIndex
+      });
     } catch (error) {
-      console.error("Failed to get auto-compact status:", error);
+      console.error("Failed to record Codex prompt completed:", error);
       throw error;
     }
   },
 
   /**
-   * Gets active sessions information
-   * @returns Promise resolving to array of active session info
+   * Gets Codex prompt list for a session (used by revert picker)
    */
-  async getActiveSessions(): Promise<any[]> {
+  async getCodexPromptList(sessionId: string): Promise<PromptRecord[]> {
     try {
-      return await invoke("get_active_sessions");
+      return await invoke<PromptRecord[]>("get_codex_prompt_list", { sessionId });
     } catch (error) {
-      console.error('Failed to get active sessions:', error);
-      throw error;
+      console.error("Failed to get Codex prompt list:", error);
+      return [];
     }
   },
 
-  // Subagent Management & Specialization API methods
-
-
-
-
-
-
-
-
-  // Enhanced Hooks Automation API methods
-
   /**
-   * Triggers a hook event with context
-   * @param event - The hook event name
-   * @param context - The hook execution context
-   * @returns Promise resolving to hook chain execution result
+   * Checks rewind capabilities for a Codex prompt
+   * @param sessionId - Codex session ID
+   * @param promptIndex - Prompt index to check
    */
-  async triggerHookEvent(event: string, context: any): Promise<any> {
+  async checkCodexRewindCapabilities(
+    sessionId: string,
+    promptIndex: number
+  ): Promise<RewindCapabilities> {
     try {
-      return await invoke<any>("trigger_hook_event", { event, context });
+      return await invoke<RewindCapabilities>("check_codex_rewind_capabilities", {
+        sessionId,
+        promptIndex,
+      });
     } catch (error) {
-      console.error("Failed to trigger hook event:", error);
-      throw error;
+      console.error("Failed to check Codex rewind capabilities:", error);
+      // Fallback to conversation-only to keep UI functional
+      return {
+        conversation: true,
+        code: false,
+        both: false,
+        warning: "无法获取 Codex 撤回能力，只能删除对话记录。",
+        source: "cli",
+      };
     }
   },
 
   /**
-   * Tests a hook condition expression
-   * @param condition - The condition expression to test
-   * @param context - The hook context for evaluation
-   * @returns Promise resolving to whether condition is true
+   * Reverts a Codex session to a specific prompt
+   * @param sessionId - The Codex session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to revert to
+   * @param mode - The rewind mode (conversation_only, code_only, or both)
+   * @param partial - Undo only Codex's recorded changes, keeping manual edits
+   * @returns Promise resolving to the prompt text (for restoring to input)
    */
-  async testHookCondition(condition: string, context: any): Promise<boolean> {
+  async revertCodexToPrompt(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number,
+    mode: RewindMode = "both",
+    partial?: boolean
+  ): Promise<string> {
     try {
-      return await invoke<boolean>("test_hook_condition", { condition, context });
+      return await invoke<string>("revert_codex_to_prompt", {
+        sessionId,
+        projectPath,
+        promptIndex,
+        mode,
+        partial
+      });
     } catch (error) {
-      console.error("Failed to test hook condition:", error);
+      console.error("Failed to revert Codex to prompt:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // Gemini Rewind Commands
+  // ============================================================================
+
   /**
-   * Executes pre-commit code review hook with intelligent decision making
-   * @param projectPath - The project path to review
-   * @param config - Optional configuration for the review hook
-   * @returns Promise resolving to commit decision
+   * Records a Gemini prompt being sent (called before execution)
+   * @param sessionId - The Gemini session ID
+   * @param projectPath - The project path
+   * @param promptText - The prompt text
+   * @returns Promise resolving to the prompt index
    */
-  async executePreCommitReview(
+  async recordGeminiPromptSent(
+    sessionId: string,
     projectPath: string,
-    config?: import('@/types/enhanced-hooks').PreCommitCodeReviewConfig
-  ): Promise<import('@/types/enhanced-hooks').CommitDecision> {
+    promptText: string
+  ): Promise<number> {
     try {
-      return await invoke<import('@/types/enhanced-hooks').CommitDecision>("execute_pre_commit_review", {
+      return await invoke<number>("record_gemini_prompt_sent", {
+        sessionId,
         projectPath,
-        config
+        promptText
       });
     } catch (error) {
-      console.error("Failed to execute pre-commit review:", error);
+      console.error("Failed to record Gemini prompt sent:", error);
       throw error;
     }
   },
 
-  // ==================== Checkpoint API Methods ====================
-
-  /**
   /**
-   * Tracks a batch of messages for a session for checkpointing
+   * Records a Gemini prompt completion (called after AI response)
+   * @param sessionId - The Gemini session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to complete
    */
-  async trackSessionMessages(
+  async recordGeminiPromptCompleted(
     sessionId: string,
-    projectId: string,
     projectPath: string,
-    messages: string[]
+    promptIndex: number
   ): Promise<void> {
     try {
-      return await invoke<void>("track_session_messages", {
+      await invoke("record_gemini_prompt_completed", {
         sessionId,
-        projectId,
         projectPath,
-        messages
+        promptIndex
       });
     } catch (error) {
-      console.error("Failed to track session messages:", error);
+      console.error("Failed to record Gemini prompt completed:", error);
       throw error;
     }
   },
 
-  // ==================== Prompt Revert System ====================
+  /**
+   * Gets Gemini prompt list for a session (used by revert picker)
+   */
+  async getGeminiPromptList(sessionId: string, projectPath: string): Promise<PromptRecord[]> {
+    try {
+      return await invoke<PromptRecord[]>("get_gemini_prompt_list", { sessionId, projectPath });
+    } catch (error) {
+      console.error("Failed to get Gemini prompt list:", error);
+      return [];
+    }
+  },
 
   /**
-   * Check and initialize Git repository
+   * Checks rewind capabilities for a Gemini prompt
+   * @param sessionId - Gemini session ID
+   * @param projectPath - The project path
+   * @param promptIndex - Prompt index to check
    */
-  async checkAndInitGit(projectPath: string): Promise<boolean> {
+  async checkGeminiRewindCapabilities(
+    sessionId: string,
+    projectPath: string,
+    promptIndex: number
+  ): Promise<RewindCapabilities> {
     try {
-      return await invoke<boolean>("check_and_init_git", { projectPath });
+      return await invoke<RewindCapabilities>("check_gemini_rewind_capabilities", {
+        sessionId,
+        projectPath,
+        promptIndex,
+      });
     } catch (error) {
-      console.error("Failed to check/init Git:", error);
-      return false;
+      console.error("Failed to check Gemini rewind capabilities:", error);
+      // Fallback to conversation-only to keep UI functional
+      return {
+        conversation: true,
+        code: false,
+        both: false,
+        warning: "无法获取 Gemini 撤回能力，只能删除对话记录。",
+        source: "project",
+      };
     }
   },
 
   /**
-   * Record a prompt being sent
+   * Reverts a Gemini session to a specific prompt
+   * @param sessionId - The Gemini session ID
+   * @param projectPath - The project path
+   * @param promptIndex - The prompt index to revert to
+   * @param mode - The rewind mode (conversation_only, code_only, or both)
+   * @returns Promise resolving to success message
    */
-  async recordPromptSent(
+  async revertGeminiToPrompt(
     sessionId: string,
-    projectId: string,
     projectPath: string,
-    promptText: string
-  ): Promise<number> {
+    promptIndex: number,
+    mode: RewindMode = "both"
+  ): Promise<string> {
     try {
-      return await invoke<number>("record_prompt_sent", {
+      return await invoke<string>("revert_gemini_to_prompt", {
         sessionId,
-        projectId,
         projectPath,
-        promptText
+        promptIndex,
+        mode
       });
     } catch (error) {
-      console.error("Failed to record prompt:", error);
+      console.error("Failed to revert Gemini to prompt:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // CODEX CONFIG.TOML FILE PROVIDERS (AnyCode)
+  // ============================================================================
+
   /**
-   * Mark a prompt as completed
+   * Reads current ~/.codex/config.toml as text (WSL-aware on Windows)
    */
-  async markPromptCompleted(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    promptIndex: number
-  ): Promise<void> {
+  async readCodexConfigToml(): Promise<string> {
     try {
-      return await invoke<void>("mark_prompt_completed", {
-        sessionId,
-        projectId,
-        projectPath,
-        promptIndex
-      });
+      return await invoke<string>("read_codex_config_toml");
     } catch (error) {
-      console.error("Failed to mark prompt completed:", error);
+      console.error("Failed to read Codex config.toml:", error);
       throw error;
     }
   },
 
   /**
-   * Revert to a specific prompt with support for different rewind modes
+   * Writes ~/.codex/config.toml as text (WSL-aware on Windows)
    */
-  async revertToPrompt(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    promptIndex: number,
-    mode: RewindMode = "both"
-  ): Promise<string> {
+  async writeCodexConfigToml(content: string): Promise<string> {
     try {
-      return await invoke<string>("revert_to_prompt", {
-        sessionId,
-        projectId,
-        projectPath,
-        promptIndex,
-        mode
-      });
+      return await invoke<string>("write_codex_config_toml", { content });
     } catch (error) {
-      console.error("Failed to revert to prompt:", error);
+      console.error("Failed to write Codex config.toml:", error);
       throw error;
     }
   },
 
   /**
-   * Get list of all prompts for a session
-   * Extracts all prompts from .jsonl (single source of truth)
+   * Reads current ~/.codex/auth.json as text (WSL-aware on Windows)
    */
-  async getPromptList(
-    sessionId: string,
-    projectId: string
-  ): Promise<PromptRecord[]> {
+  async readCodexAuthJsonText(): Promise<string> {
     try {
-      return await invoke<PromptRecord[]>("get_prompt_list", {
-        sessionId,
-        projectId
-      });
+      return await invoke<string>("read_codex_auth_json_text");
     } catch (error) {
-      console.error("Failed to get prompt list:", error);
-      return [];
+      console.error("Failed to read Codex auth.json:", error);
+      throw error;
     }
   },
 
   /**
-   * Get unified prompt list with git records enriched from .git-records.json
-   * Combines .jsonl prompts (all messages) with git records (hash-based mapping)
-   * This includes both project interface prompts (with git records) and CLI prompts (without git records)
+   * Writes ~/.codex/auth.json as text (WSL-aware on Windows)
    */
-  async getUnifiedPromptList(
-    sessionId: string,
-    projectId: string
-  ): Promise<PromptRecord[]> {
+  async writeCodexAuthJsonText(content: string): Promise<string> {
     try {
-      return await invoke<PromptRecord[]>("get_unified_prompt_list", {
-        sessionId,
-        projectId
-      });
+      return await invoke<string>("write_codex_auth_json_text", { content });
     } catch (error) {
-      console.error("Failed to get unified prompt list:", error);
-      return [];
+      console.error("Failed to write Codex auth.json:", error);
+      throw error;
     }
   },
 
   /**
-   * Check rewind capabilities for a specific prompt
-   * Determines whether a prompt can be reverted fully (conversation + code) or partially (conversation only)
+   * Writes both ~/.codex/config.toml and ~/.codex/auth.json (WSL-aware on Windows)
    */
-  async checkRewindCapabilities(
-    sessionId: string,
-    projectId: string,
-    promptIndex: number
-  ): Promise<RewindCapabilities> {
+  async writeCodexConfigFiles(configToml: string, authJson: string): Promise<string> {
     try {
-      return await invoke<RewindCapabilities>("check_rewind_capabilities", {
-        sessionId,
-        projectId,
-        promptIndex
-      });
+      return await invoke<string>("write_codex_config_files", { configToml, authJson });
     } catch (error) {
-      console.error("Failed to check rewind capabilities:", error);
+      console.error("Failed to write Codex config files:", error);
       throw error;
     }
   },
 
-  // ==================== Claude Extensions (Plugins, Subagents & Skills) ====================
-
   /**
-   * List all installed plugins
+   * Gets list of saved Codex config.toml presets
    */
-  async listPlugins(projectPath?: string): Promise<any[]> {
+  async getCodexConfigFileProviders(): Promise<CodexConfigFileProvider[]> {
     try {
-      return await invoke<any[]>("list_plugins", { projectPath });
+      return await invoke<CodexConfigFileProvider[]>("get_codex_config_file_providers");
     } catch (error) {
-      console.error("Failed to list plugins:", error);
-      return [];
+      console.error("Failed to get Codex config.toml presets:", error);
+      throw error;
     }
   },
 
   /**
-   * Open plugins directory
+   * Adds a Codex config.toml preset
    */
-  async openPluginsDirectory(projectPath?: string): Promise<string> {
+  async addCodexConfigFileProvider(config: Omit<CodexConfigFileProvider, 'id'>): Promise<string> {
+    const id = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
+
+    const fullConfig: CodexConfigFileProvider = {
+      ...config,
+      id,
+      createdAt: Date.now(),
+    };
+
     try {
-      return await invoke<string>("open_plugins_directory", { projectPath });
+      return await invoke<string>("add_codex_config_file_provider", { config: fullConfig });
     } catch (error) {
-      console.error("Failed to open plugins directory:", error);
+      console.error("Failed to add Codex config.toml preset:", error);
       throw error;
     }
   },
 
   /**
-   * List all subagents
+   * Updates a Codex config.toml preset
    */
-  async listSubagents(projectPath?: string): Promise<any[]> {
+  async updateCodexConfigFileProvider(config: CodexConfigFileProvider): Promise<string> {
     try {
-      return await invoke<any[]>("list_subagents", { projectPath });
+      return await invoke<string>("update_codex_config_file_provider", { config });
     } catch (error) {
-      console.error("Failed to list subagents:", error);
-      return [];
+      console.error("Failed to update Codex config.toml preset:", error);
+      throw error;
     }
   },
 
   /**
-   * List all agent skills
+   * Deletes a Codex config.toml preset
    */
-  async listAgentSkills(projectPath?: string): Promise<any[]> {
+  async deleteCodexConfigFileProvider(id: string): Promise<string> {
     try {
-      return await invoke<any[]>("list_agent_skills", { projectPath });
+      return await invoke<string>("delete_codex_config_file_provider", { id });
     } catch (error) {
-      console.error("Failed to list agent skills:", error);
-      return [];
+      console.error("Failed to delete Codex config.toml preset:", error);
+      throw error;
     }
   },
 
+  // ============================================================================
+  // CODEX PROVIDER MANAGEMENT
+  // ============================================================================
+
   /**
-   * Read a subagent file
+   * Gets the list of Codex provider presets
+   * @returns Promise resolving to array of Codex provider configurations
    */
-  async readSubagent(filePath: string): Promise<string> {
+  async getCodexProviderPresets(): Promise<CodexProviderConfig[]> {
     try {
-      return await invoke<string>("read_subagent", { filePath });
+      return await invoke<CodexProviderConfig[]>("get_codex_provider_presets");
     } catch (error) {
-      console.error("Failed to read subagent:", error);
+      console.error("Failed to get Codex provider presets:", error);
       throw error;
     }
   },
 
   /**
-   * Read a skill file
+   * Gets the current Codex provider configuration from ~/.codex directory
+   * @returns Promise resolving to current Codex configuration
    */
-  async readSkill(filePath: string): Promise<string> {
+  async getCurrentCodexConfig(): Promise<CurrentCodexConfig> {
     try {
-      return await invoke<string>("read_skill", { filePath });
+      return await invoke<CurrentCodexConfig>("get_current_codex_config");
     } catch (error) {
-      console.error("Failed to read skill:", error);
+      console.error("Failed to get current Codex config:", error);
       throw error;
     }
   },
 
   /**
-   * Open agents directory in file explorer
+   * Switches to a Codex provider configuration
+   * Writes auth.json and config.toml to ~/.codex directory
+   * @param config - The Codex provider configuration to switch to
+   * @returns Promise resolving to success message
    */
-  async openAgentsDirectory(projectPath?: string): Promise<string> {
+  async switchCodexProvider(config: CodexProviderConfig): Promise<string> {
     try {
-      return await invoke<string>("open_agents_directory", { projectPath });
+      return await invoke<string>("switch_codex_provider", { config });
     } catch (error) {
-      console.error("Failed to open agents directory:", error);
+      console.error("Failed to switch Codex provider:", error);
       throw error;
     }
   },
 
   /**
-   * Open skills directory in file explorer
+   * Adds a new Codex provider configuration
+   * @param config - The Codex provider configuration to add
+   * @returns Promise resolving to success message
    */
-  async openSkillsDirectory(projectPath?: string): Promise<string> {
+  async addCodexProviderConfig(config: Omit<CodexProviderConfig, 'id'>): Promise<string> {
+    // Generate ID from name
+    const id = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
+
+    const fullConfig: CodexProviderConfig = {
+      ...config,
+      id,
+      createdAt: Date.now(),
+    };
+
     try {
-      return await invoke<string>("open_skills_directory", { projectPath });
+      return await invoke<string>("add_codex_provider_config", { config: fullConfig });
     } catch (error) {
-      console.error("Failed to open skills directory:", error);
+      console.error("Failed to add Codex provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Create a new subagent
-   * @param name - Agent name (alphanumeric, hyphens, underscores only)
-   * @param description - Short description of the agent
-   * @param content - Agent system prompt content
-   * @param scope - "project" or "user"
-   * @param projectPath - Required for project scope
+   * Updates an existing Codex provider configuration
+   * @param config - The Codex provider configuration to update (with id)
+   * @returns Promise resolving to success message
    */
-  async createSubagent(
-    name: string,
-    description: string,
-    content: string,
-    scope: 'project' | 'user',
-    projectPath?: string
-  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
+  async updateCodexProviderConfig(config: CodexProviderConfig): Promise<string> {
     try {
-      return await invoke("create_subagent", { name, description, content, scope, projectPath });
+      return await invoke<string>("update_codex_provider_config", { config });
     } catch (error) {
-      console.error("Failed to create subagent:", error);
+      console.error("Failed to update Codex provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Create a new Agent Skill
-   * @param name - Skill name (alphanumeric, hyphens, underscores only)
-   * @param description - Short description of what this skill does
-   * @param content - Skill instructions content
-   * @param scope - "project" or "user"
-   * @param projectPath - Required for project scope
+   * Deletes a Codex provider configuration by ID
+   * @param id - The ID of the Codex provider configuration to delete
+   * @returns Promise resolving to success message
    */
-  async createSkill(
-    name: string,
-    description: string,
-    content: string,
-    scope: 'project' | 'user',
-    projectPath?: string
-  ): Promise<{ name: string; path: string; scope: string; description: string; content: string }> {
+  async deleteCodexProviderConfig(id: string): Promise<string> {
     try {
-      return await invoke("create_skill", { name, description, content, scope, projectPath });
+      return await invoke<string>("delete_codex_provider_config", { id });
     } catch (error) {
-      console.error("Failed to create skill:", error);
+      console.error("Failed to delete Codex provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Open a directory in system file explorer (cross-platform)
+   * Clears Codex provider configuration (resets to official)
+   * Removes auth.json and config.toml from ~/.codex directory
+   * @returns Promise resolving to success message
    */
-  async openDirectoryInExplorer(directoryPath: string): Promise<void> {
+  async clearCodexProviderConfig(): Promise<string> {
     try {
-      return await invoke<void>("open_directory_in_explorer", { directoryPath });
+      return await invoke<string>("clear_codex_provider_config");
     } catch (error) {
-      console.error("Failed to open directory in explorer:", error);
+      console.error("Failed to clear Codex provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Open a file with system default application (cross-platform)
+   * Tests Codex provider connection
+   * @param baseUrl - The base URL to test
+   * @param apiKey - The API key to use for testing
+   * @param providerId - Provider preset ID the latency sample is recorded under (defaults to baseUrl)
+   * @returns Promise resolving to test result message
    */
-  async openFileWithDefaultApp(filePath: string): Promise<void> {
+  async testCodexProviderConnection(baseUrl: string, apiKey?: string, providerId?: string): Promise<string> {
     try {
-      return await invoke<void>("open_file_with_default_app", { filePath });
+      return await invoke<string>("test_codex_provider_connection", { baseUrl, apiKey, providerId });
     } catch (error) {
-      console.error("Failed to open file with default app:", error);
+      console.error("Failed to test Codex provider connection:", error);
       throw error;
     }
   },
 
-  // ==================== Git Statistics ====================
-
   /**
-   * Get Git diff statistics between commits
+   * 获取 Codex 代理商备用链配置
    */
-  async getGitDiffStats(
-    projectPath: string,
-    fromCommit: string,
-    toCommit?: string
-  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
+  async getCodexFailoverConfig(): Promise<CodexFailoverConfig> {
     try {
-      return await invoke("get_git_diff_stats", { projectPath, fromCommit, toCommit });
+      return await invoke<CodexFailoverConfig>("get_codex_failover_config");
     } catch (error) {
-      console.error("Failed to get git diff stats:", error);
+      console.error("Failed to get Codex failover config:", error);
       throw error;
     }
   },
 
   /**
-   * Get code changes for current session
+   * 保存 Codex 代理商备用链配置
+   * @param config - 备用链（代理商预设 ID 需已存在且不重复）
    */
-  async getSessionCodeChanges(
-    projectPath: string,
-    sessionStartCommit: string
-  ): Promise<{ linesAdded: number; linesRemoved: number; filesChanged: number }> {
+  async setCodexFailoverConfig(config: CodexFailoverConfig): Promise<void> {
     try {
-      return await invoke("get_session_code_changes", { projectPath, sessionStartCommit });
+      return await invoke("set_codex_failover_config", { config });
     } catch (error) {
-      console.error("Failed to get session code changes:", error);
+      console.error("Failed to save Codex failover config:", error);
       throw error;
     }
   },
 
-  // ==================== OpenAI Codex Integration ====================
+  // ============================================================================
+  // CODEX PROVIDER MODE SWITCHING (Official vs Third-Party)
+  // ============================================================================
 
   /**
-   * Executes a Codex task in non-interactive mode with streaming output
-   * @param options - Codex execution options
-   * @returns Promise resolving when execution starts (events are streamed via event listeners)
+   * Gets current Codex provider mode status
+   * @returns Promise resolving to provider mode information
    */
-  async executeCodex(options: import('@/types/codex').CodexExecutionOptions): Promise<void> {
+  async getCodexProviderMode(): Promise<CodexProviderMode> {
     try {
-      return await invoke("execute_codex", { options });
+      return await invoke<CodexProviderMode>("get_codex_provider_mode");
     } catch (error) {
-      console.error("Failed to execute Codex:", error);
+      console.error("Failed to get Codex provider mode:", error);
       throw error;
     }
   },
 
   /**
-   * Resumes a previous Codex session
-   * @param sessionId - The session ID to resume
-   * @param options - Codex execution options (prompt, mode, etc.)
-   * @returns Promise resolving when execution starts
+   * Backup third-party auth.json
+   * @returns Promise resolving to success message
    */
-  async resumeCodex(
-    sessionId: string,
-    options: Omit<import('@/types/codex').CodexExecutionOptions, 'sessionId'>
-  ): Promise<void> {
+  async backupThirdPartyAuth(): Promise<string> {
     try {
-      return await invoke("resume_codex", { sessionId, options });
+      return await invoke<string>("backup_third_party_auth");
     } catch (error) {
-      console.error("Failed to resume Codex session:", error);
+      console.error("Failed to backup third-party auth:", error);
       throw error;
     }
   },
 
   /**
-   * Resumes the last Codex session
-   * @param options - Codex execution options
-   * @returns Promise resolving when execution starts
+   * Backup official auth.json (OAuth tokens)
+   * @returns Promise resolving to success message
    */
-  async resumeLastCodex(
-    options: Omit<import('@/types/codex').CodexExecutionOptions, 'resumeLast'>
-  ): Promise<void> {
+  async backupOfficialAuth(): Promise<string> {
     try {
-      return await invoke("resume_last_codex", { options });
+      return await invoke<string>("backup_official_auth");
     } catch (error) {
-      console.error("Failed to resume last Codex session:", error);
+      console.error("Failed to backup official auth:", error);
       throw error;
     }
   },
 
   /**
-   * Cancels a running Codex execution
-   * @param sessionId - Optional session ID to cancel a specific session
-   * @returns Promise resolving when cancellation is complete
+   * Restore third-party auth.json from backup
+   * @returns Promise resolving to success message
    */
-  async cancelCodex(sessionId?: string): Promise<void> {
+  async restoreThirdPartyAuth(): Promise<string> {
     try {
-      return await invoke("cancel_codex", { sessionId });
+      return await invoke<string>("restore_third_party_auth");
     } catch (error) {
-      console.error("Failed to cancel Codex execution:", error);
+      console.error("Failed to restore third-party auth:", error);
       throw error;
     }
   },
 
   /**
-   * Gets a list of all Codex sessions
-   * @returns Promise resolving to array of Codex sessions
+   * Restore official auth.json from backup
+   * @returns Promise resolving to success message
    */
-  async listCodexSessions(): Promise<import('@/types/codex').CodexSession[]> {
+  async restoreOfficialAuth(): Promise<string> {
     try {
-      return await invoke<import('@/types/codex').CodexSession[]>("list_codex_sessions");
+      return await invoke<string>("restore_official_auth");
     } catch (error) {
-      console.error("Failed to list Codex sessions:", error);
+      console.error("Failed to restore official auth:", error);
       throw error;
     }
   },
 
   /**
-   * Gets Codex sessions filtered by project path (optimized)
-   * @param projectPath - The project path to filter by
-   * @returns Promise resolving to array of Codex sessions for the project
+   * Switch to official OpenAI mode
+   * Backs up third-party auth, restores/clears official auth, comments out third-party config
+   * @returns Promise resolving to success message
    */
-  async listCodexSessionsForProject(projectPath: string): Promise<import('@/types/codex').CodexSession[]> {
+  async switchToOfficialMode(): Promise<string> {
     try {
-      return await invoke<import('@/types/codex').CodexSession[]>("list_codex_sessions_for_project", { projectPath });
+      return await invoke<string>("switch_to_official_mode");
     } catch (error) {
-      console.error("Failed to list Codex sessions for project:", error);
+      console.error("Failed to switch to official mode:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all Codex projects (grouped by project path)
-   * @returns Promise resolving to array of Codex projects
+   * Switch to third-party mode
+   * @param apiKey - Optional API key (uses backup if not provided)
+   * @param modelProvider - Optional model provider name
+   * @param model - Optional model name
+   * @param modelReasoningEffort - Optional reasoning effort level
+   * @returns Promise resolving to success message
    */
-  async listCodexProjects(): Promise<CodexProject[]> {
+  async switchToThirdPartyMode(
+    apiKey?: string,
+    modelProvider?: string,
+    model?: string,
+    modelReasoningEffort?: string
+  ): Promise<string> {
     try {
-      return await invoke<CodexProject[]>("list_codex_projects");
+      return await invoke<string>("switch_to_third_party_mode", {
+        apiKey,
+        modelProvider,
+        model,
+        modelReasoningEffort,
+      });
     } catch (error) {
-      console.error("Failed to list Codex projects:", error);
-      // Return empty array on error to allow graceful degradation
-      return [];
+      console.error("Failed to switch to third-party mode:", error);
+      throw error;
     }
   },
 
   /**
-   * Deletes a Codex session
-   * @param sessionId - The session ID to delete
+   * Open terminal for Codex authentication
    * @returns Promise resolving to success message
    */
-  async deleteCodexSession(sessionId: string): Promise<string> {
+  async openCodexAuthTerminal(): Promise<string> {
     try {
-      return await invoke<string>("delete_codex_session", { sessionId });
+      return await invoke<string>("open_codex_auth_terminal");
     } catch (error) {
-      console.error("Failed to delete Codex session:", error);
+      console.error("Failed to open Codex auth terminal:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Codex is available and properly configured
-   * @returns Promise resolving to availability status
+   * Check if Codex authentication is valid
+   * @returns Promise resolving to boolean indicating auth status
    */
-  async checkCodexAvailability(): Promise<{
-    available: boolean;
-    version?: string;
-    error?: string;
-  }> {
+  async checkCodexAuthStatus(): Promise<boolean> {
     try {
-      return await invoke("check_codex_availability");
+      return await invoke<boolean>("check_codex_auth_status");
     } catch (error) {
-      console.error("Failed to check Codex availability:", error);
-      return {
-        available: false,
-        error: error instanceof Error ? error.message : String(error)
-      };
+      console.error("Failed to check Codex auth status:", error);
+      throw error;
     }
   },
 
   // ============================================================================
-  // Codex Mode Configuration (WSL Support)
+  // GEMINI PROVIDER MANAGEMENT
   // ============================================================================
 
   /**
-   * Gets Codex mode configuration
-   * @returns Promise resolving to mode configuration info
+   * Gets the list of Gemini provider presets
+   * @returns Promise resolving to array of Gemini provider configurations
    */
-  async getCodexModeConfig(): Promise<{
-    mode: 'auto' | 'native' | 'wsl';
-    wslDistro: string | null;
-    actualMode: 'native' | 'wsl';
-    nativeAvailable: boolean;
-    wslAvailable: boolean;
-    availableDistros: string[];
-  }> {
+  async getGeminiProviderPresets(): Promise<GeminiProviderConfig[]> {
     try {
-      return await invoke("get_codex_mode_config");
+      return await invoke<GeminiProviderConfig[]>("get_gemini_provider_presets");
     } catch (error) {
-      console.error("Failed to get Codex mode config:", error);
+      console.error("Failed to get Gemini provider presets:", error);
       throw error;
     }
   },
 
   /**
-   * Sets Codex mode configuration
-   * @param mode - The mode to set: 'auto', 'native', or 'wsl'
-   * @param wslDistro - Optional WSL distro name
-   * @param customCodexPath - Optional custom Codex path
-   * @returns Promise resolving to success message
+   * Gets the current Gemini provider configuration from ~/.gemini directory
+   * @returns Promise resolving to current Gemini configuration
    */
-  async setCodexModeConfig(
-    mode: 'auto' | 'native' | 'wsl',
-    wslDistro?: string | null,
-    customCodexPath?: string | null
-  ): Promise<string> {
+  async getCurrentGeminiProviderConfig(): Promise<CurrentGeminiProviderConfig> {
     try {
-      return await invoke<string>("set_codex_mode_config", {
-        mode,
-        wslDistro: wslDistro || null,
-        customCodexPath: customCodexPath || null
-      });
+      return await invoke<CurrentGeminiProviderConfig>("get_current_gemini_provider_config");
     } catch (error) {
-      console.error("Failed to set Codex mode config:", error);
+      console.error("Failed to get current Gemini provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Sets custom Codex CLI path
-   * @param path - Path to custom Codex CLI executable (null to clear)
+   * Switches to a Gemini provider configuration
+   * Writes env to ~/.gemini/.env and updates settings.json
+   * @param config - The Gemini provider configuration to switch to
    * @returns Promise resolving to success message
    */
-  async setCodexCustomPath(path: string | null): Promise<string> {
+  async switchGeminiProvider(config: GeminiProviderConfig): Promise<string> {
     try {
-      return await invoke<string>("set_codex_custom_path", { path });
+      return await invoke<string>("switch_gemini_provider", { config });
     } catch (error) {
-      console.error("Failed to set custom Codex path:", error);
+      console.error("Failed to switch Gemini provider:", error);
       throw error;
     }
   },
 
   /**
-   * Validates a Codex path
-   * @param path - Path to validate
-   * @returns Promise resolving to whether the path is valid
-   */
-  async validateCodexPath(path: string): Promise<boolean> {
-    try {
-      return await invoke<boolean>("validate_codex_path_cmd", { path });
-    } catch (error) {
-      console.error("Failed to validate Codex path:", error);
-      return false;
-    }
-  },
-
-  /**
-   * Scans for all possible Codex installation paths
-   * @returns Promise resolving to array of found paths
+   * Adds a new Gemini provider configuration
+   * @param config - The Gemini provider configuration to add
+   * @returns Promise resolving to success message
    */
-  async scanCodexPaths(): Promise<string[]> {
-    try {
-      return await invoke<string[]>("scan_codex_paths");
-    } catch (error) {
-      console.error("Failed to scan Codex paths:", error);
-      return [];
-    }
-  },
+  async addGeminiProviderConfig(config: Omit<GeminiProviderConfig, 'id'>): Promise<string> {
+    // Generate ID from name
+    const id = config.name
+      .toLowerCase()
+      .replace(/[^a-z0-9]/g, '-')
+      .replace(/-+/g, '-')
+      .replace(/^-|-$/g, '');
 
-  // ============================================================================
-  // Codex Rewind Commands
-  // ============================================================================
+    const fullConfig: GeminiProviderConfig = {
+      ...config,
+      id,
+      createdAt: Date.now(),
+    };
 
-  /**
-   * Records a Codex prompt being sent (called before execution)
-   * @param sessionId - The Codex session ID
-   * @param projectPath - The project path
-   * @param promptText - The prompt text
-   * @returns Promise resolving to the prompt index
-   */
-  async recordCodexPromptSent(
-    sessionId: string,
-    projectPath: string,
-    promptText: string
-  ): Promise<number> {
     try {
-      return await invoke<number>("record_codex_prompt_sent", {
-        sessionId,
-        projectPath,
-        promptText
-      });
+      return await invoke<string>("add_gemini_provider_config", { config: fullConfig });
     } catch (error) {
-      console.error("Failed to record Codex prompt sent:", error);
+      console.error("Failed to add Gemini provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Records a Codex prompt completion (called after AI response)
-   * @param sessionId - The Codex session ID
-   * @param projectPath - The project path
-   * @param promptIndex - The prompt index to complete
+   * Updates an existing Gemini provider configuration
+   * @param config - The Gemini provider configuration to update (with id)
+   * @returns Promise resolving to success message
    */
-  async recordCodexPromptCompleted(
-    sessionId: string,
-    projectPath: string,
-    promptIndex: number
-  ): Promise<void> {
+  async updateGeminiProviderConfig(config: GeminiProviderConfig): Promise<string> {
     try {
-      await invoke("record_codex_prompt_completed", {
-        sessionId,
-        projectPath,
-        promptIndex
-      });
+      return await invoke<string>("update_gemini_provider_config", { config });
     } catch (error) {
-      console.error("Failed to record Codex prompt completed:", error);
+      console.error("Failed to update Gemini provider config:", error);
       throw error;
     }
   },
 
   /**
-   * Gets Codex prompt list for a session (used by revert picker)
+   * Deletes a Gemini provider configuration by ID
+   * @param id - The ID of the Gemini provider configuration to delete
+   * @returns Promise resolving to success message
    */
-  async getCodexPromptList(sessionId: string): Promise<PromptRecord[]> {
+  async deleteGeminiProviderConfig(id: string): Promise<string> {
     try {
-      return await invoke<PromptRecord[]>("get_codex_prompt_list", { sessionId });
+      return await invoke<string>("delete_gemini_provider_config", { id });
     } catch (error) {
-      console.error("Failed to get Codex prompt list:", error);
-      return [];
+      console.error("Failed to delete Gemini provider config:", error);
+      throw error;
     }
   },
 
   /**
-   * Checks rewind capabilities for a Codex prompt
-   * @param sessionId - Codex session ID
-   * @param promptIndex - Prompt index to check
+   * Clears Gemini provider configuration (resets to official OAuth)
+   * Clears .env and sets auth type to oauth-personal
+   * @returns Promise resolving to success message
    */
-  async checkCodexRewindCapabilities(
-    sessionId: string,
-    promptIndex: number
-  ): Promise<RewindCapabilities> {
+  async clearGeminiProviderConfig(): Promise<string> {
     try {
-      return await invoke<RewindCapabilities>("check_codex_rewind_capabilities", {
-        sessionId,
-        promptIndex,
-      });
+      return await invoke<string>("clear_gemini_provider_config");
     } catch (error) {
-      console.error("Failed to check Codex rewind capabilities:", error);
-      // Fallback to conversation-only to keep UI functional
-      return {
-        conversation: true,
-        code: false,
-        both: false,
-        warning: "无法获取 Codex 撤回能力，只能删除对话记录。",
-        source: "cli",
-      };
+      console.error("Failed to clear Gemini provider config:", error);
+      throw error;
     }
   },
 
   /**
-   * Reverts a Codex session to a specific prompt
-   * @param sessionId - The Codex session ID
-   * @param projectPath - The project path
-   * @param promptIndex - The prompt index to revert to
-   * @param mode - The rewind mode (conversation_only, code_only, or both)
-   * @returns Promise resolving to the prompt text (for restoring to input)
+   * Tests Gemini provider connection
+   * @param baseUrl - The base URL to test
+   * @param apiKey - The API key to use for testing
+   * @param providerId - Provider preset ID the latency sample is recorded under (defaults to baseUrl)
+   * @returns Promise resolving to test result message
    */
-  async revertCodexToPrompt(
-    sessionId: string,
-    projectPath: string,
-    promptIndex: number,
-    mode: RewindMode = "both"
-  ): Promise<string> {
+  async testGeminiProviderConnection(baseUrl: string, apiKey?: string, providerId?: string): Promise<string> {
     try {
-      return await invoke<string>("revert_codex_to_prompt", {
-        sessionId,
-        projectPath,
-        promptIndex,
-        mode
-      });
+      return await invoke<string>("test_gemini_provider_connection", { baseUrl, apiKey, providerId });
     } catch (error) {
-      console.error("Failed to revert Codex to prompt:", error);
+      console.error("Failed to test Gemini provider connection:", error);
       throw error;
     }
   },
 
   // ============================================================================
-  // Gemini Rewind Commands
+  // Session Conversion (Claude ↔ Codex)
   // ============================================================================
 
   /**
-   * Records a Gemini prompt being sent (called before execution)
-   * @param sessionId - The Gemini session ID
+   * Convert a session between Claude and Codex formats
+   * @param sessionId - The source session ID
+   * @param targetEngine - The target engine ('claude' | 'codex')
+   * @param projectId - The project ID (directory name)
    * @param projectPath - The project path
-   * @param promptText - The prompt text
-   * @returns Promise resolving to the prompt index
+   * @returns Promise resolving to conversion result
    */
-  async recordGeminiPromptSent(
+  async convertSession(
     sessionId: string,
-    projectPath: string,
-    promptText: string
-  ): Promise<number> {
+    targetEngine: 'claude' | 'codex',
+    projectId: string,
+    projectPath: string
+  ): Promise<ConversionResult> {
     try {
-      return await invoke<number>("record_gemini_prompt_sent", {
+      return await invoke<ConversionResult>("convert_session", {
         sessionId,
+        targetEngine,
+        projectId,
         projectPath,
-        promptText
       });
     } catch (error) {
-      console.error("Failed to record Gemini prompt sent:", error);
+      console.error("Failed to convert session:", error);
       throw error;
     }
   },
 
   /**
-   * Records a Gemini prompt completion (called after AI response)
-   * @param sessionId - The Gemini session ID
+   * Convert a Claude session to Codex format
+   * @param sessionId - The Claude session ID (UUID format)
+   * @param projectId - The project ID (directory name)
    * @param projectPath - The project path
-   * @param promptIndex - The prompt index to complete
+   * @returns Promise resolving to conversion result
    */
-  async recordGeminiPromptCompleted(
+  async convertClaudeToCodex(
     sessionId: string,
-    projectPath: string,
-    promptIndex: number
-  ): Promise<void> {
+    projectId: string,
+    projectPath: string
+  ): Promise<ConversionResult> {
     try {
-      await invoke("record_gemini_prompt_completed", {
+      return await invoke<ConversionResult>("convert_claude_to_codex", {
         sessionId,
+        projectId,
         projectPath,
-        promptIndex
       });
     } catch (error) {
-      console.error("Failed to record Gemini prompt completed:", error);
+      console.error("Failed to convert Claude to Codex:", error);
       throw error;
     }
   },
 
   /**
-   * Gets Gemini prompt list for a session (used by revert picker)
-   */
-  async getGeminiPromptList(sessionId: string, projectPath: string): Promise<PromptRecord[]> {
-    try {
-      return await invoke<PromptRecord[]>("get_gemini_prompt_list", { sessionId, projectPath });
-    } catch (error) {
-      console.error("Failed to get Gemini prompt list:", error);
-      return [];
-    }
-  },
-
-  /**
-   * Checks rewind capabilities for a Gemini prompt
-   * @param sessionId - Gemini session ID
+   * Convert a Codex session to Claude format
+   * @param sessionId - The Codex session ID (rollout-* format)
+   * @param projectId - The project ID (directory name)
    * @param projectPath - The project path
-   * @param promptIndex - Prompt index to check
+   * @returns Promise resolving to conversion result
    */
-  async checkGeminiRewindCapabilities(
+  async convertCodexToClaude(
     sessionId: string,
-    projectPath: string,
-    promptIndex: number
-  ): Promise<RewindCapabilities> {
+    projectId: string,
+    projectPath: string
+  ): Promise<ConversionResult> {
     try {
-      return await invoke<RewindCapabilities>("check_gemini_rewind_capabilities", {
+      return await invoke<ConversionResult>("convert_codex_to_claude", {
         sessionId,
+        projectId,
         projectPath,
-        promptIndex,
       });
     } catch (error) {
-      console.error("Failed to check Gemini rewind capabilities:", error);
-      // Fallback to conversation-only to keep UI functional
-      return {
-        conversation: true,
-        code: false,
-        both: false,
-        warning: "无法获取 Gemini 撤回能力，只能删除对话记录。",
-        source: "project",
-      };
+      console.error("Failed to convert Codex to Claude:", error);
+      throw error;
     }
   },
 
+  // ==================== Google Gemini CLI Integration ====================
+
   /**
-   * Reverts a Gemini session to a specific prompt
-   * @param sessionId - The Gemini session ID
-   * @param projectPath - The project path
-   * @param promptIndex - The prompt index to revert to
-   * @param mode - The rewind mode (conversation_only, code_only, or both)
-   * @returns Promise resolving to success message
+   * Executes a Gemini CLI session with streaming output
+   * @param options - Gemini execution options
+   * @returns Promise resolving when execution starts (events are streamed via event listeners)
    */
-  async revertGeminiToPrompt(
-    sessionId: string,
-    projectPath: string,
-    promptIndex: number,
-    mode: RewindMode = "both"
-  ): Promise<string> {
+  async executeGemini(options: import('@/types/gemini').GeminiExecutionOptions): Promise<void> {
     try {
-      return await invoke<string>("revert_gemini_to_prompt", {
-        sessionId,
-        projectPath,
-        promptIndex,
-        mode
-      });
+      return await invoke("execute_gemini", { options });
     } catch (error) {
-      console.error("Failed to revert Gemini to prompt:", error);
+      console.error("Failed to execute Gemini:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // CODEX CONFIG.TOML FILE PROVIDERS (AnyCode)
-  // ============================================================================
-
   /**
-   * Reads current ~/.codex/config.toml as text (WSL-aware on Windows)
+   * Cancels a running Gemini execution
+   * @param sessionId - Optional session ID to cancel (cancels all if not provided)
    */
-  async readCodexConfigToml(): Promise<string> {
+  async cancelGemini(sessionId?: string): Promise<void> {
     try {
-      return await invoke<string>("read_codex_config_toml");
+      await invoke("cancel_gemini", { sessionId });
     } catch (error) {
-      console.error("Failed to read Codex config.toml:", error);
+      console.error("Failed to cancel Gemini:", error);
       throw error;
     }
   },
 
   /**
-   * Writes ~/.codex/config.toml as text (WSL-aware on Windows)
+   * Checks if Gemini CLI is installed
+   * @returns Promise resolving to installation status
    */
-  async writeCodexConfigToml(content: string): Promise<string> {
+  async checkGeminiInstalled(): Promise<import('@/types/gemini').GeminiInstallStatus> {
     try {
-      return await invoke<string>("write_codex_config_toml", { content });
+      return await invoke("check_gemini_installed");
     } catch (error) {
-      console.error("Failed to write Codex config.toml:", error);
-      throw error;
+      console.error("Failed to check Gemini installation:", error);
+      return {
+        installed: false,
+        error: String(error),
+      };
     }
   },
 
   /**
-   * Reads current ~/.codex/auth.json as text (WSL-aware on Windows)
+   * Gets Gemini CLI configuration
+   * @returns Promise resolving to Gemini configuration
    */
-  async readCodexAuthJsonText(): Promise<string> {
+  async getGeminiConfig(): Promise<import('@/types/gemini').GeminiConfig> {
     try {
-      return await invoke<string>("read_codex_auth_json_text");
+      return await invoke("get_gemini_config");
     } catch (error) {
-      console.error("Failed to read Codex auth.json:", error);
+      console.error("Failed to get Gemini config:", error);
       throw error;
     }
   },
 
   /**
-   * Writes ~/.codex/auth.json as text (WSL-aware on Windows)
+   * Updates Gemini CLI configuration
+   * @param config - New configuration to apply
    */
-  async writeCodexAuthJsonText(content: string): Promise<string> {
+  async updateGeminiConfig(config: import('@/types/gemini').GeminiConfig): Promise<void> {
     try {
-      return await invoke<string>("write_codex_auth_json_text", { content });
+      await invoke("update_gemini_config", { config });
     } catch (error) {
-      console.error("Failed to write Codex auth.json:", error);
+      console.error("Failed to update Gemini config:", error);
       throw error;
     }
   },
 
   /**
-   * Writes both ~/.codex/config.toml and ~/.codex/auth.json (WSL-aware on Windows)
+   * Gets available Gemini models
+   * @returns Promise resolving to array of model information
    */
-  async writeCodexConfigFiles(configToml: string, authJson: string): Promise<string> {
+  async getGeminiModels(): Promise<import('@/types/gemini').GeminiModelInfo[]> {
     try {
-      return await invoke<string>("write_codex_config_files", { configToml, authJson });
+      return await invoke("get_gemini_models");
     } catch (error) {
-      console.error("Failed to write Codex config files:", error);
+      console.error("Failed to get Gemini models:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // Gemini Session History
+  // ============================================================================
+
   /**
-   * Gets list of saved Codex config.toml presets
+   * Gets session logs for a project (from logs.json)
+   * @param projectPath - Project path to get session logs for
+   * @returns Promise resolving to array of session logs
    */
-  async getCodexConfigFileProviders(): Promise<CodexConfigFileProvider[]> {
+  async getGeminiSessionLogs(projectPath: string): Promise<import('@/types/gemini').GeminiSessionLog[]> {
     try {
-      return await invoke<CodexConfigFileProvider[]>("get_codex_config_file_providers");
+      return await invoke("get_gemini_session_logs", { projectPath });
     } catch (error) {
-      console.error("Failed to get Codex config.toml presets:", error);
+      console.error("Failed to get Gemini session logs:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a Codex config.toml preset
+   * Lists all sessions for a project (from chats/ directory)
+   * @param projectPath - Project path to list sessions for
+   * @returns Promise resolving to array of session info
    */
-  async addCodexConfigFileProvider(config: Omit<CodexConfigFileProvider, 'id'>): Promise<string> {
-    const id = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
-
-    const fullConfig: CodexConfigFileProvider = {
-      ...config,
-      id,
-      createdAt: Date.now(),
-    };
-
+  async listGeminiSessions(projectPath: string): Promise<import('@/types/gemini').GeminiSessionInfo[]> {
     try {
-      return await invoke<string>("add_codex_config_file_provider", { config: fullConfig });
+      return await invoke("list_gemini_sessions", { projectPath });
     } catch (error) {
-      console.error("Failed to add Codex config.toml preset:", error);
+      console.error("Failed to list Gemini sessions:", error);
       throw error;
     }
   },
 
   /**
-   * Updates a Codex config.toml preset
+   * Gets detailed session information
+   * @param projectPath - Project path
+   * @param sessionId - Session ID to get details for
+   * @returns Promise resolving to complete session detail
    */
-  async updateCodexConfigFileProvider(config: CodexConfigFileProvider): Promise<string> {
+  async getGeminiSessionDetail(
+    projectPath: string,
+    sessionId: string
+  ): Promise<import('@/types/gemini').GeminiSessionDetail> {
     try {
-      return await invoke<string>("update_codex_config_file_provider", { config });
+      return await invoke("get_gemini_session_detail", { projectPath, sessionId });
     } catch (error) {
-      console.error("Failed to update Codex config.toml preset:", error);
+      console.error("Failed to get Gemini session detail:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a Codex config.toml preset
+   * Delete a Gemini session
+   * @param projectPath - Project path
+   * @param sessionId - Session ID to delete
    */
-  async deleteCodexConfigFileProvider(id: string): Promise<string> {
+  async deleteGeminiSession(projectPath: string, sessionId: string): Promise<void> {
     try {
-      return await invoke<string>("delete_codex_config_file_provider", { id });
+      await invoke("delete_gemini_session", { projectPath, sessionId });
     } catch (error) {
-      console.error("Failed to delete Codex config.toml preset:", error);
+      console.error("Failed to delete Gemini session:", error);
       throw error;
     }
   },
 
   // ============================================================================
-  // CODEX PROVIDER MANAGEMENT
+  // CODEX MULTI-PROMPT MANAGEMENT
   // ============================================================================
 
   /**
-   * Gets the list of Codex provider presets
-   * @returns Promise resolving to array of Codex provider configurations
+   * Lists all Codex prompt templates
+   * @returns Promise resolving to array of prompt templates
    */
-  async getCodexProviderPresets(): Promise<CodexProviderConfig[]> {
+  async listCodexPrompts(): Promise<CodexPromptTemplate[]> {
     try {
-      return await invoke<CodexProviderConfig[]>("get_codex_provider_presets");
+      return await invoke<CodexPromptTemplate[]>("list_codex_prompts");
     } catch (error) {
-      console.error("Failed to get Codex provider presets:", error);
+      console.error("Failed to list Codex prompts:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the current Codex provider configuration from ~/.codex directory
-   * @returns Promise resolving to current Codex configuration
+   * Gets a specific Codex prompt template content
+   * @param id - The prompt template ID
+   * @returns Promise resolving to prompt content
    */
-  async getCurrentCodexConfig(): Promise<CurrentCodexConfig> {
+  async getCodexPrompt(id: string): Promise<string> {
     try {
-      return await invoke<CurrentCodexConfig>("get_current_codex_config");
+      return await invoke<string>("get_codex_prompt", { id });
     } catch (error) {
-      console.error("Failed to get current Codex config:", error);
+      console.error("Failed to get Codex prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Switches to a Codex provider configuration
-   * Writes auth.json and config.toml to ~/.codex directory
-   * @param config - The Codex provider configuration to switch to
+   * Creates or updates a Codex prompt template
+   * @param id - The prompt template ID
+   * @param content - The prompt content
    * @returns Promise resolving to success message
    */
-  async switchCodexProvider(config: CodexProviderConfig): Promise<string> {
+  async saveCodexPrompt(id: string, content: string): Promise<string> {
     try {
-      return await invoke<string>("switch_codex_provider", { config });
+      return await invoke<string>("save_codex_prompt", { id, content });
     } catch (error) {
-      console.error("Failed to switch Codex provider:", error);
+      console.error("Failed to save Codex prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a new Codex provider configuration
-   * @param config - The Codex provider configuration to add
+   * Renames a Codex prompt template (changes template ID / filename)
+   * @param oldId - The old prompt template ID
+   * @param newId - The new prompt template ID
    * @returns Promise resolving to success message
    */
-  async addCodexProviderConfig(config: Omit<CodexProviderConfig, 'id'>): Promise<string> {
-    // Generate ID from name
-    const id = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
-
-    const fullConfig: CodexProviderConfig = {
-      ...config,
-      id,
-      createdAt: Date.now(),
-    };
-
+  async renameCodexPrompt(oldId: string, newId: string): Promise<string> {
     try {
-      return await invoke<string>("add_codex_provider_config", { config: fullConfig });
+      return await invoke<string>("rename_codex_prompt", { oldId, newId });
     } catch (error) {
-      console.error("Failed to add Codex provider config:", error);
+      console.error("Failed to rename Codex prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an existing Codex provider configuration
-   * @param config - The Codex provider configuration to update (with id)
+   * Deletes a Codex prompt template
+   * @param id - The prompt template ID
    * @returns Promise resolving to success message
    */
-  async updateCodexProviderConfig(config: CodexProviderConfig): Promise<string> {
+  async deleteCodexPrompt(id: string): Promise<string> {
     try {
-      return await invoke<string>("update_codex_provider_config", { config });
+      return await invoke<string>("delete_codex_prompt", { id });
     } catch (error) {
-      console.error("Failed to update Codex provider config:", error);
+      console.error("Failed to delete Codex prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a Codex provider configuration by ID
-   * @param id - The ID of the Codex provider configuration to delete
+   * Activates a Codex prompt template (copies it to AGENTS.md)
+   * @param id - The prompt template ID to activate
    * @returns Promise resolving to success message
    */
-  async deleteCodexProviderConfig(id: string): Promise<string> {
+  async activateCodexPrompt(id: string): Promise<string> {
     try {
-      return await invoke<string>("delete_codex_provider_config", { id });
+      return await invoke<string>("activate_codex_prompt", { id });
     } catch (error) {
-      console.error("Failed to delete Codex provider config:", error);
+      console.error("Failed to activate Codex prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Clears Codex provider configuration (resets to official)
-   * Removes auth.json and config.toml from ~/.codex directory
+   * Deactivates the current Codex prompt (clears AGENTS.md)
    * @returns Promise resolving to success message
    */
-  async clearCodexProviderConfig(): Promise<string> {
+  async deactivateCodexPrompt(): Promise<string> {
     try {
-      return await invoke<string>("clear_codex_provider_config");
+      return await invoke<string>("deactivate_codex_prompt");
     } catch (error) {
-      console.error("Failed to clear Codex provider config:", error);
+      console.error("Failed to deactivate Codex prompt:", error);
       throw error;
     }
   },
 
   /**
-   * Tests Codex provider connection
-   * @param baseUrl - The base URL to test
-   * @param apiKey - The API key to use for testing
-   * @returns Promise resolving to test result message
+   * Gets the currently active prompt ID
+   * @returns Promise resolving to active prompt ID or null
    */
-  async testCodexProviderConnection(baseUrl: string, apiKey?: string): Promise<string> {
+  async getActiveCodexPromptId(): Promise<string | null> {
     try {
-      return await invoke<string>("test_codex_provider_connection", { baseUrl, apiKey });
+      return await invoke<string | null>("get_active_codex_prompt_id");
     } catch (error) {
-      console.error("Failed to test Codex provider connection:", error);
-      throw error;
+      console.error("Failed to get active Codex prompt ID:", error);
+      return null;
     }
   },
 
   // ============================================================================
-  // CODEX PROVIDER MODE SWITCHING (Official vs Third-Party)
+  // PROJECT-LEVEL AGENTS.MD MANAGEMENT
   // ============================================================================
 
   /**
-   * Gets current Codex provider mode status
-   * @returns Promise resolving to provider mode information
+   * Checks if AGENTS.md exists in the project directory
+   * @param projectPath - The project directory path
+   * @returns Promise resolving to AGENTS.md status
    */
-  async getCodexProviderMode(): Promise<CodexProviderMode> {
+  async checkProjectAgentsMd(projectPath: string): Promise<AgentsMdStatus> {
     try {
-      return await invoke<CodexProviderMode>("get_codex_provider_mode");
+      return await invoke<AgentsMdStatus>("check_project_agents_md", { projectPath });
     } catch (error) {
-      console.error("Failed to get Codex provider mode:", error);
+      console.error("Failed to check project AGENTS.md:", error);
       throw error;
     }
   },
 
   /**
-   * Backup third-party auth.json
-   * @returns Promise resolving to success message
+   * Activates a Codex prompt template to a project directory
+   * @param id - The prompt template ID to activate
+   * @param projectPath - The project directory path
+   * @param backupExisting - Whether to backup existing AGENTS.md
+   * @returns Promise resolving to activation result
    */
-  async backupThirdPartyAuth(): Promise<string> {
+  async activateCodexPromptToProject(id: string, projectPath: string, backupExisting: boolean): Promise<ActivationResult> {
     try {
-      return await invoke<string>("backup_third_party_auth");
+      return await invoke<ActivationResult>("activate_codex_prompt_to_project", { id, projectPath, backupExisting });
     } catch (error) {
-      console.error("Failed to backup third-party auth:", error);
+      console.error("Failed to activate Codex prompt to project:", error);
       throw error;
     }
   },
 
   /**
-   * Backup official auth.json (OAuth tokens)
+   * Deactivates Codex prompt from a project directory
+   * @param projectPath - The project directory path
+   * @param restoreBackup - Whether to restore backup file
    * @returns Promise resolving to success message
    */
-  async backupOfficialAuth(): Promise<string> {
+  async deactivateCodexPromptFromProject(projectPath: string, restoreBackup: boolean): Promise<string> {
     try {
-      return await invoke<string>("backup_official_auth");
+      return await invoke<string>("deactivate_codex_prompt_from_project", { projectPath, restoreBackup });
     } catch (error) {
-      console.error("Failed to backup official auth:", error);
+      console.error("Failed to deactivate Codex prompt from project:", error);
       throw error;
     }
   },
 
+  // ============================================================================
+  // Claude Sub-agents API
+  // ============================================================================
+
   /**
-   * Restore third-party auth.json from backup
-   * @returns Promise resolving to success message
+   * 列出 Claude 子代理
+   * @param projectPath - 传入时同时列出项目级子代理，并标记用户级子代理是否已在项目中启用
    */
-  async restoreThirdPartyAuth(): Promise<string> {
+  async listClaudeAgents(projectPath?: string): Promise<ClaudeAgent[]> {
     try {
-      return await invoke<string>("restore_third_party_auth");
+      return await invoke<ClaudeAgent[]>("list_claude_agents", { projectPath });
     } catch (error) {
-      console.error("Failed to restore third-party auth:", error);
+      console.error("Failed to list Claude agents:", error);
       throw error;
     }
   },
 
   /**
-   * Restore official auth.json from backup
-   * @returns Promise resolving to success message
+   * 校验子代理文件内容（frontmatter）
    */
-  async restoreOfficialAuth(): Promise<string> {
+  async validateClaudeAgent(content: string): Promise<ClaudeAgentValidation> {
     try {
-      return await invoke<string>("restore_official_auth");
+      return await invoke<ClaudeAgentValidation>("validate_claude_agent", { content });
     } catch (error) {
-      console.error("Failed to restore official auth:", error);
+      console.error("Failed to validate Claude agent:", error);
       throw error;
     }
   },
 
   /**
-   * Switch to official OpenAI mode
-   * Backs up third-party auth, restores/clears official auth, comments out third-party config
-   * @returns Promise resolving to success message
+   * 新建或更新子代理（文件名取自 frontmatter 的 name）
+   * @param content - 完整文件内容
+   * @param projectPath - 为空时保存为用户级子代理
+   * @param previousName - 编辑已有子代理时的原名称（名称变化时重命名）
    */
-  async switchToOfficialMode(): Promise<string> {
+  async saveClaudeAgent(content: string, projectPath?: string, previousName?: string): Promise<ClaudeAgent> {
     try {
-      return await invoke<string>("switch_to_official_mode");
+      return await invoke<ClaudeAgent>("save_claude_agent", { content, projectPath, previousName });
     } catch (error) {
-      console.error("Failed to switch to official mode:", error);
+      console.error("Failed to save Claude agent:", error);
       throw error;
     }
   },
 
-  /**
-   * Switch to third-party mode
-   * @param apiKey - Optional API key (uses backup if not provided)
-   * @param modelProvider - Optional model provider name
-   * @param model - Optional model name
-   * @param modelReasoningEffort - Optional reasoning effort level
-   * @returns Promise resolving to success message
-   */
-  async switchToThirdPartyMode(
-    apiKey?: string,
-    modelProvider?: string,
-    model?: string,
-    modelReasoningEffort?: string
-  ): Promise<string> {
-    try {
-      return await invoke<string>("switch_to_third_party_mode", {
-        apiKey,
-        modelProvider,
-        model,
-        modelReasoningEffort,
-      });
+  /**
+   * 删除子代理
+   */
+  async deleteClaudeAgent(name: string, projectPath?: string): Promise<string> {
+    try {
+      return await invoke<string>("delete_claude_agent", { name, projectPath });
     } catch (error) {
-      console.error("Failed to switch to third-party mode:", error);
+      console.error("Failed to delete Claude agent:", error);
       throw error;
     }
   },
 
   /**
-   * Open terminal for Codex authentication
-   * @returns Promise resolving to success message
+   * 在项目中启用用户级子代理
+   * @param overwrite - 项目中已有内容不同的同名子代理时是否覆盖
    */
-  async openCodexAuthTerminal(): Promise<string> {
+  async activateClaudeAgentToProject(name: string, projectPath: string, overwrite?: boolean): Promise<string> {
     try {
-      return await invoke<string>("open_codex_auth_terminal");
+      return await invoke<string>("activate_claude_agent_to_project", { name, projectPath, overwrite });
     } catch (error) {
-      console.error("Failed to open Codex auth terminal:", error);
+      console.error("Failed to activate Claude agent to project:", error);
       throw error;
     }
   },
 
   /**
-   * Check if Codex authentication is valid
-   * @returns Promise resolving to boolean indicating auth status
+   * 在项目中停用子代理
    */
-  async checkCodexAuthStatus(): Promise<boolean> {
+  async deactivateClaudeAgentFromProject(name: string, projectPath: string): Promise<string> {
     try {
-      return await invoke<boolean>("check_codex_auth_status");
+      return await invoke<string>("deactivate_claude_agent_from_project", { name, projectPath });
     } catch (error) {
-      console.error("Failed to check Codex auth status:", error);
+      console.error("Failed to deactivate Claude agent from project:", error);
       throw error;
     }
   },
 
   // ============================================================================
-  // GEMINI PROVIDER MANAGEMENT
+  // Codex Model and Reasoning Mode Selector API
   // ============================================================================
 
   /**
-   * Gets the list of Gemini provider presets
-   * @returns Promise resolving to array of Gemini provider configurations
+   * Gets the current Codex selection configuration
+   * @returns Promise resolving to selection config or null if not set
    */
-  async getGeminiProviderPresets(): Promise<GeminiProviderConfig[]> {
+  async getCodexSelectionConfig(): Promise<import('@/types/codex-selector').CodexSelectionConfig | null> {
     try {
-      return await invoke<GeminiProviderConfig[]>("get_gemini_provider_presets");
+      return await invoke<import('@/types/codex-selector').CodexSelectionConfig | null>("get_codex_selection_config");
     } catch (error) {
-      console.error("Failed to get Gemini provider presets:", error);
-      throw error;
+      console.error("Failed to get Codex selection config:", error);
+      return null;
     }
   },
 
   /**
-   * Gets the current Gemini provider configuration from ~/.gemini directory
-   * @returns Promise resolving to current Gemini configuration
+   * Saves the Codex selection configuration
+   * @param config - The selection configuration to save
+   * @returns Promise resolving when config is saved
    */
-  async getCurrentGeminiProviderConfig(): Promise<CurrentGeminiProviderConfig> {
+  async saveCodexSelectionConfig(config: import('@/types/codex-selector').CodexSelectionConfig): Promise<void> {
     try {
-      return await invoke<CurrentGeminiProviderConfig>("get_current_gemini_provider_config");
+      return await invoke("save_codex_selection_config", { config });
     } catch (error) {
-      console.error("Failed to get current Gemini provider config:", error);
+      console.error("Failed to save Codex selection config:", error);
       throw error;
     }
   },
 
   /**
-   * Switches to a Gemini provider configuration
-   * Writes env to ~/.gemini/.env and updates settings.json
-   * @param config - The Gemini provider configuration to switch to
-   * @returns Promise resolving to success message
+   * Gets the default Codex selection configuration
+   * @returns Promise resolving to default selection config
    */
-  async switchGeminiProvider(config: GeminiProviderConfig): Promise<string> {
+  async getDefaultCodexSelectionConfig(): Promise<import('@/types/codex-selector').CodexSelectionConfig> {
     try {
-      return await invoke<string>("switch_gemini_provider", { config });
+      return await invoke<import('@/types/codex-selector').CodexSelectionConfig>("get_default_codex_selection_config");
     } catch (error) {
-      console.error("Failed to switch Gemini provider:", error);
+      console.error("Failed to get default Codex selection config:", error);
       throw error;
     }
   },
 
   /**
-   * Adds a new Gemini provider configuration
-   * @param config - The Gemini provider configuration to add
-   * @returns Promise resolving to success message
+   * Gets available reasoning modes from Codex CLI
+   * @returns Promise resolving to array of reasoning mode options
    */
-  async addGeminiProviderConfig(config: Omit<GeminiProviderConfig, 'id'>): Promise<string> {
-    // Generate ID from name
-    const id = config.name
-      .toLowerCase()
-      .replace(/[^a-z0-9]/g, '-')
-      .replace(/-+/g, '-')
-      .replace(/^-|-$/g, '');
-
-    const fullConfig: GeminiProviderConfig = {
-      ...config,
-      id,
-      createdAt: Date.now(),
-    };
-
+  async getAvailableReasoningModes(): Promise<import('@/types/codex-selector').ReasoningModeOption[]> {
     try {
-      return await invoke<string>("add_gemini_provider_config", { config: fullConfig });
+      return await invoke<import('@/types/codex-selector').ReasoningModeOption[]>("get_available_reasoning_modes");
     } catch (error) {
-      console.error("Failed to add Gemini provider config:", error);
+      console.error("Failed to get available reasoning modes:", error);
       throw error;
     }
   },
 
   /**
-   * Updates an existing Gemini provider configuration
-   * @param config - The Gemini provider configuration to update (with id)
-   * @returns Promise resolving to success message
+   * Gets available Codex models from Codex CLI
+   * @returns Promise resolving to array of model options
    */
-  async updateGeminiProviderConfig(config: GeminiProviderConfig): Promise<string> {
+  async getAvailableCodexModels(): Promise<import('@/types/codex-selector').CodexModelOption[]> {
     try {
-      return await invoke<string>("update_gemini_provider_config", { config });
+      return await invoke<import('@/types/codex-selector').CodexModelOption[]>("get_available_codex_models");
     } catch (error) {
-      console.error("Failed to update Gemini provider config:", error);
+      console.error("Failed to get available Codex models:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a Gemini provider configuration by ID
-   * @param id - The ID of the Gemini provider configuration to delete
-   * @returns Promise resolving to success message
+   * Refreshes Codex capabilities (models and reasoning modes)
+   * @returns Promise resolving to updated capabilities
    */
-  async deleteGeminiProviderConfig(id: string): Promise<string> {
+  async refreshCodexCapabilities(): Promise<import('@/types/codex-selector').CodexCapabilities> {
     try {
-      return await invoke<string>("delete_gemini_provider_config", { id });
+      return await invoke<import('@/types/codex-selector').CodexCapabilities>("refresh_codex_capabilities");
     } catch (error) {
-      console.error("Failed to delete Gemini provider config:", error);
+      console.error("Failed to refresh Codex capabilities:", error);
       throw error;
     }
   },
 
   /**
-   * Clears Gemini provider configuration (resets to official OAuth)
-   * Clears .env and sets auth type to oauth-personal
-   * @returns Promise resolving to success message
+   * Force refreshes Codex capabilities (ignores cache)
+   * @returns Promise resolving to updated capabilities
    */
-  async clearGeminiProviderConfig(): Promise<string> {
+  async forceRefreshCodexCapabilities(): Promise<import('@/types/codex-selector').CodexCapabilities> {
     try {
-      return await invoke<string>("clear_gemini_provider_config");
+      return await invoke<import('@/types/codex-selector').CodexCapabilities>("force_refresh_codex_capabilities");
     } catch (error) {
-      console.error("Failed to clear Gemini provider config:", error);
+      console.error("Failed to force refresh Codex capabilities:", error);
       throw error;
     }
   },
 
   /**
-   * Tests Gemini provider connection
-   * @param baseUrl - The base URL to test
-   * @param apiKey - The API key to use for testing
-   * @returns Promise resolving to test result message
+   * Suggests a Codex reasoning mode from past run outcomes in this project
+   * @param projectPath - Project the prompt will run in
+   * @param prompt - Prompt to be sent (only its length is used)
+   * @param model - Model to suggest for (defaults to the current selection)
+   * @returns Promise resolving to the suggestion and per-mode statistics
    */
-  async testGeminiProviderConnection(baseUrl: string, apiKey?: string): Promise<string> {
+  async suggestReasoningMode(
+    projectPath: string,
+    prompt: string,
+    model?: string
+  ): Promise<import('@/types/codex-selector').ReasoningSuggestion> {
     try {
-      return await invoke<string>("test_gemini_provider_connection", { baseUrl, apiKey });
+      return await invoke<import('@/types/codex-selector').ReasoningSuggestion>("suggest_reasoning_mode", {
+        projectPath,
+        prompt,
+        model,
+      });
     } catch (error) {
-      console.error("Failed to test Gemini provider connection:", error);
+      console.error("Failed to suggest reasoning mode:", error);
       throw error;
     }
   },
 
   // ============================================================================
-  // Session Conversion (Claude ↔ Codex)
+  // 引擎状态管理
   // ============================================================================
 
   /**
-   * Convert a session between Claude and Codex formats
-   * @param sessionId - The source session ID
-   * @param targetEngine - The target engine ('claude' | 'codex')
-   * @param projectId - The project ID (directory name)
-   * @param projectPath - The project path
-   * @returns Promise resolving to conversion result
+   * 检查指定引擎的状态
+   * @param engine - 引擎类型 ('claude' | 'codex' | 'gemini')
+   * @returns Promise resolving to engine status
    */
-  async convertSession(
-    sessionId: string,
-    targetEngine: 'claude' | 'codex',
-    projectId: string,
-    projectPath: string
-  ): Promise<ConversionResult> {
+  async checkEngineStatus(engine: string): Promise<import('@/types/engine').UnifiedEngineStatus> {
     try {
-      return await invoke<ConversionResult>("convert_session", {
-        sessionId,
-        targetEngine,
-        projectId,
-        projectPath,
-      });
+      return await invoke<import('@/types/engine').UnifiedEngineStatus>("check_engine_status", { engine });
     } catch (error) {
-      console.error("Failed to convert session:", error);
+      console.error(`Failed to check ${engine} status:`, error);
       throw error;
     }
   },
 
   /**
-   * Convert a Claude session to Codex format
-   * @param sessionId - The Claude session ID (UUID format)
-   * @param projectId - The project ID (directory name)
-   * @param projectPath - The project path
-   * @returns Promise resolving to conversion result
+   * 清除引擎的检测缓存（二进制路径与版本），下次检查时重新检测
+   * @param engine - 引擎类型，省略时清除全部引擎
    */
-  async convertClaudeToCodex(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<ConversionResult> {
+  async invalidateEngineCache(engine?: string): Promise<void> {
+    return await invoke<void>("invalidate_engine_cache", { engine });
+  },
+
+  /**
+   * 更新指定引擎
+   * @param engine - 引擎类型 ('claude' | 'codex' | 'gemini')
+   * @param environment - 运行环境 ('native' | 'wsl')
+   * @param wslDistro - WSL 发行版名称（可选）
+   * @returns Promise resolving to update result
+   */
+  async updateEngine(
+    engine: string, 
+    environment: string, 
+    wslDistro?: string
+  ): Promise<import('@/types/engine').EngineUpdateResult> {
     try {
-      return await invoke<ConversionResult>("convert_claude_to_codex", {
-        sessionId,
-        projectId,
-        projectPath,
+      return await invoke<import('@/types/engine').EngineUpdateResult>("update_engine", { 
+        engine, 
+        environment, 
+        wslDistro 
       });
     } catch (error) {
-      console.error("Failed to convert Claude to Codex:", error);
+      console.error(`Failed to update ${engine}:`, error);
       throw error;
     }
   },
 
   /**
-   * Convert a Codex session to Claude format
-   * @param sessionId - The Codex session ID (rollout-* format)
-   * @param projectId - The project ID (directory name)
-   * @param projectPath - The project path
-   * @returns Promise resolving to conversion result
+   * 检查引擎更新
+   * @param engine - 引擎类型 ('claude' | 'codex' | 'gemini')
+   * @param environment - 运行环境 ('native' | 'wsl')
+   * @param wslDistro - WSL 发行版名称（可选）
+   * @returns Promise resolving to check update result
    */
-  async convertCodexToClaude(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<ConversionResult> {
+  async checkEngineUpdate(
+    engine: string, 
+    environment: string, 
+    wslDistro?: string
+  ): Promise<import('@/types/engine').CheckUpdateResult> {
     try {
-      return await invoke<ConversionResult>("convert_codex_to_claude", {
-        sessionId,
-        projectId,
-        projectPath,
+      return await invoke<import('@/types/engine').CheckUpdateResult>("check_engine_update", { 
+        engine, 
+        environment, 
+        wslDistro 
       });
     } catch (error) {
-      console.error("Failed to convert Codex to Claude:", error);
+      console.error(`Failed to check ${engine} update:`, error);
       throw error;
     }
   },
 
-  // ==================== Google Gemini CLI Integration ====================
+  // ==================== IDE Integration ====================
 
   /**
-   * Executes a Gemini CLI session with streaming output
-   * @param options - Gemini execution options
-   * @returns Promise resolving when execution starts (events are streamed via event listeners)
+   * 获取 IDE 配置
+   * @returns Promise resolving to IDE configuration
    */
-  async executeGemini(options: import('@/types/gemini').GeminiExecutionOptions): Promise<void> {
+  async getIDEConfig(): Promise<IDEConfig> {
     try {
-      return await invoke("execute_gemini", { options });
+      return await invoke<IDEConfig>("get_ide_config");
     } catch (error) {
-      console.error("Failed to execute Gemini:", error);
+      console.error("Failed to get IDE config:", error);
       throw error;
     }
   },
 
   /**
-   * Cancels a running Gemini execution
-   * @param sessionId - Optional session ID to cancel (cancels all if not provided)
+   * 保存 IDE 配置
+   * @param config - IDE 配置对象
+   * @returns Promise resolving when config is saved
    */
-  async cancelGemini(sessionId?: string): Promise<void> {
+  async saveIDEConfig(config: IDEConfig): Promise<void> {
     try {
-      await invoke("cancel_gemini", { sessionId });
+      return await invoke<void>("save_ide_config_cmd", { config });
     } catch (error) {
-      console.error("Failed to cancel Gemini:", error);
+      console.error("Failed to save IDE config:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Gemini CLI is installed
-   * @returns Promise resolving to installation status
+   * 检测已安装的 IDE
+   * @returns Promise resolving to array of detected IDEs
    */
-  async checkGeminiInstalled(): Promise<import('@/types/gemini').GeminiInstallStatus> {
+  async detectInstalledIDEs(): Promise<DetectedIDE[]> {
     try {
-      return await invoke("check_gemini_installed");
+      return await invoke<DetectedIDE[]>("detect_ides");
     } catch (error) {
-      console.error("Failed to check Gemini installation:", error);
-      return {
-        installed: false,
-        error: String(error),
-      };
+      console.error("Failed to detect IDEs:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets Gemini CLI configuration
-   * @returns Promise resolving to Gemini configuration
+   * 在 IDE 中打开文件
+   * @param options - 打开文件选项
+   * @returns Promise resolving to operation result
    */
-  async getGeminiConfig(): Promise<import('@/types/gemini').GeminiConfig> {
+  async openFileInIDE(options: OpenFileInIDEOptions): Promise<IDEResult> {
     try {
-      return await invoke("get_gemini_config");
+      return await invoke<IDEResult>("open_file_in_ide", { options });
     } catch (error) {
-      console.error("Failed to get Gemini config:", error);
+      console.error("Failed to open file in IDE:", error);
       throw error;
     }
   },
 
   /**
-   * Updates Gemini CLI configuration
-   * @param config - New configuration to apply
+   * 验证 IDE 路径是否有效
+   * @param path - IDE 可执行文件路径
+   * @returns Promise resolving to validation result
    */
-  async updateGeminiConfig(config: import('@/types/gemini').GeminiConfig): Promise<void> {
+  async validateIDEPath(path: string): Promise<boolean> {
     try {
-      await invoke("update_gemini_config", { config });
+      return await invoke<boolean>("validate_ide_path", { path });
     } catch (error) {
-      console.error("Failed to update Gemini config:", error);
+      console.error("Failed to validate IDE path:", error);
       throw error;
     }
   },
 
   /**
-   * Gets available Gemini models
-   * @returns Promise resolving to array of model information
+   * 列出内置编辑器注册表及检测结果
+   * @returns Promise resolving to registry editors
    */
-  async getGeminiModels(): Promise<import('@/types/gemini').GeminiModelInfo[]> {
+  async listEditors(): Promise<EditorInfo[]> {
     try {
-      return await invoke("get_gemini_models");
+      return await invoke<EditorInfo[]>("list_editors");
     } catch (error) {
-      console.error("Failed to get Gemini models:", error);
+      console.error("Failed to list editors:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Gemini Session History
-  // ============================================================================
-
   /**
-   * Gets session logs for a project (from logs.json)
-   * @param projectPath - Project path to get session logs for
-   * @returns Promise resolving to array of session logs
+   * 用指定编辑器打开文件
+   * @param editorId - 编辑器 id（见 listEditors）
+   * @param file - 文件路径（相对路径按 projectPath 解析）
+   * @returns Promise resolving to operation result
    */
-  async getGeminiSessionLogs(projectPath: string): Promise<import('@/types/gemini').GeminiSessionLog[]> {
+  async openInIde(
+    editorId: string,
+    file: string,
+    line?: number,
+    column?: number,
+    projectPath?: string
+  ): Promise<IDEResult> {
     try {
-      return await invoke("get_gemini_session_logs", { projectPath });
+      return await invoke<IDEResult>("open_in_ide", { editorId, file, line, column, projectPath });
     } catch (error) {
-      console.error("Failed to get Gemini session logs:", error);
+      console.error(`Failed to open ${file} in ${editorId}:`, error);
       throw error;
     }
   },
 
   /**
-   * Lists all sessions for a project (from chats/ directory)
-   * @param projectPath - Project path to list sessions for
-   * @returns Promise resolving to array of session info
+   * 在编辑器的对比视图中打开一条变更记录
+   * @param changeId - 变更记录 ID
+   * @param editorId - 编辑器 id（需支持对比视图）
+   * @returns Promise resolving to operation result
    */
-  async listGeminiSessions(projectPath: string): Promise<import('@/types/gemini').GeminiSessionInfo[]> {
+  async openChangeInIdeDiff(changeId: string, editorId: string): Promise<IDEResult> {
     try {
-      return await invoke("list_gemini_sessions", { projectPath });
+      return await invoke<IDEResult>("open_change_in_ide_diff", { changeId, editorId });
     } catch (error) {
-      console.error("Failed to list Gemini sessions:", error);
+      console.error(`Failed to open change ${changeId} in ${editorId}:`, error);
       throw error;
     }
   },
 
   /**
-   * Gets detailed session information
-   * @param projectPath - Project path
-   * @param sessionId - Session ID to get details for
-   * @returns Promise resolving to complete session detail
+   * 以 IDEA 格式导出会话变更并用指定编辑器打开（JetBrains IDE 会弹出 Apply Patch）
+   * @param sessionId - Codex 会话 ID
+   * @param target - 编辑器 id
+   * @returns Promise resolving to operation result
    */
-  async getGeminiSessionDetail(
-    projectPath: string,
-    sessionId: string
-  ): Promise<import('@/types/gemini').GeminiSessionDetail> {
+  async exportAndOpenPatch(sessionId: string, target: string): Promise<IDEResult> {
     try {
-      return await invoke("get_gemini_session_detail", { projectPath, sessionId });
+      return await invoke<IDEResult>("export_and_open_patch", { sessionId, target });
     } catch (error) {
-      console.error("Failed to get Gemini session detail:", error);
+      console.error("Failed to export and open patch:", error);
       throw error;
     }
   },
 
+  // ==================== External Terminal ====================
+
   /**
-   * Delete a Gemini session
-   * @param projectPath - Project path
-   * @param sessionId - Session ID to delete
+   * 获取终端设置
    */
-  async deleteGeminiSession(projectPath: string, sessionId: string): Promise<void> {
+  async getTerminalSettings(): Promise<TerminalSettings> {
     try {
-      await invoke("delete_gemini_session", { projectPath, sessionId });
+      return await invoke<TerminalSettings>("get_terminal_settings");
     } catch (error) {
-      console.error("Failed to delete Gemini session:", error);
+      console.error("Failed to get terminal settings:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // CODEX MULTI-PROMPT MANAGEMENT
-  // ============================================================================
-
   /**
-   * Lists all Codex prompt templates
-   * @returns Promise resolving to array of prompt templates
+   * 保存终端设置
    */
-  async listCodexPrompts(): Promise<CodexPromptTemplate[]> {
+  async saveTerminalSettings(settings: TerminalSettings): Promise<void> {
     try {
-      return await invoke<CodexPromptTemplate[]>("list_codex_prompts");
+      return await invoke<void>("save_terminal_settings", { settings });
     } catch (error) {
-      console.error("Failed to list Codex prompts:", error);
+      console.error("Failed to save terminal settings:", error);
       throw error;
     }
   },
 
   /**
-   * Gets a specific Codex prompt template content
-   * @param id - The prompt template ID
-   * @returns Promise resolving to prompt content
+   * 列出当前平台的内置终端预设
    */
-  async getCodexPrompt(id: string): Promise<string> {
+  async listTerminals(): Promise<TerminalInfo[]> {
     try {
-      return await invoke<string>("get_codex_prompt", { id });
+      return await invoke<TerminalInfo[]>("list_terminals");
     } catch (error) {
-      console.error("Failed to get Codex prompt:", error);
+      console.error("Failed to list terminals:", error);
       throw error;
     }
   },
 
   /**
-   * Creates or updates a Codex prompt template
-   * @param id - The prompt template ID
-   * @param content - The prompt content
-   * @returns Promise resolving to success message
+   * 在外部终端中执行命令
+   * @param command - 要执行的命令（为空时只打开终端）
+   * @param cwd - 工作目录（默认为用户主目录）
    */
-  async saveCodexPrompt(id: string, content: string): Promise<string> {
+  async openTerminal(command?: string, cwd?: string): Promise<string> {
     try {
-      return await invoke<string>("save_codex_prompt", { id, content });
+      return await invoke<string>("open_terminal", { command, cwd });
     } catch (error) {
-      console.error("Failed to save Codex prompt:", error);
+      console.error("Failed to open terminal:", error);
       throw error;
     }
   },
 
   /**
-   * Renames a Codex prompt template (changes template ID / filename)
-   * @param oldId - The old prompt template ID
-   * @param newId - The new prompt template ID
-   * @returns Promise resolving to success message
+   * 在项目目录中打开终端
    */
-  async renameCodexPrompt(oldId: string, newId: string): Promise<string> {
+  async openProjectTerminal(projectPath: string): Promise<string> {
     try {
-      return await invoke<string>("rename_codex_prompt", { oldId, newId });
+      return await invoke<string>("open_project_terminal", { projectPath });
     } catch (error) {
-      console.error("Failed to rename Codex prompt:", error);
+      console.error("Failed to open project terminal:", error);
       throw error;
     }
   },
 
+  // ==================== Embedded Terminal (PTY) ====================
+
   /**
-   * Deletes a Codex prompt template
-   * @param id - The prompt template ID
-   * @returns Promise resolving to success message
+   * 在项目目录中启动 PTY 会话
+   * @param command - 要启动的程序（为空时启动默认 shell），例如 'codex'
    */
-  async deleteCodexPrompt(id: string): Promise<string> {
+  async ptySpawn(
+    projectPath: string,
+    cols: number,
+    rows: number,
+    command?: string,
+    args?: string[]
+  ): Promise<PtySessionInfo> {
     try {
-      return await invoke<string>("delete_codex_prompt", { id });
+      return await invoke<PtySessionInfo>("pty_spawn", { projectPath, command, args, cols, rows });
     } catch (error) {
-      console.error("Failed to delete Codex prompt:", error);
+      console.error("Failed to spawn PTY session:", error);
       throw error;
     }
   },
 
   /**
-   * Activates a Codex prompt template (copies it to AGENTS.md)
-   * @param id - The prompt template ID to activate
-   * @returns Promise resolving to success message
+   * 向 PTY 会话写入输入
    */
-  async activateCodexPrompt(id: string): Promise<string> {
+  async ptyWrite(id: string, data: string): Promise<void> {
     try {
-      return await invoke<string>("activate_codex_prompt", { id });
+      return await invoke<void>("pty_write", { id, data });
     } catch (error) {
-      console.error("Failed to activate Codex prompt:", error);
+      console.error("Failed to write to PTY session:", error);
       throw error;
     }
   },
 
   /**
-   * Deactivates the current Codex prompt (clears AGENTS.md)
-   * @returns Promise resolving to success message
+   * 调整 PTY 窗口大小
    */
-  async deactivateCodexPrompt(): Promise<string> {
+  async ptyResize(id: string, cols: number, rows: number): Promise<void> {
     try {
-      return await invoke<string>("deactivate_codex_prompt");
+      return await invoke<void>("pty_resize", { id, cols, rows });
     } catch (error) {
-      console.error("Failed to deactivate Codex prompt:", error);
+      console.error("Failed to resize PTY session:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the currently active prompt ID
-   * @returns Promise resolving to active prompt ID or null
+   * 结束 PTY 会话
    */
-  async getActiveCodexPromptId(): Promise<string | null> {
+  async ptyKill(id: string): Promise<void> {
     try {
-      return await invoke<string | null>("get_active_codex_prompt_id");
+      return await invoke<void>("pty_kill", { id });
     } catch (error) {
-      console.error("Failed to get active Codex prompt ID:", error);
-      return null;
+      console.error("Failed to kill PTY session:", error);
+      throw error;
     }
   },
 
-  // ============================================================================
-  // PROJECT-LEVEL AGENTS.MD MANAGEMENT
-  // ============================================================================
-
   /**
-   * Checks if AGENTS.md exists in the project directory
-   * @param projectPath - The project directory path
-   * @returns Promise resolving to AGENTS.md status
+   * 列出运行中的 PTY 会话
    */
-  async checkProjectAgentsMd(projectPath: string): Promise<AgentsMdStatus> {
+  async ptyList(): Promise<PtySessionInfo[]> {
     try {
-      return await invoke<AgentsMdStatus>("check_project_agents_md", { projectPath });
+      return await invoke<PtySessionInfo[]>("pty_list");
     } catch (error) {
-      console.error("Failed to check project AGENTS.md:", error);
+      console.error("Failed to list PTY sessions:", error);
       throw error;
     }
   },
 
+  // ==================== Tool Approvals ====================
+
   /**
-   * Activates a Codex prompt template to a project directory
-   * @param id - The prompt template ID to activate
-   * @param projectPath - The project directory path
-   * @param backupExisting - Whether to backup existing AGENTS.md
-   * @returns Promise resolving to activation result
+   * 回复工具调用审批
+   * @param message - 拒绝时告诉模型的原因（可选）
    */
-  async activateCodexPromptToProject(id: string, projectPath: string, backupExisting: boolean): Promise<ActivationResult> {
+  async respondToApproval(
+    sessionId: string,
+    requestId: string,
+    allow: boolean,
+    message?: string
+  ): Promise<void> {
     try {
-      return await invoke<ActivationResult>("activate_codex_prompt_to_project", { id, projectPath, backupExisting });
+      return await invoke<void>("respond_to_approval", { sessionId, requestId, allow, message });
     } catch (error) {
-      console.error("Failed to activate Codex prompt to project:", error);
+      console.error("Failed to respond to approval:", error);
       throw error;
     }
   },
 
   /**
-   * Deactivates Codex prompt from a project directory
-   * @param projectPath - The project directory path
-   * @param restoreBackup - Whether to restore backup file
-   * @returns Promise resolving to success message
+   * 列出等待中的审批请求
    */
-  async deactivateCodexPromptFromProject(projectPath: string, restoreBackup: boolean): Promise<string> {
+  async listPendingApprovals(sessionId?: string): Promise<ToolApprovalRequest[]> {
     try {
-      return await invoke<string>("deactivate_codex_prompt_from_project", { projectPath, restoreBackup });
+      return await invoke<ToolApprovalRequest[]>("list_pending_approvals", { sessionId });
     } catch (error) {
-      console.error("Failed to deactivate Codex prompt from project:", error);
+      console.error("Failed to list pending approvals:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // Codex Model and Reasoning Mode Selector API
-  // ============================================================================
+  // ==================== Prompt Attachments ====================
 
   /**
-   * Gets the current Codex selection configuration
-   * @returns Promise resolving to selection config or null if not set
+   * 暂存附件（校验类型与大小后复制到附件目录）
+   * @param sessionId - 会话 ID（新会话可使用临时 ID）
    */
-  async getCodexSelectionConfig(): Promise<import('@/types/codex-selector').CodexSelectionConfig | null> {
+  async stageAttachment(sessionId: string, path: string): Promise<StagedAttachment> {
     try {
-      return await invoke<import('@/types/codex-selector').CodexSelectionConfig | null>("get_codex_selection_config");
+      return await invoke<StagedAttachment>("stage_attachment", { sessionId, path });
     } catch (error) {
-      console.error("Failed to get Codex selection config:", error);
-      return null;
+      console.error("Failed to stage attachment:", error);
+      throw error;
     }
   },
 
   /**
-   * Saves the Codex selection configuration
-   * @param config - The selection configuration to save
-   * @returns Promise resolving when config is saved
+   * 删除尚未使用的暂存附件
    */
-  async saveCodexSelectionConfig(config: import('@/types/codex-selector').CodexSelectionConfig): Promise<void> {
+  async removeStagedAttachment(path: string): Promise<void> {
     try {
-      return await invoke("save_codex_selection_config", { config });
+      return await invoke<void>("remove_staged_attachment", { path });
     } catch (error) {
-      console.error("Failed to save Codex selection config:", error);
+      console.error("Failed to remove staged attachment:", error);
       throw error;
     }
   },
 
+  // ==================== Project Registry ====================
+
   /**
-   * Gets the default Codex selection configuration
-   * @returns Promise resolving to default selection config
+   * 注册项目目录
    */
-  async getDefaultCodexSelectionConfig(): Promise<import('@/types/codex-selector').CodexSelectionConfig> {
+  async registerProject(path: string): Promise<RegisteredProject> {
     try {
-      return await invoke<import('@/types/codex-selector').CodexSelectionConfig>("get_default_codex_selection_config");
+      return await invoke<RegisteredProject>("register_project", { path });
     } catch (error) {
-      console.error("Failed to get default Codex selection config:", error);
+      console.error("Failed to register project:", error);
       throw error;
     }
   },
 
   /**
-   * Gets available reasoning modes from Codex CLI
-   * @returns Promise resolving to array of reasoning mode options
+   * 扫描目录中的 Git 仓库并批量注册（拖入文件夹时使用）
+   * @param maxDepth - 向下查找的层数（默认 2）
    */
-  async getAvailableReasoningModes(): Promise<import('@/types/codex-selector').ReasoningModeOption[]> {
+  async scanProjects(root: string, maxDepth?: number): Promise<RegisteredProject[]> {
     try {
-      return await invoke<import('@/types/codex-selector').ReasoningModeOption[]>("get_available_reasoning_modes");
+      return await invoke<RegisteredProject[]>("scan_projects", { root, maxDepth });
     } catch (error) {
-      console.error("Failed to get available reasoning modes:", error);
+      console.error("Failed to scan projects:", error);
       throw error;
     }
   },
 
   /**
-   * Gets available Codex models from Codex CLI
-   * @returns Promise resolving to array of model options
+   * 列出已注册的项目（附带 Git 状态和各引擎的活动）
    */
-  async getAvailableCodexModels(): Promise<import('@/types/codex-selector').CodexModelOption[]> {
+  async listRegisteredProjects(): Promise<RegisteredProject[]> {
     try {
-      return await invoke<import('@/types/codex-selector').CodexModelOption[]>("get_available_codex_models");
+      return await invoke<RegisteredProject[]>("list_registered_projects");
     } catch (error) {
-      console.error("Failed to get available Codex models:", error);
+      console.error("Failed to list registered projects:", error);
       throw error;
     }
   },
 
   /**
-   * Refreshes Codex capabilities (models and reasoning modes)
-   * @returns Promise resolving to updated capabilities
+   * 从注册表中移除项目（不删除文件）
    */
-  async refreshCodexCapabilities(): Promise<import('@/types/codex-selector').CodexCapabilities> {
+  async removeProject(path: string): Promise<void> {
     try {
-      return await invoke<import('@/types/codex-selector').CodexCapabilities>("refresh_codex_capabilities");
+      return await invoke<void>("remove_project", { path });
     } catch (error) {
-      console.error("Failed to refresh Codex capabilities:", error);
+      console.error("Failed to remove project:", error);
       throw error;
     }
   },
 
   /**
-   * Force refreshes Codex capabilities (ignores cache)
-   * @returns Promise resolving to updated capabilities
+   * 列出项目中最近修改的文件（用于快速打开）
+   * @param since - Unix 秒，默认最近 7 天
+   * @param limit - 默认 50
    */
-  async forceRefreshCodexCapabilities(): Promise<import('@/types/codex-selector').CodexCapabilities> {
+  async getRecentlyModifiedFiles(projectPath: string, since?: number, limit?: number): Promise<RecentFile[]> {
     try {
-      return await invoke<import('@/types/codex-selector').CodexCapabilities>("force_refresh_codex_capabilities");
+      return await invoke<RecentFile[]>("get_recently_modified_files", { projectPath, since, limit });
     } catch (error) {
-      console.error("Failed to force refresh Codex capabilities:", error);
+      console.error("Failed to get recently modified files:", error);
       throw error;
     }
   },
 
-  // ============================================================================
-  // 引擎状态管理
-  // ============================================================================
-
   /**
-   * 检查指定引擎的状态
-   * @param engine - 引擎类型 ('claude' | 'codex' | 'gemini')
-   * @returns Promise resolving to engine status
+   * 批量重命名 / 移动 / 删除文件（事务执行，可撤销）
+   * @param tracking - 传入会话时，变更会记录到该会话的变更历史
    */
-  async checkEngineStatus(engine: string): Promise<import('@/types/engine').UnifiedEngineStatus> {
+  async batchFileOperations(
+    projectPath: string,
+    operations: BatchFileOperation[],
+    tracking?: FileBatchTracking
+  ): Promise<FileBatch> {
     try {
-      return await invoke<import('@/types/engine').UnifiedEngineStatus>("check_engine_status", { engine });
+      return await invoke<FileBatch>("batch_file_operations", { projectPath, operations, tracking });
     } catch (error) {
-      console.error(`Failed to check ${engine} status:`, error);
+      console.error("Failed to run batch file operations:", error);
       throw error;
     }
   },
 
   /**
-   * 更新指定引擎
-   * @param engine - 引擎类型 ('claude' | 'codex' | 'gemini')
-   * @param environment - 运行环境 ('native' | 'wsl')
-   * @param wslDistro - WSL 发行版名称（可选）
-   * @returns Promise resolving to update result
+   * 撤销一次批量文件操作（恢复被删除的文件）
    */
-  async updateEngine(
-    engine: string, 
-    environment: string, 
-    wslDistro?: string
-  ): Promise<import('@/types/engine').EngineUpdateResult> {
+  async revertFileBatch(batchId: string): Promise<FileBatch> {
     try {
-      return await invoke<import('@/types/engine').EngineUpdateResult>("update_engine", { 
-        engine, 
-        environment, 
-        wslDistro 
-      });
+      return await invoke<FileBatch>("revert_file_batch", { batchId });
     } catch (error) {
-      console.error(`Failed to update ${engine}:`, error);
+      console.error("Failed to revert file batch:", error);
       throw error;
     }
   },
 
   /**
-   * 检查引擎更新
-   * @param engine - 引擎类型 ('claude' | 'codex' | 'gemini')
-   * @param environment - 运行环境 ('native' | 'wsl')
-   * @param wslDistro - WSL 发行版名称（可选）
-   * @returns Promise resolving to check update result
+   * 列出项目的批量文件操作记录（最新的在前）
    */
-  async checkEngineUpdate(
-    engine: string, 
-    environment: string, 
-    wslDistro?: string
-  ): Promise<import('@/types/engine').CheckUpdateResult> {
+  async listFileBatches(projectPath: string): Promise<FileBatch[]> {
     try {
-      return await invoke<import('@/types/engine').CheckUpdateResult>("check_engine_update", { 
-        engine, 
-        environment, 
-        wslDistro 
-      });
+      return await invoke<FileBatch[]>("list_file_batches", { projectPath });
     } catch (error) {
-      console.error(`Failed to check ${engine} update:`, error);
+      console.error("Failed to list file batches:", error);
       throw error;
     }
   },
 
-  // ==================== IDE Integration ====================
-
   /**
-   * 获取 IDE 配置
-   * @returns Promise resolving to IDE configuration
+   * 删除项目中的文件（移入回收站，可恢复）
    */
-  async getIDEConfig(): Promise<IDEConfig> {
+  async trashFiles(projectPath: string, paths: string[]): Promise<TrashEntry[]> {
     try {
-      return await invoke<IDEConfig>("get_ide_config");
+      return await invoke<TrashEntry[]>("trash_files", { projectPath, paths });
     } catch (error) {
-      console.error("Failed to get IDE config:", error);
+      console.error("Failed to move files to trash:", error);
       throw error;
     }
   },
 
   /**
-   * 保存 IDE 配置
-   * @param config - IDE 配置对象
-   * @returns Promise resolving when config is saved
+   * 列出项目回收站中的条目（最新的在前）
    */
-  async saveIDEConfig(config: IDEConfig): Promise<void> {
+  async listTrash(projectPath: string): Promise<TrashEntry[]> {
     try {
-      return await invoke<void>("save_ide_config_cmd", { config });
+      return await invoke<TrashEntry[]>("list_trash", { projectPath });
     } catch (error) {
-      console.error("Failed to save IDE config:", error);
+      console.error("Failed to list trash:", error);
       throw error;
     }
   },
 
   /**
-   * 检测已安装的 IDE
-   * @returns Promise resolving to array of detected IDEs
+   * 恢复回收站条目到原位置
    */
-  async detectInstalledIDEs(): Promise<DetectedIDE[]> {
+  async restoreTrashed(id: string): Promise<TrashEntry> {
     try {
-      return await invoke<DetectedIDE[]>("detect_ides");
+      return await invoke<TrashEntry>("restore_trashed", { id });
     } catch (error) {
-      console.error("Failed to detect IDEs:", error);
+      console.error("Failed to restore trashed file:", error);
       throw error;
     }
   },
 
   /**
-   * 在 IDE 中打开文件
-   * @param options - 打开文件选项
-   * @returns Promise resolving to operation result
+   * 彻底删除回收站条目
    */
-  async openFileInIDE(options: OpenFileInIDEOptions): Promise<IDEResult> {
+  async deleteTrashed(id: string): Promise<void> {
     try {
-      return await invoke<IDEResult>("open_file_in_ide", { options });
+      return await invoke<void>("delete_trashed", { id });
     } catch (error) {
-      console.error("Failed to open file in IDE:", error);
+      console.error("Failed to delete trashed file:", error);
       throw error;
     }
   },
 
   /**
-   * 验证 IDE 路径是否有效
-   * @param path - IDE 可执行文件路径
-   * @returns Promise resolving to validation result
+   * 按保留天数和容量清理回收站（不传项目时清理所有项目）
    */
-  async validateIDEPath(path: string): Promise<boolean> {
+  async purgeTrash(projectPath?: string, maxAgeDays?: number, maxSizeMb?: number): Promise<TrashPurgeResult> {
     try {
-      return await invoke<boolean>("validate_ide_path", { path });
+      return await invoke<TrashPurgeResult>("purge_trash", { projectPath, maxAgeDays, maxSizeMb });
     } catch (error) {
-      console.error("Failed to validate IDE path:", error);
+      console.error("Failed to purge trash:", error);
       throw error;
     }
   },
@@ -0,0 +1,38 @@
+/**
+ * 流式输出事件监听
+ *
+ * 后端默认把引擎输出合并为批量事件发送（`<engine>-output-batch[:sessionId]`，payload 为行数组），
+ * 关闭批量发送后仍按行发送 `<engine>-output[:sessionId]`。这里同时监听两种事件，
+ * 对调用方仍然按行回调，处理逻辑不需要区分。
+ */
+
+import { listen, type Event, type EventCallback, type UnlistenFn } from '@tauri-apps/api/event';
+
+/**
+ * 逐行事件名对应的批量事件名，如 `claude-output:abc` → `claude-output-batch:abc`
+ */
+export function batchEventName(event: string): string {
+  const index = event.indexOf(':');
+  return index === -1
+    ? `${event}-batch`
+    : `${event.slice(0, index)}-batch${event.slice(index)}`;
+}
+
+/**
+ * 监听一个输出通道（逐行事件 + 批量事件），每行调用一次 handler
+ */
+export async function listenOutputLines(
+  event: string,
+  handler: EventCallback<string>
+): Promise<UnlistenFn> {
+  const unlistenLine = await listen<string>(event, handler);
+  const unlistenBatch = await listen<string[]>(batchEventName(event), (evt: Event<string[]>) => {
+    for (const line of evt.payload) {
+      handler({ ...evt, payload: line });
+    }
+  });
+  return () => {
+    unlistenLine();
+    unlistenBatch();
+  };
+}
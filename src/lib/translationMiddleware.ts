@@ -597,8 +597,8 @@ export class TranslationMiddleware {
       };
     }
 
-    // 检查翻译功能是否启用
-    if (!this.config?.enabled) {
+    // 检查翻译功能是否启用；开启双向翻译时由后端在发送前翻译（并保存原文）
+    if (!this.config?.enabled || this.config.pipeline?.enabled) {
       const detectedLang = await this.detectLanguage(userInput);
       return {
         translatedText: userInput,
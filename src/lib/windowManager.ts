@@ -25,6 +25,30 @@ export interface WindowCreationResult {
   success: boolean;
 }
 
+export interface SessionWindowState {
+  window_label: string;
+  tab_id: string;
+  session_id: string | null;
+  project_path: string | null;
+  engine: string | null;
+  title: string;
+  x: number | null;
+  y: number | null;
+  width: number | null;
+  height: number | null;
+  maximized: boolean;
+  /** Whether the window is reopened on next startup */
+  reopen: boolean;
+}
+
+export interface SaveWindowStateParams {
+  windowLabel: string;
+  sessionId?: string;
+  projectPath?: string;
+  engine?: 'claude' | 'codex' | 'gemini';
+  title?: string;
+}
+
 // Event types for cross-window communication
 export interface WindowSyncEvent {
   type: 'session_update' | 'session_complete' | 'tab_closed' | 'tab_detached' | 'tab_attached';
@@ -111,6 +135,67 @@ export async function focusSessionWindow(windowLabel: string): Promise<void> {
   }
 }
 
+/**
+ * Saves a session window's layout and binding (project, session, engine tab)
+ *
+ * @param params - The window label and any binding fields that changed
+ * @returns The saved window state
+ */
+export async function saveWindowState(params: SaveWindowStateParams): Promise<SessionWindowState> {
+  try {
+    return await invoke<SessionWindowState>('save_window_state', {
+      params: {
+        window_label: params.windowLabel,
+        session_id: params.sessionId || null,
+        project_path: params.projectPath || null,
+        engine: params.engine || null,
+        title: params.title || null,
+      },
+    });
+  } catch (error) {
+    console.error('[WindowManager] Failed to save window state:', error);
+    throw error;
+  }
+}
+
+/**
+ * Reopens the session windows that were open when the app last exited
+ *
+ * @returns Labels of the restored windows
+ */
+export async function restoreWindowsOnStartup(): Promise<string[]> {
+  try {
+    const results = await invoke<WindowCreationResult[]>('restore_windows_on_startup');
+    return results.map((result) => result.window_label);
+  } catch (error) {
+    console.error('[WindowManager] Failed to restore windows:', error);
+    return [];
+  }
+}
+
+/**
+ * Opens a window bound to a project, reusing an existing one if present
+ *
+ * @param projectPath - The project to open
+ * @param engine - Engine tab for a newly created window
+ * @returns The label of the focused or created window
+ */
+export async function openProjectWindow(
+  projectPath: string,
+  engine?: 'claude' | 'codex' | 'gemini'
+): Promise<string> {
+  try {
+    const result = await invoke<WindowCreationResult>('open_project_window', {
+      projectPath,
+      engine: engine || null,
+    });
+    return result.window_label;
+  } catch (error) {
+    console.error('[WindowManager] Failed to open project window:', error);
+    throw error;
+  }
+}
+
 // ============================================================================
 // Cross-Window Communication
 // ============================================================================
@@ -253,6 +338,26 @@ export function isSessionWindow(): boolean {
   return params.get('window') === 'session';
 }
 
+/**
+ * Checks if the current window is the global quick-capture window
+ *
+ * @returns True if this is the quick-capture window
+ */
+export function isQuickCaptureWindow(): boolean {
+  const params = new URLSearchParams(window.location.search);
+  return params.get('window') === 'quick-capture';
+}
+
+/**
+ * Reads the project path the quick-capture window was opened for
+ *
+ * @returns The project path, or undefined when there was no active project
+ */
+export function parseQuickCaptureProjectPath(): string | undefined {
+  const params = new URLSearchParams(window.location.search);
+  return params.get('project_path') || undefined;
+}
+
 // ============================================================================
 // Window Label Utilities
 // ============================================================================
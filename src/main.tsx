@@ -8,13 +8,15 @@ import "./styles.css";
 import "@git-diff-view/react/styles/diff-view-pure.css";
 import "./i18n"; // ✅ i18n 必须同步加载（App 立即需要使用）
 import { getCurrentWindow } from '@tauri-apps/api/window';
-import { isSessionWindow } from "./lib/windowManager";
+import { isQuickCaptureWindow, isSessionWindow } from "./lib/windowManager";
 
 // ⚡ 优化：只异步加载 toolRegistry（可以延迟）
 // import { initializeToolRegistry } from "./lib/toolRegistryInit"; // ❌ 改为异步
 
 // 🆕 懒加载 SessionWindow 组件（仅在需要时加载）
 const SessionWindow = React.lazy(() => import('./pages/SessionWindow'));
+// 全局快捷键打开的快速输入窗口
+const QuickCaptureWindow = React.lazy(() => import('./pages/QuickCaptureWindow'));
 
 // 防止窗口闪烁的React包装组件
 const AppWrapper: React.FC = () => {
@@ -53,6 +55,18 @@ const AppWrapper: React.FC = () => {
   }, []);
 
   // 🆕 根据窗口类型渲染不同的组件
+  if (isQuickCaptureWindow()) {
+    return (
+      <ErrorBoundary>
+        <ThemeProvider>
+          <React.Suspense fallback={null}>
+            <QuickCaptureWindow />
+          </React.Suspense>
+        </ThemeProvider>
+      </ErrorBoundary>
+    );
+  }
+
   if (isDetachedWindow) {
     return (
       <ErrorBoundary>
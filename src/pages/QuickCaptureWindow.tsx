@@ -0,0 +1,86 @@
+/**
+ * QuickCaptureWindow - Global Quick Prompt Window
+ *
+ * Opened by the `quick_capture` global hotkey. Enter sends the prompt to a new
+ * session in the active project, Escape hides the window.
+ */
+
+import React, { useEffect, useRef, useState } from 'react';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { listen } from '@tauri-apps/api/event';
+import { api } from '@/lib/api';
+import { parseQuickCaptureProjectPath } from '@/lib/windowManager';
+import { Textarea } from '@/components/ui/textarea';
+
+export const QuickCaptureWindow: React.FC = () => {
+  const [prompt, setPrompt] = useState('');
+  const [projectPath, setProjectPath] = useState<string | undefined>(() => parseQuickCaptureProjectPath());
+  const [submitting, setSubmitting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const inputRef = useRef<HTMLTextAreaElement>(null);
+
+  // The window is reused: the backend reports the current project each time it is shown
+  useEffect(() => {
+    const unlisten = listen<string | null>('quick-capture-opened', (event) => {
+      setProjectPath(event.payload || undefined);
+      setError(null);
+      inputRef.current?.focus();
+    });
+    inputRef.current?.focus();
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const hide = () => {
+    getCurrentWindow().hide().catch(() => {});
+  };
+
+  const submit = async () => {
+    const text = prompt.trim();
+    if (!text || submitting) return;
+    setSubmitting(true);
+    setError(null);
+    try {
+      await api.submitQuickCapture(text, projectPath);
+      setPrompt('');
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  const handleKeyDown = (e: React.KeyboardEvent<HTMLTextAreaElement>) => {
+    if (e.key === 'Escape') {
+      e.preventDefault();
+      hide();
+    } else if (e.key === 'Enter' && !e.shiftKey && !e.nativeEvent.isComposing) {
+      e.preventDefault();
+      submit();
+    }
+  };
+
+  return (
+    <div className="h-screen w-screen flex flex-col gap-2 p-3 bg-background border border-border rounded-lg" data-tauri-drag-region>
+      <div className="flex items-center justify-between text-xs text-muted-foreground" data-tauri-drag-region>
+        <span className="truncate" title={projectPath}>
+          {projectPath ?? '没有活动项目，请先在主窗口打开一个项目'}
+        </span>
+        <span className="shrink-0 ml-2">Enter 发送 · Shift+Enter 换行 · Esc 关闭</span>
+      </div>
+      <Textarea
+        ref={inputRef}
+        value={prompt}
+        onChange={(e) => setPrompt(e.target.value)}
+        onKeyDown={handleKeyDown}
+        placeholder="输入提示词，在当前项目中新建会话执行..."
+        disabled={submitting}
+        className="flex-1 resize-none"
+      />
+      {error && <div className="text-xs text-destructive truncate" title={error}>{error}</div>}
+    </div>
+  );
+};
+
+export default QuickCaptureWindow;
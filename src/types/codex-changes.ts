@@ -14,6 +14,11 @@ export type ChangeType = 'create' | 'update' | 'delete';
  */
 export type ChangeSource = 'tool' | 'command';
 
+/**
+ * patch 导出格式（idea：每个文件合并为一个 git 格式 diff，IDEA 可直接应用）
+ */
+export type PatchFormat = 'raw' | 'idea';
+
 /**
  * 单个文件变更记录
  */
@@ -69,6 +74,16 @@ export interface CodexChangeRecords {
   changes: CodexFileChange[];
 }
 
+/**
+ * 命令执行后的变更检测结果
+ */
+export interface CommandChangeDetection {
+  /** 记录的变更 ID */
+  changeIds: string[];
+  /** 变更未知的原因（大仓库超出检测时间预算） */
+  unknownReason?: string;
+}
+
 /**
  * 按 prompt 分组的变更记录
  */
@@ -89,6 +89,35 @@ export interface CodexCapabilities {
   codexVersion?: string;
 }
 
+/**
+ * 某个推理模式的历史执行统计
+ */
+export interface ReasoningModeStats {
+  mode: string;
+  runs: number;
+  completed: number;
+  failed: number;
+  timedOut: number;
+  cancelled: number;
+  /** 完成率（0-1） */
+  successRate: number;
+  /** 完成的任务的耗时中位数 */
+  medianDurationMs: number | null;
+}
+
+/**
+ * 推理模式推荐
+ */
+export interface ReasoningSuggestion {
+  mode: ReasoningMode;
+  /** project: 本项目历史；global: 所有项目历史；heuristic: 样本不足，按提示词长度 */
+  basis: 'project' | 'global' | 'heuristic';
+  promptTokens: number;
+  promptSize: 'short' | 'medium' | 'long';
+  reason: string;
+  stats: ReasoningModeStats[];
+}
+
 // ============================================================================
 // 组件 Props 类型
 // ============================================================================
@@ -5,6 +5,8 @@
  * Based on: https://github.com/openai/codex/blob/main/docs/exec.md
  */
 
+import type { AutoFixOptions, StagedAttachment } from '@/lib/api';
+
 // ============================================================================
 // Event Types (JSONL Stream)
 // ============================================================================
@@ -179,6 +181,12 @@ export interface CodexExecutionOptions {
 
   /** Resume last session */
   resumeLast?: boolean;
+
+  /** Staged attachments (images via --image, other files listed in the prompt) */
+  attachments?: StagedAttachment[];
+
+  /** Verify after completion and send fix prompts on failure (auto-fix loop) */
+  autoFix?: AutoFixOptions;
 }
 
 // ============================================================================
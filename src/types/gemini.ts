@@ -1,5 +1,7 @@
 // Gemini CLI Types
 
+import type { AutoFixOptions, StagedAttachment } from '@/lib/api';
+
 /**
  * Gemini authentication method
  */
@@ -39,6 +41,10 @@ export interface GeminiExecutionOptions {
   includeDirectories?: string[];
   sessionId?: string;
   debug?: boolean;
+  /** Staged attachments (referenced via @path in the prompt) */
+  attachments?: StagedAttachment[];
+  /** Verify after completion and send fix prompts on failure (auto-fix loop) */
+  autoFix?: AutoFixOptions;
 }
 
 /**